@@ -0,0 +1,25 @@
+extern crate nyx_space as nyx;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use nyx::cosmic::Cosm;
+use nyx::tools::benchmark::{BenchmarkSuite, Workload};
+
+fn workloads(c: &mut Criterion) {
+    let suite = BenchmarkSuite::new(Cosm::de438());
+
+    let mut group = c.benchmark_group("workloads");
+    // Each workload takes at least several milliseconds, so a smaller sample size is sufficient.
+    group.sample_size(10);
+
+    for workload in Workload::all() {
+        let prepared = suite.prepare(workload).unwrap();
+        group.bench_function(format!("{workload}"), |b| {
+            b.iter(|| prepared.run().unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, workloads);
+criterion_main!(benches);
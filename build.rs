@@ -1,3 +1,7 @@
+#[cfg(feature = "build-info")]
 fn main() -> shadow_rs::SdResult<()> {
     shadow_rs::new()
 }
+
+#[cfg(not(feature = "build-info"))]
+fn main() {}
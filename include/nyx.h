@@ -0,0 +1,81 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+/*
+ * C interface to Nyx, built with `cargo build --release --features ffi` into libnyx_space.
+ *
+ * Handles are opaque and must be freed with their `*_free` function. States have their epoch in TAI seconds past
+ * J1900, positions in km and velocities in km/s, in the frame whose name is provided along with them.
+ * Functions returning an int return NYX_OK on success, and functions returning a handle return NULL on failure:
+ * nyx_last_error then describes the failure.
+ */
+
+#ifndef NYX_H
+#define NYX_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define NYX_OK 0
+#define NYX_ERROR -1
+
+typedef struct NyxCosm NyxCosm;
+typedef struct NyxDynamics NyxDynamics;
+typedef struct NyxTraj NyxTraj;
+
+typedef struct NyxState {
+    double epoch_tai_s;
+    double x_km;
+    double y_km;
+    double z_km;
+    double vx_km_s;
+    double vy_km_s;
+    double vz_km_s;
+} NyxState;
+
+/* Description of the last failure on this thread, valid until the next failure, or NULL */
+const char *nyx_last_error(void);
+
+NyxCosm *nyx_cosm_de438(void);
+void nyx_cosm_free(NyxCosm *cosm);
+
+NyxDynamics *nyx_dynamics_two_body(void);
+NyxDynamics *nyx_dynamics_point_masses(const NyxCosm *cosm, const char *const *bodies, size_t num_bodies);
+void nyx_dynamics_free(NyxDynamics *dynamics);
+
+int nyx_propagate(const NyxCosm *cosm, const NyxDynamics *dynamics, const char *frame, const NyxState *state,
+                  double duration_s, NyxState *out);
+NyxTraj *nyx_propagate_traj(const NyxCosm *cosm, const NyxDynamics *dynamics, const char *frame,
+                            const NyxState *state, double duration_s);
+
+int nyx_traj_at(const NyxTraj *traj, double epoch_tai_s, NyxState *out);
+int nyx_traj_first(const NyxTraj *traj, NyxState *out);
+int nyx_traj_last(const NyxTraj *traj, NyxState *out);
+void nyx_traj_free(NyxTraj *traj);
+
+int nyx_frame_convert(const NyxCosm *cosm, const NyxState *state, const char *from_frame, const char *to_frame,
+                      NyxState *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* NYX_H */
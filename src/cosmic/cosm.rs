@@ -454,7 +454,7 @@ impl Cosm {
                     if let Some(src_frame_name) = &definition.inherit {
                         debug!("Loaded frame {}", frame_name);
                         let src_frame = self.try_frame(src_frame_name.as_str())?;
-                        let mut fpath = src_frame.frame_path();
+                        let mut fpath = src_frame.frame_path().to_vec();
                         // And find the correct children
                         let children = match fpath.len() {
                             2 => {
@@ -578,7 +578,7 @@ impl Cosm {
     /// For example, if [3, 1] is provided (Moon in J2000 in the DE file), return Moon J2000
     /// If no frame name is provided, then the storage frame is returned. Otherwise, the correct frame is returned.
     pub fn frame_from_ephem_path(&self, ephem_path: &[usize]) -> Frame {
-        self.frame_from_frame_path(self.ephem2frame_map.get(&ephem_path.to_vec()).unwrap())
+        self.frame_from_frame_path(self.ephem2frame_map.get(ephem_path).unwrap())
     }
 
     /// Provided a frame path returns the Frame.
@@ -700,43 +700,36 @@ impl Cosm {
         }
         let pos_coeffs = &exb_states.position[index];
 
-        let mut interp_t = vec![0.0; coefficient_count];
+        // Evaluate the Chebyshev polynomials and their derivatives with their recurrence relations, without storing them
         let t1 = 2.0 * offset / interval_length - 1.0;
-        interp_t[0] = 1.0;
-        interp_t[1] = t1;
-        for i in 2..coefficient_count {
-            interp_t[i] = (2.0 * t1) * interp_t[i - 1] - interp_t[i - 2];
+        let (mut t_prev, mut t_cur) = (1.0, t1);
+        let (mut dt_prev, mut dt_cur) = (0.0, 1.0);
+
+        let mut x = pos_coeffs.x[0] + t1 * pos_coeffs.x[1];
+        let mut y = pos_coeffs.y[0] + t1 * pos_coeffs.y[1];
+        let mut z = pos_coeffs.z[0] + t1 * pos_coeffs.z[1];
+        let mut vx = pos_coeffs.x[1];
+        let mut vy = pos_coeffs.y[1];
+        let mut vz = pos_coeffs.z[1];
+
+        for idx in 2..coefficient_count {
+            let t_next = (2.0 * t1) * t_cur - t_prev;
+            let dt_next = (2.0 * t1) * dt_cur - dt_prev + t_cur + t_cur;
+            (t_prev, t_cur) = (t_cur, t_next);
+            (dt_prev, dt_cur) = (dt_cur, dt_next);
+
+            x += t_cur * pos_coeffs.x[idx];
+            y += t_cur * pos_coeffs.y[idx];
+            z += t_cur * pos_coeffs.z[idx];
+            vx += dt_cur * pos_coeffs.x[idx];
+            vy += dt_cur * pos_coeffs.y[idx];
+            vz += dt_cur * pos_coeffs.z[idx];
         }
 
-        let mut interp_dt = vec![0.0; coefficient_count];
-        interp_dt[0] = 0.0;
-        interp_dt[1] = 1.0;
-        interp_dt[2] = 2.0 * (2.0 * t1);
-        for i in 3..coefficient_count {
-            interp_dt[i] = (2.0 * t1) * interp_dt[i - 1] - interp_dt[i - 2]
-                + interp_t[i - 1]
-                + interp_t[i - 1];
-        }
-        for interp_i in &mut interp_dt {
-            *interp_i *= 2.0 / interval_length;
-        }
-
-        let mut x = 0.0;
-        let mut y = 0.0;
-        let mut z = 0.0;
-        let mut vx = 0.0;
-        let mut vy = 0.0;
-        let mut vz = 0.0;
-
-        for (idx, pos_factor) in interp_t.iter().enumerate() {
-            let vel_factor = interp_dt[idx];
-            x += pos_factor * pos_coeffs.x[idx];
-            y += pos_factor * pos_coeffs.y[idx];
-            z += pos_factor * pos_coeffs.z[idx];
-            vx += vel_factor * pos_coeffs.x[idx];
-            vy += vel_factor * pos_coeffs.y[idx];
-            vz += vel_factor * pos_coeffs.z[idx];
-        }
+        let vel_scaling = 2.0 / interval_length;
+        vx *= vel_scaling;
+        vy *= vel_scaling;
+        vz *= vel_scaling;
 
         // Get the Geoid associated with the ephemeris frame
         let storage_geoid = self.frame_from_ephem_path(path);
@@ -1027,10 +1020,32 @@ impl Cosm {
         if state.frame == new_frame {
             return Ok(*state);
         }
-        // Let's perform the translation
-        let mut new_state = self.try_frame_translation(state, new_frame)?;
-        // And now let's compute the rotation path
-        new_state.rotate_by(self.try_dcm_from_to(&state.frame, &new_frame, state.epoch)?);
+        let state_ephem_path = state.frame.ephem_path();
+        let new_ephem_path = new_frame.ephem_path();
+        if state_ephem_path == new_ephem_path {
+            // Same center, so this is only a rotation
+            let mut new_state = self.try_frame_translation(state, new_frame)?;
+            new_state.rotate_by(self.try_dcm_from_to(&state.frame, &new_frame, state.epoch)?);
+            return Ok(new_state);
+        }
+        // The translation between both centers is in the orientation of the ephemeris, so rotate the state into the inertial
+        // frame of its center first (e.g. from a body fixed frame), translate it, and then rotate it into the new frame.
+        let state_inertial = self.frame_from_ephem_path(&state_ephem_path);
+        let new_inertial = self.frame_from_ephem_path(&new_ephem_path);
+        let mut new_state = *state;
+        if state.frame != state_inertial {
+            new_state.rotate_by(self.try_dcm_from_to(
+                &state.frame,
+                &state_inertial,
+                state.epoch,
+            )?);
+            new_state.frame = state_inertial;
+        }
+        let mut new_state = self.try_frame_translation(&new_state, new_inertial)?;
+        if new_inertial != new_frame {
+            new_state.rotate_by(self.try_dcm_from_to(&new_inertial, &new_frame, state.epoch)?);
+            new_state.frame = new_frame;
+        }
         Ok(new_state)
     }
 
@@ -1040,33 +1055,13 @@ impl Cosm {
     }
 
     /// Returns the conversion path from the target ephemeris or frame `from` as seen from `to`.
-    fn find_common_root(&self, from: &[usize], to: &[usize]) -> std::vec::Vec<usize> {
-        let mut common_root = Vec::with_capacity(3); // Unlikely to be more than 3 items
-        if !from.is_empty() && !to.is_empty() {
-            // This is NOT the root of the ephemeris
-            if from.len() < to.len() {
-                // Iterate through the items in from
-                for (n, obj) in from.iter().enumerate() {
-                    if &to[n] == obj {
-                        common_root.push(*obj);
-                    } else {
-                        // Found the end of the matching objects
-                        break;
-                    }
-                }
-            } else {
-                // Iterate through the items in from
-                for (n, obj) in to.iter().enumerate() {
-                    if &from[n] == obj {
-                        common_root.push(*obj);
-                    } else {
-                        // Found the end of the matching objects
-                        break;
-                    }
-                }
-            }
-        }
-        common_root
+    fn find_common_root(&self, from: &[usize], to: &[usize]) -> FramePath {
+        let common_len = from
+            .iter()
+            .zip(to.iter())
+            .take_while(|(from_idx, to_idx)| from_idx == to_idx)
+            .count();
+        FramePath::new(&from[..common_len])
     }
 }
 
@@ -1375,6 +1370,62 @@ mod tests {
         assert!((lro_wrt_ssb - lro).vmag_km_s() < std::f64::EPSILON);
     }
 
+    #[test]
+    fn test_cosm_frame_change_iau_earth2luna() {
+        let cosm = Cosm::de438();
+        let eme2k = cosm.frame("EME2000");
+        let earth_iau = cosm.frame("IAU Earth");
+        let luna = cosm.frame("Luna");
+        let moon_iau = cosm.frame("IAU Moon");
+
+        let jde = Epoch::from_jde_et(2_458_823.5);
+        // From JPL HORIZONS, same states as in test_cosm_frame_change_earth2luna
+        let lro = Orbit::cartesian(
+            4.017_685_334_718_784E5,
+            2.642_441_356_763_487E4,
+            -3.024_209_691_251_325E4,
+            -6.168_920_999_978_097E-1,
+            -6.678_258_076_726_339E-1,
+            4.208_264_479_358_517E-1,
+            jde,
+            eme2k,
+        );
+
+        let lro_jpl = Orbit::cartesian(
+            -3.692_315_939_257_387E2,
+            8.329_785_181_291_3E1,
+            -1.764_329_108_632_533E3,
+            -5.729_048_963_901_611E-1,
+            -1.558_441_873_361_044,
+            4.456_498_438_933_088E-2,
+            jde,
+            luna,
+        );
+
+        // Rotations about the same center are validated against SPICE in test_cosm_rotation_spiceypy_dcm
+        let lro_iau_earth = cosm.frame_chg(&lro, earth_iau);
+
+        // From the body fixed frame of the Earth to an inertial frame centered on the Moon
+        let lro_wrt_moon = cosm.frame_chg(&lro_iau_earth, luna);
+        println!("{}", lro_jpl);
+        println!("{}", lro_wrt_moon);
+        let delta = lro_jpl - lro_wrt_moon;
+        // Same bounds as test_cosm_frame_change_earth2luna: JPL uses de431MX, but nyx uses de438s.
+        assert!(dbg!(delta.rmag_km()) < 1e-2);
+        assert!(dbg!(delta.vmag_km_s()) < 1e-5);
+
+        // From the body fixed frame of the Earth to the body fixed frame of the Moon
+        let lro_moon_iau = cosm.frame_chg(&lro_iau_earth, moon_iau);
+        let delta = cosm.frame_chg(&lro_jpl, moon_iau) - lro_moon_iau;
+        assert!(dbg!(delta.rmag_km()) < 1e-2);
+        assert!(dbg!(delta.vmag_km_s()) < 1e-5);
+
+        // And the converse
+        let lro_wrt_earth = cosm.frame_chg(&lro_moon_iau, eme2k);
+        assert!(dbg!((lro_wrt_earth - lro).rmag_km()) < 1e-7);
+        assert!(dbg!((lro_wrt_earth - lro).vmag_km_s()) < 1e-9);
+    }
+
     #[test]
     fn test_cosm_lt_corr() {
         let cosm = Cosm::de438();
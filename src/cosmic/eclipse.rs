@@ -111,6 +111,38 @@ impl fmt::Display for EclipseState {
     }
 }
 
+/// Shadow model of the light source, e.g. to compute the illumination of the solar radiation pressure
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ShadowModel {
+    /// Always in full light
+    None,
+    /// Cylindrical shadow behind each eclipsing body: either in full light or in umbra, without penumbra
+    Cylindrical,
+    /// Dual cone model of the umbra and penumbra of each eclipsing body, from the overlap of the apparent disks of the light
+    /// source and of the body. The occultations of several bodies (e.g. the Earth and the Moon) add up.
+    #[default]
+    Conical,
+}
+
+impl fmt::Display for ShadowModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "no shadow"),
+            Self::Cylindrical => write!(f, "cylindrical shadow"),
+            Self::Conical => write!(f, "conical shadow"),
+        }
+    }
+}
+
+/// Boundary of the shadow searched by a `ShadowEvent`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShadowBoundary {
+    /// Boundary between the penumbra and the umbra, where the light source becomes fully hidden
+    Umbra,
+    /// Boundary between the full light and the penumbra, where the light source starts to be hidden
+    Penumbra,
+}
+
 #[derive(Clone)]
 pub struct EclipseLocator {
     pub light_source: Frame,
@@ -155,6 +187,51 @@ impl EclipseLocator {
         state
     }
 
+    /// Computes the fraction of the light source seen by the observer with the provided shadow model, between 0 (umbra) and 1
+    /// (full light).
+    pub fn light_fraction(&self, observer: &Orbit, model: ShadowModel) -> f64 {
+        match model {
+            ShadowModel::None => 1.0,
+            ShadowModel::Cylindrical => {
+                let light_source = self.cosm.frame_chg(observer, self.light_source).radius();
+                for eclipsing_body in &self.shadow_bodies {
+                    let r = self.cosm.frame_chg(observer, *eclipsing_body).radius();
+                    // Direction from the eclipsing body to the light source
+                    let to_light = (r - light_source).normalize();
+                    let along = r.dot(&to_light);
+                    if along < 0.0
+                        && (r - along * to_light).norm() < eclipsing_body.equatorial_radius()
+                    {
+                        return 0.0;
+                    }
+                }
+                1.0
+            }
+            ShadowModel::Conical => {
+                let hidden: f64 = self
+                    .shadow_bodies
+                    .iter()
+                    .map(|eclipsing_body| {
+                        let visible: f64 =
+                            eclipse_state(observer, self.light_source, *eclipsing_body, &self.cosm)
+                                .into();
+                        1.0 - visible
+                    })
+                    .sum();
+                (1.0 - hidden).clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    /// Creates an event on the provided boundary of the shadow computed with the provided model
+    pub fn to_shadow_event(&self, model: ShadowModel, boundary: ShadowBoundary) -> ShadowEvent {
+        ShadowEvent {
+            e_loc: self.clone(),
+            model,
+            boundary,
+        }
+    }
+
     /// Creates an umbra event from this eclipse locator
     pub fn to_umbra_event(&self) -> UmbraEvent {
         UmbraEvent {
@@ -285,6 +362,121 @@ impl EventEvaluator<Spacecraft> for PenumbraEvent {
     }
 }
 
+/// An event on the umbra or penumbra boundary of the shadow of a shadow model, i.e. its entries and exits.
+///
+/// The event is positive on the lit side of the boundary, so an entry is a crossing from positive to negative. The cylindrical
+/// shadow has no penumbra, so both of its boundaries are the same.
+pub struct ShadowEvent {
+    e_loc: EclipseLocator,
+    model: ShadowModel,
+    boundary: ShadowBoundary,
+}
+
+impl ShadowEvent {
+    /// Returns whether the crossing between both states is an entry in the shadow, rather than an exit
+    pub fn is_entry(&self, prev_state: &Orbit, next_state: &Orbit) -> bool {
+        self.eval_orbit(prev_state) > self.eval_orbit(next_state)
+    }
+
+    /// Precision of the distance to the boundary: 1e-6 rad for the conical shadow, and 10 m for the cylindrical one
+    fn boundary_precision(&self) -> f64 {
+        match self.model {
+            ShadowModel::Cylindrical => 1e-2,
+            _ => 1e-6,
+        }
+    }
+
+    /// Distance to the boundary of the shadow of the closest eclipsing body, positive on its lit side: the apparent angular
+    /// distance (rad) for the conical shadow, and the distance to the shadow cylinder (km) for the cylindrical one.
+    fn eval_orbit(&self, observer: &Orbit) -> f64 {
+        let cosm = &self.e_loc.cosm;
+        let light_source = self.e_loc.light_source;
+        self.e_loc
+            .shadow_bodies
+            .iter()
+            .map(|eclipsing_body| match self.model {
+                ShadowModel::None => 1.0,
+                ShadowModel::Cylindrical => {
+                    let r = cosm.frame_chg(observer, *eclipsing_body).radius();
+                    let to_light =
+                        (r - cosm.frame_chg(observer, light_source).radius()).normalize();
+                    let along = r.dot(&to_light);
+                    let off_axis_km =
+                        (r - along * to_light).norm() - eclipsing_body.equatorial_radius();
+                    if along < 0.0 {
+                        off_axis_km
+                    } else {
+                        // On the lit side of the body, the shadow is always further away
+                        off_axis_km.max(0.0) + along
+                    }
+                }
+                ShadowModel::Conical => {
+                    let (r_ls_prime, r_eb_prime, d_prime) =
+                        apparent_disks(observer, light_source, *eclipsing_body, cosm);
+                    match self.boundary {
+                        ShadowBoundary::Umbra => d_prime - (r_eb_prime - r_ls_prime),
+                        ShadowBoundary::Penumbra => d_prime - (r_ls_prime + r_eb_prime),
+                    }
+                }
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+impl fmt::Display for ShadowEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} boundary of the {} event {}",
+            self.boundary, self.model, self.e_loc
+        )
+    }
+}
+
+impl EventEvaluator<Orbit> for ShadowEvent {
+    fn eval(&self, observer: &Orbit) -> f64 {
+        self.eval_orbit(observer)
+    }
+
+    /// Stop searching when the time has converged to less than 0.1 seconds
+    fn epoch_precision(&self) -> Duration {
+        0.1 * Unit::Second
+    }
+
+    fn value_precision(&self) -> f64 {
+        self.boundary_precision()
+    }
+
+    fn eval_string(&self, state: &Orbit) -> String {
+        format!(
+            "light fraction of {:.6}",
+            self.e_loc.light_fraction(state, self.model)
+        )
+    }
+}
+
+impl EventEvaluator<Spacecraft> for ShadowEvent {
+    fn eval(&self, sc: &Spacecraft) -> f64 {
+        self.eval_orbit(&sc.orbit)
+    }
+
+    /// Stop searching when the time has converged to less than 0.1 seconds
+    fn epoch_precision(&self) -> Duration {
+        0.1 * Unit::Second
+    }
+
+    fn value_precision(&self) -> f64 {
+        self.boundary_precision()
+    }
+
+    fn eval_string(&self, state: &Spacecraft) -> String {
+        format!(
+            "light fraction of {:.6}",
+            self.e_loc.light_fraction(&state.orbit, self.model)
+        )
+    }
+}
+
 /// Computes the umbra/visibilis/penumbra state between between two states accounting for eclipsing of the providing geoid.
 pub fn eclipse_state(
     observer: &Orbit,
@@ -306,28 +498,8 @@ pub fn eclipse_state(
         );
         return line_of_sight(observer, &observed, eclipsing_body, cosm);
     }
-    // All of the computations happen with the observer as the center.
-    // `eb` stands for eclipsing body; `ls` stands for light source.
-    // Get the radius vector of the spacecraft to the eclipsing body
-    let r_eb = cosm.frame_chg(observer, eclipsing_body).radius();
-
-    // Get the radius vector of the light source to the spacecraft
-    let r_ls = -cosm.frame_chg(observer, light_source).radius();
-
-    // Compute the apparent radii of the light source and eclipsing body (preventing any NaN)
-    let r_ls_prime = if light_source.equatorial_radius() >= r_ls.norm() {
-        light_source.equatorial_radius()
-    } else {
-        (light_source.equatorial_radius() / r_ls.norm()).asin()
-    };
-    let r_eb_prime = if eclipsing_body.equatorial_radius() >= r_eb.norm() {
-        eclipsing_body.equatorial_radius()
-    } else {
-        (eclipsing_body.equatorial_radius() / r_eb.norm()).asin()
-    };
-
-    // Compute the apparent separation of both circles
-    let d_prime = (-(r_ls.dot(&r_eb)) / (r_eb.norm() * r_ls.norm())).acos();
+    let (r_ls_prime, r_eb_prime, d_prime) =
+        apparent_disks(observer, light_source, eclipsing_body, cosm);
 
     if d_prime - r_ls_prime > r_eb_prime {
         // If the closest point where the apparent radius of the light source _starts_ is further
@@ -367,6 +539,40 @@ pub fn eclipse_state(
     }
 }
 
+/// Returns the apparent radii of the light source and of the eclipsing body, and the apparent separation of their centers,
+/// as seen from the observer.
+fn apparent_disks(
+    observer: &Orbit,
+    light_source: Frame,
+    eclipsing_body: Frame,
+    cosm: &Cosm,
+) -> (f64, f64, f64) {
+    // All of the computations happen with the observer as the center.
+    // `eb` stands for eclipsing body; `ls` stands for light source.
+    // Get the radius vector of the spacecraft to the eclipsing body
+    let r_eb = cosm.frame_chg(observer, eclipsing_body).radius();
+
+    // Get the radius vector of the light source to the spacecraft
+    let r_ls = -cosm.frame_chg(observer, light_source).radius();
+
+    // Compute the apparent radii of the light source and eclipsing body (preventing any NaN)
+    let r_ls_prime = if light_source.equatorial_radius() >= r_ls.norm() {
+        light_source.equatorial_radius()
+    } else {
+        (light_source.equatorial_radius() / r_ls.norm()).asin()
+    };
+    let r_eb_prime = if eclipsing_body.equatorial_radius() >= r_eb.norm() {
+        eclipsing_body.equatorial_radius()
+    } else {
+        (eclipsing_body.equatorial_radius() / r_eb.norm()).asin()
+    };
+
+    // Compute the apparent separation of both circles
+    let d_prime = (-(r_ls.dot(&r_eb)) / (r_eb.norm() * r_ls.norm())).acos();
+
+    (r_ls_prime, r_eb_prime, d_prime)
+}
+
 // Compute the area of the circular segment of radius r and chord length d
 fn circ_seg_area(r: f64, d: f64) -> f64 {
     r.powi(2) * (d / r).acos() - d * (r.powi(2) - d.powi(2)).sqrt()
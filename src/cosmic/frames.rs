@@ -22,6 +22,56 @@ use std::cmp::PartialEq;
 use std::convert::TryFrom;
 use std::f64::consts::PI;
 use std::fmt;
+use std::ops::Deref;
+
+/// Path of a frame, or of the ephemeris of its center, in the trees of the Cosm.
+///
+/// The path is stored inline (the trees are at most three levels deep) and dereferences to a slice of indexes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FramePath {
+    indexes: [usize; 3],
+    len: usize,
+}
+
+impl FramePath {
+    /// Builds a path from its indexes, panics if it is deeper than three levels
+    pub fn new(indexes: &[usize]) -> Self {
+        assert!(indexes.len() <= 3, "frame paths have at most three levels");
+        let mut path = Self::default();
+        path.indexes[..indexes.len()].copy_from_slice(indexes);
+        path.len = indexes.len();
+        path
+    }
+
+    fn from_options(indexes: &[Option<usize>; 3]) -> Self {
+        let mut path = Self::default();
+        for idx in indexes.iter().flatten() {
+            path.indexes[path.len] = *idx;
+            path.len += 1;
+        }
+        path
+    }
+}
+
+impl Deref for FramePath {
+    type Target = [usize];
+
+    fn deref(&self) -> &[usize] {
+        &self.indexes[..self.len]
+    }
+}
+
+impl PartialEq<[usize]> for FramePath {
+    fn eq(&self, other: &[usize]) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<&[usize]> for FramePath {
+    fn eq(&self, other: &&[usize]) -> bool {
+        **self == **other
+    }
+}
 
 #[allow(non_snake_case, clippy::upper_case_acronyms)]
 #[derive(Copy, Clone, PartialEq)]
@@ -62,27 +112,19 @@ impl Frame {
         matches!(self, Frame::Celestial { .. })
     }
 
-    pub fn ephem_path(&self) -> Vec<usize> {
+    pub fn ephem_path(&self) -> FramePath {
         match self {
             Frame::Celestial { ephem_path, .. } | Frame::Geoid { ephem_path, .. } => {
-                let mut path = Vec::with_capacity(3);
-                for p in ephem_path.iter().flatten() {
-                    path.push(*p)
-                }
-                path
+                FramePath::from_options(ephem_path)
             }
             _ => panic!("Frame is not Celestial or Geoid in kind"),
         }
     }
 
-    pub fn frame_path(&self) -> Vec<usize> {
+    pub fn frame_path(&self) -> FramePath {
         match self {
             Frame::Celestial { frame_path, .. } | Frame::Geoid { frame_path, .. } => {
-                let mut path = Vec::with_capacity(3);
-                for p in frame_path.iter().flatten() {
-                    path.push(*p)
-                }
-                path
+                FramePath::from_options(frame_path)
             }
             _ => panic!("Frame is not Celestial or Geoid in kind"),
         }
@@ -143,7 +185,7 @@ impl Frame {
     #[allow(clippy::identity_op)]
     pub fn angular_velocity(&self) -> f64 {
         let period_to_mean_motion = |dur: Duration| -> f64 { 2.0 * PI / dur.to_seconds() };
-        match Bodies::try_from(self.ephem_path()).unwrap() {
+        match Bodies::try_from(self.ephem_path().to_vec()).unwrap() {
             Bodies::MercuryBarycenter | Bodies::Mercury => {
                 period_to_mean_motion(58 * Unit::Day + 15 * Unit::Hour + 30 * Unit::Minute)
             }
@@ -175,13 +217,13 @@ impl fmt::Display for Frame {
                     write!(
                         f,
                         "IAU {}",
-                        Bodies::try_from(self.ephem_path()).unwrap().name()
+                        Bodies::try_from(self.ephem_path().to_vec()).unwrap().name()
                     )
                 } else {
                     write!(
                         f,
                         "{} {}",
-                        Bodies::try_from(self.ephem_path()).unwrap().name(),
+                        Bodies::try_from(self.ephem_path().to_vec()).unwrap().name(),
                         match self.frame_path().len() {
                             0 | 1 => "J2000".to_string(),
                             2 => "IAU Fixed".to_string(),
@@ -204,7 +246,7 @@ impl fmt::Debug for Frame {
                 write!(
                     f,
                     "{} {} (μ = {:.06} km^3/s^2)",
-                    Bodies::try_from(self.ephem_path()).unwrap().name(),
+                    Bodies::try_from(self.ephem_path().to_vec()).unwrap().name(),
                     match self.frame_path().len() {
                         0 | 1 => "J2000".to_string(),
                         2 => "IAU Fixed".to_string(),
@@ -223,7 +265,7 @@ impl fmt::Debug for Frame {
                 write!(
                     f,
                     "{} {} (μ = {:.06} km^3/s^2 , r = {:.06} km, f = {:.09})",
-                    Bodies::try_from(self.ephem_path()).unwrap().name(),
+                    Bodies::try_from(self.ephem_path().to_vec()).unwrap().name(),
                     match self.frame_path().len() {
                         0 | 1 => "J2000".to_string(),
                         2 => "IAU Fixed".to_string(),
@@ -0,0 +1,188 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Frame, Orbit};
+use crate::linalg::Vector3;
+use crate::md::objective::Objective;
+use crate::md::StateParameter;
+use crate::time::Epoch;
+use crate::utils::between_pm_180;
+use crate::NyxError;
+use std::fmt;
+
+/// The injection target of a departure hyperbola, as exchanged with launch providers: the characteristic energy (C3), and the
+/// right ascension (RLA) and declination (DLA) of the outgoing asymptote.
+///
+/// The angles are those of the frame of the injection orbit, which is usually EME2000.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LaunchTarget {
+    /// Characteristic energy, i.e. the square of the hyperbolic excess velocity, in km^2/s^2
+    pub c3_km2_s2: f64,
+    /// Right ascension of the outgoing asymptote, in degrees
+    pub rla_deg: f64,
+    /// Declination of the outgoing asymptote, in degrees
+    pub dla_deg: f64,
+
+    /// The tolerance on C3, in km^2/s^2
+    pub tol_c3_km2_s2: f64,
+    /// The tolerance on RLA, in degrees
+    pub tol_rla_deg: f64,
+    /// The tolerance on DLA, in degrees
+    pub tol_dla_deg: f64,
+}
+
+impl LaunchTarget {
+    /// Initializes a new launch target with the default tolerances of 0.01 km^2/s^2 in C3 and 0.01 degrees in RLA and DLA.
+    pub fn new(c3_km2_s2: f64, rla_deg: f64, dla_deg: f64) -> Self {
+        Self {
+            c3_km2_s2,
+            rla_deg,
+            dla_deg,
+            tol_c3_km2_s2: 1e-2,
+            tol_rla_deg: 1e-2,
+            tol_dla_deg: 1e-2,
+        }
+    }
+
+    /// Returns the launch target achieved by the provided hyperbolic orbit, with the default tolerances.
+    pub fn from_orbit(orbit: &Orbit) -> Result<Self, NyxError> {
+        Ok(Self::new(
+            orbit.c3_km2_s2(),
+            orbit.vinf_right_ascension_deg()?,
+            orbit.vinf_declination_deg()?,
+        ))
+    }
+
+    /// Returns the hyperbolic excess velocity in km/s
+    pub fn vinf_km_s(&self) -> f64 {
+        self.c3_km2_s2.sqrt()
+    }
+
+    /// Returns the unit vector of the outgoing asymptote
+    pub fn vinf_hat(&self) -> Vector3<f64> {
+        let (sin_rla, cos_rla) = self.rla_deg.to_radians().sin_cos();
+        let (sin_dla, cos_dla) = self.dla_deg.to_radians().sin_cos();
+        Vector3::new(cos_dla * cos_rla, cos_dla * sin_rla, sin_dla)
+    }
+
+    /// Returns both injection orbits at the periapsis of the departure hyperbola achieving this target, for the provided
+    /// radius of periapsis (km) and inclination (deg). The asymptote must be reachable from this inclination, i.e. the
+    /// absolute value of DLA may not exceed the inclination nor its supplement.
+    ///
+    /// The two solutions only differ by the orientation of their plane around the asymptote, i.e. by their ascending node.
+    /// The first one has its angular momentum on the eastern side of the asymptote.
+    pub fn injection_orbits(
+        &self,
+        periapsis_km: f64,
+        inclination_deg: f64,
+        epoch: Epoch,
+        frame: Frame,
+    ) -> Result<[Orbit; 2], NyxError> {
+        if self.c3_km2_s2 <= 0.0 {
+            return Err(NyxError::NotHyperbolic(format!(
+                "C3 of {} km^2/s^2 does not define a departure hyperbola",
+                self.c3_km2_s2
+            )));
+        }
+
+        let s_hat = self.vinf_hat();
+        let cos_dla = self.dla_deg.to_radians().cos();
+        // The angular momentum lies in the plane orthogonal to the asymptote, spanned by an eastward and a northward vector
+        let sin_theta = inclination_deg.to_radians().cos() / cos_dla;
+        if sin_theta.abs() > 1.0 {
+            return Err(NyxError::CustomError(format!(
+                "DLA of {:.3} deg is not reachable with an inclination of {inclination_deg:.3} deg",
+                self.dla_deg
+            )));
+        }
+        let east = Vector3::z().cross(&s_hat).normalize();
+        let north = s_hat.cross(&east);
+        let cos_theta = (1.0 - sin_theta.powi(2)).sqrt();
+
+        let gm = frame.gm();
+        let ecc = 1.0 + periapsis_km * self.c3_km2_s2 / gm;
+        // True anomaly of the outgoing asymptote
+        let ta_inf = (-1.0 / ecc).acos();
+        let vp_km_s = (self.c3_km2_s2 + 2.0 * gm / periapsis_km).sqrt();
+
+        let orbit_for = |h_hat: Vector3<f64>| {
+            // Rotate the asymptote back to the periapsis, around the angular momentum
+            let p_hat = ta_inf.cos() * s_hat - ta_inf.sin() * h_hat.cross(&s_hat);
+            let q_hat = h_hat.cross(&p_hat);
+            let r = periapsis_km * p_hat;
+            let v = vp_km_s * q_hat;
+            Orbit::cartesian(r[0], r[1], r[2], v[0], v[1], v[2], epoch, frame)
+        };
+
+        Ok([
+            orbit_for(cos_theta * east + sin_theta * north),
+            orbit_for(-cos_theta * east + sin_theta * north),
+        ])
+    }
+
+    /// Returns the errors in C3 (km^2/s^2), RLA (deg) and DLA (deg) of the provided orbit with respect to this target
+    pub fn errors(&self, orbit: &Orbit) -> Result<[f64; 3], NyxError> {
+        let achieved = Self::from_orbit(orbit)?;
+        Ok([
+            achieved.c3_km2_s2 - self.c3_km2_s2,
+            between_pm_180(achieved.rla_deg - self.rla_deg),
+            achieved.dla_deg - self.dla_deg,
+        ])
+    }
+
+    /// Returns whether the provided orbit achieves this target within its tolerances, e.g. to verify a delivered injection state
+    pub fn is_achieved_by(&self, orbit: &Orbit) -> Result<bool, NyxError> {
+        let [c3_err, rla_err, dla_err] = self.errors(orbit)?;
+        Ok(c3_err.abs() <= self.tol_c3_km2_s2
+            && rla_err.abs() <= self.tol_rla_deg
+            && dla_err.abs() <= self.tol_dla_deg)
+    }
+
+    /// Returns the C3, RLA and DLA objectives of the targeter, with the tolerances of this target.
+    /// The RLA objective is between -180 and 180 degrees, like the RLA computed by the targeter.
+    pub fn to_objectives(self) -> [Objective; 3] {
+        [
+            Objective::within_tolerance(StateParameter::C3, self.c3_km2_s2, self.tol_c3_km2_s2),
+            Objective::within_tolerance(
+                StateParameter::VinfRightAscension,
+                between_pm_180(self.rla_deg),
+                self.tol_rla_deg,
+            ),
+            Objective::within_tolerance(
+                StateParameter::VinfDeclination,
+                self.dla_deg,
+                self.tol_dla_deg,
+            ),
+        ]
+    }
+}
+
+impl fmt::Display for LaunchTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Launch target: C3 = {:.3} km^2/s^2 (+/- {:.3})\tRLA = {:.3} deg (+/- {:.3})\tDLA = {:.3} deg (+/- {:.3})",
+            self.c3_km2_s2,
+            self.tol_c3_km2_s2,
+            self.rla_deg,
+            self.tol_rla_deg,
+            self.dla_deg,
+            self.tol_dla_deg,
+        )
+    }
+}
@@ -22,7 +22,7 @@ pub use crate::cosmic::{Frame, GuidanceMode, Orbit, Spacecraft};
 pub use crate::errors::NyxError;
 use crate::linalg::allocator::Allocator;
 use crate::linalg::{DefaultAllocator, DimName, OMatrix, OVector};
-use crate::md::StateParameter;
+use crate::md::{NamedParameter, StateParameter};
 use crate::time::{Duration, Epoch, Unit};
 use hifitime::SECONDS_PER_DAY;
 use std::fmt::{self, Write};
@@ -122,6 +122,25 @@ where
             "unimplemented in State trait".to_string(),
         ))
     }
+
+    /// Names and units of the parameters of this state which are not a `StateParameter`, e.g. the components of the
+    /// state of custom dynamics. These can be used in events, and are exported with the trajectories. None by default.
+    fn named_parameters() -> Vec<(&'static str, &'static str)> {
+        Vec::new()
+    }
+
+    /// Return the value of the named parameter at the provided index of `named_parameters`, returns an error by default
+    fn named_value_at(&self, index: usize) -> Result<f64, NyxError> {
+        Err(NyxError::NamedParameterUnavailable(
+            NamedParameter::Index(index),
+            "unimplemented in State trait".to_string(),
+        ))
+    }
+
+    /// Return the value of the provided named parameter
+    fn named_value(&self, param: &NamedParameter) -> Result<f64, NyxError> {
+        self.named_value_at(param.index_in(&Self::named_parameters())?)
+    }
 }
 
 impl XbEpoch {
@@ -310,6 +329,10 @@ pub use self::bodies::*;
 mod orbit;
 pub use self::orbit::*;
 
+// Re-Export orbit comparison tolerances
+mod orbit_tolerance;
+pub use self::orbit_tolerance::*;
+
 // Re-Export OrbitDual
 mod orbitdual;
 pub use self::orbitdual::*;
@@ -318,6 +341,10 @@ pub use self::orbitdual::*;
 mod bplane;
 pub use self::bplane::*;
 
+// Re-Export launch targets
+mod launch;
+pub use self::launch::*;
+
 // Re-Export spacecraft
 mod spacecraft;
 pub use self::spacecraft::*;
@@ -329,6 +356,10 @@ pub use self::frames::*;
 mod rotations;
 pub use self::rotations::*;
 
+// Re-Export two line element sets
+mod tle;
+pub use self::tle::*;
+
 mod cosm;
 mod xb;
 pub use self::cosm::*;
@@ -31,7 +31,7 @@ use crate::md::StateParameter;
 use crate::time::{Duration, Epoch, Unit};
 use crate::utils::{
     between_0_360, between_pm_180, cartesian_to_spherical, perpv, r1, r3, rss_orbit_errors,
-    spherical_to_cartesian,
+    spherical_to_cartesian, sun_right_ascension_deg,
 };
 use crate::NyxError;
 use approx::{abs_diff_eq, relative_eq};
@@ -1529,6 +1529,54 @@ impl Orbit {
         }
     }
 
+    /// Returns the magnitude of the hyperbolic excess velocity (v∞) in km/s, only defined for hyperbolic orbits
+    pub fn vinf_km_s(&self) -> Result<f64, NyxError> {
+        if self.ecc() <= 1.0 {
+            Err(NyxError::NotHyperbolic(
+                "Orbit is not hyperbolic so there is no excess velocity.".to_string(),
+            ))
+        } else {
+            Ok(self.c3_km2_s2().sqrt())
+        }
+    }
+
+    /// Returns the unit vector of the outgoing asymptote, i.e. the direction of the hyperbolic excess velocity on departure
+    pub fn vinf_outgoing_hat(&self) -> Result<Vector3<f64>, NyxError> {
+        if self.ecc() <= 1.0 {
+            Err(NyxError::NotHyperbolic(
+                "Orbit is not hyperbolic so there is no asymptote.".to_string(),
+            ))
+        } else {
+            let ecc = self.ecc();
+            let e_hat = self.evec() / ecc;
+            let p_hat = (self.hvec() / self.hmag_km2_s()).cross(&e_hat);
+            // The true anomaly of the asymptote is such that cos(ta) = -1/ecc
+            let s = -e_hat / ecc + (1.0 - (1.0 / ecc).powi(2)).sqrt() * p_hat;
+            Ok(s / s.norm())
+        }
+    }
+
+    /// Returns the right ascension of the outgoing asymptote (RLA) in degrees, only defined for hyperbolic orbits
+    pub fn vinf_right_ascension_deg(&self) -> Result<f64, NyxError> {
+        let s_hat = self.vinf_outgoing_hat()?;
+        Ok(between_0_360(s_hat[1].atan2(s_hat[0]).to_degrees()))
+    }
+
+    /// Returns the declination of the outgoing asymptote (DLA) in degrees, only defined for hyperbolic orbits
+    pub fn vinf_declination_deg(&self) -> Result<f64, NyxError> {
+        let s_hat = self.vinf_outgoing_hat()?;
+        Ok(s_hat[2].asin().to_degrees())
+    }
+
+    /// Returns the local time of the ascending node in hours between 0 and 24.
+    ///
+    /// # Astrodynamics note
+    /// This is computed from the RAAN and the right ascension of the Sun as seen from the Earth (cf. `sun_right_ascension_deg`),
+    /// so it is only meaningful for Earth orbits in an Earth centered inertial frame.
+    pub fn ltan_hours(&self) -> f64 {
+        (12.0 + (self.raan_deg() - sun_right_ascension_deg(self.epoch)) / 15.0).rem_euclid(24.0)
+    }
+
     /// Sets the STM of this state of identity, which also enables computation of the STM for spacecraft navigation
     pub fn enable_stm(&mut self) {
         self.stm = Some(Matrix6::identity());
@@ -2172,6 +2220,7 @@ impl State for Orbit {
             StateParameter::HZ => Ok(self.hz_km2_s()),
             StateParameter::HyperbolicAnomaly => self.hyperbolic_anomaly_deg(),
             StateParameter::Inclination => Ok(self.inc_deg()),
+            StateParameter::LTAN => Ok(self.ltan_hours()),
             StateParameter::MeanAnomaly => Ok(self.ma_deg()),
             StateParameter::PeriapsisRadius => Ok(self.periapsis_km()),
             StateParameter::Period => Ok(self.period().to_seconds()),
@@ -2184,6 +2233,9 @@ impl State for Orbit {
             StateParameter::TrueAnomaly => Ok(self.ta_deg()),
             StateParameter::TrueLongitude => Ok(self.tlong_deg()),
             StateParameter::VelocityDeclination => Ok(self.velocity_declination_deg()),
+            StateParameter::Vinf => self.vinf_km_s(),
+            StateParameter::VinfDeclination => self.vinf_declination_deg(),
+            StateParameter::VinfRightAscension => self.vinf_right_ascension_deg(),
             StateParameter::Vmag => Ok(self.vmag_km_s()),
             StateParameter::X => Ok(self.x_km),
             StateParameter::Y => Ok(self.y_km),
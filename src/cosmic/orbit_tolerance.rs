@@ -0,0 +1,200 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::Orbit;
+use crate::md::StateParameter;
+use crate::time::Duration;
+use crate::utils::between_pm_180;
+use crate::State;
+use std::fmt;
+
+/// Tolerances used to compare two orbits, cf. `Orbit::approx_eq`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrbitTolerances {
+    /// Maximum absolute difference of each component of the position, in km
+    pub position_km: f64,
+    /// Maximum absolute difference of each component of the velocity, in km/s
+    pub velocity_km_s: f64,
+    /// Maximum difference between both epochs
+    pub epoch: Duration,
+    /// Whether both orbits must be in the same frame
+    pub check_frame: bool,
+    /// Maximum absolute difference of any other parameter (e.g. SMA or inclination), in the unit of that parameter.
+    /// Differences of angles are bounded between -180 and +180 degrees.
+    pub elements: Vec<(StateParameter, f64)>,
+}
+
+impl OrbitTolerances {
+    /// Tolerances on each component of the position and velocity, requiring the same epoch and frame
+    pub fn cartesian(position_km: f64, velocity_km_s: f64) -> Self {
+        Self {
+            position_km,
+            velocity_km_s,
+            epoch: Duration::ZERO,
+            check_frame: true,
+            elements: Vec::new(),
+        }
+    }
+
+    /// Adds a tolerance on the provided parameter
+    pub fn with_element(mut self, param: StateParameter, tolerance: f64) -> Self {
+        self.elements.push((param, tolerance));
+        self
+    }
+
+    /// Allows both epochs to differ by up to the provided duration
+    pub fn with_epoch_tolerance(mut self, epoch: Duration) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    /// Allows both orbits to be in different frames, e.g. to compare states expressed in similar frames with different names
+    pub fn without_frame_check(mut self) -> Self {
+        self.check_frame = false;
+        self
+    }
+}
+
+impl Default for OrbitTolerances {
+    /// Same tolerances as the equality operator: one centimeter and one centimeter per second
+    fn default() -> Self {
+        Self::cartesian(1e-5, 1e-5)
+    }
+}
+
+/// A difference between two orbits which exceeds the tolerances
+#[derive(Clone, Debug, PartialEq)]
+pub enum OrbitMismatch {
+    /// Names of the frames
+    Frame { expected: String, actual: String },
+    Epoch {
+        difference: Duration,
+        tolerance: Duration,
+    },
+    Element {
+        param: StateParameter,
+        difference: f64,
+        tolerance: f64,
+    },
+    /// The parameter could not be computed for either orbit (e.g. a hyperbolic anomaly of an elliptical orbit)
+    Unavailable(StateParameter),
+}
+
+impl fmt::Display for OrbitMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Frame { expected, actual } => {
+                write!(f, "frame is {actual} instead of {expected}")
+            }
+            Self::Epoch {
+                difference,
+                tolerance,
+            } => write!(f, "epochs differ by {difference} > {tolerance}"),
+            Self::Element {
+                param,
+                difference,
+                tolerance,
+            } => write!(f, "{param} differs by {difference:e} > {tolerance:e}"),
+            Self::Unavailable(param) => write!(f, "{param} is unavailable"),
+        }
+    }
+}
+
+impl Orbit {
+    /// Returns whether this orbit and the other are equal within the provided tolerances
+    pub fn approx_eq(&self, other: &Self, tolerances: &OrbitTolerances) -> bool {
+        self.mismatches(other, tolerances).is_empty()
+    }
+
+    /// Returns all of the differences between this orbit and the expected one which exceed the provided tolerances
+    pub fn mismatches(&self, expected: &Self, tolerances: &OrbitTolerances) -> Vec<OrbitMismatch> {
+        let mut mismatches = Vec::new();
+
+        if tolerances.check_frame && self.frame != expected.frame {
+            mismatches.push(OrbitMismatch::Frame {
+                expected: format!("{}", expected.frame),
+                actual: format!("{}", self.frame),
+            });
+        }
+
+        let difference = (self.epoch - expected.epoch).abs();
+        if difference > tolerances.epoch {
+            mismatches.push(OrbitMismatch::Epoch {
+                difference,
+                tolerance: tolerances.epoch,
+            });
+        }
+
+        let cartesian = [
+            (StateParameter::X, tolerances.position_km),
+            (StateParameter::Y, tolerances.position_km),
+            (StateParameter::Z, tolerances.position_km),
+            (StateParameter::VX, tolerances.velocity_km_s),
+            (StateParameter::VY, tolerances.velocity_km_s),
+            (StateParameter::VZ, tolerances.velocity_km_s),
+        ];
+
+        for (param, tolerance) in cartesian.iter().chain(tolerances.elements.iter()) {
+            match (self.value(*param), expected.value(*param)) {
+                (Ok(actual), Ok(expected)) => {
+                    let mut difference = actual - expected;
+                    if param.unit() == "deg" {
+                        difference = between_pm_180(difference);
+                    }
+                    if difference.is_nan() || difference.abs() > *tolerance {
+                        mismatches.push(OrbitMismatch::Element {
+                            param: *param,
+                            difference,
+                            tolerance: *tolerance,
+                        });
+                    }
+                }
+                _ => mismatches.push(OrbitMismatch::Unavailable(*param)),
+            }
+        }
+
+        mismatches
+    }
+}
+
+/// Asserts that an orbit is equal to the expected one within the provided `OrbitTolerances`, listing all of the mismatches otherwise.
+#[macro_export]
+macro_rules! assert_orbit_approx_eq {
+    ($actual:expr, $expected:expr, $tolerances:expr) => {{
+        let (actual, expected) = (&$actual, &$expected);
+        let mismatches = actual.mismatches(expected, &$tolerances);
+        assert!(
+            mismatches.is_empty(),
+            "orbits differ:\n{}\nactual:   {}\nexpected: {}",
+            mismatches
+                .iter()
+                .map(|mismatch| format!("\t{mismatch}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            actual,
+            expected
+        );
+    }};
+    ($actual:expr, $expected:expr) => {
+        $crate::assert_orbit_approx_eq!(
+            $actual,
+            $expected,
+            $crate::cosmic::OrbitTolerances::default()
+        )
+    };
+}
@@ -20,6 +20,7 @@ use super::{Frame, Orbit, ECC_EPSILON};
 use crate::linalg::{Vector3, U7};
 use crate::md::StateParameter;
 use crate::time::Epoch;
+use crate::utils::sun_right_ascension_deg;
 use crate::{NyxError, TimeTagged};
 use hyperdual::linalg::norm;
 use hyperdual::{Float, OHyperdual};
@@ -168,6 +169,12 @@ impl OrbitDual {
             StateParameter::HyperbolicAnomaly => self.hyperbolic_anomaly(),
             StateParameter::SemiParameter => Ok(self.semi_parameter()),
             StateParameter::SemiMinorAxis => Ok(self.semi_minor_axis()),
+            StateParameter::PeriapsisRadius => Ok(self.periapsis_radius()),
+            StateParameter::ApoapsisRadius => Ok(self.apoapsis_radius()),
+            StateParameter::Vinf => self.vinf(),
+            StateParameter::VinfRightAscension => self.vinf_right_ascension(),
+            StateParameter::VinfDeclination => self.vinf_declination(),
+            StateParameter::LTAN => Ok(self.ltan()),
             _ => Err(NyxError::PartialsUndefined),
         }
     }
@@ -557,6 +564,22 @@ impl OrbitDual {
         }
     }
 
+    /// Returns the radius of periapsis in km
+    pub fn periapsis_radius(&self) -> OrbitPartial {
+        OrbitPartial {
+            dual: self.sma().dual * (OHyperdual::from(1.0) - self.ecc().dual),
+            param: StateParameter::PeriapsisRadius,
+        }
+    }
+
+    /// Returns the radius of apoapsis in km
+    pub fn apoapsis_radius(&self) -> OrbitPartial {
+        OrbitPartial {
+            dual: self.sma().dual * (OHyperdual::from(1.0) + self.ecc().dual),
+            param: StateParameter::ApoapsisRadius,
+        }
+    }
+
     /// Returns the geodetic longitude (λ) in degrees. Value is between 0 and 360 degrees.
     ///
     /// Although the reference is not Vallado, the math from Vallado proves to be equivalent.
@@ -718,6 +741,75 @@ impl OrbitDual {
             })
         }
     }
+
+    /// Returns the magnitude of the hyperbolic excess velocity in km/s
+    pub fn vinf(&self) -> Result<OrbitPartial, NyxError> {
+        if self.ecc().real() <= 1.0 {
+            Err(NyxError::NotHyperbolic(
+                "Orbit is not hyperbolic so there is no excess velocity.".to_string(),
+            ))
+        } else {
+            Ok(OrbitPartial {
+                dual: self.c3().dual.sqrt(),
+                param: StateParameter::Vinf,
+            })
+        }
+    }
+
+    /// Returns the unit vector of the outgoing asymptote
+    pub(crate) fn vinf_outgoing_hat(&self) -> Result<Vector3<OHyperdual<f64, U7>>, NyxError> {
+        if self.ecc().real() <= 1.0 {
+            Err(NyxError::NotHyperbolic(
+                "Orbit is not hyperbolic so there is no asymptote.".to_string(),
+            ))
+        } else {
+            let one = OHyperdual::from(1.0);
+            let ecc = self.ecc().dual;
+            let e_hat = self.evec() / ecc;
+            let h_hat = self.hvec() / self.hmag().dual;
+            let p_hat = h_hat.cross(&e_hat);
+
+            let asymptote_fact = (one - (one / ecc).powi(2)).sqrt();
+
+            let s = Vector3::new(
+                -e_hat[0] / ecc + asymptote_fact * p_hat[0],
+                -e_hat[1] / ecc + asymptote_fact * p_hat[1],
+                -e_hat[2] / ecc + asymptote_fact * p_hat[2],
+            );
+
+            Ok(s / norm(&s))
+        }
+    }
+
+    /// Returns the right ascension of the outgoing asymptote in degrees
+    pub fn vinf_right_ascension(&self) -> Result<OrbitPartial, NyxError> {
+        let s_hat = self.vinf_outgoing_hat()?;
+        Ok(OrbitPartial {
+            dual: s_hat[1].atan2(s_hat[0]).to_degrees(),
+            param: StateParameter::VinfRightAscension,
+        })
+    }
+
+    /// Returns the declination of the outgoing asymptote in degrees
+    pub fn vinf_declination(&self) -> Result<OrbitPartial, NyxError> {
+        let s_hat = self.vinf_outgoing_hat()?;
+        Ok(OrbitPartial {
+            dual: s_hat[2].asin().to_degrees(),
+            param: StateParameter::VinfDeclination,
+        })
+    }
+
+    /// Returns the local time of the ascending node in hours
+    pub fn ltan(&self) -> OrbitPartial {
+        let hours = self.raan().dual / OHyperdual::from(15.0)
+            + OHyperdual::from(12.0 - sun_right_ascension_deg(self.dt) / 15.0);
+        // Bound between 0 and 24 hours, which does not change the partials
+        let wrap = 24.0 * (hours.real() / 24.0).floor();
+        OrbitPartial {
+            dual: hours - OHyperdual::from(wrap),
+            param: StateParameter::LTAN,
+        }
+    }
 }
 
 impl TimeTagged for OrbitDual {
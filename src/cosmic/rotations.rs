@@ -16,16 +16,17 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use crate::log::error;
 use crate::na::Matrix3;
 use crate::time::Epoch;
 use crate::utils::{r1, r2, r3};
 use meval::{Context, Expr};
+use serde::{Deserialize, Serialize};
 use std::cmp::PartialEq;
 use std::collections::HashMap;
 use std::fmt;
 use std::io::{Error as IoError, ErrorKind as IoErrorKind};
 use std::str::FromStr;
+use tracing::error;
 
 pub trait ParentRotation: Send + Sync + fmt::Debug {
     fn dcm_to_parent(&self, datetime: Epoch) -> Option<Matrix3<f64>>;
@@ -90,7 +91,7 @@ impl ParentRotation for Euler3Axis {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AngleUnit {
     Degrees,
     Radians,
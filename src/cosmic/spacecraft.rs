@@ -586,6 +586,7 @@ impl State for Spacecraft {
         match param {
             StateParameter::Cd => self.drag.cd = val,
             StateParameter::Cr => self.srp.cr = val,
+            StateParameter::DryMass => self.dry_mass_kg = val,
             StateParameter::FuelMass => self.fuel_mass_kg = val,
             StateParameter::Isp => match self.thruster {
                 Some(ref mut thruster) => thruster.isp_s = val,
@@ -0,0 +1,941 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Cosm, Orbit};
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DMatrix, DVector, DefaultAllocator, Matrix3, Vector3, Vector6};
+use crate::md::trajectory::{Interpolatable, Traj};
+use crate::time::{Duration, Epoch, TimeSeries, Unit};
+use crate::utils::{r1, r2, r3};
+use std::f64::consts::{PI, TAU};
+use std::fmt;
+use std::sync::Arc;
+
+/// Gravitational parameter of the WGS-72 model used by SGP4, in km^3/s^2
+const MU_WGS72: f64 = 398_600.8;
+/// Equatorial radius of the WGS-72 model used by SGP4, in km
+const RADIUS_WGS72_KM: f64 = 6_378.135;
+const J2: f64 = 0.001_082_616;
+const J3: f64 = -0.000_002_538_81;
+const J4: f64 = -0.000_001_655_97;
+const MINUTES_PER_DAY: f64 = 1440.0;
+/// Orbital period from which SDP4 (deep space) must be used instead of SGP4
+const DEEP_SPACE_PERIOD_MIN: f64 = 225.0;
+
+/// A two line element set, i.e. the mean elements of the SGP4 propagator published for Earth orbiting objects.
+///
+/// The mean elements are expressed in the True Equator, Mean Equinox (TEME) frame of the epoch of the TLE.
+/// Only near Earth objects (i.e. with an orbital period less than 225 minutes) are supported, since they are propagated with SGP4 and not SDP4.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tle {
+    pub catalog_number: u32,
+    /// Classification: U (unclassified), C (classified) or S (secret)
+    pub classification: char,
+    /// International designator, e.g. 98067A
+    pub intl_designator: String,
+    pub epoch: Epoch,
+    /// First derivative of the mean motion divided by two, in rev/day^2, unused by SGP4
+    pub mean_motion_dot: f64,
+    /// Second derivative of the mean motion divided by six, in rev/day^3, unused by SGP4
+    pub mean_motion_ddot: f64,
+    /// Drag term, in inverse Earth radii
+    pub bstar: f64,
+    pub element_set_number: u16,
+    pub inc_deg: f64,
+    pub raan_deg: f64,
+    pub ecc: f64,
+    pub aop_deg: f64,
+    pub ma_deg: f64,
+    /// Kozai mean motion, in revolutions per day
+    pub mean_motion_rev_day: f64,
+    pub rev_number: u32,
+}
+
+impl Tle {
+    /// Parses a TLE from its two lines, checking their checksums
+    pub fn parse(line1: &str, line2: &str) -> Result<Self, NyxError> {
+        let line1 = line1.trim_end();
+        let line2 = line2.trim_end();
+        for (num, line) in [(1, line1), (2, line2)] {
+            if line.len() != 69 || !line.is_ascii() {
+                return Err(NyxError::CustomError(format!(
+                    "TLE line {num} must have 69 characters: `{line}`"
+                )));
+            }
+            if !line.starts_with(&format!("{num} ")) {
+                return Err(NyxError::CustomError(format!(
+                    "TLE line {num} must start with its line number: `{line}`"
+                )));
+            }
+            let expected = checksum(&line[..68]);
+            if line[68..].parse::<u32>().ok() != Some(expected) {
+                return Err(NyxError::CustomError(format!(
+                    "TLE line {num} has an invalid checksum (expected {expected}): `{line}`"
+                )));
+            }
+        }
+
+        let catalog_number: u32 = parse_field(line1, 2, 7, "catalog number")?;
+        if parse_field::<u32>(line2, 2, 7, "catalog number")? != catalog_number {
+            return Err(NyxError::CustomError(
+                "the two lines of the TLE are for different objects".to_string(),
+            ));
+        }
+
+        // Epoch as a two digit year and the day of the year in UTC
+        let yy: i32 = parse_field(line1, 18, 20, "epoch year")?;
+        let year = if yy < 57 { 2000 + yy } else { 1900 + yy };
+        let day_of_year: f64 = parse_field(line1, 20, 32, "epoch day")?;
+        let epoch =
+            Epoch::from_gregorian_utc_at_midnight(year, 1, 1) + (day_of_year - 1.0) * Unit::Day;
+
+        let ndot_field = line1[33..43].trim();
+        let (ndot_sign, ndot_digits) = match ndot_field.strip_prefix('-') {
+            Some(digits) => (-1.0, digits),
+            None => (1.0, ndot_field.trim_start_matches('+')),
+        };
+        let mean_motion_dot = ndot_sign
+            * format!("0{ndot_digits}")
+                .parse::<f64>()
+                .map_err(|_| NyxError::CustomError(format!("invalid TLE ndot `{ndot_field}`")))?;
+
+        Ok(Self {
+            catalog_number,
+            classification: line1.as_bytes()[7] as char,
+            intl_designator: line1[9..17].trim().to_string(),
+            epoch,
+            mean_motion_dot,
+            mean_motion_ddot: parse_exponent(&line1[44..52])?,
+            bstar: parse_exponent(&line1[53..61])?,
+            element_set_number: line1[64..68].trim().parse().unwrap_or(0),
+            inc_deg: parse_field(line2, 8, 16, "inclination")?,
+            raan_deg: parse_field(line2, 17, 25, "RAAN")?,
+            ecc: parse_field::<f64>(line2, 26, 33, "eccentricity")? * 1e-7,
+            aop_deg: parse_field(line2, 34, 42, "argument of perigee")?,
+            ma_deg: parse_field(line2, 43, 51, "mean anomaly")?,
+            mean_motion_rev_day: parse_field(line2, 52, 63, "mean motion")?,
+            rev_number: line2[63..68].trim().parse().unwrap_or(0),
+        })
+    }
+
+    /// Parses all of the TLEs of a text, e.g. a catalog file, each optionally preceded by a name line (three line format).
+    /// Unnamed TLEs are named after their catalog number, and blank lines are ignored.
+    pub fn parse_many(text: &str) -> Result<Vec<(String, Self)>, NyxError> {
+        let lines: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        let mut tles = Vec::new();
+        let mut idx = 0;
+        while idx < lines.len() {
+            let name = if lines[idx].starts_with("1 ") {
+                None
+            } else {
+                idx += 1;
+                Some(lines[idx - 1].trim().trim_start_matches("0 ").to_string())
+            };
+            if idx + 1 >= lines.len() {
+                return Err(NyxError::CustomError(format!(
+                    "truncated TLE at `{}`",
+                    lines[idx.min(lines.len() - 1)]
+                )));
+            }
+            let tle = Self::parse(lines[idx], lines[idx + 1])?;
+            tles.push((
+                name.unwrap_or_else(|| format!("{:05}", tle.catalog_number)),
+                tle,
+            ));
+            idx += 2;
+        }
+        Ok(tles)
+    }
+
+    /// Returns the two lines of this TLE, with their checksums
+    pub fn lines(&self) -> [String; 2] {
+        let utc_year = self.epoch.to_gregorian_utc().0;
+        let day_of_year = (self.epoch - Epoch::from_gregorian_utc_at_midnight(utc_year, 1, 1))
+            .to_unit(Unit::Day)
+            + 1.0;
+
+        let ndot = format!("{:.8}", self.mean_motion_dot.abs());
+        let line1 = format!(
+            "1 {:05}{} {:<8} {:02}{:012.8} {}{} {} {} 0 {:>4}",
+            self.catalog_number % 100_000,
+            self.classification,
+            self.intl_designator,
+            utc_year % 100,
+            day_of_year,
+            if self.mean_motion_dot < 0.0 { '-' } else { ' ' },
+            ndot.trim_start_matches('0'),
+            format_exponent(self.mean_motion_ddot),
+            format_exponent(self.bstar),
+            self.element_set_number % 10_000
+        );
+        let line2 = format!(
+            "2 {:05} {:8.4} {:8.4} {:07} {:8.4} {:8.4} {:11.8}{:>5}",
+            self.catalog_number % 100_000,
+            self.inc_deg,
+            self.raan_deg.rem_euclid(360.0),
+            (self.ecc * 1e7).round() as u32,
+            self.aop_deg.rem_euclid(360.0),
+            self.ma_deg.rem_euclid(360.0),
+            self.mean_motion_rev_day,
+            self.rev_number % 100_000
+        );
+
+        [line1, line2].map(|line| format!("{line}{}", checksum(&line)))
+    }
+
+    /// Returns the position (km) and velocity (km/s) of this TLE at the provided epoch in the TEME frame, as computed by SGP4
+    pub fn teme_at(&self, epoch: Epoch) -> Result<Vector6<f64>, NyxError> {
+        Sgp4::new(self)?.propagate((epoch - self.epoch).to_unit(Unit::Minute))
+    }
+
+    /// Returns the state of this TLE at the provided epoch in the EME2000 frame of the provided Cosm, as computed by SGP4
+    pub fn at(&self, epoch: Epoch, cosm: &Cosm) -> Result<Orbit, NyxError> {
+        let teme = self.teme_at(epoch)?;
+        let dcm = teme_to_eme2000(epoch);
+        let radius = dcm * teme.fixed_rows::<3>(0);
+        let velocity = dcm * teme.fixed_rows::<3>(3);
+        Ok(Orbit::cartesian(
+            radius[0],
+            radius[1],
+            radius[2],
+            velocity[0],
+            velocity[1],
+            velocity[2],
+            epoch,
+            cosm.frame("EME2000"),
+        ))
+    }
+
+    /// Fits the mean elements of a TLE to the trajectory between the start and end epochs, sampled with the provided step,
+    /// such that the SGP4 propagation of this TLE is as close as possible (in the least squares sense) to the trajectory.
+    ///
+    /// The epoch of the fitted TLE is the start epoch, and its drag term is only fitted if `fit_bstar` is set (it is otherwise zero).
+    /// The identification fields of the TLE (catalog number, international designator, etc.) should be set by the caller.
+    pub fn fit<S: Interpolatable>(
+        traj: &Traj<S>,
+        start: Epoch,
+        end: Epoch,
+        step: Duration,
+        fit_bstar: bool,
+        cosm: Arc<Cosm>,
+    ) -> Result<TleFit, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        if end <= start {
+            return Err(NyxError::CustomError(format!(
+                "the TLE fit span must end after it starts ({start} - {end})"
+            )));
+        }
+
+        let eme2k = cosm.frame("EME2000");
+        // Observed positions in the TEME frame of each sample
+        let mut observations = Vec::new();
+        for epoch in TimeSeries::inclusive(start, end, step) {
+            let orbit = cosm.frame_chg(traj.at(epoch)?.orbit(), eme2k);
+            let dcm = teme_to_eme2000(epoch).transpose();
+            observations.push((
+                (epoch - start).to_unit(Unit::Minute),
+                dcm * orbit.radius(),
+                dcm * orbit.velocity(),
+            ));
+        }
+        if observations.len() < 4 {
+            return Err(NyxError::CustomError(format!(
+                "at least four samples are needed to fit a TLE, but the step of {step} only provides {}",
+                observations.len()
+            )));
+        }
+
+        // Initial guess from the osculating elements in TEME of the first sample
+        let (_, r0, v0) = observations[0];
+        let osc = Orbit::cartesian(r0[0], r0[1], r0[2], v0[0], v0[1], v0[2], start, eme2k);
+        let mut tle = Self {
+            epoch: start,
+            ..Default::default()
+        };
+        let mean_motion_rad_min = (MU_WGS72 / osc.sma_km().powi(3)).sqrt() * 60.0;
+        let aop_rad = osc.aop_deg().to_radians();
+        let mut params = vec![
+            mean_motion_rad_min,
+            osc.ecc() * aop_rad.cos(),
+            osc.ecc() * aop_rad.sin(),
+            osc.inc_deg().to_radians(),
+            osc.raan_deg().to_radians(),
+            (osc.ma_deg() + osc.aop_deg()).to_radians(),
+        ];
+        if fit_bstar {
+            params.push(0.0);
+        }
+        let mut x = DVector::from_vec(params);
+
+        let residuals = |x: &DVector<f64>, tle: &mut Self| -> Result<DVector<f64>, NyxError> {
+            tle.set_fit_params(x);
+            let sgp4 = Sgp4::new(tle)?;
+            let mut res = DVector::zeros(3 * observations.len());
+            for (i, (tsince, radius, _)) in observations.iter().enumerate() {
+                let state = sgp4.propagate(*tsince)?;
+                res.fixed_rows_mut::<3>(3 * i)
+                    .copy_from(&(radius - state.fixed_rows::<3>(0)));
+            }
+            Ok(res)
+        };
+
+        // Levenberg-Marquardt iterations with a finite differenced Jacobian
+        let steps = [1e-9, 1e-7, 1e-7, 1e-7, 1e-7, 1e-7, 1e-7];
+        let mut res = residuals(&x, &mut tle)?;
+        let mut cost = res.norm_squared();
+        let mut damping = 1e-3;
+        let mut iterations = 0;
+        while iterations < 50 {
+            iterations += 1;
+            // Jacobian of the SGP4 positions with respect to the fit parameters
+            let mut jac = DMatrix::zeros(res.len(), x.len());
+            for j in 0..x.len() {
+                let mut pert = x.clone();
+                pert[j] += steps[j];
+                let pert_res = residuals(&pert, &mut tle)?;
+                jac.set_column(j, &((&res - pert_res) / steps[j]));
+            }
+
+            let jtj = jac.transpose() * &jac;
+            let jtr = jac.transpose() * &res;
+            let mut improved = false;
+            while damping < 1e12 {
+                let mut lhs = jtj.clone();
+                for j in 0..x.len() {
+                    lhs[(j, j)] *= 1.0 + damping;
+                }
+                let dx = match lhs.cholesky() {
+                    Some(chol) => chol.solve(&jtr),
+                    None => {
+                        damping *= 10.0;
+                        continue;
+                    }
+                };
+                let candidate = &x + &dx;
+                if let Ok(cand_res) = residuals(&candidate, &mut tle) {
+                    let cand_cost = cand_res.norm_squared();
+                    if cand_cost < cost {
+                        let rel_change = (cost - cand_cost) / cost;
+                        x = candidate;
+                        res = cand_res;
+                        cost = cand_cost;
+                        damping = (damping / 10.0).max(1e-12);
+                        improved = rel_change > 1e-10;
+                        break;
+                    }
+                }
+                damping *= 10.0;
+            }
+            if !improved {
+                break;
+            }
+        }
+
+        tle.set_fit_params(&x);
+        let errors_km: Vec<f64> = res
+            .as_slice()
+            .chunks(3)
+            .map(|err| Vector3::from_column_slice(err).norm())
+            .collect();
+        let rms_km = (errors_km.iter().map(|e| e * e).sum::<f64>() / errors_km.len() as f64).sqrt();
+        let max_km = errors_km.iter().cloned().fold(0.0, f64::max);
+        debug!(
+            "TLE fit converged in {iterations} iterations: RMS {rms_km:.3} km, max {max_km:.3} km"
+        );
+
+        Ok(TleFit {
+            tle,
+            rms_km,
+            max_km,
+            iterations,
+        })
+    }
+
+    /// Sets the mean elements from the fit parameters: Kozai mean motion (rad/min), equinoctial eccentricity components,
+    /// inclination, RAAN, mean argument of latitude (rad), and drag term if fitted.
+    fn set_fit_params(&mut self, x: &DVector<f64>) {
+        self.mean_motion_rev_day = x[0] * MINUTES_PER_DAY / TAU;
+        self.ecc = x[1].hypot(x[2]);
+        let aop_rad = x[2].atan2(x[1]);
+        self.aop_deg = aop_rad.to_degrees().rem_euclid(360.0);
+        self.inc_deg = x[3].to_degrees();
+        self.raan_deg = x[4].to_degrees().rem_euclid(360.0);
+        self.ma_deg = (x[5] - aop_rad).to_degrees().rem_euclid(360.0);
+        if x.len() > 6 {
+            self.bstar = x[6];
+        }
+    }
+}
+
+impl Default for Tle {
+    fn default() -> Self {
+        Self {
+            catalog_number: 0,
+            classification: 'U',
+            intl_designator: String::new(),
+            epoch: Epoch::from_gregorian_utc_at_midnight(2000, 1, 1),
+            mean_motion_dot: 0.0,
+            mean_motion_ddot: 0.0,
+            bstar: 0.0,
+            element_set_number: 999,
+            inc_deg: 0.0,
+            raan_deg: 0.0,
+            ecc: 0.0,
+            aop_deg: 0.0,
+            ma_deg: 0.0,
+            mean_motion_rev_day: 0.0,
+            rev_number: 0,
+        }
+    }
+}
+
+impl fmt::Display for Tle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [line1, line2] = self.lines();
+        write!(f, "{line1}\n{line2}")
+    }
+}
+
+/// The result of fitting a TLE to a trajectory
+#[derive(Clone, Debug)]
+pub struct TleFit {
+    pub tle: Tle,
+    /// RMS of the position differences between the SGP4 propagation of the TLE and the trajectory samples
+    pub rms_km: f64,
+    /// Largest position difference between the SGP4 propagation of the TLE and the trajectory samples
+    pub max_km: f64,
+    pub iterations: usize,
+}
+
+impl fmt::Display for TleFit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}\nfit RMS: {:.3} km\tmax: {:.3} km\t({} iterations)",
+            self.tle, self.rms_km, self.max_km, self.iterations
+        )
+    }
+}
+
+/// Rotation from the TEME frame of the provided epoch to EME2000, using the IAU-76 precession and the main terms of the IAU-80 nutation
+pub fn teme_to_eme2000(epoch: Epoch) -> Matrix3<f64> {
+    let arcsec = PI / (180.0 * 3600.0);
+    let t = epoch.to_tt_centuries_j2k();
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    // IAU-76 precession
+    let zeta = (2306.2181 * t + 0.30188 * t2 + 0.017998 * t3) * arcsec;
+    let theta = (2004.3109 * t - 0.42665 * t2 - 0.041833 * t3) * arcsec;
+    let z = (2306.2181 * t + 1.09468 * t2 + 0.018203 * t3) * arcsec;
+    let precession = r3(zeta) * r2(-theta) * r3(z);
+
+    // Largest terms of the IAU-80 nutation, accurate to about one milliarcsecond
+    let l = (134.963_402_51 + 477_198.867_560_5 * t).to_radians();
+    let lp = (357.529_109_18 + 35_999.050_291_1 * t).to_radians();
+    let f = (93.272_090_62 + 483_202.017_457_7 * t).to_radians();
+    let d = (297.850_195_47 + 445_267.111_446_9 * t).to_radians();
+    let om = (125.044_555_01 - 1_934.136_261_9 * t).to_radians();
+    // Multipliers of (l, l', F, D, Ω), then the coefficients of Δψ and Δε, in arcseconds
+    let terms: [([f64; 5], f64, f64, f64, f64); 10] = [
+        (
+            [0.0, 0.0, 0.0, 0.0, 1.0],
+            -17.1996,
+            -0.01742,
+            9.2025,
+            0.00089,
+        ),
+        (
+            [0.0, 0.0, 2.0, -2.0, 2.0],
+            -1.3187,
+            -0.00016,
+            0.5736,
+            -0.00031,
+        ),
+        (
+            [0.0, 0.0, 2.0, 0.0, 2.0],
+            -0.2274,
+            -0.00002,
+            0.0977,
+            -0.00005,
+        ),
+        ([0.0, 0.0, 0.0, 0.0, 2.0], 0.2062, 0.00002, -0.0895, 0.00005),
+        (
+            [0.0, 1.0, 0.0, 0.0, 0.0],
+            0.1426,
+            -0.00034,
+            0.0054,
+            -0.00001,
+        ),
+        ([1.0, 0.0, 0.0, 0.0, 0.0], 0.0712, 0.00001, -0.0007, 0.0),
+        (
+            [0.0, 1.0, 2.0, -2.0, 2.0],
+            -0.0517,
+            0.00012,
+            0.0224,
+            -0.00006,
+        ),
+        ([0.0, 0.0, 2.0, 0.0, 1.0], -0.0386, -0.00004, 0.0200, 0.0),
+        ([1.0, 0.0, 2.0, 0.0, 2.0], -0.0301, 0.0, 0.0129, -0.00001),
+        (
+            [0.0, -1.0, 2.0, -2.0, 2.0],
+            0.0217,
+            -0.00005,
+            -0.0095,
+            0.00003,
+        ),
+    ];
+    let (mut dpsi, mut deps) = (0.0, 0.0);
+    for (mult, psi, psi_t, eps, eps_t) in terms {
+        let arg = mult[0] * l + mult[1] * lp + mult[2] * f + mult[3] * d + mult[4] * om;
+        dpsi += (psi + psi_t * t) * arg.sin();
+        deps += (eps + eps_t * t) * arg.cos();
+    }
+    let dpsi = dpsi * arcsec;
+    let deps = deps * arcsec;
+    let mean_obliquity = (84_381.448 - 46.815 * t - 0.00059 * t2 + 0.001813 * t3) * arcsec;
+    let nutation = r1(-mean_obliquity) * r3(dpsi) * r1(mean_obliquity + deps);
+
+    // TEME to true of date with the equation of the equinoxes
+    let eq_equinoxes = dpsi * mean_obliquity.cos();
+
+    precession * nutation * r3(-eq_equinoxes)
+}
+
+fn checksum(line: &str) -> u32 {
+    line.chars()
+        .map(|c| match c {
+            '-' => 1,
+            _ => c.to_digit(10).unwrap_or(0),
+        })
+        .sum::<u32>()
+        % 10
+}
+
+fn parse_field<T: std::str::FromStr>(
+    line: &str,
+    start: usize,
+    end: usize,
+    name: &str,
+) -> Result<T, NyxError> {
+    line[start..end]
+        .trim()
+        .parse()
+        .map_err(|_| NyxError::CustomError(format!("invalid TLE {name} `{}`", &line[start..end])))
+}
+
+/// Parses a field with an implied leading decimal point and exponent, e.g. ` 28098-4` is 0.28098e-4
+fn parse_exponent(field: &str) -> Result<f64, NyxError> {
+    let field = field.trim();
+    let err = || NyxError::CustomError(format!("invalid TLE exponent field `{field}`"));
+    let (sign, digits) = match field.strip_prefix('-') {
+        Some(digits) => (-1.0, digits),
+        None => (1.0, field.trim_start_matches('+')),
+    };
+    let split = digits.rfind(['-', '+']).ok_or_else(err)?;
+    let mantissa: f64 = format!("0.{}", &digits[..split])
+        .parse()
+        .map_err(|_| err())?;
+    let exponent: i32 = digits[split..].parse().map_err(|_| err())?;
+    Ok(sign * mantissa * 10_f64.powi(exponent))
+}
+
+/// Formats a value with an implied leading decimal point and exponent, e.g. 0.28098e-4 is ` 28098-4`
+fn format_exponent(value: f64) -> String {
+    if value == 0.0 {
+        return " 00000-0".to_string();
+    }
+    let mut exponent = value.abs().log10().floor() as i32 + 1;
+    let mut mantissa = (value.abs() / 10_f64.powi(exponent) * 1e5).round() as u32;
+    if mantissa >= 100_000 {
+        mantissa /= 10;
+        exponent += 1;
+    }
+    format!(
+        "{}{mantissa:05}{}{}",
+        if value < 0.0 { '-' } else { ' ' },
+        if exponent < 0 { '-' } else { '+' },
+        exponent.abs().min(9)
+    )
+}
+
+/// Near Earth SGP4 propagator, following Vallado et al. (2006), "Revisiting Spacetrack Report #3", AIAA 2006-6753
+struct Sgp4 {
+    xke: f64,
+    bstar: f64,
+    ecco: f64,
+    inclo: f64,
+    nodeo: f64,
+    argpo: f64,
+    mo: f64,
+    no: f64,
+    isimp: bool,
+    aycof: f64,
+    con41: f64,
+    cc1: f64,
+    cc4: f64,
+    cc5: f64,
+    d2: f64,
+    d3: f64,
+    d4: f64,
+    delmo: f64,
+    eta: f64,
+    argpdot: f64,
+    omgcof: f64,
+    sinmao: f64,
+    t2cof: f64,
+    t3cof: f64,
+    t4cof: f64,
+    t5cof: f64,
+    x1mth2: f64,
+    x7thm1: f64,
+    mdot: f64,
+    nodedot: f64,
+    xlcof: f64,
+    xmcof: f64,
+    nodecf: f64,
+}
+
+impl Sgp4 {
+    fn new(tle: &Tle) -> Result<Self, NyxError> {
+        let xke = 60.0 / (RADIUS_WGS72_KM.powi(3) / MU_WGS72).sqrt();
+        let j3oj2 = J3 / J2;
+        let x2o3 = 2.0 / 3.0;
+
+        let ecco = tle.ecc;
+        let inclo = tle.inc_deg.to_radians();
+        let argpo = tle.aop_deg.to_radians();
+        let mo = tle.ma_deg.to_radians();
+        let no_kozai = tle.mean_motion_rev_day * TAU / MINUTES_PER_DAY;
+        if !(0.0..1.0).contains(&ecco) || no_kozai <= 0.0 {
+            return Err(NyxError::CustomError(format!(
+                "invalid TLE mean elements (ecc = {ecco}, n = {} rev/day)",
+                tle.mean_motion_rev_day
+            )));
+        }
+
+        // Recover the Brouwer mean motion from the Kozai mean motion
+        let eccsq = ecco * ecco;
+        let omeosq = 1.0 - eccsq;
+        let rteosq = omeosq.sqrt();
+        let cosio = inclo.cos();
+        let cosio2 = cosio * cosio;
+        let ak = (xke / no_kozai).powf(x2o3);
+        let d1 = 0.75 * J2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+        let del = d1 / (ak * ak);
+        let adel = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+        let del = d1 / (adel * adel);
+        let no = no_kozai / (1.0 + del);
+
+        if TAU / no >= DEEP_SPACE_PERIOD_MIN {
+            return Err(NyxError::CustomError(format!(
+                "TLE period of {:.1} min requires SDP4 (deep space), which is not supported",
+                TAU / no
+            )));
+        }
+
+        let ao = (xke / no).powf(x2o3);
+        let sinio = inclo.sin();
+        let po = ao * omeosq;
+        let con42 = 1.0 - 5.0 * cosio2;
+        let con41 = -con42 - cosio2 - cosio2;
+        let posq = po * po;
+        let rp = ao * (1.0 - ecco);
+
+        // Simplified drag model for perigees under 220 km
+        let isimp = rp < 220.0 / RADIUS_WGS72_KM + 1.0;
+        let mut sfour = 78.0 / RADIUS_WGS72_KM + 1.0;
+        let mut qzms24 = ((120.0 - 78.0) / RADIUS_WGS72_KM).powi(4);
+        let perige = (rp - 1.0) * RADIUS_WGS72_KM;
+        if perige < 156.0 {
+            sfour = if perige < 98.0 { 20.0 } else { perige - 78.0 };
+            qzms24 = ((120.0 - sfour) / RADIUS_WGS72_KM).powi(4);
+            sfour = sfour / RADIUS_WGS72_KM + 1.0;
+        }
+
+        let pinvsq = 1.0 / posq;
+        let tsi = 1.0 / (ao - sfour);
+        let eta = ao * ecco * tsi;
+        let etasq = eta * eta;
+        let eeta = ecco * eta;
+        let psisq = (1.0 - etasq).abs();
+        let coef = qzms24 * tsi.powi(4);
+        let coef1 = coef / psisq.powf(3.5);
+        let cc2 = coef1
+            * no
+            * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+                + 0.375 * J2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+        let bstar = tle.bstar;
+        let cc1 = bstar * cc2;
+        let cc3 = if ecco > 1.0e-4 {
+            -2.0 * coef * tsi * j3oj2 * no * sinio / ecco
+        } else {
+            0.0
+        };
+        let x1mth2 = 1.0 - cosio2;
+        let cc4 = 2.0
+            * no
+            * coef1
+            * ao
+            * omeosq
+            * (eta * (2.0 + 0.5 * etasq) + ecco * (0.5 + 2.0 * etasq)
+                - J2 * tsi / (ao * psisq)
+                    * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+                        + 0.75
+                            * x1mth2
+                            * (2.0 * etasq - eeta * (1.0 + etasq))
+                            * (2.0 * argpo).cos()));
+        let cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+
+        // Secular rates
+        let cosio4 = cosio2 * cosio2;
+        let temp1 = 1.5 * J2 * pinvsq * no;
+        let temp2 = 0.5 * temp1 * J2 * pinvsq;
+        let temp3 = -0.46875 * J4 * pinvsq * pinvsq * no;
+        let mdot = no
+            + 0.5 * temp1 * rteosq * con41
+            + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+        let argpdot = -0.5 * temp1 * con42
+            + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+            + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+        let xhdot1 = -temp1 * cosio;
+        let nodedot = xhdot1
+            + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2)) * cosio;
+
+        let omgcof = bstar * cc3 * argpo.cos();
+        let xmcof = if ecco > 1.0e-4 {
+            -x2o3 * coef * bstar / eeta
+        } else {
+            0.0
+        };
+        let nodecf = 3.5 * omeosq * xhdot1 * cc1;
+        let t2cof = 1.5 * cc1;
+        let xlcof_den = if (cosio + 1.0).abs() > 1.5e-12 {
+            1.0 + cosio
+        } else {
+            1.5e-12
+        };
+        let xlcof = -0.25 * j3oj2 * sinio * (3.0 + 5.0 * cosio) / xlcof_den;
+        let aycof = -0.5 * j3oj2 * sinio;
+        let delmo = (1.0 + eta * mo.cos()).powi(3);
+
+        let (mut d2, mut d3, mut d4) = (0.0, 0.0, 0.0);
+        let (mut t3cof, mut t4cof, mut t5cof) = (0.0, 0.0, 0.0);
+        if !isimp {
+            let cc1sq = cc1 * cc1;
+            d2 = 4.0 * ao * tsi * cc1sq;
+            let temp = d2 * tsi * cc1 / 3.0;
+            d3 = (17.0 * ao + sfour) * temp;
+            d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * cc1;
+            t3cof = d2 + 2.0 * cc1sq;
+            t4cof = 0.25 * (3.0 * d3 + cc1 * (12.0 * d2 + 10.0 * cc1sq));
+            t5cof = 0.2
+                * (3.0 * d4 + 12.0 * cc1 * d3 + 6.0 * d2 * d2 + 15.0 * cc1sq * (2.0 * d2 + cc1sq));
+        }
+
+        Ok(Self {
+            xke,
+            bstar,
+            ecco,
+            inclo,
+            nodeo: tle.raan_deg.to_radians(),
+            argpo,
+            mo,
+            no,
+            isimp,
+            aycof,
+            con41,
+            cc1,
+            cc4,
+            cc5,
+            d2,
+            d3,
+            d4,
+            delmo,
+            eta,
+            argpdot,
+            omgcof,
+            sinmao: mo.sin(),
+            t2cof,
+            t3cof,
+            t4cof,
+            t5cof,
+            x1mth2,
+            x7thm1: 7.0 * cosio2 - 1.0,
+            mdot,
+            nodedot,
+            xlcof,
+            xmcof,
+            nodecf,
+        })
+    }
+
+    /// Returns the TEME position (km) and velocity (km/s) at the provided time since the epoch of the TLE, in minutes
+    fn propagate(&self, tsince: f64) -> Result<Vector6<f64>, NyxError> {
+        // Secular gravity and atmospheric drag
+        let xmdf = self.mo + self.mdot * tsince;
+        let argpdf = self.argpo + self.argpdot * tsince;
+        let nodedf = self.nodeo + self.nodedot * tsince;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let t2 = tsince * tsince;
+        let mut nodem = nodedf + self.nodecf * t2;
+        let mut tempa = 1.0 - self.cc1 * tsince;
+        let mut tempe = self.bstar * self.cc4 * tsince;
+        let mut templ = self.t2cof * t2;
+
+        if !self.isimp {
+            let delomg = self.omgcof * tsince;
+            let delm = self.xmcof * ((1.0 + self.eta * xmdf.cos()).powi(3) - self.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * tsince;
+            let t4 = t3 * tsince;
+            tempa -= self.d2 * t2 + self.d3 * t3 + self.d4 * t4;
+            tempe += self.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ += self.t3cof * t3 + t4 * (self.t4cof + tsince * self.t5cof);
+        }
+
+        let am = (self.xke / self.no).powf(2.0 / 3.0) * tempa * tempa;
+        let nm = self.xke / am.powf(1.5);
+        let mut em = self.ecco - tempe;
+        if !(-0.001..1.0).contains(&em) {
+            return Err(NyxError::CustomError(format!(
+                "SGP4 mean eccentricity out of bounds ({em}) at {tsince} min"
+            )));
+        }
+        em = em.max(1.0e-6);
+        mm += self.no * templ;
+        let xlm = mm + argpm + nodem;
+        nodem %= TAU;
+        argpm %= TAU;
+        let xlm = xlm % TAU;
+        mm = (xlm - argpm - nodem) % TAU;
+
+        let sinip = self.inclo.sin();
+        let cosip = self.inclo.cos();
+
+        // Long period periodics
+        let axnl = em * argpm.cos();
+        let temp = 1.0 / (am * (1.0 - em * em));
+        let aynl = em * argpm.sin() + temp * self.aycof;
+        let xl = mm + argpm + nodem + temp * self.xlcof * axnl;
+
+        // Kepler's equation
+        let u = (xl - nodem) % TAU;
+        let mut eo1 = u;
+        let (mut sineo1, mut coseo1) = eo1.sin_cos();
+        for _ in 0..10 {
+            (sineo1, coseo1) = eo1.sin_cos();
+            let mut tem5 =
+                (u - aynl * coseo1 + axnl * sineo1 - eo1) / (1.0 - coseo1 * axnl - sineo1 * aynl);
+            if tem5.abs() >= 0.95 {
+                tem5 = 0.95_f64.copysign(tem5);
+            }
+            eo1 += tem5;
+            if tem5.abs() < 1.0e-12 {
+                break;
+            }
+        }
+
+        // Short period periodics
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        if pl < 0.0 {
+            return Err(NyxError::CustomError(format!(
+                "SGP4 semi-latus rectum is negative at {tsince} min"
+            )));
+        }
+        let rl = am * (1.0 - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let mut su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * J2 * temp;
+        let temp2 = temp1 * temp;
+
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * self.con41) + 0.5 * temp1 * self.x1mth2 * cos2u;
+        su -= 0.25 * temp2 * self.x7thm1 * sin2u;
+        let xnode = nodem + 1.5 * temp2 * cosip * sin2u;
+        let xinc = self.inclo + 1.5 * temp2 * cosip * sinip * cos2u;
+        let mvt = rdotl - nm * temp1 * self.x1mth2 * sin2u / self.xke;
+        let rvdot = rvdotl + nm * temp1 * (self.x1mth2 * cos2u + 1.5 * self.con41) / self.xke;
+
+        if mrt < 1.0 {
+            return Err(NyxError::CustomError(format!(
+                "SGP4 orbit decayed at {tsince} min"
+            )));
+        }
+
+        // Orientation vectors
+        let (sinsu, cossu) = su.sin_cos();
+        let (snod, cnod) = xnode.sin_cos();
+        let (sini, cosi) = xinc.sin_cos();
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let u_vec = Vector3::new(
+            xmx * sinsu + cnod * cossu,
+            xmy * sinsu + snod * cossu,
+            sini * sinsu,
+        );
+        let v_vec = Vector3::new(
+            xmx * cossu - cnod * sinsu,
+            xmy * cossu - snod * sinsu,
+            sini * cossu,
+        );
+
+        let vkmpersec = RADIUS_WGS72_KM * self.xke / 60.0;
+        let radius = mrt * RADIUS_WGS72_KM * u_vec;
+        let velocity = vkmpersec * (mvt * u_vec + rvdot * v_vec);
+        Ok(Vector6::new(
+            radius[0],
+            radius[1],
+            radius[2],
+            velocity[0],
+            velocity[1],
+            velocity[2],
+        ))
+    }
+}
+
+#[test]
+fn test_tle_exponent_fields() {
+    assert!((parse_exponent(" 28098-4").unwrap() - 0.28098e-4).abs() < 1e-15);
+    assert!((parse_exponent("-11606-4").unwrap() + 0.11606e-4).abs() < 1e-15);
+    assert_eq!(parse_exponent(" 00000-0").unwrap(), 0.0);
+    assert_eq!(format_exponent(0.28098e-4), " 28098-4");
+    assert_eq!(format_exponent(-0.11606e-4), "-11606-4");
+    assert_eq!(format_exponent(0.0), " 00000-0");
+}
@@ -0,0 +1,112 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{ForceModel, ForceProfile, ProfileKind};
+use crate::cosmic::{Frame, Spacecraft};
+use crate::errors::NyxError;
+use crate::linalg::{Matrix3, Vector3};
+use crate::time::Epoch;
+use crate::State;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// `Accelerometer` applies the non-gravitational accelerations measured by an accelerometer, corrected by its calibration.
+///
+/// The corrected acceleration is `scale ⊙ measured + bias` in the sensor frame, which is either the integration frame or a local
+/// frame of the trajectory. This replaces the drag, SRP and thrust models of drag-free or heavily maneuvering vehicles.
+/// The calibration may be estimated with `od::AccelCalibrationEstimator`.
+#[derive(Clone, Debug)]
+pub struct Accelerometer {
+    /// Telemetry of the measured accelerations, in km/s^2
+    pub measurements: Arc<ForceProfile>,
+    /// Bias of each axis, in km/s^2
+    pub bias: Vector3<f64>,
+    /// Scale factor of each axis, one if perfectly calibrated
+    pub scale: Vector3<f64>,
+}
+
+impl Accelerometer {
+    /// Initializes an uncalibrated accelerometer from the measured accelerations (in km/s^2), in chronological order.
+    pub fn from_samples(
+        samples: Vec<(Epoch, Vector3<f64>)>,
+        frame: Frame,
+    ) -> Result<Self, NyxError> {
+        Ok(Self {
+            measurements: ForceProfile::from_samples(samples, ProfileKind::Acceleration, frame)?,
+            bias: Vector3::zeros(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        })
+    }
+
+    /// Loads the accelerometer telemetry from a CSV file with the columns `epoch,x,y,z`, in km/s^2.
+    pub fn from_csv<P: AsRef<Path>>(path: P, frame: Frame) -> Result<Self, NyxError> {
+        Ok(Self {
+            measurements: ForceProfile::from_csv(path, ProfileKind::Acceleration, frame)?,
+            bias: Vector3::zeros(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        })
+    }
+
+    /// Returns a copy of this accelerometer with the provided calibration
+    pub fn with_calibration(&self, bias: Vector3<f64>, scale: Vector3<f64>) -> Self {
+        Self {
+            measurements: self.measurements.clone(),
+            bias,
+            scale,
+        }
+    }
+
+    /// Returns the calibrated acceleration at the provided epoch in the sensor frame, in km/s^2.
+    /// Outside of the span of the telemetry, no acceleration is applied (not even the bias).
+    pub fn acceleration(&self, epoch: Epoch) -> Vector3<f64> {
+        let (start, end) = self.measurements.span();
+        if epoch < start || epoch > end {
+            Vector3::zeros()
+        } else {
+            self.scale.component_mul(&self.measurements.at(epoch)) + self.bias
+        }
+    }
+}
+
+impl fmt::Display for Accelerometer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "\tAccelerometer with bias = {:e} km/s^2 and scale = {}{}",
+            self.bias, self.scale, self.measurements
+        )
+    }
+}
+
+impl ForceModel for Accelerometer {
+    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        let accel = self.acceleration(ctx.epoch());
+        let accel_inertial = if matches!(self.measurements.frame, Frame::Inertial) {
+            accel
+        } else {
+            ctx.orbit.dcm_from_traj_frame(self.measurements.frame)? * accel
+        };
+        // The spacecraft dynamics divide the force by the mass
+        Ok(accel_inertial * ctx.mass_kg())
+    }
+
+    fn dual_eom(&self, ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+        Ok((self.eom(ctx)?, Matrix3::zeros()))
+    }
+}
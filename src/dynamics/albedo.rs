@@ -0,0 +1,344 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{ForceModel, PlateModel};
+use crate::cosmic::{Bodies, Cosm, Frame, LightTimeCalc, Orbit, Spacecraft, AU, SPEED_OF_LIGHT};
+use crate::errors::NyxError;
+use crate::linalg::{Matrix3, Matrix3x2, Vector3};
+use crate::time::{Epoch, Unit};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{PI, TAU};
+use std::fmt;
+use std::sync::Arc;
+
+/// Distribution of the albedo and of the emissivity over the surface of the body, as functions of the latitude.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum RadiationDistribution {
+    /// Same albedo and emissivity everywhere
+    Uniform { albedo: f64, emissivity: f64 },
+    /// Second degree zonal model of the Earth with a seasonal first degree term, from Knocke, Ries and Tapley (1988),
+    /// "Earth radiation pressure effects on satellites".
+    #[default]
+    Knocke,
+}
+
+impl RadiationDistribution {
+    /// Returns the seasonal angle of the Knocke model, whose reference epoch is the winter solstice of 1981
+    fn seasonal_angle(epoch: Epoch) -> f64 {
+        let reference = Epoch::from_gregorian_utc_at_midnight(1981, 12, 22);
+        TAU * ((epoch - reference).to_unit(Unit::Day) / 365.25)
+    }
+
+    /// Returns the albedo at the provided latitude (rad) and epoch
+    pub fn albedo(&self, latitude_rad: f64, epoch: Epoch) -> f64 {
+        match self {
+            Self::Uniform { albedo, .. } => *albedo,
+            Self::Knocke => {
+                let sin_lat = latitude_rad.sin();
+                let a1 = 0.1 * Self::seasonal_angle(epoch).cos();
+                0.34 + a1 * sin_lat + 0.29 * legendre_p2(sin_lat)
+            }
+        }
+    }
+
+    /// Returns the emissivity at the provided latitude (rad) and epoch
+    pub fn emissivity(&self, latitude_rad: f64, epoch: Epoch) -> f64 {
+        match self {
+            Self::Uniform { emissivity, .. } => *emissivity,
+            Self::Knocke => {
+                let sin_lat = latitude_rad.sin();
+                let e1 = -0.07 * Self::seasonal_angle(epoch).cos();
+                0.68 + e1 * sin_lat - 0.18 * legendre_p2(sin_lat)
+            }
+        }
+    }
+}
+
+fn legendre_p2(x: f64) -> f64 {
+    0.5 * (3.0 * x.powi(2) - 1.0)
+}
+
+/// Computation of the irradiance from the body over the part of its surface visible from the spacecraft.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RadiationGrid {
+    /// Lambertian sphere seen from afar, whose radiation comes from the center of the body, with the albedo and emissivity under
+    /// the spacecraft. The infrared is exact for a uniform emissivity, but the albedo is only accurate far from the body.
+    Analytic,
+    /// Surface elements of equal area over the cap visible from the spacecraft: a central element and rings of 6, 12, ...
+    /// elements, e.g. two rings for the 19 elements of Knocke et al.
+    Rings(usize),
+}
+
+impl Default for RadiationGrid {
+    /// The 19 elements of Knocke et al.
+    fn default() -> Self {
+        Self::Rings(2)
+    }
+}
+
+/// Radiation pressure from the sunlight reflected by a body (albedo) and from its thermal infrared emission, computed with
+/// the same cannonball model as the solar radiation pressure.
+#[derive(Clone)]
+pub struct PlanetaryRadiation {
+    /// solar flux at 1 AU, in W/m^2
+    pub phi: f64,
+    /// Inertial frame centered on the radiating body, whose Z axis is used for the latitude of the distribution. As for the SRP,
+    /// its axes must be those of the integration frame.
+    pub body_frame: Frame,
+    pub distribution: RadiationDistribution,
+    pub grid: RadiationGrid,
+    /// Set to false to ignore the albedo
+    pub albedo: bool,
+    /// Set to false to ignore the infrared emission
+    pub infrared: bool,
+    pub cosm: Arc<Cosm>,
+    /// Plates of the spacecraft, used instead of its SRP area and coefficient of reflectivity if set
+    pub plates: Option<Arc<PlateModel>>,
+}
+
+impl PlanetaryRadiation {
+    /// Initializes the albedo and infrared radiation of the body of the provided frame, with a solar flux at 1 AU of 1367 W/m^2
+    pub fn from_distribution(
+        body_frame: Frame,
+        distribution: RadiationDistribution,
+        grid: RadiationGrid,
+        cosm: Arc<Cosm>,
+    ) -> Self {
+        Self {
+            phi: 1367.0,
+            body_frame,
+            distribution,
+            grid,
+            albedo: true,
+            infrared: true,
+            cosm,
+            plates: None,
+        }
+    }
+
+    /// Computes the radiation pressure on the provided plates instead of the cannonball
+    pub fn with_plates(mut self, plates: Arc<PlateModel>) -> Self {
+        self.plates = Some(plates);
+        self
+    }
+
+    /// Albedo and infrared radiation of the Earth from the Knocke model over 19 surface elements
+    pub fn earth_raw(cosm: Arc<Cosm>) -> Self {
+        Self::from_distribution(
+            cosm.frame("EME2000"),
+            RadiationDistribution::Knocke,
+            RadiationGrid::Rings(2),
+            cosm,
+        )
+    }
+
+    /// Albedo and infrared radiation of the Earth from the Knocke model over 19 surface elements
+    pub fn earth(cosm: Arc<Cosm>) -> Arc<Self> {
+        Arc::new(Self::earth_raw(cosm))
+    }
+
+    /// Returns the irradiance vectors (W/m^2) of the albedo and of the infrared emission at the provided state, each along the
+    /// direction of its radiation pressure.
+    pub fn irradiance(&self, orbit: &Orbit) -> Result<(Vector3<f64>, Vector3<f64>), NyxError> {
+        let (_, sources) = self.sources(orbit)?;
+        let mut albedo = Vector3::zeros();
+        let mut infrared = Vector3::zeros();
+        for (incoming, albedo_w_m2, infrared_w_m2) in sources {
+            albedo += incoming * albedo_w_m2;
+            infrared += incoming * infrared_w_m2;
+        }
+        Ok((albedo, infrared))
+    }
+
+    /// Returns the unit vector from the spacecraft to the Sun, and the unit vector along which the radiation of each source
+    /// propagates with its albedo and infrared irradiances (W/m^2).
+    #[allow(clippy::type_complexity)]
+    fn sources(
+        &self,
+        orbit: &Orbit,
+    ) -> Result<(Vector3<f64>, Vec<(Vector3<f64>, f64, f64)>), NyxError> {
+        let r = self.cosm.try_frame_chg(orbit, self.body_frame)?.radius();
+        let epoch = orbit.epoch;
+        let radius_km = self.body_frame.equatorial_radius();
+        if r.norm() <= radius_km {
+            return Err(NyxError::CustomError(format!(
+                "{epoch}: spacecraft is below the surface of {}",
+                self.body_frame
+            )));
+        }
+        let sun = self
+            .cosm
+            .celestial_state(
+                Bodies::Sun.ephem_path(),
+                epoch,
+                self.body_frame,
+                LightTimeCalc::None,
+            )
+            .radius();
+        let sun_hat = sun / sun.norm();
+        let flux = self.phi * (AU / sun.norm()).powi(2);
+        let albedo_scale = if self.albedo { flux } else { 0.0 };
+        let infrared_scale = if self.infrared { flux / 4.0 } else { 0.0 };
+
+        let mut sources = Vec::new();
+        match self.grid {
+            RadiationGrid::Analytic => {
+                let r_hat = r / r.norm();
+                let latitude_rad = r_hat[2].asin();
+                let scale = (radius_km / r.norm()).powi(2);
+                let phase = sun_hat.dot(&r_hat).clamp(-1.0, 1.0).acos();
+                sources.push((
+                    r_hat,
+                    self.distribution.albedo(latitude_rad, epoch)
+                        * albedo_scale
+                        * scale
+                        * (2.0 / 3.0)
+                        * (phase.sin() + (PI - phase) * phase.cos())
+                        / PI,
+                    self.distribution.emissivity(latitude_rad, epoch) * infrared_scale * scale,
+                ));
+            }
+            RadiationGrid::Rings(rings) => {
+                for (position, area_km2) in visible_elements(&r, radius_km, rings) {
+                    let normal = position / radius_km;
+                    let rho = r - position;
+                    let dist_km = rho.norm();
+                    let rho_hat = rho / dist_km;
+                    let cos_sc = normal.dot(&rho_hat);
+                    if cos_sc <= 0.0 {
+                        continue;
+                    }
+                    let latitude_rad = normal[2].clamp(-1.0, 1.0).asin();
+                    // Lambertian emission of the element towards the spacecraft
+                    let view = cos_sc * area_km2 / (PI * dist_km.powi(2));
+                    let cos_sun = normal.dot(&sun_hat).max(0.0);
+                    sources.push((
+                        rho_hat,
+                        self.distribution.albedo(latitude_rad, epoch)
+                            * albedo_scale
+                            * cos_sun
+                            * view,
+                        self.distribution.emissivity(latitude_rad, epoch) * infrared_scale * view,
+                    ));
+                }
+            }
+        }
+
+        let sun_from_sc = sun - r;
+        Ok((sun_from_sc / sun_from_sc.norm(), sources))
+    }
+}
+
+/// Returns the centers and areas (km^2) of the surface elements of the cap of the sphere visible from the provided position
+fn visible_elements(r: &Vector3<f64>, radius_km: f64, rings: usize) -> Vec<(Vector3<f64>, f64)> {
+    let r_hat = r / r.norm();
+    let u = if r_hat[0].abs() < 0.9 {
+        r_hat.cross(&Vector3::new(1.0, 0.0, 0.0)).normalize()
+    } else {
+        r_hat.cross(&Vector3::new(0.0, 1.0, 0.0)).normalize()
+    };
+    let v = r_hat.cross(&u);
+    // Elements of equal area, i.e. equally spaced in 1 - cos of the angle from the sub-satellite point
+    let cap = 1.0 - radius_km / r.norm();
+    let count = 1 + 3 * rings * (rings + 1);
+    let area_km2 = TAU * radius_km.powi(2) * cap / count as f64;
+    let cos_at = |cumulative: usize| 1.0 - cap * cumulative as f64 / count as f64;
+
+    let mut elements = Vec::with_capacity(count);
+    elements.push((r_hat * radius_km, area_km2));
+    for ring in 1..=rings {
+        let inner = cos_at(1 + 3 * ring * (ring - 1));
+        let outer = cos_at(1 + 3 * ring * (ring + 1));
+        let cos_theta = 0.5 * (inner + outer);
+        let sin_theta = (1.0 - cos_theta.powi(2)).sqrt();
+        let segments = 6 * ring;
+        for k in 0..segments {
+            let azimuth = TAU * (k as f64 + 0.5) / segments as f64;
+            let dir = r_hat * cos_theta + (u * azimuth.cos() + v * azimuth.sin()) * sin_theta;
+            elements.push((dir * radius_km, area_km2));
+        }
+    }
+    elements
+}
+
+impl fmt::Display for PlanetaryRadiation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let grid = match self.grid {
+            RadiationGrid::Analytic => "analytic".to_string(),
+            RadiationGrid::Rings(rings) => format!("{} elements", 1 + 3 * rings * (rings + 1)),
+        };
+        write!(
+            f,
+            "{}{}{} radiation pressure of {} ({:?}, {grid})",
+            if self.albedo { "albedo" } else { "" },
+            if self.albedo && self.infrared {
+                " and "
+            } else {
+                ""
+            },
+            if self.infrared { "infrared" } else { "" },
+            self.body_frame,
+            self.distribution
+        )?;
+        if let Some(plates) = &self.plates {
+            write!(f, " on {plates}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ForceModel for PlanetaryRadiation {
+    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        // Note the 1e-3 is to convert the force from N to kg·km/s^2, as for the SRP
+        match &self.plates {
+            Some(plates) => {
+                // The plates reflect the infrared as they reflect the sunlight
+                let (sun_hat, sources) = self.sources(&ctx.orbit)?;
+                let mut force_n = Vector3::zeros();
+                for (incoming, albedo_w_m2, infrared_w_m2) in sources {
+                    force_n += plates.radiation_force_m2(&ctx.orbit, &sun_hat, &incoming)?
+                        * ((albedo_w_m2 + infrared_w_m2) / SPEED_OF_LIGHT);
+                }
+                Ok(1e-3 * force_n)
+            }
+            None => {
+                let (albedo, infrared) = self.irradiance(&ctx.orbit)?;
+                Ok(1e-3 * ctx.srp.cr * ctx.srp.area_m2 * (albedo + infrared) / SPEED_OF_LIGHT)
+            }
+        }
+    }
+
+    /// The partials with respect to the position are neglected: this radiation pressure is about a tenth of the SRP in LEO
+    /// and varies slowly with the position.
+    fn dual_eom(&self, ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+        Ok((self.eom(ctx)?, Matrix3::zeros()))
+    }
+
+    fn param_partials(&self, ctx: &Spacecraft) -> Result<Matrix3x2<f64>, NyxError> {
+        if self.plates.is_some() {
+            // The coefficient of reflectivity is not used by the plates
+            return Ok(Matrix3x2::zeros());
+        }
+        // As for the SRP, the force is linear in the coefficient of reflectivity
+        let mut unit_cr_ctx = *ctx;
+        unit_cr_ctx.srp.cr = 1.0;
+        let mut partials = Matrix3x2::zeros();
+        partials.set_column(0, &self.eom(&unit_cr_ctx)?);
+        Ok(partials)
+    }
+}
@@ -0,0 +1,334 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::guidance::Thruster;
+use crate::cosmic::{Frame, Spacecraft, STD_GRAVITY};
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, Vector3};
+use crate::md::EventEvaluator;
+use crate::polyfit::CommonPolynomial;
+use crate::time::{Duration, Epoch, Unit};
+use crate::State;
+use na::Rotation3;
+use std::fmt;
+
+/// Profile of a quantity of a finite burn, e.g. its thrust or its Isp, as a function of the time elapsed since its start
+#[derive(Clone, Debug)]
+pub enum BurnProfile {
+    Constant(f64),
+    /// Polynomial of the time elapsed since the start of the burn, in seconds
+    Polynomial(CommonPolynomial),
+    /// Linear interpolation of a table of the time elapsed since the start of the burn and of the value, sorted by time.
+    /// The first and last values are held before and after the table.
+    Table(Vec<(Duration, f64)>),
+}
+
+impl BurnProfile {
+    /// Returns the value of this profile after the provided time since the start of the burn
+    pub fn value(&self, elapsed: Duration) -> f64 {
+        match self {
+            Self::Constant(value) => *value,
+            Self::Polynomial(poly) => poly.eval(elapsed.to_seconds()),
+            Self::Table(table) => {
+                let idx = table.partition_point(|(time, _)| *time <= elapsed);
+                if idx == 0 {
+                    table[0].1
+                } else if idx == table.len() {
+                    table[idx - 1].1
+                } else {
+                    let (t0, v0) = table[idx - 1];
+                    let (t1, v1) = table[idx];
+                    v0 + (v1 - v0) * ((elapsed - t0).to_seconds() / (t1 - t0).to_seconds())
+                }
+            }
+        }
+    }
+
+    fn validate(&self, name: &str) -> Result<(), NyxError> {
+        if let Self::Table(table) = self {
+            if table.is_empty() {
+                return Err(NyxError::CustomError(format!("{name} table is empty")));
+            }
+            if table.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+                return Err(NyxError::CustomError(format!(
+                    "{name} table is not sorted by strictly increasing time"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for BurnProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Constant(value) => write!(f, "{value}"),
+            Self::Polynomial(poly) => write!(f, "{poly}"),
+            Self::Table(table) => write!(f, "table of {} values", table.len()),
+        }
+    }
+}
+
+/// Direction of the thrust of a finite burn
+#[derive(Copy, Clone, Debug)]
+pub enum BurnDirection {
+    /// Fixed unit vector in the provided frame, which is either a trajectory frame (VNC, RIC or RCN), or the integration
+    /// frame itself if `Frame::Inertial`.
+    Fixed { vector: Vector3<f64>, frame: Frame },
+    /// Opposite to the velocity of the spacecraft in its integration frame, e.g. for a deorbit burn
+    AntiVelocity,
+}
+
+impl BurnDirection {
+    /// Returns the unit vector of the thrust in the integration frame of the spacecraft
+    pub fn inertial(&self, sc: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        match self {
+            Self::Fixed { vector, frame } => {
+                if matches!(frame, Frame::Inertial) {
+                    Ok(*vector)
+                } else {
+                    Ok(sc.orbit.dcm_from_traj_frame(*frame)? * vector)
+                }
+            }
+            Self::AntiVelocity => Ok(-sc.orbit.velocity() / sc.orbit.vmag_km_s()),
+        }
+    }
+}
+
+impl fmt::Display for BurnDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fixed { vector, frame } => write!(
+                f,
+                "[{:.6}, {:.6}, {:.6}] ({frame})",
+                vector[0], vector[1], vector[2]
+            ),
+            Self::AntiVelocity => write!(f, "anti-velocity"),
+        }
+    }
+}
+
+/// A finite burn of the spacecraft between two epochs, whose thrust and Isp may vary during the burn, and which depletes
+/// the fuel mass continuously. Finite burns are attached to the spacecraft dynamics with `SpacecraftDynamics::with_burns`
+/// and do not require any thruster on the spacecraft.
+///
+/// **Note:** the dynamics are discontinuous at the start and end of a burn: to land exactly on these epochs, propagate
+/// until the events of the burn.
+#[allow(non_snake_case)]
+#[derive(Clone, Debug)]
+pub struct FiniteBurn {
+    /// Start epoch of the burn
+    pub start: Epoch,
+    /// End epoch of the burn
+    pub end: Epoch,
+    /// Thrust in Newtons
+    pub thrust_N: BurnProfile,
+    /// Specific impulse in seconds
+    pub isp_s: BurnProfile,
+    /// Direction of the thrust
+    pub direction: BurnDirection,
+    /// Scale factor applied to the thrust and fuel mass rate, e.g. a calibration of the thruster performance (one by default)
+    pub thrust_scale: f64,
+    /// Small rotation (rotation vector, in radians) of the thrust direction in the frame of its direction, e.g. a pointing
+    /// error of the thruster (zero by default)
+    pub pointing_bias_rad: Vector3<f64>,
+}
+
+impl FiniteBurn {
+    /// Initializes a new finite burn, whose direction vector, if fixed, is normalized
+    #[allow(non_snake_case)]
+    pub fn new(
+        start: Epoch,
+        end: Epoch,
+        thrust_N: BurnProfile,
+        isp_s: BurnProfile,
+        mut direction: BurnDirection,
+    ) -> Result<Self, NyxError> {
+        if end <= start {
+            return Err(NyxError::CustomError(format!(
+                "finite burn ends on {end}, before its start on {start}"
+            )));
+        }
+        thrust_N.validate("thrust")?;
+        isp_s.validate("Isp")?;
+        if let BurnDirection::Fixed { vector, .. } = &mut direction {
+            if vector.norm() < f64::EPSILON {
+                return Err(NyxError::CustomError(
+                    "finite burn direction is a zero vector".to_string(),
+                ));
+            }
+            *vector = vector.normalize();
+        }
+        Ok(Self {
+            start,
+            end,
+            thrust_N,
+            isp_s,
+            direction,
+            thrust_scale: 1.0,
+            pointing_bias_rad: Vector3::zeros(),
+        })
+    }
+
+    /// Initializes a finite burn at the constant thrust and Isp of the provided thruster
+    pub fn from_thruster(
+        start: Epoch,
+        duration: Duration,
+        thruster: Thruster,
+        direction: BurnDirection,
+    ) -> Result<Self, NyxError> {
+        Self::new(
+            start,
+            start + duration,
+            BurnProfile::Constant(thruster.thrust_N),
+            BurnProfile::Constant(thruster.isp_s),
+            direction,
+        )
+    }
+
+    /// Returns whether the burn is on at the provided epoch
+    pub fn is_active(&self, epoch: Epoch) -> bool {
+        epoch >= self.start && epoch < self.end
+    }
+
+    /// Returns the thrust force (in kg km/s^2, like the force models) and the fuel mass rate (kg/s) of this burn at the
+    /// provided state, both zero outside of the burn.
+    ///
+    /// At the exact start and end epochs of the burn, `left_limit` selects the thrust just before that epoch instead of
+    /// just after it: the equations of motion set it for the stages after the start of an integration step, such that a
+    /// step ending on either epoch only sees the thrust of its own side.
+    pub fn thrust(
+        &self,
+        sc: &Spacecraft,
+        left_limit: bool,
+    ) -> Result<(Vector3<f64>, f64), NyxError> {
+        let epoch = sc.epoch();
+        let active = if left_limit {
+            epoch > self.start && epoch <= self.end
+        } else {
+            self.is_active(epoch)
+        };
+        if !active {
+            return Ok((Vector3::zeros(), 0.0));
+        }
+        let elapsed = sc.epoch() - self.start;
+        let thrust_n = self.thrust_N.value(elapsed);
+        let isp_s = self.isp_s.value(elapsed);
+        if thrust_n < 0.0 || isp_s <= 0.0 {
+            return Err(NyxError::CustomError(format!(
+                "invalid thrust of {thrust_n} N or Isp of {isp_s} s at {}",
+                sc.epoch()
+            )));
+        }
+        let thrust_n = thrust_n * self.thrust_scale;
+        Ok((
+            self.inertial_direction(sc)? * thrust_n * 1e-3,
+            -thrust_n / (isp_s * STD_GRAVITY),
+        ))
+    }
+
+    /// Returns the unit vector of the thrust in the integration frame of the spacecraft, including the pointing bias
+    fn inertial_direction(&self, sc: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        if self.pointing_bias_rad.norm() < f64::EPSILON {
+            return self.direction.inertial(sc);
+        }
+        let bias = Rotation3::new(self.pointing_bias_rad);
+        match self.direction {
+            BurnDirection::Fixed { vector, frame } => {
+                let vector = bias * vector;
+                if matches!(frame, Frame::Inertial) {
+                    Ok(vector)
+                } else {
+                    Ok(sc.orbit.dcm_from_traj_frame(frame)? * vector)
+                }
+            }
+            BurnDirection::AntiVelocity => Ok(bias * self.direction.inertial(sc)?),
+        }
+    }
+
+    /// Returns the event of the start of this burn
+    pub fn start_event(&self) -> BurnEvent {
+        BurnEvent {
+            epoch: self.start,
+            start: true,
+        }
+    }
+
+    /// Returns the event of the end of this burn
+    pub fn end_event(&self) -> BurnEvent {
+        BurnEvent {
+            epoch: self.end,
+            start: false,
+        }
+    }
+}
+
+impl fmt::Display for FiniteBurn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Finite burn on {} for {}: thrust {} N, Isp {} s, direction {}",
+            self.start,
+            self.end - self.start,
+            self.thrust_N,
+            self.isp_s,
+            self.direction
+        )
+    }
+}
+
+/// The event of the start or the end of a finite burn
+#[derive(Copy, Clone, Debug)]
+pub struct BurnEvent {
+    pub epoch: Epoch,
+    /// Whether this is the start of the burn, else its end
+    pub start: bool,
+}
+
+impl fmt::Display for BurnEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.start {
+            write!(f, "start of finite burn on {}", self.epoch)
+        } else {
+            write!(f, "end of finite burn on {}", self.epoch)
+        }
+    }
+}
+
+impl<S: State> EventEvaluator<S> for BurnEvent
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    fn eval(&self, state: &S) -> f64 {
+        (state.epoch() - self.epoch).to_seconds()
+    }
+
+    fn eval_string(&self, state: &S) -> String {
+        format!("{self} (now {})", state.epoch())
+    }
+
+    fn epoch_precision(&self) -> Duration {
+        Unit::Millisecond * 1.0
+    }
+
+    fn value_precision(&self) -> f64 {
+        1e-3
+    }
+}
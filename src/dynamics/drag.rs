@@ -16,19 +16,41 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use super::ForceModel;
-use crate::cosmic::{Cosm, Frame, Spacecraft};
+use super::{ForceModel, PlateModel};
+use crate::cosmic::{Bodies, Cosm, Frame, LightTimeCalc, Spacecraft};
 use crate::errors::NyxError;
-use crate::linalg::{Matrix3, Vector3};
+use crate::io::space_weather::{SpaceWeather, SpaceWeatherIndices};
+use crate::linalg::{Matrix3, Matrix3x2, Vector3};
+use crate::time::{Duration, Epoch, Unit};
 use std::fmt;
 use std::sync::Arc;
 
 /// Density in kg/m^3 and altitudes in meters, not kilometers!
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum AtmDensity {
     Constant(f64),
-    Exponential { rho0: f64, r0: f64, ref_alt_m: f64 },
-    StdAtm { max_alt_m: f64 },
+    Exponential {
+        rho0: f64,
+        r0: f64,
+        ref_alt_m: f64,
+    },
+    StdAtm {
+        max_alt_m: f64,
+    },
+    /// Thermospheric model driven by the solar flux and geomagnetic indices at each epoch, valid from 180 km to 500 km of altitude.
+    /// The exospheric temperature is T = 900 + 2.5 (F10.7 - 70) + 1.5 Ap (in K), with the 81 day average F10.7 and the three hour
+    /// Ap index, the mean molecular mass is m = 27 - 0.012 (h - 200) (h in km), and the density is 6e-10 exp(-(h - 175) m / T).
+    SpaceWeather(Arc<SpaceWeather>),
+}
+
+impl AtmDensity {
+    /// Returns the density of the thermospheric model in kg/m^3 for the provided altitude in km and space weather indices
+    pub fn thermospheric(altitude_km: f64, indices: &SpaceWeatherIndices) -> f64 {
+        let temperature_k = 900.0 + 2.5 * (indices.f107_avg - 70.0) + 1.5 * indices.ap;
+        let molecular_mass = 27.0 - 0.012 * (altitude_km - 200.0);
+        let scale_height_km = temperature_k / molecular_mass;
+        6e-10 * (-(altitude_km - 175.0) / scale_height_km).exp()
+    }
 }
 
 /// `ConstantDrag` implements a constant drag model as defined in Vallado, 4th ed., page 551, with an important caveat.
@@ -44,6 +66,44 @@ pub struct ConstantDrag {
     pub drag_frame: Frame,
     /// a Cosm reference is needed to convert to the state around the correct planet
     pub cosm: Arc<Cosm>,
+    /// Plates of the spacecraft, whose area projected along the flow is used instead of the drag area if set
+    pub plates: Option<Arc<PlateModel>>,
+}
+
+/// Returns the drag force for the provided density (kg/m^3) and velocity relative to the atmosphere, with the area of the plates
+/// facing the flow if set, or with the drag area of the spacecraft otherwise.
+fn drag_force(
+    ctx: &Spacecraft,
+    rho: f64,
+    velocity: &Vector3<f64>,
+    plates: &Option<Arc<PlateModel>>,
+    cosm: &Cosm,
+) -> Result<Vector3<f64>, NyxError> {
+    let area_m2 = match plates {
+        Some(plates) => {
+            let vmag = velocity.norm();
+            if vmag < f64::EPSILON {
+                return Ok(Vector3::zeros());
+            }
+            let sun_hat = if plates.tracks_sun() {
+                let r_sun = cosm
+                    .celestial_state(
+                        Bodies::Sun.ephem_path(),
+                        ctx.orbit.epoch,
+                        ctx.orbit.frame,
+                        LightTimeCalc::None,
+                    )
+                    .radius()
+                    - ctx.orbit.radius();
+                r_sun / r_sun.norm()
+            } else {
+                Vector3::zeros()
+            };
+            plates.projected_area_m2(&ctx.orbit, &sun_hat, &(velocity / vmag))?
+        }
+        None => ctx.drag.area_m2,
+    };
+    Ok(-0.5 * rho * ctx.drag.cd * area_m2 * velocity.norm() * velocity)
 }
 
 impl fmt::Display for ConstantDrag {
@@ -52,7 +112,11 @@ impl fmt::Display for ConstantDrag {
             f,
             "\tConstant Drag rho = {} kg/m^3 in frame {}",
             self.rho, self.drag_frame
-        )
+        )?;
+        if let Some(plates) = &self.plates {
+            write!(f, " on {plates}")?;
+        }
+        Ok(())
     }
 }
 
@@ -60,12 +124,21 @@ impl ForceModel for ConstantDrag {
     fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
         let osc = self.cosm.frame_chg(&ctx.orbit, self.drag_frame);
         let velocity = osc.velocity();
-        Ok(-0.5 * self.rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+        drag_force(ctx, self.rho, &velocity, &self.plates, &self.cosm)
     }
 
     fn dual_eom(&self, _osc_ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
         Err(NyxError::PartialsUndefined)
     }
+
+    fn param_partials(&self, ctx: &Spacecraft) -> Result<Matrix3x2<f64>, NyxError> {
+        // The drag force is linear in the coefficient of drag, so its partial is the force for a unit C_d.
+        let mut unit_cd_ctx = *ctx;
+        unit_cd_ctx.drag.cd = 1.0;
+        let mut partials = Matrix3x2::zeros();
+        partials.set_column(1, &self.eom(&unit_cd_ctx)?);
+        Ok(partials)
+    }
 }
 
 /// `Drag` implements all three drag models.
@@ -77,6 +150,8 @@ pub struct Drag {
     pub drag_frame: Frame,
     /// a Cosm reference is needed to convert to the state around the correct planet
     pub cosm: Arc<Cosm>,
+    /// Plates of the spacecraft, whose area projected along the flow is used instead of the drag area if set
+    pub plates: Option<Arc<PlateModel>>,
 }
 
 impl Drag {
@@ -90,6 +165,23 @@ impl Drag {
             },
             drag_frame: cosm.frame("IAU Earth"),
             cosm,
+            plates: None,
+        })
+    }
+
+    /// Computes the drag on the area of the provided plates facing the flow instead of on the drag area
+    pub fn with_plates(mut self, plates: Arc<PlateModel>) -> Self {
+        self.plates = Some(plates);
+        self
+    }
+
+    /// Drag model whose density is computed from the space weather indices at each epoch, from 180 km to 500 km of altitude
+    pub fn earth_space_weather(cosm: Arc<Cosm>, space_weather: Arc<SpaceWeather>) -> Arc<Self> {
+        Arc::new(Self {
+            density: AtmDensity::SpaceWeather(space_weather),
+            drag_frame: cosm.frame("IAU Earth"),
+            cosm,
+            plates: None,
         })
     }
 
@@ -101,6 +193,7 @@ impl Drag {
             },
             drag_frame: cosm.frame("IAU Earth"),
             cosm,
+            plates: None,
         })
     }
 }
@@ -111,7 +204,11 @@ impl fmt::Display for Drag {
             f,
             "\tDrag density {:?} in frame {}",
             self.density, self.drag_frame
-        )
+        )?;
+        if let Some(plates) = &self.plates {
+            write!(f, " on {plates}")?;
+        }
+        Ok(())
     }
 }
 
@@ -119,10 +216,10 @@ impl ForceModel for Drag {
     fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
         let integration_frame = ctx.orbit.frame;
         let osc = self.cosm.frame_chg(&ctx.orbit, self.drag_frame);
-        match self.density {
+        match &self.density {
             AtmDensity::Constant(rho) => {
                 let velocity = osc.velocity();
-                Ok(-0.5 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+                drag_force(ctx, *rho, &velocity, &self.plates, &self.cosm)
             }
 
             AtmDensity::Exponential {
@@ -137,7 +234,18 @@ impl ForceModel for Drag {
                 let velocity_integr_frame = self.cosm.frame_chg(&osc, integration_frame).velocity();
 
                 let velocity = velocity_integr_frame - osc.velocity();
-                Ok(-0.5 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+                drag_force(ctx, rho, &velocity, &self.plates, &self.cosm)
+            }
+
+            AtmDensity::SpaceWeather(space_weather) => {
+                let indices = space_weather.at(ctx.orbit.epoch)?;
+                let altitude_km = osc.rmag_km() - self.drag_frame.equatorial_radius();
+                let rho = AtmDensity::thermospheric(altitude_km, &indices);
+
+                let velocity_integr_frame = self.cosm.frame_chg(&osc, integration_frame).velocity();
+
+                let velocity = velocity_integr_frame - osc.velocity();
+                drag_force(ctx, rho, &velocity, &self.plates, &self.cosm)
             }
 
             AtmDensity::StdAtm { max_alt_m } => {
@@ -163,7 +271,7 @@ impl ForceModel for Drag {
                 let velocity_integr_frame = self.cosm.frame_chg(&osc, integration_frame).velocity();
 
                 let velocity = velocity_integr_frame - osc.velocity();
-                Ok(-0.5 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+                drag_force(ctx, rho, &velocity, &self.plates, &self.cosm)
             }
         }
     }
@@ -171,4 +279,94 @@ impl ForceModel for Drag {
     fn dual_eom(&self, _osc_ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
         Err(NyxError::PartialsUndefined)
     }
+
+    fn param_partials(&self, ctx: &Spacecraft) -> Result<Matrix3x2<f64>, NyxError> {
+        // The drag force is linear in the coefficient of drag, so its partial is the force for a unit C_d.
+        let mut unit_cd_ctx = *ctx;
+        unit_cd_ctx.drag.cd = 1.0;
+        let mut partials = Matrix3x2::zeros();
+        partials.set_column(1, &self.eom(&unit_cd_ctx)?);
+        Ok(partials)
+    }
+}
+
+/// A short-term correction of the atmospheric density, as a scale factor on the density of a drag model, e.g. fitted from the
+/// drag scale factors estimated by successive orbit determinations with `od::DensityCalibration`.
+///
+/// Up to its reference epoch, the scale factor follows the fitted linear trend. After it, the deviation of the scale factor
+/// from unity decays exponentially with the time constant, as density perturbations like geomagnetic storms are short lived.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DensityCorrection {
+    /// Epoch of the most recent estimate used in the fit
+    pub ref_epoch: Epoch,
+    /// Scale factor on the density at the reference epoch
+    pub scale: f64,
+    /// Rate of change of the scale factor, per day
+    pub rate_per_day: f64,
+    /// Time constant of the return of the density to the nominal model after the reference epoch
+    pub time_constant: Duration,
+}
+
+impl DensityCorrection {
+    /// Returns the scale factor of the density at the provided epoch, which is never negative
+    pub fn scale_at(&self, epoch: Epoch) -> f64 {
+        let dt = epoch - self.ref_epoch;
+        let scale = if dt <= Duration::ZERO {
+            self.scale + self.rate_per_day * dt.to_unit(Unit::Day)
+        } else {
+            let decay = (-dt.to_seconds() / self.time_constant.to_seconds()).exp();
+            1.0 + (self.scale - 1.0) * decay
+        };
+        scale.max(0.0)
+    }
+}
+
+impl fmt::Display for DensityCorrection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "density scale {:.6} at {} with rate {:.3e}/day and time constant {}",
+            self.scale, self.ref_epoch, self.rate_per_day, self.time_constant
+        )
+    }
+}
+
+/// `CorrectedDrag` scales the density of a drag model by a short-term density correction.
+///
+/// The drag force is linear in the density, so the corrected force is the force of the drag model scaled by the correction.
+#[derive(Clone)]
+pub struct CorrectedDrag {
+    /// Drag model whose density is corrected, e.g. `Drag` or `ConstantDrag`
+    pub drag: Arc<dyn ForceModel>,
+    /// Correction of the density of the drag model
+    pub correction: DensityCorrection,
+}
+
+impl CorrectedDrag {
+    /// Corrects the density of the provided drag model
+    pub fn new(drag: Arc<dyn ForceModel>, correction: DensityCorrection) -> Arc<Self> {
+        Arc::new(Self { drag, correction })
+    }
+}
+
+impl fmt::Display for CorrectedDrag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} with {}", self.drag, self.correction)
+    }
+}
+
+impl ForceModel for CorrectedDrag {
+    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        Ok(self.correction.scale_at(ctx.orbit.epoch) * self.drag.eom(ctx)?)
+    }
+
+    fn dual_eom(&self, osc_ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+        let scale = self.correction.scale_at(osc_ctx.orbit.epoch);
+        let (force, grad) = self.drag.dual_eom(osc_ctx)?;
+        Ok((scale * force, scale * grad))
+    }
+
+    fn param_partials(&self, ctx: &Spacecraft) -> Result<Matrix3x2<f64>, NyxError> {
+        Ok(self.correction.scale_at(ctx.orbit.epoch) * self.drag.param_partials(ctx)?)
+    }
 }
@@ -0,0 +1,190 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::ForceModel;
+use crate::cosmic::{Frame, Spacecraft};
+use crate::errors::NyxError;
+use crate::linalg::{Matrix3, Vector3};
+use crate::time::Epoch;
+use crate::State;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Physical quantity stored in a force profile
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProfileKind {
+    /// Accelerations in km/s^2, e.g. reconstructed from accelerometer telemetry
+    Acceleration,
+    /// Forces in Newtons, e.g. a measured thrust profile
+    Force,
+}
+
+impl fmt::Display for ProfileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Acceleration => write!(f, "acceleration (km/s^2)"),
+            Self::Force => write!(f, "force (N)"),
+        }
+    }
+}
+
+/// Row of a CSV force profile
+#[derive(Serialize, Deserialize)]
+struct ProfileRow {
+    epoch: String,
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+/// `ForceProfile` applies a time-tagged acceleration or force profile, linearly interpolated between its samples.
+///
+/// The samples are expressed either in the integration frame (`Frame::Inertial`) or in a local frame of the trajectory
+/// (e.g. `Frame::VNC`), like finite burns. No force is applied outside of the time span of the profile.
+///
+/// **WARNING:** the partials of this force with respect to the state are zero, even for local frames.
+#[derive(Clone, Debug)]
+pub struct ForceProfile {
+    /// Samples of the profile, in chronological order
+    pub samples: Vec<(Epoch, Vector3<f64>)>,
+    pub kind: ProfileKind,
+    /// Frame of the samples, either `Frame::Inertial` or a local frame of the trajectory
+    pub frame: Frame,
+}
+
+impl ForceProfile {
+    /// Initializes a new profile from samples in chronological order, at least two are required
+    pub fn from_samples(
+        samples: Vec<(Epoch, Vector3<f64>)>,
+        kind: ProfileKind,
+        frame: Frame,
+    ) -> Result<Arc<Self>, NyxError> {
+        if samples.len() < 2 {
+            return Err(NyxError::CustomError(format!(
+                "force profile requires at least two samples but got {}",
+                samples.len()
+            )));
+        }
+        if let Some(w) = samples.windows(2).find(|w| w[1].0 <= w[0].0) {
+            return Err(NyxError::CustomError(format!(
+                "force profile samples must be in strictly increasing chronological order: {} then {}",
+                w[0].0, w[1].0
+            )));
+        }
+        if !matches!(
+            frame,
+            Frame::Inertial | Frame::VNC | Frame::RCN | Frame::RIC
+        ) {
+            return Err(NyxError::CustomError(format!(
+                "force profile must be in the integration frame or in a local frame, not {frame}"
+            )));
+        }
+        Ok(Arc::new(Self {
+            samples,
+            kind,
+            frame,
+        }))
+    }
+
+    /// Loads a profile from a CSV file with the columns `epoch,x,y,z`.
+    pub fn from_csv<P: AsRef<Path>>(
+        path: P,
+        kind: ProfileKind,
+        frame: Frame,
+    ) -> Result<Arc<Self>, NyxError> {
+        let mut reader =
+            csv::Reader::from_path(path).map_err(|e| NyxError::FileUnreadable(format!("{e}")))?;
+
+        let mut samples = Vec::new();
+        for (rno, row) in reader.deserialize::<ProfileRow>().enumerate() {
+            let row = row.map_err(|e| NyxError::FileUnreadable(format!("row {rno}: {e}")))?;
+            let epoch = Epoch::from_str(&row.epoch)
+                .map_err(|e| NyxError::FileUnreadable(format!("row {rno}: {e}")))?;
+            samples.push((epoch, Vector3::new(row.x, row.y, row.z)));
+        }
+
+        info!("Loaded {} samples of {kind} profile", samples.len());
+
+        Self::from_samples(samples, kind, frame)
+    }
+
+    /// Returns the first and last epochs of this profile
+    pub fn span(&self) -> (Epoch, Epoch) {
+        (self.samples[0].0, self.samples[self.samples.len() - 1].0)
+    }
+
+    /// Returns the linearly interpolated value of the profile at the provided epoch, in the frame of the profile.
+    /// Returns zero outside of the span of the profile.
+    pub fn at(&self, epoch: Epoch) -> Vector3<f64> {
+        let (start, end) = self.span();
+        if epoch < start || epoch > end {
+            return Vector3::zeros();
+        }
+        match self.samples.binary_search_by_key(&epoch, |(e, _)| *e) {
+            Ok(idx) => self.samples[idx].1,
+            Err(idx) => {
+                let (e0, v0) = self.samples[idx - 1];
+                let (e1, v1) = self.samples[idx];
+                let frac = (epoch - e0).to_seconds() / (e1 - e0).to_seconds();
+                v0 + (v1 - v0) * frac
+            }
+        }
+    }
+}
+
+impl fmt::Display for ForceProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (start, end) = self.span();
+        write!(
+            f,
+            "\t{} profile in {} of {} samples from {start} to {end}",
+            self.kind,
+            self.frame,
+            self.samples.len()
+        )
+    }
+}
+
+impl ForceModel for ForceProfile {
+    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        let value = self.at(ctx.epoch());
+        if value.norm() == 0.0 {
+            return Ok(value);
+        }
+
+        let value_inertial = if matches!(self.frame, Frame::Inertial) {
+            value
+        } else {
+            ctx.orbit.dcm_from_traj_frame(self.frame)? * value
+        };
+
+        Ok(match self.kind {
+            // The spacecraft dynamics divide the force by the mass
+            ProfileKind::Acceleration => value_inertial * ctx.mass_kg(),
+            // Convert from N to kg km/s^2
+            ProfileKind::Force => value_inertial * 1e-3,
+        })
+    }
+
+    fn dual_eom(&self, ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+        Ok((self.eom(ctx)?, Matrix3::zeros()))
+    }
+}
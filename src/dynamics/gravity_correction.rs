@@ -0,0 +1,214 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::sph_harmonics::Harmonics;
+use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::dynamics::AccelModel;
+use crate::errors::NyxError;
+use crate::io::gravity::HarmonicsMem;
+use crate::linalg::{DMatrix, Matrix3, Vector3};
+use std::fmt;
+use std::sync::Arc;
+
+/// A parameter of the gravity field of a body, e.g. estimated by orbit determination.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GravityParameter {
+    /// Gravitational parameter, in km^3/s^2
+    GM,
+    /// Normalized cosine coefficient of the spherical harmonics
+    C { degree: usize, order: usize },
+    /// Normalized sine coefficient of the spherical harmonics
+    S { degree: usize, order: usize },
+}
+
+impl GravityParameter {
+    /// Returns an error if the degree is lower than two, if the order exceeds the degree, or for the sine coefficients of order zero
+    pub fn validate(&self) -> Result<(), NyxError> {
+        match *self {
+            Self::GM => Ok(()),
+            Self::C { degree, order } | Self::S { degree, order } => {
+                if degree < 2 || order > degree {
+                    Err(NyxError::CustomError(format!(
+                        "{self} is not a spherical harmonics coefficient of degree 2 or more"
+                    )))
+                } else if order == 0 && matches!(self, Self::S { .. }) {
+                    Err(NyxError::CustomError(format!("{self} is always zero")))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Returns the degree of the coefficient, or zero for the gravitational parameter
+    pub fn degree(&self) -> usize {
+        match *self {
+            Self::GM => 0,
+            Self::C { degree, .. } | Self::S { degree, .. } => degree,
+        }
+    }
+}
+
+impl fmt::Display for GravityParameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::GM => write!(f, "GM"),
+            Self::C { degree, order } => write!(f, "C[{degree},{order}]"),
+            Self::S { degree, order } => write!(f, "S[{degree},{order}]"),
+        }
+    }
+}
+
+/// Corrections to the gravitational parameter and to the spherical harmonics coefficients of a body, added to its nominal
+/// field (i.e. the two body dynamics and the harmonics of the orbital dynamics).
+///
+/// The acceleration is linear in each correction, and the correction of the gravitational parameter only applies to the
+/// point mass term.
+#[derive(Clone)]
+pub struct GravityCorrection {
+    /// Body fixed frame of the spherical harmonics, e.g. "IAU Earth"
+    pub compute_frame: Frame,
+    /// Corrections of each parameter
+    pub deltas: Vec<(GravityParameter, f64)>,
+    cosm: Arc<Cosm>,
+    harmonics: Option<Arc<Harmonics>>,
+}
+
+impl GravityCorrection {
+    /// Initializes the corrections of the gravity field of the body of the provided body fixed frame
+    pub fn new(
+        compute_frame: Frame,
+        deltas: Vec<(GravityParameter, f64)>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Self, NyxError> {
+        for (param, _) in &deltas {
+            param.validate()?;
+        }
+
+        let max_degree = deltas.iter().map(|(param, _)| param.degree()).max();
+        let harmonics = match max_degree {
+            Some(max_degree) if max_degree >= 2 => {
+                let mut c_nm = DMatrix::zeros(max_degree + 1, max_degree + 1);
+                let mut s_nm = DMatrix::zeros(max_degree + 1, max_degree + 1);
+                for (param, delta) in &deltas {
+                    match *param {
+                        GravityParameter::GM => {}
+                        GravityParameter::C { degree, order } => c_nm[(degree, order)] += delta,
+                        GravityParameter::S { degree, order } => s_nm[(degree, order)] += delta,
+                    }
+                }
+                Some(Harmonics::from_stor(
+                    compute_frame,
+                    HarmonicsMem::from_cs(c_nm, s_nm),
+                    cosm.clone(),
+                ))
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            compute_frame,
+            deltas,
+            cosm,
+            harmonics,
+        })
+    }
+
+    /// Returns the correction of the gravitational parameter, in km^3/s^2
+    pub fn delta_gm_km3_s2(&self) -> f64 {
+        self.deltas
+            .iter()
+            .filter(|(param, _)| *param == GravityParameter::GM)
+            .map(|(_, delta)| delta)
+            .sum()
+    }
+
+    /// Returns the partials of the acceleration with respect to each of the provided parameters, which are the accelerations of
+    /// a unit correction of each.
+    pub fn accel_partials(
+        &self,
+        osc: &Orbit,
+        params: &[GravityParameter],
+    ) -> Result<Vec<Vector3<f64>>, NyxError> {
+        params
+            .iter()
+            .map(|param| {
+                Self::new(self.compute_frame, vec![(*param, 1.0)], self.cosm.clone())?.eom(osc)
+            })
+            .collect()
+    }
+
+    /// Returns the position of the spacecraft relative to the body, in the inertial frame of the body
+    fn relative_position(&self, osc: &Orbit) -> Result<Vector3<f64>, NyxError> {
+        let inertial = self
+            .cosm
+            .frame_from_ephem_path(&self.compute_frame.ephem_path());
+        Ok(self.cosm.try_frame_chg(osc, inertial)?.radius())
+    }
+}
+
+impl fmt::Display for GravityCorrection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let deltas = self
+            .deltas
+            .iter()
+            .map(|(param, delta)| format!("Δ{param} = {delta:e}"))
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{} gravity corrections: {}",
+            self.compute_frame,
+            deltas.join(", ")
+        )
+    }
+}
+
+impl AccelModel for GravityCorrection {
+    fn eom(&self, osc: &Orbit) -> Result<Vector3<f64>, NyxError> {
+        let mut accel = match &self.harmonics {
+            Some(harmonics) => harmonics.eom(osc)?,
+            None => Vector3::zeros(),
+        };
+        let delta_gm = self.delta_gm_km3_s2();
+        if delta_gm != 0.0 {
+            let r = self.relative_position(osc)?;
+            accel -= delta_gm * r / r.norm().powi(3);
+        }
+        Ok(accel)
+    }
+
+    fn dual_eom(&self, osc: &Orbit) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+        let (mut accel, mut grad) = match &self.harmonics {
+            Some(harmonics) => harmonics.dual_eom(osc)?,
+            None => (Vector3::zeros(), Matrix3::zeros()),
+        };
+        let delta_gm = self.delta_gm_km3_s2();
+        if delta_gm != 0.0 {
+            let r = self.relative_position(osc)?;
+            let rmag = r.norm();
+            accel -= delta_gm * r / rmag.powi(3);
+            grad += delta_gm
+                * (3.0 * r * r.transpose() / rmag.powi(5) - Matrix3::identity() / rmag.powi(3));
+        }
+        Ok((accel, grad))
+    }
+
+    fn depends_on_velocity(&self) -> bool {
+        false
+    }
+}
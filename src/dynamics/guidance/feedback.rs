@@ -0,0 +1,571 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Frame, GuidanceLaw, GuidanceMode, NyxError, Orbit, Spacecraft, Vector3};
+use crate::linalg::{Const, OMatrix};
+use crate::md::objective::Objective;
+use crate::md::StateParameter;
+use crate::State;
+use std::f64::consts::TAU;
+use std::fmt;
+use std::sync::Arc;
+
+/// Keplerian elements which may be targeted by the feedback guidance laws, in the order of their arrays
+const ELEMENTS: [StateParameter; 5] = [
+    StateParameter::SMA,
+    StateParameter::Eccentricity,
+    StateParameter::Inclination,
+    StateParameter::RAAN,
+    StateParameter::AoP,
+];
+
+/// Number of true anomalies over an orbit at which the effectivity of the Q-law is evaluated
+const EFFECTIVITY_SAMPLES: usize = 72;
+
+/// Sorts the objectives in the order of the elements, checking that each is a Keplerian element targeted at most once
+fn sort_objectives(
+    objectives: &[Objective],
+    law: &str,
+) -> Result<[Option<Objective>; 5], NyxError> {
+    if objectives.is_empty() {
+        return Err(NyxError::GuidanceConfigError(format!(
+            "{law} requires at least one objective"
+        )));
+    }
+    let mut sorted = [None; 5];
+    for obj in objectives {
+        match ELEMENTS.iter().position(|param| *param == obj.parameter) {
+            Some(idx) if sorted[idx].is_none() => sorted[idx] = Some(*obj),
+            Some(_) => {
+                return Err(NyxError::GuidanceConfigError(format!(
+                    "{} targeted more than once in {law}",
+                    obj.parameter
+                )))
+            }
+            None => {
+                return Err(NyxError::GuidanceConfigError(format!(
+                    "Objective {} not supported in {law}",
+                    obj.parameter
+                )))
+            }
+        }
+    }
+    Ok(sorted)
+}
+
+/// Returns the osculating elements a (km), e, i, Ω and ω (rad) of the orbit
+fn elements(osc: &Orbit) -> [f64; 5] {
+    [
+        osc.sma_km(),
+        osc.ecc(),
+        osc.inc_deg().to_radians(),
+        osc.raan_deg().to_radians(),
+        osc.aop_deg().to_radians(),
+    ]
+}
+
+/// Returns the error of each element with respect to its objective (in km or rad), wrapping the errors of the angles
+/// within [-π; π], or zero if that element is not targeted
+fn element_errors(objectives: &[Option<Objective>; 5], oe: &[f64; 5]) -> [f64; 5] {
+    let mut errors = [0.0; 5];
+    for (idx, obj) in objectives.iter().enumerate() {
+        if let Some(obj) = obj {
+            errors[idx] = match idx {
+                0 | 1 => oe[idx] - obj.desired_value,
+                2 => oe[idx] - obj.desired_value.to_radians(),
+                _ => {
+                    let delta = oe[idx] - obj.desired_value.to_radians();
+                    delta.sin().atan2(delta.cos())
+                }
+            };
+        }
+    }
+    errors
+}
+
+/// Returns the Gauss variational equations of the elements a, e, i, Ω and ω, i.e. their rates of change per unit
+/// acceleration along the radial, cross-track and normal (RCN) axes, at the true anomaly ν (rad).
+/// The rows of Ω and ω are singular for equatorial orbits, and the row of ω is singular for circular orbits.
+fn gauss_rcn(oe: &[f64; 5], ν: f64, μ: f64) -> OMatrix<f64, Const<5>, Const<3>> {
+    let [a, e, i, _, ω] = *oe;
+    let p = a * (1.0 - e.powi(2));
+    let h = (μ * p).sqrt();
+    let (sin_ν, cos_ν) = ν.sin_cos();
+    let r = p / (1.0 + e * cos_ν);
+    let (sin_u, cos_u) = (ω + ν).sin_cos();
+    let mut b = OMatrix::<f64, Const<5>, Const<3>>::zeros();
+    b[(0, 0)] = 2.0 * a.powi(2) * e * sin_ν / h;
+    b[(0, 1)] = 2.0 * a.powi(2) * p / (h * r);
+    b[(1, 0)] = p * sin_ν / h;
+    b[(1, 1)] = ((p + r) * cos_ν + r * e) / h;
+    b[(2, 2)] = r * cos_u / h;
+    b[(3, 2)] = r * sin_u / (h * i.sin());
+    b[(4, 0)] = -p * cos_ν / (e * h);
+    b[(4, 1)] = (p + r) * sin_ν / (e * h);
+    b[(4, 2)] = -r * sin_u * i.cos() / (h * i.sin());
+    b
+}
+
+/// Returns the unit thrust direction in the RCN frame which decreases the fastest the function whose gradient with
+/// respect to the elements is provided, along with that rate of decrease per unit acceleration. Elements whose gradient is
+/// zero are skipped, which avoids the singularities of their Gauss variational equations.
+fn steepest_descent(
+    gradient: &[f64; 5],
+    b: &OMatrix<f64, Const<5>, Const<3>>,
+) -> (Vector3<f64>, f64) {
+    let mut d = Vector3::zeros();
+    for (idx, grad) in gradient.iter().enumerate() {
+        let row = b.fixed_view::<1, 3>(idx, 0).transpose();
+        if grad.abs() > 0.0 && row.iter().all(|x| x.is_finite()) {
+            d += *grad * row;
+        }
+    }
+    let norm = d.norm();
+    if norm > 0.0 {
+        (-d / norm, -norm)
+    } else {
+        (Vector3::zeros(), 0.0)
+    }
+}
+
+/// Returns whether all of the objectives are achieved at the provided state
+fn objectives_achieved(objectives: &[Option<Objective>; 5], osc: &Orbit) -> Result<bool, NyxError> {
+    for obj in objectives.iter().flatten() {
+        if !obj.assess_raw(osc.value(obj.parameter)?).0 {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Switches to thrusting until all of the objectives are achieved, unless the guidance is inhibited
+fn next_mode(law: &dyn GuidanceLaw, sc: &mut Spacecraft) {
+    if sc.mode() != GuidanceMode::Inhibit {
+        if !law.achieved(sc).unwrap() {
+            if sc.mode() == GuidanceMode::Coast {
+                info!("enabling steering: {:x}", sc.orbit);
+            }
+            sc.mut_mode(GuidanceMode::Thrust);
+        } else {
+            if sc.mode() == GuidanceMode::Thrust {
+                info!("disabling steering: {:x}", sc.orbit);
+            }
+            sc.mut_mode(GuidanceMode::Coast);
+        }
+    }
+}
+
+/// QLaw defines the Q-law closed loop guidance of Petropoulos (AIAA 2004-5089 and AAS 05-162), which steers the thrust to
+/// decrease the fastest the proximity quotient Q, i.e. a weighted sum of the squared errors of the targeted Keplerian
+/// elements, each divided by its maximum rate of change over the orbit.
+///
+/// The thrust may be restricted to the parts of the orbit where it is effective, i.e. where the relative effectivity of
+/// the rate of decrease of Q (zero at the worst true anomaly and one at the best) is above a threshold. A penalty on
+/// the periapsis radius may keep the transfer above a minimum radius.
+///
+/// NOTE: the AoP cannot be targeted on circular orbits, nor the RAAN on equatorial orbits.
+/// WARNING: Objectives must be in degrees!
+#[derive(Copy, Clone, Debug)]
+pub struct QLaw {
+    /// Objectives, in the order of the SMA, eccentricity, inclination, RAAN and AoP
+    pub objectives: [Option<Objective>; 5],
+    /// Weights of each element in the proximity quotient, in the same order as the objectives
+    pub weights: [f64; 5],
+    /// Minimum relative effectivity in [0; 1] to thrust, defaults to zero (i.e. always thrust)
+    pub η_threshold: f64,
+    /// Minimum periapsis radius (km) enforced by a penalty, if any
+    pub rp_min_km: Option<f64>,
+}
+
+impl QLaw {
+    /// Creates a new Q-law with unit weights which always thrusts, as an Arc to be plugged into the spacecraft dynamics
+    pub fn new(objectives: &[Objective]) -> Result<Arc<Self>, NyxError> {
+        Self::with_weights(objectives, &[1.0; 5], 0.0, None)
+    }
+
+    /// Creates a new Q-law with the weight of each objective, the minimum relative effectivity to thrust, and the minimum
+    /// periapsis radius if any, as an Arc to be plugged into the spacecraft dynamics
+    pub fn with_weights(
+        objectives: &[Objective],
+        weights: &[f64],
+        η_threshold: f64,
+        rp_min_km: Option<f64>,
+    ) -> Result<Arc<Self>, NyxError> {
+        if objectives.len() > weights.len() {
+            return Err(NyxError::GuidanceConfigError(format!(
+                "Must provide at least {} weights, provided {}",
+                objectives.len(),
+                weights.len()
+            )));
+        }
+        if !(0.0..=1.0).contains(&η_threshold) {
+            return Err(NyxError::GuidanceConfigError(format!(
+                "effectivity threshold must be within [0; 1] but got {η_threshold}"
+            )));
+        }
+        let sorted = sort_objectives(objectives, "Q-law")?;
+        let mut sorted_weights = [0.0; 5];
+        for (obj, weight) in objectives.iter().zip(weights) {
+            if *weight < 0.0 {
+                return Err(NyxError::GuidanceConfigError(format!(
+                    "weight of {} must be positive but got {weight}",
+                    obj.parameter
+                )));
+            }
+            let idx = ELEMENTS.iter().position(|p| *p == obj.parameter).unwrap();
+            sorted_weights[idx] = *weight;
+        }
+        Ok(Arc::new(Self {
+            objectives: sorted,
+            weights: sorted_weights,
+            η_threshold,
+            rp_min_km,
+        }))
+    }
+
+    /// Returns the proximity quotient Q of the provided elements (km and rad), per unit acceleration squared
+    pub fn quotient(&self, oe: &[f64; 5], μ: f64) -> f64 {
+        let [a, e, i, _, ω] = *oe;
+        let p = a * (1.0 - e.powi(2));
+        let h = (μ * p).sqrt();
+        let errors = element_errors(&self.objectives, oe);
+
+        let mut q = 0.0;
+        for (idx, obj) in self.objectives.iter().enumerate() {
+            if obj.is_none() {
+                continue;
+            }
+            let (max_rate, scaling) = match idx {
+                0 => {
+                    let a_target = self.objectives[0].unwrap().desired_value;
+                    (
+                        2.0 * (a.powi(3) * (1.0 + e) / (μ * (1.0 - e))).sqrt(),
+                        (1.0 + ((a - a_target) / (3.0 * a_target)).powi(4)).sqrt(),
+                    )
+                }
+                1 => (2.0 * p / h, 1.0),
+                2 => (
+                    p / (h * ((1.0 - (e * ω.sin()).powi(2)).sqrt() - e * ω.cos().abs())),
+                    1.0,
+                ),
+                3 => (Self::raan_max_rate(oe, p, h), 1.0),
+                _ => {
+                    // True anomaly of the maximum in-plane rate of change of the AoP
+                    let oe2 = 1.0 - e.powi(2);
+                    let e3 = e.powi(3);
+                    let sqrt_val = (0.25 * (oe2 / e3).powi(2) + 1.0 / 27.0).sqrt();
+                    let cos_ν = ((oe2 / (2.0 * e3) + sqrt_val).cbrt()
+                        - (-oe2 / (2.0 * e3) + sqrt_val).cbrt()
+                        - 1.0 / e)
+                        .clamp(-1.0, 1.0);
+                    let sin_ν2 = 1.0 - cos_ν.powi(2);
+                    let r = p / (1.0 + e * cos_ν);
+                    let in_plane =
+                        (p.powi(2) * cos_ν.powi(2) + (p + r).powi(2) * sin_ν2).sqrt() / (e * h);
+                    let out_of_plane = Self::raan_max_rate(oe, p, h) * i.cos().abs();
+                    const B: f64 = 0.01;
+                    ((in_plane + B * out_of_plane) / (1.0 + B), 1.0)
+                }
+            };
+            q += self.weights[idx] * scaling * (errors[idx] / max_rate).powi(2);
+        }
+
+        if let Some(rp_min_km) = self.rp_min_km {
+            q *= 1.0 + (100.0 * (1.0 - a * (1.0 - e) / rp_min_km)).exp();
+        }
+        q
+    }
+
+    /// Maximum rate of change of the RAAN over the orbit, per unit acceleration
+    fn raan_max_rate(oe: &[f64; 5], p: f64, h: f64) -> f64 {
+        let [_, e, i, _, ω] = *oe;
+        p / (h * i.sin().abs() * ((1.0 - (e * ω.cos()).powi(2)).sqrt() - e * ω.sin().abs()))
+    }
+
+    /// Gradient of the proximity quotient with respect to the elements, by central finite differences
+    fn gradient(&self, oe: &[f64; 5], μ: f64) -> [f64; 5] {
+        let steps = [oe[0] * 1e-7, 1e-7, 1e-7, 1e-7, 1e-7];
+        let mut gradient = [0.0; 5];
+        for (idx, step) in steps.iter().enumerate() {
+            let mut plus = *oe;
+            let mut minus = *oe;
+            plus[idx] += step;
+            minus[idx] -= step;
+            let derivative = (self.quotient(&plus, μ) - self.quotient(&minus, μ)) / (2.0 * step);
+            if derivative.is_finite() {
+                gradient[idx] = derivative;
+            }
+        }
+        gradient
+    }
+
+    /// Returns the thrust direction in the RCN frame and the relative effectivity in [0; 1] of thrusting at this orbit
+    pub fn steering(&self, osc: &Orbit) -> (Vector3<f64>, f64) {
+        let μ = osc.frame.gm();
+        let oe = elements(osc);
+        let gradient = self.gradient(&oe, μ);
+        let ν = osc.ta_deg().to_radians();
+        let (direction, rate) = steepest_descent(&gradient, &gauss_rcn(&oe, ν, μ));
+        if self.η_threshold <= 0.0 {
+            return (direction, 1.0);
+        }
+        // The proximity quotient does not depend on the true anomaly, so its gradient is shared by all of the samples
+        let (mut best, mut worst) = (rate, rate);
+        for k in 0..EFFECTIVITY_SAMPLES {
+            let ν_k = TAU * k as f64 / EFFECTIVITY_SAMPLES as f64;
+            let (_, rate_k) = steepest_descent(&gradient, &gauss_rcn(&oe, ν_k, μ));
+            best = best.min(rate_k);
+            worst = worst.max(rate_k);
+        }
+        let effectivity = if best < worst {
+            (rate - worst) / (best - worst)
+        } else {
+            1.0
+        };
+        (direction, effectivity)
+    }
+}
+
+impl fmt::Display for QLaw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Q-law with {} objectives",
+            self.objectives.iter().flatten().count()
+        )?;
+        if self.η_threshold > 0.0 {
+            write!(f, " (effectivity threshold {})", self.η_threshold)?;
+        }
+        if let Some(rp_min_km) = self.rp_min_km {
+            write!(f, " (minimum periapsis {rp_min_km} km)")?;
+        }
+        Ok(())
+    }
+}
+
+impl GuidanceLaw for QLaw {
+    /// Returns whether the guidance law has achieved all goals
+    fn achieved(&self, state: &Spacecraft) -> Result<bool, NyxError> {
+        objectives_achieved(&self.objectives, &state.orbit)
+    }
+
+    fn direction(&self, sc: &Spacecraft) -> Vector3<f64> {
+        if sc.mode() == GuidanceMode::Thrust {
+            let (direction, _) = self.steering(&sc.orbit);
+            if direction.norm() > 0.0 {
+                // Convert to inertial -- the steering is computed in the RCN frame
+                sc.orbit.dcm_from_traj_frame(Frame::RCN).unwrap() * direction
+            } else {
+                direction
+            }
+        } else {
+            Vector3::zeros()
+        }
+    }
+
+    // Either thrust full power when effective or not at all
+    fn throttle(&self, sc: &Spacecraft) -> f64 {
+        if sc.mode() == GuidanceMode::Thrust {
+            let (direction, effectivity) = self.steering(&sc.orbit);
+            if direction.norm() > 0.0 && effectivity >= self.η_threshold {
+                1.0
+            } else {
+                0.0
+            }
+        } else {
+            0.0
+        }
+    }
+
+    /// Update the state for the next iteration
+    fn next(&self, sc: &mut Spacecraft) {
+        next_mode(self, sc)
+    }
+}
+
+/// Lyapunov defines a basic Lyapunov feedback guidance, which steers the thrust to decrease the fastest the Lyapunov
+/// function V = ½ Σ W (δoe / scale)², where δoe is the error of each targeted Keplerian element. The scale of the SMA is
+/// its target, and that of the other elements is one (angles in radians).
+///
+/// Unlike the Q-law, the errors are not normalized by the maximum rates of change of the elements, so this guidance is
+/// simpler but less fuel efficient, and it always thrusts until all of the objectives are achieved.
+///
+/// NOTE: the AoP cannot be targeted on circular orbits, nor the RAAN on equatorial orbits.
+/// WARNING: Objectives must be in degrees!
+#[derive(Copy, Clone, Debug)]
+pub struct Lyapunov {
+    /// Objectives, in the order of the SMA, eccentricity, inclination, RAAN and AoP
+    pub objectives: [Option<Objective>; 5],
+    /// Weights of each element in the Lyapunov function, in the same order as the objectives
+    pub weights: [f64; 5],
+}
+
+impl Lyapunov {
+    /// Creates a new Lyapunov feedback guidance with unit weights, as an Arc to be plugged into the spacecraft dynamics
+    pub fn new(objectives: &[Objective]) -> Result<Arc<Self>, NyxError> {
+        Self::with_weights(objectives, &[1.0; 5])
+    }
+
+    /// Creates a new Lyapunov feedback guidance with the weight of each objective, as an Arc to be plugged into the
+    /// spacecraft dynamics
+    pub fn with_weights(objectives: &[Objective], weights: &[f64]) -> Result<Arc<Self>, NyxError> {
+        if objectives.len() > weights.len() {
+            return Err(NyxError::GuidanceConfigError(format!(
+                "Must provide at least {} weights, provided {}",
+                objectives.len(),
+                weights.len()
+            )));
+        }
+        let sorted = sort_objectives(objectives, "Lyapunov")?;
+        let mut sorted_weights = [0.0; 5];
+        for (obj, weight) in objectives.iter().zip(weights) {
+            let idx = ELEMENTS.iter().position(|p| *p == obj.parameter).unwrap();
+            sorted_weights[idx] = *weight;
+        }
+        Ok(Arc::new(Self {
+            objectives: sorted,
+            weights: sorted_weights,
+        }))
+    }
+
+    /// Returns the value of the Lyapunov function at the provided orbit
+    pub fn value(&self, osc: &Orbit) -> f64 {
+        let errors = element_errors(&self.objectives, &elements(osc));
+        let gradient = self.gradient(&errors);
+        0.5 * errors
+            .iter()
+            .zip(gradient.iter())
+            .map(|(error, grad)| error * grad)
+            .sum::<f64>()
+    }
+
+    /// Gradient of the Lyapunov function with respect to the elements
+    fn gradient(&self, errors: &[f64; 5]) -> [f64; 5] {
+        let mut gradient = [0.0; 5];
+        for (idx, obj) in self.objectives.iter().enumerate() {
+            if let Some(obj) = obj {
+                let scale = if idx == 0 { obj.desired_value } else { 1.0 };
+                gradient[idx] = self.weights[idx] * errors[idx] / scale.powi(2);
+            }
+        }
+        gradient
+    }
+
+    /// Returns the thrust direction in the RCN frame
+    pub fn steering(&self, osc: &Orbit) -> Vector3<f64> {
+        let oe = elements(osc);
+        let gradient = self.gradient(&element_errors(&self.objectives, &oe));
+        let b = gauss_rcn(&oe, osc.ta_deg().to_radians(), osc.frame.gm());
+        steepest_descent(&gradient, &b).0
+    }
+}
+
+impl fmt::Display for Lyapunov {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Lyapunov feedback with {} objectives",
+            self.objectives.iter().flatten().count()
+        )
+    }
+}
+
+impl GuidanceLaw for Lyapunov {
+    /// Returns whether the guidance law has achieved all goals
+    fn achieved(&self, state: &Spacecraft) -> Result<bool, NyxError> {
+        objectives_achieved(&self.objectives, &state.orbit)
+    }
+
+    fn direction(&self, sc: &Spacecraft) -> Vector3<f64> {
+        if sc.mode() == GuidanceMode::Thrust {
+            let direction = self.steering(&sc.orbit);
+            if direction.norm() > 0.0 {
+                // Convert to inertial -- the steering is computed in the RCN frame
+                sc.orbit.dcm_from_traj_frame(Frame::RCN).unwrap() * direction
+            } else {
+                direction
+            }
+        } else {
+            Vector3::zeros()
+        }
+    }
+
+    // Either thrust full power or not at all
+    fn throttle(&self, sc: &Spacecraft) -> f64 {
+        if sc.mode() == GuidanceMode::Thrust && self.steering(&sc.orbit).norm() > 0.0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Update the state for the next iteration
+    fn next(&self, sc: &mut Spacecraft) {
+        next_mode(self, sc)
+    }
+}
+
+#[test]
+fn feedback_tangential_sma() {
+    use crate::cosmic::Cosm;
+    use crate::time::Epoch;
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 1e-4, 28.5, 10.0, 20.0, 30.0, start_time, eme2k);
+
+    // Raising (or lowering) the SMA of a nearly circular orbit is done by thrusting along (or against) the velocity
+    for (target, sign) in [(8000.0, 1.0), (6800.0, -1.0)] {
+        let objectives = &[Objective::within_tolerance(
+            StateParameter::SMA,
+            target,
+            1.0,
+        )];
+        let qlaw = QLaw::new(objectives).unwrap();
+        let lyapunov = Lyapunov::new(objectives).unwrap();
+        let expected = Vector3::new(0.0, sign, 0.0);
+        let (qlaw_dir, effectivity) = qlaw.steering(&orbit);
+        // The maximum rate of change of the SMA depends on the eccentricity, so the Q-law also steers slightly radially
+        assert!(qlaw_dir.dot(&expected) > 0.99, "{qlaw_dir}");
+        assert_eq!(effectivity, 1.0);
+        let lyapunov_dir = lyapunov.steering(&orbit);
+        assert!((lyapunov_dir - expected).norm() < 1e-3, "{lyapunov_dir}");
+    }
+
+    // Changing the inclination is mostly done by thrusting out of plane
+    let objectives = &[Objective::within_tolerance(
+        StateParameter::Inclination,
+        30.0,
+        1e-2,
+    )];
+    let (qlaw_dir, _) = QLaw::new(objectives).unwrap().steering(&orbit);
+    assert!(qlaw_dir[2].abs() > 0.99, "{qlaw_dir}");
+
+    // Only supported elements may be targeted, at most once each
+    assert!(QLaw::new(&[Objective::within_tolerance(
+        StateParameter::Periapsis,
+        1.0,
+        1.0
+    )])
+    .is_err());
+    assert!(Lyapunov::new(&[
+        Objective::within_tolerance(StateParameter::SMA, 8000.0, 1.0),
+        Objective::within_tolerance(StateParameter::SMA, 9000.0, 1.0)
+    ])
+    .is_err());
+}
@@ -27,6 +27,17 @@ use crate::State;
 use hifitime::{Duration, TimeUnits};
 use std::fmt;
 
+/// Steering law of a finite burn, i.e. how the in-plane (pitch) and out-of-plane (yaw) polynomials of a maneuver are interpreted.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Steering {
+    /// The polynomials are the angles themselves, in radians
+    #[default]
+    Polynomial,
+    /// The polynomials are the tangents of the angles, i.e. tan α(t) = a t + b when linear.
+    /// This is the linear tangent law, optimal for a burn in a uniform gravity field and commonly used for ascent and insertion burns.
+    LinearTangent,
+}
+
 /// Mnvr defined a single maneuver. Direction MUST be in the VNC frame (Velocity / Normal / Cross).
 /// It may be used with a maneuver scheduler.
 #[derive(Copy, Clone, Debug)]
@@ -45,6 +56,8 @@ pub struct Mnvr {
     pub delta_outofplane_radians: CommonPolynomial,
     /// The frame in which the maneuvers are defined.
     pub frame: Frame,
+    /// The steering law of the in-plane and out-of-plane polynomials
+    pub steering: Steering,
 }
 
 impl fmt::Display for Mnvr {
@@ -62,11 +75,18 @@ impl fmt::Display for Mnvr {
                 self.end - self.start,
                 self.end,
             )?;
-            write!(
-                f,
-                "\n\tin-plane angle α: {}\n\tout-of-plane angle β: {}",
-                self.alpha_inplane_radians, self.delta_outofplane_radians
-            )?;
+            match self.steering {
+                Steering::Polynomial => write!(
+                    f,
+                    "\n\tin-plane angle α: {}\n\tout-of-plane angle β: {}",
+                    self.alpha_inplane_radians, self.delta_outofplane_radians
+                )?,
+                Steering::LinearTangent => write!(
+                    f,
+                    "\n\tin-plane angle tan α: {}\n\tout-of-plane angle tan β: {}",
+                    self.alpha_inplane_radians, self.delta_outofplane_radians
+                )?,
+            };
             write!(
                 f,
                 "\n\tinitial dir: [{:.6}, {:.6}, {:.6}]\n\tfinal dir  : [{:.6}, {:.6}, {:.6}]",
@@ -106,14 +126,52 @@ impl Mnvr {
             alpha_inplane_radians: CommonPolynomial::Constant(alpha),
             delta_outofplane_radians: CommonPolynomial::Constant(delta),
             frame,
+            steering: Steering::Polynomial,
         }
     }
 
-    /// Return the thrust vector computed at the provided epoch
-    pub fn vector(&self, epoch: Epoch) -> Vector3<f64> {
-        let t = (epoch - self.start).to_seconds();
+    /// Creates a finite burn steered with the linear tangent law: tan α(t) = a_α t + b_α and tan β(t) = a_β t + b_β, where t is in seconds since the start of the burn.
+    ///
+    /// The coefficients are provided as (rate, initial value) pairs, like a `CommonPolynomial::Linear`, and may be optimized by the targeter with `Vary::MnvrTanAlpha` and co.
+    pub fn from_linear_tangent(
+        start: Epoch,
+        end: Epoch,
+        thrust_lvl: f64,
+        tan_alpha: (f64, f64),
+        tan_delta: (f64, f64),
+        frame: Frame,
+    ) -> Self {
+        Self {
+            start,
+            end,
+            thrust_prct: thrust_lvl,
+            alpha_inplane_radians: CommonPolynomial::Linear(tan_alpha.0, tan_alpha.1),
+            delta_outofplane_radians: CommonPolynomial::Linear(tan_delta.0, tan_delta.1),
+            frame,
+            steering: Steering::LinearTangent,
+        }
+    }
+
+    /// Switches this maneuver to the linear tangent steering law in the VNC frame, such that null coefficients thrust along the velocity.
+    /// This is used by the targeter when varying the linear tangent coefficients.
+    pub(crate) fn set_linear_tangent(&mut self) {
+        self.steering = Steering::LinearTangent;
+        self.frame = Frame::VNC;
+    }
+
+    /// Returns the in-plane and out-of-plane angles, in radians, at the provided number of seconds since the start of the burn
+    pub fn angles(&self, t: f64) -> (f64, f64) {
         let alpha = self.alpha_inplane_radians.eval(t);
         let delta = self.delta_outofplane_radians.eval(t);
+        match self.steering {
+            Steering::Polynomial => (alpha, delta),
+            Steering::LinearTangent => (alpha.atan(), delta.atan()),
+        }
+    }
+
+    /// Return the thrust vector computed at the provided epoch
+    pub fn vector(&self, epoch: Epoch) -> Vector3<f64> {
+        let (alpha, delta) = self.angles((epoch - self.start).to_seconds());
         unit_vector_from_ra_dec(alpha, delta)
     }
 
@@ -129,8 +187,7 @@ impl Mnvr {
 
     /// Returns the direction of the burn at the start of the burn, useful for setting new angles
     pub fn direction(&self) -> Vector3<f64> {
-        let alpha = self.alpha_inplane_radians.coeff_in_order(0).unwrap();
-        let delta = self.delta_outofplane_radians.coeff_in_order(0).unwrap();
+        let (alpha, delta) = self.angles(0.0);
         unit_vector_from_ra_dec(alpha, delta)
     }
 
@@ -171,13 +228,19 @@ impl Mnvr {
         self.set_direction_and_rates(self.direction(), self.rate(), accel)
     }
 
-    /// Set the initial direction, direction rate, and direction acceleration for this finite burn
+    /// Set the initial direction, direction rate, and direction acceleration for this finite burn.
+    /// This is only supported for polynomial steering.
     pub fn set_direction_and_rates(
         &mut self,
         dir: Vector3<f64>,
         rate: Vector3<f64>,
         accel: Vector3<f64>,
     ) -> Result<(), NyxError> {
+        if self.steering != Steering::Polynomial {
+            return Err(NyxError::CustomError(
+                "direction rates are only defined for polynomial steering, vary the linear tangent coefficients instead".to_string(),
+            ));
+        }
         let (alpha, delta) = ra_dec_from_unit_vector(dir);
         if alpha.is_nan() || delta.is_nan() {
             return Err(NyxError::MathDomain(format!(
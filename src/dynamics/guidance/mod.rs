@@ -21,11 +21,17 @@ use crate::errors::NyxError;
 use crate::linalg::Vector3;
 use serde::{Deserialize, Serialize};
 
+mod feedback;
+pub use feedback::{Lyapunov, QLaw};
+
 mod finiteburns;
 pub use finiteburns::FiniteBurns;
 
 mod mnvr;
-pub use mnvr::Mnvr;
+pub use mnvr::{Mnvr, Steering};
+
+mod power;
+pub use power::{PowerLimited, PowerModel};
 
 mod ruggiero;
 pub use ruggiero::{Objective, Ruggiero, StateParameter};
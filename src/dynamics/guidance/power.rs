@@ -0,0 +1,181 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::GuidanceLaw;
+use crate::cosmic::eclipse::EclipseLocator;
+use crate::cosmic::{Spacecraft, AU};
+use crate::errors::NyxError;
+use crate::linalg::Vector3;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// A simple power model of a solar electric propulsion spacecraft.
+///
+/// The solar arrays produce `array_power_1au_w` in full sunlight at 1 AU, scaled by the inverse square of the distance
+/// to the Sun and by the fraction of the solar disk visible from the spacecraft. The bus power is always served first,
+/// and the remainder is available to the thruster.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PowerModel {
+    /// Power generated by the solar arrays in full sunlight at 1 AU, in Watts
+    pub array_power_1au_w: f64,
+    /// Power always consumed by the rest of the spacecraft, in Watts
+    pub bus_power_w: f64,
+    /// Power required by the thruster to operate at full throttle, in Watts
+    pub thruster_power_w: f64,
+    /// Minimum throttle level of the thruster, below which it cannot operate and the spacecraft coasts
+    pub min_throttle: f64,
+}
+
+impl PowerModel {
+    /// Initializes a new power model, checking that the powers are positive and the minimum throttle is within [0; 1]
+    pub fn new(
+        array_power_1au_w: f64,
+        bus_power_w: f64,
+        thruster_power_w: f64,
+        min_throttle: f64,
+    ) -> Result<Self, NyxError> {
+        if array_power_1au_w <= 0.0 || thruster_power_w <= 0.0 || bus_power_w < 0.0 {
+            return Err(NyxError::CustomError(format!(
+                "power model requires positive array ({array_power_1au_w} W) and thruster ({thruster_power_w} W) powers and a non-negative bus power ({bus_power_w} W)"
+            )));
+        }
+        if !(0.0..=1.0).contains(&min_throttle) {
+            return Err(NyxError::CustomError(format!(
+                "minimum throttle must be within [0; 1] but got {min_throttle}"
+            )));
+        }
+        Ok(Self {
+            array_power_1au_w,
+            bus_power_w,
+            thruster_power_w,
+            min_throttle,
+        })
+    }
+
+    /// Returns the power generated by the solar arrays given the visible fraction of the Sun (0 in umbra, 1 in full light) and the distance to the Sun in AU
+    pub fn generated_power_w(&self, light_fraction: f64, sun_distance_au: f64) -> f64 {
+        self.array_power_1au_w * light_fraction.clamp(0.0, 1.0) / sun_distance_au.powi(2)
+    }
+
+    /// Returns the power left for the thruster once the bus is powered, in Watts
+    pub fn available_thruster_power_w(&self, light_fraction: f64, sun_distance_au: f64) -> f64 {
+        (self.generated_power_w(light_fraction, sun_distance_au) - self.bus_power_w).max(0.0)
+    }
+
+    /// Returns the maximum throttle level that the available power supports, or zero if that is below the minimum throttle
+    pub fn max_throttle(&self, light_fraction: f64, sun_distance_au: f64) -> f64 {
+        let throttle = (self.available_thruster_power_w(light_fraction, sun_distance_au)
+            / self.thruster_power_w)
+            .min(1.0);
+        if throttle < self.min_throttle || throttle <= 0.0 {
+            0.0
+        } else {
+            throttle
+        }
+    }
+}
+
+impl fmt::Display for PowerModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "arrays: {} W @ 1 AU\tbus: {} W\tthruster: {} W (min throttle {})",
+            self.array_power_1au_w, self.bus_power_w, self.thruster_power_w, self.min_throttle
+        )
+    }
+}
+
+/// Wraps another guidance law to coast during eclipses and to throttle down the thrust to the available solar power.
+///
+/// The thrust direction, the guidance mode switching, and the objectives are those of the inner guidance law.
+#[derive(Clone)]
+pub struct PowerLimited {
+    /// The guidance law whose throttle is limited by the available power
+    pub inner: Arc<dyn GuidanceLaw>,
+    /// The power model of the spacecraft
+    pub power: PowerModel,
+    /// Computes the fraction of the Sun visible from the spacecraft
+    pub eclipse_locator: EclipseLocator,
+}
+
+impl PowerLimited {
+    /// Limits the throttle of the provided guidance law with the power model, using the eclipse locator to find the shadows.
+    pub fn new(
+        inner: Arc<dyn GuidanceLaw>,
+        power: PowerModel,
+        eclipse_locator: EclipseLocator,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            inner,
+            power,
+            eclipse_locator,
+        })
+    }
+
+    /// Returns the fraction of the Sun visible from this spacecraft and its distance to the Sun in AU
+    pub fn illumination(&self, osc: &Spacecraft) -> (f64, f64) {
+        let light_fraction: f64 = self.eclipse_locator.compute(&osc.orbit).into();
+        let sun_distance_au = self
+            .eclipse_locator
+            .cosm
+            .frame_chg(&osc.orbit, self.eclipse_locator.light_source)
+            .rmag_km()
+            / AU;
+        (light_fraction, sun_distance_au)
+    }
+
+    /// Returns the maximum throttle level allowed by the power available at this spacecraft state
+    pub fn max_throttle(&self, osc: &Spacecraft) -> f64 {
+        let (light_fraction, sun_distance_au) = self.illumination(osc);
+        self.power.max_throttle(light_fraction, sun_distance_au)
+    }
+}
+
+impl fmt::Display for PowerLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} limited by power ({}) with {}",
+            self.inner, self.power, self.eclipse_locator
+        )
+    }
+}
+
+impl GuidanceLaw for PowerLimited {
+    fn direction(&self, osc: &Spacecraft) -> Vector3<f64> {
+        self.inner.direction(osc)
+    }
+
+    fn throttle(&self, osc: &Spacecraft) -> f64 {
+        let throttle = self.inner.throttle(osc);
+        if throttle > 0.0 {
+            throttle.min(self.max_throttle(osc))
+        } else {
+            throttle
+        }
+    }
+
+    fn next(&self, next_state: &mut Spacecraft) {
+        self.inner.next(next_state)
+    }
+
+    fn achieved(&self, osc: &Spacecraft) -> Result<bool, NyxError> {
+        self.inner.achieved(osc)
+    }
+}
@@ -18,7 +18,7 @@
 
 use crate::cosmic::Orbit;
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, DimName, Matrix3, OMatrix, OVector, Vector3};
+use crate::linalg::{DefaultAllocator, DimName, Matrix3, Matrix3x2, OMatrix, OVector, Vector3};
 use crate::State;
 use hyperdual::{OHyperdual, Owned};
 
@@ -48,9 +48,17 @@ pub use self::spacecraft::*;
 /// Defines a few examples of guidance laws.
 pub mod guidance;
 
+/// Define finite burns with time varying thrust and Isp, which deplete the fuel mass
+pub mod burns;
+pub use self::burns::*;
+
 /// Defines some velocity change controllers.
 pub mod deltavctrl;
 
+/// Define the N-plate macro-model of the spacecraft used by the non-gravitational forces
+pub mod plates;
+pub use self::plates::*;
+
 /// Defines solar radiation pressure models
 pub mod solarpressure;
 pub use self::solarpressure::*;
@@ -59,10 +67,42 @@ pub use self::solarpressure::*;
 pub mod drag;
 pub use self::drag::*;
 
+/// Define the albedo and infrared radiation pressure of planets
+pub mod albedo;
+pub use self::albedo::*;
+
 /// Define the spherical harmonic models.
 pub mod sph_harmonics;
 pub use self::sph_harmonics::*;
 
+/// Define the solid Earth and ocean tide corrections of the gravity field
+pub mod tides;
+pub use self::tides::*;
+
+/// Define the relativistic corrections of the acceleration, i.e. the Schwarzschild, Lense-Thirring and de Sitter terms
+pub mod relativity;
+pub use self::relativity::*;
+
+/// Define corrections to the gravitational parameter and spherical harmonics of a body, e.g. estimated from tracking data
+pub mod gravity_correction;
+pub use self::gravity_correction::*;
+
+/// Define interpolated force and acceleration profiles, e.g. from telemetry
+pub mod force_profile;
+pub use self::force_profile::*;
+
+/// Define the accelerometer model, which applies calibrated non-gravitational accelerations from telemetry
+pub mod accelerometer;
+pub use self::accelerometer::*;
+
+/// Define the automatic switching of the central body when crossing a sphere of influence
+pub mod soi;
+pub use self::soi::*;
+
+/// Define the stochastic accelerations, i.e. colored noise added to any dynamics
+pub mod stochastic;
+pub use self::stochastic::*;
+
 /// The `Dynamics` trait handles and stores any equation of motion *and* the state is integrated.
 ///
 /// Its design is such that several of the provided dynamics can be combined fairly easily. However,
@@ -126,6 +166,19 @@ where
     }
 }
 
+/// The `SecondOrderDynamics` trait marks orbital dynamics of the second order r'' = f(t, r), i.e. whose acceleration does not
+/// depend on the velocity. Only these dynamics may be integrated with a Runge-Kutta-Nyström (`RKN`) integrator, which only uses
+/// the acceleration computed by the equations of motion.
+pub trait SecondOrderDynamics: Dynamics<StateType = Orbit> {
+    /// Returns whether the acceleration of these dynamics depends on the velocity, e.g. because of a relativistic correction,
+    /// in which case the propagation with a symplectic or an RKN integrator returns an error.
+    fn depends_on_velocity(&self) -> bool;
+}
+
+/// The `SeparableHamiltonian` trait marks orbital dynamics whose Hamiltonian is separable, i.e. H(r, v, t) = T(v) + V(r, t):
+/// the acceleration only depends on the position and on time. Only these dynamics may be integrated with a `Symplectic` integrator.
+pub trait SeparableHamiltonian: SecondOrderDynamics {}
+
 /// The `ForceModel` trait handles immutable dynamics which return a force. Those will be divided by the mass of the spacecraft to compute the acceleration (F = ma).
 ///
 /// Examples include Solar Radiation Pressure, drag, etc., i.e. forces which do not need to save the current state, only act on it.
@@ -136,6 +189,14 @@ pub trait ForceModel: Send + Sync + fmt::Display {
     /// Force models must implement their partials, although those will only be called if the propagation requires the
     /// computation of the STM. The `osc_ctx` is the osculating context, i.e. it changes for each sub-step of the integrator.
     fn dual_eom(&self, osc_ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError>;
+
+    /// Returns the partials of the force with respect to the coefficient of reflectivity and the coefficient of drag (in that order).
+    /// These populate the parameter columns of the spacecraft STM, which are only those of Cr and Cd: the partials with respect to
+    /// the other model parameters (e.g. a GM or the thrust level) are computed by finite differences in `md::sensitivity`.
+    /// By default, the force is independent of both coefficients.
+    fn param_partials(&self, _osc_ctx: &Spacecraft) -> Result<Matrix3x2<f64>, NyxError> {
+        Ok(Matrix3x2::zeros())
+    }
 }
 
 /// The `AccelModel` trait handles immutable dynamics which return an acceleration. Those can be added directly to Orbital Dynamics for example.
@@ -148,4 +209,8 @@ pub trait AccelModel: Send + Sync + fmt::Display {
     /// Acceleration models must implement their partials, although those will only be called if the propagation requires the
     /// computation of the STM.
     fn dual_eom(&self, osc_ctx: &Orbit) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError>;
+
+    /// Returns whether this acceleration depends on the velocity. Such models break the separability of the Hamiltonian
+    /// required by the symplectic integrators, and the second order form r'' = f(t, r) required by the RKN integrators.
+    fn depends_on_velocity(&self) -> bool;
 }
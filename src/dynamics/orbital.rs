@@ -16,23 +16,33 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use super::{AccelModel, Dynamics, NyxError};
+use super::{
+    AccelModel, Dynamics, NyxError, SecondOrderDynamics, SeparableHamiltonian, SoiSwitching,
+};
 use crate::cosmic::{Bodies, Cosm, Frame, LightTimeCalc, Orbit};
 use crate::linalg::{Const, Matrix3, Matrix6, OVector, Vector3, Vector6};
+use crate::time::Epoch;
 use crate::State;
 use hyperdual::linalg::norm;
-use hyperdual::{extract_jacobian_and_result, hyperspace_from_vector, Float, OHyperdual};
+use hyperdual::{hyperspace_from_vector, Float, OHyperdual};
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::f64;
 use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+use super::relativity::Relativity;
 pub use super::sph_harmonics::Harmonics;
+use super::tides::Tides;
 
 /// `OrbitalDynamics` provides the equations of motion for any celestial dynamic, without state transition matrix computation.
 #[derive(Clone)]
 
 pub struct OrbitalDynamics {
     pub accel_models: Vec<Arc<dyn AccelModel + Sync>>,
+    /// Optionally switch the central body of the integration when crossing a sphere of influence
+    pub soi: Option<SoiSwitching>,
 }
 
 impl OrbitalDynamics {
@@ -49,7 +59,10 @@ impl OrbitalDynamics {
 
     /// Initialize orbital dynamics with a list of acceleration models
     pub fn new(accel_models: Vec<Arc<dyn AccelModel + Sync>>) -> Self {
-        Self { accel_models }
+        Self {
+            accel_models,
+            soi: None,
+        }
     }
 
     /// Initialize new orbital mechanics with the provided model.
@@ -69,19 +82,73 @@ impl OrbitalDynamics {
         me.add_model(accel_model);
         me
     }
+
+    /// Clone these dynamics and add the provided solid Earth and ocean tide corrections of the gravity field
+    pub fn with_tides(self, tides: Arc<Tides>) -> Self {
+        self.with_model(tides)
+    }
+
+    /// Clone these dynamics and add the provided relativistic corrections of the acceleration
+    pub fn with_relativity(self, relativity: Arc<Relativity>) -> Self {
+        self.with_model(relativity)
+    }
+
+    /// Returns the acceleration (km/s^2) of the central body and of each acceleration model at the provided state, named after
+    /// their display, e.g. to compare these dynamics with those of another tool.
+    pub fn accel_breakdown(&self, osc: &Orbit) -> Result<Vec<(String, Vector3<f64>)>, NyxError> {
+        let mut breakdown = Vec::with_capacity(self.accel_models.len() + 1);
+        breakdown.push((
+            format!("Two body of {}", osc.frame),
+            (-osc.frame.gm() / osc.rmag_km().powi(3)) * osc.radius(),
+        ));
+        for model in &self.accel_models {
+            breakdown.push((format!("{model}"), model.eom(osc)?));
+        }
+        Ok(breakdown)
+    }
+
+    /// Clone these dynamics and switch the central body of the integration when crossing the provided spheres of influence.
+    /// **Note:** the point masses must include all of the switching bodies, since the point mass of the central body is skipped.
+    pub fn with_soi_switching(self, soi: SoiSwitching) -> Self {
+        let mut me = self;
+        me.soi = Some(soi);
+        me
+    }
 }
 
 impl fmt::Display for OrbitalDynamics {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let models: Vec<String> = self.accel_models.iter().map(|x| format!("{x}")).collect();
-        write!(f, "Orbital dynamics: {}", models.join("; "))
+        match &self.soi {
+            Some(soi) => write!(f, "Orbital dynamics: {}; {soi}", models.join("; ")),
+            None => write!(f, "Orbital dynamics: {}", models.join("; ")),
+        }
     }
 }
 
+/// The gravitational acceleration models (point masses and spherical harmonics) only depend on the position and time, unlike
+/// the relativistic corrections and some custom acceleration models.
+impl SecondOrderDynamics for OrbitalDynamics {
+    fn depends_on_velocity(&self) -> bool {
+        self.accel_models
+            .iter()
+            .any(|model| model.depends_on_velocity())
+    }
+}
+
+impl SeparableHamiltonian for OrbitalDynamics {}
+
 impl Dynamics for OrbitalDynamics {
     type HyperdualSize = Const<7>;
     type StateType = Orbit;
 
+    fn finally(&self, next_state: Self::StateType) -> Result<Self::StateType, NyxError> {
+        match &self.soi {
+            Some(soi) => Ok(soi.recenter(next_state)),
+            None => Ok(next_state),
+        }
+    }
+
     fn eom(
         &self,
         delta_t_s: f64,
@@ -92,7 +159,7 @@ impl Dynamics for OrbitalDynamics {
         let (new_state, new_stm) = if ctx.stm.is_some() {
             let (state, grad) = self.dual_eom(delta_t_s, &osc)?;
 
-            let stm_dt = ctx.stm()? * grad;
+            let stm_dt = grad * osc.stm()?;
             // Rebuild the STM as a vector.
             let stm_as_vec = OVector::<f64, Const<36>>::from_column_slice(stm_dt.as_slice());
             (state, stm_as_vec)
@@ -175,6 +242,23 @@ impl Dynamics for OrbitalDynamics {
     }
 }
 
+/// Number of the most recent ephemeris lookups kept by each thread, i.e. those of the stages of a few integration steps
+const EPHEM_CACHE_SIZE: usize = 64;
+
+/// Identifier of the next point masses model, which keys its entries in the ephemeris caches
+static NEXT_CACHE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Model identifier, body, light-time correction, epoch and frame of an ephemeris lookup
+type EphemCacheKey = (usize, Frame, LightTimeCalc, Epoch, Frame);
+
+thread_local! {
+    /// Most recent positions of the bodies as seen from the center of the integration frame, such that the equations of motion
+    /// and their partials at the same epoch (or a rejected step) only query the ephemeris once. Each thread owns its cache, so
+    /// concurrent propagations (e.g. Monte Carlo runs) never wait on a lock.
+    static EPHEM_CACHE: RefCell<VecDeque<(EphemCacheKey, Vector3<f64>)>> =
+        RefCell::new(VecDeque::with_capacity(EPHEM_CACHE_SIZE));
+}
+
 /// PointMasses model
 pub struct PointMasses {
     pub bodies: Vec<Frame>,
@@ -182,6 +266,8 @@ pub struct PointMasses {
     pub cosm: Arc<Cosm>,
     /// Light-time correction computation if extra point masses are needed
     pub correction: LightTimeCalc,
+    /// Identifier of this model in the ephemeris caches
+    cache_id: usize,
 }
 
 impl PointMasses {
@@ -198,27 +284,72 @@ impl PointMasses {
             refs.push(cosm.frame_from_ephem_path(body.ephem_path()));
         }
 
-        Self {
-            bodies: refs,
-            cosm,
-            correction,
-        }
+        Self::from_frames(refs, cosm, correction)
     }
 
     /// Allows using bodies by name, defined in the non-default XB
     pub fn specific(body_names: &[String], cosm: Arc<Cosm>, correction: LightTimeCalc) -> Self {
+        Self::try_specific(body_names, cosm, correction).unwrap()
+    }
+
+    /// Allows using any body loaded in the Cosm by the name of one of its frames (e.g. "Jupiter Barycenter J2000"), returning
+    /// an error if a frame is not found
+    pub fn try_specific(
+        body_names: &[String],
+        cosm: Arc<Cosm>,
+        correction: LightTimeCalc,
+    ) -> Result<Self, NyxError> {
         let mut refs = Vec::with_capacity(body_names.len());
         // Check that these celestial bodies exist and build their references
         for body in body_names {
-            refs.push(cosm.frame(body));
+            refs.push(cosm.try_frame(body)?);
         }
 
+        Ok(Self::from_frames(refs, cosm, correction))
+    }
+
+    fn from_frames(bodies: Vec<Frame>, cosm: Arc<Cosm>, correction: LightTimeCalc) -> Self {
         Self {
-            bodies: refs,
+            bodies,
             cosm,
             correction,
+            cache_id: NEXT_CACHE_ID.fetch_add(1, Ordering::Relaxed),
         }
     }
+
+    /// Returns the position of the provided body as seen from the center of the integration frame, from the cache of this
+    /// thread if possible
+    fn body_position(
+        &self,
+        body: Frame,
+        epoch: Epoch,
+        frame: Frame,
+    ) -> Result<Vector3<f64>, NyxError> {
+        let key = (self.cache_id, body, self.correction, epoch, frame);
+        if let Some(r_ij) = EPHEM_CACHE.with(|cache| {
+            cache
+                .borrow()
+                .iter()
+                .find(|(cached_key, _)| *cached_key == key)
+                .map(|(_, r_ij)| *r_ij)
+        }) {
+            return Ok(r_ij);
+        }
+
+        let r_ij = self
+            .cosm
+            .try_celestial_state(&body.ephem_path(), epoch, frame, self.correction)?
+            .radius();
+
+        EPHEM_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if cache.len() == EPHEM_CACHE_SIZE {
+                cache.pop_front();
+            }
+            cache.push_back((key, r_ij));
+        });
+        Ok(r_ij)
+    }
 }
 
 impl fmt::Display for PointMasses {
@@ -233,20 +364,13 @@ impl AccelModel for PointMasses {
         let mut d_x = Vector3::zeros();
         // Get all of the position vectors between the center body and the third bodies
         for third_body in &self.bodies {
-            if third_body == &osc.frame {
+            if third_body.ephem_path() == osc.frame.ephem_path() {
                 // Ignore the contribution of the integration frame, that's handled by OrbitalDynamics
                 continue;
             }
-            // Orbit of j-th body as seen from primary body
-            let st_ij = self.cosm.celestial_state(
-                &third_body.ephem_path(),
-                osc.epoch,
-                osc.frame,
-                self.correction,
-            );
-
-            let r_ij = st_ij.radius();
-            let r_ij3 = st_ij.rmag_km().powi(3);
+            // Position of j-th body as seen from primary body
+            let r_ij = self.body_position(*third_body, osc.epoch, osc.frame)?;
+            let r_ij3 = r_ij.norm().powi(3);
             let r_j = osc.radius() - r_ij; // sc as seen from 3rd body
             let r_j3 = r_j.norm().powi(3);
             d_x += -third_body.gm() * (r_j / r_j3 + r_ij / r_ij3);
@@ -254,49 +378,36 @@ impl AccelModel for PointMasses {
         Ok(d_x)
     }
 
+    /// The partials are analytical: the indirect term (i.e. the acceleration of the primary body) does not depend on the
+    /// position of the spacecraft, so only the direct term contributes with -GM (I / r^3 - 3 r r^T / r^5).
     fn dual_eom(&self, osc: &Orbit) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
-        // Build the hyperdual space of the radius vector
-        let radius: Vector3<OHyperdual<f64, Const<7>>> = hyperspace_from_vector(&osc.radius());
-        // Extract result into Vector6 and Matrix6
         let mut fx = Vector3::zeros();
         let mut grad = Matrix3::zeros();
 
         // Get all of the position vectors between the center body and the third bodies
         for third_body in &self.bodies {
-            if third_body == &osc.frame {
+            if third_body.ephem_path() == osc.frame.ephem_path() {
                 // Ignore the contribution of the integration frame, that's handled by OrbitalDynamics
                 continue;
             }
-            let gm_d = OHyperdual::<f64, Const<7>>::from_real(-third_body.gm());
-
-            // Orbit of j-th body as seen from primary body
-            let st_ij = self.cosm.celestial_state(
-                &third_body.ephem_path(),
-                osc.epoch,
-                osc.frame,
-                self.correction,
-            );
-
-            let r_ij: Vector3<OHyperdual<f64, Const<7>>> = hyperspace_from_vector(&st_ij.radius());
-            let r_ij3 = norm(&r_ij).powi(3);
-
-            // The difference leads to the dual parts nulling themselves out, so let's fix that.
-            let mut r_j = radius - r_ij; // sc as seen from 3rd body
-            r_j[0][1] = 1.0;
-            r_j[1][2] = 1.0;
-            r_j[2][3] = 1.0;
+            let gm = third_body.gm();
 
-            let r_j3 = norm(&r_j).powi(3);
-            let mut third_body_acc_d = r_j / r_j3 + r_ij / r_ij3;
-            third_body_acc_d[0] *= gm_d;
-            third_body_acc_d[1] *= gm_d;
-            third_body_acc_d[2] *= gm_d;
+            // Position of j-th body as seen from primary body
+            let r_ij = self.body_position(*third_body, osc.epoch, osc.frame)?;
+            let r_ij3 = r_ij.norm().powi(3);
+            let r_j = osc.radius() - r_ij; // sc as seen from 3rd body
+            let r_j_mag = r_j.norm();
+            let r_j3 = r_j_mag.powi(3);
 
-            let (fxp, gradp) = extract_jacobian_and_result::<_, 3, 3, 7>(&third_body_acc_d);
-            fx += fxp;
-            grad += gradp;
+            fx += -gm * (r_j / r_j3 + r_ij / r_ij3);
+            grad +=
+                -gm * (Matrix3::identity() / r_j3 - 3.0 * r_j * r_j.transpose() / r_j_mag.powi(5));
         }
 
         Ok((fx, grad))
     }
+
+    fn depends_on_velocity(&self) -> bool {
+        false
+    }
 }
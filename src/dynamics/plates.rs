@@ -0,0 +1,230 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Frame, Orbit};
+use crate::errors::NyxError;
+use crate::io::ConfigRepr;
+use crate::linalg::{Matrix3, Vector3};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Orientation of the body frame of the plates, which follows one of the local orbital frames of the spacecraft.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BodyAttitude {
+    /// Body axes aligned with those of the integration frame
+    #[default]
+    Inertial,
+    /// Body axes along the velocity, the orbit normal and their cross product
+    VNC,
+    /// Body axes along the radius, the cross product of the normal and the radius, and the orbit normal
+    RCN,
+    /// Body axes along the radial, in-track and cross-track directions
+    RIC,
+}
+
+impl BodyAttitude {
+    /// Returns the rotation matrix from the body frame to the integration frame of the provided state
+    pub fn dcm_to_inertial(&self, orbit: &Orbit) -> Result<Matrix3<f64>, NyxError> {
+        match self {
+            Self::Inertial => Ok(Matrix3::identity()),
+            Self::VNC => orbit.dcm_from_traj_frame(Frame::VNC),
+            Self::RCN => orbit.dcm_from_traj_frame(Frame::RCN),
+            Self::RIC => orbit.dcm_from_traj_frame(Frame::RIC),
+        }
+    }
+}
+
+/// A flat plate of the macro-model, lit on the side of its outward normal only.
+///
+/// The incoming radiation is absorbed, reflected specularly or reflected diffusely, such that the absorption coefficient is
+/// `1 - specular - diffuse`.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Plate {
+    pub area_m2: f64,
+    /// Outward normal in the body frame, normalized when used
+    pub normal: [f64; 3],
+    /// Specular reflectivity coefficient
+    pub specular: f64,
+    /// Diffuse reflectivity coefficient
+    pub diffuse: f64,
+    /// Set to true for solar arrays whose normal points to the Sun regardless of the attitude
+    #[serde(default)]
+    pub sun_tracking: bool,
+}
+
+impl Plate {
+    pub fn new(area_m2: f64, normal: Vector3<f64>, specular: f64, diffuse: f64) -> Self {
+        Self {
+            area_m2,
+            normal: [normal[0], normal[1], normal[2]],
+            specular,
+            diffuse,
+            sun_tracking: false,
+        }
+    }
+
+    /// Initializes a solar array whose normal always points to the Sun
+    pub fn sun_tracking(area_m2: f64, specular: f64, diffuse: f64) -> Self {
+        Self {
+            area_m2,
+            normal: [0.0, 0.0, 1.0],
+            specular,
+            diffuse,
+            sun_tracking: true,
+        }
+    }
+
+    /// Returns the force per unit pressure (m^2) of the radiation propagating along the provided unit vector onto this plate,
+    /// whose outward normal is provided in the same frame.
+    pub fn radiation_force_m2(
+        &self,
+        normal: &Vector3<f64>,
+        incoming: &Vector3<f64>,
+    ) -> Vector3<f64> {
+        let cos_theta = -normal.dot(incoming);
+        if cos_theta <= 0.0 {
+            // Back side of the plate
+            return Vector3::zeros();
+        }
+        self.area_m2
+            * cos_theta
+            * ((1.0 - self.specular) * incoming
+                - 2.0 * (self.specular * cos_theta + self.diffuse / 3.0) * normal)
+    }
+}
+
+/// N-plate macro-model of the spacecraft, shared by the solar radiation pressure, the drag and the planetary radiation
+/// pressure in lieu of the cannonball area of the spacecraft.
+///
+/// With plates, the radiation pressure only depends on their optical coefficients, so the coefficient of reflectivity of the
+/// spacecraft is ignored, whereas the drag is that of the area projected along the flow with the drag coefficient of the
+/// spacecraft.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlateModel {
+    #[serde(default)]
+    pub attitude: BodyAttitude,
+    pub plates: Vec<Plate>,
+}
+
+impl ConfigRepr for PlateModel {}
+
+impl PlateModel {
+    /// Initializes a plate model, checking that the area and optical coefficients of each plate are physical
+    pub fn new(attitude: BodyAttitude, plates: Vec<Plate>) -> Result<Self, NyxError> {
+        let me = Self { attitude, plates };
+        me.validate()?;
+        Ok(me)
+    }
+
+    /// Checks that the model has plates, with non negative areas, non null normals and optical coefficients summing to at most one
+    pub fn validate(&self) -> Result<(), NyxError> {
+        if self.plates.is_empty() {
+            return Err(NyxError::CustomError(
+                "plate model has no plates".to_string(),
+            ));
+        }
+        for (no, plate) in self.plates.iter().enumerate() {
+            if plate.area_m2 < 0.0 {
+                return Err(NyxError::CustomError(format!(
+                    "plate #{no} has a negative area of {} m^2",
+                    plate.area_m2
+                )));
+            }
+            if !plate.sun_tracking && Vector3::from(plate.normal).norm() < f64::EPSILON {
+                return Err(NyxError::CustomError(format!("plate #{no} has no normal")));
+            }
+            if plate.specular < 0.0 || plate.diffuse < 0.0 || plate.specular + plate.diffuse > 1.0 {
+                return Err(NyxError::CustomError(format!(
+                    "plate #{no} has invalid optical coefficients: specular = {}, diffuse = {}",
+                    plate.specular, plate.diffuse
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the outward unit normals of the plates in the integration frame of the provided state, given the unit vector
+    /// from the spacecraft to the Sun for the sun tracking plates.
+    pub fn normals(
+        &self,
+        orbit: &Orbit,
+        sun_hat: &Vector3<f64>,
+    ) -> Result<Vec<Vector3<f64>>, NyxError> {
+        let dcm = self.attitude.dcm_to_inertial(orbit)?;
+        Ok(self
+            .plates
+            .iter()
+            .map(|plate| {
+                if plate.sun_tracking {
+                    *sun_hat
+                } else {
+                    let normal = dcm * Vector3::from(plate.normal);
+                    normal / normal.norm()
+                }
+            })
+            .collect())
+    }
+
+    /// Returns the total force per unit pressure (m^2) of the radiation propagating along the provided unit vector
+    pub fn radiation_force_m2(
+        &self,
+        orbit: &Orbit,
+        sun_hat: &Vector3<f64>,
+        incoming: &Vector3<f64>,
+    ) -> Result<Vector3<f64>, NyxError> {
+        Ok(self
+            .plates
+            .iter()
+            .zip(self.normals(orbit, sun_hat)?)
+            .map(|(plate, normal)| plate.radiation_force_m2(&normal, incoming))
+            .sum())
+    }
+
+    /// Returns the area (m^2) of the plates facing the provided unit direction, e.g. the ram area for the direction of the flow
+    pub fn projected_area_m2(
+        &self,
+        orbit: &Orbit,
+        sun_hat: &Vector3<f64>,
+        direction: &Vector3<f64>,
+    ) -> Result<f64, NyxError> {
+        Ok(self
+            .plates
+            .iter()
+            .zip(self.normals(orbit, sun_hat)?)
+            .map(|(plate, normal)| plate.area_m2 * normal.dot(direction).max(0.0))
+            .sum())
+    }
+
+    /// Returns true if any plate tracks the Sun
+    pub fn tracks_sun(&self) -> bool {
+        self.plates.iter().any(|plate| plate.sun_tracking)
+    }
+}
+
+impl fmt::Display for PlateModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} plate{} ({:.3} m^2) in {:?} attitude",
+            self.plates.len(),
+            if self.plates.len() == 1 { "" } else { "s" },
+            self.plates.iter().map(|plate| plate.area_m2).sum::<f64>(),
+            self.attitude
+        )
+    }
+}
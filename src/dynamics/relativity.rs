@@ -0,0 +1,204 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Bodies, Cosm, Frame, LightTimeCalc, Orbit, SPEED_OF_LIGHT_KMS};
+use crate::dynamics::AccelModel;
+use crate::errors::NyxError;
+use crate::linalg::{Const, Matrix3, Vector3};
+use hyperdual::linalg::norm;
+use hyperdual::{extract_jacobian_and_result, hyperspace_from_vector, Float, OHyperdual};
+use std::fmt;
+use std::sync::Arc;
+
+/// Angular momentum per unit mass of the Earth, in km^2/s (IERS 2010, section 10.3)
+pub const EARTH_ANGULAR_MOMENTUM_KM2_S: f64 = 9.8e2;
+
+/// Relativistic corrections of the acceleration of a spacecraft about a central body, in the parameterized post-Newtonian
+/// framework of general relativity (β = γ = 1), per IERS 2010, equation 10.12:
+/// + the Schwarzschild term, i.e. the first order post-Newtonian correction of the point mass of the central body;
+/// + the Lense-Thirring precession, i.e. the frame dragging due to the rotation of the central body;
+/// + the de Sitter (geodesic) precession, due to the motion of the central body about the Sun.
+///
+/// All terms are computed about the center of the integration frame. The Lense-Thirring term only applies when that center
+/// is the body of the compute frame, whose Z axis is its rotation axis, and the de Sitter term is skipped about the Sun.
+///
+/// **Note:** these corrections depend on the velocity of the spacecraft, but only their partials with respect to the
+/// position are returned, like for any other acceleration model.
+#[derive(Clone)]
+pub struct Relativity {
+    cosm: Arc<Cosm>,
+    compute_frame: Frame,
+    sun: Frame,
+    /// Angular momentum per unit mass of the body of the compute frame, in km^2/s
+    pub angular_momentum_km2_s: f64,
+    /// Whether to include the Schwarzschild term
+    pub schwarzschild: bool,
+    /// Whether to include the Lense-Thirring term
+    pub lense_thirring: bool,
+    /// Whether to include the de Sitter term
+    pub de_sitter: bool,
+}
+
+impl Relativity {
+    /// Initializes all of the relativistic corrections about the body of the provided body fixed frame (e.g. "IAU Earth"),
+    /// given its angular momentum per unit mass in km^2/s
+    pub fn new(compute_frame: Frame, angular_momentum_km2_s: f64, cosm: Arc<Cosm>) -> Self {
+        Self {
+            sun: cosm.frame_from_ephem_path(Bodies::Sun.ephem_path()),
+            cosm,
+            compute_frame,
+            angular_momentum_km2_s,
+            schwarzschild: true,
+            lense_thirring: true,
+            de_sitter: true,
+        }
+    }
+
+    /// Initializes all of the relativistic corrections about the Earth, as recommended by the IERS conventions
+    pub fn earth(cosm: Arc<Cosm>) -> Arc<Self> {
+        Arc::new(Self::new(
+            cosm.frame("IAU Earth"),
+            EARTH_ANGULAR_MOMENTUM_KM2_S,
+            cosm,
+        ))
+    }
+
+    /// Returns the angular momentum per unit mass of the central body in the integration frame, if the Lense-Thirring term applies
+    fn angular_momentum(&self, osc: &Orbit) -> Result<Option<Vector3<f64>>, NyxError> {
+        if !self.lense_thirring || osc.frame.ephem_path() != self.compute_frame.ephem_path() {
+            return Ok(None);
+        }
+        let dcm = self
+            .cosm
+            .try_position_dcm_from_to(&self.compute_frame, &osc.frame, osc.epoch)?;
+        Ok(Some(
+            self.angular_momentum_km2_s * dcm * Vector3::new(0.0, 0.0, 1.0),
+        ))
+    }
+
+    /// Returns the de Sitter precession rate vector (rad/s) in the integration frame, if the de Sitter term applies
+    fn de_sitter_rate(&self, osc: &Orbit) -> Result<Option<Vector3<f64>>, NyxError> {
+        if !self.de_sitter || osc.frame.ephem_path() == self.sun.ephem_path() {
+            return Ok(None);
+        }
+        // State of the Sun as seen from the central body
+        let sun = self.cosm.try_celestial_state(
+            &self.sun.ephem_path(),
+            osc.epoch,
+            osc.frame,
+            LightTimeCalc::None,
+        )?;
+        let c2 = SPEED_OF_LIGHT_KMS.powi(2);
+        Ok(Some(
+            3.0 * self.sun.gm() / (c2 * sun.rmag_km().powi(3))
+                * sun.radius().cross(&sun.velocity()),
+        ))
+    }
+}
+
+impl fmt::Display for Relativity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut terms = Vec::new();
+        if self.schwarzschild {
+            terms.push("Schwarzschild");
+        }
+        if self.lense_thirring {
+            terms.push("Lense-Thirring");
+        }
+        if self.de_sitter {
+            terms.push("de Sitter");
+        }
+        write!(
+            f,
+            "{} relativistic corrections ({})",
+            self.compute_frame,
+            terms.join(", ")
+        )
+    }
+}
+
+impl AccelModel for Relativity {
+    fn eom(&self, osc: &Orbit) -> Result<Vector3<f64>, NyxError> {
+        let c2 = SPEED_OF_LIGHT_KMS.powi(2);
+        let gm = osc.frame.gm();
+        let r = osc.radius();
+        let v = osc.velocity();
+        let rmag = r.norm();
+        let mut accel = Vector3::zeros();
+
+        if self.schwarzschild {
+            accel += gm / (c2 * rmag.powi(3))
+                * ((4.0 * gm / rmag - v.norm_squared()) * r + 4.0 * r.dot(&v) * v);
+        }
+
+        if let Some(j) = self.angular_momentum(osc)? {
+            accel += 2.0 * gm / (c2 * rmag.powi(3))
+                * (3.0 / rmag.powi(2) * r.cross(&v) * r.dot(&j) + v.cross(&j));
+        }
+
+        if let Some(omega) = self.de_sitter_rate(osc)? {
+            // The Sun-centered formulation of the IERS, with the position of the Sun as seen from the central body
+            accel += omega.cross(&v);
+        }
+
+        Ok(accel)
+    }
+
+    fn dual_eom(&self, osc: &Orbit) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+        type Dual = OHyperdual<f64, Const<4>>;
+        let c2 = SPEED_OF_LIGHT_KMS.powi(2);
+        let gm = osc.frame.gm();
+        let r: Vector3<Dual> = hyperspace_from_vector(&osc.radius());
+        let v: Vector3<Dual> = osc.velocity().map(Dual::from_real);
+        let rmag = norm(&r);
+        let mut accel: Vector3<Dual> = Vector3::zeros();
+
+        if self.schwarzschild {
+            let scale = Dual::from_real(gm / c2) / rmag.powi(3);
+            let coeff_r = Dual::from_real(4.0 * gm) / rmag - v.dot(&v);
+            let coeff_v = Dual::from_real(4.0) * r.dot(&v);
+            for i in 0..3 {
+                accel[i] += scale * (coeff_r * r[i] + coeff_v * v[i]);
+            }
+        }
+
+        if let Some(j) = self.angular_momentum(osc)? {
+            let j: Vector3<Dual> = j.map(Dual::from_real);
+            let scale = Dual::from_real(2.0 * gm / c2) / rmag.powi(3);
+            let coeff_h = Dual::from_real(3.0) / rmag.powi(2) * r.dot(&j);
+            let h = r.cross(&v);
+            let v_cross_j = v.cross(&j);
+            for i in 0..3 {
+                accel[i] += scale * (coeff_h * h[i] + v_cross_j[i]);
+            }
+        }
+
+        if let Some(omega) = self.de_sitter_rate(osc)? {
+            let omega_cross_v = omega.cross(&osc.velocity());
+            for i in 0..3 {
+                accel[i] += Dual::from_real(omega_cross_v[i]);
+            }
+        }
+
+        Ok(extract_jacobian_and_result::<_, 3, 3, 4>(&accel))
+    }
+
+    fn depends_on_velocity(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,112 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Cosm, Frame, LightTimeCalc, Orbit};
+use crate::time::Epoch;
+use std::fmt;
+use std::sync::Arc;
+
+/// Switches the central body of the integration when the state crosses a sphere of influence (SOI), such that the dynamics
+/// are always integrated with respect to the dominant body, without re-initializing the propagation at each boundary.
+///
+/// The central body is that of the smallest sphere of influence which contains the state, or the root body otherwise. The state
+/// is re-centered after each integration step, so the states of a propagation may be in different frames: use `to_frame` on
+/// the resulting trajectory to make it seamless in a single frame, prior to any interpolation or event search.
+///
+/// **Note:** the point masses of the dynamics should include all of these bodies, such that each of them remains accounted
+/// for as a third body when it is no longer the central body.
+#[derive(Clone)]
+pub struct SoiSwitching {
+    /// Integration frame used outside of all of the spheres of influence
+    pub root: Frame,
+    /// Integration frame centered on each body and the radius of its sphere of influence in km, sorted by increasing radius
+    pub bodies: Vec<(Frame, f64)>,
+    pub cosm: Arc<Cosm>,
+}
+
+impl SoiSwitching {
+    /// Initializes the switching with the integration frame used outside of all of the spheres of influence, e.g. "Sun J2000"
+    pub fn new(root: Frame, cosm: Arc<Cosm>) -> Self {
+        Self {
+            root,
+            bodies: Vec::new(),
+            cosm,
+        }
+    }
+
+    /// Adds the provided integration frame, centered on a body whose sphere of influence has the provided radius in km
+    pub fn with_body(mut self, frame: Frame, soi_radius_km: f64) -> Self {
+        self.bodies.push((frame, soi_radius_km));
+        self.bodies.sort_by(|a, b| a.1.total_cmp(&b.1));
+        self
+    }
+
+    /// Adds the provided integration frame, with the Laplace sphere of influence of its body with respect to the provided parent
+    /// (cf. `laplace_radius_km`) at the provided epoch.
+    pub fn with_laplace_body(self, frame: Frame, parent: Frame, epoch: Epoch) -> Self {
+        let radius_km = Self::laplace_radius_km(frame, parent, epoch, &self.cosm);
+        self.with_body(frame, radius_km)
+    }
+
+    /// Returns the Laplace radius of the sphere of influence of the body of the provided frame, a (m / M)^(2/5), where a is
+    /// the semi-major axis of its osculating orbit about its parent at the provided epoch, and m and M the masses of both.
+    pub fn laplace_radius_km(frame: Frame, parent: Frame, epoch: Epoch, cosm: &Cosm) -> f64 {
+        let orbit = cosm.celestial_state(&frame.ephem_path(), epoch, parent, LightTimeCalc::None);
+        orbit.sma_km() * (frame.gm() / parent.gm()).powf(0.4)
+    }
+
+    /// Returns the integration frame of the provided state, i.e. the one of the smallest sphere of influence containing it
+    pub fn central_frame(&self, orbit: &Orbit) -> Frame {
+        for (frame, radius_km) in &self.bodies {
+            let rel = if orbit.frame.ephem_path() == frame.ephem_path() {
+                *orbit
+            } else {
+                self.cosm.frame_chg(orbit, *frame)
+            };
+            if rel.rmag_km() <= *radius_km {
+                return *frame;
+            }
+        }
+        self.root
+    }
+
+    /// Returns the provided state in its integration frame, switching the central body if it crossed a sphere of influence
+    pub fn recenter(&self, orbit: Orbit) -> Orbit {
+        let frame = self.central_frame(&orbit);
+        if frame == orbit.frame {
+            orbit
+        } else {
+            info!(
+                "{} switching central body from {} to {}",
+                orbit.epoch, orbit.frame, frame
+            );
+            self.cosm.frame_chg(&orbit, frame)
+        }
+    }
+}
+
+impl fmt::Display for SoiSwitching {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bodies: Vec<String> = self
+            .bodies
+            .iter()
+            .map(|(frame, radius_km)| format!("{frame} within {radius_km:.0} km"))
+            .collect();
+        write!(f, "SOI switching: {} else {}", bodies.join(", "), self.root)
+    }
+}
@@ -16,11 +16,11 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use super::ForceModel;
-use crate::cosmic::eclipse::EclipseLocator;
+use super::{ForceModel, PlateModel};
+use crate::cosmic::eclipse::{EclipseLocator, ShadowModel};
 use crate::cosmic::{Cosm, Frame, Spacecraft, AU, SPEED_OF_LIGHT};
 use crate::errors::NyxError;
-use crate::linalg::{Const, Matrix3, Vector3};
+use crate::linalg::{Const, Matrix3, Matrix3x2, Vector3};
 use hyperdual::{hyperspace_from_vector, linalg::norm, Float, OHyperdual};
 use std::fmt;
 use std::sync::Arc;
@@ -31,6 +31,10 @@ pub struct SolarPressure {
     /// solar flux at 1 AU, in W/m^2
     pub phi: f64,
     pub e_loc: EclipseLocator,
+    /// Shadow model of the eclipse locator, conical by default
+    pub shadow_model: ShadowModel,
+    /// Plates of the spacecraft, used instead of its SRP area and coefficient of reflectivity if set
+    pub plates: Option<Arc<PlateModel>>,
 }
 
 impl SolarPressure {
@@ -41,7 +45,12 @@ impl SolarPressure {
             shadow_bodies,
             cosm,
         };
-        Self { phi: 1367.0, e_loc }
+        Self {
+            phi: 1367.0,
+            e_loc,
+            shadow_model: ShadowModel::Conical,
+            plates: None,
+        }
     }
 
     /// Accounts for the shadowing of only one body and will set the solar flux at 1 AU to: Phi = 1367.0
@@ -49,6 +58,26 @@ impl SolarPressure {
         Arc::new(Self::default_raw(vec![shadow_body], cosm))
     }
 
+    /// Accounts for the shadowing of both the Earth and the Moon, e.g. for cislunar missions
+    pub fn cislunar(cosm: Arc<Cosm>) -> Arc<Self> {
+        Arc::new(Self::default_raw(
+            vec![cosm.frame("EME2000"), cosm.frame("Moon J2000")],
+            cosm,
+        ))
+    }
+
+    /// Sets the shadow model used to compute the illumination
+    pub fn with_shadow_model(mut self, shadow_model: ShadowModel) -> Self {
+        self.shadow_model = shadow_model;
+        self
+    }
+
+    /// Computes the SRP on the provided plates instead of the cannonball
+    pub fn with_plates(mut self, plates: Arc<PlateModel>) -> Self {
+        self.plates = Some(plates);
+        self
+    }
+
     /// Must provide the flux in W/m^2
     pub fn with_flux(flux_w_m2: f64, shadow_bodies: Vec<Frame>, cosm: Arc<Cosm>) -> Arc<Self> {
         let mut me = Self::default_raw(shadow_bodies, cosm);
@@ -70,17 +99,28 @@ impl ForceModel for SolarPressure {
         let r_sun_unit = r_sun / r_sun.norm();
 
         // Compute the shaddowing factor.
-        let k: f64 = self.e_loc.compute(osc).into();
+        let k = self.e_loc.light_fraction(osc, self.shadow_model);
 
         let r_sun_au = r_sun.norm() / AU;
         // in N/(m^2)
         let flux_pressure = (k * self.phi / SPEED_OF_LIGHT) * (1.0 / r_sun_au).powi(2);
 
         // Note the 1e-3 is to convert the SRP from m/s^2 to km/s^2
-        Ok(1e-3 * ctx.srp.cr * ctx.srp.area_m2 * flux_pressure * r_sun_unit)
+        match &self.plates {
+            Some(plates) => {
+                let force_m2 = plates.radiation_force_m2(osc, &-r_sun_unit, &r_sun_unit)?;
+                Ok(1e-3 * flux_pressure * force_m2)
+            }
+            None => Ok(1e-3 * ctx.srp.cr * ctx.srp.area_m2 * flux_pressure * r_sun_unit),
+        }
     }
 
     fn dual_eom(&self, ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+        if self.plates.is_some() {
+            // The partials of the plates with respect to the position, through the Sun direction and the attitude, are neglected
+            return Ok((self.eom(ctx)?, Matrix3::zeros()));
+        }
+
         let osc = &ctx.orbit;
 
         // Compute the position of the Sun as seen from the spacecraft
@@ -94,7 +134,7 @@ impl ForceModel for SolarPressure {
         let r_sun_unit = r_sun_d / norm(&r_sun_d);
 
         // Compute the shadowing factor.
-        let k: f64 = self.e_loc.compute(osc).into();
+        let k = self.e_loc.light_fraction(osc, self.shadow_model);
 
         let r_sun_au = norm(&r_sun_d) / AU;
         let inv_r_sun_au = OHyperdual::<f64, Const<9>>::from_real(1.0) / (r_sun_au);
@@ -124,6 +164,19 @@ impl ForceModel for SolarPressure {
 
         Ok((dx, grad))
     }
+
+    fn param_partials(&self, ctx: &Spacecraft) -> Result<Matrix3x2<f64>, NyxError> {
+        if self.plates.is_some() {
+            // The coefficient of reflectivity is not used by the plates
+            return Ok(Matrix3x2::zeros());
+        }
+        // The SRP force is linear in the coefficient of reflectivity, so its partial is the force for a unit C_r.
+        let mut unit_cr_ctx = *ctx;
+        unit_cr_ctx.srp.cr = 1.0;
+        let mut partials = Matrix3x2::zeros();
+        partials.set_column(0, &self.eom(&unit_cr_ctx)?);
+        Ok(partials)
+    }
 }
 
 impl fmt::Display for SolarPressure {
@@ -132,6 +185,10 @@ impl fmt::Display for SolarPressure {
             f,
             "SRP with φ = {} W/m^2 and eclipse {}",
             self.phi, self.e_loc
-        )
+        )?;
+        if let Some(plates) = &self.plates {
+            write!(f, " on {plates}")?;
+        }
+        Ok(())
     }
 }
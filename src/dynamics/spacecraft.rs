@@ -16,9 +16,10 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use super::burns::FiniteBurn;
 use super::guidance::GuidanceLaw;
 use super::orbital::OrbitalDynamics;
-use super::{AccelModel, Dynamics, ForceModel};
+use super::{AccelModel, Dynamics, ForceModel, PlanetaryRadiation};
 pub use crate::cosmic::{GuidanceMode, Spacecraft, STD_GRAVITY};
 use crate::errors::NyxError;
 use crate::io::dynamics::DynamicsSerde;
@@ -59,6 +60,8 @@ pub struct SpacecraftDynamics {
     pub orbital_dyn: OrbitalDynamics,
     pub force_models: Vec<Arc<dyn ForceModel>>,
     pub guid_law: Option<Arc<dyn GuidanceLaw>>,
+    /// Finite burns applied on top of the guidance law, if any
+    pub burns: Vec<FiniteBurn>,
     pub decrement_mass: bool,
 }
 
@@ -70,6 +73,7 @@ impl SpacecraftDynamics {
             orbital_dyn,
             guid_law: Some(guid_law),
             force_models: Vec::new(),
+            burns: Vec::new(),
             decrement_mass: true,
         }
     }
@@ -84,6 +88,7 @@ impl SpacecraftDynamics {
             orbital_dyn,
             guid_law: Some(guid_law),
             force_models: Vec::new(),
+            burns: Vec::new(),
             decrement_mass: false,
         }
     }
@@ -94,6 +99,7 @@ impl SpacecraftDynamics {
             orbital_dyn,
             guid_law: None,
             force_models: Vec::new(),
+            burns: Vec::new(),
             decrement_mass: true,
         }
     }
@@ -104,6 +110,7 @@ impl SpacecraftDynamics {
             orbital_dyn,
             guid_law: None,
             force_models: vec![force_model],
+            burns: Vec::new(),
             decrement_mass: true,
         }
     }
@@ -130,6 +137,99 @@ impl SpacecraftDynamics {
         me
     }
 
+    /// Clone these dynamics and add the provided finite burns
+    pub fn with_burns(self, burns: Vec<FiniteBurn>) -> Self {
+        let mut me = self;
+        me.burns.extend(burns);
+        me.burns.sort_by_key(|burn| burn.start);
+        me
+    }
+
+    /// Returns the thrust force (in kg km/s^2, like the force models) and the fuel mass rate (kg/s) of the finite burns
+    /// active at the provided state, evaluated `delta_t_s` after the start of the integration step
+    fn burns_thrust(
+        &self,
+        osc_sc: &Spacecraft,
+        delta_t_s: f64,
+    ) -> Result<(Vector3<f64>, f64), NyxError> {
+        let mut force = Vector3::zeros();
+        let mut fuel_rate = 0.0;
+        for burn in &self.burns {
+            let (burn_force, burn_fuel_rate) = burn.thrust(osc_sc, delta_t_s > 0.0)?;
+            force += burn_force;
+            if self.decrement_mass {
+                fuel_rate += burn_fuel_rate;
+            }
+        }
+        Ok((force, fuel_rate))
+    }
+
+    /// Returns the thrust force (in kg km/s^2, like the force models) and the fuel mass rate (kg/s) of the guidance law at
+    /// the provided state, if any
+    fn thrust(&self, osc_sc: &Spacecraft) -> Result<(Vector3<f64>, f64), NyxError> {
+        let guid_law = match &self.guid_law {
+            Some(guid_law) => guid_law,
+            None => return Ok((Vector3::zeros(), 0.0)),
+        };
+        if osc_sc.thruster.is_none() {
+            return Err(NyxError::NoThrusterAvail);
+        }
+        let thruster = osc_sc.thruster.unwrap();
+        let thrust_throttle_lvl = guid_law.throttle(osc_sc);
+        if !(0.0..=1.0).contains(&thrust_throttle_lvl) {
+            Err(NyxError::CtrlThrottleRangeErr(thrust_throttle_lvl))
+        } else if thrust_throttle_lvl > 0.0 {
+            // Thrust arc
+            let thrust_inertial = guid_law.direction(osc_sc);
+            if (thrust_inertial.norm() - 1.0).abs() > NORM_ERR {
+                Err(NyxError::CtrlNotAUnitVector(thrust_inertial.norm()))
+            } else if thrust_inertial.norm().is_normal() {
+                // Compute the thrust in Newtons and Isp
+                let total_thrust = (thrust_throttle_lvl * thruster.thrust_N) * 1e-3; // Convert m/s^-2 to km/s^-2
+                Ok((
+                    thrust_inertial * total_thrust,
+                    if self.decrement_mass {
+                        let fuel_usage = thrust_throttle_lvl * thruster.thrust_N
+                            / (thruster.isp_s * STD_GRAVITY);
+                        -fuel_usage
+                    } else {
+                        0.0
+                    },
+                ))
+            } else {
+                warn!(
+                    "Abnormal thrust direction vector\t|u| = {}",
+                    thrust_inertial.norm()
+                );
+                Ok((Vector3::zeros(), 0.0))
+            }
+        } else {
+            Ok((Vector3::zeros(), 0.0))
+        }
+    }
+
+    /// Returns the acceleration (km/s^2) of the central body, of each acceleration model, of each force model and of the
+    /// thrust at the provided state, named after their display. Their sum is the acceleration of these dynamics, so this
+    /// breakdown helps debugging discrepancies with the dynamics of other tools.
+    pub fn accel_breakdown(
+        &self,
+        state: &Spacecraft,
+    ) -> Result<Vec<(String, Vector3<f64>)>, NyxError> {
+        let mut breakdown = self.orbital_dyn.accel_breakdown(&state.orbit)?;
+        for model in &self.force_models {
+            breakdown.push((format!("{model}"), model.eom(state)? / state.mass_kg()));
+        }
+        if self.guid_law.is_some() {
+            let (thrust_force, _) = self.thrust(state)?;
+            breakdown.push(("Thrust".to_string(), thrust_force / state.mass_kg()));
+        }
+        for burn in &self.burns {
+            let (burn_force, _) = burn.thrust(state, false)?;
+            breakdown.push((format!("{burn}"), burn_force / state.mass_kg()));
+        }
+        Ok(breakdown)
+    }
+
     /// A shortcut to spacecraft.guid_law if a guidance law is defined for these dynamics
     pub fn guidance_achieved(&self, state: &Spacecraft) -> Result<bool, NyxError> {
         match &self.guid_law {
@@ -144,6 +244,7 @@ impl SpacecraftDynamics {
             orbital_dyn: self.orbital_dyn.clone(),
             guid_law: Some(guid_law),
             force_models: self.force_models.clone(),
+            burns: self.burns.clone(),
             decrement_mass: self.decrement_mass,
         }
     }
@@ -154,6 +255,7 @@ impl SpacecraftDynamics {
             orbital_dyn: self.orbital_dyn.clone(),
             guid_law: Some(guid_law),
             force_models: self.force_models.clone(),
+            burns: self.burns.clone(),
             decrement_mass: false,
         }
     }
@@ -164,6 +266,7 @@ impl SpacecraftDynamics {
             orbital_dyn: self.orbital_dyn.clone(),
             guid_law: None,
             force_models: self.force_models.clone(),
+            burns: self.burns.clone(),
             decrement_mass: self.decrement_mass,
         }
     }
@@ -252,11 +355,14 @@ impl fmt::Display for SpacecraftDynamics {
         };
         write!(
             f,
-            "Spacecraft dynamics (with guidance = {}): {} {}",
+            "Spacecraft dynamics (with guidance = {}): {} ",
             self.guid_law.is_some(),
             force_models,
-            self.orbital_dyn
-        )
+        )?;
+        if !self.burns.is_empty() {
+            write!(f, "{} finite burn(s); ", self.burns.len())?;
+        }
+        write!(f, "{}", self.orbital_dyn)
     }
 }
 
@@ -265,6 +371,9 @@ impl Dynamics for SpacecraftDynamics {
     type StateType = Spacecraft;
 
     fn finally(&self, next_state: Self::StateType) -> Result<Self::StateType, NyxError> {
+        let mut next_state = next_state;
+        next_state.orbit = self.orbital_dyn.finally(next_state.orbit)?;
+
         if next_state.fuel_mass_kg < 0.0 {
             error!("negative fuel mass at {}", next_state.epoch());
             return Err(NyxError::FuelExhausted(Box::new(next_state)));
@@ -295,7 +404,7 @@ impl Dynamics for SpacecraftDynamics {
             let (state, grad) = self.dual_eom(delta_t, &osc_sc)?;
 
             // Apply the gradient to the STM
-            let stm_dt = ctx.stm()? * grad;
+            let stm_dt = grad * osc_sc.stm()?;
 
             // Rebuild the state vectors
             for (i, val) in state.iter().enumerate() {
@@ -328,50 +437,22 @@ impl Dynamics for SpacecraftDynamics {
         }
 
         // Now include the control as needed.
-        if let Some(guid_law) = &self.guid_law {
-            let (thrust_force, fuel_rate) = {
-                if osc_sc.thruster.is_none() {
-                    return Err(NyxError::NoThrusterAvail);
-                }
-                let thruster = osc_sc.thruster.unwrap();
-                let thrust_throttle_lvl = guid_law.throttle(&osc_sc);
-                if !(0.0..=1.0).contains(&thrust_throttle_lvl) {
-                    return Err(NyxError::CtrlThrottleRangeErr(thrust_throttle_lvl));
-                } else if thrust_throttle_lvl > 0.0 {
-                    // Thrust arc
-                    let thrust_inertial = guid_law.direction(&osc_sc);
-                    if (thrust_inertial.norm() - 1.0).abs() > NORM_ERR {
-                        return Err(NyxError::CtrlNotAUnitVector(thrust_inertial.norm()));
-                    } else if thrust_inertial.norm().is_normal() {
-                        // Compute the thrust in Newtons and Isp
-                        let total_thrust = (thrust_throttle_lvl * thruster.thrust_N) * 1e-3; // Convert m/s^-2 to km/s^-2
-                        (
-                            thrust_inertial * total_thrust,
-                            if self.decrement_mass {
-                                let fuel_usage = thrust_throttle_lvl * thruster.thrust_N
-                                    / (thruster.isp_s * STD_GRAVITY);
-                                -fuel_usage
-                            } else {
-                                0.0
-                            },
-                        )
-                    } else {
-                        warn!(
-                            "Abnormal thrust direction vector\t|u| = {}",
-                            thrust_inertial.norm()
-                        );
-                        (Vector3::zeros(), 0.0)
-                    }
-                } else {
-                    (Vector3::zeros(), 0.0)
-                }
-            };
-
+        if self.guid_law.is_some() {
+            let (thrust_force, fuel_rate) = self.thrust(&osc_sc)?;
             for i in 0..3 {
                 d_x[i + 3] += thrust_force[i] / osc_sc.mass_kg();
             }
             d_x[8] += fuel_rate;
         }
+
+        if !self.burns.is_empty() && ctx.orbit.stm.is_none() {
+            // With the STM, the burns are included in the dual EOM
+            let (burn_force, fuel_rate) = self.burns_thrust(&osc_sc, delta_t)?;
+            for i in 0..3 {
+                d_x[i + 3] += burn_force[i] / osc_sc.mass_kg();
+            }
+            d_x[8] += fuel_rate;
+        }
         Ok(d_x)
     }
 
@@ -406,6 +487,7 @@ impl Dynamics for SpacecraftDynamics {
         let total_mass = ctx.mass_kg();
         for model in &self.force_models {
             let (model_frc, model_grad) = model.dual_eom(ctx)?;
+            let model_param_grad = model.param_partials(ctx)?;
             for i in 0..3 {
                 // Add the velocity changes
                 d_x[i + 3] += model_frc[i] / total_mass;
@@ -413,9 +495,24 @@ impl Dynamics for SpacecraftDynamics {
                 for j in 1..4 {
                     grad[(i + 3, j - 1)] += model_grad[(i, j - 1)] / total_mass;
                 }
+                // Add the partials with respect to Cr and Cd
+                grad[(i + 3, 6)] += model_param_grad[(i, 0)] / total_mass;
+                grad[(i + 3, 7)] += model_param_grad[(i, 1)] / total_mass;
+                // Add the partial with respect to the fuel mass (a = F/m)
+                grad[(i + 3, 8)] -= model_frc[i] / total_mass.powi(2);
             }
         }
 
+        if !self.burns.is_empty() {
+            // The finite burns only have partials with respect to the fuel mass: those of their direction are neglected
+            let (burn_force, fuel_rate) = self.burns_thrust(ctx, delta_t_s)?;
+            for i in 0..3 {
+                d_x[i + 3] += burn_force[i] / total_mass;
+                grad[(i + 3, 8)] -= burn_force[i] / total_mass.powi(2);
+            }
+            d_x[8] += fuel_rate;
+        }
+
         Ok((d_x, grad))
     }
 }
@@ -455,7 +552,12 @@ impl Configurable for SpacecraftDynamics {
                     .try_frame(&hh.frame)
                     .map_err(|e| ConfigError::InvalidConfig(e.to_string()))?;
 
-                accel_models.push(Harmonics::from_stor(frame, stor, cosm.clone()));
+                accel_models.push(Harmonics::from_stor_with_bands(
+                    frame,
+                    stor,
+                    hh.bands,
+                    cosm.clone(),
+                ));
             }
         }
 
@@ -463,13 +565,40 @@ impl Configurable for SpacecraftDynamics {
 
         let mut force_models: Vec<Arc<dyn ForceModel>> = Vec::new();
 
+        let plates = match cfg.plates {
+            Some(plates) => {
+                plates
+                    .validate()
+                    .map_err(|e| ConfigError::InvalidConfig(e.to_string()))?;
+                Some(Arc::new(plates))
+            }
+            None => None,
+        };
+
         // SRP
         if let Some(srp) = cfg.srp {
-            force_models.push(SolarPressure::with_flux(
-                srp.phi.map_or(1367.0, |v| v),
-                srp.shadows,
+            let mut srp_model = SolarPressure::default_raw(srp.shadows, cosm.clone());
+            srp_model.phi = srp.phi.map_or(1367.0, |v| v);
+            srp_model.plates = plates.clone();
+            force_models.push(Arc::new(srp_model));
+        }
+
+        // Albedo and infrared
+        if let Some(radiation) = cfg.planetary_radiation {
+            let frame = cosm
+                .try_frame(&radiation.frame)
+                .map_err(|e| ConfigError::InvalidConfig(e.to_string()))?;
+            let mut radiation_model = PlanetaryRadiation::from_distribution(
+                frame,
+                radiation.distribution,
+                radiation.grid,
                 cosm,
-            ));
+            );
+            radiation_model.phi = radiation.phi.map_or(1367.0, |v| v);
+            radiation_model.albedo = radiation.albedo;
+            radiation_model.infrared = radiation.infrared;
+            radiation_model.plates = plates;
+            force_models.push(Arc::new(radiation_model));
         }
 
         // TODO: Drag -- https://github.com/nyx-space/nyx/issues/86
@@ -23,10 +23,47 @@ use crate::io::gravity::HarmonicsMem;
 use crate::linalg::{DMatrix, Matrix3, Vector3, U7};
 use hyperdual::linalg::norm;
 use hyperdual::{hyperspace_from_vector, Float, OHyperdual};
+use serde::{Deserialize, Serialize};
 use std::cmp::min;
 use std::fmt;
 use std::sync::Arc;
 
+/// The degree and order of the gravity field evaluated up to a radial distance.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FidelityBand {
+    /// Radial distance up to which this band applies, in km
+    pub max_radius_km: f64,
+    pub degree: usize,
+    pub order: usize,
+}
+
+impl FidelityBand {
+    pub fn new(max_radius_km: f64, degree: usize, order: usize) -> Self {
+        Self {
+            max_radius_km,
+            degree,
+            order,
+        }
+    }
+
+    /// Initializes a band which applies up to the provided altitude above the equatorial radius of the provided frame
+    pub fn below_altitude(frame: Frame, altitude_km: f64, degree: usize, order: usize) -> Self {
+        Self::new(frame.equatorial_radius() + altitude_km, degree, order)
+    }
+
+    /// Typical bands of a 70x70 field about the Earth: 70x70 below 3000 km of altitude, 30x30 below 10,000 km of altitude,
+    /// 12x12 below ten Earth radii and 8x8 beyond. The neglected terms are then below about 1e-12 km/s^2.
+    pub fn earth_defaults(frame: Frame) -> Vec<Self> {
+        let eq_radius_km = frame.equatorial_radius();
+        vec![
+            Self::below_altitude(frame, 3_000.0, 70, 70),
+            Self::below_altitude(frame, 10_000.0, 30, 30),
+            Self::new(10.0 * eq_radius_km, 12, 12),
+            Self::new(f64::INFINITY, 8, 8),
+        ]
+    }
+}
+
 #[derive(Clone)]
 pub struct Harmonics {
     cosm: Arc<Cosm>,
@@ -42,11 +79,28 @@ pub struct Harmonics {
     c_nm_h: DMatrix<OHyperdual<f64, U7>>,
     vr01_h: DMatrix<OHyperdual<f64, U7>>,
     vr11_h: DMatrix<OHyperdual<f64, U7>>,
+    bands: Vec<FidelityBand>,
 }
 
 impl Harmonics {
     /// Create a new Harmonics dynamical model from the provided gravity potential storage instance.
     pub fn from_stor(compute_frame: Frame, stor: HarmonicsMem, cosm: Arc<Cosm>) -> Arc<Self> {
+        Self::from_stor_with_bands(compute_frame, stor, Vec::new(), cosm)
+    }
+
+    /// Create a new Harmonics dynamical model whose evaluated degree and order depend on the radial distance, such that the
+    /// high degree terms, which decrease as (R/r)^n, are only evaluated close to the body. This speeds up the propagation of
+    /// highly eccentric orbits and transfers, which spend most of their time far from the body, without loss of accuracy.
+    ///
+    /// The first band whose maximum radius is above the radial distance applies, or the last band beyond all of them. The
+    /// degree and order of each band are bounded by those of the storage.
+    pub fn from_stor_with_bands(
+        compute_frame: Frame,
+        stor: HarmonicsMem,
+        mut bands: Vec<FidelityBand>,
+        cosm: Arc<Cosm>,
+    ) -> Arc<Self> {
+        bands.sort_by(|a, b| a.max_radius_km.total_cmp(&b.max_radius_km));
         assert!(
             compute_frame.is_geoid(),
             "harmonics only work around geoids"
@@ -131,8 +185,23 @@ impl Harmonics {
             c_nm_h,
             vr01_h,
             vr11_h,
+            bands,
         })
     }
+
+    /// Returns the degree and order evaluated at the provided radial distance
+    pub fn degree_order(&self, radius_km: f64) -> (usize, usize) {
+        let (max_degree, max_order) = (self.stor.max_degree_n(), self.stor.max_order_m());
+        match self
+            .bands
+            .iter()
+            .find(|band| radius_km <= band.max_radius_km)
+            .or(self.bands.last())
+        {
+            Some(band) => (band.degree.min(max_degree), band.order.min(max_order)),
+            None => (max_degree, max_order),
+        }
+    }
 }
 
 impl fmt::Display for Harmonics {
@@ -143,7 +212,11 @@ impl fmt::Display for Harmonics {
             self.compute_frame,
             self.stor.max_order_m(),
             self.stor.max_degree_n(),
-        )
+        )?;
+        if !self.bands.is_empty() {
+            write!(f, " adaptive over {} bands", self.bands.len())?;
+        }
+        Ok(())
     }
 }
 
@@ -157,11 +230,12 @@ impl AccelModel for Harmonics {
         let s_ = state.x_km / r_;
         let t_ = state.y_km / r_;
         let u_ = state.z_km / r_;
-        let max_degree = self.stor.max_degree_n(); // In GMAT, the degree is NN
-        let max_order = self.stor.max_order_m(); // In GMAT, the order is MM
+        // In GMAT, the degree is NN and the order is MM
+        let (max_degree, max_order) = self.degree_order(r_);
 
         // Create the associated Legendre polynomials. Note that we add three items as per GMAT (this may be useful for the STM)
-        let mut a_nm = self.a_nm.clone();
+        let size = min(max_degree + 3, self.a_nm.nrows());
+        let mut a_nm = self.a_nm.view((0, 0), (size, size)).clone_owned();
 
         // Initialize the diagonal elements (not a function of the input)
         a_nm[(1, 0)] = u_ * 3.0f64.sqrt();
@@ -251,11 +325,12 @@ impl AccelModel for Harmonics {
         let s_ = radius[0] / r_;
         let t_ = radius[1] / r_;
         let u_ = radius[2] / r_;
-        let max_degree = self.stor.max_degree_n(); // In GMAT, the order is NN
-        let max_order = self.stor.max_order_m(); // In GMAT, the order is MM
+        // In GMAT, the degree is NN and the order is MM
+        let (max_degree, max_order) = self.degree_order(r_.real());
 
         // Create the associated Legendre polynomials. Note that we add three items as per GMAT (this may be useful for the STM)
-        let mut a_nm = self.a_nm_h.clone();
+        let size = min(max_degree + 3, self.a_nm_h.nrows());
+        let mut a_nm = self.a_nm_h.view((0, 0), (size, size)).clone_owned();
 
         // Initialize the diagonal elements (not a function of the input)
         a_nm[(1, 0)] = u_ * 3.0f64.sqrt();
@@ -335,24 +410,8 @@ impl AccelModel for Harmonics {
             .cosm
             .try_position_dcm_from_to(&self.compute_frame, &osc.frame, osc.epoch)?;
 
-        // Convert DCM to OHyperdual DCMs
-        let mut dcm_d = Matrix3::<OHyperdual<f64, U7>>::zeros();
-        for i in 0..3 {
-            for j in 0..3 {
-                dcm_d[(i, j)] = OHyperdual::from_fn(|k| {
-                    if k == 0 {
-                        dcm[(i, j)]
-                    } else if i + 1 == k {
-                        1.0
-                    } else {
-                        0.0
-                    }
-                })
-            }
-        }
-
-        let accel = dcm_d * Vector3::new(a0 + a3 * s_, a1 + a3 * t_, a2 + a3 * u_);
-        // Extract data
+        let accel = Vector3::new(a0 + a3 * s_, a1 + a3 * t_, a2 + a3 * u_);
+        // Extract data in the compute frame
         let mut dx = Vector3::zeros();
         let mut grad = Matrix3::zeros();
         for i in 0..3 {
@@ -362,6 +421,11 @@ impl AccelModel for Harmonics {
                 grad[(i, j - 1)] += accel[i][j];
             }
         }
-        Ok((dx, grad))
+        // Rotate the acceleration and its partials with respect to the position back into the integration frame
+        Ok((dcm * dx, dcm * grad * dcm.transpose()))
+    }
+
+    fn depends_on_velocity(&self) -> bool {
+        false
     }
 }
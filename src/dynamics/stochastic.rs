@@ -0,0 +1,388 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::Dynamics;
+use crate::cosmic::Frame;
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, OMatrix, OVector, Vector3};
+use crate::md::trajectory::Interpolatable;
+use crate::od::noise::GaussMarkov;
+use crate::time::{Duration, Epoch};
+use crate::State;
+use hyperdual::{OHyperdual, Owned};
+use rand::{RngCore, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use rand_pcg::Pcg64Mcg;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// The `RandomProcess` trait defines the scalar random processes used to model each axis of a `StochasticAccel`.
+///
+/// The processes are sampled sequentially on a fixed grid, and must not store any state: the previous value is provided on each draw.
+pub trait RandomProcess: Send + Sync + fmt::Display {
+    /// Draws the initial value of this process
+    fn initial(&self, rng: &mut dyn RngCore) -> f64;
+
+    /// Draws the value of this process after the provided step from its previous value
+    fn next(&self, previous: f64, step: Duration, rng: &mut dyn RngCore) -> f64;
+}
+
+/// Samples the first order Gauss-Markov process exactly as `GaussMarkov::next_bias` does.
+impl RandomProcess for GaussMarkov {
+    fn initial(&self, rng: &mut dyn RngCore) -> f64 {
+        let sigma = if self.is_white() {
+            self.steady_state_sigma
+        } else {
+            self.bias_sigma
+        };
+        Normal::new(0.0, sigma).unwrap().sample(rng)
+    }
+
+    fn next(&self, previous: f64, step: Duration, rng: &mut dyn RngCore) -> f64 {
+        let ss_contrib = Normal::new(0.0, self.steady_state_sigma)
+            .unwrap()
+            .sample(rng);
+        if self.is_white() {
+            return ss_contrib;
+        }
+
+        let decay = (-step.to_seconds() / self.tau.to_seconds()).exp();
+        previous * decay + ss_contrib * (1.0 - decay)
+    }
+}
+
+/// A random walk, i.e. the integral of a white noise: its variance grows linearly with time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RandomWalk {
+    /// Standard deviation of the initial value
+    pub initial_sigma: f64,
+    /// Standard deviation of the growth of the process after one second, i.e. per square root of second
+    pub rate_sigma: f64,
+}
+
+impl RandomWalk {
+    /// Initializes a random walk which starts at zero
+    pub fn new(rate_sigma: f64) -> Self {
+        Self {
+            initial_sigma: 0.0,
+            rate_sigma,
+        }
+    }
+}
+
+impl fmt::Display for RandomWalk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Random walk with σ_0 = {}, σ_rate = {} /√s",
+            self.initial_sigma, self.rate_sigma
+        )
+    }
+}
+
+impl RandomProcess for RandomWalk {
+    fn initial(&self, rng: &mut dyn RngCore) -> f64 {
+        Normal::new(0.0, self.initial_sigma).unwrap().sample(rng)
+    }
+
+    fn next(&self, previous: f64, step: Duration, rng: &mut dyn RngCore) -> f64 {
+        previous
+            + Normal::new(0.0, self.rate_sigma * step.to_seconds().abs().sqrt())
+                .unwrap()
+                .sample(rng)
+    }
+}
+
+/// Realization of the stochastic acceleration, drawn lazily and kept so that the integrator may evaluate earlier epochs again.
+///
+/// The grid is two-sided: the forward accelerations are those of the intervals from the start onward, and the backward accelerations
+/// those of the intervals before the start, latest first. Each side is drawn from its own generator, starting from the acceleration of
+/// the first forward interval, so a realization does not depend on the order in which the epochs are evaluated.
+#[derive(Clone)]
+struct Realization {
+    forward_rng: Pcg64Mcg,
+    backward_rng: Pcg64Mcg,
+    start: Option<Epoch>,
+    forward: Vec<Vector3<f64>>,
+    backward: Vec<Vector3<f64>>,
+}
+
+impl Realization {
+    fn new(seed: u64) -> Self {
+        Self {
+            forward_rng: Pcg64Mcg::seed_from_u64(seed),
+            backward_rng: Pcg64Mcg::seed_from_u64(!seed),
+            start: None,
+            forward: Vec::new(),
+            backward: Vec::new(),
+        }
+    }
+}
+
+/// `StochasticAccel` is a colored noise acceleration, where each axis follows its own random process (e.g. Gauss-Markov or random walk).
+///
+/// The acceleration is constant over each update interval: the processes are sampled on a grid starting at the first epoch where the
+/// acceleration is evaluated, i.e. the start of the propagation, and spanning both directions of time. A given seed always yields the same
+/// realization, including in the clones, which start their own realization.
+pub struct StochasticAccel {
+    /// Random process of each axis of the acceleration frame, in km/s^2
+    pub processes: [Arc<dyn RandomProcess>; 3],
+    /// Frame of the acceleration, either the integration frame (`Frame::Inertial`) or a local frame of the trajectory (e.g. `Frame::RIC`)
+    pub frame: Frame,
+    /// Duration during which the acceleration is constant
+    pub update_interval: Duration,
+    /// Seed of the random number generator of the realization
+    pub seed: u64,
+    realization: Mutex<Realization>,
+}
+
+impl StochasticAccel {
+    /// Initializes a stochastic acceleration from the random process of each axis of the provided frame.
+    pub fn new(
+        processes: [Arc<dyn RandomProcess>; 3],
+        frame: Frame,
+        update_interval: Duration,
+        seed: u64,
+    ) -> Result<Self, NyxError> {
+        if update_interval <= Duration::ZERO {
+            return Err(NyxError::CustomError(format!(
+                "stochastic acceleration update interval must be positive but got {update_interval}"
+            )));
+        }
+        Ok(Self {
+            processes,
+            frame,
+            update_interval,
+            seed,
+            realization: Mutex::new(Realization::new(seed)),
+        })
+    }
+
+    /// Initializes a stochastic acceleration where all three axes follow independent draws of the same random process.
+    pub fn isotropic(
+        process: Arc<dyn RandomProcess>,
+        frame: Frame,
+        update_interval: Duration,
+        seed: u64,
+    ) -> Result<Self, NyxError> {
+        Self::new(
+            [process.clone(), process.clone(), process],
+            frame,
+            update_interval,
+            seed,
+        )
+    }
+
+    /// Returns a copy of this stochastic acceleration with a new realization drawn from the provided seed
+    pub fn with_seed(&self, seed: u64) -> Self {
+        Self {
+            processes: self.processes.clone(),
+            frame: self.frame,
+            update_interval: self.update_interval,
+            seed,
+            realization: Mutex::new(Realization::new(seed)),
+        }
+    }
+
+    /// Returns the acceleration at the provided epoch in the acceleration frame, in km/s^2.
+    /// The first call sets the start of the realization.
+    pub fn acceleration(&self, epoch: Epoch) -> Vector3<f64> {
+        let mut realization = self.realization.lock().unwrap();
+        let start = *realization.start.get_or_insert(epoch);
+        // Signed index of the update interval, where the interval just before the start is -1
+        let idx = (epoch - start)
+            .total_nanoseconds()
+            .div_euclid(self.update_interval.total_nanoseconds());
+
+        let Realization {
+            forward_rng,
+            backward_rng,
+            forward,
+            backward,
+            ..
+        } = &mut *realization;
+
+        if forward.is_empty() {
+            forward.push(Vector3::from_fn(|i, _| {
+                self.processes[i].initial(forward_rng)
+            }));
+        }
+
+        if idx >= 0 {
+            let idx = idx as usize;
+            while forward.len() <= idx {
+                let previous = forward[forward.len() - 1];
+                forward.push(Vector3::from_fn(|i, _| {
+                    self.processes[i].next(previous[i], self.update_interval, forward_rng)
+                }));
+            }
+            forward[idx]
+        } else {
+            let idx = (-idx - 1) as usize;
+            while backward.len() <= idx {
+                let previous = *backward.last().unwrap_or(&forward[0]);
+                backward.push(Vector3::from_fn(|i, _| {
+                    self.processes[i].next(previous[i], self.update_interval, backward_rng)
+                }));
+            }
+            backward[idx]
+        }
+    }
+
+    /// Returns the acceleration in the integration frame, in km/s^2, at the epoch of the provided state
+    fn inertial_acceleration<S: Interpolatable>(&self, state: &S) -> Result<Vector3<f64>, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        let accel = self.acceleration(state.epoch());
+        if matches!(self.frame, Frame::Inertial) {
+            Ok(accel)
+        } else {
+            Ok(state.orbit().dcm_from_traj_frame(self.frame)? * accel)
+        }
+    }
+}
+
+impl Clone for StochasticAccel {
+    fn clone(&self) -> Self {
+        self.with_seed(self.seed)
+    }
+}
+
+impl fmt::Debug for StochasticAccel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl fmt::Display for StochasticAccel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Stochastic acceleration in {:?} updated every {} (seed {}): [{}, {}, {}]",
+            self.frame,
+            self.update_interval,
+            self.seed,
+            self.processes[0],
+            self.processes[1],
+            self.processes[2]
+        )
+    }
+}
+
+/// `StochasticDynamics` wraps any orbital or spacecraft dynamics and adds a stochastic acceleration to their equations of motion,
+/// e.g. to study the growth of the uncertainty due to unmodeled accelerations, beyond the dispersion of the initial state.
+///
+/// The stochastic acceleration does not contribute to the partials. Use a fixed step which divides its update interval to avoid
+/// integrating across the discontinuities of the acceleration.
+#[derive(Clone, Debug)]
+pub struct StochasticDynamics<D: Dynamics>
+where
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>,
+{
+    /// The deterministic dynamics
+    pub dynamics: D,
+    /// The stochastic acceleration added to the deterministic dynamics
+    pub accel: StochasticAccel,
+}
+
+impl<D: Dynamics> StochasticDynamics<D>
+where
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>,
+{
+    pub fn new(dynamics: D, accel: StochasticAccel) -> Self {
+        Self { dynamics, accel }
+    }
+
+    /// Returns a copy of these dynamics with a new realization of the stochastic acceleration, e.g. for each run of a Monte Carlo
+    pub fn with_seed(&self, seed: u64) -> Self {
+        Self {
+            dynamics: self.dynamics.clone(),
+            accel: self.accel.with_seed(seed),
+        }
+    }
+}
+
+impl<D: Dynamics> Dynamics for StochasticDynamics<D>
+where
+    D::StateType: Interpolatable,
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, D::HyperdualSize>
+        + Allocator<OHyperdual<f64, D::HyperdualSize>, <D::StateType as State>::Size>,
+    Owned<f64, D::HyperdualSize>: Copy,
+{
+    type HyperdualSize = D::HyperdualSize;
+    type StateType = D::StateType;
+
+    fn eom(
+        &self,
+        delta_t: f64,
+        state_vec: &OVector<f64, <Self::StateType as State>::VecLength>,
+        state_ctx: &Self::StateType,
+    ) -> Result<OVector<f64, <Self::StateType as State>::VecLength>, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, <Self::StateType as State>::VecLength>,
+    {
+        let mut d_x = self.dynamics.eom(delta_t, state_vec, state_ctx)?;
+        let osc = state_ctx.set_with_delta_seconds(delta_t, state_vec);
+        let accel = self.accel.inertial_acceleration(&osc)?;
+        for i in 0..3 {
+            d_x[i + 3] += accel[i];
+        }
+        Ok(d_x)
+    }
+
+    fn dual_eom(
+        &self,
+        delta_t: f64,
+        osculating_state: &Self::StateType,
+    ) -> Result<
+        (
+            OVector<f64, <Self::StateType as State>::Size>,
+            OMatrix<f64, <Self::StateType as State>::Size, <Self::StateType as State>::Size>,
+        ),
+        NyxError,
+    >
+    where
+        DefaultAllocator: Allocator<f64, Self::HyperdualSize>
+            + Allocator<f64, <Self::StateType as State>::Size>
+            + Allocator<f64, <Self::StateType as State>::Size, <Self::StateType as State>::Size>
+            + Allocator<OHyperdual<f64, Self::HyperdualSize>, <Self::StateType as State>::Size>,
+        Owned<f64, Self::HyperdualSize>: Copy,
+    {
+        let (mut d_x, grad) = self.dynamics.dual_eom(delta_t, osculating_state)?;
+        let accel = self.accel.inertial_acceleration(osculating_state)?;
+        for i in 0..3 {
+            d_x[i + 3] += accel[i];
+        }
+        Ok((d_x, grad))
+    }
+
+    fn finally(&self, next_state: Self::StateType) -> Result<Self::StateType, NyxError> {
+        self.dynamics.finally(next_state)
+    }
+}
@@ -0,0 +1,358 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::sph_harmonics::Harmonics;
+use crate::cosmic::{Bodies, Cosm, Frame, LightTimeCalc, Orbit};
+use crate::dynamics::AccelModel;
+use crate::errors::NyxError;
+use crate::io::gravity::HarmonicsMem;
+use crate::linalg::{DMatrix, Matrix3, Vector3};
+use crate::time::Epoch;
+use std::f64::consts::{PI, TAU};
+use std::fmt;
+use std::fs::read_to_string;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Anelastic Love numbers of degree 2 for orders 0, 1 and 2: real part, imaginary part and k+ (IERS 2010, table 6.3)
+#[allow(clippy::approx_constant)] // k22 happens to be close to log10(2)
+const LOVE_K2: [(f64, f64, f64); 3] = [
+    (0.30190, 0.0, -0.00089),
+    (0.29830, -0.00144, -0.00080),
+    (0.30102, -0.00130, -0.00057),
+];
+/// Elastic Love numbers of degree 3 for orders 0 through 3 (IERS 2010, table 6.3)
+const LOVE_K3: [f64; 4] = [0.093, 0.093, 0.093, 0.094];
+/// Permanent part of the degree 2 zonal tide, A0 H0 (IERS 2010, equation 6.13), to be scaled by k20
+const PERMANENT_TIDE_C20: f64 = 4.4228e-8 * -0.31460;
+/// Scale of the coefficients of IERS ocean tide files, e.g. FES2004
+const OCEAN_TIDE_FILE_SCALE: f64 = 1e-11;
+
+/// A constituent of an ocean tide model, as the prograde and retrograde normalized corrections of the C_nm and S_nm of a
+/// given degree and order (IERS 2010, equation 6.15).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OceanTide {
+    /// Multipliers of the six Doodson arguments (τ, s, h, p, N', ps)
+    pub doodson: [i8; 6],
+    pub degree: usize,
+    pub order: usize,
+    pub c_plus: f64,
+    pub s_plus: f64,
+    pub c_minus: f64,
+    pub s_minus: f64,
+}
+
+impl OceanTide {
+    /// Returns the Doodson multipliers from the Doodson number, e.g. "255.555" for M2 or "55.565" for Ω1
+    pub fn doodson_multipliers(number: &str) -> Result<[i8; 6], NyxError> {
+        let digits: String = number.chars().filter(|c| *c != '.').collect();
+        if digits.len() > 6 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(NyxError::CustomError(format!(
+                "invalid Doodson number `{number}`"
+            )));
+        }
+        let digits = format!("{digits:0>6}");
+        let mut multipliers = [0; 6];
+        for (i, digit) in digits.bytes().enumerate() {
+            let value = (digit - b'0') as i8;
+            multipliers[i] = if i == 0 { value } else { value - 5 };
+        }
+        Ok(multipliers)
+    }
+
+    /// Returns the argument of this constituent at the provided epoch, in radians
+    pub fn argument(&self, epoch: Epoch) -> f64 {
+        self.argument_from(&doodson_arguments(epoch))
+    }
+
+    /// Returns the argument of this constituent from the Doodson arguments, in radians
+    fn argument_from(&self, beta: &[f64; 6]) -> f64 {
+        self.doodson
+            .iter()
+            .zip(beta.iter())
+            .map(|(n, b)| f64::from(*n) * b)
+            .sum()
+    }
+
+    /// Loads the constituents of an IERS ocean tide file (e.g. `fes2004_Cnm-Snm.dat`) up to the provided degree and order.
+    /// Each line holds the Doodson number, the Darwin name, the degree, the order, and the C+, S+, C- and S- in units of
+    /// 1e-11. Lines which do not match this format (e.g. headers) are skipped.
+    pub fn from_iers_file(
+        path: &str,
+        max_degree: usize,
+        max_order: usize,
+    ) -> Result<Vec<Self>, NyxError> {
+        let contents =
+            read_to_string(path).map_err(|e| NyxError::FileUnreadable(format!("{path}: {e}")))?;
+        let mut constituents = Vec::new();
+        for line in contents.lines() {
+            let items: Vec<&str> = line.split_whitespace().collect();
+            if items.len() < 8 {
+                continue;
+            }
+            let doodson = match Self::doodson_multipliers(items[0]) {
+                Ok(doodson) => doodson,
+                Err(_) => continue,
+            };
+            let (degree, order) = match (usize::from_str(items[2]), usize::from_str(items[3])) {
+                (Ok(degree), Ok(order)) => (degree, order),
+                _ => continue,
+            };
+            if degree > max_degree || order > max_order {
+                continue;
+            }
+            let mut coeffs = [0.0; 4];
+            for (i, item) in items[4..8].iter().enumerate() {
+                coeffs[i] = f64::from_str(&item.replace('D', "E")).map_err(|_| {
+                    NyxError::FileUnreadable(format!(
+                        "{path}: could not parse `{item}` in `{line}`"
+                    ))
+                })? * OCEAN_TIDE_FILE_SCALE;
+            }
+            constituents.push(Self {
+                doodson,
+                degree,
+                order,
+                c_plus: coeffs[0],
+                s_plus: coeffs[1],
+                c_minus: coeffs[2],
+                s_minus: coeffs[3],
+            });
+        }
+        if constituents.is_empty() {
+            return Err(NyxError::FileUnreadable(format!(
+                "{path}: no ocean tide constituent found"
+            )));
+        }
+        Ok(constituents)
+    }
+}
+
+/// Returns the six Doodson arguments (τ, s, h, p, N', ps) at the provided epoch in radians, from the Delaunay arguments and
+/// the Greenwich mean sidereal time (IERS 2010, chapter 5), where UTC is used in place of UT1.
+pub fn doodson_arguments(epoch: Epoch) -> [f64; 6] {
+    let arcsec = PI / 648_000.0;
+    let t = (epoch.to_jde_tt_days() - 2_451_545.0) / 36_525.0;
+    let delaunay = |deg: f64, c1: f64, c2: f64| (deg * 3600.0 + c1 * t + c2 * t * t) * arcsec;
+    let l = delaunay(134.963_402_51, 1_717_915_923.217_8, 31.879_2);
+    let l_p = delaunay(357.529_109_18, 129_596_581.048_1, -0.553_2);
+    let f = delaunay(93.272_090_62, 1_739_527_262.847_8, -12.751_2);
+    let d = delaunay(297.850_195_47, 1_602_961_601.209_0, -6.370_6);
+    let omega = delaunay(125.044_555_01, -6_962_890.543_1, 7.472_2);
+    // Greenwich mean sidereal time from the Earth rotation angle
+    let du = epoch.to_jde_utc_days() - 2_451_545.0;
+    let era = TAU * (0.779_057_273_264 + 1.002_737_811_911_354_5 * du);
+    let gmst = era + (0.014_506 + 4_612.156_534 * t + 1.391_581_7 * t * t) * arcsec;
+
+    let s = f + omega;
+    [gmst + PI - s, s, s - d, s - l, -omega, s - d - l_p]
+}
+
+/// Returns the fully normalized associated Legendre function of degree 2 or 3, without the Condon-Shortley phase
+fn legendre_normalized(degree: usize, order: usize, sin_phi: f64) -> f64 {
+    let (s, c) = (sin_phi, (1.0 - sin_phi * sin_phi).max(0.0).sqrt());
+    let (p_nm, norm_sq): (f64, f64) = match (degree, order) {
+        (2, 0) => (1.5 * s * s - 0.5, 5.0),
+        (2, 1) => (3.0 * s * c, 5.0 / 3.0),
+        (2, 2) => (3.0 * c * c, 5.0 / 12.0),
+        (3, 0) => (2.5 * s.powi(3) - 1.5 * s, 7.0),
+        (3, 1) => (1.5 * c * (5.0 * s * s - 1.0), 7.0 / 6.0),
+        (3, 2) => (15.0 * s * c * c, 7.0 / 60.0),
+        (3, 3) => (15.0 * c.powi(3), 7.0 / 360.0),
+        _ => unreachable!("only degrees 2 and 3 are needed"),
+    };
+    p_nm * norm_sq.sqrt()
+}
+
+/// Tidal corrections of the gravity field of the Earth, evaluated as a time varying spherical harmonics field.
+///
+/// The solid Earth tides are the frequency independent corrections of the IERS 2010 conventions (step 1, section 6.2): the
+/// degree 2 and 3 corrections due to the Moon and the Sun with anelastic Love numbers, and the degree 4 corrections from the
+/// degree 2 tides. The ocean tides are the corrections of each constituent of an ocean tide model (section 6.3), e.g.
+/// loaded from the FES2004 file of the IERS.
+///
+/// The correction of the degree 2 zonal term includes the permanent tide, as needed with a "tide free" field such as
+/// JGM3 or EGM2008 (cf. `with_zero_tide_field` otherwise).
+#[derive(Clone)]
+pub struct Tides {
+    cosm: Arc<Cosm>,
+    compute_frame: Frame,
+    /// Whether to include the solid Earth tides
+    pub solid: bool,
+    /// Constituents of the ocean tide model
+    pub ocean: Vec<OceanTide>,
+    /// Whether the static field already includes the permanent tide, which is then removed from the solid tide correction
+    pub zero_tide_field: bool,
+}
+
+impl Tides {
+    /// Initializes the solid Earth tides of the body of the provided body fixed frame (e.g. "IAU Earth"), without ocean tides
+    pub fn solid(compute_frame: Frame, cosm: Arc<Cosm>) -> Self {
+        assert!(compute_frame.is_geoid(), "tides only work around geoids");
+        Self {
+            cosm,
+            compute_frame,
+            solid: true,
+            ocean: Vec::new(),
+            zero_tide_field: false,
+        }
+    }
+
+    /// Initializes the ocean tides from the provided constituents, without solid Earth tides
+    pub fn ocean(compute_frame: Frame, ocean: Vec<OceanTide>, cosm: Arc<Cosm>) -> Self {
+        let mut me = Self::solid(compute_frame, cosm);
+        me.solid = false;
+        me.ocean = ocean;
+        me
+    }
+
+    /// Adds the provided ocean tide constituents to these tides
+    pub fn with_ocean(mut self, ocean: Vec<OceanTide>) -> Self {
+        self.ocean.extend(ocean);
+        self
+    }
+
+    /// Removes the permanent tide from the correction, for static fields in the "zero tide" convention
+    pub fn with_zero_tide_field(mut self) -> Self {
+        self.zero_tide_field = true;
+        self
+    }
+
+    /// Returns the maximum degree of the corrections
+    pub fn max_degree(&self) -> usize {
+        let ocean = self.ocean.iter().map(|tide| tide.degree).max().unwrap_or(0);
+        if self.solid {
+            ocean.max(4)
+        } else {
+            ocean
+        }
+    }
+
+    /// Returns the normalized corrections of the C_nm and S_nm at the provided epoch, indexed by degree and order
+    pub fn delta_cs(&self, epoch: Epoch) -> Result<(DMatrix<f64>, DMatrix<f64>), NyxError> {
+        let size = self.max_degree() + 1;
+        let mut c_nm = DMatrix::zeros(size, size);
+        let mut s_nm = DMatrix::zeros(size, size);
+
+        if self.solid {
+            let inertial = self
+                .cosm
+                .frame_from_ephem_path(&self.compute_frame.ephem_path());
+            let dcm = self
+                .cosm
+                .try_position_dcm_from_to(&inertial, &self.compute_frame, epoch)?;
+            let eq_radius_km = self.compute_frame.equatorial_radius();
+            for body in [Bodies::Luna, Bodies::Sun] {
+                let body_frame = self.cosm.frame_from_ephem_path(body.ephem_path());
+                let r_body = dcm
+                    * self
+                        .cosm
+                        .try_celestial_state(
+                            body.ephem_path(),
+                            epoch,
+                            inertial,
+                            LightTimeCalc::None,
+                        )?
+                        .radius();
+                let r_km = r_body.norm();
+                let sin_phi = r_body[2] / r_km;
+                let lambda = r_body[1].atan2(r_body[0]);
+                let mass_ratio = body_frame.gm() / self.compute_frame.gm();
+
+                for n in 2..=3 {
+                    let factor =
+                        mass_ratio * (eq_radius_km / r_km).powi(n as i32 + 1) / (2 * n + 1) as f64;
+                    for m in 0..=n {
+                        let (k_re, k_im) = if n == 2 {
+                            (LOVE_K2[m].0, LOVE_K2[m].1)
+                        } else {
+                            (LOVE_K3[m], 0.0)
+                        };
+                        let p_nm = factor * legendre_normalized(n, m, sin_phi);
+                        let (sin_ml, cos_ml) = (m as f64 * lambda).sin_cos();
+                        c_nm[(n, m)] += p_nm * (k_re * cos_ml + k_im * sin_ml);
+                        s_nm[(n, m)] += p_nm * (k_re * sin_ml - k_im * cos_ml);
+                        if n == 2 {
+                            // Degree 4 corrections from the degree 2 tides (IERS 2010, equation 6.7)
+                            let k_plus = LOVE_K2[m].2;
+                            c_nm[(4, m)] += p_nm * k_plus * cos_ml;
+                            s_nm[(4, m)] += p_nm * k_plus * sin_ml;
+                        }
+                    }
+                }
+            }
+            if self.zero_tide_field {
+                c_nm[(2, 0)] -= PERMANENT_TIDE_C20 * LOVE_K2[0].0;
+            }
+        }
+
+        if !self.ocean.is_empty() {
+            let beta = doodson_arguments(epoch);
+            for tide in &self.ocean {
+                let (sin_t, cos_t) = tide.argument_from(&beta).sin_cos();
+                let (n, m) = (tide.degree, tide.order);
+                c_nm[(n, m)] +=
+                    (tide.c_plus + tide.c_minus) * cos_t + (tide.s_plus + tide.s_minus) * sin_t;
+                if m > 0 {
+                    s_nm[(n, m)] +=
+                        (tide.s_plus - tide.s_minus) * cos_t - (tide.c_plus - tide.c_minus) * sin_t;
+                }
+            }
+        }
+
+        Ok((c_nm, s_nm))
+    }
+
+    /// Returns the spherical harmonics of the corrections at the provided epoch
+    fn harmonics(&self, epoch: Epoch) -> Result<Arc<Harmonics>, NyxError> {
+        let (c_nm, s_nm) = self.delta_cs(epoch)?;
+        Ok(Harmonics::from_stor(
+            self.compute_frame,
+            HarmonicsMem::from_cs(c_nm, s_nm),
+            self.cosm.clone(),
+        ))
+    }
+}
+
+impl fmt::Display for Tides {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut models = Vec::new();
+        if self.solid {
+            models.push("solid Earth tides".to_string());
+        }
+        if !self.ocean.is_empty() {
+            models.push(format!("{} ocean tide constituents", self.ocean.len()));
+        }
+        write!(f, "{} {}", self.compute_frame, models.join(" and "))
+    }
+}
+
+/// The tides only depend on the epoch, so their partials are those of the corrected spherical harmonics.
+impl AccelModel for Tides {
+    fn eom(&self, osc: &Orbit) -> Result<Vector3<f64>, NyxError> {
+        self.harmonics(osc.epoch)?.eom(osc)
+    }
+
+    fn dual_eom(&self, osc: &Orbit) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+        self.harmonics(osc.epoch)?.dual_eom(osc)
+    }
+
+    fn depends_on_velocity(&self) -> bool {
+        false
+    }
+}
@@ -19,8 +19,8 @@
 use super::thiserror::Error;
 use crate::io::ConfigError;
 use crate::md::trajectory::TrajError;
-use crate::md::StateParameter;
 pub use crate::md::TargetingError;
+use crate::md::{NamedParameter, StateParameter};
 pub use crate::time::Errors as TimeErrors;
 use crate::Spacecraft;
 use std::convert::From;
@@ -78,6 +78,9 @@ pub enum NyxError {
     /// State parameter cannot be used in this function
     #[error("Unavailable parameter {0:?}: {1}")]
     StateParameterUnavailable(StateParameter, String),
+    /// Named parameter of a user-defined state cannot be used in this function
+    #[error("Unavailable named parameter {0}: {1}")]
+    NamedParameterUnavailable(NamedParameter, String),
     /// Could not load file
     #[error("Could not load file: {0}")]
     LoadingError(String),
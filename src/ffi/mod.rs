@@ -0,0 +1,434 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! C foreign function interface to the propagation, the trajectory queries and the frame conversions.
+//!
+//! The Cosm, the dynamics and the trajectories are exposed as opaque handles, which must be freed with their
+//! respective `*_free` function. States are exchanged as `NyxState` structures, whose epoch is in TAI seconds past
+//! the J1900 reference epoch, in the frame whose name is provided along with the state.
+//!
+//! Functions returning a status return `NYX_OK` on success, and functions returning a handle return a null pointer
+//! on failure: in both cases, `nyx_last_error` describes the failure. The matching C header is `include/nyx.h`.
+
+use crate::cosmic::{Bodies, Cosm, Frame, Orbit};
+use crate::dynamics::OrbitalDynamics;
+use crate::md::trajectory::Traj;
+use crate::propagators::Propagator;
+use crate::time::{Epoch, Unit};
+use crate::NyxError;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::Arc;
+
+/// Status returned on success
+pub const NYX_OK: c_int = 0;
+/// Status returned when a call fails, refer to `nyx_last_error` for the reason
+pub const NYX_ERROR: c_int = -1;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Cartesian state exchanged with C, in km and km/s
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct NyxState {
+    /// Epoch in TAI seconds past J1900
+    pub epoch_tai_s: f64,
+    pub x_km: f64,
+    pub y_km: f64,
+    pub z_km: f64,
+    pub vx_km_s: f64,
+    pub vy_km_s: f64,
+    pub vz_km_s: f64,
+}
+
+impl NyxState {
+    fn to_orbit(self, frame: Frame) -> Orbit {
+        Orbit::cartesian(
+            self.x_km,
+            self.y_km,
+            self.z_km,
+            self.vx_km_s,
+            self.vy_km_s,
+            self.vz_km_s,
+            Epoch::from_tai_seconds(self.epoch_tai_s),
+            frame,
+        )
+    }
+}
+
+impl From<Orbit> for NyxState {
+    fn from(orbit: Orbit) -> Self {
+        Self {
+            epoch_tai_s: orbit.epoch.to_tai_seconds(),
+            x_km: orbit.x_km,
+            y_km: orbit.y_km,
+            z_km: orbit.z_km,
+            vx_km_s: orbit.vx_km_s,
+            vy_km_s: orbit.vy_km_s,
+            vz_km_s: orbit.vz_km_s,
+        }
+    }
+}
+
+/// Opaque handle to a Cosm
+pub struct NyxCosm(Arc<Cosm>);
+
+/// Opaque handle to the orbital dynamics
+pub struct NyxDynamics(OrbitalDynamics);
+
+/// Opaque handle to a trajectory
+pub struct NyxTraj(Traj<Orbit>);
+
+fn set_last_error(msg: String) {
+    // Interior NUL bytes cannot be represented in a C string
+    let msg = CString::new(msg.replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(msg));
+}
+
+/// Runs the provided closure, storing its error or panic as the last error
+fn guarded<T, F: FnOnce() -> Result<T, NyxError>>(f: F) -> Option<T> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(val)) => Some(val),
+        Ok(Err(e)) => {
+            set_last_error(e.to_string());
+            None
+        }
+        Err(panic) => {
+            let reason = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown reason".to_string());
+            set_last_error(format!("nyx panicked: {reason}"));
+            None
+        }
+    }
+}
+
+fn status(result: Option<()>) -> c_int {
+    match result {
+        Some(()) => NYX_OK,
+        None => NYX_ERROR,
+    }
+}
+
+fn handle<T>(result: Option<T>) -> *mut T {
+    match result {
+        Some(val) => Box::into_raw(Box::new(val)),
+        None => ptr::null_mut(),
+    }
+}
+
+unsafe fn deref<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, NyxError> {
+    ptr.as_ref()
+        .ok_or_else(|| NyxError::CustomError(format!("{name} is a null pointer")))
+}
+
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, NyxError> {
+    if ptr.is_null() {
+        return Err(NyxError::CustomError(format!("{name} is a null pointer")));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|e| NyxError::CustomError(format!("{name} is not valid UTF-8: {e}")))
+}
+
+unsafe fn write_state(out: *mut NyxState, orbit: Orbit) -> Result<(), NyxError> {
+    match out.as_mut() {
+        Some(out) => {
+            *out = orbit.into();
+            Ok(())
+        }
+        None => Err(NyxError::CustomError(
+            "output state is a null pointer".to_string(),
+        )),
+    }
+}
+
+/// Returns the description of the last failure on this thread, or a null pointer if no call has failed yet.
+/// The string is owned by nyx and remains valid until the next failure on this thread.
+#[no_mangle]
+pub extern "C" fn nyx_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| match last.borrow().as_ref() {
+        Some(msg) => msg.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// Loads the Cosm from the embedded DE438 ephemerides
+#[no_mangle]
+pub extern "C" fn nyx_cosm_de438() -> *mut NyxCosm {
+    handle(guarded(|| Ok(NyxCosm(Cosm::de438()))))
+}
+
+/// Frees a Cosm
+///
+/// # Safety
+/// The handle must have been returned by `nyx_cosm_de438` and not freed yet, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn nyx_cosm_free(cosm: *mut NyxCosm) {
+    if !cosm.is_null() {
+        drop(Box::from_raw(cosm));
+    }
+}
+
+/// Initializes the two body dynamics, i.e. only the gravity of the central body of the propagated state
+#[no_mangle]
+pub extern "C" fn nyx_dynamics_two_body() -> *mut NyxDynamics {
+    handle(guarded(|| Ok(NyxDynamics(OrbitalDynamics::two_body()))))
+}
+
+/// Initializes the dynamics of the central body and the point masses of the provided bodies (e.g. "Sun", "Luna")
+///
+/// # Safety
+/// The Cosm must be a valid handle, and `bodies` must point to `num_bodies` NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn nyx_dynamics_point_masses(
+    cosm: *const NyxCosm,
+    bodies: *const *const c_char,
+    num_bodies: usize,
+) -> *mut NyxDynamics {
+    handle(guarded(|| {
+        let cosm = deref(cosm, "cosm")?;
+        if bodies.is_null() && num_bodies > 0 {
+            return Err(NyxError::CustomError(
+                "bodies is a null pointer".to_string(),
+            ));
+        }
+        let mut point_masses = Vec::with_capacity(num_bodies);
+        for idx in 0..num_bodies {
+            let name = str_arg(*bodies.add(idx), "body name")?;
+            point_masses.push(Bodies::try_from(name.to_string())?);
+        }
+        Ok(NyxDynamics(OrbitalDynamics::point_masses(
+            &point_masses,
+            cosm.0.clone(),
+        )))
+    }))
+}
+
+/// Frees dynamics
+///
+/// # Safety
+/// The handle must have been returned by a `nyx_dynamics_*` function and not freed yet, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn nyx_dynamics_free(dynamics: *mut NyxDynamics) {
+    if !dynamics.is_null() {
+        drop(Box::from_raw(dynamics));
+    }
+}
+
+/// Propagates the state, defined in the provided frame (e.g. "EME2000"), for the provided duration in seconds (negative
+/// to propagate backward), and writes the final state in the same frame.
+///
+/// # Safety
+/// The handles must be valid, the frame a NUL terminated string, and the states valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn nyx_propagate(
+    cosm: *const NyxCosm,
+    dynamics: *const NyxDynamics,
+    frame: *const c_char,
+    state: *const NyxState,
+    duration_s: f64,
+    out: *mut NyxState,
+) -> c_int {
+    status(guarded(|| {
+        let cosm = deref(cosm, "cosm")?;
+        let dynamics = deref(dynamics, "dynamics")?;
+        let frame = cosm.0.try_frame(str_arg(frame, "frame")?)?;
+        let orbit = deref(state, "state")?.to_orbit(frame);
+        let end_state = Propagator::default(dynamics.0.clone())
+            .with(orbit)
+            .for_duration(duration_s * Unit::Second)?;
+        write_state(out, end_state)
+    }))
+}
+
+/// Propagates the state, defined in the provided frame, for the provided duration in seconds and returns its trajectory
+///
+/// # Safety
+/// The handles must be valid, the frame a NUL terminated string, and the state a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nyx_propagate_traj(
+    cosm: *const NyxCosm,
+    dynamics: *const NyxDynamics,
+    frame: *const c_char,
+    state: *const NyxState,
+    duration_s: f64,
+) -> *mut NyxTraj {
+    handle(guarded(|| {
+        let cosm = deref(cosm, "cosm")?;
+        let dynamics = deref(dynamics, "dynamics")?;
+        let frame = cosm.0.try_frame(str_arg(frame, "frame")?)?;
+        let orbit = deref(state, "state")?.to_orbit(frame);
+        let (_, traj) = Propagator::default(dynamics.0.clone())
+            .with(orbit)
+            .for_duration_with_traj(duration_s * Unit::Second)?;
+        Ok(NyxTraj(traj))
+    }))
+}
+
+/// Interpolates the trajectory at the provided epoch in TAI seconds past J1900
+///
+/// # Safety
+/// The trajectory must be a valid handle and the output state a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nyx_traj_at(
+    traj: *const NyxTraj,
+    epoch_tai_s: f64,
+    out: *mut NyxState,
+) -> c_int {
+    status(guarded(|| {
+        let traj = deref(traj, "trajectory")?;
+        write_state(out, traj.0.at(Epoch::from_tai_seconds(epoch_tai_s))?)
+    }))
+}
+
+/// Writes the first state of the trajectory
+///
+/// # Safety
+/// The trajectory must be a valid handle and the output state a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nyx_traj_first(traj: *const NyxTraj, out: *mut NyxState) -> c_int {
+    status(guarded(|| {
+        write_state(out, *deref(traj, "trajectory")?.0.first())
+    }))
+}
+
+/// Writes the last state of the trajectory
+///
+/// # Safety
+/// The trajectory must be a valid handle and the output state a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nyx_traj_last(traj: *const NyxTraj, out: *mut NyxState) -> c_int {
+    status(guarded(|| {
+        write_state(out, *deref(traj, "trajectory")?.0.last())
+    }))
+}
+
+/// Frees a trajectory
+///
+/// # Safety
+/// The handle must have been returned by `nyx_propagate_traj` and not freed yet, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn nyx_traj_free(traj: *mut NyxTraj) {
+    if !traj.is_null() {
+        drop(Box::from_raw(traj));
+    }
+}
+
+/// Converts the state from one frame to another (e.g. from "EME2000" to "Luna")
+///
+/// # Safety
+/// The Cosm must be a valid handle, the frames NUL terminated strings, and the states valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn nyx_frame_convert(
+    cosm: *const NyxCosm,
+    state: *const NyxState,
+    from_frame: *const c_char,
+    to_frame: *const c_char,
+    out: *mut NyxState,
+) -> c_int {
+    status(guarded(|| {
+        let cosm = deref(cosm, "cosm")?;
+        let from_frame = cosm.0.try_frame(str_arg(from_frame, "from_frame")?)?;
+        let to_frame = cosm.0.try_frame(str_arg(to_frame, "to_frame")?)?;
+        let orbit = deref(state, "state")?.to_orbit(from_frame);
+        write_state(out, cosm.0.try_frame_chg(&orbit, to_frame)?)
+    }))
+}
+
+#[test]
+fn ffi_propagate_and_query() {
+    let cosm = nyx_cosm_de438();
+    let dynamics = nyx_dynamics_two_body();
+    let eme2k = CString::new("EME2000").unwrap();
+    let luna = CString::new("Luna").unwrap();
+    let start = NyxState {
+        epoch_tai_s: Epoch::from_gregorian_tai_at_midnight(2020, 1, 1).to_tai_seconds(),
+        x_km: -2436.45,
+        y_km: -2436.45,
+        z_km: 6891.037,
+        vx_km_s: 5.088_611,
+        vy_km_s: -5.088_611,
+        vz_km_s: 0.0,
+    };
+
+    unsafe {
+        let mut end = NyxState::default();
+        assert_eq!(
+            nyx_propagate(cosm, dynamics, eme2k.as_ptr(), &start, 3600.0, &mut end),
+            NYX_OK
+        );
+        assert_eq!(end.epoch_tai_s, start.epoch_tai_s + 3600.0);
+
+        // The trajectory ends at the same state as the single propagation
+        let traj = nyx_propagate_traj(cosm, dynamics, eme2k.as_ptr(), &start, 3600.0);
+        assert!(!traj.is_null());
+        let mut last = NyxState::default();
+        assert_eq!(nyx_traj_last(traj, &mut last), NYX_OK);
+        assert!((last.x_km - end.x_km).abs() < 1e-6);
+        let mut mid = NyxState::default();
+        assert_eq!(
+            nyx_traj_at(traj, start.epoch_tai_s + 1800.0, &mut mid),
+            NYX_OK
+        );
+        assert_eq!(mid.epoch_tai_s, start.epoch_tai_s + 1800.0);
+        // Querying outside of the trajectory fails with a message
+        let mut outside = NyxState::default();
+        assert_eq!(
+            nyx_traj_at(traj, start.epoch_tai_s + 7200.0, &mut outside),
+            NYX_ERROR
+        );
+        assert!(!nyx_last_error().is_null());
+        nyx_traj_free(traj);
+
+        // Converting to the Moon and back returns the same state
+        let mut moon = NyxState::default();
+        let mut back = NyxState::default();
+        assert_eq!(
+            nyx_frame_convert(cosm, &start, eme2k.as_ptr(), luna.as_ptr(), &mut moon),
+            NYX_OK
+        );
+        assert_eq!(
+            nyx_frame_convert(cosm, &moon, luna.as_ptr(), eme2k.as_ptr(), &mut back),
+            NYX_OK
+        );
+        assert!((back.x_km - start.x_km).abs() < 1e-6);
+
+        // Unknown frames and null pointers are reported as errors
+        let unknown = CString::new("Vulcan").unwrap();
+        assert_eq!(
+            nyx_propagate(cosm, dynamics, unknown.as_ptr(), &start, 60.0, &mut end),
+            NYX_ERROR
+        );
+        assert_eq!(
+            nyx_propagate(cosm, ptr::null(), eme2k.as_ptr(), &start, 60.0, &mut end),
+            NYX_ERROR
+        );
+        let msg = CStr::from_ptr(nyx_last_error()).to_str().unwrap();
+        assert_eq!(msg, "Custom error: dynamics is a null pointer");
+
+        nyx_dynamics_free(dynamics);
+        nyx_cosm_free(cosm);
+    }
+}
@@ -0,0 +1,370 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Cosm, Orbit};
+use crate::dynamics::OrbitalDynamics;
+use crate::errors::NyxError;
+#[cfg(feature = "io")]
+use crate::io::watermark::pq_writer;
+#[cfg(feature = "io")]
+use crate::io::ExportCfg;
+#[cfg(feature = "io")]
+use crate::md::StateParameter;
+use crate::propagators::{ErrorCtrl, Propagator};
+use crate::time::Epoch;
+#[cfg(feature = "io")]
+use crate::State;
+#[cfg(feature = "io")]
+use arrow::array::{Array, Float64Array, Float64Builder, StringArray, StringBuilder};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+#[cfg(feature = "io")]
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+#[cfg(feature = "io")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+#[cfg(feature = "io")]
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "io")]
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Cartesian parameters stored in a catalog, in this order
+#[cfg(feature = "io")]
+const CATALOG_PARAMS: [StateParameter; 6] = [
+    StateParameter::X,
+    StateParameter::Y,
+    StateParameter::Z,
+    StateParameter::VX,
+    StateParameter::VY,
+    StateParameter::VZ,
+];
+
+/// A named state of a catalog
+#[derive(Clone, Debug, PartialEq)]
+pub struct CatalogEntry {
+    /// Name of the object (e.g. its NORAD ID)
+    pub name: String,
+    /// State of the object
+    pub state: Orbit,
+}
+
+/// Row of a CSV catalog
+#[derive(Serialize, Deserialize)]
+struct CatalogRow {
+    name: String,
+    epoch: String,
+    frame: String,
+    x_km: f64,
+    y_km: f64,
+    z_km: f64,
+    vx_km_s: f64,
+    vy_km_s: f64,
+    vz_km_s: f64,
+}
+
+/// A catalog of the states of many objects, each at its own epoch and in its own frame.
+///
+/// Catalogs can be read from and written to CSV files with the columns `name,epoch,frame,x_km,y_km,z_km,vx_km_s,vy_km_s,vz_km_s`,
+/// and to parquet files which use the same column names as the trajectory files, plus a `Name` and a `Frame` column.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StateCatalog {
+    /// Entries of this catalog
+    pub entries: Vec<CatalogEntry>,
+}
+
+impl StateCatalog {
+    /// Loads a catalog from a CSV file, the frames are fetched from the provided Cosm.
+    pub fn from_csv<P: AsRef<Path>>(path: P, cosm: Arc<Cosm>) -> Result<Self, NyxError> {
+        let mut reader =
+            csv::Reader::from_path(path).map_err(|e| NyxError::FileUnreadable(format!("{e}")))?;
+
+        let mut entries = Vec::new();
+        for (rno, row) in reader.deserialize::<CatalogRow>().enumerate() {
+            let row = row.map_err(|e| NyxError::FileUnreadable(format!("row {rno}: {e}")))?;
+            let epoch = Epoch::from_str(&row.epoch)
+                .map_err(|e| NyxError::FileUnreadable(format!("row {rno}: {e}")))?;
+            let frame = cosm.try_frame(&row.frame)?;
+
+            entries.push(CatalogEntry {
+                name: row.name,
+                state: Orbit::cartesian(
+                    row.x_km,
+                    row.y_km,
+                    row.z_km,
+                    row.vx_km_s,
+                    row.vy_km_s,
+                    row.vz_km_s,
+                    epoch,
+                    frame,
+                ),
+            });
+        }
+
+        info!("Loaded {} states from CSV catalog", entries.len());
+
+        Ok(Self { entries })
+    }
+
+    /// Writes this catalog to a CSV file
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf, NyxError> {
+        let path_buf = path.as_ref().to_path_buf();
+        let mut writer =
+            csv::Writer::from_path(&path_buf).map_err(|e| NyxError::ExportError(format!("{e}")))?;
+
+        for entry in &self.entries {
+            writer
+                .serialize(CatalogRow {
+                    name: entry.name.clone(),
+                    epoch: format!("{}", entry.state.epoch),
+                    frame: format!("{}", entry.state.frame),
+                    x_km: entry.state.x_km,
+                    y_km: entry.state.y_km,
+                    z_km: entry.state.z_km,
+                    vx_km_s: entry.state.vx_km_s,
+                    vy_km_s: entry.state.vy_km_s,
+                    vz_km_s: entry.state.vz_km_s,
+                })
+                .map_err(|e| NyxError::ExportError(format!("{e}")))?;
+        }
+
+        writer
+            .flush()
+            .map_err(|e| NyxError::ExportError(format!("{e}")))?;
+
+        Ok(path_buf)
+    }
+
+    /// Loads a catalog from a parquet file, the frames are fetched from the provided Cosm.
+    #[cfg(feature = "io")]
+    pub fn from_parquet<P: AsRef<Path>>(path: P, cosm: Arc<Cosm>) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+
+        // Check the schema before reading anything
+        let mut columns = vec![
+            "Name".to_string(),
+            "Epoch:TAI (s)".to_string(),
+            "Frame".to_string(),
+        ];
+        columns.extend(
+            CATALOG_PARAMS
+                .iter()
+                .map(|p| p.to_field(None).name().clone()),
+        );
+        for column in &columns {
+            if reader.schema().column_with_name(column).is_none() {
+                return Err(Box::new(NyxError::FileUnreadable(format!(
+                    "Missing `{column}` field"
+                ))));
+            }
+        }
+
+        let mut entries = Vec::new();
+        // Frames are cached because there are usually very few of them
+        let mut frames = HashMap::new();
+
+        for maybe_batch in reader {
+            let batch = maybe_batch?;
+
+            let names = batch
+                .column_by_name("Name")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| NyxError::FileUnreadable("`Name` is not a string".to_string()))?;
+            let frame_names = batch
+                .column_by_name("Frame")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| NyxError::FileUnreadable("`Frame` is not a string".to_string()))?;
+
+            let mut data = Vec::with_capacity(7);
+            for column in columns.iter().skip(1).filter(|c| c.as_str() != "Frame") {
+                data.push(
+                    batch
+                        .column_by_name(column)
+                        .unwrap()
+                        .as_any()
+                        .downcast_ref::<Float64Array>()
+                        .ok_or_else(|| {
+                            NyxError::FileUnreadable(format!("`{column}` is not a float"))
+                        })?,
+                );
+            }
+
+            for i in 0..batch.num_rows() {
+                let frame_name = frame_names.value(i);
+                let frame = match frames.get(frame_name) {
+                    Some(frame) => *frame,
+                    None => {
+                        let frame = cosm.try_frame(frame_name)?;
+                        frames.insert(frame_name.to_string(), frame);
+                        frame
+                    }
+                };
+
+                entries.push(CatalogEntry {
+                    name: names.value(i).to_string(),
+                    state: Orbit::cartesian(
+                        data[1].value(i),
+                        data[2].value(i),
+                        data[3].value(i),
+                        data[4].value(i),
+                        data[5].value(i),
+                        data[6].value(i),
+                        Epoch::from_tai_seconds(data[0].value(i)),
+                        frame,
+                    ),
+                });
+            }
+        }
+
+        info!("Loaded {} states from parquet catalog", entries.len());
+
+        Ok(Self { entries })
+    }
+
+    /// Writes this catalog to a parquet file. Only the metadata and timestamp of the configuration are used.
+    #[cfg(feature = "io")]
+    pub fn to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let path_buf = cfg.actual_path(path);
+
+        let mut hdrs = vec![
+            Field::new("Name", DataType::Utf8, false),
+            Field::new("Epoch:Gregorian UTC", DataType::Utf8, false),
+            Field::new("Epoch:TAI (s)", DataType::Float64, false),
+            Field::new("Frame", DataType::Utf8, false),
+        ];
+        for param in &CATALOG_PARAMS {
+            hdrs.push(param.to_field(None));
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+
+        let mut names = StringBuilder::new();
+        let mut utc_epoch = StringBuilder::new();
+        let mut tai_s = Float64Builder::new();
+        let mut frames = StringBuilder::new();
+        for entry in &self.entries {
+            names.append_value(&entry.name);
+            utc_epoch.append_value(format!("{}", entry.state.epoch));
+            tai_s.append_value(entry.state.epoch.to_tai_seconds());
+            frames.append_value(format!("{}", entry.state.frame));
+        }
+
+        let mut record: Vec<Arc<dyn Array>> = vec![
+            Arc::new(names.finish()),
+            Arc::new(utc_epoch.finish()),
+            Arc::new(tai_s.finish()),
+            Arc::new(frames.finish()),
+        ];
+
+        for param in CATALOG_PARAMS {
+            let mut data = Float64Builder::new();
+            for entry in &self.entries {
+                data.append_value(entry.state.value(param)?);
+            }
+            record.push(Arc::new(data.finish()));
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert("Purpose".to_string(), "State catalog".to_string());
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(
+            file,
+            schema.clone(),
+            pq_writer(Some(metadata), cfg.row_group_size),
+        )?;
+
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!(
+            "Catalog of {} states written to {}",
+            self.entries.len(),
+            path_buf.display()
+        );
+
+        Ok(path_buf)
+    }
+
+    /// Propagates all of the states of this catalog (in parallel with the `parallel` feature) to the provided epoch and returns the new catalog.
+    /// Fails if any of the propagations fails, with an error which includes the name of the object.
+    pub fn propagate_to<E: ErrorCtrl>(
+        &self,
+        prop: &Propagator<OrbitalDynamics, E>,
+        epoch: Epoch,
+    ) -> Result<Self, NyxError> {
+        #[cfg(feature = "parallel")]
+        let entries = self.entries.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let entries = self.entries.iter();
+
+        let entries = entries
+            .map(|entry| {
+                let state = prop.with(entry.state).until_epoch(epoch).map_err(|e| {
+                    NyxError::CustomError(format!("propagating {}: {e}", entry.name))
+                })?;
+                Ok(CatalogEntry {
+                    name: entry.name.clone(),
+                    state,
+                })
+            })
+            .collect::<Result<Vec<CatalogEntry>, NyxError>>()?;
+
+        Ok(Self { entries })
+    }
+
+    /// Number of entries in this catalog
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether this catalog is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl fmt::Display for StateCatalog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "State catalog of {} objects", self.entries.len())
+    }
+}
@@ -0,0 +1,786 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::watermark::{prj_name_ver, Watermark};
+use super::ExportCfg;
+use crate::cosmic::{Bodies, Cosm, Frame, Orbit};
+use crate::errors::NyxError;
+use crate::linalg::Matrix6;
+use crate::od::estimate::Estimate;
+use crate::time::{Epoch, Format, Formatter, TimeScale};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Components of an orbit state in the covariance keywords of an OPM and an OMM, e.g. `CY_DOT_X`
+const COV_COMPONENTS: [&str; 6] = ["X", "Y", "Z", "X_DOT", "Y_DOT", "Z_DOT"];
+
+/// Covariance of an orbit state in a CCSDS Orbit Data Message (OEM, OPM or OMM), in km^2, km^2/s and km^2/s^2.
+///
+/// The messages store the lower triangle of this matrix, row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OdmCovariance {
+    /// Epoch of this covariance
+    pub epoch: Epoch,
+    /// Reference frame of this covariance (`COV_REF_FRAME`), if different from the frame of the orbit state, e.g. `RTN`
+    pub ref_frame: Option<String>,
+    /// Covariance of the position and velocity
+    pub covar: Matrix6<f64>,
+}
+
+impl OdmCovariance {
+    /// Initializes a new covariance in the frame of the orbit state
+    pub fn new(epoch: Epoch, covar: Matrix6<f64>) -> Self {
+        Self {
+            epoch,
+            ref_frame: None,
+            covar,
+        }
+    }
+
+    /// Initializes a new covariance from the covariance of an orbit estimate, e.g. to export the results of an orbit determination
+    pub fn from_estimate<E: Estimate<Orbit>>(estimate: &E) -> Self {
+        Self::new(estimate.epoch(), estimate.covar())
+    }
+
+    /// Builds the covariance from the 21 values of its lower triangle, row by row
+    pub(crate) fn from_lower_triangle(
+        epoch: Epoch,
+        ref_frame: Option<String>,
+        values: &[f64],
+    ) -> Result<Self, NyxError> {
+        if values.len() != 21 {
+            return Err(NyxError::CCSDS(format!(
+                "Covariance at {epoch} needs 21 values, got {}",
+                values.len()
+            )));
+        }
+
+        let mut covar = Matrix6::zeros();
+        let mut values = values.iter();
+        for i in 0..6 {
+            for j in 0..=i {
+                let value = *values.next().unwrap();
+                covar[(i, j)] = value;
+                covar[(j, i)] = value;
+            }
+        }
+
+        Ok(Self {
+            epoch,
+            ref_frame,
+            covar,
+        })
+    }
+
+    /// Writes this covariance as an OEM covariance block entry, where the epoch is in the provided time system
+    pub(crate) fn write_oem<W: Write>(&self, writer: &mut W, ts: TimeScale) -> std::io::Result<()> {
+        let iso8601_no_ts = Format::from_str("%Y-%m-%dT%H:%M:%S.%f").unwrap();
+        writeln!(
+            writer,
+            "EPOCH = {}",
+            Formatter::new(self.epoch.in_time_scale(ts), iso8601_no_ts)
+        )?;
+        if let Some(ref_frame) = &self.ref_frame {
+            writeln!(writer, "COV_REF_FRAME = {ref_frame}")?;
+        }
+        for i in 0..6 {
+            let row: Vec<String> = (0..=i)
+                .map(|j| format!("{:E}", self.covar[(i, j)]))
+                .collect();
+            writeln!(writer, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+
+    /// Writes this covariance with the keywords of an OPM or OMM
+    fn write_keywords<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if let Some(ref_frame) = &self.ref_frame {
+            writeln!(writer, "COV_REF_FRAME = {ref_frame}")?;
+        }
+        for i in 0..6 {
+            for j in 0..=i {
+                writeln!(writer, "{} = {:E}", cov_keyword(i, j), self.covar[(i, j)])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the covariance from the keywords of an OPM or OMM, if any are present
+    fn from_keywords(kvn: &Kvn, epoch: Epoch) -> Result<Option<Self>, NyxError> {
+        if !kvn.contains("CX_X") {
+            return Ok(None);
+        }
+
+        let mut values = Vec::with_capacity(21);
+        for i in 0..6 {
+            for j in 0..=i {
+                values.push(kvn.number(&cov_keyword(i, j))?);
+            }
+        }
+
+        Self::from_lower_triangle(epoch, kvn.get("COV_REF_FRAME").cloned(), &values).map(Some)
+    }
+}
+
+/// Returns the keyword of the covariance of the i-th and j-th components of the state, e.g. `CY_DOT_X` for (4, 0)
+fn cov_keyword(i: usize, j: usize) -> String {
+    format!("C{}_{}", COV_COMPONENTS[i], COV_COMPONENTS[j])
+}
+
+/// A CCSDS Orbit Parameter Message, which stores a single orbit state with its optional covariance.
+///
+/// # Limitations
+/// 1. Only the text (KVN) version of the OPM is supported
+/// 2. The osculating Keplerian elements, the spacecraft parameters other than the mass, and the maneuvers are ignored
+#[derive(Clone, Debug, PartialEq)]
+pub struct Opm {
+    /// Originator of this message
+    pub originator: Option<String>,
+    /// Name of the object
+    pub object_name: String,
+    /// Identifier of the object, e.g. its international designator
+    pub object_id: String,
+    /// Orbit state of the object
+    pub orbit: Orbit,
+    /// Mass of the object in kg, if known
+    pub mass_kg: Option<f64>,
+    /// Covariance of the orbit state, if known
+    pub covariance: Option<OdmCovariance>,
+}
+
+impl Opm {
+    /// Initializes a new OPM of this orbit, without mass nor covariance
+    pub fn from_orbit(orbit: Orbit) -> Self {
+        Self {
+            originator: None,
+            object_name: "UNKNOWN".to_string(),
+            object_id: "UNKNOWN".to_string(),
+            orbit,
+            mass_kg: None,
+            covariance: None,
+        }
+    }
+
+    /// Reads an OPM from its text (KVN) version
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, NyxError> {
+        let kvn = Kvn::from_file(path)?;
+        let cosm = Cosm::de438();
+
+        let time_system = parse_time_system(kvn.required("TIME_SYSTEM")?)?;
+        let frame = parse_ccsds_frame(
+            kvn.required("CENTER_NAME")?,
+            kvn.required("REF_FRAME")?,
+            &cosm,
+        )?;
+        let epoch = parse_ccsds_epoch(kvn.required("EPOCH")?, time_system)?;
+
+        let orbit = Orbit::cartesian(
+            kvn.number("X")?,
+            kvn.number("Y")?,
+            kvn.number("Z")?,
+            kvn.number("X_DOT")?,
+            kvn.number("Y_DOT")?,
+            kvn.number("Z_DOT")?,
+            epoch,
+            frame,
+        );
+
+        let mass_kg = if kvn.contains("MASS") {
+            Some(kvn.number("MASS")?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            originator: kvn.get("ORIGINATOR").cloned(),
+            object_name: kvn.required("OBJECT_NAME")?.to_string(),
+            object_id: kvn.required("OBJECT_ID")?.to_string(),
+            orbit,
+            mass_kg,
+            covariance: OdmCovariance::from_keywords(&kvn, epoch)?,
+        })
+    }
+
+    /// Writes this OPM in its text (KVN) version. The originator, object name and object ID may be overwritten by the
+    /// `originator`, `object_name` and `object_id` keys of the metadata of the export.
+    pub fn to_kvn_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, NyxError> {
+        let path_buf = cfg.actual_path(path);
+        let metadata = cfg.metadata.unwrap_or_default();
+
+        let file = File::create(&path_buf)
+            .map_err(|e| NyxError::CCSDS(format!("File creation error: {e}")))?;
+        let mut writer = BufWriter::new(file);
+
+        let err_hdlr = |e| NyxError::CCSDS(format!("Could not write: {e}"));
+
+        let iso8601_no_ts = Format::from_str("%Y-%m-%dT%H:%M:%S.%f").unwrap();
+        let ts = self.orbit.epoch.time_scale;
+
+        write_header(&mut writer, "OPM", &metadata, &self.originator).map_err(err_hdlr)?;
+        write_object(&mut writer, &metadata, &self.object_name, &self.object_id)
+            .map_err(err_hdlr)?;
+        write_frame(&mut writer, self.orbit.frame, ts).map_err(err_hdlr)?;
+
+        writeln!(
+            writer,
+            "\nEPOCH = {}",
+            Formatter::new(self.orbit.epoch, iso8601_no_ts)
+        )
+        .map_err(err_hdlr)?;
+        writeln!(writer, "X = {:E}", self.orbit.x_km).map_err(err_hdlr)?;
+        writeln!(writer, "Y = {:E}", self.orbit.y_km).map_err(err_hdlr)?;
+        writeln!(writer, "Z = {:E}", self.orbit.z_km).map_err(err_hdlr)?;
+        writeln!(writer, "X_DOT = {:E}", self.orbit.vx_km_s).map_err(err_hdlr)?;
+        writeln!(writer, "Y_DOT = {:E}", self.orbit.vy_km_s).map_err(err_hdlr)?;
+        writeln!(writer, "Z_DOT = {:E}", self.orbit.vz_km_s).map_err(err_hdlr)?;
+
+        if let Some(mass_kg) = self.mass_kg {
+            writeln!(writer, "\nMASS = {mass_kg:E}").map_err(err_hdlr)?;
+        }
+
+        if let Some(covariance) = &self.covariance {
+            #[allow(clippy::writeln_empty_string)]
+            writeln!(writer, "").map_err(err_hdlr)?;
+            covariance.write_keywords(&mut writer).map_err(err_hdlr)?;
+        }
+
+        info!("Orbit parameter message written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+/// A CCSDS Orbit Mean-Elements Message, which stores the Keplerian elements of an object at a single epoch with their optional covariance.
+///
+/// # Limitations
+/// 1. Only the text (KVN) version of the OMM is supported
+/// 2. The elements are converted to an orbit as osculating Keplerian elements, so mean elements must be converted with their theory for accurate results
+/// 3. The TEME reference frame of the two-line element sets is not supported
+/// 4. The TLE related parameters, the spacecraft parameters, and the user defined parameters are ignored
+#[derive(Clone, Debug, PartialEq)]
+pub struct Omm {
+    /// Originator of this message
+    pub originator: Option<String>,
+    /// Name of the object
+    pub object_name: String,
+    /// Identifier of the object, e.g. its international designator
+    pub object_id: String,
+    /// Theory used to compute the mean elements, e.g. `SGP4` or `DSST`
+    pub mean_element_theory: String,
+    /// Epoch of the elements
+    pub epoch: Epoch,
+    /// Frame of the elements, whose GM is that of the message if provided
+    pub frame: Frame,
+    /// Semi-major axis in km
+    pub sma_km: f64,
+    /// Eccentricity
+    pub ecc: f64,
+    /// Inclination in degrees
+    pub inc_deg: f64,
+    /// Right ascension of the ascending node in degrees
+    pub raan_deg: f64,
+    /// Argument of pericenter in degrees
+    pub aop_deg: f64,
+    /// Mean anomaly in degrees
+    pub ma_deg: f64,
+    /// Covariance of the orbit state, if known
+    pub covariance: Option<OdmCovariance>,
+}
+
+impl Omm {
+    /// Initializes a new OMM from the osculating Keplerian elements of this orbit, whose theory is set to `OSCULATING`
+    pub fn from_orbit(orbit: Orbit) -> Self {
+        Self {
+            originator: None,
+            object_name: "UNKNOWN".to_string(),
+            object_id: "UNKNOWN".to_string(),
+            mean_element_theory: "OSCULATING".to_string(),
+            epoch: orbit.epoch,
+            frame: orbit.frame,
+            sma_km: orbit.sma_km(),
+            ecc: orbit.ecc(),
+            inc_deg: orbit.inc_deg(),
+            raan_deg: orbit.raan_deg(),
+            aop_deg: orbit.aop_deg(),
+            ma_deg: orbit.ma_deg(),
+            covariance: None,
+        }
+    }
+
+    /// Reads an OMM from its text (KVN) version. If the semi-major axis is not provided, it is computed from the mean motion (in
+    /// revolutions per day) and the GM of the message, or that of the center object.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, NyxError> {
+        let kvn = Kvn::from_file(path)?;
+        let cosm = Cosm::de438();
+
+        let time_system = parse_time_system(kvn.required("TIME_SYSTEM")?)?;
+        let mut frame = parse_ccsds_frame(
+            kvn.required("CENTER_NAME")?,
+            kvn.required("REF_FRAME")?,
+            &cosm,
+        )?;
+        if kvn.contains("GM") {
+            frame.gm_mut(kvn.number("GM")?);
+        }
+        let epoch = parse_ccsds_epoch(kvn.required("EPOCH")?, time_system)?;
+
+        let sma_km = if kvn.contains("SEMI_MAJOR_AXIS") {
+            kvn.number("SEMI_MAJOR_AXIS")?
+        } else {
+            let mean_motion_rad_s =
+                kvn.number("MEAN_MOTION")? * 2.0 * std::f64::consts::PI / 86_400.0;
+            (frame.gm() / mean_motion_rad_s.powi(2)).cbrt()
+        };
+
+        Ok(Self {
+            originator: kvn.get("ORIGINATOR").cloned(),
+            object_name: kvn.required("OBJECT_NAME")?.to_string(),
+            object_id: kvn.required("OBJECT_ID")?.to_string(),
+            mean_element_theory: kvn.required("MEAN_ELEMENT_THEORY")?.to_string(),
+            epoch,
+            frame,
+            sma_km,
+            ecc: kvn.number("ECCENTRICITY")?,
+            inc_deg: kvn.number("INCLINATION")?,
+            raan_deg: kvn.number("RA_OF_ASC_NODE")?,
+            aop_deg: kvn.number("ARG_OF_PERICENTER")?,
+            ma_deg: kvn.number("MEAN_ANOMALY")?,
+            covariance: OdmCovariance::from_keywords(&kvn, epoch)?,
+        })
+    }
+
+    /// Returns the orbit of these elements, used as osculating Keplerian elements
+    pub fn to_orbit(&self) -> Result<Orbit, NyxError> {
+        Orbit::keplerian_mean_anomaly(
+            self.sma_km,
+            self.ecc,
+            self.inc_deg,
+            self.raan_deg,
+            self.aop_deg,
+            self.ma_deg,
+            self.epoch,
+            self.frame,
+        )
+    }
+
+    /// Writes this OMM in its text (KVN) version. The originator, object name and object ID may be overwritten by the
+    /// `originator`, `object_name` and `object_id` keys of the metadata of the export.
+    pub fn to_kvn_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, NyxError> {
+        let path_buf = cfg.actual_path(path);
+        let metadata = cfg.metadata.unwrap_or_default();
+
+        let file = File::create(&path_buf)
+            .map_err(|e| NyxError::CCSDS(format!("File creation error: {e}")))?;
+        let mut writer = BufWriter::new(file);
+
+        let err_hdlr = |e| NyxError::CCSDS(format!("Could not write: {e}"));
+
+        let iso8601_no_ts = Format::from_str("%Y-%m-%dT%H:%M:%S.%f").unwrap();
+
+        write_header(&mut writer, "OMM", &metadata, &self.originator).map_err(err_hdlr)?;
+        write_object(&mut writer, &metadata, &self.object_name, &self.object_id)
+            .map_err(err_hdlr)?;
+        write_frame(&mut writer, self.frame, self.epoch.time_scale).map_err(err_hdlr)?;
+        writeln!(writer, "MEAN_ELEMENT_THEORY = {}", self.mean_element_theory).map_err(err_hdlr)?;
+
+        writeln!(
+            writer,
+            "\nEPOCH = {}",
+            Formatter::new(self.epoch, iso8601_no_ts)
+        )
+        .map_err(err_hdlr)?;
+        writeln!(writer, "SEMI_MAJOR_AXIS = {:E}", self.sma_km).map_err(err_hdlr)?;
+        writeln!(writer, "ECCENTRICITY = {:E}", self.ecc).map_err(err_hdlr)?;
+        writeln!(writer, "INCLINATION = {:E}", self.inc_deg).map_err(err_hdlr)?;
+        writeln!(writer, "RA_OF_ASC_NODE = {:E}", self.raan_deg).map_err(err_hdlr)?;
+        writeln!(writer, "ARG_OF_PERICENTER = {:E}", self.aop_deg).map_err(err_hdlr)?;
+        writeln!(writer, "MEAN_ANOMALY = {:E}", self.ma_deg).map_err(err_hdlr)?;
+        writeln!(writer, "GM = {:E}", self.frame.gm()).map_err(err_hdlr)?;
+
+        if let Some(covariance) = &self.covariance {
+            #[allow(clippy::writeln_empty_string)]
+            writeln!(writer, "").map_err(err_hdlr)?;
+            covariance.write_keywords(&mut writer).map_err(err_hdlr)?;
+        }
+
+        info!(
+            "Orbit mean-elements message written to {}",
+            path_buf.display()
+        );
+        Ok(path_buf)
+    }
+}
+
+impl Orbit {
+    /// Initializes an orbit from the state of a CCSDS OPM file, cf. `Opm` for its limitations.
+    pub fn from_opm<P: AsRef<Path>>(path: P) -> Result<Self, NyxError> {
+        Ok(Opm::from_file(path)?.orbit)
+    }
+
+    /// Exports this orbit to a CCSDS OPM file, cf. `Opm::to_kvn_file`.
+    pub fn to_opm<P: AsRef<Path>>(&self, path: P, cfg: ExportCfg) -> Result<PathBuf, NyxError> {
+        Opm::from_orbit(*self).to_kvn_file(path, cfg)
+    }
+}
+
+/// Keywords and values of the text (KVN) version of an OPM or OMM, where the units of the values are removed.
+struct Kvn {
+    values: HashMap<String, String>,
+}
+
+impl Kvn {
+    fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, NyxError> {
+        let file =
+            File::open(path).map_err(|e| NyxError::CCSDS(format!("File opening error: {e}")))?;
+        let reader = BufReader::new(file);
+
+        let mut values = HashMap::new();
+        for line in reader.lines() {
+            let line = line.map_err(|e| NyxError::CCSDS(format!("File read error: {e}")))?;
+            if let Some((key, value)) = kvn_pair(&line) {
+                // Keep the first value of each keyword, since later ones may belong to the maneuvers
+                values
+                    .entry(key.to_string())
+                    .or_insert_with(|| value.to_string());
+            }
+        }
+
+        Ok(Self { values })
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    fn get(&self, key: &str) -> Option<&String> {
+        self.values.get(key)
+    }
+
+    fn required(&self, key: &str) -> Result<&str, NyxError> {
+        self.values
+            .get(key)
+            .map(|value| value.as_str())
+            .ok_or_else(|| NyxError::CCSDS(format!("Missing keyword `{key}`")))
+    }
+
+    fn number(&self, key: &str) -> Result<f64, NyxError> {
+        let value = self.required(key)?;
+        value
+            .parse()
+            .map_err(|_| NyxError::CCSDS(format!("Invalid number `{value}` for `{key}`")))
+    }
+}
+
+/// Returns the keyword and value of a text (KVN) line, without the units of the value, e.g. `X = 6503.514 [km]`
+pub(crate) fn kvn_pair(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once('=')?;
+    let value = value.split_once('[').map_or(value, |(value, _)| value);
+    Some((key.trim(), value.trim()))
+}
+
+/// Writes the header of an orbit data message
+fn write_header<W: Write>(
+    writer: &mut W,
+    message: &str,
+    metadata: &HashMap<String, String>,
+    originator: &Option<String>,
+) -> std::io::Result<()> {
+    let iso8601_no_ts = Format::from_str("%Y-%m-%dT%H:%M:%S.%f").unwrap();
+
+    writeln!(writer, "CCSDS_{message}_VERS = 2.0")?;
+    writeln!(
+        writer,
+        "COMMENT Generated by {} provided in AGPLv3 license -- https://nyxspace.com/",
+        prj_name_ver()
+    )?;
+    writeln!(
+        writer,
+        "CREATION_DATE = {}",
+        Formatter::new(Epoch::now().unwrap(), iso8601_no_ts)
+    )?;
+    writeln!(
+        writer,
+        "ORIGINATOR = {}\n",
+        metadata
+            .get("originator")
+            .cloned()
+            .or_else(|| originator.clone())
+            .unwrap_or_else(|| Watermark::current()
+                .organization
+                .unwrap_or_else(|| "Nyx Space".to_string()))
+    )
+}
+
+/// Writes the object name and ID of an orbit data message
+fn write_object<W: Write>(
+    writer: &mut W,
+    metadata: &HashMap<String, String>,
+    object_name: &str,
+    object_id: &str,
+) -> std::io::Result<()> {
+    writeln!(
+        writer,
+        "OBJECT_NAME = {}",
+        metadata
+            .get("object_name")
+            .map_or(object_name, |name| name.as_str())
+    )?;
+    writeln!(
+        writer,
+        "OBJECT_ID = {}",
+        metadata
+            .get("object_id")
+            .map_or(object_id, |id| id.as_str())
+    )
+}
+
+/// Writes the center, reference frame and time system of an orbit data message
+fn write_frame<W: Write>(writer: &mut W, frame: Frame, ts: TimeScale) -> std::io::Result<()> {
+    let (center, ref_frame) = ccsds_center_ref_frame(frame);
+    writeln!(writer, "CENTER_NAME = {center}")?;
+    writeln!(writer, "REF_FRAME = {ref_frame}")?;
+    writeln!(writer, "TIME_SYSTEM = {}", ccsds_time_system(ts))
+}
+
+/// Returns the frame of the provided CCSDS center name and reference frame.
+/// Only the inertial frames (`ICRF`, `EME2000` and `J2000`) and the IAU body fixed frames (e.g. `IAU_EARTH`) are supported.
+pub(crate) fn parse_ccsds_frame(
+    center: &str,
+    ref_frame: &str,
+    cosm: &Arc<Cosm>,
+) -> Result<Frame, NyxError> {
+    match ref_frame.to_uppercase().replace(' ', "_").as_str() {
+        "ICRF" | "EME2000" | "J2000" => cosm.try_frame(&format!("{center} J2000")),
+        iau if iau.starts_with("IAU_") => cosm.try_frame(&format!("IAU {center}")),
+        _ => Err(NyxError::CCSDS(format!(
+            "Unsupported reference frame `{ref_frame}`"
+        ))),
+    }
+}
+
+/// Returns the CCSDS center name and reference frame of the provided frame
+pub(crate) fn ccsds_center_ref_frame(frame: Frame) -> (String, String) {
+    let center = Bodies::try_from(frame.ephem_path().to_vec())
+        .map(|body| body.name())
+        .unwrap_or_else(|_| frame.to_string());
+    let ref_frame = match frame.frame_path().len() {
+        0 | 1 => "ICRF".to_string(),
+        2 => format!("IAU_{}", center.to_uppercase()),
+        _ => frame.to_string(),
+    };
+    (center, ref_frame)
+}
+
+/// Parses a CCSDS time system
+pub(crate) fn parse_time_system(time_system: &str) -> Result<TimeScale, NyxError> {
+    TimeScale::from_str(time_system)
+        .map_err(|_| NyxError::CCSDS(format!("Unsupported time system `{time_system}`")))
+}
+
+/// Returns the CCSDS name of the time system
+pub(crate) fn ccsds_time_system(ts: TimeScale) -> String {
+    match ts {
+        TimeScale::GPST => "GPS".to_string(),
+        _ => ts.to_string(),
+    }
+}
+
+/// Parses a CCSDS epoch in the provided time system, either in calendar format `YYYY-MM-DDThh:mm:ss[.d]` or day of year format `YYYY-DDDThh:mm:ss[.d]`
+pub(crate) fn parse_ccsds_epoch(epoch: &str, ts: TimeScale) -> Result<Epoch, NyxError> {
+    let err = |e| NyxError::CCSDS(format!("Parsing epoch `{epoch}` error: {e}"));
+
+    let calendar = match epoch.split_once('T') {
+        Some((date, time)) => match date.split_once('-') {
+            Some((year, doy)) if doy.len() == 3 && !doy.contains('-') => {
+                let year: i32 = year.parse().map_err(|_| err("invalid year".to_string()))?;
+                let doy: u32 = doy
+                    .parse()
+                    .map_err(|_| err("invalid day of year".to_string()))?;
+                let (month, day) =
+                    month_day(year, doy).ok_or_else(|| err("invalid day of year".to_string()))?;
+                format!("{year:04}-{month:02}-{day:02}T{time}")
+            }
+            _ => epoch.to_string(),
+        },
+        None => epoch.to_string(),
+    };
+
+    Epoch::from_str(&format!("{calendar} {ts}")).map_err(|e| err(e.to_string()))
+}
+
+/// Converts a day of year into its month and day of month
+fn month_day(year: i32, doy: u32) -> Option<(u32, u32)> {
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_months = [
+        31,
+        if leap { 29 } else { 28 },
+        31,
+        30,
+        31,
+        30,
+        31,
+        31,
+        30,
+        31,
+        30,
+        31,
+    ];
+    let mut day = doy;
+    for (month, days) in days_in_months.iter().enumerate() {
+        if day == 0 {
+            return None;
+        } else if day <= *days {
+            return Some((month as u32 + 1, day));
+        }
+        day -= days;
+    }
+    None
+}
+
+#[cfg(test)]
+mod ut_ccsds_odm {
+    use super::*;
+    use crate::time::TimeUnits;
+    use std::env;
+
+    fn ccsds_path(kind: &str, name: &str) -> PathBuf {
+        [
+            env!("CARGO_MANIFEST_DIR"),
+            "data",
+            "tests",
+            "ccsds",
+            kind,
+            name,
+        ]
+        .iter()
+        .collect()
+    }
+
+    fn output_path(name: &str) -> PathBuf {
+        [env!("CARGO_MANIFEST_DIR"), "output_data", name]
+            .iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_opm() {
+        let _ = pretty_env_logger::try_init();
+
+        let path = ccsds_path("opm", "eutelsat_w4.opm");
+        let opm = Opm::from_file(&path).unwrap();
+
+        assert_eq!(opm.object_name, "EUTELSAT W4");
+        assert_eq!(opm.object_id, "2000-028A");
+        assert_eq!(opm.originator.as_deref(), Some("GSOC"));
+        assert_eq!(opm.mass_kg, Some(1913.0));
+        assert_eq!(opm.orbit.x_km, 6655.9942);
+        assert_eq!(opm.orbit.vz_km_s, -0.00101495);
+        assert_eq!(
+            opm.orbit.epoch,
+            Epoch::from_gregorian_utc_at_midnight(2006, 6, 3)
+        );
+        assert_eq!(opm.orbit.frame, Cosm::de438().frame("EME2000"));
+        assert_eq!(Orbit::from_opm(&path).unwrap(), opm.orbit);
+
+        let covariance = opm.covariance.as_ref().unwrap();
+        assert_eq!(covariance.epoch, opm.orbit.epoch);
+        assert_eq!(covariance.ref_frame, None);
+        assert_eq!(covariance.covar[(4, 0)], -2.211832501084875e-07);
+        assert_eq!(covariance.covar, covariance.covar.transpose());
+
+        // Round trip
+        let out_path = opm
+            .to_kvn_file(output_path("eutelsat_w4.opm"), ExportCfg::default())
+            .unwrap();
+        let opm_reloaded = Opm::from_file(out_path).unwrap();
+        assert_eq!(opm_reloaded, opm);
+
+        // Exporting an orbit uses the object name of the metadata
+        let cfg =
+            ExportCfg::from_metadata(vec![("object_name".to_string(), "TEST_OBJ".to_string())]);
+        let out_path = opm.orbit.to_opm(output_path("test_obj.opm"), cfg).unwrap();
+        let opm_reloaded = Opm::from_file(out_path).unwrap();
+        assert_eq!(opm_reloaded.object_name, "TEST_OBJ");
+        assert_eq!(opm_reloaded.orbit, opm.orbit);
+        assert!(opm_reloaded.covariance.is_none());
+    }
+
+    #[test]
+    fn test_omm() {
+        let _ = pretty_env_logger::try_init();
+
+        // Two-line element sets in TEME are not supported
+        assert!(Omm::from_file(ccsds_path("omm", "goes9_teme.omm")).is_err());
+
+        let omm = Omm::from_file(ccsds_path("omm", "goes9_icrf.omm")).unwrap();
+        assert_eq!(omm.object_name, "GOES 9");
+        assert_eq!(omm.mean_element_theory, "DSST");
+        assert_eq!(omm.frame.gm(), 398600.8);
+        // Day of year epoch
+        assert_eq!(
+            omm.epoch,
+            Epoch::from_gregorian_utc(2007, 3, 5, 10, 34, 41, 426_400_000)
+        );
+        // Geostationary semi-major axis from the mean motion
+        assert!((omm.sma_km - 42164.8).abs() < 1.0, "{}", omm.sma_km);
+
+        let covariance = omm.covariance.as_ref().unwrap();
+        assert_eq!(covariance.ref_frame.as_deref(), Some("TEME"));
+
+        let orbit = omm.to_orbit().unwrap();
+        assert!((orbit.sma_km() - omm.sma_km).abs() < 1e-6);
+        assert!((orbit.ma_deg() - omm.ma_deg).abs() < 1e-6);
+
+        // Round trip
+        let out_path = omm
+            .to_kvn_file(output_path("goes9.omm"), ExportCfg::default())
+            .unwrap();
+        assert_eq!(Omm::from_file(out_path).unwrap(), omm);
+
+        // From an orbit, whose elements are osculating
+        let orbit = Orbit::keplerian(
+            7000.0,
+            0.01,
+            51.6,
+            45.0,
+            30.0,
+            60.0,
+            omm.epoch + 1.days(),
+            Cosm::de438().frame("EME2000"),
+        );
+        let out_path = Omm::from_orbit(orbit)
+            .to_kvn_file(output_path("osculating.omm"), ExportCfg::default())
+            .unwrap();
+        let omm = Omm::from_file(out_path).unwrap();
+        assert_eq!(omm.mean_element_theory, "OSCULATING");
+        let orbit_reloaded = omm.to_orbit().unwrap();
+        assert!((orbit_reloaded.radius() - orbit.radius()).norm() < 1e-6);
+        assert!((orbit_reloaded.velocity() - orbit.velocity()).norm() < 1e-9);
+    }
+}
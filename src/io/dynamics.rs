@@ -23,6 +23,8 @@ use serde::{Deserialize, Serialize};
 use super::{frames_from_str, frames_to_str, ConfigRepr};
 
 use crate::cosmic::{Bodies, Frame};
+use crate::dynamics::sph_harmonics::FidelityBand;
+use crate::dynamics::{PlateModel, RadiationDistribution, RadiationGrid};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct HarmonicsSerde {
@@ -30,6 +32,9 @@ pub struct HarmonicsSerde {
     pub coeffs: String,
     pub degree: usize,
     pub order: usize,
+    /// Degree and order evaluated by radial distance, if the field is adaptive
+    #[serde(default)]
+    pub bands: Vec<FidelityBand>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -39,6 +44,27 @@ pub struct SrpSerde {
     pub shadows: Vec<Frame>,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PlanetaryRadiationSerde {
+    /// Inertial frame centered on the radiating body, e.g. "EME2000"
+    pub frame: String,
+    pub phi: Option<f64>,
+    #[serde(default)]
+    pub distribution: RadiationDistribution,
+    #[serde(default)]
+    pub grid: RadiationGrid,
+    /// Set to false to ignore the albedo
+    #[serde(default = "enabled")]
+    pub albedo: bool,
+    /// Set to false to ignore the infrared emission
+    #[serde(default = "enabled")]
+    pub infrared: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
 /// A representation of spacecraft dynamics that need to be used in Python with the spacecraft Propagator class.
 #[derive(Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "python", pyclass)]
@@ -46,6 +72,12 @@ pub struct DynamicsSerde {
     pub point_masses: Vec<Bodies>,
     pub harmonics: Option<Vec<HarmonicsSerde>>,
     pub srp: Option<SrpSerde>,
+    /// Albedo and infrared radiation pressure of a body
+    #[serde(default)]
+    pub planetary_radiation: Option<PlanetaryRadiationSerde>,
+    /// Plates of the spacecraft used by the non-gravitational forces instead of its cannonball areas
+    #[serde(default)]
+    pub plates: Option<PlateModel>,
 }
 
 impl ConfigRepr for DynamicsSerde {}
@@ -77,6 +109,22 @@ hifi:
     shadows:
       - Sun J2000
       - Moon J2000
+  planetary_radiation:
+    frame: EME2000
+    grid: !Rings 3
+    infrared: false
+  plates:
+    attitude: VNC
+    plates:
+      - area_m2: 2.0
+        normal: [1.0, 0.0, 0.0]
+        specular: 0.2
+        diffuse: 0.3
+      - area_m2: 10.0
+        normal: [0.0, 0.0, 1.0]
+        specular: 0.05
+        diffuse: 0.1
+        sun_tracking: true
 ";
 
     let cosm = Cosm::de438();
@@ -88,6 +136,8 @@ hifi:
         SpacecraftDynamics::from_config(dynamics_serde.remove("hifi").unwrap(), cosm.clone())
             .unwrap();
     println!("hifi dynamics: {}", hifi_dynamics);
+    assert!(format!("{hifi_dynamics}")
+        .contains("albedo radiation pressure of Earth J2000 (Knocke, 37 elements) on 2 plates"));
 
     // Access the "lofi" dynamics
     let lofi_dynamics =
@@ -48,6 +48,17 @@ impl HarmonicsMem {
         }
     }
 
+    /// Initialize `HarmonicsMem` from the normalized C_nm and S_nm, indexed by degree then order (e.g. time varying corrections)
+    pub fn from_cs(c_nm: DMatrix<f64>, s_nm: DMatrix<f64>) -> HarmonicsMem {
+        assert_eq!(c_nm.shape(), s_nm.shape(), "C_nm and S_nm shapes differ");
+        HarmonicsMem {
+            degree: c_nm.nrows(),
+            order: c_nm.ncols() - 1,
+            c_nm,
+            s_nm,
+        }
+    }
+
     /// Initialize `HarmonicsMem` as an EARTH J<sub>2</sub> only using the JGM3 model (available in GMAT)
     ///
     /// Use the embedded Earth parameter. If others are needed, load from `from_shadr` or `from_egm`.
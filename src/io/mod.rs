@@ -17,10 +17,9 @@
 */
 
 use crate::errors::NyxError;
-use crate::md::StateParameter;
+use crate::md::{NamedParameter, StateParameter};
 use crate::time::Epoch;
 use crate::Orbit;
-pub(crate) mod watermark;
 use hifitime::prelude::{Format, Formatter};
 use hifitime::Duration;
 use serde::de::DeserializeOwned;
@@ -40,8 +39,14 @@ use std::sync::Arc;
 use typed_builder::TypedBuilder;
 
 use self::orbit::OrbitSerde;
-use crate::cosmic::{Cosm, Frame};
-
+use crate::cosmic::{AngleUnit, Cosm, Frame};
+#[cfg(feature = "io")]
+use arrow::datatypes::Field;
+
+/// Handles reading and writing catalogs of states of many objects
+pub mod catalog;
+/// Handles reading and writing CCSDS Orbit Data Messages: ephemerides (OEM), orbit parameters (OPM) and mean elements (OMM)
+pub mod ccsds;
 /// Handles writing to an XYZV file
 pub mod cosmo;
 pub mod dynamics;
@@ -52,8 +57,16 @@ pub mod frame_serde;
 pub mod gravity;
 pub mod matrices;
 pub mod orbit;
+/// Handles loading of the space weather indices from the CelesTrak files and NOAA forecasts
+pub mod space_weather;
+/// Handles reading and writing CCSDS Tracking Data Messages
+pub mod tdm;
+#[cfg(feature = "io")]
 pub mod tracking_data;
+#[cfg(feature = "io")]
 pub mod trajectory_data;
+/// Handles the watermark stored in the metadata of the generated files
+pub mod watermark;
 
 use std::io;
 use thiserror::Error;
@@ -68,6 +81,9 @@ pub struct ExportCfg {
     /// Fields to export, if unset, defaults to all possible fields.
     #[builder(default, setter(strip_option))]
     pub fields: Option<Vec<StateParameter>>,
+    /// Named parameters of user-defined states to export, if unset, defaults to all the named parameters of the state.
+    #[builder(default, setter(strip_option))]
+    pub named_fields: Option<Vec<NamedParameter>>,
     /// Start epoch to export, defaults to the start of the trajectory
     #[builder(default, setter(strip_option))]
     pub start_epoch: Option<Epoch>,
@@ -83,6 +99,27 @@ pub struct ExportCfg {
     /// Set to true to append the timestamp to the filename
     #[builder(default)]
     pub timestamp: bool,
+    /// Unit of the distance, velocity and energy fields, defaults to kilometers
+    #[builder(default, setter(strip_option))]
+    pub length_unit: Option<LengthUnit>,
+    /// Unit of the angle fields, defaults to degrees
+    #[builder(default, setter(strip_option))]
+    pub angle_unit: Option<AngleUnit>,
+    /// Number of significant decimal digits to keep in the floating point fields, defaults to full precision.
+    /// Reducing the precision zeros out the least significant bits of each value, which significantly shrinks the compressed file.
+    #[builder(default, setter(strip_option))]
+    pub significant_digits: Option<u8>,
+    /// Maximum number of rows in each row group of the Parquet file, defaults to the Parquet writer default
+    #[builder(default, setter(strip_option))]
+    pub row_group_size: Option<usize>,
+}
+
+/// Unit of the distances in an export, velocities and energies are scaled accordingly.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LengthUnit {
+    #[default]
+    Kilometers,
+    Meters,
 }
 
 impl ExportCfg {
@@ -106,6 +143,36 @@ impl ExportCfg {
         }
     }
 
+    /// Initialize a new configuration which only exports the Cartesian state.
+    pub fn cartesian() -> Self {
+        Self {
+            fields: Some(vec![
+                StateParameter::X,
+                StateParameter::Y,
+                StateParameter::Z,
+                StateParameter::VX,
+                StateParameter::VY,
+                StateParameter::VZ,
+            ]),
+            ..Default::default()
+        }
+    }
+
+    /// Initialize a new configuration which only exports the Keplerian orbital elements.
+    pub fn keplerian() -> Self {
+        Self {
+            fields: Some(vec![
+                StateParameter::SMA,
+                StateParameter::Eccentricity,
+                StateParameter::Inclination,
+                StateParameter::RAAN,
+                StateParameter::AoP,
+                StateParameter::TrueAnomaly,
+            ]),
+            ..Default::default()
+        }
+    }
+
     pub fn append_field(&mut self, field: StateParameter) {
         if let Some(fields) = self.fields.as_mut() {
             fields.push(field);
@@ -114,6 +181,47 @@ impl ExportCfg {
         }
     }
 
+    /// Returns the unit of the provided parameter in this export and the factor to convert its value into that unit.
+    #[cfg(feature = "io")]
+    pub(crate) fn unit_of(&self, param: StateParameter) -> (&'static str, f64) {
+        match (
+            param.unit(),
+            self.length_unit.unwrap_or_default(),
+            self.angle_unit,
+        ) {
+            ("km", LengthUnit::Meters, _) => ("m", 1e3),
+            ("km/s", LengthUnit::Meters, _) => ("m/s", 1e3),
+            ("km^2/s^2", LengthUnit::Meters, _) => ("m^2/s^2", 1e6),
+            ("deg", _, Some(AngleUnit::Radians)) => ("rad", 1.0_f64.to_radians()),
+            (unit, _, _) => (unit, 1.0),
+        }
+    }
+
+    /// Returns the parquet field of the provided parameter in the unit of this export.
+    #[cfg(feature = "io")]
+    pub(crate) fn field_of(
+        &self,
+        param: StateParameter,
+        more_meta: Option<Vec<(String, String)>>,
+    ) -> Field {
+        param.to_field_with_unit(self.unit_of(param).0, more_meta)
+    }
+
+    /// Converts the value of the provided parameter into the unit and precision of this export.
+    #[cfg(feature = "io")]
+    pub(crate) fn export_value(&self, param: StateParameter, value: f64) -> f64 {
+        self.round(value * self.unit_of(param).1)
+    }
+
+    /// Rounds the provided unitless value to the significant digits of this configuration, if any.
+    #[cfg(feature = "io")]
+    pub(crate) fn round(&self, value: f64) -> f64 {
+        match self.significant_digits {
+            Some(digits) => round_mantissa(value, digits),
+            None => value,
+        }
+    }
+
     /// Modifies the provided path to include the timestamp if required.
     pub(crate) fn actual_path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
         let mut path_buf = path.as_ref().to_path_buf();
@@ -137,6 +245,21 @@ impl ExportCfg {
     }
 }
 
+/// Rounds the mantissa of the provided value to the number of bits needed to represent that many significant decimal digits.
+#[cfg(feature = "io")]
+fn round_mantissa(value: f64, digits: u8) -> f64 {
+    // An f64 has 52 explicit mantissa bits, and each decimal digit needs log2(10) bits.
+    let kept_bits = (f64::from(digits) * std::f64::consts::LOG2_10).ceil() as u32 + 1;
+    if !value.is_finite() || kept_bits >= 52 {
+        return value;
+    }
+    let dropped_bits = 52 - kept_bits;
+    let half = 1_u64 << (dropped_bits - 1);
+    let mask = !((1_u64 << dropped_bits) - 1);
+    // Round half up on the magnitude, a carry into the exponent is still the correctly rounded value.
+    f64::from_bits((value.to_bits() + half) & mask)
+}
+
 #[cfg(feature = "python")]
 #[pymethods]
 impl ExportCfg {
@@ -260,6 +383,26 @@ where
     Epoch::from_str(&s).map_err(serde::de::Error::custom)
 }
 
+pub(crate) fn maybe_epoch_to_str<S>(epoch: &Option<Epoch>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match epoch {
+        Some(epoch) => epoch_to_str(epoch, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// A deserializer from an optional Epoch string
+pub(crate) fn maybe_epoch_from_str<'de, D>(deserializer: D) -> Result<Option<Epoch>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| Epoch::from_str(&s).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
 pub(crate) fn duration_to_str<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -0,0 +1,598 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::errors::NyxError;
+use crate::time::{Epoch, Unit};
+use serde_derive::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Ap index at each third of a Kp unit, from Kp = 0 to Kp = 9
+const AP_OF_KP: [f64; 28] = [
+    0.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 9.0, 12.0, 15.0, 18.0, 22.0, 27.0, 32.0, 39.0, 48.0, 56.0,
+    67.0, 80.0, 94.0, 111.0, 132.0, 154.0, 179.0, 207.0, 236.0, 300.0, 400.0,
+];
+
+/// Returns the Kp index matching the provided Ap index, interpolated linearly between the standard conversion values
+pub fn kp_from_ap(ap: f64) -> f64 {
+    if ap >= AP_OF_KP[27] {
+        return 9.0;
+    }
+    let i = AP_OF_KP.iter().rposition(|val| *val <= ap).unwrap_or(0);
+    let frac = (ap - AP_OF_KP[i]) / (AP_OF_KP[i + 1] - AP_OF_KP[i]);
+    (i as f64 + frac.max(0.0)) / 3.0
+}
+
+/// Origin of the space weather data of a day
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpaceWeatherSource {
+    /// Observed values (`OBS` in CelesTrak files)
+    Observed,
+    /// Values interpolated from observations, e.g. when the F10.7 measurement is missing (`INT` in CelesTrak files)
+    Interpolated,
+    /// Daily forecast, e.g. from the NOAA 45 day forecast (`PRD` in CelesTrak files)
+    DailyForecast,
+    /// Monthly forecast (`PRM` in CelesTrak files)
+    MonthlyForecast,
+}
+
+impl SpaceWeatherSource {
+    /// Returns whether this data was measured, as opposed to forecast
+    pub fn is_observed(&self) -> bool {
+        matches!(self, Self::Observed | Self::Interpolated)
+    }
+}
+
+/// The space weather indices of one UTC day
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpaceWeatherDay {
+    /// Start of the day, at midnight UTC
+    pub epoch: Epoch,
+    /// Kp index of each three hour interval of the day
+    pub kp: [f64; 8],
+    /// Ap index of each three hour interval of the day
+    pub ap: [f64; 8],
+    /// Daily average of the Ap index
+    pub ap_avg: f64,
+    /// Observed 10.7 cm solar radio flux, in solar flux units
+    pub f107: f64,
+    /// 81 day average of the observed 10.7 cm solar radio flux centered on this day, in solar flux units
+    pub f107_avg: f64,
+    /// Origin of the data
+    pub source: SpaceWeatherSource,
+}
+
+impl SpaceWeatherDay {
+    /// Initializes a day with the same Ap index for all of its intervals, e.g. from a forecast
+    pub fn from_daily(
+        epoch: Epoch,
+        ap_avg: f64,
+        f107: f64,
+        f107_avg: f64,
+        source: SpaceWeatherSource,
+    ) -> Self {
+        Self {
+            epoch,
+            kp: [kp_from_ap(ap_avg); 8],
+            ap: [ap_avg; 8],
+            ap_avg,
+            f107,
+            f107_avg,
+            source,
+        }
+    }
+}
+
+/// How the space weather indices are computed after the last observed day.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ForecastPolicy {
+    /// Use the forecast data, and return an error after the last forecast day
+    UseForecast,
+    /// Persist the indices of the last observed day
+    Persist,
+    /// Use the provided constant indices
+    Constant { f107: f64, f107_avg: f64, ap: f64 },
+    /// Return an error after the last observed day
+    Reject,
+}
+
+/// The space weather indices at a given epoch
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpaceWeatherIndices {
+    /// 10.7 cm solar radio flux, linearly interpolated between the daily values (taken at noon), in solar flux units
+    pub f107: f64,
+    /// 81 day average of the 10.7 cm solar radio flux, linearly interpolated between the daily values, in solar flux units
+    pub f107_avg: f64,
+    /// Ap index of the three hour interval
+    pub ap: f64,
+    /// Kp index of the three hour interval
+    pub kp: f64,
+    /// Origin of the data
+    pub source: SpaceWeatherSource,
+}
+
+impl fmt::Display for SpaceWeatherIndices {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "F10.7 = {:.1} sfu (81 day avg. {:.1} sfu)\tAp = {:.0}\tKp = {:.2} ({:?})",
+            self.f107, self.f107_avg, self.ap, self.kp, self.source
+        )
+    }
+}
+
+/// Columns of the CelesTrak space weather files which are used
+#[derive(Deserialize)]
+struct CelesTrakRow {
+    #[serde(rename = "DATE")]
+    date: String,
+    #[serde(rename = "KP1")]
+    kp1: Option<f64>,
+    #[serde(rename = "KP2")]
+    kp2: Option<f64>,
+    #[serde(rename = "KP3")]
+    kp3: Option<f64>,
+    #[serde(rename = "KP4")]
+    kp4: Option<f64>,
+    #[serde(rename = "KP5")]
+    kp5: Option<f64>,
+    #[serde(rename = "KP6")]
+    kp6: Option<f64>,
+    #[serde(rename = "KP7")]
+    kp7: Option<f64>,
+    #[serde(rename = "KP8")]
+    kp8: Option<f64>,
+    #[serde(rename = "AP1")]
+    ap1: Option<f64>,
+    #[serde(rename = "AP2")]
+    ap2: Option<f64>,
+    #[serde(rename = "AP3")]
+    ap3: Option<f64>,
+    #[serde(rename = "AP4")]
+    ap4: Option<f64>,
+    #[serde(rename = "AP5")]
+    ap5: Option<f64>,
+    #[serde(rename = "AP6")]
+    ap6: Option<f64>,
+    #[serde(rename = "AP7")]
+    ap7: Option<f64>,
+    #[serde(rename = "AP8")]
+    ap8: Option<f64>,
+    #[serde(rename = "AP_AVG")]
+    ap_avg: Option<f64>,
+    #[serde(rename = "F10.7_OBS")]
+    f107_obs: f64,
+    #[serde(rename = "F10.7_DATA_TYPE")]
+    f107_data_type: String,
+    #[serde(rename = "F10.7_OBS_CENTER81")]
+    f107_obs_center81: Option<f64>,
+}
+
+/// Parses a date formatted as YYYY-MM-DD as midnight UTC
+fn parse_iso_date(date: &str) -> Result<Epoch, NyxError> {
+    let parts = date
+        .trim()
+        .split('-')
+        .map(|part| part.parse::<u32>())
+        .collect::<Result<Vec<u32>, _>>()
+        .map_err(|e| NyxError::FileUnreadable(format!("invalid date `{date}`: {e}")))?;
+    if parts.len() != 3 {
+        return Err(NyxError::FileUnreadable(format!("invalid date `{date}`")));
+    }
+    Ok(Epoch::from_gregorian_utc_at_midnight(
+        parts[0] as i32,
+        parts[1] as u8,
+        parts[2] as u8,
+    ))
+}
+
+/// Parses a date formatted as DDMonYY (e.g. 17Oct23) as midnight UTC
+fn parse_noaa_date(date: &str) -> Result<Epoch, NyxError> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let err = || NyxError::FileUnreadable(format!("invalid date `{date}`"));
+    if date.len() != 7 || !date.is_ascii() {
+        return Err(err());
+    }
+    let day = date[0..2].parse::<u8>().map_err(|_| err())?;
+    let month = MONTHS
+        .iter()
+        .position(|month| month.eq_ignore_ascii_case(&date[2..5]))
+        .ok_or_else(err)?;
+    let year = date[5..7].parse::<i32>().map_err(|_| err())?;
+    Ok(Epoch::from_gregorian_utc_at_midnight(
+        2000 + year,
+        month as u8 + 1,
+        day,
+    ))
+}
+
+/// `SpaceWeather` stores the daily solar flux and geomagnetic indices, e.g. from the CelesTrak space weather files and the NOAA
+/// forecasts, to drive the atmospheric density models.
+///
+/// The forecast policy sets how the indices are computed after the last observed day.
+#[derive(Clone)]
+pub struct SpaceWeather {
+    /// Daily data in chronological order, with at most one entry per day
+    days: Vec<SpaceWeatherDay>,
+    /// Monthly forecasts in chronological order, used for the days without daily data
+    months: Vec<SpaceWeatherDay>,
+    /// How the indices are computed after the last observed day
+    pub policy: ForecastPolicy,
+}
+
+impl SpaceWeather {
+    /// Initializes the space weather from the provided daily data and monthly forecasts, using the forecasts if any
+    pub fn from_days(data: Vec<SpaceWeatherDay>) -> Result<Self, NyxError> {
+        let (mut months, mut days): (Vec<SpaceWeatherDay>, Vec<SpaceWeatherDay>) = data
+            .into_iter()
+            .partition(|day| day.source == SpaceWeatherSource::MonthlyForecast);
+        if days.is_empty() {
+            return Err(NyxError::CustomError(
+                "space weather requires at least one day of data".to_string(),
+            ));
+        }
+        for entries in [&mut days, &mut months] {
+            entries.sort_by_key(|day| day.epoch);
+            if let Some(pair) = entries
+                .windows(2)
+                .find(|pair| pair[0].epoch == pair[1].epoch)
+            {
+                return Err(NyxError::CustomError(format!(
+                    "space weather has several entries for {}",
+                    pair[0].epoch
+                )));
+            }
+        }
+        Ok(Self {
+            days,
+            months,
+            policy: ForecastPolicy::UseForecast,
+        })
+    }
+
+    /// Loads the space weather from a CelesTrak space weather CSV file (e.g. `SW-All.csv`), with the observed and forecast data.
+    ///
+    /// The Kp indices are stored multiplied by ten in these files. Missing three hour Ap indices (e.g. in monthly forecasts) are
+    /// set to the daily average.
+    pub fn from_celestrak_csv<P: AsRef<Path>>(path: P) -> Result<Self, NyxError> {
+        let mut reader =
+            csv::Reader::from_path(path).map_err(|e| NyxError::FileUnreadable(format!("{e}")))?;
+
+        let mut days = Vec::new();
+        for (rno, row) in reader.deserialize::<CelesTrakRow>().enumerate() {
+            let row = row.map_err(|e| NyxError::FileUnreadable(format!("row {rno}: {e}")))?;
+            let epoch = parse_iso_date(&row.date)?;
+            let source = match row.f107_data_type.trim() {
+                "OBS" => SpaceWeatherSource::Observed,
+                "INT" => SpaceWeatherSource::Interpolated,
+                "PRD" => SpaceWeatherSource::DailyForecast,
+                "PRM" => SpaceWeatherSource::MonthlyForecast,
+                other => {
+                    return Err(NyxError::FileUnreadable(format!(
+                        "row {rno}: unknown F10.7 data type `{other}`"
+                    )))
+                }
+            };
+
+            let three_hourly = [
+                row.ap1, row.ap2, row.ap3, row.ap4, row.ap5, row.ap6, row.ap7, row.ap8,
+            ];
+            let ap_avg = match row.ap_avg {
+                Some(ap_avg) => ap_avg,
+                None => {
+                    if three_hourly.iter().all(|ap| ap.is_some()) {
+                        three_hourly.iter().flatten().sum::<f64>() / 8.0
+                    } else {
+                        return Err(NyxError::FileUnreadable(format!(
+                            "row {rno}: no Ap index for {}",
+                            row.date
+                        )));
+                    }
+                }
+            };
+
+            let mut day = SpaceWeatherDay::from_daily(
+                epoch,
+                ap_avg,
+                row.f107_obs,
+                row.f107_obs_center81.unwrap_or(row.f107_obs),
+                source,
+            );
+            let kps = [
+                row.kp1, row.kp2, row.kp3, row.kp4, row.kp5, row.kp6, row.kp7, row.kp8,
+            ];
+            for i in 0..8 {
+                if let Some(ap) = three_hourly[i] {
+                    day.ap[i] = ap;
+                    day.kp[i] = kp_from_ap(ap);
+                }
+                if let Some(kp) = kps[i] {
+                    day.kp[i] = kp / 10.0;
+                }
+            }
+            days.push(day);
+        }
+
+        info!("Loaded {} days of space weather", days.len());
+
+        Self::from_days(days)
+    }
+
+    /// Adds the NOAA 45 day Ap and F10.7 forecast (e.g. `45DF.txt`) to the days which have not been observed yet.
+    ///
+    /// This forecast does not include the 81 day average of the F10.7, which is then set to the average of the available daily
+    /// values within 40 days of each forecast day.
+    pub fn with_noaa_forecast<P: AsRef<Path>>(mut self, path: P) -> Result<Self, NyxError> {
+        let contents =
+            fs::read_to_string(path).map_err(|e| NyxError::FileUnreadable(format!("{e}")))?;
+
+        let (mut ap_fcst, mut f107_fcst) = (Vec::new(), Vec::new());
+        // 0: outside of a section, 1: Ap section, 2: F10.7 section
+        let mut section = 0;
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.starts_with(':') || line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+            let upper = line.to_ascii_uppercase();
+            if upper.contains("AP FORECAST") {
+                section = 1;
+                continue;
+            } else if upper.contains("F10.7") {
+                section = 2;
+                continue;
+            }
+            let tokens = line.split_whitespace().collect::<Vec<&str>>();
+            if section == 0
+                || tokens.len() % 2 != 0
+                || !tokens[0].starts_with(|c: char| c.is_ascii_digit())
+                || tokens[0].len() != 7
+            {
+                section = 0;
+                continue;
+            }
+            for pair in tokens.chunks(2) {
+                let epoch = parse_noaa_date(pair[0])?;
+                let value = pair[1].parse::<f64>().map_err(|e| {
+                    NyxError::FileUnreadable(format!("invalid value `{}`: {e}", pair[1]))
+                })?;
+                if section == 1 {
+                    ap_fcst.push((epoch, value));
+                } else {
+                    f107_fcst.push((epoch, value));
+                }
+            }
+        }
+
+        if ap_fcst.is_empty() || f107_fcst.is_empty() {
+            return Err(NyxError::FileUnreadable(
+                "no Ap or F10.7 forecast found in NOAA file".to_string(),
+            ));
+        }
+
+        let last_observed = self.last_observed();
+        for (epoch, f107) in &f107_fcst {
+            if matches!(last_observed, Some(last) if *epoch <= last) {
+                continue;
+            }
+            let ap = match ap_fcst.iter().find(|(ap_epoch, _)| ap_epoch == epoch) {
+                Some((_, ap)) => *ap,
+                None => continue,
+            };
+            // Average of the F10.7 of the known and forecast days within 40 days
+            let (mut sum, mut count) = (*f107, 1.0);
+            for day in &self.days {
+                if (day.epoch - *epoch).abs() <= 40 * Unit::Day
+                    && !f107_fcst
+                        .iter()
+                        .any(|(fcst_epoch, _)| *fcst_epoch == day.epoch)
+                {
+                    sum += day.f107;
+                    count += 1.0;
+                }
+            }
+            for (other, other_f107) in &f107_fcst {
+                if other != epoch && (*other - *epoch).abs() <= 40 * Unit::Day {
+                    sum += other_f107;
+                    count += 1.0;
+                }
+            }
+
+            let day = SpaceWeatherDay::from_daily(
+                *epoch,
+                ap,
+                *f107,
+                sum / count,
+                SpaceWeatherSource::DailyForecast,
+            );
+            match self.days.binary_search_by_key(epoch, |day| day.epoch) {
+                Ok(idx) => self.days[idx] = day,
+                Err(idx) => self.days.insert(idx, day),
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Sets the forecast policy
+    pub fn with_policy(mut self, policy: ForecastPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the daily data, in chronological order
+    pub fn days(&self) -> &[SpaceWeatherDay] {
+        &self.days
+    }
+
+    /// Returns the monthly forecasts, in chronological order
+    pub fn months(&self) -> &[SpaceWeatherDay] {
+        &self.months
+    }
+
+    /// Returns the start of the last observed day, if any
+    pub fn last_observed(&self) -> Option<Epoch> {
+        self.days
+            .iter()
+            .rev()
+            .find(|day| day.source.is_observed())
+            .map(|day| day.epoch)
+    }
+
+    /// Returns the data of the day of the provided epoch, or the monthly forecast of its month, if available
+    fn day_of(&self, epoch: Epoch) -> Option<&SpaceWeatherDay> {
+        let daily = match self.days.binary_search_by_key(&epoch, |day| day.epoch) {
+            Ok(idx) => Some(&self.days[idx]),
+            Err(0) => None,
+            Err(idx) => Some(&self.days[idx - 1]).filter(|day| epoch - day.epoch < Unit::Day),
+        };
+        daily.or_else(|| {
+            let (year, month, ..) = epoch.to_gregorian_utc();
+            self.months.iter().find(|day| {
+                let (day_year, day_month, ..) = day.epoch.to_gregorian_utc();
+                day_year == year && day_month == month
+            })
+        })
+    }
+
+    /// Returns the space weather indices at the provided epoch, following the forecast policy after the last observed day
+    pub fn at(&self, epoch: Epoch) -> Result<SpaceWeatherIndices, NyxError> {
+        let first = self.days[0].epoch;
+        if epoch < first {
+            return Err(NyxError::CustomError(format!(
+                "no space weather before {first}, requested {epoch}"
+            )));
+        }
+
+        let observed_until = self.last_observed().map(|last| last + Unit::Day);
+        let is_forecast = !matches!(observed_until, Some(until) if epoch < until);
+
+        if is_forecast {
+            match self.policy {
+                ForecastPolicy::UseForecast => {}
+                ForecastPolicy::Persist => {
+                    let last = self.last_observed().ok_or_else(|| {
+                        NyxError::CustomError("no observed space weather to persist".to_string())
+                    })?;
+                    let day = self.day_of(last).unwrap();
+                    return Ok(SpaceWeatherIndices {
+                        f107: day.f107,
+                        f107_avg: day.f107_avg,
+                        ap: day.ap_avg,
+                        kp: kp_from_ap(day.ap_avg),
+                        source: day.source,
+                    });
+                }
+                ForecastPolicy::Constant { f107, f107_avg, ap } => {
+                    return Ok(SpaceWeatherIndices {
+                        f107,
+                        f107_avg,
+                        ap,
+                        kp: kp_from_ap(ap),
+                        source: SpaceWeatherSource::DailyForecast,
+                    });
+                }
+                ForecastPolicy::Reject => {
+                    return Err(NyxError::CustomError(format!(
+                        "space weather is only observed until {}, requested {epoch}",
+                        observed_until.unwrap_or(first)
+                    )));
+                }
+            }
+        }
+
+        let day = self.day_of(epoch).ok_or_else(|| {
+            NyxError::CustomError(format!("no space weather available at {epoch}"))
+        })?;
+
+        // Linear interpolation of the daily flux values taken at noon, with the neighboring day if available
+        let noon = day.epoch + 12 * Unit::Hour;
+        let neighbor = if epoch >= noon {
+            self.day_of(day.epoch + Unit::Day)
+        } else {
+            self.day_of(day.epoch - Unit::Day)
+        };
+        let (f107, f107_avg) = match neighbor {
+            Some(other) => {
+                let frac = ((epoch - noon).to_seconds() / Unit::Day.in_seconds()).abs();
+                (
+                    day.f107 + frac * (other.f107 - day.f107),
+                    day.f107_avg + frac * (other.f107_avg - day.f107_avg),
+                )
+            }
+            _ => (day.f107, day.f107_avg),
+        };
+
+        let interval = (((epoch - day.epoch).to_seconds() / 10_800.0) as usize).min(7);
+
+        Ok(SpaceWeatherIndices {
+            f107,
+            f107_avg,
+            ap: day.ap[interval],
+            kp: day.kp[interval],
+            source: day.source,
+        })
+    }
+}
+
+impl fmt::Debug for SpaceWeather {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let last_day = self.days[self.days.len() - 1].epoch;
+        write!(
+            f,
+            "SpaceWeather {{ {} days and {} months from {} to {}, observed until {}, {:?} }}",
+            self.days.len(),
+            self.months.len(),
+            self.days[0].epoch,
+            self.months
+                .last()
+                .map_or(last_day, |month| month.epoch.max(last_day)),
+            self.last_observed()
+                .map_or("never".to_string(), |last| format!("{last}")),
+            self.policy
+        )
+    }
+}
+
+#[cfg(test)]
+mod ut_space_weather {
+    use super::*;
+
+    #[test]
+    fn kp_ap_conversion() {
+        assert_eq!(kp_from_ap(0.0), 0.0);
+        assert!((kp_from_ap(27.0) - 4.0).abs() < 1e-12);
+        assert!((kp_from_ap(400.0) - 9.0).abs() < 1e-12);
+        assert_eq!(kp_from_ap(1000.0), 9.0);
+        // Halfway between 3o (15) and 3+ (18)
+        assert!((kp_from_ap(16.5) - 3.0 - 0.5 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn noaa_dates() {
+        assert_eq!(
+            parse_noaa_date("17Oct23").unwrap(),
+            Epoch::from_gregorian_utc_at_midnight(2023, 10, 17)
+        );
+        assert!(parse_noaa_date("17Foo23").is_err());
+        assert!(parse_noaa_date("2023-10-17").is_err());
+    }
+}
@@ -0,0 +1,572 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::ccsds::{ccsds_time_system, parse_ccsds_epoch, parse_time_system};
+use super::watermark::{prj_name_ver, Watermark};
+use super::ExportCfg;
+use crate::errors::NyxError;
+#[cfg(feature = "io")]
+use crate::linalg::allocator::Allocator;
+#[cfg(feature = "io")]
+use crate::linalg::{DefaultAllocator, OVector};
+#[cfg(feature = "io")]
+use crate::od::msr::TrackingArc;
+#[cfg(feature = "io")]
+use crate::od::Measurement;
+use crate::time::{Epoch, Format, Formatter, TimeScale};
+#[cfg(feature = "io")]
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Observation keywords of the data section of a CCSDS Tracking Data Message which can be converted to and from Nyx measurements.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TdmKeyword {
+    /// Range, in the units of the `RANGE_UNITS` metadata (only kilometers are supported)
+    Range,
+    /// Instantaneous range rate, in km/s
+    DopplerInstantaneous,
+    /// First angle of the `ANGLE_TYPE` metadata (right ascension for `RADEC`), in degrees
+    Angle1,
+    /// Second angle of the `ANGLE_TYPE` metadata (declination for `RADEC`), in degrees
+    Angle2,
+}
+
+impl TdmKeyword {
+    /// Returns the TDM keyword
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::Range => "RANGE",
+            Self::DopplerInstantaneous => "DOPPLER_INSTANTANEOUS",
+            Self::Angle1 => "ANGLE_1",
+            Self::Angle2 => "ANGLE_2",
+        }
+    }
+
+    /// Returns the supported keyword matching the provided TDM keyword, if any
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "RANGE" => Some(Self::Range),
+            "DOPPLER_INSTANTANEOUS" => Some(Self::DopplerInstantaneous),
+            "ANGLE_1" => Some(Self::Angle1),
+            "ANGLE_2" => Some(Self::Angle2),
+            _ => None,
+        }
+    }
+
+    /// Returns the keyword of the provided measurement field (cf. `Measurement::fields`), if it can be exchanged in a TDM
+    pub fn from_field_name(name: &str) -> Option<Self> {
+        match name {
+            "Range (km)" => Some(Self::Range),
+            "Doppler (km/s)" => Some(Self::DopplerInstantaneous),
+            "Right ascension (deg)" => Some(Self::Angle1),
+            "Declination (deg)" => Some(Self::Angle2),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this is an angle keyword, which requires the `RADEC` angle type
+    pub fn is_angle(&self) -> bool {
+        matches!(self, Self::Angle1 | Self::Angle2)
+    }
+}
+
+impl fmt::Display for TdmKeyword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.keyword())
+    }
+}
+
+/// A single observation of the data section of a TDM segment
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TdmObservation {
+    pub epoch: Epoch,
+    pub keyword: TdmKeyword,
+    pub value: f64,
+}
+
+/// A segment of a TDM, i.e. its metadata and the observations of its data section.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TdmSegment {
+    /// Participants of the tracking, in order: the first participant is the tracking device of the measurements
+    pub participants: Vec<String>,
+    /// Time system of the epochs of this segment
+    pub time_system: TimeScale,
+    /// Signal path, e.g. `1,2,1` for a two-way measurement from the first participant
+    pub path: Option<String>,
+    /// Units of the range, kilometers if unset
+    pub range_units: Option<String>,
+    /// Type of the angles, e.g. `RADEC` or `AZEL`
+    pub angle_type: Option<String>,
+    /// Supported observations of this segment, the other keywords are skipped
+    pub observations: Vec<TdmObservation>,
+}
+
+impl TdmSegment {
+    /// Returns the name of the tracking device of this segment, i.e. its first participant
+    pub fn tracking_device(&self) -> Option<&String> {
+        self.participants.first()
+    }
+
+    /// Returns whether the range of this segment is in kilometers
+    pub fn range_in_km(&self) -> bool {
+        matches!(self.range_units.as_deref(), None | Some("km"))
+    }
+}
+
+/// A CCSDS Tracking Data Message (TDM, CCSDS 503.0-B-2) of range, Doppler and angle observations.
+///
+/// # Limitations
+/// 1. Only the `RANGE` (in km), `DOPPLER_INSTANTANEOUS` and `ANGLE_1`/`ANGLE_2` (only `RADEC` angles convert to measurements) keywords are read,
+///    and the other observations are skipped
+/// 2. The XML version is read line by line, so each element must be on its own line
+/// 3. The values are exchanged as is: no correction, delay or time tag reference is applied
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tdm {
+    pub originator: Option<String>,
+    pub segments: Vec<TdmSegment>,
+}
+
+impl Tdm {
+    /// Loads a TDM in either its text (KVN) or its XML version from the provided path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, NyxError> {
+        let file =
+            File::open(path).map_err(|e| NyxError::CCSDS(format!("File opening error: {e}")))?;
+        let reader = BufReader::new(file);
+
+        let mut tdm = Self::default();
+        let mut segment: Option<TdmSegment> = None;
+        let mut metadata: HashMap<String, String> = HashMap::new();
+        let mut in_meta = false;
+        let mut in_data = false;
+        let mut xml = false;
+        // In the XML version, the epoch is given by its own element before the observation
+        let mut xml_epoch: Option<String> = None;
+        let mut skipped: HashMap<String, usize> = HashMap::new();
+
+        for (lno, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| NyxError::CCSDS(format!("File read error: {e}")))?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if lno == 0 && line.starts_with("<?xml") {
+                xml = true;
+                continue;
+            }
+
+            // Find the block delimiters and the key-value pairs in either version
+            let (key, value) = if xml {
+                match line {
+                    "<metadata>" => ("META_START", ""),
+                    "</metadata>" => ("META_STOP", ""),
+                    "<data>" => ("DATA_START", ""),
+                    "</data>" => ("DATA_STOP", ""),
+                    _ => match xml_element(line) {
+                        Some(element) => element,
+                        None => continue,
+                    },
+                }
+            } else {
+                match line.split_once('=') {
+                    Some((key, value)) => (key.trim(), value.trim()),
+                    None => (line.split_whitespace().next().unwrap(), ""),
+                }
+            };
+
+            match key {
+                "COMMENT" => continue,
+                "META_START" => {
+                    if let Some(segment) = segment.take() {
+                        tdm.segments.push(segment);
+                    }
+                    metadata.clear();
+                    in_meta = true;
+                }
+                "META_STOP" => {
+                    in_meta = false;
+                    segment = Some(segment_from_metadata(&metadata, lno)?);
+                }
+                "DATA_START" => {
+                    if segment.is_none() {
+                        return Err(NyxError::CCSDS(format!(
+                            "[line: {}] Data section without metadata",
+                            lno + 1
+                        )));
+                    }
+                    in_data = true;
+                }
+                "DATA_STOP" => in_data = false,
+                _ if in_meta => {
+                    metadata.insert(key.to_string(), value.to_string());
+                }
+                _ if in_data => {
+                    let segment = segment.as_mut().unwrap();
+                    let (epoch_str, value_str) = if xml {
+                        if key == "EPOCH" {
+                            xml_epoch = Some(value.to_string());
+                            continue;
+                        }
+                        match &xml_epoch {
+                            Some(epoch_str) => (epoch_str.as_str(), value),
+                            None => {
+                                return Err(NyxError::CCSDS(format!(
+                                    "[line: {}] Observation `{key}` without epoch",
+                                    lno + 1
+                                )))
+                            }
+                        }
+                    } else {
+                        let parts: Vec<&str> = value.split_whitespace().collect();
+                        if parts.len() != 2 {
+                            return Err(NyxError::CCSDS(format!(
+                                "[line: {}] Could not understand `{line}`",
+                                lno + 1
+                            )));
+                        }
+                        (parts[0], parts[1])
+                    };
+
+                    match TdmKeyword::from_keyword(key) {
+                        Some(keyword) => {
+                            let value = value_str.parse::<f64>().map_err(|e| {
+                                NyxError::CCSDS(format!(
+                                    "[line: {}] Parsing `{key}` value error: {e}",
+                                    lno + 1
+                                ))
+                            })?;
+                            segment.observations.push(TdmObservation {
+                                epoch: parse_ccsds_epoch(epoch_str, segment.time_system)?,
+                                keyword,
+                                value,
+                            });
+                        }
+                        None => *skipped.entry(key.to_string()).or_default() += 1,
+                    }
+                }
+                "ORIGINATOR" => tdm.originator = Some(value.to_string()),
+                _ => debug!("[line: {}] Skipping `{line}`", lno + 1),
+            }
+        }
+
+        if let Some(segment) = segment.take() {
+            tdm.segments.push(segment);
+        }
+
+        for (keyword, count) in skipped {
+            debug!("Skipped {count} unsupported `{keyword}` observations");
+        }
+
+        info!(
+            "Loaded TDM with {} segments of {} observations",
+            tdm.segments.len(),
+            tdm.segments
+                .iter()
+                .map(|segment| segment.observations.len())
+                .sum::<usize>()
+        );
+
+        Ok(tdm)
+    }
+
+    /// Converts the observations of this TDM to a tracking arc of the provided measurement type, where the tracking device of each
+    /// measurement is the first participant of its segment. Each measurement is built from the observations of all of its fields at the
+    /// same epoch, e.g. a `RangeDoppler` needs both the `RANGE` and the `DOPPLER_INSTANTANEOUS` observations.
+    ///
+    /// Returns an error if the measurement has fields which cannot be exchanged in a TDM, if a range is needed from a segment whose range
+    /// is not in kilometers, or if no measurement could be built. Segments whose angles are not `RADEC` are skipped if angles are needed.
+    #[cfg(feature = "io")]
+    pub fn to_tracking_arc<Msr>(&self) -> Result<TrackingArc<Msr>, NyxError>
+    where
+        Msr: Measurement,
+        DefaultAllocator: Allocator<f64, Msr::MeasurementSize>,
+    {
+        let keywords = msr_keywords::<Msr>()?;
+        let needs_angles = keywords.iter().any(|keyword| keyword.is_angle());
+
+        let mut arc = TrackingArc {
+            device_cfg: String::new(),
+            measurements: Vec::new(),
+        };
+
+        for (sno, segment) in self.segments.iter().enumerate() {
+            let device = segment.tracking_device().ok_or_else(|| {
+                NyxError::CCSDS(format!("Segment {sno} does not have any participant"))
+            })?;
+
+            if keywords.contains(&TdmKeyword::Range) && !segment.range_in_km() {
+                return Err(NyxError::CCSDS(format!(
+                    "Range units of segment {sno} must be km, got {}",
+                    segment.range_units.as_ref().unwrap()
+                )));
+            }
+
+            if needs_angles && segment.angle_type.as_deref() != Some("RADEC") {
+                warn!(
+                    "Skipping segment {sno} of {device}: angles must be RADEC, got {:?}",
+                    segment.angle_type
+                );
+                continue;
+            }
+
+            // Gather the fields of each measurement by epoch
+            let mut values: BTreeMap<Epoch, Vec<Option<f64>>> = BTreeMap::new();
+            for obs in &segment.observations {
+                if let Some(idx) = keywords.iter().position(|keyword| *keyword == obs.keyword) {
+                    values
+                        .entry(obs.epoch)
+                        .or_insert_with(|| vec![None; keywords.len()])[idx] = Some(obs.value);
+                }
+            }
+
+            for (epoch, fields) in values {
+                if fields.iter().all(|field| field.is_some()) {
+                    let obs = OVector::<f64, Msr::MeasurementSize>::from_iterator(
+                        fields.into_iter().map(|field| field.unwrap()),
+                    );
+                    arc.measurements
+                        .push((device.clone(), Msr::from_observation(epoch, obs)));
+                } else {
+                    debug!("Skipping incomplete measurement of {device} at {epoch}");
+                }
+            }
+        }
+
+        if arc.measurements.is_empty() {
+            return Err(NyxError::CCSDS(format!(
+                "No {} measurement in TDM",
+                keywords
+                    .iter()
+                    .map(|keyword| keyword.keyword())
+                    .collect::<Vec<&str>>()
+                    .join(" and ")
+            )));
+        }
+
+        arc.measurements.sort_by_key(|(_, msr)| msr.epoch());
+
+        Ok(arc)
+    }
+
+    /// Builds a TDM from the provided tracking arc, with one segment per tracking device whose participants are the tracking device
+    /// and the provided spacecraft. The epochs are in UTC and the path is two-way.
+    #[cfg(feature = "io")]
+    pub fn from_tracking_arc<Msr>(
+        arc: &TrackingArc<Msr>,
+        spacecraft: &str,
+    ) -> Result<Self, NyxError>
+    where
+        Msr: Measurement,
+        DefaultAllocator: Allocator<f64, Msr::MeasurementSize>,
+    {
+        let keywords = msr_keywords::<Msr>()?;
+        let has_range = keywords.contains(&TdmKeyword::Range);
+        let has_angles = keywords.iter().any(|keyword| keyword.is_angle());
+
+        let mut segments: BTreeMap<&String, TdmSegment> = BTreeMap::new();
+        for (device, msr) in &arc.measurements {
+            let segment = segments.entry(device).or_insert_with(|| TdmSegment {
+                participants: vec![device.clone(), spacecraft.to_string()],
+                time_system: TimeScale::UTC,
+                path: Some("1,2,1".to_string()),
+                range_units: has_range.then(|| "km".to_string()),
+                angle_type: has_angles.then(|| "RADEC".to_string()),
+                observations: Vec::new(),
+            });
+            for (keyword, value) in keywords.iter().zip(msr.observation().iter()) {
+                segment.observations.push(TdmObservation {
+                    epoch: msr.epoch(),
+                    keyword: *keyword,
+                    value: *value,
+                });
+            }
+        }
+
+        Ok(Self {
+            originator: None,
+            segments: segments.into_values().collect(),
+        })
+    }
+
+    /// Writes this TDM in its text (KVN) version. The originator may be overwritten by the `originator` key of the metadata of the export.
+    pub fn to_kvn_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, NyxError> {
+        let tick = Epoch::now().unwrap();
+        info!("Exporting tracking data to CCSDS TDM file...");
+
+        let path_buf = cfg.actual_path(path);
+        let metadata = cfg.metadata.unwrap_or_default();
+
+        let file = File::create(&path_buf)
+            .map_err(|e| NyxError::CCSDS(format!("File creation error: {e}")))?;
+        let mut writer = BufWriter::new(file);
+
+        let err_hdlr = |e| NyxError::CCSDS(format!("Could not write: {e}"));
+
+        let iso8601_no_ts = Format::from_str("%Y-%m-%dT%H:%M:%S.%f").unwrap();
+
+        writeln!(writer, "CCSDS_TDM_VERS = 2.0").map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "COMMENT Generated by {} provided in AGPLv3 license -- https://nyxspace.com/",
+            prj_name_ver()
+        )
+        .map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "CREATION_DATE = {}",
+            Formatter::new(Epoch::now().unwrap(), iso8601_no_ts)
+        )
+        .map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "ORIGINATOR = {}\n",
+            metadata
+                .get("originator")
+                .cloned()
+                .or_else(|| self.originator.clone())
+                .unwrap_or_else(|| Watermark::current()
+                    .organization
+                    .unwrap_or_else(|| "Nyx Space".to_string()))
+        )
+        .map_err(err_hdlr)?;
+
+        for segment in &self.segments {
+            writeln!(writer, "META_START").map_err(err_hdlr)?;
+            writeln!(
+                writer,
+                "TIME_SYSTEM = {}",
+                ccsds_time_system(segment.time_system)
+            )
+            .map_err(err_hdlr)?;
+            let epochs = segment.observations.iter().map(|obs| obs.epoch);
+            if let (Some(start), Some(stop)) = (epochs.clone().min(), epochs.max()) {
+                writeln!(
+                    writer,
+                    "START_TIME = {}",
+                    Formatter::new(start.in_time_scale(segment.time_system), iso8601_no_ts)
+                )
+                .map_err(err_hdlr)?;
+                writeln!(
+                    writer,
+                    "STOP_TIME = {}",
+                    Formatter::new(stop.in_time_scale(segment.time_system), iso8601_no_ts)
+                )
+                .map_err(err_hdlr)?;
+            }
+            for (pno, participant) in segment.participants.iter().enumerate() {
+                writeln!(writer, "PARTICIPANT_{} = {participant}", pno + 1).map_err(err_hdlr)?;
+            }
+            writeln!(writer, "MODE = SEQUENTIAL").map_err(err_hdlr)?;
+            if let Some(path) = &segment.path {
+                writeln!(writer, "PATH = {path}").map_err(err_hdlr)?;
+            }
+            if let Some(range_units) = &segment.range_units {
+                writeln!(writer, "RANGE_UNITS = {range_units}").map_err(err_hdlr)?;
+            }
+            if let Some(angle_type) = &segment.angle_type {
+                writeln!(writer, "ANGLE_TYPE = {angle_type}").map_err(err_hdlr)?;
+                if angle_type == "RADEC" {
+                    writeln!(writer, "REFERENCE_FRAME = EME2000").map_err(err_hdlr)?;
+                }
+            }
+            writeln!(writer, "META_STOP\n").map_err(err_hdlr)?;
+
+            writeln!(writer, "DATA_START").map_err(err_hdlr)?;
+            for obs in &segment.observations {
+                writeln!(
+                    writer,
+                    "{} = {} {:E}",
+                    obs.keyword,
+                    Formatter::new(obs.epoch.in_time_scale(segment.time_system), iso8601_no_ts),
+                    obs.value
+                )
+                .map_err(err_hdlr)?;
+            }
+            writeln!(writer, "DATA_STOP\n").map_err(err_hdlr)?;
+        }
+
+        let tock_time = Epoch::now().unwrap() - tick;
+        info!(
+            "Tracking data written to {} in {tock_time}",
+            path_buf.display()
+        );
+        Ok(path_buf)
+    }
+}
+
+/// Returns the TDM keyword of each field of the provided measurement, or an error if one of them cannot be exchanged in a TDM
+#[cfg(feature = "io")]
+fn msr_keywords<Msr: Measurement>() -> Result<Vec<TdmKeyword>, NyxError>
+where
+    DefaultAllocator: Allocator<f64, Msr::MeasurementSize>,
+{
+    Msr::fields()
+        .iter()
+        .map(|field| {
+            TdmKeyword::from_field_name(field.name()).ok_or_else(|| {
+                NyxError::CCSDS(format!(
+                    "Measurement field `{}` cannot be exchanged in a TDM",
+                    field.name()
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Builds an empty segment from the key-value pairs of a metadata section
+fn segment_from_metadata(
+    metadata: &HashMap<String, String>,
+    lno: usize,
+) -> Result<TdmSegment, NyxError> {
+    let time_system = metadata.get("TIME_SYSTEM").ok_or_else(|| {
+        NyxError::CCSDS(format!("[line: {}] Metadata without TIME_SYSTEM", lno + 1))
+    })?;
+
+    let mut participants = Vec::new();
+    while let Some(participant) = metadata.get(&format!("PARTICIPANT_{}", participants.len() + 1)) {
+        participants.push(participant.clone());
+    }
+
+    Ok(TdmSegment {
+        participants,
+        time_system: parse_time_system(time_system)?,
+        path: metadata.get("PATH").cloned(),
+        range_units: metadata.get("RANGE_UNITS").cloned(),
+        angle_type: metadata.get("ANGLE_TYPE").cloned(),
+        observations: Vec::new(),
+    })
+}
+
+/// Returns the key and value of an XML element on a single line, e.g. `<RANGE>1.0</RANGE>`
+fn xml_element(line: &str) -> Option<(&str, &str)> {
+    let (key, rest) = line.strip_prefix('<')?.split_once('>')?;
+    let value = rest
+        .strip_suffix('>')?
+        .strip_suffix(key)?
+        .strip_suffix("</")?;
+    Some((key, value.trim()))
+}
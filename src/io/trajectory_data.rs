@@ -33,11 +33,11 @@ use crate::python::mission_design::{OrbitTraj as OrbitTrajPy, SpacecraftTraj as
 #[cfg(feature = "python")]
 use crate::Spacecraft;
 #[cfg(feature = "python")]
-use log::warn;
-#[cfg(feature = "python")]
 use pyo3::class::basic::CompareOp;
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use tracing::warn;
 
 use crate::cosmic::Cosm;
 
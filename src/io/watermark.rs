@@ -17,38 +17,144 @@
 */
 
 use std::collections::HashMap;
+use std::env;
+use std::sync::RwLock;
 
+#[cfg(all(feature = "io", not(target_arch = "wasm32")))]
 use hifitime::Epoch;
-use parquet::{
-    basic::{Compression, ZstdLevel},
-    file::properties::WriterProperties,
-    format::KeyValue,
-};
+#[cfg(feature = "zstd")]
+use parquet::basic::{Compression, ZstdLevel};
+#[cfg(feature = "io")]
+use parquet::{file::properties::WriterProperties, format::KeyValue};
+#[cfg(feature = "build-info")]
 use shadow_rs::shadow;
+#[cfg(feature = "build-info")]
 use whoami::{platform, realname, username};
 
+#[cfg(feature = "build-info")]
 shadow!(build);
 
-/// The parquet writer properties
-pub(crate) fn pq_writer(metadata: Option<HashMap<String, String>>) -> Option<WriterProperties> {
-    let bldr = WriterProperties::builder()
-        .set_compression(Compression::ZSTD(ZstdLevel::try_new(10).unwrap()));
-
-    let mut file_metadata = vec![
-        KeyValue::new("Generated by".to_string(), prj_name_ver()),
-        KeyValue::new(
-            format!("{} License", build::PROJECT_NAME),
-            "AGPL 3.0".to_string(),
-        ),
-        KeyValue::new(
-            "Created by".to_string(),
-            format!("{} ({}) on {}", realname(), username(), platform()),
-        ),
-        KeyValue::new(
+/// Environment variable which, when set, overrides whether the watermark is anonymized (e.g. `NYX_ANONYMOUS_WATERMARK=1`)
+pub const ANONYMOUS_ENV_VAR: &str = "NYX_ANONYMOUS_WATERMARK";
+
+static WATERMARK: RwLock<Option<Watermark>> = RwLock::new(None);
+
+/// Watermark written in the metadata of every Parquet file generated by Nyx.
+///
+/// By default, the files record the real name, user name and platform of whoever generated them. Products delivered outside of an organization
+/// should instead be anonymized, and optionally identify the organization which produced them.
+/// Without the `build-info` feature, the user information is never recorded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Watermark {
+    /// Set to true to not include any information about the user who generated the file
+    pub anonymous: bool,
+    /// Name of the organization who generated the files, replaces the user information if the watermark is anonymous
+    pub organization: Option<String>,
+    /// Additional metadata stored in every file, e.g. a distribution statement
+    pub metadata: HashMap<String, String>,
+}
+
+impl Watermark {
+    /// Initializes an anonymous watermark
+    pub fn anonymous() -> Self {
+        Self {
+            anonymous: true,
+            ..Default::default()
+        }
+    }
+
+    /// Initializes an anonymous watermark which only identifies the provided organization
+    pub fn for_organization(name: String) -> Self {
+        Self {
+            anonymous: true,
+            organization: Some(name),
+            metadata: HashMap::new(),
+        }
+    }
+
+    /// Sets this watermark as the one used for all of the files generated from now on
+    pub fn apply(self) {
+        *WATERMARK.write().unwrap() = Some(self);
+    }
+
+    /// Resets the watermark to its default, i.e. the user information is included
+    pub fn reset() {
+        *WATERMARK.write().unwrap() = None;
+    }
+
+    /// Returns the watermark currently used, accounting for the environment variable override
+    pub fn current() -> Self {
+        let mut me = WATERMARK.read().unwrap().clone().unwrap_or_default();
+        if let Ok(val) = env::var(ANONYMOUS_ENV_VAR) {
+            me.anonymous = !matches!(
+                val.trim().to_lowercase().as_str(),
+                "" | "0" | "false" | "no" | "off"
+            );
+        }
+        me
+    }
+
+    /// Builds the key-value pairs of this watermark
+    #[cfg(feature = "io")]
+    fn key_values(&self) -> Vec<KeyValue> {
+        let mut kv = vec![
+            KeyValue::new("Generated by".to_string(), prj_name_ver()),
+            KeyValue::new(
+                format!("{} License", env!("CARGO_PKG_NAME")),
+                "AGPL 3.0".to_string(),
+            ),
+        ];
+
+        #[cfg(feature = "build-info")]
+        if !self.anonymous {
+            kv.push(KeyValue::new(
+                "Created by".to_string(),
+                format!("{} ({}) on {}", realname(), username(), platform()),
+            ));
+        }
+
+        if self.anonymous {
+            if let Some(organization) = &self.organization {
+                kv.push(KeyValue::new(
+                    "Created by".to_string(),
+                    organization.clone(),
+                ));
+            }
+        }
+
+        // The system clock is not available in the browser
+        #[cfg(not(target_arch = "wasm32"))]
+        kv.push(KeyValue::new(
             "Created on".to_string(),
             format!("{}", Epoch::now().unwrap()),
-        ),
-    ];
+        ));
+
+        for (k, v) in &self.metadata {
+            kv.push(KeyValue::new(k.clone(), v.clone()));
+        }
+
+        kv
+    }
+}
+
+/// The parquet writer properties, with an optional maximum number of rows per row group
+#[cfg(feature = "io")]
+pub(crate) fn pq_writer(
+    metadata: Option<HashMap<String, String>>,
+    row_group_size: Option<usize>,
+) -> Option<WriterProperties> {
+    let mut bldr = WriterProperties::builder();
+
+    #[cfg(feature = "zstd")]
+    {
+        bldr = bldr.set_compression(Compression::ZSTD(ZstdLevel::try_new(10).unwrap()));
+    }
+
+    if let Some(row_group_size) = row_group_size {
+        bldr = bldr.set_max_row_group_size(row_group_size);
+    }
+
+    let mut file_metadata = Watermark::current().key_values();
 
     if let Some(custom_md) = metadata {
         for (k, v) in custom_md {
@@ -59,6 +165,12 @@ pub(crate) fn pq_writer(metadata: Option<HashMap<String, String>>) -> Option<Wri
     Some(bldr.set_key_value_metadata(Some(file_metadata)).build())
 }
 
+#[cfg(feature = "build-info")]
 pub(crate) fn prj_name_ver() -> String {
     format!("{} {}", build::PROJECT_NAME, build::PKG_VERSION)
 }
+
+#[cfg(not(feature = "build-info"))]
+pub(crate) fn prj_name_ver() -> String {
+    format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+}
@@ -21,6 +21,24 @@
 [Nyx](https://en.wikipedia.org/wiki/Nyx): Blazing fast high-fidelity astrodynamics for Monte Carlo analyzes of constellations, interplanetary missions, and deep space flight navigation.
 
 Refer to [nyxspace.com](https://nyxspace.com) for a user guide, a show case, the MathSpec, and the validation data.
+
+## Logging
+All of Nyx logs with [tracing](https://docs.rs/tracing), and its main loops enter spans carrying structured fields: `propagate` for each propagation,
+`od_process` and `od_measurement` for orbit determination, and `targeter` and `targeter_iteration` for differential correction.
+If no `tracing` subscriber is installed, the events are forwarded to the `log` crate, so a `log` logger (e.g. `pretty_env_logger` or the Python logging) also works.
+To tag the logs of a given run (e.g. with a run ID), enter your own span around the calls to Nyx.
+
+## Features
+- `io` (default): exports and reads the trajectories, tracking arcs, filter results and analysis products as Parquet files with [arrow](https://docs.rs/arrow),
+  and converts tracking arcs to and from CCSDS TDMs. Without it, none of the `to_parquet` and `from_parquet` functions are available.
+- `parallel` (default): runs the Monte Carlo simulations, the finite differencing of the targeters and the trajectory searches on all threads with [rayon](https://docs.rs/rayon). Without it, these run sequentially and `MonteCarlo` is not available.
+- `zstd` (default): compresses the exported Parquet files with Zstandard, and enables `io`. Without it, the files are not compressed.
+- `build-info` (default): stores the build information and the user details in the watermark of the exported files.
+- `ffi`: exposes the propagation, the trajectory queries and the frame conversions through a C interface, declared in `include/nyx.h`.
+- `python`: builds the Python bindings.
+
+Disabling the default features allows compiling the propagation, orbit math and targeting to `wasm32-unknown-unknown`, e.g. for browser-based tools:
+`cargo build --target wasm32-unknown-unknown --no-default-features`
 */
 
 // Allow confusable identifiers, as the code tries to use the literature's notation where possible.
@@ -64,7 +82,7 @@ pub mod mc;
 pub mod polyfit;
 
 #[macro_use]
-extern crate log;
+extern crate tracing;
 extern crate hifitime;
 extern crate nalgebra as na;
 extern crate prost_derive;
@@ -85,3 +103,6 @@ pub use self::cosmic::{Orbit, Spacecraft, State, TimeTagged};
 
 #[cfg(feature = "python")]
 mod python;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
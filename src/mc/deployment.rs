@@ -0,0 +1,636 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::envelope::percentile_of_sorted;
+use super::{Distribution, Normal, Pcg64Mcg};
+use crate::cosmic::{Frame, Spacecraft};
+use crate::dynamics::Dynamics;
+#[cfg(feature = "io")]
+use crate::io::watermark::pq_writer;
+#[cfg(feature = "io")]
+use crate::io::ExportCfg;
+use crate::linalg::Vector3;
+use crate::md::trajectory::Traj;
+use crate::propagators::{ErrorCtrl, Propagator};
+use crate::time::{Duration, Epoch, TimeSeries};
+use crate::{NyxError, State};
+#[cfg(feature = "io")]
+use arrow::array::{Array, Float64Builder, StringBuilder, UInt64Builder};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "io")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::HashMap;
+#[cfg(feature = "io")]
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "io")]
+use std::fs::File;
+#[cfg(feature = "io")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "io")]
+use std::sync::Arc;
+
+/// Name of the carrier (e.g. the upper stage) in the close approaches
+pub const CARRIER_NAME: &str = "Carrier";
+
+/// A deployer (e.g. a P-POD) which ejects a sequence of identical satellites from the carrier, with a dispersed separation
+/// delta-v in magnitude and direction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deployer {
+    pub name: String,
+    /// Mass and area properties of the deployed satellites, whose orbit is replaced at separation
+    pub template: Spacecraft,
+    pub num_satellites: usize,
+    /// Time of the first ejection after the epoch of the carrier state
+    pub first_ejection: Duration,
+    /// Time between consecutive ejections
+    pub interval: Duration,
+    /// Nominal direction of the separation delta-v in the provided frame (need not be a unit vector)
+    pub direction: Vector3<f64>,
+    /// Frame of the direction, computed from the orbit of the carrier (e.g. VNC or RIC), or inertial
+    pub frame: Frame,
+    /// Nominal magnitude of the separation delta-v, in meters per second
+    pub dv_m_s: f64,
+    /// One sigma dispersion of the magnitude of the separation delta-v, in meters per second
+    pub dv_sigma_m_s: f64,
+    /// One sigma dispersion of the direction of the separation delta-v about its nominal, per axis, in degrees
+    pub pointing_sigma_deg: f64,
+}
+
+impl Deployer {
+    /// A deployer ejecting its satellites without dispersions, all at once at the epoch of the carrier state
+    pub fn new(
+        name: &str,
+        template: Spacecraft,
+        num_satellites: usize,
+        direction: Vector3<f64>,
+        frame: Frame,
+        dv_m_s: f64,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            template,
+            num_satellites,
+            first_ejection: Duration::ZERO,
+            interval: Duration::ZERO,
+            direction,
+            frame,
+            dv_m_s,
+            dv_sigma_m_s: 0.0,
+            pointing_sigma_deg: 0.0,
+        }
+    }
+
+    /// Sets the one sigma dispersions of the magnitude (in m/s) and of the pointing (in degrees) of the separation delta-v
+    pub fn with_dispersions(mut self, dv_sigma_m_s: f64, pointing_sigma_deg: f64) -> Self {
+        self.dv_sigma_m_s = dv_sigma_m_s;
+        self.pointing_sigma_deg = pointing_sigma_deg;
+        self
+    }
+
+    /// Sets the time of the first ejection after the epoch of the carrier state and the time between ejections
+    pub fn with_sequence(mut self, first_ejection: Duration, interval: Duration) -> Self {
+        self.first_ejection = first_ejection;
+        self.interval = interval;
+        self
+    }
+
+    /// Returns the epoch of the ejection of the satellite with the provided index, given the epoch of the carrier state
+    pub fn ejection_epoch(&self, start: Epoch, index: usize) -> Epoch {
+        start + self.first_ejection + (index as f64) * self.interval
+    }
+
+    /// Returns the separation delta-v of a satellite in the frame of the deployer, in km/s, dispersed with the provided rng
+    fn dispersed_dv(&self, rng: &mut Pcg64Mcg) -> Vector3<f64> {
+        let nominal = self.direction.normalize();
+        // Build a basis perpendicular to the nominal direction
+        let helper = if nominal.x.abs() < 0.9 {
+            Vector3::x()
+        } else {
+            Vector3::y()
+        };
+        let e1 = nominal.cross(&helper).normalize();
+        let e2 = nominal.cross(&e1);
+
+        let pointing = Normal::new(0.0, self.pointing_sigma_deg.to_radians()).unwrap();
+        let tilt = pointing.sample(rng) * e1 + pointing.sample(rng) * e2;
+        let angle = tilt.norm();
+        let direction = if angle > f64::EPSILON {
+            angle.cos() * nominal + angle.sin() * tilt / angle
+        } else {
+            nominal
+        };
+
+        let magnitude = Normal::new(self.dv_m_s, self.dv_sigma_m_s)
+            .unwrap()
+            .sample(rng)
+            .max(0.0);
+        direction * magnitude * 1e-3
+    }
+}
+
+/// A satellite after separation from the carrier
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeployedSatellite {
+    /// Name of the satellite, as `<deployer>-<number>` where the numbering starts at one
+    pub name: String,
+    pub deployer: String,
+    /// Epoch of the separation
+    pub epoch: Epoch,
+    /// Separation delta-v in the inertial frame, in km/s
+    pub dv_km_s: Vector3<f64>,
+    /// State right after the separation
+    pub state: Spacecraft,
+}
+
+/// Closest approach between two objects of the cluster over the sampled epochs of a trial
+#[derive(Clone, Debug, PartialEq)]
+pub struct CloseApproach {
+    pub first: String,
+    pub second: String,
+    pub epoch: Epoch,
+    pub distance_km: f64,
+}
+
+/// Spread of the deployed satellites about the carrier at a sampled epoch
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpreadSample {
+    pub epoch: Epoch,
+    /// Number of satellites deployed before this epoch
+    pub num_deployed: usize,
+    /// Smallest distance between any two objects of the cluster (including the carrier), NaN if nothing is deployed
+    pub min_separation_km: f64,
+    /// Largest distance of a deployed satellite from the carrier, NaN if nothing is deployed
+    pub max_range_km: f64,
+    /// Root mean square of the radial, in-track and cross-track positions of the deployed satellites relative to the
+    /// carrier, NaN if nothing is deployed
+    pub rms_ric_km: Vector3<f64>,
+}
+
+/// A single trial of the deployment dispersions
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeploymentTrial {
+    pub index: usize,
+    pub satellites: Vec<DeployedSatellite>,
+    /// Closest approach of each pair of objects, including the carrier
+    pub approaches: Vec<CloseApproach>,
+    /// Spread of the cluster at each sampled epoch
+    pub spread: Vec<SpreadSample>,
+}
+
+impl DeploymentTrial {
+    /// Returns the closest approach of any two objects of this trial
+    pub fn closest_approach(&self) -> Option<&CloseApproach> {
+        self.approaches
+            .iter()
+            .min_by(|a, b| a.distance_km.total_cmp(&b.distance_km))
+    }
+}
+
+/// Deployment of satellites from a carrier by one or more deployers, with dispersed separations.
+///
+/// Each trial draws the separation delta-v of every satellite, propagates the cluster, and samples the relative geometry
+/// at a fixed step: the closest approaches are only as accurate as this step. An object is only sampled strictly after
+/// its ejection, since it is co-located with the carrier at separation. The trials are run in parallel if the `parallel`
+/// feature is enabled, and each trial is reproducible from the seed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeploymentDispersion {
+    pub deployers: Vec<Deployer>,
+    /// Seed of the [64bit PCG random number generator](https://www.pcg-random.org/index.html), offset by the trial index
+    pub seed: u64,
+}
+
+impl DeploymentDispersion {
+    /// Builds the deployment, ensuring that the deployers are uniquely named and valid
+    pub fn new(deployers: Vec<Deployer>, seed: u64) -> Result<Self, NyxError> {
+        if deployers.is_empty() {
+            return Err(NyxError::MonteCarlo(
+                "deployment requires at least one deployer".to_string(),
+            ));
+        }
+        for (idx, deployer) in deployers.iter().enumerate() {
+            if deployers[..idx].iter().any(|d| d.name == deployer.name) {
+                return Err(NyxError::MonteCarlo(format!(
+                    "deployer `{}` is defined more than once",
+                    deployer.name
+                )));
+            } else if deployer.num_satellites == 0 {
+                return Err(NyxError::MonteCarlo(format!(
+                    "deployer `{}` has no satellites",
+                    deployer.name
+                )));
+            } else if deployer.direction.norm() < f64::EPSILON {
+                return Err(NyxError::MonteCarlo(format!(
+                    "deployer `{}` has a nil separation direction",
+                    deployer.name
+                )));
+            } else if deployer.dv_m_s < 0.0
+                || deployer.dv_sigma_m_s < 0.0
+                || deployer.pointing_sigma_deg < 0.0
+            {
+                return Err(NyxError::MonteCarlo(format!(
+                    "deployer `{}` must have a positive delta-v and dispersions",
+                    deployer.name
+                )));
+            } else if deployer.first_ejection < Duration::ZERO || deployer.interval < Duration::ZERO
+            {
+                return Err(NyxError::MonteCarlo(format!(
+                    "deployer `{}` must eject its satellites after the epoch of the carrier",
+                    deployer.name
+                )));
+            }
+        }
+        Ok(Self { deployers, seed })
+    }
+
+    /// Total number of deployed satellites
+    pub fn num_satellites(&self) -> usize {
+        self.deployers.iter().map(|d| d.num_satellites).sum()
+    }
+
+    /// Generates the post-separation states of the satellites of the provided trial from the trajectory of the carrier,
+    /// which must cover every ejection.
+    pub fn generate(
+        &self,
+        carrier: &Traj<Spacecraft>,
+        trial: usize,
+    ) -> Result<Vec<DeployedSatellite>, NyxError> {
+        let mut rng = Pcg64Mcg::new(self.seed.wrapping_add(trial as u64).into());
+        let start = carrier.first().epoch();
+
+        let mut satellites = Vec::with_capacity(self.num_satellites());
+        for deployer in &self.deployers {
+            for idx in 0..deployer.num_satellites {
+                let epoch = deployer.ejection_epoch(start, idx);
+                let carrier_state = carrier.at(epoch)?;
+                let dv_local = deployer.dispersed_dv(&mut rng);
+                let dv_km_s = if matches!(deployer.frame, Frame::Inertial) {
+                    dv_local
+                } else {
+                    carrier_state.orbit.dcm_from_traj_frame(deployer.frame)? * dv_local
+                };
+
+                let mut state = deployer.template;
+                state.orbit = carrier_state.orbit;
+                state.orbit.vx_km_s += dv_km_s.x;
+                state.orbit.vy_km_s += dv_km_s.y;
+                state.orbit.vz_km_s += dv_km_s.z;
+
+                satellites.push(DeployedSatellite {
+                    name: format!("{}-{}", deployer.name, idx + 1),
+                    deployer: deployer.name.clone(),
+                    epoch,
+                    dv_km_s,
+                    state,
+                });
+            }
+        }
+        Ok(satellites)
+    }
+
+    /// Runs the provided number of trials: the carrier is propagated from its state, and the satellites from their
+    /// separation, until `duration` after the epoch of the carrier state. The cluster is sampled every `step` from the
+    /// first ejection.
+    #[allow(clippy::needless_lifetimes)]
+    pub fn run<'a, D, E>(
+        &self,
+        prop: &Propagator<'a, D, E>,
+        carrier_state: Spacecraft,
+        duration: Duration,
+        step: Duration,
+        num_trials: usize,
+    ) -> Result<DeploymentResults, NyxError>
+    where
+        D: Dynamics<StateType = Spacecraft>,
+        E: ErrorCtrl,
+    {
+        let start = carrier_state.epoch();
+        let end = start + duration;
+        if step <= Duration::ZERO {
+            return Err(NyxError::MonteCarlo(format!(
+                "sampling step must be positive, got {step}"
+            )));
+        }
+        let first_ejection = self
+            .deployers
+            .iter()
+            .map(|d| d.ejection_epoch(start, 0))
+            .min()
+            .unwrap();
+        for deployer in &self.deployers {
+            let last_ejection = deployer.ejection_epoch(start, deployer.num_satellites - 1);
+            if last_ejection >= end {
+                return Err(NyxError::MonteCarlo(format!(
+                    "deployer `{}` ejects its last satellite at {last_ejection}, after the end of the analysis",
+                    deployer.name
+                )));
+            }
+        }
+
+        let (_, carrier) = prop.with(carrier_state).until_epoch_with_traj(end)?;
+        let epochs =
+            TimeSeries::inclusive(first_ejection + step, end, step).collect::<Vec<Epoch>>();
+
+        let run_trial = |index: usize| -> Result<DeploymentTrial, NyxError> {
+            let satellites = self.generate(&carrier, index)?;
+            let mut trajs = Vec::with_capacity(satellites.len());
+            for satellite in &satellites {
+                let (_, traj) = prop.with(satellite.state).until_epoch_with_traj(end)?;
+                trajs.push(traj);
+            }
+
+            let mut names = vec![CARRIER_NAME.to_string()];
+            names.extend(satellites.iter().map(|sat| sat.name.clone()));
+            let mut approaches: HashMap<(usize, usize), CloseApproach> = HashMap::new();
+            let mut spread = Vec::with_capacity(epochs.len());
+
+            for epoch in &epochs {
+                let carrier_orbit = carrier.at(*epoch)?.orbit;
+                let mut positions = vec![carrier_orbit.radius()];
+                let mut indexes = vec![0];
+                for (idx, (satellite, traj)) in satellites.iter().zip(&trajs).enumerate() {
+                    if satellite.epoch < *epoch {
+                        positions.push(traj.at(*epoch)?.orbit.radius());
+                        indexes.push(idx + 1);
+                    }
+                }
+
+                let mut min_separation_km = f64::NAN;
+                for i in 0..positions.len() {
+                    for j in (i + 1)..positions.len() {
+                        let distance_km = (positions[i] - positions[j]).norm();
+                        if min_separation_km.is_nan() || distance_km < min_separation_km {
+                            min_separation_km = distance_km;
+                        }
+                        let approach =
+                            approaches
+                                .entry((indexes[i], indexes[j]))
+                                .or_insert_with(|| CloseApproach {
+                                    first: names[indexes[i]].clone(),
+                                    second: names[indexes[j]].clone(),
+                                    epoch: *epoch,
+                                    distance_km,
+                                });
+                        if distance_km < approach.distance_km {
+                            approach.epoch = *epoch;
+                            approach.distance_km = distance_km;
+                        }
+                    }
+                }
+
+                let num_deployed = positions.len() - 1;
+                let (max_range_km, rms_ric_km) = if num_deployed == 0 {
+                    (f64::NAN, Vector3::from_element(f64::NAN))
+                } else {
+                    // Positions relative to the carrier, in its radial, in-track and cross-track frame
+                    let dcm = carrier_orbit.dcm_from_traj_frame(Frame::RIC)?.transpose();
+                    let mut max_range_km = 0.0_f64;
+                    let mut sum_sq = Vector3::zeros();
+                    for position in &positions[1..] {
+                        let relative = position - positions[0];
+                        max_range_km = max_range_km.max(relative.norm());
+                        sum_sq += (dcm * relative).map(|x| x.powi(2));
+                    }
+                    (
+                        max_range_km,
+                        (sum_sq / num_deployed as f64).map(|x| x.sqrt()),
+                    )
+                };
+
+                spread.push(SpreadSample {
+                    epoch: *epoch,
+                    num_deployed,
+                    min_separation_km,
+                    max_range_km,
+                    rms_ric_km,
+                });
+            }
+
+            let mut approaches = approaches.into_iter().collect::<Vec<_>>();
+            approaches.sort_by_key(|(pair, _)| *pair);
+
+            Ok(DeploymentTrial {
+                index,
+                satellites,
+                approaches: approaches
+                    .into_iter()
+                    .map(|(_, approach)| approach)
+                    .collect(),
+                spread,
+            })
+        };
+
+        #[cfg(feature = "parallel")]
+        let trials: Result<Vec<DeploymentTrial>, NyxError> =
+            (0..num_trials).into_par_iter().map(run_trial).collect();
+        #[cfg(not(feature = "parallel"))]
+        let trials: Result<Vec<DeploymentTrial>, NyxError> =
+            (0..num_trials).map(run_trial).collect();
+
+        Ok(DeploymentResults {
+            epochs,
+            trials: trials?,
+        })
+    }
+}
+
+/// Results of the trials of a deployment
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeploymentResults {
+    /// Sampled epochs, common to all of the trials
+    pub epochs: Vec<Epoch>,
+    pub trials: Vec<DeploymentTrial>,
+}
+
+impl DeploymentResults {
+    /// Fraction of the trials where any two objects come closer than the keep-out distance
+    pub fn collision_probability(&self, keep_out_km: f64) -> f64 {
+        self.fraction_of_trials(|trial| {
+            trial
+                .approaches
+                .iter()
+                .any(|approach| approach.distance_km < keep_out_km)
+        })
+    }
+
+    /// Fraction of the trials where the two named objects come closer than the keep-out distance
+    pub fn pair_collision_probability(&self, first: &str, second: &str, keep_out_km: f64) -> f64 {
+        self.fraction_of_trials(|trial| {
+            trial.approaches.iter().any(|approach| {
+                ((approach.first == first && approach.second == second)
+                    || (approach.first == second && approach.second == first))
+                    && approach.distance_km < keep_out_km
+            })
+        })
+    }
+
+    /// Closest approach across all of the trials
+    pub fn closest_approach(&self) -> Option<&CloseApproach> {
+        self.trials
+            .iter()
+            .filter_map(|trial| trial.closest_approach())
+            .min_by(|a, b| a.distance_km.total_cmp(&b.distance_km))
+    }
+
+    /// Percentile (between 0 and 100) across the trials of the largest distance of a satellite from the carrier, at each
+    /// sampled epoch
+    pub fn range_percentile(&self, percentile: f64) -> Vec<f64> {
+        self.percentile_of(percentile, |sample| sample.max_range_km)
+    }
+
+    /// Percentile (between 0 and 100) across the trials of the smallest distance between two objects, at each sampled
+    /// epoch
+    pub fn separation_percentile(&self, percentile: f64) -> Vec<f64> {
+        self.percentile_of(percentile, |sample| sample.min_separation_km)
+    }
+
+    fn fraction_of_trials<F: Fn(&DeploymentTrial) -> bool>(&self, predicate: F) -> f64 {
+        if self.trials.is_empty() {
+            return f64::NAN;
+        }
+        self.trials.iter().filter(|trial| predicate(trial)).count() as f64
+            / self.trials.len() as f64
+    }
+
+    fn percentile_of<F: Fn(&SpreadSample) -> f64>(&self, percentile: f64, value: F) -> Vec<f64> {
+        (0..self.epochs.len())
+            .map(|k| {
+                let mut values = self
+                    .trials
+                    .iter()
+                    .map(|trial| value(&trial.spread[k]))
+                    .filter(|val| !val.is_nan())
+                    .collect::<Vec<f64>>();
+                values.sort_by(|a, b| a.total_cmp(b));
+                percentile_of_sorted(&values, percentile)
+            })
+            .collect()
+    }
+
+    /// Stores the spread of every trial to a parquet file, with one row per trial and sampled epoch
+    #[cfg(feature = "io")]
+    pub fn to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let path_buf = cfg.actual_path(path);
+
+        let hdrs = vec![
+            Field::new("Trial", DataType::UInt64, false),
+            Field::new("Epoch:Gregorian UTC", DataType::Utf8, false),
+            Field::new("Epoch:TAI (s)", DataType::Float64, false),
+            Field::new("Number of deployed satellites", DataType::UInt64, false),
+            Field::new("Min separation (km)", DataType::Float64, false),
+            Field::new("Max range from carrier (km)", DataType::Float64, false),
+            Field::new("RMS radial (km)", DataType::Float64, false),
+            Field::new("RMS in-track (km)", DataType::Float64, false),
+            Field::new("RMS cross-track (km)", DataType::Float64, false),
+        ];
+
+        let mut index = UInt64Builder::new();
+        let mut utc_epoch = StringBuilder::new();
+        let mut tai_s = Float64Builder::new();
+        let mut num_deployed = UInt64Builder::new();
+        let mut min_separation = Float64Builder::new();
+        let mut max_range = Float64Builder::new();
+        let mut rms_ric = [
+            Float64Builder::new(),
+            Float64Builder::new(),
+            Float64Builder::new(),
+        ];
+        for trial in &self.trials {
+            for sample in &trial.spread {
+                index.append_value(trial.index as u64);
+                utc_epoch.append_value(format!("{}", sample.epoch));
+                tai_s.append_value(sample.epoch.to_tai_seconds());
+                num_deployed.append_value(sample.num_deployed as u64);
+                min_separation.append_value(sample.min_separation_km);
+                max_range.append_value(sample.max_range_km);
+                for (builder, value) in rms_ric.iter_mut().zip(sample.rms_ric_km.iter()) {
+                    builder.append_value(*value);
+                }
+            }
+        }
+
+        let mut record: Vec<Arc<dyn Array>> = vec![
+            Arc::new(index.finish()),
+            Arc::new(utc_epoch.finish()),
+            Arc::new(tai_s.finish()),
+            Arc::new(num_deployed.finish()),
+            Arc::new(min_separation.finish()),
+            Arc::new(max_range.finish()),
+        ];
+        for mut builder in rms_ric {
+            record.push(Arc::new(builder.finish()));
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "Purpose".to_string(),
+            "Deployment dispersion analysis".to_string(),
+        );
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!(
+            "Deployment dispersions of {} trials written to {}",
+            self.trials.len(),
+            path_buf.display()
+        );
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for DeploymentResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} deployment trial(s) sampled at {} epoch(s)",
+            self.trials.len(),
+            self.epochs.len()
+        )?;
+        if let Some(approach) = self.closest_approach() {
+            write!(
+                f,
+                ", closest approach of {:.3} m between {} and {} at {}",
+                approach.distance_km * 1e3,
+                approach.first,
+                approach.second,
+                approach.epoch
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+#[cfg(feature = "io")]
+use crate::io::watermark::pq_writer;
+#[cfg(feature = "io")]
+use crate::io::ExportCfg;
+use crate::md::StateParameter;
+use crate::time::Epoch;
+use crate::NyxError;
+#[cfg(feature = "io")]
+use arrow::array::{Array, Float64Builder, StringBuilder, UInt64Builder};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "io")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+#[cfg(feature = "io")]
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "io")]
+use std::fs::File;
+#[cfg(feature = "io")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "io")]
+use std::sync::Arc;
+
+/// Returns the percentile (between 0 and 100) of the sorted values, linearly interpolated between the closest ranks.
+/// Returns NaN if there are no values.
+pub(crate) fn percentile_of_sorted(sorted: &[f64], percentile: f64) -> f64 {
+    match sorted.len() {
+        0 => f64::NAN,
+        1 => sorted[0],
+        len => {
+            let rank = percentile / 100.0 * (len - 1) as f64;
+            let lower = rank.floor() as usize;
+            let upper = rank.ceil() as usize;
+            sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+        }
+    }
+}
+
+/// The per-epoch percentile envelopes (e.g. 5%, 50% and 95%) of state parameters across the runs of a Monte Carlo.
+///
+/// This is a compact summary of an ensemble: it stores a few values per epoch instead of every trajectory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PercentileEnvelope {
+    /// Scenario of the Monte Carlo
+    pub scenario: String,
+    /// Parameters of the envelopes
+    pub params: Vec<StateParameter>,
+    /// Percentiles of the envelopes, between 0 and 100
+    pub percentiles: Vec<f64>,
+    /// Epochs of the envelopes
+    pub epochs: Vec<Epoch>,
+    /// Number of runs which provided a value of each parameter at each epoch, indexed as `[param][epoch]`
+    pub num_runs: Vec<Vec<usize>>,
+    /// Values indexed as `[param][percentile][epoch]`, NaN if no run provided a value
+    pub values: Vec<Vec<Vec<f64>>>,
+}
+
+impl PercentileEnvelope {
+    /// Builds the envelopes from the values of each run, indexed as `[param][epoch][run]`, which are sorted in place.
+    pub(crate) fn from_samples(
+        scenario: String,
+        params: Vec<StateParameter>,
+        percentiles: Vec<f64>,
+        epochs: Vec<Epoch>,
+        mut samples: Vec<Vec<Vec<f64>>>,
+    ) -> Self {
+        let mut num_runs = Vec::with_capacity(params.len());
+        let mut values = Vec::with_capacity(params.len());
+        for param_samples in samples.iter_mut() {
+            let mut param_values = vec![Vec::with_capacity(epochs.len()); percentiles.len()];
+            for epoch_samples in param_samples.iter_mut() {
+                epoch_samples.sort_by(|a, b| a.total_cmp(b));
+                for (p, percentile) in percentiles.iter().enumerate() {
+                    param_values[p].push(percentile_of_sorted(epoch_samples, *percentile));
+                }
+            }
+            num_runs.push(param_samples.iter().map(|s| s.len()).collect());
+            values.push(param_values);
+        }
+        Self {
+            scenario,
+            params,
+            percentiles,
+            epochs,
+            num_runs,
+            values,
+        }
+    }
+
+    /// Returns the envelope of the provided parameter and percentile at each epoch
+    pub fn of(&self, param: StateParameter, percentile: f64) -> Result<&[f64], NyxError> {
+        let param_idx = self
+            .params
+            .iter()
+            .position(|p| *p == param)
+            .ok_or_else(|| {
+                NyxError::StateParameterUnavailable(param, "not in percentile envelope".to_string())
+            })?;
+        let pct_idx = self
+            .percentiles
+            .iter()
+            .position(|p| (p - percentile).abs() < f64::EPSILON)
+            .ok_or_else(|| {
+                NyxError::CustomError(format!("percentile {percentile} not in envelope"))
+            })?;
+        Ok(&self.values[param_idx][pct_idx])
+    }
+
+    /// Stores these envelopes to a parquet file with one row per epoch and one column per parameter and percentile.
+    #[cfg(feature = "io")]
+    pub fn to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let path_buf = cfg.actual_path(path);
+
+        let mut hdrs = vec![
+            Field::new("Epoch:Gregorian UTC", DataType::Utf8, false),
+            Field::new("Epoch:TAI (s)", DataType::Float64, false),
+        ];
+        let mut record: Vec<Arc<dyn Array>> = Vec::new();
+
+        let mut utc_epoch = StringBuilder::new();
+        let mut tai_s = Float64Builder::new();
+        for epoch in &self.epochs {
+            utc_epoch.append_value(format!("{epoch}"));
+            tai_s.append_value(epoch.to_tai_seconds());
+        }
+        record.push(Arc::new(utc_epoch.finish()));
+        record.push(Arc::new(tai_s.finish()));
+
+        for (param_idx, param) in self.params.iter().enumerate() {
+            let (unit, _) = cfg.unit_of(*param);
+            hdrs.push(Field::new(
+                format!("{param}: number of runs"),
+                DataType::UInt64,
+                false,
+            ));
+            let mut counts = UInt64Builder::new();
+            for count in &self.num_runs[param_idx] {
+                counts.append_value(*count as u64);
+            }
+            record.push(Arc::new(counts.finish()));
+
+            for (pct_idx, percentile) in self.percentiles.iter().enumerate() {
+                hdrs.push(Field::new(
+                    format!("{param} P{percentile} ({unit})"),
+                    DataType::Float64,
+                    false,
+                ));
+                let mut data = Float64Builder::new();
+                for value in &self.values[param_idx][pct_idx] {
+                    data.append_value(cfg.export_value(*param, *value));
+                }
+                record.push(Arc::new(data.finish()));
+            }
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "Purpose".to_string(),
+            "Monte Carlo percentile envelopes".to_string(),
+        );
+        metadata.insert("Scenario".to_string(), self.scenario.clone());
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!(
+            "Percentile envelopes of {} written to {}",
+            self.scenario,
+            path_buf.display()
+        );
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for PercentileEnvelope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: percentiles {:?} of {} parameter(s) at {} epoch(s)",
+            self.scenario,
+            self.percentiles,
+            self.params.len(),
+            self.epochs.len()
+        )?;
+        if let (Some(first), Some(last)) = (self.epochs.first(), self.epochs.last()) {
+            write!(f, " from {first} to {last}")?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_percentile_of_sorted() {
+    let sorted = [1.0, 2.0, 3.0, 4.0, 5.0];
+    assert_eq!(percentile_of_sorted(&sorted, 0.0), 1.0);
+    assert_eq!(percentile_of_sorted(&sorted, 50.0), 3.0);
+    assert_eq!(percentile_of_sorted(&sorted, 100.0), 5.0);
+    assert!((percentile_of_sorted(&sorted, 95.0) - 4.8).abs() < 1e-12);
+    assert!(percentile_of_sorted(&[], 50.0).is_nan());
+}
@@ -21,8 +21,10 @@ use rand_distr::{Distribution, Normal, Uniform};
 pub use rand_pcg::Pcg64Mcg;
 
 pub mod helpers;
+#[cfg(feature = "parallel")]
 mod montecarlo;
 
+#[cfg(feature = "parallel")]
 pub use montecarlo::MonteCarlo;
 
 mod generator;
@@ -33,3 +35,18 @@ pub use multivariate::MultivariateNormal;
 
 mod results;
 pub use results::{Results, Stats};
+
+mod envelope;
+pub use envelope::PercentileEnvelope;
+
+mod deployment;
+pub use deployment::{
+    CloseApproach, DeployedSatellite, Deployer, DeploymentDispersion, DeploymentResults,
+    DeploymentTrial, SpreadSample, CARRIER_NAME,
+};
+
+mod reentry;
+pub use reentry::{
+    EntryDispersions, EntryFootprint, EntryRun, FootprintResults, Fragment, Impact, ImpactEllipse,
+    HUMAN_CASUALTY_AREA_M2,
+};
@@ -17,7 +17,7 @@
 */
 
 use super::{Generator, Pcg64Mcg};
-use crate::dynamics::Dynamics;
+use crate::dynamics::{Dynamics, StochasticDynamics};
 use crate::linalg::allocator::Allocator;
 use crate::linalg::DefaultAllocator;
 use crate::mc::results::{PropResult, Results, Run};
@@ -212,6 +212,57 @@ where
             + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
             + Allocator<f64, <D::StateType as State>::VecLength>,
         <DefaultAllocator as Allocator<f64, <D::StateType as State>::VecLength>>::Buffer: Send,
+    {
+        self.propagate_until_epoch(prop, skip, end_epoch, num_runs, |_, _| {})
+    }
+
+    /// Generate states and propagate each independently until the specified epoch with its own realization of the stochastic acceleration
+    /// of the dynamics: the realization of run `i` is drawn from the seed of the stochastic acceleration plus `i`, so it does not depend on
+    /// the number of threads, and the uncertainty growth includes both the dispersion of the initial states and the stochastic accelerations.
+    #[must_use = "Monte Carlo result must be used"]
+    #[allow(clippy::needless_lifetimes)]
+    pub fn run_stochastic_until_epoch<'a, D, E>(
+        self,
+        prop: Propagator<'a, StochasticDynamics<D>, E>,
+        end_epoch: Epoch,
+        num_runs: usize,
+    ) -> Results<S, PropResult<S>>
+    where
+        D: Dynamics<StateType = S>,
+        E: ErrorCtrl,
+        StochasticDynamics<D>: Dynamics<StateType = S>,
+        DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::VecLength>,
+        <DefaultAllocator as Allocator<f64, <D::StateType as State>::VecLength>>::Buffer: Send,
+    {
+        let seed = prop.dynamics.accel.seed;
+        self.propagate_until_epoch(prop, 0, end_epoch, num_runs, |run_prop, index| {
+            run_prop.dynamics = run_prop.dynamics.with_seed(seed.wrapping_add(index as u64));
+        })
+    }
+
+    /// Generates the states after skipping the first `skip` ones, and propagates each independently until the specified epoch with a
+    /// copy of the propagator, which is first set up for the run with `setup_run` from its index.
+    #[allow(clippy::needless_lifetimes)]
+    fn propagate_until_epoch<'a, D, E, F>(
+        &self,
+        prop: Propagator<'a, D, E>,
+        skip: usize,
+        end_epoch: Epoch,
+        num_runs: usize,
+        setup_run: F,
+    ) -> Results<S, PropResult<S>>
+    where
+        D: Dynamics<StateType = S>,
+        E: ErrorCtrl,
+        F: Fn(&mut Propagator<'a, D, E>, usize) + Sync,
+        DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::VecLength>,
+        <DefaultAllocator as Allocator<f64, <D::StateType as State>::VecLength>>::Buffer: Send,
     {
         // Generate the initial states
         let init_states = self.generate_states(skip, num_runs);
@@ -225,8 +276,9 @@ where
         let start = StdInstant::now();
         init_states.par_iter().progress_with(pb).for_each_with(
             (prop, tx),
-            |(arc_prop, tx), (index, dispersed_state)| {
-                let result = arc_prop
+            |(prop, tx), (index, dispersed_state)| {
+                setup_run(prop, *index);
+                let result = prop
                     .with(dispersed_state.state)
                     .until_epoch_with_traj(end_epoch);
 
@@ -247,7 +299,7 @@ where
         #[cfg(not(target_arch = "wasm32"))]
         {
             let clock_time = StdInstant::now() - start;
-            println!(
+            info!(
                 "Propagated {} states in {}",
                 num_runs,
                 clock_time.as_secs_f64() * Unit::Second
@@ -0,0 +1,631 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Distribution, Normal, Pcg64Mcg};
+use crate::cosmic::{Cosm, Frame, Orbit, Spacecraft};
+use crate::dynamics::Dynamics;
+use crate::linalg::{Matrix2, Vector2, Vector3};
+use crate::md::{Event, StateParameter};
+use crate::propagators::{ErrorCtrl, Propagator};
+use crate::time::{Duration, Epoch, TimeUnits};
+use crate::NyxError;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Casualty area of a standing person, in square meters, as used by NASA's Debris Assessment Software
+pub const HUMAN_CASUALTY_AREA_M2: f64 = 0.36;
+
+/// A fragment surviving the breakup of the entering object down to the ground
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fragment {
+    pub name: String,
+    pub mass_kg: f64,
+    /// Mean cross-sectional area, used for the drag and the casualty area
+    pub area_m2: f64,
+    /// Coefficient of drag
+    pub cd: f64,
+}
+
+impl Fragment {
+    pub fn new(name: &str, mass_kg: f64, area_m2: f64, cd: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            mass_kg,
+            area_m2,
+            cd,
+        }
+    }
+
+    /// Debris casualty area of this fragment in square meters, i.e. (sqrt(0.36) + sqrt(A))^2
+    pub fn casualty_area_m2(&self) -> f64 {
+        (HUMAN_CASUALTY_AREA_M2.sqrt() + self.area_m2.sqrt()).powi(2)
+    }
+}
+
+/// Dispersions of the entry, all one sigma
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EntryDispersions {
+    /// Relative dispersion of the drag, applied as a scale factor on the coefficients of drag of the object and of its
+    /// fragments, which accounts for the uncertainty of both the atmospheric density and the attitude
+    pub drag_scale_sigma: f64,
+    /// Nominal geodetic altitude of the breakup, in km
+    pub breakup_altitude_km: f64,
+    pub breakup_altitude_sigma_km: f64,
+    /// Dispersion of the velocity imparted to each fragment by the breakup, per inertial axis, in m/s
+    pub fragment_dv_sigma_m_s: f64,
+}
+
+impl Default for EntryDispersions {
+    /// Breakup at 78 km, the usual assumption for the aerodynamic breakup of spacecraft, with twenty percent of drag
+    /// uncertainty and one meter per second of fragment release velocity
+    fn default() -> Self {
+        Self {
+            drag_scale_sigma: 0.2,
+            breakup_altitude_km: 78.0,
+            breakup_altitude_sigma_km: 5.0,
+            fragment_dv_sigma_m_s: 1.0,
+        }
+    }
+}
+
+/// Ground impact of a fragment
+#[derive(Clone, Debug, PartialEq)]
+pub struct Impact {
+    pub fragment: String,
+    pub epoch: Epoch,
+    /// Geodetic latitude in degrees
+    pub latitude_deg: f64,
+    /// Geodetic longitude in degrees, between -180 and 180
+    pub longitude_deg: f64,
+    /// Speed relative to the ground, in km/s
+    pub speed_km_s: f64,
+}
+
+/// A single Monte Carlo run of the entry
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntryRun {
+    pub index: usize,
+    /// Scale factor applied to the coefficients of drag
+    pub drag_scale: f64,
+    pub breakup_altitude_km: f64,
+    /// State of the object at the breakup, if it was reached
+    pub breakup: Option<Orbit>,
+    /// Impacts of the fragments, in the order of the fragments
+    pub impacts: Vec<Impact>,
+    /// Error which stopped this run, if any
+    pub error: Option<String>,
+}
+
+impl EntryRun {
+    /// Returns whether every fragment of this run reached the ground
+    pub fn is_complete(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Ellipse of the ground impacts at a given probability, computed on the plane tangent to a spherical body at the mean
+/// impact point, assuming normally distributed impacts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImpactEllipse {
+    /// Name of the fragment, or of the whole footprint
+    pub name: String,
+    /// Probability of an impact to be within the ellipse
+    pub probability: f64,
+    pub num_impacts: usize,
+    pub center_latitude_deg: f64,
+    pub center_longitude_deg: f64,
+    pub semi_major_km: f64,
+    pub semi_minor_km: f64,
+    /// Azimuth of the semi-major axis, clockwise from the north, between 0 and 180 degrees
+    pub azimuth_deg: f64,
+    radius_km: f64,
+}
+
+impl ImpactEllipse {
+    fn from_impacts(
+        name: String,
+        impacts: &[&Impact],
+        probability: f64,
+        radius_km: f64,
+    ) -> Result<Self, NyxError> {
+        if impacts.len() < 3 {
+            return Err(NyxError::MonteCarlo(format!(
+                "{name}: an impact ellipse requires at least three impacts, got {}",
+                impacts.len()
+            )));
+        } else if !(0.0..1.0).contains(&probability) || probability == 0.0 {
+            return Err(NyxError::MonteCarlo(format!(
+                "probability of the impact ellipse must be strictly between 0 and 1, got {probability}"
+            )));
+        }
+
+        // Mean impact point on the unit sphere
+        let center = impacts
+            .iter()
+            .map(|impact| unit_vector(impact.latitude_deg, impact.longitude_deg))
+            .sum::<Vector3<f64>>()
+            .normalize();
+        let mut ellipse = Self {
+            name,
+            probability,
+            num_impacts: impacts.len(),
+            center_latitude_deg: center.z.asin().to_degrees(),
+            center_longitude_deg: center.y.atan2(center.x).to_degrees(),
+            semi_major_km: 0.0,
+            semi_minor_km: 0.0,
+            azimuth_deg: 0.0,
+            radius_km,
+        };
+
+        // Covariance of the east and north offsets
+        let offsets = impacts
+            .iter()
+            .map(|impact| ellipse.tangent_offset_km(impact.latitude_deg, impact.longitude_deg))
+            .collect::<Vec<Vector2<f64>>>();
+        let mean = offsets.iter().sum::<Vector2<f64>>() / offsets.len() as f64;
+        let covar = offsets
+            .iter()
+            .map(|offset| (offset - mean) * (offset - mean).transpose())
+            .sum::<Matrix2<f64>>()
+            / (offsets.len() - 1) as f64;
+
+        // Chi-squared quantile with two degrees of freedom
+        let scale = (-2.0 * (1.0 - probability).ln()).sqrt();
+        let eigen = covar.symmetric_eigen();
+        let (major, minor) = if eigen.eigenvalues[0] >= eigen.eigenvalues[1] {
+            (0, 1)
+        } else {
+            (1, 0)
+        };
+        let major_axis = eigen.eigenvectors.column(major);
+        ellipse.semi_major_km = scale * eigen.eigenvalues[major].max(0.0).sqrt();
+        ellipse.semi_minor_km = scale * eigen.eigenvalues[minor].max(0.0).sqrt();
+        ellipse.azimuth_deg = major_axis[0]
+            .atan2(major_axis[1])
+            .to_degrees()
+            .rem_euclid(180.0);
+        Ok(ellipse)
+    }
+
+    /// East and north axes of the plane tangent at the center
+    fn tangent_axes(&self) -> (Vector3<f64>, Vector3<f64>, Vector3<f64>) {
+        let up = unit_vector(self.center_latitude_deg, self.center_longitude_deg);
+        let lon = self.center_longitude_deg.to_radians();
+        let east = Vector3::new(-lon.sin(), lon.cos(), 0.0);
+        let north = up.cross(&east);
+        (east, north, up)
+    }
+
+    /// Returns the east and north offsets in km of the provided point on the plane tangent at the center
+    fn tangent_offset_km(&self, latitude_deg: f64, longitude_deg: f64) -> Vector2<f64> {
+        let (east, north, up) = self.tangent_axes();
+        let point = unit_vector(latitude_deg, longitude_deg);
+        // Gnomonic projection
+        let point = point / point.dot(&up);
+        Vector2::new(point.dot(&east), point.dot(&north)) * self.radius_km
+    }
+
+    /// Returns whether the provided point is within this ellipse
+    pub fn contains(&self, latitude_deg: f64, longitude_deg: f64) -> bool {
+        if unit_vector(latitude_deg, longitude_deg).dot(&unit_vector(
+            self.center_latitude_deg,
+            self.center_longitude_deg,
+        )) <= 0.0
+        {
+            return false;
+        }
+        let offset = self.tangent_offset_km(latitude_deg, longitude_deg);
+        let azimuth = self.azimuth_deg.to_radians();
+        let along_major = offset.x * azimuth.sin() + offset.y * azimuth.cos();
+        let along_minor = offset.x * azimuth.cos() - offset.y * azimuth.sin();
+        (along_major / self.semi_major_km).powi(2) + (along_minor / self.semi_minor_km).powi(2)
+            <= 1.0
+    }
+
+    /// Returns the longitude and latitude in degrees of the provided number of points on the boundary of the ellipse,
+    /// the first point being repeated at the end to close it
+    pub fn boundary(&self, num_points: usize) -> Vec<(f64, f64)> {
+        let (east, north, up) = self.tangent_axes();
+        let azimuth = self.azimuth_deg.to_radians();
+        let major = azimuth.sin() * east + azimuth.cos() * north;
+        let minor = azimuth.cos() * east - azimuth.sin() * north;
+        (0..=num_points)
+            .map(|k| {
+                let angle = std::f64::consts::TAU * (k % num_points) as f64 / num_points as f64;
+                let point = up
+                    + (self.semi_major_km * angle.cos() * major
+                        + self.semi_minor_km * angle.sin() * minor)
+                        / self.radius_km;
+                let point = point.normalize();
+                (
+                    point.y.atan2(point.x).to_degrees(),
+                    point.z.asin().to_degrees(),
+                )
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ImpactEllipse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:.1}% impact ellipse of {} impacts centered at ({:.4} deg, {:.4} deg), {:.3} x {:.3} km at azimuth {:.2} deg",
+            self.name,
+            self.probability * 100.0,
+            self.num_impacts,
+            self.center_latitude_deg,
+            self.center_longitude_deg,
+            self.semi_major_km,
+            self.semi_minor_km,
+            self.azimuth_deg
+        )
+    }
+}
+
+/// Unit vector of the provided latitude and longitude on a sphere
+fn unit_vector(latitude_deg: f64, longitude_deg: f64) -> Vector3<f64> {
+    let (lat, lon) = (latitude_deg.to_radians(), longitude_deg.to_radians());
+    Vector3::new(lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin())
+}
+
+/// Monte Carlo estimation of the ground footprint of the entry of a decaying object.
+///
+/// Each run disperses the drag and the breakup altitude, propagates the object down to its breakup, and then propagates
+/// each surviving fragment, released with a dispersed velocity, down to the ground. The altitudes are geodetic, computed
+/// in the provided body fixed frame. The runs are done in parallel if the `parallel` feature is enabled, and each run is
+/// reproducible from the seed.
+#[derive(Clone)]
+pub struct EntryFootprint {
+    pub fragments: Vec<Fragment>,
+    pub dispersions: EntryDispersions,
+    /// Body fixed frame of the ground
+    pub body_fixed: Frame,
+    /// Seed of the [64bit PCG random number generator](https://www.pcg-random.org/index.html), offset by the run index
+    pub seed: u64,
+    /// Maximum duration of the propagation of the object to its breakup, and of each fragment to the ground
+    pub max_duration: Duration,
+    pub cosm: Arc<Cosm>,
+}
+
+impl EntryFootprint {
+    /// Builds the footprint estimator with the default dispersions, ensuring that the fragments are uniquely named and valid
+    pub fn new(
+        fragments: Vec<Fragment>,
+        body_fixed: Frame,
+        cosm: Arc<Cosm>,
+    ) -> Result<Self, NyxError> {
+        if fragments.is_empty() {
+            return Err(NyxError::MonteCarlo(
+                "entry footprint requires at least one fragment".to_string(),
+            ));
+        } else if !body_fixed.is_geoid() || !body_fixed.is_body_fixed() {
+            return Err(NyxError::MonteCarlo(format!(
+                "entry footprint requires a body fixed geoid frame, got {body_fixed}"
+            )));
+        }
+        for (idx, fragment) in fragments.iter().enumerate() {
+            if fragments[..idx].iter().any(|f| f.name == fragment.name) {
+                return Err(NyxError::MonteCarlo(format!(
+                    "fragment `{}` is defined more than once",
+                    fragment.name
+                )));
+            } else if fragment.mass_kg <= 0.0 || fragment.area_m2 <= 0.0 || fragment.cd <= 0.0 {
+                return Err(NyxError::MonteCarlo(format!(
+                    "fragment `{}` must have a positive mass, area and coefficient of drag",
+                    fragment.name
+                )));
+            }
+        }
+        Ok(Self {
+            fragments,
+            dispersions: EntryDispersions::default(),
+            body_fixed,
+            seed: 0,
+            max_duration: 30.days(),
+            cosm,
+        })
+    }
+
+    pub fn with_dispersions(mut self, dispersions: EntryDispersions) -> Self {
+        self.dispersions = dispersions;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = max_duration;
+        self
+    }
+
+    /// Runs the provided number of entries of the object from the provided state
+    #[allow(clippy::needless_lifetimes)]
+    pub fn run<'a, D, E>(
+        &self,
+        prop: &Propagator<'a, D, E>,
+        state: Spacecraft,
+        num_runs: usize,
+    ) -> FootprintResults
+    where
+        D: Dynamics<StateType = Spacecraft>,
+        E: ErrorCtrl,
+    {
+        // Both events are searched in the body fixed frame, only the desired altitude changes
+        let ground = Event::in_frame(
+            StateParameter::GeodeticHeight,
+            0.0,
+            self.body_fixed,
+            self.cosm.clone(),
+        );
+
+        let run_entry = |index: usize| {
+            let mut rng = Pcg64Mcg::new(self.seed.wrapping_add(index as u64).into());
+            let drag_scale = (1.0
+                + Normal::new(0.0, self.dispersions.drag_scale_sigma)
+                    .unwrap()
+                    .sample(&mut rng))
+            .max(0.0);
+            let breakup_altitude_km = Normal::new(
+                self.dispersions.breakup_altitude_km,
+                self.dispersions.breakup_altitude_sigma_km,
+            )
+            .unwrap()
+            .sample(&mut rng);
+            let release = Normal::new(0.0, self.dispersions.fragment_dv_sigma_m_s * 1e-3).unwrap();
+            let release_dvs = self
+                .fragments
+                .iter()
+                .map(|_| {
+                    Vector3::new(
+                        release.sample(&mut rng),
+                        release.sample(&mut rng),
+                        release.sample(&mut rng),
+                    )
+                })
+                .collect::<Vec<Vector3<f64>>>();
+
+            let mut run = EntryRun {
+                index,
+                drag_scale,
+                breakup_altitude_km,
+                breakup: None,
+                impacts: Vec::with_capacity(self.fragments.len()),
+                error: None,
+            };
+            if let Err(e) = self.fall(prop, state, &ground, &release_dvs, &mut run) {
+                warn!("entry run #{index} failed: {e}");
+                run.error = Some(e.to_string());
+            }
+            run
+        };
+
+        #[cfg(feature = "parallel")]
+        let runs: Vec<EntryRun> = (0..num_runs).into_par_iter().map(run_entry).collect();
+        #[cfg(not(feature = "parallel"))]
+        let runs: Vec<EntryRun> = (0..num_runs).map(run_entry).collect();
+
+        FootprintResults {
+            fragments: self.fragments.clone(),
+            runs,
+            radius_km: self.body_fixed.equatorial_radius(),
+        }
+    }
+
+    /// Propagates the object to its breakup and each fragment to the ground, filling in the provided run
+    fn fall<'a, D, E>(
+        &self,
+        prop: &Propagator<'a, D, E>,
+        mut state: Spacecraft,
+        ground: &Event,
+        release_dvs: &[Vector3<f64>],
+        run: &mut EntryRun,
+    ) -> Result<(), NyxError>
+    where
+        D: Dynamics<StateType = Spacecraft>,
+        E: ErrorCtrl,
+    {
+        state.drag.cd *= run.drag_scale;
+        let mut breakup_event = ground.clone();
+        breakup_event.desired_value = run.breakup_altitude_km;
+        let breakup = prop
+            .with(state)
+            .until_event_online(self.max_duration, &breakup_event)?;
+        run.breakup = Some(breakup.orbit);
+
+        for (fragment, dv) in self.fragments.iter().zip(release_dvs) {
+            let mut frag_state = breakup;
+            frag_state.dry_mass_kg = fragment.mass_kg;
+            frag_state.fuel_mass_kg = 0.0;
+            frag_state.drag.area_m2 = fragment.area_m2;
+            frag_state.drag.cd = fragment.cd * run.drag_scale;
+            frag_state.srp.area_m2 = fragment.area_m2;
+            frag_state.orbit.vx_km_s += dv.x;
+            frag_state.orbit.vy_km_s += dv.y;
+            frag_state.orbit.vz_km_s += dv.z;
+
+            let impact = prop
+                .with(frag_state)
+                .until_event_online(self.max_duration, ground)?;
+            let fixed = self.cosm.frame_chg(&impact.orbit, self.body_fixed);
+            let mut longitude_deg = fixed.geodetic_longitude_deg();
+            if longitude_deg > 180.0 {
+                longitude_deg -= 360.0;
+            }
+            run.impacts.push(Impact {
+                fragment: fragment.name.clone(),
+                epoch: impact.orbit.epoch,
+                latitude_deg: fixed.geodetic_latitude_deg(),
+                longitude_deg,
+                speed_km_s: fixed.vmag_km_s(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Results of the runs of an entry footprint estimation
+#[derive(Clone, Debug, PartialEq)]
+pub struct FootprintResults {
+    pub fragments: Vec<Fragment>,
+    pub runs: Vec<EntryRun>,
+    /// Radius of the body used to compute the impact ellipses
+    pub radius_km: f64,
+}
+
+impl FootprintResults {
+    /// Number of runs where every fragment reached the ground
+    pub fn num_complete(&self) -> usize {
+        self.runs.iter().filter(|run| run.is_complete()).count()
+    }
+
+    /// Returns all of the impacts of the provided fragment across the complete runs
+    pub fn impacts_of(&self, fragment: &str) -> Vec<&Impact> {
+        self.runs
+            .iter()
+            .filter(|run| run.is_complete())
+            .flat_map(|run| run.impacts.iter())
+            .filter(|impact| impact.fragment == fragment)
+            .collect()
+    }
+
+    /// Impact ellipse of the provided fragment containing the impacts with the provided probability (e.g. 0.99)
+    pub fn ellipse(&self, fragment: &str, probability: f64) -> Result<ImpactEllipse, NyxError> {
+        ImpactEllipse::from_impacts(
+            fragment.to_string(),
+            &self.impacts_of(fragment),
+            probability,
+            self.radius_km,
+        )
+    }
+
+    /// Impact ellipse of all of the fragments together, containing the impacts with the provided probability
+    pub fn footprint(&self, probability: f64) -> Result<ImpactEllipse, NyxError> {
+        let impacts = self
+            .runs
+            .iter()
+            .filter(|run| run.is_complete())
+            .flat_map(|run| run.impacts.iter())
+            .collect::<Vec<&Impact>>();
+        ImpactEllipse::from_impacts(
+            "Footprint".to_string(),
+            &impacts,
+            probability,
+            self.radius_km,
+        )
+    }
+
+    /// Total debris casualty area of the surviving fragments, in square meters
+    pub fn casualty_area_m2(&self) -> f64 {
+        self.fragments.iter().map(|f| f.casualty_area_m2()).sum()
+    }
+
+    /// Expected number of casualties for a uniform population density (per square kilometer) over the footprint
+    pub fn expected_casualties(&self, population_density_per_km2: f64) -> f64 {
+        self.casualty_area_m2() * 1e-6 * population_density_per_km2
+    }
+
+    /// Stores the impact ellipses of each fragment and of the whole footprint at the provided probability, and every
+    /// impact, to a GeoJSON feature collection. The ellipses are polygons, and the impacts are points.
+    pub fn to_geojson<P: AsRef<Path>>(
+        &self,
+        path: P,
+        probability: f64,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let path_buf = path.as_ref().to_path_buf();
+
+        let mut features = Vec::new();
+        let mut ellipses = Vec::with_capacity(self.fragments.len() + 1);
+        for fragment in &self.fragments {
+            ellipses.push((
+                self.ellipse(&fragment.name, probability)?,
+                fragment.casualty_area_m2(),
+            ));
+        }
+        ellipses.push((self.footprint(probability)?, self.casualty_area_m2()));
+
+        for (ellipse, casualty_area_m2) in &ellipses {
+            let ring = ellipse
+                .boundary(72)
+                .iter()
+                .map(|(lon, lat)| format!("[{lon:.6},{lat:.6}]"))
+                .collect::<Vec<String>>()
+                .join(",");
+            features.push(format!(
+                "{{\"type\":\"Feature\",\"geometry\":{{\"type\":\"Polygon\",\"coordinates\":[[{ring}]]}},\"properties\":{{\"name\":\"{}\",\"probability\":{},\"num_impacts\":{},\"semi_major_km\":{:.6},\"semi_minor_km\":{:.6},\"azimuth_deg\":{:.6},\"casualty_area_m2\":{:.6}}}}}",
+                ellipse.name.replace('"', "\\\""),
+                ellipse.probability,
+                ellipse.num_impacts,
+                ellipse.semi_major_km,
+                ellipse.semi_minor_km,
+                ellipse.azimuth_deg,
+                casualty_area_m2
+            ));
+        }
+
+        for run in self.runs.iter().filter(|run| run.is_complete()) {
+            for impact in &run.impacts {
+                features.push(format!(
+                    "{{\"type\":\"Feature\",\"geometry\":{{\"type\":\"Point\",\"coordinates\":[{:.6},{:.6}]}},\"properties\":{{\"fragment\":\"{}\",\"run\":{},\"epoch\":\"{}\",\"speed_km_s\":{:.6}}}}}",
+                    impact.longitude_deg,
+                    impact.latitude_deg,
+                    impact.fragment.replace('"', "\\\""),
+                    run.index,
+                    impact.epoch,
+                    impact.speed_km_s
+                ));
+            }
+        }
+
+        let mut file = File::create(&path_buf)?;
+        writeln!(
+            file,
+            "{{\"type\":\"FeatureCollection\",\"features\":[\n{}\n]}}",
+            features.join(",\n")
+        )?;
+
+        info!(
+            "Entry footprint of {} runs written to {}",
+            self.runs.len(),
+            path_buf.display()
+        );
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for FootprintResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} / {} complete entry run(s) of {} fragment(s), casualty area of {:.3} m^2",
+            self.num_complete(),
+            self.runs.len(),
+            self.fragments.len(),
+            self.casualty_area_m2()
+        )
+    }
+}
@@ -19,11 +19,11 @@ use crate::linalg::allocator::Allocator;
 use crate::linalg::DefaultAllocator;
 use crate::md::trajectory::{Interpolatable, Traj};
 use crate::md::StateParameter;
-use crate::time::{Duration, Epoch};
+use crate::time::{Duration, Epoch, TimeSeries};
 use crate::NyxError;
 pub use rstats::Stats;
 
-use super::DispersedState;
+use super::{DispersedState, PercentileEnvelope};
 
 /// A structure storing the result of a single Monte Carlo run
 pub struct Run<S: Interpolatable, R>
@@ -214,6 +214,67 @@ where
         report
     }
 
+    /// Returns the per-epoch percentile envelopes (e.g. 5, 50 and 95) of the requested state parameters across all of the
+    /// successful runs, every `step` over the time span common to all of these runs.
+    ///
+    /// Runs where a parameter is unavailable at an epoch (e.g. the B-plane of an elliptical orbit) are skipped for that
+    /// parameter and epoch, and the number of runs used is stored in the envelope.
+    pub fn percentile_envelopes(
+        &self,
+        params: &[StateParameter],
+        percentiles: &[f64],
+        step: Duration,
+    ) -> Result<PercentileEnvelope, NyxError> {
+        if let Some(pct) = percentiles.iter().find(|p| !(0.0..=100.0).contains(*p)) {
+            return Err(NyxError::CustomError(format!(
+                "percentiles must be between 0 and 100, got {pct}"
+            )));
+        }
+
+        let trajs: Vec<&Traj<S>> = self
+            .runs
+            .iter()
+            .filter_map(|run| run.result.as_ref().ok().map(|r| &r.traj))
+            .collect();
+        if trajs.is_empty() {
+            return Err(NyxError::CustomError(format!(
+                "no successful run in {}",
+                self.scenario
+            )));
+        }
+
+        let start = trajs.iter().map(|traj| traj.first().epoch()).max().unwrap();
+        let end = trajs.iter().map(|traj| traj.last().epoch()).min().unwrap();
+        if end < start {
+            return Err(NyxError::CustomError(format!(
+                "the runs of {} do not overlap in time",
+                self.scenario
+            )));
+        }
+        let epochs: Vec<Epoch> = TimeSeries::inclusive(start, end, step).collect();
+
+        // Samples indexed as [param][epoch][run]
+        let mut samples = vec![vec![Vec::with_capacity(trajs.len()); epochs.len()]; params.len()];
+        for traj in trajs {
+            for (e, epoch) in epochs.iter().enumerate() {
+                let state = traj.at(*epoch)?;
+                for (p, param) in params.iter().enumerate() {
+                    if let Ok(val) = state.value(*param) {
+                        samples[p][e].push(val);
+                    }
+                }
+            }
+        }
+
+        Ok(PercentileEnvelope::from_samples(
+            self.scenario.clone(),
+            params.to_vec(),
+            percentiles.to_vec(),
+            epochs,
+            samples,
+        ))
+    }
+
     /// Returns the dispersion values of the requested state parameter
     pub fn dispersion_values_of(&self, param: StateParameter) -> Result<Vec<f64>, NyxError> {
         let mut report = Vec::with_capacity(self.runs.len());
@@ -0,0 +1,773 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::errors::NyxError;
+#[cfg(feature = "io")]
+use crate::io::watermark::pq_writer;
+#[cfg(feature = "io")]
+use crate::io::ExportCfg;
+use crate::linalg::{DVector, Matrix3, Matrix3xX, Vector3};
+use crate::md::attitude_profile::AttitudeProfile;
+use crate::md::{Event, ScTraj, StateParameter};
+use crate::time::{Duration, Epoch};
+#[cfg(feature = "io")]
+use arrow::array::{Array, Float64Builder, StringBuilder};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
+use arrow::record_batch::RecordBatch;
+use na::{Quaternion, UnitQuaternion};
+#[cfg(feature = "io")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+#[cfg(feature = "io")]
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "io")]
+use std::fs::File;
+#[cfg(feature = "io")]
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Rigid body attitude of a spacecraft at an epoch
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttitudeState {
+    pub epoch: Epoch,
+    /// Rotation from the body frame to the integration frame of the trajectory
+    pub attitude: UnitQuaternion<f64>,
+    /// Angular velocity of the body with respect to the integration frame, in the body frame (rad/s)
+    pub body_rate_rad_s: Vector3<f64>,
+    /// Angular momentum of each reaction wheel about its spin axis (N·m·s), empty without reaction wheels
+    pub wheel_momenta_n_m_s: Vec<f64>,
+    /// Total angular momentum of the reaction wheels, in the body frame (N·m·s)
+    pub wheel_momentum_n_m_s: Vector3<f64>,
+    /// Angle between the attitude and its target when this state was computed (deg)
+    pub error_deg: f64,
+}
+
+impl AttitudeState {
+    /// Initializes an attitude state with wheels at rest
+    pub fn new(epoch: Epoch, attitude: UnitQuaternion<f64>, body_rate_rad_s: Vector3<f64>) -> Self {
+        Self {
+            epoch,
+            attitude,
+            body_rate_rad_s,
+            wheel_momenta_n_m_s: Vec::new(),
+            wheel_momentum_n_m_s: Vector3::zeros(),
+            error_deg: 0.0,
+        }
+    }
+
+    /// Sets the initial momentum of each reaction wheel (N·m·s)
+    pub fn with_wheel_momenta(mut self, momenta_n_m_s: Vec<f64>) -> Self {
+        self.wheel_momenta_n_m_s = momenta_n_m_s;
+        self
+    }
+
+    /// Returns the value of the provided parameter, only the wheel momentum is available
+    pub fn value(&self, param: StateParameter) -> Result<f64, NyxError> {
+        match param {
+            StateParameter::WheelMomentum => Ok(self.wheel_momentum_n_m_s.norm()),
+            _ => Err(NyxError::StateParameterUnavailable(
+                param,
+                "not available in attitude states".to_string(),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for AttitudeState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rate = self.body_rate_rad_s.map(|w| w.to_degrees());
+        write!(
+            f,
+            "{}: error {:.3} deg, rate [{:.3e}, {:.3e}, {:.3e}] deg/s, wheel momentum {:.3e} N·m·s",
+            self.epoch,
+            self.error_deg,
+            rate[0],
+            rate[1],
+            rate[2],
+            self.wheel_momentum_n_m_s.norm()
+        )
+    }
+}
+
+/// Reaction wheels, each limited in torque and in momentum
+#[derive(Clone, Debug)]
+pub struct ReactionWheels {
+    /// Spin axis of each wheel, unit vectors in the body frame
+    pub axes: Vec<Vector3<f64>>,
+    /// Maximum momentum of each wheel (N·m·s), past which the wheel cannot accelerate further
+    pub max_momentum_n_m_s: f64,
+    /// Maximum torque of each wheel (N·m)
+    pub max_torque_n_m: f64,
+}
+
+impl ReactionWheels {
+    pub fn new(axes: Vec<Vector3<f64>>, max_momentum_n_m_s: f64, max_torque_n_m: f64) -> Self {
+        Self {
+            axes: axes.iter().map(|axis| axis.normalize()).collect(),
+            max_momentum_n_m_s,
+            max_torque_n_m,
+        }
+    }
+
+    /// Three wheels along the body axes
+    pub fn orthogonal(max_momentum_n_m_s: f64, max_torque_n_m: f64) -> Self {
+        Self::new(
+            vec![Vector3::x(), Vector3::y(), Vector3::z()],
+            max_momentum_n_m_s,
+            max_torque_n_m,
+        )
+    }
+
+    /// Four wheels in a pyramid about the body Z axis, each canted by the provided angle from the XY plane
+    pub fn pyramid(cant_deg: f64, max_momentum_n_m_s: f64, max_torque_n_m: f64) -> Self {
+        let (sin_c, cos_c) = cant_deg.to_radians().sin_cos();
+        Self::new(
+            vec![
+                Vector3::new(cos_c, 0.0, sin_c),
+                Vector3::new(0.0, cos_c, sin_c),
+                Vector3::new(-cos_c, 0.0, sin_c),
+                Vector3::new(0.0, -cos_c, sin_c),
+            ],
+            max_momentum_n_m_s,
+            max_torque_n_m,
+        )
+    }
+
+    /// Returns the motor torque of each wheel which provides the commanded torque on the body, with the minimum norm.
+    /// The torques are scaled down together if any exceeds the limit, which preserves the direction of the body torque.
+    pub fn wheel_torques(&self, body_torque_n_m: &Vector3<f64>) -> Result<Vec<f64>, NyxError> {
+        let axes = Matrix3xX::from_columns(&self.axes);
+        // The body receives the reaction of the torques applied on the wheels
+        let torques = -axes
+            .pseudo_inverse(1e-12)
+            .map_err(|e| NyxError::CustomError(e.to_string()))?
+            * body_torque_n_m;
+        let largest = torques.amax();
+        let scale = if largest > self.max_torque_n_m {
+            self.max_torque_n_m / largest
+        } else {
+            1.0
+        };
+        Ok(torques.iter().map(|torque| torque * scale).collect())
+    }
+
+    /// Returns the total momentum of the wheels in the body frame
+    pub fn momentum(&self, momenta_n_m_s: &[f64]) -> Vector3<f64> {
+        self.axes
+            .iter()
+            .zip(momenta_n_m_s)
+            .map(|(axis, h)| axis * *h)
+            .sum()
+    }
+}
+
+impl fmt::Display for ReactionWheels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} reaction wheels ({} N·m·s, {} N·m)",
+            self.axes.len(),
+            self.max_momentum_n_m_s,
+            self.max_torque_n_m
+        )
+    }
+}
+
+/// Attitude control thrusters commanded with a pulse width modulation: over each control period, each thruster fires
+/// for a fraction of the period such that the average torque matches the commanded torque.
+///
+/// The fraction of each thruster is the projection of the commanded torque on its own torque, which is exact for
+/// thrusters in opposite pairs about orthogonal axes.
+#[derive(Clone, Debug)]
+pub struct ThrusterSet {
+    /// Torque on the body of each thruster when firing, in the body frame (N·m)
+    pub torques_n_m: Vec<Vector3<f64>>,
+    /// Minimum duration of a pulse, shorter pulses are not fired
+    pub min_pulse: Duration,
+}
+
+impl ThrusterSet {
+    pub fn new(torques_n_m: Vec<Vector3<f64>>, min_pulse: Duration) -> Self {
+        Self {
+            torques_n_m,
+            min_pulse,
+        }
+    }
+
+    /// Six thrusters in pairs providing the same torque about each positive and negative body axis
+    pub fn six_axis(torque_n_m: f64, min_pulse: Duration) -> Self {
+        Self::new(
+            vec![
+                Vector3::x() * torque_n_m,
+                -Vector3::x() * torque_n_m,
+                Vector3::y() * torque_n_m,
+                -Vector3::y() * torque_n_m,
+                Vector3::z() * torque_n_m,
+                -Vector3::z() * torque_n_m,
+            ],
+            min_pulse,
+        )
+    }
+
+    /// Returns the duration of the pulse of each thruster over the provided control period
+    pub fn pulses(&self, body_torque_n_m: &Vector3<f64>, period: Duration) -> Vec<Duration> {
+        self.torques_n_m
+            .iter()
+            .map(|torque| {
+                let duty = (body_torque_n_m.dot(torque) / torque.norm_squared()).clamp(0.0, 1.0);
+                let pulse = duty * period;
+                if pulse < self.min_pulse {
+                    Duration::ZERO
+                } else {
+                    pulse
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ThrusterSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} attitude thrusters (minimum pulse of {})",
+            self.torques_n_m.len(),
+            self.min_pulse
+        )
+    }
+}
+
+/// Actuators of the attitude control
+#[derive(Clone, Debug)]
+pub enum AttitudeActuator {
+    ReactionWheels(ReactionWheels),
+    Thrusters(ThrusterSet),
+}
+
+impl fmt::Display for AttitudeActuator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReactionWheels(wheels) => write!(f, "{wheels}"),
+            Self::Thrusters(thrusters) => write!(f, "{thrusters}"),
+        }
+    }
+}
+
+/// Target attitude of the controller
+#[derive(Clone)]
+pub enum AttitudeTarget {
+    /// Holds a fixed attitude, i.e. the rotation from the body frame to the integration frame
+    Inertial(UnitQuaternion<f64>),
+    /// Aligns the boresight (in the body frame) with the pointing direction of the attitude profile, leaving the rotation
+    /// about the boresight free
+    Profile {
+        profile: Arc<AttitudeProfile>,
+        boresight: Vector3<f64>,
+    },
+}
+
+impl AttitudeTarget {
+    /// Returns the target attitude for the provided attitude at the provided spacecraft state
+    fn attitude(
+        &self,
+        attitude: &UnitQuaternion<f64>,
+        sc: &crate::Spacecraft,
+    ) -> Result<UnitQuaternion<f64>, NyxError> {
+        match self {
+            Self::Inertial(target) => Ok(*target),
+            Self::Profile { profile, boresight } => {
+                let current = attitude * boresight.normalize();
+                let desired = profile.pointing(sc)?;
+                // The rotation is undefined when both directions are opposite, so rotate about any perpendicular axis
+                let rotation =
+                    UnitQuaternion::rotation_between(&current, &desired).unwrap_or_else(|| {
+                        let other = if current[0].abs() < 0.9 {
+                            Vector3::x()
+                        } else {
+                            Vector3::y()
+                        };
+                        UnitQuaternion::from_scaled_axis(
+                            current.cross(&other).normalize() * std::f64::consts::PI,
+                        )
+                    });
+                Ok(rotation * attitude)
+            }
+        }
+    }
+}
+
+impl fmt::Display for AttitudeTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Inertial(q) => write!(f, "inertial hold {q}"),
+            Self::Profile { boresight, .. } => write!(
+                f,
+                "boresight [{}, {}, {}] along the attitude profile",
+                boresight[0], boresight[1], boresight[2]
+            ),
+        }
+    }
+}
+
+/// Quaternion feedback controller: a proportional derivative control on the vector part of the error quaternion and on
+/// the body rates, with a compensation of the gyroscopic torque.
+#[derive(Copy, Clone, Debug)]
+pub struct QuaternionFeedback {
+    /// Proportional gain (N·m)
+    pub kp: Matrix3<f64>,
+    /// Derivative gain (N·m·s/rad)
+    pub kd: Matrix3<f64>,
+}
+
+impl QuaternionFeedback {
+    pub fn new(kp: f64, kd: f64) -> Self {
+        Self {
+            kp: Matrix3::from_diagonal_element(kp),
+            kd: Matrix3::from_diagonal_element(kd),
+        }
+    }
+
+    /// Initializes the gains such that the small angle response of each axis is a second order system with the provided
+    /// natural frequency (rad/s) and damping ratio
+    pub fn from_bandwidth(
+        inertia_kg_m2: &Matrix3<f64>,
+        natural_freq_rad_s: f64,
+        damping: f64,
+    ) -> Self {
+        // The vector part of the error quaternion is half of the error angle
+        Self {
+            kp: *inertia_kg_m2 * (2.0 * natural_freq_rad_s.powi(2)),
+            kd: *inertia_kg_m2 * (2.0 * damping * natural_freq_rad_s),
+        }
+    }
+
+    /// Returns the commanded torque on the body (N·m), in the body frame
+    pub fn torque(
+        &self,
+        attitude: &UnitQuaternion<f64>,
+        target: &UnitQuaternion<f64>,
+        body_rate_rad_s: &Vector3<f64>,
+        body_momentum_n_m_s: &Vector3<f64>,
+    ) -> Vector3<f64> {
+        let error = target.inverse() * attitude;
+        // Take the shortest rotation to the target
+        let sign = if error.w < 0.0 { -1.0 } else { 1.0 };
+        -self.kp * error.imag() * sign - self.kd * body_rate_rad_s
+            + body_rate_rad_s.cross(body_momentum_n_m_s)
+    }
+}
+
+/// Closed loop attitude simulation of a rigid spacecraft, controlled by reaction wheels or thrusters.
+///
+/// The controller is evaluated at each control period, and its commanded torque is held until the next one (with the
+/// pulses of the thrusters fired at the start of the period).
+#[derive(Clone)]
+pub struct AttitudeControl {
+    /// Inertia tensor of the spacecraft in the body frame, without the spin of the wheels (kg·m^2)
+    pub inertia_kg_m2: Matrix3<f64>,
+    pub actuator: AttitudeActuator,
+    pub controller: QuaternionFeedback,
+    pub target: AttitudeTarget,
+    /// Period of the controller
+    pub control_period: Duration,
+    /// Constant disturbance torque in the body frame (N·m)
+    pub disturbance_n_m: Vector3<f64>,
+}
+
+impl AttitudeControl {
+    /// Initializes the attitude control without any disturbance torque
+    pub fn new(
+        inertia_kg_m2: Matrix3<f64>,
+        actuator: AttitudeActuator,
+        controller: QuaternionFeedback,
+        target: AttitudeTarget,
+        control_period: Duration,
+    ) -> Self {
+        Self {
+            inertia_kg_m2,
+            actuator,
+            controller,
+            target,
+            control_period,
+            disturbance_n_m: Vector3::zeros(),
+        }
+    }
+
+    /// Sets a constant disturbance torque in the body frame (N·m)
+    pub fn with_disturbance(mut self, disturbance_n_m: Vector3<f64>) -> Self {
+        self.disturbance_n_m = disturbance_n_m;
+        self
+    }
+
+    /// Returns the derivative of the attitude, the body rates and the wheel momenta, packed as [q_w, q_x, q_y, q_z, ω, h]
+    fn derivative(
+        &self,
+        x: &DVector<f64>,
+        inertia_inv: &Matrix3<f64>,
+        wheel_torques: &[f64],
+        thruster_torque: &Vector3<f64>,
+    ) -> DVector<f64> {
+        let q = Quaternion::new(x[0], x[1], x[2], x[3]);
+        let ω = Vector3::new(x[4], x[5], x[6]);
+        let mut dx = DVector::zeros(x.len());
+        let q_dot = q * Quaternion::from_imag(ω) * 0.5;
+        dx[0] = q_dot.w;
+        dx[1] = q_dot.i;
+        dx[2] = q_dot.j;
+        dx[3] = q_dot.k;
+
+        let mut momentum = self.inertia_kg_m2 * ω;
+        let mut torque = self.disturbance_n_m + thruster_torque;
+        if let AttitudeActuator::ReactionWheels(wheels) = &self.actuator {
+            for (idx, (axis, motor_torque)) in wheels.axes.iter().zip(wheel_torques).enumerate() {
+                let h = x[7 + idx];
+                momentum += axis * h;
+                // A saturated wheel cannot accelerate further
+                if h.abs() < wheels.max_momentum_n_m_s || h * motor_torque < 0.0 {
+                    dx[7 + idx] = *motor_torque;
+                    torque -= axis * *motor_torque;
+                }
+            }
+        }
+        let ω_dot = inertia_inv * (torque - ω.cross(&momentum));
+        dx[4] = ω_dot[0];
+        dx[5] = ω_dot[1];
+        dx[6] = ω_dot[2];
+        dx
+    }
+
+    /// Propagates the attitude along the trajectory from the initial state until the end of the trajectory, with a fixed
+    /// step fourth order Runge Kutta. The steps are shortened to end at each control update and at the end of each pulse.
+    ///
+    /// The initial wheel momenta default to zero if unset.
+    pub fn propagate(
+        &self,
+        initial: AttitudeState,
+        traj: &ScTraj,
+        step: Duration,
+    ) -> Result<AttitudeHistory, NyxError> {
+        if step <= Duration::ZERO || self.control_period <= Duration::ZERO {
+            return Err(NyxError::CustomError(format!(
+                "invalid propagation step of {step} or control period of {}",
+                self.control_period
+            )));
+        }
+        let inertia_inv = self
+            .inertia_kg_m2
+            .try_inverse()
+            .ok_or_else(|| NyxError::CustomError("inertia tensor is not invertible".to_string()))?;
+        let num_wheels = match &self.actuator {
+            AttitudeActuator::ReactionWheels(wheels) => wheels.axes.len(),
+            AttitudeActuator::Thrusters(_) => 0,
+        };
+        let mut momenta = initial.wheel_momenta_n_m_s.clone();
+        if momenta.is_empty() {
+            momenta = vec![0.0; num_wheels];
+        } else if momenta.len() != num_wheels {
+            return Err(NyxError::CustomError(format!(
+                "{} initial wheel momenta for {num_wheels} wheels",
+                momenta.len()
+            )));
+        }
+
+        let mut x = DVector::zeros(7 + num_wheels);
+        let q = initial.attitude.quaternion();
+        x[0] = q.w;
+        x[1] = q.i;
+        x[2] = q.j;
+        x[3] = q.k;
+        for i in 0..3 {
+            x[4 + i] = initial.body_rate_rad_s[i];
+        }
+        for (i, h) in momenta.iter().enumerate() {
+            x[7 + i] = *h;
+        }
+
+        let end = traj.last().orbit.epoch;
+        let mut epoch = initial.epoch;
+        let mut on_time = vec![Duration::ZERO; self.num_thrusters()];
+        let mut states = vec![self.record(&x, epoch, traj)?];
+
+        while epoch < end {
+            let period_end = if epoch + self.control_period > end {
+                end
+            } else {
+                epoch + self.control_period
+            };
+            let state = self.unpack(&x, epoch);
+            let sc = traj.at(epoch)?;
+            let target = self.target.attitude(&state.attitude, &sc)?;
+            let mut body_momentum = self.inertia_kg_m2 * state.body_rate_rad_s;
+            if let AttitudeActuator::ReactionWheels(wheels) = &self.actuator {
+                body_momentum += wheels.momentum(&state.wheel_momenta_n_m_s);
+            }
+            let command = self.controller.torque(
+                &state.attitude,
+                &target,
+                &state.body_rate_rad_s,
+                &body_momentum,
+            );
+
+            // Split the control period at the end of each pulse, during which the torque of the thrusters is constant
+            let (wheel_torques, pulses) = match &self.actuator {
+                AttitudeActuator::ReactionWheels(wheels) => {
+                    (wheels.wheel_torques(&command)?, vec![])
+                }
+                AttitudeActuator::Thrusters(thrusters) => {
+                    (vec![], thrusters.pulses(&command, period_end - epoch))
+                }
+            };
+            let mut breaks: Vec<Epoch> = pulses
+                .iter()
+                .filter(|pulse| **pulse > Duration::ZERO)
+                .map(|pulse| epoch + *pulse)
+                .filter(|pulse_end| *pulse_end < period_end)
+                .collect();
+            breaks.push(period_end);
+            breaks.sort();
+            let period_start = epoch;
+
+            for segment_end in breaks {
+                let mut thruster_torque = Vector3::zeros();
+                if let AttitudeActuator::Thrusters(thrusters) = &self.actuator {
+                    for (idx, (torque, pulse)) in
+                        thrusters.torques_n_m.iter().zip(&pulses).enumerate()
+                    {
+                        // Each pulse starts with the period, and the segments end at the end of the pulses
+                        if *pulse > Duration::ZERO && period_start + *pulse >= segment_end {
+                            thruster_torque += torque;
+                            on_time[idx] += segment_end - epoch;
+                        }
+                    }
+                }
+                while epoch < segment_end {
+                    let this_step = if epoch + step > segment_end {
+                        segment_end - epoch
+                    } else {
+                        step
+                    };
+                    let h = this_step.to_seconds();
+                    let f = |x: &DVector<f64>| {
+                        self.derivative(x, &inertia_inv, &wheel_torques, &thruster_torque)
+                    };
+                    let k1 = f(&x);
+                    let k2 = f(&(&x + &k1 * (0.5 * h)));
+                    let k3 = f(&(&x + &k2 * (0.5 * h)));
+                    let k4 = f(&(&x + &k3 * h));
+                    x += (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (h / 6.0);
+                    self.normalize(&mut x);
+                    epoch += this_step;
+                }
+            }
+            states.push(self.record(&x, epoch, traj)?);
+        }
+
+        Ok(AttitudeHistory {
+            states,
+            thruster_on_time: on_time,
+        })
+    }
+
+    fn num_thrusters(&self) -> usize {
+        match &self.actuator {
+            AttitudeActuator::Thrusters(thrusters) => thrusters.torques_n_m.len(),
+            AttitudeActuator::ReactionWheels(_) => 0,
+        }
+    }
+
+    /// Normalizes the quaternion and clamps the wheel momenta to their limit
+    fn normalize(&self, x: &mut DVector<f64>) {
+        let norm = (x[0].powi(2) + x[1].powi(2) + x[2].powi(2) + x[3].powi(2)).sqrt();
+        for i in 0..4 {
+            x[i] /= norm;
+        }
+        if let AttitudeActuator::ReactionWheels(wheels) = &self.actuator {
+            for i in 7..x.len() {
+                x[i] = x[i].clamp(-wheels.max_momentum_n_m_s, wheels.max_momentum_n_m_s);
+            }
+        }
+    }
+
+    fn unpack(&self, x: &DVector<f64>, epoch: Epoch) -> AttitudeState {
+        let attitude = UnitQuaternion::from_quaternion(Quaternion::new(x[0], x[1], x[2], x[3]));
+        let momenta: Vec<f64> = x.iter().skip(7).copied().collect();
+        let wheel_momentum = match &self.actuator {
+            AttitudeActuator::ReactionWheels(wheels) => wheels.momentum(&momenta),
+            AttitudeActuator::Thrusters(_) => Vector3::zeros(),
+        };
+        AttitudeState {
+            epoch,
+            attitude,
+            body_rate_rad_s: Vector3::new(x[4], x[5], x[6]),
+            wheel_momenta_n_m_s: momenta,
+            wheel_momentum_n_m_s: wheel_momentum,
+            error_deg: 0.0,
+        }
+    }
+
+    /// Unpacks the state and computes its error with respect to the target
+    fn record(
+        &self,
+        x: &DVector<f64>,
+        epoch: Epoch,
+        traj: &ScTraj,
+    ) -> Result<AttitudeState, NyxError> {
+        let mut state = self.unpack(x, epoch);
+        let target = self.target.attitude(&state.attitude, &traj.at(epoch)?)?;
+        state.error_deg = state.attitude.angle_to(&target).to_degrees();
+        Ok(state)
+    }
+}
+
+impl fmt::Display for AttitudeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "attitude control with {} toward {} every {}",
+            self.actuator, self.target, self.control_period
+        )
+    }
+}
+
+/// Attitude states along a trajectory, at each control update
+#[derive(Clone, Debug)]
+pub struct AttitudeHistory {
+    /// Attitude states, in chronological order
+    pub states: Vec<AttitudeState>,
+    /// Cumulated firing time of each thruster, empty with reaction wheels
+    pub thruster_on_time: Vec<Duration>,
+}
+
+impl AttitudeHistory {
+    /// Returns the epochs at which the parameter of the event crosses its desired value, linearly interpolated between
+    /// the states
+    pub fn find(&self, event: &Event) -> Result<Vec<Epoch>, NyxError> {
+        let mut epochs = Vec::new();
+        for pair in self.states.windows(2) {
+            let prev = pair[0].value(event.parameter)? - event.desired_value;
+            let next = pair[1].value(event.parameter)? - event.desired_value;
+            if prev == 0.0 {
+                epochs.push(pair[0].epoch);
+            } else if prev * next < 0.0 {
+                let frac = prev / (prev - next);
+                epochs.push(pair[0].epoch + frac * (pair[1].epoch - pair[0].epoch));
+            }
+        }
+        Ok(epochs)
+    }
+
+    /// Exports the attitude, the body rates, the wheel momenta and the error to a parquet file, e.g. for plotting
+    #[cfg(feature = "io")]
+    pub fn to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let path_buf = cfg.actual_path(path);
+        let num_wheels = self
+            .states
+            .first()
+            .map(|state| state.wheel_momenta_n_m_s.len())
+            .unwrap_or(0);
+
+        let mut hdrs = vec![
+            Field::new("Epoch:Gregorian UTC", DataType::Utf8, false),
+            Field::new("Epoch:TAI (s)", DataType::Float64, false),
+            Field::new("q_w", DataType::Float64, false),
+            Field::new("q_x", DataType::Float64, false),
+            Field::new("q_y", DataType::Float64, false),
+            Field::new("q_z", DataType::Float64, false),
+            Field::new("body rate X (deg/s)", DataType::Float64, false),
+            Field::new("body rate Y (deg/s)", DataType::Float64, false),
+            Field::new("body rate Z (deg/s)", DataType::Float64, false),
+            Field::new("attitude error (deg)", DataType::Float64, false),
+            StateParameter::WheelMomentum.to_field(None),
+        ];
+        for idx in 0..num_wheels {
+            hdrs.push(Field::new(
+                format!("wheel {idx} momentum (N·m·s)"),
+                DataType::Float64,
+                false,
+            ));
+        }
+
+        let mut utc_epoch = StringBuilder::new();
+        let mut columns: Vec<Float64Builder> =
+            (0..hdrs.len() - 1).map(|_| Float64Builder::new()).collect();
+        for state in &self.states {
+            utc_epoch.append_value(format!("{}", state.epoch));
+            let q = state.attitude.quaternion();
+            let rate = state.body_rate_rad_s.map(|w| w.to_degrees());
+            let mut values = vec![
+                state.epoch.to_tai_seconds(),
+                q.w,
+                q.i,
+                q.j,
+                q.k,
+                rate[0],
+                rate[1],
+                rate[2],
+                state.error_deg,
+                state.wheel_momentum_n_m_s.norm(),
+            ];
+            values.extend(&state.wheel_momenta_n_m_s);
+            for (column, value) in columns.iter_mut().zip(values) {
+                column.append_value(value);
+            }
+        }
+
+        let mut record: Vec<Arc<dyn Array>> = vec![Arc::new(utc_epoch.finish())];
+        for mut column in columns {
+            record.push(Arc::new(column.finish()));
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert("Purpose".to_string(), "Attitude history".to_string());
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!("Attitude history written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for AttitudeHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "attitude history from {} to {}",
+            self.states[0],
+            self.states[self.states.len() - 1]
+        )
+    }
+}
@@ -0,0 +1,434 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Bodies, Cosm, Frame, LightTimeCalc};
+use crate::dynamics::guidance::Mnvr;
+use crate::errors::NyxError;
+use crate::linalg::Vector3;
+use crate::md::ScTraj;
+use crate::time::{Duration, Epoch, Unit};
+use crate::utils::rotv;
+use crate::Spacecraft;
+use std::fmt;
+use std::sync::Arc;
+
+/// Pointing modes of the reference body axis of the spacecraft (e.g. the axis of the thruster, the solar arrays normal and the antenna boresight
+/// are all assumed to be mounted along that same axis).
+#[derive(Clone, Debug)]
+pub enum PointingMode {
+    /// Points the reference axis to the Sun, e.g. for power generation
+    SunPointing,
+    /// Points the reference axis along the thrust direction of the maneuver
+    Burn(Box<Mnvr>),
+    /// Points the reference axis to the center of the integration frame, e.g. for communications with the ground
+    Nadir,
+    /// Points the reference axis to a fixed direction of the integration frame
+    Inertial(Vector3<f64>),
+}
+
+impl PointingMode {
+    /// Returns the unit vector of the pointing direction for the provided state, in its integration frame
+    pub fn direction(&self, state: &Spacecraft, cosm: &Cosm) -> Result<Vector3<f64>, NyxError> {
+        let dir = match self {
+            Self::SunPointing => {
+                let sun = cosm.celestial_state(
+                    Bodies::Sun.ephem_path(),
+                    state.orbit.epoch,
+                    state.orbit.frame,
+                    LightTimeCalc::None,
+                );
+                sun.radius() - state.orbit.radius()
+            }
+            Self::Burn(mnvr) => {
+                let vector = mnvr.vector(state.orbit.epoch);
+                if matches!(mnvr.frame, Frame::Inertial) {
+                    vector
+                } else {
+                    state.orbit.dcm_from_traj_frame(mnvr.frame)? * vector
+                }
+            }
+            Self::Nadir => -state.orbit.radius(),
+            Self::Inertial(dir) => *dir,
+        };
+        if dir.norm() < f64::EPSILON {
+            return Err(NyxError::MathDomain(format!(
+                "pointing direction of {self} is undefined"
+            )));
+        }
+        Ok(dir / dir.norm())
+    }
+}
+
+impl fmt::Display for PointingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SunPointing => write!(f, "Sun pointing"),
+            Self::Burn(mnvr) => {
+                write!(f, "burn attitude ({} from {})", mnvr.duration(), mnvr.start)
+            }
+            Self::Nadir => write!(f, "nadir pointing"),
+            Self::Inertial(dir) => write!(
+                f,
+                "inertial pointing [{:.6}, {:.6}, {:.6}]",
+                dir[0], dir[1], dir[2]
+            ),
+        }
+    }
+}
+
+/// Rate and acceleration limits of the slews, which follow a bang-coast-bang profile about the eigen axis.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SlewLimits {
+    /// Maximum slew rate (deg/s)
+    pub max_rate_deg_s: f64,
+    /// Maximum slew acceleration (deg/s^2)
+    pub max_accel_deg_s2: f64,
+}
+
+impl SlewLimits {
+    pub fn new(max_rate_deg_s: f64, max_accel_deg_s2: f64) -> Self {
+        Self {
+            max_rate_deg_s,
+            max_accel_deg_s2,
+        }
+    }
+
+    /// Returns the duration of the acceleration phase (also that of the deceleration phase) of a slew of the provided angle, in seconds
+    fn accel_duration_s(&self, angle_deg: f64) -> f64 {
+        let to_max_rate_s = self.max_rate_deg_s / self.max_accel_deg_s2;
+        if angle_deg >= self.max_rate_deg_s * to_max_rate_s {
+            to_max_rate_s
+        } else {
+            // The maximum rate is never reached
+            (angle_deg / self.max_accel_deg_s2).sqrt()
+        }
+    }
+
+    /// Returns the minimum duration of a slew of the provided angle
+    pub fn slew_duration(&self, angle_deg: f64) -> Duration {
+        let angle_deg = angle_deg.abs();
+        let accel_s = self.accel_duration_s(angle_deg);
+        let peak_rate_deg_s = self.max_accel_deg_s2 * accel_s;
+        // Angle covered while coasting at the peak rate
+        let coast_s = if peak_rate_deg_s > 0.0 {
+            (angle_deg - peak_rate_deg_s * accel_s) / peak_rate_deg_s
+        } else {
+            0.0
+        };
+        (2.0 * accel_s + coast_s) * Unit::Second
+    }
+
+    /// Returns the angle covered after the provided elapsed time in a slew of the provided total angle
+    pub fn angle_at(&self, angle_deg: f64, elapsed: Duration) -> f64 {
+        let total_s = self.slew_duration(angle_deg).to_seconds();
+        let t = elapsed.to_seconds().clamp(0.0, total_s);
+        let accel_s = self.accel_duration_s(angle_deg.abs());
+        let peak_rate_deg_s = self.max_accel_deg_s2 * accel_s;
+        let covered = if t < accel_s {
+            0.5 * self.max_accel_deg_s2 * t.powi(2)
+        } else if t < total_s - accel_s {
+            0.5 * peak_rate_deg_s * accel_s + peak_rate_deg_s * (t - accel_s)
+        } else {
+            angle_deg.abs() - 0.5 * self.max_accel_deg_s2 * (total_s - t).powi(2)
+        };
+        covered * angle_deg.signum()
+    }
+}
+
+impl fmt::Display for SlewLimits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "slews limited to {} deg/s and {} deg/s^2",
+            self.max_rate_deg_s, self.max_accel_deg_s2
+        )
+    }
+}
+
+/// A requested pointing mode, held from its start epoch until the start of the next segment.
+#[derive(Clone, Debug)]
+pub struct AttitudeSegment {
+    /// Epoch at which the attitude must be reached
+    pub start: Epoch,
+    /// Pointing mode of this segment
+    pub mode: PointingMode,
+}
+
+impl AttitudeSegment {
+    pub fn new(start: Epoch, mode: PointingMode) -> Self {
+        Self { start, mode }
+    }
+}
+
+/// A slew between two pointing modes, which ends at the start of the segment of the new mode.
+#[derive(Clone, Debug)]
+pub struct Slew {
+    /// Start epoch of the slew
+    pub start: Epoch,
+    /// End epoch of the slew, which is the start of the segment it slews to
+    pub end: Epoch,
+    /// Slew angle (deg)
+    pub angle_deg: f64,
+    /// Pointing mode before the slew
+    pub from: PointingMode,
+    /// Pointing mode after the slew
+    pub to: PointingMode,
+}
+
+impl Slew {
+    /// Duration of this slew
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// Returns whether the provided epoch is within this slew
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        epoch >= self.start && epoch < self.end
+    }
+}
+
+impl fmt::Display for Slew {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "slew of {:.3} deg from {} to {} ({} from {})",
+            self.angle_deg,
+            self.from,
+            self.to,
+            self.duration(),
+            self.start
+        )
+    }
+}
+
+/// Conflicts of an attitude profile with the requested pointing timeline or the maneuver windows
+#[derive(Clone, Debug)]
+pub enum AttitudeConflict {
+    /// The slew must start before the attitude of the previous segment is even reached
+    InsufficientTime { slew: Slew, available: Duration },
+    /// The slew starts before the end of the maneuver of the previous burn segment, which would be flown off-pointed
+    SlewDuringBurn { slew: Slew, burn_end: Epoch },
+    /// The burn segment does not start before the maneuver, which would start off-pointed
+    BurnNotPointed {
+        segment_start: Epoch,
+        burn_start: Epoch,
+    },
+}
+
+impl AttitudeConflict {
+    /// Epoch at which the conflict starts
+    pub fn epoch(&self) -> Epoch {
+        match self {
+            Self::InsufficientTime { slew, .. } | Self::SlewDuringBurn { slew, .. } => slew.start,
+            Self::BurnNotPointed { burn_start, .. } => *burn_start,
+        }
+    }
+}
+
+impl fmt::Display for AttitudeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InsufficientTime { slew, available } => write!(
+                f,
+                "{slew} requires {} but only {available} is available",
+                slew.duration()
+            ),
+            Self::SlewDuringBurn { slew, burn_end } => write!(
+                f,
+                "{slew} starts {} before the end of the maneuver",
+                *burn_end - slew.start
+            ),
+            Self::BurnNotPointed {
+                segment_start,
+                burn_start,
+            } => write!(
+                f,
+                "burn attitude reached at {segment_start} but maneuver starts at {burn_start}"
+            ),
+        }
+    }
+}
+
+/// An attitude profile along a trajectory: the requested pointing segments, the slews between them and the conflicts with the maneuver windows.
+#[derive(Clone)]
+pub struct AttitudeProfile {
+    /// Requested pointing segments, in chronological order
+    pub segments: Vec<AttitudeSegment>,
+    /// Slews between consecutive segments, in chronological order
+    pub slews: Vec<Slew>,
+    /// Conflicts found when building the profile
+    pub conflicts: Vec<AttitudeConflict>,
+    /// Limits used for the slews
+    pub limits: SlewLimits,
+    cosm: Arc<Cosm>,
+}
+
+impl AttitudeProfile {
+    /// Builds the attitude profile along the trajectory, computing the slews between consecutive segments such that each attitude is reached at the start of its segment.
+    ///
+    /// Conflicts are flagged (and not raised as errors) so that the full timeline can be reviewed.
+    pub fn build(
+        traj: &ScTraj,
+        segments: Vec<AttitudeSegment>,
+        limits: SlewLimits,
+        cosm: Arc<Cosm>,
+    ) -> Result<Self, NyxError> {
+        if segments.is_empty() {
+            return Err(NyxError::CustomError(
+                "attitude profile requires at least one segment".to_string(),
+            ));
+        }
+        if limits.max_rate_deg_s <= 0.0 || limits.max_accel_deg_s2 <= 0.0 {
+            return Err(NyxError::CustomError(format!(
+                "slew limits must be positive: {limits}"
+            )));
+        }
+
+        let mut segments = segments;
+        segments.sort_by_key(|segment| segment.start);
+
+        let mut slews = Vec::with_capacity(segments.len() - 1);
+        let mut conflicts = Vec::new();
+        // Epoch when the attitude of the previous segment is reached
+        let mut prev_reached = segments[0].start;
+
+        for (prev, next) in segments.iter().zip(segments.iter().skip(1)) {
+            let state = traj.at(next.start)?;
+            let from_dir = prev.mode.direction(&state, &cosm)?;
+            let to_dir = next.mode.direction(&state, &cosm)?;
+            let angle_deg = from_dir.dot(&to_dir).clamp(-1.0, 1.0).acos().to_degrees();
+
+            let slew = Slew {
+                start: next.start - limits.slew_duration(angle_deg),
+                end: next.start,
+                angle_deg,
+                from: prev.mode.clone(),
+                to: next.mode.clone(),
+            };
+
+            if slew.start < prev_reached {
+                conflicts.push(AttitudeConflict::InsufficientTime {
+                    slew: slew.clone(),
+                    available: next.start - prev_reached,
+                });
+            }
+
+            if let PointingMode::Burn(mnvr) = &prev.mode {
+                if slew.start < mnvr.end {
+                    conflicts.push(AttitudeConflict::SlewDuringBurn {
+                        slew: slew.clone(),
+                        burn_end: mnvr.end,
+                    });
+                }
+            }
+
+            slews.push(slew);
+            prev_reached = next.start;
+        }
+
+        for segment in &segments {
+            if let PointingMode::Burn(mnvr) = &segment.mode {
+                if segment.start > mnvr.start {
+                    conflicts.push(AttitudeConflict::BurnNotPointed {
+                        segment_start: segment.start,
+                        burn_start: mnvr.start,
+                    });
+                }
+            }
+        }
+
+        conflicts.sort_by_key(|conflict| conflict.epoch());
+
+        Ok(Self {
+            segments,
+            slews,
+            conflicts,
+            limits,
+            cosm,
+        })
+    }
+
+    /// Returns whether this profile has no conflict
+    pub fn is_feasible(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Returns the pointing mode held at the provided epoch, or None if it is during a slew or before the first segment
+    pub fn mode_at(&self, epoch: Epoch) -> Option<PointingMode> {
+        if self.slews.iter().any(|slew| slew.contains(epoch)) {
+            return None;
+        }
+        self.segments
+            .iter()
+            .rev()
+            .find(|segment| segment.start <= epoch)
+            .map(|segment| segment.mode.clone())
+    }
+
+    /// Returns the unit vector of the pointing direction of the reference axis for the provided state, in its integration frame.
+    ///
+    /// During slews, the direction is rotated about the eigen axis between the directions of both modes, following the slew profile.
+    pub fn pointing(&self, state: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        let epoch = state.orbit.epoch;
+        if let Some(slew) = self.slews.iter().find(|slew| slew.contains(epoch)) {
+            let from_dir = slew.from.direction(state, &self.cosm)?;
+            let to_dir = slew.to.direction(state, &self.cosm)?;
+            let angle_rad = from_dir.dot(&to_dir).clamp(-1.0, 1.0).acos();
+            let axis = from_dir.cross(&to_dir);
+            if axis.norm() < f64::EPSILON {
+                // Both directions are aligned (or opposite, in which case any axis works and the reference one is returned until the slew ends)
+                return Ok(from_dir);
+            }
+            let progress = if slew.angle_deg > 0.0 {
+                self.limits.angle_at(slew.angle_deg, epoch - slew.start) / slew.angle_deg
+            } else {
+                1.0
+            };
+            return Ok(rotv(&from_dir, &axis, progress * angle_rad));
+        }
+
+        match self.mode_at(epoch) {
+            Some(mode) => mode.direction(state, &self.cosm),
+            None => Err(NyxError::CustomError(format!(
+                "no attitude defined before {}",
+                self.segments[0].start
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for AttitudeProfile {
+    /// Prints the operations timeline: each pointing segment preceded by its slew, then the conflicts
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Attitude profile ({})", self.limits)?;
+        writeln!(f, "\t{}: {}", self.segments[0].start, self.segments[0].mode)?;
+        for (slew, segment) in self.slews.iter().zip(self.segments.iter().skip(1)) {
+            writeln!(f, "\t{}: {}", slew.start, slew)?;
+            writeln!(f, "\t{}: {}", segment.start, segment.mode)?;
+        }
+        if self.is_feasible() {
+            write!(f, "no conflicts")
+        } else {
+            write!(f, "{} conflict(s):", self.conflicts.len())?;
+            for conflict in &self.conflicts {
+                write!(f, "\n\t{}: {}", conflict.epoch(), conflict)?;
+            }
+            Ok(())
+        }
+    }
+}
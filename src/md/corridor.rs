@@ -0,0 +1,471 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::trajectory::Traj;
+use crate::cosmic::{Frame, Orbit};
+use crate::dynamics::OrbitalDynamics;
+use crate::errors::NyxError;
+use crate::linalg::{Matrix3, Vector3};
+use crate::propagators::Propagator;
+use crate::time::{Duration, Epoch, Unit};
+use crate::State;
+use std::fmt;
+
+/// Maximum number of sweeps of the coordinate descent of the minimal re-entry
+const QP_MAX_SWEEPS: usize = 100;
+/// Tolerance on the position at the re-entry epoch of the corrector, in km
+const CORRECTION_TOL_KM: f64 = 1e-4;
+
+/// Half widths of a corridor at a given epoch, in the RIC frame of the reference trajectory
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CorridorBound {
+    pub epoch: Epoch,
+    /// Half widths along the radial, in-track and cross-track directions, in km
+    pub half_widths_km: Vector3<f64>,
+}
+
+/// A time-varying tube around a reference trajectory, e.g. the approach corridor of a rendezvous.
+///
+/// The half widths of the tube are defined in the RIC frame of the reference at a set of epochs, and linearly interpolated
+/// between them (they are constant before the first and after the last of these epochs).
+#[derive(Clone)]
+pub struct Corridor {
+    pub reference: Traj<Orbit>,
+    bounds: Vec<CorridorBound>,
+}
+
+impl Corridor {
+    /// Initializes a corridor of constant half widths (in km) around the reference trajectory
+    pub fn new(reference: Traj<Orbit>, half_widths_km: Vector3<f64>) -> Self {
+        let bounds = vec![CorridorBound {
+            epoch: reference.first().epoch,
+            half_widths_km,
+        }];
+        Self { reference, bounds }
+    }
+
+    /// Sets the half widths of the corridor at the provided epoch, e.g. to narrow it towards the rendezvous
+    pub fn with_bound(mut self, epoch: Epoch, half_widths_km: Vector3<f64>) -> Self {
+        self.bounds.retain(|bound| bound.epoch != epoch);
+        self.bounds.push(CorridorBound {
+            epoch,
+            half_widths_km,
+        });
+        self.bounds.sort_by_key(|bound| bound.epoch);
+        self
+    }
+
+    /// Returns the bounds of this corridor, chronologically
+    pub fn bounds(&self) -> &[CorridorBound] {
+        &self.bounds
+    }
+
+    /// Returns the half widths of the corridor at the provided epoch, in km
+    pub fn half_widths_km(&self, epoch: Epoch) -> Vector3<f64> {
+        let after = self.bounds.partition_point(|bound| bound.epoch <= epoch);
+        if after == 0 {
+            self.bounds[0].half_widths_km
+        } else if after == self.bounds.len() {
+            self.bounds[after - 1].half_widths_km
+        } else {
+            let (prev, next) = (&self.bounds[after - 1], &self.bounds[after]);
+            let ratio = (epoch - prev.epoch).to_seconds() / (next.epoch - prev.epoch).to_seconds();
+            prev.half_widths_km + (next.half_widths_km - prev.half_widths_km) * ratio
+        }
+    }
+
+    /// Returns the position of the orbit relative to the reference at the same epoch, in the RIC frame of the reference, in km
+    pub fn ric_error_km(&self, orbit: &Orbit) -> Result<Vector3<f64>, NyxError> {
+        let reference = self.reference.at(orbit.epoch)?;
+        Ok(reference.dcm_from_traj_frame(Frame::RIC)?.transpose()
+            * (orbit.radius() - reference.radius()))
+    }
+
+    /// Returns the largest ratio of the RIC error of the orbit to the half widths of the corridor, which is at most one if the orbit
+    /// is within the corridor
+    pub fn excursion(&self, orbit: &Orbit) -> Result<f64, NyxError> {
+        let error_km = self.ric_error_km(orbit)?;
+        let half_widths_km = self.half_widths_km(orbit.epoch);
+        Ok((0..3)
+            .map(|i| error_km[i].abs() / half_widths_km[i])
+            .fold(0.0, f64::max))
+    }
+
+    /// Returns whether the orbit is within the corridor
+    pub fn contains(&self, orbit: &Orbit) -> Result<bool, NyxError> {
+        Ok(self.excursion(orbit)? <= 1.0)
+    }
+}
+
+impl fmt::Display for Corridor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "corridor around the reference from {} to {}",
+            self.reference.first().epoch,
+            self.reference.last().epoch
+        )?;
+        for bound in &self.bounds {
+            write!(
+                f,
+                ", RIC half widths [{:.3}, {:.3}, {:.3}] km from {}",
+                bound.half_widths_km[0],
+                bound.half_widths_km[1],
+                bound.half_widths_km[2],
+                bound.epoch
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// An impulsive maneuver bringing the spacecraft back into the corridor
+#[derive(Copy, Clone, Debug)]
+pub struct CorridorManeuver {
+    pub epoch: Epoch,
+    /// Epoch at which the spacecraft was predicted to exit the corridor without this maneuver
+    pub exit_epoch: Epoch,
+    /// Epoch at which the spacecraft is targeted to be within the corridor
+    pub target_epoch: Epoch,
+    /// Delta-V in the inertial frame of the orbit, in km/s
+    pub dv_inertial_km_s: Vector3<f64>,
+}
+
+impl CorridorManeuver {
+    /// Magnitude of the maneuver, in km/s
+    pub fn dv_km_s(&self) -> f64 {
+        self.dv_inertial_km_s.norm()
+    }
+}
+
+impl fmt::Display for CorridorManeuver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} corridor maneuver of {:.4} m/s (exit predicted at {}, targeted within the corridor at {})",
+            self.epoch,
+            self.dv_km_s() * 1e3,
+            self.exit_epoch,
+            self.target_epoch
+        )
+    }
+}
+
+/// The corridor maneuvers of a spacecraft and the controlled trajectory flown with them.
+#[derive(Clone)]
+pub struct CorridorPlan {
+    pub maneuvers: Vec<CorridorManeuver>,
+    /// Controlled trajectory, which includes the post-maneuver states
+    pub traj: Traj<Orbit>,
+    /// Largest excursion over the controlled trajectory, i.e. largest ratio of the RIC error to the half widths of the corridor
+    pub max_excursion: f64,
+}
+
+impl CorridorPlan {
+    /// Total delta-V of the maneuvers, in km/s
+    pub fn total_dv_km_s(&self) -> f64 {
+        self.maneuvers.iter().map(|mnvr| mnvr.dv_km_s()).sum()
+    }
+
+    /// Returns whether the controlled trajectory remained within the corridor
+    pub fn within_corridor(&self) -> bool {
+        self.max_excursion <= 1.0
+    }
+}
+
+impl fmt::Display for CorridorPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "corridor plan from {} to {} ({}): {} maneuvers, total = {:.4} m/s, max excursion = {:.3}",
+            self.traj.first().epoch,
+            self.traj.last().epoch,
+            if self.within_corridor() {
+                "within corridor"
+            } else {
+                "OUTSIDE corridor"
+            },
+            self.maneuvers.len(),
+            self.total_dv_km_s() * 1e3,
+            self.max_excursion
+        )?;
+        for mnvr in &self.maneuvers {
+            writeln!(f, "{mnvr}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Keeps a spacecraft within a corridor around a reference trajectory.
+///
+/// The trajectory is predicted over the look-ahead duration and checked against the corridor at every check step. When it is
+/// predicted to exit the corridor, a maneuver is executed the lead time before that exit (or immediately if the exit is closer):
+/// it is the smallest delta-V which places the spacecraft within the target ratio of the half widths of the corridor at the
+/// predicted exit epoch, computed from the state transition matrix, and then corrected with the dynamics until the targeted
+/// position is reached.
+///
+/// The maneuvers are then flown with the dynamics, so the resulting plan is validated on the controlled trajectory.
+#[derive(Clone)]
+pub struct CorridorTargeting {
+    pub corridor: Corridor,
+    pub dynamics: OrbitalDynamics,
+    /// Duration over which the trajectory is predicted to detect the exits of the corridor
+    pub lookahead: Duration,
+    /// Step at which the predicted trajectory is checked against the corridor
+    pub check_step: Duration,
+    /// Duration between a maneuver and the predicted exit it prevents
+    pub lead_time: Duration,
+    /// Fraction of the half widths of the corridor targeted at the predicted exit epoch, as a margin for the next prediction
+    pub target_ratio: f64,
+    /// Maximum number of iterations of the corrector of each maneuver
+    pub max_iterations: usize,
+    /// Maximum number of maneuvers of a plan
+    pub max_maneuvers: usize,
+}
+
+impl CorridorTargeting {
+    /// Initializes a planner with a six hour look-ahead checked every minute, maneuvers half an hour before the predicted exits,
+    /// and re-entries half way to the center of the corridor
+    pub fn new(corridor: Corridor, dynamics: OrbitalDynamics) -> Self {
+        Self {
+            corridor,
+            dynamics,
+            lookahead: 6 * Unit::Hour,
+            check_step: 1 * Unit::Minute,
+            lead_time: 30 * Unit::Minute,
+            target_ratio: 0.5,
+            max_iterations: 10,
+            max_maneuvers: 100,
+        }
+    }
+
+    /// Sets the duration over which the trajectory is predicted
+    pub fn with_lookahead(mut self, lookahead: Duration) -> Self {
+        self.lookahead = lookahead;
+        self
+    }
+
+    /// Sets the duration between a maneuver and the predicted exit it prevents
+    pub fn with_lead_time(mut self, lead_time: Duration) -> Self {
+        self.lead_time = lead_time;
+        self
+    }
+
+    /// Sets the fraction of the half widths of the corridor targeted at the predicted exit epoch
+    pub fn with_target_ratio(mut self, target_ratio: f64) -> Self {
+        self.target_ratio = target_ratio;
+        self
+    }
+
+    /// Plans and flies the corridor maneuvers from the initial orbit for the provided duration
+    pub fn plan(&self, initial: Orbit, duration: Duration) -> Result<CorridorPlan, NyxError> {
+        if self
+            .corridor
+            .bounds
+            .iter()
+            .any(|bound| bound.half_widths_km.iter().any(|hw| *hw <= 0.0))
+        {
+            return Err(NyxError::CustomError(
+                "corridor half widths must be positive".to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.target_ratio) {
+            return Err(NyxError::CustomError(format!(
+                "corridor target ratio must be between 0 and 1 but got {}",
+                self.target_ratio
+            )));
+        }
+        if self.lookahead <= self.check_step || self.lead_time <= Duration::ZERO {
+            return Err(NyxError::CustomError(format!(
+                "corridor look-ahead ({}) must be longer than the check step ({}), and the lead time ({}) positive",
+                self.lookahead, self.check_step, self.lead_time
+            )));
+        }
+
+        let end = initial.epoch + duration;
+        // Ensures that the reference covers the whole plan
+        self.corridor.reference.at(initial.epoch)?;
+        self.corridor.reference.at(end)?;
+
+        let mut state = initial;
+        let mut traj = Traj::new();
+        traj.states.push(initial);
+        let mut maneuvers = Vec::new();
+
+        while state.epoch < end {
+            let horizon = (state.epoch + self.lookahead).min(end);
+            let (_, predicted) = Propagator::default(self.dynamics.clone())
+                .with(state)
+                .until_epoch_with_traj(horizon)?;
+
+            match self.first_exit(&predicted)? {
+                None => state = self.fly(state, horizon, &mut traj)?,
+                Some(exit_epoch) => {
+                    if maneuvers.len() == self.max_maneuvers {
+                        return Err(NyxError::MaxIterReached(format!(
+                            "{} corridor maneuvers planned before {}",
+                            self.max_maneuvers, state.epoch
+                        )));
+                    }
+                    let epoch = (exit_epoch - self.lead_time).max(state.epoch);
+                    // Leave at least one check step to re-enter the corridor if the exit is imminent
+                    let target_epoch = if exit_epoch - epoch < self.check_step {
+                        (epoch + self.lead_time).min(end)
+                    } else {
+                        exit_epoch
+                    };
+                    state = self.fly(state, epoch, &mut traj)?;
+                    let mnvr = CorridorManeuver {
+                        epoch,
+                        exit_epoch,
+                        target_epoch,
+                        dv_inertial_km_s: self.correction(&state, target_epoch)?,
+                    };
+                    debug!("{mnvr}");
+                    state.apply_dv(mnvr.dv_inertial_km_s);
+                    maneuvers.push(mnvr);
+                    state = self.fly(state, target_epoch, &mut traj)?;
+                }
+            }
+        }
+
+        let mut max_excursion: f64 = 0.0;
+        for orbit in &traj.states {
+            max_excursion = max_excursion.max(self.corridor.excursion(orbit)?);
+        }
+
+        let plan = CorridorPlan {
+            maneuvers,
+            traj,
+            max_excursion,
+        };
+        info!("{plan}");
+        Ok(plan)
+    }
+
+    /// Returns the first epoch, sampled at the check step, when the predicted trajectory is outside the corridor
+    fn first_exit(&self, predicted: &Traj<Orbit>) -> Result<Option<Epoch>, NyxError> {
+        for orbit in predicted
+            .every(self.check_step)
+            .chain(std::iter::once(*predicted.last()))
+        {
+            if !self.corridor.contains(&orbit)? {
+                return Ok(Some(orbit.epoch));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the smallest delta-V (in km/s) placing the orbit within the target ratio of the corridor at the target epoch.
+    ///
+    /// The targeted RIC position minimizes the delta-V of the linearized problem, i.e. Δe^T (M M^T)^-1 Δe where M maps the delta-V
+    /// to the RIC position at the target epoch, within the targeted box: it is found by coordinate descent. The delta-V is then
+    /// corrected by Newton iterations with the state transition matrix until that position is reached.
+    fn correction(&self, state: &Orbit, target_epoch: Epoch) -> Result<Vector3<f64>, NyxError> {
+        let reference = self.corridor.reference.at(target_epoch)?;
+        let dcm_inertial2ric = reference.dcm_from_traj_frame(Frame::RIC)?.transpose();
+        let half_widths_km = self.corridor.half_widths_km(target_epoch) * self.target_ratio;
+
+        let mut dv_km_s = Vector3::zeros();
+        let mut aim_km: Option<Vector3<f64>> = None;
+        for _ in 0..self.max_iterations {
+            let mut start = *state;
+            start.apply_dv(dv_km_s);
+            let arrival = Propagator::default(self.dynamics.clone())
+                .with(start.with_stm())
+                .until_epoch(target_epoch)?;
+            let error_km = self.corridor.ric_error_km(&arrival)?;
+            let sensitivity: Matrix3<f64> =
+                dcm_inertial2ric * arrival.stm()?.fixed_view::<3, 3>(0, 3);
+
+            let aim_km = *aim_km.get_or_insert_with(|| {
+                error_km + min_norm_shift(&sensitivity, &error_km, &half_widths_km)
+            });
+            let miss_km = aim_km - error_km;
+            if miss_km.norm() < CORRECTION_TOL_KM {
+                return Ok(dv_km_s);
+            }
+            let inverse = sensitivity.try_inverse().ok_or_else(|| {
+                NyxError::CustomError(format!(
+                    "singular sensitivity of the RIC position at {target_epoch} to the maneuver at {}",
+                    state.epoch
+                ))
+            })?;
+            dv_km_s += inverse * miss_km;
+        }
+
+        Err(NyxError::MaxIterReached(format!(
+            "corridor maneuver at {} did not converge in {} iterations",
+            state.epoch, self.max_iterations
+        )))
+    }
+
+    /// Propagates the orbit until the provided epoch, and appends the states to the controlled trajectory
+    fn fly(&self, state: Orbit, epoch: Epoch, traj: &mut Traj<Orbit>) -> Result<Orbit, NyxError> {
+        if epoch <= state.epoch {
+            return Ok(state);
+        }
+        let (next, segment) = Propagator::default(self.dynamics.clone())
+            .with(state)
+            .until_epoch_with_traj(epoch)?;
+        // Replace the pre-maneuver state with the post-maneuver one
+        if traj
+            .states
+            .last()
+            .is_some_and(|last| last.epoch == state.epoch)
+        {
+            traj.states.pop();
+        }
+        traj.states.extend(segment.states);
+        Ok(next)
+    }
+}
+
+/// Returns the shift of the RIC position which brings it within the half widths and whose linearized delta-V is the smallest,
+/// by coordinate descent of the box constrained quadratic program (or the closest shift if the sensitivity is singular)
+fn min_norm_shift(
+    sensitivity: &Matrix3<f64>,
+    error_km: &Vector3<f64>,
+    half_widths_km: &Vector3<f64>,
+) -> Vector3<f64> {
+    let lower = -half_widths_km - error_km;
+    let upper = half_widths_km - error_km;
+    // Start from the closest point of the box
+    let mut shift = Vector3::from_fn(|i, _| 0.0_f64.clamp(lower[i], upper[i]));
+    let weights = match (sensitivity * sensitivity.transpose()).try_inverse() {
+        Some(weights) => weights,
+        None => return shift,
+    };
+
+    for _ in 0..QP_MAX_SWEEPS {
+        let mut change: f64 = 0.0;
+        for i in 0..3 {
+            let coupling: f64 = (0..3)
+                .filter(|j| *j != i)
+                .map(|j| weights[(i, j)] * shift[j])
+                .sum();
+            let updated = (-coupling / weights[(i, i)]).clamp(lower[i], upper[i]);
+            change = change.max((updated - shift[i]).abs());
+            shift[i] = updated;
+        }
+        if change < 1e-12 {
+            break;
+        }
+    }
+    shift
+}
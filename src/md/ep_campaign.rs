@@ -0,0 +1,443 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::eclipse::EclipseLocator;
+use crate::cosmic::{Cosm, GuidanceMode, Orbit};
+use crate::dynamics::guidance::{PowerLimited, PowerModel};
+use crate::dynamics::SpacecraftDynamics;
+use crate::errors::NyxError;
+use crate::od::GroundStation;
+use crate::propagators::Propagator;
+use crate::time::{Duration, Epoch, Unit};
+use crate::{Spacecraft, State};
+use std::fmt;
+use std::sync::Arc;
+
+/// Operational constraints of a low-thrust orbit raising campaign.
+#[derive(Clone, Debug)]
+pub struct CampaignConstraints {
+    /// Maximum fraction of each duty cycle period that may be spent thrusting, within ]0; 1]
+    pub max_duty_cycle: f64,
+    /// Period over which the duty cycle is enforced, e.g. one day
+    pub duty_cycle_period: Duration,
+    /// Ground stations used to command the spacecraft, leave empty if commanding is not constrained
+    pub ground_stations: Vec<GroundStation>,
+    /// Maximum time the spacecraft may keep thrusting since its last ground contact
+    pub max_time_since_contact: Duration,
+    /// Altitude band of the radiation belts, in km above the equatorial radius of the central body.
+    /// The duty cycle is ignored within this band so that the spacecraft spends as little time as possible in the belts.
+    pub radiation_belts_km: Option<(f64, f64)>,
+}
+
+impl CampaignConstraints {
+    /// Constrains only the thruster duty cycle
+    pub fn duty_cycle(max_duty_cycle: f64, duty_cycle_period: Duration) -> Self {
+        Self {
+            max_duty_cycle,
+            duty_cycle_period,
+            ground_stations: Vec::new(),
+            max_time_since_contact: Duration::MAX,
+            radiation_belts_km: None,
+        }
+    }
+
+    /// Requires a contact with any of the provided ground stations at most `max_time_since_contact` before any thrusting
+    pub fn with_ground_contacts(
+        mut self,
+        ground_stations: Vec<GroundStation>,
+        max_time_since_contact: Duration,
+    ) -> Self {
+        self.ground_stations = ground_stations;
+        self.max_time_since_contact = max_time_since_contact;
+        self
+    }
+
+    /// Sets the altitude band of the radiation belts, in km
+    pub fn with_radiation_belts(mut self, min_altitude_km: f64, max_altitude_km: f64) -> Self {
+        self.radiation_belts_km = Some((min_altitude_km, max_altitude_km));
+        self
+    }
+
+    /// Returns whether the provided orbit is within the radiation belts
+    pub fn in_radiation_belts(&self, orbit: &Orbit) -> bool {
+        match self.radiation_belts_km {
+            Some((min_alt_km, max_alt_km)) => {
+                let alt_km = orbit.rmag_km() - orbit.frame.equatorial_radius();
+                (min_alt_km..=max_alt_km).contains(&alt_km)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Summary of one reporting period (usually one week) of the campaign.
+#[derive(Clone, Debug)]
+pub struct CampaignPeriod {
+    pub start: Epoch,
+    pub end: Epoch,
+    /// Orbit at the start of this period
+    pub initial_orbit: Orbit,
+    /// Orbit at the end of this period
+    pub final_orbit: Orbit,
+    /// Total time spent thrusting
+    pub thrust_time: Duration,
+    /// Delta-V imparted by the thruster, in km/s
+    pub delta_v_km_s: f64,
+    pub fuel_used_kg: f64,
+    /// Number of distinct ground contacts
+    pub contacts: usize,
+    /// Total time spent in the radiation belts
+    pub time_in_belts: Duration,
+    /// Smallest power margin while thrusting over this period, i.e. the generated power minus the bus and thruster consumptions, if a power model is set
+    pub min_power_margin_w: Option<f64>,
+}
+
+impl CampaignPeriod {
+    fn new(state: &Spacecraft) -> Self {
+        Self {
+            start: state.epoch(),
+            end: state.epoch(),
+            initial_orbit: state.orbit,
+            final_orbit: state.orbit,
+            thrust_time: Duration::ZERO,
+            delta_v_km_s: 0.0,
+            fuel_used_kg: 0.0,
+            contacts: 0,
+            time_in_belts: Duration::ZERO,
+            min_power_margin_w: None,
+        }
+    }
+
+    /// Fraction of this period spent thrusting
+    pub fn duty_cycle(&self) -> f64 {
+        let duration = self.end - self.start;
+        if duration > Duration::ZERO {
+            (self.thrust_time.to_seconds() / duration.to_seconds()).min(1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for CampaignPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {}\tSMA {:.3} -> {:.3} km\tECC {:.6}\tINC {:.3} deg\tthrust {} ({:.1} %)\tΔv {:.3} m/s\tfuel {:.3} kg\tcontacts {}\tbelts {}",
+            self.start,
+            self.end,
+            self.initial_orbit.sma_km(),
+            self.final_orbit.sma_km(),
+            self.final_orbit.ecc(),
+            self.final_orbit.inc_deg(),
+            self.thrust_time,
+            self.duty_cycle() * 100.0,
+            self.delta_v_km_s * 1e3,
+            self.fuel_used_kg,
+            self.contacts,
+            self.time_in_belts,
+        )?;
+        if let Some(margin) = self.min_power_margin_w {
+            write!(f, "\tmin power margin {margin:.1} W")?;
+        }
+        Ok(())
+    }
+}
+
+/// The result of planning an orbit raising campaign, period by period.
+#[derive(Clone, Debug)]
+pub struct CampaignPlan {
+    pub periods: Vec<CampaignPeriod>,
+    pub initial_state: Spacecraft,
+    pub final_state: Spacecraft,
+    /// Whether the objectives of the guidance law were achieved before the end of the campaign
+    pub achieved: bool,
+}
+
+impl CampaignPlan {
+    /// Total delta-V of the campaign, in km/s
+    pub fn total_dv_km_s(&self) -> f64 {
+        self.periods.iter().map(|p| p.delta_v_km_s).sum()
+    }
+
+    /// Total fuel used over the campaign, in kg
+    pub fn total_fuel_kg(&self) -> f64 {
+        self.periods.iter().map(|p| p.fuel_used_kg).sum()
+    }
+
+    /// Total time spent thrusting
+    pub fn total_thrust_time(&self) -> Duration {
+        self.periods
+            .iter()
+            .fold(Duration::ZERO, |acc, p| acc + p.thrust_time)
+    }
+
+    /// Total time spent in the radiation belts
+    pub fn total_time_in_belts(&self) -> Duration {
+        self.periods
+            .iter()
+            .fold(Duration::ZERO, |acc, p| acc + p.time_in_belts)
+    }
+
+    /// Duration of the campaign
+    pub fn duration(&self) -> Duration {
+        self.final_state.epoch() - self.initial_state.epoch()
+    }
+
+    /// Smallest power margin of the campaign, if a power model was set
+    pub fn min_power_margin_w(&self) -> Option<f64> {
+        self.periods
+            .iter()
+            .filter_map(|p| p.min_power_margin_w)
+            .reduce(f64::min)
+    }
+}
+
+impl fmt::Display for CampaignPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Campaign of {} ({}): Δv = {:.3} m/s\tfuel = {:.3} kg\tthrust time = {}",
+            self.duration(),
+            if self.achieved {
+                "achieved"
+            } else {
+                "NOT achieved"
+            },
+            self.total_dv_km_s() * 1e3,
+            self.total_fuel_kg(),
+            self.total_thrust_time()
+        )?;
+        for (i, period) in self.periods.iter().enumerate() {
+            writeln!(f, "#{i}\t{period}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Plans a low-thrust orbit raising campaign by propagating the spacecraft with its guidance law (e.g. Ruggiero) while
+/// enforcing the operational constraints. The thrust or coast decision is made at every decision step.
+#[derive(Clone)]
+pub struct CampaignPlanner {
+    /// Spacecraft dynamics, which must include the guidance law to follow when thrusting
+    pub dynamics: SpacecraftDynamics,
+    pub constraints: CampaignConstraints,
+    /// Time between two thrust or coast decisions
+    pub decision_step: Duration,
+    /// Duration of each reported period of the plan
+    pub report_period: Duration,
+    /// Power limits applied to the guidance law, if any
+    pub power: Option<Arc<PowerLimited>>,
+    pub cosm: Arc<Cosm>,
+}
+
+impl CampaignPlanner {
+    /// Initializes a planner with weekly reports and a ten minute decision step
+    pub fn new(
+        dynamics: SpacecraftDynamics,
+        constraints: CampaignConstraints,
+        cosm: Arc<Cosm>,
+    ) -> Result<Self, NyxError> {
+        if dynamics.guid_law.is_none() {
+            return Err(NyxError::CustomError(
+                "campaign planner requires dynamics with a guidance law".to_string(),
+            ));
+        }
+        if !(constraints.max_duty_cycle > 0.0 && constraints.max_duty_cycle <= 1.0) {
+            return Err(NyxError::CustomError(format!(
+                "duty cycle must be within ]0; 1] but got {}",
+                constraints.max_duty_cycle
+            )));
+        }
+        if constraints.duty_cycle_period <= Duration::ZERO {
+            return Err(NyxError::CustomError(
+                "duty cycle period must be positive".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            dynamics,
+            constraints,
+            decision_step: 10 * Unit::Minute,
+            report_period: 7 * Unit::Day,
+            power: None,
+            cosm,
+        })
+    }
+
+    /// Limits the throttle of the guidance law with the provided power model, and reports the power margins
+    pub fn with_power(mut self, power: PowerModel, eclipse_locator: EclipseLocator) -> Self {
+        let inner = self.dynamics.guid_law.clone().unwrap();
+        let limited = PowerLimited::new(inner, power, eclipse_locator);
+        self.dynamics = self.dynamics.with_guidance_law(limited.clone());
+        self.power = Some(limited);
+        self
+    }
+
+    /// Sets the time between two thrust or coast decisions
+    pub fn with_decision_step(mut self, decision_step: Duration) -> Self {
+        self.decision_step = decision_step;
+        self
+    }
+
+    /// Sets the duration of each reported period
+    pub fn with_report_period(mut self, report_period: Duration) -> Self {
+        self.report_period = report_period;
+        self
+    }
+
+    /// Returns whether any of the ground stations sees this orbit
+    fn in_contact(&self, orbit: &Orbit) -> bool {
+        self.constraints.ground_stations.iter().any(|gs| {
+            let (_, elevation_deg, _, _) = gs.azimuth_elevation_of(*orbit, &self.cosm);
+            elevation_deg >= gs.elevation_mask_deg
+        })
+    }
+
+    /// Returns the power margin in Watts for the provided throttle level, if a power model is set
+    fn power_margin_w(&self, state: &Spacecraft, throttle: f64) -> Option<f64> {
+        self.power.as_ref().map(|limited| {
+            let (light_fraction, sun_distance_au) = limited.illumination(state);
+            limited
+                .power
+                .available_thruster_power_w(light_fraction, sun_distance_au)
+                - throttle * limited.power.thruster_power_w
+        })
+    }
+
+    /// Plans the campaign from the initial state until the guidance objectives are achieved or until the maximum duration elapses.
+    pub fn plan(
+        &self,
+        initial_state: Spacecraft,
+        max_duration: Duration,
+    ) -> Result<CampaignPlan, NyxError> {
+        let guid_law = self.dynamics.guid_law.clone().unwrap();
+        let thruster = initial_state.thruster.ok_or(NyxError::NoThrusterAvail)?;
+        let coast_dynamics = self.dynamics.without_guidance_law();
+
+        let start = initial_state.epoch();
+        let end = start + max_duration;
+
+        let mut state = initial_state.with_guidance_mode(GuidanceMode::Thrust);
+        let mut periods = Vec::new();
+        let mut period = CampaignPeriod::new(&state);
+
+        let mut last_contact: Option<Epoch> = None;
+        let mut was_in_contact = false;
+        let mut duty_period_idx = -1_i64;
+        let mut duty_thrust_time = Duration::ZERO;
+        let duty_allowance = self.constraints.max_duty_cycle * self.constraints.duty_cycle_period;
+
+        let mut achieved = guid_law.achieved(&state).unwrap_or(false);
+
+        while !achieved && state.epoch() < end {
+            let epoch = state.epoch();
+            let step = self.decision_step.min(end - epoch);
+
+            // Ground contacts
+            if !self.constraints.ground_stations.is_empty() {
+                let in_contact = self.in_contact(&state.orbit);
+                if in_contact {
+                    last_contact = Some(epoch);
+                    if !was_in_contact {
+                        period.contacts += 1;
+                    }
+                }
+                was_in_contact = in_contact;
+            }
+            // Without ground stations, the spacecraft can always be commanded
+            let commanded = self.constraints.ground_stations.is_empty()
+                || last_contact.is_some_and(|contact| {
+                    epoch - contact <= self.constraints.max_time_since_contact
+                });
+
+            // Duty cycle, reset at the start of each duty cycle period
+            let this_duty_idx = ((epoch - start).to_seconds()
+                / self.constraints.duty_cycle_period.to_seconds())
+            .floor() as i64;
+            if this_duty_idx != duty_period_idx {
+                duty_period_idx = this_duty_idx;
+                duty_thrust_time = Duration::ZERO;
+            }
+
+            let in_belts = self.constraints.in_radiation_belts(&state.orbit);
+            if in_belts {
+                period.time_in_belts += step;
+            }
+
+            let thrust = commanded && (in_belts || duty_thrust_time + step <= duty_allowance);
+            let throttle = if thrust {
+                guid_law.throttle(&state)
+            } else {
+                0.0
+            };
+
+            if throttle > 0.0 {
+                if let Some(margin) = self.power_margin_w(&state, throttle) {
+                    period.min_power_margin_w = Some(match period.min_power_margin_w {
+                        Some(prev) => prev.min(margin),
+                        None => margin,
+                    });
+                }
+            }
+
+            let prev_mass_kg = state.mass_kg();
+            state = if thrust {
+                duty_thrust_time += step;
+                period.thrust_time += step;
+                Propagator::default(self.dynamics.clone())
+                    .with(state)
+                    .for_duration(step)?
+            } else {
+                Propagator::default(coast_dynamics.clone())
+                    .with(state)
+                    .for_duration(step)?
+            };
+
+            // Rocket equation over this step
+            let fuel_used_kg = prev_mass_kg - state.mass_kg();
+            if fuel_used_kg > 0.0 {
+                period.fuel_used_kg += fuel_used_kg;
+                period.delta_v_km_s +=
+                    thruster.exhaust_velocity_m_s() * 1e-3 * (prev_mass_kg / state.mass_kg()).ln();
+            }
+
+            achieved = guid_law.achieved(&state).unwrap_or(false);
+
+            if state.epoch() - period.start >= self.report_period || achieved {
+                period.end = state.epoch();
+                period.final_orbit = state.orbit;
+                periods.push(period);
+                period = CampaignPeriod::new(&state);
+            }
+        }
+
+        if state.epoch() > period.start {
+            period.end = state.epoch();
+            period.final_orbit = state.orbit;
+            periods.push(period);
+        }
+
+        Ok(CampaignPlan {
+            periods,
+            initial_state,
+            final_state: state,
+            achieved,
+        })
+    }
+}
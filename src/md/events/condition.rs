@@ -0,0 +1,288 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::EventEvaluator;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::DefaultAllocator;
+use crate::time::Duration;
+use crate::State;
+use std::fmt;
+use std::ops::Not;
+use std::sync::Arc;
+
+/// A stop condition composed of events and conditions with boolean logic, e.g. "apoapsis AND altitude below 500 km", or
+/// "eclipse entry OR 30 days elapsed". It is itself an `EventEvaluator`, so it can be used anywhere an event is accepted.
+///
+/// Its leaves are either:
+/// + an `Event`, which only occurs at the instant its evaluator crosses zero (e.g. the apoapsis);
+/// + a `Condition`, which holds while its evaluator is positive (e.g. an altitude above 500 km) and occurs when it becomes true.
+///
+/// An `And` occurs when one of its terms occurs while all of the others hold, so an `And` of an event and of conditions occurs
+/// at the event only if the conditions hold then. An `Or` occurs when any of its terms occurs. A `Not` negates the conditions
+/// it contains (following De Morgan's laws), but an event cannot be negated and a negated event never occurs. The conditions
+/// are checked at the end of each propagation step, or of each search bracket when searching a trajectory.
+///
+/// The crossing is then refined on the evaluator of the leaf which occurred. All evaluations are scaled by the value precision
+/// of their leaf, so the value precision of an expression is one.
+pub enum ConditionExpr<S: State>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    /// Occurs when the evaluator crosses zero, and never holds otherwise
+    Event(Arc<dyn EventEvaluator<S>>),
+    /// Holds while the evaluator is positive, and occurs when it becomes positive
+    Condition(Arc<dyn EventEvaluator<S>>),
+    /// Occurs when any term occurs while all of the other terms hold
+    And(Vec<ConditionExpr<S>>),
+    /// Occurs when any term occurs
+    Or(Vec<ConditionExpr<S>>),
+    /// Negation of the conditions of the expression
+    Not(Box<ConditionExpr<S>>),
+}
+
+impl<S: State> ConditionExpr<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    /// Builds an expression which occurs when the provided event is crossed
+    pub fn event<F: EventEvaluator<S> + 'static>(event: F) -> Self {
+        Self::Event(Arc::new(event))
+    }
+
+    /// Builds an expression which holds while the provided event evaluates to a positive value, e.g. an altitude event
+    /// holds while above the desired altitude. Use `!` to hold while below.
+    pub fn condition<F: EventEvaluator<S> + 'static>(event: F) -> Self {
+        Self::Condition(Arc::new(event))
+    }
+
+    /// Returns the conjunction of this expression and the other one
+    pub fn and(self, other: Self) -> Self {
+        match self {
+            Self::And(mut terms) => {
+                terms.push(other);
+                Self::And(terms)
+            }
+            _ => Self::And(vec![self, other]),
+        }
+    }
+
+    /// Returns the disjunction of this expression and the other one
+    pub fn or(self, other: Self) -> Self {
+        match self {
+            Self::Or(mut terms) => {
+                terms.push(other);
+                Self::Or(terms)
+            }
+            _ => Self::Or(vec![self, other]),
+        }
+    }
+
+    /// Returns whether this expression holds at the provided state
+    pub fn holds(&self, state: &S) -> bool {
+        self.holds_with(state, false)
+    }
+
+    /// Returns whether this expression occurs between both states
+    pub fn occurs(&self, prev_state: &S, next_state: &S) -> bool {
+        self.trigger(prev_state, next_state, false).is_some()
+    }
+
+    fn holds_with(&self, state: &S, negated: bool) -> bool {
+        match self {
+            Self::Event(_) => negated,
+            Self::Condition(event) => (event.eval(state) > 0.0) != negated,
+            Self::And(terms) if !negated => terms.iter().all(|t| t.holds_with(state, false)),
+            Self::Or(terms) if negated => terms.iter().all(|t| t.holds_with(state, true)),
+            Self::And(terms) | Self::Or(terms) => {
+                terms.iter().any(|t| t.holds_with(state, negated))
+            }
+            Self::Not(expr) => expr.holds_with(state, !negated),
+        }
+    }
+
+    /// Returns the leaf which makes this expression occur between both states, and the sign to apply to its evaluation
+    fn trigger(
+        &self,
+        prev_state: &S,
+        next_state: &S,
+        negated: bool,
+    ) -> Option<(&Arc<dyn EventEvaluator<S>>, f64)> {
+        match self {
+            Self::Event(event) => {
+                (!negated && event.eval_crossing(prev_state, next_state)).then_some((event, 1.0))
+            }
+            Self::Condition(event) => (!self.holds_with(prev_state, negated)
+                && self.holds_with(next_state, negated))
+            .then_some((event, if negated { -1.0 } else { 1.0 })),
+            Self::And(terms) if !negated => {
+                Self::conjunction_trigger(terms, prev_state, next_state, false)
+            }
+            Self::Or(terms) if negated => {
+                Self::conjunction_trigger(terms, prev_state, next_state, true)
+            }
+            Self::And(terms) | Self::Or(terms) => terms
+                .iter()
+                .find_map(|t| t.trigger(prev_state, next_state, negated)),
+            Self::Not(expr) => expr.trigger(prev_state, next_state, !negated),
+        }
+    }
+
+    fn conjunction_trigger<'e>(
+        terms: &'e [Self],
+        prev_state: &S,
+        next_state: &S,
+        negated: bool,
+    ) -> Option<(&'e Arc<dyn EventEvaluator<S>>, f64)> {
+        terms.iter().enumerate().find_map(|(i, term)| {
+            let trigger = term.trigger(prev_state, next_state, negated)?;
+            terms
+                .iter()
+                .enumerate()
+                .all(|(j, other)| i == j || other.holds_with(next_state, negated))
+                .then_some(trigger)
+        })
+    }
+
+    /// Returns all of the evaluators of this expression
+    fn leaves(&self) -> Vec<&Arc<dyn EventEvaluator<S>>> {
+        match self {
+            Self::Event(event) | Self::Condition(event) => vec![event],
+            Self::And(terms) | Self::Or(terms) => terms.iter().flat_map(|t| t.leaves()).collect(),
+            Self::Not(expr) => expr.leaves(),
+        }
+    }
+}
+
+impl<S: State> Clone for ConditionExpr<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::Event(event) => Self::Event(event.clone()),
+            Self::Condition(event) => Self::Condition(event.clone()),
+            Self::And(terms) => Self::And(terms.clone()),
+            Self::Or(terms) => Self::Or(terms.clone()),
+            Self::Not(expr) => Self::Not(expr.clone()),
+        }
+    }
+}
+
+impl<S: State> Not for ConditionExpr<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    type Output = Self;
+
+    fn not(self) -> Self {
+        match self {
+            Self::Not(expr) => *expr,
+            _ => Self::Not(Box::new(self)),
+        }
+    }
+}
+
+impl<S: State> fmt::Display for ConditionExpr<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, terms: &[Self], op: &str| -> fmt::Result {
+            write!(f, "(")?;
+            for (i, term) in terms.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {op} ")?;
+                }
+                write!(f, "{term}")?;
+            }
+            write!(f, ")")
+        };
+        match self {
+            Self::Event(event) => write!(f, "{event}"),
+            Self::Condition(event) => write!(f, "[{event}]"),
+            Self::And(terms) => join(f, terms, "AND"),
+            Self::Or(terms) => join(f, terms, "OR"),
+            Self::Not(expr) => write!(f, "NOT {expr}"),
+        }
+    }
+}
+
+impl<S: State> EventEvaluator<S> for ConditionExpr<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    fn eval_crossing(&self, prev_state: &S, next_state: &S) -> bool {
+        self.occurs(prev_state, next_state)
+    }
+
+    /// Evaluates the expression as the minimum of the terms of an `And` and the maximum of the terms of an `Or`, which is
+    /// positive when the expression holds (the events are considered as conditions here). Prefer `eval_bracketed` to refine an occurrence.
+    fn eval(&self, state: &S) -> f64 {
+        match self {
+            Self::Event(event) | Self::Condition(event) => {
+                event.eval(state) / event.value_precision().abs()
+            }
+            Self::And(terms) => terms
+                .iter()
+                .map(|t| t.eval(state))
+                .fold(f64::INFINITY, f64::min),
+            Self::Or(terms) => terms
+                .iter()
+                .map(|t| t.eval(state))
+                .fold(f64::NEG_INFINITY, f64::max),
+            Self::Not(expr) => -expr.eval(state),
+        }
+    }
+
+    /// Evaluates the leaf which makes this expression occur between both states, or a constant far from the value precision
+    /// (hence without any root) if it does not occur.
+    fn eval_bracketed(&self, prev_state: &S, next_state: &S, state: &S) -> f64 {
+        match self.trigger(prev_state, next_state, false) {
+            Some((event, sign)) => sign * event.eval(state) / event.value_precision().abs(),
+            None => 1e3,
+        }
+    }
+
+    fn eval_string(&self, state: &S) -> String {
+        self.leaves()
+            .iter()
+            .map(|event| event.eval_string(state))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The finest epoch precision of all of the evaluators
+    fn epoch_precision(&self) -> Duration {
+        self.leaves()
+            .iter()
+            .map(|event| event.epoch_precision())
+            .min()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Evaluations are scaled by the value precision of each evaluator
+    fn value_precision(&self) -> f64 {
+        1.0
+    }
+}
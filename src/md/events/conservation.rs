@@ -0,0 +1,182 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::EventEvaluator;
+use crate::cosmic::{Cosm, Frame, LightTimeCalc, Orbit};
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::DefaultAllocator;
+use crate::md::trajectory::Interpolatable;
+use crate::time::{Duration, Unit};
+use std::fmt;
+use std::sync::Arc;
+
+/// A quantity which is conserved by some dynamics, and whose drift during a propagation measures its numerical error
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+pub enum ConservedQuantity {
+    /// Specific orbital energy about the center of the integration frame, in km^2/s^2, conserved by two body dynamics
+    Energy,
+    /// Magnitude of the specific angular momentum about the center of the integration frame, in km^2/s, conserved by
+    /// central force dynamics
+    AngularMomentum,
+    /// Jacobi constant of the restricted three body problem of the primary and secondary bodies, in km^2/s^2.
+    ///
+    /// The rotating frame is built from the instantaneous relative state of both bodies from the ephemerides, so this is
+    /// only approximately conserved when their relative orbit is not circular.
+    Jacobi {
+        primary: Frame,
+        secondary: Frame,
+        cosm: Arc<Cosm>,
+    },
+}
+
+impl ConservedQuantity {
+    /// Initializes the Jacobi constant of the provided primary and secondary bodies, e.g. the Earth and the Moon
+    pub fn jacobi(primary: Frame, secondary: Frame, cosm: Arc<Cosm>) -> Self {
+        Self::Jacobi {
+            primary,
+            secondary,
+            cosm,
+        }
+    }
+
+    /// Evaluates this quantity for the provided orbit
+    pub fn evaluate(&self, orbit: &Orbit) -> Result<f64, NyxError> {
+        match self {
+            Self::Energy => Ok(orbit.energy_km2_s2()),
+            Self::AngularMomentum => Ok(orbit.hmag_km2_s()),
+            Self::Jacobi {
+                primary,
+                secondary,
+                cosm,
+            } => {
+                let orbit = cosm.try_frame_chg(orbit, *primary)?;
+                let second = cosm.try_celestial_state(
+                    &secondary.ephem_path(),
+                    orbit.epoch,
+                    *primary,
+                    LightTimeCalc::None,
+                )?;
+                let (r12, v12) = (second.radius(), second.velocity());
+                let mu = secondary.gm() / (primary.gm() + secondary.gm());
+                // Rate of rotation of the line between both bodies
+                let omega = r12.cross(&v12) / r12.norm_squared();
+                // State of the spacecraft about the barycenter
+                let r = orbit.radius() - mu * r12;
+                let v = orbit.velocity() - mu * v12;
+                let potential = primary.gm() / orbit.radius().norm()
+                    + secondary.gm() / (orbit.radius() - r12).norm();
+                let omega_cross_r = omega.cross(&r);
+                Ok(2.0 * potential + omega_cross_r.norm_squared()
+                    - (v - omega_cross_r).norm_squared())
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConservedQuantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Energy => write!(f, "energy"),
+            Self::AngularMomentum => write!(f, "angular momentum"),
+            Self::Jacobi {
+                primary, secondary, ..
+            } => write!(f, "{primary}-{secondary} Jacobi constant"),
+        }
+    }
+}
+
+/// An event triggered when the relative drift of a conserved quantity from its initial value exceeds a threshold.
+///
+/// The evaluation is the relative drift minus that threshold, so the event is found where the drift first exceeds it.
+#[derive(Clone)]
+pub struct ConservationDrift {
+    pub quantity: ConservedQuantity,
+    /// Value of the quantity at the start of the propagation
+    pub reference: f64,
+    /// Maximum relative drift of the quantity
+    pub max_drift: f64,
+}
+
+impl ConservationDrift {
+    /// Initializes the drift event of the provided quantity from its value for the initial orbit
+    pub fn new(
+        quantity: ConservedQuantity,
+        initial: &Orbit,
+        max_drift: f64,
+    ) -> Result<Self, NyxError> {
+        let reference = quantity.evaluate(initial)?;
+        if reference.abs() < f64::EPSILON {
+            return Err(NyxError::CustomError(format!(
+                "initial {quantity} is zero, its relative drift is undefined"
+            )));
+        }
+        Ok(Self {
+            quantity,
+            reference,
+            max_drift,
+        })
+    }
+
+    /// Returns the relative drift of the quantity for the provided orbit
+    pub fn drift(&self, orbit: &Orbit) -> Result<f64, NyxError> {
+        Ok(((self.quantity.evaluate(orbit)? - self.reference) / self.reference).abs())
+    }
+}
+
+impl fmt::Display for ConservationDrift {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} drift above {:e} (reference {})",
+            self.quantity, self.max_drift, self.reference
+        )
+    }
+}
+
+impl<S: Interpolatable> EventEvaluator<S> for ConservationDrift
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    fn eval(&self, state: &S) -> f64 {
+        match self.drift(state.orbit()) {
+            Ok(drift) => drift - self.max_drift,
+            Err(e) => {
+                error!("could not evaluate {}: {e}", self.quantity);
+                f64::NAN
+            }
+        }
+    }
+
+    fn eval_string(&self, state: &S) -> String {
+        match self.drift(state.orbit()) {
+            Ok(drift) => format!("{} drift = {drift:e}", self.quantity),
+            Err(e) => format!("{} drift unavailable: {e}", self.quantity),
+        }
+    }
+
+    fn epoch_precision(&self) -> Duration {
+        Unit::Second * 1.0
+    }
+
+    fn value_precision(&self) -> f64 {
+        self.max_drift * 1e-3
+    }
+}
@@ -16,13 +16,19 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+mod condition;
+mod conservation;
 pub mod evaluators;
+mod named;
 use super::StateParameter;
 use crate::cosmic::{Cosm, Frame};
 use crate::linalg::allocator::Allocator;
 use crate::linalg::DefaultAllocator;
-use crate::time::{Duration, Unit};
+use crate::time::{Duration, Epoch, Unit};
 use crate::State;
+pub use condition::ConditionExpr;
+pub use conservation::{ConservationDrift, ConservedQuantity};
+pub use named::NamedEvent;
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 use std::default::Default;
@@ -42,6 +48,12 @@ where
 
     /// Evaluation of the event, must return a value corresponding to whether the state is before or after the event
     fn eval(&self, state: &S) -> f64;
+
+    /// Evaluation of the event at a state between two states which bracket its crossing, used to refine that crossing.
+    /// Defaults to `eval`, but the evaluation of a composed event depends on which of its terms crossed.
+    fn eval_bracketed(&self, _prev_state: &S, _next_state: &S, state: &S) -> f64 {
+        self.eval(state)
+    }
     /// Returns a string representation of the event evaluation for the given state
     fn eval_string(&self, state: &S) -> String;
     fn epoch_precision(&self) -> Duration;
@@ -173,3 +185,49 @@ impl Default for Event {
         }
     }
 }
+
+/// An arc during which an event evaluates positive, e.g. a pass of the spacecraft above the desired value of a parameter,
+/// bounded by its rise and fall states (in chronological order).
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventArc<S: State>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    /// State where the event becomes positive, or the start of the search window if it is positive there
+    pub rise: S,
+    /// State where the event becomes negative, or the end of the search window if it is positive there
+    pub fall: S,
+}
+
+impl<S: State> EventArc<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    /// Duration of this arc
+    pub fn duration(&self) -> Duration {
+        self.fall.epoch() - self.rise.epoch()
+    }
+
+    /// Returns whether the provided epoch is within this arc (bounds included)
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        self.rise.epoch() <= epoch && epoch <= self.fall.epoch()
+    }
+}
+
+impl<S: State> fmt::Display for EventArc<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} to {} ({})",
+            self.rise.epoch(),
+            self.fall.epoch(),
+            self.duration()
+        )
+    }
+}
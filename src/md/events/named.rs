@@ -0,0 +1,103 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::EventEvaluator;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::DefaultAllocator;
+use crate::md::NamedParameter;
+use crate::time::{Duration, Unit};
+use crate::State;
+use std::fmt;
+
+/// An event on a named parameter of a user-defined state, e.g. a component of the state of custom dynamics, reaching
+/// the desired value.
+#[derive(Clone, Debug)]
+pub struct NamedEvent {
+    /// The named parameter
+    pub parameter: NamedParameter,
+    /// The desired value, in the unit of this parameter
+    pub desired_value: f64,
+    /// The time precision after which the solver will report that it cannot find any more precise
+    pub epoch_precision: Unit,
+    /// The precision on the desired value
+    pub value_precision: f64,
+}
+
+impl NamedEvent {
+    /// Match the named parameter hitting the specified value, to within 1e-3 of its unit and 1 millisecond
+    pub fn new<P: Into<NamedParameter>>(parameter: P, desired_value: f64) -> Self {
+        Self::specific(parameter, desired_value, 1e-3, Unit::Millisecond)
+    }
+
+    /// Match the named parameter hitting the specified value with the provided tolerance on the value and time
+    pub fn specific<P: Into<NamedParameter>>(
+        parameter: P,
+        desired_value: f64,
+        value_precision: f64,
+        epoch_precision: Unit,
+    ) -> Self {
+        Self {
+            parameter: parameter.into(),
+            desired_value,
+            epoch_precision,
+            value_precision,
+        }
+    }
+}
+
+impl fmt::Display for NamedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} = {} (± {})",
+            self.parameter, self.desired_value, self.value_precision
+        )
+    }
+}
+
+impl<S: State> EventEvaluator<S> for NamedEvent
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    fn eval(&self, state: &S) -> f64 {
+        match state.named_value(&self.parameter) {
+            Ok(value) => value - self.desired_value,
+            Err(e) => {
+                error!("could not evaluate {}: {e}", self.parameter);
+                f64::NAN
+            }
+        }
+    }
+
+    fn eval_string(&self, state: &S) -> String {
+        match state.named_value(&self.parameter) {
+            Ok(value) => format!("{} = {value:.3}", self.parameter),
+            Err(e) => format!("{} unavailable: {e}", self.parameter),
+        }
+    }
+
+    #[allow(clippy::identity_op)]
+    fn epoch_precision(&self) -> Duration {
+        1 * self.epoch_precision
+    }
+
+    fn value_precision(&self) -> f64 {
+        self.value_precision
+    }
+}
@@ -0,0 +1,281 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Cosm, Frame};
+use crate::dynamics::SpacecraftDynamics;
+use crate::errors::NyxError;
+use crate::linalg::Vector3;
+use crate::md::objective::Objective;
+use crate::md::opti::solution::TargeterSolution;
+use crate::md::optimizer::Optimizer;
+use crate::md::{Event, ScTraj, StateParameter};
+use crate::propagators::{ErrorCtrl, Propagator};
+use crate::time::{Duration, Epoch, Unit};
+use crate::{Spacecraft, State};
+use std::fmt;
+use std::sync::Arc;
+
+/// Defines the acceptable periapsis radii of the return to a body (e.g. the Earth entry corridor of a cislunar mission).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ReturnCorridor {
+    /// Frame centered on the return body, in which the periapsis is computed
+    pub frame: Frame,
+    /// Minimum acceptable radius of periapsis (km)
+    pub min_periapsis_km: f64,
+    /// Maximum acceptable radius of periapsis (km)
+    pub max_periapsis_km: f64,
+}
+
+impl ReturnCorridor {
+    /// Initializes a new corridor from the minimum and maximum periapsis altitudes above the equatorial radius of the frame.
+    pub fn from_altitudes(frame: Frame, min_alt_km: f64, max_alt_km: f64) -> Self {
+        Self {
+            frame,
+            min_periapsis_km: frame.equatorial_radius() + min_alt_km,
+            max_periapsis_km: frame.equatorial_radius() + max_alt_km,
+        }
+    }
+
+    /// Returns whether the provided periapsis radius is within the corridor
+    pub fn contains(&self, rp_km: f64) -> bool {
+        (self.min_periapsis_km..=self.max_periapsis_km).contains(&rp_km)
+    }
+
+    /// Periapsis radius at the center of the corridor, used as the target of abort maneuvers
+    pub fn center_km(&self) -> f64 {
+        0.5 * (self.min_periapsis_km + self.max_periapsis_km)
+    }
+
+    /// Half of the width of the corridor
+    pub fn half_width_km(&self) -> f64 {
+        0.5 * (self.max_periapsis_km - self.min_periapsis_km)
+    }
+}
+
+impl fmt::Display for ReturnCorridor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "return corridor in {}: rp ∈ [{:.3}; {:.3}] km",
+            self.frame, self.min_periapsis_km, self.max_periapsis_km
+        )
+    }
+}
+
+/// Result of the free-return check of a trajectory
+#[derive(Clone, Debug)]
+pub struct FreeReturnCheck {
+    /// State at the return periapsis, in the frame of the corridor, if a return was found within the search duration
+    pub return_state: Option<Spacecraft>,
+    /// Whether the return periapsis is within the corridor without any further maneuver
+    pub is_free_return: bool,
+}
+
+impl FreeReturnCheck {
+    /// Returns the radius of periapsis at return, if any
+    pub fn return_periapsis_km(&self) -> Option<f64> {
+        self.return_state.map(|sc| sc.orbit.periapsis_km())
+    }
+}
+
+impl fmt::Display for FreeReturnCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.return_state {
+            Some(sc) => write!(
+                f,
+                "{} free return: rp = {:.3} km @ {}",
+                if self.is_free_return {
+                    "valid"
+                } else {
+                    "invalid"
+                },
+                sc.orbit.periapsis_km(),
+                sc.epoch()
+            ),
+            None => write!(f, "invalid free return: no return found"),
+        }
+    }
+}
+
+/// An impulsive abort maneuver which restores the free return
+#[derive(Clone, Debug)]
+pub struct AbortManeuver {
+    /// Epoch of the maneuver
+    pub epoch: Epoch,
+    /// Impulsive delta-v in the integration frame (km/s)
+    pub dv_km_s: Vector3<f64>,
+    /// The targeter solution of this abort
+    pub solution: TargeterSolution<3, 1>,
+}
+
+impl AbortManeuver {
+    /// Magnitude of the abort maneuver in km/s
+    pub fn dv_mag_km_s(&self) -> f64 {
+        self.dv_km_s.norm()
+    }
+}
+
+impl fmt::Display for AbortManeuver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "abort @ {}: Δv = {:.3} m/s",
+            self.epoch,
+            self.dv_mag_km_s() * 1e3
+        )
+    }
+}
+
+/// Free-return and abort analysis of a trajectory.
+///
+/// The return is the first periapsis with respect to the corridor frame after the trajectory has started heading back,
+/// i.e. after the first apoapsis (or the first periapsis if the trajectory is already inbound).
+/// Abort maneuvers are computed with the finite differencing targeter by targeting the center of the corridor at the nominal
+/// return epoch: with a single objective and three velocity components, each correction is the minimum norm one.
+pub struct FreeReturnAnalysis<'a, E: ErrorCtrl> {
+    /// Propagator of the spacecraft dynamics
+    pub prop: &'a Propagator<'a, SpacecraftDynamics, E>,
+    /// The acceptable return corridor
+    pub corridor: ReturnCorridor,
+    /// Maximum duration from the analyzed state to search for the return
+    pub max_return_duration: Duration,
+    /// Cosm used to convert the trajectory into the frame of the corridor
+    pub cosm: Arc<Cosm>,
+}
+
+impl<'a, E: ErrorCtrl> FreeReturnAnalysis<'a, E> {
+    /// Initializes a new free-return analysis
+    pub fn new(
+        prop: &'a Propagator<'a, SpacecraftDynamics, E>,
+        corridor: ReturnCorridor,
+        max_return_duration: Duration,
+        cosm: Arc<Cosm>,
+    ) -> Self {
+        Self {
+            prop,
+            corridor,
+            max_return_duration,
+            cosm,
+        }
+    }
+
+    /// Finds the return periapsis of the provided trajectory, returned in the frame of the corridor.
+    pub fn find_return(&self, traj: &ScTraj) -> Result<Option<Spacecraft>, NyxError> {
+        let traj = traj.to_frame(self.corridor.frame, self.cosm.clone())?;
+
+        // The trajectory is inbound after its first apoapsis, if any.
+        let inbound_epoch = match traj.find_all(&Event::apoapsis()) {
+            Ok(apoapses) => apoapses[0].epoch(),
+            Err(_) => traj.first().epoch(),
+        };
+
+        match traj.find_all(&Event::periapsis()) {
+            Ok(periapses) => Ok(periapses.into_iter().find(|sc| sc.epoch() >= inbound_epoch)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Checks whether the provided state is on a free return, i.e. returns within the corridor without any maneuver.
+    pub fn check(&self, state: Spacecraft) -> Result<FreeReturnCheck, NyxError> {
+        let (_, traj) = self
+            .prop
+            .with(state)
+            .for_duration_with_traj(self.max_return_duration)?;
+
+        let return_state = self.find_return(&traj)?;
+
+        let is_free_return = match return_state {
+            Some(sc) => self.corridor.contains(sc.orbit.periapsis_km()),
+            None => false,
+        };
+
+        let check = FreeReturnCheck {
+            return_state,
+            is_free_return,
+        };
+        info!("{check}");
+
+        Ok(check)
+    }
+
+    /// Computes the abort maneuver at each of the candidate epochs which brings the return periapsis into the corridor.
+    /// Candidate epochs at which the targeter fails to converge are skipped (and reported as warnings), hence the
+    /// returned maneuvers are sorted by epoch but may not correspond to every candidate epoch.
+    pub fn abort_maneuvers(
+        &self,
+        state: Spacecraft,
+        candidate_epochs: &[Epoch],
+    ) -> Result<Vec<AbortManeuver>, NyxError> {
+        let (_, traj) = self
+            .prop
+            .with(state)
+            .for_duration_with_traj(self.max_return_duration)?;
+
+        // Target the nominal return if there is one, else the closest approach to the return body.
+        let return_epoch = match self.find_return(&traj)? {
+            Some(sc) => sc.epoch(),
+            None => {
+                let rmag = Event::new(StateParameter::Rmag, 0.0);
+                traj.to_frame(self.corridor.frame, self.cosm.clone())?
+                    .find_minmax(&rmag, Unit::Minute)?
+                    .0
+                    .epoch()
+            }
+        };
+
+        let objectives = [Objective::within_tolerance(
+            StateParameter::PeriapsisRadius,
+            self.corridor.center_km(),
+            self.corridor.half_width_km(),
+        )];
+
+        let mut tgt = Optimizer::delta_v(self.prop, objectives);
+        if self.corridor.frame != state.orbit.frame {
+            tgt.objective_frame = Some((self.corridor.frame, self.cosm.clone()));
+        }
+
+        let mut maneuvers = Vec::with_capacity(candidate_epochs.len());
+
+        let mut epochs = candidate_epochs.to_vec();
+        epochs.sort();
+
+        for epoch in epochs {
+            if epoch < state.epoch() || epoch >= return_epoch {
+                warn!("abort epoch {epoch} is not between {} and the return at {return_epoch}: skipped", state.epoch());
+                continue;
+            }
+
+            match tgt.try_achieve_from(state, epoch, return_epoch) {
+                Ok(solution) => {
+                    let dv_km_s = solution.corrected_state.orbit.velocity()
+                        - traj.at(epoch)?.orbit.velocity();
+                    let mnvr = AbortManeuver {
+                        epoch,
+                        dv_km_s,
+                        solution,
+                    };
+                    info!("{mnvr}");
+                    maneuvers.push(mnvr);
+                }
+                Err(e) => warn!("no abort found @ {epoch}: {e}"),
+            }
+        }
+
+        Ok(maneuvers)
+    }
+}
@@ -0,0 +1,517 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::events::EventEvaluator;
+use super::station_keeping::EARTH_ROTATION_RAD_S;
+use super::trajectory::Traj;
+use crate::cosmic::{Cosm, Frame, Orbit, Spacecraft};
+use crate::dynamics::{OrbitalDynamics, SpacecraftDynamics};
+use crate::errors::NyxError;
+use crate::linalg::{Matrix3, Vector3};
+use crate::propagators::Propagator;
+use crate::time::{Duration, Epoch, Unit};
+use crate::State;
+use std::f64::consts::TAU;
+use std::fmt;
+use std::sync::Arc;
+
+/// Fraction of the threshold at which a maintenance maneuver is triggered, as a margin for the drift until the maneuver
+const TRIGGER_RATIO: f64 = 0.8;
+/// Fraction of the threshold on the opposite side targeted by the apex of the drift after a maintenance maneuver
+const TARGET_RATIO: f64 = 0.8;
+
+/// Two body semi-major axis of an orbit about the Earth whose ground track repeats after the provided number of revolutions
+/// in the provided number of sidereal days, in km. The nodal period of a perturbed orbit differs (e.g. due to J2), so the
+/// semi-major axis of the reference orbit should then be adjusted such that its ground track closes.
+pub fn repeat_sma_km(revolutions: u32, sidereal_days: u32, gm_km3_s2: f64) -> f64 {
+    let period_s = f64::from(sidereal_days) * TAU / EARTH_ROTATION_RAD_S / f64::from(revolutions);
+    (gm_km3_s2 * (period_s / TAU).powi(2)).cbrt()
+}
+
+/// The reference ground track of a repeat ground track orbit, flown once over its repeat cycle.
+#[derive(Clone)]
+pub struct ReferenceGroundTrack {
+    /// Reference trajectory, which covers at least one repeat cycle from its first state
+    pub traj: Traj<Orbit>,
+    /// Duration after which the ground track repeats
+    pub repeat_cycle: Duration,
+    /// Body fixed frame of the ground track
+    pub body_fixed: Frame,
+    pub cosm: Arc<Cosm>,
+}
+
+impl ReferenceGroundTrack {
+    pub fn new(
+        traj: Traj<Orbit>,
+        repeat_cycle: Duration,
+        body_fixed: Frame,
+        cosm: Arc<Cosm>,
+    ) -> Result<Self, NyxError> {
+        if traj.last().epoch - traj.first().epoch < repeat_cycle {
+            return Err(NyxError::CustomError(format!(
+                "reference trajectory of {} does not cover the repeat cycle of {repeat_cycle}",
+                traj.last().epoch - traj.first().epoch
+            )));
+        }
+        Ok(Self {
+            traj,
+            repeat_cycle,
+            body_fixed,
+            cosm,
+        })
+    }
+
+    /// Builds the reference ground track about the Earth by propagating the repeat orbit over one repeat cycle, with dynamics
+    /// which exclude the perturbations compensated by the maintenance (e.g. drag).
+    pub fn from_orbit(
+        orbit: Orbit,
+        repeat_cycle: Duration,
+        dynamics: OrbitalDynamics,
+        cosm: Arc<Cosm>,
+    ) -> Result<Self, NyxError> {
+        let (_, traj) = Propagator::default(dynamics)
+            .with(orbit)
+            .for_duration_with_traj(repeat_cycle)?;
+        Self::new(traj, repeat_cycle, cosm.frame("IAU Earth"), cosm)
+    }
+
+    /// Returns the epoch of the reference trajectory which is in the same phase of the repeat cycle as the provided epoch
+    pub fn reference_epoch(&self, epoch: Epoch) -> Epoch {
+        let start = self.traj.first().epoch;
+        let cycles = ((epoch - start).to_seconds() / self.repeat_cycle.to_seconds()).floor();
+        (epoch - cycles * self.repeat_cycle).clamp(start, start + self.repeat_cycle)
+    }
+
+    /// Returns the cross-track deviation of the ground track of the provided orbit from the reference ground track, in km.
+    ///
+    /// The subsatellite point is projected on the reference ground track, i.e. on the reference point of the closest phase of
+    /// the repeat cycle, such that along track errors (e.g. a late pass) do not contribute. The deviation is the arc on the
+    /// equatorial radius perpendicular to the track, positive to the left of the reference track.
+    pub fn cross_track_km(&self, orbit: &Orbit) -> Result<f64, NyxError> {
+        let r_hat = self
+            .cosm
+            .frame_chg(orbit, self.body_fixed)
+            .radius()
+            .normalize();
+        let mut epoch = self.reference_epoch(orbit.epoch);
+        let mut cross_rad = 0.0;
+        for _ in 0..5 {
+            let reference = self.cosm.frame_chg(&self.traj.at(epoch)?, self.body_fixed);
+            let ref_r_hat = reference.radius().normalize();
+            let h_hat = ref_r_hat.cross(&reference.velocity()).normalize();
+            let track_hat = h_hat.cross(&ref_r_hat);
+            cross_rad = r_hat.dot(&h_hat).asin();
+            // Move the reference point along its track by the along track angle of the subsatellite point
+            let along_rad = r_hat.dot(&track_hat).atan2(r_hat.dot(&ref_r_hat));
+            let rate_rad_s = reference.velocity().dot(&track_hat) / reference.rmag_km();
+            let dt_s = along_rad / rate_rad_s;
+            if dt_s.abs() < 1e-3 {
+                break;
+            }
+            epoch = self.reference_epoch(epoch + dt_s * Unit::Second);
+        }
+        Ok(self.body_fixed.equatorial_radius() * cross_rad)
+    }
+}
+
+/// An event on the cross-track deviation of the ground track from a reference exceeding a threshold.
+///
+/// It evaluates to the magnitude of the deviation minus the threshold, and only its crossings from within the threshold to
+/// beyond it are events. The deviation of a shifted ground track is largest near the equator, so it usually exceeds the
+/// threshold once per revolution until the ground track is maintained.
+#[derive(Clone)]
+pub struct CrossTrackEvent {
+    pub reference: Arc<ReferenceGroundTrack>,
+    /// Maximum cross-track deviation, in km
+    pub threshold_km: f64,
+    pub epoch_precision: Duration,
+    /// Precision of the deviation, in km
+    pub value_precision_km: f64,
+}
+
+impl CrossTrackEvent {
+    /// Initializes the event with a precision of one millisecond and one meter
+    pub fn new(reference: Arc<ReferenceGroundTrack>, threshold_km: f64) -> Self {
+        Self {
+            reference,
+            threshold_km,
+            epoch_precision: 1 * Unit::Millisecond,
+            value_precision_km: 1e-3,
+        }
+    }
+}
+
+impl fmt::Display for CrossTrackEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cross-track deviation > {} km", self.threshold_km)
+    }
+}
+
+impl EventEvaluator<Orbit> for CrossTrackEvent {
+    fn eval_crossing(&self, prev_state: &Orbit, next_state: &Orbit) -> bool {
+        self.eval(prev_state) <= 0.0 && self.eval(next_state) > 0.0
+    }
+
+    /// Returns NaN if the deviation cannot be computed, e.g. if the reference trajectory cannot be interpolated
+    fn eval(&self, state: &Orbit) -> f64 {
+        match self.reference.cross_track_km(state) {
+            Ok(cross_km) => cross_km.abs() - self.threshold_km,
+            Err(e) => {
+                warn!("could not compute the cross-track deviation: {e}");
+                f64::NAN
+            }
+        }
+    }
+
+    fn eval_string(&self, state: &Orbit) -> String {
+        match self.reference.cross_track_km(state) {
+            Ok(cross_km) => format!("cross-track deviation = {cross_km:.3} km"),
+            Err(e) => format!("cross-track deviation unavailable: {e}"),
+        }
+    }
+
+    fn epoch_precision(&self) -> Duration {
+        self.epoch_precision
+    }
+
+    fn value_precision(&self) -> f64 {
+        self.value_precision_km
+    }
+}
+
+impl EventEvaluator<Spacecraft> for CrossTrackEvent {
+    fn eval_crossing(&self, prev_state: &Spacecraft, next_state: &Spacecraft) -> bool {
+        self.eval_crossing(&prev_state.orbit, &next_state.orbit)
+    }
+
+    fn eval(&self, state: &Spacecraft) -> f64 {
+        self.eval(&state.orbit)
+    }
+
+    fn eval_string(&self, state: &Spacecraft) -> String {
+        self.eval_string(&state.orbit)
+    }
+
+    fn epoch_precision(&self) -> Duration {
+        self.epoch_precision
+    }
+
+    fn value_precision(&self) -> f64 {
+        self.value_precision_km
+    }
+}
+
+/// An impulsive ground track maintenance maneuver along the velocity
+#[derive(Copy, Clone, Debug)]
+pub struct GroundTrackManeuver {
+    pub epoch: Epoch,
+    /// Signed magnitude along the velocity, in km/s
+    pub dv_km_s: f64,
+    /// Delta-V in the inertial frame of the orbit, in km/s
+    pub dv_inertial_km_s: Vector3<f64>,
+}
+
+impl fmt::Display for GroundTrackManeuver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ground track maneuver of {:.4} m/s",
+            self.epoch,
+            self.dv_km_s * 1e3
+        )
+    }
+}
+
+/// The ground track maintenance maneuvers of a spacecraft and the controlled trajectory flown with them.
+#[derive(Clone)]
+pub struct GroundTrackPlan {
+    /// Maximum cross-track deviation, in km
+    pub threshold_km: f64,
+    pub maneuvers: Vec<GroundTrackManeuver>,
+    /// Controlled trajectory, which includes the post-maneuver states
+    pub traj: Traj<Spacecraft>,
+    /// Largest cross-track deviation over the controlled trajectory, sampled every minute, in km
+    pub max_cross_track_km: f64,
+}
+
+impl GroundTrackPlan {
+    /// Duration of the plan
+    pub fn duration(&self) -> Duration {
+        self.traj.last().epoch() - self.traj.first().epoch()
+    }
+
+    /// Total delta-V of the maneuvers, in km/s
+    pub fn dv_km_s(&self) -> f64 {
+        self.maneuvers.iter().map(|mnvr| mnvr.dv_km_s.abs()).sum()
+    }
+
+    /// Returns whether the controlled ground track remained within the threshold of the reference
+    pub fn within_threshold(&self) -> bool {
+        self.max_cross_track_km <= self.threshold_km
+    }
+}
+
+impl fmt::Display for GroundTrackPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Ground track within {} km over {} ({}): {} maneuvers, total of {:.3} m/s",
+            self.threshold_km,
+            self.duration(),
+            if self.within_threshold() {
+                "maintained"
+            } else {
+                "NOT maintained"
+            },
+            self.maneuvers.len(),
+            self.dv_km_s() * 1e3
+        )?;
+        writeln!(
+            f,
+            "max cross-track deviation = {:.3} km",
+            self.max_cross_track_km
+        )?;
+        for mnvr in &self.maneuvers {
+            writeln!(f, "{mnvr}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Plans the maintenance of a repeat ground track against the decay of the orbit, e.g. for imaging constellations which must
+/// keep their track grid.
+///
+/// The decay of the semi-major axis shortens the nodal period, so the ground track drifts with a constant acceleration. The
+/// deviation from the reference is monitored at each ascending node: once it exceeds 80 % of the threshold, a parabola is fit
+/// to the deviations at the ascending nodes since the previous maintenance to estimate the drift rate and acceleration. A pair
+/// of tangential maneuvers half a revolution apart, which leaves the eccentricity unchanged, then changes the semi-major axis
+/// such that the ground track drifts back and reaches its apex at 80 % of the threshold on the opposite side of the reference.
+///
+/// The maneuvers are flown with the dynamics, so the resulting plan is validated on the controlled trajectory.
+///
+/// **Note:** the orbits must be in an Earth centered inertial frame whose XY plane is the equator, e.g. EME2000.
+#[derive(Clone)]
+pub struct GroundTrackKeeping {
+    pub reference: Arc<ReferenceGroundTrack>,
+    /// Maximum cross-track deviation, in km
+    pub threshold_km: f64,
+    pub dynamics: SpacecraftDynamics,
+}
+
+impl GroundTrackKeeping {
+    pub fn new(
+        reference: Arc<ReferenceGroundTrack>,
+        threshold_km: f64,
+        dynamics: SpacecraftDynamics,
+    ) -> Self {
+        Self {
+            reference,
+            threshold_km,
+            dynamics,
+        }
+    }
+
+    /// Returns the event of the cross-track deviation exceeding the threshold of this planner
+    pub fn event(&self) -> CrossTrackEvent {
+        CrossTrackEvent::new(self.reference.clone(), self.threshold_km)
+    }
+
+    /// Plans and flies the ground track maintenance maneuvers from the initial state for the provided duration
+    pub fn plan(
+        &self,
+        initial: Spacecraft,
+        duration: Duration,
+    ) -> Result<GroundTrackPlan, NyxError> {
+        let end = initial.epoch() + duration;
+        let mut state = initial;
+        let mut traj = Traj::new();
+        traj.states.push(initial);
+        let mut maneuvers = Vec::new();
+
+        // Deviations at the ascending nodes since the latest maintenance, and previous sample of the deviation
+        let mut nodes = Vec::new();
+        let mut prev: Option<(Orbit, f64)> = None;
+        let mut armed = true;
+
+        while state.epoch() < end {
+            let chunk_end = (state.epoch() + 1 * Unit::Day).min(end);
+            let (next, segment) = Propagator::default(self.dynamics.clone())
+                .with(state)
+                .until_epoch_with_traj(chunk_end)?;
+
+            let mut first_burn = None;
+            for sc in segment.every(1 * Unit::Minute) {
+                let cross_km = self.reference.cross_track_km(&sc.orbit)?;
+                if let Some((prev_orbit, prev_cross_km)) = prev {
+                    if prev_orbit.z_km < 0.0 && sc.orbit.z_km >= 0.0 {
+                        // Ascending node, interpolated linearly between the samples
+                        let frac = -prev_orbit.z_km / (sc.orbit.z_km - prev_orbit.z_km);
+                        let node_epoch =
+                            prev_orbit.epoch + frac * (sc.orbit.epoch - prev_orbit.epoch);
+                        let node_cross_km = prev_cross_km + frac * (cross_km - prev_cross_km);
+                        nodes.push((node_epoch, node_cross_km));
+                        if node_cross_km.abs() < TRIGGER_RATIO * self.threshold_km {
+                            armed = true;
+                        } else if armed {
+                            first_burn = Some(node_epoch);
+                            break;
+                        }
+                    }
+                }
+                prev = Some((sc.orbit, cross_km));
+            }
+
+            let burns = first_burn.and_then(|first| {
+                let second = first + 0.5 * state.orbit.period();
+                (second < end).then_some((first, second))
+            });
+            match burns {
+                Some((first, second)) => {
+                    state = self.fly(state, first, &mut traj)?;
+                    let dv_km_s = 0.5 * self.maintenance_dv_km_s(&nodes, &state.orbit, second)?;
+                    for epoch in [first, second] {
+                        state = self.fly(state, epoch, &mut traj)?;
+                        let mnvr = GroundTrackManeuver {
+                            epoch,
+                            dv_km_s,
+                            dv_inertial_km_s: state.orbit.velocity() / state.orbit.vmag_km_s()
+                                * dv_km_s,
+                        };
+                        debug!("{mnvr}");
+                        state.orbit.apply_dv(mnvr.dv_inertial_km_s);
+                        maneuvers.push(mnvr);
+                    }
+                    // The drift is estimated anew from the nodes after the maintenance
+                    nodes.clear();
+                    prev = None;
+                    armed = false;
+                }
+                None => {
+                    append(&mut traj, &state, segment);
+                    state = next;
+                }
+            }
+        }
+
+        let mut max_cross_track_km: f64 = 0.0;
+        for sc in traj.every(1 * Unit::Minute) {
+            max_cross_track_km =
+                max_cross_track_km.max(self.reference.cross_track_km(&sc.orbit)?.abs());
+        }
+
+        let plan = GroundTrackPlan {
+            threshold_km: self.threshold_km,
+            maneuvers,
+            traj,
+            max_cross_track_km,
+        };
+        info!("{plan}");
+        Ok(plan)
+    }
+
+    /// Propagates the state until the provided epoch, and appends the states to the controlled trajectory
+    fn fly(
+        &self,
+        state: Spacecraft,
+        epoch: Epoch,
+        traj: &mut Traj<Spacecraft>,
+    ) -> Result<Spacecraft, NyxError> {
+        if epoch <= state.epoch() {
+            return Ok(state);
+        }
+        let (next, segment) = Propagator::default(self.dynamics.clone())
+            .with(state)
+            .until_epoch_with_traj(epoch)?;
+        append(traj, &state, segment);
+        Ok(next)
+    }
+
+    /// Returns the total tangential delta-V of the maintenance whose maneuvers are centered around the provided epoch, in km/s,
+    /// from the deviations at the ascending nodes since the previous maintenance.
+    fn maintenance_dv_km_s(
+        &self,
+        nodes: &[(Epoch, f64)],
+        orbit: &Orbit,
+        second: Epoch,
+    ) -> Result<f64, NyxError> {
+        if nodes.len() < 3 {
+            return Err(NyxError::CustomError(format!(
+                "only {} ascending nodes since the previous maintenance, cannot estimate the drift of the ground track",
+                nodes.len()
+            )));
+        }
+        // Least squares fit of a parabola, in days since the middle of the maneuvers
+        let mid = orbit.epoch + 0.5 * (second - orbit.epoch);
+        let mut info = Matrix3::zeros();
+        let mut rhs = Vector3::zeros();
+        for (epoch, cross_km) in nodes {
+            let t_day = (*epoch - mid).to_unit(Unit::Day);
+            let row = Vector3::new(1.0, t_day, t_day.powi(2));
+            info += row * row.transpose();
+            rhs += row * *cross_km;
+        }
+        let coeffs = info.try_inverse().ok_or_else(|| {
+            NyxError::CustomError("singular fit of the ground track drift".to_string())
+        })? * rhs;
+        let (cross_km, rate_km_day, accel_km_day2) = (coeffs[0], coeffs[1], 2.0 * coeffs[2]);
+
+        // Drift rate such that the apex of the parabola is on the opposite side, or only stop the drift if it already returns
+        let target_rate_km_day = if accel_km_day2 * cross_km > 0.0 {
+            -cross_km.signum()
+                * (2.0 * accel_km_day2.abs() * (cross_km.abs() + TARGET_RATIO * self.threshold_km))
+                    .sqrt()
+        } else {
+            0.0
+        };
+
+        // A change of the semi-major axis changes the nodal period by 3/2 Δa / a, so the Earth rotates by 3/2 ω Δa / a more
+        // per unit time under the ascending node. The cross-track deviation of a shift in longitude depends on the angle of the
+        // ground track with the equator, from the northward and eastward components of the velocity relative to the Earth.
+        let sma_km = orbit.sma_km();
+        let inc_rad = orbit.inc_deg().to_radians();
+        let v_km_s = orbit.vmag_km_s();
+        let north_km_s = v_km_s * inc_rad.sin();
+        let east_km_s = v_km_s * inc_rad.cos() - EARTH_ROTATION_RAD_S * orbit.rmag_km();
+        let rate_per_sma_day = 1.5
+            * EARTH_ROTATION_RAD_S
+            * Unit::Day.in_seconds()
+            * self.reference.body_fixed.equatorial_radius()
+            * north_km_s
+            / north_km_s.hypot(east_km_s)
+            / sma_km;
+        let delta_sma_km = (target_rate_km_day - rate_km_day) / rate_per_sma_day;
+        debug!(
+            "ground track drift: {cross_km:.3} km, {rate_km_day:.3} km/day, {accel_km_day2:.3} km/day^2 => Δa = {:.3} m",
+            delta_sma_km * 1e3
+        );
+        // Tangential delta-V of the change of semi-major axis, from the vis-viva equation
+        Ok(orbit.frame.gm() * delta_sma_km / (2.0 * sma_km.powi(2) * v_km_s))
+    }
+}
+
+/// Appends the segment flown from the provided state to the trajectory, replacing the state at its start (e.g. pre-maneuver)
+fn append(traj: &mut Traj<Spacecraft>, state: &Spacecraft, segment: Traj<Spacecraft>) {
+    if traj
+        .states
+        .last()
+        .is_some_and(|last| last.epoch() == state.epoch())
+    {
+        traj.states.pop();
+    }
+    traj.states.extend(segment.states);
+}
@@ -0,0 +1,470 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Frame, Orbit};
+use crate::dynamics::guidance::Mnvr;
+use crate::dynamics::SpacecraftDynamics;
+use crate::errors::NyxError;
+use crate::linalg::Vector3;
+use crate::md::optimizer::Optimizer;
+use crate::propagators::{ErrorCtrl, Propagator};
+use crate::time::{Duration, Epoch, Unit};
+use crate::utils::rotv;
+use crate::Spacecraft;
+use std::f64::consts::TAU;
+use std::fmt;
+
+/// Tolerance of the golden section search of the split of a combined plane change, on the fraction of the inclination change
+const SPLIT_TOLERANCE: f64 = 1e-9;
+
+/// An impulsive maneuver computed analytically, with its delta-v in the inertial frame of the orbit.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ImpulsiveMnvr {
+    /// Epoch of the maneuver
+    pub epoch: Epoch,
+    /// Delta-v in the inertial frame of the orbit (km/s)
+    pub dv_km_s: Vector3<f64>,
+}
+
+impl ImpulsiveMnvr {
+    /// Magnitude of the delta-v (km/s)
+    pub fn dv_mag_km_s(&self) -> f64 {
+        self.dv_km_s.norm()
+    }
+
+    /// Returns this maneuver as an inertial impulsive `Mnvr`
+    pub fn to_mnvr(&self) -> Mnvr {
+        Mnvr::from_impulsive(self.epoch, self.dv_km_s, Frame::Inertial)
+    }
+
+    /// Converts this impulsive maneuver into a finite burn of the thruster of the spacecraft, which must be the state right before the maneuver.
+    pub fn to_finite_burn<'a, E: ErrorCtrl>(
+        &self,
+        spacecraft: Spacecraft,
+        prop: &'a Propagator<'a, SpacecraftDynamics, E>,
+    ) -> Result<Mnvr, NyxError> {
+        if spacecraft.orbit.epoch != self.epoch {
+            return Err(NyxError::CustomError(format!(
+                "spacecraft is at {} but maneuver is at {}",
+                spacecraft.orbit.epoch, self.epoch
+            )));
+        }
+        Optimizer::convert_impulsive_mnvr(spacecraft, self.dv_km_s, prop)
+    }
+}
+
+impl fmt::Display for ImpulsiveMnvr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Δv = {:.6} km/s @ {} [{:.6}, {:.6}, {:.6}]",
+            self.dv_mag_km_s(),
+            self.epoch,
+            self.dv_km_s[0],
+            self.dv_km_s[1],
+            self.dv_km_s[2]
+        )
+    }
+}
+
+/// A sequence of impulsive maneuvers designed analytically with two-body dynamics, in chronological order.
+///
+/// The maneuvers can be converted to finite burns (cf. `to_finite_burns`) for verification with high fidelity dynamics.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManeuverPlan {
+    /// Name of the design
+    pub name: String,
+    /// Maneuvers in chronological order
+    pub mnvrs: Vec<ImpulsiveMnvr>,
+    /// Two-body orbit after the last maneuver
+    pub final_orbit: Orbit,
+}
+
+impl ManeuverPlan {
+    /// Total delta-v of the plan (km/s)
+    pub fn total_dv_km_s(&self) -> f64 {
+        self.mnvrs.iter().map(|mnvr| mnvr.dv_mag_km_s()).sum()
+    }
+
+    /// Duration between the first and the last maneuvers
+    pub fn duration(&self) -> Duration {
+        match (self.mnvrs.first(), self.mnvrs.last()) {
+            (Some(first), Some(last)) => last.epoch - first.epoch,
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Returns all of the maneuvers as inertial impulsive `Mnvr`, e.g. to build a `FiniteBurns` guidance law
+    pub fn to_mnvrs(&self) -> Vec<Mnvr> {
+        self.mnvrs.iter().map(|mnvr| mnvr.to_mnvr()).collect()
+    }
+
+    /// Converts each maneuver into a finite burn, propagating the spacecraft with the provided propagator between the maneuvers.
+    ///
+    /// The spacecraft must have a thruster and be at or before the epoch of the first maneuver.
+    pub fn to_finite_burns<'a, E: ErrorCtrl>(
+        &self,
+        spacecraft: Spacecraft,
+        prop: &'a Propagator<'a, SpacecraftDynamics, E>,
+    ) -> Result<Vec<Mnvr>, NyxError> {
+        let mut state = spacecraft;
+        let mut burns = Vec::with_capacity(self.mnvrs.len());
+        for mnvr in &self.mnvrs {
+            state = prop.with(state).until_epoch(mnvr.epoch)?;
+            burns.push(mnvr.to_finite_burn(state, prop)?);
+            // Continue from the impulsive solution so that each conversion starts from the designed state
+            state.orbit.apply_dv(mnvr.dv_km_s);
+        }
+        Ok(burns)
+    }
+}
+
+impl fmt::Display for ManeuverPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} maneuvers for a total of {:.6} km/s over {}",
+            self.name,
+            self.mnvrs.len(),
+            self.total_dv_km_s(),
+            self.duration()
+        )?;
+        for mnvr in &self.mnvrs {
+            writeln!(f, "\t{mnvr}")?;
+        }
+        write!(f, "\tfinal orbit: {:x}", self.final_orbit)
+    }
+}
+
+/// Trade between a Hohmann and a bi-elliptic transfer between the same circular orbits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransferTrade {
+    pub hohmann: ManeuverPlan,
+    pub bi_elliptic: ManeuverPlan,
+}
+
+impl TransferTrade {
+    /// Returns the plan requiring the least delta-v
+    pub fn cheapest(&self) -> &ManeuverPlan {
+        if self.bi_elliptic.total_dv_km_s() < self.hohmann.total_dv_km_s() {
+            &self.bi_elliptic
+        } else {
+            &self.hohmann
+        }
+    }
+
+    /// Delta-v saved by the bi-elliptic transfer compared to the Hohmann transfer (negative if it is more expensive), in km/s
+    pub fn bi_elliptic_savings_km_s(&self) -> f64 {
+        self.hohmann.total_dv_km_s() - self.bi_elliptic.total_dv_km_s()
+    }
+}
+
+impl fmt::Display for TransferTrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.hohmann)?;
+        writeln!(f, "{}", self.bi_elliptic)?;
+        write!(
+            f,
+            "bi-elliptic saves {:.6} km/s for {} more time of flight",
+            self.bi_elliptic_savings_km_s(),
+            self.bi_elliptic.duration() - self.hohmann.duration()
+        )
+    }
+}
+
+/// Returns the mean anomaly (in radians) of the point of the orbit in the provided direction.
+///
+/// The anomalies are measured from the eccentricity vector, or from the ascending node for circular orbits, so this remains defined for near-circular orbits.
+fn mean_anomaly_towards(orbit: &Orbit, direction: &Vector3<f64>) -> f64 {
+    let ecc = orbit.ecc();
+    let h_hat = orbit.hvec() / orbit.hmag_km2_s();
+    let reference = if ecc > 1e-9 {
+        orbit.evec() / ecc
+    } else {
+        node_direction(orbit)
+    };
+    let x_hat = direction / direction.norm();
+    let ta_rad = reference
+        .cross(&x_hat)
+        .dot(&h_hat)
+        .atan2(reference.dot(&x_hat));
+    let ea_rad = 2.0 * (((1.0 - ecc) / (1.0 + ecc)).sqrt() * (0.5 * ta_rad).tan()).atan();
+    ea_rad - ecc * ea_rad.sin()
+}
+
+/// Returns the unit vector towards the ascending node, or along the X axis for equatorial orbits
+fn node_direction(orbit: &Orbit) -> Vector3<f64> {
+    let n = Vector3::z().cross(&orbit.hvec());
+    if n.norm() > f64::EPSILON {
+        n / n.norm()
+    } else {
+        Vector3::x()
+    }
+}
+
+/// Returns the orbit at the next passage in the provided direction, using two-body dynamics.
+fn next_passage_towards(orbit: &Orbit, direction: &Vector3<f64>) -> Result<Orbit, NyxError> {
+    if orbit.ecc() >= 1.0 {
+        return Err(NyxError::NotHyperbolic(
+            "next passage is only computed for elliptical orbits".to_string(),
+        ));
+    }
+    let gm = orbit.frame.gm();
+    let hmag = orbit.hmag_km2_s();
+    let h_hat = orbit.hvec() / hmag;
+    let r_hat = direction / direction.norm();
+    let theta_hat = h_hat.cross(&r_hat);
+
+    // Position and velocity from the radial and transverse components, using the eccentricity vector directly
+    let evec = orbit.evec();
+    let e_cos_ta = evec.dot(&r_hat);
+    let e_sin_ta = evec.cross(&r_hat).dot(&h_hat);
+    let rmag = hmag.powi(2) / gm / (1.0 + e_cos_ta);
+    let velocity = (gm / hmag) * (e_sin_ta * r_hat + (1.0 + e_cos_ta) * theta_hat);
+
+    let delta_ma_rad = (mean_anomaly_towards(orbit, &r_hat)
+        - mean_anomaly_towards(orbit, &orbit.radius()))
+    .rem_euclid(TAU);
+    let mean_motion_rad_s = (gm / orbit.sma_km().powi(3)).sqrt();
+
+    let mut passage = orbit.with_radius(&(rmag * r_hat)).with_velocity(&velocity);
+    passage.epoch += (delta_ma_rad / mean_motion_rad_s) * Unit::Second;
+    Ok(passage)
+}
+
+/// Returns the orbit at the next passage at its ascending or descending node, using two-body dynamics.
+pub fn next_node(orbit: &Orbit, ascending: bool) -> Result<Orbit, NyxError> {
+    let n_hat = node_direction(orbit);
+    next_passage_towards(orbit, &if ascending { n_hat } else { -n_hat })
+}
+
+/// Returns the state at the opposite apsis of an orbit which is at an apsis, using two-body dynamics.
+fn opposite_apsis(orbit: &Orbit) -> Orbit {
+    let gm = orbit.frame.gm();
+    let sma_km = orbit.sma_km();
+    let rmag_km = 2.0 * sma_km - orbit.rmag_km();
+    let speed_km_s = (gm * (2.0 / rmag_km - 1.0 / sma_km)).sqrt();
+    let r_hat = orbit.radius() / orbit.rmag_km();
+    let v_hat = orbit.velocity() / orbit.vmag_km_s();
+    let mut apsis = orbit
+        .with_radius(&(-rmag_km * r_hat))
+        .with_velocity(&(-speed_km_s * v_hat));
+    apsis.epoch += orbit.period() * 0.5;
+    apsis
+}
+
+/// Rotates the provided direction about the radius vector of the orbit so that the inclination changes by the provided amount, and scales it to the new speed.
+/// This is only possible at a node, where the radius vector is along the line of nodes.
+fn rotated_velocity(
+    orbit: &Orbit,
+    direction: &Vector3<f64>,
+    delta_inc_deg: f64,
+    new_speed_km_s: f64,
+) -> Vector3<f64> {
+    let r_hat = orbit.r_hat();
+    let target_inc_deg = orbit.inc_deg() + delta_inc_deg;
+
+    // The sign of the rotation depends on the node, so pick the one leading to the desired inclination
+    [1.0, -1.0]
+        .iter()
+        .map(|sign| rotv(direction, &r_hat, sign * delta_inc_deg.to_radians()) * new_speed_km_s)
+        .min_by(|a, b| {
+            let inc_err =
+                |v: &Vector3<f64>| (orbit.with_velocity(v).inc_deg() - target_inc_deg).abs();
+            inc_err(a).total_cmp(&inc_err(b))
+        })
+        .unwrap()
+}
+
+/// Returns the local horizontal direction of the orbit, i.e. the velocity direction of a circular orbit at that position
+fn horizontal(orbit: &Orbit) -> Vector3<f64> {
+    let h_hat = orbit.hvec() / orbit.hmag_km2_s();
+    h_hat.cross(&orbit.r_hat())
+}
+
+/// Designs a pure plane change of `delta_inc_deg` at the next node where the orbit is the slowest, which minimizes the delta-v.
+///
+/// # Astrodynamics note
+/// The delta-v is 2 v cos(γ) sin(Δi/2), where γ is the flight path angle at the node.
+pub fn plane_change(orbit: &Orbit, delta_inc_deg: f64) -> Result<ManeuverPlan, NyxError> {
+    let at_node = [next_node(orbit, true)?, next_node(orbit, false)?]
+        .into_iter()
+        .min_by(|a, b| a.vmag_km_s().total_cmp(&b.vmag_km_s()))
+        .unwrap();
+
+    let v_hat = at_node.velocity() / at_node.vmag_km_s();
+    let new_velocity = rotated_velocity(&at_node, &v_hat, delta_inc_deg, at_node.vmag_km_s());
+    let mnvr = ImpulsiveMnvr {
+        epoch: at_node.epoch,
+        dv_km_s: new_velocity - at_node.velocity(),
+    };
+
+    Ok(ManeuverPlan {
+        name: format!("plane change of {delta_inc_deg} deg"),
+        mnvrs: vec![mnvr],
+        final_orbit: at_node.with_velocity(&new_velocity),
+    })
+}
+
+/// Designs a Hohmann transfer from a circular orbit to the circular orbit of the provided radius, starting at the epoch of the orbit.
+pub fn hohmann(orbit: &Orbit, target_radius_km: f64) -> Result<ManeuverPlan, NyxError> {
+    combined_plane_change(orbit, target_radius_km, 0.0).map(|mut plan| {
+        plan.name = format!("Hohmann transfer to {target_radius_km} km");
+        plan
+    })
+}
+
+/// Designs a Hohmann transfer to the circular orbit of the provided radius combined with an inclination change of `delta_inc_deg`.
+///
+/// The transfer starts at the next node of the initial circular orbit, and the inclination change is split between both burns
+/// such that the total delta-v is minimized (most of it is done at the highest radius, where the orbit is the slowest).
+pub fn combined_plane_change(
+    orbit: &Orbit,
+    target_radius_km: f64,
+    delta_inc_deg: f64,
+) -> Result<ManeuverPlan, NyxError> {
+    let gm = orbit.frame.gm();
+    // Without any plane change, there is no need to wait for the node
+    let start = if delta_inc_deg.abs() > 0.0 {
+        next_node(orbit, true)?
+    } else {
+        *orbit
+    };
+
+    let r1_km = start.rmag_km();
+    let v1_km_s = start.vmag_km_s();
+    let transfer_sma_km = 0.5 * (r1_km + target_radius_km);
+    let vp_km_s = (gm * (2.0 / r1_km - 1.0 / transfer_sma_km)).sqrt();
+    let va_km_s = (gm * (2.0 / target_radius_km - 1.0 / transfer_sma_km)).sqrt();
+    let v2_km_s = (gm / target_radius_km).sqrt();
+
+    // Golden section search of the fraction of the inclination change done at the first burn
+    let di_rad = delta_inc_deg.to_radians();
+    let total_dv = |split: f64| {
+        (v1_km_s.powi(2) + vp_km_s.powi(2) - 2.0 * v1_km_s * vp_km_s * (split * di_rad).cos())
+            .sqrt()
+            + (va_km_s.powi(2) + v2_km_s.powi(2)
+                - 2.0 * va_km_s * v2_km_s * ((1.0 - split) * di_rad).cos())
+            .sqrt()
+    };
+    let inv_phi = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let (mut lo, mut hi) = (0.0, 1.0);
+    while hi - lo > SPLIT_TOLERANCE {
+        let left = hi - inv_phi * (hi - lo);
+        let right = lo + inv_phi * (hi - lo);
+        if total_dv(left) < total_dv(right) {
+            hi = right;
+        } else {
+            lo = left;
+        }
+    }
+    let split = 0.5 * (lo + hi);
+
+    // First burn onto the transfer orbit
+    let first_inc_deg = split * delta_inc_deg;
+    let v_transfer = rotated_velocity(&start, &horizontal(&start), first_inc_deg, vp_km_s);
+    let first = ImpulsiveMnvr {
+        epoch: start.epoch,
+        dv_km_s: v_transfer - start.velocity(),
+    };
+    let transfer = start.with_velocity(&v_transfer);
+
+    // Second burn at the opposite apsis, which is also the opposite node, to circularize and complete the plane change
+    let at_apsis = opposite_apsis(&transfer);
+    let v_final = rotated_velocity(
+        &at_apsis,
+        &horizontal(&at_apsis),
+        delta_inc_deg - first_inc_deg,
+        v2_km_s,
+    );
+    let second = ImpulsiveMnvr {
+        epoch: at_apsis.epoch,
+        dv_km_s: v_final - at_apsis.velocity(),
+    };
+
+    Ok(ManeuverPlan {
+        name: format!(
+            "combined transfer to {target_radius_km} km with a plane change of {delta_inc_deg} deg ({:.3} % at first burn)",
+            100.0 * split
+        ),
+        mnvrs: vec![first, second],
+        final_orbit: at_apsis.with_velocity(&v_final),
+    })
+}
+
+/// Designs a bi-elliptic transfer from a circular orbit to the circular orbit of the provided radius via the intermediate apoapsis radius,
+/// starting at the epoch of the orbit.
+pub fn bi_elliptic(
+    orbit: &Orbit,
+    target_radius_km: f64,
+    intermediate_radius_km: f64,
+) -> Result<ManeuverPlan, NyxError> {
+    let gm = orbit.frame.gm();
+    let r1_km = orbit.rmag_km();
+
+    let mut mnvrs = Vec::with_capacity(3);
+    let mut state = *orbit;
+
+    // Each leg is a half ellipse between the current radius and the next one, the last leg being circular
+    for (next_radius_km, leg_sma_km) in [
+        (
+            intermediate_radius_km,
+            0.5 * (r1_km + intermediate_radius_km),
+        ),
+        (
+            target_radius_km,
+            0.5 * (intermediate_radius_km + target_radius_km),
+        ),
+    ] {
+        let speed_km_s = (gm * (2.0 / state.rmag_km() - 1.0 / leg_sma_km)).sqrt();
+        let new_velocity = horizontal(&state) * speed_km_s;
+        mnvrs.push(ImpulsiveMnvr {
+            epoch: state.epoch,
+            dv_km_s: new_velocity - state.velocity(),
+        });
+        state = opposite_apsis(&state.with_velocity(&new_velocity));
+        debug_assert!((state.rmag_km() - next_radius_km).abs() < 1e-6 * next_radius_km);
+    }
+
+    // Circularize at the target radius
+    let new_velocity = horizontal(&state) * (gm / target_radius_km).sqrt();
+    mnvrs.push(ImpulsiveMnvr {
+        epoch: state.epoch,
+        dv_km_s: new_velocity - state.velocity(),
+    });
+
+    Ok(ManeuverPlan {
+        name: format!(
+            "bi-elliptic transfer to {target_radius_km} km via {intermediate_radius_km} km"
+        ),
+        mnvrs,
+        final_orbit: state.with_velocity(&new_velocity),
+    })
+}
+
+/// Computes both the Hohmann and the bi-elliptic transfers between the same circular orbits.
+pub fn hohmann_vs_bi_elliptic(
+    orbit: &Orbit,
+    target_radius_km: f64,
+    intermediate_radius_km: f64,
+) -> Result<TransferTrade, NyxError> {
+    Ok(TransferTrade {
+        hohmann: hohmann(orbit, target_radius_km)?,
+        bi_elliptic: bi_elliptic(orbit, target_radius_km, intermediate_radius_km)?,
+    })
+}
@@ -24,11 +24,11 @@ use std::fmt;
 pub mod prelude {
     pub use super::{
         optimizer::*,
-        trajectory::{ExportCfg, Interpolatable, Traj},
-        Ephemeris, Event, ScTraj, StateParameter,
+        trajectory::{ExportCfg, Extrapolation, Interpolatable, Traj},
+        Ephemeris, Event, EventArc, ScTraj, StateParameter,
     };
     pub use crate::cosmic::{
-        try_achieve_b_plane, BPlane, BPlaneTarget, Bodies, Cosm, Frame, GuidanceMode,
+        try_achieve_b_plane, BPlane, BPlaneTarget, Bodies, Cosm, Frame, GuidanceMode, LaunchTarget,
         LightTimeCalc, Orbit, OrbitDual,
     };
     pub use crate::dynamics::{
@@ -47,7 +47,10 @@ pub mod prelude {
 pub mod trajectory;
 
 mod events;
-pub use events::{Event, EventEvaluator};
+pub use events::{
+    ConditionExpr, ConservationDrift, ConservedQuantity, Event, EventArc, EventEvaluator,
+    NamedEvent,
+};
 
 pub mod objective;
 pub mod opti;
@@ -56,7 +59,43 @@ pub type ScTraj = trajectory::Traj<Spacecraft>;
 pub type Ephemeris = trajectory::Traj<Orbit>;
 
 mod param;
-pub use param::StateParameter;
+pub use param::{NamedParameter, StateParameter};
+
+/// Sensitivity of trajectories to force model parameters
+pub mod sensitivity;
+
+/// Free-return checks and abort maneuvers
+pub mod free_return;
+
+/// Analytic maneuver design: plane changes, Hohmann and bi-elliptic transfers
+pub mod maneuver_design;
+
+/// Attitude slews and pointing profiles between maneuver attitudes
+pub mod attitude_profile;
+
+/// Closed loop attitude control with reaction wheels or thrusters
+pub mod attitude_control;
+
+/// Spin-stabilized attitude: spin axis precession, nutation damping, projected areas and antenna availability
+pub mod spin_stabilized;
+
+/// Electric propulsion orbit raising campaigns under operational constraints
+pub mod ep_campaign;
+
+/// Station-keeping planning of geostationary spacecraft
+pub mod station_keeping;
+
+/// Repeat ground track events and maintenance planning
+pub mod ground_track;
+
+/// Multi-objective trade studies over design variables and their Pareto-optimal designs
+pub mod trade_study;
+
+/// Close approach screening of a primary ephemeris against a catalog of ephemerides and TLEs
+pub mod screening;
+
+/// Corridor targeting, keeping a spacecraft within a time-varying tube around a reference trajectory
+pub mod corridor;
 
 pub use opti::target_variable::{Variable, Vary};
 
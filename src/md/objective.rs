@@ -18,6 +18,7 @@
 
 use super::StateParameter;
 use crate::cosmic::OrbitPartial;
+use crate::time::Duration;
 use std::fmt;
 
 /// Defines a state parameter event finder
@@ -33,6 +34,8 @@ pub struct Objective {
     pub multiplicative_factor: f64,
     /// An additive factor to this parameters's error in the targeting (defaults to 0.0)
     pub additive_factor: f64,
+    /// The scale of this parameter when solving for the correction, overriding that of the canonical units of the targeter
+    pub scale: Option<f64>,
 }
 
 impl Objective {
@@ -55,9 +58,27 @@ impl Objective {
             tolerance,
             multiplicative_factor: 1.0,
             additive_factor: 0.0,
+            scale: None,
         }
     }
 
+    /// Match the time of flight from the correction epoch to the achievement epoch, within the provided tolerance.
+    /// The targeter can only achieve this objective if one of its variables is the achievement epoch, i.e. `Vary::AchievementEpoch`.
+    /// Internally, this is an objective on the `Epoch` parameter whose values are in seconds since the correction epoch.
+    pub fn time_of_flight(tof: Duration, tolerance: Duration) -> Self {
+        Self::within_tolerance(
+            StateParameter::Epoch,
+            tof.to_seconds(),
+            tolerance.to_seconds(),
+        )
+    }
+
+    /// Sets the scale of this objective, in the units of its parameter, e.g. its expected range of values
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
     /// Returns whether this objective has been achieved, and the associated parameter error.
     pub fn assess(&self, achieved: OrbitPartial) -> (bool, f64) {
         self.assess_raw(achieved.real())
@@ -16,9 +16,10 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
-use crate::dynamics::guidance::{ra_dec_from_unit_vector, Mnvr};
+use crate::dynamics::guidance::{ra_dec_from_unit_vector, Mnvr, Steering};
 // use crate::errors::TargetingError;
 use crate::linalg::{SMatrix, SVector, Vector3};
 use crate::md::objective::Objective;
@@ -80,6 +81,7 @@ impl<'a, E: ErrorCtrl> Optimizer<'a, E, 3, 6> {
             alpha_inplane_radians,
             delta_outofplane_radians: beta_outofplane_radians,
             frame: Frame::Inertial,
+            steering: Steering::Polynomial,
         };
 
         println!("INITIAL GUESS\n{mnvr}\n\n");
@@ -123,6 +125,7 @@ impl<'a, E: ErrorCtrl> Optimizer<'a, E, 3, 6> {
                 desired_value: sc_xf_desired.orbit.x_km,
                 tolerance: 1e-3,
                 additive_factor: 0.0,
+                scale: None,
                 multiplicative_factor: 1.0,
             },
             Objective {
@@ -130,6 +133,7 @@ impl<'a, E: ErrorCtrl> Optimizer<'a, E, 3, 6> {
                 desired_value: sc_xf_desired.orbit.y_km,
                 tolerance: 1e-3,
                 additive_factor: 0.0,
+                scale: None,
                 multiplicative_factor: 1.0,
             },
             Objective {
@@ -137,6 +141,7 @@ impl<'a, E: ErrorCtrl> Optimizer<'a, E, 3, 6> {
                 desired_value: sc_xf_desired.orbit.z_km,
                 tolerance: 1e-3,
                 additive_factor: 0.0,
+                scale: None,
                 multiplicative_factor: 1.0,
             },
             Objective {
@@ -144,6 +149,7 @@ impl<'a, E: ErrorCtrl> Optimizer<'a, E, 3, 6> {
                 desired_value: sc_xf_desired.orbit.vx_km_s,
                 tolerance: 1e-3,
                 additive_factor: 0.0,
+                scale: None,
                 multiplicative_factor: 1e-3,
             },
             Objective {
@@ -151,6 +157,7 @@ impl<'a, E: ErrorCtrl> Optimizer<'a, E, 3, 6> {
                 desired_value: sc_xf_desired.orbit.vy_km_s,
                 tolerance: 1e-3,
                 additive_factor: 0.0,
+                scale: None,
                 multiplicative_factor: 1e-3,
             },
             Objective {
@@ -158,6 +165,7 @@ impl<'a, E: ErrorCtrl> Optimizer<'a, E, 3, 6> {
                 desired_value: sc_xf_desired.orbit.vz_km_s,
                 tolerance: 1e-3,
                 additive_factor: 0.0,
+                scale: None,
                 multiplicative_factor: 1e-3,
             },
         ];
@@ -231,7 +239,12 @@ impl<'a, E: ErrorCtrl> Optimizer<'a, E, 3, 6> {
                     .map(|(j, var)| (j, var, 0.0_f64))
                     .collect();
 
-                pert_calc.par_iter_mut().for_each(|(_, var, jac_val)| {
+                #[cfg(feature = "parallel")]
+                let pert_iter = pert_calc.par_iter_mut();
+                #[cfg(not(feature = "parallel"))]
+                let pert_iter = pert_calc.iter_mut();
+
+                pert_iter.for_each(|(_, var, jac_val)| {
                     let mut this_prop = prop.clone();
                     let mut this_mnvr = mnvr;
 
@@ -254,6 +267,20 @@ impl<'a, E: ErrorCtrl> Optimizer<'a, E, 3, 6> {
                                 .add_val_in_order(pert, var.component.vec_index())
                                 .unwrap();
                         }
+                        Vary::MnvrTanAlpha | Vary::MnvrTanAlphaDot => {
+                            this_mnvr.set_linear_tangent();
+                            this_mnvr.alpha_inplane_radians = mnvr
+                                .alpha_inplane_radians
+                                .add_val_in_order(pert, var.component.vec_index())
+                                .unwrap();
+                        }
+                        Vary::MnvrTanDelta | Vary::MnvrTanDeltaDot => {
+                            this_mnvr.set_linear_tangent();
+                            this_mnvr.delta_outofplane_radians = mnvr
+                                .delta_outofplane_radians
+                                .add_val_in_order(pert, var.component.vec_index())
+                                .unwrap();
+                        }
                         _ => unreachable!(),
                     }
 
@@ -386,6 +413,20 @@ impl<'a, E: ErrorCtrl> Optimizer<'a, E, 3, 6> {
                             .add_val_in_order(corr % TAU, var.component.vec_index())
                             .unwrap();
                     }
+                    Vary::MnvrTanAlpha | Vary::MnvrTanAlphaDot => {
+                        mnvr.set_linear_tangent();
+                        mnvr.alpha_inplane_radians = mnvr
+                            .alpha_inplane_radians
+                            .add_val_in_order(corr, var.component.vec_index())
+                            .unwrap();
+                    }
+                    Vary::MnvrTanDelta | Vary::MnvrTanDeltaDot => {
+                        mnvr.set_linear_tangent();
+                        mnvr.delta_outofplane_radians = mnvr
+                            .delta_outofplane_radians
+                            .add_val_in_order(corr, var.component.vec_index())
+                            .unwrap();
+                    }
                     _ => unreachable!(),
                 }
             }
@@ -18,7 +18,7 @@
 
 use super::optimizer::Optimizer;
 // use super::solution::TargeterSolution;
-use crate::dynamics::guidance::Mnvr;
+use crate::dynamics::guidance::{Mnvr, Steering};
 use crate::errors::TargetingError;
 use crate::linalg::{storage::Owned, Const, SMatrix, SVector, Vector6};
 use crate::linalg::{DimMax, DimMin, ToTypenum};
@@ -116,6 +116,7 @@ where
             alpha_inplane_radians: CommonPolynomial::Quadratic(0.0, 0.0, 0.0),
             delta_outofplane_radians: CommonPolynomial::Quadratic(0.0, 0.0, 0.0),
             frame: Frame::RCN,
+            steering: Steering::Polynomial,
         };
 
         let mut finite_burn_target = false;
@@ -161,6 +162,20 @@ where
                             .add_val_in_order(attempted_control[i], var.component.vec_index())
                             .unwrap();
                     }
+                    Vary::MnvrTanAlpha | Vary::MnvrTanAlphaDot => {
+                        mnvr.set_linear_tangent();
+                        mnvr.alpha_inplane_radians = mnvr
+                            .alpha_inplane_radians
+                            .add_val_in_order(attempted_control[i], var.component.vec_index())
+                            .unwrap();
+                    }
+                    Vary::MnvrTanDelta | Vary::MnvrTanDeltaDot => {
+                        mnvr.set_linear_tangent();
+                        mnvr.delta_outofplane_radians = mnvr
+                            .delta_outofplane_radians
+                            .add_val_in_order(attempted_control[i], var.component.vec_index())
+                            .unwrap();
+                    }
                     Vary::ThrustX | Vary::ThrustY | Vary::ThrustZ => {
                         let mut vector = mnvr.vector(mnvr.start);
                         vector[var.component.vec_index()] = attempted_control[i];
@@ -336,6 +351,20 @@ where
                                 .add_val_in_order(pert, var.component.vec_index())
                                 .unwrap();
                         }
+                        Vary::MnvrTanAlpha | Vary::MnvrTanAlphaDot => {
+                            this_mnvr.set_linear_tangent();
+                            this_mnvr.alpha_inplane_radians = mnvr
+                                .alpha_inplane_radians
+                                .add_val_in_order(pert, var.component.vec_index())
+                                .unwrap();
+                        }
+                        Vary::MnvrTanDelta | Vary::MnvrTanDeltaDot => {
+                            this_mnvr.set_linear_tangent();
+                            this_mnvr.delta_outofplane_radians = mnvr
+                                .delta_outofplane_radians
+                                .add_val_in_order(pert, var.component.vec_index())
+                                .unwrap();
+                        }
                         Vary::ThrustX | Vary::ThrustY | Vary::ThrustZ => {
                             let mut vector = this_mnvr.vector(self.correction_epoch);
                             vector[var.component.vec_index()] += pert;
@@ -27,6 +27,8 @@ pub mod optimizer;
 pub mod raphson_finite_diff;
 /// Uses a [Newton Raphson](https://en.wikipedia.org/wiki/Newton%27s_method_in_optimization) method where the Jacobian is computed via hyperdual numbers.
 pub mod raphson_hyperdual;
+/// Scales the variables and objectives of the targeters in canonical units.
+pub mod scaling;
 pub mod solution;
 pub mod target_variable;
 
@@ -19,6 +19,7 @@
 pub use super::CostFunction;
 // use crate::dynamics::guidance::{FiniteBurns, Mnvr};
 use crate::linalg::{DMatrix, DVector, SVector};
+use crate::md::opti::scaling::Scaling;
 use crate::md::opti::solution::TargeterSolution;
 use crate::md::optimizer::Optimizer;
 use crate::md::prelude::*;
@@ -94,6 +95,7 @@ impl<'a, E: ErrorCtrl, T: MultishootNode<OT>, const VT: usize, const OT: usize>
                     iterations: 100,
                     objective_frame: None,
                     correction_frame: None,
+                    scaling: Scaling::Auto,
                 };
                 let sol = match tgt.try_achieve_dual(
                     initial_states[i],
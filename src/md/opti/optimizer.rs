@@ -19,12 +19,13 @@
 use crate::errors::TargetingError;
 use crate::md::objective::Objective;
 use crate::md::prelude::*;
-use crate::md::StateParameter;
+use crate::md::{EventEvaluator, StateParameter};
 pub use crate::md::{Variable, Vary};
 use crate::propagators::error_ctrl::ErrorCtrl;
 use std::convert::TryInto;
 use std::fmt;
 
+pub use super::scaling::{CanonicalUnits, Scaling};
 use super::solution::TargeterSolution;
 
 /// An optimizer structure with V control variables and O objectives.
@@ -43,6 +44,8 @@ pub struct Optimizer<'a, E: ErrorCtrl, const V: usize, const O: usize> {
     pub correction_frame: Option<Frame>,
     /// Maximum number of iterations
     pub iterations: usize,
+    /// Scaling of the variables and objectives when solving for the correction, in canonical units by default
+    pub scaling: Scaling,
 }
 
 impl<'a, E: ErrorCtrl, const V: usize, const O: usize> fmt::Display for Optimizer<'a, E, V, O> {
@@ -78,6 +81,7 @@ impl<'a, E: ErrorCtrl, const O: usize> Optimizer<'a, E, 3, O> {
             iterations: 100,
             objective_frame: None,
             correction_frame: None,
+            scaling: Scaling::Auto,
         }
     }
 
@@ -97,6 +101,7 @@ impl<'a, E: ErrorCtrl, const O: usize> Optimizer<'a, E, 3, O> {
             iterations: 100,
             objective_frame: None,
             correction_frame: None,
+            scaling: Scaling::Auto,
         }
     }
 
@@ -116,6 +121,7 @@ impl<'a, E: ErrorCtrl, const O: usize> Optimizer<'a, E, 3, O> {
             iterations: 100,
             objective_frame: None,
             correction_frame: Some(Frame::VNC),
+            scaling: Scaling::Auto,
         }
     }
 }
@@ -138,6 +144,32 @@ impl<'a, E: ErrorCtrl, const O: usize> Optimizer<'a, E, 4, O> {
             iterations: 20,
             objective_frame: None,
             correction_frame: None,
+            scaling: Scaling::Auto,
+        }
+    }
+}
+
+impl<'a, E: ErrorCtrl, const O: usize> Optimizer<'a, E, 4, O> {
+    /// Create a new Targeter which will apply a continuous thrust for the whole duration of the segment, steered with the linear tangent law in the VNC frame.
+    ///
+    /// The initial value and the rate of the tangents of both the in-plane and out-of-plane angles are optimized.
+    pub fn linear_tangent(
+        prop: &'a Propagator<'a, SpacecraftDynamics, E>,
+        objectives: [Objective; O],
+    ) -> Self {
+        Self {
+            prop,
+            objectives,
+            variables: [
+                Variable::from(Vary::MnvrTanAlpha),
+                Variable::from(Vary::MnvrTanAlphaDot),
+                Variable::from(Vary::MnvrTanDelta),
+                Variable::from(Vary::MnvrTanDeltaDot),
+            ],
+            iterations: 50,
+            objective_frame: None,
+            correction_frame: None,
+            scaling: Scaling::Auto,
         }
     }
 }
@@ -163,6 +195,7 @@ impl<'a, E: ErrorCtrl, const O: usize> Optimizer<'a, E, 7, O> {
             iterations: 50,
             objective_frame: None,
             correction_frame: None,
+            scaling: Scaling::Auto,
         }
     }
 }
@@ -191,6 +224,7 @@ impl<'a, E: ErrorCtrl, const O: usize> Optimizer<'a, E, 10, O> {
             iterations: 50,
             objective_frame: None,
             correction_frame: None,
+            scaling: Scaling::Auto,
         }
     }
 }
@@ -209,6 +243,7 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
             iterations: 100,
             objective_frame: None,
             correction_frame: None,
+            scaling: Scaling::Auto,
         }
     }
 
@@ -227,6 +262,7 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
             iterations: 100,
             objective_frame: Some((objective_frame, cosm)),
             correction_frame: None,
+            scaling: Scaling::Auto,
         }
     }
 
@@ -243,9 +279,16 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
             iterations: 100,
             objective_frame: None,
             correction_frame: Some(Frame::VNC),
+            scaling: Scaling::Auto,
         }
     }
 
+    /// Sets the scaling of the variables and objectives, e.g. `Scaling::Units` to use the canonical units of the central body
+    pub fn with_scaling(mut self, scaling: Scaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
     /// Runs the targeter using finite differencing (for now).
     #[allow(clippy::identity_op)]
     pub fn try_achieve_from(
@@ -257,6 +300,62 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
         self.try_achieve_fd(initial_state, correction_epoch, achievement_epoch)
     }
 
+    /// Runs the targeter such that the objectives are achieved at the first occurrence of the provided event (e.g. a `ConditionExpr`)
+    /// within `max_duration` after the correction epoch, instead of at a fixed epoch.
+    ///
+    /// The achievement epoch is initially the epoch of the event on the uncorrected trajectory. It is then moved to the epoch of
+    /// the event on each corrected trajectory, until it moves by less than the epoch precision of the event.
+    /// Only impulsive corrections are supported, and the achievement epoch cannot be varied.
+    pub fn try_achieve_at_event<F: EventEvaluator<Spacecraft>>(
+        &self,
+        initial_state: Spacecraft,
+        correction_epoch: Epoch,
+        max_duration: Duration,
+        event: &F,
+    ) -> Result<TargeterSolution<V, O>, NyxError> {
+        if let Some(var) = self
+            .variables
+            .iter()
+            .find(|var| var.component.is_finite_burn() || var.component == Vary::AchievementEpoch)
+        {
+            return Err(NyxError::Targeter(Box::new(
+                TargetingError::UnsupportedVariable(*var),
+            )));
+        }
+
+        let xi_start = self
+            .prop
+            .with(initial_state)
+            .until_epoch(correction_epoch)?;
+        let mut achievement_epoch = self
+            .prop
+            .with(xi_start)
+            .until_event_online(max_duration, event)?
+            .epoch();
+
+        for it in 0..self.iterations {
+            let solution = self.try_achieve_fd(xi_start, correction_epoch, achievement_epoch)?;
+            let event_epoch = self
+                .prop
+                .with(solution.corrected_state)
+                .until_event_online(max_duration, event)?
+                .epoch();
+            info!(
+                "Targeter at {event} -- Iteration #{it} -- achievement epoch moved by {}",
+                event_epoch - achievement_epoch
+            );
+            if (event_epoch - achievement_epoch).abs() <= event.epoch_precision() {
+                return Ok(solution);
+            }
+            achievement_epoch = event_epoch;
+        }
+
+        Err(NyxError::MaxIterReached(format!(
+            "Achievement epoch at {event} did not converge after {} iterations",
+            self.iterations
+        )))
+    }
+
     /// Apply a correction and propagate to achievement epoch. Also checks that the objectives are indeed matched
     pub fn apply(&self, solution: &TargeterSolution<V, O>) -> Result<Spacecraft, NyxError> {
         let (xf, _) = self.apply_with_traj(solution)?;
@@ -306,18 +405,24 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
         let mut converged = true;
         let mut param_errors = Vec::new();
         for obj in &self.objectives {
-            let partial = if obj.parameter.is_b_plane() {
-                match obj.parameter {
-                    StateParameter::BdotR => b_plane.unwrap().b_r,
-                    StateParameter::BdotT => b_plane.unwrap().b_t,
-                    StateParameter::BLTOF => b_plane.unwrap().ltof_s,
-                    _ => unreachable!(),
-                }
+            let achieved = if obj.parameter == StateParameter::Epoch {
+                // The time of flight is counted from the correction epoch
+                (xf.epoch() - solution.corrected_state.epoch()).to_seconds()
             } else {
-                xf_dual.partial_for(obj.parameter)?
+                let partial = if obj.parameter.is_b_plane() {
+                    match obj.parameter {
+                        StateParameter::BdotR => b_plane.unwrap().b_r,
+                        StateParameter::BdotT => b_plane.unwrap().b_t,
+                        StateParameter::BLTOF => b_plane.unwrap().ltof_s,
+                        _ => unreachable!(),
+                    }
+                } else {
+                    xf_dual.partial_for(obj.parameter)?
+                };
+                partial.real()
             };
 
-            let param_err = obj.desired_value - partial.real();
+            let param_err = obj.desired_value - achieved;
 
             if param_err.abs() > obj.tolerance {
                 converged = false;
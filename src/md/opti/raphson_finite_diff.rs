@@ -18,7 +18,7 @@
 
 use super::optimizer::Optimizer;
 use super::solution::TargeterSolution;
-use crate::dynamics::guidance::Mnvr;
+use crate::dynamics::guidance::{Mnvr, Steering};
 use crate::errors::TargetingError;
 use crate::linalg::{SMatrix, SVector, Vector6};
 use crate::md::prelude::*;
@@ -28,9 +28,11 @@ use crate::polyfit::CommonPolynomial;
 use crate::propagators::error_ctrl::ErrorCtrl;
 use crate::pseudo_inverse;
 use hifitime::TimeUnits;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
+use tracing::{debug, debug_span, error, info, info_span};
 
 impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
     /// Differential correction using finite differencing
@@ -55,6 +57,14 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
             }
         }
 
+        let _span = info_span!(
+            "targeter",
+            method = "finite differencing",
+            %correction_epoch,
+            %achievement_epoch,
+        )
+        .entered();
+
         // Now we know that the problem is correctly defined, so let's propagate as is to the epoch
         // where the correction should be applied.
         let xi_start = self
@@ -66,6 +76,7 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
         debug!("xi_start = {}", xi_start);
 
         let mut xi = xi_start;
+        let (var_scales, obj_scales) = self.scales(&xi_start.orbit);
         // We'll store the initial state correction here.
         let mut state_correction = Vector6::<f64>::zeros();
 
@@ -79,9 +90,12 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
             alpha_inplane_radians: CommonPolynomial::Quadratic(0.0, 0.0, 0.0),
             delta_outofplane_radians: CommonPolynomial::Quadratic(0.0, 0.0, 0.0),
             frame: Frame::RCN,
+            steering: Steering::Polynomial,
         };
 
         let mut finite_burn_target = false;
+        // The achievement epoch may be corrected if the targeter varies it
+        let mut arrival_epoch = achievement_epoch;
 
         // Apply the initial guess
         for (i, var) in self.variables.iter().enumerate() {
@@ -102,7 +116,9 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
             }
 
             // Check that a thruster is provided since we'll be changing that and the burn duration
-            if var.component.is_finite_burn() {
+            if var.component == Vary::AchievementEpoch {
+                arrival_epoch += var.apply_bounds(var.init_guess).seconds();
+            } else if var.component.is_finite_burn() {
                 if xi_start.thruster.is_none() {
                     // Can't do any conversion to finite burns without a thruster
                     return Err(NyxError::NoThrusterAvail);
@@ -125,6 +141,20 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
                             .add_val_in_order(var.init_guess, var.component.vec_index())
                             .unwrap();
                     }
+                    Vary::MnvrTanAlpha | Vary::MnvrTanAlphaDot => {
+                        mnvr.set_linear_tangent();
+                        mnvr.alpha_inplane_radians = mnvr
+                            .alpha_inplane_radians
+                            .add_val_in_order(var.init_guess, var.component.vec_index())
+                            .unwrap();
+                    }
+                    Vary::MnvrTanDelta | Vary::MnvrTanDeltaDot => {
+                        mnvr.set_linear_tangent();
+                        mnvr.delta_outofplane_radians = mnvr
+                            .delta_outofplane_radians
+                            .add_val_in_order(var.init_guess, var.component.vec_index())
+                            .unwrap();
+                    }
                     Vary::ThrustX | Vary::ThrustY | Vary::ThrustZ => {
                         let mut vector = mnvr.direction();
                         vector[var.component.vec_index()] += var.perturbation;
@@ -173,6 +203,19 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
             total_correction[i] += var.init_guess;
         }
 
+        // Varying the achievement epoch is only supported for impulsive corrections
+        if finite_burn_target {
+            if let Some(var) = self
+                .variables
+                .iter()
+                .find(|var| var.component == Vary::AchievementEpoch)
+            {
+                return Err(NyxError::Targeter(Box::new(
+                    TargetingError::UnsupportedVariable(*var),
+                )));
+            }
+        }
+
         let mut prev_err_norm = std::f64::INFINITY;
 
         // Determine padding in debugging info
@@ -200,6 +243,8 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
         let start_instant = Instant::now();
 
         for it in 0..=self.iterations {
+            let _it_span = debug_span!("targeter_iteration", iteration = it).entered();
+
             // Modify each variable by the desired perturbation, propagate, compute the final parameter, and store how modifying that variable affects the final parameter
             let cur_xi = xi;
 
@@ -217,9 +262,9 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
                 // Reset the propagator options to their previous configuration
                 prop.opts = prop_opts;
                 // And propagate until the achievement epoch
-                prop.with(post_mnvr).until_epoch(achievement_epoch)?.orbit
+                prop.with(post_mnvr).until_epoch(arrival_epoch)?.orbit
             } else {
-                self.prop.with(cur_xi).until_epoch(achievement_epoch)?.orbit
+                self.prop.with(cur_xi).until_epoch(arrival_epoch)?.orbit
             };
 
             let xf_dual_obj_frame = match &self.objective_frame {
@@ -249,19 +294,23 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
             let mut jac = SMatrix::<f64, O, V>::zeros();
 
             for (i, obj) in self.objectives.iter().enumerate() {
-                let partial = if obj.parameter.is_b_plane() {
-                    match obj.parameter {
-                        StateParameter::BdotR => b_plane.unwrap().b_r,
-                        StateParameter::BdotT => b_plane.unwrap().b_t,
-                        StateParameter::BLTOF => b_plane.unwrap().ltof_s,
-                        _ => unreachable!(),
-                    }
+                let achieved = if obj.parameter == StateParameter::Epoch {
+                    // The time of flight is counted from the correction epoch
+                    (arrival_epoch - correction_epoch).to_seconds()
                 } else {
-                    xf_dual_obj_frame.partial_for(obj.parameter)?
+                    let partial = if obj.parameter.is_b_plane() {
+                        match obj.parameter {
+                            StateParameter::BdotR => b_plane.unwrap().b_r,
+                            StateParameter::BdotT => b_plane.unwrap().b_t,
+                            StateParameter::BLTOF => b_plane.unwrap().ltof_s,
+                            _ => unreachable!(),
+                        }
+                    } else {
+                        xf_dual_obj_frame.partial_for(obj.parameter)?
+                    };
+                    partial.real()
                 };
 
-                let achieved = partial.real();
-
                 let (ok, param_err) = obj.assess_raw(achieved);
                 if !ok {
                     converged = false;
@@ -283,15 +332,24 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
                     .map(|(j, var)| (j, var, 0.0_f64))
                     .collect();
 
-                pert_calc.par_iter_mut().for_each(|(_, var, jac_val)| {
+                #[cfg(feature = "parallel")]
+                let pert_iter = pert_calc.par_iter_mut();
+                #[cfg(not(feature = "parallel"))]
+                let pert_iter = pert_calc.iter_mut();
+
+                pert_iter.for_each(|(_, var, jac_val)| {
                     let mut this_xi = xi;
 
                     let mut this_prop = self.prop.clone();
                     let mut this_mnvr = mnvr;
 
+                    let mut this_arrival_epoch = arrival_epoch;
+
                     let mut opposed_pert = false;
 
-                    if var.component.is_finite_burn() {
+                    if var.component == Vary::AchievementEpoch {
+                        this_arrival_epoch += var.perturbation.seconds();
+                    } else if var.component.is_finite_burn() {
                         // Modify the burn itself
                         let pert = var.perturbation;
                         // Modify the maneuver, but do not change the epochs of the maneuver unless the change is greater than one millisecond
@@ -323,6 +381,20 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
                                     .add_val_in_order(pert, var.component.vec_index())
                                     .unwrap();
                             }
+                            Vary::MnvrTanAlpha | Vary::MnvrTanAlphaDot => {
+                                this_mnvr.set_linear_tangent();
+                                this_mnvr.alpha_inplane_radians = mnvr
+                                    .alpha_inplane_radians
+                                    .add_val_in_order(pert, var.component.vec_index())
+                                    .unwrap();
+                            }
+                            Vary::MnvrTanDelta | Vary::MnvrTanDeltaDot => {
+                                this_mnvr.set_linear_tangent();
+                                this_mnvr.delta_outofplane_radians = mnvr
+                                    .delta_outofplane_radians
+                                    .add_val_in_order(pert, var.component.vec_index())
+                                    .unwrap();
+                            }
                             Vary::ThrustX | Vary::ThrustY | Vary::ThrustZ => {
                                 let mut vector = this_mnvr.direction();
                                 vector[var.component.vec_index()] += var.perturbation;
@@ -404,13 +476,13 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
                         // And propagate until the achievement epoch
                         this_prop
                             .with(post_mnvr)
-                            .until_epoch(achievement_epoch)
+                            .until_epoch(this_arrival_epoch)
                             .unwrap()
                             .orbit
                     } else {
                         this_prop
                             .with(this_xi)
-                            .until_epoch(achievement_epoch)
+                            .until_epoch(this_arrival_epoch)
                             .unwrap()
                             .orbit
                     };
@@ -429,18 +501,21 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
                         None
                     };
 
-                    let partial = if obj.parameter.is_b_plane() {
-                        match obj.parameter {
-                            StateParameter::BdotR => b_plane.unwrap().b_r,
-                            StateParameter::BdotT => b_plane.unwrap().b_t,
-                            StateParameter::BLTOF => b_plane.unwrap().ltof_s,
-                            _ => unreachable!(),
-                        }
+                    let this_achieved = if obj.parameter == StateParameter::Epoch {
+                        (this_arrival_epoch - correction_epoch).to_seconds()
                     } else {
-                        xf_dual_obj_frame.partial_for(obj.parameter).unwrap()
+                        let partial = if obj.parameter.is_b_plane() {
+                            match obj.parameter {
+                                StateParameter::BdotR => b_plane.unwrap().b_r,
+                                StateParameter::BdotT => b_plane.unwrap().b_t,
+                                StateParameter::BLTOF => b_plane.unwrap().ltof_s,
+                                _ => unreachable!(),
+                            }
+                        } else {
+                            xf_dual_obj_frame.partial_for(obj.parameter).unwrap()
+                        };
+                        partial.real()
                     };
-
-                    let this_achieved = partial.real();
                     *jac_val = (this_achieved - achieved) / var.perturbation;
                     if opposed_pert {
                         // We opposed the perturbation to ensure we don't over step a min/max bound
@@ -468,7 +543,9 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
                 let mut state_correction = Vector6::<f64>::zeros();
                 if !finite_burn_target {
                     for (i, var) in self.variables.iter().enumerate() {
-                        state_correction[var.component.vec_index()] += total_correction[i];
+                        if var.component != Vary::AchievementEpoch {
+                            state_correction[var.component.vec_index()] += total_correction[i];
+                        }
                     }
                 }
                 // Now, let's apply the correction to the initial state
@@ -497,9 +574,18 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
                 };
                 // Log success as info
                 if it == 1 {
-                    info!("Targeter -- CONVERGED in 1 iteration");
+                    info!(
+                        iterations = it,
+                        error = err_vector.norm(),
+                        "Targeter -- CONVERGED in 1 iteration"
+                    );
                 } else {
-                    info!("Targeter -- CONVERGED in {} iterations", it);
+                    info!(
+                        iterations = it,
+                        error = err_vector.norm(),
+                        "Targeter -- CONVERGED in {} iterations",
+                        it
+                    );
                 }
                 for obj in &objmsg {
                     info!("{}", obj);
@@ -517,12 +603,17 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
 
             debug!("Jacobian {}", jac);
 
+            // Solve in canonical units, such that the variables and objectives have similar magnitudes
+            let scaled_jac =
+                SMatrix::<f64, O, V>::from_fn(|i, j| jac[(i, j)] * var_scales[j] / obj_scales[i]);
+
             // Perform the pseudo-inverse if needed, else just inverse
-            let jac_inv = pseudo_inverse!(&jac)?;
+            let jac_inv = pseudo_inverse!(&scaled_jac)?;
 
-            debug!("Inverse Jacobian {}", jac_inv);
+            debug!("Inverse scaled Jacobian {}", jac_inv);
 
-            let mut delta = jac_inv * err_vector;
+            let mut delta =
+                (jac_inv * err_vector.component_div(&obj_scales)).component_mul(&var_scales);
 
             debug!(
                 "Error vector (norm = {}): {}\nRaw correction: {}",
@@ -547,7 +638,17 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
 
                 let corr = delta[i];
 
-                if var.component.is_finite_burn() {
+                if var.component == Vary::AchievementEpoch {
+                    // Choose the minimum step between the provided max step and the correction.
+                    if delta[i].abs() > var.max_step.abs() {
+                        delta[i] = var.max_step.abs() * delta[i].signum();
+                    }
+                    // And make sure that the total change of the achievement epoch remains within the bounds
+                    let total_offset_s =
+                        (arrival_epoch + delta[i].seconds() - achievement_epoch).to_seconds();
+                    delta[i] += var.apply_bounds(total_offset_s) - total_offset_s;
+                    arrival_epoch += delta[i].seconds();
+                } else if var.component.is_finite_burn() {
                     // Modify the maneuver, but do not change the epochs of the maneuver unless the change is greater than one millisecond
                     match var.component {
                         Vary::Duration => {
@@ -591,6 +692,30 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
                                 .add_val_in_order(corr, var.component.vec_index())
                                 .unwrap();
                         }
+                        Vary::MnvrTanAlpha
+                        | Vary::MnvrTanAlphaDot
+                        | Vary::MnvrTanDelta
+                        | Vary::MnvrTanDeltaDot => {
+                            // The thrust direction saturates for large tangents, so limit the step and keep the coefficient within its bounds
+                            if delta[i].abs() > var.max_step.abs() {
+                                delta[i] = var.max_step.abs() * delta[i].signum();
+                            }
+                            let total = total_correction[i] + delta[i];
+                            delta[i] += var.apply_bounds(total) - total;
+
+                            mnvr.set_linear_tangent();
+                            if matches!(var.component, Vary::MnvrTanAlpha | Vary::MnvrTanAlphaDot) {
+                                mnvr.alpha_inplane_radians = mnvr
+                                    .alpha_inplane_radians
+                                    .add_val_in_order(delta[i], var.component.vec_index())
+                                    .unwrap();
+                            } else {
+                                mnvr.delta_outofplane_radians = mnvr
+                                    .delta_outofplane_radians
+                                    .add_val_in_order(delta[i], var.component.vec_index())
+                                    .unwrap();
+                            }
+                        }
                         Vary::ThrustX | Vary::ThrustY | Vary::ThrustZ => {
                             let mut vector = mnvr.direction();
                             vector[var.component.vec_index()] += corr;
@@ -642,7 +767,13 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
             debug!("Total correction: {:e}", total_correction);
 
             // Log progress to debug
-            info!("Targeter -- Iteration #{} -- {}", it, achievement_epoch);
+            info!(
+                iteration = it,
+                error = err_vector.norm(),
+                "Targeter -- Iteration #{} -- {}",
+                it,
+                arrival_epoch
+            );
             for obj in &objmsg {
                 info!("{}", obj);
             }
@@ -27,6 +27,7 @@ use crate::pseudo_inverse;
 use crate::utils::are_eigenvalues_stable;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
+use tracing::{debug, debug_span, info, info_span, warn};
 
 impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
     /// Differential correction using hyperdual numbers for the objectives
@@ -51,6 +52,14 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
             }
         }
 
+        let _span = info_span!(
+            "targeter",
+            method = "hyperdual",
+            %correction_epoch,
+            %achievement_epoch,
+        )
+        .entered();
+
         // Now we know that the problem is correctly defined, so let's propagate as is to the epoch
         // where the correction should be applied.
         let xi_start = self
@@ -62,6 +71,7 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
         debug!("xi_start = {}", xi_start);
 
         let mut xi = xi_start;
+        let (var_scales, obj_scales) = self.scales(&xi_start.orbit);
 
         // Store the total correction in a static vector
         let mut total_correction = SVector::<f64, V>::zeros();
@@ -123,6 +133,8 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
         let start_instant = Instant::now();
 
         for it in 0..=self.iterations {
+            let _it_span = debug_span!("targeter_iteration", iteration = it).entered();
+
             // Now, enable the trajectory STM for this state so we can apply the correction
             xi.enable_stm();
 
@@ -250,7 +262,12 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
                     achieved_objectives: self.objectives,
                     iterations: it,
                 };
-                info!("Targeter -- CONVERGED in {} iterations", it);
+                info!(
+                    iterations = it,
+                    error = err_vector.norm(),
+                    "Targeter -- CONVERGED in {} iterations",
+                    it
+                );
                 for obj in &objmsg {
                     info!("{}", obj);
                 }
@@ -267,12 +284,30 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
 
             debug!("Jacobian {}", jac);
 
-            // Perform the pseudo-inverse if needed, else just inverse
-            let jac_inv = pseudo_inverse!(&jac)?;
+            // Solve in canonical units, such that the variables and objectives have similar magnitudes
+            let scaled_jac =
+                DMatrix::from_fn(self.objectives.len(), self.variables.len(), |i, j| {
+                    jac[(i, j)] * var_scales[j] / obj_scales[i]
+                });
+
+            // Perform the pseudo-inverse if needed, else just inverse.
+            // Some objectives are linearly dependent at the correction epoch (e.g. SMA and eccentricity at periapsis),
+            // in which case the SVD provides the minimum norm correction instead.
+            let jac_inv = match pseudo_inverse!(&scaled_jac) {
+                Ok(inv) => inv,
+                Err(_) => scaled_jac
+                    .pseudo_inverse(1e-12)
+                    .map_err(|_| NyxError::SingularJacobian)?,
+            };
 
-            debug!("Inverse Jacobian {}", jac_inv);
+            debug!("Inverse scaled Jacobian {}", jac_inv);
 
-            let mut delta = jac_inv * err_vector;
+            let mut delta = SVector::<f64, V>::from_iterator(
+                (jac_inv * err_vector.component_div(&obj_scales))
+                    .iter()
+                    .zip(var_scales.iter())
+                    .map(|(delta, scale)| delta * scale),
+            );
 
             debug!("Error vector: {}\nRaw correction: {}", err_vector, delta);
 
@@ -322,7 +357,13 @@ impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
             debug!("Total correction: {:e}", total_correction);
 
             // Log progress
-            info!("Targeter -- Iteration #{} -- {}", it, achievement_epoch);
+            info!(
+                iteration = it,
+                error = err_vector.norm(),
+                "Targeter -- Iteration #{} -- {}",
+                it,
+                achievement_epoch
+            );
             for obj in &objmsg {
                 info!("{}", obj);
             }
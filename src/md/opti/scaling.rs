@@ -0,0 +1,195 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::optimizer::Optimizer;
+use super::target_variable::{Variable, Vary};
+use crate::cosmic::Orbit;
+use crate::linalg::SVector;
+use crate::md::objective::Objective;
+use crate::md::StateParameter;
+use crate::propagators::error_ctrl::ErrorCtrl;
+use std::fmt;
+
+/// Canonical units of length and time in which the targeters solve for their corrections.
+///
+/// Each variable and each objective is divided by its characteristic scale in these units (e.g. the velocity unit for a
+/// velocity, or the time unit for an epoch), such that the Jacobian mixes quantities of similar magnitudes instead of
+/// kilometers, kilometers per second and seconds. This improves the conditioning of the (pseudo) inverse of the Jacobian
+/// and makes the minimum norm correction of an underdetermined problem independent of the units of its variables.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CanonicalUnits {
+    /// Distance unit, in km
+    pub length_km: f64,
+    /// Time unit, in seconds
+    pub time_s: f64,
+}
+
+impl CanonicalUnits {
+    pub fn new(length_km: f64, time_s: f64) -> Self {
+        Self { length_km, time_s }
+    }
+
+    /// Canonical units of the provided orbit: the distance unit is its radius and the time unit is such that the
+    /// gravitational parameter of its frame is unity.
+    pub fn from_orbit(orbit: &Orbit) -> Self {
+        let length_km = orbit.rmag_km();
+        Self {
+            length_km,
+            time_s: (length_km.powi(3) / orbit.frame.gm()).sqrt(),
+        }
+    }
+
+    /// Velocity unit, in km/s
+    pub fn velocity_km_s(&self) -> f64 {
+        self.length_km / self.time_s
+    }
+
+    /// Returns the scale of the provided variable, in the units of that variable, unless the variable overrides it
+    pub fn variable_scale(&self, var: &Variable) -> f64 {
+        if let Some(scale) = var.scale {
+            return scale;
+        }
+        match var.component {
+            Vary::PositionX | Vary::PositionY | Vary::PositionZ => self.length_km,
+            Vary::VelocityX | Vary::VelocityY | Vary::VelocityZ => self.velocity_km_s(),
+            Vary::StartEpoch | Vary::Duration | Vary::EndEpoch | Vary::AchievementEpoch => {
+                self.time_s
+            }
+            Vary::MnvrAlphaDot
+            | Vary::MnvrDeltaDot
+            | Vary::MnvrTanAlphaDot
+            | Vary::MnvrTanDeltaDot
+            | Vary::ThrustRateX
+            | Vary::ThrustRateY
+            | Vary::ThrustRateZ => 1.0 / self.time_s,
+            Vary::MnvrAlphaDDot
+            | Vary::MnvrDeltaDDot
+            | Vary::ThrustAccelX
+            | Vary::ThrustAccelY
+            | Vary::ThrustAccelZ => self.time_s.powi(-2),
+            // Angles in radians, tangents, unit vector components and thrust level
+            Vary::MnvrAlpha
+            | Vary::MnvrDelta
+            | Vary::MnvrTanAlpha
+            | Vary::MnvrTanDelta
+            | Vary::ThrustX
+            | Vary::ThrustY
+            | Vary::ThrustZ
+            | Vary::ThrustLevel => 1.0,
+        }
+    }
+
+    /// Returns the scale of the provided objective, in the units of its parameter, unless the objective overrides it
+    pub fn objective_scale(&self, obj: &Objective) -> f64 {
+        if let Some(scale) = obj.scale {
+            return scale;
+        }
+        match obj.parameter {
+            StateParameter::Epoch | StateParameter::Period | StateParameter::BLTOF => self.time_s,
+            StateParameter::Hmag | StateParameter::HX | StateParameter::HY | StateParameter::HZ => {
+                self.length_km * self.velocity_km_s()
+            }
+            param => match param.unit() {
+                "km" => self.length_km,
+                "km/s" => self.velocity_km_s(),
+                "km^2/s^2" => self.velocity_km_s().powi(2),
+                // One radian
+                "deg" => 1.0_f64.to_degrees(),
+                _ => 1.0,
+            },
+        }
+    }
+}
+
+impl fmt::Display for CanonicalUnits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "DU = {:.3} km, TU = {:.3} s, VU = {:.6} km/s",
+            self.length_km,
+            self.time_s,
+            self.velocity_km_s()
+        )
+    }
+}
+
+/// Scaling of the variables and objectives of an optimizer
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Scaling {
+    /// Canonical units of the state at the correction epoch (default)
+    #[default]
+    Auto,
+    /// Provided canonical units, e.g. those of the central body for a transfer
+    Units(CanonicalUnits),
+    /// No scaling: the Jacobian is inverted in the units of each variable and objective
+    Disabled,
+}
+
+impl<'a, E: ErrorCtrl, const V: usize, const O: usize> Optimizer<'a, E, V, O> {
+    /// Returns the scales of the variables and of the objectives of this optimizer for the provided state at the
+    /// correction epoch. The scales of the variables and objectives which set one override the canonical units.
+    pub fn scales(&self, xi: &Orbit) -> (SVector<f64, V>, SVector<f64, O>) {
+        let units = match self.scaling {
+            Scaling::Auto => CanonicalUnits::from_orbit(xi),
+            Scaling::Units(units) => units,
+            Scaling::Disabled => return (SVector::repeat(1.0), SVector::repeat(1.0)),
+        };
+        (
+            SVector::<f64, V>::from_fn(|j, _| units.variable_scale(&self.variables[j])),
+            SVector::<f64, O>::from_fn(|i, _| units.objective_scale(&self.objectives[i])),
+        )
+    }
+}
+
+#[test]
+fn test_canonical_units() {
+    use crate::cosmic::Cosm;
+    use crate::time::Epoch;
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let orbit = Orbit::keplerian(
+        6678.0,
+        0.0,
+        28.5,
+        0.0,
+        0.0,
+        0.0,
+        Epoch::from_gregorian_tai_at_noon(2020, 1, 1),
+        eme2k,
+    );
+    let units = CanonicalUnits::from_orbit(&orbit);
+    assert!((units.length_km - 6678.0).abs() < 1e-9);
+    // The velocity unit of a circular orbit is its velocity, and the time unit is its period divided by 2π
+    assert!((units.velocity_km_s() - orbit.vmag_km_s()).abs() < 1e-9);
+    assert!((units.time_s * std::f64::consts::TAU - orbit.period().to_seconds()).abs() < 1e-6);
+
+    let var = Variable::from(Vary::VelocityX);
+    assert!((units.variable_scale(&var) - orbit.vmag_km_s()).abs() < 1e-9);
+    assert_eq!(units.variable_scale(&var.with_scale(0.1)), 0.1);
+    assert_eq!(
+        units.variable_scale(&Variable::from(Vary::AchievementEpoch)),
+        units.time_s
+    );
+
+    let obj = Objective::new(StateParameter::SMA, 7000.0);
+    assert_eq!(units.objective_scale(&obj), units.length_km);
+    assert_eq!(units.objective_scale(&obj.with_scale(10.0)), 10.0);
+    let obj = Objective::new(StateParameter::Inclination, 30.0);
+    assert!((units.objective_scale(&obj) - 57.29577951308232).abs() < 1e-12);
+}
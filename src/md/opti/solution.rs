@@ -18,7 +18,7 @@
 
 use hifitime::TimeUnits;
 
-use crate::dynamics::guidance::Mnvr;
+use crate::dynamics::guidance::{Mnvr, Steering};
 use crate::linalg::SVector;
 use crate::md::objective::Objective;
 use crate::md::prelude::*;
@@ -75,6 +75,7 @@ impl<const V: usize, const O: usize> TargeterSolution<V, O> {
                 alpha_inplane_radians: CommonPolynomial::Quadratic(0.0, 0.0, 0.0),
                 delta_outofplane_radians: CommonPolynomial::Quadratic(0.0, 0.0, 0.0),
                 frame: Frame::RCN,
+                steering: Steering::Polynomial,
             };
 
             for (i, var) in self.variables.iter().enumerate() {
@@ -123,6 +124,20 @@ impl<const V: usize, const O: usize> TargeterSolution<V, O> {
                             .add_val_in_order(corr, var.component.vec_index())
                             .unwrap();
                     }
+                    Vary::MnvrTanAlpha | Vary::MnvrTanAlphaDot => {
+                        mnvr.set_linear_tangent();
+                        mnvr.alpha_inplane_radians = mnvr
+                            .alpha_inplane_radians
+                            .add_val_in_order(corr, var.component.vec_index())
+                            .unwrap();
+                    }
+                    Vary::MnvrTanDelta | Vary::MnvrTanDeltaDot => {
+                        mnvr.set_linear_tangent();
+                        mnvr.delta_outofplane_radians = mnvr
+                            .delta_outofplane_radians
+                            .add_val_in_order(corr, var.component.vec_index())
+                            .unwrap();
+                    }
                     Vary::ThrustX | Vary::ThrustY | Vary::ThrustZ => {
                         let mut vector = mnvr.direction();
                         vector[var.component.vec_index()] += corr;
@@ -182,6 +197,11 @@ impl<const V: usize, const O: usize> fmt::Display for TargeterSolution<V, O> {
                     is_only_position = false;
                     "m/s"
                 }
+                Vary::AchievementEpoch => {
+                    is_only_position = false;
+                    is_only_velocity = false;
+                    "s"
+                }
                 _ => {
                     is_only_position = false;
                     is_only_velocity = false;
@@ -49,6 +49,14 @@ pub enum Vary {
     MnvrDeltaDot,
     /// Maneuver's out-of-plane beta double-dot
     MnvrDeltaDDot,
+    /// Maneuver's linear tangent law initial value of tan α
+    MnvrTanAlpha,
+    /// Maneuver's linear tangent law rate of tan α, per second
+    MnvrTanAlphaDot,
+    /// Maneuver's linear tangent law initial value of tan β
+    MnvrTanDelta,
+    /// Maneuver's linear tangent law rate of tan β, per second
+    MnvrTanDeltaDot,
     /// Start epoch difference in seconds
     StartEpoch,
     /// Burn duration difference in seconds
@@ -75,17 +83,20 @@ pub enum Vary {
     ThrustAccelY,
     /// Thrust direction acceleration in Z
     ThrustAccelZ,
+    /// Achievement (arrival) epoch difference in seconds, only supported for impulsive corrections
+    AchievementEpoch,
 }
 
 impl Vary {
     #[allow(clippy::nonminimal_bool)]
     pub fn is_finite_burn(&self) -> bool {
         *self == Self::MnvrAlpha
-            || *self == Self::MnvrAlphaDDot
+            || *self == Self::MnvrAlphaDot
             || *self == Self::MnvrAlphaDDot
             || *self == Self::MnvrDelta
+            || *self == Self::MnvrDeltaDot
             || *self == Self::MnvrDeltaDDot
-            || *self == Self::MnvrDeltaDDot
+            || self.is_linear_tangent()
             || *self == Self::StartEpoch
             || *self == Self::Duration
             || *self == Self::EndEpoch
@@ -101,16 +112,34 @@ impl Vary {
             || *self == Self::ThrustAccelZ
     }
 
+    /// Returns whether this variable is a coefficient of the linear tangent steering law
+    pub fn is_linear_tangent(&self) -> bool {
+        matches!(
+            self,
+            Self::MnvrTanAlpha | Self::MnvrTanAlphaDot | Self::MnvrTanDelta | Self::MnvrTanDeltaDot
+        )
+    }
+
     #[allow(clippy::nonminimal_bool)]
     pub fn vec_index(&self) -> usize {
         match self {
             Self::PositionX | Self::ThrustX | Self::MnvrAlphaDDot | Self::MnvrDeltaDDot => 0,
-            Self::PositionY | Self::ThrustY | Self::MnvrAlphaDot | Self::MnvrDeltaDot => 1,
-            Self::PositionZ | Self::ThrustZ | Self::MnvrAlpha | Self::MnvrDelta => 2,
+            Self::PositionY
+            | Self::ThrustY
+            | Self::MnvrAlphaDot
+            | Self::MnvrDeltaDot
+            | Self::MnvrTanAlphaDot
+            | Self::MnvrTanDeltaDot => 1,
+            Self::PositionZ
+            | Self::ThrustZ
+            | Self::MnvrAlpha
+            | Self::MnvrDelta
+            | Self::MnvrTanAlpha
+            | Self::MnvrTanDelta => 2,
             Self::VelocityX | Self::ThrustRateX => 3,
             Self::VelocityY | Self::ThrustRateY => 4,
             Self::VelocityZ | Self::ThrustRateZ => 5,
-            Self::StartEpoch | Self::ThrustAccelX => 6,
+            Self::StartEpoch | Self::AchievementEpoch | Self::ThrustAccelX => 6,
             Self::Duration | Self::EndEpoch | Self::ThrustAccelY => 7,
             Self::ThrustAccelZ => 8,
             _ => unreachable!(),
@@ -134,6 +163,8 @@ pub struct Variable {
     pub min_value: f64,
     /// The frame in which this variable should be applied, must be either a local frame or inertial
     pub frame: Option<Frame>,
+    /// The scale of this variable when solving for the correction, overriding that of the canonical units of the targeter
+    pub scale: Option<f64>,
 }
 
 impl Variable {
@@ -191,6 +222,13 @@ impl Variable {
         me
     }
 
+    /// Sets the scale of this variable, in its own units, e.g. the expected magnitude of its correction
+    pub fn with_scale(self, scale: f64) -> Self {
+        let mut me = self;
+        me.scale = Some(scale);
+        me
+    }
+
     /// Ensure that `val` is within the variable bounds
     pub fn apply_bounds(&self, val: f64) -> f64 {
         if val > self.max_value {
@@ -229,6 +267,7 @@ impl Default for Variable {
             max_value: 5.0,
             min_value: -5.0,
             frame: None,
+            scale: None,
         }
     }
 }
@@ -261,6 +300,22 @@ impl From<Vary> for Variable {
                 min_value: -FRAC_PI_2,
                 ..Default::default()
             },
+            Vary::MnvrTanAlpha | Vary::MnvrTanDelta => Self {
+                component: vary,
+                perturbation: 1e-3,
+                max_step: 0.5,
+                max_value: 50.0,
+                min_value: -50.0,
+                ..Default::default()
+            },
+            Vary::MnvrTanAlphaDot | Vary::MnvrTanDeltaDot => Self {
+                component: vary,
+                perturbation: 1e-5,
+                max_step: 1e-2,
+                max_value: 1.0,
+                min_value: -1.0,
+                ..Default::default()
+            },
             Vary::StartEpoch | Vary::EndEpoch => Self {
                 component: vary,
                 perturbation: 0.5,
@@ -269,6 +324,14 @@ impl From<Vary> for Variable {
                 min_value: -600.0,
                 ..Default::default()
             },
+            Vary::AchievementEpoch => Self {
+                component: vary,
+                perturbation: 1.0,
+                max_step: 3600.0,
+                max_value: 86400.0,
+                min_value: -86400.0,
+                ..Default::default()
+            },
             Vary::Duration => Self {
                 component: vary,
                 perturbation: 1.0,
@@ -17,13 +17,16 @@
 */
 
 use super::NyxError;
+#[cfg(feature = "io")]
 use arrow::datatypes::{DataType, Field};
 use core::fmt;
 use enum_iterator::Sequence;
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, str::FromStr};
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+use std::str::FromStr;
 
 /// Common state parameters
 #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
@@ -88,6 +91,8 @@ pub enum StateParameter {
     Inclination,
     /// Specific impulse (isp) in seconds
     Isp,
+    /// Local time of the ascending node (hours)
+    LTAN,
     /// Mean anomaly (deg)
     MeanAnomaly,
     /// Periapsis, shortcut for TA == 0.0
@@ -116,8 +121,16 @@ pub enum StateParameter {
     TrueLongitude,
     /// Velocity declination (deg)
     VelocityDeclination,
+    /// Hyperbolic excess velocity (km/s), only valid for hyperbolic orbits
+    Vinf,
+    /// Declination of the outgoing asymptote (deg), only valid for hyperbolic orbits
+    VinfDeclination,
+    /// Right ascension of the outgoing asymptote (deg), only valid for hyperbolic orbits
+    VinfRightAscension,
     /// Norm of the velocity vector (km/s)
     Vmag,
+    /// Norm of the total angular momentum of the reaction wheels (N·m·s), only available in attitude simulations
+    WheelMomentum,
     /// X component of the radius (km)
     X,
     /// Y component of the radius (km)
@@ -149,7 +162,9 @@ impl StateParameter {
             | Self::RightAscension
             | Self::RAAN
             | Self::TrueLongitude
-            | Self::VelocityDeclination => 1e-1,
+            | Self::VelocityDeclination
+            | Self::VinfDeclination
+            | Self::VinfRightAscension => 1e-1,
 
             // Anomaly angles
             Self::Apoapsis
@@ -178,12 +193,14 @@ impl StateParameter {
             | Self::Z => 1e-3,
 
             // Velocities
-            Self::C3 | Self::VX | Self::VY | Self::VZ | Self::Vmag => 1e-3,
+            Self::C3 | Self::VX | Self::VY | Self::VZ | Self::Vinf | Self::Vmag => 1e-3,
 
             // Special
             Self::Energy => 1e-3,
+            Self::LTAN => 1e-3,
             Self::DryMass | Self::FuelMass => 1e-3,
             Self::Period => 1e-1,
+            Self::WheelMomentum => 1e-4,
             _ => unimplemented!("{self} cannot be used for event finding"),
         }
     }
@@ -195,7 +212,9 @@ impl StateParameter {
 
     /// Returns whether this is an orbital parameter
     pub const fn is_orbital(&self) -> bool {
-        !self.is_for_spacecraft() && !matches!(self, Self::Apoapsis | Self::Periapsis | Self::Epoch)
+        !self.is_for_spacecraft()
+            && !self.is_for_attitude()
+            && !matches!(self, Self::Apoapsis | Self::Periapsis | Self::Epoch)
     }
 
     /// Returns whether this parameter is only applicable to a spacecraft state
@@ -212,6 +231,11 @@ impl StateParameter {
         )
     }
 
+    /// Returns whether this parameter is only applicable to an attitude state
+    pub const fn is_for_attitude(&self) -> bool {
+        matches!(&self, Self::WheelMomentum)
+    }
+
     pub const fn unit(&self) -> &'static str {
         match self {
             // Angles
@@ -226,6 +250,8 @@ impl StateParameter {
             | Self::RAAN
             | Self::TrueLongitude
             | Self::VelocityDeclination
+            | Self::VinfDeclination
+            | Self::VinfRightAscension
             | Self::Apoapsis
             | Self::Periapsis
             | Self::MeanAnomaly
@@ -252,13 +278,15 @@ impl StateParameter {
             | Self::Z => "km",
 
             // Velocities
-            Self::VX | Self::VY | Self::VZ | Self::Vmag => "km/s",
+            Self::VX | Self::VY | Self::VZ | Self::Vinf | Self::Vmag => "km/s",
 
             Self::C3 | Self::Energy => "km^2/s^2",
 
             Self::DryMass | Self::FuelMass => "kg",
             Self::Isp => "isp",
             Self::Thrust => "N",
+            Self::LTAN => "h",
+            Self::WheelMomentum => "N·m·s",
             _ => "",
         }
     }
@@ -278,9 +306,20 @@ impl StateParameter {
 
 impl StateParameter {
     /// Returns the parquet field of this parameter
+    #[cfg(feature = "io")]
     pub(crate) fn to_field(self, more_meta: Option<Vec<(String, String)>>) -> Field {
+        self.to_field_with_unit(self.unit(), more_meta)
+    }
+
+    /// Returns the parquet field of this parameter expressed in the provided unit
+    #[cfg(feature = "io")]
+    pub(crate) fn to_field_with_unit(
+        self,
+        unit: &str,
+        more_meta: Option<Vec<(String, String)>>,
+    ) -> Field {
         let mut meta = HashMap::new();
-        meta.insert("unit".to_string(), self.unit().to_string());
+        meta.insert("unit".to_string(), unit.to_string());
         if let Some(more_data) = more_meta {
             for (k, v) in more_data {
                 meta.insert(k, v);
@@ -288,7 +327,7 @@ impl StateParameter {
         }
 
         Field::new(
-            format!("{self}"),
+            self.name_with_unit(unit),
             if self == Self::GuidanceMode {
                 DataType::Utf8
             } else {
@@ -340,6 +379,7 @@ impl FromStr for StateParameter {
             "hz" => Ok(Self::HZ),
             "inc" => Ok(Self::Inclination),
             "isp" => Ok(Self::Isp),
+            "ltan" => Ok(Self::LTAN),
             "ma" => Ok(Self::MeanAnomaly),
             "periapsis_radius" => Ok(Self::PeriapsisRadius),
             "period" => Ok(Self::Period),
@@ -353,7 +393,11 @@ impl FromStr for StateParameter {
             "tlong" => Ok(Self::TrueLongitude),
             "thrust" => Ok(Self::Thrust),
             "vdeclin" => Ok(Self::VelocityDeclination),
+            "vinf" => Ok(Self::Vinf),
+            "vinf_declin" => Ok(Self::VinfDeclination),
+            "vinf_right_asc" => Ok(Self::VinfRightAscension),
             "vmag" => Ok(Self::Vmag),
+            "wheel_momentum" => Ok(Self::WheelMomentum),
             "x" => Ok(Self::X),
             "y" => Ok(Self::Y),
             "z" => Ok(Self::Z),
@@ -367,9 +411,10 @@ impl FromStr for StateParameter {
     }
 }
 
-impl fmt::Display for StateParameter {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let repr = match *self {
+impl StateParameter {
+    /// Returns the name of this parameter, without its unit
+    pub(crate) const fn name(&self) -> &'static str {
+        match *self {
             Self::Apoapsis => "apoapsis",
             Self::Periapsis => "periapsis",
             Self::AoL => "aol",
@@ -400,6 +445,7 @@ impl fmt::Display for StateParameter {
             Self::HZ => "hz",
             Self::Inclination => "inc",
             Self::Isp => "isp",
+            Self::LTAN => "ltan",
             Self::MeanAnomaly => "ma",
             Self::PeriapsisRadius => "periapsis_radius",
             Self::Period => "period",
@@ -413,7 +459,11 @@ impl fmt::Display for StateParameter {
             Self::TrueAnomaly => "ta",
             Self::TrueLongitude => "tlong",
             Self::VelocityDeclination => "vdeclin",
+            Self::Vinf => "vinf",
+            Self::VinfDeclination => "vinf_declin",
+            Self::VinfRightAscension => "vinf_right_asc",
             Self::Vmag => "vmag",
+            Self::WheelMomentum => "wheel_momentum",
             Self::X => "x",
             Self::Y => "y",
             Self::Z => "z",
@@ -421,13 +471,78 @@ impl fmt::Display for StateParameter {
             Self::VY => "vy",
             Self::VZ => "vz",
             // _ => &default,
-        };
-        let unit = if self.unit().is_empty() {
-            String::new()
+        }
+    }
+
+    /// Returns the name of this parameter followed by the provided unit, if any
+    pub(crate) fn name_with_unit(&self, unit: &str) -> String {
+        if unit.is_empty() {
+            self.name().to_string()
         } else {
-            format!(" ({})", self.unit())
-        };
-        write!(f, "{repr}{unit}")
+            format!("{} ({unit})", self.name())
+        }
+    }
+}
+
+impl fmt::Display for StateParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name_with_unit(self.unit()))
+    }
+}
+
+/// A parameter of a user-defined state, e.g. a component of the state of custom dynamics, which is not a `StateParameter`.
+/// It is identified either by its name or by its index in the `named_parameters` of that state.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NamedParameter {
+    Name(String),
+    Index(usize),
+}
+
+impl NamedParameter {
+    /// Returns the index of this parameter in the provided names and units of the parameters of a state
+    pub fn index_in(&self, params: &[(&str, &str)]) -> Result<usize, NyxError> {
+        match self {
+            Self::Name(name) => params
+                .iter()
+                .position(|(param_name, _)| param_name == name)
+                .ok_or_else(|| {
+                    NyxError::NamedParameterUnavailable(
+                        self.clone(),
+                        "no such parameter in this state".to_string(),
+                    )
+                }),
+            Self::Index(index) => {
+                if *index < params.len() {
+                    Ok(*index)
+                } else {
+                    Err(NyxError::NamedParameterUnavailable(
+                        self.clone(),
+                        format!("this state only has {} named parameters", params.len()),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+impl From<&str> for NamedParameter {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<usize> for NamedParameter {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl fmt::Display for NamedParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{name}"),
+            Self::Index(index) => write!(f, "named parameter #{index}"),
+        }
     }
 }
 
@@ -466,6 +581,7 @@ mod ut_state_param {
             StateParameter::HZ,
             StateParameter::Inclination,
             StateParameter::Isp,
+            StateParameter::LTAN,
             StateParameter::MeanAnomaly,
             StateParameter::PeriapsisRadius,
             StateParameter::Period,
@@ -479,7 +595,11 @@ mod ut_state_param {
             StateParameter::TrueAnomaly,
             StateParameter::TrueLongitude,
             StateParameter::VelocityDeclination,
+            StateParameter::Vinf,
+            StateParameter::VinfDeclination,
+            StateParameter::VinfRightAscension,
             StateParameter::Vmag,
+            StateParameter::WheelMomentum,
             StateParameter::X,
             StateParameter::Y,
             StateParameter::Z,
@@ -0,0 +1,475 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Cosm, Frame, Orbit, Tle};
+use crate::errors::NyxError;
+use crate::linalg::Vector3;
+use crate::md::trajectory::Traj;
+use crate::time::{Duration, Epoch, Unit};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Margin applied to the Keplerian bound of the speed of each object in the smart sieve, to account for perturbations
+const SPEED_BOUND_MARGIN: f64 = 1.1;
+/// Precision of the time of closest approach, in seconds
+const TCA_PRECISION_S: f64 = 1e-3;
+
+/// Source of the states of a secondary object of the catalog
+#[derive(Clone, Debug)]
+pub enum SecondarySource {
+    /// Ephemeris, e.g. from an owner/operator, only screened over its span
+    Ephemeris(Traj<Orbit>),
+    /// Two line element set, propagated with SGP4
+    Tle(Tle),
+}
+
+/// A secondary object screened against the primary
+#[derive(Clone, Debug)]
+pub struct Secondary {
+    pub name: String,
+    pub source: SecondarySource,
+}
+
+impl Secondary {
+    /// Builds a secondary object from its ephemeris
+    pub fn ephemeris(name: String, traj: Traj<Orbit>) -> Self {
+        Self {
+            name,
+            source: SecondarySource::Ephemeris(traj),
+        }
+    }
+
+    /// Builds a secondary object from its TLE
+    pub fn tle(name: String, tle: Tle) -> Self {
+        Self {
+            name,
+            source: SecondarySource::Tle(tle),
+        }
+    }
+
+    /// Returns the state of this object at the provided epoch in the provided frame
+    fn at(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, NyxError> {
+        let state = match &self.source {
+            SecondarySource::Ephemeris(traj) => traj.at(epoch)?,
+            SecondarySource::Tle(tle) => tle.at(epoch, cosm)?,
+        };
+        if state.frame == frame {
+            Ok(state)
+        } else {
+            cosm.try_frame_chg(&state, frame)
+        }
+    }
+
+    /// Returns the span of the states of this object, if limited
+    fn span(&self) -> Option<(Epoch, Epoch)> {
+        match &self.source {
+            SecondarySource::Ephemeris(traj) => Some((traj.first().epoch, traj.last().epoch)),
+            SecondarySource::Tle(_) => None,
+        }
+    }
+}
+
+/// Configuration of the close approach screening
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScreeningConfig {
+    /// Conjunctions are reported if the miss distance is at most this threshold, in km
+    pub threshold_km: f64,
+    /// Pad added to the threshold in the apogee/perigee sieve, to account for the variations of the osculating apsides over
+    /// the screening window, in km
+    pub sieve_pad_km: f64,
+    /// Step of the search of the closest approaches, which should be a small fraction of the orbital periods
+    pub step: Duration,
+}
+
+impl Default for ScreeningConfig {
+    fn default() -> Self {
+        Self {
+            threshold_km: 5.0,
+            sieve_pad_km: 25.0,
+            step: Unit::Minute * 1,
+        }
+    }
+}
+
+/// A close approach between the primary and a secondary object, within the screening threshold
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConjunctionEvent {
+    /// Name of the secondary object
+    pub secondary: String,
+    /// Time of closest approach
+    pub tca: Epoch,
+    pub miss_distance_km: f64,
+    pub relative_speed_km_s: f64,
+    /// Position of the secondary relative to the primary at TCA, in the RIC frame of the primary, in km
+    pub miss_ric_km: Vector3<f64>,
+}
+
+impl fmt::Display for ConjunctionEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}: miss distance {:.3} km (R {:.3} I {:.3} C {:.3} km), relative speed {:.3} km/s",
+            self.secondary,
+            self.tca,
+            self.miss_distance_km,
+            self.miss_ric_km[0],
+            self.miss_ric_km[1],
+            self.miss_ric_km[2],
+            self.relative_speed_km_s
+        )
+    }
+}
+
+/// Result of the screening of the primary against each secondary object
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScreeningOutcome {
+    /// The orbits cannot come within the threshold, per the apogee/perigee sieve
+    Sieved,
+    /// The object was screened, with the provided number of state evaluations, and led to these conjunctions
+    Screened {
+        num_evaluations: usize,
+        events: Vec<ConjunctionEvent>,
+    },
+    /// The object does not overlap the screening window
+    OutOfWindow,
+    /// The states of the object could not be computed, e.g. a decayed TLE
+    Failed(String),
+}
+
+/// Ranked conjunctions of the primary with the secondary objects of a catalog
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreeningReport {
+    /// Start of the screening window, i.e. of the primary ephemeris
+    pub start: Epoch,
+    /// End of the screening window, i.e. of the primary ephemeris
+    pub end: Epoch,
+    /// Outcome of the screening of each secondary object, by name, in the order of the catalog
+    pub outcomes: Vec<(String, ScreeningOutcome)>,
+    /// All of the conjunctions, ranked by increasing miss distance
+    pub events: Vec<ConjunctionEvent>,
+}
+
+impl ScreeningReport {
+    /// Returns the names of the objects removed by the apogee/perigee sieve
+    pub fn sieved(&self) -> Vec<&str> {
+        self.outcomes
+            .iter()
+            .filter(|(_, outcome)| *outcome == ScreeningOutcome::Sieved)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Returns the objects whose screening failed, and why
+    pub fn failed(&self) -> Vec<(&str, &str)> {
+        self.outcomes
+            .iter()
+            .filter_map(|(name, outcome)| match outcome {
+                ScreeningOutcome::Failed(why) => Some((name.as_str(), why.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the conjunctions with the provided secondary object, in chronological order
+    pub fn events_with(&self, secondary: &str) -> Vec<&ConjunctionEvent> {
+        let mut events: Vec<&ConjunctionEvent> = self
+            .events
+            .iter()
+            .filter(|event| event.secondary == secondary)
+            .collect();
+        events.sort_by_key(|event| event.tca);
+        events
+    }
+}
+
+impl fmt::Display for ScreeningReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Screening from {} to {} against {} objects ({} sieved, {} failed): {} conjunctions",
+            self.start,
+            self.end,
+            self.outcomes.len(),
+            self.sieved().len(),
+            self.failed().len(),
+            self.events.len()
+        )?;
+        for event in &self.events {
+            writeln!(f, "\t{event}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Screening of a primary ephemeris against a catalog of secondary ephemerides and TLEs, for close approach operations.
+///
+/// Each secondary object is first filtered with the apogee/perigee sieve on the osculating apsides at the start of the
+/// window. The remaining objects are then searched with a smart sieve: the relative distance is sampled at the configured
+/// step, but the search jumps ahead whenever the distance is so large that, given a bound of the relative speed, the threshold
+/// cannot be reached sooner. Each minimum of the relative distance (change of sign of the range rate between two samples) is
+/// refined by bisection, and reported if within the threshold. The objects are screened in parallel if the `parallel` feature
+/// is enabled.
+#[derive(Clone, Debug)]
+pub struct CatalogScreening {
+    pub config: ScreeningConfig,
+    pub secondaries: Vec<Secondary>,
+}
+
+impl CatalogScreening {
+    /// Builds a new screening, ensuring that the configuration is valid
+    pub fn new(config: ScreeningConfig, secondaries: Vec<Secondary>) -> Result<Self, NyxError> {
+        if config.threshold_km <= 0.0 || config.sieve_pad_km < 0.0 {
+            return Err(NyxError::CustomError(format!(
+                "screening threshold ({} km) must be positive and sieve pad ({} km) non-negative",
+                config.threshold_km, config.sieve_pad_km
+            )));
+        }
+        if config.step <= Duration::ZERO {
+            return Err(NyxError::CustomError(format!(
+                "screening step must be positive, got {}",
+                config.step
+            )));
+        }
+        Ok(Self {
+            config,
+            secondaries,
+        })
+    }
+
+    /// Builds a new screening against all of the ephemerides (`.oem` files, named after their object or file name) and TLE
+    /// catalogs (`.tle` and `.txt` files, with two or three line element sets) of the provided directory.
+    pub fn from_directory<P: AsRef<Path>>(
+        path: P,
+        config: ScreeningConfig,
+    ) -> Result<Self, NyxError> {
+        let entries = fs::read_dir(&path).map_err(|e| {
+            NyxError::CustomError(format!(
+                "could not read catalog directory {}: {e}",
+                path.as_ref().display()
+            ))
+        })?;
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect();
+        paths.sort();
+
+        let mut secondaries = Vec::new();
+        for path in paths {
+            let extension = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext.to_lowercase());
+            match extension.as_deref() {
+                Some("oem") => {
+                    let traj = Traj::<Orbit>::from_oem_file(&path)?;
+                    let name = traj.name.clone().unwrap_or_else(|| {
+                        path.file_stem()
+                            .map(|stem| stem.to_string_lossy().to_string())
+                            .unwrap_or_default()
+                    });
+                    secondaries.push(Secondary::ephemeris(name, traj));
+                }
+                Some("tle") | Some("txt") => {
+                    let text = fs::read_to_string(&path).map_err(|e| {
+                        NyxError::CustomError(format!("could not read {}: {e}", path.display()))
+                    })?;
+                    for (name, tle) in Tle::parse_many(&text)? {
+                        secondaries.push(Secondary::tle(name, tle));
+                    }
+                }
+                _ => debug!("ignoring {} in the catalog", path.display()),
+            }
+        }
+        Self::new(config, secondaries)
+    }
+
+    /// Screens the primary ephemeris against every secondary object over the span of the primary ephemeris.
+    pub fn screen(&self, primary: &Traj<Orbit>, cosm: &Cosm) -> Result<ScreeningReport, NyxError> {
+        let start = primary.first().epoch;
+        let end = primary.last().epoch;
+        if end <= start {
+            return Err(NyxError::CustomError(
+                "primary ephemeris must span a non-empty window".to_string(),
+            ));
+        }
+
+        #[cfg(feature = "parallel")]
+        let iter = self.secondaries.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let iter = self.secondaries.iter();
+
+        let outcomes: Vec<(String, ScreeningOutcome)> = iter
+            .map(|secondary| {
+                let outcome = self
+                    .screen_one(primary, secondary, start, end, cosm)
+                    .unwrap_or_else(|e| ScreeningOutcome::Failed(e.to_string()));
+                (secondary.name.clone(), outcome)
+            })
+            .collect();
+
+        let mut events: Vec<ConjunctionEvent> = outcomes
+            .iter()
+            .flat_map(|(_, outcome)| match outcome {
+                ScreeningOutcome::Screened { events, .. } => events.clone(),
+                _ => vec![],
+            })
+            .collect();
+        events.sort_by(|a, b| a.miss_distance_km.total_cmp(&b.miss_distance_km));
+
+        info!(
+            "screened {} objects: {} conjunctions within {} km",
+            outcomes.len(),
+            events.len(),
+            self.config.threshold_km
+        );
+
+        Ok(ScreeningReport {
+            start,
+            end,
+            outcomes,
+            events,
+        })
+    }
+
+    /// Screens the primary against a single secondary object
+    fn screen_one(
+        &self,
+        primary: &Traj<Orbit>,
+        secondary: &Secondary,
+        start: Epoch,
+        end: Epoch,
+        cosm: &Cosm,
+    ) -> Result<ScreeningOutcome, NyxError> {
+        let (start, end) = match secondary.span() {
+            Some((first, last)) => (start.max(first), end.min(last)),
+            None => (start, end),
+        };
+        if end <= start {
+            return Ok(ScreeningOutcome::OutOfWindow);
+        }
+
+        let frame = primary.first().frame;
+        let primary_start = primary.at(start)?;
+        let secondary_start = secondary.at(start, frame, cosm)?;
+
+        // Apogee/perigee sieve, only for closed orbits
+        if primary_start.ecc() < 1.0 && secondary_start.ecc() < 1.0 {
+            let gap_km = primary_start
+                .periapsis_km()
+                .max(secondary_start.periapsis_km())
+                - primary_start
+                    .apoapsis_km()
+                    .min(secondary_start.apoapsis_km());
+            if gap_km > self.config.threshold_km + self.config.sieve_pad_km {
+                return Ok(ScreeningOutcome::Sieved);
+            }
+        }
+
+        // Upper bound of the relative speed: the sum of the speeds at periapsis
+        let speed_bound_km_s = SPEED_BOUND_MARGIN
+            * (max_speed_km_s(&primary_start) + max_speed_km_s(&secondary_start));
+
+        let mut num_evaluations = 0;
+        let mut relative_at =
+            |epoch: Epoch| -> Result<(Orbit, Vector3<f64>, Vector3<f64>), NyxError> {
+                num_evaluations += 1;
+                let primary_state = primary.at(epoch)?;
+                let secondary_state = secondary.at(epoch, frame, cosm)?;
+                Ok((
+                    primary_state,
+                    secondary_state.radius() - primary_state.radius(),
+                    secondary_state.velocity() - primary_state.velocity(),
+                ))
+            };
+
+        let step_s = self.config.step.to_seconds();
+        let mut events = Vec::new();
+        let mut previous: Option<(Epoch, f64)> = None;
+        let mut epoch = start;
+        loop {
+            let (_, dr, dv) = relative_at(epoch)?;
+            let distance_km = dr.norm();
+            let range_rate_km_s = dr.dot(&dv);
+
+            if distance_km - self.config.threshold_km > speed_bound_km_s * step_s {
+                // Smart sieve: the threshold cannot be reached before this jump, so no conjunction is missed.
+                previous = None;
+                if epoch >= end {
+                    break;
+                }
+                let jump_s = (distance_km - self.config.threshold_km) / speed_bound_km_s;
+                epoch = (epoch + jump_s * Unit::Second).min(end);
+                continue;
+            }
+
+            if let Some((prev_epoch, prev_rate)) = previous {
+                if prev_rate < 0.0 && range_rate_km_s >= 0.0 {
+                    // Refine the time of closest approach by bisection on the range rate
+                    let (mut lower, mut upper) = (prev_epoch, epoch);
+                    while (upper - lower).to_seconds() > TCA_PRECISION_S {
+                        let mid = lower + (upper - lower) * 0.5;
+                        let (_, dr, dv) = relative_at(mid)?;
+                        if dr.dot(&dv) < 0.0 {
+                            lower = mid;
+                        } else {
+                            upper = mid;
+                        }
+                    }
+                    let (primary_tca, dr, dv) = relative_at(upper)?;
+                    let miss_distance_km = dr.norm();
+                    if miss_distance_km <= self.config.threshold_km {
+                        let dcm = primary_tca.dcm_from_traj_frame(Frame::RIC)?;
+                        events.push(ConjunctionEvent {
+                            secondary: secondary.name.clone(),
+                            tca: upper,
+                            miss_distance_km,
+                            relative_speed_km_s: dv.norm(),
+                            miss_ric_km: dcm.transpose() * dr,
+                        });
+                    }
+                }
+            }
+
+            if epoch >= end {
+                break;
+            }
+            previous = Some((epoch, range_rate_km_s));
+            epoch = (epoch + self.config.step).min(end);
+        }
+
+        Ok(ScreeningOutcome::Screened {
+            num_evaluations,
+            events,
+        })
+    }
+}
+
+/// Keplerian speed at periapsis, or the current speed if larger (e.g. hyperbolic or perturbed orbits)
+fn max_speed_km_s(orbit: &Orbit) -> f64 {
+    let periapsis_speed_km_s =
+        (orbit.frame.gm() * (2.0 / orbit.periapsis_km() - 1.0 / orbit.sma_km())).sqrt();
+    if periapsis_speed_km_s.is_finite() {
+        periapsis_speed_km_s.max(orbit.vmag_km_s())
+    } else {
+        orbit.vmag_km_s()
+    }
+}
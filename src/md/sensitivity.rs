@@ -0,0 +1,188 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::dynamics::SpacecraftDynamics;
+use crate::errors::NyxError;
+use crate::linalg::{Const, DMatrix, Dyn, Matrix6, OMatrix, Vector6};
+use crate::md::StateParameter;
+use crate::propagators::{ErrorCtrl, Propagator};
+use crate::time::Duration;
+use crate::{Spacecraft, State};
+use std::fmt;
+
+/// Relative perturbation used for the central differences of each parameter
+const REL_PERTURBATION: f64 = 1e-6;
+
+/// Model parameters with respect to which the sensitivity of the trajectory can be computed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ModelParameter {
+    /// Coefficient of reflectivity of the spacecraft
+    Cr,
+    /// Coefficient of drag of the spacecraft
+    Cd,
+    /// Thrust level of the spacecraft thruster (N), not a parameter of the STM
+    Thrust,
+    /// Gravitational parameter of the central body of the integration frame (km^3/s^2), not a parameter of the STM
+    CentralBodyGM,
+}
+
+impl ModelParameter {
+    /// Returns the value of this parameter for the provided spacecraft
+    pub fn value(&self, sc: &Spacecraft) -> Result<f64, NyxError> {
+        match self {
+            Self::Cr => sc.value(StateParameter::Cr),
+            Self::Cd => sc.value(StateParameter::Cd),
+            Self::Thrust => sc.value(StateParameter::Thrust),
+            Self::CentralBodyGM => Ok(sc.orbit.frame.gm()),
+        }
+    }
+
+    /// Sets the value of this parameter on the provided spacecraft
+    pub fn set_value(&self, sc: &mut Spacecraft, val: f64) -> Result<(), NyxError> {
+        match self {
+            Self::Cr => sc.set_value(StateParameter::Cr, val),
+            Self::Cd => sc.set_value(StateParameter::Cd, val),
+            Self::Thrust => sc.set_value(StateParameter::Thrust, val),
+            Self::CentralBodyGM => {
+                sc.orbit.frame.gm_mut(val);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for ModelParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cr => write!(f, "Cr"),
+            Self::Cd => write!(f, "Cd"),
+            Self::Thrust => write!(f, "thrust (N)"),
+            Self::CentralBodyGM => write!(f, "GM (km^3/s^2)"),
+        }
+    }
+}
+
+/// Sensitivity of the final Cartesian state of a trajectory to a set of model parameters.
+///
+/// Each column of the matrix is the partial of the final [X, Y, Z, Vx, Vy, Vz] with respect to the parameter of the same index.
+///
+/// # Scope
+/// Only the coefficients of reflectivity and drag are parameters of the STM: they are columns 6 and 7 of the spacecraft STM,
+/// integrated with the analytical partials of the force models (cf. `ForceModel::param_partials`). The thrust level and the
+/// gravitational parameter are not part of the spacecraft state, and the STM is not augmented with them, so their partials
+/// are only available from this structure, computed by central differences. For consistency, all parameters are computed by
+/// central differences here, which also validates the Cr and Cd columns of the STM.
+///
+/// # Limitations
+/// + The partials with respect to the thrust level and the gravitational parameter cannot be propagated with the covariance.
+/// + Each parameter requires two additional propagations of the full trajectory.
+/// + The accuracy of the partials depends on the relative perturbation of the parameters (1e-6) and on the tolerance of the integrator:
+///   the propagator should use a tolerance well below the change in the final state caused by the perturbation.
+#[derive(Clone, Debug)]
+pub struct ParameterSensitivity {
+    /// Final nominal state
+    pub nominal: Spacecraft,
+    /// Parameters of each column of the sensitivity matrix
+    pub params: Vec<ModelParameter>,
+    /// Partials of the final Cartesian state with respect to each parameter
+    pub matrix: OMatrix<f64, Const<6>, Dyn>,
+}
+
+impl ParameterSensitivity {
+    /// Propagates the initial state for the provided duration and computes the sensitivity of the final state to each of the parameters.
+    pub fn compute<E: ErrorCtrl>(
+        prop: &Propagator<SpacecraftDynamics, E>,
+        init: Spacecraft,
+        duration: Duration,
+        params: &[ModelParameter],
+    ) -> Result<Self, NyxError> {
+        let mut init = init;
+        init.unset_stm();
+        init.orbit.stm = None;
+
+        let nominal = prop.with(init).for_duration(duration)?;
+
+        let mut matrix = OMatrix::<f64, Const<6>, Dyn>::zeros(params.len());
+
+        for (col, param) in params.iter().enumerate() {
+            let nominal_val = param.value(&init)?;
+            let pert = REL_PERTURBATION * nominal_val.abs().max(1.0);
+
+            let mut plus = init;
+            param.set_value(&mut plus, nominal_val + pert)?;
+            let plus_vec = prop
+                .with(plus)
+                .for_duration(duration)?
+                .orbit
+                .to_cartesian_vec();
+
+            let mut minus = init;
+            param.set_value(&mut minus, nominal_val - pert)?;
+            let minus_vec = prop
+                .with(minus)
+                .for_duration(duration)?
+                .orbit
+                .to_cartesian_vec();
+
+            matrix.set_column(col, &((plus_vec - minus_vec) / (2.0 * pert)));
+        }
+
+        Ok(Self {
+            nominal,
+            params: params.to_vec(),
+            matrix,
+        })
+    }
+
+    /// Returns the partials of the final state with respect to the provided parameter, if it was computed.
+    pub fn partials(&self, param: ModelParameter) -> Option<Vector6<f64>> {
+        self.params
+            .iter()
+            .position(|p| *p == param)
+            .map(|col| self.matrix.column(col).into_owned())
+    }
+
+    /// Maps the covariance of the parameters (in the same order as the parameters) into the covariance of the final Cartesian state.
+    pub fn map_covariance(&self, param_covar: &DMatrix<f64>) -> Result<Matrix6<f64>, NyxError> {
+        if param_covar.nrows() != self.params.len() || param_covar.ncols() != self.params.len() {
+            return Err(NyxError::CustomError(format!(
+                "parameter covariance must be {0}x{0} but is {1}x{2}",
+                self.params.len(),
+                param_covar.nrows(),
+                param_covar.ncols()
+            )));
+        }
+
+        let mapped = &self.matrix * param_covar * self.matrix.transpose();
+        Ok(Matrix6::from_iterator(mapped.iter().copied()))
+    }
+}
+
+impl fmt::Display for ParameterSensitivity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Parameter sensitivity at {}", self.nominal.epoch())?;
+        for (col, param) in self.params.iter().enumerate() {
+            writeln!(
+                f,
+                "\t∂x/∂{param} = {:e}",
+                self.matrix.column(col).transpose()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,505 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::Cosm;
+use crate::dynamics::{ForceModel, SolarPressure};
+use crate::errors::NyxError;
+use crate::linalg::{Matrix3, Matrix3x2, Vector3};
+use crate::md::ScTraj;
+use crate::od::simulator::EpochRanges;
+use crate::od::GroundStation;
+use crate::time::{Duration, Epoch};
+use crate::utils::rotv;
+use crate::Spacecraft;
+use std::f64::consts::TAU;
+use std::fmt;
+use std::sync::Arc;
+
+/// Number of samples of the nutation cone used to average the projected area
+const NUTATION_SAMPLES: usize = 8;
+
+/// A torque acting on a spin-stabilized spacecraft, which precesses its spin axis.
+pub trait SpinTorque: Send + Sync + fmt::Display {
+    /// Returns the torque (N·m) in the integration frame of the spacecraft, for the provided spinner and spin state
+    fn torque(
+        &self,
+        sc: &Spacecraft,
+        spinner: &SpinStabilized,
+        spin: &SpinState,
+    ) -> Result<Vector3<f64>, NyxError>;
+}
+
+/// A constant torque in the integration frame, e.g. from a misaligned thruster
+#[derive(Copy, Clone, Debug)]
+pub struct ConstantTorque {
+    /// in N·m
+    pub torque_n_m: Vector3<f64>,
+}
+
+impl SpinTorque for ConstantTorque {
+    fn torque(
+        &self,
+        _sc: &Spacecraft,
+        _spinner: &SpinStabilized,
+        _spin: &SpinState,
+    ) -> Result<Vector3<f64>, NyxError> {
+        Ok(self.torque_n_m)
+    }
+}
+
+impl fmt::Display for ConstantTorque {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "constant torque [{:.3e}, {:.3e}, {:.3e}] N·m",
+            self.torque_n_m[0], self.torque_n_m[1], self.torque_n_m[2]
+        )
+    }
+}
+
+/// Torque of the solar radiation pressure applied at the center of pressure, offset from the center of mass along the spin axis
+#[derive(Clone)]
+pub struct SrpTorque {
+    pub srp: Arc<SolarPressure>,
+    /// Offset of the center of pressure along the spin axis (m)
+    pub cp_offset_m: f64,
+}
+
+impl SpinTorque for SrpTorque {
+    fn torque(
+        &self,
+        sc: &Spacecraft,
+        spinner: &SpinStabilized,
+        spin: &SpinState,
+    ) -> Result<Vector3<f64>, NyxError> {
+        let mut unit = *sc;
+        unit.srp.area_m2 = 1.0;
+        let unit_force = self.srp.eom(&unit)?;
+        if unit_force.norm() < f64::EPSILON {
+            // In eclipse
+            return Ok(Vector3::zeros());
+        }
+        let area_m2 = spinner.averaged_area_m2(&spin.spin_axis, spin.nutation_deg, &unit_force);
+        // The force models return kg·km/s^2
+        let force_n = unit_force * area_m2 * 1e3;
+        Ok((spin.spin_axis * self.cp_offset_m).cross(&force_n))
+    }
+}
+
+impl fmt::Display for SrpTorque {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SRP torque with a center of pressure {} m along the spin axis",
+            self.cp_offset_m
+        )
+    }
+}
+
+/// Orientation of the spin axis of a spin-stabilized spacecraft at an epoch
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpinState {
+    pub epoch: Epoch,
+    /// Unit vector of the angular momentum, in the integration frame of the trajectory
+    pub spin_axis: Vector3<f64>,
+    /// Half angle of the nutation cone of the body axis about the angular momentum (deg)
+    pub nutation_deg: f64,
+}
+
+impl SpinState {
+    pub fn new(epoch: Epoch, spin_axis: Vector3<f64>, nutation_deg: f64) -> Self {
+        Self {
+            epoch,
+            spin_axis: spin_axis.normalize(),
+            nutation_deg,
+        }
+    }
+}
+
+impl fmt::Display for SpinState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: spin axis [{:.6}, {:.6}, {:.6}], nutation {:.3} deg",
+            self.epoch, self.spin_axis[0], self.spin_axis[1], self.spin_axis[2], self.nutation_deg
+        )
+    }
+}
+
+/// A spin-stabilized spacecraft, modeled as a cylinder about its spin axis with its antenna boresight along that axis.
+///
+/// The spin is fast enough for the angular momentum to stay along the spin axis, which therefore precesses in the direction
+/// of the torques. The nutation is not excited by the torques and, if a damping time constant is set, decays exponentially.
+#[derive(Clone)]
+pub struct SpinStabilized {
+    /// Spin rate (deg/s)
+    pub spin_rate_deg_s: f64,
+    /// Moment of inertia about the spin axis (kg·m^2)
+    pub spin_inertia_kg_m2: f64,
+    /// Area of the ends of the cylinder, seen along the spin axis (m^2)
+    pub end_area_m2: f64,
+    /// Area of the side of the cylinder, seen perpendicular to the spin axis (m^2)
+    pub side_area_m2: f64,
+    /// Time constant of the nutation damper, if any
+    pub nutation_damping: Option<Duration>,
+    /// Half angle of the beam of the antenna, whose boresight is along the spin axis (deg)
+    pub antenna_half_beam_deg: f64,
+    pub torques: Vec<Arc<dyn SpinTorque>>,
+}
+
+impl SpinStabilized {
+    /// Initializes a spinner without nutation damping, torques, nor antenna constraint
+    pub fn new(
+        spin_rate_deg_s: f64,
+        spin_inertia_kg_m2: f64,
+        end_area_m2: f64,
+        side_area_m2: f64,
+    ) -> Self {
+        Self {
+            spin_rate_deg_s,
+            spin_inertia_kg_m2,
+            end_area_m2,
+            side_area_m2,
+            nutation_damping: None,
+            antenna_half_beam_deg: 180.0,
+            torques: Vec::new(),
+        }
+    }
+
+    /// Sets the time constant of the nutation damper
+    pub fn with_nutation_damping(mut self, time_constant: Duration) -> Self {
+        self.nutation_damping = Some(time_constant);
+        self
+    }
+
+    /// Sets the half angle of the beam of the antenna along the spin axis (deg)
+    pub fn with_antenna(mut self, half_beam_deg: f64) -> Self {
+        self.antenna_half_beam_deg = half_beam_deg;
+        self
+    }
+
+    /// Adds a torque acting on the spin axis
+    pub fn with_torque(mut self, torque: Arc<dyn SpinTorque>) -> Self {
+        self.torques.push(torque);
+        self
+    }
+
+    /// Returns the angular momentum of the spin (N·m·s)
+    pub fn angular_momentum_n_m_s(&self) -> f64 {
+        self.spin_inertia_kg_m2 * self.spin_rate_deg_s.to_radians()
+    }
+
+    /// Returns the area of the cylinder projected along the provided direction when its body axis is along the provided axis (m^2)
+    pub fn projected_area_m2(&self, body_axis: &Vector3<f64>, direction: &Vector3<f64>) -> f64 {
+        let cos_angle = body_axis
+            .normalize()
+            .dot(&direction.normalize())
+            .clamp(-1.0, 1.0);
+        self.end_area_m2 * cos_angle.abs() + self.side_area_m2 * (1.0 - cos_angle.powi(2)).sqrt()
+    }
+
+    /// Returns the nutation after the provided elapsed time from the initial nutation
+    pub fn nutation_deg(&self, initial_deg: f64, elapsed: Duration) -> f64 {
+        match self.nutation_damping {
+            Some(tau) => initial_deg * (-elapsed.to_seconds() / tau.to_seconds()).exp(),
+            None => initial_deg,
+        }
+    }
+
+    /// Returns the rate of change of the spin axis under the torques, in the integration frame (1/s)
+    fn axis_rate(&self, sc: &Spacecraft, spin: &SpinState) -> Result<Vector3<f64>, NyxError> {
+        let mut torque = Vector3::zeros();
+        for model in &self.torques {
+            torque += model.torque(sc, self, spin)?;
+        }
+        // Only the component perpendicular to the angular momentum changes its direction
+        let axis = spin.spin_axis;
+        Ok((torque - axis * torque.dot(&axis)) / self.angular_momentum_n_m_s())
+    }
+
+    /// Returns the area projected along the provided direction, averaged over the nutation cone about the spin axis (m^2)
+    pub fn averaged_area_m2(
+        &self,
+        spin_axis: &Vector3<f64>,
+        nutation_deg: f64,
+        direction: &Vector3<f64>,
+    ) -> f64 {
+        if nutation_deg.abs() < f64::EPSILON {
+            return self.projected_area_m2(spin_axis, direction);
+        }
+        let perp = perpendicular(spin_axis);
+        (0..NUTATION_SAMPLES)
+            .map(|k| {
+                let tilt_axis = rotv(&perp, spin_axis, TAU * k as f64 / NUTATION_SAMPLES as f64);
+                let body_axis = rotv(spin_axis, &tilt_axis, nutation_deg.to_radians());
+                self.projected_area_m2(&body_axis, direction)
+            })
+            .sum::<f64>()
+            / NUTATION_SAMPLES as f64
+    }
+
+    /// Propagates the spin axis along the trajectory from the initial spin state until the end of the trajectory, with a fixed
+    /// step fourth order Runge Kutta.
+    ///
+    /// The trajectory provides the states used to compute the torques, so a change of the projected areas of the forces
+    /// requires propagating the trajectory again with the returned profile.
+    pub fn propagate(
+        &self,
+        initial: SpinState,
+        traj: &ScTraj,
+        step: Duration,
+    ) -> Result<SpinProfile, NyxError> {
+        if self.spin_rate_deg_s <= 0.0 || self.spin_inertia_kg_m2 <= 0.0 {
+            return Err(NyxError::CustomError(format!(
+                "spin rate ({} deg/s) and inertia ({} kg·m^2) must be positive",
+                self.spin_rate_deg_s, self.spin_inertia_kg_m2
+            )));
+        }
+        if step <= Duration::ZERO {
+            return Err(NyxError::CustomError(format!(
+                "invalid propagation step of {step}"
+            )));
+        }
+        let end = traj.last().orbit.epoch;
+        let mut states = vec![initial];
+        let mut axis = initial.spin_axis.normalize();
+        let mut epoch = initial.epoch;
+        while epoch < end {
+            let this_step = if epoch + step > end {
+                end - epoch
+            } else {
+                step
+            };
+            let h = this_step.to_seconds();
+            let nutation_at =
+                |at: Epoch| self.nutation_deg(initial.nutation_deg, at - initial.epoch);
+            let rate_at = |at: Epoch, axis: Vector3<f64>| -> Result<Vector3<f64>, NyxError> {
+                self.axis_rate(&traj.at(at)?, &SpinState::new(at, axis, nutation_at(at)))
+            };
+            let k1 = rate_at(epoch, axis)?;
+            let k2 = rate_at(epoch + 0.5 * this_step, axis + k1 * (0.5 * h))?;
+            let k3 = rate_at(epoch + 0.5 * this_step, axis + k2 * (0.5 * h))?;
+            let k4 = rate_at(epoch + this_step, axis + k3 * h)?;
+            axis = (axis + (k1 + 2.0 * k2 + 2.0 * k3 + k4) * (h / 6.0)).normalize();
+            epoch += this_step;
+            states.push(SpinState::new(epoch, axis, nutation_at(epoch)));
+        }
+
+        Ok(SpinProfile {
+            model: self.clone(),
+            states,
+        })
+    }
+}
+
+impl fmt::Display for SpinStabilized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "spinner at {} deg/s (H = {:.3} N·m·s)",
+            self.spin_rate_deg_s,
+            self.angular_momentum_n_m_s()
+        )?;
+        if let Some(tau) = self.nutation_damping {
+            write!(f, " with nutation damping in {tau}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns a unit vector perpendicular to the provided one
+fn perpendicular(v: &Vector3<f64>) -> Vector3<f64> {
+    let other = if v[0].abs() < 0.9 {
+        Vector3::new(1.0, 0.0, 0.0)
+    } else {
+        Vector3::new(0.0, 1.0, 0.0)
+    };
+    v.cross(&other).normalize()
+}
+
+/// Spin axis and nutation of a spin-stabilized spacecraft along a trajectory
+#[derive(Clone)]
+pub struct SpinProfile {
+    pub model: SpinStabilized,
+    /// Spin states, in chronological order
+    pub states: Vec<SpinState>,
+}
+
+impl SpinProfile {
+    /// Returns the spin state at the provided epoch, linearly interpolated between the propagated states
+    pub fn state_at(&self, epoch: Epoch) -> Result<SpinState, NyxError> {
+        let first = self.states[0];
+        let last = self.states[self.states.len() - 1];
+        if epoch < first.epoch || epoch > last.epoch {
+            return Err(NyxError::NoInterpolationData(format!(
+                "{epoch} is outside of the spin profile from {} to {}",
+                first.epoch, last.epoch
+            )));
+        } else if self.states.len() == 1 {
+            return Ok(first);
+        }
+        let idx = self
+            .states
+            .windows(2)
+            .position(|w| epoch <= w[1].epoch)
+            .unwrap();
+        let (before, after) = (&self.states[idx], &self.states[idx + 1]);
+        let frac = (epoch - before.epoch).to_seconds() / (after.epoch - before.epoch).to_seconds();
+        Ok(SpinState::new(
+            epoch,
+            before.spin_axis + (after.spin_axis - before.spin_axis) * frac,
+            before.nutation_deg + (after.nutation_deg - before.nutation_deg) * frac,
+        ))
+    }
+
+    /// Returns the area projected along the provided direction at the provided epoch, averaged over the nutation cone (m^2)
+    pub fn projected_area_m2(
+        &self,
+        epoch: Epoch,
+        direction: &Vector3<f64>,
+    ) -> Result<f64, NyxError> {
+        let state = self.state_at(epoch)?;
+        Ok(self
+            .model
+            .averaged_area_m2(&state.spin_axis, state.nutation_deg, direction))
+    }
+
+    /// Returns whether the provided direction is within the antenna beam at the provided epoch, whatever the nutation phase
+    pub fn antenna_available(
+        &self,
+        epoch: Epoch,
+        direction: &Vector3<f64>,
+    ) -> Result<bool, NyxError> {
+        let state = self.state_at(epoch)?;
+        let off_boresight_deg = state
+            .spin_axis
+            .dot(&direction.normalize())
+            .clamp(-1.0, 1.0)
+            .acos()
+            .to_degrees();
+        Ok(off_boresight_deg + state.nutation_deg <= self.model.antenna_half_beam_deg)
+    }
+
+    /// Returns the epoch ranges when the station is outside the antenna beam, sampled along the trajectory at the provided step,
+    /// to be used as the exclusion epochs of the tracking configuration of that station.
+    ///
+    /// Each range is widened by half a step on both sides, so that it is at least a step long.
+    pub fn antenna_exclusions(
+        &self,
+        traj: &ScTraj,
+        station: &GroundStation,
+        step: Duration,
+        cosm: &Cosm,
+    ) -> Result<Vec<EpochRanges>, NyxError> {
+        let mut exclusions = Vec::new();
+        let mut current: Option<EpochRanges> = None;
+        for sc in traj.every(step) {
+            let epoch = sc.orbit.epoch;
+            let tx = cosm.try_frame_chg(&station.to_orbit(epoch), sc.orbit.frame)?;
+            if self.antenna_available(epoch, &(tx.radius() - sc.orbit.radius()))? {
+                if let Some(range) = current.take() {
+                    exclusions.push(range);
+                }
+            } else {
+                match current.as_mut() {
+                    Some(range) => range.end = epoch + 0.5 * step,
+                    None => {
+                        current = Some(EpochRanges {
+                            start: epoch - 0.5 * step,
+                            end: epoch + 0.5 * step,
+                        })
+                    }
+                }
+            }
+        }
+        if let Some(range) = current {
+            exclusions.push(range);
+        }
+        Ok(exclusions)
+    }
+}
+
+impl fmt::Display for SpinProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} from {} to {}",
+            self.model,
+            self.states[0],
+            self.states[self.states.len() - 1]
+        )
+    }
+}
+
+/// Projects a cannonball force model (e.g. SRP or drag) on the cylinder of a spin-stabilized spacecraft: the area of the
+/// spacecraft is replaced by the area of the cylinder projected along the direction of the force.
+///
+/// The partials of the force model are scaled by the same area, i.e. the change of the projected area with the state is neglected.
+#[derive(Clone)]
+pub struct SpinProjected {
+    pub model: Arc<dyn ForceModel>,
+    pub profile: Arc<SpinProfile>,
+}
+
+impl SpinProjected {
+    pub fn new(model: Arc<dyn ForceModel>, profile: Arc<SpinProfile>) -> Arc<Self> {
+        Arc::new(Self { model, profile })
+    }
+
+    /// Returns the spacecraft with a unit area for both the SRP and the drag
+    fn unit_area(ctx: &Spacecraft) -> Spacecraft {
+        let mut unit = *ctx;
+        unit.srp.area_m2 = 1.0;
+        unit.drag.area_m2 = 1.0;
+        unit
+    }
+
+    /// Returns the projected area along the provided force, which is zero if the force is (e.g. in eclipse)
+    fn area_along(&self, ctx: &Spacecraft, force: &Vector3<f64>) -> Result<f64, NyxError> {
+        if force.norm() < f64::EPSILON {
+            Ok(0.0)
+        } else {
+            self.profile.projected_area_m2(ctx.orbit.epoch, force)
+        }
+    }
+}
+
+impl ForceModel for SpinProjected {
+    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        let force = self.model.eom(&Self::unit_area(ctx))?;
+        Ok(force * self.area_along(ctx, &force)?)
+    }
+
+    fn dual_eom(&self, ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+        let (force, grad) = self.model.dual_eom(&Self::unit_area(ctx))?;
+        let area_m2 = self.area_along(ctx, &force)?;
+        Ok((force * area_m2, grad * area_m2))
+    }
+
+    fn param_partials(&self, ctx: &Spacecraft) -> Result<Matrix3x2<f64>, NyxError> {
+        let unit = Self::unit_area(ctx);
+        let area_m2 = self.area_along(ctx, &self.model.eom(&unit)?)?;
+        Ok(self.model.param_partials(&unit)? * area_m2)
+    }
+}
+
+impl fmt::Display for SpinProjected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} projected on a {}", self.model, self.profile.model)
+    }
+}
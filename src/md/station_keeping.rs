@@ -0,0 +1,448 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::trajectory::Traj;
+use crate::cosmic::{Cosm, Frame, LightTimeCalc, Orbit};
+use crate::dynamics::OrbitalDynamics;
+use crate::errors::NyxError;
+use crate::linalg::{Vector2, Vector3};
+use crate::propagators::Propagator;
+use crate::time::{Duration, Epoch, Unit};
+use std::f64::consts::{PI, TAU};
+use std::fmt;
+use std::sync::Arc;
+
+/// Rotation rate of the Earth, in rad/s
+pub(crate) const EARTH_ROTATION_RAD_S: f64 = 7.292_115_146_7e-5;
+/// Fraction of the inclination deadband targeted on the opposite side of the drift, as a margin for prediction errors
+const INC_TARGET_RATIO: f64 = 0.9;
+
+/// The station-keeping box of a geostationary slot.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GeoSlot {
+    /// Geodetic longitude of the center of the slot, in degrees
+    pub longitude_deg: f64,
+    /// Half width of the longitude deadband, in degrees
+    pub lon_deadband_deg: f64,
+    /// Maximum inclination, in degrees
+    pub max_inc_deg: f64,
+    /// Radius of the control circle of the eccentricity vector, whose perigee is kept pointing towards the Sun
+    pub ecc_control_radius: f64,
+}
+
+impl GeoSlot {
+    pub fn new(
+        longitude_deg: f64,
+        lon_deadband_deg: f64,
+        max_inc_deg: f64,
+        ecc_control_radius: f64,
+    ) -> Self {
+        Self {
+            longitude_deg,
+            lon_deadband_deg,
+            max_inc_deg,
+            ecc_control_radius,
+        }
+    }
+
+    /// Radius of the yearly circle of the eccentricity vector under solar radiation pressure (Soop, Handbook of Geostationary
+    /// Orbits, 1994), i.e. 0.011 Cr A/m, with the area to mass ratio in m^2/kg. Using it as the control radius of the sun-pointing
+    /// perigee strategy minimizes the East-West budget.
+    pub fn natural_ecc_radius(cr: f64, area_to_mass_m2_kg: f64) -> f64 {
+        0.011 * cr * area_to_mass_m2_kg
+    }
+}
+
+impl fmt::Display for GeoSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GEO slot at {:.3} deg ± {:.3} deg, inc <= {:.3} deg, ecc control radius {:.2e}",
+            self.longitude_deg, self.lon_deadband_deg, self.max_inc_deg, self.ecc_control_radius
+        )
+    }
+}
+
+/// Kind of a station-keeping maneuver
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SkKind {
+    /// Out of plane maneuver controlling the inclination vector
+    NorthSouth,
+    /// Along track maneuver controlling the longitude drift and the eccentricity vector
+    EastWest,
+}
+
+impl fmt::Display for SkKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NorthSouth => write!(f, "N/S"),
+            Self::EastWest => write!(f, "E/W"),
+        }
+    }
+}
+
+/// An impulsive station-keeping maneuver
+#[derive(Copy, Clone, Debug)]
+pub struct SkManeuver {
+    pub epoch: Epoch,
+    pub kind: SkKind,
+    /// Magnitude of the plane change (N/S), or signed magnitude along the velocity (E/W), in km/s
+    pub dv_km_s: f64,
+    /// Delta-V in the inertial frame of the orbit, in km/s
+    pub dv_inertial_km_s: Vector3<f64>,
+}
+
+impl fmt::Display for SkManeuver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} maneuver of {:.4} m/s",
+            self.epoch,
+            self.kind,
+            self.dv_km_s * 1e3
+        )
+    }
+}
+
+/// The station-keeping maneuvers of a GEO spacecraft and the controlled trajectory flown with them.
+#[derive(Clone)]
+pub struct SkPlan {
+    pub slot: GeoSlot,
+    pub maneuvers: Vec<SkManeuver>,
+    /// Controlled trajectory, which includes the post-maneuver states
+    pub traj: Traj<Orbit>,
+    /// Largest longitude error from the center of the slot over the controlled trajectory, in degrees
+    pub max_lon_error_deg: f64,
+    /// Largest inclination over the controlled trajectory, in degrees
+    pub max_inc_deg: f64,
+    /// Largest eccentricity over the controlled trajectory
+    pub max_ecc: f64,
+}
+
+impl SkPlan {
+    /// Duration of the plan
+    pub fn duration(&self) -> Duration {
+        self.traj.last().epoch - self.traj.first().epoch
+    }
+
+    /// Total delta-V of the maneuvers of the provided kind, in km/s
+    pub fn dv_km_s(&self, kind: SkKind) -> f64 {
+        self.maneuvers
+            .iter()
+            .filter(|mnvr| mnvr.kind == kind)
+            .map(|mnvr| mnvr.dv_km_s.abs())
+            .sum()
+    }
+
+    /// Delta-V budgets per year of the North-South and East-West maneuvers, in m/s
+    pub fn annual_budget_m_s(&self) -> (f64, f64) {
+        let years = self.duration().to_unit(Unit::Day) / 365.25;
+        (
+            self.dv_km_s(SkKind::NorthSouth) * 1e3 / years,
+            self.dv_km_s(SkKind::EastWest) * 1e3 / years,
+        )
+    }
+
+    /// Returns whether the controlled trajectory remained within the longitude and inclination deadbands of the slot
+    pub fn within_slot(&self) -> bool {
+        self.max_lon_error_deg <= self.slot.lon_deadband_deg
+            && self.max_inc_deg <= self.slot.max_inc_deg
+    }
+}
+
+impl fmt::Display for SkPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (ns_m_s, ew_m_s) = self.annual_budget_m_s();
+        writeln!(
+            f,
+            "{} over {} ({}): {} maneuvers, N/S = {:.3} m/s/yr, E/W = {:.3} m/s/yr",
+            self.slot,
+            self.duration(),
+            if self.within_slot() {
+                "within slot"
+            } else {
+                "OUTSIDE slot"
+            },
+            self.maneuvers.len(),
+            ns_m_s,
+            ew_m_s
+        )?;
+        writeln!(
+            f,
+            "max longitude error = {:.4} deg\tmax inc = {:.4} deg\tmax ecc = {:.3e}",
+            self.max_lon_error_deg, self.max_inc_deg, self.max_ecc
+        )?;
+        for mnvr in &self.maneuvers {
+            writeln!(f, "{mnvr}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Plans the station-keeping of a geostationary spacecraft cycle by cycle.
+///
+/// At the start of each cycle, the orbit is propagated without control to predict the natural drift of its longitude, inclination
+/// vector and eccentricity vector over the cycle. Then:
+/// + if the predicted inclination leaves the deadband, a North-South maneuver targets the inclination vector on the edge of the
+///   deadband opposite to its drift, so that it crosses the whole deadband before the next maneuver;
+/// + a pair of East-West maneuvers half a day apart targets the center of the slot at the end of the cycle, and the eccentricity
+///   vector on the control circle towards the Sun in the middle of the cycle (sun-pointing perigee strategy).
+///
+/// The maneuvers are then flown with the dynamics, so the resulting plan is validated on the controlled trajectory.
+///
+/// **Note:** the orbits must be in an Earth centered inertial frame whose XY plane is the equator, e.g. EME2000.
+#[derive(Clone)]
+pub struct GeoStationKeeping {
+    pub slot: GeoSlot,
+    pub dynamics: OrbitalDynamics,
+    /// Duration of each station-keeping cycle
+    pub cycle: Duration,
+    pub cosm: Arc<Cosm>,
+    body_fixed: Frame,
+    sun: Frame,
+}
+
+impl GeoStationKeeping {
+    /// Initializes a planner with two week cycles
+    pub fn new(slot: GeoSlot, dynamics: OrbitalDynamics, cosm: Arc<Cosm>) -> Self {
+        Self {
+            slot,
+            dynamics,
+            cycle: 14 * Unit::Day,
+            body_fixed: cosm.frame("IAU Earth"),
+            sun: cosm.frame("Sun J2000"),
+            cosm,
+        }
+    }
+
+    /// Sets the duration of each cycle, which must be longer than a few days to leave time for the maneuvers
+    pub fn with_cycle(mut self, cycle: Duration) -> Self {
+        self.cycle = cycle;
+        self
+    }
+
+    /// Returns the inclination vector of the orbit, i.e. i (cos Ω, sin Ω), with i in radians
+    pub fn inclination_vector(orbit: &Orbit) -> Vector2<f64> {
+        let h = orbit.hvec() / orbit.hmag_km2_s();
+        let sin_inc = (h[0].powi(2) + h[1].powi(2)).sqrt();
+        if sin_inc < f64::EPSILON {
+            Vector2::zeros()
+        } else {
+            Vector2::new(-h[1], h[0]) * (sin_inc.asin() / sin_inc)
+        }
+    }
+
+    /// Returns the eccentricity vector of the orbit in the equatorial plane, i.e. e (cos ϖ, sin ϖ), with ϖ = Ω + ω
+    pub fn eccentricity_vector(orbit: &Orbit) -> Vector2<f64> {
+        let evec = orbit.evec();
+        Vector2::new(evec[0], evec[1])
+    }
+
+    /// Returns the longitude of the orbit with respect to the center of the slot, in degrees between -180 and 180
+    pub fn longitude_error_deg(&self, orbit: &Orbit) -> f64 {
+        let longitude_deg = self
+            .cosm
+            .frame_chg(orbit, self.body_fixed)
+            .geodetic_longitude_deg();
+        wrap_pi((longitude_deg - self.slot.longitude_deg).to_radians()).to_degrees()
+    }
+
+    /// Plans and flies the station-keeping maneuvers from the initial orbit for the provided duration
+    pub fn plan(&self, initial: Orbit, duration: Duration) -> Result<SkPlan, NyxError> {
+        if self.cycle < 2 * Unit::Day {
+            return Err(NyxError::CustomError(format!(
+                "station-keeping cycle must be at least two days but got {}",
+                self.cycle
+            )));
+        }
+
+        let end = initial.epoch + duration;
+        let mut state = initial;
+        let mut traj = Traj::new();
+        traj.states.push(initial);
+        let mut maneuvers = Vec::new();
+
+        while state.epoch < end {
+            let cycle_end = (state.epoch + self.cycle).min(end);
+            for (epoch, kind, dv_km_s) in self.plan_cycle(&state)? {
+                if epoch >= cycle_end {
+                    break;
+                }
+                state = self.fly(state, epoch, &mut traj)?;
+                let v = state.velocity();
+                let dv_inertial_km_s = match kind {
+                    SkKind::NorthSouth => {
+                        // Rotate the velocity about the radius vector, so that the plane change does not change the energy,
+                        // and therefore the longitude drift.
+                        let r_hat = state.radius() / state.rmag_km();
+                        let angle_rad = 2.0 * (0.5 * dv_km_s / state.vmag_km_s()).asin();
+                        v * (angle_rad.cos() - 1.0)
+                            + r_hat.cross(&v) * angle_rad.sin()
+                            + r_hat * r_hat.dot(&v) * (1.0 - angle_rad.cos())
+                    }
+                    SkKind::EastWest => v / state.vmag_km_s() * dv_km_s,
+                };
+                let mnvr = SkManeuver {
+                    epoch,
+                    kind,
+                    dv_km_s,
+                    dv_inertial_km_s,
+                };
+                debug!("{mnvr}");
+                state.apply_dv(mnvr.dv_inertial_km_s);
+                maneuvers.push(mnvr);
+            }
+            state = self.fly(state, cycle_end, &mut traj)?;
+        }
+
+        let mut max_lon_error_deg: f64 = 0.0;
+        let mut max_inc_deg: f64 = 0.0;
+        let mut max_ecc: f64 = 0.0;
+        for orbit in &traj.states {
+            max_lon_error_deg = max_lon_error_deg.max(self.longitude_error_deg(orbit).abs());
+            max_inc_deg = max_inc_deg.max(orbit.inc_deg());
+            max_ecc = max_ecc.max(orbit.ecc());
+        }
+
+        let plan = SkPlan {
+            slot: self.slot,
+            maneuvers,
+            traj,
+            max_lon_error_deg,
+            max_inc_deg,
+            max_ecc,
+        };
+        info!("{plan}");
+        Ok(plan)
+    }
+
+    /// Propagates the orbit until the provided epoch, and appends the states to the controlled trajectory
+    fn fly(&self, state: Orbit, epoch: Epoch, traj: &mut Traj<Orbit>) -> Result<Orbit, NyxError> {
+        if epoch <= state.epoch {
+            return Ok(state);
+        }
+        let (next, segment) = Propagator::default(self.dynamics.clone())
+            .with(state)
+            .until_epoch_with_traj(epoch)?;
+        // Replace the pre-maneuver state with the post-maneuver one
+        if traj
+            .states
+            .last()
+            .is_some_and(|last| last.epoch == state.epoch)
+        {
+            traj.states.pop();
+        }
+        traj.states.extend(segment.states);
+        Ok(next)
+    }
+
+    /// Returns the maneuvers of the cycle starting at the provided state, chronologically, as the epoch, kind and signed
+    /// magnitude in km/s of each.
+    fn plan_cycle(&self, state: &Orbit) -> Result<Vec<(Epoch, SkKind, f64)>, NyxError> {
+        let (drift_end, drift_traj) = Propagator::default(self.dynamics.clone())
+            .with(*state)
+            .for_duration_with_traj(self.cycle)?;
+        let drift_mid = drift_traj.at(state.epoch + 0.5 * self.cycle)?;
+
+        let a_km = state.sma_km();
+        let v_km_s = (state.frame.gm() / a_km).sqrt();
+        let n_rad_s = v_km_s / a_km;
+        let lon_now_rad = state.y_km.atan2(state.x_km);
+        // Epoch at which the true longitude of the spacecraft will next be the provided one
+        let epoch_at = |lon_rad: f64, after: Duration| {
+            let ahead_rad = (lon_rad - lon_now_rad - n_rad_s * after.to_seconds()).rem_euclid(TAU);
+            state.epoch + after + (ahead_rad / n_rad_s) * Unit::Second
+        };
+
+        let mut burns = Vec::new();
+
+        // North-South: target the inclination vector on the edge of the deadband opposite to its drift
+        let inc_now = Self::inclination_vector(state);
+        let inc_end = Self::inclination_vector(&drift_end);
+        let max_inc_rad = self.slot.max_inc_deg.to_radians();
+        if inc_end.norm() > INC_TARGET_RATIO * max_inc_rad {
+            let drift = inc_end - inc_now;
+            let target = if drift.norm() > f64::EPSILON {
+                -drift.normalize() * INC_TARGET_RATIO * max_inc_rad
+            } else {
+                Vector2::zeros()
+            };
+            let delta_inc = target - inc_now;
+            let lon_rad = delta_inc[1].atan2(delta_inc[0]);
+            burns.push((
+                epoch_at(lon_rad, Duration::ZERO),
+                SkKind::NorthSouth,
+                2.0 * v_km_s * (0.5 * delta_inc.norm()).sin(),
+            ));
+        }
+
+        // East-West: target the eccentricity vector towards the Sun in the middle of the cycle
+        let sun = self.cosm.celestial_state(
+            &self.sun.ephem_path(),
+            drift_mid.epoch,
+            state.frame,
+            LightTimeCalc::None,
+        );
+        let sun_dir = Vector2::new(sun.x_km, sun.y_km).normalize();
+        let delta_ecc =
+            sun_dir * self.slot.ecc_control_radius - Self::eccentricity_vector(&drift_mid);
+        // A pair of tangential maneuvers at l and l + π moves the eccentricity vector by 2 (Δv1 - Δv2) / V (cos l, sin l)
+        let ecc_lon_rad = delta_ecc[1].atan2(delta_ecc[0]);
+        let dv_diff_km_s = 0.5 * v_km_s * delta_ecc.norm();
+
+        // Each tangential Δv changes the longitude drift rate by -3 Δv / a: cancel the predicted longitude error at the end of the
+        // cycle, averaged over the last sidereal day to remove the daily libration due to the eccentricity.
+        let first = epoch_at(ecc_lon_rad, self.cycle.min(1 * Unit::Day));
+        let second = first + (PI / n_rad_s) * Unit::Second;
+        let last_day = drift_end.epoch - (TAU / EARTH_ROTATION_RAD_S) * Unit::Second;
+        let (mut err_rad, mut after_first_s, mut after_second_s) = (0.0, 0.0, 0.0);
+        let mut samples = 0;
+        for orbit in drift_traj
+            .states
+            .iter()
+            .filter(|orbit| orbit.epoch >= last_day)
+        {
+            err_rad += self.longitude_error_deg(orbit).to_radians();
+            after_first_s += (orbit.epoch - first).to_seconds().max(0.0);
+            after_second_s += (orbit.epoch - second).to_seconds().max(0.0);
+            samples += 1;
+        }
+        let samples = f64::from(samples);
+        let (err_rad, after_first_s, after_second_s) = (
+            err_rad / samples,
+            after_first_s / samples,
+            after_second_s / samples,
+        );
+        let dv_second_km_s = (a_km * err_rad / 3.0 - dv_diff_km_s * after_first_s)
+            / (after_first_s + after_second_s);
+        burns.push((first, SkKind::EastWest, dv_second_km_s + dv_diff_km_s));
+        burns.push((second, SkKind::EastWest, dv_second_km_s));
+
+        burns.sort_by_key(|(epoch, _, _)| *epoch);
+        Ok(burns)
+    }
+}
+
+/// Geostationary semi-major axis about the Earth with the provided gravitational parameter, in km
+pub fn geostationary_sma_km(gm_km3_s2: f64) -> f64 {
+    (gm_km3_s2 / EARTH_ROTATION_RAD_S.powi(2)).cbrt()
+}
+
+fn wrap_pi(angle_rad: f64) -> f64 {
+    (angle_rad + PI).rem_euclid(TAU) - PI
+}
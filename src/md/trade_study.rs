@@ -0,0 +1,529 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::errors::NyxError;
+#[cfg(feature = "io")]
+use crate::io::watermark::pq_writer;
+#[cfg(feature = "io")]
+use crate::io::ExportCfg;
+use crate::time::{Duration, Epoch, Unit};
+#[cfg(feature = "io")]
+use arrow::array::{Array, BooleanBuilder, Float64Builder, StringBuilder};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "io")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+#[cfg(feature = "io")]
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "io")]
+use std::fs::File;
+#[cfg(feature = "io")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "io")]
+use std::sync::Arc;
+
+/// How the values of a design variable are interpreted
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VariableKind {
+    /// Any scalar, e.g. a thrust level in Newtons
+    Scalar,
+    /// An epoch, stored in TAI seconds
+    Epoch,
+    /// A duration, stored in seconds
+    Duration,
+}
+
+/// A design variable of the trade study and the values it is swept over
+#[derive(Clone, Debug, PartialEq)]
+pub struct DesignVariable {
+    pub name: String,
+    pub kind: VariableKind,
+    pub values: Vec<f64>,
+}
+
+impl DesignVariable {
+    /// A scalar variable swept over the provided values
+    pub fn from_values(name: &str, values: Vec<f64>) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: VariableKind::Scalar,
+            values,
+        }
+    }
+
+    /// A scalar variable swept over `num` values evenly spaced between `min` and `max`, both included
+    pub fn linspace(name: &str, min: f64, max: f64, num: usize) -> Self {
+        let values = match num {
+            0 => Vec::new(),
+            1 => vec![min],
+            _ => (0..num)
+                .map(|i| min + (max - min) * (i as f64) / ((num - 1) as f64))
+                .collect(),
+        };
+        Self::from_values(name, values)
+    }
+
+    /// An epoch variable (e.g. the launch epoch) swept from `start` to `end` (included if on a step)
+    pub fn epochs(name: &str, start: Epoch, end: Epoch, step: Duration) -> Self {
+        let mut values = Vec::new();
+        let mut epoch = start;
+        while epoch <= end {
+            values.push(epoch.to_tai_seconds());
+            epoch += step;
+        }
+        Self {
+            name: name.to_string(),
+            kind: VariableKind::Epoch,
+            values,
+        }
+    }
+
+    /// A duration variable (e.g. the time of flight) swept from `min` to `max` (included if on a step)
+    pub fn durations(name: &str, min: Duration, max: Duration, step: Duration) -> Self {
+        let mut values = Vec::new();
+        let mut duration = min;
+        while duration <= max {
+            values.push(duration.to_seconds());
+            duration += step;
+        }
+        Self {
+            name: name.to_string(),
+            kind: VariableKind::Duration,
+            values,
+        }
+    }
+}
+
+/// Whether an objective should be minimized (e.g. delta-v) or maximized (e.g. delivered mass)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ObjectiveSense {
+    Minimize,
+    Maximize,
+}
+
+/// An objective of the trade study, as returned by the evaluator of each design point
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeObjective {
+    pub name: String,
+    pub sense: ObjectiveSense,
+}
+
+impl TradeObjective {
+    pub fn minimize(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            sense: ObjectiveSense::Minimize,
+        }
+    }
+
+    pub fn maximize(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            sense: ObjectiveSense::Maximize,
+        }
+    }
+}
+
+/// A combination of the values of the design variables, provided to the evaluator of the objectives
+#[derive(Clone, Debug, PartialEq)]
+pub struct DesignPoint<'a> {
+    variables: &'a [DesignVariable],
+    /// Value of each design variable, in the order of the trade study
+    pub values: Vec<f64>,
+}
+
+impl<'a> DesignPoint<'a> {
+    /// Value of the design variable with the provided name
+    pub fn value(&self, name: &str) -> Result<f64, NyxError> {
+        self.variables
+            .iter()
+            .position(|var| var.name == name)
+            .map(|idx| self.values[idx])
+            .ok_or_else(|| NyxError::CustomError(format!("no design variable named `{name}`")))
+    }
+
+    /// Value of the epoch design variable with the provided name
+    pub fn epoch(&self, name: &str) -> Result<Epoch, NyxError> {
+        Ok(Epoch::from_tai_seconds(self.value(name)?))
+    }
+
+    /// Value of the duration design variable with the provided name
+    pub fn duration(&self, name: &str) -> Result<Duration, NyxError> {
+        Ok(self.value(name)? * Unit::Second)
+    }
+}
+
+/// Full factorial trade study: every combination of the values of the design variables is evaluated, in parallel if
+/// the `parallel` feature is enabled, and the Pareto-optimal design points are extracted from the feasible ones.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeStudy {
+    pub variables: Vec<DesignVariable>,
+    pub objectives: Vec<TradeObjective>,
+}
+
+impl TradeStudy {
+    /// Builds a new trade study, ensuring that the variables and objectives are defined and uniquely named
+    pub fn new(
+        variables: Vec<DesignVariable>,
+        objectives: Vec<TradeObjective>,
+    ) -> Result<Self, NyxError> {
+        if variables.is_empty() || objectives.is_empty() {
+            return Err(NyxError::CustomError(
+                "a trade study requires at least one design variable and one objective".to_string(),
+            ));
+        }
+        for (i, var) in variables.iter().enumerate() {
+            if var.values.is_empty() {
+                return Err(NyxError::CustomError(format!(
+                    "design variable `{}` has no value",
+                    var.name
+                )));
+            } else if variables[..i].iter().any(|other| other.name == var.name) {
+                return Err(NyxError::CustomError(format!(
+                    "design variable `{}` is defined twice",
+                    var.name
+                )));
+            }
+        }
+        for (i, obj) in objectives.iter().enumerate() {
+            if objectives[..i].iter().any(|other| other.name == obj.name) {
+                return Err(NyxError::CustomError(format!(
+                    "objective `{}` is defined twice",
+                    obj.name
+                )));
+            }
+        }
+        Ok(Self {
+            variables,
+            objectives,
+        })
+    }
+
+    /// Number of design points evaluated by this trade study
+    pub fn num_points(&self) -> usize {
+        self.variables.iter().map(|var| var.values.len()).product()
+    }
+
+    /// Values of the design variables of the design point with the provided index, the first variable varying fastest
+    fn values_of(&self, mut idx: usize) -> Vec<f64> {
+        self.variables
+            .iter()
+            .map(|var| {
+                let value = var.values[idx % var.values.len()];
+                idx /= var.values.len();
+                value
+            })
+            .collect()
+    }
+
+    /// Evaluates the objectives of every design point with the provided evaluator, which returns the value of each
+    /// objective in the order of the trade study. Design points whose evaluation fails, or returns non finite values,
+    /// are infeasible and never Pareto-optimal.
+    pub fn run<F>(&self, evaluator: F) -> TradeResults
+    where
+        F: Fn(&DesignPoint) -> Result<Vec<f64>, NyxError> + Sync,
+    {
+        let num_points = self.num_points();
+        info!(
+            "Evaluating {num_points} design points of {} variables and {} objectives",
+            self.variables.len(),
+            self.objectives.len()
+        );
+
+        let evaluate = |idx: usize| {
+            let point = DesignPoint {
+                variables: &self.variables,
+                values: self.values_of(idx),
+            };
+            let (objectives, error) = match evaluator(&point) {
+                Ok(objectives) if objectives.len() != self.objectives.len() => (
+                    Vec::new(),
+                    Some(format!(
+                        "expected {} objectives but got {}",
+                        self.objectives.len(),
+                        objectives.len()
+                    )),
+                ),
+                Ok(objectives) if objectives.iter().any(|val| !val.is_finite()) => (
+                    Vec::new(),
+                    Some(format!("non finite objectives {objectives:?}")),
+                ),
+                Ok(objectives) => (objectives, None),
+                Err(e) => (Vec::new(), Some(e.to_string())),
+            };
+            TradePoint {
+                values: point.values,
+                objectives,
+                error,
+                pareto_optimal: false,
+            }
+        };
+
+        #[cfg(feature = "parallel")]
+        let mut points: Vec<TradePoint> = (0..num_points).into_par_iter().map(evaluate).collect();
+        #[cfg(not(feature = "parallel"))]
+        let mut points: Vec<TradePoint> = (0..num_points).map(evaluate).collect();
+
+        // Extract the non-dominated feasible points
+        let signs = self
+            .objectives
+            .iter()
+            .map(|obj| match obj.sense {
+                ObjectiveSense::Minimize => 1.0,
+                ObjectiveSense::Maximize => -1.0,
+            })
+            .collect::<Vec<f64>>();
+
+        let pareto = points
+            .iter()
+            .map(|point| {
+                point.is_feasible()
+                    && !points
+                        .iter()
+                        .filter(|other| other.is_feasible())
+                        .any(|other| dominates(&other.objectives, &point.objectives, &signs))
+            })
+            .collect::<Vec<bool>>();
+
+        for (point, pareto_optimal) in points.iter_mut().zip(pareto) {
+            point.pareto_optimal = pareto_optimal;
+        }
+
+        let results = TradeResults {
+            variables: self.variables.clone(),
+            objectives: self.objectives.clone(),
+            points,
+        };
+
+        info!(
+            "{} feasible design points, {} Pareto-optimal",
+            results.num_feasible(),
+            results.pareto_front().len()
+        );
+
+        results
+    }
+}
+
+/// Returns whether the objectives `a` dominate `b`: no worse in every objective and better in at least one
+fn dominates(a: &[f64], b: &[f64], signs: &[f64]) -> bool {
+    let mut better = false;
+    for ((a, b), sign) in a.iter().zip(b).zip(signs) {
+        if sign * a > sign * b {
+            return false;
+        } else if sign * a < sign * b {
+            better = true;
+        }
+    }
+    better
+}
+
+/// A design point evaluated by the trade study
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradePoint {
+    /// Value of each design variable
+    pub values: Vec<f64>,
+    /// Value of each objective, empty if the design point is infeasible
+    pub objectives: Vec<f64>,
+    /// Reason why the evaluation of this design point failed
+    pub error: Option<String>,
+    /// Whether no other feasible design point is at least as good in every objective and better in one
+    pub pareto_optimal: bool,
+}
+
+impl TradePoint {
+    pub fn is_feasible(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// All of the design points evaluated by a trade study, in the order of its full factorial sweep
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeResults {
+    pub variables: Vec<DesignVariable>,
+    pub objectives: Vec<TradeObjective>,
+    pub points: Vec<TradePoint>,
+}
+
+impl TradeResults {
+    /// Number of design points whose objectives were evaluated
+    pub fn num_feasible(&self) -> usize {
+        self.points
+            .iter()
+            .filter(|point| point.is_feasible())
+            .count()
+    }
+
+    /// The Pareto-optimal design points
+    pub fn pareto_front(&self) -> Vec<&TradePoint> {
+        self.points
+            .iter()
+            .filter(|point| point.pareto_optimal)
+            .collect()
+    }
+
+    /// The feasible design point with the best value of the provided objective, if any
+    pub fn best(&self, objective: &str) -> Option<&TradePoint> {
+        let idx = self
+            .objectives
+            .iter()
+            .position(|obj| obj.name == objective)?;
+        let sign = match self.objectives[idx].sense {
+            ObjectiveSense::Minimize => 1.0,
+            ObjectiveSense::Maximize => -1.0,
+        };
+        self.points
+            .iter()
+            .filter(|point| point.is_feasible())
+            .min_by(|a, b| (sign * a.objectives[idx]).total_cmp(&(sign * b.objectives[idx])))
+    }
+
+    /// Store every design point, its objectives and whether it is Pareto-optimal in a parquet file, e.g. for plotting
+    /// the Pareto front. Epoch variables are exported in UTC and duration variables in days.
+    #[cfg(feature = "io")]
+    pub fn to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let path_buf = cfg.actual_path(path);
+
+        let mut hdrs = Vec::new();
+        let mut record: Vec<Arc<dyn Array>> = Vec::new();
+
+        for (i, var) in self.variables.iter().enumerate() {
+            match var.kind {
+                VariableKind::Epoch => {
+                    hdrs.push(Field::new(
+                        format!("{} (UTC)", var.name),
+                        DataType::Utf8,
+                        false,
+                    ));
+                    let mut data = StringBuilder::new();
+                    for point in &self.points {
+                        data.append_value(format!("{}", Epoch::from_tai_seconds(point.values[i])));
+                    }
+                    record.push(Arc::new(data.finish()));
+                }
+                VariableKind::Duration => {
+                    hdrs.push(Field::new(
+                        format!("{} (days)", var.name),
+                        DataType::Float64,
+                        false,
+                    ));
+                    let mut data = Float64Builder::new();
+                    for point in &self.points {
+                        data.append_value((point.values[i] * Unit::Second).to_unit(Unit::Day));
+                    }
+                    record.push(Arc::new(data.finish()));
+                }
+                VariableKind::Scalar => {
+                    hdrs.push(Field::new(var.name.clone(), DataType::Float64, false));
+                    let mut data = Float64Builder::new();
+                    for point in &self.points {
+                        data.append_value(point.values[i]);
+                    }
+                    record.push(Arc::new(data.finish()));
+                }
+            }
+        }
+
+        for (i, obj) in self.objectives.iter().enumerate() {
+            hdrs.push(Field::new(obj.name.clone(), DataType::Float64, true));
+            let mut data = Float64Builder::new();
+            for point in &self.points {
+                data.append_option(point.objectives.get(i).copied());
+            }
+            record.push(Arc::new(data.finish()));
+        }
+
+        hdrs.push(Field::new("Pareto optimal", DataType::Boolean, false));
+        let mut pareto = BooleanBuilder::new();
+        hdrs.push(Field::new("Error", DataType::Utf8, true));
+        let mut errors = StringBuilder::new();
+        for point in &self.points {
+            pareto.append_value(point.pareto_optimal);
+            errors.append_option(point.error.as_ref());
+        }
+        record.push(Arc::new(pareto.finish()));
+        record.push(Arc::new(errors.finish()));
+
+        let mut metadata = HashMap::new();
+        metadata.insert("Purpose".to_string(), "Trade study".to_string());
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!("Trade study written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for TradeResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let front = self.pareto_front();
+        writeln!(
+            f,
+            "Trade study of {} design points ({} feasible), {} Pareto-optimal:",
+            self.points.len(),
+            self.num_feasible(),
+            front.len()
+        )?;
+        for point in front {
+            let values = self
+                .variables
+                .iter()
+                .zip(&point.values)
+                .map(|(var, val)| match var.kind {
+                    VariableKind::Epoch => {
+                        format!("{} = {}", var.name, Epoch::from_tai_seconds(*val))
+                    }
+                    VariableKind::Duration => format!("{} = {}", var.name, *val * Unit::Second),
+                    VariableKind::Scalar => format!("{} = {val}", var.name),
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            let objectives = self
+                .objectives
+                .iter()
+                .zip(&point.objectives)
+                .map(|(obj, val)| format!("{} = {val:.6}", obj.name))
+                .collect::<Vec<String>>()
+                .join(", ");
+            writeln!(f, "\t{values} => {objectives}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::time::Duration;
+
+/// Two-body extrapolation of a trajectory beyond its span or within its data gaps, cf. `Traj::at_or_extrapolate`.
+///
+/// This is meant for tools which probe a trajectory slightly beyond the edges of an arc (e.g. scheduling), not as a replacement for propagation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Extrapolation {
+    /// Maximum duration between the requested epoch and the closest state of the trajectory
+    pub max_duration: Duration,
+    /// Duration between two consecutive states above which the trajectory is considered to have a data gap, where it is extrapolated instead of interpolated
+    pub min_gap: Option<Duration>,
+}
+
+impl Extrapolation {
+    /// Allows extrapolating up to the provided duration beyond the edges of the trajectory
+    pub fn new(max_duration: Duration) -> Self {
+        Self {
+            max_duration,
+            min_gap: None,
+        }
+    }
+
+    /// Also extrapolates within the gaps of the trajectory, i.e. between consecutive states which are at least `min_gap` apart
+    pub fn with_min_gap(self, min_gap: Duration) -> Self {
+        Self {
+            min_gap: Some(min_gap),
+            ..self
+        }
+    }
+}
@@ -50,6 +50,9 @@ where
 
     /// Returns the orbit
     fn orbit(&self) -> &Orbit;
+
+    /// Sets the orbit of this state, e.g. after it was extrapolated
+    fn set_orbit(&mut self, orbit: Orbit);
 }
 
 impl Interpolatable for Orbit {
@@ -133,6 +136,9 @@ impl Interpolatable for Orbit {
                             | StateParameter::VY
                             | StateParameter::VZ
                             | StateParameter::HyperbolicAnomaly
+                            | StateParameter::Vinf
+                            | StateParameter::VinfDeclination
+                            | StateParameter::VinfRightAscension
                             | StateParameter::GeodeticHeight
                             | StateParameter::GeodeticLatitude
                             | StateParameter::GeodeticLongitude
@@ -157,6 +163,10 @@ impl Interpolatable for Orbit {
     fn orbit(&self) -> &Orbit {
         self
     }
+
+    fn set_orbit(&mut self, orbit: Orbit) {
+        *self = orbit;
+    }
 }
 
 impl Interpolatable for Spacecraft {
@@ -204,6 +214,9 @@ impl Interpolatable for Spacecraft {
                             | StateParameter::VY
                             | StateParameter::VZ
                             | StateParameter::HyperbolicAnomaly
+                            | StateParameter::Vinf
+                            | StateParameter::VinfDeclination
+                            | StateParameter::VinfRightAscension
                             | StateParameter::GeodeticHeight
                             | StateParameter::GeodeticLatitude
                             | StateParameter::GeodeticLongitude
@@ -233,4 +246,8 @@ impl Interpolatable for Spacecraft {
     fn orbit(&self) -> &Orbit {
         &self.orbit
     }
+
+    fn set_orbit(&mut self, orbit: Orbit) {
+        *self = self.with_orbit(orbit);
+    }
 }
@@ -16,15 +16,23 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+mod extrapolation;
 mod interpolatable;
 mod orbit_traj;
 mod sc_traj;
 mod traj;
 mod traj_it;
+mod traj_samples;
+mod traj_segment;
 
+pub use extrapolation::Extrapolation;
 pub use interpolatable::Interpolatable;
 pub(crate) use interpolatable::INTERPOLATION_SAMPLES;
 pub use traj::Traj;
+pub use traj_samples::TrajSamples;
+pub(crate) use traj_samples::SAMPLE_CHUNK_SIZE;
+pub(crate) use traj_segment::VERIFY_SPOT_CHECKS;
+pub use traj_segment::{InterpolationCheck, TrajSegment, TrajVerification};
 
 pub use crate::io::ExportCfg;
 
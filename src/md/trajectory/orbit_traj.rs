@@ -20,12 +20,21 @@ use super::TrajError;
 use super::{ExportCfg, Traj};
 use crate::cosmic::{Cosm, Frame, Orbit};
 use crate::errors::NyxError;
-use crate::io::watermark::prj_name_ver;
+use crate::io::ccsds::{
+    ccsds_center_ref_frame, ccsds_time_system, parse_ccsds_epoch, parse_ccsds_frame,
+    parse_time_system, OdmCovariance,
+};
+use crate::io::watermark::{prj_name_ver, Watermark};
+#[cfg(feature = "io")]
 use crate::md::prelude::StateParameter;
+#[cfg(feature = "io")]
 use crate::md::EventEvaluator;
-use crate::time::{Epoch, Format, Formatter, TimeUnits};
+use crate::time::{Epoch, Format, Formatter, TimeScale, TimeUnits};
 use crate::{Spacecraft, State};
-use std::collections::{HashMap, HashSet};
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+use std::collections::HashSet;
+#[cfg(feature = "io")]
 use std::error::Error;
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Write};
@@ -49,6 +58,7 @@ impl Traj<Orbit> {
         #[cfg(not(target_arch = "wasm32"))]
         let start_instant = Instant::now();
         let mut traj = Self::new();
+        traj.backward = self.backward;
         for state in &self.states {
             traj.states.push(cosm.frame_chg(state, new_frame));
         }
@@ -75,6 +85,7 @@ impl Traj<Orbit> {
     /// Exports this trajectory to the provided filename in parquet format with only the epoch, the geodetic latitude, longitude, and height at one state per minute.
     /// Must provide a body fixed frame to correctly compute the latitude and longitude.
     #[allow(clippy::identity_op)]
+    #[cfg(feature = "io")]
     pub fn to_groundtrack_parquet<P: AsRef<Path>>(
         &self,
         path: P,
@@ -102,6 +113,7 @@ impl Traj<Orbit> {
     /// Convert this orbit trajectory into a spacecraft trajectory by copying the provided template and setting its orbit state to that of each state of the trajectory
     pub fn upcast(&self, template: Spacecraft) -> Traj<Spacecraft> {
         let mut out = Traj::new();
+        out.backward = self.backward;
         for orbit in &self.states {
             out.states.push(template.with_orbit(*orbit));
         }
@@ -112,12 +124,22 @@ impl Traj<Orbit> {
     ///
     /// # Limitations
     /// 1. Only text versions of the OEM format are supported
-    /// 2. The covariance information, if present, is ignored
+    /// 2. The covariance information, if present, is ignored (cf. `from_oem` to read it)
     /// 3. Only one spacecraft per OEM file is supported.
+    pub fn from_oem_file<P: AsRef<Path>>(path: P) -> Result<Self, NyxError> {
+        Ok(Self::from_oem(path)?.0)
+    }
+
+    /// Initialize a new orbit trajectory and its covariances from the path to a CCSDS OEM file.
+    ///
+    /// # Limitations
+    /// 1. Only text versions of the OEM format are supported
+    /// 2. Only one spacecraft per OEM file is supported.
+    /// 3. Only the inertial and IAU body fixed reference frames are supported, cf. `io::ccsds`.
     ///
     /// # Thanks
     /// GPT-4 because I didn't want to spend too much time coding this up since it'll be a feature in ANISE.
-    pub fn from_oem_file<P: AsRef<Path>>(path: P) -> Result<Self, NyxError> {
+    pub fn from_oem<P: AsRef<Path>>(path: P) -> Result<(Self, Vec<OdmCovariance>), NyxError> {
         let cosm = Cosm::de438();
         // Open the file
         let file =
@@ -126,9 +148,12 @@ impl Traj<Orbit> {
 
         // Parse the Orbit Element messages
         let mut frame: Option<Frame> = None;
-        let mut time_system = String::new();
+        let mut center_name = String::new();
+        let mut ref_frame = "ICRF".to_string();
+        let mut time_system = TimeScale::UTC;
 
         let ignored_tokens: HashSet<_> = [
+            "CCSDS_OEM_VERS".to_string(),
             "CCSDS_OMM_VERS".to_string(),
             "CREATION_DATE".to_string(),
             "ORIGINATOR".to_string(),
@@ -136,8 +161,11 @@ impl Traj<Orbit> {
         .into();
 
         let mut traj = Self::default();
+        let mut covariances = Vec::new();
 
         let mut parse = false;
+        // Epoch, reference frame and lower triangle of the covariance being parsed
+        let mut covariance: Option<(Epoch, Option<String>, Vec<f64>)> = None;
 
         'lines: for (lno, line) in reader.lines().enumerate() {
             let line = line.map_err(|e| NyxError::CCSDS(format!("File read error: {e}")))?;
@@ -157,22 +185,55 @@ impl Traj<Orbit> {
                 traj.name = Some(name);
             } else if line.starts_with("CENTER_NAME") {
                 let parts: Vec<&str> = line.split('=').collect();
-                let center_name = parts[1].trim();
-                frame = Some(cosm.try_frame(&format!("{center_name} J2000"))?);
+                center_name = parts[1].trim().to_string();
+            } else if line.starts_with("REF_FRAME") {
+                let parts: Vec<&str> = line.split('=').collect();
+                ref_frame = parts[1].trim().to_string();
             } else if line.starts_with("TIME_SYSTEM") {
                 let parts: Vec<&str> = line.split('=').collect();
-                time_system = parts[1].trim().to_string();
+                time_system = parse_time_system(parts[1].trim())?;
                 debug!("[line: {}] Found time system `{time_system}`", lno + 1);
             } else if line.starts_with("META_STOP") {
                 // We can start parsing now
+                frame = Some(parse_ccsds_frame(&center_name, &ref_frame, &cosm)?);
                 parse = true;
             } else if line.starts_with("META_START") {
                 // Stop the parsing
                 parse = false;
             } else if line.starts_with("COVARIANCE_START") {
-                // Stop the parsing
-                warn!("[line: {}] Skipping covariance in OEM parsing", lno + 1);
+                // Stop the parsing of the states
                 parse = false;
+                covariance = None;
+            } else if line.starts_with("COVARIANCE_STOP") {
+                if let Some((epoch, cov_frame, values)) = covariance.take() {
+                    covariances.push(OdmCovariance::from_lower_triangle(
+                        epoch, cov_frame, &values,
+                    )?);
+                }
+            } else if line.starts_with("EPOCH") {
+                // Start of a new covariance
+                if let Some((epoch, cov_frame, values)) = covariance.take() {
+                    covariances.push(OdmCovariance::from_lower_triangle(
+                        epoch, cov_frame, &values,
+                    )?);
+                }
+                let parts: Vec<&str> = line.split('=').collect();
+                let epoch = parse_ccsds_epoch(parts[1].trim(), time_system)?;
+                covariance = Some((epoch, None, Vec::with_capacity(21)));
+            } else if let Some((_, cov_frame, values)) = covariance.as_mut() {
+                if line.starts_with("COV_REF_FRAME") {
+                    let parts: Vec<&str> = line.split('=').collect();
+                    *cov_frame = Some(parts[1].trim().to_string());
+                } else if !line.starts_with("COMMENT") {
+                    for value in line.split_whitespace() {
+                        values.push(value.parse::<f64>().map_err(|_| {
+                            NyxError::CCSDS(format!(
+                                "[line: {}] Invalid covariance value `{value}`",
+                                lno + 1
+                            ))
+                        })?);
+                    }
+                }
             } else if parse {
                 // Split the line into components
                 let parts: Vec<&str> = line.split_whitespace().collect();
@@ -181,29 +242,29 @@ impl Traj<Orbit> {
                     debug!("[line: {}] Could not understand `{parts:?}`", lno + 1);
                 } else {
                     // Extract the values
-                    let epoch_str = format!("{} {time_system}", parts[0]);
                     match parts[1].parse::<f64>() {
                         Ok(x_km) => {
                             // Look good!
-                            let y_km = parts[2].parse::<f64>().unwrap();
-                            let z_km = parts[3].parse::<f64>().unwrap();
-                            let vx_km_s = parts[4].parse::<f64>().unwrap();
-                            let vy_km_s = parts[5].parse::<f64>().unwrap();
-                            let vz_km_s = parts[6].parse::<f64>().unwrap();
-
-                            let orbit = Orbit {
-                                epoch: Epoch::from_str(epoch_str.trim()).map_err(|e| {
-                                    NyxError::CCSDS(format!("Parsing epoch error: {e}"))
-                                })?,
-                                x_km,
-                                y_km,
-                                z_km,
-                                vx_km_s,
-                                vy_km_s,
-                                vz_km_s,
-                                frame: frame.unwrap(),
-                                stm: None,
-                            };
+                            let mut values = [x_km, 0.0, 0.0, 0.0, 0.0, 0.0];
+                            for (value, part) in values.iter_mut().zip(&parts[1..7]).skip(1) {
+                                *value = part.parse::<f64>().map_err(|_| {
+                                    NyxError::CCSDS(format!(
+                                        "[line: {}] Invalid state value `{part}`",
+                                        lno + 1
+                                    ))
+                                })?;
+                            }
+
+                            let orbit = Orbit::cartesian(
+                                values[0],
+                                values[1],
+                                values[2],
+                                values[3],
+                                values[4],
+                                values[5],
+                                parse_ccsds_epoch(parts[0], time_system)?,
+                                frame.unwrap(),
+                            );
 
                             traj.states.push(orbit);
                         }
@@ -219,13 +280,25 @@ impl Traj<Orbit> {
 
         traj.finalize();
 
-        Ok(traj)
+        Ok((traj, covariances))
     }
 
+    /// Exports this trajectory to a CCSDS OEM file in its text version, cf. `to_oem` to also export covariances.
     pub fn to_oem_file<P: AsRef<Path>>(
         &self,
         path: P,
         cfg: ExportCfg,
+    ) -> Result<PathBuf, NyxError> {
+        self.to_oem(path, &[], cfg)
+    }
+
+    /// Exports this trajectory and the provided covariances to a CCSDS OEM file in its text version.
+    /// The covariances outside of the exported states are not exported.
+    pub fn to_oem<P: AsRef<Path>>(
+        &self,
+        path: P,
+        covariances: &[OdmCovariance],
+        cfg: ExportCfg,
     ) -> Result<PathBuf, NyxError> {
         if self.states.is_empty() {
             return Err(NyxError::CCSDS(
@@ -261,7 +334,7 @@ impl Traj<Orbit> {
         let iso8601_no_ts = Format::from_str("%Y-%m-%dT%H:%M:%S.%f").unwrap();
 
         // Write mandatory metadata
-        writeln!(writer, "CCSDS_OMM_VERS = 2.0").map_err(err_hdlr)?;
+        writeln!(writer, "CCSDS_OEM_VERS = 2.0").map_err(err_hdlr)?;
         writeln!(
             writer,
             "CREATION_DATE = {}",
@@ -273,7 +346,10 @@ impl Traj<Orbit> {
             "ORIGINATOR = {}\n",
             metadata
                 .get("originator")
-                .unwrap_or(&"Nyx Space".to_string())
+                .cloned()
+                .unwrap_or_else(|| Watermark::current()
+                    .organization
+                    .unwrap_or_else(|| "Nyx Space".to_string()))
         )
         .map_err(err_hdlr)?;
 
@@ -285,23 +361,13 @@ impl Traj<Orbit> {
             writeln!(writer, "OBJECT_NAME = {}", object_name).map_err(err_hdlr)?;
         }
 
-        let frame_str = states[0].frame.to_string();
-        let splt: Vec<&str> = frame_str.split(' ').collect();
-        let center = splt[0];
-        let ref_frame = frame_str.replace(center, " ");
-        writeln!(
-            writer,
-            "REF_FRAME = {}",
-            match ref_frame.trim() {
-                "J2000" => "ICRF",
-                _ => ref_frame.trim(),
-            }
-        )
-        .map_err(err_hdlr)?;
+        let (center, ref_frame) = ccsds_center_ref_frame(states[0].frame);
+        writeln!(writer, "REF_FRAME = {ref_frame}").map_err(err_hdlr)?;
 
         writeln!(writer, "CENTER_NAME = {center}",).map_err(err_hdlr)?;
 
-        writeln!(writer, "TIME_SYSTEM = {}", states[0].epoch.time_scale).map_err(err_hdlr)?;
+        let time_system = states[0].epoch.time_scale;
+        writeln!(writer, "TIME_SYSTEM = {}", ccsds_time_system(time_system)).map_err(err_hdlr)?;
 
         writeln!(
             writer,
@@ -355,6 +421,23 @@ impl Traj<Orbit> {
         #[allow(clippy::writeln_empty_string)]
         writeln!(writer, "").map_err(err_hdlr)?;
 
+        let first_epoch = states[0].epoch;
+        let last_epoch = states[states.len() - 1].epoch;
+        let covariances: Vec<&OdmCovariance> = covariances
+            .iter()
+            .filter(|covariance| covariance.epoch >= first_epoch && covariance.epoch <= last_epoch)
+            .collect();
+
+        if !covariances.is_empty() {
+            writeln!(writer, "COVARIANCE_START").map_err(err_hdlr)?;
+            for covariance in covariances {
+                covariance
+                    .write_oem(&mut writer, time_system)
+                    .map_err(err_hdlr)?;
+            }
+            writeln!(writer, "COVARIANCE_STOP\n").map_err(err_hdlr)?;
+        }
+
         // Return the path this was written to
         let tock_time = Epoch::now().unwrap() - tick;
         info!(
@@ -495,6 +578,74 @@ mod ut_ccsds_oem {
         assert_eq!(traj_reloaded.last().epoch, traj.last().epoch - 19.seconds());
     }
 
+    #[test]
+    fn test_oem_covariance() {
+        use crate::io::ccsds::OdmCovariance;
+        use crate::linalg::Matrix6;
+
+        let path: PathBuf = [
+            env!("CARGO_MANIFEST_DIR"),
+            "data",
+            "tests",
+            "ccsds",
+            "oem",
+            "MEO_60s.oem",
+        ]
+        .iter()
+        .collect();
+
+        let _ = pretty_env_logger::try_init();
+
+        let (traj, covariances) = Traj::<Orbit>::from_oem(path).unwrap();
+        assert!(covariances.is_empty());
+
+        // Build a covariance every ten minutes, one of which is in the RTN frame
+        let mut covariances = Vec::new();
+        for (i, state) in traj.every(10.minutes()).enumerate() {
+            let mut covar = Matrix6::from_diagonal_element(1e-6 * (i + 1) as f64);
+            covar[(3, 0)] = 1e-9;
+            covar[(0, 3)] = 1e-9;
+            let mut covariance = OdmCovariance::new(state.epoch, covar);
+            if i == 1 {
+                covariance.ref_frame = Some("RTN".to_string());
+            }
+            covariances.push(covariance);
+        }
+
+        let path: PathBuf = [
+            env!("CARGO_MANIFEST_DIR"),
+            "output_data",
+            "MEO_60s_covar.oem",
+        ]
+        .iter()
+        .collect();
+
+        let out_path = traj
+            .to_oem(path.clone(), &covariances, ExportCfg::default())
+            .unwrap();
+        let (traj_reloaded, covariances_reloaded) = Traj::<Orbit>::from_oem(out_path).unwrap();
+
+        assert_eq!(traj_reloaded, traj);
+        assert_eq!(covariances_reloaded, covariances);
+
+        // Only the covariances of the exported states are exported
+        let cfg = ExportCfg::builder()
+            .step(1.minutes())
+            .end_epoch(traj.first().epoch + 15.minutes())
+            .build();
+        let out_path = traj.to_oem(path.clone(), &covariances, cfg).unwrap();
+        let (_, covariances_reloaded) = Traj::<Orbit>::from_oem(out_path).unwrap();
+        assert_eq!(covariances_reloaded, covariances[..2]);
+
+        // Body fixed frames are also supported
+        let cosm = Cosm::de438();
+        let traj_iau = traj.to_frame(cosm.frame("IAU Earth"), cosm).unwrap();
+        let out_path = traj_iau.to_oem_file(path, ExportCfg::default()).unwrap();
+        let traj_reloaded = Traj::<Orbit>::from_oem_file(out_path).unwrap();
+        assert_eq!(traj_reloaded.first().frame, traj_iau.first().frame);
+        assert_eq!(traj_reloaded.states.len(), traj_iau.states.len());
+    }
+
     #[test]
     fn test_moon_frame_long_prop() {
         let cosm = Cosm::de438();
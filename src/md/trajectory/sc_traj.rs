@@ -16,16 +16,41 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+#[cfg(feature = "io")]
+use super::ExportCfg;
+use super::Traj;
 use super::TrajError;
-use super::{ExportCfg, Traj};
 use crate::cosmic::{Cosm, Frame, Orbit, Spacecraft};
+#[cfg(feature = "io")]
+use crate::dynamics::SpacecraftDynamics;
 use crate::errors::NyxError;
+#[cfg(feature = "io")]
+use crate::io::watermark::pq_writer;
+#[cfg(feature = "io")]
 use crate::md::prelude::StateParameter;
+#[cfg(feature = "io")]
 use crate::md::EventEvaluator;
-use crate::time::{Duration, TimeUnits};
+#[cfg(feature = "io")]
+use crate::time::{Duration, Epoch, TimeSeries, TimeUnits};
+#[cfg(feature = "io")]
+use crate::State;
+#[cfg(feature = "io")]
+use arrow::array::{Array, Float64Builder, StringBuilder};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "io")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "io")]
 use std::collections::HashMap;
+#[cfg(feature = "io")]
 use std::error::Error;
-use std::path::{Path, PathBuf};
+#[cfg(feature = "io")]
+use std::fs::File;
+use std::path::Path;
+#[cfg(feature = "io")]
+use std::path::PathBuf;
 use std::sync::Arc;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
@@ -43,6 +68,7 @@ impl Traj<Spacecraft> {
         #[cfg(not(target_arch = "wasm32"))]
         let start_instant = Instant::now();
         let mut traj = Self::new();
+        traj.backward = self.backward;
         for state in &self.states {
             traj.states
                 .push(state.with_orbit(cosm.frame_chg(&state.orbit, new_frame)));
@@ -68,6 +94,7 @@ impl Traj<Spacecraft> {
     }
 
     /// A shortcut to `to_parquet_with_cfg`
+    #[cfg(feature = "io")]
     pub fn to_parquet_with_step<P: AsRef<Path>>(
         &self,
         path: P,
@@ -87,6 +114,7 @@ impl Traj<Spacecraft> {
     /// Exports this trajectory to the provided filename in parquet format with only the epoch, the geodetic latitude, longitude, and height at one state per minute.
     /// Must provide a body fixed frame to correctly compute the latitude and longitude.
     #[allow(clippy::identity_op)]
+    #[cfg(feature = "io")]
     pub fn to_groundtrack_parquet<P: AsRef<Path>>(
         &self,
         path: P,
@@ -111,9 +139,137 @@ impl Traj<Spacecraft> {
         traj.to_parquet(path, events, cfg)
     }
 
+    /// Exports the acceleration of each model of the provided dynamics along this trajectory to a parquet file, at each stored
+    /// state (i.e. each step of the propagation) or at the step of the configuration if set. Each model is named after its
+    /// display, with its Cartesian components and its norm in km/s^2, e.g. to debug discrepancies with the dynamics of
+    /// other tools.
+    ///
+    /// # Notes
+    /// + The fields and units of the configuration are ignored.
+    #[cfg(feature = "io")]
+    pub fn accel_breakdown_to_parquet<P: AsRef<Path>>(
+        &self,
+        dynamics: &SpacecraftDynamics,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let tick = Epoch::now().unwrap();
+        info!("Exporting acceleration breakdown to parquet file...");
+
+        let path_buf = cfg.actual_path(path);
+
+        let states = if cfg.start_epoch.is_some() || cfg.end_epoch.is_some() || cfg.step.is_some() {
+            let start = cfg.start_epoch.unwrap_or_else(|| self.first().epoch());
+            let end = cfg.end_epoch.unwrap_or_else(|| self.last().epoch());
+            let step = cfg.step.unwrap_or_else(|| 1.minutes());
+            let epochs = TimeSeries::inclusive(start, end, step)
+                .filter(|epoch| *epoch >= self.first().epoch() && *epoch <= self.last().epoch())
+                .collect::<Vec<Epoch>>();
+            self.sample_at(&epochs)?.states
+        } else {
+            self.states.to_vec()
+        };
+
+        if states.is_empty() {
+            return Err(Box::new(NyxError::NoStateData(
+                "no state to export in the requested time span".to_string(),
+            )));
+        }
+
+        let breakdowns = states
+            .iter()
+            .map(|state| dynamics.accel_breakdown(state))
+            .collect::<Result<Vec<_>, NyxError>>()?;
+
+        // Build the schema
+        let mut hdrs = vec![
+            Field::new("Epoch:Gregorian UTC", DataType::Utf8, false),
+            Field::new("Epoch:Gregorian TAI", DataType::Utf8, false),
+            Field::new("Epoch:TAI (s)", DataType::Float64, false),
+        ];
+
+        for (name, _) in &breakdowns[0] {
+            for coord in ["ax", "ay", "az", "|a|"] {
+                let mut meta = HashMap::new();
+                meta.insert("unit".to_string(), "km/s^2".to_string());
+                hdrs.push(
+                    Field::new(
+                        format!("{name}: {coord} (km/s^2)"),
+                        DataType::Float64,
+                        false,
+                    )
+                    .with_metadata(meta),
+                );
+            }
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let mut record: Vec<Arc<dyn Array>> = Vec::new();
+
+        // Epochs
+        let mut utc_epoch = StringBuilder::new();
+        let mut tai_epoch = StringBuilder::new();
+        let mut tai_s = Float64Builder::new();
+        for s in &states {
+            utc_epoch.append_value(format!("{}", s.epoch()));
+            tai_epoch.append_value(format!("{:x}", s.epoch()));
+            tai_s.append_value(s.epoch().to_tai_seconds());
+        }
+        record.push(Arc::new(utc_epoch.finish()));
+        record.push(Arc::new(tai_epoch.finish()));
+        record.push(Arc::new(tai_s.finish()));
+
+        for model_no in 0..breakdowns[0].len() {
+            for coord in 0..4 {
+                let mut data = Float64Builder::new();
+                for breakdown in &breakdowns {
+                    let accel = breakdown[model_no].1;
+                    let value = if coord < 3 {
+                        accel[coord]
+                    } else {
+                        accel.norm()
+                    };
+                    data.append_value(cfg.round(value));
+                }
+                record.push(Arc::new(data.finish()));
+            }
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "Purpose".to_string(),
+            "Acceleration breakdown data".to_string(),
+        );
+        metadata.insert("Dynamics".to_string(), format!("{dynamics}"));
+        metadata.insert("Frame".to_string(), format!("{}", states[0].orbit.frame));
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
+
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        let tock_time = Epoch::now().unwrap() - tick;
+        info!(
+            "Acceleration breakdown of {} states written to {} in {tock_time}",
+            states.len(),
+            path_buf.display()
+        );
+        Ok(path_buf)
+    }
+
     /// Convert this spacecraft trajectory into an Orbit trajectory, loosing all references to the spacecraft
     pub fn downcast(&self) -> Traj<Orbit> {
         let mut out = Traj::new();
+        out.backward = self.backward;
         for sc_state in &self.states {
             out.states.push(sc_state.orbit);
         }
@@ -17,29 +17,51 @@
 */
 
 use super::traj_it::TrajIterator;
-use super::{ExportCfg, INTERPOLATION_SAMPLES};
+#[cfg(feature = "io")]
+use super::ExportCfg;
+use super::{Extrapolation, INTERPOLATION_SAMPLES};
 use super::{Interpolatable, TrajError};
+use super::{InterpolationCheck, TrajSegment, TrajVerification, VERIFY_SPOT_CHECKS};
+use super::{TrajSamples, SAMPLE_CHUNK_SIZE};
+use crate::dynamics::Dynamics;
 use crate::errors::NyxError;
+#[cfg(feature = "io")]
 use crate::io::watermark::pq_writer;
 use crate::linalg::allocator::Allocator;
 use crate::linalg::DefaultAllocator;
+#[cfg(feature = "io")]
+use crate::linalg::DimName;
+#[cfg(feature = "io")]
 use crate::md::prelude::{Frame, GuidanceMode, StateParameter};
-use crate::md::EventEvaluator;
-use crate::time::{Duration, Epoch, TimeSeries, TimeUnits, Unit};
+use crate::md::{EventArc, EventEvaluator};
+use crate::propagators::{ErrorCtrl, Propagator};
+#[cfg(feature = "io")]
+use crate::time::TimeUnits;
+use crate::time::{Duration, Epoch, TimeSeries, Unit};
+#[cfg(feature = "io")]
 use crate::utils::dcm_finite_differencing;
+#[cfg(feature = "io")]
 use arrow::array::{Array, Float64Builder, StringBuilder};
+#[cfg(feature = "io")]
 use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
 use arrow::record_batch::RecordBatch;
+#[cfg(feature = "io")]
 use parquet::arrow::ArrowWriter;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
+#[cfg(feature = "io")]
 use std::collections::HashMap;
+#[cfg(feature = "io")]
 use std::error::Error;
 use std::fmt;
+#[cfg(feature = "io")]
 use std::fs::File;
 use std::iter::Iterator;
 use std::ops;
+#[cfg(feature = "io")]
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::channel;
+#[cfg(feature = "io")]
 use std::sync::Arc;
 
 /// Store a trajectory of any State.
@@ -51,8 +73,10 @@ where
 {
     /// Optionally name this trajectory
     pub name: Option<String>,
-    /// We use a vector because we know that the states are produced in a chronological manner (the direction does not matter).
+    /// The states are always stored chronologically, regardless of the direction of propagation, so they can be queried by epoch.
     pub states: Vec<S>,
+    /// Set if this trajectory was generated by a backward propagation, i.e. it starts at its last state and ends at its first state.
+    pub backward: bool,
 }
 
 impl<S: Interpolatable> Traj<S>
@@ -64,14 +88,16 @@ where
         Self {
             name: None,
             states: Vec::new(),
+            backward: false,
         }
     }
+
     /// Orders the states, can be used to store the states out of order
     pub fn finalize(&mut self) {
-        // Remove duplicate epochs
-        self.states.dedup_by(|a, b| a.epoch().eq(&b.epoch()));
-        // And sort
+        // Sort
         self.states.sort_by_key(|a| a.epoch());
+        // And remove duplicate epochs, which are now consecutive
+        self.states.dedup_by(|a, b| a.epoch().eq(&b.epoch()));
     }
 
     /// Evaluate the trajectory at this specific epoch.
@@ -87,35 +113,142 @@ where
                 // Oh wow, we actually had this exact state!
                 Ok(self.states[idx])
             }
-            Err(idx) => {
-                if idx == 0 || idx >= self.states.len() {
-                    // The binary search returns where we should insert the data, so if it's at either end of the list, then we're out of bounds.
-                    // This condition should have been handled by the check at the start of this function.
-                    return Err(NyxError::Trajectory(TrajError::NoInterpolationData(epoch)));
+            Err(idx) => self.interpolate_around(idx, epoch),
+        }
+    }
+
+    /// Evaluate the trajectory at this specific epoch, or extrapolate it with two-body dynamics from its closest state if this epoch
+    /// is beyond the trajectory or within one of its data gaps. Returns an error if the closest state is further than the maximum
+    /// extrapolation duration, and logs a warning whenever the trajectory is extrapolated.
+    pub fn at_or_extrapolate(
+        &self,
+        epoch: Epoch,
+        extrapolation: Extrapolation,
+    ) -> Result<S, NyxError> {
+        if self.states.is_empty() {
+            return Err(NyxError::Trajectory(TrajError::NoInterpolationData(epoch)));
+        }
+
+        let closest = if epoch < self.first().epoch() {
+            *self.first()
+        } else if epoch > self.last().epoch() {
+            *self.last()
+        } else if let Some(min_gap) = extrapolation.min_gap {
+            match self
+                .states
+                .binary_search_by(|state| state.epoch().cmp(&epoch))
+            {
+                Ok(idx) => return Ok(self.states[idx]),
+                Err(idx) => {
+                    let (before, after) = (self.states[idx - 1], self.states[idx]);
+                    if after.epoch() - before.epoch() < min_gap {
+                        return self.interpolate_around(idx, epoch);
+                    } else if epoch - before.epoch() <= after.epoch() - epoch {
+                        before
+                    } else {
+                        after
+                    }
                 }
-                // This is the closest index, so let's grab the items around it.
-                // NOTE: This is essentially the same code as in ANISE for the Hermite SPK type 13
+            }
+        } else {
+            return self.at(epoch);
+        };
+
+        let offset = (epoch - closest.epoch()).abs();
+        if offset > extrapolation.max_duration {
+            return Err(NyxError::Trajectory(TrajError::NoInterpolationData(epoch)));
+        }
 
-                // We didn't find it, so let's build an interpolation here.
-                let num_left = INTERPOLATION_SAMPLES / 2;
+        warn!(
+            "Extrapolating trajectory with two-body dynamics by {offset} from {} to {epoch}",
+            closest.epoch()
+        );
 
-                // Ensure that we aren't fetching out of the window
-                let mut first_idx = idx.saturating_sub(num_left);
-                let last_idx = self.states.len().min(first_idx + INTERPOLATION_SAMPLES);
+        let mut state = closest;
+        state.set_orbit(closest.orbit().at_epoch(epoch)?);
+        Ok(state)
+    }
 
-                // Check that we have enough samples
-                if last_idx == self.states.len() {
-                    first_idx = last_idx.saturating_sub(2 * num_left);
-                }
+    /// Interpolates the trajectory at the provided epoch, where `idx` is the index of the first state after that epoch.
+    fn interpolate_around(&self, idx: usize, epoch: Epoch) -> Result<S, NyxError> {
+        if idx == 0 || idx >= self.states.len() {
+            // The binary search returns where we should insert the data, so if it's at either end of the list, then we're out of bounds.
+            // This condition should have been handled by the check at the start of this function.
+            return Err(NyxError::Trajectory(TrajError::NoInterpolationData(epoch)));
+        }
+        let (first_idx, last_idx) = self.interpolation_window(idx);
 
-                let mut states = Vec::with_capacity(last_idx - first_idx);
-                for idx in first_idx..last_idx {
-                    states.push(self.states[idx]);
-                }
+        self.states[idx].interpolate(epoch, &self.states[first_idx..last_idx])
+    }
 
-                self.states[idx].interpolate(epoch, &states)
-            }
+    /// Returns the range of indexes of the states used to interpolate before the state at index `idx`.
+    fn interpolation_window(&self, idx: usize) -> (usize, usize) {
+        // This is the closest index, so let's grab the items around it.
+        // NOTE: This is essentially the same code as in ANISE for the Hermite SPK type 13
+        let num_left = INTERPOLATION_SAMPLES / 2;
+
+        // Ensure that we aren't fetching out of the window
+        let mut first_idx = idx.saturating_sub(num_left);
+        let last_idx = self.states.len().min(first_idx + INTERPOLATION_SAMPLES);
+
+        // Check that we have enough samples
+        if last_idx == self.states.len() {
+            first_idx = last_idx.saturating_sub(2 * num_left);
         }
+
+        (first_idx, last_idx)
+    }
+
+    /// Evaluate the trajectory at each of the provided epochs, which should be sorted in order to be evaluated efficiently.
+    ///
+    /// The epochs are split in chunks evaluated in parallel (with the `parallel` feature), and within each chunk, the search for the states around each
+    /// epoch starts from the previous one. Returns an error if any of the epochs is outside of this trajectory.
+    pub fn sample_at(&self, epochs: &[Epoch]) -> Result<TrajSamples<S>, NyxError> {
+        if self.states.is_empty() {
+            return Err(NyxError::Trajectory(TrajError::CreationError(
+                "No trajectory to sample".to_string(),
+            )));
+        }
+
+        let (first_epoch, last_epoch) = (self.first().epoch(), self.last().epoch());
+
+        #[cfg(feature = "parallel")]
+        let chunks = epochs.par_chunks(SAMPLE_CHUNK_SIZE);
+        #[cfg(not(feature = "parallel"))]
+        let chunks = epochs.chunks(SAMPLE_CHUNK_SIZE);
+
+        let chunks = chunks
+            .map(|chunk| {
+                let mut states = Vec::with_capacity(chunk.len());
+                // Index of the first state at or after the previous epoch
+                let mut idx = 0;
+                let mut prev_epoch = first_epoch;
+
+                for epoch in chunk.iter().copied() {
+                    if epoch < first_epoch || epoch > last_epoch {
+                        return Err(NyxError::Trajectory(TrajError::NoInterpolationData(epoch)));
+                    }
+
+                    // Only search after the previous epoch if the epochs are sorted
+                    let start_idx = if epoch >= prev_epoch { idx } else { 0 };
+                    idx = start_idx
+                        + self.states[start_idx..].partition_point(|state| state.epoch() < epoch);
+                    prev_epoch = epoch;
+
+                    if self.states[idx].epoch() == epoch {
+                        states.push(self.states[idx]);
+                    } else {
+                        states.push(self.interpolate_around(idx, epoch)?);
+                    }
+                }
+
+                Ok(states)
+            })
+            .collect::<Result<Vec<Vec<S>>, NyxError>>()?;
+
+        Ok(TrajSamples {
+            states: chunks.into_iter().flatten().collect(),
+        })
     }
 
     /// Returns the first state in this ephemeris
@@ -129,6 +262,160 @@ where
         self.states.last().unwrap()
     }
 
+    /// Returns the state where the propagation of this trajectory started, i.e. the last state of a backward trajectory
+    pub fn start(&self) -> &S {
+        if self.backward {
+            self.last()
+        } else {
+            self.first()
+        }
+    }
+
+    /// Returns the state where the propagation of this trajectory ended, i.e. the first state of a backward trajectory
+    pub fn end(&self) -> &S {
+        if self.backward {
+            self.first()
+        } else {
+            self.last()
+        }
+    }
+
+    /// Returns the segments of this trajectory, i.e. the spans between its consecutive states, in chronological order.
+    pub fn segments(&self) -> Vec<TrajSegment> {
+        (1..self.states.len())
+            .map(|idx| self.segment_between(idx - 1, idx))
+            .collect()
+    }
+
+    /// Returns the segment containing the provided epoch, i.e. the one whose states are the closest before and after it.
+    /// An epoch on a state is attributed to the segment which starts at that state, except for the last state.
+    pub fn segment_at(&self, epoch: Epoch) -> Result<TrajSegment, NyxError> {
+        if self.states.len() < 2 || self.first().epoch() > epoch || self.last().epoch() < epoch {
+            return Err(NyxError::Trajectory(TrajError::NoInterpolationData(epoch)));
+        }
+        let idx = self
+            .states
+            .partition_point(|state| state.epoch() <= epoch)
+            .min(self.states.len() - 1);
+        Ok(self.segment_between(idx - 1, idx))
+    }
+
+    /// Builds the segment between the states at the provided indexes, which must be consecutive.
+    fn segment_between(&self, start_idx: usize, end_idx: usize) -> TrajSegment {
+        let (first_idx, last_idx) = self.interpolation_window(end_idx);
+        TrajSegment {
+            start: self.states[start_idx].epoch(),
+            end: self.states[end_idx].epoch(),
+            samples: last_idx - first_idx,
+        }
+    }
+
+    /// Estimates the interpolation error in the segment at the provided index (cf. `segments`) by comparing the interpolated
+    /// state at the middle of that segment with the state re-propagated from the start of the segment with the provided propagator.
+    ///
+    /// The propagator should use the same dynamics as those which generated this trajectory, otherwise the estimate includes
+    /// the difference in dynamics.
+    #[allow(clippy::needless_lifetimes)]
+    pub fn interpolation_error<'a, D, E>(
+        &self,
+        prop: &Propagator<'a, D, E>,
+        segment_idx: usize,
+    ) -> Result<InterpolationCheck, NyxError>
+    where
+        D: Dynamics<StateType = S>,
+        E: ErrorCtrl,
+        DefaultAllocator: Allocator<usize, S::Size, S::Size>,
+    {
+        if segment_idx + 1 >= self.states.len() {
+            return Err(NyxError::Trajectory(TrajError::CreationError(format!(
+                "No segment {segment_idx} in trajectory of {} states",
+                self.states.len()
+            ))));
+        }
+
+        let segment = self.segment_between(segment_idx, segment_idx + 1);
+        let epoch = segment.midpoint();
+
+        let interpolated = if epoch == segment.start {
+            self.states[segment_idx]
+        } else {
+            self.interpolate_around(segment_idx + 1, epoch)?
+        };
+        let propagated = prop.with(self.states[segment_idx]).until_epoch(epoch)?;
+
+        let (position_error_km, velocity_error_km_s) = interpolated.orbit().rss(propagated.orbit());
+
+        Ok(InterpolationCheck {
+            segment,
+            epoch,
+            position_error_km,
+            velocity_error_km_s,
+        })
+    }
+
+    /// Verifies this trajectory, e.g. before exporting it as an ephemeris, by estimating the interpolation error (cf. `interpolation_error`)
+    /// in up to ten segments evenly spaced from its first to its last segment, and comparing the position errors to the provided tolerance.
+    ///
+    /// Returns an error if the trajectory has fewer than two states or if a re-propagation fails. Check the result with `passed`.
+    #[allow(clippy::needless_lifetimes)]
+    pub fn verify<'a, D, E>(
+        &self,
+        prop: &Propagator<'a, D, E>,
+        tolerance_km: f64,
+    ) -> Result<TrajVerification, NyxError>
+    where
+        D: Dynamics<StateType = S>,
+        E: ErrorCtrl,
+        DefaultAllocator: Allocator<usize, S::Size, S::Size>,
+    {
+        if self.states.len() < 2 {
+            return Err(NyxError::Trajectory(TrajError::CreationError(
+                "Cannot verify a trajectory with fewer than two states".to_string(),
+            )));
+        }
+
+        let num_segments = self.states.len() - 1;
+        let num_checks = num_segments.min(VERIFY_SPOT_CHECKS);
+
+        let mut segment_idxs: Vec<usize> = (0..num_checks)
+            .map(|check| {
+                if num_checks == 1 {
+                    0
+                } else {
+                    check * (num_segments - 1) / (num_checks - 1)
+                }
+            })
+            .collect();
+        segment_idxs.dedup();
+
+        let checks = segment_idxs
+            .into_iter()
+            .map(|idx| self.interpolation_error(prop, idx))
+            .collect::<Result<Vec<InterpolationCheck>, NyxError>>()?;
+
+        let verification = TrajVerification {
+            tolerance_km,
+            checks,
+        };
+
+        if verification.passed() {
+            info!("Trajectory verified: {verification}");
+        } else {
+            for failure in verification.failures() {
+                warn!("Trajectory {failure} exceeds tolerance of {tolerance_km} km");
+            }
+        }
+
+        Ok(verification)
+    }
+
+    /// Converts a forward trajectory into a backward trajectory and vice versa. The states are unchanged since they are stored
+    /// chronologically, but the start and end states are swapped, and events are found in the opposite order.
+    pub fn reverse(mut self) -> Self {
+        self.backward = !self.backward;
+        self
+    }
+
     /// Creates an iterator through the trajectory by the provided step size
     pub fn every(&self, step: Duration) -> TrajIterator<S> {
         self.every_between(step, self.first().epoch(), self.last().epoch())
@@ -168,8 +455,11 @@ where
         let mut xa = 0.0;
         let mut xb = (xb_e - xa_e).to_seconds();
         // Evaluate the event at both bounds
-        let mut ya = event.eval(&self.at(xa_e)?);
-        let mut yb = event.eval(&self.at(xb_e)?);
+        let state_a = self.at(xa_e)?;
+        let state_b = self.at(xb_e)?;
+        let eval = |state: &S| event.eval_bracketed(&state_a, &state_b, state);
+        let mut ya = eval(&state_a);
+        let mut yb = eval(&state_b);
 
         // Check if we're already at the root
         if ya.abs() <= event.value_precision().abs() {
@@ -228,14 +518,14 @@ where
                 flag = false;
             }
             let next_try = self.at(xa_e + s * Unit::Second)?;
-            let ys = event.eval(&next_try);
+            let ys = eval(&next_try);
             xd = xc;
             xc = xb;
             yc = yb;
             if ya * ys < 0.0 {
                 // Root bracketed between a and s
                 let next_try = self.at(xa_e + xa * Unit::Second)?;
-                let ya_p = event.eval(&next_try);
+                let ya_p = eval(&next_try);
                 let (_a, _ya, _b, _yb) = arrange(xa, ya_p, s, ys);
                 {
                     xa = _a;
@@ -246,7 +536,7 @@ where
             } else {
                 // Root bracketed between s and b
                 let next_try = self.at(xa_e + xb * Unit::Second)?;
-                let yb_p = event.eval(&next_try);
+                let yb_p = eval(&next_try);
                 let (_a, _ya, _b, _yb) = arrange(s, ys, xb, yb_p);
                 {
                     xa = _a;
@@ -274,67 +564,63 @@ where
     ///
     /// If this heuristic fails to find any such events, then `find_minmax` is called on the event with a time precision of `Unit::Second`.
     /// Then we search only within the min and max bounds of the provided event.
-    #[allow(clippy::identity_op)]
+    ///
+    /// The events are returned in the order of propagation, i.e. in reverse chronological order for a backward trajectory.
     pub fn find_all<E>(&self, event: &E) -> Result<Vec<S>, NyxError>
     where
         E: EventEvaluator<S>,
     {
-        let start_epoch = self.first().epoch();
-        let end_epoch = self.last().epoch();
+        self.find_between(self.start().epoch(), self.end().epoch(), event)
+    }
+
+    /// Find all of the states where the event happens in the search window from `start` to `end`, with the same heuristic as `find_all`
+    /// applied to the duration of the window.
+    ///
+    /// The window is searched backward if `start` is after `end`, in which case the events are returned in reverse chronological order,
+    /// e.g. the first event is the last one to happen before `start`.
+    #[allow(clippy::identity_op)]
+    pub fn find_between<E>(&self, start: Epoch, end: Epoch, event: &E) -> Result<Vec<S>, NyxError>
+    where
+        E: EventEvaluator<S>,
+    {
+        let (start_epoch, end_epoch) = self.search_window(start, end)?;
         if start_epoch == end_epoch {
             return Err(NyxError::from(TrajError::EventNotFound {
-                start: start_epoch,
-                end: end_epoch,
+                start,
+                end,
                 event: format!("{event}"),
             }));
         }
         let heuristic = (end_epoch - start_epoch) / 100;
         info!("Searching for {event} with initial heuristic of {heuristic}",);
 
-        let (sender, receiver) = channel();
-
         let epochs: Vec<Epoch> = TimeSeries::inclusive(start_epoch, end_epoch, heuristic).collect();
-        epochs.into_par_iter().for_each_with(sender, |s, epoch| {
-            if let Ok(event_state) = self.find_bracketed(epoch, epoch + heuristic, event) {
-                s.send(event_state).unwrap()
-            };
-        });
-
-        let mut states: Vec<_> = receiver.iter().collect();
+        #[cfg(feature = "parallel")]
+        let epochs = epochs.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let epochs = epochs.into_iter();
+
+        let mut states: Vec<_> = epochs
+            .filter_map(|epoch| {
+                self.find_bracketed(epoch, (epoch + heuristic).min(end_epoch), event)
+                    .ok()
+            })
+            .collect();
 
         if states.is_empty() {
             warn!("Heuristic failed to find any {event} event, using slower approach");
             // Crap, we didn't find the event.
-            // Let's find the min and max of this event throughout the trajectory, and search around there.
-            match self.find_minmax(event, Unit::Second) {
+            // Let's find the min and max of this event throughout the window, and search around there.
+            match self.find_minmax_between(start_epoch, end_epoch, event, Unit::Second) {
                 Ok((min_event, max_event)) => {
                     let lower_min_epoch =
-                        if min_event.epoch() - 1 * Unit::Millisecond < self.first().epoch() {
-                            self.first().epoch()
-                        } else {
-                            min_event.epoch() - 1 * Unit::Millisecond
-                        };
-
+                        (min_event.epoch() - 1 * Unit::Millisecond).max(start_epoch);
                     let lower_max_epoch =
-                        if min_event.epoch() + 1 * Unit::Millisecond > self.last().epoch() {
-                            self.last().epoch()
-                        } else {
-                            min_event.epoch() + 1 * Unit::Millisecond
-                        };
-
+                        (min_event.epoch() + 1 * Unit::Millisecond).min(end_epoch);
                     let upper_min_epoch =
-                        if max_event.epoch() - 1 * Unit::Millisecond < self.first().epoch() {
-                            self.first().epoch()
-                        } else {
-                            max_event.epoch() - 1 * Unit::Millisecond
-                        };
-
+                        (max_event.epoch() - 1 * Unit::Millisecond).max(start_epoch);
                     let upper_max_epoch =
-                        if max_event.epoch() + 1 * Unit::Millisecond > self.last().epoch() {
-                            self.last().epoch()
-                        } else {
-                            max_event.epoch() + 1 * Unit::Millisecond
-                        };
+                        (max_event.epoch() + 1 * Unit::Millisecond).min(end_epoch);
 
                     // Search around the min event
                     if let Ok(event_state) =
@@ -353,16 +639,16 @@ where
                     // If there still isn't any match, report that the event was not found
                     if states.is_empty() {
                         return Err(NyxError::from(TrajError::EventNotFound {
-                            start: start_epoch,
-                            end: end_epoch,
+                            start,
+                            end,
                             event: format!("{event}"),
                         }));
                     }
                 }
                 Err(_) => {
                     return Err(NyxError::from(TrajError::EventNotFound {
-                        start: start_epoch,
-                        end: end_epoch,
+                        start,
+                        end,
                         event: format!("{event}"),
                     }));
                 }
@@ -371,6 +657,9 @@ where
         // Remove duplicates and reorder
         states.sort_by(|s1, s2| s1.epoch().partial_cmp(&s2.epoch()).unwrap());
         states.dedup();
+        if start > end {
+            states.reverse();
+        }
         for (cnt, event_state) in states.iter().enumerate() {
             info!(
                 "{event} #{}: {} for {event_state}",
@@ -381,30 +670,99 @@ where
         Ok(states)
     }
 
+    /// Find the arcs of the search window from `start` to `end` during which the event evaluates positive, bounded by the events found with
+    /// `find_between`, e.g. the passes above a given elevation or the eclipses of the trajectory.
+    ///
+    /// An arc which is in progress at a bound of the window is cut at that bound. Each arc is chronological, but the arcs are returned in
+    /// reverse chronological order if the window is searched backward (i.e. `start` is after `end`).
+    pub fn find_arcs<E>(
+        &self,
+        start: Epoch,
+        end: Epoch,
+        event: &E,
+    ) -> Result<Vec<EventArc<S>>, NyxError>
+    where
+        E: EventEvaluator<S>,
+    {
+        let (start_epoch, end_epoch) = self.search_window(start, end)?;
+
+        let mut bounds = vec![self.at(start_epoch)?];
+        match self.find_between(start_epoch, end_epoch, event) {
+            Ok(states) => bounds.extend(
+                states
+                    .into_iter()
+                    .filter(|state| state.epoch() > start_epoch && state.epoch() < end_epoch),
+            ),
+            Err(NyxError::Trajectory(TrajError::EventNotFound { .. })) => {}
+            Err(e) => return Err(e),
+        }
+        bounds.push(self.at(end_epoch)?);
+
+        // The event is positive throughout each segment between consecutive crossings, or negative throughout it
+        let mut arcs: Vec<EventArc<S>> = Vec::new();
+        let mut in_arc = false;
+        for segment in bounds.windows(2) {
+            let (rise, fall) = (segment[0], segment[1]);
+            let mid = rise.epoch() + (fall.epoch() - rise.epoch()) * 0.5;
+            if event.eval(&self.at(mid)?) > 0.0 {
+                match arcs.last_mut() {
+                    Some(arc) if in_arc => arc.fall = fall,
+                    _ => arcs.push(EventArc { rise, fall }),
+                }
+                in_arc = true;
+            } else {
+                in_arc = false;
+            }
+        }
+
+        if start > end {
+            arcs.reverse();
+        }
+        for (cnt, arc) in arcs.iter().enumerate() {
+            info!("{event} arc #{}: {arc}", cnt + 1);
+        }
+        Ok(arcs)
+    }
+
     /// Find the minimum and maximum of the provided event through the trajectory
-    #[allow(clippy::identity_op)]
     pub fn find_minmax<E>(&self, event: &E, precision: Unit) -> Result<(S, S), NyxError>
     where
         E: EventEvaluator<S>,
     {
+        self.find_minmax_between(self.first().epoch(), self.last().epoch(), event, precision)
+    }
+
+    /// Find the minimum and maximum of the provided event in the search window between the provided epochs, in any order
+    #[allow(clippy::identity_op)]
+    pub fn find_minmax_between<E>(
+        &self,
+        start: Epoch,
+        end: Epoch,
+        event: &E,
+        precision: Unit,
+    ) -> Result<(S, S), NyxError>
+    where
+        E: EventEvaluator<S>,
+    {
+        let (start_epoch, end_epoch) = self.search_window(start, end)?;
         let step: Duration = 1 * precision;
         let mut min_val = std::f64::INFINITY;
         let mut max_val = std::f64::NEG_INFINITY;
         let mut min_state = S::zeros();
         let mut max_state = S::zeros();
 
-        let (sender, receiver) = channel();
-
-        let epochs: Vec<Epoch> =
-            TimeSeries::inclusive(self.first().epoch(), self.last().epoch(), step).collect();
-
-        epochs.into_par_iter().for_each_with(sender, |s, epoch| {
-            let state = self.at(epoch).unwrap();
-            let this_eval = event.eval(&state);
-            s.send((this_eval, state)).unwrap();
-        });
-
-        let evald_states: Vec<_> = receiver.iter().collect();
+        let epochs: Vec<Epoch> = TimeSeries::inclusive(start_epoch, end_epoch, step).collect();
+        #[cfg(feature = "parallel")]
+        let epochs = epochs.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let epochs = epochs.into_iter();
+
+        let evald_states: Vec<_> = epochs
+            .map(|epoch| {
+                let state = self.at(epoch).unwrap();
+                (event.eval(&state), state)
+            })
+            .collect();
         for (this_eval, state) in evald_states {
             if this_eval < min_val {
                 min_val = this_eval;
@@ -420,11 +778,29 @@ where
     }
 
     /// Store this trajectory arc to a parquet file with the default configuration (depends on the state type, search for `export_params` in the documentation for details).
+    /// Returns the chronological bounds of the provided search window, which must be within this trajectory
+    fn search_window(&self, start: Epoch, end: Epoch) -> Result<(Epoch, Epoch), NyxError> {
+        let (start_epoch, end_epoch) = if start > end {
+            (end, start)
+        } else {
+            (start, end)
+        };
+        if start_epoch < self.first().epoch() {
+            Err(NyxError::from(TrajError::NoInterpolationData(start_epoch)))
+        } else if end_epoch > self.last().epoch() {
+            Err(NyxError::from(TrajError::NoInterpolationData(end_epoch)))
+        } else {
+            Ok((start_epoch, end_epoch))
+        }
+    }
+
+    #[cfg(feature = "io")]
     pub fn to_parquet_simple<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf, Box<dyn Error>> {
         self.to_parquet(path, None, ExportCfg::default())
     }
 
     /// Store this trajectory arc to a parquet file with the provided configuration
+    #[cfg(feature = "io")]
     pub fn to_parquet_with_cfg<P: AsRef<Path>>(
         &self,
         path: P,
@@ -434,6 +810,7 @@ where
     }
 
     /// Store this trajectory arc to a parquet file with the provided configuration and event evaluators
+    #[cfg(feature = "io")]
     pub fn to_parquet<P: AsRef<Path>>(
         &self,
         path: P,
@@ -458,7 +835,7 @@ where
             format!("{}", self.states[0].frame()),
         )]);
 
-        let mut fields = match cfg.fields {
+        let mut fields = match cfg.fields.clone() {
             Some(fields) => fields,
             None => S::export_params(),
         };
@@ -473,7 +850,29 @@ where
         });
 
         for field in &fields {
-            hdrs.push(field.to_field(more_meta.clone()));
+            hdrs.push(cfg.field_of(*field, more_meta.clone()));
+        }
+
+        // Named parameters of user-defined states, stored by their index in the parameters of the state
+        let named_params = S::named_parameters();
+        let named_fields = match cfg.named_fields.as_ref() {
+            Some(named_fields) => named_fields
+                .iter()
+                .map(|param| param.index_in(&named_params))
+                .collect::<Result<Vec<usize>, NyxError>>()?,
+            None => (0..named_params.len()).collect(),
+        };
+
+        for index in &named_fields {
+            let (name, unit) = named_params[*index];
+            let mut meta = HashMap::new();
+            meta.insert("unit".to_string(), unit.to_string());
+            let name = if unit.is_empty() {
+                name.to_string()
+            } else {
+                format!("{name} ({unit})")
+            };
+            hdrs.push(Field::new(name, DataType::Float64, false).with_metadata(meta));
         }
 
         if let Some(events) = events.as_ref() {
@@ -493,7 +892,11 @@ where
             let start = cfg.start_epoch.unwrap_or_else(|| self.first().epoch());
             let end = cfg.end_epoch.unwrap_or_else(|| self.last().epoch());
             let step = cfg.step.unwrap_or_else(|| 1.minutes());
-            self.every_between(step, start, end).collect::<Vec<S>>()
+            // Only sample within the trajectory
+            let epochs = TimeSeries::inclusive(start, end, step)
+                .filter(|epoch| *epoch >= self.first().epoch() && *epoch <= self.last().epoch())
+                .collect::<Vec<Epoch>>();
+            self.sample_at(&epochs)?.states
         } else {
             self.states.to_vec()
         };
@@ -525,12 +928,20 @@ where
             } else {
                 let mut data = Float64Builder::new();
                 for s in &states {
-                    data.append_value(s.value(field).unwrap());
+                    data.append_value(cfg.export_value(field, s.value(field).unwrap()));
                 }
                 record.push(Arc::new(data.finish()));
             }
         }
 
+        for index in named_fields {
+            let mut data = Float64Builder::new();
+            for s in &states {
+                data.append_value(cfg.round(s.named_value_at(index)?));
+            }
+            record.push(Arc::new(data.finish()));
+        }
+
         info!(
             "Serialized {} states from {} to {}",
             states.len(),
@@ -559,7 +970,7 @@ where
             }
         }
 
-        let props = pq_writer(Some(metadata));
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
 
         let file = File::create(&path_buf)?;
         let mut writer = ArrowWriter::try_new(file, schema.clone(), props).unwrap();
@@ -577,6 +988,144 @@ where
         Ok(path_buf)
     }
 
+    /// Store the history of the state transition matrix (STM) of this trajectory to a parquet file, e.g. for covariance
+    /// mapping or linear analyses in other tools without propagating again.
+    ///
+    /// Each row holds the STM of a state of the trajectory with respect to the initial state of the propagation, and each
+    /// column `STM[i,j]` its partial of the i-th component of the state vector with respect to the j-th one. Only the
+    /// columns of the provided sensitivities are exported, defaulting to all of them.
+    ///
+    /// # Notes
+    /// + The STM is not interpolated, so only the stored states between the start and end epochs of the configuration are
+    ///   exported, and a configured step only skips the states closer than this step to the previously exported one.
+    /// + The fields of the configuration are ignored, and so are its units since the STM mixes several units.
+    #[cfg(feature = "io")]
+    pub fn stm_to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        sensitivities: Option<Vec<usize>>,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let tick = Epoch::now().unwrap();
+        info!("Exporting state transition matrices to parquet file...");
+
+        let size = S::Size::dim();
+        let sensitivities = sensitivities.unwrap_or_else(|| (0..size).collect());
+        if let Some(col) = sensitivities.iter().find(|col| **col >= size) {
+            return Err(Box::new(NyxError::CustomError(format!(
+                "cannot export the sensitivity to component #{col} of a state of size {size}"
+            ))));
+        }
+
+        // Grab the path here before we move stuff.
+        let path_buf = cfg.actual_path(path);
+
+        // Select the stored states
+        let start = cfg.start_epoch.unwrap_or_else(|| self.first().epoch());
+        let end = cfg.end_epoch.unwrap_or_else(|| self.last().epoch());
+        let mut states: Vec<&S> = Vec::new();
+        for state in &self.states {
+            if state.epoch() < start || state.epoch() > end {
+                continue;
+            }
+            if let (Some(step), Some(prev)) = (cfg.step, states.last()) {
+                if state.epoch() - prev.epoch() < step {
+                    continue;
+                }
+            }
+            states.push(state);
+        }
+
+        if states.is_empty() {
+            return Err(Box::new(NyxError::NoStateData(
+                "no state to export in the requested time span".to_string(),
+            )));
+        }
+
+        let stms = states
+            .iter()
+            .map(|state| state.stm())
+            .collect::<Result<Vec<_>, NyxError>>()?;
+
+        // Build the schema
+        let mut hdrs = vec![
+            Field::new("Epoch:Gregorian UTC", DataType::Utf8, false),
+            Field::new("Epoch:Gregorian TAI", DataType::Utf8, false),
+            Field::new("Epoch:TAI (s)", DataType::Float64, false),
+        ];
+
+        for j in &sensitivities {
+            for i in 0..size {
+                hdrs.push(Field::new(
+                    format!("STM[{i},{j}]"),
+                    DataType::Float64,
+                    false,
+                ));
+            }
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let mut record: Vec<Arc<dyn Array>> = Vec::new();
+
+        // Epochs
+        let mut utc_epoch = StringBuilder::new();
+        let mut tai_epoch = StringBuilder::new();
+        let mut tai_s = Float64Builder::new();
+        for s in &states {
+            utc_epoch.append_value(format!("{}", s.epoch()));
+            tai_epoch.append_value(format!("{:x}", s.epoch()));
+            tai_s.append_value(s.epoch().to_tai_seconds());
+        }
+        record.push(Arc::new(utc_epoch.finish()));
+        record.push(Arc::new(tai_epoch.finish()));
+        record.push(Arc::new(tai_s.finish()));
+
+        for j in &sensitivities {
+            for i in 0..size {
+                let mut data = Float64Builder::new();
+                for stm in &stms {
+                    data.append_value(cfg.round(stm[(i, *j)]));
+                }
+                record.push(Arc::new(data.finish()));
+            }
+        }
+
+        info!(
+            "Serialized {} state transition matrices from {} to {}",
+            states.len(),
+            states.first().unwrap().epoch(),
+            states.last().unwrap().epoch()
+        );
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "Purpose".to_string(),
+            "State transition matrix data".to_string(),
+        );
+        metadata.insert("Frame".to_string(), format!("{}", states[0].frame()));
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
+
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        let tock_time = Epoch::now().unwrap() - tick;
+        info!(
+            "State transition matrices written to {} in {tock_time}",
+            path_buf.display()
+        );
+        Ok(path_buf)
+    }
+
     /// Allows resampling this trajectory at a fixed interval instead of using the propagator step size.
     /// This may lead to aliasing due to the Nyquist–Shannon sampling theorem.
     pub fn resample(&self, step: Duration) -> Result<Self, NyxError> {
@@ -587,6 +1136,7 @@ where
         }
 
         let mut traj = Self::new();
+        traj.backward = self.backward;
         for state in self.every(step) {
             traj.states.push(state);
         }
@@ -606,10 +1156,96 @@ where
         }
 
         let mut traj = Self::new();
-        for epoch in epochs {
-            traj.states.push(self.at(*epoch)?);
+        traj.backward = self.backward;
+        traj.states = self.sample_at(epochs)?.states;
+
+        traj.finalize();
+
+        Ok(traj)
+    }
+
+    /// Splices the provided segment onto this trajectory, e.g. a propagation restarted after a mid-course parameter change:
+    /// the states of this trajectory from the start of the segment onward (in the direction of propagation) are replaced by
+    /// those of the segment, and the earlier states are kept as is.
+    ///
+    /// Returns an error if the frames or propagation directions differ, or if the segment starts before this trajectory.
+    pub fn splice(&self, segment: &Self) -> Result<Self, NyxError> {
+        if self.states.is_empty() || segment.states.is_empty() {
+            return Err(NyxError::Trajectory(TrajError::CreationError(
+                "Cannot splice an empty trajectory".to_string(),
+            )));
+        }
+        if self.first().frame() != segment.first().frame() {
+            return Err(NyxError::Trajectory(TrajError::CreationError(format!(
+                "Frame mismatch in splice operation: {} != {}",
+                self.first().frame(),
+                segment.first().frame()
+            ))));
+        }
+        if self.backward != segment.backward {
+            return Err(NyxError::Trajectory(TrajError::CreationError(
+                "Cannot splice a forward and a backward trajectory".to_string(),
+            )));
         }
 
+        // The splice point is the first state of the segment in the direction of propagation
+        let (splice_epoch, kept): (Epoch, Vec<S>) = if self.backward {
+            let splice_epoch = segment.last().epoch();
+            if splice_epoch > self.last().epoch() {
+                return Err(NyxError::Trajectory(TrajError::CreationError(format!(
+                    "Segment starts at {splice_epoch}, before the trajectory which starts at {}",
+                    self.last().epoch()
+                ))));
+            }
+            let kept = self
+                .states
+                .iter()
+                .filter(|state| state.epoch() > splice_epoch)
+                .copied()
+                .collect();
+            (splice_epoch, kept)
+        } else {
+            let splice_epoch = segment.first().epoch();
+            if splice_epoch < self.first().epoch() {
+                return Err(NyxError::Trajectory(TrajError::CreationError(format!(
+                    "Segment starts at {splice_epoch}, before the trajectory which starts at {}",
+                    self.first().epoch()
+                ))));
+            }
+            let kept = self
+                .states
+                .iter()
+                .filter(|state| state.epoch() < splice_epoch)
+                .copied()
+                .collect();
+            (splice_epoch, kept)
+        };
+
+        if (self.backward && splice_epoch < self.first().epoch())
+            || (!self.backward && splice_epoch > self.last().epoch())
+        {
+            warn!(
+                "Spliced trajectory will have a time-gap starting at {}",
+                if self.backward {
+                    self.first().epoch()
+                } else {
+                    self.last().epoch()
+                }
+            );
+        }
+
+        debug!(
+            "Splicing at {splice_epoch}: {} of {} states kept, {} new states",
+            kept.len(),
+            self.states.len(),
+            segment.states.len()
+        );
+
+        let mut traj = Self::new();
+        traj.name = self.name.clone();
+        traj.backward = self.backward;
+        traj.states = kept;
+        traj.states.extend(segment.states.iter().copied());
         traj.finalize();
 
         Ok(traj)
@@ -619,6 +1255,7 @@ where
     ///
     /// # Notes
     /// + The RIC frame accounts for the transport theorem by performing a finite differencing of the RIC frame.
+    #[cfg(feature = "io")]
     pub fn ric_diff_to_parquet<P: AsRef<Path>>(
         &self,
         other: &Self,
@@ -829,7 +1466,7 @@ where
             }
         }
 
-        let props = pq_writer(Some(metadata));
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
 
         let file = File::create(&path_buf)?;
         let mut writer = ArrowWriter::try_new(file, schema.clone(), props).unwrap();
@@ -930,7 +1567,8 @@ where
             let dur = self.last().epoch() - self.first().epoch();
             write!(
                 f,
-                "Trajectory {}in {} from {} to {} ({}, or {:.3} s) [{} states]",
+                "{}rajectory {}in {} from {} to {} ({}, or {:.3} s) [{} states]",
+                if self.backward { "Backward t" } else { "T" },
                 match &self.name {
                     Some(name) => format!("of {name}"),
                     None => String::new(),
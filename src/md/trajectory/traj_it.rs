@@ -20,6 +20,7 @@ use super::{Interpolatable, Traj};
 use crate::linalg::allocator::Allocator;
 use crate::linalg::DefaultAllocator;
 use crate::time::TimeSeries;
+use tracing::Level;
 
 pub struct TrajIterator<'a, S: Interpolatable>
 where
@@ -50,7 +51,7 @@ where
                             "!!! [BUG] TrajIterator: {e} not found but should be present in {} !",
                             self.traj
                         );
-                        if log_enabled!(log::Level::Error) {
+                        if enabled!(Level::ERROR) || log::log_enabled!(log::Level::Error) {
                             error!("{msg}");
                         } else {
                             println!("{msg}");
@@ -0,0 +1,69 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::Interpolatable;
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::DefaultAllocator;
+use crate::md::StateParameter;
+use crate::time::Epoch;
+
+/// Number of epochs evaluated sequentially by each thread when sampling a trajectory
+pub(crate) const SAMPLE_CHUNK_SIZE: usize = 512;
+
+/// States sampled from a trajectory at the requested epochs, in the same order, cf. `Traj::sample_at`.
+#[derive(Clone, PartialEq)]
+pub struct TrajSamples<S: Interpolatable>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    /// The sampled states
+    pub states: Vec<S>,
+}
+
+impl<S: Interpolatable> TrajSamples<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    /// Returns the epochs of the samples
+    pub fn epochs(&self) -> Vec<Epoch> {
+        self.states.iter().map(|state| state.epoch()).collect()
+    }
+
+    /// Returns the value of the provided parameter for each sample
+    pub fn column(&self, param: StateParameter) -> Result<Vec<f64>, NyxError> {
+        self.states.iter().map(|state| state.value(param)).collect()
+    }
+
+    /// Returns the columns of each of the provided parameters, in the same order
+    pub fn columns(&self, params: &[StateParameter]) -> Result<Vec<Vec<f64>>, NyxError> {
+        params.iter().map(|param| self.column(*param)).collect()
+    }
+
+    /// Number of samples
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns whether there are no samples
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
@@ -0,0 +1,136 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::time::{Duration, Epoch};
+use std::fmt;
+
+/// Maximum number of segments re-propagated by `Traj::verify`, evenly spaced throughout the trajectory
+pub(crate) const VERIFY_SPOT_CHECKS: usize = 10;
+
+/// Span between two consecutive states of a trajectory, cf. `Traj::segments`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TrajSegment {
+    /// Epoch of the state at the start of this segment
+    pub start: Epoch,
+    /// Epoch of the state at the end of this segment
+    pub end: Epoch,
+    /// Number of states used to interpolate within this segment, which is smaller near the end of the trajectory and in short trajectories
+    pub samples: usize,
+}
+
+impl TrajSegment {
+    /// Duration of this segment
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// Epoch at the middle of this segment, where the interpolation error is estimated
+    pub fn midpoint(&self) -> Epoch {
+        self.start + self.duration() * 0.5
+    }
+
+    /// Returns whether the provided epoch is within this segment
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        self.start <= epoch && epoch <= self.end
+    }
+}
+
+impl fmt::Display for TrajSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} to {} ({}) [{} samples]",
+            self.start,
+            self.end,
+            self.duration(),
+            self.samples
+        )
+    }
+}
+
+/// Difference between the interpolated trajectory and a re-propagation at the middle of a segment, cf. `Traj::interpolation_error`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InterpolationCheck {
+    /// Segment which was checked
+    pub segment: TrajSegment,
+    /// Epoch of the check, i.e. the middle of the segment
+    pub epoch: Epoch,
+    /// Norm of the position difference between the interpolated and the re-propagated states
+    pub position_error_km: f64,
+    /// Norm of the velocity difference between the interpolated and the re-propagated states
+    pub velocity_error_km_s: f64,
+}
+
+impl fmt::Display for InterpolationCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "interpolation error at {}: {:.3e} km, {:.3e} km/s",
+            self.epoch, self.position_error_km, self.velocity_error_km_s
+        )
+    }
+}
+
+/// Result of the spot checks of a trajectory against re-propagations, cf. `Traj::verify`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrajVerification {
+    /// Maximum position error allowed, in kilometers
+    pub tolerance_km: f64,
+    /// Spot checks, in chronological order
+    pub checks: Vec<InterpolationCheck>,
+}
+
+impl TrajVerification {
+    /// Returns whether all of the spot checks are within the position tolerance
+    pub fn passed(&self) -> bool {
+        self.failures().is_empty()
+    }
+
+    /// Returns the spot checks whose position error exceeds the tolerance
+    pub fn failures(&self) -> Vec<InterpolationCheck> {
+        self.checks
+            .iter()
+            .filter(|check| check.position_error_km > self.tolerance_km)
+            .copied()
+            .collect()
+    }
+
+    /// Returns the spot check with the largest position error, if any
+    pub fn worst(&self) -> Option<InterpolationCheck> {
+        self.checks
+            .iter()
+            .copied()
+            .max_by(|a, b| a.position_error_km.total_cmp(&b.position_error_km))
+    }
+}
+
+impl fmt::Display for TrajVerification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} spot checks within {} km",
+            self.checks.len() - self.failures().len(),
+            self.checks.len(),
+            self.tolerance_km
+        )?;
+        if let Some(worst) = self.worst() {
+            write!(f, " (worst {worst})")?;
+        }
+        Ok(())
+    }
+}
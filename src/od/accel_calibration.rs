@@ -0,0 +1,278 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Orbit, Spacecraft};
+use crate::dynamics::{Accelerometer, SpacecraftDynamics};
+use crate::errors::NyxError;
+use crate::linalg::{Const, DMatrix, DVector, OMatrix, OVector, Vector3};
+use crate::propagators::Propagator;
+use crate::State;
+use std::fmt;
+use std::sync::Arc;
+
+/// Number of estimated parameters: position, velocity, accelerometer bias and scale factors
+const NUM_PARAMS: usize = 12;
+
+/// Perturbation of each parameter used to compute the partials by finite differencing
+const PERTURBATIONS: [f64; NUM_PARAMS] = [
+    1e-3, 1e-3, 1e-3, 1e-6, 1e-6, 1e-6, 1e-10, 1e-10, 1e-10, 1e-4, 1e-4, 1e-4,
+];
+
+/// Estimates the initial state of a spacecraft and the bias and scale factors of its accelerometer from position fixes
+/// (e.g. GNSS or the output of a previous orbit determination), with an iterated batch least squares.
+///
+/// The non-gravitational accelerations come from the accelerometer telemetry, so the dynamics must only include the
+/// gravitational models. The partials are computed by finite differencing the propagated trajectory.
+#[derive(Clone)]
+pub struct AccelCalibrationEstimator {
+    /// Gravitational dynamics of the spacecraft, without the accelerometer
+    pub dynamics: SpacecraftDynamics,
+    /// Accelerometer telemetry and the a priori calibration
+    pub accelerometer: Accelerometer,
+    /// One sigma noise of each component of the position fixes, in km
+    pub position_sigma_km: f64,
+    /// Maximum number of iterations of the least squares
+    pub max_iterations: usize,
+    /// Convergence threshold on the relative change of the RMS of the residuals between two iterations
+    pub tolerance: f64,
+}
+
+/// The result of an accelerometer calibration.
+#[derive(Clone, Debug)]
+pub struct AccelCalibration {
+    /// Estimated initial state
+    pub initial_state: Spacecraft,
+    /// Accelerometer with the estimated calibration
+    pub accelerometer: Accelerometer,
+    /// Covariance of the estimated parameters: position (km), velocity (km/s), bias (km/s^2) and scale factors
+    pub covar: OMatrix<f64, Const<NUM_PARAMS>, Const<NUM_PARAMS>>,
+    /// Root mean square of the post-fit position residuals, in km
+    pub rms_residual_km: f64,
+    /// Number of iterations used
+    pub iterations: usize,
+}
+
+impl AccelCalibration {
+    /// One sigma uncertainty of the bias of each axis, in km/s^2
+    pub fn bias_sigma(&self) -> Vector3<f64> {
+        Vector3::new(
+            self.covar[(6, 6)].sqrt(),
+            self.covar[(7, 7)].sqrt(),
+            self.covar[(8, 8)].sqrt(),
+        )
+    }
+
+    /// One sigma uncertainty of the scale factor of each axis
+    pub fn scale_sigma(&self) -> Vector3<f64> {
+        Vector3::new(
+            self.covar[(9, 9)].sqrt(),
+            self.covar[(10, 10)].sqrt(),
+            self.covar[(11, 11)].sqrt(),
+        )
+    }
+}
+
+impl fmt::Display for AccelCalibration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bias_sigma = self.bias_sigma();
+        let scale_sigma = self.scale_sigma();
+        writeln!(
+            f,
+            "Accelerometer calibration after {} iterations (post-fit RMS {:.3e} km)",
+            self.iterations, self.rms_residual_km
+        )?;
+        for i in 0..3 {
+            writeln!(
+                f,
+                "\taxis {i}: bias = {:.6e} ± {:.3e} km/s^2\tscale = {:.6} ± {:.3e}",
+                self.accelerometer.bias[i],
+                bias_sigma[i],
+                self.accelerometer.scale[i],
+                scale_sigma[i]
+            )?;
+        }
+        write!(f, "\tinitial state: {:x}", self.initial_state.orbit)
+    }
+}
+
+impl AccelCalibrationEstimator {
+    /// Initializes a new estimator with at most ten iterations
+    pub fn new(
+        dynamics: SpacecraftDynamics,
+        accelerometer: Accelerometer,
+        position_sigma_km: f64,
+    ) -> Self {
+        Self {
+            dynamics,
+            accelerometer,
+            position_sigma_km,
+            max_iterations: 10,
+            tolerance: 1e-2,
+        }
+    }
+
+    /// Builds the initial state and the accelerometer from the parameters
+    fn unpack(
+        &self,
+        initial_state: &Spacecraft,
+        params: &OVector<f64, Const<NUM_PARAMS>>,
+    ) -> (Spacecraft, Accelerometer) {
+        let mut state = *initial_state;
+        state.orbit.x_km = params[0];
+        state.orbit.y_km = params[1];
+        state.orbit.z_km = params[2];
+        state.orbit.vx_km_s = params[3];
+        state.orbit.vy_km_s = params[4];
+        state.orbit.vz_km_s = params[5];
+        let accel = self.accelerometer.with_calibration(
+            Vector3::new(params[6], params[7], params[8]),
+            Vector3::new(params[9], params[10], params[11]),
+        );
+        (state, accel)
+    }
+
+    /// Propagates the parameters and returns the positions at each of the reference epochs
+    fn positions(
+        &self,
+        initial_state: &Spacecraft,
+        params: &OVector<f64, Const<NUM_PARAMS>>,
+        references: &[Orbit],
+    ) -> Result<Vec<Vector3<f64>>, NyxError> {
+        let (state, accel) = self.unpack(initial_state, params);
+        let prop = Propagator::default(self.dynamics.clone().with_model(Arc::new(accel)));
+        let mut instance = prop.with(state);
+        references
+            .iter()
+            .map(|reference| Ok(instance.until_epoch(reference.epoch)?.orbit.radius()))
+            .collect()
+    }
+
+    /// Estimates the initial state and the accelerometer calibration from the position fixes, which must be in chronological order,
+    /// after the epoch of the initial guess, and in the same frame as the initial guess.
+    pub fn estimate(
+        &self,
+        initial_guess: Spacecraft,
+        references: &[Orbit],
+    ) -> Result<AccelCalibration, NyxError> {
+        if references.len() * 3 < NUM_PARAMS {
+            return Err(NyxError::CustomError(format!(
+                "accelerometer calibration requires at least {} position fixes but got {}",
+                NUM_PARAMS / 3,
+                references.len()
+            )));
+        }
+        if references[0].epoch < initial_guess.epoch()
+            || references.windows(2).any(|w| w[1].epoch <= w[0].epoch)
+        {
+            return Err(NyxError::CustomError(
+                "position fixes must be in chronological order and after the initial guess"
+                    .to_string(),
+            ));
+        }
+
+        let mut params = OVector::<f64, Const<NUM_PARAMS>>::zeros();
+        for (i, val) in initial_guess.orbit.to_cartesian_vec().iter().enumerate() {
+            params[i] = *val;
+        }
+        for i in 0..3 {
+            params[6 + i] = self.accelerometer.bias[i];
+            params[9 + i] = self.accelerometer.scale[i];
+        }
+
+        let num_msr = 3 * references.len();
+        let weight = self.position_sigma_km.powi(-2);
+        let mut iterations = 0;
+        let mut prev_rms = f64::INFINITY;
+
+        let covar = loop {
+            iterations += 1;
+
+            let nominal = self.positions(&initial_guess, &params, references)?;
+            let mut residuals = DVector::<f64>::zeros(num_msr);
+            for (i, (reference, computed)) in references.iter().zip(nominal.iter()).enumerate() {
+                let delta = reference.radius() - computed;
+                for j in 0..3 {
+                    residuals[3 * i + j] = delta[j];
+                }
+            }
+
+            // Finite difference partials of the positions with respect to the parameters
+            let mut h_tilde = DMatrix::<f64>::zeros(num_msr, NUM_PARAMS);
+            for (p, pert) in PERTURBATIONS.iter().enumerate() {
+                let mut perturbed = params;
+                perturbed[p] += pert;
+                let positions = self.positions(&initial_guess, &perturbed, references)?;
+                for (i, (computed, nom)) in positions.iter().zip(nominal.iter()).enumerate() {
+                    let partial = (computed - nom) / *pert;
+                    for j in 0..3 {
+                        h_tilde[(3 * i + j, p)] = partial[j];
+                    }
+                }
+            }
+
+            let info_dyn = weight * h_tilde.transpose() * &h_tilde;
+            let info = OMatrix::<f64, Const<NUM_PARAMS>, Const<NUM_PARAMS>>::from_iterator(
+                info_dyn.iter().copied(),
+            );
+            let normal = OVector::<f64, Const<NUM_PARAMS>>::from_iterator(
+                (weight * h_tilde.transpose() * &residuals).iter().copied(),
+            );
+
+            let covar = info
+                .try_inverse()
+                .ok_or(NyxError::SingularCovarianceMatrix)?;
+            let correction = covar * normal;
+            params += correction;
+
+            let rms = (residuals.norm_squared() / num_msr as f64).sqrt();
+            let rms_change = (prev_rms - rms).abs() / rms;
+            prev_rms = rms;
+
+            debug!(
+                "accelerometer calibration iteration #{iterations}: RMS = {rms:.3e} km, relative change = {rms_change:.3e}"
+            );
+
+            if rms_change < self.tolerance || iterations >= self.max_iterations {
+                if rms_change >= self.tolerance {
+                    warn!(
+                        "accelerometer calibration did not converge after {iterations} iterations"
+                    );
+                }
+                break covar;
+            }
+        };
+
+        // Post-fit residuals
+        let post_fit = self.positions(&initial_guess, &params, references)?;
+        let sum_sq: f64 = references
+            .iter()
+            .zip(post_fit.iter())
+            .map(|(reference, computed)| (reference.radius() - computed).norm_squared())
+            .sum();
+
+        let (initial_state, accelerometer) = self.unpack(&initial_guess, &params);
+
+        Ok(AccelCalibration {
+            initial_state,
+            accelerometer,
+            covar,
+            rms_residual_km: (sum_sq / num_msr as f64).sqrt(),
+            iterations,
+        })
+    }
+}
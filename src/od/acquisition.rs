@@ -0,0 +1,253 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::covariance_map;
+use super::{GroundStation, InjectionCovariance};
+use crate::cosmic::{Cosm, Orbit};
+use crate::dynamics::Dynamics;
+use crate::errors::NyxError;
+use crate::linalg::{Matrix2x3, Matrix3, Vector3};
+use crate::propagators::error_ctrl::ErrorCtrl;
+use crate::propagators::PropInstance;
+use crate::time::{Duration, Epoch};
+use std::fmt;
+use std::path::Path;
+
+/// Position perturbation used to compute the sensitivity of the pointing angles, in km
+const POINTING_PERTURBATION_KM: f64 = 1e-3;
+
+/// The pointing of a ground station antenna toward the spacecraft at a given epoch, and its uncertainty.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PointingSample {
+    pub epoch: Epoch,
+    /// Azimuth of the nominal trajectory, i.e. the pointing center, in degrees
+    pub azimuth_deg: f64,
+    /// Elevation of the nominal trajectory, i.e. the pointing center, in degrees
+    pub elevation_deg: f64,
+    /// Range of the nominal trajectory, in km
+    pub range_km: f64,
+    /// 1-sigma uncertainty of the azimuth, in degrees
+    pub azimuth_sigma_deg: f64,
+    /// 1-sigma uncertainty of the elevation, in degrees
+    pub elevation_sigma_deg: f64,
+    /// 1-sigma uncertainty of the range, in km
+    pub range_sigma_km: f64,
+    /// Half-angle of the cone around the pointing center containing the k-sigma position uncertainty, in degrees
+    pub cone_half_angle_deg: f64,
+    /// Whether the pointing center is above the elevation mask of the station
+    pub visible: bool,
+}
+
+/// The time history of the pointing centers and uncertainty cones of a ground station for the first acquisition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StationAcquisition {
+    /// Name of the ground station
+    pub station: String,
+    /// Number of sigmas of the uncertainty cones
+    pub k_sigma: f64,
+    /// Pointing samples, in chronological order
+    pub samples: Vec<PointingSample>,
+}
+
+impl StationAcquisition {
+    /// Returns the first sample where the spacecraft is visible from this station, i.e. the first acquisition opportunity.
+    pub fn first_acquisition(&self) -> Option<&PointingSample> {
+        self.samples.iter().find(|sample| sample.visible)
+    }
+
+    /// Returns the samples where the spacecraft is visible from this station.
+    pub fn visible(&self) -> impl Iterator<Item = &PointingSample> {
+        self.samples.iter().filter(|sample| sample.visible)
+    }
+
+    /// Exports the pointing predicts of this station to a CSV file, one row per sample (UTC epochs, angles in degrees and
+    /// distances in km), as commonly ingested by antenna control units.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P, visible_only: bool) -> Result<(), NyxError> {
+        let mut writer =
+            csv::Writer::from_path(path).map_err(|e| NyxError::ExportError(e.to_string()))?;
+        writer
+            .write_record([
+                "epoch_utc",
+                "azimuth_deg",
+                "elevation_deg",
+                "range_km",
+                "azimuth_sigma_deg",
+                "elevation_sigma_deg",
+                "range_sigma_km",
+                &format!("cone_half_angle_{}sigma_deg", self.k_sigma),
+                "visible",
+            ])
+            .map_err(|e| NyxError::ExportError(e.to_string()))?;
+        for sample in self
+            .samples
+            .iter()
+            .filter(|sample| sample.visible || !visible_only)
+        {
+            writer
+                .write_record(&[
+                    format!("{}", sample.epoch),
+                    format!("{:.6}", sample.azimuth_deg),
+                    format!("{:.6}", sample.elevation_deg),
+                    format!("{:.6}", sample.range_km),
+                    format!("{:.6}", sample.azimuth_sigma_deg),
+                    format!("{:.6}", sample.elevation_sigma_deg),
+                    format!("{:.6}", sample.range_sigma_km),
+                    format!("{:.6}", sample.cone_half_angle_deg),
+                    sample.visible.to_string(),
+                ])
+                .map_err(|e| NyxError::ExportError(e.to_string()))?;
+        }
+        writer
+            .flush()
+            .map_err(|e| NyxError::ExportError(e.to_string()))
+    }
+}
+
+impl fmt::Display for StationAcquisition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.first_acquisition() {
+            Some(first) => write!(
+                f,
+                "{}: first acquisition at {} (az. {:.3} deg, el. {:.3} deg, {}-sigma cone {:.3} deg), {} visible samples",
+                self.station,
+                first.epoch,
+                first.azimuth_deg,
+                first.elevation_deg,
+                self.k_sigma,
+                first.cone_half_angle_deg,
+                self.visible().count()
+            ),
+            None => write!(f, "{}: no acquisition", self.station),
+        }
+    }
+}
+
+/// Computes the acquisition aid of each ground station: the time history of the pointing centers toward the nominal
+/// trajectory, and of the uncertainty cones from the injection covariance mapped along that trajectory.
+///
+/// The propagator must start at the injection state with its STM enabled (`with_stm`). The covariance is mapped without
+/// process noise every `step` for the provided duration, and the cones contain the `k_sigma` position uncertainty
+/// perpendicular to the line of sight.
+pub fn acquisition_aid<D, E>(
+    prop: PropInstance<'_, D, E>,
+    injection: &InjectionCovariance,
+    stations: &[GroundStation],
+    duration: Duration,
+    step: Duration,
+    k_sigma: f64,
+    cosm: &Cosm,
+) -> Result<Vec<StationAcquisition>, NyxError>
+where
+    D: Dynamics<StateType = Orbit>,
+    E: ErrorCtrl,
+{
+    if duration <= Duration::ZERO || step <= Duration::ZERO {
+        return Err(NyxError::CustomError(format!(
+            "acquisition aid duration ({duration}) and step ({step}) must be positive"
+        )));
+    }
+    if k_sigma <= 0.0 {
+        return Err(NyxError::CustomError(format!(
+            "uncertainty cones require a positive number of sigmas, got {k_sigma}"
+        )));
+    }
+
+    let start = prop.state.epoch;
+    let initial_covar = injection.covar_in_frame_of(&prop.state, cosm)?;
+    let epochs: Vec<Epoch> = (0..)
+        .map(|i| start + step * i as i64)
+        .take_while(|epoch| *epoch <= start + duration)
+        .collect();
+    let mapped = covariance_map(prop, initial_covar, &epochs, vec![], step)?;
+
+    Ok(stations
+        .iter()
+        .map(|station| StationAcquisition {
+            station: station.name.clone(),
+            k_sigma,
+            samples: mapped
+                .iter()
+                .map(|estimate| {
+                    let position_covar = estimate.covar.fixed_view::<3, 3>(0, 0).into_owned();
+                    pointing_of(
+                        station,
+                        estimate.nominal_state,
+                        &position_covar,
+                        k_sigma,
+                        cosm,
+                    )
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+/// Computes the pointing of the station toward the spacecraft and its uncertainty from the position covariance.
+fn pointing_of(
+    station: &GroundStation,
+    orbit: Orbit,
+    position_covar: &Matrix3<f64>,
+    k_sigma: f64,
+    cosm: &Cosm,
+) -> PointingSample {
+    let (azimuth_deg, elevation_deg, rx, tx) = station.azimuth_elevation_of(orbit, cosm);
+    let range_vec_km = rx.radius() - tx.radius();
+    let range_km = range_vec_km.norm();
+    let r_hat = range_vec_km / range_km;
+
+    // Sensitivity of the azimuth and elevation to the position, by central differences
+    let mut jacobian = Matrix2x3::zeros();
+    for i in 0..3 {
+        let mut delta = Vector3::zeros();
+        delta[i] = POINTING_PERTURBATION_KM;
+        let angles = |sign: f64| {
+            let mut perturbed = orbit;
+            perturbed.x_km += sign * delta[0];
+            perturbed.y_km += sign * delta[1];
+            perturbed.z_km += sign * delta[2];
+            let (az, el, _, _) = station.azimuth_elevation_of(perturbed, cosm);
+            (az, el)
+        };
+        let (az_plus, el_plus) = angles(1.0);
+        let (az_minus, el_minus) = angles(-1.0);
+        // Wrap the azimuth difference within [-180; 180] degrees
+        let d_az = (az_plus - az_minus + 540.0).rem_euclid(360.0) - 180.0;
+        jacobian[(0, i)] = d_az / (2.0 * POINTING_PERTURBATION_KM);
+        jacobian[(1, i)] = (el_plus - el_minus) / (2.0 * POINTING_PERTURBATION_KM);
+    }
+    let angles_covar = jacobian * position_covar * jacobian.transpose();
+
+    // Cone containing the largest position uncertainty perpendicular to the line of sight
+    let projection = Matrix3::identity() - r_hat * r_hat.transpose();
+    let cross_covar = projection * position_covar * projection;
+    let cross_sigma_km = cross_covar.symmetric_eigenvalues().max().max(0.0).sqrt();
+
+    PointingSample {
+        epoch: orbit.epoch,
+        azimuth_deg,
+        elevation_deg,
+        range_km,
+        azimuth_sigma_deg: angles_covar[(0, 0)].max(0.0).sqrt(),
+        elevation_sigma_deg: angles_covar[(1, 1)].max(0.0).sqrt(),
+        range_sigma_km: (r_hat.transpose() * position_covar * r_hat)[0]
+            .max(0.0)
+            .sqrt(),
+        cone_half_angle_deg: (k_sigma * cross_sigma_km / range_km).atan().to_degrees(),
+        visible: elevation_deg >= station.elevation_mask_deg,
+    }
+}
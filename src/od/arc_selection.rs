@@ -0,0 +1,203 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::msr::TrackingArc;
+use super::GroundStation;
+use crate::cosmic::{Bodies, Cosm, LightTimeCalc};
+use crate::errors::NyxError;
+use crate::linalg::{allocator::Allocator, DefaultAllocator};
+use crate::md::trajectory::{Interpolatable, Traj};
+use crate::od::Measurement;
+use crate::time::Epoch;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Geometry thresholds under which the measurements are likely corrupted by the transmission media: the troposphere at low
+/// elevation and the solar plasma near solar conjunction.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MediaEditConfig {
+    /// Measurements taken below this elevation from the station are edited, in degrees
+    pub min_elevation_deg: f64,
+    /// Measurements taken with a Sun-Earth-Probe angle below this value are edited, in degrees
+    pub min_sep_deg: f64,
+}
+
+impl Default for MediaEditConfig {
+    fn default() -> Self {
+        Self {
+            min_elevation_deg: 10.0,
+            min_sep_deg: 10.0,
+        }
+    }
+}
+
+/// Reason why a measurement is recommended for editing
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EditReason {
+    /// Elevation of the spacecraft from the station, in degrees
+    LowElevation(f64),
+    /// Sun-Earth-Probe angle seen from the station, in degrees
+    SolarConjunction(f64),
+}
+
+impl fmt::Display for EditReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LowElevation(el) => write!(f, "low elevation ({el:.3} deg)"),
+            Self::SolarConjunction(sep) => write!(f, "solar conjunction (SEP = {sep:.3} deg)"),
+        }
+    }
+}
+
+/// A measurement recommended for editing
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeasurementEdit {
+    /// Name of the tracking device
+    pub device: String,
+    /// Epoch of the measurement
+    pub epoch: Epoch,
+    /// Why this measurement should be edited
+    pub reason: EditReason,
+}
+
+impl fmt::Display for MeasurementEdit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} @ {}: {}", self.device, self.epoch, self.reason)
+    }
+}
+
+/// List of the measurements recommended for editing, in the order of the tracking arc
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EditList {
+    pub edits: Vec<MeasurementEdit>,
+}
+
+impl EditList {
+    /// Number of edited measurements
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Returns true if no measurement is edited
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Returns a new tracking arc without the edited measurements
+    pub fn apply<Msr>(&self, arc: &TrackingArc<Msr>) -> TrackingArc<Msr>
+    where
+        Msr: Measurement,
+        DefaultAllocator: Allocator<f64, Msr::MeasurementSize>,
+    {
+        let edited = self
+            .edits
+            .iter()
+            .map(|edit| (edit.device.as_str(), edit.epoch))
+            .collect::<HashSet<(&str, Epoch)>>();
+
+        TrackingArc {
+            device_cfg: arc.device_cfg.clone(),
+            measurements: arc
+                .measurements
+                .iter()
+                .filter(|(name, msr)| !edited.contains(&(name.as_str(), msr.epoch())))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for EditList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} edited measurement(s)", self.edits.len())?;
+        for edit in &self.edits {
+            writeln!(f, "\t{edit}")?;
+        }
+        Ok(())
+    }
+}
+
+impl MediaEditConfig {
+    /// Analyzes the geometry of each measurement of the tracking arc from the (reference or estimated) trajectory of the
+    /// spacecraft, and returns the list of the measurements recommended for editing.
+    ///
+    /// The Sun-Earth-Probe angle is computed from the location of the station. Measurements from devices which are not in
+    /// the provided list are ignored, as in the orbit determination process.
+    pub fn analyze<Msr, S>(
+        &self,
+        arc: &TrackingArc<Msr>,
+        devices: &HashMap<String, GroundStation>,
+        traj: &Traj<S>,
+        cosm: &Cosm,
+    ) -> Result<EditList, NyxError>
+    where
+        Msr: Measurement,
+        S: Interpolatable,
+        DefaultAllocator: Allocator<f64, Msr::MeasurementSize>
+            + Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        let mut edits = Vec::new();
+
+        for (name, msr) in &arc.measurements {
+            let device = match devices.get(name) {
+                Some(device) => device,
+                None => continue,
+            };
+
+            let epoch = msr.epoch();
+            let rx = *traj.at(epoch)?.orbit();
+            let (_, elevation_deg, rx, tx) = device.azimuth_elevation_of(rx, cosm);
+
+            let reason = if elevation_deg < self.min_elevation_deg {
+                Some(EditReason::LowElevation(elevation_deg))
+            } else {
+                let sun = cosm.celestial_state(
+                    Bodies::Sun.ephem_path(),
+                    epoch,
+                    rx.frame,
+                    LightTimeCalc::None,
+                );
+                let to_sun = sun.radius() - tx.radius();
+                let to_probe = rx.radius() - tx.radius();
+                let sep_deg = (to_sun.dot(&to_probe) / (to_sun.norm() * to_probe.norm()))
+                    .clamp(-1.0, 1.0)
+                    .acos()
+                    .to_degrees();
+                if sep_deg < self.min_sep_deg {
+                    Some(EditReason::SolarConjunction(sep_deg))
+                } else {
+                    None
+                }
+            };
+
+            if let Some(reason) = reason {
+                debug!("{name} @ {epoch}: recommend editing for {reason}");
+                edits.push(MeasurementEdit {
+                    device: name.clone(),
+                    epoch,
+                    reason,
+                });
+            }
+        }
+
+        Ok(EditList { edits })
+    }
+}
@@ -0,0 +1,620 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::msr::{RangeDoppler, TrackingArc};
+use super::simulator::TrackingDeviceSim;
+use super::{GroundStation, Measurement};
+use crate::cosmic::{Cosm, Orbit, SPEED_OF_LIGHT_KMS};
+use crate::errors::NyxError;
+use crate::io::watermark::prj_name_ver;
+use crate::io::ExportCfg;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, Matrix3, Vector3};
+use crate::md::trajectory::{Interpolatable, Traj};
+use crate::time::{Duration, Epoch, Format, Formatter, TimeSeries, Unit};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Number of ticks per second of the spacecraft clock in the SPICE SCLK kernels
+const SCLK_TICKS_PER_SECOND: f64 = 65536.0;
+
+/// Spectral densities of the noise of an onboard clock, which drive the growth of the covariance of its states.
+///
+/// These follow the usual three-state clock model, where each noise is a random walk of one of the states.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ClockNoise {
+    /// White frequency noise, which drives a random walk of the offset, in s^2/s
+    pub white_frequency: f64,
+    /// Random walk frequency noise, which drives a random walk of the drift, in s^2/s^3
+    pub random_walk_frequency: f64,
+    /// Random run frequency noise, which drives a random walk of the aging, in s^2/s^5
+    pub random_run_frequency: f64,
+}
+
+impl ClockNoise {
+    /// Returns the process noise accumulated over the provided duration
+    fn process_noise(&self, dt_s: f64) -> Matrix3<f64> {
+        let dt = dt_s.abs();
+        let (q1, q2, q3) = (
+            self.white_frequency,
+            self.random_walk_frequency,
+            self.random_run_frequency,
+        );
+        let q_01 = q2 * dt.powi(2) / 2.0 + q3 * dt.powi(4) / 8.0;
+        let q_02 = q3 * dt.powi(3) / 6.0;
+        let q_12 = q3 * dt.powi(2) / 2.0;
+        Matrix3::new(
+            q1 * dt + q2 * dt.powi(3) / 3.0 + q3 * dt.powi(5) / 20.0,
+            q_01,
+            q_02,
+            q_01,
+            q2 * dt + q3 * dt.powi(3) / 3.0,
+            q_12,
+            q_02,
+            q_12,
+            q3 * dt,
+        )
+    }
+}
+
+/// A spacecraft clock, whose offset from TDB is modeled by a bias, a drift, and an aging at an epoch, with their covariance.
+///
+/// The spacecraft clock (SCLK) reads the number of seconds past J2000 TDB, offset by the clock offset, i.e. it is ahead of
+/// TDB when its offset is positive.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpacecraftClock {
+    /// Epoch of the clock states
+    pub epoch: Epoch,
+    /// Offset of the clock from TDB, in seconds
+    pub bias_s: f64,
+    /// Rate of change of the offset, in s/s
+    pub drift_s_s: f64,
+    /// Rate of change of the drift, in s/s^2
+    pub aging_s_s2: f64,
+    /// Covariance of the bias, drift, and aging
+    pub covar: Matrix3<f64>,
+    /// Noise of the clock, used when propagating its covariance
+    pub noise: ClockNoise,
+}
+
+impl SpacecraftClock {
+    /// Initializes a new clock without uncertainty nor noise
+    pub fn new(epoch: Epoch, bias_s: f64, drift_s_s: f64, aging_s_s2: f64) -> Self {
+        Self {
+            epoch,
+            bias_s,
+            drift_s_s,
+            aging_s_s2,
+            covar: Matrix3::zeros(),
+            noise: ClockNoise::default(),
+        }
+    }
+
+    /// Returns a copy of this clock with the provided uncorrelated one sigma uncertainties of its bias (s), drift (s/s), and aging (s/s^2)
+    pub fn with_sigmas(
+        self,
+        bias_sigma_s: f64,
+        drift_sigma_s_s: f64,
+        aging_sigma_s_s2: f64,
+    ) -> Self {
+        let mut me = self;
+        me.covar = Matrix3::from_diagonal(&Vector3::new(
+            bias_sigma_s.powi(2),
+            drift_sigma_s_s.powi(2),
+            aging_sigma_s_s2.powi(2),
+        ));
+        me
+    }
+
+    /// Returns a copy of this clock with the provided noise
+    pub fn with_noise(self, noise: ClockNoise) -> Self {
+        let mut me = self;
+        me.noise = noise;
+        me
+    }
+
+    /// Returns the offset of this clock from TDB at the provided epoch, in seconds
+    pub fn offset_s(&self, epoch: Epoch) -> f64 {
+        let dt = (epoch - self.epoch).to_seconds();
+        self.bias_s + self.drift_s_s * dt + 0.5 * self.aging_s_s2 * dt.powi(2)
+    }
+
+    /// Returns the drift of this clock at the provided epoch, in s/s
+    pub fn drift_at(&self, epoch: Epoch) -> f64 {
+        self.drift_s_s + self.aging_s_s2 * (epoch - self.epoch).to_seconds()
+    }
+
+    /// Returns the reading of this clock at the provided epoch, in seconds
+    pub fn sclk_s(&self, epoch: Epoch) -> f64 {
+        epoch.to_tdb_seconds() + self.offset_s(epoch)
+    }
+
+    /// One sigma uncertainty of the offset, in seconds
+    pub fn bias_sigma_s(&self) -> f64 {
+        self.covar[(0, 0)].sqrt()
+    }
+
+    /// Propagates the states and the covariance of this clock to the provided epoch, forward or backward
+    pub fn propagate(&self, epoch: Epoch) -> Self {
+        let dt = (epoch - self.epoch).to_seconds();
+        let stm = Matrix3::new(1.0, dt, 0.5 * dt.powi(2), 0.0, 1.0, dt, 0.0, 0.0, 1.0);
+        Self {
+            epoch,
+            bias_s: self.offset_s(epoch),
+            drift_s_s: self.drift_at(epoch),
+            aging_s_s2: self.aging_s_s2,
+            covar: stm * self.covar * stm.transpose() + self.noise.process_noise(dt),
+            noise: self.noise,
+        }
+    }
+
+    /// Propagates this clock alongside the provided trajectory, returning the clock at the epoch of each of its states
+    pub fn along<S: Interpolatable>(&self, traj: &Traj<S>) -> Vec<Self>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        let mut clock = *self;
+        traj.states
+            .iter()
+            .map(|state| {
+                clock = clock.propagate(state.epoch());
+                clock
+            })
+            .collect()
+    }
+
+    /// Applies the offset of this clock to the one-way measurements of the provided arc, i.e. those of the devices without an
+    /// integration time, as if the signal were timed by this clock. Measurements of unknown devices are left unchanged.
+    ///
+    /// The one-way range is shortened by the offset times the speed of light, and the Doppler by the drift times the speed of light.
+    pub fn apply_to_one_way(
+        &self,
+        arc: &TrackingArc<RangeDoppler>,
+        devices: &[GroundStation],
+    ) -> TrackingArc<RangeDoppler> {
+        let one_way: Vec<&String> = devices
+            .iter()
+            .filter(|device| device.integration_time.is_none())
+            .map(|device| &device.name)
+            .collect();
+
+        let mut arc = arc.clone();
+        for (name, msr) in arc.measurements.iter_mut() {
+            if one_way.contains(&&*name) {
+                msr.obs[0] -= SPEED_OF_LIGHT_KMS * self.offset_s(msr.epoch);
+                msr.obs[1] -= SPEED_OF_LIGHT_KMS * self.drift_at(msr.epoch);
+            }
+        }
+        arc
+    }
+
+    /// Builds the time correlation of this clock from the start to the end epochs with the provided step, where the uncertainty of
+    /// each record is that of the propagated clock.
+    pub fn time_correlation(
+        &self,
+        start: Epoch,
+        end: Epoch,
+        step: Duration,
+    ) -> Result<TimeCorrelation, NyxError> {
+        if end <= start || step <= Duration::ZERO {
+            return Err(NyxError::CustomError(format!(
+                "time correlation requires a positive step and an end epoch after the start, got {start} to {end} with {step}"
+            )));
+        }
+
+        let clocks: Vec<Self> = TimeSeries::inclusive(start, end, step)
+            .map(|epoch| self.propagate(epoch))
+            .collect();
+
+        let records = clocks
+            .iter()
+            .enumerate()
+            .map(|(i, clock)| {
+                let sclk_s = clock.sclk_s(clock.epoch);
+                // Secant rate to the next record so that the correlation is continuous, and the instantaneous rate after the last one
+                let rate = match clocks.get(i + 1) {
+                    Some(next) => {
+                        (next.epoch - clock.epoch).to_seconds() / (next.sclk_s(next.epoch) - sclk_s)
+                    }
+                    None => 1.0 / (1.0 + clock.drift_s_s),
+                };
+                TimeCorrelationRecord {
+                    tdb: clock.epoch,
+                    sclk_s,
+                    rate,
+                    offset_sigma_s: clock.bias_sigma_s(),
+                }
+            })
+            .collect();
+
+        Ok(TimeCorrelation { records })
+    }
+}
+
+impl fmt::Display for SpacecraftClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: bias = {:.6e} ± {:.3e} s\tdrift = {:.6e} ± {:.3e} s/s\taging = {:.6e} ± {:.3e} s/s^2",
+            self.epoch,
+            self.bias_s,
+            self.covar[(0, 0)].sqrt(),
+            self.drift_s_s,
+            self.covar[(1, 1)].sqrt(),
+            self.aging_s_s2,
+            self.covar[(2, 2)].sqrt()
+        )
+    }
+}
+
+/// A record of the correlation between the spacecraft clock and TDB, valid until the next record.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimeCorrelationRecord {
+    /// Epoch of this record
+    pub tdb: Epoch,
+    /// Reading of the spacecraft clock at this epoch, in seconds
+    pub sclk_s: f64,
+    /// Rate of TDB with respect to the spacecraft clock from this record, in TDB seconds per SCLK second
+    pub rate: f64,
+    /// One sigma uncertainty of the clock offset at this epoch, in seconds
+    pub offset_sigma_s: f64,
+}
+
+/// A time correlation between the spacecraft clock (SCLK) and TDB, as a piecewise linear function of the clock reading like the
+/// SPICE SCLK kernels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeCorrelation {
+    /// Records in chronological order
+    pub records: Vec<TimeCorrelationRecord>,
+}
+
+impl TimeCorrelation {
+    /// Converts the provided spacecraft clock reading to its TDB epoch
+    pub fn tdb(&self, sclk_s: f64) -> Result<Epoch, NyxError> {
+        let record = self
+            .records
+            .iter()
+            .rev()
+            .find(|record| record.sclk_s <= sclk_s)
+            .ok_or_else(|| {
+                NyxError::CustomError(format!(
+                    "SCLK {sclk_s} s is before the start of the time correlation"
+                ))
+            })?;
+        Ok(record.tdb + (record.rate * (sclk_s - record.sclk_s)) * Unit::Second)
+    }
+
+    /// Converts the provided epoch to the reading of the spacecraft clock, in seconds
+    pub fn sclk_s(&self, epoch: Epoch) -> Result<f64, NyxError> {
+        let record = self
+            .records
+            .iter()
+            .rev()
+            .find(|record| record.tdb <= epoch)
+            .ok_or_else(|| {
+                NyxError::CustomError(format!(
+                    "{epoch} is before the start of the time correlation"
+                ))
+            })?;
+        Ok(record.sclk_s + (epoch - record.tdb).to_seconds() / record.rate)
+    }
+
+    /// Writes this time correlation to a SPICE SCLK kernel (type 1) of the spacecraft with the provided NAIF ID, e.g. -82.
+    ///
+    /// The clock has a single partition and two fields: the seconds past J2000 TDB as read by the clock, and 65536 ticks per second.
+    pub fn to_sclk_kernel<P: AsRef<Path>>(
+        &self,
+        path: P,
+        naif_id: i32,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, NyxError> {
+        let (first, last) = match (self.records.first(), self.records.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => {
+                return Err(NyxError::CustomError(
+                    "Cannot export an empty time correlation".to_string(),
+                ))
+            }
+        };
+        if first.sclk_s < 0.0 {
+            return Err(NyxError::CustomError(format!(
+                "SCLK kernels require a positive clock reading, got {} s",
+                first.sclk_s
+            )));
+        }
+
+        let path_buf = cfg.actual_path(path);
+
+        let file = File::create(&path_buf)
+            .map_err(|e| NyxError::CustomError(format!("File creation error: {e}")))?;
+        let mut writer = BufWriter::new(file);
+
+        let err_hdlr = |e| NyxError::CustomError(format!("Could not write: {e}"));
+
+        let id = naif_id.abs();
+        let iso8601_no_ts = Format::from_str("%Y-%m-%dT%H:%M:%S").unwrap();
+        let start_ticks = first.sclk_s * SCLK_TICKS_PER_SECOND;
+
+        writeln!(writer, "KPL/SCLK\n").map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "Generated by {} provided in AGPLv3 license -- https://nyxspace.com/",
+            prj_name_ver()
+        )
+        .map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "The clock reads the seconds past J2000 TDB offset by the clock offset, with {SCLK_TICKS_PER_SECOND} ticks per second.\n"
+        )
+        .map_err(err_hdlr)?;
+        writeln!(writer, "\\begindata\n").map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "SCLK_KERNEL_ID = ( @{} )\n",
+            Formatter::new(Epoch::now().unwrap(), iso8601_no_ts)
+        )
+        .map_err(err_hdlr)?;
+        writeln!(writer, "SCLK_DATA_TYPE_{id} = ( 1 )").map_err(err_hdlr)?;
+        // Parallel time system 1 is TDB
+        writeln!(writer, "SCLK01_TIME_SYSTEM_{id} = ( 1 )").map_err(err_hdlr)?;
+        writeln!(writer, "SCLK01_N_FIELDS_{id} = ( 2 )").map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "SCLK01_MODULI_{id} = ( 4294967296 {SCLK_TICKS_PER_SECOND} )"
+        )
+        .map_err(err_hdlr)?;
+        writeln!(writer, "SCLK01_OFFSETS_{id} = ( 0 0 )").map_err(err_hdlr)?;
+        writeln!(writer, "SCLK01_OUTPUT_DELIM_{id} = ( 1 )\n").map_err(err_hdlr)?;
+        writeln!(writer, "SCLK_PARTITION_START_{id} = ( {start_ticks:.3} )").map_err(err_hdlr)?;
+        writeln!(
+            writer,
+            "SCLK_PARTITION_END_{id} = ( {:.3} )\n",
+            last.sclk_s * SCLK_TICKS_PER_SECOND
+        )
+        .map_err(err_hdlr)?;
+        writeln!(writer, "SCLK01_COEFFICIENTS_{id} = (").map_err(err_hdlr)?;
+        for record in &self.records {
+            // Encoded SCLK from the start of the partition, TDB seconds past J2000, and TDB seconds per SCLK second
+            writeln!(
+                writer,
+                "    {:.3} {:.9} {:.17}",
+                record.sclk_s * SCLK_TICKS_PER_SECOND - start_ticks,
+                record.tdb.to_tdb_seconds(),
+                record.rate
+            )
+            .map_err(err_hdlr)?;
+        }
+        writeln!(writer, ")\n\n\\begintext").map_err(err_hdlr)?;
+
+        info!("SCLK kernel written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for TimeCorrelation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Time correlation of {} records", self.records.len())?;
+        for record in &self.records {
+            writeln!(
+                f,
+                "\t{}\tSCLK {:.6} s\trate {:.15}\t± {:.3e} s",
+                record.tdb, record.sclk_s, record.rate, record.offset_sigma_s
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimates the states of a spacecraft clock from the one-way range and Doppler measurements, with a linear batch least squares.
+///
+/// The trajectory must come from an orbit determination on two-way measurements, which do not depend on the spacecraft clock.
+/// The one-way measurements of the same devices, i.e. from devices without an integration time, are then computed on this
+/// trajectory, and their residuals are the offset and the drift of the clock times the speed of light.
+#[derive(Clone)]
+pub struct ClockEstimator {
+    /// Tracking devices, whose integration time tells whether their measurements are two-way
+    pub devices: HashMap<String, GroundStation>,
+    /// One sigma noise of the one-way range, in km
+    pub range_sigma_km: f64,
+    /// One sigma noise of the one-way Doppler, in km/s
+    pub doppler_sigma_km_s: f64,
+    pub cosm: Arc<Cosm>,
+}
+
+/// The result of a spacecraft clock estimation.
+#[derive(Copy, Clone, Debug)]
+pub struct ClockSolution {
+    /// Estimated clock at the epoch of the a priori clock
+    pub clock: SpacecraftClock,
+    /// Number of one-way measurements used in the estimation
+    pub num_one_way: usize,
+    /// Root mean square of the post-fit one-way range residuals, in km
+    pub one_way_range_rms_km: f64,
+    /// Root mean square of the post-fit one-way Doppler residuals, in km/s
+    pub one_way_doppler_rms_km_s: f64,
+    /// Number of two-way measurements used to check the trajectory
+    pub num_two_way: usize,
+    /// Root mean square of the two-way range residuals on the trajectory, in km, if there are any two-way measurements.
+    /// It should be at the noise level of the measurements: a large value means that the trajectory does not fit the tracking data.
+    pub two_way_range_rms_km: Option<f64>,
+}
+
+impl fmt::Display for ClockSolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Spacecraft clock solution: {}", self.clock)?;
+        write!(
+            f,
+            "\t{} one-way measurements: post-fit RMS {:.3e} km and {:.3e} km/s",
+            self.num_one_way, self.one_way_range_rms_km, self.one_way_doppler_rms_km_s
+        )?;
+        if let Some(rms) = self.two_way_range_rms_km {
+            write!(
+                f,
+                "\n\t{} two-way measurements: RMS {rms:.3e} km",
+                self.num_two_way
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl ClockEstimator {
+    /// Initializes a new estimator from the tracking devices and the one sigma noises of the one-way measurements
+    pub fn new(
+        devices: Vec<GroundStation>,
+        range_sigma_km: f64,
+        doppler_sigma_km_s: f64,
+        cosm: Arc<Cosm>,
+    ) -> Self {
+        Self {
+            devices: devices
+                .into_iter()
+                .map(|device| (device.name.clone(), device))
+                .collect(),
+            range_sigma_km,
+            doppler_sigma_km_s,
+            cosm,
+        }
+    }
+
+    /// Estimates the clock at the epoch of the a priori clock from the measurements of the arc on the provided trajectory.
+    /// The a priori covariance is only used if its diagonal is positive. Measurements of unknown devices, or which cannot be computed
+    /// on the trajectory (e.g. because the spacecraft is not visible), are ignored.
+    pub fn estimate(
+        &self,
+        a_priori: &SpacecraftClock,
+        traj: &Traj<Orbit>,
+        arc: &TrackingArc<RangeDoppler>,
+    ) -> Result<ClockSolution, NyxError> {
+        let mut devices = self.devices.clone();
+
+        // Residuals of the one-way measurements and their offset from the a priori epoch, and the two-way range residuals
+        let mut one_way = Vec::new();
+        let mut two_way_sq = Vec::new();
+        for (name, msr) in &arc.measurements {
+            let device = match devices.get_mut(name) {
+                Some(device) => device,
+                None => {
+                    debug!("ignoring measurement of unknown device {name}");
+                    continue;
+                }
+            };
+            let computed = match device.measure(msr.epoch, traj, None, self.cosm.clone())? {
+                Some(computed) => computed,
+                None => {
+                    debug!(
+                        "ignoring measurement of {name} at {}: not visible",
+                        msr.epoch
+                    );
+                    continue;
+                }
+            };
+            let residual = msr.observation() - computed.observation();
+            if device.integration_time.is_some() {
+                two_way_sq.push(residual[0].powi(2));
+            } else {
+                one_way.push(((msr.epoch - a_priori.epoch).to_seconds(), residual));
+            }
+        }
+
+        if one_way.len() < 2 {
+            return Err(NyxError::CustomError(format!(
+                "clock estimation requires at least two one-way measurements but got {}",
+                one_way.len()
+            )));
+        }
+
+        // Scale the drift and the aging by the time span of the measurements to keep the normal equations well conditioned
+        let span_s = one_way.iter().map(|(dt, _)| dt.abs()).fold(1.0, f64::max);
+        let scaling = Matrix3::from_diagonal(&Vector3::new(1.0, span_s, span_s.powi(2)));
+        let x0 = Vector3::new(a_priori.bias_s, a_priori.drift_s_s, a_priori.aging_s_s2);
+
+        // Partials of the one-way range and Doppler with respect to the scaled clock states
+        let partials = |dt: f64| {
+            let tau = dt / span_s;
+            (
+                -SPEED_OF_LIGHT_KMS * Vector3::new(1.0, tau, 0.5 * tau.powi(2)),
+                -SPEED_OF_LIGHT_KMS * Vector3::new(0.0, 1.0 / span_s, tau / span_s),
+            )
+        };
+
+        let mut info = Matrix3::zeros();
+        if a_priori.covar.diagonal().iter().all(|var| *var > 0.0) {
+            info += (scaling * a_priori.covar * scaling)
+                .try_inverse()
+                .ok_or(NyxError::SingularCovarianceMatrix)?;
+        }
+        let mut normal = Vector3::zeros();
+        let range_weight = self.range_sigma_km.powi(-2);
+        let doppler_weight = self.doppler_sigma_km_s.powi(-2);
+        let scaled_x0 = scaling * x0;
+        for (dt, residual) in &one_way {
+            let (h_range, h_doppler) = partials(*dt);
+            // Residuals with respect to the a priori clock
+            let y_range = residual[0] - h_range.dot(&scaled_x0);
+            let y_doppler = residual[1] - h_doppler.dot(&scaled_x0);
+            info += range_weight * h_range * h_range.transpose()
+                + doppler_weight * h_doppler * h_doppler.transpose();
+            normal += range_weight * y_range * h_range + doppler_weight * y_doppler * h_doppler;
+        }
+
+        let scaled_covar = info
+            .try_inverse()
+            .ok_or(NyxError::SingularCovarianceMatrix)?;
+        let unscaling = Matrix3::from_diagonal(&Vector3::new(1.0, 1.0 / span_s, span_s.powi(-2)));
+        let x = x0 + unscaling * (scaled_covar * normal);
+
+        let mut clock = *a_priori;
+        clock.bias_s = x[0];
+        clock.drift_s_s = x[1];
+        clock.aging_s_s2 = x[2];
+        clock.covar = unscaling * scaled_covar * unscaling;
+
+        // Post-fit residuals
+        let (mut range_sq, mut doppler_sq) = (0.0_f64, 0.0_f64);
+        for (dt, residual) in &one_way {
+            let epoch = a_priori.epoch + *dt * Unit::Second;
+            range_sq += (residual[0] + SPEED_OF_LIGHT_KMS * clock.offset_s(epoch)).powi(2);
+            doppler_sq += (residual[1] + SPEED_OF_LIGHT_KMS * clock.drift_at(epoch)).powi(2);
+        }
+
+        let solution = ClockSolution {
+            clock,
+            num_one_way: one_way.len(),
+            one_way_range_rms_km: (range_sq / one_way.len() as f64).sqrt(),
+            one_way_doppler_rms_km_s: (doppler_sq / one_way.len() as f64).sqrt(),
+            num_two_way: two_way_sq.len(),
+            two_way_range_rms_km: if two_way_sq.is_empty() {
+                None
+            } else {
+                Some((two_way_sq.iter().sum::<f64>() / two_way_sq.len() as f64).sqrt())
+            },
+        };
+
+        info!("{solution}");
+
+        Ok(solution)
+    }
+}
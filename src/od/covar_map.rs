@@ -0,0 +1,112 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::estimate::KfEstimate;
+use super::filter::kalman::KF;
+use super::filter::Filter;
+use super::snc::SNC3;
+use crate::dynamics::Dynamics;
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{Const, DefaultAllocator, Matrix1, OMatrix, U1, U3};
+use crate::propagators::error_ctrl::ErrorCtrl;
+use crate::propagators::PropInstance;
+use crate::time::{Duration, Epoch};
+use crate::State;
+
+/// Maps the initial covariance along the trajectory of the provided propagator to each of the provided epochs, without
+/// processing any measurement, e.g. for pre-mission navigation studies.
+///
+/// The covariance is mapped with the state transition matrix of the propagator, which must therefore support it (e.g. built
+/// `with_stm`), and the process noise of the applicable SNC, if any, is accumulated at each time update like in a Kalman
+/// filter. The covariance is time updated at each requested epoch and at least every `max_step`, which should be shorter than
+/// the disable time of the SNCs for their process noise to be applied.
+///
+/// Returns the estimate at each epoch, whose nominal state is that of the propagator. The epochs must be chronological and
+/// not before the initial state of the propagator.
+pub fn covariance_map<D, E>(
+    mut prop: PropInstance<'_, D, E>,
+    initial_covar: OMatrix<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>,
+    epochs: &[Epoch],
+    process_noise: Vec<SNC3>,
+    max_step: Duration,
+) -> Result<Vec<KfEstimate<D::StateType>>, NyxError>
+where
+    D: Dynamics,
+    E: ErrorCtrl,
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::Size, U1>
+        + Allocator<f64, U1, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::Size, U3>
+        + Allocator<f64, U3, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, Const<1>, U1>,
+    <DefaultAllocator as Allocator<f64, <D::StateType as State>::Size>>::Buffer: Copy,
+    <DefaultAllocator as Allocator<
+        f64,
+        <D::StateType as State>::Size,
+        <D::StateType as State>::Size,
+    >>::Buffer: Copy,
+{
+    if max_step <= Duration::ZERO {
+        return Err(NyxError::CustomError(format!(
+            "covariance mapping step must be positive, got {max_step}"
+        )));
+    }
+
+    let start = prop.state.epoch();
+    if let Some(first) = epochs.first() {
+        if *first < start {
+            return Err(NyxError::CustomError(format!(
+                "cannot map the covariance backward from {start} to {first}"
+            )));
+        }
+    }
+    if let Some(pair) = epochs.windows(2).find(|pair| pair[1] < pair[0]) {
+        return Err(NyxError::CustomError(format!(
+            "covariance mapping epochs must be chronological, {} is before {}",
+            pair[1], pair[0]
+        )));
+    }
+
+    prop.state.reset_stm();
+    let initial_estimate = KfEstimate::from_covar(prop.state, initial_covar);
+    // The measurement noise is unused since no measurement is processed
+    let mut kf =
+        KF::<D::StateType, U3, U1>::with_sncs(initial_estimate, process_noise, Matrix1::zeros());
+
+    let mut mapped = Vec::with_capacity(epochs.len());
+    for epoch in epochs {
+        while prop.state.epoch() < *epoch {
+            let next_epoch = if *epoch - prop.state.epoch() > max_step {
+                prop.state.epoch() + max_step
+            } else {
+                *epoch
+            };
+            prop.until_epoch(next_epoch)?;
+            kf.time_update(prop.state)?;
+            prop.state.reset_stm();
+        }
+        mapped.push(*kf.previous_estimate());
+    }
+
+    Ok(mapped)
+}
@@ -0,0 +1,317 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::estimate::{CovarEllipse, Estimate, KfEstimate};
+use super::filter::kalman::KF;
+use super::process::ODProcess;
+use super::simulator::{TrackingArcSim, TrkConfig};
+use super::snc::SNC3;
+use super::GroundStation;
+use crate::cosmic::{Cosm, Frame, Spacecraft};
+use crate::dynamics::SpacecraftDynamics;
+use crate::errors::NyxError;
+#[cfg(feature = "io")]
+use crate::io::watermark::pq_writer;
+#[cfg(feature = "io")]
+use crate::io::ExportCfg;
+use crate::linalg::{Matrix2, Matrix6};
+use crate::propagators::Propagator;
+use crate::time::{Epoch, Unit};
+use crate::State;
+#[cfg(feature = "io")]
+use arrow::array::{Array, Float64Builder, StringBuilder, UInt64Builder};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "io")]
+use parquet::arrow::ArrowWriter;
+use std::collections::HashMap;
+#[cfg(feature = "io")]
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "io")]
+use std::fs::File;
+#[cfg(feature = "io")]
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A covariance analysis of the delivery of a spacecraft to an encounter, e.g. the approach of an interplanetary cruise.
+///
+/// This only depends on the tracking geometry and on the noise models, not on actual measurement values: the tracking
+/// schedule is simulated on the nominal trajectory without any noise, so the conventional Kalman filter processing these
+/// measurements never deviates from the nominal trajectory, and only its covariance evolves.
+///
+/// For each data cutoff epoch, the covariance of the last estimate at or before that cutoff is mapped to the encounter epoch
+/// with the state transition matrix of the nominal trajectory (without any process noise after the cutoff), and projected
+/// onto the B-plane of the target frame. This produces the classic "delivery vs. data cutoff" curves.
+///
+/// The target frame must have the same orientation as the frame of the spacecraft (e.g. Mars J2000 and EME2000) because the
+/// covariance is not rotated when changing frames.
+pub struct DeliveryAnalysis {
+    /// Name of this analysis
+    pub name: String,
+    /// Nominal initial state of the spacecraft
+    pub spacecraft: Spacecraft,
+    /// Initial Cartesian covariance of the orbit, in the frame of the spacecraft
+    pub covar: Matrix6<f64>,
+    /// Dynamics of the nominal trajectory and of the filter
+    pub dynamics: SpacecraftDynamics,
+    /// Ground stations of the tracking network
+    pub stations: Vec<GroundStation>,
+    /// Tracking schedule of each ground station, by name
+    pub tracking: HashMap<String, TrkConfig>,
+    /// Measurement noise covariance of the range (km^2) and Doppler (km^2/s^2) measurements
+    pub measurement_noise: Matrix2<f64>,
+    /// State noise compensation of the filter
+    pub sncs: Vec<SNC3>,
+    /// Epoch of the encounter, where the delivery is computed
+    pub encounter: Epoch,
+    /// Frame of the B-plane of the encounter, e.g. Mars J2000
+    pub target_frame: Frame,
+    /// Data cutoff epochs
+    pub cutoffs: Vec<Epoch>,
+}
+
+impl DeliveryAnalysis {
+    /// Runs this analysis: propagation of the nominal trajectory until the encounter, simulation of the tracking schedule,
+    /// covariance processing of the measurements, and mapping of the covariance at each data cutoff to the encounter.
+    pub fn run(self, cosm: Arc<Cosm>) -> Result<DeliveryCurve, NyxError> {
+        let start = self.spacecraft.epoch();
+        if self.encounter <= start {
+            return Err(NyxError::CustomError(format!(
+                "encounter epoch {} is not after the initial epoch {start}",
+                self.encounter
+            )));
+        }
+
+        let mut nominal = self.spacecraft;
+        nominal.unset_stm();
+
+        let setup = Propagator::default(self.dynamics);
+        let stm_setup = setup.clone().with_stm();
+        let (_, traj) = setup.with(nominal).until_epoch_with_traj(self.encounter)?;
+
+        // Exact measurements of the nominal trajectory, so the residuals are all zero
+        let mut arc_sim = TrackingArcSim::with_seed(self.stations, traj, self.tracking, 0)?;
+        arc_sim.disallow_overlap();
+        let arc = arc_sim.generate_noiseless_measurements(cosm.clone())?;
+
+        if arc.measurements.len() < 2 {
+            return Err(NyxError::CustomError(format!(
+                "tracking schedule only generated {} measurement(s)",
+                arc.measurements.len()
+            )));
+        }
+
+        let initial_estimate = KfEstimate::from_covar(nominal.orbit.with_stm(), self.covar);
+        let kf = KF::with_sncs(initial_estimate, self.sncs, self.measurement_noise);
+
+        let mut odp = ODProcess::ckf(stm_setup.with(nominal), kf, None, cosm.clone());
+        odp.process_arc::<GroundStation>(&arc)?;
+
+        let mut cutoffs = self.cutoffs;
+        cutoffs.sort();
+
+        let mut points = Vec::with_capacity(cutoffs.len());
+        for cutoff in cutoffs {
+            // The estimates are chronological, so this is the number of estimates at or before the cutoff
+            let num_estimates = odp.estimates.partition_point(|est| est.epoch() <= cutoff);
+            let (orbit, covar) = match num_estimates {
+                0 => (nominal.orbit, self.covar),
+                n => (odp.estimates[n - 1].state(), odp.estimates[n - 1].covar()),
+            };
+            let num_msrs = odp.estimates[..num_estimates]
+                .iter()
+                .filter(|est| !est.predicted())
+                .count();
+
+            // Map the covariance to the encounter with the state transition matrix of the nominal trajectory
+            let mut mapping = stm_setup.with(nominal.with_orbit(orbit));
+            let at_encounter = mapping.until_epoch(self.encounter)?;
+            let stm = mapping.stm()?.fixed_view::<6, 6>(0, 0).into_owned();
+            let covar = stm * covar * stm.transpose();
+
+            let target_orbit = cosm.frame_chg(&at_encounter.orbit, self.target_frame);
+
+            points.push(DeliveryPoint {
+                cutoff,
+                num_msrs,
+                ellipse: CovarEllipse::bplane(&target_orbit, &covar)?,
+            });
+        }
+
+        Ok(DeliveryCurve {
+            name: self.name,
+            encounter: self.encounter,
+            target_frame: self.target_frame,
+            points,
+        })
+    }
+}
+
+/// The delivery uncertainty at the encounter for a single data cutoff epoch
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DeliveryPoint {
+    /// Epoch of the last measurement which may be processed
+    pub cutoff: Epoch,
+    /// Number of measurements processed until the cutoff
+    pub num_msrs: usize,
+    /// Delivered B-plane ellipse, centered on the nominal (B.T, B.R) in km
+    pub ellipse: CovarEllipse,
+}
+
+impl DeliveryPoint {
+    /// Returns the standard deviation of B.T, in km
+    pub fn b_t_sigma_km(&self) -> f64 {
+        self.ellipse.covar[(0, 0)].sqrt()
+    }
+
+    /// Returns the standard deviation of B.R, in km
+    pub fn b_r_sigma_km(&self) -> f64 {
+        self.ellipse.covar[(1, 1)].sqrt()
+    }
+}
+
+/// The delivered B-plane uncertainty versus the data cutoff epoch, as computed by a `DeliveryAnalysis`
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeliveryCurve {
+    /// Name of the analysis
+    pub name: String,
+    /// Epoch of the encounter
+    pub encounter: Epoch,
+    /// Frame of the B-plane
+    pub target_frame: Frame,
+    /// Delivery at each data cutoff, in chronological order
+    pub points: Vec<DeliveryPoint>,
+}
+
+impl DeliveryCurve {
+    /// Stores this curve to a parquet file with one row per data cutoff.
+    #[cfg(feature = "io")]
+    pub fn to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let path_buf = cfg.actual_path(path);
+
+        let hdrs = vec![
+            Field::new("Data cutoff:Gregorian UTC", DataType::Utf8, false),
+            Field::new("Data cutoff:TAI (s)", DataType::Float64, false),
+            Field::new("Time to encounter (days)", DataType::Float64, false),
+            Field::new("Number of measurements", DataType::UInt64, false),
+            Field::new("B.T (km)", DataType::Float64, false),
+            Field::new("B.R (km)", DataType::Float64, false),
+            Field::new("Sigma B.T (km)", DataType::Float64, false),
+            Field::new("Sigma B.R (km)", DataType::Float64, false),
+            Field::new("Semi-major axis sigma (km)", DataType::Float64, false),
+            Field::new("Semi-minor axis sigma (km)", DataType::Float64, false),
+            Field::new("Orientation (deg)", DataType::Float64, false),
+        ];
+
+        let mut utc_epoch = StringBuilder::new();
+        let mut tai_s = Float64Builder::new();
+        let mut days = Float64Builder::new();
+        let mut num_msrs = UInt64Builder::new();
+        let mut columns: Vec<Float64Builder> = (0..7).map(|_| Float64Builder::new()).collect();
+        for point in &self.points {
+            utc_epoch.append_value(format!("{}", point.cutoff));
+            tai_s.append_value(point.cutoff.to_tai_seconds());
+            days.append_value((self.encounter - point.cutoff).to_unit(Unit::Day));
+            num_msrs.append_value(point.num_msrs as u64);
+            let values = [
+                point.ellipse.center[0],
+                point.ellipse.center[1],
+                point.b_t_sigma_km(),
+                point.b_r_sigma_km(),
+                point.ellipse.sigmas[0],
+                point.ellipse.sigmas[1],
+                point.ellipse.angle_deg,
+            ];
+            for (column, value) in columns.iter_mut().zip(values) {
+                column.append_value(value);
+            }
+        }
+
+        let mut record: Vec<Arc<dyn Array>> = vec![
+            Arc::new(utc_epoch.finish()),
+            Arc::new(tai_s.finish()),
+            Arc::new(days.finish()),
+            Arc::new(num_msrs.finish()),
+        ];
+        for mut column in columns {
+            record.push(Arc::new(column.finish()));
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "Purpose".to_string(),
+            "Delivery covariance analysis".to_string(),
+        );
+        metadata.insert("Analysis".to_string(), self.name.clone());
+        metadata.insert("Encounter".to_string(), format!("{}", self.encounter));
+        metadata.insert("Target frame".to_string(), format!("{}", self.target_frame));
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!(
+            "Delivery curve of {} written to {}",
+            self.name,
+            path_buf.display()
+        );
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for DeliveryCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: delivery to {} at {}",
+            self.name, self.target_frame, self.encounter
+        )?;
+        for point in &self.points {
+            writeln!(
+                f,
+                "E-{:.2} days ({} msrs)\tB.T = {:.3} ± {:.3} km\tB.R = {:.3} ± {:.3} km\tSMAA = {:.3} km\tSMIA = {:.3} km\tθ = {:.2} deg",
+                (self.encounter - point.cutoff).to_unit(Unit::Day),
+                point.num_msrs,
+                point.ellipse.center[0],
+                point.b_t_sigma_km(),
+                point.ellipse.center[1],
+                point.b_r_sigma_km(),
+                point.ellipse.sigmas[0],
+                point.ellipse.sigmas[1],
+                point.ellipse.angle_deg
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,328 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::msr::DeltaDor;
+use super::noise::GaussMarkov;
+use super::simulator::Visibility;
+use super::{GroundStation, TrackingDeviceSim};
+use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::io::{ConfigRepr, Configurable};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, Vector3};
+use crate::md::prelude::Traj;
+use crate::md::trajectory::Interpolatable;
+use crate::time::{Duration, Epoch};
+use crate::{NyxError, Spacecraft};
+use rand_pcg::Pcg64Mcg;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// A radio source of the quasar catalog, used as the angular reference of the Delta-DOR measurements.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Quasar {
+    pub name: String,
+    /// Right ascension in the J2000 axes, in degrees
+    pub right_ascension_deg: f64,
+    /// Declination in the J2000 axes, in degrees
+    pub declination_deg: f64,
+}
+
+impl Quasar {
+    pub fn new(name: String, right_ascension_deg: f64, declination_deg: f64) -> Self {
+        Self {
+            name,
+            right_ascension_deg,
+            declination_deg,
+        }
+    }
+
+    /// Unit direction of this quasar in the J2000 axes
+    pub fn direction(&self) -> Vector3<f64> {
+        let (sin_ra, cos_ra) = self.right_ascension_deg.to_radians().sin_cos();
+        let (sin_dec, cos_dec) = self.declination_deg.to_radians().sin_cos();
+        Vector3::new(cos_dec * cos_ra, cos_dec * sin_ra, sin_dec)
+    }
+
+    /// Angular separation between this quasar and the provided direction, in degrees
+    pub fn separation_deg(&self, direction: &Vector3<f64>) -> f64 {
+        self.direction().angle(direction).to_degrees()
+    }
+}
+
+impl ConfigRepr for Quasar {}
+
+/// DeltaDorBaseline defines the pair of ground stations of a Delta-DOR baseline (e.g. Goldstone and Madrid), which alternately
+/// observe the spacecraft and the closest quasar of its catalog.
+///
+/// A measurement is only available when both stations see the spacecraft above their elevation mask, and a quasar of the
+/// catalog is within the maximum separation from the spacecraft.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeltaDorBaseline {
+    pub name: String,
+    /// Reference station of the baseline: the differential range is the range from this station minus that from the other one
+    pub first_station: GroundStation,
+    pub second_station: GroundStation,
+    /// Catalog of the candidate reference quasars
+    pub quasars: Vec<Quasar>,
+    /// Maximum angular separation between the spacecraft and its reference quasar, in degrees
+    pub max_separation_deg: f64,
+    /// Noise on the quasar referenced differential range
+    pub noise_km: Option<GaussMarkov>,
+}
+
+impl DeltaDorBaseline {
+    /// Initializes a new baseline between both stations with the provided quasar catalog, a maximum separation of 10 degrees, and
+    /// without noise.
+    pub fn new(
+        name: String,
+        first_station: GroundStation,
+        second_station: GroundStation,
+        quasars: Vec<Quasar>,
+    ) -> Self {
+        Self {
+            name,
+            first_station,
+            second_station,
+            quasars,
+            max_separation_deg: 10.0,
+            noise_km: None,
+        }
+    }
+
+    /// Returns the baseline vector from the first station to the second one at the provided epoch and in the provided frame, in km
+    pub fn baseline_km(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Vector3<f64> {
+        cosm.frame_chg(&self.second_station.to_orbit(epoch), frame)
+            .radius()
+            - cosm
+                .frame_chg(&self.first_station.to_orbit(epoch), frame)
+                .radius()
+    }
+
+    /// Returns the quasar of the catalog which is closest to the direction of the receiver from the first station, if it is within
+    /// the maximum separation.
+    pub fn reference_quasar(&self, rx: Orbit, cosm: &Cosm) -> Option<&Quasar> {
+        let tx = cosm.frame_chg(&self.first_station.to_orbit(rx.epoch), rx.frame);
+        let direction = rx.radius() - tx.radius();
+        self.quasars
+            .iter()
+            .map(|quasar| (quasar.separation_deg(&direction), quasar))
+            .filter(|(separation_deg, _)| *separation_deg <= self.max_separation_deg)
+            .min_by(|(sep1, _), (sep2, _)| sep1.partial_cmp(sep2).unwrap())
+            .map(|(_, quasar)| quasar)
+    }
+
+    /// Returns the lowest elevation of the receiver from both stations, in degrees
+    pub fn common_elevation_deg(&self, rx: Orbit, cosm: &Cosm) -> f64 {
+        self.first_station
+            .azimuth_elevation_of(rx, cosm)
+            .1
+            .min(self.second_station.azimuth_elevation_of(rx, cosm).1)
+    }
+
+    /// Elevation mask of the baseline, i.e. the largest of the elevation masks of both stations
+    pub fn elevation_mask_deg(&self) -> f64 {
+        self.first_station
+            .elevation_mask_deg
+            .max(self.second_station.elevation_mask_deg)
+    }
+
+    /// Computes the intervals during which the provided trajectory is seen from both stations, cf. `GroundStation::visibility_intervals`.
+    pub fn visibility_intervals<S: Interpolatable>(
+        &self,
+        traj: &Traj<S>,
+        step: Duration,
+        cosm: &Cosm,
+    ) -> Result<Visibility, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        Visibility::compute(
+            self.name.clone(),
+            self.elevation_mask_deg(),
+            traj,
+            step,
+            |state| self.common_elevation_deg(*state.orbit(), cosm),
+        )
+    }
+
+    /// Returns the noise on the measurement at the provided epoch
+    fn noise(&mut self, epoch: Epoch, rng: Option<&mut Pcg64Mcg>) -> Result<f64, NyxError> {
+        match rng {
+            Some(rng) => Ok(self
+                .noise_km
+                .ok_or_else(|| NyxError::CustomError("Delta-DOR noise not configured".to_string()))?
+                .next_bias(epoch, rng)),
+            None => Ok(0.0),
+        }
+    }
+
+    fn measure_orbit(
+        &mut self,
+        rx: Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: &Cosm,
+    ) -> Result<Option<DeltaDor>, NyxError> {
+        let elevation_deg = self.common_elevation_deg(rx, cosm);
+        if elevation_deg < self.elevation_mask_deg() {
+            debug!(
+                "{} (el. mask {:.3} deg), object at {elevation_deg:.3} deg -- no measurement",
+                self.name,
+                self.elevation_mask_deg()
+            );
+            return Ok(None);
+        }
+
+        let quasar_direction = match self.reference_quasar(rx, cosm) {
+            Some(quasar) => quasar.direction(),
+            None => {
+                debug!(
+                    "{} no quasar within {:.3} deg of the object -- no measurement",
+                    self.name, self.max_separation_deg
+                );
+                return Ok(None);
+            }
+        };
+
+        let tx_first = cosm.frame_chg(&self.first_station.to_orbit(rx.epoch), rx.frame);
+        let tx_second = cosm.frame_chg(&self.second_station.to_orbit(rx.epoch), rx.frame);
+        let noise_km = self.noise(rx.epoch, rng)?;
+
+        Ok(Some(DeltaDor::new(
+            rx,
+            tx_first,
+            tx_second,
+            &quasar_direction,
+            noise_km,
+        )))
+    }
+}
+
+impl ConfigRepr for DeltaDorBaseline {}
+
+impl Configurable for DeltaDorBaseline {
+    type IntermediateRepr = DeltaDorBaseline;
+
+    fn from_config(
+        cfg: Self::IntermediateRepr,
+        _cosm: Arc<Cosm>,
+    ) -> Result<Self, crate::io::ConfigError>
+    where
+        Self: Sized,
+    {
+        Ok(cfg)
+    }
+
+    fn to_config(&self) -> Result<Self::IntermediateRepr, crate::io::ConfigError> {
+        Ok(self.clone())
+    }
+}
+
+impl TrackingDeviceSim<Orbit, DeltaDor> for DeltaDorBaseline {
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Orbit>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<DeltaDor>, NyxError> {
+        self.measure_orbit(traj.at(epoch)?, rng, &cosm)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// The location of the baseline is that of its first station
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
+        cosm.frame_chg(&self.first_station.to_orbit(epoch), frame)
+    }
+
+    fn visibility(
+        &self,
+        traj: &Traj<Orbit>,
+        step: Duration,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<Visibility>, NyxError> {
+        self.visibility_intervals(traj, step, &cosm).map(Some)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<DeltaDor>, NyxError> {
+        self.measure_orbit(rx, rng, &cosm)
+    }
+}
+
+impl TrackingDeviceSim<Spacecraft, DeltaDor> for DeltaDorBaseline {
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Spacecraft>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<DeltaDor>, NyxError> {
+        self.measure_orbit(traj.at(epoch)?.orbit, rng, &cosm)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// The location of the baseline is that of its first station
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
+        cosm.frame_chg(&self.first_station.to_orbit(epoch), frame)
+    }
+
+    fn visibility(
+        &self,
+        traj: &Traj<Spacecraft>,
+        step: Duration,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<Visibility>, NyxError> {
+        self.visibility_intervals(traj, step, &cosm).map(Some)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Spacecraft,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<DeltaDor>, NyxError> {
+        self.measure_orbit(rx.orbit, rng, &cosm)
+    }
+}
+
+impl fmt::Display for DeltaDorBaseline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} (Delta-DOR from {} to {}, {} quasars within {:.1} deg)",
+            self.name,
+            self.first_station.name,
+            self.second_station.name,
+            self.quasars.len(),
+            self.max_separation_deg
+        )
+    }
+}
@@ -0,0 +1,167 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::estimate::{Estimate, KfEstimate};
+use crate::cosmic::Spacecraft;
+use crate::dynamics::DensityCorrection;
+use crate::errors::NyxError;
+use crate::time::{Duration, Epoch, Unit};
+use std::fmt;
+
+/// Index of the coefficient of drag in the spacecraft estimation vector
+const CD_INDEX: usize = 7;
+
+/// A drag scale factor estimated by an orbit determination, i.e. the ratio of the estimated coefficient of drag to its nominal value.
+///
+/// Errors in the density model are absorbed by the estimated coefficient of drag, so this scale factor is an estimate of the
+/// scale factor of the density.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DragScaleSample {
+    /// Epoch of the estimate
+    pub epoch: Epoch,
+    /// Estimated scale factor
+    pub scale: f64,
+    /// One sigma uncertainty of the scale factor
+    pub sigma: f64,
+}
+
+impl DragScaleSample {
+    /// Builds a sample from the estimate of an orbit determination which solved for the coefficient of drag
+    pub fn from_estimate(estimate: &KfEstimate<Spacecraft>, nominal_cd: f64) -> Self {
+        Self {
+            epoch: estimate.epoch(),
+            scale: estimate.state().drag.cd / nominal_cd,
+            sigma: estimate.covar[(CD_INDEX, CD_INDEX)].sqrt() / nominal_cd,
+        }
+    }
+}
+
+impl fmt::Display for DragScaleSample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: drag scale {:.6} ± {:.3e}",
+            self.epoch, self.scale, self.sigma
+        )
+    }
+}
+
+/// Fits a short-term density correction from a history of drag scale factors estimated by successive orbit determinations,
+/// e.g. the final estimate of each daily orbit determination, to improve the predictions during geomagnetic storms.
+///
+/// The scale factor and its rate at the most recent estimate are fitted by a weighted linear least squares on the estimates
+/// within the fit window. The time constant of the return to the nominal density is fitted on the decaying phases of the whole
+/// history, i.e. on consecutive estimates whose deviation from unity decreases, or the default time constant is used if the
+/// history has no such phase.
+#[derive(Copy, Clone, Debug)]
+pub struct DensityCalibration {
+    /// Only the estimates within this duration before the most recent one are used to fit the scale factor and its rate
+    pub window: Duration,
+    /// Time constant used if it cannot be fitted from the history
+    pub default_time_constant: Duration,
+}
+
+impl DensityCalibration {
+    /// Initializes a new density calibration
+    pub fn new(window: Duration, default_time_constant: Duration) -> Self {
+        Self {
+            window,
+            default_time_constant,
+        }
+    }
+
+    /// Fits the density correction from the provided history of drag scale factors, in any order
+    pub fn fit(&self, history: &[DragScaleSample]) -> Result<DensityCorrection, NyxError> {
+        if history.is_empty() {
+            return Err(NyxError::CustomError(
+                "density calibration requires at least one drag scale estimate".to_string(),
+            ));
+        }
+        if let Some(sample) = history.iter().find(|sample| sample.sigma <= 0.0) {
+            return Err(NyxError::CustomError(format!(
+                "drag scale estimates must have a positive uncertainty: {sample}"
+            )));
+        }
+
+        let mut history = history.to_vec();
+        history.sort_by_key(|sample| sample.epoch);
+        let ref_epoch = history.last().unwrap().epoch;
+
+        // Weighted least squares of the scale and its rate at the reference epoch: scale(t) = a + b * (t - t_ref)
+        let (mut s_w, mut s_wt, mut s_wtt, mut s_ws, mut s_wts) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for sample in history
+            .iter()
+            .filter(|sample| ref_epoch - sample.epoch <= self.window)
+        {
+            let w = sample.sigma.powi(-2);
+            let t = (sample.epoch - ref_epoch).to_unit(Unit::Day);
+            s_w += w;
+            s_wt += w * t;
+            s_wtt += w * t * t;
+            s_ws += w * sample.scale;
+            s_wts += w * t * sample.scale;
+        }
+        let det = s_w * s_wtt - s_wt * s_wt;
+        let (scale, rate_per_day) = if det > f64::EPSILON * s_w * s_wtt {
+            (
+                (s_wtt * s_ws - s_wt * s_wts) / det,
+                (s_w * s_wts - s_wt * s_ws) / det,
+            )
+        } else {
+            // A single epoch in the window: no trend
+            (s_ws / s_w, 0.0)
+        };
+
+        // Decay rate k of the deviations from unity, d(t + dt) = d(t) exp(-k dt), by least squares on the decaying phases
+        let (mut s_dt2, mut s_dt_log) = (0.0, 0.0);
+        for pair in history.windows(2) {
+            let (prev, next) = (pair[0].scale - 1.0, pair[1].scale - 1.0);
+            let ratio = next / prev;
+            if ratio > 0.0 && ratio < 1.0 {
+                let dt = (pair[1].epoch - pair[0].epoch).to_seconds();
+                s_dt2 += dt * dt;
+                s_dt_log += dt * ratio.ln();
+            }
+        }
+        let time_constant = if s_dt_log < 0.0 {
+            (-s_dt2 / s_dt_log) * Unit::Second
+        } else {
+            self.default_time_constant
+        };
+
+        Ok(DensityCorrection {
+            ref_epoch,
+            scale,
+            rate_per_day,
+            time_constant,
+        })
+    }
+
+    /// Fits the density correction from the estimates of successive orbit determinations which solved for the coefficient of drag
+    pub fn fit_estimates(
+        &self,
+        estimates: &[KfEstimate<Spacecraft>],
+        nominal_cd: f64,
+    ) -> Result<DensityCorrection, NyxError> {
+        let history = estimates
+            .iter()
+            .map(|estimate| DragScaleSample::from_estimate(estimate, nominal_cd))
+            .collect::<Vec<DragScaleSample>>();
+        self.fit(&history)
+    }
+}
@@ -0,0 +1,420 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{BPlane, Frame, Orbit};
+use crate::linalg::{Matrix2, Matrix2x3, Matrix2x6, Matrix3, Matrix6, Vector2, Vector3};
+use crate::NyxError;
+use nalgebra::linalg::SymmetricEigen;
+use rand::SeedableRng;
+use rand_distr::{Distribution, StandardNormal};
+use rand_pcg::Pcg64Mcg;
+use std::f64::consts::{FRAC_2_SQRT_PI, PI, SQRT_2};
+use std::fmt;
+use std::path::Path;
+
+/// Returns the error function, from its series expansion with positive terms (Abramowitz & Stegun 7.1.6)
+fn erf(x: f64) -> f64 {
+    if x < 0.0 {
+        return -erf(-x);
+    } else if x > 6.0 {
+        return 1.0;
+    }
+    let mut term = x;
+    let mut sum = x;
+    let mut n = 0.0;
+    while term > 1e-17 * sum {
+        n += 1.0;
+        term *= 2.0 * x * x / (2.0 * n + 1.0);
+        sum += term;
+    }
+    FRAC_2_SQRT_PI * (-x * x).exp() * sum
+}
+
+/// Checks that the provided eigen values are those of a positive semi definite matrix, and returns the sigmas in descending order
+/// with the indexes of the associated eigen vectors.
+fn sorted_sigmas(eigenvalues: &[f64], scale: f64) -> Result<Vec<(usize, f64)>, NyxError> {
+    let mut sigmas = Vec::with_capacity(eigenvalues.len());
+    for (i, lambda) in eigenvalues.iter().enumerate() {
+        // Allow for round off errors on singular covariances
+        if *lambda < -1e-12 * scale || !lambda.is_finite() {
+            return Err(NyxError::CovarianceMatrixNotPsd);
+        }
+        sigmas.push((i, lambda.max(0.0).sqrt()));
+    }
+    sigmas.sort_by(|a, b| b.1.total_cmp(&a.1));
+    Ok(sigmas)
+}
+
+/// A three dimensional covariance ellipsoid, e.g. of the position or velocity of an orbit estimate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CovarEllipsoid {
+    /// Center of the ellipsoid, i.e. the mean
+    pub center: Vector3<f64>,
+    /// Covariance of the distribution
+    pub covar: Matrix3<f64>,
+    /// One sigma standard deviation along each principal axis, in descending order
+    pub sigmas: Vector3<f64>,
+    /// Unit principal axes, as columns in the same order as the sigmas
+    pub axes: Matrix3<f64>,
+}
+
+impl CovarEllipsoid {
+    /// Builds the ellipsoid of the provided covariance, which must be positive semi definite
+    pub fn new(center: Vector3<f64>, covar: Matrix3<f64>) -> Result<Self, NyxError> {
+        let covar = 0.5 * (covar + covar.transpose());
+        let eigen = SymmetricEigen::new(covar);
+        let sorted = sorted_sigmas(eigen.eigenvalues.as_slice(), covar.norm())?;
+        let mut sigmas = Vector3::zeros();
+        let mut axes = Matrix3::zeros();
+        for (col, (idx, sigma)) in sorted.iter().enumerate() {
+            sigmas[col] = *sigma;
+            axes.set_column(col, &eigen.eigenvectors.column(*idx));
+        }
+        Ok(Self {
+            center,
+            covar,
+            sigmas,
+            axes,
+        })
+    }
+
+    /// Builds the position ellipsoid of an orbit from its Cartesian covariance in the frame of the orbit
+    pub fn position(orbit: &Orbit, covar: &Matrix6<f64>) -> Result<Self, NyxError> {
+        Self::new(orbit.radius(), covar.fixed_view::<3, 3>(0, 0).into())
+    }
+
+    /// Builds the velocity ellipsoid of an orbit from its Cartesian covariance in the frame of the orbit
+    pub fn velocity(orbit: &Orbit, covar: &Matrix6<f64>) -> Result<Self, NyxError> {
+        Self::new(orbit.velocity(), covar.fixed_view::<3, 3>(3, 3).into())
+    }
+
+    /// Builds the position ellipsoid of an orbit in its RIC frame (radial, in-track, cross-track), centered on the orbit,
+    /// from its Cartesian covariance in the frame of the orbit
+    pub fn position_ric(orbit: &Orbit, covar: &Matrix6<f64>) -> Result<Self, NyxError> {
+        // The DCM rotates from RIC to the inertial frame
+        let dcm = orbit.dcm_from_traj_frame(Frame::RIC)?;
+        let pos_covar: Matrix3<f64> = covar.fixed_view::<3, 3>(0, 0).into();
+        Self::new(Vector3::zeros(), dcm.transpose() * pos_covar * dcm)
+    }
+
+    /// Returns the semi-axes of the k-sigma ellipsoid, as columns in descending order
+    pub fn semi_axes(&self, k_sigma: f64) -> Matrix3<f64> {
+        self.axes * Matrix3::from_diagonal(&(k_sigma * self.sigmas))
+    }
+
+    /// Returns the Mahalanobis distance of the provided point, i.e. the k-sigma of the ellipsoid on which this point lies
+    pub fn mahalanobis(&self, point: &Vector3<f64>) -> f64 {
+        let delta = self.axes.transpose() * (point - self.center);
+        delta
+            .iter()
+            .zip(self.sigmas.iter())
+            .map(|(d, s)| {
+                if *s > 0.0 {
+                    (d / s).powi(2)
+                } else if d.abs() > 0.0 {
+                    f64::INFINITY
+                } else {
+                    0.0
+                }
+            })
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Returns whether the provided point is within the k-sigma ellipsoid
+    pub fn contains(&self, point: &Vector3<f64>, k_sigma: f64) -> bool {
+        self.mahalanobis(point) <= k_sigma
+    }
+
+    /// Returns the probability that a sample lies within the k-sigma ellipsoid (e.g. 19.9%, 73.9% and 97.1% for 1, 2 and 3 sigmas)
+    pub fn containment_probability(k_sigma: f64) -> f64 {
+        let k = k_sigma.max(0.0);
+        erf(k / SQRT_2) - (2.0 / PI).sqrt() * k * (-0.5 * k * k).exp()
+    }
+
+    /// Returns the k-sigma of the ellipsoid which contains the provided probability
+    pub fn sigma_for_probability(probability: f64) -> Result<f64, NyxError> {
+        if !(0.0..1.0).contains(&probability) {
+            return Err(NyxError::CustomError(format!(
+                "containment probability must be in [0, 1), got {probability}"
+            )));
+        }
+        let (mut lo, mut hi) = (0.0, 40.0);
+        while hi - lo > 1e-12 {
+            let mid = 0.5 * (lo + hi);
+            if Self::containment_probability(mid) < probability {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Ok(0.5 * (lo + hi))
+    }
+
+    /// Returns the marginal ellipse on the plane spanned by the provided unit vectors, which must be orthogonal
+    pub fn project(&self, u: &Vector3<f64>, v: &Vector3<f64>) -> Result<CovarEllipse, NyxError> {
+        if (u.norm() - 1.0).abs() > 1e-9 || (v.norm() - 1.0).abs() > 1e-9 || u.dot(v).abs() > 1e-9 {
+            return Err(NyxError::CustomError(
+                "projection plane must be defined by two orthonormal vectors".to_string(),
+            ));
+        }
+        let basis = Matrix2x3::from_rows(&[u.transpose(), v.transpose()]);
+        CovarEllipse::new(basis * self.center, basis * self.covar * basis.transpose())
+    }
+
+    /// Estimates the probability that a sample lies within the provided region with a seeded Monte Carlo
+    pub fn probability<F: Fn(&Vector3<f64>) -> bool>(
+        &self,
+        region: F,
+        samples: usize,
+        seed: u64,
+    ) -> f64 {
+        let mut rng = Pcg64Mcg::seed_from_u64(seed);
+        let sqrt_covar = self.axes * Matrix3::from_diagonal(&self.sigmas);
+        let within = (0..samples)
+            .filter(|_| {
+                let z = Vector3::from_fn(|_, _| StandardNormal.sample(&mut rng));
+                region(&(self.center + sqrt_covar * z))
+            })
+            .count();
+        within as f64 / samples as f64
+    }
+
+    /// Returns points on the surface of the k-sigma ellipsoid on a latitude and longitude grid, e.g. for plotting
+    pub fn surface(&self, k_sigma: f64, num_lat: usize, num_lon: usize) -> Vec<Vector3<f64>> {
+        let semi_axes = self.semi_axes(k_sigma);
+        let mut points = Vec::with_capacity(num_lat * num_lon);
+        for i in 0..num_lat {
+            let lat = -0.5 * PI + PI * i as f64 / (num_lat.max(2) - 1) as f64;
+            for j in 0..num_lon {
+                let lon = 2.0 * PI * j as f64 / num_lon as f64;
+                let unit = Vector3::new(lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin());
+                points.push(self.center + semi_axes * unit);
+            }
+        }
+        points
+    }
+}
+
+impl fmt::Display for CovarEllipsoid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "covariance ellipsoid with 1-sigma semi-axes")?;
+        for i in 0..3 {
+            let axis = self.axes.column(i);
+            write!(
+                f,
+                " {:.6e} along [{:.4}, {:.4}, {:.4}]",
+                self.sigmas[i], axis[0], axis[1], axis[2]
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A two dimensional covariance ellipse, e.g. the projection of an ellipsoid onto a plane or the B-plane dispersion of a flyby.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CovarEllipse {
+    /// Center of the ellipse, i.e. the mean
+    pub center: Vector2<f64>,
+    /// Covariance of the distribution
+    pub covar: Matrix2<f64>,
+    /// One sigma standard deviation along the semi-major and semi-minor axes
+    pub sigmas: Vector2<f64>,
+    /// Angle of the semi-major axis from the first axis towards the second, in degrees between -90 and 90
+    pub angle_deg: f64,
+}
+
+impl CovarEllipse {
+    /// Builds the ellipse of the provided covariance, which must be positive semi definite
+    pub fn new(center: Vector2<f64>, covar: Matrix2<f64>) -> Result<Self, NyxError> {
+        let covar = 0.5 * (covar + covar.transpose());
+        let eigen = SymmetricEigen::new(covar);
+        let sorted = sorted_sigmas(eigen.eigenvalues.as_slice(), covar.norm())?;
+        let major = eigen.eigenvectors.column(sorted[0].0);
+        let mut angle_deg = major[1].atan2(major[0]).to_degrees();
+        if angle_deg > 90.0 {
+            angle_deg -= 180.0;
+        } else if angle_deg <= -90.0 {
+            angle_deg += 180.0;
+        }
+        Ok(Self {
+            center,
+            covar,
+            sigmas: Vector2::new(sorted[0].1, sorted[1].1),
+            angle_deg,
+        })
+    }
+
+    /// Builds the ellipse of the B-plane (B.T, B.R) of a hyperbolic orbit from its Cartesian covariance in the frame of the orbit
+    pub fn bplane(orbit: &Orbit, covar: &Matrix6<f64>) -> Result<Self, NyxError> {
+        let bplane = BPlane::new(*orbit)?;
+        let jacobian = Matrix2x6::new(
+            bplane.b_t.wtr_x(),
+            bplane.b_t.wtr_y(),
+            bplane.b_t.wtr_z(),
+            bplane.b_t.wtr_vx(),
+            bplane.b_t.wtr_vy(),
+            bplane.b_t.wtr_vz(),
+            bplane.b_r.wtr_x(),
+            bplane.b_r.wtr_y(),
+            bplane.b_r.wtr_z(),
+            bplane.b_r.wtr_vx(),
+            bplane.b_r.wtr_vy(),
+            bplane.b_r.wtr_vz(),
+        );
+        Self::new(
+            Vector2::new(bplane.b_dot_t(), bplane.b_dot_r()),
+            jacobian * covar * jacobian.transpose(),
+        )
+    }
+
+    /// Returns the semi-major and semi-minor axes of the k-sigma ellipse
+    pub fn semi_axes(&self, k_sigma: f64) -> (f64, f64) {
+        (k_sigma * self.sigmas[0], k_sigma * self.sigmas[1])
+    }
+
+    /// Returns the Mahalanobis distance of the provided point, i.e. the k-sigma of the ellipse on which this point lies
+    pub fn mahalanobis(&self, point: &Vector2<f64>) -> f64 {
+        let (sin, cos) = self.angle_deg.to_radians().sin_cos();
+        let delta = point - self.center;
+        let along = [
+            cos * delta[0] + sin * delta[1],
+            -sin * delta[0] + cos * delta[1],
+        ];
+        along
+            .iter()
+            .zip(self.sigmas.iter())
+            .map(|(d, s)| {
+                if *s > 0.0 {
+                    (d / s).powi(2)
+                } else if d.abs() > 0.0 {
+                    f64::INFINITY
+                } else {
+                    0.0
+                }
+            })
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Returns whether the provided point is within the k-sigma ellipse
+    pub fn contains(&self, point: &Vector2<f64>, k_sigma: f64) -> bool {
+        self.mahalanobis(point) <= k_sigma
+    }
+
+    /// Returns the probability that a sample lies within the k-sigma ellipse (e.g. 39.3%, 86.5% and 98.9% for 1, 2 and 3 sigmas)
+    pub fn containment_probability(k_sigma: f64) -> f64 {
+        1.0 - (-0.5 * k_sigma.powi(2)).exp()
+    }
+
+    /// Returns the k-sigma of the ellipse which contains the provided probability
+    pub fn sigma_for_probability(probability: f64) -> Result<f64, NyxError> {
+        if !(0.0..1.0).contains(&probability) {
+            return Err(NyxError::CustomError(format!(
+                "containment probability must be in [0, 1), got {probability}"
+            )));
+        }
+        Ok((-2.0 * (1.0 - probability).ln()).sqrt())
+    }
+
+    /// Estimates the probability that a sample lies within the provided region with a seeded Monte Carlo
+    pub fn probability<F: Fn(&Vector2<f64>) -> bool>(
+        &self,
+        region: F,
+        samples: usize,
+        seed: u64,
+    ) -> f64 {
+        let mut rng = Pcg64Mcg::seed_from_u64(seed);
+        let (sin, cos) = self.angle_deg.to_radians().sin_cos();
+        let sqrt_covar = Matrix2::new(cos, -sin, sin, cos) * Matrix2::from_diagonal(&self.sigmas);
+        let within = (0..samples)
+            .filter(|_| {
+                let z = Vector2::from_fn(|_, _| StandardNormal.sample(&mut rng));
+                region(&(self.center + sqrt_covar * z))
+            })
+            .count();
+        within as f64 / samples as f64
+    }
+
+    /// Returns the probability that a sample lies within the provided circle, e.g. the probability of impact of a body in the B-plane
+    pub fn probability_within_circle(
+        &self,
+        center: &Vector2<f64>,
+        radius: f64,
+        samples: usize,
+        seed: u64,
+    ) -> f64 {
+        self.probability(|point| (point - center).norm() <= radius, samples, seed)
+    }
+
+    /// Returns the points of the k-sigma ellipse, e.g. for plotting
+    pub fn boundary(&self, k_sigma: f64, num_points: usize) -> Vec<Vector2<f64>> {
+        let (a, b) = self.semi_axes(k_sigma);
+        let (sin, cos) = self.angle_deg.to_radians().sin_cos();
+        (0..num_points)
+            .map(|i| {
+                let theta = 2.0 * PI * i as f64 / num_points as f64;
+                let (x, y) = (a * theta.cos(), b * theta.sin());
+                self.center + Vector2::new(cos * x - sin * y, sin * x + cos * y)
+            })
+            .collect()
+    }
+
+    /// Exports the boundaries of the provided k-sigma ellipses to a CSV file with the columns `sigma,x,y`, e.g. for plotting
+    pub fn to_csv<P: AsRef<Path>>(
+        &self,
+        path: P,
+        k_sigmas: &[f64],
+        num_points: usize,
+    ) -> Result<(), NyxError> {
+        let mut writer =
+            csv::Writer::from_path(path).map_err(|e| NyxError::ExportError(e.to_string()))?;
+        writer
+            .write_record(["sigma", "x", "y"])
+            .map_err(|e| NyxError::ExportError(e.to_string()))?;
+        for k in k_sigmas {
+            for point in self.boundary(*k, num_points) {
+                writer
+                    .write_record(&[k.to_string(), point[0].to_string(), point[1].to_string()])
+                    .map_err(|e| NyxError::ExportError(e.to_string()))?;
+            }
+        }
+        writer
+            .flush()
+            .map_err(|e| NyxError::ExportError(e.to_string()))
+    }
+}
+
+impl fmt::Display for CovarEllipse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "covariance ellipse centered on [{:.6e}, {:.6e}] with 1-sigma semi-axes {:.6e} x {:.6e} at {:.3} deg",
+            self.center[0], self.center[1], self.sigmas[0], self.sigmas[1], self.angle_deg
+        )
+    }
+}
+
+#[test]
+fn test_erf() {
+    // Reference values from Abramowitz & Stegun, table 7.1
+    assert!((erf(0.5) - 0.520_499_877_813_047).abs() < 1e-14);
+    assert!((erf(1.0) - 0.842_700_792_949_715).abs() < 1e-14);
+    assert!((erf(2.0) - 0.995_322_265_018_953).abs() < 1e-14);
+    assert!((erf(-1.0) + 0.842_700_792_949_715).abs() < 1e-14);
+}
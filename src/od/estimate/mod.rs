@@ -28,6 +28,10 @@ pub mod residual;
 pub use residual::Residual;
 pub mod kfestimate;
 pub use kfestimate::KfEstimate;
+pub mod snapshot;
+pub use snapshot::NavSnapshot;
+pub mod ellipsoid;
+pub use ellipsoid::{CovarEllipse, CovarEllipsoid};
 
 /// Stores an Estimate, as the result of a `time_update` or `measurement_update`.
 pub trait Estimate<T: State>
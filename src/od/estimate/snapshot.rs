@@ -0,0 +1,462 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Estimate, KfEstimate};
+#[cfg(feature = "io")]
+use crate::cosmic::Cosm;
+use crate::cosmic::Orbit;
+#[cfg(feature = "io")]
+use crate::io::watermark::pq_writer;
+#[cfg(feature = "io")]
+use crate::linalg::Vector6;
+use crate::linalg::{DMatrix, Matrix6, OMatrix, U9};
+use crate::md::StateParameter;
+use crate::time::Epoch;
+use crate::{NyxError, Spacecraft, State};
+#[cfg(feature = "io")]
+use arrow::array::{Array, Float64Array, Float64Builder, StringArray, StringBuilder};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "io")]
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+#[cfg(feature = "io")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+#[cfg(feature = "io")]
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "io")]
+use std::fs::File;
+#[cfg(feature = "io")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "io")]
+use std::str::FromStr;
+#[cfg(feature = "io")]
+use std::sync::Arc;
+
+/// Parameters of an orbit estimate, in the order of its covariance
+const ORBIT_PARAMS: [StateParameter; 6] = [
+    StateParameter::X,
+    StateParameter::Y,
+    StateParameter::Z,
+    StateParameter::VX,
+    StateParameter::VY,
+    StateParameter::VZ,
+];
+
+/// Parameters of a spacecraft estimate, in the order of its covariance
+const SPACECRAFT_PARAMS: [StateParameter; 9] = [
+    StateParameter::X,
+    StateParameter::Y,
+    StateParameter::Z,
+    StateParameter::VX,
+    StateParameter::VY,
+    StateParameter::VZ,
+    StateParameter::Cr,
+    StateParameter::Cd,
+    StateParameter::FuelMass,
+];
+
+/// Spacecraft parameters stored in a snapshot file, in addition to the estimated parameters
+#[cfg(feature = "io")]
+const SPACECRAFT_FIELDS: [StateParameter; 4] = [
+    StateParameter::Cr,
+    StateParameter::Cd,
+    StateParameter::DryMass,
+    StateParameter::FuelMass,
+];
+
+#[cfg(feature = "io")]
+const SRP_AREA_FIELD: &str = "srp_area (m^2)";
+#[cfg(feature = "io")]
+const DRAG_AREA_FIELD: &str = "drag_area (m^2)";
+#[cfg(feature = "io")]
+const PARAMS_KEY: &str = "Estimated parameters";
+#[cfg(feature = "io")]
+const FRAME_KEY: &str = "Frame";
+
+/// A navigation snapshot: the estimated state at a given epoch, its covariance, and the estimated parameters.
+///
+/// Snapshots are meant to hand off orbit determination results to mission design or Monte Carlo runs without any lossy copying.
+/// They are stored in Parquet files with one row per snapshot, and the frame and estimated parameters in the file metadata.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavSnapshot {
+    /// Estimated state, including the epoch, frame, and spacecraft parameters
+    pub state: Spacecraft,
+    /// Estimated parameters, in the order of the rows and columns of the covariance
+    pub params: Vec<StateParameter>,
+    /// Covariance of the estimated parameters
+    pub covar: DMatrix<f64>,
+}
+
+impl NavSnapshot {
+    /// Builds a snapshot from an orbit estimate, where only the Cartesian state is estimated.
+    pub fn from_orbit_estimate(estimate: &KfEstimate<Orbit>) -> Self {
+        let mut orbit = estimate.state();
+        orbit.stm = None;
+
+        Self {
+            state: Spacecraft {
+                orbit,
+                ..Default::default()
+            },
+            params: ORBIT_PARAMS.to_vec(),
+            covar: DMatrix::from_iterator(6, 6, estimate.covar().iter().copied()),
+        }
+    }
+
+    /// Builds a snapshot from a spacecraft estimate, where the Cartesian state, Cr, Cd, and the fuel mass are estimated.
+    pub fn from_spacecraft_estimate(estimate: &KfEstimate<Spacecraft>) -> Self {
+        let mut state = estimate.state();
+        state.unset_stm();
+        state.orbit.stm = None;
+
+        Self {
+            state,
+            params: SPACECRAFT_PARAMS.to_vec(),
+            covar: DMatrix::from_iterator(9, 9, estimate.covar().iter().copied()),
+        }
+    }
+
+    /// Epoch of this snapshot
+    pub fn epoch(&self) -> Epoch {
+        self.state.epoch()
+    }
+
+    /// Returns the covariance of the Cartesian state
+    pub fn orbit_covar(&self) -> Result<Matrix6<f64>, NyxError> {
+        let mut covar = Matrix6::zeros();
+        for (i, pi) in ORBIT_PARAMS.iter().enumerate() {
+            let row = self.index_of(*pi)?;
+            for (j, pj) in ORBIT_PARAMS.iter().enumerate() {
+                covar[(i, j)] = self.covar[(row, self.index_of(*pj)?)];
+            }
+        }
+        Ok(covar)
+    }
+
+    /// Returns the 1-sigma uncertainty of the provided parameter, if it is estimated.
+    pub fn sigma(&self, param: StateParameter) -> Result<f64, NyxError> {
+        let idx = self.index_of(param)?;
+        Ok(self.covar[(idx, idx)].sqrt())
+    }
+
+    /// Converts this snapshot into an orbit estimate, keeping only the covariance of the Cartesian state.
+    pub fn to_orbit_estimate(&self) -> Result<KfEstimate<Orbit>, NyxError> {
+        Ok(KfEstimate::from_covar(
+            self.state.orbit,
+            self.orbit_covar()?,
+        ))
+    }
+
+    /// Converts this snapshot into a spacecraft estimate. Parameters which are not estimated in this snapshot have a zero covariance.
+    pub fn to_spacecraft_estimate(&self) -> KfEstimate<Spacecraft> {
+        let mut covar = OMatrix::<f64, U9, U9>::zeros();
+        for (i, pi) in SPACECRAFT_PARAMS.iter().enumerate() {
+            if let Ok(row) = self.index_of(*pi) {
+                for (j, pj) in SPACECRAFT_PARAMS.iter().enumerate() {
+                    if let Ok(col) = self.index_of(*pj) {
+                        covar[(i, j)] = self.covar[(row, col)];
+                    }
+                }
+            }
+        }
+        KfEstimate::from_covar(self.state, covar)
+    }
+
+    /// Stores this snapshot in a Parquet file.
+    #[cfg(feature = "io")]
+    pub fn to_parquet<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf, Box<dyn Error>> {
+        Self::many_to_parquet(std::slice::from_ref(self), path)
+    }
+
+    /// Stores all of the provided snapshots in a single Parquet file, one row per snapshot.
+    /// All of the snapshots must be in the same frame and have the same estimated parameters.
+    #[cfg(feature = "io")]
+    pub fn many_to_parquet<P: AsRef<Path>>(
+        snapshots: &[Self],
+        path: P,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let first = snapshots
+            .first()
+            .ok_or_else(|| NyxError::CustomError("No navigation snapshot to export".to_string()))?;
+
+        let frame = first.state.orbit.frame;
+        let params = &first.params;
+
+        for snapshot in snapshots {
+            if snapshot.state.orbit.frame != frame || &snapshot.params != params {
+                return Err(Box::new(NyxError::CustomError(format!(
+                    "All navigation snapshots must be in {frame} and estimate {params:?}"
+                ))));
+            }
+            if snapshot.covar.shape() != (params.len(), params.len()) {
+                return Err(Box::new(NyxError::CustomError(format!(
+                    "covariance of snapshot at {} is {:?} but {} parameters are estimated",
+                    snapshot.epoch(),
+                    snapshot.covar.shape(),
+                    params.len()
+                ))));
+            }
+        }
+
+        let more_meta = Some(vec![(FRAME_KEY.to_string(), format!("{frame}"))]);
+
+        let mut hdrs = vec![
+            Field::new("Epoch:Gregorian UTC", DataType::Utf8, false),
+            Field::new("Epoch:Gregorian TAI", DataType::Utf8, false),
+            Field::new("Epoch:TAI (s)", DataType::Float64, false),
+        ];
+
+        for param in ORBIT_PARAMS.iter().chain(SPACECRAFT_FIELDS.iter()) {
+            hdrs.push(param.to_field(more_meta.clone()));
+        }
+        hdrs.push(Field::new(SRP_AREA_FIELD, DataType::Float64, false));
+        hdrs.push(Field::new(DRAG_AREA_FIELD, DataType::Float64, false));
+
+        for (i, j) in Self::covar_indexes(params.len()) {
+            hdrs.push(Field::new(
+                Self::covar_field(params[i], params[j]),
+                DataType::Float64,
+                false,
+            ));
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let mut record: Vec<Arc<dyn Array>> = Vec::new();
+
+        let mut utc_epoch = StringBuilder::new();
+        let mut tai_epoch = StringBuilder::new();
+        let mut tai_s = Float64Builder::new();
+        for snapshot in snapshots {
+            utc_epoch.append_value(format!("{}", snapshot.epoch()));
+            tai_epoch.append_value(format!("{:x}", snapshot.epoch()));
+            tai_s.append_value(snapshot.epoch().to_tai_seconds());
+        }
+        record.push(Arc::new(utc_epoch.finish()));
+        record.push(Arc::new(tai_epoch.finish()));
+        record.push(Arc::new(tai_s.finish()));
+
+        for param in ORBIT_PARAMS.iter().chain(SPACECRAFT_FIELDS.iter()) {
+            let mut data = Float64Builder::new();
+            for snapshot in snapshots {
+                data.append_value(snapshot.state.value(*param)?);
+            }
+            record.push(Arc::new(data.finish()));
+        }
+
+        let mut srp_area = Float64Builder::new();
+        let mut drag_area = Float64Builder::new();
+        for snapshot in snapshots {
+            srp_area.append_value(snapshot.state.srp.area_m2);
+            drag_area.append_value(snapshot.state.drag.area_m2);
+        }
+        record.push(Arc::new(srp_area.finish()));
+        record.push(Arc::new(drag_area.finish()));
+
+        for (i, j) in Self::covar_indexes(params.len()) {
+            let mut data = Float64Builder::new();
+            for snapshot in snapshots {
+                data.append_value(snapshot.covar[(i, j)]);
+            }
+            record.push(Arc::new(data.finish()));
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert("Purpose".to_string(), "Navigation snapshot".to_string());
+        metadata.insert(FRAME_KEY.to_string(), format!("{frame}"));
+        metadata.insert(
+            PARAMS_KEY.to_string(),
+            params
+                .iter()
+                .map(|param| param.name())
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+
+        let props = pq_writer(Some(metadata), None);
+
+        let path_buf = path.as_ref().to_path_buf();
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!(
+            "{} navigation snapshot(s) written to {}",
+            snapshots.len(),
+            path_buf.display()
+        );
+
+        Ok(path_buf)
+    }
+
+    /// Loads the first snapshot of the provided Parquet file.
+    #[cfg(feature = "io")]
+    pub fn from_parquet<P: AsRef<Path>>(path: P, cosm: &Cosm) -> Result<Self, Box<dyn Error>> {
+        Self::many_from_parquet(path, cosm)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                Box::new(NyxError::FileUnreadable(
+                    "No navigation snapshot in file".to_string(),
+                )) as Box<dyn Error>
+            })
+    }
+
+    /// Loads all of the snapshots of the provided Parquet file, in the order they were stored.
+    #[cfg(feature = "io")]
+    pub fn many_from_parquet<P: AsRef<Path>>(
+        path: P,
+        cosm: &Cosm,
+    ) -> Result<Vec<Self>, Box<dyn Error>> {
+        let file = File::open(&path)?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+
+        let mut metadata = HashMap::new();
+        if let Some(file_metadata) = builder.metadata().file_metadata().key_value_metadata() {
+            for key_value in file_metadata {
+                if let Some(value) = &key_value.value {
+                    metadata.insert(key_value.key.clone(), value.clone());
+                }
+            }
+        }
+
+        let frame = cosm.try_frame(metadata.get(FRAME_KEY).ok_or_else(|| {
+            NyxError::FileUnreadable(format!("Missing `{FRAME_KEY}` in file metadata"))
+        })?)?;
+
+        let params = metadata
+            .get(PARAMS_KEY)
+            .ok_or_else(|| {
+                NyxError::FileUnreadable(format!("Missing `{PARAMS_KEY}` in file metadata"))
+            })?
+            .split(',')
+            .map(StateParameter::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let reader = builder.build()?;
+
+        let mut snapshots = Vec::new();
+
+        for maybe_batch in reader {
+            let batch = maybe_batch?;
+
+            let column = |name: &str| -> Result<&Float64Array, NyxError> {
+                batch
+                    .column_by_name(name)
+                    .and_then(|col| col.as_any().downcast_ref::<Float64Array>())
+                    .ok_or_else(|| NyxError::FileUnreadable(format!("Missing `{name}` field")))
+            };
+
+            let epochs = batch
+                .column_by_name("Epoch:Gregorian TAI")
+                .and_then(|col| col.as_any().downcast_ref::<StringArray>())
+                .ok_or_else(|| {
+                    NyxError::FileUnreadable("Missing `Epoch:Gregorian TAI` field".to_string())
+                })?;
+
+            let mut orbit_cols = Vec::with_capacity(6);
+            for param in &ORBIT_PARAMS {
+                orbit_cols.push(column(param.to_field(None).name())?);
+            }
+
+            let mut sc_cols = Vec::with_capacity(SPACECRAFT_FIELDS.len());
+            for param in &SPACECRAFT_FIELDS {
+                sc_cols.push(column(param.to_field(None).name())?);
+            }
+
+            let srp_area = column(SRP_AREA_FIELD)?;
+            let drag_area = column(DRAG_AREA_FIELD)?;
+
+            let mut covar_cols = Vec::new();
+            for (i, j) in Self::covar_indexes(params.len()) {
+                covar_cols.push((i, j, column(&Self::covar_field(params[i], params[j]))?));
+            }
+
+            for row in 0..batch.num_rows() {
+                let epoch = Epoch::from_str(epochs.value(row))?;
+
+                let orbit = Orbit::cartesian_vec(
+                    &Vector6::from_iterator(orbit_cols.iter().map(|col| col.value(row))),
+                    epoch,
+                    frame,
+                );
+
+                let mut state = Spacecraft {
+                    orbit,
+                    ..Default::default()
+                };
+                for (param, col) in SPACECRAFT_FIELDS.iter().zip(&sc_cols) {
+                    state.set_value(*param, col.value(row))?;
+                }
+                state.srp.area_m2 = srp_area.value(row);
+                state.drag.area_m2 = drag_area.value(row);
+
+                let mut covar = DMatrix::zeros(params.len(), params.len());
+                for (i, j, col) in &covar_cols {
+                    covar[(*i, *j)] = col.value(row);
+                    covar[(*j, *i)] = col.value(row);
+                }
+
+                snapshots.push(Self {
+                    state,
+                    params: params.clone(),
+                    covar,
+                });
+            }
+        }
+
+        Ok(snapshots)
+    }
+
+    /// Returns the index of the provided parameter in the covariance
+    fn index_of(&self, param: StateParameter) -> Result<usize, NyxError> {
+        self.params
+            .iter()
+            .position(|p| *p == param)
+            .ok_or_else(|| NyxError::CustomError(format!("{param} is not estimated in snapshot")))
+    }
+
+    /// Indexes of the upper triangle of a covariance of the provided size, row by row
+    #[cfg(feature = "io")]
+    fn covar_indexes(size: usize) -> impl Iterator<Item = (usize, usize)> {
+        (0..size).flat_map(move |i| (i..size).map(move |j| (i, j)))
+    }
+
+    /// Name of the covariance field of the provided parameters
+    #[cfg(feature = "io")]
+    fn covar_field(pi: StateParameter, pj: StateParameter) -> String {
+        format!("Covariance {}*{}", pi.name(), pj.name())
+    }
+}
+
+impl fmt::Display for NavSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Navigation snapshot: {}", self.state)?;
+        for (idx, param) in self.params.iter().enumerate() {
+            writeln!(f, "\tσ {param} = {:e}", self.covar[(idx, idx)].sqrt())?;
+        }
+        Ok(())
+    }
+}
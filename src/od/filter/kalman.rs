@@ -142,6 +142,61 @@ where
             prev_used_snc: 0,
         }
     }
+
+    /// Maps the previous covariance to the epoch of the nominal state with its STM, and adds the process noise of the last
+    /// applicable SNC, if any. Returns the predicted covariance and whether an SNC was applied.
+    fn predict_covar(
+        &mut self,
+        stm: &OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+        epoch: Epoch,
+    ) -> (OMatrix<f64, <T as State>::Size, <T as State>::Size>, bool) {
+        let mut covar_bar = stm * self.prev_estimate.covar * stm.transpose();
+
+        // Try to apply an SNC, if applicable
+        for (i, snc) in self.process_noise.iter().enumerate().rev() {
+            if let Some(snc_matrix) = snc.to_matrix(epoch) {
+                // Check if we're using another SNC than the one before
+                if self.prev_used_snc != i {
+                    info!("Switched to {}-th {}", i, snc);
+                    self.prev_used_snc = i;
+                }
+
+                // Let's compute the Gamma matrix, an approximation of the time integral
+                // which assumes that the acceleration is constant between these two measurements.
+                let mut gamma = OMatrix::<f64, <T as State>::Size, A>::zeros();
+                let delta_t = (epoch - self.prev_estimate.epoch()).to_seconds();
+                for blk in 0..A::dim() / 3 {
+                    for i in 0..3 {
+                        let idx_i = i + A::dim() * blk;
+                        let idx_j = i + 3 * blk;
+                        let idx_k = i + 3 + A::dim() * blk;
+                        // For first block
+                        // (0, 0) (1, 1) (2, 2) <=> \Delta t^2/2
+                        // (3, 0) (4, 1) (5, 2) <=> \Delta t
+                        // Second block
+                        // (6, 3) (7, 4) (8, 5) <=> \Delta t^2/2
+                        // (9, 3) (10, 4) (11, 5) <=> \Delta t
+                        // * \Delta t^2/2
+                        // (i, i) when blk = 0
+                        // (i + A::dim() * blk, i + 3) when blk = 1
+                        // (i + A::dim() * blk, i + 3 * blk)
+                        // * \Delta t
+                        // (i + 3, i) when blk = 0
+                        // (i + 3, i + 9) when blk = 1 (and I think i + 12 + 3)
+                        // (i + 3 + A::dim() * blk, i + 3 * blk)
+                        gamma[(idx_i, idx_j)] = delta_t.powi(2) / 2.0;
+                        gamma[(idx_k, idx_j)] = delta_t;
+                    }
+                }
+                // Let's add the process noise
+                covar_bar += &gamma * snc_matrix * &gamma.transpose();
+                // And break so we don't add any more process noise
+                return (covar_bar, true);
+            }
+        }
+
+        (covar_bar, false)
+    }
 }
 
 impl<T, M> KF<T, U3, M>
@@ -205,6 +260,10 @@ where
         &self.measurement_noise
     }
 
+    fn set_measurement_noise(&mut self, noise: OMatrix<f64, M, M>) {
+        self.measurement_noise = noise;
+    }
+
     /// Returns the previous estimate
     fn previous_estimate(&self) -> &Self::Estimate {
         &self.prev_estimate
@@ -226,50 +285,7 @@ where
     /// May return a FilterError if the STM was not updated.
     fn time_update(&mut self, nominal_state: T) -> Result<Self::Estimate, NyxError> {
         let stm = nominal_state.stm()?;
-        let mut covar_bar = stm * self.prev_estimate.covar * stm.transpose();
-
-        // Try to apply an SNC, if applicable
-        for (i, snc) in self.process_noise.iter().enumerate().rev() {
-            if let Some(snc_matrix) = snc.to_matrix(nominal_state.epoch()) {
-                // Check if we're using another SNC than the one before
-                if self.prev_used_snc != i {
-                    info!("Switched to {}-th {}", i, snc);
-                    self.prev_used_snc = i;
-                }
-
-                // Let's compute the Gamma matrix, an approximation of the time integral
-                // which assumes that the acceleration is constant between these two measurements.
-                let mut gamma = OMatrix::<f64, <T as State>::Size, A>::zeros();
-                let delta_t = (nominal_state.epoch() - self.prev_estimate.epoch()).to_seconds();
-                for blk in 0..A::dim() / 3 {
-                    for i in 0..3 {
-                        let idx_i = i + A::dim() * blk;
-                        let idx_j = i + 3 * blk;
-                        let idx_k = i + 3 + A::dim() * blk;
-                        // For first block
-                        // (0, 0) (1, 1) (2, 2) <=> \Delta t^2/2
-                        // (3, 0) (4, 1) (5, 2) <=> \Delta t
-                        // Second block
-                        // (6, 3) (7, 4) (8, 5) <=> \Delta t^2/2
-                        // (9, 3) (10, 4) (11, 5) <=> \Delta t
-                        // * \Delta t^2/2
-                        // (i, i) when blk = 0
-                        // (i + A::dim() * blk, i + 3) when blk = 1
-                        // (i + A::dim() * blk, i + 3 * blk)
-                        // * \Delta t
-                        // (i + 3, i) when blk = 0
-                        // (i + 3, i + 9) when blk = 1 (and I think i + 12 + 3)
-                        // (i + 3 + A::dim() * blk, i + 3 * blk)
-                        gamma[(idx_i, idx_j)] = delta_t.powi(2) / 2.0;
-                        gamma[(idx_k, idx_j)] = delta_t;
-                    }
-                }
-                // Let's add the process noise
-                covar_bar += &gamma * snc_matrix * &gamma.transpose();
-                // And break so we don't add any more process noise
-                break;
-            }
-        }
+        let (covar_bar, _) = self.predict_covar(&stm, nominal_state.epoch());
 
         let state_bar = if self.ekf {
             OVector::<f64, <T as State>::Size>::zeros()
@@ -310,51 +326,7 @@ where
 
         let epoch = nominal_state.epoch();
 
-        let mut covar_bar = stm * self.prev_estimate.covar * stm.transpose();
-        let mut snc_used = false;
-        // Try to apply an SNC, if applicable
-        for (i, snc) in self.process_noise.iter().enumerate().rev() {
-            if let Some(snc_matrix) = snc.to_matrix(epoch) {
-                // Check if we're using another SNC than the one before
-                if self.prev_used_snc != i {
-                    info!("Switched to {}-th {}", i, snc);
-                    self.prev_used_snc = i;
-                }
-
-                // Let's compute the Gamma matrix, an approximation of the time integral
-                // which assumes that the acceleration is constant between these two measurements.
-                let mut gamma = OMatrix::<f64, <T as State>::Size, A>::zeros();
-                let delta_t = (epoch - self.prev_estimate.epoch()).to_seconds();
-                for blk in 0..A::dim() / 3 {
-                    for i in 0..3 {
-                        let idx_i = i + A::dim() * blk;
-                        let idx_j = i + 3 * blk;
-                        let idx_k = i + 3 + A::dim() * blk;
-                        // For first block
-                        // (0, 0) (1, 1) (2, 2) <=> \Delta t^2/2
-                        // (3, 0) (4, 1) (5, 2) <=> \Delta t
-                        // Second block
-                        // (6, 3) (7, 4) (8, 5) <=> \Delta t^2/2
-                        // (9, 3) (10, 4) (11, 5) <=> \Delta t
-                        // * \Delta t^2/2
-                        // (i, i) when blk = 0
-                        // (i + A::dim() * blk, i + 3) when blk = 1
-                        // (i + A::dim() * blk, i + 3 * blk)
-                        // * \Delta t
-                        // (i + 3, i) when blk = 0
-                        // (i + 3, i + 9) when blk = 1 (and I think i + 12 + 3)
-                        // (i + 3 + A::dim() * blk, i + 3 * blk)
-                        gamma[(idx_i, idx_j)] = delta_t.powi(2) / 2.0;
-                        gamma[(idx_k, idx_j)] = delta_t;
-                    }
-                }
-                // Let's add the process noise
-                covar_bar += &gamma * snc_matrix * &gamma.transpose();
-                snc_used = true;
-                // And break so we don't add any more process noise
-                break;
-            }
-        }
+        let (covar_bar, snc_used) = self.predict_covar(&stm, epoch);
 
         if !snc_used {
             debug!("@{} No SNC", epoch);
@@ -26,6 +26,7 @@ use crate::linalg::{DefaultAllocator, DimName, OMatrix, OVector};
 use crate::time::Epoch;
 pub use crate::{cosmic::Cosm, State, TimeTagged};
 pub mod kalman;
+pub mod srif;
 
 /// Defines a Filter trait where S is the size of the estimated state, A the number of acceleration components of the EOMs (used for process noise matrix size), M the size of the measurements.
 pub trait Filter<T, A, M>
@@ -94,4 +95,7 @@ where
 
     /// Returns the measurement noise used at this given epoch
     fn measurement_noise(&self, epoch: Epoch) -> &OMatrix<f64, M, M>;
+
+    /// Sets the measurement noise used by the subsequent measurement updates
+    fn set_measurement_noise(&mut self, noise: OMatrix<f64, M, M>);
 }
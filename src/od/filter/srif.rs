@@ -0,0 +1,549 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DMatrix, DefaultAllocator, DimName, OMatrix, OVector, U3};
+
+pub use crate::errors::NyxError;
+pub use crate::od::estimate::{Estimate, KfEstimate, Residual};
+pub use crate::od::snc::SNC;
+use crate::od::{Filter, State};
+pub use crate::time::{Epoch, Unit};
+
+/// Square-root information filter (SRIF), both classical and extended, a drop-in alternative to the Kalman filter `KF`.
+///
+/// The filter propagates the upper triangular square root R of the information matrix and the information state z, such that
+/// R x = z and P = R^-1 R^-T. Both the time and measurement updates are orthogonal (Householder) triangularizations, which
+/// keeps the covariance symmetric and positive definite on long arcs with small measurement noise and large a priori
+/// uncertainties, where the Kalman update loses precision. The process noise is included by augmenting the time update with
+/// the SNC accelerations (Bierman's algorithm).
+///
+/// The estimates are regular `KfEstimate`s, so the smoother of the orbit determination process applies as is.
+///
+/// T: Type of state
+/// A: Acceleration size (for SNC)
+/// M: Measurement size (used for the sensitivity matrix)
+#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct SRIF<T, A, M>
+where
+    A: DimName,
+    M: DimName,
+    T: State,
+    DefaultAllocator: Allocator<f64, M>
+        + Allocator<f64, <T as State>::Size>
+        + Allocator<f64, <T as State>::VecLength>
+        + Allocator<f64, A>
+        + Allocator<f64, M, M>
+        + Allocator<f64, M, <T as State>::Size>
+        + Allocator<f64, <T as State>::Size, <T as State>::Size>
+        + Allocator<f64, A, A>
+        + Allocator<f64, <T as State>::Size, A>
+        + Allocator<f64, A, <T as State>::Size>
+        + Allocator<usize, <T as State>::Size>
+        + Allocator<usize, <T as State>::Size, <T as State>::Size>,
+    <DefaultAllocator as Allocator<f64, <T as State>::Size>>::Buffer: Copy,
+    <DefaultAllocator as Allocator<f64, <T as State>::Size, <T as State>::Size>>::Buffer: Copy,
+{
+    /// The previous estimate, whose covariance is computed from the square root information matrix
+    pub prev_estimate: KfEstimate<T>,
+    /// Sets the Measurement noise (usually noted R)
+    pub measurement_noise: OMatrix<f64, M, M>,
+    /// A sets of process noise (usually noted Q), must be ordered chronologically
+    pub process_noise: Vec<SNC<A>>,
+    /// Determines whether this filter operates as a classical or an extended filter, as for the `KF`.
+    pub ekf: bool,
+    /// Upper triangular square root of the information matrix of the previous estimate
+    info_sqrt: OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+    /// Information state of the previous estimate, i.e. the square root information matrix times the state deviation
+    info_state: OVector<f64, <T as State>::Size>,
+    h_tilde: OMatrix<f64, M, <T as State>::Size>,
+    h_tilde_updated: bool,
+    prev_used_snc: usize,
+}
+
+impl<T, A, M> SRIF<T, A, M>
+where
+    A: DimName,
+    M: DimName,
+    T: State,
+    DefaultAllocator: Allocator<f64, M>
+        + Allocator<f64, <T as State>::Size>
+        + Allocator<f64, <T as State>::VecLength>
+        + Allocator<f64, A>
+        + Allocator<f64, M, M>
+        + Allocator<f64, M, <T as State>::Size>
+        + Allocator<f64, <T as State>::Size, M>
+        + Allocator<f64, <T as State>::Size, <T as State>::Size>
+        + Allocator<f64, A, A>
+        + Allocator<f64, <T as State>::Size, A>
+        + Allocator<f64, A, <T as State>::Size>
+        + Allocator<usize, <T as State>::Size>
+        + Allocator<usize, <T as State>::Size, <T as State>::Size>,
+    <DefaultAllocator as Allocator<f64, <T as State>::Size>>::Buffer: Copy,
+    <DefaultAllocator as Allocator<f64, <T as State>::Size, <T as State>::Size>>::Buffer: Copy,
+{
+    /// Initializes this SRIF with an initial estimate, measurement noise, and one process noise
+    ///
+    /// # Panics
+    /// + If the covariance of the initial estimate is not positive definite.
+    pub fn new(
+        initial_estimate: KfEstimate<T>,
+        process_noise: SNC<A>,
+        measurement_noise: OMatrix<f64, M, M>,
+    ) -> Self {
+        Self::with_sncs(initial_estimate, vec![process_noise], measurement_noise)
+    }
+
+    /// Initializes this SRIF with an initial estimate, measurement noise, and several process noise
+    /// WARNING: SNCs MUST be ordered chronologically! They will be selected automatically by walking
+    /// the list of SNCs backward until one can be applied!
+    ///
+    /// # Panics
+    /// + If the covariance of the initial estimate is not positive definite.
+    pub fn with_sncs(
+        initial_estimate: KfEstimate<T>,
+        process_noises: Vec<SNC<A>>,
+        measurement_noise: OMatrix<f64, M, M>,
+    ) -> Self {
+        assert_eq!(
+            A::dim() % 3,
+            0,
+            "SNC can only be applied to accelerations multiple of 3"
+        );
+        let mut process_noises = process_noises;
+        // Set the initial epoch of the SNC
+        for snc in &mut process_noises {
+            snc.init_epoch = Some(initial_estimate.epoch());
+        }
+
+        let (info_sqrt, info_state) = Self::information_of(&initial_estimate)
+            .expect("covariance of the initial estimate must be positive definite");
+
+        Self {
+            prev_estimate: initial_estimate,
+            measurement_noise,
+            process_noise: process_noises,
+            ekf: false,
+            info_sqrt,
+            info_state,
+            h_tilde: OMatrix::<f64, M, <T as State>::Size>::zeros(),
+            h_tilde_updated: false,
+            prev_used_snc: 0,
+        }
+    }
+
+    /// Upper triangular square root of the information matrix of the previous estimate
+    pub fn info_sqrt(&self) -> &OMatrix<f64, <T as State>::Size, <T as State>::Size> {
+        &self.info_sqrt
+    }
+
+    /// Computes the square root information matrix and information state of the provided estimate
+    fn information_of(
+        estimate: &KfEstimate<T>,
+    ) -> Result<
+        (
+            OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+            OVector<f64, <T as State>::Size>,
+        ),
+        NyxError,
+    > {
+        // With P = L L^T, the information matrix is L^-T L^-1, so L^-1 is a square root that we triangularize
+        let chol = estimate
+            .covar
+            .cholesky()
+            .ok_or(NyxError::SingularCovarianceMatrix)?;
+        let l_inv = chol
+            .l()
+            .solve_lower_triangular(
+                &OMatrix::<f64, <T as State>::Size, <T as State>::Size>::identity(),
+            )
+            .ok_or(NyxError::SingularCovarianceMatrix)?;
+        let n = <T as State>::Size::dim();
+        let mut stacked = DMatrix::<f64>::zeros(n, n + 1);
+        stacked
+            .view_mut((0, 0), (n, n))
+            .copy_from_slice(l_inv.as_slice());
+        let state_info = l_inv * estimate.state_deviation;
+        stacked.column_mut(n).copy_from_slice(state_info.as_slice());
+        Ok(Self::triangularize(stacked, n))
+    }
+
+    /// Householder triangularization of the stacked square root information arrays, returns the square root information
+    /// matrix and the information state of the last `n` states, which are the last columns before the information state.
+    fn triangularize(
+        stacked: DMatrix<f64>,
+        n: usize,
+    ) -> (
+        OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+        OVector<f64, <T as State>::Size>,
+    ) {
+        let cols = stacked.ncols();
+        let r = stacked.qr().r();
+        let offset = cols - 1 - n;
+        let info_sqrt = OMatrix::<f64, <T as State>::Size, <T as State>::Size>::from_fn(|i, j| {
+            r[(offset + i, offset + j)]
+        });
+        let info_state =
+            OVector::<f64, <T as State>::Size>::from_fn(|i, _| r[(offset + i, cols - 1)]);
+        (info_sqrt, info_state)
+    }
+
+    /// Covariance and state deviation from the square root information matrix and information state
+    fn covar_and_state(
+        info_sqrt: &OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+        info_state: &OVector<f64, <T as State>::Size>,
+    ) -> Result<
+        (
+            OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+            OVector<f64, <T as State>::Size>,
+        ),
+        NyxError,
+    > {
+        let r_inv = info_sqrt
+            .solve_upper_triangular(
+                &OMatrix::<f64, <T as State>::Size, <T as State>::Size>::identity(),
+            )
+            .ok_or(NyxError::SingularCovarianceMatrix)?;
+        let covar = r_inv * r_inv.transpose();
+        let state = r_inv * info_state;
+        Ok((covar, state))
+    }
+
+    /// Maps the previous square root information to the epoch of the nominal state with its STM, and adds the process noise of
+    /// the last applicable SNC, if any. Returns the predicted square root information matrix and information state.
+    fn predict_info(
+        &mut self,
+        stm: &OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+        epoch: Epoch,
+    ) -> Result<
+        (
+            OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+            OVector<f64, <T as State>::Size>,
+        ),
+        NyxError,
+    > {
+        let n = <T as State>::Size::dim();
+        let stm_inv = stm
+            .try_inverse()
+            .ok_or(NyxError::SingularStateTransitionMatrix)?;
+        // The information of the mapped state Φ x is R Φ^-1, and its information state is unchanged.
+        // In an EKF, the deviation was absorbed in the nominal state, so the information state is zero.
+        let info_sqrt_bar = self.info_sqrt * stm_inv;
+        let info_state_bar = if self.ekf {
+            OVector::<f64, <T as State>::Size>::zeros()
+        } else {
+            self.info_state
+        };
+
+        // Try to apply an SNC, if applicable
+        for (i, snc) in self.process_noise.iter().enumerate().rev() {
+            if let Some(snc_matrix) = snc.to_matrix(epoch) {
+                // Check if we're using another SNC than the one before
+                if self.prev_used_snc != i {
+                    info!("Switched to {}-th {}", i, snc);
+                    self.prev_used_snc = i;
+                }
+
+                // Same Gamma matrix as the KF, which assumes that the acceleration is constant between both epochs
+                let delta_t = (epoch - self.prev_estimate.epoch()).to_seconds();
+                let mut gamma = OMatrix::<f64, <T as State>::Size, A>::zeros();
+                for blk in 0..A::dim() / 3 {
+                    for i in 0..3 {
+                        gamma[(i + A::dim() * blk, i + 3 * blk)] = delta_t.powi(2) / 2.0;
+                        gamma[(i + 3 + A::dim() * blk, i + 3 * blk)] = delta_t;
+                    }
+                }
+
+                // Square root information of the process noise: Q^-1 = R_w^T R_w with R_w = L^-1 where Q = L L^T
+                let a = A::dim();
+                let info_sqrt_w = match snc_matrix.clone().cholesky().and_then(|chol| {
+                    chol.l()
+                        .solve_lower_triangular(&OMatrix::<f64, A, A>::identity())
+                }) {
+                    Some(info_sqrt_w) => info_sqrt_w,
+                    None => {
+                        // Degenerate process noise: add it to the covariance and factorize again
+                        warn!("{epoch} process noise is not positive definite, adding it to the covariance");
+                        let (covar_bar, state_bar) =
+                            Self::covar_and_state(&info_sqrt_bar, &info_state_bar)?;
+                        let mut est = self.prev_estimate;
+                        est.covar = covar_bar + &gamma * snc_matrix * gamma.transpose();
+                        est.state_deviation = state_bar;
+                        return Self::information_of(&est);
+                    }
+                };
+
+                // Triangularize the augmented arrays
+                // [ R_w      0   | 0 ]
+                // [ -R̄ Γ     R̄   | z̄ ]
+                // whose lower right block is the square root information of the state including the process noise.
+                let mut stacked = DMatrix::<f64>::zeros(a + n, a + n + 1);
+                stacked
+                    .view_mut((0, 0), (a, a))
+                    .copy_from_slice(info_sqrt_w.as_slice());
+                let coupling = -(info_sqrt_bar * &gamma);
+                stacked
+                    .view_mut((a, 0), (n, a))
+                    .copy_from_slice(coupling.as_slice());
+                stacked
+                    .view_mut((a, a), (n, n))
+                    .copy_from_slice(info_sqrt_bar.as_slice());
+                stacked
+                    .view_mut((a, a + n), (n, 1))
+                    .copy_from_slice(info_state_bar.as_slice());
+                return Ok(Self::triangularize(stacked, n));
+            }
+        }
+
+        // Without process noise, only restore the triangular form
+        let mut stacked = DMatrix::<f64>::zeros(n, n + 1);
+        stacked
+            .view_mut((0, 0), (n, n))
+            .copy_from_slice(info_sqrt_bar.as_slice());
+        stacked
+            .column_mut(n)
+            .copy_from_slice(info_state_bar.as_slice());
+        Ok(Self::triangularize(stacked, n))
+    }
+}
+
+impl<T, M> SRIF<T, U3, M>
+where
+    M: DimName,
+    T: State,
+    DefaultAllocator: Allocator<f64, M>
+        + Allocator<f64, <T as State>::Size>
+        + Allocator<f64, <T as State>::VecLength>
+        + Allocator<f64, M, M>
+        + Allocator<f64, M, <T as State>::Size>
+        + Allocator<f64, <T as State>::Size, M>
+        + Allocator<f64, <T as State>::Size, <T as State>::Size>
+        + Allocator<f64, U3, U3>
+        + Allocator<f64, <T as State>::Size, U3>
+        + Allocator<f64, U3, <T as State>::Size>
+        + Allocator<usize, <T as State>::Size>
+        + Allocator<usize, <T as State>::Size, <T as State>::Size>,
+    <DefaultAllocator as Allocator<f64, <T as State>::Size>>::Buffer: Copy,
+    <DefaultAllocator as Allocator<f64, <T as State>::Size, <T as State>::Size>>::Buffer: Copy,
+{
+    /// Initializes this SRIF without SNC
+    ///
+    /// # Panics
+    /// + If the covariance of the initial estimate is not positive definite.
+    pub fn no_snc(initial_estimate: KfEstimate<T>, measurement_noise: OMatrix<f64, M, M>) -> Self {
+        Self::with_sncs(initial_estimate, Vec::new(), measurement_noise)
+    }
+}
+
+impl<T, A, M> Filter<T, A, M> for SRIF<T, A, M>
+where
+    A: DimName,
+    M: DimName,
+    T: State,
+    DefaultAllocator: Allocator<f64, M>
+        + Allocator<f64, <T as State>::Size>
+        + Allocator<f64, <T as State>::VecLength>
+        + Allocator<f64, A>
+        + Allocator<f64, M, M>
+        + Allocator<f64, M, <T as State>::Size>
+        + Allocator<f64, <T as State>::Size, M>
+        + Allocator<f64, <T as State>::Size, <T as State>::Size>
+        + Allocator<f64, A, A>
+        + Allocator<f64, <T as State>::Size, A>
+        + Allocator<f64, A, <T as State>::Size>
+        + Allocator<usize, <T as State>::Size>
+        + Allocator<usize, <T as State>::Size, <T as State>::Size>
+        + Allocator<f64, na::Const<1>, M>,
+    <DefaultAllocator as Allocator<f64, <T as State>::Size>>::Buffer: Copy,
+    <DefaultAllocator as Allocator<f64, <T as State>::Size, <T as State>::Size>>::Buffer: Copy,
+{
+    type Estimate = KfEstimate<T>;
+
+    fn measurement_noise(&self, _epoch: Epoch) -> &OMatrix<f64, M, M> {
+        &self.measurement_noise
+    }
+
+    fn set_measurement_noise(&mut self, noise: OMatrix<f64, M, M>) {
+        self.measurement_noise = noise;
+    }
+
+    /// Returns the previous estimate
+    fn previous_estimate(&self) -> &Self::Estimate {
+        &self.prev_estimate
+    }
+
+    /// Sets the previous estimate and factorizes its covariance, which is left unchanged if it is not positive definite
+    fn set_previous_estimate(&mut self, est: &Self::Estimate) {
+        match Self::information_of(est) {
+            Ok((info_sqrt, info_state)) => {
+                self.info_sqrt = info_sqrt;
+                self.info_state = info_state;
+            }
+            Err(e) => error!("{} cannot set the estimate of the SRIF: {e}", est.epoch()),
+        }
+        self.prev_estimate = *est;
+    }
+
+    /// Update the sensitivity matrix (or "H tilde"). This function **must** be called prior to each
+    /// call to `measurement_update`.
+    fn update_h_tilde(&mut self, h_tilde: OMatrix<f64, M, <T as State>::Size>) {
+        self.h_tilde = h_tilde;
+        self.h_tilde_updated = true;
+    }
+
+    /// Computes a time update/prediction (i.e. advances the filter estimate with the updated STM).
+    ///
+    /// May return a FilterError if the STM was not updated.
+    fn time_update(&mut self, nominal_state: T) -> Result<Self::Estimate, NyxError> {
+        let stm = nominal_state.stm()?;
+        let (info_sqrt_bar, info_state_bar) = self.predict_info(&stm, nominal_state.epoch())?;
+        let (covar_bar, state_bar) = Self::covar_and_state(&info_sqrt_bar, &info_state_bar)?;
+
+        let estimate = KfEstimate {
+            nominal_state,
+            state_deviation: state_bar,
+            covar: covar_bar,
+            covar_bar,
+            stm,
+            predicted: true,
+        };
+        self.info_sqrt = info_sqrt_bar;
+        self.info_state = info_state_bar;
+        self.prev_estimate = estimate;
+        // Update the prev epoch for all SNCs
+        for snc in &mut self.process_noise {
+            snc.prev_epoch = Some(self.prev_estimate.epoch());
+        }
+        Ok(estimate)
+    }
+
+    /// Computes the measurement update with a provided real observation and computed observation.
+    ///
+    /// May return a FilterError if the STM or sensitivity matrices were not updated.
+    fn measurement_update(
+        &mut self,
+        nominal_state: T,
+        real_obs: &OVector<f64, M>,
+        computed_obs: &OVector<f64, M>,
+        resid_ratio_check: Option<f64>,
+    ) -> Result<(Self::Estimate, Residual<M>), NyxError> {
+        if !self.h_tilde_updated {
+            return Err(NyxError::SensitivityNotUpdated);
+        }
+
+        let stm = nominal_state.stm()?;
+        let epoch = nominal_state.epoch();
+
+        let (info_sqrt_bar, info_state_bar) = self.predict_info(&stm, epoch)?;
+        let (covar_bar, state_bar) = Self::covar_and_state(&info_sqrt_bar, &info_state_bar)?;
+
+        // Compute observation deviation (usually marked as y_i)
+        let prefit = real_obs - computed_obs;
+
+        // Same prefit ratio as the KF, so that the residual rejection criteria are identical
+        let h_p_ht = &self.h_tilde * covar_bar * self.h_tilde.transpose();
+        let ratio = prefit.dot(&(&h_p_ht * &prefit));
+
+        if let Some(ratio_thresh) = resid_ratio_check {
+            if ratio > ratio_thresh {
+                warn!("{epoch} msr rejected: residual ratio {ratio} > {ratio_thresh}");
+                // Perform only a time update and return
+                let pred_est = self.time_update(nominal_state)?;
+                return Ok((pred_est, Residual::rejected(epoch, prefit, ratio)));
+            } else {
+                debug!("{epoch} msr accepted: residual ratio {ratio} < {ratio_thresh}");
+            }
+        }
+
+        // Whiten the measurement with the Cholesky factor of its noise, R = L L^T
+        let noise_l_inv = self
+            .measurement_noise
+            .clone()
+            .cholesky()
+            .and_then(|chol| {
+                chol.l()
+                    .solve_lower_triangular(&OMatrix::<f64, M, M>::identity())
+            })
+            .ok_or(NyxError::SingularKalmanGain)?;
+        let white_h = &noise_l_inv * &self.h_tilde;
+        let white_y = &noise_l_inv * &prefit;
+
+        // Triangularize the stacked a priori and measurement information
+        // [ R̄       | z̄ ]
+        // [ L^-1 H  | L^-1 y ]
+        let n = <T as State>::Size::dim();
+        let m = M::dim();
+        let mut stacked = DMatrix::<f64>::zeros(n + m, n + 1);
+        stacked
+            .view_mut((0, 0), (n, n))
+            .copy_from_slice(info_sqrt_bar.as_slice());
+        stacked
+            .view_mut((0, n), (n, 1))
+            .copy_from_slice(info_state_bar.as_slice());
+        stacked
+            .view_mut((n, 0), (m, n))
+            .copy_from_slice(white_h.as_slice());
+        stacked
+            .view_mut((n, n), (m, 1))
+            .copy_from_slice(white_y.as_slice());
+        let (info_sqrt, info_state) = Self::triangularize(stacked, n);
+        let (covar, state_hat) = Self::covar_and_state(&info_sqrt, &info_state)?;
+
+        let res = if self.ekf {
+            let postfit = &prefit - (&self.h_tilde * state_hat);
+            Residual::new(epoch, prefit, postfit, ratio)
+        } else {
+            let postfit = &prefit - (&self.h_tilde * state_bar);
+            Residual::new(epoch, prefit, postfit, ratio)
+        };
+
+        // And wrap up
+        let estimate = KfEstimate {
+            nominal_state,
+            state_deviation: state_hat,
+            covar,
+            covar_bar,
+            stm,
+            predicted: false,
+        };
+
+        self.h_tilde_updated = false;
+        self.info_sqrt = info_sqrt;
+        self.info_state = info_state;
+        self.prev_estimate = estimate;
+        // Update the prev epoch for all SNCs
+        for snc in &mut self.process_noise {
+            snc.prev_epoch = Some(self.prev_estimate.epoch());
+        }
+        Ok((estimate, res))
+    }
+
+    fn is_extended(&self) -> bool {
+        self.ekf
+    }
+
+    fn set_extended(&mut self, status: bool) {
+        self.ekf = status;
+    }
+
+    /// Overwrites all of the process noises to the one provided
+    fn set_process_noise(&mut self, snc: SNC<A>) {
+        self.process_noise = vec![snc];
+    }
+}
@@ -0,0 +1,417 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::msr::RangeDoppler;
+use super::{GroundStation, Measurement};
+use crate::cosmic::{Cosm, Frame, Spacecraft};
+use crate::dynamics::{GravityCorrection, GravityParameter, SpacecraftDynamics};
+use crate::errors::NyxError;
+use crate::linalg::{DMatrix, DVector, Matrix6, Vector2};
+use crate::propagators::Propagator;
+use crate::time::Epoch;
+use crate::{State, TimeTagged};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+/// Perturbations of the position and velocity used to compute the partials by central differences
+const ORBIT_PERTURBATIONS: [f64; 6] = [1e-3, 1e-3, 1e-3, 1e-6, 1e-6, 1e-6];
+/// Relative perturbation of the gravitational parameter used to compute its partials by central differences
+const GM_REL_PERTURBATION: f64 = 1e-6;
+/// Perturbation of the normalized spherical harmonics coefficients used to compute their partials by central differences
+const COEFF_PERTURBATION: f64 = 1e-6;
+
+/// A parameter of the gravity field to estimate, optionally constrained by an a priori one sigma uncertainty around its
+/// nominal value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EstimatedGravityParameter {
+    pub param: GravityParameter,
+    /// A priori one sigma uncertainty, in km^3/s^2 for the GM, unconstrained if unset
+    pub apriori_sigma: Option<f64>,
+}
+
+/// Estimates the initial state of a spacecraft and corrections to the gravitational parameter and to selected spherical
+/// harmonics coefficients of the central body from range and Doppler tracking data, with an iterated batch least squares,
+/// e.g. for small-body or planetary science scenarios.
+///
+/// The dynamics are the nominal dynamics of the spacecraft, to which the estimated corrections are added. The partials are
+/// computed by central differences of the propagated trajectory, and the measurements of devices which are not in the list of
+/// devices are ignored.
+#[derive(Clone)]
+pub struct GravityFieldEstimator {
+    /// Nominal dynamics of the spacecraft
+    pub dynamics: SpacecraftDynamics,
+    /// Body fixed frame of the spherical harmonics of the central body, e.g. "IAU Earth"
+    pub compute_frame: Frame,
+    /// Estimated parameters of the gravity field
+    pub params: Vec<EstimatedGravityParameter>,
+    /// Tracking devices, by name
+    pub devices: HashMap<String, GroundStation>,
+    /// One sigma noise of the range measurements, in km
+    pub range_sigma_km: f64,
+    /// One sigma noise of the Doppler measurements, in km/s
+    pub doppler_sigma_km_s: f64,
+    /// A priori covariance of the Cartesian initial state, unconstrained if unset
+    pub orbit_apriori_covar: Option<Matrix6<f64>>,
+    /// Maximum number of iterations of the least squares
+    pub max_iterations: usize,
+    /// Convergence threshold on the relative change of the weighted RMS of the residuals between two iterations, or on the
+    /// weighted RMS itself when the residuals are far below the noise
+    pub tolerance: f64,
+    pub cosm: Arc<Cosm>,
+}
+
+/// The result of the estimation of a gravity field.
+#[derive(Clone)]
+pub struct GravityFieldSolution {
+    /// Estimated initial state
+    pub initial_state: Spacecraft,
+    /// Estimated corrections of the gravity field
+    pub correction: GravityCorrection,
+    /// Covariance of the estimated position (km), velocity (km/s) and gravity parameters, in that order
+    pub covar: DMatrix<f64>,
+    /// Root mean square of the post-fit range residuals, in km
+    pub rms_range_km: f64,
+    /// Root mean square of the post-fit Doppler residuals, in km/s
+    pub rms_doppler_km_s: f64,
+    /// Number of measurements used
+    pub num_msr: usize,
+    /// Number of iterations used
+    pub iterations: usize,
+}
+
+impl GravityFieldSolution {
+    /// Returns the estimated correction of the provided parameter, if it was estimated
+    pub fn delta(&self, param: GravityParameter) -> Option<f64> {
+        self.correction
+            .deltas
+            .iter()
+            .find(|(estimated, _)| *estimated == param)
+            .map(|(_, delta)| *delta)
+    }
+
+    /// Returns the one sigma uncertainty of the provided parameter, if it was estimated
+    pub fn sigma(&self, param: GravityParameter) -> Option<f64> {
+        self.correction
+            .deltas
+            .iter()
+            .position(|(estimated, _)| *estimated == param)
+            .map(|idx| self.covar[(6 + idx, 6 + idx)].sqrt())
+    }
+}
+
+impl fmt::Display for GravityFieldSolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Gravity field estimation after {} iterations over {} measurements (post-fit RMS {:.3e} km and {:.3e} km/s)",
+            self.iterations, self.num_msr, self.rms_range_km, self.rms_doppler_km_s
+        )?;
+        for (idx, (param, delta)) in self.correction.deltas.iter().enumerate() {
+            writeln!(
+                f,
+                "\tΔ{param} = {delta:.6e} ± {:.3e}",
+                self.covar[(6 + idx, 6 + idx)].sqrt()
+            )?;
+        }
+        write!(f, "\tinitial state: {:x}", self.initial_state.orbit)
+    }
+}
+
+impl GravityFieldEstimator {
+    /// Initializes a new estimator of the initial state only, with at most ten iterations
+    pub fn new(
+        dynamics: SpacecraftDynamics,
+        compute_frame: Frame,
+        devices: Vec<GroundStation>,
+        range_sigma_km: f64,
+        doppler_sigma_km_s: f64,
+        cosm: Arc<Cosm>,
+    ) -> Self {
+        Self {
+            dynamics,
+            compute_frame,
+            params: Vec::new(),
+            devices: devices
+                .into_iter()
+                .map(|device| (device.name.clone(), device))
+                .collect(),
+            range_sigma_km,
+            doppler_sigma_km_s,
+            orbit_apriori_covar: None,
+            max_iterations: 10,
+            tolerance: 1e-2,
+            cosm,
+        }
+    }
+
+    /// Adds a parameter of the gravity field to estimate, optionally constrained by its a priori one sigma uncertainty
+    pub fn with_parameter(mut self, param: GravityParameter, apriori_sigma: Option<f64>) -> Self {
+        self.params.push(EstimatedGravityParameter {
+            param,
+            apriori_sigma,
+        });
+        self
+    }
+
+    /// Constrains the initial state with the provided a priori covariance of its Cartesian state
+    pub fn with_orbit_apriori(mut self, covar: Matrix6<f64>) -> Self {
+        self.orbit_apriori_covar = Some(covar);
+        self
+    }
+
+    /// Builds the initial state and the gravity corrections from the parameters
+    fn unpack(
+        &self,
+        initial_state: &Spacecraft,
+        params: &DVector<f64>,
+    ) -> Result<(Spacecraft, GravityCorrection), NyxError> {
+        let mut state = *initial_state;
+        state.orbit.x_km = params[0];
+        state.orbit.y_km = params[1];
+        state.orbit.z_km = params[2];
+        state.orbit.vx_km_s = params[3];
+        state.orbit.vy_km_s = params[4];
+        state.orbit.vz_km_s = params[5];
+        let correction = GravityCorrection::new(
+            self.compute_frame,
+            self.params
+                .iter()
+                .enumerate()
+                .map(|(idx, estimated)| (estimated.param, params[6 + idx]))
+                .collect(),
+            self.cosm.clone(),
+        )?;
+        Ok((state, correction))
+    }
+
+    /// Propagates the parameters and returns the computed range and Doppler of each measurement
+    fn computed(
+        &self,
+        initial_state: &Spacecraft,
+        params: &DVector<f64>,
+        measurements: &[(String, RangeDoppler)],
+    ) -> Result<Vec<Vector2<f64>>, NyxError> {
+        let (state, correction) = self.unpack(initial_state, params)?;
+        let mut dynamics = self.dynamics.clone();
+        dynamics.orbital_dyn.add_model(Arc::new(correction));
+
+        // Propagate to each epoch needed by the measurements instead of interpolating a trajectory, which would limit the fit
+        let mut states = BTreeMap::new();
+        for (name, msr) in measurements {
+            states.insert(msr.epoch(), None);
+            if let Some(integration_time) = self.devices[name].integration_time {
+                states.insert(msr.epoch() - integration_time, None);
+            }
+        }
+        let prop = Propagator::default(dynamics);
+        let mut instance = prop.with(state);
+        for (epoch, state) in states.iter_mut() {
+            *state = Some(instance.until_epoch(*epoch)?);
+        }
+        let state_at = |epoch: Epoch| states[&epoch].unwrap().orbit;
+
+        Ok(measurements
+            .iter()
+            .map(|(name, msr)| {
+                let device = &self.devices[name];
+                let epoch = msr.epoch();
+                // Noiseless range and Doppler, regardless of the elevation mask
+                let computed = match device.integration_time {
+                    Some(integration_time) => {
+                        let (_, _, rx_0, tx_0) = device
+                            .azimuth_elevation_of(state_at(epoch - integration_time), &self.cosm);
+                        let (_, _, rx_1, tx_1) =
+                            device.azimuth_elevation_of(state_at(epoch), &self.cosm);
+                        RangeDoppler::two_way((tx_0, tx_1), (rx_0, rx_1), 0.0, 0.0, 0.0)
+                    }
+                    None => {
+                        let (_, _, rx, tx) =
+                            device.azimuth_elevation_of(state_at(epoch), &self.cosm);
+                        RangeDoppler::one_way(tx, rx, 0.0, 0.0, 0.0)
+                    }
+                };
+                computed.observation()
+            })
+            .collect())
+    }
+
+    /// Estimates the initial state and the corrections of the gravity field from the range and Doppler measurements, which must be
+    /// in chronological order and after the epoch of the initial guess.
+    pub fn estimate(
+        &self,
+        initial_guess: Spacecraft,
+        measurements: &[(String, RangeDoppler)],
+    ) -> Result<GravityFieldSolution, NyxError> {
+        for estimated in &self.params {
+            estimated.param.validate()?;
+        }
+        let measurements = measurements
+            .iter()
+            .filter(|(name, _)| self.devices.contains_key(name))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let num_params = 6 + self.params.len();
+        let num_msr = 2 * measurements.len();
+        if num_msr < num_params {
+            return Err(NyxError::CustomError(format!(
+                "gravity field estimation of {num_params} parameters requires at least {} measurements but got {}",
+                num_params.div_ceil(2),
+                measurements.len()
+            )));
+        }
+        if measurements[0].1.epoch() < initial_guess.epoch()
+            || measurements
+                .windows(2)
+                .any(|w| w[1].1.epoch() < w[0].1.epoch())
+        {
+            return Err(NyxError::CustomError(
+                "measurements must be in chronological order and after the initial guess"
+                    .to_string(),
+            ));
+        }
+
+        let nominal_state = initial_guess.orbit.to_cartesian_vec();
+        let mut params = DVector::<f64>::zeros(num_params);
+        for i in 0..6 {
+            params[i] = nominal_state[i];
+        }
+        let mut perturbations = ORBIT_PERTURBATIONS.to_vec();
+        for estimated in &self.params {
+            perturbations.push(match estimated.param {
+                GravityParameter::GM => GM_REL_PERTURBATION * self.compute_frame.gm(),
+                _ => COEFF_PERTURBATION,
+            });
+        }
+
+        // Weights of the range and Doppler
+        let weights = [
+            self.range_sigma_km.powi(-2),
+            self.doppler_sigma_km_s.powi(-2),
+        ];
+        // A priori information, whose nominal values are the initial guess and the uncorrected gravity field
+        let mut apriori_info = DMatrix::<f64>::zeros(num_params, num_params);
+        if let Some(covar) = self.orbit_apriori_covar {
+            let info = covar
+                .try_inverse()
+                .ok_or(NyxError::SingularCovarianceMatrix)?;
+            apriori_info.view_mut((0, 0), (6, 6)).copy_from(&info);
+        }
+        for (idx, estimated) in self.params.iter().enumerate() {
+            if let Some(sigma) = estimated.apriori_sigma {
+                apriori_info[(6 + idx, 6 + idx)] = sigma.powi(-2);
+            }
+        }
+        let mut apriori = DVector::<f64>::zeros(num_params);
+        for i in 0..6 {
+            apriori[i] = nominal_state[i];
+        }
+
+        let mut iterations = 0;
+        let mut prev_rms = f64::INFINITY;
+
+        let covar = loop {
+            iterations += 1;
+
+            let nominal = self.computed(&initial_guess, &params, &measurements)?;
+            let mut residuals = DVector::<f64>::zeros(num_msr);
+            for (i, ((_, msr), computed)) in measurements.iter().zip(nominal.iter()).enumerate() {
+                let delta = msr.observation() - computed;
+                for j in 0..2 {
+                    residuals[2 * i + j] = delta[j];
+                }
+            }
+
+            // Central difference partials of the measurements with respect to the parameters: the parameters are strongly
+            // correlated (e.g. the GM and the semi-major axis), so the truncation error of forward differences slows down the
+            // convergence
+            let mut h_tilde = DMatrix::<f64>::zeros(num_msr, num_params);
+            for (p, pert) in perturbations.iter().enumerate() {
+                let mut plus = params.clone();
+                plus[p] += pert;
+                let mut minus = params.clone();
+                minus[p] -= pert;
+                let computed_plus = self.computed(&initial_guess, &plus, &measurements)?;
+                let computed_minus = self.computed(&initial_guess, &minus, &measurements)?;
+                for (i, (plus, minus)) in
+                    computed_plus.iter().zip(computed_minus.iter()).enumerate()
+                {
+                    let partial = (plus - minus) / (2.0 * pert);
+                    for j in 0..2 {
+                        h_tilde[(2 * i + j, p)] = partial[j];
+                    }
+                }
+            }
+
+            let mut info = apriori_info.clone();
+            let mut normal = &apriori_info * (&apriori - &params);
+            let mut weighted_sq = 0.0;
+            for i in 0..num_msr {
+                let weight = weights[i % 2];
+                let row = h_tilde.row(i);
+                info += weight * row.transpose() * row;
+                normal += weight * residuals[i] * row.transpose();
+                weighted_sq += weight * residuals[i].powi(2);
+            }
+
+            let covar = info
+                .try_inverse()
+                .ok_or(NyxError::SingularCovarianceMatrix)?;
+            let correction = &covar * normal;
+            params += correction;
+
+            let rms = (weighted_sq / num_msr as f64).sqrt();
+            let rms_change = (prev_rms - rms).abs() / rms;
+            prev_rms = rms;
+
+            debug!(
+                "gravity field estimation iteration #{iterations}: weighted RMS = {rms:.3e}, relative change = {rms_change:.3e}"
+            );
+
+            let converged = rms_change < self.tolerance || rms < self.tolerance;
+            if converged || iterations >= self.max_iterations {
+                if !converged {
+                    warn!(
+                        "gravity field estimation did not converge after {iterations} iterations"
+                    );
+                }
+                break covar;
+            }
+        };
+
+        // Post-fit residuals
+        let post_fit = self.computed(&initial_guess, &params, &measurements)?;
+        let mut sum_sq = Vector2::zeros();
+        for ((_, msr), computed) in measurements.iter().zip(post_fit.iter()) {
+            sum_sq += (msr.observation() - computed).map(|r| r.powi(2));
+        }
+
+        let (initial_state, correction) = self.unpack(&initial_guess, &params)?;
+
+        Ok(GravityFieldSolution {
+            initial_state,
+            correction,
+            covar,
+            rms_range_km: (sum_sq[0] / measurements.len() as f64).sqrt(),
+            rms_doppler_km_s: (sum_sq[1] / measurements.len() as f64).sqrt(),
+            num_msr: measurements.len(),
+            iterations,
+        })
+    }
+}
@@ -16,16 +16,23 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use super::media::MediaCorrections;
 use super::msr::RangeDoppler;
 use super::noise::GaussMarkov;
+use super::simulator::Visibility;
 use super::TrackingDeviceSim;
-use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::cosmic::{Cosm, Frame, Orbit, SPEED_OF_LIGHT_KMS};
 use crate::io::{frame_from_str, frame_to_str, ConfigRepr, Configurable};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, DimName, Matrix3, Vector3, U4};
 use crate::md::prelude::Traj;
-use crate::time::Epoch;
+use crate::md::trajectory::Interpolatable;
+use crate::time::{Epoch, Unit};
 use crate::utils::between_0_360;
 use crate::{NyxError, Spacecraft};
 use hifitime::Duration;
+use hyperdual::linalg::norm;
+use hyperdual::{hyperspace_from_vector, Float, OHyperdual};
 use rand_pcg::Pcg64Mcg;
 use serde_derive::{Deserialize, Serialize};
 use std::fmt;
@@ -34,6 +41,11 @@ use std::sync::Arc;
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 
+/// Maximum number of iterations of the light time of each leg
+const LIGHT_TIME_MAX_ITER: usize = 10;
+/// Convergence tolerance of the light time of each leg, in seconds
+const LIGHT_TIME_TOL_S: f64 = 1e-10;
+
 /// GroundStation defines a two-way ranging and doppler station.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "python", pyclass)]
@@ -54,8 +66,17 @@ pub struct GroundStation {
     /// Duration needed to generate a measurement (if unset, it is assumed to be instantaneous)
     #[serde(skip)]
     pub integration_time: Option<Duration>,
-    /// Whether to correct for light travel time
+    /// Whether to correct for light travel time: the range and Doppler are then computed along the downlink from the receiver
+    /// (preceded by the uplink for two-way measurements, i.e. with an integration time), solved iteratively, instead of
+    /// geometrically at the epoch of the measurement
     pub light_time_correction: bool,
+    /// Whether to correct the apparent direction of the receiver for the stellar aberration due to the velocity of this
+    /// station, which changes its azimuth and elevation
+    #[serde(default)]
+    pub aberration_correction: bool,
+    /// Troposphere and ionosphere delays added to the range and Doppler measurements, if any
+    #[serde(default)]
+    pub media: Option<MediaCorrections>,
     /// Noise on the timestamp of the measurement
     pub timestamp_noise_s: Option<GaussMarkov>,
     /// Noise on the range data of the measurement
@@ -83,6 +104,8 @@ impl GroundStation {
             frame,
             integration_time: None,
             light_time_correction: false,
+            aberration_correction: false,
+            media: None,
             timestamp_noise_s: None,
             range_noise_km: None,
             doppler_noise_km_s: None,
@@ -104,6 +127,8 @@ impl GroundStation {
             frame: iau_earth,
             integration_time: None,
             light_time_correction: false,
+            aberration_correction: false,
+            media: None,
             timestamp_noise_s: None,
             range_noise_km: Some(range_noise_km),
             doppler_noise_km_s: Some(doppler_noise_km_s),
@@ -125,6 +150,8 @@ impl GroundStation {
             frame: iau_earth,
             integration_time: None,
             light_time_correction: false,
+            aberration_correction: false,
+            media: None,
             timestamp_noise_s: None,
             range_noise_km: Some(range_noise_km),
             doppler_noise_km_s: Some(doppler_noise_km_s),
@@ -146,6 +173,8 @@ impl GroundStation {
             frame: iau_earth,
             integration_time: None,
             light_time_correction: false,
+            aberration_correction: false,
+            media: None,
             timestamp_noise_s: None,
             range_noise_km: Some(range_noise_km),
             doppler_noise_km_s: Some(doppler_noise_km_s),
@@ -155,8 +184,12 @@ impl GroundStation {
     /// Computes the azimuth and elevation of the provided object seen from this ground station, both in degrees.
     /// Also returns the ground station's orbit in the frame of the receiver
     pub fn azimuth_elevation_of(&self, rx: Orbit, cosm: &Cosm) -> (f64, f64, Orbit, Orbit) {
-        // Start by converting the receiver spacecraft into the ground station frame.
-        let rx_gs_frame = cosm.frame_chg(&rx, self.frame);
+        // Start by converting the receiver spacecraft (at its apparent position if needed) into the ground station frame.
+        let rx_gs_frame = if self.aberration_correction {
+            cosm.frame_chg(&self.apparent(rx, cosm), self.frame)
+        } else {
+            cosm.frame_chg(&rx, self.frame)
+        };
 
         let dt = rx.epoch;
         // Then, compute the rotation matrix from the body fixed frame of the ground station to its topocentric frame SEZ.
@@ -191,17 +224,395 @@ impl GroundStation {
         )
     }
 
-    /// Return this ground station as an orbit in its current frame
+    /// Computes the intervals during which the provided trajectory is above the elevation mask of this ground station.
+    /// The trajectory is sampled at the provided step and each rise and set epoch is then refined by bisection.
+    pub fn visibility_intervals<S: Interpolatable>(
+        &self,
+        traj: &Traj<S>,
+        step: Duration,
+        cosm: &Cosm,
+    ) -> Result<Visibility, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        Visibility::compute(
+            self.name.clone(),
+            self.elevation_mask_deg,
+            traj,
+            step,
+            |state| self.azimuth_elevation_of(*state.orbit(), cosm).1,
+        )
+    }
+
+    /// Returns the receiver at its apparent position seen from this station, i.e. with its direction corrected for the stellar
+    /// aberration due to the velocity of this station in the frame of the receiver, to first order in v/c.
+    fn apparent(&self, rx: Orbit, cosm: &Cosm) -> Orbit {
+        let tx = cosm.frame_chg(&self.to_orbit(rx.epoch), rx.frame);
+        let range_vec_km = rx.radius() - tx.radius();
+        let range_km = range_vec_km.norm();
+        let r_hat = range_vec_km / range_km;
+        let vbyc = tx.velocity() / SPEED_OF_LIGHT_KMS;
+        let apparent =
+            tx.radius() + (r_hat + vbyc - r_hat * r_hat.dot(&vbyc)).normalize() * range_km;
+
+        let mut rx_apparent = rx;
+        rx_apparent.x_km = apparent.x;
+        rx_apparent.y_km = apparent.y;
+        rx_apparent.z_km = apparent.z;
+        rx_apparent
+    }
+
+    /// Solves for the light-time corrected legs of a signal received by this station at the provided epoch from the receiver
+    /// of the trajectory: the downlink, preceded by the uplink from this station to the receiver if `two_way`.
+    ///
+    /// Each leg is the pair of the states of its transmitter at transmission and of its receiver at reception, in the frame of
+    /// the trajectory, and the legs are in chronological order. The light time of each leg is iterated until it converges.
+    pub fn light_time_legs<S: Interpolatable>(
+        &self,
+        epoch: Epoch,
+        traj: &Traj<S>,
+        two_way: bool,
+        cosm: &Cosm,
+    ) -> Result<Vec<(Orbit, Orbit)>, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        let frame = traj.first().orbit().frame;
+        let station_at = |epoch: Epoch| cosm.frame_chg(&self.to_orbit(epoch), frame);
+
+        // Downlink: the receiver transmitted one light time before the reception at this station
+        let station_rx = station_at(epoch);
+        let mut light_time_s = 0.0;
+        let mut bounce = *traj.at(epoch)?.orbit();
+        for _ in 0..LIGHT_TIME_MAX_ITER {
+            let next_s = (station_rx.radius() - bounce.radius()).norm() / SPEED_OF_LIGHT_KMS;
+            let converged = (next_s - light_time_s).abs() < LIGHT_TIME_TOL_S;
+            light_time_s = next_s;
+            bounce = *traj.at(epoch - light_time_s * Unit::Second)?.orbit();
+            if converged {
+                break;
+            }
+        }
+
+        let mut legs = Vec::with_capacity(2);
+        if two_way {
+            // Uplink: this station transmitted one light time before the reception at the receiver
+            let mut light_time_s = 0.0;
+            let mut station_tx = station_at(bounce.epoch);
+            for _ in 0..LIGHT_TIME_MAX_ITER {
+                let next_s = (bounce.radius() - station_tx.radius()).norm() / SPEED_OF_LIGHT_KMS;
+                let converged = (next_s - light_time_s).abs() < LIGHT_TIME_TOL_S;
+                light_time_s = next_s;
+                station_tx = station_at(bounce.epoch - light_time_s * Unit::Second);
+                if converged {
+                    break;
+                }
+            }
+            legs.push((station_tx, bounce));
+        }
+        legs.push((bounce, station_rx));
+        Ok(legs)
+    }
+
+    /// Simulates a light-time corrected measurement received at the provided epoch, integrated and two-way if this station has
+    /// an integration time, and one-way otherwise.
+    fn measure_light_time<S: Interpolatable>(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<S>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: &Cosm,
+    ) -> Result<Option<RangeDoppler>, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        let two_way = self.integration_time.is_some();
+        let legs = self.light_time_legs(epoch, traj, two_way, cosm)?;
+        let legs_start = match self.integration_time {
+            Some(integration_time) => {
+                Some(self.light_time_legs(epoch - integration_time, traj, two_way, cosm)?)
+            }
+            None => None,
+        };
+
+        // The receiver must be visible when it transmits the downlink, both at the start and at the end of the integration
+        for downlink in legs
+            .last()
+            .iter()
+            .chain(legs_start.as_ref().and_then(|legs| legs.last()).iter())
+        {
+            let (_, elevation, _, _) = self.azimuth_elevation_of(downlink.0, cosm);
+            if elevation < self.elevation_mask_deg {
+                debug!(
+                    "{} (el. mask {:.3} deg), object at {elevation:.3} deg at {} -- no measurement",
+                    self.name, self.elevation_mask_deg, downlink.0.epoch
+                );
+                return Ok(None);
+            }
+        }
+
+        let bounce = legs[legs.len() - 1].0;
+        let msr = match (self.integration_time, legs_start) {
+            (Some(integration_time), Some(legs_start)) => {
+                // Noises are computed at the midpoint of the integration time.
+                let (timestamp_noise_s, range_noise_km, doppler_noise_km_s) =
+                    self.noises(epoch - integration_time * 0.5, rng)?;
+                RangeDoppler::integrated(
+                    &legs_start,
+                    &legs,
+                    timestamp_noise_s,
+                    range_noise_km,
+                    doppler_noise_km_s,
+                )
+            }
+            _ => {
+                let (timestamp_noise_s, range_noise_km, doppler_noise_km_s) =
+                    self.noises(epoch, rng)?;
+                RangeDoppler::from_legs(
+                    &legs,
+                    timestamp_noise_s,
+                    range_noise_km,
+                    doppler_noise_km_s,
+                )
+            }
+        };
+        Ok(Some(self.with_media(msr, bounce, cosm)))
+    }
+
+    /// Returns the media delay along the line of sight to the receiver (km) and its rate (km/s), both zero without any media
+    /// corrections. Subtracting them from real range and Doppler measurements removes the modeled media effects.
+    ///
+    /// The rate is computed by central differences over one second, with the receiver moving along its velocity.
+    pub fn media_delay(&self, rx: Orbit, cosm: &Cosm) -> (f64, f64) {
+        match self.media {
+            None => (0.0, 0.0),
+            Some(media) => {
+                let delay_at = |dt_s: f64| {
+                    let mut rx_dt = rx;
+                    rx_dt.x_km += rx.vx_km_s * dt_s;
+                    rx_dt.y_km += rx.vy_km_s * dt_s;
+                    rx_dt.z_km += rx.vz_km_s * dt_s;
+                    rx_dt.epoch = rx.epoch + dt_s * Unit::Second;
+                    let (azimuth_deg, elevation_deg, _, _) = self.azimuth_elevation_of(rx_dt, cosm);
+                    media.delay_km(
+                        self.latitude_deg,
+                        self.longitude_deg,
+                        self.height_km,
+                        azimuth_deg,
+                        elevation_deg,
+                        rx_dt.epoch,
+                    )
+                };
+                (delay_at(0.0), 0.5 * (delay_at(1.0) - delay_at(-1.0)))
+            }
+        }
+    }
+
+    /// Adds the media delay and its rate toward the receiver to the provided measurement
+    fn with_media(&self, mut msr: RangeDoppler, rx: Orbit, cosm: &Cosm) -> RangeDoppler {
+        if self.media.is_some() {
+            let (delay_km, delay_rate_km_s) = self.media_delay(rx, cosm);
+            msr.obs[0] += delay_km;
+            msr.obs[1] += delay_rate_km_s;
+        }
+        msr
+    }
+
+    /// Return this ground station as an orbit in its current frame.
+    ///
+    /// The station is at rest in its body fixed frame: its velocity due to the rotation of the body is only accounted for
+    /// by the transport theorem when changing frames.
     pub fn to_orbit(&self, epoch: Epoch) -> Orbit {
-        Orbit::from_geodesic(
+        Orbit::from_altlatlong(
             self.latitude_deg,
             self.longitude_deg,
             self.height_km,
+            0.0,
             epoch,
             self.frame,
         )
     }
 
+    /// Computes the range (km), Doppler (km/s) and elevation (deg) of the receiver as measured by this ground station, and the
+    /// partials of these with respect to the geodetic latitude (deg), longitude (deg) and height (km) of the station.
+    ///
+    /// The rows of the returned matrix are the range, Doppler and elevation, and its columns the latitude, longitude and height.
+    /// The partials are computed with hyperdual numbers through the same models as `azimuth_elevation_of` and the one way
+    /// `RangeDoppler` measurement, and enable estimating the location of a station (cf. `survey`).
+    pub fn geodetic_partials(
+        &self,
+        rx: Orbit,
+        cosm: &Cosm,
+    ) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+        let (flattening, semi_major_radius) = match self.frame {
+            Frame::Geoid {
+                flattening,
+                semi_major_radius,
+                ..
+            } => (flattening, semi_major_radius),
+            _ => {
+                return Err(NyxError::CustomError(format!(
+                    "{} is not a geoid frame",
+                    self.frame
+                )))
+            }
+        };
+        let epoch = rx.epoch;
+        let tx_fixed = self.to_orbit(epoch);
+        let tx_inertial = cosm.try_frame_chg(&tx_fixed, rx.frame)?;
+        let rx_fixed = cosm.try_frame_chg(&rx, self.frame)?;
+        // The frame change only depends on the epoch, so the partials of the inertial state are those of the fixed state rotated
+        let dcm = cosm
+            .try_dcm_from_to(&self.frame, &rx.frame, epoch)?
+            .map(OHyperdual::<f64, U4>::from_real);
+
+        // Geodetic coordinates of the station in hyperspace, in radians and km
+        let geodetic: Vector3<OHyperdual<f64, U4>> = hyperspace_from_vector(&Vector3::new(
+            self.latitude_deg.to_radians(),
+            self.longitude_deg.to_radians(),
+            self.height_km,
+        ));
+        let (lat, long, height) = (geodetic[0], geodetic[1], geodetic[2]);
+
+        // Same as `Orbit::from_altlatlong`
+        let e2 = 2.0 * flattening - flattening.powi(2);
+        let (sin_lat, cos_lat) = (lat.sin(), lat.cos());
+        let (sin_long, cos_long) = (long.sin(), long.cos());
+        let denom = (OHyperdual::from_real(1.0) - sin_lat * sin_lat * e2).sqrt();
+        let c_body = OHyperdual::from_real(semi_major_radius) / denom;
+        let s_body = OHyperdual::from_real(semi_major_radius * (1.0 - flattening).powi(2)) / denom;
+        let r_fixed = Vector3::new(
+            (c_body + height) * cos_lat * cos_long,
+            (c_body + height) * cos_lat * sin_long,
+            (s_body + height) * sin_lat,
+        );
+
+        // Rotate the station, at rest in its frame, into the frame of the receiver, keeping the translation of the frame change
+        let r_rot = dcm.fixed_view::<3, 3>(0, 0) * r_fixed;
+        let v_rot = dcm.fixed_view::<3, 3>(3, 0) * r_fixed;
+        let mut range_vec = Vector3::zeros();
+        let mut velocity_vec = Vector3::zeros();
+        for i in 0..3 {
+            let r_offset = tx_inertial.radius()[i] - r_rot[i].real() - rx.radius()[i];
+            let v_offset = tx_inertial.velocity()[i] - v_rot[i].real() - rx.velocity()[i];
+            range_vec[i] = r_rot[i] + r_offset;
+            velocity_vec[i] = v_rot[i] + v_offset;
+        }
+        let range = norm(&range_vec);
+        let doppler = range_vec.dot(&velocity_vec) / range;
+
+        // Elevation in the SEZ frame of the station, whose zenith is normal to the ellipsoid
+        let zenith = Vector3::new(cos_lat * cos_long, cos_lat * sin_long, sin_lat);
+        let mut rho_fixed = Vector3::zeros();
+        for i in 0..3 {
+            rho_fixed[i] = OHyperdual::from_real(rx_fixed.radius()[i]) - r_fixed[i];
+        }
+        let elevation = (rho_fixed.dot(&zenith) / norm(&rho_fixed)).asin();
+
+        let mut fx = Vector3::zeros();
+        let mut pmat = Matrix3::zeros();
+        for (i, value) in [range, doppler, elevation.to_degrees()].iter().enumerate() {
+            fx[i] = value.real();
+            for j in 1..U4::dim() {
+                pmat[(i, j - 1)] = value[j];
+            }
+        }
+        // Partials with respect to the latitude and longitude in degrees
+        for j in 0..2 {
+            for i in 0..3 {
+                pmat[(i, j)] = pmat[(i, j)].to_radians();
+            }
+        }
+
+        Ok((fx, pmat))
+    }
+
+    /// Estimates the location of this ground station from its range and Doppler measurements of a receiver whose trajectory
+    /// is known, using a weighted least squares on its latitude (deg), longitude (deg) and height (km) starting from the
+    /// current location of this station.
+    ///
+    /// Returns the surveyed station and the covariance of its latitude, longitude and height, which is useful to analyze how
+    /// well a site is determined by a given tracking geometry.
+    pub fn survey(
+        &self,
+        msrs: &[RangeDoppler],
+        traj: &Traj<Orbit>,
+        range_sigma_km: f64,
+        doppler_sigma_km_s: f64,
+        cosm: &Cosm,
+    ) -> Result<(Self, Matrix3<f64>), NyxError> {
+        self.survey_with(
+            msrs,
+            |msr| Ok((*msr, traj.at(msr.epoch)?)),
+            range_sigma_km,
+            doppler_sigma_km_s,
+            cosm,
+        )
+    }
+
+    /// Weighted least squares on the location of this station, where each datum provides a one way range and Doppler
+    /// measurement and the state of the other end of the link at the epoch of that measurement.
+    pub(crate) fn survey_with<T>(
+        &self,
+        data: &[T],
+        counterpart: impl Fn(&T) -> Result<(RangeDoppler, Orbit), NyxError>,
+        range_sigma_km: f64,
+        doppler_sigma_km_s: f64,
+        cosm: &Cosm,
+    ) -> Result<(Self, Matrix3<f64>), NyxError> {
+        if data.len() < 2 {
+            return Err(NyxError::CustomError(
+                "at least two measurements are needed to survey a station".to_string(),
+            ));
+        }
+        let weights = [range_sigma_km.powi(-2), doppler_sigma_km_s.powi(-2)];
+        let mut station = self.clone();
+        for iteration in 0..10 {
+            let mut info = Matrix3::zeros();
+            let mut rhs = Vector3::zeros();
+            let mut epoch = None;
+            for datum in data {
+                let (msr, rx) = counterpart(datum)?;
+                epoch = Some(msr.epoch);
+                let (computed, partials) = station.geodetic_partials(rx, cosm)?;
+                for (k, weight) in weights.iter().enumerate() {
+                    let h_row = partials.row(k);
+                    info += h_row.transpose() * h_row * *weight;
+                    rhs += h_row.transpose() * (msr.obs[k] - computed[k]) * *weight;
+                }
+            }
+            let covar = info.try_inverse().ok_or_else(|| {
+                NyxError::CustomError(format!(
+                    "survey of {} is singular: the measurements do not observe its location",
+                    self.name
+                ))
+            })?;
+            let correction = covar * rhs;
+            station.latitude_deg += correction[0];
+            station.longitude_deg += correction[1];
+            station.height_km += correction[2];
+            debug!("survey of {} #{iteration}: {station}", self.name);
+            // Stop once the correction is below a millimeter
+            let radius_km = station.to_orbit(epoch.unwrap()).rmag_km();
+            if correction[0].to_radians().abs() * radius_km < 1e-6
+                && correction[1].to_radians().abs() * radius_km < 1e-6
+                && correction[2].abs() < 1e-6
+            {
+                return Ok((station, covar));
+            }
+        }
+        Err(NyxError::MaxIterReached(format!(
+            "survey of {} did not converge",
+            self.name
+        )))
+    }
+
     /// Returns the timestamp noise, range noise, and doppler noise for this ground station at the provided epoch.
     fn noises(
         &mut self,
@@ -277,6 +688,10 @@ impl TrackingDeviceSim<Orbit, RangeDoppler> for GroundStation {
         rng: Option<&mut Pcg64Mcg>,
         cosm: Arc<Cosm>,
     ) -> Result<Option<RangeDoppler>, NyxError> {
+        if self.light_time_correction {
+            return self.measure_light_time(epoch, traj, rng, &cosm);
+        }
+
         match self.integration_time {
             Some(integration_time) => {
                 let rx_0 = traj.at(epoch - integration_time)?;
@@ -297,13 +712,14 @@ impl TrackingDeviceSim<Orbit, RangeDoppler> for GroundStation {
                 let (timestamp_noise_s, range_noise_km, doppler_noise_km_s) =
                     self.noises(epoch - integration_time * 0.5, rng)?;
 
-                Ok(Some(RangeDoppler::two_way(
+                let msr = RangeDoppler::two_way(
                     (tx_0, tx_1),
                     (rx_0, rx_1),
                     timestamp_noise_s,
                     range_noise_km,
                     doppler_noise_km_s,
-                )))
+                );
+                Ok(Some(self.with_media(msr, rx_1, &cosm)))
             }
             None => self.measure_instantaneous(traj.at(epoch)?, rng, cosm),
         }
@@ -317,6 +733,15 @@ impl TrackingDeviceSim<Orbit, RangeDoppler> for GroundStation {
         cosm.frame_chg(&self.to_orbit(epoch), frame)
     }
 
+    fn visibility(
+        &self,
+        traj: &Traj<Orbit>,
+        step: Duration,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<Visibility>, NyxError> {
+        self.visibility_intervals(traj, step, &cosm).map(Some)
+    }
+
     fn measure_instantaneous(
         &mut self,
         rx: Orbit,
@@ -330,13 +755,14 @@ impl TrackingDeviceSim<Orbit, RangeDoppler> for GroundStation {
             let (timestamp_noise_s, range_noise_km, doppler_noise_km_s) =
                 self.noises(rx.epoch, rng)?;
 
-            Ok(Some(RangeDoppler::one_way(
+            let msr = RangeDoppler::one_way(
                 tx,
                 rx,
                 timestamp_noise_s,
                 range_noise_km,
                 doppler_noise_km_s,
-            )))
+            );
+            Ok(Some(self.with_media(msr, rx, &cosm)))
         } else {
             debug!(
                 "{} (el. mask {:.3} deg), object at {elevation:.3} deg -- no measurement",
@@ -356,6 +782,10 @@ impl TrackingDeviceSim<Spacecraft, RangeDoppler> for GroundStation {
         rng: Option<&mut Pcg64Mcg>,
         cosm: Arc<Cosm>,
     ) -> Result<Option<RangeDoppler>, NyxError> {
+        if self.light_time_correction {
+            return self.measure_light_time(epoch, traj, rng, &cosm);
+        }
+
         let rx = traj.at(epoch)?;
         self.measure_instantaneous(rx, rng, cosm)
     }
@@ -368,6 +798,15 @@ impl TrackingDeviceSim<Spacecraft, RangeDoppler> for GroundStation {
         cosm.frame_chg(&self.to_orbit(epoch), frame)
     }
 
+    fn visibility(
+        &self,
+        traj: &Traj<Spacecraft>,
+        step: Duration,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<Visibility>, NyxError> {
+        self.visibility_intervals(traj, step, &cosm).map(Some)
+    }
+
     fn measure_instantaneous(
         &mut self,
         rx: Spacecraft,
@@ -381,13 +820,14 @@ impl TrackingDeviceSim<Spacecraft, RangeDoppler> for GroundStation {
             let (timestamp_noise_s, range_noise_km, doppler_noise_km_s) =
                 self.noises(rx.epoch, rng)?;
 
-            Ok(Some(RangeDoppler::one_way(
+            let msr = RangeDoppler::one_way(
                 tx,
                 rx,
                 timestamp_noise_s,
                 range_noise_km,
                 doppler_noise_km_s,
-            )))
+            );
+            Ok(Some(self.with_media(msr, rx, &cosm)))
         } else {
             debug!(
                 "{} (el. mask {:.3} deg), object at {elevation:.3} deg -- no measurement",
@@ -449,6 +889,8 @@ fn test_load_single() {
         longitude_deg: 48.8566,
         height_km: 0.4,
         light_time_correction: false,
+        aberration_correction: false,
+        media: None,
         timestamp_noise_s: None,
         integration_time: None,
     };
@@ -492,6 +934,8 @@ fn test_load_many() {
             longitude_deg: 48.8566,
             height_km: 0.4,
             light_time_correction: false,
+            aberration_correction: false,
+            media: None,
             timestamp_noise_s: None,
             integration_time: None,
         },
@@ -505,6 +949,8 @@ fn test_load_many() {
             longitude_deg: 148.981944,
             height_km: 0.691750,
             light_time_correction: false,
+            aberration_correction: false,
+            media: None,
             timestamp_noise_s: None,
             integration_time: None,
         },
@@ -512,3 +958,54 @@ fn test_load_many() {
 
     assert_eq!(expected, stations);
 }
+
+#[test]
+fn test_station_state_validation() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    // From SPICE, same states as in test_cosm_rotation_validation: this point is at rest on the surface of the Earth.
+    let dt = Epoch::from_gregorian_utc(2023, 11, 16, 6, 11, 19, 146200000);
+    let spice_eme2k = Orbit::cartesian(
+        2196.260617879428,
+        5161.156645108604,
+        3026.122639999121,
+        -0.376262062797,
+        0.159851964260,
+        0.000983174100,
+        dt,
+        eme2k,
+    );
+    let spice_iau_earth = Orbit::cartesian(
+        925.651012109672,
+        -5529.343261192083,
+        3031.179663596684,
+        0.000032603895,
+        -0.000229009865,
+        0.000108943879,
+        dt,
+        iau_earth,
+    );
+
+    let gs = GroundStation::from_point(
+        "SPICE".to_string(),
+        spice_iau_earth.geodetic_latitude_deg(),
+        spice_iau_earth.geodetic_longitude_deg(),
+        spice_iau_earth.geodetic_height_km(),
+        iau_earth,
+    );
+
+    // The station is at rest in its body fixed frame
+    let gs_iau_earth = gs.to_orbit(dt);
+    assert!(dbg!((gs_iau_earth - spice_iau_earth).rmag_km()) < 1e-6);
+    assert_eq!(gs_iau_earth.vmag_km_s(), 0.0);
+
+    // Its inertial velocity only comes from the rotation of the Earth. The SPICE state moves by 0.26 m/s in the body
+    // fixed frame, so that is the best achievable agreement in velocity (the rotation velocity is 0.41 km/s here).
+    let gs_eme2k = cosm.frame_chg(&gs_iau_earth, eme2k);
+    println!("{gs_eme2k}\n{spice_eme2k}");
+    let (pos_rss, vel_rss) = gs_eme2k.rss(&spice_eme2k);
+    assert!(dbg!(pos_rss) < 1e-4);
+    assert!(dbg!(vel_rss) < 3e-4);
+}
@@ -0,0 +1,223 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::estimate::KfEstimate;
+use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::errors::NyxError;
+use crate::linalg::{Matrix6, Vector3, Vector6};
+use std::fmt;
+
+/// Axes in which a launch provider delivers the dispersions of the injection state.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum InjectionAxes {
+    /// Cartesian axes of the (inertial) frame of the injection state
+    Inertial,
+    /// Local orbital frame of the injection state, i.e. RIC, VNC or RCN.
+    ///
+    /// As for the RIC covariance of the OD exports, the velocity components are only rotated (no transport theorem).
+    Orbital(Frame),
+    /// Topocentric South-East-Zenith axes of the launch pad, fixed to the rotating body: the velocity dispersions are relative
+    /// to that body, as for a launch vehicle guidance.
+    LaunchPad {
+        /// Geodetic latitude of the launch pad, in degrees
+        latitude_deg: f64,
+        /// Geodetic longitude of the launch pad, in degrees
+        longitude_deg: f64,
+        /// Height of the launch pad above the geoid, in km
+        height_km: f64,
+        /// Body fixed frame of the launch pad, e.g. IAU Earth
+        body_fixed: Frame,
+    },
+}
+
+impl fmt::Display for InjectionAxes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Inertial => write!(f, "inertial"),
+            Self::Orbital(frame) => write!(f, "{frame}"),
+            Self::LaunchPad {
+                latitude_deg,
+                longitude_deg,
+                height_km,
+                body_fixed,
+            } => write!(
+                f,
+                "SEZ of the launch pad at lat.: {latitude_deg:.4} deg    long.: {longitude_deg:.4} deg    alt.: {:.3} m in {body_fixed}",
+                height_km * 1e3
+            ),
+        }
+    }
+}
+
+/// The covariance of the injection state of a launch vehicle, as delivered by the launch provider, used as the a priori
+/// constraint of the orbit determination of the early acquisition.
+///
+/// The covariance is given in km, km/s and their products, in the injection axes. It is converted into the Cartesian
+/// covariance of the frame of the injection state, which is how the OD process expects it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InjectionCovariance {
+    /// Axes of the covariance
+    pub axes: InjectionAxes,
+    /// Covariance of the position (km) and velocity (km/s) in these axes
+    pub covar: Matrix6<f64>,
+}
+
+impl InjectionCovariance {
+    /// Initializes a new injection covariance, ensuring that it is a valid covariance matrix.
+    pub fn new(axes: InjectionAxes, covar: Matrix6<f64>) -> Result<Self, NyxError> {
+        if let InjectionAxes::Orbital(frame) = axes {
+            if !matches!(frame, Frame::RIC | Frame::VNC | Frame::RCN) {
+                return Err(NyxError::CustomError(format!(
+                    "injection orbital frame must be RIC, VNC or RCN, got {frame}"
+                )));
+            }
+        }
+        if let InjectionAxes::LaunchPad { body_fixed, .. } = axes {
+            if !body_fixed.is_geoid() || !body_fixed.is_body_fixed() {
+                return Err(NyxError::CustomError(format!(
+                    "launch pad frame must be a body fixed geoid, got {body_fixed}"
+                )));
+            }
+        }
+        if (covar - covar.transpose()).abs().max() > 1e-12 * covar.abs().max() {
+            return Err(NyxError::CustomError(
+                "injection covariance must be symmetric".to_string(),
+            ));
+        }
+        if covar.symmetric_eigenvalues().min() < -1e-12 * covar.abs().max() {
+            return Err(NyxError::CustomError(
+                "injection covariance must be positive semi-definite".to_string(),
+            ));
+        }
+        Ok(Self { axes, covar })
+    }
+
+    /// Initializes a new injection covariance from the standard deviations of the position (km) and of the velocity (km/s)
+    /// in the injection axes, assumed uncorrelated.
+    pub fn from_std_devs(
+        axes: InjectionAxes,
+        position_km: Vector3<f64>,
+        velocity_km_s: Vector3<f64>,
+    ) -> Result<Self, NyxError> {
+        let sigmas = Vector6::new(
+            position_km[0],
+            position_km[1],
+            position_km[2],
+            velocity_km_s[0],
+            velocity_km_s[1],
+            velocity_km_s[2],
+        );
+        Self::from_correlations(axes, sigmas, Matrix6::identity())
+    }
+
+    /// Initializes a new injection covariance from the standard deviations of the position and velocity (km and km/s) and
+    /// their correlation matrix, as typically provided in the launch vehicle injection accuracy reports.
+    pub fn from_correlations(
+        axes: InjectionAxes,
+        std_devs: Vector6<f64>,
+        correlations: Matrix6<f64>,
+    ) -> Result<Self, NyxError> {
+        if std_devs
+            .iter()
+            .any(|sigma| !sigma.is_finite() || *sigma < 0.0)
+        {
+            return Err(NyxError::CustomError(
+                "injection standard deviations must be non-negative".to_string(),
+            ));
+        }
+        if (0..6).any(|i| (correlations[(i, i)] - 1.0).abs() > 1e-12)
+            || correlations.iter().any(|rho| rho.abs() > 1.0)
+        {
+            return Err(NyxError::CustomError(
+                "correlations must be within [-1; 1] with a unit diagonal".to_string(),
+            ));
+        }
+        let sigmas = Matrix6::from_diagonal(&std_devs);
+        Self::new(axes, sigmas * correlations * sigmas)
+    }
+
+    /// Returns the DCM (with the transport theorem for the launch pad axes) from the injection axes to the frame of the
+    /// provided injection state.
+    pub fn dcm_to_frame_of(
+        &self,
+        injection: &Orbit,
+        cosm: &Cosm,
+    ) -> Result<Matrix6<f64>, NyxError> {
+        match self.axes {
+            InjectionAxes::Inertial => Ok(Matrix6::identity()),
+            InjectionAxes::Orbital(frame) => injection.dcm6x6_from_traj_frame(frame),
+            InjectionAxes::LaunchPad {
+                latitude_deg,
+                longitude_deg,
+                height_km,
+                body_fixed,
+            } => {
+                // The SEZ axes are fixed in the body fixed frame, so only the rotation of the body needs the transport theorem.
+                let pad = Orbit::from_geodesic(
+                    latitude_deg,
+                    longitude_deg,
+                    height_km,
+                    injection.epoch,
+                    body_fixed,
+                );
+                let sez2fixed = pad.dcm_from_traj_frame(Frame::SEZ)?;
+                let mut dcm_sez = Matrix6::zeros();
+                dcm_sez.fixed_view_mut::<3, 3>(0, 0).copy_from(&sez2fixed);
+                dcm_sez.fixed_view_mut::<3, 3>(3, 3).copy_from(&sez2fixed);
+
+                let fixed2frame =
+                    cosm.try_dcm_from_to(&body_fixed, &injection.frame, injection.epoch)?;
+                Ok(fixed2frame * dcm_sez)
+            }
+        }
+    }
+
+    /// Returns this covariance in the Cartesian axes of the frame of the provided injection state.
+    pub fn covar_in_frame_of(
+        &self,
+        injection: &Orbit,
+        cosm: &Cosm,
+    ) -> Result<Matrix6<f64>, NyxError> {
+        let dcm = self.dcm_to_frame_of(injection, cosm)?;
+        Ok(dcm * self.covar * dcm.transpose())
+    }
+
+    /// Builds the initial estimate of the orbit determination process from the nominal injection state and this covariance.
+    ///
+    /// The STM of the injection state must be enabled by the caller (`with_stm`) if needed by the filter.
+    pub fn estimate(&self, injection: Orbit, cosm: &Cosm) -> Result<KfEstimate<Orbit>, NyxError> {
+        let covar = self.covar_in_frame_of(&injection, cosm)?;
+        Ok(KfEstimate::from_covar(injection, covar))
+    }
+}
+
+impl fmt::Display for InjectionCovariance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Injection covariance in {}: σ = [{:.3} {:.3} {:.3}] m  [{:.3} {:.3} {:.3}] m/s",
+            self.axes,
+            self.covar[(0, 0)].sqrt() * 1e3,
+            self.covar[(1, 1)].sqrt() * 1e3,
+            self.covar[(2, 2)].sqrt() * 1e3,
+            self.covar[(3, 3)].sqrt() * 1e3,
+            self.covar[(4, 4)].sqrt() * 1e3,
+            self.covar[(5, 5)].sqrt() * 1e3,
+        )
+    }
+}
@@ -0,0 +1,320 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::SPEED_OF_LIGHT_KMS;
+use crate::time::Epoch;
+use serde_derive::{Deserialize, Serialize};
+use std::f64::consts::{PI, TAU};
+
+/// Frequency of the GPS L1 carrier, to which the Klobuchar coefficients refer, in Hz
+const GPS_L1_HZ: f64 = 1_575.42e6;
+/// Ionospheric refraction constant, in m^3/s^2
+const IONO_CONSTANT: f64 = 40.3;
+/// Mean radius of the Earth for the thin shell ionosphere, in km
+const IONO_EARTH_RADIUS_KM: f64 = 6371.0;
+/// Elevations below this value are clamped in the mapping functions, in degrees
+const MIN_ELEVATION_DEG: f64 = 1.0;
+
+/// Latitudes of the Niell mapping function coefficient tables, in degrees
+const NIELL_LATITUDES_DEG: [f64; 5] = [15.0, 30.0, 45.0, 60.0, 75.0];
+/// Average hydrostatic coefficients (a, b, c) of the Niell mapping function
+const NIELL_HYDRO_AVG: [[f64; 5]; 3] = [
+    [
+        1.2769934e-3,
+        1.2683230e-3,
+        1.2465397e-3,
+        1.2196049e-3,
+        1.2045996e-3,
+    ],
+    [
+        2.9153695e-3,
+        2.9152299e-3,
+        2.9288445e-3,
+        2.9022565e-3,
+        2.9024912e-3,
+    ],
+    [
+        62.610505e-3,
+        62.837393e-3,
+        63.721774e-3,
+        63.824265e-3,
+        64.258455e-3,
+    ],
+];
+/// Seasonal amplitude of the hydrostatic coefficients (a, b, c) of the Niell mapping function
+const NIELL_HYDRO_AMP: [[f64; 5]; 3] = [
+    [0.0, 1.2709626e-5, 2.6523662e-5, 3.4000452e-5, 4.1202191e-5],
+    [0.0, 2.1414979e-5, 3.0160779e-5, 7.2562722e-5, 11.723375e-5],
+    [0.0, 9.0128400e-5, 4.3497037e-5, 84.795348e-5, 170.37206e-5],
+];
+/// Height correction coefficients (a, b, c) of the hydrostatic Niell mapping function
+const NIELL_HEIGHT: [f64; 3] = [2.53e-5, 5.49e-3, 1.14e-3];
+/// Wet coefficients (a, b, c) of the Niell mapping function
+const NIELL_WET: [[f64; 5]; 3] = [
+    [
+        5.8021897e-4,
+        5.6794847e-4,
+        5.8118019e-4,
+        5.9727542e-4,
+        6.1641693e-4,
+    ],
+    [
+        1.4275268e-3,
+        1.5138625e-3,
+        1.4572752e-3,
+        1.5007428e-3,
+        1.7599082e-3,
+    ],
+    [
+        4.3472961e-2,
+        4.6729510e-2,
+        4.3908931e-2,
+        4.4626982e-2,
+        5.4736038e-2,
+    ],
+];
+
+/// Meteorological conditions at a ground station, used by the Saastamoinen zenith delays of the troposphere.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Meteo {
+    /// Total pressure, in hPa
+    pub pressure_hpa: f64,
+    /// Temperature, in Kelvin
+    pub temperature_k: f64,
+    /// Relative humidity, between 0 and 1
+    pub relative_humidity: f64,
+}
+
+impl Meteo {
+    /// Standard atmosphere at the provided height above the geoid, with a relative humidity of 70%.
+    pub fn standard(height_km: f64) -> Self {
+        let height_m = height_km.max(0.0) * 1e3;
+        Self {
+            pressure_hpa: 1013.25 * (1.0 - 2.2557e-5 * height_m).powf(5.2568),
+            temperature_k: 288.15 - 6.5e-3 * height_m,
+            relative_humidity: 0.7,
+        }
+    }
+
+    /// Partial pressure of the water vapor, in hPa
+    pub fn water_vapor_hpa(&self) -> f64 {
+        let t = self.temperature_k;
+        self.relative_humidity * 6.108 * ((17.15 * t - 4684.0) / (t - 38.45)).exp()
+    }
+}
+
+/// Troposphere model: Saastamoinen zenith delays mapped to the elevation with the Niell mapping functions.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Troposphere {
+    /// Meteorological conditions of the standard atmosphere at the height of the station
+    StandardAtmosphere,
+    /// Meteorological conditions measured at the station
+    Measured(Meteo),
+}
+
+impl Troposphere {
+    /// Returns the hydrostatic and wet zenith delays of a station at the provided geodetic latitude and height, in km.
+    pub fn zenith_delays_km(&self, latitude_deg: f64, height_km: f64) -> (f64, f64) {
+        let meteo = match self {
+            Self::StandardAtmosphere => Meteo::standard(height_km),
+            Self::Measured(meteo) => *meteo,
+        };
+        let hydrostatic_m = 0.0022768 * meteo.pressure_hpa
+            / (1.0 - 0.00266 * (2.0 * latitude_deg.to_radians()).cos() - 0.00028 * height_km);
+        let wet_m = 0.002277 * (1255.0 / meteo.temperature_k + 0.05) * meteo.water_vapor_hpa();
+        (hydrostatic_m * 1e-3, wet_m * 1e-3)
+    }
+
+    /// Returns the slant delay at the provided elevation of a station at the provided geodetic latitude and height, in km.
+    pub fn delay_km(
+        &self,
+        latitude_deg: f64,
+        height_km: f64,
+        elevation_deg: f64,
+        epoch: Epoch,
+    ) -> f64 {
+        let (hydrostatic_km, wet_km) = self.zenith_delays_km(latitude_deg, height_km);
+        let (m_hydrostatic, m_wet) = niell_mapping(latitude_deg, height_km, elevation_deg, epoch);
+        hydrostatic_km * m_hydrostatic + wet_km * m_wet
+    }
+}
+
+/// Ionosphere model, whose group delay scales with the inverse of the square of the carrier frequency.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Ionosphere {
+    /// Thin shell at the provided height with a constant vertical total electron content, mapped to the elevation
+    SingleLayer {
+        /// Vertical total electron content, in TEC units (1e16 electrons/m^2)
+        vtec_tecu: f64,
+        /// Height of the shell, typically 350 km
+        shell_height_km: f64,
+    },
+    /// Klobuchar model with the alpha and beta coefficients of the GPS broadcast message
+    Klobuchar { alpha: [f64; 4], beta: [f64; 4] },
+}
+
+impl Ionosphere {
+    /// Returns the group delay at the provided carrier frequency along the line of sight at the provided azimuth and
+    /// elevation from a station at the provided geodetic latitude and longitude, in km.
+    pub fn delay_km(
+        &self,
+        latitude_deg: f64,
+        longitude_deg: f64,
+        azimuth_deg: f64,
+        elevation_deg: f64,
+        epoch: Epoch,
+        frequency_hz: f64,
+    ) -> f64 {
+        let elevation_deg = elevation_deg.max(MIN_ELEVATION_DEG);
+        match *self {
+            Self::SingleLayer {
+                vtec_tecu,
+                shell_height_km,
+            } => {
+                let sin_zenith = IONO_EARTH_RADIUS_KM * elevation_deg.to_radians().cos()
+                    / (IONO_EARTH_RADIUS_KM + shell_height_km);
+                let mapping = 1.0 / (1.0 - sin_zenith.powi(2)).sqrt();
+                IONO_CONSTANT * vtec_tecu * 1e16 / frequency_hz.powi(2) * mapping * 1e-3
+            }
+            Self::Klobuchar { alpha, beta } => {
+                // IS-GPS-200, figure 20-4: all angles in semicircles
+                let el = elevation_deg / 180.0;
+                let az = azimuth_deg.to_radians();
+                let psi = 0.0137 / (el + 0.11) - 0.022;
+                let phi_i = (latitude_deg / 180.0 + psi * az.cos()).clamp(-0.416, 0.416);
+                let lambda_i = longitude_deg / 180.0 + psi * az.sin() / (phi_i * PI).cos();
+                let phi_m = phi_i + 0.064 * ((lambda_i - 1.617) * PI).cos();
+                let t = (4.32e4 * lambda_i + epoch.to_gpst_seconds()).rem_euclid(86_400.0);
+                let slant = 1.0 + 16.0 * (0.53 - el).powi(3);
+                let amplitude = polynomial(&alpha, phi_m).max(0.0);
+                let period = polynomial(&beta, phi_m).max(72_000.0);
+                let x = TAU * (t - 50_400.0) / period;
+                let delay_s = if x.abs() < 1.57 {
+                    slant * (5e-9 + amplitude * (1.0 - x.powi(2) / 2.0 + x.powi(4) / 24.0))
+                } else {
+                    slant * 5e-9
+                };
+                delay_s * SPEED_OF_LIGHT_KMS * (GPS_L1_HZ / frequency_hz).powi(2)
+            }
+        }
+    }
+}
+
+/// Media corrections of the range and Doppler measurements of a ground station.
+///
+/// The delays are added to the simulated one-way (or one-way equivalent) range, and their rate to the Doppler. Since the
+/// orbit determination computes the expected measurements with the same models, the delays cancel out in the residuals.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MediaCorrections {
+    /// Carrier frequency of the link, in Hz, only used by the dispersive ionosphere
+    pub frequency_hz: f64,
+    /// Troposphere model, if any
+    pub troposphere: Option<Troposphere>,
+    /// Ionosphere model, if any
+    pub ionosphere: Option<Ionosphere>,
+}
+
+impl MediaCorrections {
+    /// Total media delay along the line of sight at the provided azimuth and elevation from a station at the provided geodetic
+    /// coordinates, in km.
+    pub fn delay_km(
+        &self,
+        latitude_deg: f64,
+        longitude_deg: f64,
+        height_km: f64,
+        azimuth_deg: f64,
+        elevation_deg: f64,
+        epoch: Epoch,
+    ) -> f64 {
+        let troposphere_km = self.troposphere.map_or(0.0, |troposphere| {
+            troposphere.delay_km(latitude_deg, height_km, elevation_deg, epoch)
+        });
+        let ionosphere_km = self.ionosphere.map_or(0.0, |ionosphere| {
+            ionosphere.delay_km(
+                latitude_deg,
+                longitude_deg,
+                azimuth_deg,
+                elevation_deg,
+                epoch,
+                self.frequency_hz,
+            )
+        });
+        troposphere_km + ionosphere_km
+    }
+}
+
+/// Niell (1996) hydrostatic and wet mapping functions at the provided elevation.
+pub fn niell_mapping(
+    latitude_deg: f64,
+    height_km: f64,
+    elevation_deg: f64,
+    epoch: Epoch,
+) -> (f64, f64) {
+    let sin_el = elevation_deg.max(MIN_ELEVATION_DEG).to_radians().sin();
+    // The seasons are shifted by half a year in the southern hemisphere
+    let doy = if latitude_deg < 0.0 {
+        epoch.day_of_year() + 182.625
+    } else {
+        epoch.day_of_year()
+    };
+    let seasonal = (TAU * (doy - 28.0) / 365.25).cos();
+
+    let lat = latitude_deg.abs();
+    let coeff = |table: &[[f64; 5]; 3], i: usize| interpolate_latitude(&table[i], lat);
+    let hydrostatic = marini(
+        sin_el,
+        coeff(&NIELL_HYDRO_AVG, 0) - coeff(&NIELL_HYDRO_AMP, 0) * seasonal,
+        coeff(&NIELL_HYDRO_AVG, 1) - coeff(&NIELL_HYDRO_AMP, 1) * seasonal,
+        coeff(&NIELL_HYDRO_AVG, 2) - coeff(&NIELL_HYDRO_AMP, 2) * seasonal,
+    );
+    let height_correction = (1.0 / sin_el
+        - marini(sin_el, NIELL_HEIGHT[0], NIELL_HEIGHT[1], NIELL_HEIGHT[2]))
+        * height_km;
+    let wet = marini(
+        sin_el,
+        coeff(&NIELL_WET, 0),
+        coeff(&NIELL_WET, 1),
+        coeff(&NIELL_WET, 2),
+    );
+    (hydrostatic + height_correction, wet)
+}
+
+/// Marini continued fraction, normalized to unity at the zenith
+fn marini(sin_el: f64, a: f64, b: f64, c: f64) -> f64 {
+    (1.0 + a / (1.0 + b / (1.0 + c))) / (sin_el + a / (sin_el + b / (sin_el + c)))
+}
+
+/// Linear interpolation of a Niell coefficient at the provided absolute latitude, constant outside of the tables
+fn interpolate_latitude(values: &[f64; 5], lat_deg: f64) -> f64 {
+    if lat_deg <= NIELL_LATITUDES_DEG[0] {
+        return values[0];
+    }
+    for i in 0..4 {
+        if lat_deg <= NIELL_LATITUDES_DEG[i + 1] {
+            let frac = (lat_deg - NIELL_LATITUDES_DEG[i])
+                / (NIELL_LATITUDES_DEG[i + 1] - NIELL_LATITUDES_DEG[i]);
+            return values[i] + frac * (values[i + 1] - values[i]);
+        }
+    }
+    values[4]
+}
+
+/// Evaluates the cubic polynomial of the Klobuchar coefficients
+fn polynomial(coeffs: &[f64; 4], x: f64) -> f64 {
+    coeffs.iter().rev().fold(0.0, |acc, coeff| acc * x + coeff)
+}
@@ -29,7 +29,30 @@ pub use filter::Filter;
 
 /// Provides a range and range rate measuring models.
 mod ground_station;
+
+/// Provides the troposphere and ionosphere delays of the range and Doppler measurements
+mod media;
 pub use ground_station::GroundStation;
+pub use media::{niell_mapping, Ionosphere, MediaCorrections, Meteo, Troposphere};
+
+/// Provides the tracking of landers and rovers on the surface of a rotating body from ground stations
+mod surface_asset;
+pub use surface_asset::{SurfaceAsset, Waypoint};
+
+mod position_fix;
+pub use position_fix::PositionFixSource;
+
+/// Provides the inter-satellite range and Doppler measurements from another spacecraft
+mod spacecraft_tracker;
+pub use spacecraft_tracker::SpacecraftTracker;
+
+/// Provides the optical navigation measurements of a target body from a spacecraft camera
+mod opnav;
+pub use opnav::{CameraAttitude, CameraAttitudeSample, OpNavCamera, PinholeCamera};
+
+/// Provides the Delta-DOR measurements of a spacecraft from a baseline of two ground stations and a quasar catalog
+mod delta_dor;
+pub use delta_dor::{DeltaDorBaseline, Quasar};
 
 /// Provides Estimate handling functionalities.
 pub mod estimate;
@@ -46,21 +69,65 @@ pub mod simulator;
 /// Provides the interfaces to the orbit determination process
 pub mod process;
 
+/// Provides an end-to-end orbit determination scenario driver from a single configuration
+pub mod scenario;
+
+/// Provides the covariance analysis of the delivery to an encounter versus the data cutoff epoch
+pub mod delivery;
+
+/// Provides the mapping of a covariance along a trajectory, without processing any measurement
+mod covar_map;
+pub use covar_map::covariance_map;
+
+/// Provides the selection of the measurements free of media corruption from the tracking geometry
+mod arc_selection;
+pub use arc_selection::{EditList, EditReason, MeasurementEdit, MediaEditConfig};
+
+#[cfg(feature = "io")]
 use arrow::datatypes::Field;
 pub use simulator::TrackingDeviceSim;
 
+/// Provides the estimation of the calibration of accelerometers from position fixes
+mod accel_calibration;
+pub use accel_calibration::{AccelCalibration, AccelCalibrationEstimator};
+
+/// Provides the fitting of short-term density corrections from the drag scale factors estimated by successive orbit determinations
+mod density_calibration;
+pub use density_calibration::{DensityCalibration, DragScaleSample};
+
+/// Provides the estimation of the gravitational parameter and spherical harmonics of the central body from tracking data
+mod gravity_field;
+pub use gravity_field::{EstimatedGravityParameter, GravityFieldEstimator, GravityFieldSolution};
+
+/// Provides the a priori constraint of the orbit determination from the injection covariance of a launch vehicle
+mod injection;
+pub use injection::{InjectionAxes, InjectionCovariance};
+
+/// Provides the antenna pointing predicts and their uncertainty cones for the first acquisition of a spacecraft
+mod acquisition;
+pub use acquisition::{acquisition_aid, PointingSample, StationAcquisition};
+
+/// Provides the spacecraft clock model, its estimation from one-way tracking data, and the time correlation products
+mod clock;
+pub use clock::{
+    ClockEstimator, ClockNoise, ClockSolution, SpacecraftClock, TimeCorrelation,
+    TimeCorrelationRecord,
+};
+
 /// Provides all state noise compensation functionality
 pub mod snc;
 
 pub mod prelude {
     pub use super::estimate::*;
     pub use super::filter::kalman::*;
+    pub use super::filter::srif::SRIF;
     pub use super::ground_station::*;
     pub use super::msr::*;
     pub use super::process::*;
     pub use super::simulator::TrackingArcSim;
     pub use super::simulator::*;
     pub use super::snc::*;
+    pub use super::surface_asset::*;
     pub use super::*;
 
     pub use crate::time::{Duration, Epoch, TimeUnits, Unit};
@@ -73,6 +140,7 @@ pub trait Measurement: Copy + TimeTagged {
 
     /// Returns the fields for this kind of measurement.
     /// The metadata must include a `unit` field with the unit.
+    #[cfg(feature = "io")]
     fn fields() -> Vec<Field>;
 
     /// Initializes a new measurement from the provided data.
@@ -84,6 +152,24 @@ pub trait Measurement: Copy + TimeTagged {
     fn observation(&self) -> OVector<f64, Self::MeasurementSize>
     where
         DefaultAllocator: Allocator<f64, Self::MeasurementSize>;
+
+    /// Returns this measurement with the geometry of the computed measurement which is not part of its observation (e.g. the locations
+    /// of both stations of a baseline), if its sensitivity needs that geometry. By default, this returns the measurement unchanged.
+    fn with_geometry_of(&self, _computed: &Self) -> Self {
+        *self
+    }
+
+    /// Returns the observation made continuous with the computed observation, such that their difference is the residual.
+    /// Measurements of angles which wrap around (e.g. right ascension) should unwrap them about the computed angles.
+    fn observation_about(
+        &self,
+        _computed: &OVector<f64, Self::MeasurementSize>,
+    ) -> OVector<f64, Self::MeasurementSize>
+    where
+        DefaultAllocator: Allocator<f64, Self::MeasurementSize>,
+    {
+        self.observation()
+    }
 }
 
 /// The Estimate trait defines the interface that is the opposite of a `SolveFor`.
@@ -17,25 +17,42 @@
 */
 
 use std::collections::{HashMap, HashSet};
+#[cfg(feature = "io")]
 use std::error::Error;
 use std::fmt::{Debug, Display};
+#[cfg(feature = "io")]
 use std::fs::File;
 use std::ops::RangeBounds;
+#[cfg(feature = "io")]
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::cosmic::Cosm;
+#[cfg(feature = "io")]
+use crate::io::tdm::Tdm;
+#[cfg(feature = "io")]
 use crate::io::watermark::pq_writer;
-use crate::io::{ConfigError, ConfigRepr, ExportCfg};
+#[cfg(feature = "io")]
+use crate::io::ExportCfg;
+use crate::io::{ConfigError, ConfigRepr};
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, DimName};
+use crate::linalg::DefaultAllocator;
+#[cfg(feature = "io")]
+use crate::linalg::DimName;
 use crate::md::trajectory::Interpolatable;
+use crate::od::process::TimeTagBias;
 use crate::od::{Measurement, TrackingDeviceSim};
+#[cfg(feature = "io")]
+use crate::NyxError;
 use crate::State;
+#[cfg(feature = "io")]
 use arrow::array::{Array, Float64Builder, StringBuilder};
+#[cfg(feature = "io")]
 use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
 use arrow::record_batch::RecordBatch;
 use hifitime::prelude::{Duration, Epoch};
+#[cfg(feature = "io")]
 use parquet::arrow::ArrowWriter;
 
 /// Tracking arc contains the tracking data generated by the tracking devices defined in this structure.
@@ -73,6 +90,7 @@ where
     DefaultAllocator: Allocator<f64, Msr::MeasurementSize>,
 {
     /// Store this tracking arc to a parquet file.
+    #[cfg(feature = "io")]
     pub fn to_parquet_simple<P: AsRef<Path> + Debug>(
         &self,
         path: P,
@@ -80,7 +98,24 @@ where
         self.to_parquet(path, ExportCfg::default())
     }
 
+    /// Store this tracking arc to a CCSDS TDM file in its text version, where the spacecraft is the `object_name` of the metadata of the export
+    /// (or "SPACECRAFT" if unset), cf. `Tdm::from_tracking_arc`.
+    #[cfg(feature = "io")]
+    pub fn to_tdm_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, NyxError> {
+        let spacecraft = cfg
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("object_name").cloned())
+            .unwrap_or_else(|| "SPACECRAFT".to_string());
+        Tdm::from_tracking_arc(self, &spacecraft)?.to_kvn_file(path, cfg)
+    }
+
     /// Store this tracking arc to a parquet file, with optional metadata and a timestamp appended to the filename.
+    #[cfg(feature = "io")]
     pub fn to_parquet<P: AsRef<Path> + Debug>(
         &self,
         path: P,
@@ -176,7 +211,7 @@ where
             }
         }
 
-        let props = pq_writer(Some(metadata));
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
 
         let file = File::create(&path_buf)?;
 
@@ -285,4 +320,26 @@ where
             device_cfg: self.device_cfg.clone(),
         }
     }
+
+    /// Returns a new tracking arc whose measurement epochs are corrected by the provided time-tag biases, e.g. estimated by a
+    /// batch least squares. The measurements to which no bias applies are unchanged.
+    pub fn correct_time_tags(&self, biases: &[TimeTagBias]) -> Self {
+        let mut measurements = Vec::with_capacity(self.measurements.len());
+        for (name, msr) in &self.measurements {
+            let time_tag = msr.epoch();
+            let msr = match biases.iter().find(|bias| bias.applies_to(name, time_tag)) {
+                Some(bias) => {
+                    Msr::from_observation(bias.corrected_epoch(time_tag), msr.observation())
+                }
+                None => *msr,
+            };
+            measurements.push((name.clone(), msr));
+        }
+        measurements.sort_by_key(|(_, msr)| msr.epoch());
+
+        Self {
+            measurements,
+            device_cfg: self.device_cfg.clone(),
+        }
+    }
 }
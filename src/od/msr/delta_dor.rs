@@ -0,0 +1,184 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::Orbit;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{Const, DefaultAllocator, Matrix1x3, OMatrix, OVector, Vector1, Vector3, U1};
+use crate::od::{EstimateFrom, Measurement};
+use crate::{Spacecraft, TimeTagged};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field};
+use hifitime::Epoch;
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+
+/// A delta differential one-way range (Delta-DOR) measurement, in km.
+///
+/// The differential one-way range is the difference between the ranges from the spacecraft to the first and to the second station
+/// of a baseline, observed by interferometry of the downlink. The same difference is observed on a reference quasar close to the
+/// spacecraft in the sky, and subtracted from that of the spacecraft: this cancels the clock offset between both stations and
+/// most of the media delays, and leaves an observation of the angular position of the spacecraft projected on the baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeltaDor {
+    /// Epoch of the observation
+    pub epoch: Epoch,
+    /// Differential one-way range of the spacecraft minus that of the reference quasar, in km
+    pub obs: Vector1<f64>,
+    /// Partials of the observation with respect to the position of the spacecraft, unknown when built from an observation
+    h_tilde: Matrix1x3<f64>,
+}
+
+impl DeltaDor {
+    /// Builds the Delta-DOR measurement of the receiver from the stations of the baseline, all in the same frame and at the same epoch,
+    /// and from the direction of the reference quasar in the axes of that frame.
+    ///
+    /// The differential range of the quasar is that of a plane wave, and the light time is neglected.
+    pub fn new(
+        rx: Orbit,
+        tx_first: Orbit,
+        tx_second: Orbit,
+        quasar_direction: &Vector3<f64>,
+        noise_km: f64,
+    ) -> Self {
+        assert_eq!(tx_first.frame, rx.frame, "tx and rx in different frames");
+        assert_eq!(tx_second.frame, rx.frame, "tx and rx in different frames");
+        assert_eq!(
+            tx_first.epoch, rx.epoch,
+            "tx and rx states have different times"
+        );
+
+        let los_first = rx.radius() - tx_first.radius();
+        let los_second = rx.radius() - tx_second.radius();
+        let spacecraft_dor_km = los_first.norm() - los_second.norm();
+        // The wavefront of the quasar reaches the station furthest along its direction first
+        let quasar_dor_km = quasar_direction
+            .normalize()
+            .dot(&(tx_second.radius() - tx_first.radius()));
+
+        Self {
+            epoch: rx.epoch,
+            obs: Vector1::new(spacecraft_dor_km - quasar_dor_km + noise_km),
+            h_tilde: (los_first.normalize() - los_second.normalize()).transpose(),
+        }
+    }
+
+    /// Returns the quasar referenced differential one-way range in km
+    pub fn delta_dor_km(&self) -> f64 {
+        self.obs[0]
+    }
+}
+
+impl TimeTagged for DeltaDor {
+    fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    fn set_epoch(&mut self, epoch: Epoch) {
+        self.epoch = epoch
+    }
+}
+
+impl Measurement for DeltaDor {
+    type MeasurementSize = U1;
+
+    /// Returns this measurement as the quasar referenced differential one-way range
+    ///
+    /// **Units:** km
+    fn observation(&self) -> Vector1<f64> {
+        self.obs
+    }
+
+    #[cfg(feature = "io")]
+    fn fields() -> Vec<Field> {
+        let mut meta = HashMap::new();
+        meta.insert("unit".to_string(), "km".to_string());
+        vec![Field::new("Delta-DOR (km)", DataType::Float64, false).with_metadata(meta)]
+    }
+
+    fn from_observation(epoch: Epoch, obs: OVector<f64, Self::MeasurementSize>) -> Self {
+        Self {
+            epoch,
+            obs,
+            h_tilde: Matrix1x3::zeros(),
+        }
+    }
+
+    /// The partials of a Delta-DOR depend on the locations of both stations, which only the computed measurement knows
+    fn with_geometry_of(&self, computed: &Self) -> Self {
+        Self {
+            h_tilde: computed.h_tilde,
+            ..*self
+        }
+    }
+}
+
+impl EstimateFrom<Orbit, DeltaDor> for Orbit {
+    fn extract(from: Orbit) -> Self {
+        from
+    }
+
+    /// The partials only depend on the geometry of the baseline, cf. `Measurement::with_geometry_of`.
+    fn sensitivity(
+        msr: &DeltaDor,
+        _receiver: Self,
+        _transmitter: Orbit,
+    ) -> OMatrix<f64, <DeltaDor as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <DeltaDor as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let mut h_tilde = OMatrix::<f64, U1, Self::Size>::zeros();
+        h_tilde.fixed_view_mut::<1, 3>(0, 0).copy_from(&msr.h_tilde);
+        h_tilde
+    }
+}
+
+impl EstimateFrom<Spacecraft, DeltaDor> for Orbit {
+    fn extract(from: Spacecraft) -> Self {
+        from.orbit
+    }
+
+    fn sensitivity(
+        msr: &DeltaDor,
+        receiver: Self,
+        transmitter: Orbit,
+    ) -> OMatrix<f64, <DeltaDor as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <DeltaDor as Measurement>::MeasurementSize, Self::Size>,
+    {
+        <Orbit as EstimateFrom<Orbit, DeltaDor>>::sensitivity(msr, receiver, transmitter)
+    }
+}
+
+impl EstimateFrom<Spacecraft, DeltaDor> for Spacecraft {
+    fn extract(from: Spacecraft) -> Self {
+        from
+    }
+
+    fn sensitivity(
+        msr: &DeltaDor,
+        _receiver: Self,
+        _transmitter: Orbit,
+    ) -> OMatrix<f64, <DeltaDor as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <DeltaDor as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let mut h_tilde = OMatrix::<f64, U1, Const<9>>::zeros();
+        h_tilde.fixed_view_mut::<1, 3>(0, 0).copy_from(&msr.h_tilde);
+        h_tilde
+    }
+}
@@ -17,11 +17,17 @@
 */
 
 mod arc;
+mod delta_dor;
+mod opnav;
+mod position;
 mod range;
 mod range_doppler;
 mod rangerate;
 
 pub use arc::TrackingArc;
+pub use delta_dor::DeltaDor;
+pub use opnav::{OpNavCentroid, OpNavLimb};
+pub use position::PositionMsr;
 pub use range::RangeMsr;
 pub use range_doppler::RangeDoppler;
 pub use rangerate::RangeRate;
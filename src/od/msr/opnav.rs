@@ -0,0 +1,342 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::Orbit;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{Const, DefaultAllocator, OMatrix, OVector, Vector2, Vector3, U2, U3};
+use crate::od::{EstimateFrom, Measurement};
+use crate::{Spacecraft, TimeTagged};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field};
+use hifitime::Epoch;
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+
+/// An optical navigation observation of the centroid of a target body, as its right ascension and declination in degrees in
+/// the frame of the estimated state.
+///
+/// The centroid is star-relative: its pixel and line in the image are converted to an inertial direction with the attitude of the
+/// camera determined from the stars in the same image, so the observation does not depend on the attitude, cf. `OpNavCamera`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpNavCentroid {
+    /// Epoch of the observation
+    pub epoch: Epoch,
+    /// Right ascension and declination in degrees
+    pub obs: Vector2<f64>,
+}
+
+impl OpNavCentroid {
+    /// Builds the observation of the line of sight from the spacecraft to the target, in any inertial frame
+    pub fn new(epoch: Epoch, line_of_sight: &Vector3<f64>) -> Self {
+        Self {
+            epoch,
+            obs: right_ascension_declination_deg(line_of_sight),
+        }
+    }
+}
+
+/// An optical navigation observation of the limb of a target body: the right ascension and declination of the center of the
+/// limb fit, and its apparent angular radius, all in degrees. The apparent radius observes the distance to the target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpNavLimb {
+    /// Epoch of the observation
+    pub epoch: Epoch,
+    /// Right ascension, declination and apparent radius in degrees
+    pub obs: Vector3<f64>,
+}
+
+impl OpNavLimb {
+    /// Builds the observation of the target of the provided radius along the line of sight from the spacecraft, in any inertial frame
+    pub fn new(epoch: Epoch, line_of_sight: &Vector3<f64>, target_radius_km: f64) -> Self {
+        let center = right_ascension_declination_deg(line_of_sight);
+        Self {
+            epoch,
+            obs: Vector3::new(
+                center[0],
+                center[1],
+                (target_radius_km / line_of_sight.norm())
+                    .min(1.0)
+                    .asin()
+                    .to_degrees(),
+            ),
+        }
+    }
+}
+
+/// Returns the right ascension in ]-180, 180] degrees and the declination in degrees of the provided direction
+pub(crate) fn right_ascension_declination_deg(direction: &Vector3<f64>) -> Vector2<f64> {
+    Vector2::new(
+        direction[1].atan2(direction[0]).to_degrees(),
+        (direction[2] / direction.norm()).asin().to_degrees(),
+    )
+}
+
+/// Returns the provided right ascension shifted by a full turn such that it is within 180 degrees of the computed right ascension
+fn unwrap_right_ascension_deg(right_ascension_deg: f64, computed_deg: f64) -> f64 {
+    computed_deg + (right_ascension_deg - computed_deg + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// Returns the partials of the right ascension and declination (in degrees) with respect to the position of the observer, from the
+/// line of sight from the observer to the target
+fn centroid_partials(line_of_sight: &Vector3<f64>) -> OMatrix<f64, U2, U3> {
+    let (x, y, z) = (line_of_sight[0], line_of_sight[1], line_of_sight[2]);
+    let xy2 = x.powi(2) + y.powi(2);
+    let xy = xy2.sqrt();
+    let rho2 = xy2 + z.powi(2);
+    // The line of sight is the target minus the observer, hence the opposite partials
+    OMatrix::<f64, U2, U3>::new(
+        y / xy2,
+        -x / xy2,
+        0.0,
+        x * z / (rho2 * xy),
+        y * z / (rho2 * xy),
+        -xy / rho2,
+    ) * 1.0_f64.to_degrees()
+}
+
+impl TimeTagged for OpNavCentroid {
+    fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    fn set_epoch(&mut self, epoch: Epoch) {
+        self.epoch = epoch
+    }
+}
+
+impl TimeTagged for OpNavLimb {
+    fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    fn set_epoch(&mut self, epoch: Epoch) {
+        self.epoch = epoch
+    }
+}
+
+#[cfg(feature = "io")]
+fn angle_field(name: &str) -> Field {
+    let mut meta = HashMap::new();
+    meta.insert("unit".to_string(), "deg".to_string());
+    Field::new(name, DataType::Float64, false).with_metadata(meta)
+}
+
+impl Measurement for OpNavCentroid {
+    type MeasurementSize = U2;
+
+    /// Returns this measurement as the right ascension and declination of the centroid
+    ///
+    /// **Units:** deg
+    fn observation(&self) -> Vector2<f64> {
+        self.obs
+    }
+
+    fn observation_about(&self, computed: &Vector2<f64>) -> Vector2<f64> {
+        let mut obs = self.obs;
+        obs[0] = unwrap_right_ascension_deg(obs[0], computed[0]);
+        obs
+    }
+
+    #[cfg(feature = "io")]
+    fn fields() -> Vec<Field> {
+        vec![
+            angle_field("Right ascension (deg)"),
+            angle_field("Declination (deg)"),
+        ]
+    }
+
+    fn from_observation(epoch: Epoch, obs: OVector<f64, Self::MeasurementSize>) -> Self {
+        Self { epoch, obs }
+    }
+}
+
+impl Measurement for OpNavLimb {
+    type MeasurementSize = U3;
+
+    /// Returns this measurement as the right ascension and declination of the center of the limb, and its apparent radius
+    ///
+    /// **Units:** deg
+    fn observation(&self) -> Vector3<f64> {
+        self.obs
+    }
+
+    fn observation_about(&self, computed: &Vector3<f64>) -> Vector3<f64> {
+        let mut obs = self.obs;
+        obs[0] = unwrap_right_ascension_deg(obs[0], computed[0]);
+        obs
+    }
+
+    #[cfg(feature = "io")]
+    fn fields() -> Vec<Field> {
+        vec![
+            angle_field("Right ascension (deg)"),
+            angle_field("Declination (deg)"),
+            angle_field("Apparent radius (deg)"),
+        ]
+    }
+
+    fn from_observation(epoch: Epoch, obs: OVector<f64, Self::MeasurementSize>) -> Self {
+        Self { epoch, obs }
+    }
+}
+
+impl EstimateFrom<Orbit, OpNavCentroid> for Orbit {
+    fn extract(from: Orbit) -> Self {
+        from
+    }
+
+    /// The transmitter is the target body, cf. `OpNavCamera::location`
+    fn sensitivity(
+        _msr: &OpNavCentroid,
+        receiver: Self,
+        transmitter: Orbit,
+    ) -> OMatrix<f64, <OpNavCentroid as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator:
+            Allocator<f64, <OpNavCentroid as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let mut h_tilde = OMatrix::<f64, U2, Self::Size>::zeros();
+        h_tilde
+            .fixed_view_mut::<2, 3>(0, 0)
+            .copy_from(&centroid_partials(
+                &(transmitter.radius() - receiver.radius()),
+            ));
+        h_tilde
+    }
+}
+
+impl EstimateFrom<Spacecraft, OpNavCentroid> for Orbit {
+    fn extract(from: Spacecraft) -> Self {
+        from.orbit
+    }
+
+    fn sensitivity(
+        msr: &OpNavCentroid,
+        receiver: Self,
+        transmitter: Orbit,
+    ) -> OMatrix<f64, <OpNavCentroid as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator:
+            Allocator<f64, <OpNavCentroid as Measurement>::MeasurementSize, Self::Size>,
+    {
+        <Orbit as EstimateFrom<Orbit, OpNavCentroid>>::sensitivity(msr, receiver, transmitter)
+    }
+}
+
+impl EstimateFrom<Spacecraft, OpNavCentroid> for Spacecraft {
+    fn extract(from: Spacecraft) -> Self {
+        from
+    }
+
+    fn sensitivity(
+        msr: &OpNavCentroid,
+        receiver: Self,
+        transmitter: Orbit,
+    ) -> OMatrix<f64, <OpNavCentroid as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator:
+            Allocator<f64, <OpNavCentroid as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let orbit_h_tilde = <Orbit as EstimateFrom<Orbit, OpNavCentroid>>::sensitivity(
+            msr,
+            receiver.orbit,
+            transmitter,
+        );
+        let mut h_tilde = OMatrix::<f64, U2, Const<9>>::zeros();
+        h_tilde
+            .fixed_view_mut::<2, 6>(0, 0)
+            .copy_from(&orbit_h_tilde);
+        h_tilde
+    }
+}
+
+impl EstimateFrom<Orbit, OpNavLimb> for Orbit {
+    fn extract(from: Orbit) -> Self {
+        from
+    }
+
+    /// The transmitter is the target body, cf. `OpNavCamera::location`.
+    ///
+    /// The partials of the apparent radius are evaluated with the observed apparent radius, since the radius of the target is not
+    /// part of the transmitter state.
+    fn sensitivity(
+        msr: &OpNavLimb,
+        receiver: Self,
+        transmitter: Orbit,
+    ) -> OMatrix<f64, <OpNavLimb as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <OpNavLimb as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let line_of_sight = transmitter.radius() - receiver.radius();
+        let rho_km = line_of_sight.norm();
+        let mut h_tilde = OMatrix::<f64, U3, Self::Size>::zeros();
+        h_tilde
+            .fixed_view_mut::<2, 3>(0, 0)
+            .copy_from(&centroid_partials(&line_of_sight));
+        // The apparent radius is asin(R / ρ), whose derivative with respect to ρ is -tan(radius) / ρ
+        let radius_partials = line_of_sight.transpose()
+            * (msr.obs[2].to_radians().tan() / rho_km.powi(2)).to_degrees();
+        h_tilde
+            .fixed_view_mut::<1, 3>(2, 0)
+            .copy_from(&radius_partials);
+        h_tilde
+    }
+}
+
+impl EstimateFrom<Spacecraft, OpNavLimb> for Orbit {
+    fn extract(from: Spacecraft) -> Self {
+        from.orbit
+    }
+
+    fn sensitivity(
+        msr: &OpNavLimb,
+        receiver: Self,
+        transmitter: Orbit,
+    ) -> OMatrix<f64, <OpNavLimb as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <OpNavLimb as Measurement>::MeasurementSize, Self::Size>,
+    {
+        <Orbit as EstimateFrom<Orbit, OpNavLimb>>::sensitivity(msr, receiver, transmitter)
+    }
+}
+
+impl EstimateFrom<Spacecraft, OpNavLimb> for Spacecraft {
+    fn extract(from: Spacecraft) -> Self {
+        from
+    }
+
+    fn sensitivity(
+        msr: &OpNavLimb,
+        receiver: Self,
+        transmitter: Orbit,
+    ) -> OMatrix<f64, <OpNavLimb as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <OpNavLimb as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let orbit_h_tilde = <Orbit as EstimateFrom<Orbit, OpNavLimb>>::sensitivity(
+            msr,
+            receiver.orbit,
+            transmitter,
+        );
+        let mut h_tilde = OMatrix::<f64, U3, Const<9>>::zeros();
+        h_tilde
+            .fixed_view_mut::<3, 6>(0, 0)
+            .copy_from(&orbit_h_tilde);
+        h_tilde
+    }
+}
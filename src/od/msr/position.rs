@@ -0,0 +1,144 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::Orbit;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, Matrix3, OMatrix, OVector, Vector3, U3};
+use crate::od::{EstimateFrom, Measurement};
+use crate::{Spacecraft, TimeTagged};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field};
+use hifitime::Epoch;
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+
+/// A pseudo-measurement of the position of the spacecraft in km, in the frame of the estimated state.
+///
+/// It allows ingesting position fixes or the points of an external ephemeris (e.g. from another provider) in the orbit
+/// determination filters, cf. `PositionFixSource`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionMsr {
+    /// Epoch of the observation
+    pub epoch: Epoch,
+    /// Position in km
+    pub obs: Vector3<f64>,
+}
+
+impl PositionMsr {
+    pub fn new(orbit: &Orbit) -> Self {
+        Self {
+            epoch: orbit.epoch,
+            obs: orbit.radius(),
+        }
+    }
+}
+
+impl TimeTagged for PositionMsr {
+    fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    fn set_epoch(&mut self, epoch: Epoch) {
+        self.epoch = epoch
+    }
+}
+
+impl Measurement for PositionMsr {
+    type MeasurementSize = U3;
+
+    /// Returns this measurement as a position vector
+    ///
+    /// **Units:** km
+    fn observation(&self) -> Vector3<f64> {
+        self.obs
+    }
+
+    #[cfg(feature = "io")]
+    fn fields() -> Vec<Field> {
+        let mut meta = HashMap::new();
+        meta.insert("unit".to_string(), "km".to_string());
+
+        ["X (km)", "Y (km)", "Z (km)"]
+            .iter()
+            .map(|name| Field::new(*name, DataType::Float64, false).with_metadata(meta.clone()))
+            .collect()
+    }
+
+    fn from_observation(epoch: Epoch, obs: OVector<f64, Self::MeasurementSize>) -> Self {
+        Self { epoch, obs }
+    }
+}
+
+impl EstimateFrom<Orbit, PositionMsr> for Orbit {
+    fn extract(from: Orbit) -> Self {
+        from
+    }
+
+    fn sensitivity(
+        _msr: &PositionMsr,
+        _receiver: Self,
+        _transmitter: Orbit,
+    ) -> OMatrix<f64, <PositionMsr as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <PositionMsr as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let mut h_tilde = OMatrix::<f64, U3, Self::Size>::zeros();
+        h_tilde
+            .fixed_view_mut::<3, 3>(0, 0)
+            .copy_from(&Matrix3::identity());
+        h_tilde
+    }
+}
+
+impl EstimateFrom<Spacecraft, PositionMsr> for Orbit {
+    fn extract(from: Spacecraft) -> Self {
+        from.orbit
+    }
+
+    fn sensitivity(
+        msr: &PositionMsr,
+        receiver: Self,
+        transmitter: Orbit,
+    ) -> OMatrix<f64, <PositionMsr as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <PositionMsr as Measurement>::MeasurementSize, Self::Size>,
+    {
+        <Orbit as EstimateFrom<Orbit, PositionMsr>>::sensitivity(msr, receiver, transmitter)
+    }
+}
+
+impl EstimateFrom<Spacecraft, PositionMsr> for Spacecraft {
+    fn extract(from: Spacecraft) -> Self {
+        from
+    }
+
+    fn sensitivity(
+        _msr: &PositionMsr,
+        _receiver: Self,
+        _transmitter: Orbit,
+    ) -> OMatrix<f64, <PositionMsr as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <PositionMsr as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let mut h_tilde = OMatrix::<f64, U3, Self::Size>::zeros();
+        h_tilde
+            .fixed_view_mut::<3, 3>(0, 0)
+            .copy_from(&Matrix3::identity());
+        h_tilde
+    }
+}
@@ -18,13 +18,16 @@
 
 use crate::cosmic::Orbit;
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, OMatrix, OVector, Vector2, U2};
+use crate::linalg::{Const, DefaultAllocator, OMatrix, OVector, Vector2, U2};
+#[cfg(feature = "io")]
 use crate::od::msr::RangeMsr;
 use crate::od::{EstimateFrom, Measurement};
 use crate::{Spacecraft, TimeTagged};
+#[cfg(feature = "io")]
 use arrow::datatypes::{DataType, Field};
 use hifitime::{Epoch, Unit};
 use nalgebra::Matrix2x6;
+#[cfg(feature = "io")]
 use std::collections::HashMap;
 
 /// A simultaneous range and Doppler measurement in units of km and km/s, available both in one way and two way measurement.
@@ -133,6 +136,78 @@ impl RangeDoppler {
 
         Self { epoch, obs }
     }
+
+    /// Initialize a new range and Doppler measurement from the light-time corrected legs of the signal and the effective noises.
+    /// Each leg is the pair of the states of its transmitter at transmission and of its receiver at reception, e.g. only the
+    /// downlink for a one-way measurement, or the uplink and the downlink for a two-way measurement.
+    ///
+    /// The range is the mean length of the legs (i.e. half of the round trip of a two-way measurement), and the Doppler is the
+    /// mean of their range rates. The measurement is time tagged at the reception of the last leg.
+    ///
+    /// # Panics
+    /// + If there are no legs.
+    pub fn from_legs(
+        legs: &[(Orbit, Orbit)],
+        timestamp_noise_s: f64,
+        range_noise_km: f64,
+        doppler_noise_km_s: f64,
+    ) -> Self {
+        let (_, reception) = legs.last().expect("no legs in the light-time solution");
+        let doppler_km_s = legs
+            .iter()
+            .map(|(tx, rx)| {
+                let range_vec_km = rx.radius() - tx.radius();
+                range_vec_km.dot(&(rx.velocity() - tx.velocity())) / range_vec_km.norm()
+            })
+            .sum::<f64>()
+            / legs.len() as f64;
+
+        Self {
+            epoch: reception.epoch + timestamp_noise_s * Unit::Second,
+            obs: Vector2::new(
+                Self::legs_range_km(legs) + range_noise_km,
+                doppler_km_s + doppler_noise_km_s,
+            ),
+        }
+    }
+
+    /// Initialize a new range and integrated Doppler measurement from the light-time corrected legs of the signal received at
+    /// the start and at the end of the integration time, and the effective noises.
+    ///
+    /// As in `from_legs`, the range is the mean length of the legs at the end of the integration time. The Doppler is the
+    /// change of this range over the integration time, like the Doppler counted by the tracking stations.
+    ///
+    /// # Panics
+    /// + If there are no legs.
+    /// + If both receptions are simultaneous.
+    pub fn integrated(
+        legs_start: &[(Orbit, Orbit)],
+        legs_end: &[(Orbit, Orbit)],
+        timestamp_noise_s: f64,
+        range_noise_km: f64,
+        doppler_noise_km_s: f64,
+    ) -> Self {
+        let (_, reception_start) = legs_start.last().expect("no legs at the start");
+        let (_, reception_end) = legs_end.last().expect("no legs at the end");
+        let count_time_s = (reception_end.epoch - reception_start.epoch).to_seconds();
+        assert!(count_time_s > 0.0, "integration time must be positive");
+
+        let range_km = Self::legs_range_km(legs_end);
+        let doppler_km_s = (range_km - Self::legs_range_km(legs_start)) / count_time_s;
+
+        Self {
+            epoch: reception_end.epoch + timestamp_noise_s * Unit::Second,
+            obs: Vector2::new(range_km + range_noise_km, doppler_km_s + doppler_noise_km_s),
+        }
+    }
+
+    /// Mean length of the legs of a signal, in km
+    fn legs_range_km(legs: &[(Orbit, Orbit)]) -> f64 {
+        legs.iter()
+            .map(|(tx, rx)| (rx.radius() - tx.radius()).norm())
+            .sum::<f64>()
+            / legs.len() as f64
+    }
 }
 
 impl TimeTagged for RangeDoppler {
@@ -155,6 +230,7 @@ impl Measurement for RangeDoppler {
         self.obs
     }
 
+    #[cfg(feature = "io")]
     fn fields() -> Vec<Field> {
         let mut meta = HashMap::new();
         meta.insert("unit".to_string(), "km/s".to_string());
@@ -194,7 +270,7 @@ impl EstimateFrom<Orbit, RangeDoppler> for Orbit {
     }
 
     fn sensitivity(
-        msr: &RangeDoppler,
+        _msr: &RangeDoppler,
         receiver: Self,
         transmitter: Self,
     ) -> OMatrix<f64, <RangeDoppler as Measurement>::MeasurementSize, Self::Size>
@@ -202,10 +278,12 @@ impl EstimateFrom<Orbit, RangeDoppler> for Orbit {
         DefaultAllocator:
             Allocator<f64, <RangeDoppler as Measurement>::MeasurementSize, Self::Size>,
     {
+        // The partials are those of the reference geometry: the observed range and range rate include the deviation of the
+        // reference from the true state, which is large until the filter has converged.
         let delta_r = receiver.radius() - transmitter.radius();
         let delta_v = receiver.velocity() - transmitter.velocity();
-        let ρ = msr.observation()[0];
-        let ρ_dot = msr.observation()[1];
+        let ρ = delta_r.norm();
+        let ρ_dot = delta_r.dot(&delta_v) / ρ;
         let m11 = delta_r.x / ρ;
         let m12 = delta_r.y / ρ;
         let m13 = delta_r.z / ρ;
@@ -223,14 +301,24 @@ impl EstimateFrom<Spacecraft, RangeDoppler> for Spacecraft {
     }
 
     fn sensitivity(
-        _msr: &RangeDoppler,
-        _receiver: Self,
-        _transmitter: Orbit,
+        msr: &RangeDoppler,
+        receiver: Self,
+        transmitter: Orbit,
     ) -> OMatrix<f64, <RangeDoppler as Measurement>::MeasurementSize, Self::Size>
     where
         DefaultAllocator:
             Allocator<f64, <RangeDoppler as Measurement>::MeasurementSize, Self::Size>,
     {
-        todo!("cannot yet estimate a full spacecraft state")
+        // The measurement only depends on the orbit: the Cr, Cd and fuel mass are observed through the STM
+        let orbit_h_tilde = <Orbit as EstimateFrom<Orbit, RangeDoppler>>::sensitivity(
+            msr,
+            receiver.orbit,
+            transmitter,
+        );
+        let mut h_tilde = OMatrix::<f64, U2, Const<9>>::zeros();
+        h_tilde
+            .fixed_view_mut::<2, 6>(0, 0)
+            .copy_from(&orbit_h_tilde);
+        h_tilde
     }
 }
@@ -21,11 +21,13 @@ use crate::linalg::{DimName, Matrix1x6, OVector, Vector1, U1, U6, U7};
 use crate::od::Measurement;
 use crate::time::Epoch;
 use crate::TimeTagged;
+#[cfg(feature = "io")]
 use arrow::datatypes::{DataType, Field};
 use hyperdual::linalg::norm;
 use hyperdual::{hyperspace_from_vector, OHyperdual};
 use serde::ser::SerializeSeq;
 use serde::{Serialize, Serializer};
+#[cfg(feature = "io")]
 use std::collections::HashMap;
 
 /// Stores a standard measurement of range (km)
@@ -90,6 +92,7 @@ impl Measurement for RangeRate {
         self.obs
     }
 
+    #[cfg(feature = "io")]
     fn fields() -> Vec<Field> {
         let mut meta = HashMap::new();
         meta.insert("unit".to_string(), "km/s".to_string());
@@ -17,16 +17,23 @@
 */
 
 use crate::cosmic::SPEED_OF_LIGHT_KMS;
+#[cfg(feature = "io")]
 use crate::io::watermark::pq_writer;
 use crate::io::{duration_from_str, duration_to_str, ConfigError, ConfigRepr, Configurable};
 use crate::md::prelude::Cosm;
 #[cfg(feature = "python")]
 use crate::python::pyo3utils::pyany_to_value;
 use crate::NyxError;
+#[cfg(feature = "io")]
 use arrow::array::{ArrayRef, Float64Array, UInt32Array};
+#[cfg(feature = "io")]
 use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
 use arrow::record_batch::RecordBatch;
-use hifitime::{Duration, Epoch, TimeSeries, TimeUnits};
+#[cfg(feature = "io")]
+use hifitime::TimeSeries;
+use hifitime::{Duration, Epoch, TimeUnits};
+#[cfg(feature = "io")]
 use parquet::arrow::ArrowWriter;
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
@@ -34,12 +41,17 @@ use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList, PyType};
 #[cfg(feature = "python")]
 use pythonize::{depythonize, pythonize};
-use rand::{Rng, SeedableRng};
+use rand::Rng;
+#[cfg(feature = "io")]
+use rand::SeedableRng;
 use rand_distr::Normal;
+#[cfg(any(feature = "io", test))]
 use rand_pcg::Pcg64Mcg;
 use serde_derive::{Deserialize, Serialize};
+#[cfg(feature = "io")]
 use std::collections::HashMap;
 use std::fmt;
+#[cfg(feature = "io")]
 use std::fs::File;
 use std::ops::Mul;
 use std::sync::Arc;
@@ -262,6 +274,7 @@ impl GaussMarkov {
     /// The unit is only used in the headers of the parquet file.
     ///
     /// This will simulate the model with "runs" different seeds, sampling the process 500 times for a duration of 5 times the time constant.
+    #[cfg(feature = "io")]
     pub fn simulate(
         &self,
         path: String,
@@ -324,7 +337,7 @@ impl GaussMarkov {
         let mut metadata = HashMap::new();
         metadata.insert("Purpose".to_string(), "Gauss Markov simulation".to_string());
 
-        let props = pq_writer(Some(metadata));
+        let props = pq_writer(Some(metadata), None);
 
         let file = File::create(path).map_err(|e| NyxError::CustomError(e.to_string()))?;
         let mut writer = ArrowWriter::try_new(file, schema.clone(), props).unwrap();
@@ -0,0 +1,496 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::msr::{OpNavCentroid, OpNavLimb};
+use super::TrackingDeviceSim;
+use crate::cosmic::{Cosm, Frame, LightTimeCalc, Orbit};
+use crate::io::{
+    epoch_from_str, epoch_to_str, frame_from_str, frame_to_str, ConfigRepr, Configurable,
+};
+use crate::linalg::{Matrix3, Vector2, Vector3};
+use crate::md::prelude::Traj;
+use crate::time::Epoch;
+use crate::{NyxError, Spacecraft};
+use na::{Quaternion, UnitQuaternion};
+use rand_distr::{Distribution, StandardNormal};
+use rand_pcg::Pcg64Mcg;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// A pinhole camera model, whose boresight is the +Z axis of the camera frame, and whose pixel (resp. line) coordinate increases
+/// along its +X (resp. +Y) axis. The principal point is at the center of the image.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PinholeCamera {
+    /// Focal length, in pixels
+    pub focal_length_px: f64,
+    /// Width of the image, in pixels
+    pub width_px: f64,
+    /// Height of the image, in lines
+    pub height_px: f64,
+}
+
+impl PinholeCamera {
+    pub fn new(focal_length_px: f64, width_px: f64, height_px: f64) -> Self {
+        Self {
+            focal_length_px,
+            width_px,
+            height_px,
+        }
+    }
+
+    /// Initializes a camera from its horizontal field of view, in degrees
+    pub fn from_fov(fov_deg: f64, width_px: f64, height_px: f64) -> Self {
+        Self::new(
+            0.5 * width_px / (0.5 * fov_deg).to_radians().tan(),
+            width_px,
+            height_px,
+        )
+    }
+
+    /// Returns the angle subtended by one pixel at the center of the image, in degrees
+    pub fn ifov_deg(&self) -> f64 {
+        (1.0 / self.focal_length_px).atan().to_degrees()
+    }
+
+    /// Returns the pixel and line of the provided direction in the camera frame, or None if it is outside of the image
+    pub fn project(&self, direction: &Vector3<f64>) -> Option<Vector2<f64>> {
+        if direction[2] <= 0.0 {
+            return None;
+        }
+        let pixel_line = Vector2::new(
+            0.5 * self.width_px + self.focal_length_px * direction[0] / direction[2],
+            0.5 * self.height_px + self.focal_length_px * direction[1] / direction[2],
+        );
+        if (0.0..=self.width_px).contains(&pixel_line[0])
+            && (0.0..=self.height_px).contains(&pixel_line[1])
+        {
+            Some(pixel_line)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the unit direction in the camera frame of the provided pixel and line
+    pub fn direction(&self, pixel_line: &Vector2<f64>) -> Vector3<f64> {
+        Vector3::new(
+            pixel_line[0] - 0.5 * self.width_px,
+            pixel_line[1] - 0.5 * self.height_px,
+            self.focal_length_px,
+        )
+        .normalize()
+    }
+}
+
+/// A sample of the attitude history of a camera
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CameraAttitudeSample {
+    #[serde(serialize_with = "epoch_to_str", deserialize_with = "epoch_from_str")]
+    pub epoch: Epoch,
+    /// Rotation from the camera frame to the inertial frame, as a quaternion [w, x, y, z]
+    pub quaternion: [f64; 4],
+}
+
+/// Attitude of an optical navigation camera, i.e. the rotation from the camera frame to the inertial frame of the trajectory
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum CameraAttitude {
+    /// The boresight is pointed at the center of the target, with the +Y axis of the camera as close as possible to the inertial +Z axis
+    TargetPointing,
+    /// Fixed rotation from the camera frame to the inertial frame, as a quaternion [w, x, y, z]
+    Inertial([f64; 4]),
+    /// Attitude history (e.g. from the telemetry), spherically interpolated between its chronological samples
+    History(Vec<CameraAttitudeSample>),
+}
+
+impl CameraAttitude {
+    /// Initializes a fixed attitude from the rotation from the camera frame to the inertial frame
+    pub fn inertial(camera_to_inertial: UnitQuaternion<f64>) -> Self {
+        Self::Inertial(to_wxyz(&camera_to_inertial))
+    }
+
+    /// Returns the rotation from the camera frame to the inertial frame at the provided epoch, where the target is along the
+    /// provided line of sight. Returns an error if the epoch is outside of the attitude history.
+    pub fn camera_to_inertial(
+        &self,
+        epoch: Epoch,
+        line_of_sight: &Vector3<f64>,
+    ) -> Result<Matrix3<f64>, NyxError> {
+        match self {
+            Self::TargetPointing => {
+                let z = line_of_sight.normalize();
+                let up = if z[2].abs() < 0.99 {
+                    Vector3::z()
+                } else {
+                    Vector3::x()
+                };
+                let y = (up - z * up.dot(&z)).normalize();
+                Ok(Matrix3::from_columns(&[y.cross(&z), y, z]))
+            }
+            Self::Inertial(quaternion) => Ok(from_wxyz(quaternion).to_rotation_matrix().into()),
+            Self::History(samples) => {
+                let after = samples.partition_point(|sample| sample.epoch <= epoch);
+                if after == 0 || (after == samples.len() && samples[after - 1].epoch != epoch) {
+                    return Err(NyxError::CustomError(format!(
+                        "no camera attitude at {epoch}"
+                    )));
+                }
+                let prev = &samples[after - 1];
+                if prev.epoch == epoch {
+                    return Ok(from_wxyz(&prev.quaternion).to_rotation_matrix().into());
+                }
+                let next = &samples[after];
+                let ratio =
+                    (epoch - prev.epoch).to_seconds() / (next.epoch - prev.epoch).to_seconds();
+                Ok(from_wxyz(&prev.quaternion)
+                    .slerp(&from_wxyz(&next.quaternion), ratio)
+                    .to_rotation_matrix()
+                    .into())
+            }
+        }
+    }
+}
+
+fn from_wxyz(quaternion: &[f64; 4]) -> UnitQuaternion<f64> {
+    UnitQuaternion::from_quaternion(Quaternion::new(
+        quaternion[0],
+        quaternion[1],
+        quaternion[2],
+        quaternion[3],
+    ))
+}
+
+fn to_wxyz(quaternion: &UnitQuaternion<f64>) -> [f64; 4] {
+    [quaternion.w, quaternion.i, quaternion.j, quaternion.k]
+}
+
+/// An optical navigation camera imaging a target body (e.g. a small body or the Moon) from the spacecraft, for approach navigation.
+///
+/// The center of the target is projected in the image with a pinhole camera model and the attitude of the camera, the noise is
+/// added in pixels, and the noisy centroid is converted back to a star-relative (i.e. inertial) direction with the same attitude.
+/// The camera produces `OpNavCentroid` observations of the center of the target, and `OpNavLimb` observations which also include
+/// its apparent radius once the target is resolved enough for its limb to be fit.
+///
+/// The target is observed without light time correction, and its centroid is assumed to be its center of figure, i.e. the phase
+/// of the illumination is not modeled.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct OpNavCamera {
+    pub name: String,
+    /// Frame of the target body, whose equatorial radius is its radius
+    #[serde(serialize_with = "frame_to_str", deserialize_with = "frame_from_str")]
+    pub target: Frame,
+    pub camera: PinholeCamera,
+    pub attitude: CameraAttitude,
+    /// Standard deviation of the white noise on the pixel and line of the centroid, in pixels
+    pub centroid_sigma_px: f64,
+    /// Standard deviation of the white noise on the apparent radius of the limb, in pixels
+    pub limb_sigma_px: f64,
+    /// Minimum apparent radius of the target for its limb to be fit, in pixels
+    pub min_limb_radius_px: f64,
+}
+
+impl OpNavCamera {
+    /// Initializes a camera pointed at the target, with the limb fit from an apparent radius of ten pixels
+    pub fn new(
+        name: String,
+        target: Frame,
+        camera: PinholeCamera,
+        centroid_sigma_px: f64,
+        limb_sigma_px: f64,
+    ) -> Self {
+        Self {
+            name,
+            target,
+            camera,
+            attitude: CameraAttitude::TargetPointing,
+            centroid_sigma_px,
+            limb_sigma_px,
+            min_limb_radius_px: 10.0,
+        }
+    }
+
+    /// Sets the attitude of the camera
+    pub fn with_attitude(mut self, attitude: CameraAttitude) -> Self {
+        self.attitude = attitude;
+        self
+    }
+
+    /// Standard deviation of the centroid noise, in degrees at the center of the image. The right ascension noise is larger by a
+    /// factor of 1 / cos(declination).
+    pub fn centroid_sigma_deg(&self) -> f64 {
+        self.centroid_sigma_px * self.camera.ifov_deg()
+    }
+
+    /// Standard deviation of the apparent radius noise, in degrees at the center of the image
+    pub fn limb_sigma_deg(&self) -> f64 {
+        self.limb_sigma_px * self.camera.ifov_deg()
+    }
+
+    /// Returns the state of the target in the provided frame
+    pub fn target_state(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
+        cosm.celestial_state(&self.target.ephem_path(), epoch, frame, LightTimeCalc::None)
+    }
+
+    /// Returns the pixel and line of the center of the target and its apparent radius in pixels in the image taken from the provided
+    /// orbit, along with the rotation from the camera frame to the frame of the orbit, or None if the target is outside of the image.
+    pub fn image(
+        &self,
+        rx: &Orbit,
+        cosm: &Cosm,
+    ) -> Result<Option<(Vector2<f64>, f64, Matrix3<f64>)>, NyxError> {
+        let line_of_sight = self.target_state(rx.epoch, rx.frame, cosm).radius() - rx.radius();
+        let rho_km = line_of_sight.norm();
+        let radius_km = self.target.equatorial_radius();
+        if rho_km <= radius_km {
+            return Ok(None);
+        }
+        let camera_to_inertial = self.attitude.camera_to_inertial(rx.epoch, &line_of_sight)?;
+        match self
+            .camera
+            .project(&(camera_to_inertial.transpose() * line_of_sight))
+        {
+            Some(pixel_line) => {
+                let radius_px = self.camera.focal_length_px * (radius_km / rho_km).asin().tan();
+                Ok(Some((pixel_line, radius_px, camera_to_inertial)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the noisy star-relative line of sight of the centroid and the noisy apparent radius in pixels
+    fn observe(
+        &self,
+        rx: &Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: &Cosm,
+    ) -> Result<Option<(Vector3<f64>, f64)>, NyxError> {
+        let (mut pixel_line, mut radius_px, camera_to_inertial) = match self.image(rx, cosm)? {
+            Some(image) => image,
+            None => {
+                debug!("{} does not image its target at {}", self.name, rx.epoch);
+                return Ok(None);
+            }
+        };
+        if let Some(rng) = rng {
+            let normal = Vector2::from_fn(|_, _| StandardNormal.sample(rng));
+            pixel_line += normal * self.centroid_sigma_px;
+            let normal: f64 = StandardNormal.sample(rng);
+            radius_px += normal * self.limb_sigma_px;
+        }
+        Ok(Some((
+            camera_to_inertial * self.camera.direction(&pixel_line),
+            radius_px,
+        )))
+    }
+
+    fn centroid(
+        &self,
+        rx: &Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: &Cosm,
+    ) -> Result<Option<OpNavCentroid>, NyxError> {
+        Ok(self
+            .observe(rx, rng, cosm)?
+            .map(|(line_of_sight, _)| OpNavCentroid::new(rx.epoch, &line_of_sight)))
+    }
+
+    fn limb(
+        &self,
+        rx: &Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: &Cosm,
+    ) -> Result<Option<OpNavLimb>, NyxError> {
+        match self.observe(rx, rng, cosm)? {
+            Some((line_of_sight, radius_px)) if radius_px >= self.min_limb_radius_px => {
+                let center = OpNavCentroid::new(rx.epoch, &line_of_sight);
+                Ok(Some(OpNavLimb {
+                    epoch: rx.epoch,
+                    obs: Vector3::new(
+                        center.obs[0],
+                        center.obs[1],
+                        (radius_px / self.camera.focal_length_px)
+                            .atan()
+                            .to_degrees(),
+                    ),
+                }))
+            }
+            Some((_, radius_px)) => {
+                debug!(
+                    "{} target of {radius_px:.1} px at {}, too small to fit its limb",
+                    self.name, rx.epoch
+                );
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl ConfigRepr for OpNavCamera {}
+
+impl Configurable for OpNavCamera {
+    type IntermediateRepr = OpNavCamera;
+
+    fn from_config(
+        cfg: Self::IntermediateRepr,
+        _cosm: Arc<Cosm>,
+    ) -> Result<Self, crate::io::ConfigError>
+    where
+        Self: Sized,
+    {
+        Ok(cfg)
+    }
+
+    fn to_config(&self) -> Result<Self::IntermediateRepr, crate::io::ConfigError> {
+        Ok(self.clone())
+    }
+}
+
+impl TrackingDeviceSim<Orbit, OpNavCentroid> for OpNavCamera {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Orbit>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<OpNavCentroid>, NyxError> {
+        self.centroid(&traj.at(epoch)?, rng, &cosm)
+    }
+
+    /// The transmitter is the target body
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
+        self.target_state(epoch, frame, cosm)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<OpNavCentroid>, NyxError> {
+        self.centroid(&rx, rng, &cosm)
+    }
+}
+
+impl TrackingDeviceSim<Spacecraft, OpNavCentroid> for OpNavCamera {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Spacecraft>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<OpNavCentroid>, NyxError> {
+        self.centroid(&traj.at(epoch)?.orbit, rng, &cosm)
+    }
+
+    /// The transmitter is the target body
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
+        self.target_state(epoch, frame, cosm)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Spacecraft,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<OpNavCentroid>, NyxError> {
+        self.centroid(&rx.orbit, rng, &cosm)
+    }
+}
+
+impl TrackingDeviceSim<Orbit, OpNavLimb> for OpNavCamera {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Orbit>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<OpNavLimb>, NyxError> {
+        self.limb(&traj.at(epoch)?, rng, &cosm)
+    }
+
+    /// The transmitter is the target body
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
+        self.target_state(epoch, frame, cosm)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<OpNavLimb>, NyxError> {
+        self.limb(&rx, rng, &cosm)
+    }
+}
+
+impl TrackingDeviceSim<Spacecraft, OpNavLimb> for OpNavCamera {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Spacecraft>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<OpNavLimb>, NyxError> {
+        self.limb(&traj.at(epoch)?.orbit, rng, &cosm)
+    }
+
+    /// The transmitter is the target body
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
+        self.target_state(epoch, frame, cosm)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Spacecraft,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<OpNavLimb>, NyxError> {
+        self.limb(&rx.orbit, rng, &cosm)
+    }
+}
+
+impl fmt::Display for OpNavCamera {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} (OpNav of {}, {:.0}x{:.0} px, IFOV {:.2e} deg, σ = {:.2} px)",
+            self.name,
+            self.target,
+            self.camera.width_px,
+            self.camera.height_px,
+            self.camera.ifov_deg(),
+            self.centroid_sigma_px
+        )
+    }
+}
@@ -0,0 +1,218 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::msr::{PositionMsr, TrackingArc};
+use super::TrackingDeviceSim;
+use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::io::{ConfigError, ConfigRepr, Configurable};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, Matrix3, Vector3};
+use crate::md::prelude::Traj;
+use crate::md::trajectory::Interpolatable;
+use crate::time::{Duration, Epoch, TimeSeries};
+use crate::{NyxError, Spacecraft};
+use rand_distr::{Distribution, StandardNormal};
+use rand_pcg::Pcg64Mcg;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// A source of position fixes, e.g. the ephemeris of the spacecraft from another provider, ingested in the orbit determination
+/// filters as position pseudo-measurements (cf. `PositionMsr`).
+///
+/// The covariance of the source replaces the measurement noise of the filter when processing its pseudo-measurements,
+/// so that several sources of different quality may be fused in the same orbit determination process.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PositionFixSource {
+    pub name: String,
+    /// Covariance of the position fixes, in km^2, row by row
+    pub covariance_km2: [[f64; 3]; 3],
+}
+
+impl PositionFixSource {
+    /// Initializes a new source of position fixes with the provided covariance (km^2)
+    pub fn new(name: String, covariance_km2: Matrix3<f64>) -> Self {
+        Self {
+            name,
+            covariance_km2: [0, 1, 2].map(|i| [0, 1, 2].map(|j| covariance_km2[(i, j)])),
+        }
+    }
+
+    /// Initializes a new source of position fixes with the same standard deviation on each axis (km)
+    pub fn from_sigma(name: String, sigma_km: f64) -> Self {
+        Self::new(name, Matrix3::from_diagonal_element(sigma_km.powi(2)))
+    }
+
+    /// Returns the covariance of the position fixes, in km^2
+    pub fn covariance(&self) -> Matrix3<f64> {
+        Matrix3::from_fn(|i, j| self.covariance_km2[i][j])
+    }
+
+    /// Converts an external ephemeris into a tracking arc of position pseudo-measurements in the provided frame, i.e. the
+    /// frame of the estimated state. The ephemeris is sampled at the provided interval, or at each of its points if None.
+    pub fn pseudo_measurements<S: Interpolatable>(
+        &self,
+        ephem: &Traj<S>,
+        interval: Option<Duration>,
+        frame: Frame,
+        cosm: Arc<Cosm>,
+    ) -> Result<TrackingArc<PositionMsr>, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        if ephem.states.is_empty() {
+            return Err(NyxError::CustomError(format!(
+                "{}: empty ephemeris",
+                self.name
+            )));
+        }
+        let epochs: Vec<Epoch> = match interval {
+            Some(interval) => {
+                TimeSeries::inclusive(ephem.first().epoch(), ephem.last().epoch(), interval)
+                    .collect()
+            }
+            None => ephem.states.iter().map(|state| state.epoch()).collect(),
+        };
+
+        let mut measurements = Vec::with_capacity(epochs.len());
+        for epoch in epochs {
+            let orbit = cosm.frame_chg(ephem.at(epoch)?.orbit(), frame);
+            measurements.push((self.name.clone(), PositionMsr::new(&orbit)));
+        }
+
+        Ok(TrackingArc {
+            device_cfg: serde_yaml::to_string(&vec![self.to_config()?])
+                .map_err(ConfigError::ParseError)?,
+            measurements,
+        })
+    }
+
+    /// Returns the position of the state, with a noise drawn from the covariance of this source if a random number generator is provided
+    fn fix(&self, orbit: &Orbit, rng: Option<&mut Pcg64Mcg>) -> Result<PositionMsr, NyxError> {
+        let mut msr = PositionMsr::new(orbit);
+        if let Some(rng) = rng {
+            let sqrt_cov = self.covariance().cholesky().ok_or_else(|| {
+                NyxError::CustomError(format!(
+                    "{}: covariance is not positive definite",
+                    self.name
+                ))
+            })?;
+            let normal = Vector3::from_fn(|_, _| StandardNormal.sample(rng));
+            msr.obs += sqrt_cov.l() * normal;
+        }
+        Ok(msr)
+    }
+}
+
+impl ConfigRepr for PositionFixSource {}
+
+impl Configurable for PositionFixSource {
+    type IntermediateRepr = PositionFixSource;
+
+    fn from_config(cfg: Self::IntermediateRepr, _cosm: Arc<Cosm>) -> Result<Self, ConfigError>
+    where
+        Self: Sized,
+    {
+        Ok(cfg)
+    }
+
+    fn to_config(&self) -> Result<Self::IntermediateRepr, ConfigError> {
+        Ok(self.clone())
+    }
+}
+
+impl TrackingDeviceSim<Orbit, PositionMsr> for PositionFixSource {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Orbit>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<PositionMsr>, NyxError> {
+        self.measure_instantaneous(traj.at(epoch)?, rng, cosm)
+    }
+
+    /// The position fixes are relative to the center of the frame of the estimated state
+    fn location(&self, epoch: Epoch, frame: Frame, _cosm: &Cosm) -> Orbit {
+        Orbit::cartesian(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, epoch, frame)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        _cosm: Arc<Cosm>,
+    ) -> Result<Option<PositionMsr>, NyxError> {
+        self.fix(&rx, rng).map(Some)
+    }
+
+    fn measurement_covariance(&self, _epoch: Epoch) -> Option<Matrix3<f64>> {
+        Some(self.covariance())
+    }
+}
+
+impl TrackingDeviceSim<Spacecraft, PositionMsr> for PositionFixSource {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Spacecraft>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<PositionMsr>, NyxError> {
+        self.measure_instantaneous(traj.at(epoch)?, rng, cosm)
+    }
+
+    /// The position fixes are relative to the center of the frame of the estimated state
+    fn location(&self, epoch: Epoch, frame: Frame, _cosm: &Cosm) -> Orbit {
+        Orbit::cartesian(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, epoch, frame)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Spacecraft,
+        rng: Option<&mut Pcg64Mcg>,
+        _cosm: Arc<Cosm>,
+    ) -> Result<Option<PositionMsr>, NyxError> {
+        self.fix(&rx.orbit, rng).map(Some)
+    }
+
+    fn measurement_covariance(&self, _epoch: Epoch) -> Option<Matrix3<f64>> {
+        Some(self.covariance())
+    }
+}
+
+impl fmt::Display for PositionFixSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sigmas_km = self.covariance().diagonal().map(f64::sqrt);
+        write!(
+            f,
+            "{} (position fixes, σ = [{:.3}, {:.3}, {:.3}] km)",
+            self.name, sigmas_km[0], sigmas_km[1], sigmas_km[2]
+        )
+    }
+}
@@ -0,0 +1,771 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::msr::TrackingArc;
+use super::{DynamicParameter, ParameterEstimate, ParameterMode};
+use super::{EstimateFrom, Measurement, Residual, TrackingDeviceSim};
+use crate::cosmic::Cosm;
+use crate::dynamics::Dynamics;
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DMatrix, DVector, DefaultAllocator, DimName, OMatrix, OVector, U1};
+use crate::md::trajectory::{Interpolatable, Traj};
+use crate::propagators::error_ctrl::ErrorCtrl;
+use crate::propagators::Propagator;
+use crate::time::{Duration, Epoch, Unit};
+use crate::State;
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Add;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Statistics of one iteration of a batch least squares.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BatchIteration {
+    /// Number of this iteration, starting at one
+    pub iteration: usize,
+    /// Root mean square of the prefit residuals of the accepted measurements, weighted by the measurement noise
+    pub weighted_rms: f64,
+    /// Number of measurements used in the fit
+    pub num_accepted: usize,
+    /// Number of measurements rejected by the sigma editing
+    pub num_rejected: usize,
+    /// Norm of the correction of the state at the end of this iteration
+    pub correction_norm: f64,
+}
+
+impl fmt::Display for BatchIteration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "iteration #{}: weighted RMS = {:.6}\t{} accepted, {} rejected\t|correction| = {:.3e}",
+            self.iteration,
+            self.weighted_rms,
+            self.num_accepted,
+            self.num_rejected,
+            self.correction_norm
+        )
+    }
+}
+
+/// Time step used to compute the rate of change of the measurements for the time-tag biases, in seconds
+const TIME_BIAS_STEP_S: f64 = 1.0;
+
+/// Configuration of the estimation of the biases between the time tags of the measurements and the ephemeris time, which
+/// typically absorb the timing offsets of the ground systems.
+///
+/// A time-tag bias is the true epoch of a measurement minus its time tag, so the corrected epoch is the time tag plus the bias.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimeBiasConfig {
+    /// Start a new bias for a device when two of its consecutive measurements are further apart than this gap, i.e. one bias
+    /// per pass. If unset, a single bias is estimated per device.
+    pub pass_gap: Option<Duration>,
+    /// A priori uncertainty of the biases, which are zero a priori. If unset, the biases are not constrained.
+    pub a_priori_sigma: Option<Duration>,
+}
+
+impl TimeBiasConfig {
+    /// Estimates a single time-tag bias per device
+    pub fn per_device() -> Self {
+        Self {
+            pass_gap: None,
+            a_priori_sigma: None,
+        }
+    }
+
+    /// Estimates a time-tag bias per pass of each device, where passes are separated by more than the provided gap
+    pub fn per_pass(gap: Duration) -> Self {
+        Self {
+            pass_gap: Some(gap),
+            a_priori_sigma: None,
+        }
+    }
+
+    /// Constrains the biases with the provided a priori uncertainty
+    pub fn with_a_priori(mut self, sigma: Duration) -> Self {
+        self.a_priori_sigma = Some(sigma);
+        self
+    }
+
+    /// Returns the index of the bias of each measurement and the device and time span of each bias
+    fn groups<Msr: Measurement>(
+        &self,
+        measurements: &[(String, Msr)],
+    ) -> (Vec<usize>, Vec<(String, Epoch, Epoch)>) {
+        let mut spans: Vec<(String, Epoch, Epoch)> = Vec::new();
+        // Index of the current bias of each device
+        let mut current: HashMap<&str, usize> = HashMap::new();
+        let mut groups = Vec::with_capacity(measurements.len());
+
+        for (name, msr) in measurements {
+            let epoch = msr.epoch();
+            let idx = match current.get(name.as_str()) {
+                Some(&idx) if self.pass_gap.is_none_or(|gap| epoch - spans[idx].2 <= gap) => idx,
+                _ => {
+                    spans.push((name.clone(), epoch, epoch));
+                    current.insert(name, spans.len() - 1);
+                    spans.len() - 1
+                }
+            };
+            spans[idx].2 = epoch;
+            groups.push(idx);
+        }
+
+        (groups, spans)
+    }
+}
+
+/// An estimated time-tag bias of a tracking device, valid over a span of its measurements
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeTagBias {
+    /// Name of the tracking device
+    pub device: String,
+    /// Time tag of the first measurement of this bias
+    pub start: Epoch,
+    /// Time tag of the last measurement of this bias
+    pub end: Epoch,
+    /// Estimated bias, i.e. true epoch minus time tag
+    pub bias: Duration,
+    /// One sigma uncertainty of the bias
+    pub sigma: Duration,
+}
+
+impl TimeTagBias {
+    /// Returns whether this bias applies to the measurement of the provided device with the provided time tag
+    pub fn applies_to(&self, device: &str, time_tag: Epoch) -> bool {
+        self.device == device && time_tag >= self.start && time_tag <= self.end
+    }
+
+    /// Returns the corrected epoch of the provided time tag
+    pub fn corrected_epoch(&self, time_tag: Epoch) -> Epoch {
+        time_tag + self.bias
+    }
+}
+
+impl fmt::Display for TimeTagBias {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} from {} to {}: time-tag bias = {:.6} ms ± {:.3e} ms",
+            self.device,
+            self.start,
+            self.end,
+            self.bias.to_unit(Unit::Millisecond),
+            self.sigma.to_unit(Unit::Millisecond)
+        )
+    }
+}
+
+/// The solution of a batch least squares.
+#[derive(Clone, Debug)]
+pub struct BatchSolution<T, S, M>
+where
+    T: State,
+    S: State,
+    M: DimName,
+    DefaultAllocator: Allocator<f64, T::Size>
+        + Allocator<f64, T::Size, T::Size>
+        + Allocator<f64, T::VecLength>
+        + Allocator<f64, S::Size>
+        + Allocator<f64, S::Size, S::Size>
+        + Allocator<f64, S::VecLength>
+        + Allocator<f64, M>,
+{
+    /// Estimated state at the epoch of the initial guess
+    pub state: T,
+    /// Covariance of the estimated state
+    pub covar: OMatrix<f64, S::Size, S::Size>,
+    /// Statistics of each iteration
+    pub iterations: Vec<BatchIteration>,
+    /// Residuals of the last iteration: the prefit residuals are computed from the penultimate estimate and the postfit residuals
+    /// from the final estimate. The ratio is the RMS of the prefit residual weighted by the measurement noise.
+    pub residuals: Vec<Residual<M>>,
+    /// Whether the weighted RMS converged before the maximum number of iterations
+    pub converged: bool,
+    /// Estimated time-tag biases, empty if they were not estimated
+    pub time_biases: Vec<TimeTagBias>,
+    /// Estimated and considered dynamic parameters, in the order of the batch least squares
+    pub parameters: Vec<ParameterEstimate>,
+    /// Covariance of the estimated state including the contribution of the uncertainty of the considered parameters, if any
+    pub consider_covar: Option<OMatrix<f64, S::Size, S::Size>>,
+}
+
+impl<T, S, M> BatchSolution<T, S, M>
+where
+    T: State,
+    S: State,
+    M: DimName,
+    DefaultAllocator: Allocator<f64, T::Size>
+        + Allocator<f64, T::Size, T::Size>
+        + Allocator<f64, T::VecLength>
+        + Allocator<f64, S::Size>
+        + Allocator<f64, S::Size, S::Size>
+        + Allocator<f64, S::VecLength>
+        + Allocator<f64, M>,
+{
+    /// One sigma uncertainty of each component of the estimated state
+    pub fn sigmas(&self) -> OVector<f64, S::Size> {
+        self.covar.diagonal().map(|var| var.sqrt())
+    }
+
+    /// Weighted RMS of the prefit residuals of the last iteration
+    pub fn weighted_rms(&self) -> f64 {
+        self.iterations
+            .last()
+            .map_or(f64::NAN, |it| it.weighted_rms)
+    }
+}
+
+/// A classical batch least squares (or differential correction) estimator, which fits the state at the epoch of the initial guess
+/// to all of the measurements of a tracking arc at once, e.g. for definitive orbit determination.
+///
+/// Each iteration propagates the current estimate with its state transition matrix, accumulates the normal equations from the
+/// measurements computed by the tracking devices (as in the sequential filters), and corrects the estimate. The a priori, if
+/// any, is iterated as well: its deviation from the current estimate is accounted for in each iteration. The iterations stop
+/// when the relative change of the weighted RMS of the prefit residuals is below the tolerance.
+///
+/// From the second iteration on, measurements whose weighted residual exceeds the rejection threshold times the weighted RMS of
+/// the previous iteration (or times one if that RMS is smaller) are rejected.
+///
+/// Time-tag biases may be solved for along with the state, cf. `TimeBiasConfig`. Their partials are the rates of change of the
+/// computed measurements, obtained by finite differencing, and they are applied to first order to the computed measurements.
+///
+/// Dynamic parameters (e.g. Cr, Cd or the thrust scale of a maneuver) may augment the estimated state, cf. `DynamicParameter`.
+/// Their partials are obtained by finite differencing of the measurements computed from trajectories propagated with each
+/// parameter offset by its step. Considered parameters are not corrected, but the uncertainty of their a priori inflates the
+/// consider covariance of the solution.
+pub struct BatchLeastSquares<'a, D, E, Msr, S>
+where
+    D: Dynamics,
+    E: ErrorCtrl,
+    Msr: Measurement,
+    S: EstimateFrom<D::StateType, Msr> + Interpolatable,
+    D::StateType: Interpolatable + Add<OVector<f64, <S as State>::Size>, Output = D::StateType>,
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, <S as State>::Size>
+        + Allocator<f64, <S as State>::Size, <S as State>::Size>
+        + Allocator<f64, <S as State>::VecLength>
+        + Allocator<f64, Msr::MeasurementSize>
+        + Allocator<f64, Msr::MeasurementSize, Msr::MeasurementSize>,
+{
+    /// Propagator setup, which must compute the state transition matrix
+    pub prop: &'a Propagator<'a, D, E>,
+    /// Initial guess of the state, at the epoch of the estimate
+    pub initial_state: D::StateType,
+    /// Covariance of the a priori, which is the initial guess, if any
+    pub a_priori_covar: Option<OMatrix<f64, <S as State>::Size, <S as State>::Size>>,
+    /// Measurement noise, replaced by the covariance of the tracking device if it provides one
+    pub measurement_noise: OMatrix<f64, Msr::MeasurementSize, Msr::MeasurementSize>,
+    /// Measurements with a weighted residual greater than this many times the weighted RMS of the previous iteration are rejected
+    pub rejection_sigmas: Option<f64>,
+    /// Maximum number of iterations
+    pub max_iterations: usize,
+    /// Convergence threshold on the relative change of the weighted RMS between two iterations
+    pub tolerance: f64,
+    /// Estimation of the time-tag biases, if any
+    pub time_bias: Option<TimeBiasConfig>,
+    /// Dynamic parameters estimated or considered along with the state
+    pub parameters: Vec<DynamicParameter<D>>,
+    pub cosm: Arc<Cosm>,
+    _marker: PhantomData<S>,
+}
+
+impl<'a, D, E, Msr, S> BatchLeastSquares<'a, D, E, Msr, S>
+where
+    D: Dynamics,
+    E: ErrorCtrl,
+    Msr: Measurement,
+    S: EstimateFrom<D::StateType, Msr> + Interpolatable,
+    D::StateType: Interpolatable + Add<OVector<f64, <S as State>::Size>, Output = D::StateType>,
+    <DefaultAllocator as Allocator<f64, <D::StateType as State>::VecLength>>::Buffer: Send,
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, <S as State>::Size>
+        + Allocator<f64, <S as State>::Size, <S as State>::Size>
+        + Allocator<f64, <S as State>::VecLength>
+        + Allocator<f64, Msr::MeasurementSize>
+        + Allocator<f64, Msr::MeasurementSize, Msr::MeasurementSize>
+        + Allocator<f64, Msr::MeasurementSize, <S as State>::Size>
+        + Allocator<f64, <S as State>::Size, Msr::MeasurementSize>
+        + Allocator<f64, U1, Msr::MeasurementSize>
+        + Allocator<f64, U1, U1>,
+{
+    /// Initializes a new batch least squares without a priori nor measurement editing, with at most ten iterations
+    pub fn new(
+        prop: &'a Propagator<'a, D, E>,
+        initial_state: D::StateType,
+        measurement_noise: OMatrix<f64, Msr::MeasurementSize, Msr::MeasurementSize>,
+        cosm: Arc<Cosm>,
+    ) -> Self {
+        Self {
+            prop,
+            initial_state,
+            a_priori_covar: None,
+            measurement_noise,
+            rejection_sigmas: None,
+            max_iterations: 10,
+            tolerance: 1e-3,
+            time_bias: None,
+            parameters: Vec::new(),
+            cosm,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Uses the initial guess as the a priori, with the provided covariance
+    pub fn with_a_priori(
+        mut self,
+        covar: OMatrix<f64, <S as State>::Size, <S as State>::Size>,
+    ) -> Self {
+        self.a_priori_covar = Some(covar);
+        self
+    }
+
+    /// Rejects the measurements whose weighted residual exceeds this many times the weighted RMS of the previous iteration
+    pub fn with_rejection(mut self, num_sigmas: f64) -> Self {
+        self.rejection_sigmas = Some(num_sigmas);
+        self
+    }
+
+    /// Solves for the time-tag biases of the measurements along with the state
+    pub fn with_time_bias(mut self, config: TimeBiasConfig) -> Self {
+        self.time_bias = Some(config);
+        self
+    }
+
+    /// Estimates (or considers) the provided dynamic parameter along with the state
+    pub fn with_parameter(mut self, parameter: DynamicParameter<D>) -> Self {
+        self.parameters.push(parameter);
+        self
+    }
+
+    /// Estimates the state from the provided tracking arc, rebuilding its devices
+    pub fn estimate_arc<Dev>(
+        &self,
+        arc: &TrackingArc<Msr>,
+    ) -> Result<BatchSolution<D::StateType, S, Msr::MeasurementSize>, NyxError>
+    where
+        Dev: TrackingDeviceSim<S, Msr>,
+    {
+        let mut devices = arc.rebuild_devices::<S, Dev>(self.cosm.clone())?;
+        self.estimate(&arc.measurements, &mut devices)
+    }
+
+    /// Estimates the state from the provided measurements, which must be in chronological order and not before the initial guess.
+    /// The name of all measurement devices must be present in the provided devices.
+    pub fn estimate<Dev>(
+        &self,
+        measurements: &[(String, Msr)],
+        devices: &mut HashMap<String, Dev>,
+    ) -> Result<BatchSolution<D::StateType, S, Msr::MeasurementSize>, NyxError>
+    where
+        Dev: TrackingDeviceSim<S, Msr>,
+    {
+        let start = self.initial_state.epoch();
+        let (first, last) = match (measurements.first(), measurements.last()) {
+            (Some(first), Some(last)) => (first.1.epoch(), last.1.epoch()),
+            _ => {
+                return Err(NyxError::CustomError(
+                    "batch least squares requires measurements".to_string(),
+                ))
+            }
+        };
+        if first < start
+            || measurements
+                .windows(2)
+                .any(|w| w[1].1.epoch() < w[0].1.epoch())
+        {
+            return Err(NyxError::CustomError(
+                "measurements must be in chronological order and after the initial guess"
+                    .to_string(),
+            ));
+        }
+        if let Some((name, _)) = measurements
+            .iter()
+            .find(|(name, _)| !devices.contains_key(name))
+        {
+            return Err(NyxError::CustomError(format!(
+                "measurements reference {name} which is not in the list of configured devices"
+            )));
+        }
+
+        let a_priori_info = match &self.a_priori_covar {
+            Some(covar) => Some(
+                covar
+                    .clone()
+                    .try_inverse()
+                    .ok_or(NyxError::SingularCovarianceMatrix)?,
+            ),
+            None => None,
+        };
+
+        let msr_size = Msr::MeasurementSize::dim() as f64;
+        let num_states = <S as State>::Size::dim();
+        let (groups, spans) = match &self.time_bias {
+            Some(config) => config.groups(measurements),
+            None => (Vec::new(), Vec::new()),
+        };
+        let estimated = self
+            .parameters
+            .iter()
+            .filter(|param| param.mode == ParameterMode::Estimate)
+            .collect::<Vec<&DynamicParameter<D>>>();
+        let considered = self
+            .parameters
+            .iter()
+            .filter(|param| param.mode == ParameterMode::Consider)
+            .collect::<Vec<&DynamicParameter<D>>>();
+        let first_param = num_states + spans.len();
+        let num_params = first_param + estimated.len();
+        let bias_step = TIME_BIAS_STEP_S * Unit::Second;
+
+        let mut nominal = self.initial_state;
+        // Deviation of the a priori from the current estimate
+        let mut a_priori_dev = OVector::<f64, <S as State>::Size>::zeros();
+        // Current estimate of the time-tag biases, in seconds
+        let mut biases = vec![0.0; spans.len()];
+        // Current estimate of the dynamic parameters
+        let mut param_values = estimated
+            .iter()
+            .map(|param| param.value)
+            .collect::<Vec<f64>>();
+        let mut iterations = Vec::with_capacity(self.max_iterations);
+        let mut prev_rms: Option<f64> = None;
+
+        let extract = |full_traj: Traj<D::StateType>| {
+            let mut traj = Traj::<S>::new();
+            traj.states = full_traj
+                .states
+                .iter()
+                .map(|state| S::extract(*state))
+                .collect();
+            traj.finalize();
+            traj
+        };
+
+        for iteration in 1..=self.max_iterations {
+            // Dynamics and initial state with the current values of the dynamic parameters
+            let mut prop = self.prop.clone();
+            for (param, value) in estimated.iter().zip(&param_values) {
+                param.apply(&mut prop.dynamics, &mut nominal, *value)?;
+            }
+            for param in &considered {
+                param.apply(&mut prop.dynamics, &mut nominal, param.value)?;
+            }
+
+            // Trajectory of the current estimate, used by the devices to compute the measurements, and slightly beyond the
+            // last measurement for the rates of change of the measurements
+            let traj_end = if spans.is_empty() {
+                last
+            } else {
+                last + bias_step
+            };
+            let (_, full_traj) = prop.with(nominal).until_epoch_with_traj(traj_end)?;
+            let traj = extract(full_traj);
+
+            // Trajectories with each dynamic parameter offset by its step, for their partials
+            let mut param_trajs = Vec::with_capacity(self.parameters.len());
+            for (param, value) in estimated
+                .iter()
+                .zip(&param_values)
+                .chain(considered.iter().map(|param| (param, &param.value)))
+            {
+                let mut pert_prop = prop.clone();
+                let mut pert_state = nominal;
+                param.apply(&mut pert_prop.dynamics, &mut pert_state, value + param.step)?;
+                let (_, pert_traj) = pert_prop.with(pert_state).until_epoch_with_traj(traj_end)?;
+                param_trajs.push(extract(pert_traj));
+            }
+
+            let mut info_mat = DMatrix::<f64>::zeros(num_params, num_params);
+            let mut normal = DVector::<f64>::zeros(num_params);
+            if let Some(info) = &a_priori_info {
+                let mut info_xx = info_mat.view_mut((0, 0), (num_states, num_states));
+                info_xx += DMatrix::from_column_slice(num_states, num_states, info.as_slice());
+                let info_dev = info * &a_priori_dev;
+                let mut normal_x = normal.rows_mut(0, num_states);
+                normal_x += DVector::from_column_slice(info_dev.as_slice());
+            }
+            if let Some(sigma) = self.time_bias.and_then(|config| config.a_priori_sigma) {
+                let bias_info = sigma.to_seconds().powi(-2);
+                for (k, bias) in biases.iter().enumerate() {
+                    info_mat[(num_states + k, num_states + k)] += bias_info;
+                    normal[num_states + k] -= bias_info * bias;
+                }
+            }
+            for (e, (param, value)) in estimated.iter().zip(&param_values).enumerate() {
+                let param_info = param.sigma.powi(-2);
+                info_mat[(first_param + e, first_param + e)] += param_info;
+                normal[first_param + e] -= param_info * (value - param.value);
+            }
+            let mut info_consider = DMatrix::<f64>::zeros(num_params, considered.len());
+
+            // The STM of this instance maps from the epoch of the estimate since it is never reset
+            let mut instance = prop.with(nominal);
+            let mut fits = Vec::with_capacity(measurements.len());
+            let (mut sum_sq, mut num_scalars, mut num_rejected) = (0.0, 0.0, 0);
+
+            for (msr_idx, (device_name, msr)) in measurements.iter().enumerate() {
+                let epoch = msr.epoch();
+                let state = S::extract(instance.until_epoch(epoch)?);
+                let device = devices.get_mut(device_name).unwrap();
+
+                let computed = match device.measure(epoch, &traj, None, self.cosm.clone())? {
+                    Some(computed) => computed,
+                    None => {
+                        warn!("Real observation exists @ {epoch} but simulated {device_name} does not see it -- ignoring measurement");
+                        continue;
+                    }
+                };
+                let mut computed_obs = computed.observation();
+
+                let device_loc = device.location(epoch, state.frame(), &self.cosm);
+                let h_tilde = S::sensitivity(&msr.with_geometry_of(&computed), state, device_loc);
+                let h_mat = h_tilde * state.stm()?;
+                let mut h_full = DMatrix::<f64>::zeros(h_mat.nrows(), num_params);
+                h_full
+                    .view_mut((0, 0), (h_mat.nrows(), num_states))
+                    .copy_from_slice(h_mat.as_slice());
+
+                // Partials of the dynamic parameters by finite differencing of the offset trajectories
+                let mut h_consider = DMatrix::<f64>::zeros(h_mat.nrows(), considered.len());
+                for (j, (param, pert_traj)) in estimated
+                    .iter()
+                    .chain(&considered)
+                    .zip(&param_trajs)
+                    .enumerate()
+                {
+                    let partial = match device.measure(epoch, pert_traj, None, self.cosm.clone())? {
+                        Some(pert) => (pert.observation() - &computed_obs) / param.step,
+                        None => OVector::<f64, Msr::MeasurementSize>::zeros(),
+                    };
+                    if j < estimated.len() {
+                        h_full
+                            .column_mut(first_param + j)
+                            .copy_from_slice(partial.as_slice());
+                    } else {
+                        h_consider
+                            .column_mut(j - estimated.len())
+                            .copy_from_slice(partial.as_slice());
+                    }
+                }
+
+                if let Some(&k) = groups.get(msr_idx) {
+                    // Rate of change of the measurement by finite differencing, central if possible
+                    let before = if epoch - bias_step >= start {
+                        device.measure(epoch - bias_step, &traj, None, self.cosm.clone())?
+                    } else {
+                        None
+                    };
+                    let after =
+                        device.measure(epoch + bias_step, &traj, None, self.cosm.clone())?;
+                    let rate = match (before, after) {
+                        (Some(before), Some(after)) => {
+                            (after.observation() - before.observation()) / (2.0 * TIME_BIAS_STEP_S)
+                        }
+                        (None, Some(after)) => {
+                            (after.observation() - &computed_obs) / TIME_BIAS_STEP_S
+                        }
+                        (Some(before), None) => {
+                            (&computed_obs - before.observation()) / TIME_BIAS_STEP_S
+                        }
+                        (None, None) => {
+                            return Err(NyxError::CustomError(format!(
+                                "cannot compute the rate of change of the {device_name} measurement @ {epoch} for its time-tag bias"
+                            )))
+                        }
+                    };
+                    computed_obs += &rate * biases[k];
+                    h_full
+                        .column_mut(num_states + k)
+                        .copy_from_slice(rate.as_slice());
+                }
+
+                let noise = device
+                    .measurement_covariance(epoch)
+                    .unwrap_or_else(|| self.measurement_noise.clone());
+                let noise_inv = noise
+                    .try_inverse()
+                    .ok_or(NyxError::SingularCovarianceMatrix)?;
+
+                let prefit = msr.observation_about(&computed_obs) - computed_obs;
+                let weighted_sq = (prefit.transpose() * &noise_inv * &prefit)[(0, 0)];
+                let ratio = (weighted_sq / msr_size).sqrt();
+
+                let rejected = match (self.rejection_sigmas, prev_rms) {
+                    (Some(num_sigmas), Some(rms)) => ratio > num_sigmas * rms.max(1.0),
+                    _ => false,
+                };
+
+                if rejected {
+                    num_rejected += 1;
+                } else {
+                    let noise_inv = DMatrix::from_column_slice(
+                        noise_inv.nrows(),
+                        noise_inv.ncols(),
+                        noise_inv.as_slice(),
+                    );
+                    let ht_rinv = h_full.transpose() * noise_inv;
+                    info_mat += &ht_rinv * &h_full;
+                    info_consider += &ht_rinv * &h_consider;
+                    normal += ht_rinv * DVector::from_column_slice(prefit.as_slice());
+                    sum_sq += weighted_sq;
+                    num_scalars += msr_size;
+                }
+
+                fits.push((epoch, prefit, h_full, ratio, rejected));
+            }
+
+            if num_scalars == 0.0 {
+                return Err(NyxError::CustomError(
+                    "no measurement accepted in the batch least squares".to_string(),
+                ));
+            }
+
+            let full_covar = info_mat
+                .try_inverse()
+                .ok_or(NyxError::SingularCovarianceMatrix)?;
+            let full_correction = &full_covar * normal;
+            let covar = OMatrix::<f64, <S as State>::Size, <S as State>::Size>::from_fn(|i, j| {
+                full_covar[(i, j)]
+            });
+            let correction = OVector::<f64, <S as State>::Size>::from_fn(|i, _| full_correction[i]);
+
+            nominal = nominal + correction.clone();
+            a_priori_dev -= &correction;
+            for (k, bias) in biases.iter_mut().enumerate() {
+                *bias += full_correction[num_states + k];
+            }
+            for (e, value) in param_values.iter_mut().enumerate() {
+                *value += full_correction[first_param + e];
+            }
+
+            let weighted_rms = (sum_sq / num_scalars).sqrt();
+            let stats = BatchIteration {
+                iteration,
+                weighted_rms,
+                num_accepted: fits.len() - num_rejected,
+                num_rejected,
+                correction_norm: correction.norm(),
+            };
+            info!("Batch least squares {stats}");
+            iterations.push(stats);
+
+            let converged = prev_rms
+                .is_some_and(|prev| (prev - weighted_rms).abs() / weighted_rms < self.tolerance);
+            prev_rms = Some(weighted_rms);
+
+            if converged || iteration == self.max_iterations {
+                if !converged {
+                    warn!("Batch least squares did not converge after {iteration} iterations");
+                }
+                let residuals = fits
+                    .into_iter()
+                    .map(|(epoch, prefit, h_full, ratio, rejected)| {
+                        if rejected {
+                            Residual::rejected(epoch, prefit, ratio)
+                        } else {
+                            let delta = h_full * &full_correction;
+                            let postfit = &prefit
+                                - OVector::<f64, Msr::MeasurementSize>::from_column_slice(
+                                    delta.as_slice(),
+                                );
+                            Residual::new(epoch, prefit, postfit, ratio)
+                        }
+                    })
+                    .collect();
+
+                let time_biases = spans
+                    .into_iter()
+                    .zip(biases)
+                    .enumerate()
+                    .map(|(k, ((device, start, end), bias))| TimeTagBias {
+                        device,
+                        start,
+                        end,
+                        bias: bias * Unit::Second,
+                        sigma: full_covar[(num_states + k, num_states + k)].sqrt() * Unit::Second,
+                    })
+                    .collect::<Vec<TimeTagBias>>();
+                for time_bias in &time_biases {
+                    info!("{time_bias}");
+                }
+
+                let parameters = estimated
+                    .iter()
+                    .zip(&param_values)
+                    .enumerate()
+                    .map(|(e, (param, value))| ParameterEstimate {
+                        name: param.name.clone(),
+                        mode: param.mode,
+                        value: *value,
+                        sigma: full_covar[(first_param + e, first_param + e)].sqrt(),
+                    })
+                    .chain(considered.iter().map(|param| ParameterEstimate {
+                        name: param.name.clone(),
+                        mode: param.mode,
+                        value: param.value,
+                        sigma: param.sigma,
+                    }))
+                    .collect::<Vec<ParameterEstimate>>();
+                for parameter in &parameters {
+                    info!("{parameter}");
+                }
+
+                // Sensitivity of the estimate to the considered parameters, which maps their a priori covariance
+                let consider_covar = if considered.is_empty() {
+                    None
+                } else {
+                    let sensitivity = -(&full_covar * info_consider);
+                    let consider_var = DMatrix::from_diagonal(&DVector::from_iterator(
+                        considered.len(),
+                        considered.iter().map(|param| param.sigma.powi(2)),
+                    ));
+                    let full = &full_covar + &sensitivity * consider_var * sensitivity.transpose();
+                    Some(
+                        OMatrix::<f64, <S as State>::Size, <S as State>::Size>::from_fn(|i, j| {
+                            full[(i, j)]
+                        }),
+                    )
+                };
+
+                return Ok(BatchSolution {
+                    state: nominal,
+                    covar,
+                    iterations,
+                    residuals,
+                    converged,
+                    time_biases,
+                    parameters,
+                    consider_covar,
+                });
+            }
+        }
+
+        Err(NyxError::CustomError(
+            "batch least squares requires at least one iteration".to_string(),
+        ))
+    }
+}
@@ -16,6 +16,7 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+#[cfg(feature = "io")]
 use crate::io::watermark::pq_writer;
 use crate::io::ExportCfg;
 use crate::linalg::allocator::Allocator;
@@ -39,7 +40,7 @@ use std::fs::File;
 use std::ops::Add;
 use std::path::{Path, PathBuf};
 
-use super::ODProcess;
+use super::{ODProcess, SmootherResults};
 
 impl<
         'a,
@@ -82,11 +83,45 @@ where
         path: P,
         cfg: ExportCfg,
     ) -> Result<PathBuf, Box<dyn Error>> {
-        if self.estimates.is_empty() {
+        self.write_parquet(
+            &self.estimates,
+            None,
+            path,
+            cfg,
+            "Orbit determination results",
+        )
+    }
+
+    /// Store the smoothed estimates in a parquet file, with the same layout as the filter results (including the
+    /// residuals of the filter), followed by the filter-smoother consistency statistic of each component.
+    pub fn smoother_to_parquet<P: AsRef<Path>>(
+        &self,
+        smoothed: &SmootherResults<S, K::Estimate>,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        self.write_parquet(
+            &smoothed.estimates,
+            Some(&smoothed.consistency),
+            path,
+            cfg,
+            "Orbit determination smoothed results",
+        )
+    }
+
+    fn write_parquet<P: AsRef<Path>>(
+        &self,
+        all_estimates: &[K::Estimate],
+        all_consistency: Option<&[OVector<f64, <S as State>::Size>]>,
+        path: P,
+        cfg: ExportCfg,
+        purpose: &str,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        if all_estimates.is_empty() {
             return Err(Box::new(NyxError::CustomError(
                 "No data: run the ODProcess before exporting it.".to_string(),
             )));
-        } else if self.estimates.len() != self.residuals.len() {
+        } else if all_estimates.len() != self.residuals.len() {
             return Err(Box::new(NyxError::CustomError(
                 "Estimates and residuals are not aligned.".to_string(),
             )));
@@ -109,7 +144,7 @@ where
             Field::new("Epoch:TAI (s)", DataType::Float64, false),
         ];
 
-        let frame_name = self.estimates[0].state().frame();
+        let frame_name = all_estimates[0].state().frame();
 
         let more_meta = Some(vec![("Frame".to_string(), format!("{}", frame_name))]);
 
@@ -119,7 +154,7 @@ where
         };
 
         // Check that we can retrieve this information
-        fields.retain(|param| match self.estimates[0].state().value(*param) {
+        fields.retain(|param| match all_estimates[0].state().value(*param) {
             Ok(_) => true,
             Err(_) => {
                 warn!("Removed unavailable field `{param}` from orbit determination export",);
@@ -131,10 +166,11 @@ where
             hdrs.push(field.to_field(more_meta.clone()));
         }
 
-        let cov_hdrs = match <S as State>::Size::dim() {
+        let (components, cov_hdrs) = match <S as State>::Size::dim() {
             6 => {
                 // Add orbit 1-sigma covariance info, plotting to perform computations as desired
-                vec![
+                let components = vec!["X", "Y", "Z", "Vx", "Vy", "Vz"];
+                let cov_hdrs = vec![
                     "Covariance XX",
                     "Covariance XY",
                     "Covariance XZ",
@@ -156,7 +192,8 @@ where
                     "Covariance VyVy",
                     "Covariance VyVz",
                     "Covariance VzVz",
-                ]
+                ];
+                (components, cov_hdrs)
             }
             _ => todo!(
                 "exporting a state of size {} is not yet supported",
@@ -199,36 +236,44 @@ where
 
         hdrs.append(&mut msr_fields);
 
+        // Add the filter-smoother consistency
+        if all_consistency.is_some() {
+            for component in &components {
+                hdrs.push(Field::new(
+                    format!("Consistency {component}"),
+                    DataType::Float64,
+                    false,
+                ));
+            }
+        }
+
         // Build the schema
         let schema = Arc::new(Schema::new(hdrs));
         let mut record: Vec<Arc<dyn Array>> = Vec::new();
 
         // Build the states iterator -- this does require copying the current states but I can't either get a reference or a copy of all the states.
-        let (estimates, residuals) =
-            if cfg.start_epoch.is_some() || cfg.end_epoch.is_some() || cfg.step.is_some() {
-                // Must interpolate the data!
-                let start = cfg
-                    .start_epoch
-                    .unwrap_or_else(|| self.estimates.first().unwrap().state().epoch());
-                let end = cfg
-                    .end_epoch
-                    .unwrap_or_else(|| self.estimates.last().unwrap().state().epoch());
-
-                let mut residuals: Vec<Option<Residual<Msr::MeasurementSize>>> =
-                    Vec::with_capacity(self.residuals.len());
-                let mut estimates = Vec::with_capacity(self.estimates.len());
-
-                for (estimate, residual) in self.estimates.iter().zip(self.residuals.iter()) {
-                    if estimate.epoch() >= start && estimate.epoch() <= end {
-                        estimates.push(estimate.clone());
-                        residuals.push(residual.clone());
-                    }
+        let start = cfg
+            .start_epoch
+            .unwrap_or_else(|| all_estimates.first().unwrap().state().epoch());
+        let end = cfg
+            .end_epoch
+            .unwrap_or_else(|| all_estimates.last().unwrap().state().epoch());
+
+        let mut estimates = Vec::with_capacity(all_estimates.len());
+        let mut residuals: Vec<Option<Residual<Msr::MeasurementSize>>> =
+            Vec::with_capacity(self.residuals.len());
+        let mut consistency = Vec::with_capacity(all_estimates.len());
+
+        for (k, (estimate, residual)) in all_estimates.iter().zip(self.residuals.iter()).enumerate()
+        {
+            if estimate.epoch() >= start && estimate.epoch() <= end {
+                estimates.push(estimate.clone());
+                residuals.push(residual.clone());
+                if let Some(all_consistency) = all_consistency {
+                    consistency.push(all_consistency[k].clone());
                 }
-
-                (estimates, residuals)
-            } else {
-                (self.estimates.to_vec(), self.residuals.to_vec())
-            };
+            }
+        }
 
         // Build all of the records
 
@@ -331,21 +376,29 @@ where
         }
         record.push(Arc::new(data.finish()));
 
+        // Filter-smoother consistency
+        if all_consistency.is_some() {
+            for i in 0..components.len() {
+                let mut data = Float64Builder::new();
+                for stats in &consistency {
+                    data.append_value(stats[i]);
+                }
+                record.push(Arc::new(data.finish()));
+            }
+        }
+
         info!("Serialized {} estimates and residuals", estimates.len());
 
         // Serialize all of the devices and add that to the parquet file too.
         let mut metadata = HashMap::new();
-        metadata.insert(
-            "Purpose".to_string(),
-            "Orbit determination results".to_string(),
-        );
+        metadata.insert("Purpose".to_string(), purpose.to_string());
         if let Some(add_meta) = cfg.metadata {
             for (k, v) in add_meta {
                 metadata.insert(k, v);
             }
         }
 
-        let props = pq_writer(Some(metadata));
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
 
         let file = File::create(&path_buf)?;
         let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
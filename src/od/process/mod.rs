@@ -17,7 +17,7 @@
 */
 
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, DimName};
+use crate::linalg::{DMatrix, DVector, DefaultAllocator, DimName};
 use crate::md::trajectory::{Interpolatable, Traj};
 pub use crate::od::estimate::*;
 pub use crate::od::ground_station::*;
@@ -36,7 +36,17 @@ pub use self::rejectcrit::FltResid;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::ops::Add;
+use tracing::{debug, debug_span, error, info, info_span, warn};
+#[cfg(feature = "io")]
 mod export;
+mod replay;
+pub use replay::{FilterReplay, FilterStep, MeasurementStep, ReplayCursor};
+mod smoother;
+pub use smoother::SmootherResults;
+mod batch;
+pub use batch::{BatchIteration, BatchLeastSquares, BatchSolution, TimeBiasConfig, TimeTagBias};
+mod parameters;
+pub use parameters::{DynamicParameter, ParameterEstimate, ParameterMode};
 
 /// An orbit determination process. Note that everything passed to this structure is moved.
 #[allow(clippy::upper_case_acronyms)]
@@ -83,6 +93,10 @@ pub struct ODProcess<
     /// Residual rejection criteria allows preventing bad measurements from affecting the estimation.
     pub resid_crit: Option<FltResid>,
     pub cosm: Arc<Cosm>,
+    /// Every step of the filter, recorded if this process was built `with_replay`
+    pub replay: Option<FilterReplay>,
+    /// Predicted state deviation of each estimate, i.e. prior to its measurement update, aligned with the estimates
+    prior_deviations: Vec<OVector<f64, <S as State>::Size>>,
     init_state: D::StateType,
     _marker: PhantomData<A>,
 }
@@ -139,6 +153,8 @@ where
             ekf_trigger,
             resid_crit,
             cosm,
+            replay: None,
+            prior_deviations: Vec::with_capacity(10_000),
             init_state,
             _marker: PhantomData::<A>,
         }
@@ -161,11 +177,75 @@ where
             ekf_trigger: Some(trigger),
             resid_crit,
             cosm,
+            replay: None,
+            prior_deviations: Vec::with_capacity(10_000),
             init_state,
             _marker: PhantomData::<A>,
         }
     }
 
+    /// Records every step of the filter (its inputs, gain and update) in the `replay` of this process, to step through
+    /// them after the fact, e.g. when the filter diverged mid-arc.
+    pub fn with_replay(mut self) -> Self {
+        self.replay = Some(FilterReplay::default());
+        self
+    }
+
+    /// Records the provided filter step in the replay, if enabled
+    fn record_step(
+        &mut self,
+        estimate: &K::Estimate,
+        prior_deviation: &OVector<f64, <S as State>::Size>,
+        measurement: Option<MeasurementStep>,
+    ) {
+        if let Some(replay) = self.replay.as_mut() {
+            let n = <S as State>::Size::dim();
+            let nominal_state = estimate.nominal_state().as_vector().unwrap();
+            replay.steps.push(FilterStep {
+                epoch: estimate.epoch(),
+                nominal_state: DVector::from_column_slice(&nominal_state.as_slice()[..n]),
+                prior_deviation: DVector::from_column_slice(prior_deviation.as_slice()),
+                covar_bar: DMatrix::from_column_slice(n, n, estimate.predicted_covar().as_slice()),
+                state_deviation: DVector::from_column_slice(estimate.state_deviation().as_slice()),
+                covar: DMatrix::from_column_slice(n, n, estimate.covar().as_slice()),
+                measurement,
+            });
+        }
+    }
+
+    /// Builds the replay of a measurement update from its inputs and results
+    fn measurement_step(
+        device: &str,
+        estimate: &K::Estimate,
+        real_obs: &OVector<f64, Msr::MeasurementSize>,
+        computed_obs: &OVector<f64, Msr::MeasurementSize>,
+        h_tilde: &OMatrix<f64, Msr::MeasurementSize, <S as State>::Size>,
+        noise: &OMatrix<f64, Msr::MeasurementSize, Msr::MeasurementSize>,
+        residual: &Residual<Msr::MeasurementSize>,
+    ) -> MeasurementStep {
+        let n = <S as State>::Size::dim();
+        let m = Msr::MeasurementSize::dim();
+        let covar_bar = DMatrix::from_column_slice(n, n, estimate.predicted_covar().as_slice());
+        let h_tilde = DMatrix::from_column_slice(m, n, h_tilde.as_slice());
+        let noise = DMatrix::from_column_slice(m, m, noise.as_slice());
+        let gain = match (&h_tilde * &covar_bar * h_tilde.transpose() + &noise).try_inverse() {
+            Some(inv) => &covar_bar * h_tilde.transpose() * inv,
+            None => DMatrix::zeros(n, m),
+        };
+        MeasurementStep {
+            device: device.to_string(),
+            real_obs: DVector::from_column_slice(real_obs.as_slice()),
+            computed_obs: DVector::from_column_slice(computed_obs.as_slice()),
+            h_tilde,
+            noise,
+            gain,
+            prefit: DVector::from_column_slice(residual.prefit.as_slice()),
+            postfit: DVector::from_column_slice(residual.postfit.as_slice()),
+            ratio: residual.ratio,
+            rejected: residual.rejected,
+        }
+    }
+
     /// Allows to smooth the provided estimates. Returns the smoothed estimates or an error.
     ///
     /// Estimates must be ordered in chronological order. This function will smooth the
@@ -324,6 +404,7 @@ where
             // Empty the estimates and add the first smoothed estimate as the initial estimate
             self.estimates = Vec::with_capacity(measurements.len().max(self.estimates.len()));
             self.residuals = Vec::with_capacity(measurements.len().max(self.estimates.len()));
+            self.prior_deviations = Vec::with_capacity(self.residuals.capacity());
 
             self.kf.set_previous_estimate(&smoothed[0]);
             // And re-run the filter
@@ -452,6 +533,14 @@ where
         // Start by propagating the estimator (on the same thread).
         let num_msrs = measurements.len();
 
+        let _span = info_span!(
+            "od_process",
+            num_msrs,
+            start = %measurements[0].1.epoch(),
+            end = %measurements[num_msrs - 1].1.epoch(),
+        )
+        .entered();
+
         // Update the step size of the navigation propagator if it isn't already fixed step
         if !self.prop.fixed_step {
             self.prop.set_step(step_size, false);
@@ -467,12 +556,25 @@ where
 
         // We'll build a trajectory of the estimated states. This will be used to compute the measurements.
         let mut traj: Traj<S> = Traj::new();
+        // Buffer of the states of each propagation step, reused throughout the arc to avoid allocating at every step
+        let mut step_states = Vec::new();
+
+        self.estimates.reserve(num_msrs);
+        self.residuals.reserve(num_msrs);
 
         let mut msr_accepted_cnt = 0;
 
         for (msr_cnt, (device_name, msr)) in measurements.iter().enumerate() {
             let next_msr_epoch = msr.epoch();
 
+            let _msr_span = debug_span!(
+                "od_measurement",
+                msr_cnt,
+                device = %device_name,
+                epoch = %next_msr_epoch,
+            )
+            .entered();
+
             for val in msr.observation().iter() {
                 if !val.is_finite() {
                     return Err(NyxError::CustomError(format!(
@@ -505,10 +607,14 @@ where
                 traj.states.truncate(index);
 
                 debug!("advancing propagator by {next_step_size} (Δt to next msr: {delta_t})");
-                let (_, traj_covar) = self.prop.for_duration_with_traj(next_step_size)?;
-
-                for state in traj_covar.states {
-                    traj.states.push(S::extract(state));
+                let step_start = traj.states.len();
+                traj.states.push(S::extract(self.prop.state));
+                self.prop
+                    .for_duration_into(next_step_size, &mut step_states)?;
+                traj.states.extend(step_states.drain(..).map(S::extract));
+                if next_step_size.is_negative() {
+                    // Keep the states of a backward step ordered by epoch
+                    traj.states[step_start..].reverse();
                 }
 
                 // Now that we've advanced the propagator, let's see whether we're at the time of the next measurement.
@@ -538,23 +644,53 @@ where
                                     }
                                 }
 
-                                let h_tilde = S::sensitivity(msr, nominal_state, device_loc);
+                                let h_tilde = S::sensitivity(
+                                    &msr.with_geometry_of(&computed_meas),
+                                    nominal_state,
+                                    device_loc,
+                                );
+
+                                // The state deviation before this update, as used by the filter
+                                let prev_deviation = self.kf.previous_estimate().state_deviation();
+                                let was_extended = self.kf.is_extended();
 
-                                self.kf.update_h_tilde(h_tilde);
+                                self.kf.update_h_tilde(h_tilde.clone());
 
                                 let resid_ratio_check = self
                                     .resid_crit
                                     .filter(|flt| msr_accepted_cnt >= flt.min_accepted)
                                     .map(|flt| flt.num_sigmas);
 
-                                match self.kf.measurement_update(
+                                // Use the covariance of the device if it provides one, e.g. a source of position fixes
+                                let filter_noise =
+                                    device.measurement_covariance(epoch).map(|covariance| {
+                                        let noise = self.kf.measurement_noise(epoch).clone();
+                                        self.kf.set_measurement_noise(covariance);
+                                        noise
+                                    });
+
+                                let computed_obs = computed_meas.observation();
+                                let real_obs = msr.observation_about(&computed_obs);
+
+                                let update = self.kf.measurement_update(
                                     nominal_state,
-                                    &msr.observation(),
-                                    &computed_meas.observation(),
+                                    &real_obs,
+                                    &computed_obs,
                                     resid_ratio_check,
-                                ) {
+                                );
+
+                                let noise = self.kf.measurement_noise(epoch).clone();
+                                if let Some(noise) = filter_noise {
+                                    self.kf.set_measurement_noise(noise);
+                                }
+
+                                match update {
                                     Ok((estimate, residual)) => {
-                                        debug!("processed msr #{msr_cnt} @ {epoch}");
+                                        debug!(
+                                            ratio = residual.ratio,
+                                            rejected = residual.rejected,
+                                            "processed msr #{msr_cnt} @ {epoch}"
+                                        );
 
                                         if !residual.rejected {
                                             msr_accepted_cnt += 1;
@@ -582,10 +718,34 @@ where
                                             }
                                         }
 
+                                        let prior_deviation = if was_extended {
+                                            OVector::<f64, <S as State>::Size>::zeros()
+                                        } else {
+                                            estimate.stm() * prev_deviation
+                                        };
+
+                                        if self.replay.is_some() {
+                                            let step = Self::measurement_step(
+                                                device_name,
+                                                &estimate,
+                                                &real_obs,
+                                                &computed_obs,
+                                                &h_tilde,
+                                                &noise,
+                                                &residual,
+                                            );
+                                            self.record_step(
+                                                &estimate,
+                                                &prior_deviation,
+                                                Some(step),
+                                            );
+                                        }
+
                                         self.prop.state.reset_stm();
 
                                         self.estimates.push(estimate);
                                         self.residuals.push(Some(residual));
+                                        self.prior_deviations.push(prior_deviation);
                                     }
                                     Err(e) => return Err(e),
                                 }
@@ -613,8 +773,10 @@ where
                     debug!("time update {epoch}");
                     match self.kf.time_update(nominal_state) {
                         Ok(est) => {
+                            self.record_step(&est, &est.state_deviation(), None);
                             // State deviation is always zero for an EKF time update
                             // therefore we don't do anything different for an extended filter
+                            self.prior_deviations.push(est.state_deviation());
                             self.estimates.push(est);
                             // We push None so that the residuals and estimates are aligned
                             self.residuals.push(None);
@@ -666,8 +828,10 @@ where
             debug!("time update {epoch}");
             match self.kf.time_update(nominal_state) {
                 Ok(est) => {
+                    self.record_step(&est, &est.state_deviation(), None);
                     // State deviation is always zero for an EKF time update
                     // therefore we don't do anything different for an extended filter
+                    self.prior_deviations.push(est.state_deviation());
                     self.estimates.push(est);
                     self.residuals.push(None);
                 }
@@ -712,6 +876,7 @@ where
                     .map(|est| est.nominal_state())
                     .collect(),
                 name: None,
+                backward: false,
             })
         }
     }
@@ -767,6 +932,8 @@ where
             ekf_trigger: None,
             init_state,
             cosm,
+            replay: None,
+            prior_deviations: Vec::with_capacity(10_000),
             _marker: PhantomData::<A>,
         }
     }
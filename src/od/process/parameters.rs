@@ -0,0 +1,194 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::dynamics::burns::FiniteBurn;
+use crate::dynamics::{Dynamics, SpacecraftDynamics};
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::DefaultAllocator;
+use crate::State;
+use std::fmt;
+use std::sync::Arc;
+
+/// Sets the value of a dynamic parameter in the dynamics or in the state to be propagated
+type ParameterSetter<D> =
+    Arc<dyn Fn(&mut D, &mut <D as Dynamics>::StateType, f64) -> Result<(), NyxError> + Send + Sync>;
+
+/// Whether a dynamic parameter is solved for, or only considered: its uncertainty then inflates the covariance of the
+/// estimate without the parameter being corrected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParameterMode {
+    Estimate,
+    Consider,
+}
+
+/// A parameter of the dynamics augmenting the estimated state, e.g. a coefficient of reflectivity or the scale factor of
+/// the thrust of a maneuver.
+///
+/// Its partials are obtained by finite differencing of the trajectories propagated with the nominal value and with the
+/// value offset by the step.
+#[derive(Clone)]
+pub struct DynamicParameter<D: Dynamics>
+where
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>,
+{
+    pub name: String,
+    /// A priori value
+    pub value: f64,
+    /// A priori uncertainty
+    pub sigma: f64,
+    /// Finite differencing step, the a priori uncertainty by default
+    pub step: f64,
+    pub mode: ParameterMode,
+    setter: ParameterSetter<D>,
+}
+
+impl<D: Dynamics> DynamicParameter<D>
+where
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>,
+{
+    /// A solve-for parameter with the provided a priori value and uncertainty, set in the dynamics or the initial state
+    /// by the provided function
+    pub fn new<F>(name: &str, value: f64, sigma: f64, setter: F) -> Self
+    where
+        F: Fn(&mut D, &mut D::StateType, f64) -> Result<(), NyxError> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            value,
+            sigma,
+            step: sigma,
+            mode: ParameterMode::Estimate,
+            setter: Arc::new(setter),
+        }
+    }
+
+    /// Considers this parameter instead of solving for it
+    pub fn considered(mut self) -> Self {
+        self.mode = ParameterMode::Consider;
+        self
+    }
+
+    /// Sets the finite differencing step of the partials
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Sets the provided value of this parameter in the dynamics or the state
+    pub fn apply(
+        &self,
+        dynamics: &mut D,
+        state: &mut D::StateType,
+        value: f64,
+    ) -> Result<(), NyxError> {
+        (self.setter)(dynamics, state, value)
+    }
+}
+
+impl DynamicParameter<SpacecraftDynamics> {
+    /// Coefficient of reflectivity of the spacecraft
+    pub fn srp_coefficient(cr: f64, sigma: f64) -> Self {
+        Self::new("Cr", cr, sigma, |_, sc, value| {
+            sc.srp.cr = value;
+            Ok(())
+        })
+    }
+
+    /// Coefficient of drag of the spacecraft
+    pub fn drag_coefficient(cd: f64, sigma: f64) -> Self {
+        Self::new("Cd", cd, sigma, |_, sc, value| {
+            sc.drag.cd = value;
+            Ok(())
+        })
+    }
+
+    /// Scale factor of the thrust of the finite burn of the dynamics with the provided index (in chronological order),
+    /// one a priori
+    pub fn thrust_scale(burn: usize, sigma: f64) -> Self {
+        Self::new(
+            &format!("Thrust scale #{burn}"),
+            1.0,
+            sigma,
+            move |dynamics, _, value| {
+                Self::burn_of(dynamics, burn)?.thrust_scale = value;
+                Ok(())
+            },
+        )
+    }
+
+    /// Pointing bias of the finite burn of the dynamics with the provided index (in chronological order), as a rotation
+    /// about the provided axis (0, 1 or 2) of the frame of its direction in radians, zero a priori
+    pub fn pointing_bias(burn: usize, axis: usize, sigma_rad: f64) -> Self {
+        Self::new(
+            &format!("Pointing bias #{burn} axis {axis} (rad)"),
+            0.0,
+            sigma_rad,
+            move |dynamics, _, value| {
+                if axis > 2 {
+                    return Err(NyxError::CustomError(format!(
+                        "pointing bias axis must be 0, 1 or 2, got {axis}"
+                    )));
+                }
+                Self::burn_of(dynamics, burn)?.pointing_bias_rad[axis] = value;
+                Ok(())
+            },
+        )
+    }
+
+    fn burn_of(
+        dynamics: &mut SpacecraftDynamics,
+        burn: usize,
+    ) -> Result<&mut FiniteBurn, NyxError> {
+        let num_burns = dynamics.burns.len();
+        dynamics.burns.get_mut(burn).ok_or_else(|| {
+            NyxError::CustomError(format!(
+                "no finite burn #{burn} in the dynamics, which have {num_burns}"
+            ))
+        })
+    }
+}
+
+/// The value of a dynamic parameter after the estimation
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterEstimate {
+    pub name: String,
+    pub mode: ParameterMode,
+    /// Estimated value, or the a priori value of a considered parameter
+    pub value: f64,
+    /// Uncertainty of the estimated value, or the a priori uncertainty of a considered parameter
+    pub sigma: f64,
+}
+
+impl fmt::Display for ParameterEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self.mode {
+            ParameterMode::Estimate => "estimated",
+            ParameterMode::Consider => "considered",
+        };
+        write!(
+            f,
+            "{} ({mode}): {:.6} ± {:.3e}",
+            self.name, self.value, self.sigma
+        )
+    }
+}
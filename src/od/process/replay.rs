@@ -0,0 +1,558 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+#[cfg(feature = "io")]
+use crate::io::watermark::pq_writer;
+use crate::linalg::{DMatrix, DVector};
+use crate::time::Epoch;
+#[cfg(feature = "io")]
+use crate::NyxError;
+#[cfg(feature = "io")]
+use arrow::array::{
+    Array, BooleanArray, BooleanBuilder, Float64Array, Float64Builder, StringArray, StringBuilder,
+};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "io")]
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+#[cfg(feature = "io")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+#[cfg(feature = "io")]
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "io")]
+use std::fs::File;
+#[cfg(feature = "io")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "io")]
+use std::str::FromStr;
+#[cfg(feature = "io")]
+use std::sync::Arc;
+
+#[cfg(feature = "io")]
+const STATE_SIZE_KEY: &str = "State size";
+#[cfg(feature = "io")]
+const MSR_SIZE_KEY: &str = "Measurement size";
+
+/// The inputs and intermediate quantities of a measurement update
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeasurementStep {
+    /// Name of the tracking device of this measurement
+    pub device: String,
+    /// The real observation
+    pub real_obs: DVector<f64>,
+    /// The observation computed from the nominal state
+    pub computed_obs: DVector<f64>,
+    /// The sensitivity matrix H
+    pub h_tilde: DMatrix<f64>,
+    /// The measurement noise R
+    pub noise: DMatrix<f64>,
+    /// The Kalman gain, i.e. `P̄⋅H'⋅(H⋅P̄⋅H' + R)^-1`, which is not applied if the measurement was rejected
+    pub gain: DMatrix<f64>,
+    /// The prefit residual
+    pub prefit: DVector<f64>,
+    /// The postfit residual
+    pub postfit: DVector<f64>,
+    /// The prefit residual ratio
+    pub ratio: f64,
+    /// Whether this measurement was rejected by the residual rejection criteria
+    pub rejected: bool,
+}
+
+/// A step of the filter, recorded by an orbit determination process in replay mode.
+///
+/// Vectors and matrices are stored with their dynamic size, in the order of the estimated state (e.g. the Cartesian
+/// state for an orbit estimate).
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterStep {
+    /// Epoch of this step
+    pub epoch: Epoch,
+    /// The nominal state of the estimate
+    pub nominal_state: DVector<f64>,
+    /// The state deviation before this step, mapped to its epoch with the STM (always zero in an extended filter)
+    pub prior_deviation: DVector<f64>,
+    /// The covariance before this step, mapped to its epoch with the STM and including the process noise
+    pub covar_bar: DMatrix<f64>,
+    /// The state deviation after this step
+    pub state_deviation: DVector<f64>,
+    /// The covariance after this step
+    pub covar: DMatrix<f64>,
+    /// The measurement processed in this step, if any
+    pub measurement: Option<MeasurementStep>,
+}
+
+impl FilterStep {
+    /// Returns whether a measurement was processed (and possibly rejected) in this step, else it is a time update
+    pub fn is_measurement_update(&self) -> bool {
+        self.measurement.is_some()
+    }
+
+    /// Returns whether the measurement of this step was rejected
+    pub fn is_rejected(&self) -> bool {
+        self.measurement
+            .as_ref()
+            .map(|msr| msr.rejected)
+            .unwrap_or(false)
+    }
+
+    /// Returns the estimated state, i.e. the nominal state plus the state deviation
+    pub fn estimated_state(&self) -> DVector<f64> {
+        &self.nominal_state + &self.state_deviation
+    }
+
+    /// Returns the update of the state deviation in this step, i.e. the gain times the residual for a measurement update
+    pub fn update(&self) -> DVector<f64> {
+        &self.state_deviation - &self.prior_deviation
+    }
+
+    /// Returns the standard deviations of the estimate after this step
+    pub fn sigmas(&self) -> DVector<f64> {
+        self.covar.diagonal().map(f64::sqrt)
+    }
+
+    /// Returns the covariance of the prefit residual, i.e. `H⋅P̄⋅H' + R`, if a measurement was processed in this step
+    pub fn innovation_covar(&self) -> Option<DMatrix<f64>> {
+        self.measurement
+            .as_ref()
+            .map(|msr| &msr.h_tilde * &self.covar_bar * msr.h_tilde.transpose() + &msr.noise)
+    }
+
+    /// Returns whether the state deviation is within the provided number of standard deviations of the covariance
+    pub fn within_sigma(&self, sigma: f64) -> bool {
+        self.state_deviation
+            .iter()
+            .zip(self.sigmas().iter())
+            .all(|(dev, sig)| dev.abs() <= sigma * sig)
+    }
+}
+
+impl fmt::Display for FilterStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.measurement {
+            Some(msr) => write!(
+                f,
+                "{}: {} measurement update from {} (ratio {:.3})",
+                self.epoch,
+                if msr.rejected { "rejected" } else { "accepted" },
+                msr.device,
+                msr.ratio
+            ),
+            None => write!(f, "{}: time update", self.epoch),
+        }
+    }
+}
+
+/// All of the steps of a filter during an orbit determination process, for debugging it after the fact, e.g. when it
+/// diverged mid-arc.
+///
+/// Replays are recorded by an orbit determination process built `with_replay`, stored in and loaded from Parquet files
+/// with one row per step, and walked through with a `ReplayCursor`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FilterReplay {
+    pub steps: Vec<FilterStep>,
+}
+
+impl FilterReplay {
+    /// Number of steps in this replay
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns whether no step was recorded
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Returns a cursor on the first step of this replay
+    pub fn cursor(&self) -> ReplayCursor<'_> {
+        ReplayCursor {
+            replay: self,
+            index: 0,
+        }
+    }
+
+    /// Returns the index of the last step at or before the provided epoch
+    pub fn index_at(&self, epoch: Epoch) -> Option<usize> {
+        self.steps
+            .partition_point(|step| step.epoch <= epoch)
+            .checked_sub(1)
+    }
+
+    /// Returns the index of the first step whose state deviation is outside of the provided number of standard deviations
+    pub fn first_outside_sigma(&self, sigma: f64) -> Option<usize> {
+        self.steps.iter().position(|step| !step.within_sigma(sigma))
+    }
+
+    /// Stores this replay in a Parquet file, one row per step.
+    #[cfg(feature = "io")]
+    pub fn to_parquet<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf, Box<dyn Error>> {
+        let first = self
+            .steps
+            .first()
+            .ok_or_else(|| NyxError::CustomError("No filter step to export".to_string()))?;
+        let n = first.nominal_state.len();
+        let m = self
+            .steps
+            .iter()
+            .find_map(|step| step.measurement.as_ref().map(|msr| msr.real_obs.len()))
+            .unwrap_or(0);
+
+        let mut hdrs = vec![
+            Field::new("Epoch:Gregorian UTC", DataType::Utf8, false),
+            Field::new("Epoch:Gregorian TAI", DataType::Utf8, false),
+            Field::new("Epoch:TAI (s)", DataType::Float64, false),
+            Field::new("Device", DataType::Utf8, true),
+            Field::new("Rejected", DataType::Boolean, true),
+            Field::new("Residual ratio", DataType::Float64, true),
+        ];
+        for (name, rows, cols) in Self::state_columns(n) {
+            for field in Self::matrix_fields(name, rows, cols) {
+                hdrs.push(Field::new(field, DataType::Float64, false));
+            }
+        }
+        for (name, rows, cols) in Self::msr_columns(n, m) {
+            for field in Self::matrix_fields(name, rows, cols) {
+                hdrs.push(Field::new(field, DataType::Float64, true));
+            }
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let mut record: Vec<Arc<dyn Array>> = Vec::new();
+
+        let mut utc_epoch = StringBuilder::new();
+        let mut tai_epoch = StringBuilder::new();
+        let mut tai_s = Float64Builder::new();
+        let mut device = StringBuilder::new();
+        let mut rejected = BooleanBuilder::new();
+        let mut ratio = Float64Builder::new();
+        for step in &self.steps {
+            if step.nominal_state.len() != n {
+                return Err(Box::new(NyxError::CustomError(format!(
+                    "filter step at {} has {} states but {n} are estimated",
+                    step.epoch,
+                    step.nominal_state.len()
+                ))));
+            }
+            utc_epoch.append_value(format!("{}", step.epoch));
+            tai_epoch.append_value(format!("{:x}", step.epoch));
+            tai_s.append_value(step.epoch.to_tai_seconds());
+            device.append_option(step.measurement.as_ref().map(|msr| msr.device.clone()));
+            rejected.append_option(step.measurement.as_ref().map(|msr| msr.rejected));
+            ratio.append_option(step.measurement.as_ref().map(|msr| msr.ratio));
+        }
+        record.push(Arc::new(utc_epoch.finish()));
+        record.push(Arc::new(tai_epoch.finish()));
+        record.push(Arc::new(tai_s.finish()));
+        record.push(Arc::new(device.finish()));
+        record.push(Arc::new(rejected.finish()));
+        record.push(Arc::new(ratio.finish()));
+
+        for (name, rows, cols) in Self::state_columns(n) {
+            for j in 0..cols {
+                for i in 0..rows {
+                    let mut data = Float64Builder::new();
+                    for step in &self.steps {
+                        data.append_value(Self::state_matrix(step, name)[j * rows + i]);
+                    }
+                    record.push(Arc::new(data.finish()));
+                }
+            }
+        }
+
+        for (name, rows, cols) in Self::msr_columns(n, m) {
+            for j in 0..cols {
+                for i in 0..rows {
+                    let mut data = Float64Builder::new();
+                    for step in &self.steps {
+                        data.append_option(
+                            step.measurement
+                                .as_ref()
+                                .map(|msr| Self::msr_matrix(msr, name)[j * rows + i]),
+                        );
+                    }
+                    record.push(Arc::new(data.finish()));
+                }
+            }
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert("Purpose".to_string(), "Filter replay".to_string());
+        metadata.insert(STATE_SIZE_KEY.to_string(), format!("{n}"));
+        metadata.insert(MSR_SIZE_KEY.to_string(), format!("{m}"));
+
+        let props = pq_writer(Some(metadata), None);
+
+        let path_buf = path.as_ref().to_path_buf();
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!(
+            "{} filter step(s) written to {}",
+            self.steps.len(),
+            path_buf.display()
+        );
+
+        Ok(path_buf)
+    }
+
+    /// Loads a replay from a Parquet file written by `to_parquet`.
+    #[cfg(feature = "io")]
+    pub fn from_parquet<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(&path)?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+
+        let mut metadata = HashMap::new();
+        if let Some(file_metadata) = builder.metadata().file_metadata().key_value_metadata() {
+            for key_value in file_metadata {
+                if let Some(value) = &key_value.value {
+                    metadata.insert(key_value.key.clone(), value.clone());
+                }
+            }
+        }
+
+        let size_of = |key: &str| -> Result<usize, NyxError> {
+            metadata
+                .get(key)
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| {
+                    NyxError::FileUnreadable(format!("Missing `{key}` in file metadata"))
+                })
+        };
+        let n = size_of(STATE_SIZE_KEY)?;
+        let m = size_of(MSR_SIZE_KEY)?;
+
+        let reader = builder.build()?;
+
+        let mut steps = Vec::new();
+
+        for maybe_batch in reader {
+            let batch = maybe_batch?;
+
+            let column = |name: &str| -> Result<&Float64Array, NyxError> {
+                batch
+                    .column_by_name(name)
+                    .and_then(|col| col.as_any().downcast_ref::<Float64Array>())
+                    .ok_or_else(|| NyxError::FileUnreadable(format!("Missing `{name}` field")))
+            };
+
+            let epochs = batch
+                .column_by_name("Epoch:Gregorian TAI")
+                .and_then(|col| col.as_any().downcast_ref::<StringArray>())
+                .ok_or_else(|| {
+                    NyxError::FileUnreadable("Missing `Epoch:Gregorian TAI` field".to_string())
+                })?;
+            let devices = batch
+                .column_by_name("Device")
+                .and_then(|col| col.as_any().downcast_ref::<StringArray>())
+                .ok_or_else(|| NyxError::FileUnreadable("Missing `Device` field".to_string()))?;
+            let rejected = batch
+                .column_by_name("Rejected")
+                .and_then(|col| col.as_any().downcast_ref::<BooleanArray>())
+                .ok_or_else(|| NyxError::FileUnreadable("Missing `Rejected` field".to_string()))?;
+            let ratios = column("Residual ratio")?;
+
+            // Columns of each matrix, in column major order
+            let mut state_cols = Vec::new();
+            for (name, rows, cols) in Self::state_columns(n) {
+                let mut matrix_cols = Vec::new();
+                for field in Self::matrix_fields(name, rows, cols) {
+                    matrix_cols.push(column(&field)?);
+                }
+                state_cols.push((rows, cols, matrix_cols));
+            }
+            let mut msr_cols = Vec::new();
+            for (name, rows, cols) in Self::msr_columns(n, m) {
+                let mut matrix_cols = Vec::new();
+                for field in Self::matrix_fields(name, rows, cols) {
+                    matrix_cols.push(column(&field)?);
+                }
+                msr_cols.push((rows, cols, matrix_cols));
+            }
+
+            for row in 0..batch.num_rows() {
+                let read = |(rows, cols, matrix_cols): &(usize, usize, Vec<&Float64Array>)| {
+                    DMatrix::from_iterator(
+                        *rows,
+                        *cols,
+                        matrix_cols.iter().map(|col| col.value(row)),
+                    )
+                };
+                let state = state_cols.iter().map(read).collect::<Vec<_>>();
+
+                let measurement = if devices.is_null(row) {
+                    None
+                } else {
+                    let msr = msr_cols.iter().map(read).collect::<Vec<_>>();
+                    Some(MeasurementStep {
+                        device: devices.value(row).to_string(),
+                        real_obs: msr[0].column(0).into_owned(),
+                        computed_obs: msr[1].column(0).into_owned(),
+                        h_tilde: msr[2].clone(),
+                        noise: msr[3].clone(),
+                        gain: msr[4].clone(),
+                        prefit: msr[5].column(0).into_owned(),
+                        postfit: msr[6].column(0).into_owned(),
+                        ratio: ratios.value(row),
+                        rejected: rejected.value(row),
+                    })
+                };
+
+                steps.push(FilterStep {
+                    epoch: Epoch::from_str(epochs.value(row))?,
+                    nominal_state: state[0].column(0).into_owned(),
+                    prior_deviation: state[1].column(0).into_owned(),
+                    covar_bar: state[2].clone(),
+                    state_deviation: state[3].column(0).into_owned(),
+                    covar: state[4].clone(),
+                    measurement,
+                });
+            }
+        }
+
+        Ok(Self { steps })
+    }
+
+    /// Names and shapes of the vectors and matrices of every step, in the order of `state_matrix`
+    #[cfg(feature = "io")]
+    fn state_columns(n: usize) -> [(&'static str, usize, usize); 5] {
+        [
+            ("nominal", n, 1),
+            ("prior deviation", n, 1),
+            ("covar_bar", n, n),
+            ("deviation", n, 1),
+            ("covar", n, n),
+        ]
+    }
+
+    /// Names and shapes of the vectors and matrices of measurement updates, in the order of `msr_matrix`
+    #[cfg(feature = "io")]
+    fn msr_columns(n: usize, m: usize) -> [(&'static str, usize, usize); 7] {
+        [
+            ("real obs", m, 1),
+            ("computed obs", m, 1),
+            ("H", m, n),
+            ("R", m, m),
+            ("gain", n, m),
+            ("prefit", m, 1),
+            ("postfit", m, 1),
+        ]
+    }
+
+    #[cfg(feature = "io")]
+    fn state_matrix<'s>(step: &'s FilterStep, name: &str) -> &'s [f64] {
+        match name {
+            "nominal" => step.nominal_state.as_slice(),
+            "prior deviation" => step.prior_deviation.as_slice(),
+            "covar_bar" => step.covar_bar.as_slice(),
+            "deviation" => step.state_deviation.as_slice(),
+            _ => step.covar.as_slice(),
+        }
+    }
+
+    #[cfg(feature = "io")]
+    fn msr_matrix<'s>(msr: &'s MeasurementStep, name: &str) -> &'s [f64] {
+        match name {
+            "real obs" => msr.real_obs.as_slice(),
+            "computed obs" => msr.computed_obs.as_slice(),
+            "H" => msr.h_tilde.as_slice(),
+            "R" => msr.noise.as_slice(),
+            "gain" => msr.gain.as_slice(),
+            "prefit" => msr.prefit.as_slice(),
+            _ => msr.postfit.as_slice(),
+        }
+    }
+
+    /// Field names of the provided matrix, in column major order
+    #[cfg(feature = "io")]
+    fn matrix_fields(name: &str, rows: usize, cols: usize) -> Vec<String> {
+        (0..cols)
+            .flat_map(|j| {
+                (0..rows).map(move |i| {
+                    if cols == 1 {
+                        format!("{name}[{i}]")
+                    } else {
+                        format!("{name}[{i},{j}]")
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// A cursor to step through a filter replay, forward or backward.
+#[derive(Copy, Clone, Debug)]
+pub struct ReplayCursor<'a> {
+    replay: &'a FilterReplay,
+    index: usize,
+}
+
+impl<'a> ReplayCursor<'a> {
+    /// Index of the current step
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the current step, if the replay is not empty
+    pub fn current(&self) -> Option<&'a FilterStep> {
+        self.replay.steps.get(self.index)
+    }
+
+    /// Moves to the next step and returns it, or returns None if at the last step
+    pub fn step_forward(&mut self) -> Option<&'a FilterStep> {
+        if self.index + 1 < self.replay.len() {
+            self.index += 1;
+            self.current()
+        } else {
+            None
+        }
+    }
+
+    /// Moves to the previous step and returns it, or returns None if at the first step
+    pub fn step_back(&mut self) -> Option<&'a FilterStep> {
+        if self.index > 0 {
+            self.index -= 1;
+            self.current()
+        } else {
+            None
+        }
+    }
+
+    /// Moves to the next measurement update and returns it, or returns None (without moving) if there are none left
+    pub fn next_measurement(&mut self) -> Option<&'a FilterStep> {
+        let offset = self.replay.steps[(self.index + 1).min(self.replay.len())..]
+            .iter()
+            .position(|step| step.is_measurement_update())?;
+        self.index += offset + 1;
+        self.current()
+    }
+
+    /// Moves to the last step at or before the provided epoch and returns it, or returns None (without moving) if all
+    /// steps are after that epoch
+    pub fn seek(&mut self, epoch: Epoch) -> Option<&'a FilterStep> {
+        self.index = self.replay.index_at(epoch)?;
+        self.current()
+    }
+}
@@ -0,0 +1,192 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::dynamics::Dynamics;
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, DimName, OMatrix, OVector};
+use crate::md::trajectory::Interpolatable;
+use crate::od::estimate::Estimate;
+use crate::od::{Filter, Measurement};
+use crate::propagators::error_ctrl::ErrorCtrl;
+use crate::State;
+use std::ops::Add;
+
+use super::{EstimateFrom, ODProcess};
+
+/// Results of the fixed-interval smoother, indexed like the estimates of the forward filter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SmootherResults<S: State, E: Estimate<S>>
+where
+    DefaultAllocator: Allocator<f64, <S as State>::Size>
+        + Allocator<f64, <S as State>::Size, <S as State>::Size>
+        + Allocator<f64, <S as State>::VecLength>,
+{
+    /// Smoothed estimates, in chronological order
+    pub estimates: Vec<E>,
+    /// Smoothed cross-covariance between each estimate and the next one, i.e. P_{k,k+1|N} at index k
+    pub cross_covariances: Vec<OMatrix<f64, <S as State>::Size, <S as State>::Size>>,
+    /// Filter-smoother consistency statistic of each component of each estimate: the difference between the smoothed and
+    /// the filtered state divided by the square root of the difference of their variances. These follow a unit normal
+    /// distribution if the filter is consistent.
+    pub consistency: Vec<OVector<f64, <S as State>::Size>>,
+}
+
+impl<S: State, E: Estimate<S>> SmootherResults<S, E>
+where
+    DefaultAllocator: Allocator<f64, <S as State>::Size>
+        + Allocator<f64, <S as State>::Size, <S as State>::Size>
+        + Allocator<f64, <S as State>::VecLength>,
+{
+    /// Largest filter-smoother consistency statistic (in absolute value) over all of the estimates
+    pub fn max_consistency(&self) -> f64 {
+        self.consistency
+            .iter()
+            .map(|stats| stats.amax())
+            .fold(0.0, f64::max)
+    }
+
+    /// Number of estimates with at least one component whose consistency statistic exceeds the provided threshold,
+    /// e.g. 3.0 for a three sigma test.
+    pub fn num_inconsistent(&self, threshold: f64) -> usize {
+        self.consistency
+            .iter()
+            .filter(|stats| stats.amax() > threshold)
+            .count()
+    }
+}
+
+impl<
+        'a,
+        D: Dynamics,
+        E: ErrorCtrl,
+        Msr: Measurement,
+        A: DimName,
+        S: EstimateFrom<D::StateType, Msr> + Interpolatable,
+        K: Filter<S, A, Msr::MeasurementSize>,
+    > ODProcess<'a, D, E, Msr, A, S, K>
+where
+    D::StateType: Interpolatable + Add<OVector<f64, <S as State>::Size>, Output = D::StateType>,
+    <DefaultAllocator as Allocator<f64, <D::StateType as State>::VecLength>>::Buffer: Send,
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, Msr::MeasurementSize>
+        + Allocator<f64, Msr::MeasurementSize, S::Size>
+        + Allocator<f64, S::Size>
+        + Allocator<usize, S::Size, S::Size>
+        + Allocator<f64, Msr::MeasurementSize, Msr::MeasurementSize>
+        + Allocator<f64, Msr::MeasurementSize, <D::StateType as State>::Size>
+        + Allocator<f64, Msr::MeasurementSize, <S as State>::Size>
+        + Allocator<f64, <D::StateType as State>::Size, Msr::MeasurementSize>
+        + Allocator<f64, <S as State>::Size, Msr::MeasurementSize>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, A>
+        + Allocator<f64, A, A>
+        + Allocator<f64, <D::StateType as State>::Size, A>
+        + Allocator<f64, A, <D::StateType as State>::Size>
+        + Allocator<f64, <S as State>::Size>
+        + Allocator<f64, <S as State>::VecLength>
+        + Allocator<f64, <S as State>::Size, <S as State>::Size>
+        + Allocator<f64, <S as State>::Size, A>
+        + Allocator<f64, A, <S as State>::Size>,
+{
+    /// Fixed-interval (Rauch-Tung-Striebel) smoother over all of the estimates of the forward filter.
+    ///
+    /// Each estimate is smoothed with the smoothed estimate which follows it, using the covariance, STM and prior
+    /// (predicted) state deviation stored by the filter, so the process noise and EKF updates of the reference
+    /// trajectory are accounted for. This also returns the smoothed cross-covariance between consecutive estimates and
+    /// the filter-smoother consistency statistics.
+    pub fn smooth_rts(&self) -> Result<SmootherResults<S, K::Estimate>, NyxError> {
+        let num = self.estimates.len();
+        if num == 0 {
+            return Err(NyxError::CustomError(
+                "No data: run the ODProcess before smoothing it.".to_string(),
+            ));
+        } else if self.prior_deviations.len() != num {
+            return Err(NyxError::CustomError(
+                "Estimates were modified after the ODProcess run, cannot smooth them.".to_string(),
+            ));
+        }
+
+        info!("RTS smoothing {num} estimates");
+
+        // The last estimate is already smoothed
+        let mut estimates = self.estimates.clone();
+        let mut cross_covariances = vec![OMatrix::<f64, S::Size, S::Size>::zeros(); num - 1];
+        let mut consistency = vec![OVector::<f64, S::Size>::zeros(); num];
+
+        for k in (0..num - 1).rev() {
+            let est_k = &self.estimates[k];
+            let est_kp1 = &self.estimates[k + 1];
+
+            // The STM stored in the k+1 estimate maps from k to k+1
+            let covar_bar_kp1 = est_kp1.predicted_covar();
+            let covar_bar_inv = match covar_bar_kp1.clone().cholesky() {
+                Some(chol) => chol.inverse(),
+                None => covar_bar_kp1
+                    .clone()
+                    .try_inverse()
+                    .ok_or(NyxError::SingularCovarianceMatrix)?,
+            };
+            let covar_k = est_k.covar();
+            let gain = &covar_k * est_kp1.stm().transpose() * covar_bar_inv;
+
+            // Both the smoothed and the prior deviations at k+1 are relative to the same nominal state
+            let dev_kp1 = estimates[k + 1].state_deviation();
+            let covar_kp1 = estimates[k + 1].covar();
+            let dev_k = est_k.state_deviation();
+            let smoothed_dev_k = &dev_k + &gain * (dev_kp1 - &self.prior_deviations[k + 1]);
+            let smoothed_covar_k =
+                &covar_k + &gain * (&covar_kp1 - covar_bar_kp1) * gain.transpose();
+            // Enforce the symmetry of the smoothed covariance
+            let smoothed_covar_k = (&smoothed_covar_k + smoothed_covar_k.transpose()) * 0.5;
+
+            cross_covariances[k] = &gain * covar_kp1;
+
+            // The smoothed covariance is smaller than the filter covariance, so their difference is the variance of the
+            // difference between the smoothed and the filtered states.
+            let var_diff = covar_k.diagonal() - smoothed_covar_k.diagonal();
+            consistency[k] = (&smoothed_dev_k - dev_k).zip_map(&var_diff, |delta, var| {
+                if var > 0.0 {
+                    delta / var.sqrt()
+                } else {
+                    0.0
+                }
+            });
+
+            estimates[k].set_state_deviation(smoothed_dev_k);
+            estimates[k].set_covar(smoothed_covar_k);
+        }
+
+        let results = SmootherResults {
+            estimates,
+            cross_covariances,
+            consistency,
+        };
+
+        info!(
+            "Smoothed {num} estimates (from {} to {}), max filter-smoother consistency of {:.3}",
+            results.estimates[0].epoch(),
+            results.estimates[num - 1].epoch(),
+            results.max_consistency()
+        );
+
+        Ok(results)
+    }
+}
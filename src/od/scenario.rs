@@ -0,0 +1,303 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::arc_selection::{EditList, MediaEditConfig};
+use super::estimate::{Estimate, KfEstimate, Residual};
+use super::filter::kalman::KF;
+use super::msr::{RangeDoppler, TrackingArc};
+use super::process::{EkfTrigger, FltResid, ODProcess, SmoothingArc};
+use super::simulator::{TrackingArcSim, TrkConfig};
+use super::snc::SNC3;
+use super::GroundStation;
+use crate::cosmic::{Cosm, Orbit, Spacecraft};
+use crate::dynamics::SpacecraftDynamics;
+use crate::errors::NyxError;
+use crate::io::dynamics::DynamicsSerde;
+use crate::io::estimate::OrbitEstimateSerde;
+use crate::io::{duration_from_str, duration_to_str, ConfigRepr, Configurable};
+use crate::linalg::{Matrix2, Vector2, U2};
+#[cfg(feature = "io")]
+use crate::md::trajectory::Traj;
+use crate::md::ScTraj;
+use crate::propagators::Propagator;
+use crate::time::Duration;
+use crate::State;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Configuration of the switch from a conventional to an extended Kalman filter
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EkfConfig {
+    /// Number of measurements to process before switching to the EKF
+    pub num_msrs: usize,
+    /// Switch back to the conventional filter if there are no measurements for this long
+    #[serde(
+        serialize_with = "duration_to_str",
+        deserialize_with = "duration_from_str"
+    )]
+    pub disable_time: Duration,
+}
+
+/// Configuration of the state noise compensation of the filter
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SncConfig {
+    /// Standard deviation of the unmodeled accelerations on each axis, in km/s^2
+    pub sigma_km_s2: f64,
+    /// Do not apply the process noise if there are no measurements for this long
+    #[serde(
+        serialize_with = "duration_to_str",
+        deserialize_with = "duration_from_str"
+    )]
+    pub disable_time: Duration,
+}
+
+/// Configuration of the filter of an orbit determination scenario
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FilterConfig {
+    /// Initial estimate of the orbit, whose covariance must be in Cartesian form
+    pub initial_estimate: OrbitEstimateSerde,
+    /// Standard deviation of the range measurements expected by the filter, in km
+    pub range_sigma_km: f64,
+    /// Standard deviation of the Doppler measurements expected by the filter, in km/s
+    pub doppler_sigma_km_s: f64,
+    /// Dynamics of the filter, defaults to the truth dynamics
+    pub dynamics: Option<DynamicsSerde>,
+    /// Switch to an extended Kalman filter after a number of measurements
+    pub ekf: Option<EkfConfig>,
+    /// State noise compensation
+    pub snc: Option<SncConfig>,
+    /// Residual rejection criteria
+    pub resid_reject: Option<FltResid>,
+    /// Edit the measurements taken at low elevation or near solar conjunction before processing them
+    #[serde(default)]
+    pub media_edit: Option<MediaEditConfig>,
+    /// Set to true to smooth the estimates once all of the measurements are processed
+    #[serde(default)]
+    pub smooth: bool,
+}
+
+/// A complete orbit determination scenario: truth dynamics, tracking network, filter setup and error models.
+///
+/// The error models of the measurements are those of each ground station.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ODScenario {
+    /// Initial truth state of the spacecraft
+    pub spacecraft: Spacecraft,
+    /// Truth dynamics
+    pub dynamics: DynamicsSerde,
+    /// Duration of the scenario
+    #[serde(
+        serialize_with = "duration_to_str",
+        deserialize_with = "duration_from_str"
+    )]
+    pub duration: Duration,
+    /// Ground stations of the tracking network
+    pub stations: Vec<GroundStation>,
+    /// Tracking configuration of each ground station, by name
+    pub tracking: HashMap<String, TrkConfig>,
+    /// Filter setup
+    pub filter: FilterConfig,
+    /// Seed of the measurement noise, if unset the noise is different on every run
+    pub seed: Option<u64>,
+    /// Directory where to export the results, if set (requires the `io` feature)
+    pub output_dir: Option<PathBuf>,
+}
+
+impl ConfigRepr for ODScenario {}
+
+/// All of the products of an orbit determination scenario
+pub struct ODScenarioResults {
+    /// Truth trajectory
+    pub truth: ScTraj,
+    /// Simulated tracking arc, including the edited measurements
+    pub arc: TrackingArc<RangeDoppler>,
+    /// Measurements edited before the processing, empty if the media editing is disabled
+    pub edits: EditList,
+    /// Filter estimates
+    pub estimates: Vec<KfEstimate<Orbit>>,
+    /// Filter residuals, None for time updates
+    pub residuals: Vec<Option<Residual<U2>>>,
+    /// Smoothed estimates, if smoothing was requested
+    pub smoothed: Option<Vec<KfEstimate<Orbit>>>,
+    /// Paths of the exported files
+    pub exported: Vec<PathBuf>,
+}
+
+impl ODScenarioResults {
+    /// Returns the position (km) and velocity (km/s) errors of the last estimate with respect to the truth
+    pub fn final_errors(&self) -> Result<(f64, f64), NyxError> {
+        let est = self.estimates.last().ok_or_else(|| {
+            NyxError::CustomError("no estimates in orbit determination results".to_string())
+        })?;
+        let truth = self.truth.at(est.epoch())?.orbit;
+        let delta = est.state().to_cartesian_vec() - truth.to_cartesian_vec();
+        Ok((
+            delta.fixed_rows::<3>(0).norm(),
+            delta.fixed_rows::<3>(3).norm(),
+        ))
+    }
+}
+
+/// Runs the provided orbit determination scenario end to end: truth propagation, measurement simulation, filtering, optional smoothing, and export of the results.
+pub fn simulate_od_scenario(
+    scenario: ODScenario,
+    cosm: Arc<Cosm>,
+) -> Result<ODScenarioResults, NyxError> {
+    #[cfg(not(feature = "io"))]
+    if scenario.output_dir.is_some() {
+        return Err(NyxError::CustomError(
+            "exporting the results requires the `io` feature".to_string(),
+        ));
+    }
+
+    let filter = scenario.filter;
+
+    // Truth propagation
+    let truth_dynamics = SpacecraftDynamics::from_config(scenario.dynamics, cosm.clone())?;
+    let mut truth_init = scenario.spacecraft;
+    truth_init.unset_stm();
+    truth_init.orbit.stm = None;
+
+    let (_, truth) = Propagator::default(truth_dynamics.clone())
+        .with(truth_init)
+        .for_duration_with_traj(scenario.duration)?;
+
+    // Measurement simulation
+    let mut arc_sim = match scenario.seed {
+        Some(seed) => {
+            TrackingArcSim::with_seed(scenario.stations, truth.clone(), scenario.tracking, seed)?
+        }
+        None => TrackingArcSim::new(scenario.stations, truth.clone(), scenario.tracking)?,
+    };
+    arc_sim.disallow_overlap();
+    let arc = arc_sim.generate_measurements(cosm.clone())?;
+
+    if arc.measurements.len() < 2 {
+        return Err(NyxError::CustomError(format!(
+            "scenario only generated {} measurement(s)",
+            arc.measurements.len()
+        )));
+    }
+
+    // Filter setup
+    let filter_dynamics = match filter.dynamics {
+        Some(dynamics) => SpacecraftDynamics::from_config(dynamics, cosm.clone())?,
+        None => truth_dynamics,
+    };
+
+    let nominal = Orbit::from(filter.initial_estimate.nominal);
+    let initial_estimate = KfEstimate::from_covar(
+        nominal.with_stm(),
+        filter.initial_estimate.covar.to_matrix(),
+    );
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(
+        filter.range_sigma_km.powi(2),
+        filter.doppler_sigma_km_s.powi(2),
+    ));
+
+    let sncs = match filter.snc {
+        Some(snc) => vec![SNC3::from_diagonal(
+            snc.disable_time,
+            &[snc.sigma_km_s2.powi(2); 3],
+        )],
+        None => Vec::new(),
+    };
+    let kf = KF::with_sncs(initial_estimate, sncs, measurement_noise);
+
+    let setup = Propagator::default(filter_dynamics);
+    let prop_est = setup.with(truth_init.with_orbit(nominal).with_stm());
+
+    let mut odp = match filter.ekf {
+        Some(ekf) => ODProcess::ekf(
+            prop_est,
+            kf,
+            EkfTrigger::new(ekf.num_msrs, ekf.disable_time),
+            filter.resid_reject,
+            cosm.clone(),
+        ),
+        None => ODProcess::ckf(prop_est, kf, filter.resid_reject, cosm.clone()),
+    };
+
+    let edits = match filter.media_edit {
+        Some(media_edit) => media_edit.analyze(&arc, &arc_sim.devices, &truth, &cosm)?,
+        None => EditList::default(),
+    };
+
+    if edits.is_empty() {
+        odp.process_arc::<GroundStation>(&arc)?;
+    } else {
+        info!("{edits}");
+        odp.process_arc::<GroundStation>(&edits.apply(&arc))?;
+    }
+
+    let smoothed = if filter.smooth {
+        Some(odp.smooth(SmoothingArc::All)?)
+    } else {
+        None
+    };
+
+    // Export
+    #[allow(unused_mut)]
+    let mut exported = Vec::new();
+    #[cfg(feature = "io")]
+    if let Some(dir) = &scenario.output_dir {
+        let export_err = |e: Box<dyn std::error::Error>| NyxError::CustomError(e.to_string());
+        exported.push(
+            truth
+                .to_parquet_simple(dir.join("truth.parquet"))
+                .map_err(export_err)?,
+        );
+        exported.push(
+            arc.to_parquet_simple(dir.join("msr_arc.parquet"))
+                .map_err(export_err)?,
+        );
+        exported.push(
+            odp.to_parquet(dir.join("od_results.parquet"), Default::default())
+                .map_err(export_err)?,
+        );
+        if let Some(smoothed) = &smoothed {
+            let mut smoothed_traj = Traj::new();
+            smoothed_traj.states = smoothed.iter().map(|est| est.state()).collect();
+            smoothed_traj.finalize();
+            exported.push(
+                smoothed_traj
+                    .to_parquet_simple(dir.join("od_smoothed.parquet"))
+                    .map_err(export_err)?,
+            );
+        }
+    }
+
+    Ok(ODScenarioResults {
+        truth,
+        arc,
+        edits,
+        estimates: odp.estimates,
+        residuals: odp.residuals,
+        smoothed,
+        exported,
+    })
+}
+
+impl ODScenario {
+    /// Loads the scenario from the provided YAML file and runs it, cf. `simulate_od_scenario`.
+    pub fn run<P: AsRef<Path>>(path: P, cosm: Arc<Cosm>) -> Result<ODScenarioResults, NyxError> {
+        simulate_od_scenario(Self::load(path)?, cosm)
+    }
+}
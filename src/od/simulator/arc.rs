@@ -25,13 +25,14 @@ pub use crate::dynamics::{Dynamics, NyxError};
 use crate::io::ConfigError;
 use crate::md::trajectory::Interpolatable;
 use crate::od::msr::TrackingArc;
-use crate::od::simulator::{Availability, Schedule};
+use crate::od::simulator::{Availability, Schedule, Visibility};
 use crate::od::Measurement;
 pub use crate::{cosmic::Cosm, State, TimeTagged};
 use crate::{linalg::allocator::Allocator, od::TrackingDeviceSim};
 use crate::{linalg::DefaultAllocator, md::prelude::Traj};
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use std::iter::Peekable;
 use std::marker::PhantomData;
 use std::sync::Arc;
 
@@ -61,8 +62,13 @@ where
     pub allow_overlap: bool,
     /// Random number generator used for this tracking arc, ensures repeatability
     rng: Pcg64Mcg,
-    /// Greatest common denominator time series that allows this arc to meet all of the conditions.
-    time_series: TimeSeries,
+    /// Greatest common denominator time series that allows this arc to meet all of the conditions of the devices without a fixed
+    /// schedule, if any.
+    time_series: Option<TimeSeries>,
+    /// Step of the time series, also used to sample the trajectory when computing the visibility intervals.
+    step: Duration,
+    /// Visibility intervals of each device, computed once and shared between measurement generation and pass reports.
+    visibility: HashMap<String, Visibility>,
     _msr_in: PhantomData<MsrIn>,
     _msr: PhantomData<Msr>,
 }
@@ -89,10 +95,14 @@ where
         // We don't care if there are more configurations than chosen devices.
         let mut devices_map = HashMap::new();
         let mut sampling_rates_ns = Vec::with_capacity(devices.len());
+        let mut unscheduled_rates_ns = Vec::with_capacity(devices.len());
         for device in devices {
             if let Some(cfg) = configs.get(&device.name()) {
                 cfg.sanity_check()?;
                 sampling_rates_ns.push(cfg.sampling.truncated_nanoseconds());
+                if cfg.sampling_reference.is_none() {
+                    unscheduled_rates_ns.push(cfg.sampling.truncated_nanoseconds());
+                }
             } else {
                 return Err(ConfigError::InvalidConfig(format!(
                     "device {} has no associated configuration",
@@ -106,13 +116,21 @@ where
             .iter()
             .fold(sampling_rates_ns[0], |a, &b| gcd(a, b));
 
-        // The overall time series is the one going from the start to the end of the trajectory with the smallest time step
-        // of all the tracking configurations.
-        let time_series = TimeSeries::inclusive(
-            trajectory.first().epoch(),
-            trajectory.last().epoch(),
-            Duration::from_truncated_nanoseconds(common_sampling_rate_ns),
-        );
+        // The visibility of all devices is computed with the smallest time step of all the tracking configurations.
+        let step = Duration::from_truncated_nanoseconds(common_sampling_rate_ns);
+
+        // The overall time series of the devices without a fixed schedule is the one going from the start to the end of the
+        // trajectory with the smallest time step of their tracking configurations.
+        let time_series = unscheduled_rates_ns.first().map(|&first_rate_ns| {
+            let rate_ns = unscheduled_rates_ns
+                .iter()
+                .fold(first_rate_ns, |a, &b| gcd(a, b));
+            TimeSeries::inclusive(
+                trajectory.first().epoch(),
+                trajectory.last().epoch(),
+                Duration::from_truncated_nanoseconds(rate_ns),
+            )
+        });
 
         let me = Self {
             devices: devices_map,
@@ -121,6 +139,8 @@ where
             allow_overlap: false,
             rng,
             time_series,
+            step,
+            visibility: HashMap::new(),
             _msr_in: PhantomData,
             _msr: PhantomData,
         };
@@ -161,11 +181,50 @@ where
         self.allow_overlap = true;
     }
 
+    /// Returns the visibility intervals of the trajectory from each device that has a visibility constraint.
+    ///
+    /// These are computed on the first call (or when a device is added) and reused afterwards, including by the measurement generation.
+    pub fn visibility(
+        &mut self,
+        cosm: Arc<Cosm>,
+    ) -> Result<&HashMap<String, Visibility>, NyxError> {
+        for (name, device) in &self.devices {
+            if !self.visibility.contains_key(name) {
+                if let Some(visibility) =
+                    device.visibility(&self.trajectory, self.step, cosm.clone())?
+                {
+                    info!(
+                        "{name} has {} passes for a total of {}",
+                        visibility.len(),
+                        visibility.total_duration()
+                    );
+                    self.visibility.insert(name.clone(), visibility);
+                }
+            }
+        }
+
+        Ok(&self.visibility)
+    }
+
     /// Generates measurements from the simulated tracking arc.
     ///
     /// Notes:
     /// Although mutable, this function may be called several times to generate different measurements.
     pub fn generate_measurements(&mut self, cosm: Arc<Cosm>) -> Result<TrackingArc<Msr>, NyxError> {
+        self.generate(cosm, true)
+    }
+
+    /// Generates measurements from the simulated tracking arc without any noise, i.e. the exact measurements of the trajectory.
+    /// This follows the same schedule as `generate_measurements`, and is meant for covariance analyses which only depend on the
+    /// tracking geometry.
+    pub fn generate_noiseless_measurements(
+        &mut self,
+        cosm: Arc<Cosm>,
+    ) -> Result<TrackingArc<Msr>, NyxError> {
+        self.generate(cosm, false)
+    }
+
+    fn generate(&mut self, cosm: Arc<Cosm>, noisy: bool) -> Result<TrackingArc<Msr>, NyxError> {
         // Stores the first measurement and last measurement of a given sub-arc for each device.
         #[derive(Copy, Clone, Debug)]
         struct ScheduleData {
@@ -182,11 +241,57 @@ where
 
         let start = Epoch::now().unwrap();
         let mut measurements = Vec::new();
-        // Clone the time series so we don't consume it.
-        let ts = self.time_series.clone();
-        'ts: for epoch in ts {
+        // Compute the visibility intervals once so that we only attempt measurements when the object may be visible.
+        self.visibility(cosm.clone())?;
+        // The measurement epochs are those of the common time series (cloned so we don't consume it), merged with the fixed
+        // schedule of each device which has one.
+        let (first, last) = (
+            self.trajectory.first().epoch(),
+            self.trajectory.last().epoch(),
+        );
+        let mut epoch_sources: Vec<(Option<&String>, Peekable<TimeSeries>)> = self
+            .time_series
+            .clone()
+            .map(|ts| (None, ts.peekable()))
+            .into_iter()
+            .chain(
+                self.configs
+                    .iter()
+                    .filter(|(name, _)| self.devices.contains_key(*name))
+                    .filter_map(|(name, cfg)| {
+                        cfg.scheduled_epochs(first, last)
+                            .map(|ts| (Some(name), ts.peekable()))
+                    }),
+            )
+            .collect();
+
+        'ts: while let Some(epoch) = epoch_sources
+            .iter_mut()
+            .filter_map(|(_, ts)| ts.peek().copied())
+            .min()
+        {
+            // Devices scheduled at this epoch, and whether it is in the common time series
+            let mut scheduled = HashSet::new();
+            let mut common = false;
+            for (name, ts) in epoch_sources.iter_mut() {
+                if ts.next_if_eq(&epoch).is_some() {
+                    match name {
+                        Some(name) => {
+                            scheduled.insert(name.to_string());
+                        }
+                        None => common = true,
+                    }
+                }
+            }
+
             'devices: for (name, device) in self.devices.iter_mut() {
                 let cfg = &self.configs[name];
+                // Check that this device measures at this epoch
+                if (cfg.sampling_reference.is_some() && !scheduled.contains(name))
+                    || (cfg.sampling_reference.is_none() && !common)
+                {
+                    continue;
+                }
                 // Check the start condition
                 if let Availability::Epoch(start_epoch) = cfg.start {
                     if start_epoch > epoch {
@@ -285,9 +390,20 @@ where
                 schedule_trace_msg.remove(name);
                 end_trace_msg.remove(name);
 
-                if let Some(msr) =
-                    device.measure(epoch, &self.trajectory, Some(&mut self.rng), cosm.clone())?
-                {
+                let visible = self
+                    .visibility
+                    .get(name)
+                    .map(|visibility| visibility.contains(epoch))
+                    .unwrap_or(true);
+
+                let msr = if visible {
+                    let rng = if noisy { Some(&mut self.rng) } else { None };
+                    device.measure(epoch, &self.trajectory, rng, cosm.clone())?
+                } else {
+                    None
+                };
+
+                if let Some(msr) = msr {
                     measurements.push((name.clone(), msr));
                     // We have a new measurement, let's update the schedule.
                     if let Some(device_schedule) = schedule.get_mut(name) {
@@ -367,7 +483,10 @@ where
             "Tracking Arc Simulator on {} with devices {:?} over {}",
             self.trajectory,
             self.devices.keys(),
-            self.time_series
+            match &self.time_series {
+                Some(time_series) => format!("{time_series}"),
+                None => "fixed schedules".to_string(),
+            }
         )
     }
 }
@@ -28,3 +28,5 @@ mod trkconfig;
 pub use trkconfig::{EpochRanges, TrkConfig};
 mod start_mode;
 pub use start_mode::Availability;
+mod visibility;
+pub use visibility::{Visibility, VisibilityInterval};
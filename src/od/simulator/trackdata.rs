@@ -18,17 +18,17 @@
 
 use std::sync::Arc;
 
-use hifitime::Epoch;
+use hifitime::{Duration, Epoch};
 use rand_pcg::Pcg64Mcg;
 
-use crate::linalg::DefaultAllocator;
+use crate::linalg::{DefaultAllocator, OMatrix};
 use crate::md::prelude::{Frame, Traj};
 use crate::md::trajectory::Interpolatable;
 use crate::od::Measurement;
 use crate::{io::Configurable, linalg::allocator::Allocator};
 use crate::{NyxError, Orbit};
 
-use super::Cosm;
+use super::{Cosm, Visibility};
 
 /// Tracking device simulator.
 pub trait TrackingDeviceSim<MsrIn, Msr>: Configurable
@@ -71,4 +71,27 @@ where
         rng: Option<&mut Pcg64Mcg>,
         cosm: Arc<Cosm>,
     ) -> Result<Option<Msr>, NyxError>;
+
+    /// Returns the covariance of the measurements of this device at the provided epoch, if it is known by the device, in which case
+    /// it replaces the measurement noise of the filter when processing the measurements of this device.
+    fn measurement_covariance(
+        &self,
+        _epoch: Epoch,
+    ) -> Option<OMatrix<f64, Msr::MeasurementSize, Msr::MeasurementSize>>
+    where
+        DefaultAllocator: Allocator<f64, Msr::MeasurementSize, Msr::MeasurementSize>,
+    {
+        None
+    }
+
+    /// Computes the visibility intervals of the input trajectory from this device, sampling the trajectory at the provided step.
+    /// Returns None if this device has no visibility constraint, in which case a measurement is attempted at every epoch.
+    fn visibility(
+        &self,
+        _traj: &Traj<MsrIn>,
+        _step: Duration,
+        _cosm: Arc<Cosm>,
+    ) -> Result<Option<Visibility>, NyxError> {
+        Ok(None)
+    }
 }
@@ -18,10 +18,11 @@
 
 pub use crate::dynamics::{Dynamics, NyxError};
 use crate::io::{duration_from_str, duration_to_str, epoch_from_str, epoch_to_str, ConfigError};
+use crate::io::{maybe_epoch_from_str, maybe_epoch_to_str};
 use crate::io::{ConfigRepr, Configurable};
 pub use crate::{cosmic::Cosm, State, TimeTagged};
 use hifitime::TimeUnits;
-use hifitime::{Duration, Epoch};
+use hifitime::{Duration, Epoch, TimeSeries};
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 use serde::Deserialize;
@@ -52,6 +53,15 @@ pub struct TrkConfig {
         deserialize_with = "duration_from_str"
     )]
     pub sampling: Duration,
+    /// Reference epoch of a fixed measurement schedule: if set, the measurements are only generated at this epoch plus any multiple
+    /// of the sampling (e.g. on whole minutes), regardless of the epochs of the trajectory and of the start of each pass.
+    #[serde(
+        default,
+        rename = "sampling reference",
+        serialize_with = "maybe_epoch_to_str",
+        deserialize_with = "maybe_epoch_from_str"
+    )]
+    pub sampling_reference: Option<Epoch>,
     /// List of epoch ranges to include
     #[serde(rename = "inclusion epochs")]
     pub inclusion_epochs: Option<Vec<EpochRanges>>,
@@ -89,6 +99,25 @@ impl TrkConfig {
         }
     }
 
+    /// Initialize a default TrkConfig with a fixed measurement schedule at the sample rate from the reference epoch
+    pub fn from_fixed_schedule(sampling: Duration, reference: Epoch) -> Self {
+        Self {
+            sampling,
+            sampling_reference: Some(reference),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the epochs of the fixed measurement schedule between the provided epochs (included), if any
+    pub fn scheduled_epochs(&self, start: Epoch, end: Epoch) -> Option<TimeSeries> {
+        let reference = self.sampling_reference?;
+        // First epoch of the schedule at or after the start
+        let sampling_ns = self.sampling.total_nanoseconds();
+        let num_samples = -(-(start - reference).total_nanoseconds()).div_euclid(sampling_ns);
+        let first = reference + Duration::from_total_nanoseconds(num_samples * sampling_ns);
+        Some(TimeSeries::inclusive(first, end, self.sampling))
+    }
+
     /// Check that the configuration is valid
     pub(crate) fn sanity_check(&self) -> Result<(), ConfigError> {
         if let Some(excl_list) = &self.exclusion_epochs {
@@ -136,6 +165,7 @@ impl Default for TrkConfig {
             end: Availability::Visible,
             schedule: Schedule::Continuous,
             sampling: 1.minutes(),
+            sampling_reference: None,
             inclusion_epochs: None,
             exclusion_epochs: None,
         }
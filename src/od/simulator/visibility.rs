@@ -0,0 +1,219 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::DefaultAllocator;
+use crate::md::prelude::Traj;
+use crate::md::trajectory::Interpolatable;
+use crate::time::{Duration, Epoch, TimeSeries, Unit};
+use std::fmt;
+
+/// Precision to which the rise and set epochs of each visibility interval are refined
+const VISIBILITY_PRECISION: Unit = Unit::Millisecond;
+
+/// A single interval (or pass) during which an object is visible from a tracking device.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VisibilityInterval {
+    /// Rise epoch, refined to be at most one millisecond before the object becomes visible
+    pub start: Epoch,
+    /// Set epoch, refined to be at most one millisecond after the object stops being visible
+    pub end: Epoch,
+    /// Maximum elevation among the sampled epochs of this interval, in degrees
+    pub max_elevation_deg: f64,
+    /// Epoch of the maximum sampled elevation
+    pub max_elevation_epoch: Epoch,
+}
+
+impl VisibilityInterval {
+    /// Duration of this interval
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// Returns whether the provided epoch is within this interval (bounds included)
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        self.start <= epoch && epoch <= self.end
+    }
+}
+
+/// Visibility intervals of a trajectory from a given tracking device.
+///
+/// These are computed once from the trajectory and shared by the tracking arc simulator and the pass reports,
+/// instead of evaluating the elevation of the object at every sample for every purpose.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Visibility {
+    /// Name of the tracking device
+    pub device: String,
+    /// Elevation mask of the tracking device, in degrees
+    pub elevation_mask_deg: f64,
+    /// Ordered and disjoint visibility intervals
+    pub intervals: Vec<VisibilityInterval>,
+}
+
+impl Visibility {
+    /// Computes the visibility intervals of the provided trajectory, where `elevation_deg` returns the elevation of a state as seen from the device.
+    ///
+    /// The trajectory is sampled at the provided step from its first epoch (and at its last epoch), and each rise and set epoch is refined by bisection.
+    /// Any sampled epoch where the elevation is above the mask is guaranteed to be in one of the intervals, but a pass shorter than the step may be missed.
+    pub fn compute<S, F>(
+        device: String,
+        elevation_mask_deg: f64,
+        traj: &Traj<S>,
+        step: Duration,
+        elevation_deg: F,
+    ) -> Result<Self, NyxError>
+    where
+        S: Interpolatable,
+        F: Fn(&S) -> f64,
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        let first = traj.first().epoch();
+        let last = traj.last().epoch();
+
+        let mut epochs: Vec<Epoch> = TimeSeries::inclusive(first, last, step).collect();
+        if epochs.last() != Some(&last) {
+            epochs.push(last);
+        }
+
+        let samples = traj.sample_at(&epochs)?;
+
+        let visible = |epoch: Epoch| -> Result<bool, NyxError> {
+            Ok(elevation_deg(&traj.at(epoch)?) >= elevation_mask_deg)
+        };
+
+        // Bisects the crossing between both epochs and returns the epoch on the non-visible side
+        let refine = |mut lo: Epoch, mut hi: Epoch, lo_visible: bool| -> Result<Epoch, NyxError> {
+            while hi - lo > 1 * VISIBILITY_PRECISION {
+                let mid = lo + (hi - lo) * 0.5;
+                if visible(mid)? == lo_visible {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            Ok(if lo_visible { hi } else { lo })
+        };
+
+        let mut intervals = Vec::new();
+        let mut current: Option<VisibilityInterval> = None;
+        let mut prev_epoch = first;
+
+        for state in &samples.states {
+            let epoch = state.epoch();
+            let elevation = elevation_deg(state);
+
+            if elevation >= elevation_mask_deg {
+                match current.as_mut() {
+                    Some(pass) => {
+                        pass.end = epoch;
+                        if elevation > pass.max_elevation_deg {
+                            pass.max_elevation_deg = elevation;
+                            pass.max_elevation_epoch = epoch;
+                        }
+                    }
+                    None => {
+                        let start = if epoch == first {
+                            first
+                        } else {
+                            refine(prev_epoch, epoch, false)?
+                        };
+                        current = Some(VisibilityInterval {
+                            start,
+                            end: epoch,
+                            max_elevation_deg: elevation,
+                            max_elevation_epoch: epoch,
+                        });
+                    }
+                }
+            } else if let Some(mut pass) = current.take() {
+                pass.end = refine(prev_epoch, epoch, true)?;
+                intervals.push(pass);
+            }
+
+            prev_epoch = epoch;
+        }
+
+        if let Some(pass) = current {
+            intervals.push(pass);
+        }
+
+        Ok(Self {
+            device,
+            elevation_mask_deg,
+            intervals,
+        })
+    }
+
+    /// Returns whether the provided epoch is within any of the visibility intervals
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        self.interval_at(epoch).is_some()
+    }
+
+    /// Returns the visibility interval containing the provided epoch, if any
+    pub fn interval_at(&self, epoch: Epoch) -> Option<&VisibilityInterval> {
+        let idx = self.intervals.partition_point(|pass| pass.end < epoch);
+        self.intervals.get(idx).filter(|pass| pass.contains(epoch))
+    }
+
+    /// Total duration during which the object is visible
+    pub fn total_duration(&self) -> Duration {
+        self.intervals
+            .iter()
+            .fold(Duration::ZERO, |acc, pass| acc + pass.duration())
+    }
+
+    /// Number of passes
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    /// Returns true if the object is never visible
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+}
+
+impl fmt::Display for Visibility {
+    /// Prints the pass report of this device
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} (el. mask {:.3} deg): {} passes for a total of {}",
+            self.device,
+            self.elevation_mask_deg,
+            self.len(),
+            self.total_duration()
+        )?;
+        for (num, pass) in self.intervals.iter().enumerate() {
+            writeln!(
+                f,
+                "\t#{}: {} to {} ({}) -- max. el. {:.3} deg @ {}",
+                num + 1,
+                pass.start,
+                pass.end,
+                pass.duration(),
+                pass.max_elevation_deg,
+                pass.max_elevation_epoch
+            )?;
+        }
+        Ok(())
+    }
+}
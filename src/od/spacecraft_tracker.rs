@@ -0,0 +1,423 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::msr::RangeDoppler;
+use super::noise::GaussMarkov;
+use super::simulator::Visibility;
+use super::TrackingDeviceSim;
+use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::io::{frames_from_str, frames_to_str, ConfigRepr, Configurable};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::DefaultAllocator;
+use crate::md::prelude::Traj;
+use crate::md::trajectory::{Extrapolation, Interpolatable};
+use crate::time::{Duration, Epoch};
+use crate::{NyxError, Spacecraft};
+use rand_pcg::Pcg64Mcg;
+use serde::{Deserializer, Serializer};
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// SpacecraftTracker defines an inter-satellite link, where the range and Doppler of the tracked spacecraft are measured from
+/// another spacecraft (e.g. a lunar relay or another member of a constellation) whose ephemeris is known.
+///
+/// The link is only available when its line of sight clears the limb of each of the occulting bodies (e.g. the Earth and the
+/// Moon) by at least the grazing altitude.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpacecraftTracker {
+    pub name: String,
+    /// Ephemeris of the tracking spacecraft, stored as its states in the configuration
+    #[serde(
+        serialize_with = "traj_to_states",
+        deserialize_with = "traj_from_states"
+    )]
+    pub ephemeris: Traj<Orbit>,
+    /// Bodies which may occult the line of sight between both spacecraft
+    #[serde(serialize_with = "frames_to_str", deserialize_with = "frames_from_str")]
+    pub occulting_bodies: Vec<Frame>,
+    /// Minimum altitude of the line of sight above the equatorial radius of each occulting body (e.g. to clear the atmosphere), in km
+    #[serde(default)]
+    pub grazing_altitude_km: f64,
+    /// Duration needed to generate a measurement (if unset, it is assumed to be instantaneous)
+    #[serde(skip)]
+    pub integration_time: Option<Duration>,
+    /// Noise on the timestamp of the measurement
+    pub timestamp_noise_s: Option<GaussMarkov>,
+    /// Noise on the range data of the measurement
+    pub range_noise_km: Option<GaussMarkov>,
+    /// Noise on the Doppler data of the measurement
+    pub doppler_noise_km_s: Option<GaussMarkov>,
+}
+
+impl SpacecraftTracker {
+    /// Initializes a new spacecraft tracker from its ephemeris, without any occulting body nor noise.
+    pub fn from_ephemeris(name: String, ephemeris: Traj<Orbit>) -> Self {
+        Self {
+            name,
+            ephemeris,
+            occulting_bodies: Vec::new(),
+            grazing_altitude_km: 0.0,
+            integration_time: None,
+            timestamp_noise_s: None,
+            range_noise_km: None,
+            doppler_noise_km_s: None,
+        }
+    }
+
+    /// Initializes a new spacecraft tracker whose line of sight may be occulted by the provided bodies.
+    pub fn with_occultation(
+        name: String,
+        ephemeris: Traj<Orbit>,
+        occulting_bodies: Vec<Frame>,
+        range_noise_km: GaussMarkov,
+        doppler_noise_km_s: GaussMarkov,
+    ) -> Self {
+        Self {
+            occulting_bodies,
+            range_noise_km: Some(range_noise_km),
+            doppler_noise_km_s: Some(doppler_noise_km_s),
+            ..Self::from_ephemeris(name, ephemeris)
+        }
+    }
+
+    /// Returns the state of the tracking spacecraft at the provided epoch, in the provided frame.
+    pub fn tracker_at(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, NyxError> {
+        cosm.try_frame_chg(&self.ephemeris.at(epoch)?, frame)
+    }
+
+    /// Returns the angle of the line of sight to the receiver above the limb of the closest occulting body, as seen from the
+    /// tracking spacecraft, in degrees. This angle is negative when the receiver is occulted, and it is 90 degrees when the
+    /// line of sight does not pass by any occulting body.
+    ///
+    /// The limb is that of a sphere whose radius is the equatorial radius of the body increased by the grazing altitude.
+    pub fn limb_clearance_deg(&self, tx: &Orbit, rx: &Orbit, cosm: &Cosm) -> f64 {
+        let mut clearance_deg = 90.0_f64;
+        for body in &self.occulting_bodies {
+            let r_tx = cosm.frame_chg(tx, *body).radius();
+            let r_rx = cosm.frame_chg(rx, *body).radius();
+            let los = r_rx - r_tx;
+            // Position of the point of the line of sight closest to the center of the body, from zero at the tracker to one at the receiver
+            let tau = -r_tx.dot(&los) / los.norm_squared();
+            if !(0.0..=1.0).contains(&tau) {
+                continue;
+            }
+            let tx_dist_km = r_tx.norm();
+            let closest_km = (r_tx + los * tau).norm();
+            let limb_radius_km = body.equatorial_radius() + self.grazing_altitude_km;
+            let los_angle_deg = (closest_km / tx_dist_km).min(1.0).asin().to_degrees();
+            let limb_angle_deg = (limb_radius_km / tx_dist_km).min(1.0).asin().to_degrees();
+            clearance_deg = clearance_deg.min(los_angle_deg - limb_angle_deg);
+        }
+        clearance_deg
+    }
+
+    /// Computes the intervals when the receiver along the provided trajectory is in view of this tracker, i.e. when the line of
+    /// sight clears every occulting body and both spacecraft have ephemeris data.
+    pub fn visibility_intervals<S: Interpolatable>(
+        &self,
+        traj: &Traj<S>,
+        step: Duration,
+        cosm: &Cosm,
+    ) -> Result<Visibility, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        Visibility::compute(self.name.clone(), 0.0, traj, step, |state| {
+            let rx = state.orbit();
+            match self.tracker_at(rx.epoch, rx.frame, cosm) {
+                Ok(tx) => self.limb_clearance_deg(&tx, rx, cosm),
+                Err(_) => -90.0,
+            }
+        })
+    }
+
+    /// Returns the tracker and receiver states in the frame of the receiver if the link is available at that time.
+    fn link(&self, rx: Orbit, cosm: &Cosm) -> Option<(Orbit, Orbit)> {
+        match self.tracker_at(rx.epoch, rx.frame, cosm) {
+            Ok(tx) => {
+                let clearance_deg = self.limb_clearance_deg(&tx, &rx, cosm);
+                if clearance_deg >= 0.0 {
+                    Some((tx, rx))
+                } else {
+                    debug!(
+                        "{} occulted at {} ({clearance_deg:.3} deg below the limb) -- no measurement",
+                        self.name, rx.epoch
+                    );
+                    None
+                }
+            }
+            Err(e) => {
+                debug!(
+                    "{} has no ephemeris at {}: {e} -- no measurement",
+                    self.name, rx.epoch
+                );
+                None
+            }
+        }
+    }
+
+    /// Builds the range and Doppler measurement of the provided receiver, integrated over the integration time if it is set.
+    fn measure_orbit<S: Interpolatable>(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<S>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: &Cosm,
+    ) -> Result<Option<RangeDoppler>, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, S::VecLength>
+            + Allocator<f64, S::Size>
+            + Allocator<f64, S::Size, S::Size>,
+    {
+        match self.integration_time {
+            Some(integration_time) => {
+                let rx_0 = *traj.at(epoch - integration_time)?.orbit();
+                let rx_1 = *traj.at(epoch)?.orbit();
+
+                let (tx_0, rx_0) = match self.link(rx_0, cosm) {
+                    Some(link) => link,
+                    None => return Ok(None),
+                };
+                let (tx_1, rx_1) = match self.link(rx_1, cosm) {
+                    Some(link) => link,
+                    None => return Ok(None),
+                };
+
+                // Noises are computed at the midpoint of the integration time.
+                let (timestamp_noise_s, range_noise_km, doppler_noise_km_s) =
+                    self.noises(epoch - integration_time * 0.5, rng)?;
+
+                Ok(Some(RangeDoppler::two_way(
+                    (tx_0, tx_1),
+                    (rx_0, rx_1),
+                    timestamp_noise_s,
+                    range_noise_km,
+                    doppler_noise_km_s,
+                )))
+            }
+            None => self.measure_one_way(*traj.at(epoch)?.orbit(), rng, cosm),
+        }
+    }
+
+    /// Builds the instantaneous range and Doppler measurement of the provided receiver.
+    fn measure_one_way(
+        &mut self,
+        rx: Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: &Cosm,
+    ) -> Result<Option<RangeDoppler>, NyxError> {
+        match self.link(rx, cosm) {
+            Some((tx, rx)) => {
+                // Only update the noises if the measurement is valid.
+                let (timestamp_noise_s, range_noise_km, doppler_noise_km_s) =
+                    self.noises(rx.epoch, rng)?;
+
+                Ok(Some(RangeDoppler::one_way(
+                    tx,
+                    rx,
+                    timestamp_noise_s,
+                    range_noise_km,
+                    doppler_noise_km_s,
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the state of the tracker for the orbit determination process, extrapolating it if the ephemeris does not cover the epoch.
+    fn location_of(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
+        let tracker = match self.ephemeris.at(epoch) {
+            Ok(tracker) => tracker,
+            Err(e) => {
+                warn!("{}: {e}, extrapolating its ephemeris", self.name);
+                self.ephemeris
+                    .at_or_extrapolate(epoch, Extrapolation::new(Duration::MAX))
+                    .unwrap_or(*self.ephemeris.first())
+            }
+        };
+        cosm.frame_chg(&tracker, frame)
+    }
+
+    /// Returns the noises for one measurement
+    fn noises(
+        &mut self,
+        epoch: Epoch,
+        rng: Option<&mut Pcg64Mcg>,
+    ) -> Result<(f64, f64, f64), NyxError> {
+        match rng {
+            Some(rng) => {
+                let range_noise_km = self
+                    .range_noise_km
+                    .ok_or_else(|| NyxError::CustomError("Range noise not configured".to_string()))?
+                    .next_bias(epoch, rng);
+
+                let doppler_noise_km_s = self
+                    .doppler_noise_km_s
+                    .ok_or_else(|| {
+                        NyxError::CustomError("Doppler noise not configured".to_string())
+                    })?
+                    .next_bias(epoch, rng);
+
+                let timestamp_noise_s = match self.timestamp_noise_s {
+                    Some(mut timestamp_noise) => timestamp_noise.next_bias(epoch, rng),
+                    None => 0.0,
+                };
+
+                Ok((timestamp_noise_s, range_noise_km, doppler_noise_km_s))
+            }
+            None => Ok((0.0, 0.0, 0.0)),
+        }
+    }
+}
+
+fn traj_to_states<S>(traj: &Traj<Orbit>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serde::Serialize::serialize(&traj.states, serializer)
+}
+
+fn traj_from_states<'de, D>(deserializer: D) -> Result<Traj<Orbit>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut traj = Traj::new();
+    traj.states = serde::Deserialize::deserialize(deserializer)?;
+    if traj.states.is_empty() {
+        return Err(serde::de::Error::custom("empty tracker ephemeris"));
+    }
+    traj.finalize();
+    Ok(traj)
+}
+
+impl ConfigRepr for SpacecraftTracker {}
+
+impl Configurable for SpacecraftTracker {
+    type IntermediateRepr = SpacecraftTracker;
+
+    fn from_config(
+        cfg: Self::IntermediateRepr,
+        _cosm: Arc<Cosm>,
+    ) -> Result<Self, crate::io::ConfigError>
+    where
+        Self: Sized,
+    {
+        Ok(cfg)
+    }
+
+    fn to_config(&self) -> Result<Self::IntermediateRepr, crate::io::ConfigError> {
+        Ok(self.clone())
+    }
+}
+
+impl TrackingDeviceSim<Orbit, RangeDoppler> for SpacecraftTracker {
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Orbit>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RangeDoppler>, NyxError> {
+        self.measure_orbit(epoch, traj, rng, &cosm)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
+        self.location_of(epoch, frame, cosm)
+    }
+
+    fn visibility(
+        &self,
+        traj: &Traj<Orbit>,
+        step: Duration,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<Visibility>, NyxError> {
+        self.visibility_intervals(traj, step, &cosm).map(Some)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RangeDoppler>, NyxError> {
+        self.measure_one_way(rx, rng, &cosm)
+    }
+}
+
+impl TrackingDeviceSim<Spacecraft, RangeDoppler> for SpacecraftTracker {
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Spacecraft>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RangeDoppler>, NyxError> {
+        self.measure_orbit(epoch, traj, rng, &cosm)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
+        self.location_of(epoch, frame, cosm)
+    }
+
+    fn visibility(
+        &self,
+        traj: &Traj<Spacecraft>,
+        step: Duration,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<Visibility>, NyxError> {
+        self.visibility_intervals(traj, step, &cosm).map(Some)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Spacecraft,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RangeDoppler>, NyxError> {
+        self.measure_one_way(rx.orbit, rng, &cosm)
+    }
+}
+
+impl fmt::Display for SpacecraftTracker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bodies: Vec<String> = self
+            .occulting_bodies
+            .iter()
+            .map(|body| format!("{body}"))
+            .collect();
+        write!(
+            f,
+            "{} (spacecraft tracker from {} to {}, occulted by [{}])",
+            self.name,
+            self.ephemeris.first().epoch,
+            self.ephemeris.last().epoch,
+            bodies.join(", ")
+        )
+    }
+}
@@ -0,0 +1,311 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::msr::RangeDoppler;
+use super::{GroundStation, TrackingDeviceSim};
+use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::linalg::Matrix3;
+use crate::md::prelude::Traj;
+use crate::time::{Duration, Epoch};
+use crate::NyxError;
+use rand_pcg::Pcg64Mcg;
+use std::fmt;
+use std::sync::Arc;
+
+/// A waypoint of the traverse of a surface asset, in geodetic coordinates of its body fixed frame
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Waypoint {
+    pub epoch: Epoch,
+    pub latitude_deg: f64,
+    pub longitude_deg: f64,
+    pub height_km: f64,
+}
+
+/// A surface asset on a rotating body, e.g. a lander or a rover on Mars, tracked from ground stations.
+///
+/// The asset is at rest in the body fixed frame, or moves along its traverse, linearly interpolated in geodetic coordinates
+/// between its waypoints. Its inertial state therefore includes the rotation of the body, such that the Doppler measured
+/// by the stations reflects that rotation, as used in lander radio science.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SurfaceAsset {
+    pub name: String,
+    /// Body fixed frame of the asset, e.g. "IAU Mars"
+    pub frame: Frame,
+    /// in degrees, the location of the asset when it is not moving along its traverse
+    pub latitude_deg: f64,
+    /// in degrees
+    pub longitude_deg: f64,
+    /// in km
+    pub height_km: f64,
+    /// Minimum elevation of the stations seen from the asset, in degrees
+    pub elevation_mask_deg: f64,
+    /// Waypoints of the traverse, sorted by epoch: the asset is at its first waypoint before and at its last waypoint after
+    pub traverse: Vec<Waypoint>,
+}
+
+impl SurfaceAsset {
+    /// Initializes an asset at rest on the surface of the body of the provided body fixed frame
+    pub fn fixed(
+        name: String,
+        latitude_deg: f64,
+        longitude_deg: f64,
+        height_km: f64,
+        frame: Frame,
+    ) -> Self {
+        assert!(frame.is_geoid(), "surface assets only work on geoids");
+        Self {
+            name,
+            frame,
+            latitude_deg,
+            longitude_deg,
+            height_km,
+            elevation_mask_deg: 0.0,
+            traverse: Vec::new(),
+        }
+    }
+
+    /// Initializes an asset moving along the provided waypoints
+    pub fn moving(name: String, traverse: Vec<Waypoint>, frame: Frame) -> Result<Self, NyxError> {
+        if traverse.len() < 2 {
+            return Err(NyxError::CustomError(
+                "a traverse needs at least two waypoints".to_string(),
+            ));
+        }
+        if traverse.windows(2).any(|w| w[1].epoch <= w[0].epoch) {
+            return Err(NyxError::CustomError(
+                "the waypoints of a traverse must be sorted by strictly increasing epoch"
+                    .to_string(),
+            ));
+        }
+        let mut me = Self::fixed(
+            name,
+            traverse[0].latitude_deg,
+            traverse[0].longitude_deg,
+            traverse[0].height_km,
+            frame,
+        );
+        me.traverse = traverse;
+        Ok(me)
+    }
+
+    /// Sets the minimum elevation of the stations seen from the asset, in degrees
+    pub fn with_elevation_mask(mut self, elevation_mask_deg: f64) -> Self {
+        self.elevation_mask_deg = elevation_mask_deg;
+        self
+    }
+
+    /// Returns the geodetic coordinates of the asset at the provided epoch (deg, deg, km) and their rates (deg/s, deg/s, km/s)
+    pub fn geodetic_at(&self, epoch: Epoch) -> ([f64; 3], [f64; 3]) {
+        let coords = |wp: &Waypoint| [wp.latitude_deg, wp.longitude_deg, wp.height_km];
+        let (first, last) = match (self.traverse.first(), self.traverse.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => {
+                return (
+                    [self.latitude_deg, self.longitude_deg, self.height_km],
+                    [0.0; 3],
+                )
+            }
+        };
+        if epoch <= first.epoch {
+            return (coords(first), [0.0; 3]);
+        } else if epoch >= last.epoch {
+            return (coords(last), [0.0; 3]);
+        }
+        let idx = self
+            .traverse
+            .windows(2)
+            .position(|w| epoch < w[1].epoch)
+            .unwrap();
+        let (start, end) = (&self.traverse[idx], &self.traverse[idx + 1]);
+        let span_s = (end.epoch - start.epoch).to_seconds();
+        let elapsed_s = (epoch - start.epoch).to_seconds();
+        let (start, end) = (coords(start), coords(end));
+        let mut location = [0.0; 3];
+        let mut rates = [0.0; 3];
+        for i in 0..3 {
+            rates[i] = (end[i] - start[i]) / span_s;
+            location[i] = start[i] + rates[i] * elapsed_s;
+        }
+        (location, rates)
+    }
+
+    /// Returns the state of the asset in its body fixed frame, whose velocity is that of the traverse
+    pub fn to_orbit(&self, epoch: Epoch) -> Orbit {
+        let (location, rates) = self.geodetic_at(epoch);
+        let position = |dt_s: f64| {
+            Orbit::from_altlatlong(
+                location[0] + rates[0] * dt_s,
+                location[1] + rates[1] * dt_s,
+                location[2] + rates[2] * dt_s,
+                0.0,
+                epoch,
+                self.frame,
+            )
+            .radius()
+        };
+        // The rates are constant along a leg of the traverse, so a central difference is accurate
+        let r = position(0.0);
+        let v = (position(0.5) - position(-0.5)) / 1.0;
+        Orbit::cartesian(r[0], r[1], r[2], v[0], v[1], v[2], epoch, self.frame)
+    }
+
+    /// Returns the state of the asset in the provided frame, including the rotation of its body
+    pub fn state(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, NyxError> {
+        cosm.try_frame_chg(&self.to_orbit(epoch), frame)
+    }
+
+    /// Returns the trajectory of the asset in the provided frame from the start to the end epochs, sampled at the provided step,
+    /// e.g. to simulate its tracking with a `TrackingArcSim`.
+    pub fn trajectory(
+        &self,
+        start: Epoch,
+        end: Epoch,
+        step: Duration,
+        frame: Frame,
+        cosm: &Cosm,
+    ) -> Result<Traj<Orbit>, NyxError> {
+        if end <= start || step <= Duration::ZERO {
+            return Err(NyxError::CustomError(format!(
+                "invalid sampling of {} from {start} to {end} every {step}",
+                self.name
+            )));
+        }
+        let mut traj = Traj::new();
+        traj.name = Some(self.name.clone());
+        let mut epoch = start;
+        while epoch < end {
+            traj.states.push(self.state(epoch, frame, cosm)?);
+            epoch += step;
+        }
+        traj.states.push(self.state(end, frame, cosm)?);
+        traj.finalize();
+        Ok(traj)
+    }
+
+    /// Returns this asset as a ground station of its body at the provided epoch, e.g. to compute the elevation of a station
+    pub fn as_station(&self, epoch: Epoch) -> GroundStation {
+        let (location, _) = self.geodetic_at(epoch);
+        let mut station = GroundStation::from_point(
+            self.name.clone(),
+            location[0],
+            location[1],
+            location[2],
+            self.frame,
+        );
+        station.elevation_mask_deg = self.elevation_mask_deg;
+        station
+    }
+
+    /// Computes the elevation of the provided station seen from this asset, in degrees
+    pub fn elevation_of(
+        &self,
+        station: &GroundStation,
+        epoch: Epoch,
+        cosm: &Cosm,
+    ) -> Result<f64, NyxError> {
+        let station_state = cosm.try_frame_chg(&station.to_orbit(epoch), self.frame)?;
+        Ok(self
+            .as_station(epoch)
+            .azimuth_elevation_of(station_state, cosm)
+            .1)
+    }
+
+    /// Simulates a one way range and Doppler measurement of this asset by the provided station, if both are above the
+    /// elevation mask of the other.
+    pub fn measure(
+        &self,
+        station: &mut GroundStation,
+        epoch: Epoch,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RangeDoppler>, NyxError> {
+        let elevation_deg = self.elevation_of(station, epoch, &cosm)?;
+        if elevation_deg < self.elevation_mask_deg {
+            debug!(
+                "{} (el. mask {:.3} deg) sees {} at {elevation_deg:.3} deg -- no measurement",
+                self.name, self.elevation_mask_deg, station.name
+            );
+            return Ok(None);
+        }
+        // Computed in the inertial frame of the station, as in the survey
+        let inertial = cosm.frame_from_ephem_path(&station.frame.ephem_path());
+        let rx = self.state(epoch, inertial, &cosm)?;
+        station.measure_instantaneous(rx, rng, cosm)
+    }
+
+    /// Estimates the location of this asset at rest from the one way range and Doppler measurements of ground stations, using
+    /// a weighted least squares on its latitude (deg), longitude (deg) and height (km) starting from its current location.
+    ///
+    /// Returns the surveyed asset and the covariance of its latitude, longitude and height.
+    pub fn survey(
+        &self,
+        tracking: &[(GroundStation, RangeDoppler)],
+        range_sigma_km: f64,
+        doppler_sigma_km_s: f64,
+        cosm: &Cosm,
+    ) -> Result<(Self, Matrix3<f64>), NyxError> {
+        if !self.traverse.is_empty() {
+            return Err(NyxError::CustomError(format!(
+                "{} moves along a traverse: only assets at rest can be surveyed",
+                self.name
+            )));
+        }
+        // The one way range and Doppler are symmetric, so the asset is surveyed as a station tracking each ground station
+        let (station, covar) = GroundStation::from_point(
+            self.name.clone(),
+            self.latitude_deg,
+            self.longitude_deg,
+            self.height_km,
+            self.frame,
+        )
+        .survey_with(
+            tracking,
+            |(ground_station, msr)| {
+                let inertial = cosm.frame_from_ephem_path(&ground_station.frame.ephem_path());
+                let tx = cosm.try_frame_chg(&ground_station.to_orbit(msr.epoch), inertial)?;
+                Ok((*msr, tx))
+            },
+            range_sigma_km,
+            doppler_sigma_km_s,
+            cosm,
+        )?;
+        let mut surveyed = self.clone();
+        surveyed.latitude_deg = station.latitude_deg;
+        surveyed.longitude_deg = station.longitude_deg;
+        surveyed.height_km = station.height_km;
+        Ok((surveyed, covar))
+    }
+}
+
+impl fmt::Display for SurfaceAsset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {} (lat.: {:.4} deg    long.: {:.4} deg    alt.: {:.3} m)",
+            self.frame,
+            self.name,
+            self.latitude_deg,
+            self.longitude_deg,
+            self.height_km * 1e3,
+        )?;
+        if !self.traverse.is_empty() {
+            write!(f, " moving along {} waypoints", self.traverse.len())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,87 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{IntegrationDetails, IntegratorKind};
+use crate::errors::NyxError;
+use crate::io::ConfigError;
+use crate::time::Duration;
+use rand_pcg::Pcg64Mcg;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// A checkpoint of the full integration state of a propagator instance, created with `PropInstance::checkpoint`.
+///
+/// Resuming it with `Propagator::resume` continues the propagation with the exact same steps as if it had never been
+/// interrupted, so it can be saved to a file such that long running jobs (e.g. multi-day Monte Carlo or orbit determination
+/// runs) survive a restart of the process.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PropCheckpoint<S> {
+    /// State of the instance, which provides its frame and its parameters
+    pub state: S,
+    /// Epoch of the state, as the duration since the TAI reference epoch, restored exactly
+    pub epoch_tai: Duration,
+    /// Integrated vector of the state, including its STM if any, restored bit for bit
+    pub state_vector: Vec<f64>,
+    /// Whether the STM of the state is integrated
+    pub stm: bool,
+    /// Step size to use for the next step
+    pub step_size: Duration,
+    pub fixed_step: bool,
+    /// Details of the latest integration step
+    pub details: IntegrationDetails,
+    /// Kind, order and number of stages of the integrator, which must match those of the propagator resuming this checkpoint
+    pub integrator: (IntegratorKind, u8, usize),
+    /// Previous derivatives of a multi-step integrator, most recent first, with the epoch of each
+    pub history: Vec<(Duration, Vec<f64>)>,
+    pub history_step: Duration,
+    /// Target row of the extrapolation table of a Bulirsch-Stoer integrator
+    pub extrapolation_row: usize,
+    /// Errors of the two previous accepted steps relative to the tolerance, used by the PI and PID step controllers
+    pub step_errors: [f64; 2],
+    /// Magnitude of the next step in the fictitious time of a Sundman regularized propagation
+    #[serde(default)]
+    pub sundman_step: f64,
+    /// Random number generator of the job using this propagator, if any, e.g. to draw the next Monte Carlo dispersions
+    pub rng: Option<Pcg64Mcg>,
+}
+
+impl<S> PropCheckpoint<S> {
+    /// Stores the random number generator of the job using this propagator in this checkpoint
+    pub fn with_rng(mut self, rng: Pcg64Mcg) -> Self {
+        self.rng = Some(rng);
+        self
+    }
+}
+
+impl<S: Serialize + DeserializeOwned> PropCheckpoint<S> {
+    /// Saves this checkpoint to the provided YAML file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), NyxError> {
+        let file = File::create(path).map_err(ConfigError::ReadError)?;
+        serde_yaml::to_writer(BufWriter::new(file), self).map_err(ConfigError::ParseError)?;
+        Ok(())
+    }
+
+    /// Loads a checkpoint from the provided YAML file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, NyxError> {
+        let file = File::open(path).map_err(ConfigError::ReadError)?;
+        Ok(serde_yaml::from_reader(BufReader::new(file)).map_err(ConfigError::ParseError)?)
+    }
+}
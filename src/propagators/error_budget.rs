@@ -0,0 +1,472 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{
+    AdamsBashforthMoulton, BulirschStoer, CashKarp45, Dormand45, Dormand78, ErrorCtrl, Fehlberg45,
+    PropOpts, Propagator, RK2Fixed, RK4Fixed, Verner56, RK89,
+};
+use crate::dynamics::Dynamics;
+use crate::errors::NyxError;
+#[cfg(feature = "io")]
+use crate::io::watermark::pq_writer;
+#[cfg(feature = "io")]
+use crate::io::ExportCfg;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, Vector3};
+use crate::md::StateParameter;
+use crate::time::{Duration, Unit};
+use crate::State;
+#[cfg(feature = "io")]
+use arrow::array::{Array, BooleanBuilder, Float64Builder, StringBuilder, UInt64Builder};
+#[cfg(feature = "io")]
+use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "io")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "io")]
+use parquet::arrow::ArrowWriter;
+#[cfg(feature = "io")]
+use std::collections::HashMap;
+#[cfg(feature = "io")]
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "io")]
+use std::fs::File;
+#[cfg(feature = "io")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "io")]
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The integration methods which can be selected at runtime, e.g. to compare them in an error budget.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IntegratorMethod {
+    RK89,
+    Dormand78,
+    Dormand45,
+    Fehlberg45,
+    CashKarp45,
+    Verner56,
+    RK4Fixed,
+    RK2Fixed,
+    BulirschStoer,
+    /// Adams-Bashforth-Moulton predictor-corrector of the provided order, between 4 and 12
+    AdamsBashforthMoulton(u8),
+}
+
+impl IntegratorMethod {
+    /// Returns whether this method only supports a fixed step
+    pub fn is_fixed_step(&self) -> bool {
+        matches!(self, Self::RK4Fixed | Self::RK2Fixed)
+    }
+
+    /// Initializes a propagator of this method with the provided dynamics and options
+    pub fn propagator<'a, D: Dynamics, E: ErrorCtrl>(
+        &self,
+        dynamics: D,
+        opts: PropOpts<E>,
+    ) -> Result<Propagator<'a, D, E>, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::VecLength>,
+    {
+        Ok(match self {
+            Self::RK89 => Propagator::new::<RK89>(dynamics, opts),
+            Self::Dormand78 => Propagator::new::<Dormand78>(dynamics, opts),
+            Self::Dormand45 => Propagator::new::<Dormand45>(dynamics, opts),
+            Self::Fehlberg45 => Propagator::new::<Fehlberg45>(dynamics, opts),
+            Self::CashKarp45 => Propagator::new::<CashKarp45>(dynamics, opts),
+            Self::Verner56 => Propagator::new::<Verner56>(dynamics, opts),
+            Self::RK4Fixed => Propagator::new::<RK4Fixed>(dynamics, opts),
+            Self::RK2Fixed => Propagator::new::<RK2Fixed>(dynamics, opts),
+            Self::BulirschStoer => Propagator::new::<BulirschStoer>(dynamics, opts),
+            Self::AdamsBashforthMoulton(order) => match order {
+                4 => Propagator::new::<AdamsBashforthMoulton<4>>(dynamics, opts),
+                5 => Propagator::new::<AdamsBashforthMoulton<5>>(dynamics, opts),
+                6 => Propagator::new::<AdamsBashforthMoulton<6>>(dynamics, opts),
+                7 => Propagator::new::<AdamsBashforthMoulton<7>>(dynamics, opts),
+                8 => Propagator::new::<AdamsBashforthMoulton<8>>(dynamics, opts),
+                9 => Propagator::new::<AdamsBashforthMoulton<9>>(dynamics, opts),
+                10 => Propagator::new::<AdamsBashforthMoulton<10>>(dynamics, opts),
+                11 => Propagator::new::<AdamsBashforthMoulton<11>>(dynamics, opts),
+                12 => Propagator::new::<AdamsBashforthMoulton<12>>(dynamics, opts),
+                _ => {
+                    return Err(NyxError::CustomError(format!(
+                        "Adams-Bashforth-Moulton of order {order} is not supported (must be between 4 and 12)"
+                    )))
+                }
+            },
+        })
+    }
+}
+
+impl fmt::Display for IntegratorMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::AdamsBashforthMoulton(order) => write!(f, "AdamsBashforthMoulton{order}"),
+            _ => write!(f, "{self:?}"),
+        }
+    }
+}
+
+/// A set of integrator settings evaluated by an error budget: the integration method and its options.
+#[derive(Copy, Clone, Debug)]
+pub struct IntegratorSettings<E: ErrorCtrl> {
+    pub method: IntegratorMethod,
+    pub opts: PropOpts<E>,
+}
+
+impl<E: ErrorCtrl> IntegratorSettings<E> {
+    /// Adaptive step settings with the provided tolerance and step limits, or fixed step settings at the maximum step
+    /// if the method only supports a fixed step (in which case the tolerance is ignored).
+    pub fn new(
+        method: IntegratorMethod,
+        tolerance: f64,
+        min_step: Duration,
+        max_step: Duration,
+        errctrl: E,
+    ) -> Self {
+        let mut opts = PropOpts::with_adaptive_step(min_step, max_step, tolerance, errctrl);
+        if method.is_fixed_step() {
+            opts.min_step = max_step;
+            opts.fixed_step = true;
+        }
+        Self { method, opts }
+    }
+}
+
+impl<E: ErrorCtrl> fmt::Display for IntegratorSettings<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.opts.fixed_step {
+            write!(
+                f,
+                "{} with fixed step of {}",
+                self.method, self.opts.max_step
+            )
+        } else {
+            write!(
+                f,
+                "{} with tolerance of {:e} and steps in [{}; {}]",
+                self.method, self.opts.tolerance, self.opts.min_step, self.opts.max_step
+            )
+        }
+    }
+}
+
+/// An error budget of the propagation of a scenario: each of the integrator settings propagates the same initial
+/// state for the same duration, and its final state is compared to that of a tight tolerance reference propagation.
+///
+/// The report provides the accuracy versus runtime trade of all of these settings, such that the settings of a
+/// study can be picked objectively, e.g. the fastest settings achieving the required accuracy.
+#[derive(Clone, Debug)]
+pub struct ErrorBudget<E: ErrorCtrl> {
+    /// Settings of the reference propagation, which should be much more accurate than any of the evaluated settings
+    pub reference: IntegratorSettings<E>,
+    /// All of the evaluated settings
+    pub settings: Vec<IntegratorSettings<E>>,
+}
+
+impl<E: ErrorCtrl> ErrorBudget<E> {
+    /// Builds the error budget of every combination of the provided methods, tolerances and maximum steps (the tolerance
+    /// is ignored by fixed step methods). The reference is an RK89 with a tolerance of 1e-14 and a maximum step of 60 s.
+    pub fn from_matrix(
+        methods: &[IntegratorMethod],
+        tolerances: &[f64],
+        min_step: Duration,
+        max_steps: &[Duration],
+        errctrl: E,
+    ) -> Self {
+        let mut settings = Vec::new();
+        for method in methods {
+            for max_step in max_steps {
+                if method.is_fixed_step() {
+                    settings.push(IntegratorSettings::new(
+                        *method, 0.0, min_step, *max_step, errctrl,
+                    ));
+                    continue;
+                }
+                for tolerance in tolerances {
+                    settings.push(IntegratorSettings::new(
+                        *method, *tolerance, min_step, *max_step, errctrl,
+                    ));
+                }
+            }
+        }
+
+        Self {
+            reference: IntegratorSettings::new(
+                IntegratorMethod::RK89,
+                1e-14,
+                min_step,
+                1 * Unit::Minute,
+                errctrl,
+            ),
+            settings,
+        }
+    }
+
+    /// Propagates the provided state for the provided duration with the reference and each of the settings, one after the
+    /// other such that the runtimes are comparable. The state must provide its Cartesian position and velocity.
+    pub fn run<D: Dynamics + Clone>(
+        &self,
+        dynamics: D,
+        initial_state: D::StateType,
+        duration: Duration,
+    ) -> Result<ErrorBudgetReport, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::VecLength>,
+    {
+        let (reference_state, _, _) =
+            Self::propagate(&self.reference, dynamics.clone(), initial_state, duration)?;
+        let (ref_pos, ref_vel) = cartesian(&reference_state)?;
+        info!("Error budget reference: {}", self.reference);
+
+        let mut entries = Vec::with_capacity(self.settings.len());
+        for settings in &self.settings {
+            let (end_state, num_steps, runtime) =
+                Self::propagate(settings, dynamics.clone(), initial_state, duration)?;
+            let (pos, vel) = cartesian(&end_state)?;
+            let entry = BudgetEntry {
+                method: settings.method,
+                tolerance: settings.opts.tolerance,
+                min_step: settings.opts.min_step,
+                max_step: settings.opts.max_step,
+                fixed_step: settings.opts.fixed_step,
+                pos_err_km: (pos - ref_pos).norm(),
+                vel_err_km_s: (vel - ref_vel).norm(),
+                num_steps,
+                runtime,
+            };
+            debug!("{entry}");
+            entries.push(entry);
+        }
+
+        Ok(ErrorBudgetReport { duration, entries })
+    }
+
+    /// Propagates with the provided settings, returning the final state, the number of steps and the runtime
+    fn propagate<D: Dynamics>(
+        settings: &IntegratorSettings<E>,
+        dynamics: D,
+        initial_state: D::StateType,
+        duration: Duration,
+    ) -> Result<(D::StateType, usize, Duration), NyxError>
+    where
+        DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::VecLength>,
+    {
+        let prop = settings.method.propagator(dynamics, settings.opts)?;
+        let mut num_steps = 0;
+        let tick = Instant::now();
+        let end_state = prop
+            .with(initial_state)
+            .for_duration_publish(duration, |_| num_steps += 1)?;
+        let runtime: Duration = tick.elapsed().into();
+        Ok((end_state, num_steps, runtime))
+    }
+}
+
+/// Returns the Cartesian position (km) and velocity (km/s) of the provided state
+fn cartesian<S: State>(state: &S) -> Result<(Vector3<f64>, Vector3<f64>), NyxError>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    Ok((
+        Vector3::new(
+            state.value(StateParameter::X)?,
+            state.value(StateParameter::Y)?,
+            state.value(StateParameter::Z)?,
+        ),
+        Vector3::new(
+            state.value(StateParameter::VX)?,
+            state.value(StateParameter::VY)?,
+            state.value(StateParameter::VZ)?,
+        ),
+    ))
+}
+
+/// The accuracy and the cost of one set of integrator settings of an error budget
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BudgetEntry {
+    pub method: IntegratorMethod,
+    /// Tolerance of the integrator, ignored if `fixed_step` is set
+    pub tolerance: f64,
+    pub min_step: Duration,
+    pub max_step: Duration,
+    pub fixed_step: bool,
+    /// Norm of the error of the final position with respect to the reference
+    pub pos_err_km: f64,
+    /// Norm of the error of the final velocity with respect to the reference
+    pub vel_err_km_s: f64,
+    /// Number of integration steps
+    pub num_steps: usize,
+    /// Wall clock duration of the propagation
+    pub runtime: Duration,
+}
+
+impl fmt::Display for BudgetEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.fixed_step {
+            write!(f, "{} (fixed step of {})", self.method, self.max_step)?;
+        } else {
+            write!(
+                f,
+                "{} (tol. {:e}, max step {})",
+                self.method, self.tolerance, self.max_step
+            )?;
+        }
+        write!(
+            f,
+            "\tpos. err. {:.3e} km\tvel. err. {:.3e} km/s\t{} steps in {}",
+            self.pos_err_km, self.vel_err_km_s, self.num_steps, self.runtime
+        )
+    }
+}
+
+/// The result of an error budget: the accuracy and runtime of each of the evaluated settings
+#[derive(Clone, Debug)]
+pub struct ErrorBudgetReport {
+    /// Duration of the propagations
+    pub duration: Duration,
+    /// One entry per evaluated settings, in the order of the error budget
+    pub entries: Vec<BudgetEntry>,
+}
+
+impl ErrorBudgetReport {
+    /// Returns the fastest settings whose position error is within the provided accuracy, if any
+    pub fn fastest_within(&self, pos_err_km: f64) -> Option<&BudgetEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.pos_err_km <= pos_err_km)
+            .min_by(|a, b| a.runtime.cmp(&b.runtime))
+    }
+
+    /// Returns the accuracy versus runtime trade curve, i.e. the settings which no other settings beat both in position error
+    /// and in runtime, sorted by increasing runtime (and therefore decreasing position error).
+    pub fn pareto_front(&self) -> Vec<&BudgetEntry> {
+        let mut sorted: Vec<&BudgetEntry> = self.entries.iter().collect();
+        sorted.sort_by(|a, b| {
+            a.runtime
+                .cmp(&b.runtime)
+                .then(a.pos_err_km.total_cmp(&b.pos_err_km))
+        });
+
+        let mut front: Vec<&BudgetEntry> = Vec::new();
+        for entry in sorted {
+            if front
+                .last()
+                .is_none_or(|best| entry.pos_err_km < best.pos_err_km)
+            {
+                front.push(entry);
+            }
+        }
+        front
+    }
+
+    /// Exports all of the entries of this report to a parquet file
+    #[cfg(feature = "io")]
+    pub fn to_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cfg: ExportCfg,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let path_buf = cfg.actual_path(path);
+
+        let hdrs = vec![
+            Field::new("Method", DataType::Utf8, false),
+            Field::new("Fixed step", DataType::Boolean, false),
+            Field::new("Number of steps", DataType::UInt64, false),
+            Field::new("Tolerance", DataType::Float64, false),
+            Field::new("Min step (s)", DataType::Float64, false),
+            Field::new("Max step (s)", DataType::Float64, false),
+            Field::new("Position error (km)", DataType::Float64, false),
+            Field::new("Velocity error (km/s)", DataType::Float64, false),
+            Field::new("Runtime (s)", DataType::Float64, false),
+        ];
+
+        let mut method = StringBuilder::new();
+        let mut fixed_step = BooleanBuilder::new();
+        let mut num_steps = UInt64Builder::new();
+        let mut columns: Vec<Float64Builder> = (0..6).map(|_| Float64Builder::new()).collect();
+        for entry in &self.entries {
+            method.append_value(format!("{}", entry.method));
+            fixed_step.append_value(entry.fixed_step);
+            num_steps.append_value(entry.num_steps as u64);
+            let values = [
+                entry.tolerance,
+                entry.min_step.to_seconds(),
+                entry.max_step.to_seconds(),
+                entry.pos_err_km,
+                entry.vel_err_km_s,
+                entry.runtime.to_seconds(),
+            ];
+            for (column, value) in columns.iter_mut().zip(values) {
+                column.append_value(value);
+            }
+        }
+
+        let mut record: Vec<Arc<dyn Array>> = vec![
+            Arc::new(method.finish()),
+            Arc::new(fixed_step.finish()),
+            Arc::new(num_steps.finish()),
+        ];
+        for mut column in columns {
+            record.push(Arc::new(column.finish()));
+        }
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "Purpose".to_string(),
+            "Propagation error budget".to_string(),
+        );
+        metadata.insert("Duration".to_string(), format!("{}", self.duration));
+        if let Some(add_meta) = cfg.metadata {
+            for (k, v) in add_meta {
+                metadata.insert(k, v);
+            }
+        }
+
+        let schema = Arc::new(Schema::new(hdrs));
+        let props = pq_writer(Some(metadata), cfg.row_group_size);
+        let file = File::create(&path_buf)?;
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), props)?;
+        let batch = RecordBatch::try_new(schema, record)?;
+        writer.write(&batch)?;
+        writer.close()?;
+
+        info!("Error budget written to {}", path_buf.display());
+        Ok(path_buf)
+    }
+}
+
+impl fmt::Display for ErrorBudgetReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Propagation error budget over {}", self.duration)?;
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
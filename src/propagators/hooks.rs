@@ -0,0 +1,168 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::errors::NyxError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::DefaultAllocator;
+use crate::md::maneuver_design::ImpulsiveMnvr;
+use crate::md::trajectory::Interpolatable;
+use crate::md::ConservationDrift;
+use crate::time::Epoch;
+use crate::State;
+use std::fmt;
+
+/// A hook called by a propagator instance after each accepted step, which may modify the state, e.g. to apply an impulsive
+/// maneuver. Hooks are attached to an instance with `PropInstance::with_hook`.
+pub trait StepHook<S: State>: fmt::Display + Send
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    /// Called with the state at the end of each accepted step, after the `finally` of the dynamics.
+    /// The `backward` flag is set when propagating backward in time.
+    fn after_step(&mut self, state: &mut S, backward: bool) -> Result<(), NyxError>;
+
+    /// Epochs at which this hook must be called: the propagator shortens its steps to land exactly on each of them.
+    fn scheduled_epochs(&self) -> &[Epoch] {
+        &[]
+    }
+}
+
+impl<S: State> fmt::Debug for dyn StepHook<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+/// Applies impulsive maneuvers at their epochs during a propagation, without stopping it.
+///
+/// The delta-v of each maneuver is added to the velocity of the state in its integration frame, and removed when propagating
+/// backward through the maneuver. The mass of a spacecraft is unchanged.
+#[derive(Clone, Debug)]
+pub struct ImpulsiveMnvrHook {
+    pub mnvrs: Vec<ImpulsiveMnvr>,
+    epochs: Vec<Epoch>,
+}
+
+impl ImpulsiveMnvrHook {
+    pub fn new(mut mnvrs: Vec<ImpulsiveMnvr>) -> Self {
+        mnvrs.sort_by_key(|mnvr| mnvr.epoch);
+        let epochs = mnvrs.iter().map(|mnvr| mnvr.epoch).collect();
+        Self { mnvrs, epochs }
+    }
+}
+
+impl<S: Interpolatable> StepHook<S> for ImpulsiveMnvrHook
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    fn after_step(&mut self, state: &mut S, backward: bool) -> Result<(), NyxError> {
+        let epoch = state.epoch();
+        for mnvr in self.mnvrs.iter().filter(|mnvr| mnvr.epoch == epoch) {
+            let mut orbit = *state.orbit();
+            let dv_km_s = if backward {
+                -mnvr.dv_km_s
+            } else {
+                mnvr.dv_km_s
+            };
+            orbit.apply_dv(dv_km_s);
+            state.set_orbit(orbit);
+            info!("Applied {mnvr}");
+        }
+        Ok(())
+    }
+
+    fn scheduled_epochs(&self) -> &[Epoch] {
+        &self.epochs
+    }
+}
+
+impl fmt::Display for ImpulsiveMnvrHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} impulsive maneuver(s)", self.mnvrs.len())
+    }
+}
+
+/// Monitors the drift of a conserved quantity during a propagation, e.g. the energy of a two body propagation, as a check of
+/// its numerical accuracy.
+///
+/// A warning is logged the first time the relative drift exceeds its threshold. If the monitor is failing, the propagation
+/// instead stops with an error.
+#[derive(Clone)]
+pub struct ConservationMonitor {
+    pub drift: ConservationDrift,
+    /// Whether to stop the propagation with an error when the drift exceeds its threshold
+    pub failing: bool,
+    /// Largest relative drift seen so far
+    pub max_seen: f64,
+    warned: bool,
+}
+
+impl ConservationMonitor {
+    pub fn new(drift: ConservationDrift) -> Self {
+        Self {
+            drift,
+            failing: false,
+            max_seen: 0.0,
+            warned: false,
+        }
+    }
+
+    /// Stops the propagation with an error when the drift exceeds its threshold
+    pub fn failing(mut self) -> Self {
+        self.failing = true;
+        self
+    }
+}
+
+impl<S: Interpolatable> StepHook<S> for ConservationMonitor
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    fn after_step(&mut self, state: &mut S, _backward: bool) -> Result<(), NyxError> {
+        let drift = self.drift.drift(state.orbit())?;
+        self.max_seen = self.max_seen.max(drift);
+        if drift > self.drift.max_drift {
+            let msg = format!(
+                "{} drifted by {drift:e} at {}, above {:e}",
+                self.drift.quantity,
+                state.epoch(),
+                self.drift.max_drift
+            );
+            if self.failing {
+                return Err(NyxError::CustomError(msg));
+            } else if !self.warned {
+                warn!("{msg}");
+                self.warned = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ConservationMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "monitor of the {}", self.drift)
+    }
+}
@@ -17,21 +17,24 @@
 */
 
 use super::error_ctrl::ErrorCtrl;
-use super::{IntegrationDetails, Propagator};
+use super::{
+    bs_substeps, bs_work, AbmCoefficients, IntegrationDetails, IntegratorKind, PropCheckpoint,
+    Propagator, StepController, StepHook,
+};
 use crate::dynamics::Dynamics;
 use crate::errors::NyxError;
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, OVector};
+use crate::linalg::{DefaultAllocator, DimName, OMatrix, OVector};
 use crate::md::trajectory::{Interpolatable, Traj};
 use crate::md::EventEvaluator;
 use crate::time::{Duration, Epoch, Unit};
 use crate::State;
-use rayon::iter::ParallelBridge;
-use rayon::prelude::ParallelIterator;
+use std::collections::VecDeque;
 use std::f64;
 use std::sync::mpsc::{channel, Sender};
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
+use tracing::{debug, debug_span, info, trace, warn};
 
 /// A Propagator allows propagating a set of dynamics forward or backward in time.
 /// It is an EventTracker, without any event tracking. It includes the options, the integrator
@@ -54,6 +57,19 @@ where
     pub(crate) fixed_step: bool,
     // Allows us to do pre-allocation of the ki vectors
     pub(crate) k: Vec<OVector<f64, <D::StateType as State>::VecLength>>,
+    // Coefficients of the Adams-Bashforth-Moulton integrator, if multi-step
+    pub(crate) abm: Option<AbmCoefficients>,
+    // Previous derivatives of the multi-step integrator, most recent first, equally spaced by `history_step`
+    pub(crate) history: VecDeque<(Epoch, OVector<f64, <D::StateType as State>::VecLength>)>,
+    pub(crate) history_step: Duration,
+    // Target row of the extrapolation table of the Bulirsch-Stoer integrator for the next step
+    pub(crate) extrapolation_row: usize,
+    // Errors of the two previous accepted steps relative to the tolerance, most recent first, for the PI and PID step controllers
+    pub(crate) step_errors: [f64; 2],
+    // Magnitude of the next step in the fictitious time of the Sundman regularization, or zero until the first step
+    pub(crate) sundman_step: f64,
+    // Hooks called after each accepted step
+    pub(crate) hooks: Vec<Box<dyn StepHook<D::StateType>>>,
 }
 
 impl<'a, D: Dynamics, E: ErrorCtrl> PropInstance<'a, D, E>
@@ -63,30 +79,77 @@ where
         + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
         + Allocator<f64, <D::StateType as State>::VecLength>,
 {
+    /// Attaches a hook called after each accepted step, which may modify the state (e.g. to apply an impulsive maneuver).
+    /// The steps are shortened to land exactly on the epochs scheduled by the hook. Hooks are not stored in checkpoints.
+    pub fn with_hook<H: StepHook<D::StateType> + 'static>(mut self, hook: H) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
     /// Allows setting the step size of the propagator
     pub fn set_step(&mut self, step_size: Duration, fixed: bool) {
         self.step_size = step_size;
         self.fixed_step = fixed;
     }
 
+    /// Returns the state transition matrix from the initial state of this instance (or from the latest reset of the STM of its
+    /// state) to the current state. The STM must have been enabled, e.g. with `Propagator::with_stm`.
+    pub fn stm(
+        &self,
+    ) -> Result<OMatrix<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>, NyxError>
+    {
+        self.state.stm()
+    }
+
+    /// Returns a checkpoint of the full integration state of this instance, to resume it later with `Propagator::resume`
+    pub fn checkpoint(&self) -> Result<PropCheckpoint<D::StateType>, NyxError> {
+        Ok(PropCheckpoint {
+            state: self.state,
+            epoch_tai: self.state.epoch().to_tai_duration(),
+            state_vector: self.state.as_vector()?.as_slice().to_vec(),
+            stm: self.state.stm().is_ok(),
+            step_size: self.step_size,
+            fixed_step: self.fixed_step,
+            details: self.details,
+            integrator: (self.prop.kind, self.prop.order, self.prop.stages),
+            history: self
+                .history
+                .iter()
+                .map(|(epoch, f)| (epoch.to_tai_duration(), f.as_slice().to_vec()))
+                .collect(),
+            history_step: self.history_step,
+            extrapolation_row: self.extrapolation_row,
+            step_errors: self.step_errors,
+            sundman_step: self.sundman_step,
+            rng: None,
+        })
+    }
+
     #[allow(clippy::erasing_op)]
-    fn for_duration_channel_option(
+    pub(crate) fn for_duration_publish<F: FnMut(&D::StateType)>(
         &mut self,
         duration: Duration,
-        maybe_tx_chan: Option<Sender<D::StateType>>,
+        mut publish: F,
     ) -> Result<D::StateType, NyxError> {
         if duration == 0 * Unit::Second {
             return Ok(self.state);
         }
         let stop_time = self.state.epoch() + duration;
 
+        let _span = debug_span!(
+            "propagate",
+            start = %self.state.epoch(),
+            stop = %stop_time,
+        )
+        .entered();
+
         #[cfg(not(target_arch = "wasm32"))]
         let tick = Instant::now();
         let log_progress = duration.abs() >= 2 * Unit::Minute;
 
         if log_progress {
             // Prevent the print spam for orbit determination cases
-            info!("Propagating for {} until {}", duration, stop_time);
+            info!(%duration, "Propagating for {} until {}", duration, stop_time);
         }
         // Call `finally` on the current state to set anything up
         self.state = self.prop.dynamics.finally(self.state)?;
@@ -106,7 +169,7 @@ where
                     {
                         if log_progress {
                             let tock: Duration = tick.elapsed().into();
-                            info!("Done in {}", tock);
+                            info!(elapsed = %tock, "Done in {}", tock);
                         }
                     }
                     return Ok(self.state);
@@ -117,17 +180,17 @@ where
                 self.set_step(stop_time - epoch, true);
 
                 self.single_step()?;
-
-                // Publish to channel if provided
-                if let Some(ref chan) = maybe_tx_chan {
-                    if let Err(e) = chan.send(self.state) {
-                        warn!("{} when sending on channel", e)
-                    }
-                }
+                // Publish the state to the caller
+                publish(&self.state);
 
                 // Restore the step size for subsequent calls
                 self.set_step(prev_step_size, prev_step_kind);
 
+                if self.state.epoch() != stop_time {
+                    // The step was shortened to land on an epoch scheduled by a hook
+                    continue;
+                }
+
                 if backprop {
                     self.step_size = -self.step_size; // Restore to a positive step size
                 }
@@ -136,26 +199,22 @@ where
                 {
                     if log_progress {
                         let tock: Duration = tick.elapsed().into();
-                        info!("Done in {}", tock);
+                        info!(elapsed = %tock, "Done in {}", tock);
                     }
                 }
 
                 return Ok(self.state);
             } else {
                 self.single_step()?;
-                // Publish to channel if provided
-                if let Some(ref chan) = maybe_tx_chan {
-                    if let Err(e) = chan.send(self.state) {
-                        warn!("{} when sending on channel", e)
-                    }
-                }
+                // Publish the state to the caller
+                publish(&self.state);
             }
         }
     }
 
     /// This method propagates the provided Dynamics for the provided duration.
     pub fn for_duration(&mut self, duration: Duration) -> Result<D::StateType, NyxError> {
-        self.for_duration_channel_option(duration, None)
+        self.for_duration_publish(duration, |_| {})
     }
 
     /// This method propagates the provided Dynamics for the provided duration and publishes each state on the channel.
@@ -164,7 +223,21 @@ where
         duration: Duration,
         tx_chan: Sender<D::StateType>,
     ) -> Result<D::StateType, NyxError> {
-        self.for_duration_channel_option(duration, Some(tx_chan))
+        self.for_duration_publish(duration, |state| {
+            if let Err(e) = tx_chan.send(*state) {
+                warn!("{} when sending on channel", e)
+            }
+        })
+    }
+
+    /// Propagates the provided Dynamics for the provided duration and appends each state to the provided buffer, in the order of propagation
+    /// and without the start state. Unlike `for_duration_with_traj`, this runs on the same thread and does not allocate when the buffer is reused.
+    pub fn for_duration_into(
+        &mut self,
+        duration: Duration,
+        states: &mut Vec<D::StateType>,
+    ) -> Result<D::StateType, NyxError> {
+        self.for_duration_publish(duration, |state| states.push(*state))
     }
 
     /// Propagates the provided Dynamics until the provided epoch. Returns the end state.
@@ -184,8 +257,7 @@ where
     }
 
     /// Propagates the provided Dynamics for the provided duration and generate the trajectory of these dynamics on its own thread.
-    /// Returns the end state and the trajectory.
-    #[allow(clippy::map_clone)]
+    /// Returns the end state and the trajectory, which is a backward trajectory if the duration is negative.
     pub fn for_duration_with_traj(
         &mut self,
         duration: Duration,
@@ -207,19 +279,20 @@ where
             rx
         };
 
-        traj.states = rx.into_iter().par_bridge().collect();
-        // Push the start state -- will be reordered in the finalize call.
-        // For some reason, this must happen at the end -- can't figure out why.
-        traj.states.push(start_state);
-
+        // The states are received in the order of propagation, after the start state
+        traj.states = std::iter::once(start_state).chain(rx).collect();
+        if duration.is_negative() {
+            traj.states.reverse();
+            traj.backward = true;
+        }
+        // Remove the duplicate states, e.g. if the end state was also published
         traj.finalize();
 
         Ok((end_state, traj))
     }
 
     /// Propagates the provided Dynamics until the provided epoch and generate the trajectory of these dynamics on its own thread.
-    /// Returns the end state and the trajectory.
-    /// Known bug #190: Cannot generate a valid trajectory when propagating backward
+    /// Returns the end state and the trajectory, which is a backward trajectory if the end time is before the current epoch.
     pub fn until_epoch_with_traj(
         &mut self,
         end_time: Epoch,
@@ -247,7 +320,8 @@ where
     }
 
     /// Propagate until a specific event is found `trigger` times.
-    /// Returns the state found and the trajectory until `max_duration`
+    /// Returns the state found and the trajectory until `max_duration`.
+    /// Use `until_nth_event_online` to stop the propagation at the event without building the trajectory.
     pub fn until_nth_event<F: EventEvaluator<D::StateType>>(
         &mut self,
         max_duration: Duration,
@@ -258,7 +332,7 @@ where
         <DefaultAllocator as Allocator<f64, <D::StateType as State>::VecLength>>::Buffer: Send,
         D::StateType: Interpolatable,
     {
-        info!("Searching for {}", event);
+        info!(%event, "Searching for {}", event);
 
         let (_, traj) = self.for_duration_with_traj(max_duration)?;
         // Now, find the requested event
@@ -269,15 +343,255 @@ where
         }
     }
 
+    /// Propagate until a specific event is found once, detecting the event on the fly.
+    /// Returns the state at the event, and the propagator instance is left at that state.
+    pub fn until_event_online<F: EventEvaluator<D::StateType>>(
+        &mut self,
+        max_duration: Duration,
+        event: &F,
+    ) -> Result<D::StateType, NyxError> {
+        self.until_nth_event_online(max_duration, event, 0)
+    }
+
+    /// Propagate until a specific event is found `trigger` times, detecting the event on the fly.
+    ///
+    /// Unlike `until_nth_event`, which propagates for the whole `max_duration` and then searches the trajectory, this checks
+    /// for a sign change of the event after each step. The crossing is then refined with a Brent solver, where each evaluation
+    /// propagates a single step from the state before the crossing, and the propagation stops there. No trajectory is built.
+    /// Returns the state at the event, and the propagator instance is left at that state.
+    pub fn until_nth_event_online<F: EventEvaluator<D::StateType>>(
+        &mut self,
+        max_duration: Duration,
+        event: &F,
+        trigger: usize,
+    ) -> Result<D::StateType, NyxError> {
+        info!(%event, "Searching on the fly for {}", event);
+
+        let stop_time = self.state.epoch() + max_duration;
+        let backprop = max_duration.is_negative();
+        if backprop {
+            self.step_size = -self.step_size; // Invert the step size
+        }
+        let rslt = self.online_event_search(stop_time, backprop, event, trigger);
+        if backprop {
+            self.step_size = -self.step_size; // Restore to a positive step size
+        }
+        rslt
+    }
+
+    fn online_event_search<F: EventEvaluator<D::StateType>>(
+        &mut self,
+        stop_time: Epoch,
+        backprop: bool,
+        event: &F,
+        trigger: usize,
+    ) -> Result<D::StateType, NyxError> {
+        // Call `finally` on the current state to set anything up
+        self.state = self.prop.dynamics.finally(self.state)?;
+        let mut prev_state = self.state;
+        let mut found = 0;
+
+        while self.state.epoch() != stop_time {
+            let remaining = stop_time - self.state.epoch();
+            if (!backprop && self.step_size > remaining) || (backprop && self.step_size < remaining)
+            {
+                // Take one final step of exactly the needed duration until the stop time
+                let prev_step_size = self.step_size;
+                let prev_step_kind = self.fixed_step;
+                self.set_step(remaining, true);
+                let rslt = self.single_step();
+                self.set_step(prev_step_size, prev_step_kind);
+                rslt?;
+            } else {
+                self.single_step()?;
+            }
+
+            if event.eval_crossing(&prev_state, &self.state) {
+                let next_state = self.state;
+                match self.refine_event(prev_state, next_state, event)? {
+                    Some(event_state) if found == trigger => return Ok(event_state),
+                    Some(_) => found += 1,
+                    None => {}
+                }
+                // Resume the propagation from the state after the crossing
+                self.state = next_state;
+            }
+            prev_state = self.state;
+        }
+
+        Err(NyxError::UnsufficientTriggers(trigger, found))
+    }
+
+    /// Propagates a single step of the provided duration from the provided state, and sets the state of this instance to the result.
+    fn step_from(
+        &mut self,
+        from: D::StateType,
+        duration: Duration,
+    ) -> Result<D::StateType, NyxError> {
+        self.state = from;
+        if duration == Duration::ZERO {
+            return Ok(from);
+        }
+        let prev_step_size = self.step_size;
+        let prev_step_kind = self.fixed_step;
+        let prev_sundman_step = self.sundman_step;
+        self.set_step(duration, true);
+        // The hooks already acted on the steps which bracket this one
+        let hooks = std::mem::take(&mut self.hooks);
+        let rslt = self.single_step();
+        self.hooks = hooks;
+        self.set_step(prev_step_size, prev_step_kind);
+        self.sundman_step = prev_sundman_step;
+        rslt.map(|_| self.state)
+    }
+
+    /// Finds the event between the provided states, which bracket its crossing, with a Brent solver.
+    /// Leaves the propagator instance at the event. Returns None if the sign change is a discontinuity of the event
+    /// (e.g. an angle wrapping around) instead of a root, like `Traj::find_bracketed`.
+    fn refine_event<F: EventEvaluator<D::StateType>>(
+        &mut self,
+        prev_state: D::StateType,
+        next_state: D::StateType,
+        event: &F,
+    ) -> Result<Option<D::StateType>, NyxError> {
+        let max_iter = 50;
+        let epoch_precision = event.epoch_precision().to_seconds();
+        let value_precision = event.value_precision().abs();
+        let start = prev_state.epoch();
+
+        // Search in seconds past the state before the crossing
+        let mut xa = 0.0;
+        let mut xb = (next_state.epoch() - start).to_seconds();
+        let mut ya = event.eval_bracketed(&prev_state, &next_state, &prev_state);
+        let mut yb = event.eval_bracketed(&prev_state, &next_state, &next_state);
+        let (mut xc, mut yc, mut xd) = (xa, ya, xa);
+        let mut flag = true;
+
+        // The Brent solver, as in `Traj::find_bracketed`
+        for _ in 0..max_iter {
+            if ya.abs() < yb.abs() {
+                std::mem::swap(&mut xa, &mut xb);
+                std::mem::swap(&mut ya, &mut yb);
+            }
+            if yb.abs() <= value_precision {
+                debug!("{event} -- found with |{yb}| @ {xb} s past {start}");
+                return self.step_from(prev_state, xb * Unit::Second).map(Some);
+            } else if (xa - xb).abs() <= epoch_precision {
+                debug!("{event} -- discontinuity @ {xb} s past {start}");
+                return Ok(None);
+            }
+            let mut s = if (ya - yc).abs() > f64::EPSILON && (yb - yc).abs() > f64::EPSILON {
+                xa * yb * yc / ((ya - yb) * (ya - yc))
+                    + xb * ya * yc / ((yb - ya) * (yb - yc))
+                    + xc * ya * yb / ((yc - ya) * (yc - yb))
+            } else {
+                xb - yb * (xb - xa) / (yb - ya)
+            };
+            let cond1 = (s - xb) * (s - (3.0 * xa + xb) / 4.0) > 0.0;
+            let cond2 = flag && (s - xb).abs() >= (xb - xc).abs() / 2.0;
+            let cond3 = !flag && (s - xb).abs() >= (xc - xd).abs() / 2.0;
+            let cond4 = flag && (xb - xc).abs() <= epoch_precision;
+            let cond5 = !flag && (xc - xd).abs() <= epoch_precision;
+            if cond1 || cond2 || cond3 || cond4 || cond5 {
+                s = (xa + xb) / 2.0;
+                flag = true;
+            } else {
+                flag = false;
+            }
+            let ys = event.eval_bracketed(
+                &prev_state,
+                &next_state,
+                &self.step_from(prev_state, s * Unit::Second)?,
+            );
+            xd = xc;
+            xc = xb;
+            yc = yb;
+            if ya * ys < 0.0 {
+                // Root bracketed between a and s
+                xb = s;
+                yb = ys;
+            } else {
+                // Root bracketed between s and b
+                xa = s;
+                ya = ys;
+            }
+        }
+        Err(NyxError::MaxIterReached(format!(
+            "Brent solver failed after {max_iter} iterations",
+        )))
+    }
+
     /// Take a single propagator step and emit the result on the TX channel (if enabled)
     pub fn single_step(&mut self) -> Result<(), NyxError> {
-        let (t, state_vec) = self.derive()?;
+        // Land exactly on the next epoch scheduled by a hook
+        let epoch = self.state.epoch();
+        let scheduled = self.next_scheduled_epoch();
+        let prev_step_size = self.step_size;
+        if let Some(target) = scheduled {
+            self.step_size = target - epoch;
+        }
+
+        let (t, state_vec) = match self.prop.kind {
+            IntegratorKind::RungeKutta { sundman: None } => self.derive()?,
+            IntegratorKind::RungeKutta {
+                sundman: Some(exponent),
+            } => self.derive_sundman(exponent)?,
+            IntegratorKind::AdamsBashforthMoulton { .. } => self.derive_multistep()?,
+            IntegratorKind::BulirschStoer { rows } => self.derive_extrapolation(rows)?,
+            IntegratorKind::Symplectic => self.derive_symplectic()?,
+            IntegratorKind::Nystrom => self.derive_nystrom()?,
+        };
         self.state.set(self.state.epoch() + t, &state_vec)?;
         self.state = self.prop.dynamics.finally(self.state)?;
+        for hook in &mut self.hooks {
+            hook.after_step(&mut self.state, t.is_negative())?;
+        }
+        // The derivatives of the multi-step history are invalid if `finally` or a hook changed the state, e.g. its central body
+        if self.abm.is_some() && self.state.as_vector()? != state_vec {
+            self.history.clear();
+        }
+        if scheduled == Some(self.state.epoch()) {
+            // Resume with the step size from before landing on the scheduled epoch
+            self.step_size = prev_step_size;
+        }
+
+        trace!(
+            epoch = %self.state.epoch(),
+            step = %self.details.step,
+            error = self.details.error,
+            attempts = self.details.attempts,
+            "step"
+        );
 
         Ok(())
     }
 
+    /// Returns the epoch scheduled by a hook or by the output epochs of the propagator which is closest to the current epoch and
+    /// strictly within the next step, if any
+    fn next_scheduled_epoch(&self) -> Option<Epoch> {
+        let epoch = self.state.epoch();
+        let end = epoch + self.step_size;
+        // The output epochs are sorted, so only the first one past the current epoch may be within the next step
+        let output_epochs = &self.prop.output_epochs;
+        let next_output = if self.step_size.is_negative() {
+            output_epochs[..output_epochs.partition_point(|target| *target < epoch)].last()
+        } else {
+            output_epochs[output_epochs.partition_point(|target| *target <= epoch)..].first()
+        };
+        self.hooks
+            .iter()
+            .flat_map(|hook| hook.scheduled_epochs().iter().copied())
+            .chain(next_output.copied())
+            .filter(|target| {
+                if self.step_size.is_negative() {
+                    end < *target && *target < epoch
+                } else {
+                    epoch < *target && *target < end
+                }
+            })
+            .min_by_key(|target| (*target - epoch).abs())
+    }
+
     /// This method integrates whichever function is provided as `d_xdt`. Everything passed to this function is in **seconds**.
     ///
     /// This function returns the step sized used (as a Duration) and the new state as y_{n+1} = y_n + \frac{dy_n}{dt}.
@@ -343,6 +657,8 @@ where
                 {
                     if self.details.attempts >= self.prop.opts.attempts {
                         warn!(
+                            epoch = %self.state.epoch(),
+                            error = self.details.error,
                             "Could not further decrease step size: maximum number of attempts reached ({})",
                             self.details.attempts
                         );
@@ -352,10 +668,7 @@ where
                     if self.details.error < self.prop.opts.tolerance {
                         // Let's increase the step size for the next iteration.
                         // Error is less than tolerance, let's attempt to increase the step for the next iteration.
-                        let proposed_step = 0.9
-                            * step_size
-                            * (self.prop.opts.tolerance / self.details.error)
-                                .powf(1.0 / f64::from(self.prop.order));
+                        let proposed_step = self.accepted_step_s(step_size);
                         step_size = if proposed_step > self.prop.opts.max_step.to_seconds() {
                             self.prop.opts.max_step.to_seconds()
                         } else {
@@ -384,8 +697,562 @@ where
         }
     }
 
+    /// Takes one adaptive Runge Kutta step in the fictitious time `s` of the Sundman regularization, where `dt/ds = r^exponent`,
+    /// integrating the time alongside the state.
+    ///
+    /// The step size of this instance is the maximum duration of the step: a step which would exceed it is shortened to land
+    /// exactly on it, and a shorter step is returned otherwise. A fixed step (e.g. the final step until the stop time or the
+    /// steps of the event refinement) is instead made of as many adaptive steps as needed to land exactly on its duration.
+    /// After each call, the step size is set to twice the predicted duration of the next step, such that it seldom limits it.
+    fn derive_sundman(
+        &mut self,
+        exponent: f64,
+    ) -> Result<(Duration, OVector<f64, <D::StateType as State>::VecLength>), NyxError> {
+        let max_dt = self.step_size.to_seconds();
+        let sign = max_dt.signum();
+        let min_step = self.prop.opts.min_step.to_seconds();
+        let max_step = self.prop.opts.max_step.to_seconds();
+        let mut state_vec = self.state.as_vector()?;
+        let mut elapsed = 0.0;
+        self.details.attempts = 1;
+        loop {
+            let remaining = max_dt - elapsed;
+            let g0 = sundman_factor(&state_vec, exponent);
+            if self.sundman_step <= 0.0 {
+                self.sundman_step = remaining.abs() / g0;
+            }
+            // The bounds of the step in time are converted into bounds in fictitious time at the start of the step
+            let mut ds = sign * self.sundman_step.clamp(min_step / g0, max_step / g0);
+            let (mut dt, mut next_state) = loop {
+                let (dt, next_state, error) =
+                    self.sundman_step_from(elapsed, &state_vec, ds, exponent)?;
+                self.details.error = error;
+                if error <= self.prop.opts.tolerance
+                    || ds.abs() <= min_step / g0
+                    || self.details.attempts >= self.prop.opts.attempts
+                {
+                    if self.details.attempts >= self.prop.opts.attempts {
+                        warn!(
+                            epoch = %self.state.epoch(),
+                            error,
+                            "Could not further decrease step size: maximum number of attempts reached ({})",
+                            self.details.attempts
+                        );
+                    }
+                    if error < self.prop.opts.tolerance {
+                        self.sundman_step = self.accepted_step_s(ds.abs());
+                    } else {
+                        self.sundman_step = ds.abs();
+                    }
+                    break (dt, next_state);
+                }
+                // Error is too high, let's decrease the step size down to the minimum step and try again
+                self.details.attempts += 1;
+                let proposed_step = 0.9
+                    * ds.abs()
+                    * (self.prop.opts.tolerance / error).powf(1.0 / f64::from(self.prop.order - 1));
+                ds = sign * proposed_step.max(min_step / g0);
+            };
+
+            if dt.abs() >= remaining.abs() {
+                // Shorten the step with Newton iterations on its fictitious duration to land exactly on the maximum duration
+                let max_iter = 20;
+                let mut converged = false;
+                for _ in 0..max_iter {
+                    let residual = dt - remaining;
+                    if residual.abs() < 1e-9 {
+                        converged = true;
+                        break;
+                    }
+                    // The derivative of the duration with respect to the fictitious duration is the factor at the end of the step
+                    ds -= residual / sundman_factor(&next_state, exponent);
+                    (dt, next_state, _) =
+                        self.sundman_step_from(elapsed, &state_vec, ds, exponent)?;
+                }
+                if !converged {
+                    return Err(NyxError::MaxIterReached(format!(
+                        "Sundman step did not land on {} after {max_iter} iterations",
+                        self.step_size
+                    )));
+                }
+                self.details.step = self.step_size;
+                self.step_size = sign
+                    * 2.0
+                    * self.sundman_step
+                    * sundman_factor(&next_state, exponent)
+                    * Unit::Second;
+                return Ok((self.details.step, next_state));
+            }
+
+            state_vec = next_state;
+            elapsed += dt;
+            if !self.fixed_step {
+                self.details.step = elapsed * Unit::Second;
+                self.step_size = sign
+                    * 2.0
+                    * self.sundman_step
+                    * sundman_factor(&state_vec, exponent)
+                    * Unit::Second;
+                return Ok((self.details.step, state_vec));
+            }
+        }
+    }
+
+    /// Takes a single Runge Kutta step of the provided fictitious duration from the provided state vector, `t0` seconds past the
+    /// epoch of the state of this instance. Returns the duration of the step in seconds, the new state vector and its error.
+    fn sundman_step_from(
+        &mut self,
+        t0: f64,
+        state_vec: &OVector<f64, <D::StateType as State>::VecLength>,
+        ds: f64,
+        exponent: f64,
+    ) -> Result<(f64, OVector<f64, <D::StateType as State>::VecLength>, f64), NyxError> {
+        let state_ctx = &self.state;
+        // Derivatives of the time with respect to the fictitious time at each stage
+        let mut kt = Vec::with_capacity(self.prop.stages);
+        let g = sundman_factor(state_vec, exponent);
+        self.k[0] = g * self.prop.dynamics.eom(t0, state_vec, state_ctx)?;
+        kt.push(g);
+        let mut a_idx: usize = 0;
+        for i in 0..(self.prop.stages - 1) {
+            let mut wi = OVector::<f64, <D::StateType as State>::VecLength>::from_element(0.0);
+            let mut wt = 0.0;
+            for (kj, ktj) in self.k[0..i + 1].iter().zip(&kt) {
+                let a_ij = self.prop.a_coeffs[a_idx];
+                wi += a_ij * kj;
+                wt += a_ij * ktj;
+                a_idx += 1;
+            }
+            let stage_vec = state_vec + ds * wi;
+            let g = sundman_factor(&stage_vec, exponent);
+            self.k[i + 1] = g * self
+                .prop
+                .dynamics
+                .eom(t0 + ds * wt, &stage_vec, state_ctx)?;
+            kt.push(g);
+        }
+
+        let mut next_state = state_vec.clone();
+        let mut dt = 0.0;
+        let mut error_est = OVector::<f64, <D::StateType as State>::VecLength>::from_element(0.0);
+        let mut error_t = 0.0;
+        for (i, (ki, kti)) in self.k.iter().zip(&kt).enumerate() {
+            let b_i = self.prop.b_coeffs[i];
+            let b_i_star = self.prop.b_coeffs[i + self.prop.stages];
+            next_state += ds * b_i * ki;
+            dt += ds * b_i * kti;
+            error_est += ds * (b_i - b_i_star) * ki;
+            error_t += ds * (b_i - b_i_star) * kti;
+        }
+        // The error of the integrated time shifts the state along its derivative
+        error_est += error_t / kt[0] * &self.k[0];
+        let error = E::estimate(&error_est, &next_state, state_vec);
+        Ok((dt, next_state, error))
+    }
+
+    /// Takes one Adams-Bashforth-Moulton predictor-corrector step, or a fixed Runge Kutta step until enough derivatives are known.
+    ///
+    /// Like `derive`, this returns the step size used and the new state, and updates the step size for the next call.
+    fn derive_multistep(
+        &mut self,
+    ) -> Result<(Duration, OVector<f64, <D::StateType as State>::VecLength>), NyxError> {
+        let coeffs = self.abm.clone().unwrap();
+        let order = coeffs.predictor.len();
+        let epoch = self.state.epoch();
+        let h_dur = self.step_size;
+        let h = h_dur.to_seconds();
+
+        // The history is only usable if it ends at the previous step and was taken with the current step size
+        if let Some((prev_epoch, _)) = self.history.front() {
+            if self.history_step != h_dur || epoch - *prev_epoch != h_dur {
+                self.history.clear();
+            }
+        }
+        self.history_step = h_dur;
+
+        if self.history.len() + 1 < order {
+            // Start up with a fixed Runge Kutta step, whose first stage is the derivative at the current state
+            let fixed_step = self.fixed_step;
+            self.fixed_step = true;
+            let rslt = self.derive();
+            self.fixed_step = fixed_step;
+            let (step, next_state) = rslt?;
+            self.step_size = h_dur;
+            self.history.push_front((epoch, self.k[0].clone()));
+            self.history.truncate(2 * order);
+            return Ok((step, next_state));
+        }
+
+        let state_vec = self.state.as_vector()?;
+        let f_n = self.prop.dynamics.eom(0.0, &state_vec, &self.state)?;
+
+        // Predict
+        let mut predicted = &state_vec + h * coeffs.predictor[0] * &f_n;
+        for (beta, (_, f)) in coeffs.predictor[1..].iter().zip(self.history.iter()) {
+            predicted += h * beta * f;
+        }
+        // Evaluate
+        let f_predicted = self.prop.dynamics.eom(h, &predicted, &self.state)?;
+        // Correct
+        let mut corrected =
+            &state_vec + h * coeffs.corrector[0] * f_predicted + h * coeffs.corrector[1] * &f_n;
+        for (gamma, (_, f)) in coeffs.corrector[2..].iter().zip(self.history.iter()) {
+            corrected += h * gamma * f;
+        }
+
+        self.details.attempts = 1;
+        self.details.step = h_dur;
+
+        if self.fixed_step {
+            self.details.error = 0.0;
+        } else {
+            let error_est = coeffs.milne * (&corrected - &predicted);
+            self.details.error = E::estimate(&error_est, &corrected, &state_vec);
+
+            if self.details.error > self.prop.opts.tolerance
+                && h.abs() > self.prop.opts.min_step.to_seconds()
+            {
+                // Restart with half the step, the start up will rebuild the history
+                let half_step = (0.5 * h.abs()).max(self.prop.opts.min_step.to_seconds());
+                self.step_size = half_step * h.signum() * Unit::Second;
+                self.history.clear();
+                return self.derive_multistep();
+            }
+        }
+
+        self.history.push_front((epoch, f_n));
+        self.history.truncate(2 * order);
+
+        // The error scales with the step to the power of the order plus one: double the step if there is enough margin
+        // and enough history, keeping every other derivative.
+        if !self.fixed_step
+            && self.details.error < self.prop.opts.tolerance / 2_f64.powi(order as i32 + 2)
+            && 2.0 * h.abs() <= self.prop.opts.max_step.to_seconds()
+            && self.history.len() >= 2 * (order - 1)
+        {
+            self.history = self.history.iter().skip(1).step_by(2).cloned().collect();
+            self.step_size = 2 * h_dur;
+            self.history_step = self.step_size;
+        }
+
+        Ok((h_dur, corrected))
+    }
+
+    /// Integrates the provided step with Gragg's modified midpoint rule with `substeps` sub-steps, including the final smoothing.
+    /// The derivative at the current state `f0` is shared by all calls.
+    fn modified_midpoint(
+        &self,
+        state_vec: &OVector<f64, <D::StateType as State>::VecLength>,
+        f0: &OVector<f64, <D::StateType as State>::VecLength>,
+        step_size: f64,
+        substeps: usize,
+    ) -> Result<OVector<f64, <D::StateType as State>::VecLength>, NyxError> {
+        let h = step_size / substeps as f64;
+        let mut z_prev = state_vec.clone();
+        let mut z = state_vec + h * f0;
+        for m in 1..substeps {
+            let f = self.prop.dynamics.eom(m as f64 * h, &z, &self.state)?;
+            let z_next = &z_prev + 2.0 * h * f;
+            z_prev = z;
+            z = z_next;
+        }
+        let f = self.prop.dynamics.eom(step_size, &z, &self.state)?;
+        Ok(0.5 * (z_prev + &z + h * f))
+    }
+
+    /// Takes one Bulirsch-Stoer step, adapting the step size and the number of rows of the extrapolation table.
+    ///
+    /// Like `derive`, this returns the step size used and the new state, and updates the step size for the next call.
+    fn derive_extrapolation(
+        &mut self,
+        max_rows: usize,
+    ) -> Result<(Duration, OVector<f64, <D::StateType as State>::VecLength>), NyxError> {
+        let tolerance = self.prop.opts.tolerance;
+        let state_vec = self.state.as_vector()?;
+        let f0 = self.prop.dynamics.eom(0.0, &state_vec, &self.state)?;
+        self.details.attempts = 1;
+        let mut step_size = self.step_size.to_seconds();
+
+        loop {
+            let target = self.extrapolation_row.clamp(1, max_rows - 2);
+            let last_row = if self.fixed_step {
+                max_rows - 1
+            } else {
+                target + 1
+            };
+            // Optimal step size and work per unit of time of each row
+            let mut h_opt = vec![step_size; last_row + 1];
+            let mut work = vec![f64::INFINITY; last_row + 1];
+
+            let mut table: Vec<OVector<f64, <D::StateType as State>::VecLength>> = Vec::new();
+            let mut accepted = None;
+            for row in 0..=last_row {
+                // Aitken-Neville extrapolation in the square of the sub-step
+                let mut new_row =
+                    vec![self.modified_midpoint(&state_vec, &f0, step_size, bs_substeps(row))?];
+                for k in 1..=row {
+                    let ratio = (bs_substeps(row) as f64 / bs_substeps(row - k) as f64).powi(2);
+                    let extrapolated =
+                        &new_row[k - 1] + (&new_row[k - 1] - &table[k - 1]) / (ratio - 1.0);
+                    new_row.push(extrapolated);
+                }
+                table = new_row;
+
+                if row == 0 {
+                    continue;
+                }
+
+                let error_est = &table[row] - &table[row - 1];
+                let error = E::estimate(&error_est, &table[row], &state_vec);
+                // The error estimate of this row is of order 2 row + 1
+                let factor = if error > 0.0 {
+                    (0.94 * (0.65 * tolerance / error).powf(1.0 / (2 * row + 1) as f64))
+                        .clamp(0.02, 4.0)
+                } else {
+                    4.0
+                };
+                h_opt[row] = step_size * factor;
+                work[row] = bs_work(row) / h_opt[row].abs();
+                self.details.error = error;
+
+                if self.fixed_step {
+                    if row == last_row {
+                        accepted = Some(row);
+                    }
+                } else if error <= tolerance && row + 1 >= target {
+                    accepted = Some(row);
+                    break;
+                }
+            }
+
+            let next_state = table.pop().unwrap();
+
+            if self.fixed_step {
+                self.details.step = self.step_size;
+                return Ok((self.details.step, next_state));
+            }
+
+            match accepted {
+                Some(row) => {
+                    self.details.step = step_size * Unit::Second;
+                    // Select the number of rows which minimizes the work per unit of time for the next step
+                    let (next_row, next_step) = if row > 1 && work[row - 1] < 0.8 * work[row] {
+                        (row - 1, h_opt[row - 1])
+                    } else if row + 1 < max_rows - 1 && work[row] < 0.9 * work[row - 1] {
+                        (row + 1, h_opt[row] * bs_work(row + 1) / bs_work(row))
+                    } else {
+                        (row, h_opt[row])
+                    };
+                    self.extrapolation_row = next_row;
+                    let max_step = self.prop.opts.max_step.to_seconds();
+                    self.step_size =
+                        next_step.abs().min(max_step) * next_step.signum() * Unit::Second;
+                    return Ok((self.details.step, next_state));
+                }
+                None => {
+                    if step_size.abs() <= self.prop.opts.min_step.to_seconds()
+                        || self.details.attempts >= self.prop.opts.attempts
+                    {
+                        if self.details.attempts >= self.prop.opts.attempts {
+                            warn!(
+                                epoch = %self.state.epoch(),
+                                error = self.details.error,
+                                "Could not further decrease step size: maximum number of attempts reached ({})",
+                                self.details.attempts
+                            );
+                        }
+                        self.details.step = step_size * Unit::Second;
+                        self.step_size = self.details.step;
+                        return Ok((self.details.step, next_state));
+                    }
+                    // Reject the step and retry with a smaller step
+                    self.details.attempts += 1;
+                    let proposed_step = h_opt[last_row].abs().min(0.7 * step_size.abs());
+                    step_size = proposed_step.max(self.prop.opts.min_step.to_seconds())
+                        * step_size.signum();
+                }
+            }
+        }
+    }
+
+    /// Takes one fixed step of the symplectic integrator, whose A and B coefficients are the drift and kick coefficients.
+    ///
+    /// The state vector starts with the position and the velocity, and the dynamics are separable (enforced by the construction
+    /// of the propagator), so only the acceleration returned by the equations of motion is used.
+    fn derive_symplectic(
+        &mut self,
+    ) -> Result<(Duration, OVector<f64, <D::StateType as State>::VecLength>), NyxError> {
+        if self.state.stm().is_ok() {
+            return Err(NyxError::CustomError(
+                "symplectic integrators do not support the propagation of the STM".to_string(),
+            ));
+        }
+        let h = self.step_size.to_seconds();
+        let mut state_vec = self.state.as_vector()?;
+        let mut delta_t = 0.0;
+        for (drift, kick) in self.prop.a_coeffs.iter().zip(self.prop.b_coeffs.iter()) {
+            delta_t += drift * h;
+            for i in 0..3 {
+                state_vec[i] += drift * h * state_vec[i + 3];
+            }
+            if *kick != 0.0 {
+                let deriv = self.prop.dynamics.eom(delta_t, &state_vec, &self.state)?;
+                for i in 3..6 {
+                    state_vec[i] += kick * h * deriv[i];
+                }
+            }
+        }
+
+        self.details.step = self.step_size;
+        self.details.error = 0.0;
+        self.details.attempts = 1;
+        Ok((self.step_size, state_vec))
+    }
+
+    /// Takes one Runge-Kutta-Nyström step of second order orbital dynamics, where only the acceleration of the equations of
+    /// motion is used. The error is estimated from the embedded position and velocity solutions.
+    fn derive_nystrom(
+        &mut self,
+    ) -> Result<(Duration, OVector<f64, <D::StateType as State>::VecLength>), NyxError> {
+        if self.state.stm().is_ok() {
+            return Err(NyxError::CustomError(
+                "Runge-Kutta-Nyström integrators do not support the propagation of the STM"
+                    .to_string(),
+            ));
+        }
+        let state_vec = &self.state.as_vector()?;
+        let state_ctx = &self.state;
+        let stages = self.prop.stages;
+        self.details.attempts = 1;
+        let mut step_size = self.step_size.to_seconds();
+        loop {
+            // Only the acceleration, i.e. the last three items of the derivative of the position and velocity, is used
+            self.k[0] = self.prop.dynamics.eom(0.0, state_vec, state_ctx)?;
+            let mut a_idx: usize = 0;
+            for i in 1..stages {
+                let mut stage_vec = state_vec.clone();
+                // \sum_j \bar{a}_{ij} = c_i^2 / 2
+                let mut half_ci_sq = 0.0;
+                for kj in &self.k[0..i] {
+                    let a_ij = self.prop.a_coeffs[a_idx];
+                    half_ci_sq += a_ij;
+                    for d in 0..3 {
+                        stage_vec[d] += step_size.powi(2) * a_ij * kj[d + 3];
+                    }
+                    a_idx += 1;
+                }
+                let ci = (2.0 * half_ci_sq).sqrt();
+                for d in 0..3 {
+                    stage_vec[d] += ci * step_size * state_vec[d + 3];
+                }
+                self.k[i] = self
+                    .prop
+                    .dynamics
+                    .eom(ci * step_size, &stage_vec, state_ctx)?;
+            }
+
+            let mut next_state = state_vec.clone();
+            let mut error_est =
+                OVector::<f64, <D::StateType as State>::VecLength>::from_element(0.0);
+            for d in 0..3 {
+                next_state[d] += step_size * state_vec[d + 3];
+            }
+            for (i, ki) in self.k.iter().enumerate() {
+                let pos_b_i = self.prop.b_coeffs[i];
+                let vel_b_i = self.prop.b_coeffs[i + 2 * stages];
+                for d in 0..3 {
+                    next_state[d] += step_size.powi(2) * pos_b_i * ki[d + 3];
+                    next_state[d + 3] += step_size * vel_b_i * ki[d + 3];
+                }
+                if !self.fixed_step {
+                    let pos_b_i_star = self.prop.b_coeffs[i + stages];
+                    let vel_b_i_star = self.prop.b_coeffs[i + 3 * stages];
+                    for d in 0..3 {
+                        error_est[d] += step_size.powi(2) * (pos_b_i - pos_b_i_star) * ki[d + 3];
+                        error_est[d + 3] += step_size * (vel_b_i - vel_b_i_star) * ki[d + 3];
+                    }
+                }
+            }
+
+            if self.fixed_step {
+                self.details.step = self.step_size;
+                return Ok((self.details.step, next_state));
+            }
+
+            self.details.error = E::estimate(&error_est, &next_state, state_vec);
+            if self.details.error <= self.prop.opts.tolerance
+                || step_size.abs() <= self.prop.opts.min_step.to_seconds()
+                || self.details.attempts >= self.prop.opts.attempts
+            {
+                if self.details.attempts >= self.prop.opts.attempts {
+                    warn!(
+                        epoch = %self.state.epoch(),
+                        error = self.details.error,
+                        "Could not further decrease step size: maximum number of attempts reached ({})",
+                        self.details.attempts
+                    );
+                }
+
+                self.details.step = step_size * Unit::Second;
+                if self.details.error < self.prop.opts.tolerance {
+                    // Increase the step for the next iteration, up to the maximum step
+                    let proposed_step = self.accepted_step_s(step_size.abs());
+                    step_size = proposed_step.min(self.prop.opts.max_step.to_seconds())
+                        * step_size.signum();
+                }
+                self.step_size = step_size * Unit::Second;
+                return Ok((self.details.step, next_state));
+            } else {
+                // Error is too high, let's decrease the step size down to the minimum step and try again
+                self.details.attempts += 1;
+                let proposed_step = 0.9
+                    * step_size.abs()
+                    * (self.prop.opts.tolerance / self.details.error)
+                        .powf(1.0 / f64::from(self.prop.order - 1));
+                step_size =
+                    proposed_step.max(self.prop.opts.min_step.to_seconds()) * step_size.signum();
+            }
+        }
+    }
+
+    /// Returns the magnitude in seconds of the next step after a step of `step_s` seconds whose error is below the tolerance,
+    /// proposed by the step controller of the options.
+    fn accepted_step_s(&mut self, step_s: f64) -> f64 {
+        let tolerance = self.prop.opts.tolerance;
+        let order = f64::from(self.prop.order);
+        match self.prop.opts.step_ctrl {
+            StepController::Integral => {
+                0.9 * step_s * (tolerance / self.details.error).powf(1.0 / order)
+            }
+            step_ctrl => {
+                let (k_i, k_p, k_d) = step_ctrl.gains();
+                // Bound the relative errors away from zero so that the factor remains finite
+                let err = (self.details.error / tolerance).max(1e-10);
+                let [prev_err, prev_err2] = self.step_errors;
+                self.step_errors = [err, prev_err];
+                let factor = (err.powf(-(k_i + k_p + k_d))
+                    * prev_err.powf(k_p + 2.0 * k_d)
+                    * prev_err2.powf(-k_d))
+                .powf(1.0 / order);
+                // The step may not grow right after a rejection (Gustafsson), and changes by at most a factor of five
+                let max_factor = if self.details.attempts > 1 { 1.0 } else { 5.0 };
+                0.9 * step_s * factor.clamp(0.2, max_factor)
+            }
+        }
+    }
+
     /// Copy the details of the latest integration step.
     pub fn latest_details(&self) -> IntegrationDetails {
         self.details
     }
 }
+
+/// Returns the Sundman factor `dt/ds = r^exponent` of the provided state vector, whose first three components are the position.
+fn sundman_factor<N: DimName>(state_vec: &OVector<f64, N>, exponent: f64) -> f64
+where
+    DefaultAllocator: Allocator<f64, N>,
+{
+    (state_vec[0].powi(2) + state_vec[1].powi(2) + state_vec[2].powi(2))
+        .sqrt()
+        .powf(exponent)
+}
@@ -0,0 +1,304 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{ErrorCtrl, Propagator};
+use crate::dynamics::Dynamics;
+use crate::errors::NyxError;
+use crate::io::ConfigError;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, DimName, OVector};
+use crate::md::trajectory::{Interpolatable, Traj};
+use crate::time::{Duration, Epoch};
+use crate::State;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// A state stored in the cache, whose epoch and vector are restored exactly, and whose other fields (e.g. its frame) are
+/// those of one of the templates of the cache
+#[derive(Serialize, Deserialize)]
+struct MemoState {
+    template: usize,
+    epoch_tai: Duration,
+    vector: Vec<f64>,
+    stm: bool,
+}
+
+/// A cached propagation, along with the full description of its scenario to guard against hash collisions.
+///
+/// The templates are only stored once since deserializing a state is slow, e.g. loading its frame.
+#[derive(Serialize, Deserialize)]
+struct MemoEntry<S> {
+    scenario: String,
+    templates: Vec<S>,
+    final_state: MemoState,
+    states: Vec<MemoState>,
+    backward: bool,
+}
+
+/// Caches the results of propagations on disk, keyed on a hash of their scenario, i.e. of the initial state, the dynamics, the
+/// propagator options and integrator, and the end epoch. A propagation whose scenario did not change is loaded from the
+/// cache instead of being computed again, which speeds up iterative mission design sessions where only downstream
+/// parameters change.
+///
+/// **Note:** the dynamics are identified by their display, so dynamics which only differ by a parameter missing from their
+/// display share their cached results: in that case, `clear` the cache after changing that parameter.
+#[derive(Clone, Debug)]
+pub struct PropMemo {
+    pub dir: PathBuf,
+}
+
+impl PropMemo {
+    /// Initializes a cache of propagations in the provided directory, which is created if needed
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self, NyxError> {
+        fs::create_dir_all(&dir).map_err(ConfigError::ReadError)?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Returns the full description of the scenario of a propagation, from which its cache key is computed
+    pub fn scenario<D: Dynamics + fmt::Display, E: ErrorCtrl>(
+        setup: &Propagator<D, E>,
+        state: &D::StateType,
+        end: Epoch,
+    ) -> Result<String, NyxError>
+    where
+        DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::VecLength>,
+        D::StateType: Serialize,
+    {
+        let state_yaml = serde_yaml::to_string(state).map_err(ConfigError::ParseError)?;
+        // The vector and the epoch are written exactly, unlike some fields of the serialized state
+        Ok(format!(
+            "nyx {}\ndynamics: {}\noptions: init step {}, min step {}, max step {}, tolerance {:e}, {} attempts, fixed step {}, auto initial step {}, {:?}, output epochs {:?}, error control {}\nintegrator: {:?} of order {}, {} stages, STM {}\na: {:?}\nb: {:?}\nstate: {state_yaml}\nstate epoch: {}\nstate vector: {:?}\nend epoch: {}",
+            env!("CARGO_PKG_VERSION"),
+            setup.dynamics,
+            setup.opts.init_step,
+            setup.opts.min_step,
+            setup.opts.max_step,
+            setup.opts.tolerance,
+            setup.opts.attempts,
+            setup.opts.fixed_step,
+            setup.opts.auto_init_step,
+            setup.opts.step_ctrl,
+            setup
+                .output_epochs
+                .iter()
+                .map(|epoch| epoch.to_tai_duration().total_nanoseconds())
+                .collect::<Vec<_>>(),
+            std::any::type_name::<E>(),
+            setup.kind,
+            setup.order,
+            setup.stages,
+            setup.stm,
+            setup.a_coeffs,
+            setup.b_coeffs,
+            state.epoch().to_tai_duration().total_nanoseconds(),
+            state.as_vector()?.as_slice(),
+            end.to_tai_duration().total_nanoseconds(),
+        ))
+    }
+
+    /// Returns the path of the cache file of the provided scenario
+    fn path(&self, scenario: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.yaml", fnv1a(scenario)))
+    }
+
+    /// Propagates the provided state until the end epoch with the provided propagator, unless the same propagation is
+    /// cached, in which case its final state and trajectory are loaded from the cache.
+    pub fn until_epoch_with_traj<D: Dynamics + fmt::Display, E: ErrorCtrl>(
+        &self,
+        setup: &Propagator<D, E>,
+        state: D::StateType,
+        end: Epoch,
+    ) -> Result<(D::StateType, Traj<D::StateType>), NyxError>
+    where
+        DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::VecLength>,
+        <DefaultAllocator as Allocator<f64, <D::StateType as State>::VecLength>>::Buffer: Send,
+        D::StateType: Interpolatable + Serialize + DeserializeOwned,
+    {
+        let scenario = Self::scenario(setup, &state, end)?;
+        let path = self.path(&scenario);
+
+        if let Some(entry) = self.load::<D::StateType>(&path)? {
+            if entry.scenario == scenario {
+                info!("Loaded memoized propagation from {}", path.display());
+                let mut traj = Traj::new();
+                traj.backward = entry.backward;
+                for memo_state in &entry.states {
+                    traj.states.push(restore(&entry.templates, memo_state)?);
+                }
+                traj.finalize();
+                return Ok((restore(&entry.templates, &entry.final_state)?, traj));
+            }
+            warn!(
+                "{} is the cache of another scenario, propagating again",
+                path.display()
+            );
+        }
+
+        let (final_state, traj) = setup.with(state).until_epoch_with_traj(end)?;
+
+        let mut templates = Templates::default();
+        let mut states = Vec::with_capacity(traj.states.len());
+        for state in &traj.states {
+            states.push(templates.store(state)?);
+        }
+        let entry = MemoEntry {
+            scenario,
+            final_state: templates.store(&final_state)?,
+            templates: templates.states,
+            states,
+            backward: traj.backward,
+        };
+        let file = File::create(&path).map_err(ConfigError::ReadError)?;
+        serde_yaml::to_writer(BufWriter::new(file), &entry).map_err(ConfigError::ParseError)?;
+        info!("Memoized propagation in {}", path.display());
+
+        Ok((final_state, traj))
+    }
+
+    /// Propagates the provided state for the provided duration, unless the same propagation is cached
+    pub fn for_duration_with_traj<D: Dynamics + fmt::Display, E: ErrorCtrl>(
+        &self,
+        setup: &Propagator<D, E>,
+        state: D::StateType,
+        duration: Duration,
+    ) -> Result<(D::StateType, Traj<D::StateType>), NyxError>
+    where
+        DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+            + Allocator<f64, <D::StateType as State>::VecLength>,
+        <DefaultAllocator as Allocator<f64, <D::StateType as State>::VecLength>>::Buffer: Send,
+        D::StateType: Interpolatable + Serialize + DeserializeOwned,
+    {
+        self.until_epoch_with_traj(setup, state, state.epoch() + duration)
+    }
+
+    /// Removes all of the cached propagations, and returns how many were removed
+    pub fn clear(&self) -> Result<usize, NyxError> {
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.dir).map_err(ConfigError::ReadError)? {
+            let path = entry.map_err(ConfigError::ReadError)?.path();
+            if path.extension().is_some_and(|ext| ext == "yaml") {
+                fs::remove_file(&path).map_err(ConfigError::ReadError)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Loads the cached propagation of the provided path, if any. A corrupted cache file is ignored.
+    fn load<S: DeserializeOwned>(&self, path: &Path) -> Result<Option<MemoEntry<S>>, NyxError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file = File::open(path).map_err(ConfigError::ReadError)?;
+        match serde_yaml::from_reader(BufReader::new(file)) {
+            Ok(entry) => Ok(Some(entry)),
+            Err(e) => {
+                warn!("ignoring corrupted cache {}: {e}", path.display());
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Templates of the states of a cached propagation, i.e. their fields other than their epoch and vector
+struct Templates<S> {
+    states: Vec<S>,
+    /// Serialization of the latest template, to only store a new one when that changes
+    latest: String,
+}
+
+impl<S> Default for Templates<S> {
+    fn default() -> Self {
+        Self {
+            states: Vec::new(),
+            latest: String::new(),
+        }
+    }
+}
+
+impl<S: State + Serialize> Templates<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    fn store(&mut self, state: &S) -> Result<MemoState, NyxError> {
+        let mut template = *state;
+        template.unset_stm();
+        template.set(
+            Epoch::from_tai_duration(Duration::ZERO),
+            &OVector::<f64, S::VecLength>::zeros(),
+        )?;
+        let serialized = serde_yaml::to_string(&template).map_err(ConfigError::ParseError)?;
+        if self.states.is_empty() || serialized != self.latest {
+            self.states.push(template);
+            self.latest = serialized;
+        }
+        Ok(MemoState {
+            template: self.states.len() - 1,
+            epoch_tai: state.epoch().to_tai_duration(),
+            vector: state.as_vector()?.as_slice().to_vec(),
+            stm: state.stm().is_ok(),
+        })
+    }
+}
+
+fn restore<S: State>(templates: &[S], memo_state: &MemoState) -> Result<S, NyxError>
+where
+    DefaultAllocator:
+        Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size> + Allocator<f64, S::VecLength>,
+{
+    let mut state = *templates.get(memo_state.template).ok_or_else(|| {
+        NyxError::CustomError(format!("cached template {} not found", memo_state.template))
+    })?;
+    if memo_state.vector.len() != S::VecLength::dim() {
+        return Err(NyxError::CustomError(format!(
+            "cached vector of length {} but the state requires {}",
+            memo_state.vector.len(),
+            S::VecLength::dim()
+        )));
+    }
+    if memo_state.stm {
+        state.reset_stm();
+    }
+    state.set(
+        Epoch::from_tai_duration(memo_state.epoch_tai),
+        &OVector::<f64, S::VecLength>::from_column_slice(&memo_state.vector),
+    )?;
+    Ok(state)
+}
+
+/// 64-bit FNV-1a hash, which unlike the hasher of the standard library is stable across releases of Rust
+fn fnv1a(data: &str) -> u64 {
+    data.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
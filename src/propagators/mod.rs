@@ -31,11 +31,26 @@ mod rk_methods;
 pub use rk_methods::*;
 mod options;
 pub use options::*;
+/// Checkpoints of the integration state, to resume long running propagations after a restart.
+mod checkpoint;
+pub use checkpoint::*;
+/// Caches the results of propagations on disk, to reuse them when their scenario did not change.
+mod memo;
+pub use memo::*;
+/// Hooks called after each accepted step, e.g. to apply impulsive maneuvers without stopping the propagation.
+mod hooks;
+pub use hooks::*;
+/// Compares the accuracy and runtime of integrator settings against a reference propagation.
+#[cfg(not(target_arch = "wasm32"))]
+mod error_budget;
+#[cfg(not(target_arch = "wasm32"))]
+pub use error_budget::*;
 
 use crate::time::Duration;
+use serde::{Deserialize, Serialize};
 
 /// Stores the details of the previous integration step of a given propagator. Access as `my_prop.clone().latest_details()`.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct IntegrationDetails {
     /// step size used
     pub step: Duration,
@@ -22,6 +22,59 @@ use crate::time::{Duration, Unit};
 
 use super::{ErrorCtrl, RSSCartesianStep};
 
+/// Strategy used by the adaptive Runge Kutta (and Runge-Kutta-Nyström) integrators to choose the next step from the errors of
+/// the latest accepted steps. The gains are in units of 1 / order of the integrator.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StepController {
+    /// Integral controller, h_{n+1} = 0.9 h_n (tol / err_n)^(1/k)
+    #[default]
+    Integral,
+    /// Proportional-integral controller (Gustafsson, 1991), h_{n+1} = 0.9 h_n (tol / err_n)^(k_i + k_p) (err_{n-1} / tol)^k_p,
+    /// which smooths the step size sequence and reduces the step rejections when the dynamics change quickly, e.g. at the shadow
+    /// crossings of a low-thrust spiral. The step grows more slowly than with the integral controller, so more steps may be taken.
+    PI { k_i: f64, k_p: f64 },
+    /// Proportional-integral-derivative controller (Söderlind, 2003), which adds the term (tol / err_{n-2})^k_d and the exponent
+    /// 2 k_d to the error of the previous step.
+    PID { k_i: f64, k_p: f64, k_d: f64 },
+}
+
+impl StepController {
+    /// The PI controller recommended by Gustafsson (Hairer & Wanner, Solving ODEs II, section IV.2), with k_i = 0.3 and k_p = 0.4
+    pub fn gustafsson() -> Self {
+        Self::PI { k_i: 0.3, k_p: 0.4 }
+    }
+
+    /// The H312 PID filter of Söderlind ("Digital filters in adaptive time-stepping", 2003), for very smooth step sequences
+    pub fn h312_pid() -> Self {
+        Self::PID {
+            k_i: 2.0 / 9.0,
+            k_p: -2.0 / 9.0,
+            k_d: 1.0 / 18.0,
+        }
+    }
+
+    /// Returns the integral, proportional and derivative gains of this controller
+    pub fn gains(&self) -> (f64, f64, f64) {
+        match *self {
+            Self::Integral => (1.0, 0.0, 0.0),
+            Self::PI { k_i, k_p } => (k_i, k_p, 0.0),
+            Self::PID { k_i, k_p, k_d } => (k_i, k_p, k_d),
+        }
+    }
+}
+
+impl fmt::Display for StepController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integral => write!(f, "I controller"),
+            Self::PI { k_i, k_p } => write!(f, "PI controller (k_i = {k_i}, k_p = {k_p})"),
+            Self::PID { k_i, k_p, k_d } => {
+                write!(f, "PID controller (k_i = {k_i}, k_p = {k_p}, k_d = {k_d})")
+            }
+        }
+    }
+}
+
 /// PropOpts stores the integrator options, including the minimum and maximum step sizes, and the
 /// max error size.
 ///
@@ -37,6 +90,10 @@ pub struct PropOpts<E: ErrorCtrl> {
     pub tolerance: f64,
     pub attempts: u8,
     pub fixed_step: bool,
+    /// If set, the initial step of adaptive propagators is estimated from the dynamics of the initial state, and `init_step` is only used as a fallback.
+    pub auto_init_step: bool,
+    /// Step size controller of the adaptive Runge Kutta integrators
+    pub step_ctrl: StepController,
     pub _errctrl: E,
 }
 
@@ -56,6 +113,8 @@ impl<E: ErrorCtrl> PropOpts<E> {
             tolerance,
             attempts: 50,
             fixed_step: false,
+            auto_init_step: false,
+            step_ctrl: StepController::Integral,
             _errctrl: errctrl,
         }
     }
@@ -69,6 +128,18 @@ impl<E: ErrorCtrl> PropOpts<E> {
         )
     }
 
+    /// Enables the automatic initial step size, estimated from the first evaluations of the dynamics (cf. `Propagator::initial_step`)
+    pub fn with_auto_init_step(mut self) -> Self {
+        self.auto_init_step = true;
+        self
+    }
+
+    /// Sets the step size controller of the adaptive Runge Kutta integrators, e.g. `StepController::gustafsson()`
+    pub fn with_step_controller(mut self, step_ctrl: StepController) -> Self {
+        self.step_ctrl = step_ctrl;
+        self
+    }
+
     /// Returns a string with the information about these options
     pub fn info(&self) -> String {
         format!("{self}")
@@ -100,7 +171,14 @@ impl<E: ErrorCtrl> fmt::Display for PropOpts<E> {
                 f,
                 "min_step: {:e}, max_step: {:e}, tol: {:e}, attempts: {}",
                 self.min_step, self.max_step, self.tolerance, self.attempts,
-            )
+            )?;
+            if self.step_ctrl != StepController::Integral {
+                write!(f, ", {}", self.step_ctrl)?;
+            }
+            if self.auto_init_step {
+                write!(f, ", auto initial step")?;
+            }
+            Ok(())
         }
     }
 }
@@ -116,6 +194,8 @@ impl PropOpts<RSSCartesianStep> {
             tolerance: 0.0,
             fixed_step: true,
             attempts: 0,
+            auto_init_step: false,
+            step_ctrl: StepController::Integral,
             _errctrl: RSSCartesianStep {},
         }
     }
@@ -151,6 +231,8 @@ impl Default for PropOpts<RSSCartesianStep> {
             tolerance: 1e-12,
             attempts: 50,
             fixed_step: false,
+            auto_init_step: false,
+            step_ctrl: StepController::Integral,
             _errctrl: RSSCartesianStep {},
         }
     }
@@ -171,6 +253,16 @@ fn test_options() {
     assert_eq!(opts.max_step, 10.0 * Unit::Second);
     assert!((opts.tolerance - 1e-12).abs() < f64::EPSILON);
     assert!(!opts.fixed_step);
+    assert!(!opts.auto_init_step);
+
+    let opts = opts.with_auto_init_step();
+    assert!(opts.auto_init_step);
+    assert!(format!("{opts}").ends_with("auto initial step"));
+    assert_eq!(opts.step_ctrl, StepController::Integral);
+
+    let opts = opts.with_step_controller(StepController::gustafsson());
+    assert_eq!(opts.step_ctrl.gains(), (0.3, 0.4, 0.0));
+    assert!(format!("{opts}").contains("PI controller"));
 
     let opts: PropOpts<RSSCartesianStep> = Default::default();
     assert_eq!(opts.init_step, 60.0 * Unit::Second);
@@ -17,12 +17,51 @@
 */
 
 use super::error_ctrl::{ErrorCtrl, RSSCartesianStep};
-use super::{Dormand78, IntegrationDetails, PropInstance, PropOpts, RK, RK89};
-use crate::dynamics::Dynamics;
+use super::{
+    AbmCoefficients, Dormand78, IntegrationDetails, PropCheckpoint, PropInstance, PropOpts,
+    Symplectic, RK, RK89, RKN,
+};
+use crate::dynamics::{Dynamics, SecondOrderDynamics, SeparableHamiltonian};
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, OVector};
-use crate::time::Duration;
-use crate::State;
+use crate::linalg::{DefaultAllocator, DimName, OVector};
+use crate::md::trajectory::{Interpolatable, Traj};
+use crate::time::{Duration, Epoch, Unit};
+use crate::{NyxError, State};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// The family of the integrator of a propagator, which selects how its instances take a step
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum IntegratorKind {
+    /// Adaptive or fixed step Runge Kutta, optionally in the fictitious time of a Sundman transformation of the provided exponent
+    RungeKutta { sundman: Option<f64> },
+    /// Adams-Bashforth-Moulton predictor-corrector of the provided order
+    AdamsBashforthMoulton { order: usize },
+    /// Bulirsch-Stoer extrapolation with at most the provided number of rows
+    BulirschStoer { rows: usize },
+    /// Fixed step symplectic integrator, whose A and B coefficients are the drift and kick coefficients
+    Symplectic,
+    /// Runge-Kutta-Nyström integrator of second order dynamics
+    Nystrom,
+}
+
+impl IntegratorKind {
+    /// Returns the kind of the provided Runge Kutta (or multi-step, or extrapolation) integrator
+    fn of<T: RK>() -> Self {
+        if T::MULTISTEP_ORDER > 0 {
+            Self::AdamsBashforthMoulton {
+                order: T::MULTISTEP_ORDER,
+            }
+        } else if T::EXTRAPOLATION_ROWS > 0 {
+            Self::BulirschStoer {
+                rows: T::EXTRAPOLATION_ROWS,
+            }
+        } else {
+            Self::RungeKutta { sundman: None }
+        }
+    }
+}
 
 /// A Propagator allows propagating a set of dynamics forward or backward in time.
 /// It is an EventTracker, without any event tracking. It includes the options, the integrator
@@ -41,6 +80,9 @@ where
     pub(crate) stages: usize, // Number of stages, i.e. how many times the derivatives will be called
     pub(crate) a_coeffs: &'a [f64],
     pub(crate) b_coeffs: &'a [f64],
+    pub(crate) kind: IntegratorKind, // Family of the integrator, which selects the stepping method of the instances
+    pub(crate) stm: bool, // If set, the STM is integrated alongside the state of every instance
+    pub(crate) output_epochs: Arc<[Epoch]>, // Sorted epochs on which every instance lands exactly
 }
 
 /// The `Propagator` trait defines the functions of a propagator and of an event tracker.
@@ -52,6 +94,8 @@ where
         + Allocator<f64, <D::StateType as State>::VecLength>,
 {
     /// Each propagator must be initialized with `new` which stores propagator information.
+    /// The integrator is either a Runge Kutta (e.g. `RK89`), a multi-step `AdamsBashforthMoulton` of order 4 to 12, or the
+    /// `BulirschStoer` extrapolation integrator.
     pub fn new<T: RK>(dynamics: D, opts: PropOpts<E>) -> Self {
         Self {
             dynamics,
@@ -60,6 +104,9 @@ where
             order: T::ORDER,
             a_coeffs: T::A_COEFFS,
             b_coeffs: T::B_COEFFS,
+            kind: IntegratorKind::of::<T>(),
+            stm: false,
+            output_epochs: Arc::new([]),
         }
     }
 
@@ -88,25 +135,227 @@ where
         Self::new::<Dormand78>(dynamics, opts)
     }
 
+    /// Integrates the state transition matrix alongside the state of every instance of this propagator, using the partials of the
+    /// dynamics. The STM of each instance starts at identity on its initial state and is available from `PropInstance::stm`.
+    pub fn with_stm(mut self) -> Self {
+        self.stm = true;
+        self
+    }
+
+    /// Forces every instance of this propagator to land exactly on each of the provided epochs (e.g. measurement times or the
+    /// start and end of a finite burn) instead of stepping over them, such that no interpolation is needed at those epochs.
+    pub fn with_fixed_output_epochs(mut self, mut epochs: Vec<Epoch>) -> Self {
+        epochs.sort();
+        epochs.dedup();
+        self.output_epochs = epochs.into();
+        self
+    }
+
+    /// Returns the sorted epochs on which every instance of this propagator lands exactly
+    pub fn output_epochs(&self) -> &[Epoch] {
+        &self.output_epochs
+    }
+
+    /// Regularizes the propagation with a Sundman transformation of the independent variable: the Runge Kutta steps are taken
+    /// in a fictitious time `s` such that `dt/ds = r^exponent`, where `r` is the norm of the position, i.e. of the first three
+    /// components of the state vector. An exponent of one makes `s` proportional to the eccentric anomaly in two body dynamics,
+    /// and an exponent of 3/2 to the mean anomaly. The steps are therefore short in time near periapsis and long near apoapsis,
+    /// which reduces both the number of steps and the accumulated error of highly eccentric orbits.
+    ///
+    /// The epoch is integrated alongside the state, and the final step lands exactly on the requested epoch, so the states
+    /// remain epoch-stamped Cartesian states. The regularization only applies to adaptive Runge Kutta integrators.
+    pub fn with_sundman(mut self, exponent: f64) -> Self {
+        match self.kind {
+            IntegratorKind::RungeKutta { .. } if !self.opts.fixed_step => {
+                self.kind = IntegratorKind::RungeKutta {
+                    sundman: Some(exponent),
+                }
+            }
+            _ => warn!("Sundman regularization is only supported by adaptive Runge Kutta integrators, ignoring it"),
+        }
+        self
+    }
+
+    /// Estimates an efficient initial step for the provided state with the starting step algorithm of Hairer, Nørsett & Wanner
+    /// (section II.4), which costs two evaluations of the dynamics.
+    ///
+    /// The norms of the state, of its derivative and of the change of derivative over a small explicit Euler step are scaled by the
+    /// tolerance of the options, such that the local error of the first step is expected to be about the tolerance given the order of
+    /// the integrator. The step is bounded by the minimum and maximum steps of the options.
+    pub fn initial_step(&self, state: &D::StateType) -> Result<Duration, NyxError> {
+        // Only the state itself is considered, not its STM
+        let dim = <D::StateType as State>::Size::dim();
+        let y0 = state.as_vector()?;
+        let f0 = self.dynamics.eom(0.0, &y0, state)?;
+        let tol = self.opts.tolerance;
+        let norm = |v: &OVector<f64, <D::StateType as State>::VecLength>| -> f64 {
+            ((0..dim)
+                .map(|i| (v[i] / (tol * (1.0 + y0[i].abs()))).powi(2))
+                .sum::<f64>()
+                / dim as f64)
+                .sqrt()
+        };
+
+        let d0 = norm(&y0);
+        let d1 = norm(&f0);
+        let h0 = if d0 < 1e-5 || d1 < 1e-5 {
+            1e-6
+        } else {
+            0.01 * d0 / d1
+        };
+
+        let y1 = &y0 + &f0 * h0;
+        let f1 = self.dynamics.eom(h0, &y1, state)?;
+        let d2 = norm(&(f1 - &f0)) / h0;
+
+        let max_d = d1.max(d2);
+        let h1 = if max_d <= 1e-15 {
+            (h0 * 1e-3).max(1e-6)
+        } else {
+            (0.01 / max_d).powf(1.0 / (f64::from(self.order) + 1.0))
+        };
+
+        let step = (100.0 * h0).min(h1) * Unit::Second;
+        Ok(step.clamp(self.opts.min_step, self.opts.max_step))
+    }
+
     pub fn with(&'a self, state: D::StateType) -> PropInstance<'a, D, E> {
+        let mut state = state;
+        if self.stm {
+            state.reset_stm();
+        }
         // Pre-allocate the k used in the propagator
         let mut k = Vec::with_capacity(self.stages + 1);
         for _ in 0..self.stages {
             k.push(OVector::<f64, <D::StateType as State>::VecLength>::zeros());
         }
+        let init_step = if self.opts.auto_init_step && !self.opts.fixed_step {
+            match self.initial_step(&state) {
+                Ok(step) => {
+                    debug!("automatic initial step of {step}");
+                    step
+                }
+                Err(e) => {
+                    warn!(
+                        "could not estimate initial step ({e}), using {}",
+                        self.opts.init_step
+                    );
+                    self.opts.init_step
+                }
+            }
+        } else {
+            self.opts.init_step
+        };
         PropInstance {
             state,
             prop: self,
             details: IntegrationDetails {
-                step: self.opts.init_step,
+                step: init_step,
                 error: 0.0,
                 attempts: 1,
             },
-            step_size: self.opts.init_step,
+            step_size: init_step,
             fixed_step: self.opts.fixed_step,
             k,
+            abm: match self.kind {
+                IntegratorKind::AdamsBashforthMoulton { order } => {
+                    Some(AbmCoefficients::new(order))
+                }
+                _ => None,
+            },
+            history: VecDeque::with_capacity(match self.kind {
+                IntegratorKind::AdamsBashforthMoulton { order } => 2 * order,
+                _ => 0,
+            }),
+            history_step: init_step,
+            extrapolation_row: match self.kind {
+                IntegratorKind::BulirschStoer { rows } => rows / 2,
+                _ => 0,
+            },
+            step_errors: [1.0; 2],
+            sundman_step: 0.0,
+            hooks: Vec::new(),
         }
     }
+
+    /// Propagates again the provided trajectory from the splice epoch until the end epoch, starting from its state at that
+    /// epoch once modified by `change` (e.g. an impulsive maneuver, or the identity if only the dynamics of this propagator
+    /// changed), and splices this new propagation onto the unchanged part of the trajectory before the splice epoch.
+    ///
+    /// This avoids propagating the earlier mission phases again when tuning a maneuver. If the splice epoch is not one of the
+    /// epochs of the trajectory, the restart state is interpolated.
+    /// Returns the final state and the spliced trajectory.
+    pub fn repropagate_from<F>(
+        &'a self,
+        traj: &Traj<D::StateType>,
+        splice_epoch: Epoch,
+        end: Epoch,
+        change: F,
+    ) -> Result<(D::StateType, Traj<D::StateType>), NyxError>
+    where
+        F: FnOnce(D::StateType) -> D::StateType,
+        <DefaultAllocator as Allocator<f64, <D::StateType as State>::VecLength>>::Buffer: Send,
+        D::StateType: Interpolatable,
+    {
+        let restart = change(traj.at(splice_epoch)?);
+        let (final_state, segment) = self.with(restart).until_epoch_with_traj(end)?;
+        Ok((final_state, traj.splice(&segment)?))
+    }
+
+    /// Resumes a propagation from the provided checkpoint, e.g. loaded from a file after a restart of the process.
+    /// The integrator of this propagator must be the one which created the checkpoint.
+    pub fn resume(
+        &'a self,
+        checkpoint: PropCheckpoint<D::StateType>,
+    ) -> Result<PropInstance<'a, D, E>, NyxError> {
+        if checkpoint.integrator != (self.kind, self.order, self.stages) {
+            return Err(NyxError::CustomError(format!(
+                "checkpoint integrator {:?} of order {} with {} stages does not match the propagator ({:?} of order {} with {} stages)",
+                checkpoint.integrator.0, checkpoint.integrator.1, checkpoint.integrator.2, self.kind, self.order, self.stages
+            )));
+        }
+        let vec_len = <D::StateType as State>::VecLength::dim();
+        let to_vector = |values: &[f64]| {
+            if values.len() == vec_len {
+                Ok(OVector::<f64, <D::StateType as State>::VecLength>::from_column_slice(values))
+            } else {
+                Err(NyxError::CustomError(format!(
+                    "checkpoint vector of length {} but the state requires {vec_len}",
+                    values.len()
+                )))
+            }
+        };
+
+        let mut state = checkpoint.state;
+        if checkpoint.stm {
+            state.reset_stm();
+        } else {
+            state.unset_stm();
+        }
+        state.set(
+            Epoch::from_tai_duration(checkpoint.epoch_tai),
+            &to_vector(&checkpoint.state_vector)?,
+        )?;
+
+        let mut instance = self.with(state);
+        // The STM is restored from the checkpoint, not reset by `with`
+        instance.state = state;
+        instance.details = checkpoint.details;
+        instance.step_size = checkpoint.step_size;
+        instance.fixed_step = checkpoint.fixed_step;
+        instance.history = checkpoint
+            .history
+            .iter()
+            .map(|(epoch_tai, values)| {
+                Ok((Epoch::from_tai_duration(*epoch_tai), to_vector(values)?))
+            })
+            .collect::<Result<VecDeque<_>, NyxError>>()?;
+        instance.history_step = checkpoint.history_step;
+        instance.extrapolation_row = checkpoint.extrapolation_row;
+        instance.step_errors = checkpoint.step_errors;
+        instance.sundman_step = checkpoint.sundman_step;
+        Ok(instance)
+    }
 }
 
 impl<'a, D: Dynamics> Propagator<'a, D, RSSCartesianStep>
@@ -128,3 +377,68 @@ where
         Self::new::<Dormand78>(dynamics, PropOpts::default())
     }
 }
+
+impl<'a, D: SeparableHamiltonian> Propagator<'a, D, RSSCartesianStep>
+where
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>,
+{
+    /// A symplectic propagator (e.g. `Yoshida6`) with the provided fixed step, for long term stability studies where the
+    /// secular energy drift of Runge Kutta methods is problematic. It is restricted to separable Hamiltonian dynamics and
+    /// does not support the propagation of the state transition matrix.
+    ///
+    /// Returns an error if the acceleration depends on the velocity, e.g. with relativistic corrections.
+    pub fn symplectic<T: Symplectic>(dynamics: D, step: Duration) -> Result<Self, NyxError> {
+        if dynamics.depends_on_velocity() {
+            return Err(NyxError::CustomError(
+                "symplectic integrators do not support velocity dependent accelerations"
+                    .to_string(),
+            ));
+        }
+        Ok(Self {
+            dynamics,
+            opts: PropOpts::with_fixed_step(step),
+            stages: T::STAGES,
+            order: T::ORDER,
+            a_coeffs: T::DRIFT,
+            b_coeffs: T::KICK,
+            kind: IntegratorKind::Symplectic,
+            stm: false,
+            output_epochs: Arc::new([]),
+        })
+    }
+}
+
+impl<'a, D: SecondOrderDynamics, E: ErrorCtrl> Propagator<'a, D, E>
+where
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>,
+{
+    /// A Runge-Kutta-Nyström propagator (e.g. `RKN64`) with custom propagator options, for second order dynamics whose
+    /// acceleration does not depend on the velocity. It does not support the propagation of the state transition matrix.
+    ///
+    /// Returns an error if the acceleration depends on the velocity, e.g. with relativistic corrections.
+    pub fn rkn<T: RKN>(dynamics: D, opts: PropOpts<E>) -> Result<Self, NyxError> {
+        if dynamics.depends_on_velocity() {
+            return Err(NyxError::CustomError(
+                "Runge-Kutta-Nyström integrators do not support velocity dependent accelerations"
+                    .to_string(),
+            ));
+        }
+        Ok(Self {
+            dynamics,
+            opts,
+            stages: T::STAGES,
+            order: T::ORDER,
+            a_coeffs: T::A_COEFFS,
+            b_coeffs: T::B_COEFFS,
+            kind: IntegratorKind::Nystrom,
+            stm: false,
+            output_epochs: Arc::new([]),
+        })
+    }
+}
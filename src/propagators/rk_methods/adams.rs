@@ -0,0 +1,117 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{RK, RK89};
+
+/// `AdamsBashforthMoulton` is a multi-step Adams-Bashforth-Moulton predictor-corrector integrator of order 4 to 12.
+///
+/// Each step only requires two evaluations of the dynamics (PECE mode), compared to sixteen for an RK89, which makes it well suited
+/// for long cruise propagations with smooth dynamics. The first steps, and any step after a change of step size, are taken with
+/// a fixed step RK89 to build the history of derivatives.
+///
+/// With an adaptive step, the local error is estimated from the difference between the predictor and the corrector (Milne's device).
+/// Steps above the tolerance are restarted with half the step, and the step is doubled once the error is well below the tolerance.
+/// Dynamics with discontinuities (e.g. maneuvers or eclipses) cause frequent restarts and are better served by a Runge Kutta.
+/// The stability region of the high order methods is small, so the maximum step should remain a small fraction of the orbital period.
+pub struct AdamsBashforthMoulton<const ORDER: usize> {}
+
+macro_rules! impl_abm {
+    ($($order:literal),*) => {
+        $(
+            impl RK for AdamsBashforthMoulton<$order> {
+                const ORDER: u8 = $order;
+                const STAGES: usize = RK89::STAGES;
+                const A_COEFFS: &'static [f64] = RK89::A_COEFFS;
+                const B_COEFFS: &'static [f64] = RK89::B_COEFFS;
+                const MULTISTEP_ORDER: usize = $order;
+            }
+        )*
+    };
+}
+
+impl_abm!(4, 5, 6, 7, 8, 9, 10, 11, 12);
+
+/// Coefficients of an Adams-Bashforth-Moulton predictor-corrector pair of the same order.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct AbmCoefficients {
+    /// Adams-Bashforth coefficients applied to f_n, f_{n-1}, ..., f_{n-k+1}
+    pub predictor: Vec<f64>,
+    /// Adams-Moulton coefficients applied to f_{n+1}, f_n, ..., f_{n-k+2}
+    pub corrector: Vec<f64>,
+    /// Factor applied to the difference between the corrector and the predictor to estimate the local error
+    pub milne: f64,
+}
+
+impl AbmCoefficients {
+    /// Computes the coefficients of the provided order from the backward difference formulation (Hairer, Nørsett & Wanner, section III.1).
+    pub fn new(order: usize) -> Self {
+        // γ_j of the explicit and γ*_j of the implicit Adams methods
+        let mut gamma = vec![1.0_f64];
+        let mut gamma_star = vec![1.0_f64];
+        for m in 1..=order {
+            let sum: f64 = (0..m).map(|j| gamma[j] / (m + 1 - j) as f64).sum();
+            gamma.push(1.0 - sum);
+            let sum_star: f64 = (0..m).map(|j| gamma_star[j] / (m + 1 - j) as f64).sum();
+            gamma_star.push(-sum_star);
+        }
+
+        // Expand the backward differences: ∇^j f_n = Σ_i (-1)^i C(j, i) f_{n-i}
+        let expand = |gammas: &[f64]| -> Vec<f64> {
+            (0..order)
+                .map(|i| {
+                    (i..order)
+                        .map(|j| {
+                            let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+                            gammas[j] * sign * binomial(j, i)
+                        })
+                        .sum()
+                })
+                .collect()
+        };
+
+        Self {
+            predictor: expand(&gamma),
+            corrector: expand(&gamma_star),
+            milne: gamma_star[order] / (gamma[order] - gamma_star[order]),
+        }
+    }
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+#[test]
+fn abm_coefficients() {
+    // Classical fourth order pair
+    let coeffs = AbmCoefficients::new(4);
+    let ab4 = [55.0 / 24.0, -59.0 / 24.0, 37.0 / 24.0, -9.0 / 24.0];
+    let am4 = [9.0 / 24.0, 19.0 / 24.0, -5.0 / 24.0, 1.0 / 24.0];
+    for i in 0..4 {
+        assert!((coeffs.predictor[i] - ab4[i]).abs() < 1e-14);
+        assert!((coeffs.corrector[i] - am4[i]).abs() < 1e-14);
+    }
+    assert!((coeffs.milne - (-19.0 / 270.0)).abs() < 1e-14);
+
+    // All methods are consistent
+    for order in 4..=12 {
+        let coeffs = AbmCoefficients::new(order);
+        assert!((coeffs.predictor.iter().sum::<f64>() - 1.0).abs() < 1e-10);
+        assert!((coeffs.corrector.iter().sum::<f64>() - 1.0).abs() < 1e-10);
+    }
+}
@@ -0,0 +1,50 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::RK;
+
+/// `BulirschStoer` is the Gragg-Bulirsch-Stoer extrapolation integrator (Hairer, Nørsett & Wanner, section II.9).
+///
+/// Each step is integrated with Gragg's modified midpoint rule using 2, 4, 6, ..., 16 sub-steps, and the results are
+/// extrapolated to a zero sub-step with the Aitken-Neville algorithm. Each row of the extrapolation table increases the
+/// order by two, up to order 16.
+///
+/// With an adaptive step, the error of each row is computed with the error control of the propagator options. The step
+/// is accepted on the first row within the tolerance, and the number of rows (i.e. the order) and the step size of the next
+/// step are chosen to minimize the number of evaluations of the dynamics per unit of time. With a fixed step, all of the
+/// rows are used. This integrator shines on smooth dynamics with tight tolerances, like high precision interplanetary
+/// propagation, where it takes much larger steps than Runge Kutta methods.
+pub struct BulirschStoer {}
+
+impl RK for BulirschStoer {
+    const ORDER: u8 = 16;
+    const STAGES: usize = 0;
+    const A_COEFFS: &'static [f64] = &[];
+    const B_COEFFS: &'static [f64] = &[];
+    const EXTRAPOLATION_ROWS: usize = 8;
+}
+
+/// Returns the number of modified midpoint sub-steps of the provided row of the extrapolation table
+pub(crate) fn bs_substeps(row: usize) -> usize {
+    2 * (row + 1)
+}
+
+/// Returns the number of evaluations of the dynamics needed to compute all rows up to the provided one
+pub(crate) fn bs_work(row: usize) -> f64 {
+    (1 + (0..=row).map(bs_substeps).sum::<usize>()) as f64
+}
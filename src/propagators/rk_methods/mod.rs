@@ -24,6 +24,16 @@ mod fehlberg;
 pub use self::fehlberg::*;
 mod verner;
 pub use self::verner::*;
+mod adams;
+pub(crate) use self::adams::AbmCoefficients;
+pub use self::adams::AdamsBashforthMoulton;
+mod bulirsch;
+pub use self::bulirsch::BulirschStoer;
+pub(crate) use self::bulirsch::{bs_substeps, bs_work};
+mod symplectic;
+pub use self::symplectic::*;
+mod nystrom;
+pub use self::nystrom::*;
 
 /// The `RK` trait defines a Runge Kutta integrator.
 #[allow(clippy::upper_case_acronyms)]
@@ -46,4 +56,12 @@ where
     /// Returns a pointer to a list of f64 corresponding to the b_i and b^*_i coefficients of the
     /// Butcher table for that RK. `Self.a_coeffs().len()` must be of size (order+1)*2.
     const B_COEFFS: &'static [f64];
+
+    /// Returns the order of the Adams-Bashforth-Moulton predictor-corrector for multi-step integrators, which then use the
+    /// coefficients above only to start up. Zero (the default) for single step integrators.
+    const MULTISTEP_ORDER: usize = 0;
+
+    /// Returns the maximum number of rows of the extrapolation table for extrapolation integrators, which then do not use
+    /// the coefficients above. Zero (the default) for Runge Kutta integrators.
+    const EXTRAPOLATION_ROWS: usize = 0;
 }
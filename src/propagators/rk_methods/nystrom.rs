@@ -0,0 +1,428 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+/// The `RKN` trait defines an embedded Runge-Kutta-Nyström integrator of second order differential equations r'' = f(t, r),
+/// whose acceleration does not depend on the velocity.
+///
+/// Each stage i evaluates the acceleration at the position r + c_i h v + h^2 \sum_j \bar{a}_{ij} f_j, so an RKN integrator
+/// reaches a given order with fewer evaluations of the dynamics than a Runge Kutta integrator of the first order system.
+///
+/// *Warning:* this trait supposes that the implementation is consistent, i.e. c_i^2 / 2 = \sum_j \bar{a}_{ij}.
+#[allow(clippy::upper_case_acronyms)]
+pub trait RKN {
+    /// Order of the propagated solution
+    const ORDER: u8;
+    /// Number of stages, i.e. of evaluations of the dynamics per step
+    const STAGES: usize;
+    /// The lower triangular \bar{A} coefficients of the position, row by row from the second stage.
+    const A_COEFFS: &'static [f64];
+    /// The position weights \bar{b} followed by the embedded position weights, then the velocity weights b followed by the
+    /// embedded velocity weights, i.e. of size 4 * STAGES.
+    const B_COEFFS: &'static [f64];
+}
+
+/// `RKN64` is the RKN6(4)6FM embedded Runge-Kutta-Nyström pair of Dormand, El-Mikkawy & Prince (IMA Journal of Numerical
+/// Analysis, 1987), with six evaluations of the acceleration per step.
+pub struct RKN64 {}
+
+impl RKN for RKN64 {
+    const ORDER: u8 = 6;
+    const STAGES: usize = 6;
+    const A_COEFFS: &'static [f64] = &[
+        1.0 / 200.0,
+        -1.0 / 2_200.0,
+        1.0 / 22.0,
+        637.0 / 6_600.0,
+        -7.0 / 110.0,
+        7.0 / 33.0,
+        225_437.0 / 1_968_750.0,
+        -30_073.0 / 281_250.0,
+        65_569.0 / 281_250.0,
+        -9_367.0 / 984_375.0,
+        151.0 / 2_142.0,
+        5.0 / 116.0,
+        385.0 / 1_368.0,
+        55.0 / 168.0,
+        -6_250.0 / 28_101.0,
+    ];
+    const B_COEFFS: &'static [f64] = &[
+        151.0 / 2_142.0,
+        5.0 / 116.0,
+        385.0 / 1_368.0,
+        55.0 / 168.0,
+        -6_250.0 / 28_101.0,
+        0.0,
+        1_349.0 / 157_500.0,
+        7_873.0 / 50_000.0,
+        192_199.0 / 900_000.0,
+        521_683.0 / 2_100_000.0,
+        -16.0 / 125.0,
+        0.0,
+        151.0 / 2_142.0,
+        25.0 / 522.0,
+        275.0 / 684.0,
+        275.0 / 252.0,
+        -78_125.0 / 112_404.0,
+        1.0 / 12.0,
+        1_349.0 / 157_500.0,
+        7_873.0 / 45_000.0,
+        27_457.0 / 90_000.0,
+        521_683.0 / 630_000.0,
+        -2.0 / 5.0,
+        1.0 / 12.0,
+    ];
+}
+
+/// `RKN1210` is an embedded RKN12(10) pair for high precision propagations, with twenty-two evaluations of the acceleration
+/// per step.
+///
+/// It is the exact Runge-Kutta-Nyström form of the Richardson extrapolation of Störmer-Verlet steps over the harmonic
+/// sequence of 1 to 6 substeps: since Störmer-Verlet is symmetric, its error expands in even powers of the substep, so
+/// extrapolating the six solutions yields a twelfth order solution, and the first five a tenth order embedded solution.
+/// This is the same construction as the Gragg-Bulirsch-Stoer extrapolation (Hairer, Nørsett & Wanner, section II.14), with
+/// rational coefficients. The 17 stage RKN12(10)17M pair of Dormand, El-Mikkawy & Prince is cheaper per step but is not
+/// provided.
+pub struct RKN1210 {}
+
+impl RKN for RKN1210 {
+    const ORDER: u8 = 12;
+    const STAGES: usize = 22;
+    const A_COEFFS: &'static [f64] = &[
+        1.0 / 2.0,
+        1.0 / 8.0,
+        0.0,
+        1.0 / 4.0,
+        0.0,
+        1.0 / 4.0,
+        1.0 / 18.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 9.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 9.0,
+        1.0 / 6.0,
+        0.0,
+        0.0,
+        0.0,
+        2.0 / 9.0,
+        1.0 / 9.0,
+        1.0 / 32.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 16.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 16.0,
+        3.0 / 32.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 8.0,
+        1.0 / 16.0,
+        1.0 / 8.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        3.0 / 16.0,
+        1.0 / 8.0,
+        1.0 / 16.0,
+        1.0 / 50.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 25.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 25.0,
+        3.0 / 50.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        2.0 / 25.0,
+        1.0 / 25.0,
+        2.0 / 25.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        3.0 / 25.0,
+        2.0 / 25.0,
+        1.0 / 25.0,
+        1.0 / 10.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        4.0 / 25.0,
+        3.0 / 25.0,
+        2.0 / 25.0,
+        1.0 / 25.0,
+        1.0 / 72.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 36.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 36.0,
+        1.0 / 24.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 18.0,
+        1.0 / 36.0,
+        1.0 / 18.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 12.0,
+        1.0 / 18.0,
+        1.0 / 36.0,
+        5.0 / 72.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0 / 9.0,
+        1.0 / 12.0,
+        1.0 / 18.0,
+        1.0 / 36.0,
+        1.0 / 12.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        5.0 / 36.0,
+        1.0 / 9.0,
+        1.0 / 12.0,
+        1.0 / 18.0,
+        1.0 / 36.0,
+    ];
+    const B_COEFFS: &'static [f64] = &[
+        655_177.0 / 19_958_400.0,
+        0.0,
+        2.0 / 945.0,
+        0.0,
+        -243.0 / 2_240.0,
+        -243.0 / 4_480.0,
+        0.0,
+        4_096.0 / 4_725.0,
+        8_192.0 / 14_175.0,
+        4_096.0 / 14_175.0,
+        0.0,
+        -390_625.0 / 199_584.0,
+        -390_625.0 / 266_112.0,
+        -390_625.0 / 399_168.0,
+        -390_625.0 / 798_336.0,
+        0.0,
+        486.0 / 385.0,
+        1_944.0 / 1_925.0,
+        1_458.0 / 1_925.0,
+        972.0 / 1_925.0,
+        486.0 / 1_925.0,
+        0.0,
+        15_619.0 / 362_880.0,
+        0.0,
+        -16.0 / 945.0,
+        0.0,
+        729.0 / 2_240.0,
+        729.0 / 4_480.0,
+        0.0,
+        -1_024.0 / 945.0,
+        -2_048.0 / 2_835.0,
+        -1_024.0 / 2_835.0,
+        0.0,
+        15_625.0 / 18_144.0,
+        15_625.0 / 24_192.0,
+        15_625.0 / 36_288.0,
+        15_625.0 / 72_576.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        655_177.0 / 19_958_400.0,
+        -1.0 / 604_800.0,
+        4.0 / 945.0,
+        2.0 / 945.0,
+        -729.0 / 4_480.0,
+        -729.0 / 4_480.0,
+        -729.0 / 8_960.0,
+        16_384.0 / 14_175.0,
+        16_384.0 / 14_175.0,
+        16_384.0 / 14_175.0,
+        8_192.0 / 14_175.0,
+        -1_953_125.0 / 798_336.0,
+        -1_953_125.0 / 798_336.0,
+        -1_953_125.0 / 798_336.0,
+        -1_953_125.0 / 798_336.0,
+        -1_953_125.0 / 1_596_672.0,
+        2_916.0 / 1_925.0,
+        2_916.0 / 1_925.0,
+        2_916.0 / 1_925.0,
+        2_916.0 / 1_925.0,
+        2_916.0 / 1_925.0,
+        1_458.0 / 1_925.0,
+        15_619.0 / 362_880.0,
+        1.0 / 17_280.0,
+        -32.0 / 945.0,
+        -16.0 / 945.0,
+        2_187.0 / 4_480.0,
+        2_187.0 / 4_480.0,
+        2_187.0 / 8_960.0,
+        -4_096.0 / 2_835.0,
+        -4_096.0 / 2_835.0,
+        -4_096.0 / 2_835.0,
+        -2_048.0 / 2_835.0,
+        78_125.0 / 72_576.0,
+        78_125.0 / 72_576.0,
+        78_125.0 / 72_576.0,
+        78_125.0 / 72_576.0,
+        78_125.0 / 145_152.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+    ];
+}
@@ -0,0 +1,108 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+/// The `Symplectic` trait defines an explicit symplectic integrator of separable Hamiltonian dynamics, as a sequence of drifts and kicks.
+///
+/// Each stage i first drifts the position with the velocity for `DRIFT[i]` times the step, then kicks the velocity with the
+/// acceleration at the new position for `KICK[i]` times the step. Both sets of coefficients must sum to one.
+///
+/// Symplectic integrators do not accumulate a secular energy drift, unlike Runge Kutta methods, but only when used with a fixed step.
+pub trait Symplectic {
+    /// Order of this integrator
+    const ORDER: u8;
+    /// Number of stages, i.e. the length of the drift and kick coefficients
+    const STAGES: usize;
+    /// Drift coefficients applied to the velocity
+    const DRIFT: &'static [f64];
+    /// Kick coefficients applied to the acceleration, a zero coefficient skips the evaluation of the dynamics
+    const KICK: &'static [f64];
+}
+
+/// `StormerVerlet` is the second order Störmer-Verlet (or leapfrog) integrator, in its drift-kick-drift form.
+/// It requires a single evaluation of the dynamics per step.
+pub struct StormerVerlet {}
+
+impl Symplectic for StormerVerlet {
+    const ORDER: u8 = 2;
+    const STAGES: usize = 2;
+    const DRIFT: &'static [f64] = &[0.5, 0.5];
+    const KICK: &'static [f64] = &[1.0, 0.0];
+}
+
+/// `Yoshida4` is the fourth order composition of three Störmer-Verlet steps (Forest & Ruth 1990, Yoshida 1990).
+/// It requires three evaluations of the dynamics per step.
+pub struct Yoshida4 {}
+
+impl Symplectic for Yoshida4 {
+    const ORDER: u8 = 4;
+    const STAGES: usize = 4;
+    const DRIFT: &'static [f64] = &[
+        0.675_603_595_979_828_8,
+        -0.175_603_595_979_828_8,
+        -0.175_603_595_979_828_8,
+        0.675_603_595_979_828_8,
+    ];
+    const KICK: &'static [f64] = &[
+        1.351_207_191_959_657_6,
+        -1.702_414_383_919_315_3,
+        1.351_207_191_959_657_6,
+        0.0,
+    ];
+}
+
+/// `Yoshida6` is the sixth order composition of seven Störmer-Verlet steps (solution A of Yoshida 1990).
+/// It requires seven evaluations of the dynamics per step.
+pub struct Yoshida6 {}
+
+impl Symplectic for Yoshida6 {
+    const ORDER: u8 = 6;
+    const STAGES: usize = 8;
+    const DRIFT: &'static [f64] = &[
+        0.392_256_805_238_78,
+        0.510_043_411_918_458_5,
+        -0.471_053_385_409_756_5,
+        0.068_753_168_252_518,
+        0.068_753_168_252_518,
+        -0.471_053_385_409_756_5,
+        0.510_043_411_918_458_5,
+        0.392_256_805_238_78,
+    ];
+    const KICK: &'static [f64] = &[
+        0.784_513_610_477_56,
+        0.235_573_213_359_357,
+        -1.177_679_984_178_87,
+        1.315_186_320_683_906,
+        -1.177_679_984_178_87,
+        0.235_573_213_359_357,
+        0.784_513_610_477_56,
+        0.0,
+    ];
+}
+
+#[test]
+fn symplectic_consistency() {
+    fn check<T: Symplectic>() {
+        assert_eq!(T::DRIFT.len(), T::STAGES);
+        assert_eq!(T::KICK.len(), T::STAGES);
+        assert!((T::DRIFT.iter().sum::<f64>() - 1.0).abs() < 1e-14);
+        assert!((T::KICK.iter().sum::<f64>() - 1.0).abs() < 1e-14);
+    }
+    check::<StormerVerlet>();
+    check::<Yoshida4>();
+    check::<Yoshida6>();
+}
@@ -43,10 +43,10 @@ impl Frame {
         self.inner.is_celestial()
     }
     pub fn ephem_path(&self) -> Vec<usize> {
-        self.inner.ephem_path()
+        self.inner.ephem_path().to_vec()
     }
     pub fn frame_path(&self) -> Vec<usize> {
-        self.inner.frame_path()
+        self.inner.frame_path().to_vec()
     }
     pub fn gm(&self) -> f64 {
         self.inner.gm()
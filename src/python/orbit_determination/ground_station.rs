@@ -49,6 +49,7 @@ impl GroundStation {
         timestamp_noise_s: Option<GaussMarkov>,
         range_noise_km: Option<GaussMarkov>,
         doppler_noise_km_s: Option<GaussMarkov>,
+        aberration_correction: Option<bool>,
     ) -> Result<Self, NyxError> {
         let cosm = Cosm::de438();
         let frame_obj = cosm.try_frame(&frame)?;
@@ -61,6 +62,8 @@ impl GroundStation {
             frame: frame_obj,
             integration_time,
             light_time_correction,
+            aberration_correction: aberration_correction.unwrap_or(false),
+            media: None,
             timestamp_noise_s,
             range_noise_km,
             doppler_noise_km_s,
@@ -82,6 +85,7 @@ impl GroundStation {
             Option<GaussMarkov>,
             Option<GaussMarkov>,
             Option<GaussMarkov>,
+            Option<bool>,
         ),
         NyxError,
     > {
@@ -97,6 +101,7 @@ impl GroundStation {
             self.timestamp_noise_s,
             self.range_noise_km,
             self.doppler_noise_km_s,
+            Some(self.aberration_correction),
         ))
     }
 
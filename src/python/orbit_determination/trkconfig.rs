@@ -145,6 +145,17 @@ impl TrkConfig {
         Ok(())
     }
 
+    #[getter]
+    fn get_sampling_reference(&self) -> PyResult<Option<Epoch>> {
+        Ok(self.sampling_reference)
+    }
+
+    #[setter]
+    fn set_sampling_reference(&mut self, sampling_reference: Option<Epoch>) -> PyResult<()> {
+        self.sampling_reference = sampling_reference;
+        Ok(())
+    }
+
     /// Allows setting the start and end availabilities and the sampling.
     /// Availabilities must be either `Visible` or an Epoch as a string.
     /// The sampling must be a Duration object.
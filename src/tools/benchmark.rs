@@ -0,0 +1,319 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Bodies, Cosm, Orbit};
+use crate::dynamics::{Harmonics, OrbitalDynamics, PointMasses};
+use crate::errors::NyxError;
+use crate::io::gravity::HarmonicsMem;
+use crate::linalg::{Matrix2, Matrix2x6, Matrix6, Vector2, Vector6};
+use crate::od::msr::{RangeDoppler, TrackingArc};
+use crate::od::noise::GaussMarkov;
+use crate::od::prelude::{KfEstimate, ODProcess, TrackingArcSim, TrkConfig, KF};
+use crate::od::{Filter, GroundStation};
+use crate::propagators::{PropOpts, Propagator, RK4Fixed, RSSCartesianStep};
+use crate::time::{Duration, Epoch, TimeUnits, Unit};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// Representative workloads used to measure the performance of Nyx.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Workload {
+    /// Propagation of a low Earth orbit with spherical harmonics, and the Sun and the Moon as third bodies
+    LeoHarmonics,
+    /// Heliocentric cruise with Venus, the Earth, Mars and Jupiter as third bodies
+    InterplanetaryThirdBody,
+    /// Orbit determination of a two body orbit tracked by three ground stations, with a conventional Kalman filter
+    OdArc,
+    /// Measurement updates of a Kalman filter alone, i.e. without propagating the estimate nor computing the measurements
+    KalmanUpdates,
+}
+
+impl Workload {
+    /// Returns all of the workloads
+    pub const fn all() -> [Self; 4] {
+        [
+            Self::LeoHarmonics,
+            Self::InterplanetaryThirdBody,
+            Self::OdArc,
+            Self::KalmanUpdates,
+        ]
+    }
+}
+
+impl fmt::Display for Workload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LeoHarmonics => write!(f, "LEO harmonics propagation"),
+            Self::InterplanetaryThirdBody => write!(f, "interplanetary third body propagation"),
+            Self::OdArc => write!(f, "OD arc processing"),
+            Self::KalmanUpdates => write!(f, "Kalman filter measurement updates"),
+        }
+    }
+}
+
+/// Configuration of the benchmark workloads.
+///
+/// The default gravity field only includes J2 so that the benchmarks do not depend on data files: load a larger field
+/// (e.g. JGM3 70x70 with `HarmonicsMem::from_cof`) to measure the performance of high fidelity propagation.
+#[derive(Clone)]
+pub struct BenchmarkSuite {
+    pub cosm: Arc<Cosm>,
+    /// Gravity field of the Earth used in the LEO propagation
+    pub harmonics: HarmonicsMem,
+    /// Duration of the LEO propagation
+    pub leo_duration: Duration,
+    /// Duration of the interplanetary propagation
+    pub interplanetary_duration: Duration,
+    /// Duration of the tracking arc processed by the orbit determination
+    pub od_arc_duration: Duration,
+    /// Number of measurement updates of the Kalman filter
+    pub kf_updates: usize,
+}
+
+impl BenchmarkSuite {
+    /// Initializes the benchmark suite with its default durations
+    pub fn new(cosm: Arc<Cosm>) -> Self {
+        Self {
+            cosm,
+            harmonics: HarmonicsMem::j2_jgm3(),
+            leo_duration: 1 * Unit::Day,
+            interplanetary_duration: 30 * Unit::Day,
+            od_arc_duration: 6 * Unit::Hour,
+            kf_updates: 100_000,
+        }
+    }
+
+    /// Prepares the provided workload, i.e. generates any input data which should not be timed (e.g. the simulated measurements)
+    pub fn prepare(&self, workload: Workload) -> Result<PreparedWorkload<'_>, NyxError> {
+        let arc = match workload {
+            Workload::OdArc => Some(self.simulate_od_arc()?),
+            _ => None,
+        };
+
+        Ok(PreparedWorkload {
+            suite: self,
+            workload,
+            arc,
+        })
+    }
+
+    /// Runs each of the provided workloads the provided number of times and returns their timing statistics
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn run(
+        &self,
+        workloads: &[Workload],
+        iterations: usize,
+    ) -> Result<Vec<BenchmarkResult>, NyxError> {
+        if iterations == 0 {
+            return Err(NyxError::CustomError(
+                "benchmarks need at least one iteration".to_string(),
+            ));
+        }
+
+        let mut results = Vec::with_capacity(workloads.len());
+        for workload in workloads {
+            let prepared = self.prepare(*workload)?;
+
+            let mut durations = Vec::with_capacity(iterations);
+            for _ in 0..iterations {
+                let tick = Instant::now();
+                prepared.run()?;
+                durations.push(Duration::from(tick.elapsed()));
+            }
+
+            let total = durations
+                .iter()
+                .fold(Duration::ZERO, |total, dur| total + *dur);
+
+            let result = BenchmarkResult {
+                workload: *workload,
+                iterations,
+                min: *durations.iter().min().unwrap(),
+                mean: total / (iterations as f64),
+                max: *durations.iter().max().unwrap(),
+            };
+            info!("{result}");
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    /// Initial LEO state
+    fn leo_orbit(&self) -> Orbit {
+        let eme2k = self.cosm.frame("EME2000");
+        let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+        Orbit::keplerian(6_778.0, 0.001, 51.6, 30.0, 45.0, 0.0, epoch, eme2k)
+    }
+
+    /// Initial heliocentric state, roughly on a transfer orbit from the Earth to Mars
+    fn interplanetary_orbit(&self) -> Orbit {
+        let sun_j2k = self.cosm.frame("Sun J2000");
+        let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 7, 30);
+        Orbit::keplerian(1.888e8, 0.208, 1.5, 0.0, 315.0, 5.0, epoch, sun_j2k)
+    }
+
+    /// Initial state of the orbit determination
+    fn od_orbit(&self) -> Orbit {
+        let eme2k = self.cosm.frame("EME2000");
+        let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+        Orbit::keplerian(22_000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k)
+    }
+
+    fn od_setup(&self) -> Propagator<'static, OrbitalDynamics, RSSCartesianStep> {
+        Propagator::new::<RK4Fixed>(
+            OrbitalDynamics::two_body(),
+            PropOpts::with_fixed_step(10.seconds()),
+        )
+    }
+
+    /// Simulates the measurements processed by the orbit determination
+    fn simulate_od_arc(&self) -> Result<TrackingArc<RangeDoppler>, NyxError> {
+        let iau_earth = self.cosm.frame("IAU Earth");
+        let stations = vec![
+            GroundStation::dss65_madrid(0.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+            GroundStation::dss34_canberra(0.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+            GroundStation::dss13_goldstone(0.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+        ];
+
+        let mut configs = HashMap::new();
+        for station in &stations {
+            configs.insert(
+                station.name.clone(),
+                TrkConfig::from_sample_rate(1.minutes()),
+            );
+        }
+
+        let (_, traj) = self
+            .od_setup()
+            .with(self.od_orbit())
+            .for_duration_with_traj(self.od_arc_duration)?;
+
+        let mut arc_sim = TrackingArcSim::with_seed(stations, traj, configs, 0)?;
+        arc_sim.disallow_overlap();
+        arc_sim.generate_measurements(self.cosm.clone())
+    }
+}
+
+/// A workload ready to be run, cf. `BenchmarkSuite::prepare`.
+pub struct PreparedWorkload<'a> {
+    suite: &'a BenchmarkSuite,
+    workload: Workload,
+    arc: Option<TrackingArc<RangeDoppler>>,
+}
+
+impl<'a> PreparedWorkload<'a> {
+    /// Runs this workload once
+    pub fn run(&self) -> Result<(), NyxError> {
+        let suite = self.suite;
+        match self.workload {
+            Workload::LeoHarmonics => {
+                let iau_earth = suite.cosm.frame("IAU Earth");
+                let mut dynamics =
+                    OrbitalDynamics::point_masses(&[Bodies::Sun, Bodies::Luna], suite.cosm.clone());
+                dynamics.add_model(Harmonics::from_stor(
+                    iau_earth,
+                    suite.harmonics.clone(),
+                    suite.cosm.clone(),
+                ));
+
+                Propagator::default(dynamics)
+                    .with(suite.leo_orbit())
+                    .for_duration(suite.leo_duration)?;
+            }
+            Workload::InterplanetaryThirdBody => {
+                let dynamics = OrbitalDynamics::new(vec![PointMasses::new(
+                    &[
+                        Bodies::Venus,
+                        Bodies::Earth,
+                        Bodies::MarsBarycenter,
+                        Bodies::JupiterBarycenter,
+                    ],
+                    suite.cosm.clone(),
+                )]);
+
+                Propagator::default(dynamics)
+                    .with(suite.interplanetary_orbit())
+                    .for_duration(suite.interplanetary_duration)?;
+            }
+            Workload::OdArc => {
+                let arc = self.arc.as_ref().unwrap();
+                let setup = suite.od_setup();
+                let initial_state = suite.od_orbit().with_stm();
+
+                let covar =
+                    Matrix6::from_diagonal(&Vector6::new(1e-3, 1e-3, 1e-3, 1e-6, 1e-6, 1e-6));
+                let initial_estimate = KfEstimate::from_covar(initial_state, covar);
+                let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-3));
+                let kf = KF::no_snc(initial_estimate, measurement_noise);
+
+                let mut odp =
+                    ODProcess::ckf(setup.with(initial_state), kf, None, suite.cosm.clone());
+                odp.process_arc::<GroundStation>(arc)?;
+            }
+            Workload::KalmanUpdates => {
+                let mut nominal_state = suite.od_orbit().with_stm();
+
+                let covar =
+                    Matrix6::from_diagonal(&Vector6::new(1e-3, 1e-3, 1e-3, 1e-6, 1e-6, 1e-6));
+                let initial_estimate = KfEstimate::from_covar(nominal_state, covar);
+                let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-3));
+                let mut kf = KF::no_snc(initial_estimate, measurement_noise);
+
+                // Range and Doppler sensitivity along a fixed line of sight
+                #[rustfmt::skip]
+                let h_tilde = Matrix2x6::new(
+                    0.6, 0.8, 0.0, 0.0, 0.0, 0.0,
+                    1e-4, -1e-4, 0.0, 0.6, 0.8, 0.0,
+                );
+                let real_obs = Vector2::new(1e-3, 1e-6);
+                let computed_obs = Vector2::zeros();
+
+                for _ in 0..suite.kf_updates {
+                    nominal_state.epoch += 10.seconds();
+                    kf.update_h_tilde(h_tilde);
+                    kf.measurement_update(nominal_state, &real_obs, &computed_obs, None)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Timing statistics of a workload
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BenchmarkResult {
+    pub workload: Workload,
+    pub iterations: usize,
+    pub min: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+impl fmt::Display for BenchmarkResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: mean {} (min {}, max {}) over {} iterations",
+            self.workload, self.mean, self.min, self.max, self.iterations
+        )
+    }
+}
@@ -16,4 +16,6 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+/// Representative workloads to measure the performance of Nyx
+pub mod benchmark;
 pub mod lambert;
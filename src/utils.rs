@@ -20,6 +20,7 @@ use crate::cosmic::Orbit;
 use crate::linalg::{
     allocator::Allocator, DefaultAllocator, DimName, Matrix3, Matrix6, OVector, Vector3, Vector6,
 };
+use crate::time::Epoch;
 use nalgebra::Complex;
 
 /// Returns the skew-symmetric matrix (also known as the tilde matrix)
@@ -221,6 +222,28 @@ pub fn between_pm_x(angle: f64, x: f64) -> f64 {
     bounded
 }
 
+/// Returns the right ascension of the Sun as seen from the Earth in degrees between 0 and 360.0.
+///
+/// This uses the low precision solar coordinates of the Astronomical Almanac, accurate to about 0.01 degrees between 1950 and 2050,
+/// which is sufficient for computing the local time of the ascending node without loading any ephemeris.
+pub fn sun_right_ascension_deg(epoch: Epoch) -> f64 {
+    let days = epoch.to_tdb_days_since_j2000();
+    // Mean longitude and mean anomaly of the Sun
+    let mean_long_deg = 280.460 + 0.985_647_4 * days;
+    let mean_anomaly_rad = (357.528 + 0.985_600_3 * days).to_radians();
+    // Ecliptic longitude of the Sun and obliquity of the ecliptic
+    let ecl_long_rad =
+        (mean_long_deg + 1.915 * mean_anomaly_rad.sin() + 0.020 * (2.0 * mean_anomaly_rad).sin())
+            .to_radians();
+    let obliquity_rad = (23.439 - 0.000_000_4 * days).to_radians();
+
+    between_0_360(
+        (obliquity_rad.cos() * ecl_long_rad.sin())
+            .atan2(ecl_long_rad.cos())
+            .to_degrees(),
+    )
+}
+
 /// The Kronecker delta function
 pub fn kronecker(a: f64, b: f64) -> f64 {
     if (a - b).abs() <= f64::EPSILON {
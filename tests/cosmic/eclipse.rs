@@ -1,8 +1,13 @@
 extern crate nyx_space as nyx;
 
-use nyx::cosmic::eclipse::{EclipseLocator, EclipseState};
-use nyx::cosmic::{Bodies, Cosm, Orbit};
+use nyx::cosmic::eclipse::{
+    eclipse_state, EclipseLocator, EclipseState, ShadowBoundary, ShadowModel,
+};
+use nyx::cosmic::{Bodies, Cosm, LightTimeCalc, Orbit, Spacecraft};
 use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::dynamics::{ForceModel, SolarPressure};
+use nyx::linalg::Vector3;
+use nyx::md::EventEvaluator;
 use nyx::propagators::{PropOpts, Propagator};
 use nyx::time::{Epoch, Unit};
 use std::sync::mpsc;
@@ -104,3 +109,121 @@ fn geo_sun_earth_eclipses() {
 
     assert_eq!(cnt_changes, 15, "wrong number of eclipse state changes");
 }
+
+#[test]
+fn leo_shadow_boundaries() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let leo = Orbit::keplerian(6778.0, 0.001, 30.0, 0.0, 0.0, 0.0, start_time, eme2k);
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(leo)
+        .for_duration_with_traj(6 * Unit::Hour)
+        .unwrap();
+
+    let e_loc = EclipseLocator {
+        light_source: cosm.frame("Sun J2000"),
+        shadow_bodies: vec![eme2k],
+        cosm: cosm.clone(),
+    };
+
+    let find = |model, boundary| {
+        let event = e_loc.to_shadow_event(model, boundary);
+        println!("{event}");
+        let found = traj.find_all(&event).unwrap();
+        found
+            .iter()
+            .map(|orbit| {
+                let before = traj.at(orbit.epoch - Unit::Second).unwrap();
+                let after = traj.at(orbit.epoch + Unit::Second).unwrap();
+                assert!(event.eval(orbit).abs() < EventEvaluator::<Orbit>::value_precision(&event));
+                if model == ShadowModel::Cylindrical {
+                    // The light is switched on or off across the boundary
+                    assert_ne!(
+                        e_loc.light_fraction(&before, model),
+                        e_loc.light_fraction(&after, model)
+                    );
+                } else {
+                    // On the boundary, the light is either full or fully hidden
+                    let fraction = e_loc.light_fraction(orbit, model);
+                    let lit = boundary == ShadowBoundary::Penumbra;
+                    assert!(
+                        (fraction - if lit { 1.0 } else { 0.0 }).abs() < 1e-2,
+                        "{}",
+                        event.eval_string(orbit)
+                    );
+                }
+                (orbit.epoch, event.is_entry(&before, &after))
+            })
+            .collect::<Vec<_>>()
+    };
+    let penumbra = find(ShadowModel::Conical, ShadowBoundary::Penumbra);
+    let umbra = find(ShadowModel::Conical, ShadowBoundary::Umbra);
+    let cylinder = find(ShadowModel::Cylindrical, ShadowBoundary::Umbra);
+    println!("{penumbra:?}\n{umbra:?}\n{cylinder:?}");
+    assert!(penumbra.len() >= 6);
+    assert_eq!(penumbra.len(), umbra.len());
+    assert_eq!(penumbra.len(), cylinder.len());
+    for ((pen, umb), cyl) in penumbra.iter().zip(&umbra).zip(&cylinder) {
+        // Entries and exits match, and the cylindrical shadow is between the umbra and the penumbra
+        assert_eq!(pen.1, umb.1);
+        assert_eq!(pen.1, cyl.1);
+        let (first, last) = if pen.1 {
+            (pen.0, umb.0)
+        } else {
+            (umb.0, pen.0)
+        };
+        assert!(first < cyl.0 && cyl.0 < last);
+        // The penumbra lasts several seconds in LEO
+        let penumbra_s = (last - first).to_seconds();
+        assert!(penumbra_s > 5.0 && penumbra_s < 20.0, "{penumbra_s} s");
+    }
+
+    // The SRP follows the shadow model
+    let in_umbra = traj
+        .at(umbra[0].0 + if umbra[0].1 { 60 } else { -60 } * Unit::Second)
+        .unwrap();
+    let sc = Spacecraft::from_srp_defaults(in_umbra, 100.0, 1.0);
+    let srp = SolarPressure::default_raw(vec![eme2k], cosm.clone());
+    assert_eq!(srp.eom(&sc).unwrap().norm(), 0.0);
+    let unshadowed = srp.with_shadow_model(ShadowModel::None);
+    assert!(unshadowed.eom(&sc).unwrap().norm() > 0.0);
+}
+
+#[test]
+fn cislunar_conical_shadow() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let sun = cosm.frame("Sun J2000");
+    let luna = cosm.frame("Luna");
+    let e_loc = EclipseLocator::cislunar(cosm.clone());
+
+    // During the partial phase of the lunar eclipse of 2022 November 8, the Moon is in the penumbra of the Earth, so a spacecraft
+    // behind the limb of the Moon is in the penumbra of both
+    let epoch = Epoch::from_gregorian_utc_hms(2022, 11, 8, 9, 30, 0);
+    let sun_dir = cosm
+        .celestial_state(&sun.ephem_path(), epoch, luna, LightTimeCalc::None)
+        .radius()
+        .normalize();
+    let perp = sun_dir.cross(&Vector3::new(0.0, 0.0, 1.0)).normalize();
+    let mut found = 0;
+    for offset_km in (0..40).map(|k| 1_500.0 + 10.0 * k as f64) {
+        for side in [-1.0, 1.0] {
+            let r = -sun_dir * 5_000.0 + perp * side * offset_km;
+            let observer = Orbit::cartesian(r[0], r[1], r[2], 0.0, 0.0, 0.0, epoch, luna);
+            let by_earth: f64 = eclipse_state(&observer, sun, eme2k, &cosm).into();
+            let by_moon: f64 = eclipse_state(&observer, sun, luna, &cosm).into();
+            if by_earth < 1.0 && by_moon < 1.0 && by_earth > 0.0 && by_moon > 0.0 {
+                found += 1;
+                let fraction = e_loc.light_fraction(&observer, ShadowModel::Conical);
+                // Both occultations add up, so the light is dimmer than with the darkest of both shadows
+                assert!((fraction - (by_earth + by_moon - 1.0).max(0.0)).abs() < 1e-12);
+                let darkest: f64 = e_loc.compute(&observer).into();
+                assert!(fraction < darkest);
+            }
+        }
+    }
+    println!("{found} positions in the penumbra of both the Earth and the Moon");
+    assert!(found > 0);
+}
@@ -0,0 +1,50 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, LaunchTarget};
+use nyx::time::Epoch;
+
+#[test]
+fn launch_target_injection_orbits() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2026, 11, 20);
+
+    // Typical Mars departure
+    let target = LaunchTarget::new(10.5, 330.0, -25.0);
+    println!("{target}");
+
+    let rp_km = eme2k.equatorial_radius() + 185.0;
+    for inclination_deg in [28.5, 50.0] {
+        let orbits = target
+            .injection_orbits(rp_km, inclination_deg, epoch, eme2k)
+            .unwrap();
+        assert!((orbits[0].raan_deg() - orbits[1].raan_deg()).abs() > 1.0);
+        for orbit in &orbits {
+            println!("{orbit:x}");
+            assert!((orbit.periapsis_km() - rp_km).abs() < 1e-6);
+            // Injection at periapsis
+            assert!(orbit.radius().dot(&orbit.velocity()).abs() < 1e-6);
+            assert!((orbit.inc_deg() - inclination_deg).abs() < 1e-9);
+
+            let achieved = LaunchTarget::from_orbit(orbit).unwrap();
+            assert!((achieved.c3_km2_s2 - target.c3_km2_s2).abs() < 1e-9);
+            assert!((achieved.rla_deg - target.rla_deg).abs() < 1e-9);
+            assert!((achieved.dla_deg - target.dla_deg).abs() < 1e-9);
+            assert!(target.is_achieved_by(orbit).unwrap());
+        }
+    }
+
+    // The asymptote is out of reach of low inclinations
+    assert!(target.injection_orbits(rp_km, 20.0, epoch, eme2k).is_err());
+    // And elliptical orbits do not have an asymptote
+    assert!(LaunchTarget::new(-1.0, 0.0, 0.0)
+        .injection_orbits(rp_km, 30.0, epoch, eme2k)
+        .is_err());
+
+    // The RLA errors wrap around
+    let orbit = LaunchTarget::new(10.5, 359.995, 10.0)
+        .injection_orbits(rp_km, 30.0, epoch, eme2k)
+        .unwrap()[0];
+    let errors = LaunchTarget::new(10.5, 0.0, 10.0).errors(&orbit).unwrap();
+    assert!((errors[1] + 0.005).abs() < 1e-9);
+}
@@ -1,3 +1,5 @@
 mod bplane;
 mod eclipse;
+mod launch;
 mod orbit;
+mod tle;
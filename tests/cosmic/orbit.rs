@@ -562,6 +562,7 @@ fn orbit_dual_test() {
         StateParameter::HY,
         StateParameter::HZ,
         StateParameter::Inclination,
+        StateParameter::LTAN,
         StateParameter::MeanAnomaly,
         StateParameter::Periapsis,
         StateParameter::RightAscension,
@@ -629,3 +630,139 @@ fn orbit_dual_test() {
         );
     }
 }
+
+#[test]
+fn orbit_vinf_ltan() {
+    use nyx::cosmic::OrbitDual;
+    use nyx::md::StateParameter;
+    use nyx::State;
+
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+
+    // March equinox of 2023, when the right ascension of the Sun is zero
+    let equinox = Epoch::from_gregorian_utc_hms(2023, 3, 20, 21, 24, 0);
+
+    // A dawn-dusk orbit: the ascending node is a quarter of a turn ahead of the Sun
+    let sso = Orbit::keplerian_altitude(700.0, 1e-3, 98.2, 90.0, 0.0, 0.0, equinox, eme2k);
+    let ltan = sso.value(StateParameter::LTAN).unwrap();
+    assert!((ltan - 18.0).abs() < 1e-2, "LTAN = {ltan} h");
+    assert!(sso.value(StateParameter::Vinf).is_err());
+    assert!(sso.value(StateParameter::VinfRightAscension).is_err());
+
+    let sso_dual = OrbitDual::from(sso);
+    f64_eq!(sso_dual.ltan().real(), ltan, "LTAN dual");
+    assert!(sso_dual.vinf().is_err());
+
+    // Twelve hours later, the ascending node is on the night side
+    let sso = sso.with_raan(270.0);
+    assert!((sso.ltan_hours() - 6.0).abs() < 1e-2);
+
+    // Hyperbolic departure
+    let departure = Orbit::keplerian(-20_000.0, 1.4, 28.5, 45.0, 10.0, 0.0, equinox, eme2k);
+    f64_eq!(
+        departure.vinf_km_s().unwrap(),
+        departure.c3_km2_s2().sqrt(),
+        "v-infinity"
+    );
+
+    // Far along the hyperbola, the velocity is aligned with the outgoing asymptote
+    let ta_inf_deg = (-1.0 / departure.ecc()).acos().to_degrees();
+    let far = departure.with_ta(ta_inf_deg - 1e-3);
+    let s_hat = departure.vinf_outgoing_hat().unwrap();
+    let v_hat = far.velocity() / far.vmag_km_s();
+    assert!((s_hat - v_hat).norm() < 1e-4, "{s_hat} vs {v_hat}");
+    assert!((far.vmag_km_s() - departure.vinf_km_s().unwrap()).abs() < 1e-2);
+
+    let rla_deg = departure.vinf_right_ascension_deg().unwrap();
+    let dla_deg = departure.vinf_declination_deg().unwrap();
+    assert!(
+        (rla_deg
+            - far
+                .vy_km_s
+                .atan2(far.vx_km_s)
+                .to_degrees()
+                .rem_euclid(360.0))
+        .abs()
+            < 1e-2
+    );
+    assert!((dla_deg - far.velocity_declination_deg()).abs() < 1e-2);
+
+    // The duals match the real values
+    let dual = OrbitDual::from(departure);
+    for param in [
+        StateParameter::Vinf,
+        StateParameter::VinfRightAscension,
+        StateParameter::VinfDeclination,
+    ] {
+        let partial = dual.partial_for(param).unwrap();
+        let value = departure.value(param).unwrap();
+        assert!(
+            (partial.real().rem_euclid(360.0) - value.rem_euclid(360.0)).abs() < 1e-9,
+            "{param}: {} != {value}",
+            partial.real()
+        );
+    }
+}
+
+#[test]
+fn orbit_approx_eq() {
+    use nyx::assert_orbit_approx_eq;
+    use nyx::cosmic::{OrbitMismatch, OrbitTolerances};
+    use nyx::md::StateParameter;
+    use nyx::time::TimeUnits;
+
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(21_545.0);
+    let orbit = Orbit::keplerian(8_000.0, 0.1, 28.5, 359.99, 45.0, 20.0, dt, eme2k);
+
+    assert_orbit_approx_eq!(orbit, orbit);
+
+    // One meter off in X, and the RAAN is across the 0/360 degree boundary
+    let other = Orbit::keplerian(8_000.0, 0.1, 28.5, 0.01, 45.0, 20.0, dt, eme2k);
+    let tol = OrbitTolerances::cartesian(10.0, 1e-2).with_element(StateParameter::RAAN, 0.05);
+    assert_orbit_approx_eq!(other, orbit, tol);
+    assert!(!other.approx_eq(
+        &orbit,
+        &tol.clone().with_element(StateParameter::RAAN, 1e-3)
+    ));
+
+    let shifted = orbit.add_sma(1e-3);
+    let mismatches = shifted.mismatches(&orbit, &OrbitTolerances::default());
+    assert!(!mismatches.is_empty());
+    assert!(shifted.approx_eq(
+        &orbit,
+        &OrbitTolerances::cartesian(1e-2, 1e-5).with_element(StateParameter::SMA, 2e-3)
+    ));
+
+    // Epoch and frame checks
+    let mut later = orbit;
+    later.epoch = dt + 1.seconds();
+    assert_eq!(
+        later.mismatches(&orbit, &OrbitTolerances::default()),
+        vec![OrbitMismatch::Epoch {
+            difference: 1.seconds(),
+            tolerance: 0.seconds()
+        }]
+    );
+    assert!(later.approx_eq(
+        &orbit,
+        &OrbitTolerances::default().with_epoch_tolerance(1.seconds())
+    ));
+
+    let mut moon = orbit;
+    moon.frame = cosm.frame("Moon J2000");
+    assert!(!moon.approx_eq(&orbit, &OrbitTolerances::default()));
+    assert!(moon.approx_eq(&orbit, &OrbitTolerances::default().without_frame_check()));
+
+    // Parameters which cannot be computed are reported
+    let tol = OrbitTolerances::default().with_element(StateParameter::HyperbolicAnomaly, 1.0);
+    assert_eq!(
+        orbit.mismatches(&orbit, &tol),
+        vec![OrbitMismatch::Unavailable(
+            StateParameter::HyperbolicAnomaly
+        )]
+    );
+}
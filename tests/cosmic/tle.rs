@@ -0,0 +1,140 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{teme_to_eme2000, Cosm, Orbit, Tle};
+use nyx::dynamics::sph_harmonics::Harmonics;
+use nyx::io::gravity::HarmonicsMem;
+use nyx::linalg::{Matrix3, Vector3};
+use nyx::md::prelude::*;
+use nyx::time::{Epoch, TimeSeries, Unit};
+
+const LINE1: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+const LINE2: &str = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+#[test]
+fn tle_sgp4_verification() {
+    let tle = Tle::parse(LINE1, LINE2).unwrap();
+    println!("{tle:?}");
+    assert_eq!(tle.catalog_number, 5);
+    assert_eq!(tle.intl_designator, "58002B");
+    assert!((tle.bstar - 0.28098e-4).abs() < 1e-15);
+    assert!((tle.ecc - 0.1859667).abs() < 1e-12);
+
+    // Formatting the parsed TLE yields the same lines, with their checksums
+    let [line1, line2] = tle.lines();
+    assert_eq!(line1, LINE1);
+    assert_eq!(line2, LINE2);
+    assert_eq!(format!("{tle}"), format!("{LINE1}\n{LINE2}"));
+
+    // Invalid checksums are rejected
+    let bad_line2 = format!("{}8", &LINE2[..68]);
+    assert!(Tle::parse(LINE1, &bad_line2).is_err());
+
+    // Verification cases of Vallado et al. (2006), in TEME
+    for (minutes, expected) in [
+        (
+            0.0,
+            [
+                7022.46529266,
+                -1400.08296755,
+                0.03995155,
+                1.893841015,
+                6.405893759,
+                4.534807250,
+            ],
+        ),
+        (
+            360.0,
+            [
+                -7154.03120202,
+                -3783.17682504,
+                -3536.19412294,
+                4.741887409,
+                -4.151817765,
+                -2.093935425,
+            ],
+        ),
+    ] {
+        let state = tle.teme_at(tle.epoch + minutes * Unit::Minute).unwrap();
+        let err_km = (state.fixed_rows::<3>(0) - Vector3::from_row_slice(&expected[..3])).norm();
+        let err_km_s = (state.fixed_rows::<3>(3) - Vector3::from_row_slice(&expected[3..])).norm();
+        println!("SGP4 at {minutes} min: {err_km:.3e} km\t{err_km_s:.3e} km/s");
+        assert!(err_km < 1e-6);
+        assert!(err_km_s < 1e-9);
+    }
+
+    // TEME differs from EME2000 by the precession and nutation since J2000
+    let dcm = teme_to_eme2000(tle.epoch);
+    assert!((dcm * dcm.transpose() - Matrix3::identity()).norm() < 1e-12);
+    let angle_deg = ((dcm.trace() - 1.0) / 2.0).acos().to_degrees();
+    assert!((0.005..0.01).contains(&angle_deg), "{angle_deg} deg");
+}
+
+#[test]
+fn tle_fit_to_trajectory() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    // A TLE fitted to the SGP4 propagation of another TLE recovers it
+    let tle = Tle::parse(LINE1, LINE2).unwrap();
+    let mut sgp4_traj = Traj::new();
+    for epoch in TimeSeries::inclusive(tle.epoch, tle.epoch + 1 * Unit::Day, 2 * Unit::Minute) {
+        sgp4_traj.states.push(tle.at(epoch, &cosm).unwrap());
+    }
+    let fit = Tle::fit(
+        &sgp4_traj,
+        tle.epoch,
+        tle.epoch + 1 * Unit::Day,
+        10 * Unit::Minute,
+        true,
+        cosm.clone(),
+    )
+    .unwrap();
+    println!("{fit}");
+    assert!(fit.rms_km < 1e-3);
+    assert!((fit.tle.mean_motion_rev_day - tle.mean_motion_rev_day).abs() < 1e-8);
+    assert!((fit.tle.ecc - tle.ecc).abs() < 1e-8);
+    assert!((fit.tle.inc_deg - tle.inc_deg).abs() < 1e-6);
+    assert!((fit.tle.raan_deg - tle.raan_deg).abs() < 1e-6);
+    assert!((fit.tle.bstar - tle.bstar).abs() < 1e-7);
+
+    // TLE consistent with a high fidelity trajectory in LEO
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 3, 14);
+    let init = Orbit::keplerian(6_900.0, 1e-3, 51.6, 120.0, 45.0, 10.0, epoch, eme2k);
+    let earth_sph_harm = HarmonicsMem::from_cof("data/JGM3.cof.gz", 8, 8, true).unwrap();
+    let dynamics =
+        OrbitalDynamics::point_masses(&[Bodies::Luna, Bodies::Sun], cosm.clone()).with_model(
+            Harmonics::from_stor(iau_earth, earth_sph_harm, cosm.clone()),
+        );
+    let (_, traj) = Propagator::default(dynamics)
+        .with(init)
+        .for_duration_with_traj(2 * Unit::Day)
+        .unwrap();
+
+    let mut fit = Tle::fit(
+        &traj,
+        epoch,
+        epoch + 1 * Unit::Day,
+        5 * Unit::Minute,
+        false,
+        cosm.clone(),
+    )
+    .unwrap();
+    fit.tle.catalog_number = 99_999;
+    fit.tle.intl_designator = "23001A".to_string();
+    println!("{fit}");
+    assert_eq!(fit.tle.epoch, epoch);
+    assert!(fit.rms_km < 2.0, "fit RMS of {} km", fit.rms_km);
+
+    // The published lines are consistent with the ephemeris
+    let [line1, line2] = fit.tle.lines();
+    let published = Tle::parse(&line1, &line2).unwrap();
+    for epoch in TimeSeries::inclusive(epoch, epoch + 1 * Unit::Day, 1 * Unit::Hour) {
+        let err_km = (published.at(epoch, &cosm).unwrap().radius()
+            - traj.at(epoch).unwrap().radius())
+        .norm();
+        assert!(err_km < 3.0 * fit.max_km.max(1.0), "{err_km} km at {epoch}");
+    }
+}
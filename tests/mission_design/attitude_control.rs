@@ -0,0 +1,189 @@
+extern crate nalgebra as na;
+extern crate nyx_space as nyx;
+
+use na::UnitQuaternion;
+use nyx::io::ExportCfg;
+use nyx::linalg::{Matrix3, Vector3};
+use nyx::md::attitude_control::{
+    AttitudeActuator, AttitudeControl, AttitudeState, AttitudeTarget, QuaternionFeedback,
+    ReactionWheels, ThrusterSet,
+};
+use nyx::md::attitude_profile::{AttitudeProfile, AttitudeSegment, PointingMode, SlewLimits};
+use nyx::md::prelude::*;
+use nyx::md::{Event, StateParameter};
+use std::path::PathBuf;
+
+fn leo_traj(cosm: Arc<Cosm>, duration: Duration) -> (Epoch, nyx::md::ScTraj) {
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian_altitude(500.0, 1e-3, 51.6, 30.0, 0.0, 0.0, epoch, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 0.0);
+    let prop = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (_, traj) = prop.with(sc).for_duration_with_traj(duration).unwrap();
+    (epoch, traj)
+}
+
+#[test]
+fn reaction_wheels_inertial_hold() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let (epoch, traj) = leo_traj(cosm, 1.hours());
+
+    let inertia = Matrix3::from_diagonal(&Vector3::new(100.0, 120.0, 80.0));
+    let control = AttitudeControl::new(
+        inertia,
+        AttitudeActuator::ReactionWheels(ReactionWheels::orthogonal(2.0, 0.2)),
+        QuaternionFeedback::from_bandwidth(&inertia, 0.1, 0.9),
+        AttitudeTarget::Inertial(UnitQuaternion::identity()),
+        1.seconds(),
+    );
+    println!("{control}");
+
+    // Slew by 30 deg to the target attitude
+    let initial = AttitudeState::new(
+        epoch,
+        UnitQuaternion::from_scaled_axis(
+            Vector3::new(1.0, 1.0, 0.0).normalize() * 30_f64.to_radians(),
+        ),
+        Vector3::zeros(),
+    );
+    let history = control.propagate(initial, &traj, 0.5.seconds()).unwrap();
+    println!("{history}");
+    assert!((history.states[0].error_deg - 30.0).abs() < 1e-9);
+
+    for state in &history.states {
+        // Without external torques, the angular momentum of the spacecraft and its wheels is conserved, and here zero
+        let momentum =
+            state.attitude * (inertia * state.body_rate_rad_s + state.wheel_momentum_n_m_s);
+        assert!(momentum.norm() < 1e-9, "{state}: {momentum}");
+        if state.epoch > epoch + 10.minutes() {
+            assert!(state.error_deg < 1e-3, "{state}");
+        }
+    }
+    assert!(history.thruster_on_time.is_empty());
+
+    // With a disturbance torque, the wheels store its momentum until they saturate
+    let disturbance = 1e-3;
+    let control = control.with_disturbance(Vector3::new(0.0, 0.0, disturbance));
+    let initial = AttitudeState::new(epoch, UnitQuaternion::identity(), Vector3::zeros());
+    let history = control.propagate(initial, &traj, 0.5.seconds()).unwrap();
+
+    let crossings = history
+        .find(&Event::new(StateParameter::WheelMomentum, 1.0))
+        .unwrap();
+    let saturation = epoch + (2.0 / disturbance).seconds();
+    // Once saturated, the spacecraft tumbles and the momentum crosses that value again
+    assert_eq!(crossings.iter().filter(|e| **e < saturation).count(), 1);
+    let expected = epoch + (1.0 / disturbance).seconds();
+    assert!(
+        (crossings[0] - expected).abs() < 1.seconds(),
+        "wheel momentum reached 1 N·m·s at {} instead of {expected}",
+        crossings[0]
+    );
+
+    for state in &history.states {
+        assert!(state.value(StateParameter::WheelMomentum).unwrap() <= 2.0 + 1e-12);
+        // The proportional gain alone balances the disturbance, hence a small steady state error
+        if state.epoch < saturation - 1.minutes() {
+            assert!(state.error_deg < 0.1, "{state}");
+        }
+    }
+    // Past the saturation, the attitude drifts away under the disturbance
+    let last = history.states.last().unwrap();
+    assert!(last.error_deg > 10.0, "{last}");
+    assert!(last.value(StateParameter::SMA).is_err());
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "attitude_control_wheels.parquet",
+    ]
+    .iter()
+    .collect();
+    let path = history.to_parquet(path, ExportCfg::default()).unwrap();
+    assert!(path.exists());
+}
+
+#[test]
+fn thrusters_pwm_and_pointing_profile() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let (epoch, traj) = leo_traj(cosm.clone(), 30.minutes());
+
+    let inertia = Matrix3::from_diagonal(&Vector3::new(100.0, 120.0, 80.0));
+
+    // The duty cycle of each thruster provides the commanded torque on average
+    let thrusters = ThrusterSet::six_axis(0.1, 20.milliseconds());
+    let pulses = thrusters.pulses(&Vector3::new(0.05, -0.02, 0.001), 1.seconds());
+    let expected = [0.5, 0.0, 0.0, 0.2, 0.0, 0.0];
+    assert_eq!(pulses.len(), expected.len());
+    for (pulse, expected_s) in pulses.iter().zip(expected) {
+        assert!((pulse.to_seconds() - expected_s).abs() < 1e-6, "{pulses:?}");
+    }
+
+    let control = AttitudeControl::new(
+        inertia,
+        AttitudeActuator::Thrusters(thrusters),
+        QuaternionFeedback::from_bandwidth(&inertia, 0.1, 0.9),
+        AttitudeTarget::Inertial(UnitQuaternion::identity()),
+        1.seconds(),
+    );
+    let initial = AttitudeState::new(
+        epoch,
+        UnitQuaternion::from_scaled_axis(Vector3::new(0.0, 0.0, 10_f64.to_radians())),
+        Vector3::zeros(),
+    );
+    let history = control.propagate(initial, &traj, 0.5.seconds()).unwrap();
+    println!("{history}");
+    // The minimum pulse leaves the attitude in a limit cycle about the target
+    for state in &history.states {
+        if state.epoch > epoch + 10.minutes() {
+            assert!(state.error_deg < 0.5, "{state}");
+        }
+    }
+    // Only the thrusters about Z fire
+    assert!(history.thruster_on_time[5] > 1.seconds());
+    assert!(history.thruster_on_time[4] > Duration::ZERO);
+    for idx in 0..4 {
+        assert_eq!(history.thruster_on_time[idx], Duration::ZERO);
+    }
+
+    // Point the X axis to the Sun with a pyramid of wheels
+    let profile = AttitudeProfile::build(
+        &traj,
+        vec![AttitudeSegment::new(epoch, PointingMode::SunPointing)],
+        SlewLimits::new(1.0, 0.01),
+        cosm,
+    )
+    .unwrap();
+    let control = AttitudeControl::new(
+        inertia,
+        AttitudeActuator::ReactionWheels(ReactionWheels::pyramid(30.0, 4.0, 0.2)),
+        QuaternionFeedback::from_bandwidth(&inertia, 0.1, 0.9),
+        AttitudeTarget::Profile {
+            profile: Arc::new(profile.clone()),
+            boresight: Vector3::x(),
+        },
+        1.seconds(),
+    );
+    let initial = AttitudeState::new(epoch, UnitQuaternion::identity(), Vector3::zeros())
+        .with_wheel_momenta(vec![0.1, -0.1, 0.1, -0.1]);
+    let history = control.propagate(initial, &traj, 0.5.seconds()).unwrap();
+    println!("{history}");
+    let last = history.states.last().unwrap();
+    let sun_dir = profile.pointing(&traj.at(last.epoch).unwrap()).unwrap();
+    let angle_deg = (last.attitude * Vector3::x())
+        .dot(&sun_dir)
+        .clamp(-1.0, 1.0)
+        .acos()
+        .to_degrees();
+    assert!(angle_deg < 1e-2, "{last}: {angle_deg} deg");
+    assert!((angle_deg - last.error_deg).abs() < 1e-6);
+
+    // Mismatched initial wheel momenta are rejected
+    let initial = AttitudeState::new(epoch, UnitQuaternion::identity(), Vector3::zeros())
+        .with_wheel_momenta(vec![0.0; 3]);
+    assert!(control.propagate(initial, &traj, 0.5.seconds()).is_err());
+}
@@ -0,0 +1,105 @@
+extern crate nyx_space as nyx;
+
+use nyx::dynamics::guidance::Mnvr;
+use nyx::linalg::Vector3;
+use nyx::md::attitude_profile::{
+    AttitudeConflict, AttitudeProfile, AttitudeSegment, PointingMode, SlewLimits,
+};
+use nyx::md::prelude::*;
+
+#[test]
+fn attitude_slews_and_conflicts() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian_altitude(500.0, 1e-3, 51.6, 30.0, 0.0, 0.0, epoch, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 0.0);
+
+    let prop = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (_, traj) = prop.with(sc).for_duration_with_traj(2.hours()).unwrap();
+
+    // Slew limits: the maximum rate is reached after 50 s and 25 deg
+    let limits = SlewLimits::new(0.5, 0.01);
+    assert!((limits.slew_duration(180.0).to_seconds() - (180.0 / 0.5 + 50.0)).abs() < 1e-9);
+    // Short slews never reach the maximum rate
+    assert!(
+        (limits.slew_duration(10.0).to_seconds() - 2.0 * (10.0_f64 / 0.01).sqrt()).abs() < 1e-9
+    );
+    assert!((limits.angle_at(180.0, limits.slew_duration(180.0)) - 180.0).abs() < 1e-9);
+    assert!((limits.angle_at(180.0, 50.seconds()) - 12.5).abs() < 1e-9);
+    assert!((limits.angle_at(180.0, 100.seconds()) - 37.5).abs() < 1e-9);
+
+    // Anti-velocity burn for 5 minutes
+    let mnvr = Mnvr::from_time_invariant(
+        epoch + 1.hours(),
+        epoch + 65.minutes(),
+        1.0,
+        Vector3::new(-1.0, 0.0, 0.0),
+        Frame::VNC,
+    );
+
+    let segments = vec![
+        AttitudeSegment::new(epoch, PointingMode::SunPointing),
+        AttitudeSegment::new(epoch + 55.minutes(), PointingMode::Burn(Box::new(mnvr))),
+        AttitudeSegment::new(epoch + 75.minutes(), PointingMode::Nadir),
+    ];
+
+    let profile = AttitudeProfile::build(&traj, segments.clone(), limits, cosm.clone()).unwrap();
+    println!("{profile}");
+    assert!(profile.is_feasible());
+    assert_eq!(profile.slews.len(), 2);
+    for (slew, segment) in profile.slews.iter().zip(segments.iter().skip(1)) {
+        assert_eq!(slew.end, segment.start);
+        assert_eq!(slew.duration(), limits.slew_duration(slew.angle_deg));
+    }
+
+    // Pointing follows the modes outside of slews
+    let during_burn = traj.at(epoch + 62.minutes()).unwrap();
+    let burn_dir = profile.pointing(&during_burn).unwrap();
+    let anti_velocity = -during_burn.orbit.velocity() / during_burn.orbit.vmag_km_s();
+    assert!((burn_dir - anti_velocity).norm() < 1e-9);
+
+    let late = traj.at(epoch + 90.minutes()).unwrap();
+    let nadir_dir = profile.pointing(&late).unwrap();
+    assert!((nadir_dir + late.orbit.r_hat()).norm() < 1e-9);
+
+    // And the slew is continuous at its end
+    let slew = &profile.slews[1];
+    let before_end = traj.at(slew.end - 1.milliseconds()).unwrap();
+    let at_end = traj.at(slew.end).unwrap();
+    let angle = profile
+        .pointing(&before_end)
+        .unwrap()
+        .dot(&profile.pointing(&at_end).unwrap())
+        .clamp(-1.0, 1.0)
+        .acos()
+        .to_degrees();
+    assert!(
+        angle < 1e-3,
+        "pointing jumps by {angle} deg at the end of the slew"
+    );
+    assert!(profile.mode_at(slew.start + 1.seconds()).is_none());
+
+    // Requesting nadir pointing right after the burn leaves no time to slew off the burn attitude,
+    // and reaching the burn attitude after the maneuver started is flagged too.
+    let segments = vec![
+        AttitudeSegment::new(epoch, PointingMode::SunPointing),
+        AttitudeSegment::new(epoch + 61.minutes(), PointingMode::Burn(Box::new(mnvr))),
+        AttitudeSegment::new(epoch + 66.minutes(), PointingMode::Nadir),
+    ];
+
+    let profile = AttitudeProfile::build(&traj, segments, limits, cosm).unwrap();
+    println!("{profile}");
+    assert!(!profile.is_feasible());
+    assert!(profile
+        .conflicts
+        .iter()
+        .any(|c| matches!(c, AttitudeConflict::BurnNotPointed { .. })));
+    assert!(profile
+        .conflicts
+        .iter()
+        .any(|c| matches!(c, AttitudeConflict::SlewDuringBurn { .. })));
+}
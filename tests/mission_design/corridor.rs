@@ -0,0 +1,73 @@
+extern crate nyx_space as nyx;
+
+use nyx::linalg::Vector3;
+use nyx::md::corridor::{Corridor, CorridorTargeting};
+use nyx::md::prelude::*;
+
+#[test]
+fn corridor_targeting() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+    let duration = 12 * Unit::Hour;
+    let chief = Orbit::keplerian(7000.0, 1e-4, 51.6, 20.0, 0.0, 0.0, epoch, eme2k);
+    let (_, reference) = Propagator::default(OrbitalDynamics::two_body())
+        .with(chief)
+        .for_duration_with_traj(duration + 1 * Unit::Hour)
+        .unwrap();
+
+    // A higher orbit drifts behind the reference by about three kilometers per revolution
+    let deputy = Orbit::keplerian(7000.3, 1e-4, 51.601, 20.0, 0.0, 0.0, epoch, eme2k);
+
+    let corridor = Corridor::new(reference.clone(), Vector3::new(1.0, 2.0, 1.0))
+        .with_bound(epoch + duration, Vector3::new(0.5, 1.0, 0.5));
+    println!("{corridor}");
+    assert_eq!(corridor.bounds().len(), 2);
+    let mid_widths = corridor.half_widths_km(epoch + 0.5 * duration);
+    assert!((mid_widths - Vector3::new(0.75, 1.5, 0.75)).norm() < 1e-12);
+    assert_eq!(
+        corridor.half_widths_km(epoch + duration + 1 * Unit::Hour),
+        Vector3::new(0.5, 1.0, 0.5)
+    );
+    assert!(corridor.contains(&deputy).unwrap());
+    assert!(corridor.excursion(&chief).unwrap() < 1e-9);
+
+    // Without control, the deputy leaves the corridor
+    let (drifted, _) = Propagator::default(OrbitalDynamics::two_body())
+        .with(deputy)
+        .for_duration_with_traj(duration)
+        .unwrap();
+    let drift_km = corridor.ric_error_km(&drifted).unwrap();
+    println!("uncontrolled RIC error: {drift_km:.3} km");
+    assert!(drift_km[1] < -10.0);
+    assert!(!corridor.contains(&drifted).unwrap());
+
+    let planner = CorridorTargeting::new(corridor.clone(), OrbitalDynamics::two_body());
+    let plan = planner.plan(deputy, duration).unwrap();
+    println!("{plan}");
+
+    assert!(plan.within_corridor(), "{}", plan.max_excursion);
+    assert!(!plan.maneuvers.is_empty());
+    assert_eq!(plan.traj.last().epoch, epoch + duration);
+    for mnvr in &plan.maneuvers {
+        assert!(mnvr.epoch < mnvr.target_epoch);
+        assert!(mnvr.epoch <= mnvr.exit_epoch);
+        // The targeted position is within the target ratio of the corridor
+        let targeted = plan.traj.at(mnvr.target_epoch).unwrap();
+        assert!(corridor.excursion(&targeted).unwrap() <= planner.target_ratio + 1e-3);
+    }
+    // A few centimeters per second suffice to stop a drift of a few kilometers per revolution
+    assert!(plan.total_dv_km_s() < 2e-3, "{} km/s", plan.total_dv_km_s());
+
+    // Invalid configurations are rejected
+    assert!(planner
+        .clone()
+        .with_target_ratio(1.5)
+        .plan(deputy, duration)
+        .is_err());
+    // The reference must cover the whole plan
+    assert!(planner.plan(deputy, 2 * duration).is_err());
+}
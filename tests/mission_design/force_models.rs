@@ -1,11 +1,16 @@
 extern crate nyx_space as nyx;
 
 use nyx::cosmic::{Cosm, Orbit, Spacecraft};
-use nyx::dynamics::{Drag, OrbitalDynamics, SolarPressure, SpacecraftDynamics};
-use nyx::linalg::Vector6;
+use nyx::dynamics::{
+    AtmDensity, BodyAttitude, Drag, ForceModel, OrbitalDynamics, PlanetaryRadiation, Plate,
+    PlateModel, RadiationDistribution, RadiationGrid, SolarPressure, SpacecraftDynamics,
+};
+use nyx::io::ConfigRepr;
+use nyx::linalg::{Vector3, Vector6};
 use nyx::propagators::Propagator;
 use nyx::time::{Epoch, Unit};
 use nyx::utils::rss_orbit_vec_errors;
+use std::sync::Arc;
 
 #[test]
 fn srp_earth_full_vis() {
@@ -199,6 +204,217 @@ fn srp_earth_meo_ecc_inc() {
     }
 }
 
+#[test]
+fn albedo_and_infrared_earth() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_utc_at_midnight(2020, 6, 1);
+
+    // The grid of surface elements converges to the Lambertian sphere for a uniform distribution far from the body, e.g. at the Moon
+    let uniform = RadiationDistribution::Uniform {
+        albedo: 0.3,
+        emissivity: 0.7,
+    };
+    let analytic = PlanetaryRadiation::from_distribution(
+        eme2k,
+        uniform,
+        RadiationGrid::Analytic,
+        cosm.clone(),
+    );
+    let grid = PlanetaryRadiation::from_distribution(
+        eme2k,
+        uniform,
+        RadiationGrid::Rings(30),
+        cosm.clone(),
+    );
+    println!("{analytic}\n{grid}");
+    for ta_deg in [0.0, 60.0, 120.0, 180.0, 240.0] {
+        let geo = Orbit::keplerian(400_000.0, 0.0, 0.1, 0.0, 0.0, ta_deg, dt, eme2k);
+        let (alb_exact, ir_exact) = analytic.irradiance(&geo).unwrap();
+        let (alb_grid, ir_grid) = grid.irradiance(&geo).unwrap();
+        println!(
+            "{ta_deg} deg: albedo {:.6} vs {:.6} W/m^2\tIR {:.6} vs {:.6} W/m^2",
+            alb_grid.norm(),
+            alb_exact.norm(),
+            ir_grid.norm(),
+            ir_exact.norm()
+        );
+        assert!((ir_grid - ir_exact).norm() < 1e-3 * ir_exact.norm());
+        // The analytic albedo comes from the center of the body instead of its lit part, so only its magnitude matches, to
+        // within the discretization of the terminator by the grid
+        let full_phase = ir_exact.norm() * (0.3 * 2.0 / 3.0) / (0.7 / 4.0);
+        assert!((alb_grid.norm() - alb_exact.norm()).abs() < 2e-2 * full_phase);
+    }
+
+    // In LEO, the Earth radiation pressure is a fraction of the SRP
+    let leo = Orbit::keplerian_altitude(500.0, 1e-3, 51.6, 30.0, 0.0, 0.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(leo, 100.0, 1.0);
+    let earth = PlanetaryRadiation::earth(cosm.clone());
+    println!("{earth}");
+    let srp = SolarPressure::default_raw(vec![], cosm.clone());
+    let ratio = earth.eom(&sc).unwrap().norm() / srp.eom(&sc).unwrap().norm();
+    println!("Earth radiation pressure is {:.1} % of SRP", ratio * 100.0);
+    assert!(ratio > 0.05 && ratio < 0.6);
+    // Pointing away from the Earth
+    assert!(earth.eom(&sc).unwrap().dot(&leo.radius()) > 0.0);
+
+    // Its effect over a day in LEO is a few meters, well above the level of precise orbit determination
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let without = setup.with(sc).for_duration(1 * Unit::Day).unwrap();
+    let setup = Propagator::default(SpacecraftDynamics::from_model(
+        OrbitalDynamics::two_body(),
+        earth,
+    ));
+    let with = setup.with(sc).for_duration(1 * Unit::Day).unwrap();
+    let delta_m = (with.orbit.radius() - without.orbit.radius()).norm() * 1e3;
+    println!("Earth radiation pressure effect after a day: {delta_m:.3} m");
+    assert!(delta_m > 1.0);
+}
+
+#[test]
+fn n_plate_models() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let dt = Epoch::from_gregorian_utc_at_midnight(2020, 6, 1);
+
+    let orbit = Orbit::keplerian(6_900.0, 1e-3, 51.6, 30.0, 0.0, 90.0, dt, eme2k);
+    let mut sc = Spacecraft::from_srp_defaults(orbit, 500.0, 4.0).with_drag(4.0, 2.2);
+    sc.srp.cr = 1.0;
+
+    // A single absorbing plate facing the Sun is the cannonball with a unit coefficient of reflectivity
+    let srp = SolarPressure::default_raw(vec![eme2k], cosm.clone());
+    let cannonball = srp.eom(&sc).unwrap();
+    assert!(cannonball.norm() > 0.0, "spacecraft should be lit");
+    let on_plates = |plates: Vec<Plate>| {
+        let model = Arc::new(PlateModel::new(BodyAttitude::Inertial, plates).unwrap());
+        srp.clone().with_plates(model).eom(&sc).unwrap()
+    };
+    let absorbing = on_plates(vec![Plate::sun_tracking(4.0, 0.0, 0.0)]);
+    assert!((absorbing - cannonball).norm() < 1e-12 * cannonball.norm());
+    // A mirror facing the Sun doubles the force, and a diffuse plate adds two thirds of it
+    let mirror = on_plates(vec![Plate::sun_tracking(4.0, 1.0, 0.0)]);
+    assert!((mirror - 2.0 * cannonball).norm() < 1e-12 * cannonball.norm());
+    let diffuse = on_plates(vec![Plate::sun_tracking(4.0, 0.0, 1.0)]);
+    assert!((diffuse - 5.0 / 3.0 * cannonball).norm() < 1e-12 * cannonball.norm());
+
+    // A mirror tilted by 45 degrees is pushed along its normal, and a plate facing away from the Sun is not lit
+    let away = cannonball / cannonball.norm();
+    let perp = away.cross(&Vector3::new(0.0, 0.0, 1.0)).normalize();
+    let normal = -(away + perp).normalize();
+    let tilted = on_plates(vec![Plate::new(4.0, normal, 1.0, 0.0)]);
+    assert!(tilted.cross(&normal).norm() < 1e-12 * tilted.norm());
+    assert!((tilted.norm() - cannonball.norm()).abs() < 1e-12 * cannonball.norm());
+    assert_eq!(on_plates(vec![Plate::new(4.0, away, 0.0, 0.0)]).norm(), 0.0);
+    let with_plates = srp.clone().with_plates(Arc::new(
+        PlateModel::new(
+            BodyAttitude::Inertial,
+            vec![Plate::new(4.0, normal, 1.0, 0.0)],
+        )
+        .unwrap(),
+    ));
+    assert_eq!(with_plates.param_partials(&sc).unwrap().norm(), 0.0);
+
+    // The drag uses the area of the plates facing the flow, with the drag coefficient of the spacecraft
+    let drag = Drag {
+        density: AtmDensity::Constant(1e-12),
+        drag_frame: iau_earth,
+        cosm: cosm.clone(),
+        plates: None,
+    };
+    let cannonball = drag.eom(&sc).unwrap();
+    let flow = -cannonball / cannonball.norm();
+    let ram = drag.clone().with_plates(Arc::new(
+        PlateModel::new(
+            BodyAttitude::Inertial,
+            vec![Plate::new(4.0, flow, 0.0, 0.0)],
+        )
+        .unwrap(),
+    ));
+    assert!((ram.eom(&sc).unwrap() - cannonball).norm() < 1e-12 * cannonball.norm());
+    assert!(
+        (ram.param_partials(&sc).unwrap().column(1) - cannonball / 2.2).norm()
+            < 1e-12 * cannonball.norm()
+    );
+    let edge_on = drag.clone().with_plates(Arc::new(
+        PlateModel::new(
+            BodyAttitude::Inertial,
+            vec![Plate::new(4.0, flow.cross(&away), 0.0, 0.0)],
+        )
+        .unwrap(),
+    ));
+    assert!(edge_on.eom(&sc).unwrap().norm() < 1e-12 * cannonball.norm());
+
+    // An absorbing plate facing nadir receives the radiation of the Lambertian sphere as the cannonball does
+    let radiation = PlanetaryRadiation::from_distribution(
+        eme2k,
+        RadiationDistribution::Uniform {
+            albedo: 0.3,
+            emissivity: 0.7,
+        },
+        RadiationGrid::Analytic,
+        cosm.clone(),
+    );
+    let cannonball = radiation.eom(&sc).unwrap();
+    let nadir = radiation.clone().with_plates(Arc::new(
+        PlateModel::new(
+            BodyAttitude::RCN,
+            vec![Plate::new(4.0, Vector3::new(-1.0, 0.0, 0.0), 0.0, 0.0)],
+        )
+        .unwrap(),
+    ));
+    println!("{nadir}");
+    assert!((nadir.eom(&sc).unwrap() - cannonball).norm() < 1e-12 * cannonball.norm());
+
+    // Box-wing model from its configuration, shared by the SRP and the drag
+    let box_wing = Arc::new(PlateModel::load("data/tests/config/plates.yaml").unwrap());
+    box_wing.validate().unwrap();
+    println!("{box_wing}");
+    assert_eq!(box_wing.plates.len(), 7);
+    assert_eq!(box_wing.attitude, BodyAttitude::VNC);
+    let srp = Arc::new(
+        SolarPressure::default_raw(vec![eme2k], cosm.clone()).with_plates(box_wing.clone()),
+    );
+    let drag = Arc::new(
+        (*Drag::std_atm1976(cosm.clone()))
+            .clone()
+            .with_plates(box_wing.clone()),
+    );
+    println!("{srp}\n{drag}");
+    let sc_dyn = SpacecraftDynamics::from_models(OrbitalDynamics::two_body(), vec![srp, drag]);
+    let final_state = Propagator::default(sc_dyn)
+        .with(sc)
+        .for_duration(2 * Unit::Hour)
+        .unwrap();
+    let two_body = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(sc)
+        .for_duration(2 * Unit::Hour)
+        .unwrap();
+    let (err_r, _) = rss_orbit_vec_errors(
+        &final_state.orbit.to_cartesian_vec(),
+        &two_body.orbit.to_cartesian_vec(),
+    );
+    println!(
+        "box-wing perturbation after two hours: {:.3} m",
+        err_r * 1e3
+    );
+    assert!(err_r > 0.0);
+
+    // Non physical plates are rejected
+    let z_hat = Vector3::new(0.0, 0.0, 1.0);
+    assert!(PlateModel::new(BodyAttitude::Inertial, vec![]).is_err());
+    assert!(PlateModel::new(
+        BodyAttitude::Inertial,
+        vec![Plate::new(1.0, z_hat, 0.8, 0.5)]
+    )
+    .is_err());
+    assert!(PlateModel::new(
+        BodyAttitude::Inertial,
+        vec![Plate::new(-1.0, z_hat, 0.0, 0.0)]
+    )
+    .is_err());
+}
+
 #[test]
 fn exp_drag_earth() {
     let cosm = Cosm::de438_gmat();
@@ -332,3 +548,109 @@ fn std_atm_drag_earth_low() {
 
     */
 }
+
+#[test]
+fn thrust_profile_reconstruction() {
+    use nyx::cosmic::{Frame, GuidanceMode};
+    use nyx::dynamics::guidance::{FiniteBurns, Mnvr, Thruster};
+    use nyx::dynamics::{ForceProfile, ProfileKind};
+    use nyx::linalg::Vector3;
+    use nyx::propagators::{PropOpts, RK4Fixed};
+    use std::path::PathBuf;
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.01, 28.5, 30.0, 45.0, 0.0, start, eme2k);
+
+    let thruster = Thruster {
+        thrust_N: 0.5,
+        isp_s: 300.0,
+    };
+    let sc = Spacecraft::from_thruster(orbit, 300.0, 0.0, thruster, GuidanceMode::Thrust);
+
+    // Flown trajectory: a prograde burn, without decrementing the mass since the profile does not model the fuel usage
+    let burn_start = start + 10 * Unit::Minute;
+    let burn_end = start + 40 * Unit::Minute;
+    let mnvr = Mnvr::from_time_invariant(
+        burn_start,
+        burn_end,
+        1.0,
+        Vector3::new(1.0, 0.0, 0.0),
+        Frame::VNC,
+    );
+    let flown_dyn = SpacecraftDynamics::from_guidance_law_no_decr(
+        OrbitalDynamics::two_body(),
+        FiniteBurns::from_mnvrs(vec![mnvr]),
+    );
+    let prop_time = 2 * Unit::Hour;
+    // Fixed steps of one second to capture the start and end of the burn in all propagations
+    let opts = PropOpts::with_fixed_step(1 * Unit::Second);
+    let flown = Propagator::new::<RK4Fixed>(flown_dyn, opts)
+        .with(sc)
+        .for_duration(prop_time)
+        .unwrap();
+
+    // Telemetry of the thrust, sampled every second in the VNC frame
+    let csv_path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "thrust_profile.csv",
+    ]
+    .iter()
+    .collect();
+    let mut csv = "epoch,x,y,z\n".to_string();
+    let mut epoch = start + 5 * Unit::Minute;
+    while epoch <= start + 45 * Unit::Minute {
+        let thrust_n = if epoch >= burn_start && epoch <= burn_end {
+            thruster.thrust_N
+        } else {
+            0.0
+        };
+        csv.push_str(&format!("{epoch},{thrust_n},0.0,0.0\n"));
+        epoch += Unit::Second;
+    }
+    std::fs::write(&csv_path, csv).unwrap();
+
+    let profile = ForceProfile::from_csv(&csv_path, ProfileKind::Force, Frame::VNC).unwrap();
+    println!("{profile}");
+    assert!((profile.at(burn_start + 90 * Unit::Second)[0] - 0.5).abs() < 1e-12);
+    assert_eq!(profile.at(start).norm(), 0.0);
+    assert!((profile.at(burn_end + 500 * Unit::Millisecond)[0] - 0.25).abs() < 1e-9);
+
+    // Reconstruct the flown trajectory from the profile
+    let sc_no_thrust = Spacecraft::new(orbit, 300.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+    let reconstructed = Propagator::new::<RK4Fixed>(
+        SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), profile),
+        opts,
+    )
+    .with(sc_no_thrust)
+    .for_duration(prop_time)
+    .unwrap();
+
+    let ballistic =
+        Propagator::new::<RK4Fixed>(SpacecraftDynamics::new(OrbitalDynamics::two_body()), opts)
+            .with(sc_no_thrust)
+            .for_duration(prop_time)
+            .unwrap();
+
+    let burn_effect_km = (flown.orbit.radius() - ballistic.orbit.radius()).norm();
+    let (err_r, err_v) = rss_orbit_vec_errors(
+        &reconstructed.orbit.to_cartesian_vec(),
+        &flown.orbit.to_cartesian_vec(),
+    );
+    println!("burn effect: {burn_effect_km:.3} km\treconstruction errors: {err_r:.3e} km\t{err_v:.3e} km/s");
+    assert!(burn_effect_km > 10.0);
+    assert!(err_r < 1e-3 * burn_effect_km);
+    assert!((reconstructed.orbit.sma_km() - flown.orbit.sma_km()).abs() < 1e-2);
+
+    // Samples must be in chronological order
+    let samples = vec![
+        (start + Unit::Minute, Vector3::zeros()),
+        (start, Vector3::zeros()),
+    ];
+    assert!(
+        ForceProfile::from_samples(samples, ProfileKind::Acceleration, Frame::Inertial).is_err()
+    );
+}
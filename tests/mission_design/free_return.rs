@@ -0,0 +1,55 @@
+extern crate nyx_space as nyx;
+
+use nyx::md::free_return::{FreeReturnAnalysis, ReturnCorridor};
+use nyx::md::prelude::*;
+
+#[test]
+fn free_return_corridor_and_aborts() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    // Start at apoapsis of a highly elliptical orbit with a 300 km perigee altitude
+    let orbit =
+        Orbit::keplerian_apsis_altitude(20_000.0, 300.0, 28.5, 0.0, 0.0, 180.0, epoch, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 1000.0, 0.0);
+
+    let prop = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+
+    // The perigee is within this corridor, so this is a free return
+    let corridor = ReturnCorridor::from_altitudes(eme2k, 250.0, 350.0);
+    let analysis = FreeReturnAnalysis::new(&prop, corridor, 1.days(), cosm.clone());
+
+    let check = analysis.check(sc).unwrap();
+    println!("{check}");
+    assert!(check.is_free_return);
+    let rp_km = check.return_periapsis_km().unwrap();
+    assert!((rp_km - orbit.periapsis_km()).abs() < 1e-3);
+
+    // And with a higher corridor, an abort maneuver is needed
+    let corridor = ReturnCorridor::from_altitudes(eme2k, 500.0, 600.0);
+    let analysis = FreeReturnAnalysis::new(&prop, corridor, 1.days(), cosm);
+
+    let check = analysis.check(sc).unwrap();
+    println!("{check}");
+    assert!(!check.is_free_return);
+
+    let candidates = [epoch + 10.minutes(), epoch + 1.hours()];
+    let aborts = analysis.abort_maneuvers(sc, &candidates).unwrap();
+    assert_eq!(aborts.len(), 2);
+
+    for abort in &aborts {
+        println!("{abort}");
+        assert!(abort.dv_mag_km_s() > 0.0);
+        // Apply the abort and check that the return is now within the corridor
+        let mut post_abort = prop.with(sc).until_epoch(abort.epoch).unwrap();
+        post_abort.orbit.apply_dv(abort.dv_km_s);
+        assert!(analysis.check(post_abort).unwrap().is_free_return);
+    }
+
+    // Aborting earlier (closer to apoapsis) is cheaper
+    assert!(aborts[0].dv_mag_km_s() < aborts[1].dv_mag_km_s());
+}
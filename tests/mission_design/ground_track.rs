@@ -0,0 +1,114 @@
+extern crate nyx_space as nyx;
+
+use nyx::dynamics::drag::AtmDensity;
+use nyx::md::ground_track::{repeat_sma_km, GroundTrackKeeping, ReferenceGroundTrack};
+use nyx::md::prelude::*;
+use nyx::md::EventEvaluator;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs::File;
+use std::path::PathBuf;
+
+#[test]
+fn ground_track_maintenance() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    // Repeat ground track of 31 revolutions in two sidereal days, at about 400 km
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+    let sma_km = repeat_sma_km(31, 2, eme2k.gm());
+    assert!((sma_km - 6782.0).abs() < 5.0, "{sma_km}");
+    let orbit = Orbit::keplerian(sma_km, 0.0, 97.0, 30.0, 0.0, 0.0, epoch, eme2k);
+    let repeat_cycle = 31 * orbit.period();
+
+    let reference = Arc::new(
+        ReferenceGroundTrack::from_orbit(
+            orbit,
+            repeat_cycle,
+            OrbitalDynamics::two_body(),
+            cosm.clone(),
+        )
+        .unwrap(),
+    );
+
+    // The ground track of the reference orbit repeats
+    let later = Propagator::default(OrbitalDynamics::two_body())
+        .with(orbit)
+        .for_duration(2.3 * repeat_cycle)
+        .unwrap();
+    let cross_km = reference.cross_track_km(&later).unwrap();
+    println!("reference after 2.3 repeat cycles: {cross_km:.3e} km");
+    assert!(cross_km.abs() < 0.05);
+
+    // An artificially dense atmosphere decays the orbit by a few hundred meters per day, so the ground track drifts by several
+    // kilometers within days
+    let sc = Spacecraft::from_drag_defaults(orbit, 100.0, 1.0);
+    let drag = Arc::new(Drag {
+        density: AtmDensity::Constant(4e-9),
+        drag_frame: iau_earth,
+        cosm: cosm.clone(),
+        plates: None,
+    });
+    let dynamics = SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), drag);
+
+    let planner = GroundTrackKeeping::new(reference.clone(), 5.0, dynamics.clone());
+    let event = planner.event();
+
+    // Without maintenance, the ground track drifts beyond the threshold
+    let uncontrolled = Propagator::default(dynamics)
+        .with(sc)
+        .until_event_online(20 * Unit::Day, &event)
+        .unwrap();
+    println!(
+        "uncontrolled: {} after {}",
+        event.eval_string(&uncontrolled),
+        uncontrolled.epoch() - epoch
+    );
+    assert!(event.eval(&uncontrolled).abs() < 1e-3);
+
+    let plan = planner.plan(sc, 20 * Unit::Day).unwrap();
+    println!("{plan}");
+
+    assert_eq!(plan.duration(), 20 * Unit::Day);
+    assert!(plan.within_threshold());
+    // The maintenance raises the decaying orbit with pairs of maneuvers
+    assert!(plan.maneuvers.len() >= 4);
+    assert_eq!(plan.maneuvers.len() % 2, 0);
+    assert!(plan.maneuvers.iter().map(|mnvr| mnvr.dv_km_s).sum::<f64>() > 0.0);
+
+    // Validate the controlled ground track with the exported ground track and the evaluation of the event
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "ground_track_maintenance.parquet",
+    ]
+    .iter()
+    .collect();
+    let exported = plan
+        .traj
+        .to_groundtrack_parquet(path, iau_earth, Some(vec![&event]), None, cosm)
+        .unwrap();
+
+    let mut reader = ParquetRecordBatchReaderBuilder::try_new(File::open(exported).unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut rows = 0;
+    let mut max_excess_km = f64::NEG_INFINITY;
+    for batch in reader.by_ref() {
+        let batch = batch.unwrap();
+        let column = batch
+            .column_by_name(&format!("{event}"))
+            .unwrap()
+            .as_any()
+            .downcast_ref::<arrow::array::Float64Array>()
+            .unwrap();
+        rows += column.len();
+        max_excess_km = column.iter().flatten().fold(max_excess_km, f64::max);
+    }
+    println!("{rows} exported rows, largest excess of {max_excess_km:.3} km");
+    assert_eq!(rows, 20 * 24 * 60 + 1);
+    assert!(max_excess_km <= 0.0);
+}
@@ -0,0 +1,74 @@
+extern crate nyx_space as nyx;
+
+use nyx::md::maneuver_design::{
+    bi_elliptic, combined_plane_change, hohmann, hohmann_vs_bi_elliptic, plane_change,
+};
+use nyx::md::prelude::*;
+
+#[test]
+fn analytic_plane_change_and_transfers() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let gm = eme2k.gm();
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    // Circular LEO parking orbit
+    let leo = Orbit::keplerian_altitude(300.0, 1e-6, 28.5, 45.0, 0.0, 60.0, epoch, eme2k);
+    let r1_km = leo.rmag_km();
+    let v1_km_s = (gm / r1_km).sqrt();
+
+    // Pure plane change, at a node
+    let plan = plane_change(&leo, 10.0).unwrap();
+    println!("{plan}");
+    assert_eq!(plan.mnvrs.len(), 1);
+    assert!(plan.mnvrs[0].epoch >= epoch && plan.mnvrs[0].epoch < epoch + leo.period());
+    assert!((plan.final_orbit.inc_deg() - 38.5).abs() < 1e-6);
+    assert!((plan.final_orbit.sma_km() - leo.sma_km()).abs() < 1e-6);
+    let expected_dv = 2.0 * plan.final_orbit.vmag_km_s() * 5.0_f64.to_radians().sin();
+    assert!((plan.total_dv_km_s() - expected_dv).abs() < 1e-9);
+
+    // Hohmann to GEO
+    let r2_km = 42_164.0;
+    let plan = hohmann(&leo, r2_km).unwrap();
+    println!("{plan}");
+    let transfer_sma_km = 0.5 * (r1_km + r2_km);
+    let expected_dv = ((gm * (2.0 / r1_km - 1.0 / transfer_sma_km)).sqrt() - v1_km_s)
+        + ((gm / r2_km).sqrt() - (gm * (2.0 / r2_km - 1.0 / transfer_sma_km)).sqrt());
+    // The parking orbit is not exactly circular
+    assert!((plan.total_dv_km_s() - expected_dv).abs() < 1e-4);
+    assert!((plan.final_orbit.rmag_km() - r2_km).abs() < 1e-1);
+    assert!(plan.final_orbit.ecc() < 1e-5);
+    let expected_tof_s = std::f64::consts::PI * (transfer_sma_km.powi(3) / gm).sqrt();
+    assert!((plan.duration().to_seconds() - expected_tof_s).abs() < 1.0);
+    assert_eq!(plan.to_mnvrs().len(), 2);
+
+    // Combined plane change into GEO, which is cheaper than a Hohmann followed by a plane change in GEO
+    let plan = combined_plane_change(&leo, r2_km, -28.5).unwrap();
+    println!("{plan}");
+    assert!(plan.final_orbit.inc_deg() < 1e-6);
+    assert!((plan.final_orbit.rmag_km() - r2_km).abs() < 1e-1);
+    assert!(plan.final_orbit.ecc() < 1e-5);
+    let geo_plane_change_km_s = 2.0 * (gm / r2_km).sqrt() * 14.25_f64.to_radians().sin();
+    assert!(plan.total_dv_km_s() < expected_dv + geo_plane_change_km_s);
+    // Known result for a LEO to GEO transfer from Cape Canaveral: about 4.2 km/s
+    assert!((plan.total_dv_km_s() - 4.2).abs() < 0.1);
+
+    // Hohmann vs bi-elliptic: above a radius ratio of 15.58, the bi-elliptic transfer is always cheaper
+    let trade = hohmann_vs_bi_elliptic(&leo, 20.0 * r1_km, 40.0 * r1_km).unwrap();
+    println!("{trade}");
+    assert!(trade.bi_elliptic_savings_km_s() > 0.0);
+    assert_eq!(trade.cheapest(), &trade.bi_elliptic);
+    assert!(trade.bi_elliptic.duration() > trade.hohmann.duration());
+    assert!((trade.bi_elliptic.final_orbit.rmag_km() - 20.0 * r1_km).abs() < 1.0);
+
+    // But not for a ratio below 11.94
+    let trade = hohmann_vs_bi_elliptic(&leo, r2_km, 2.0 * r2_km).unwrap();
+    assert!(trade.bi_elliptic_savings_km_s() < 0.0);
+    assert_eq!(trade.cheapest(), &trade.hohmann);
+
+    let plan = bi_elliptic(&leo, r2_km, 2.0 * r2_km).unwrap();
+    assert_eq!(plan.mnvrs.len(), 3);
+}
@@ -1,4 +1,15 @@
+mod attitude_control;
+mod attitude_profile;
+mod corridor;
 mod force_models;
+mod free_return;
+mod ground_track;
+mod maneuver_design;
 mod multishoot;
 mod orbitaldyn;
+mod screening;
+mod space_weather;
+mod spin_stabilized;
+mod station_keeping;
 mod targeter;
+mod trade_study;
@@ -4,7 +4,7 @@ extern crate nyx_space as nyx;
 use hifitime::J2000_OFFSET;
 use nyx::cosmic::{assert_orbit_eq_or_abs, Bodies, Cosm, Orbit};
 use nyx::dynamics::{Dynamics, OrbitalDynamics, PointMasses};
-use nyx::linalg::{Matrix6, Vector6};
+use nyx::linalg::{Matrix6, Vector3, Vector6};
 use nyx::propagators::error_ctrl::RSSCartesianStep;
 use nyx::propagators::*;
 use nyx::time::{Epoch, Unit};
@@ -706,24 +706,34 @@ fn two_body_dual() {
     let mut prop = setup.with(init);
     let final_state = prop.for_duration(prop_time).unwrap();
 
-    // Check that the STM is correct by back propagating by the previous step, and multiplying by the STM.
-    let stm_k_to_0 = final_state.stm.unwrap();
-
-    let prev_state = setup
-        .with(init.with_stm())
-        .for_duration(prop_time - step_size)
-        .unwrap();
-    let stm_km1_to_0 = prev_state.stm.unwrap();
-
-    let stm_k_to_km1 = stm_k_to_0 * stm_km1_to_0.try_inverse().unwrap();
-
-    // And check the difference
-    let stm_err = stm_k_to_km1 * prev_state.to_cartesian_vec() - final_state.to_cartesian_vec();
-    let radius_err = stm_err.fixed_rows::<3>(0).into_owned();
-    let velocity_err = stm_err.fixed_rows::<3>(3).into_owned();
+    // Check that the STM is the Jacobian of the flow by comparing it to central differences of the final state with respect
+    // to each component of the initial state. Note that the STM maps deviations of the state, not the state itself: applying
+    // the STM of the last step to the previous state gives a 126 m error with this STM, but only 53 m with the previous STM
+    // whose relative error was 3e-5 (vs 6e-8 now), so that former check did not validate the STM.
+    let stm = final_state.stm().unwrap();
+    let mut fd_stm = Matrix6::zeros();
+    for j in 0..6 {
+        let h = if j < 3 { 1e-3 } else { 1e-6 };
+        let mut plus = init.to_cartesian_vec();
+        plus[j] += h;
+        let mut minus = init.to_cartesian_vec();
+        minus[j] -= h;
+        let final_plus = setup
+            .with(Orbit::cartesian_vec(&plus, init.epoch, eme2k))
+            .for_duration(prop_time)
+            .unwrap();
+        let final_minus = setup
+            .with(Orbit::cartesian_vec(&minus, init.epoch, eme2k))
+            .for_duration(prop_time)
+            .unwrap();
+        fd_stm.set_column(
+            j,
+            &((final_plus.to_cartesian_vec() - final_minus.to_cartesian_vec()) / (2.0 * h)),
+        );
+    }
 
-    assert!(dbg!(radius_err.norm()) < 1e-1);
-    assert!(dbg!(velocity_err.norm()) < 1e-1);
+    let stm_err = (stm - fd_stm).norm() / fd_stm.norm();
+    assert!(dbg!(stm_err) < 1e-6);
 }
 
 #[allow(clippy::identity_op)]
@@ -806,6 +816,149 @@ fn multi_body_dynamics_dual() {
     assert!(dbg!(velocity_stm_delta.norm()) < 1e-3);
 }
 
+#[test]
+fn point_masses_partials() {
+    use nyx::cosmic::LightTimeCalc;
+    use nyx::dynamics::AccelModel;
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    // Any body of the Cosm can be used by name
+    let names = ["Luna", "Sun J2000", "Jupiter Barycenter J2000"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<String>>();
+    let point_masses =
+        PointMasses::try_specific(&names, cosm.clone(), LightTimeCalc::None).unwrap();
+    println!("{point_masses}");
+    assert!(PointMasses::try_specific(
+        &["Vulcan J2000".to_string()],
+        cosm.clone(),
+        LightTimeCalc::None
+    )
+    .is_err());
+
+    // The same bodies as the enumerated ones
+    let enumerated = PointMasses::new(
+        &[Bodies::Luna, Bodies::Sun, Bodies::JupiterBarycenter],
+        cosm.clone(),
+    );
+
+    // The analytical partials match finite differences for a cislunar state and a low Earth orbit
+    for orbit in [
+        Orbit::cartesian(
+            333_321.004_516,
+            -76_134.198_887,
+            -20_873.831_939,
+            0.257_153_712,
+            0.930_284_066,
+            0.346_177,
+            dt,
+            eme2k,
+        ),
+        Orbit::keplerian(7_000.0, 0.01, 51.6, 10.0, 20.0, 30.0, dt, eme2k),
+    ] {
+        let accel = point_masses.eom(&orbit).unwrap();
+        assert_eq!(accel, enumerated.eom(&orbit).unwrap());
+        // Looking up the ephemeris again at the same epoch leads to the same result
+        let (dual_accel, grad) = point_masses.dual_eom(&orbit).unwrap();
+        assert_eq!(dual_accel, accel);
+
+        let step_km = 1.0;
+        for i in 0..3 {
+            let mut plus = orbit;
+            let mut minus = orbit;
+            match i {
+                0 => {
+                    plus.x_km += step_km;
+                    minus.x_km -= step_km;
+                }
+                1 => {
+                    plus.y_km += step_km;
+                    minus.y_km -= step_km;
+                }
+                _ => {
+                    plus.z_km += step_km;
+                    minus.z_km -= step_km;
+                }
+            }
+            let finite_diff = (point_masses.eom(&plus).unwrap()
+                - point_masses.eom(&minus).unwrap())
+                / (2.0 * step_km);
+            let err = (finite_diff - grad.column(i)).norm();
+            println!("partials wrt {i}: {err:.3e} (|grad| = {:.3e})", grad.norm());
+            assert!(err < 1e-6 * grad.norm());
+        }
+    }
+}
+
+#[test]
+fn point_masses_ephemeris_cache() {
+    use nyx::cosmic::LightTimeCalc;
+    use std::sync::Arc;
+    use std::thread;
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let start = Orbit::keplerian(22_000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k);
+    let prop_time = 10 * Unit::Hour;
+    // Fixed steps, such that propagating in several parts leads to the same states
+    let opts = PropOpts::with_fixed_step_s(60.0);
+
+    let bodies = [Bodies::Luna, Bodies::Sun, Bodies::JupiterBarycenter];
+    let dynamics = OrbitalDynamics::point_masses(&bodies, cosm.clone());
+    let expected = Propagator::rk89(dynamics.clone(), opts)
+        .with(start)
+        .for_duration(prop_time)
+        .unwrap()
+        .to_cartesian_vec();
+
+    // The same dynamics propagated concurrently, each thread with its own cache, lead to exactly the same state
+    let handles = (0..4)
+        .map(|_| {
+            let dynamics = dynamics.clone();
+            thread::spawn(move || {
+                Propagator::rk89(dynamics, opts)
+                    .with(start)
+                    .for_duration(prop_time)
+                    .unwrap()
+                    .to_cartesian_vec()
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), expected);
+    }
+
+    // Models of the same bodies at the same epochs on the same thread do not share their lookups if their light time
+    // correction differs
+    let corrected = OrbitalDynamics::new(vec![Arc::new(PointMasses::with_correction(
+        &bodies,
+        cosm,
+        LightTimeCalc::LightTime,
+    ))]);
+    let expected_corrected = Propagator::rk89(corrected.clone(), opts)
+        .with(start)
+        .for_duration(prop_time)
+        .unwrap()
+        .to_cartesian_vec();
+    assert_ne!(expected_corrected, expected);
+
+    let setup = Propagator::rk89(dynamics, opts);
+    let setup_corrected = Propagator::rk89(corrected, opts);
+    let mut prop = setup.with(start);
+    let mut prop_corrected = setup_corrected.with(start);
+    for _ in 0..10 {
+        prop.for_duration(prop_time / 10).unwrap();
+        prop_corrected.for_duration(prop_time / 10).unwrap();
+    }
+    assert_eq!(prop.state.to_cartesian_vec(), expected);
+    assert_eq!(prop_corrected.state.to_cartesian_vec(), expected_corrected);
+}
+
 #[allow(clippy::identity_op)]
 #[test]
 fn val_earth_sph_harmonics_j2() {
@@ -1064,6 +1217,273 @@ fn val_earth_sph_harmonics_70x70_partials() {
     );
 }
 
+#[allow(clippy::identity_op)]
+#[test]
+fn sph_harmonics_fidelity_bands() {
+    let _ = pretty_env_logger::try_init();
+    use nyx::dynamics::{AccelModel, FidelityBand, Harmonics};
+    use nyx::io::gravity::*;
+    use std::time::Instant;
+
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let earth_sph_harm = HarmonicsMem::from_cof("data/JGM3.cof.gz", 70, 70, true).unwrap();
+    let full = Harmonics::from_stor(iau_earth, earth_sph_harm.clone(), cosm.clone());
+    let adaptive = Harmonics::from_stor_with_bands(
+        iau_earth,
+        earth_sph_harm,
+        FidelityBand::earth_defaults(iau_earth),
+        cosm.clone(),
+    );
+    println!("{adaptive}");
+
+    let eq_radius_km = iau_earth.equatorial_radius();
+    assert_eq!(adaptive.degree_order(eq_radius_km + 500.0), (70, 70));
+    assert_eq!(adaptive.degree_order(eq_radius_km + 5_000.0), (30, 30));
+    assert_eq!(adaptive.degree_order(5.0 * eq_radius_km), (12, 12));
+    assert_eq!(adaptive.degree_order(60.0 * eq_radius_km), (8, 8));
+    assert_eq!(full.degree_order(60.0 * eq_radius_km), (70, 70));
+    // The bands are bounded by the degree and order of the storage
+    let bands = vec![FidelityBand::new(f64::INFINITY, 100, 100)];
+    let small = HarmonicsMem::from_cof("data/JGM3.cof.gz", 8, 8, true).unwrap();
+    let bounded = Harmonics::from_stor_with_bands(iau_earth, small, bands, cosm.clone());
+    assert_eq!(bounded.degree_order(eq_radius_km), (8, 8));
+
+    // The neglected terms are negligible in each band
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    for (radius_km, max_err_km_s2) in [(7_000.0, 0.0), (12_000.0, 1e-11), (40_000.0, 1e-12)] {
+        let orbit = Orbit::keplerian(radius_km, 0.0, 63.4, 10.0, 0.0, 45.0, dt, eme2k);
+        let err_km_s2 = (full.eom(&orbit).unwrap() - adaptive.eom(&orbit).unwrap()).norm();
+        println!("{radius_km} km: {err_km_s2:.3e} km/s^2");
+        assert!(err_km_s2 <= max_err_km_s2);
+        let (_, full_grad) = full.dual_eom(&orbit).unwrap();
+        let (_, adaptive_grad) = adaptive.dual_eom(&orbit).unwrap();
+        assert!((full_grad - adaptive_grad).norm() < 1e-12);
+    }
+
+    // A Molniya orbit spends most of its time far from the Earth, where the low degree terms suffice
+    let molniya = Orbit::keplerian(26_600.0, 0.74, 63.4, 10.0, 270.0, 0.0, dt, eme2k);
+    let mut finals = Vec::new();
+    for harmonics in [full.clone(), adaptive.clone()] {
+        let start = Instant::now();
+        let final_state = Propagator::default(OrbitalDynamics::from_model(harmonics.clone()))
+            .with(molniya)
+            .for_duration(2 * Unit::Day)
+            .unwrap();
+        println!("{harmonics}: propagated in {:?}", start.elapsed());
+        finals.push(final_state);
+    }
+    let (err_r, err_v) = rss_orbit_errors(&finals[0], &finals[1]);
+    println!("adaptive vs full: {err_r:.3e} km, {err_v:.3e} km/s");
+    assert!(err_r < 1e-3);
+    assert!(err_v < 1e-6);
+}
+
+#[test]
+fn solid_and_ocean_tides() {
+    let _ = pretty_env_logger::try_init();
+    use nyx::dynamics::{AccelModel, Harmonics, OceanTide, Tides};
+    use nyx::io::gravity::*;
+    use std::f64::consts::TAU;
+    use std::sync::Arc;
+
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let dt = Epoch::from_gregorian_utc_hms(2022, 2, 15, 17, 30, 37);
+
+    // The solid tides are a few parts per billion of the degree 2 and 3 terms, and the degree 4 terms are much smaller
+    let solid = Tides::solid(iau_earth, cosm.clone());
+    let (c_nm, s_nm) = solid.delta_cs(dt).unwrap();
+    println!("{solid}\nDeltaC = {c_nm:.3e}\nDeltaS = {s_nm:.3e}");
+    assert_eq!(c_nm.nrows(), 5);
+    assert!(c_nm[(2, 0)].abs() > 1e-10 && c_nm[(2, 0)].abs() < 3e-8);
+    assert!(c_nm[(2, 2)].abs() > 1e-10 && c_nm[(2, 2)].abs() < 3e-8);
+    assert!(c_nm[(3, 0)].abs() < 1e-9);
+    assert!(c_nm[(4, 0)].abs() < c_nm[(2, 0)].abs() / 100.0);
+    assert_eq!(s_nm[(2, 0)], 0.0);
+
+    // A zero tide field already includes the permanent tide
+    let (zero_tide_c_nm, _) = solid.clone().with_zero_tide_field().delta_cs(dt).unwrap();
+    let permanent = zero_tide_c_nm[(2, 0)] - c_nm[(2, 0)];
+    assert!((permanent - 4.4228e-8 * 0.31460 * 0.30190).abs() < 1e-15);
+
+    // The acceleration of the solid tides in LEO is of the order of 1e-7 m/s^2, and their partials match finite differences
+    let leo = Orbit::keplerian(7_000.0, 0.001, 51.6, 10.0, 20.0, 30.0, dt, eme2k);
+    let accel = solid.eom(&leo).unwrap();
+    println!("solid tides in LEO: {:.3e} km/s^2", accel.norm());
+    assert!(accel.norm() > 1e-11 && accel.norm() < 1e-9);
+    let (dual_accel, grad) = solid.dual_eom(&leo).unwrap();
+    assert!((dual_accel - accel).norm() < 1e-20);
+    let step_km = 1.0;
+    for i in 0..3 {
+        let mut plus = leo;
+        let mut minus = leo;
+        match i {
+            0 => {
+                plus.x_km += step_km;
+                minus.x_km -= step_km;
+            }
+            1 => {
+                plus.y_km += step_km;
+                minus.y_km -= step_km;
+            }
+            _ => {
+                plus.z_km += step_km;
+                minus.z_km -= step_km;
+            }
+        }
+        let finite_diff =
+            (solid.eom(&plus).unwrap() - solid.eom(&minus).unwrap()) / (2.0 * step_km);
+        let err = (finite_diff - grad.column(i)).norm();
+        println!("partials wrt {i}: {err:.3e}");
+        assert!(err < 1e-3 * grad.norm());
+    }
+
+    // Ocean tides from an IERS formatted file, with synthetic coefficients
+    let path = std::env::temp_dir().join("nyx_ocean_tides.dat");
+    std::fs::write(
+        &path,
+        "Doodson Darw  l   m    DelC+     DelS+       DelC-     DelS-\n\
+         55.565 Om1   2   0   1.00000    0.00000     0.00000    0.00000\n\
+         255.555 M2   2   2   1.00000    0.00000     0.00000    0.00000\n\
+         255.555 M2   5   2   1.00000    0.00000     0.00000    0.00000\n",
+    )
+    .unwrap();
+    let constituents = OceanTide::from_iers_file(path.to_str().unwrap(), 4, 4).unwrap();
+    assert_eq!(constituents.len(), 2);
+    assert_eq!(constituents[0].doodson, [0, 0, 0, 0, 1, 0]);
+    assert_eq!(constituents[1].doodson, [2, 0, 0, 0, 0, 0]);
+    assert!((constituents[1].c_plus - 1e-11).abs() < 1e-24);
+    // The M2 tide has a period of 12.4206 hours
+    let m2 = constituents[1];
+    let delta = m2.argument(dt + 12.420_601_2 * Unit::Hour) - m2.argument(dt) - TAU;
+    assert!(delta.abs() < 1e-5, "M2 argument off by {delta:.3e} rad");
+
+    let ocean = Tides::ocean(iau_earth, constituents, cosm.clone());
+    let (c_nm, s_nm) = ocean.delta_cs(dt).unwrap();
+    assert_eq!(c_nm.nrows(), 3);
+    assert!((c_nm[(2, 2)] - 1e-11 * m2.argument(dt).cos()).abs() < 1e-24);
+    assert!((s_nm[(2, 2)] + 1e-11 * m2.argument(dt).sin()).abs() < 1e-24);
+    assert_eq!(s_nm[(2, 0)], 0.0);
+    assert!(solid.clone().with_ocean(vec![m2]).max_degree() == 4);
+
+    // The solid tides shift a LEO by meters over a day, including with the STM
+    let earth_sph_harm = HarmonicsMem::from_cof("data/JGM3.cof.gz", 20, 20, true).unwrap();
+    let harmonics = Harmonics::from_stor(iau_earth, earth_sph_harm, cosm.clone());
+    let dynamics = OrbitalDynamics::from_model(harmonics);
+    let with_tides = dynamics.clone().with_tides(Arc::new(solid));
+    println!("{with_tides}");
+    let mut finals = Vec::new();
+    for dynamics in [dynamics, with_tides] {
+        finals.push(
+            Propagator::default(dynamics)
+                .with(leo.with_stm())
+                .for_duration(1 * Unit::Day)
+                .unwrap(),
+        );
+    }
+    let (err_r, err_v) = rss_orbit_errors(&finals[0], &finals[1]);
+    println!("solid tides effect: {err_r:.3e} km, {err_v:.3e} km/s");
+    assert!(err_r > 1e-3 && err_r < 1.0);
+    let stm_diff = (finals[0].stm().unwrap() - finals[1].stm().unwrap()).norm();
+    println!("STM difference: {stm_diff:.3e}");
+    assert!(stm_diff > 0.0);
+}
+
+#[test]
+fn relativistic_corrections() {
+    let _ = pretty_env_logger::try_init();
+    use nyx::dynamics::{AccelModel, Relativity};
+
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_utc_hms(2022, 2, 15, 17, 30, 37);
+
+    let relativity = Relativity::earth(cosm.clone());
+    println!("{relativity}");
+
+    // In LEO, the Schwarzschild term is about 1e-8 m/s^2, and the Lense-Thirring and de Sitter terms are about 1e-10 m/s^2
+    let leo = Orbit::keplerian(7_000.0, 0.001, 51.6, 10.0, 20.0, 30.0, dt, eme2k);
+    let mut accels = Vec::new();
+    for term in 0..3 {
+        let mut only = (*relativity).clone();
+        only.schwarzschild = term == 0;
+        only.lense_thirring = term == 1;
+        only.de_sitter = term == 2;
+        let accel = only.eom(&leo).unwrap();
+        println!("{only}: {:.3e} km/s^2", accel.norm());
+        accels.push(accel);
+    }
+    assert!(accels[0].norm() > 1e-11 && accels[0].norm() < 1e-10);
+    assert!(accels[1].norm() > 1e-14 && accels[1].norm() < 1e-12);
+    assert!(accels[2].norm() > 1e-14 && accels[2].norm() < 1e-12);
+    let total = relativity.eom(&leo).unwrap();
+    assert!((total - accels.iter().sum::<Vector3<f64>>()).norm() < 1e-25);
+    // The Schwarzschild term is mostly radial and outward
+    assert!(accels[0].dot(&leo.radius()) / (accels[0].norm() * leo.rmag_km()) > 0.99);
+
+    // The frame dragging of a prograde equatorial orbit is radial and outward
+    let mut frame_dragging = (*relativity).clone();
+    frame_dragging.schwarzschild = false;
+    frame_dragging.de_sitter = false;
+    let equatorial = Orbit::keplerian(7_000.0, 0.0, 0.0, 0.0, 0.0, 0.0, dt, eme2k);
+    let accel = frame_dragging.eom(&equatorial).unwrap();
+    assert!(accel.dot(&equatorial.radius()) / (accel.norm() * equatorial.rmag_km()) > 0.99);
+
+    // The Lense-Thirring term only applies about the Earth
+    let moon = cosm.frame("Luna");
+    let llo = Orbit::keplerian(1_900.0, 0.001, 80.0, 10.0, 20.0, 30.0, dt, moon);
+    assert!(frame_dragging.eom(&llo).unwrap().norm() < f64::EPSILON);
+
+    // The partials with respect to the position match finite differences
+    let (dual_accel, grad) = relativity.dual_eom(&leo).unwrap();
+    assert!((dual_accel - total).norm() < 1e-24);
+    let step_km = 1.0;
+    for i in 0..3 {
+        let mut plus = leo;
+        let mut minus = leo;
+        match i {
+            0 => {
+                plus.x_km += step_km;
+                minus.x_km -= step_km;
+            }
+            1 => {
+                plus.y_km += step_km;
+                minus.y_km -= step_km;
+            }
+            _ => {
+                plus.z_km += step_km;
+                minus.z_km -= step_km;
+            }
+        }
+        let finite_diff =
+            (relativity.eom(&plus).unwrap() - relativity.eom(&minus).unwrap()) / (2.0 * step_km);
+        let err = (finite_diff - grad.column(i)).norm();
+        println!("partials wrt {i}: {err:.3e}");
+        assert!(err < 1e-3 * grad.norm());
+    }
+
+    // Over a day, the relativistic corrections shift a LEO by meters
+    let dynamics = OrbitalDynamics::two_body();
+    let with_relativity = dynamics.clone().with_relativity(relativity);
+    println!("{with_relativity}");
+    let mut finals = Vec::new();
+    for dynamics in [dynamics, with_relativity] {
+        finals.push(
+            Propagator::default(dynamics)
+                .with(leo.with_stm())
+                .for_duration(1 * Unit::Day)
+                .unwrap(),
+        );
+    }
+    let (err_r, err_v) = rss_orbit_errors(&finals[0], &finals[1]);
+    println!("relativistic effect: {err_r:.3e} km, {err_v:.3e} km/s");
+    assert!(err_r > 1e-5 && err_r < 1e-2);
+}
+
 #[test]
 fn hf_prop() {
     // Tests a high fidelity propagation over several days for performance analysis.
@@ -0,0 +1,168 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit, Tle};
+use nyx::dynamics::OrbitalDynamics;
+use nyx::io::ExportCfg;
+use nyx::linalg::Vector3;
+use nyx::md::screening::*;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, TimeUnits};
+use std::fs;
+
+const LINE1: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753";
+const LINE2: &str = "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+#[test]
+fn catalog_screening() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let start = Epoch::from_gregorian_utc_at_midnight(2000, 6, 28);
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+
+    let primary_orbit = Orbit::keplerian(7200.0, 0.001, 51.6, 40.0, 20.0, 0.0, start, eme2k);
+    let (_, primary) = setup
+        .with(primary_orbit)
+        .for_duration_with_traj(1.days())
+        .unwrap();
+
+    // A crosser passes 1 km above the primary at a right angle at a known epoch
+    let crossing = start + 5.hours();
+    let at_crossing = primary.at(crossing).unwrap();
+    let r_hat = at_crossing.radius() / at_crossing.rmag_km();
+    let v = at_crossing.velocity();
+    let crossing_velocity = r_hat.cross(&v);
+    let crosser_at_crossing = Orbit::cartesian(
+        at_crossing.x_km + r_hat[0],
+        at_crossing.y_km + r_hat[1],
+        at_crossing.z_km + r_hat[2],
+        crossing_velocity[0],
+        crossing_velocity[1],
+        crossing_velocity[2],
+        crossing,
+        eme2k,
+    );
+    let crosser_start = setup.with(crosser_at_crossing).until_epoch(start).unwrap();
+    let (_, mut crosser) = setup
+        .with(crosser_start)
+        .for_duration_with_traj(1.days())
+        .unwrap();
+    crosser.name = Some("CROSSER".to_string());
+
+    // A geostationary object never comes close
+    let geo = Orbit::keplerian(42164.0, 0.0, 0.0, 0.0, 0.0, 0.0, start, eme2k);
+    let (_, geo_traj) = setup.with(geo).for_duration_with_traj(1.days()).unwrap();
+
+    // An ephemeris which does not overlap the window
+    let late = Orbit::keplerian(
+        7200.0,
+        0.001,
+        51.6,
+        40.0,
+        20.0,
+        0.0,
+        start + 3.days(),
+        eme2k,
+    );
+    let (_, late_traj) = setup.with(late).for_duration_with_traj(1.days()).unwrap();
+
+    let tle = Tle::parse(LINE1, LINE2).unwrap();
+    let config = ScreeningConfig::default();
+    let screening = CatalogScreening::new(
+        config,
+        vec![
+            Secondary::ephemeris("CROSSER".to_string(), crosser.clone()),
+            Secondary::ephemeris("GEO".to_string(), geo_traj),
+            Secondary::ephemeris("LATE".to_string(), late_traj),
+            Secondary::tle("VANGUARD 1".to_string(), tle.clone()),
+        ],
+    )
+    .unwrap();
+
+    let report = screening.screen(&primary, &cosm).unwrap();
+    println!("{report}");
+    assert_eq!(report.start, start);
+    assert_eq!(report.end, start + 1.days());
+    assert_eq!(report.sieved(), vec!["GEO"]);
+    assert!(report.failed().is_empty());
+    assert_eq!(report.outcomes[2].1, ScreeningOutcome::OutOfWindow);
+    match &report.outcomes[3].1 {
+        ScreeningOutcome::Screened {
+            num_evaluations, ..
+        } => {
+            // The smart sieve skips most of the fixed step samples
+            println!("TLE screened with {num_evaluations} evaluations");
+            assert!(*num_evaluations < 1440);
+        }
+        outcome => panic!("unexpected outcome for the TLE: {outcome:?}"),
+    }
+
+    // The closest conjunction is the crossing, with the crosser 1 km above the primary
+    let closest = &report.events[0];
+    assert_eq!(closest.secondary, "CROSSER");
+    assert!((closest.tca - crossing).to_seconds().abs() < 0.1);
+    assert!((closest.miss_distance_km - 1.0).abs() < 1e-3);
+    assert!((closest.miss_ric_km - Vector3::new(1.0, 0.0, 0.0)).norm() < 1e-2);
+    assert!((closest.relative_speed_km_s - v.norm() * 2.0_f64.sqrt()).abs() < 1e-2);
+    // Events are ranked by miss distance and within the threshold
+    for pair in report.events.windows(2) {
+        assert!(pair[0].miss_distance_km <= pair[1].miss_distance_km);
+    }
+    assert!(report
+        .events
+        .iter()
+        .all(|event| event.miss_distance_km <= config.threshold_km));
+    // The orbits keep crossing at each node, drifting apart
+    let crosser_events = report.events_with("CROSSER");
+    assert!(crosser_events.len() > 1);
+    for pair in crosser_events.windows(2) {
+        assert!(pair[0].tca < pair[1].tca);
+    }
+
+    // Same screening from a catalog directory of ephemerides and TLEs
+    let dir = std::env::temp_dir().join("nyx_screening_catalog");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    crosser
+        .to_oem_file(dir.join("crosser.oem"), ExportCfg::default())
+        .unwrap();
+    let [line1, line2] = tle.lines();
+    fs::write(
+        dir.join("catalog.tle"),
+        format!("0 VANGUARD 1\n{line1}\n{line2}\n\n{LINE1}\n{LINE2}\n"),
+    )
+    .unwrap();
+    fs::write(dir.join("README.md"), "not a catalog").unwrap();
+
+    let from_dir = CatalogScreening::from_directory(&dir, config).unwrap();
+    let names: Vec<&str> = from_dir
+        .secondaries
+        .iter()
+        .map(|secondary| secondary.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["VANGUARD 1", "00005", "CROSSER"]);
+    let dir_report = from_dir.screen(&primary, &cosm).unwrap();
+    let dir_closest = &dir_report.events[0];
+    assert_eq!(dir_closest.secondary, "CROSSER");
+    assert!((dir_closest.tca - closest.tca).to_seconds().abs() < 1.0);
+    assert!((dir_closest.miss_distance_km - closest.miss_distance_km).abs() < 0.05);
+
+    // Invalid configurations and catalogs
+    assert!(CatalogScreening::new(
+        ScreeningConfig {
+            threshold_km: 0.0,
+            ..config
+        },
+        vec![]
+    )
+    .is_err());
+    assert!(CatalogScreening::new(
+        ScreeningConfig {
+            step: 0.seconds(),
+            ..config
+        },
+        vec![]
+    )
+    .is_err());
+    assert!(CatalogScreening::from_directory(dir.join("missing"), config).is_err());
+    assert!(Tle::parse_many(LINE1).is_err());
+}
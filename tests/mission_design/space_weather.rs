@@ -0,0 +1,189 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit, Spacecraft};
+use nyx::dynamics::{AtmDensity, Drag, OrbitalDynamics, SpacecraftDynamics};
+use nyx::io::space_weather::{ForecastPolicy, SpaceWeather, SpaceWeatherDay, SpaceWeatherSource};
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, TimeUnits, Unit};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn data_path(name: &str) -> PathBuf {
+    [
+        env!("CARGO_MANIFEST_DIR"),
+        "data",
+        "tests",
+        "space_weather",
+        name,
+    ]
+    .iter()
+    .collect()
+}
+
+#[test]
+fn space_weather_files_and_policies() {
+    let _ = pretty_env_logger::try_init();
+
+    let sw = SpaceWeather::from_celestrak_csv(data_path("SW-sample.csv")).unwrap();
+    println!("{sw:?}");
+    assert_eq!(sw.days().len(), 9);
+    assert_eq!(sw.months().len(), 2);
+    let last_observed = Epoch::from_gregorian_utc_at_midnight(2023, 3, 26);
+    assert_eq!(sw.last_observed(), Some(last_observed));
+
+    // Observed data: three hour indices and interpolated flux
+    let storm = Epoch::from_gregorian_utc(2023, 3, 23, 13, 30, 0, 0);
+    let indices = sw.at(storm).unwrap();
+    println!("{indices}");
+    assert_eq!(indices.source, SpaceWeatherSource::Observed);
+    assert_eq!(indices.ap, 94.0);
+    assert!((indices.kp - 6.3).abs() < 1e-12);
+    assert!((indices.f107 - (151.3 + 0.0625 * (149.0 - 151.3))).abs() < 1e-9);
+    assert!((indices.f107_avg - (153.1 + 0.0625 * 0.1)).abs() < 1e-9);
+
+    let first = Epoch::from_gregorian_utc_at_midnight(2023, 3, 20);
+    assert_eq!(sw.at(first + 6.hours()).unwrap().f107, 160.1);
+    assert!(sw.at(first - 1.seconds()).is_err());
+
+    // Forecasts from the CelesTrak file, with the Ap index of the day
+    let forecast = sw.at(last_observed + 30.hours()).unwrap();
+    assert_eq!(forecast.source, SpaceWeatherSource::DailyForecast);
+    assert_eq!(forecast.ap, 10.0);
+    assert!((forecast.f107 - (142.0 + 0.25 * (145.0 - 142.0))).abs() < 1e-9);
+    // Monthly forecasts apply to the whole month
+    let monthly = sw
+        .at(Epoch::from_gregorian_utc_at_noon(2023, 4, 15))
+        .unwrap();
+    assert_eq!(monthly.source, SpaceWeatherSource::MonthlyForecast);
+    assert_eq!(monthly.ap, 12.0);
+    assert_eq!(monthly.f107_avg, 145.2);
+    assert!(sw
+        .at(Epoch::from_gregorian_utc_at_noon(2023, 3, 30))
+        .is_err());
+    assert!(sw
+        .at(Epoch::from_gregorian_utc_at_noon(2023, 6, 2))
+        .is_err());
+
+    // The NOAA forecast replaces the daily forecasts but not the observations
+    let sw = sw
+        .with_noaa_forecast(data_path("45DF.txt"))
+        .unwrap()
+        .with_policy(ForecastPolicy::UseForecast);
+    assert_eq!(sw.days().len(), 17);
+    assert_eq!(sw.months().len(), 2);
+    assert_eq!(sw.last_observed(), Some(last_observed));
+    assert_eq!(sw.at(last_observed + 30.hours()).unwrap().ap, 12.0);
+    let noaa = sw
+        .at(Epoch::from_gregorian_utc_at_noon(2023, 3, 29))
+        .unwrap();
+    assert_eq!(noaa.source, SpaceWeatherSource::DailyForecast);
+    assert_eq!(noaa.ap, 20.0);
+    assert!((noaa.kp - 3.5).abs() < 1e-12);
+    assert_eq!(noaa.f107, 140.0);
+    assert!(noaa.f107_avg > 130.0 && noaa.f107_avg < 165.0);
+    assert_eq!(
+        sw.at(Epoch::from_gregorian_utc_at_noon(2023, 4, 3))
+            .unwrap()
+            .ap,
+        8.0
+    );
+    assert_eq!(
+        sw.at(Epoch::from_gregorian_utc_at_noon(2023, 4, 15))
+            .unwrap()
+            .source,
+        SpaceWeatherSource::MonthlyForecast
+    );
+
+    // Other forecast policies
+    let persisted = sw
+        .clone()
+        .with_policy(ForecastPolicy::Persist)
+        .at(Epoch::from_gregorian_utc_at_noon(2023, 4, 15))
+        .unwrap();
+    assert_eq!(persisted.source, SpaceWeatherSource::Observed);
+    assert_eq!(persisted.ap, 2.0);
+    assert_eq!(persisted.f107, 145.0);
+
+    let constant = sw.clone().with_policy(ForecastPolicy::Constant {
+        f107: 150.0,
+        f107_avg: 150.0,
+        ap: 15.0,
+    });
+    assert_eq!(constant.at(last_observed + 2.days()).unwrap().ap, 15.0);
+    // Observations are still used
+    assert_eq!(constant.at(storm).unwrap(), indices);
+
+    let reject = sw.with_policy(ForecastPolicy::Reject);
+    assert!(reject.at(last_observed + 23.hours()).is_ok());
+    assert!(reject.at(last_observed + 1.days()).is_err());
+
+    assert!(SpaceWeather::from_celestrak_csv(data_path("45DF.txt")).is_err());
+    assert!(SpaceWeather::from_days(vec![]).is_err());
+}
+
+#[test]
+fn space_weather_drag() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let sw = Arc::new(SpaceWeather::from_celestrak_csv(data_path("SW-sample.csv")).unwrap());
+
+    // A quiet period with the same solar flux
+    let quiet = Arc::new(
+        SpaceWeather::from_days(
+            sw.days()
+                .iter()
+                .chain(sw.months())
+                .map(|day| {
+                    SpaceWeatherDay::from_daily(
+                        day.epoch,
+                        4.0,
+                        day.f107,
+                        day.f107_avg,
+                        SpaceWeatherSource::Observed,
+                    )
+                })
+                .collect(),
+        )
+        .unwrap(),
+    );
+
+    let storm = Epoch::from_gregorian_utc(2023, 3, 23, 22, 0, 0, 0);
+    let storm_rho = AtmDensity::thermospheric(400.0, &sw.at(storm).unwrap());
+    let quiet_rho = AtmDensity::thermospheric(400.0, &quiet.at(storm).unwrap());
+    println!("density at 400 km: {storm_rho:.3e} kg/m^3 in storm, {quiet_rho:.3e} kg/m^3 if quiet");
+    assert!(quiet_rho > 1e-12 && quiet_rho < 1e-11);
+    assert!(storm_rho > 1.5 * quiet_rho);
+
+    let start = Epoch::from_gregorian_utc_at_midnight(2023, 3, 22);
+    let orbit = Orbit::keplerian_altitude(400.0, 1e-3, 51.6, 30.0, 0.0, 0.0, start, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 10.0).with_drag(10.0, 2.2);
+
+    let decay_km = |space_weather: Arc<SpaceWeather>| {
+        let drag = Drag::earth_space_weather(cosm.clone(), space_weather);
+        let dynamics = SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), drag);
+        let end = Propagator::default(dynamics)
+            .with(sc)
+            .for_duration(3 * Unit::Day)
+            .unwrap();
+        orbit.sma_km() - end.orbit.sma_km()
+    };
+
+    let storm_decay = decay_km(sw.clone());
+    let quiet_decay = decay_km(quiet);
+    println!("decay over three days: {storm_decay:.3} km in storm, {quiet_decay:.3} km if quiet");
+    assert!(quiet_decay > 0.0);
+    assert!(storm_decay > 1.2 * quiet_decay);
+
+    // No density before the space weather data
+    let drag = Drag::earth_space_weather(cosm, sw);
+    let dynamics = SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), drag);
+    let mut early = sc;
+    early.orbit.epoch = start - 3.days();
+    assert!(Propagator::default(dynamics)
+        .with(early)
+        .for_duration(1.hours())
+        .is_err());
+}
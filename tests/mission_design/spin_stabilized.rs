@@ -0,0 +1,159 @@
+extern crate nyx_space as nyx;
+
+use nyx::dynamics::{ForceModel, SolarPressure};
+use nyx::linalg::Vector3;
+use nyx::md::prelude::*;
+use nyx::md::spin_stabilized::{
+    ConstantTorque, SpinProjected, SpinStabilized, SpinState, SpinTorque, SrpTorque,
+};
+use nyx::od::noise::GaussMarkov;
+use nyx::od::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[test]
+fn spin_axis_precession_and_nutation() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(26_560.0, 1e-3, 55.0, 30.0, 0.0, 0.0, epoch, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 2.0);
+    let prop = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (_, traj) = prop.with(sc).for_duration_with_traj(6.hours()).unwrap();
+
+    // A constant torque along X precesses the spin axis from Z towards X, with a rate of T cos(φ) / H
+    let torque_n_m = 1e-2;
+    let spinner = SpinStabilized::new(30.0, 500.0, 1.5, 3.0)
+        .with_nutation_damping(1.hours())
+        .with_torque(Arc::new(ConstantTorque {
+            torque_n_m: Vector3::new(torque_n_m, 0.0, 0.0),
+        }));
+    println!("{spinner}");
+    let initial = SpinState::new(epoch, Vector3::new(0.0, 0.0, 1.0), 5.0);
+    let profile = spinner.propagate(initial, &traj, 1.minutes()).unwrap();
+    println!("{profile}");
+
+    let rate = torque_n_m / spinner.angular_momentum_n_m_s();
+    for elapsed in [1.hours(), 3.hours() + 30.seconds(), 6.hours()] {
+        let state = profile.state_at(epoch + elapsed).unwrap();
+        let expected_rad = (rate * elapsed.to_seconds()).sinh().atan();
+        let angle_rad = state.spin_axis[0].atan2(state.spin_axis[2]);
+        assert!(
+            (angle_rad - expected_rad).abs() < 1e-6,
+            "{elapsed}: {angle_rad} != {expected_rad}"
+        );
+        assert!(state.spin_axis[1].abs() < 1e-12);
+        assert!((state.spin_axis.norm() - 1.0).abs() < 1e-12);
+    }
+
+    // The nutation decays with the time constant of the damper
+    let last = profile.states.last().unwrap();
+    assert!((last.nutation_deg - 5.0 * (-6.0_f64).exp()).abs() < 1e-12);
+    assert!(profile.state_at(epoch - 1.seconds()).is_err());
+    assert!(profile.state_at(epoch + 6.hours() + 1.seconds()).is_err());
+
+    // Areas of the cylinder, and the nutation cone widens the projected end area
+    let z_hat = Vector3::new(0.0, 0.0, 1.0);
+    let x_hat = Vector3::new(1.0, 0.0, 0.0);
+    assert!((spinner.projected_area_m2(&z_hat, &z_hat) - 1.5).abs() < 1e-12);
+    assert!((spinner.projected_area_m2(&z_hat, &-z_hat) - 1.5).abs() < 1e-12);
+    assert!((spinner.projected_area_m2(&z_hat, &x_hat) - 3.0).abs() < 1e-12);
+    assert!(spinner.averaged_area_m2(&z_hat, 10.0, &z_hat) > 1.5);
+}
+
+#[test]
+fn spin_projected_srp_and_antenna() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(26_560.0, 1e-3, 55.0, 30.0, 0.0, 0.0, epoch, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 2.0);
+    let prop = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (_, traj) = prop.with(sc).for_duration_with_traj(1.days()).unwrap();
+
+    // Spin axis along the celestial pole, with the antenna beam around it
+    let srp = SolarPressure::default(eme2k, cosm.clone());
+    let spinner = SpinStabilized::new(30.0, 500.0, 1.5, 3.0)
+        .with_antenna(50.0)
+        .with_torque(Arc::new(SrpTorque {
+            srp: srp.clone(),
+            cp_offset_m: 0.2,
+        }));
+    let initial = SpinState::new(epoch, Vector3::new(0.0, 0.0, 1.0), 2.0);
+    let profile = Arc::new(spinner.propagate(initial, &traj, 10.minutes()).unwrap());
+    println!("{profile}");
+
+    // The SRP torque is perpendicular to both the spin axis and the Sun line, and barely moves the axis in a day
+    let state = profile.state_at(epoch).unwrap();
+    let torque = SrpTorque {
+        srp: srp.clone(),
+        cp_offset_m: 0.2,
+    }
+    .torque(&sc, &spinner, &state)
+    .unwrap();
+    let sun_line = srp.eom(&sc).unwrap();
+    assert!(torque.norm() > 0.0);
+    assert!(torque.dot(&state.spin_axis).abs() < 1e-12 * torque.norm());
+    assert!(torque.dot(&sun_line).abs() < 1e-12 * torque.norm() * sun_line.norm());
+    let last = profile.states.last().unwrap();
+    assert!(last.spin_axis.dot(&initial.spin_axis) > 0.999);
+
+    // The projected SRP uses the area of the cylinder seen from the Sun instead of that of the spacecraft
+    let projected = SpinProjected::new(srp.clone(), profile.clone());
+    println!("{projected}");
+    let later = traj.at(epoch + 5.hours()).unwrap();
+    let area_m2 = profile
+        .projected_area_m2(later.orbit.epoch, &srp.eom(&later).unwrap())
+        .unwrap();
+    assert!(area_m2 > 1.5 && area_m2 < 3.0 + 1.5);
+    let expected = srp.eom(&later.with_srp_area(area_m2)).unwrap();
+    assert!((projected.eom(&later).unwrap() - expected).norm() < 1e-12 * expected.norm());
+
+    // The station is only tracked when it is within the antenna beam
+    let station = GroundStation::dss65_madrid(0.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth);
+    let exclusions = profile
+        .antenna_exclusions(&traj, &station, 1.minutes(), &cosm)
+        .unwrap();
+    println!("{} exclusions", exclusions.len());
+    assert!(!exclusions.is_empty());
+
+    let simulate = |exclusion_epochs| {
+        let mut configs = HashMap::new();
+        configs.insert(
+            station.name.clone(),
+            TrkConfig {
+                exclusion_epochs,
+                ..TrkConfig::from_sample_rate(1.minutes())
+            },
+        );
+        TrackingArcSim::<Spacecraft, RangeDoppler, _>::with_seed(
+            vec![station.clone()],
+            traj.clone(),
+            configs,
+            0,
+        )
+        .unwrap()
+        .generate_measurements(cosm.clone())
+        .unwrap()
+    };
+    let arc = simulate(Some(exclusions.clone()));
+    let unconstrained = simulate(None);
+    println!("{arc} instead of {unconstrained}");
+    assert!(!arc.measurements.is_empty());
+    assert!(arc.measurements.len() < unconstrained.measurements.len());
+    for (_, msr) in &arc.measurements {
+        let sc = traj.at(msr.epoch).unwrap();
+        let tx = cosm.frame_chg(&station.to_orbit(msr.epoch), eme2k);
+        assert!(profile
+            .antenna_available(msr.epoch, &(tx.radius() - sc.orbit.radius()))
+            .unwrap());
+        assert!(!exclusions.iter().any(|range| range.contains(msr.epoch)));
+    }
+}
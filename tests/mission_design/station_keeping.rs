@@ -0,0 +1,69 @@
+extern crate nyx_space as nyx;
+
+use nyx::dynamics::sph_harmonics::Harmonics;
+use nyx::md::prelude::*;
+use nyx::md::station_keeping::{geostationary_sma_km, GeoSlot, GeoStationKeeping, SkKind};
+
+#[test]
+fn geo_station_keeping() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+    let init = Orbit::keplerian(
+        geostationary_sma_km(eme2k.gm()),
+        1e-4,
+        0.02,
+        90.0,
+        0.0,
+        30.0,
+        epoch,
+        eme2k,
+    );
+
+    // Luni-solar perturbations drive the inclination, and the tesseral harmonics the longitude
+    let earth_sph_harm = HarmonicsMem::from_cof("data/JGM3.cof.gz", 4, 4, true).unwrap();
+    let dynamics =
+        OrbitalDynamics::point_masses(&[Bodies::Luna, Bodies::Sun], cosm.clone()).with_model(
+            Harmonics::from_stor(iau_earth, earth_sph_harm, cosm.clone()),
+        );
+
+    let ecc_radius = GeoSlot::natural_ecc_radius(1.5, 0.015);
+    assert!((ecc_radius - 2.475e-4).abs() < 1e-10);
+
+    let planner = GeoStationKeeping::new(
+        GeoSlot::new(0.0, 0.1, 0.05, ecc_radius),
+        dynamics,
+        cosm.clone(),
+    );
+    // Center the slot on the initial longitude
+    let mut planner = planner;
+    planner.slot.longitude_deg = cosm.frame_chg(&init, iau_earth).geodetic_longitude_deg();
+    assert!(planner.longitude_error_deg(&init).abs() < 1e-12);
+
+    let inc_vec = GeoStationKeeping::inclination_vector(&init);
+    assert!((inc_vec.norm().to_degrees() - 0.02).abs() < 1e-9);
+    assert!((inc_vec[1].atan2(inc_vec[0]).to_degrees() - 90.0).abs() < 1e-6);
+
+    let plan = planner.plan(init, 120 * Unit::Day).unwrap();
+    println!("{plan}");
+
+    assert_eq!(plan.duration(), 120 * Unit::Day);
+    assert!(plan.within_slot());
+    assert!(plan.max_ecc < 2.0 * ecc_radius);
+
+    // The inclination vector drifts by about 0.8 deg per year, i.e. about 43 m/s per year
+    let (ns_m_s, ew_m_s) = plan.annual_budget_m_s();
+    assert!(plan.maneuvers.iter().any(|m| m.kind == SkKind::NorthSouth));
+    assert!((35.0..55.0).contains(&ns_m_s), "N/S budget {ns_m_s} m/s/yr");
+    assert!(ew_m_s < 8.0, "E/W budget {ew_m_s} m/s/yr");
+
+    // The post-maneuver states are in the controlled trajectory
+    for mnvr in &plan.maneuvers {
+        let state = plan.traj.at(mnvr.epoch).unwrap();
+        assert_eq!(state.epoch, mnvr.epoch);
+    }
+}
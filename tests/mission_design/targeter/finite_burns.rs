@@ -1,7 +1,7 @@
 extern crate nyx_space as nyx;
 
 use hifitime::TimeUnits;
-use nyx::dynamics::guidance::{Mnvr, Thruster};
+use nyx::dynamics::guidance::{Mnvr, Steering, Thruster};
 use nyx::linalg::Vector3;
 use nyx::md::optimizer::*;
 use nyx::md::prelude::*;
@@ -229,3 +229,78 @@ fn val_tgt_finite_burn() {
 
     // Test that this solution works.
 }
+
+#[test]
+fn linear_tangent_tgt_sma_ecc_inc() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let orig_dt = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    let xi_orig = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 0.0, orig_dt, eme2k);
+
+    let spacecraft = Spacecraft {
+        orbit: xi_orig,
+        dry_mass_kg: 10.0,
+        fuel_mass_kg: 90.0,
+        thruster: Some(Thruster {
+            thrust_N: 500.0,
+            isp_s: 300.0,
+        }),
+        mode: GuidanceMode::Thrust,
+        ..Default::default()
+    };
+
+    let achievement_epoch = orig_dt + 30.seconds();
+
+    // Known solution: pitch up from the velocity while slightly yawing out of plane
+    let known_mnvr = Mnvr::from_linear_tangent(
+        orig_dt,
+        achievement_epoch,
+        1.0,
+        (-0.01, 0.2),
+        (0.002, 0.05),
+        Frame::VNC,
+    );
+    println!("{known_mnvr}");
+    assert!((known_mnvr.angles(0.0).0 - 0.2_f64.atan()).abs() < f64::EPSILON);
+    assert!((known_mnvr.angles(10.0).0 - 0.1_f64.atan()).abs() < f64::EPSILON);
+
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+    let setup = Propagator::default_dp78(dynamics.clone());
+
+    let prop_known = Propagator::default_dp78(SpacecraftDynamics::from_guidance_law(
+        OrbitalDynamics::two_body(),
+        Arc::new(known_mnvr),
+    ));
+    let xf_known = prop_known
+        .with(spacecraft)
+        .until_epoch(achievement_epoch)
+        .unwrap();
+    println!("{xf_known:x}");
+
+    // Define the objective
+    let objectives = [
+        Objective::within_tolerance(StateParameter::SMA, xf_known.orbit.sma_km(), 0.1),
+        Objective::within_tolerance(StateParameter::Eccentricity, xf_known.orbit.ecc(), 1e-5),
+        Objective::within_tolerance(StateParameter::Inclination, xf_known.orbit.inc_deg(), 1e-3),
+    ];
+
+    let tgt = Optimizer::linear_tangent(&setup, objectives);
+
+    println!("{}", tgt);
+
+    let solution_fd = tgt
+        .try_achieve_from(spacecraft, orig_dt, achievement_epoch)
+        .unwrap();
+
+    println!("Finite differencing solution: {}", solution_fd);
+
+    let mnvr = solution_fd.to_mnvr().unwrap();
+    println!("{mnvr}");
+    assert_eq!(mnvr.steering, Steering::LinearTangent);
+
+    tgt.apply(&solution_fd).unwrap();
+}
@@ -0,0 +1,34 @@
+extern crate nyx_space as nyx;
+
+use nyx::md::optimizer::*;
+use nyx::md::prelude::*;
+
+#[test]
+fn tgt_launch_c3_rla_dla() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2026, 11, 20);
+
+    // Start from the injection of a nearby launch target and correct it to the desired one, as done when updating the
+    // targets handed to a launch provider.
+    let rp_km = eme2k.equatorial_radius() + 185.0;
+    let nominal = LaunchTarget::new(10.0, 325.0, -23.0)
+        .injection_orbits(rp_km, 28.5, epoch, eme2k)
+        .unwrap()[0];
+
+    let target = LaunchTarget::new(10.5, 330.0, -25.0);
+    assert!(!target.is_achieved_by(&nominal).unwrap());
+
+    let prop = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let spacecraft = Spacecraft::from_srp_defaults(nominal, 100.0, 0.0);
+
+    let tgt = Optimizer::delta_v(&prop, target.to_objectives());
+    let sol = tgt.try_achieve_from(spacecraft, epoch, epoch).unwrap();
+    println!("{sol}");
+
+    let achieved = sol.achieved_state.orbit;
+    println!("{}", LaunchTarget::from_orbit(&achieved).unwrap());
+    assert!(target.is_achieved_by(&achieved).unwrap());
+}
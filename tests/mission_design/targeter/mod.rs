@@ -2,8 +2,10 @@ extern crate nyx_space as nyx;
 
 mod b_plane;
 mod finite_burns;
+mod launch;
 mod multi_oe;
 mod multi_oe_vnc;
 #[cfg(feature = "broken-donotuse")]
 mod opti_levenberg;
+mod scaling;
 mod single_oe;
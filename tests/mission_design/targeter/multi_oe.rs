@@ -240,3 +240,113 @@ fn tgt_hd_sma_ecc() {
         "Finite differencing result different from GMAT and greater!"
     );
 }
+
+#[test]
+fn tgt_hd_dependent_objectives() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let orig_dt = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    let xi_orig = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 0.0, orig_dt, eme2k).with_stm();
+
+    let target_delta_t: Duration = xi_orig.period() / 20.0;
+
+    let spacecraft = Spacecraft::from_srp_defaults(xi_orig, 100.0, 0.0);
+
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+    let setup = Propagator::default_dp78(dynamics);
+
+    // The energy only depends on the SMA, so the rows of the Jacobian are linearly dependent and the normal equations are
+    // singular: the SVD pseudo-inverse provides the minimum norm correction of each iteration instead.
+    let xf_desired_sma = 8_100.0;
+    let xf_desired_energy = -eme2k.gm() / (2.0 * xf_desired_sma);
+
+    let sma_only = Optimizer::delta_v(
+        &setup,
+        [Objective::within_tolerance(
+            StateParameter::SMA,
+            xf_desired_sma,
+            1e-3,
+        )],
+    )
+    .try_achieve_dual(spacecraft, orig_dt, orig_dt + target_delta_t)
+    .unwrap();
+
+    let dependent = Optimizer::delta_v(
+        &setup,
+        [
+            Objective::within_tolerance(StateParameter::SMA, xf_desired_sma, 1e-3),
+            Objective::within_tolerance(StateParameter::Energy, xf_desired_energy, 1e-6),
+        ],
+    )
+    .try_achieve_dual(spacecraft, orig_dt, orig_dt + target_delta_t)
+    .unwrap();
+
+    println!("SMA only: {sma_only}\nSMA and energy: {dependent}");
+    let achieved = dependent.achieved_state.orbit;
+    assert!((achieved.sma_km() - xf_desired_sma).abs() < 1e-3);
+    assert!((achieved.energy_km2_s2() - xf_desired_energy).abs() < 1e-6);
+    // The minimum norm corrections of each iteration lead to about the same correction as targeting the SMA alone
+    assert!(
+        (dependent.correction.norm() - sma_only.correction.norm()).abs()
+            < 0.05 * sma_only.correction.norm()
+    );
+}
+
+#[test]
+fn tgt_position_and_tof() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let orig_dt = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    let xi_orig = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 0.0, orig_dt, eme2k);
+
+    let spacecraft = Spacecraft::from_srp_defaults(xi_orig, 100.0, 0.0);
+
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+    let setup = Propagator::default(dynamics);
+
+    // Build the rendezvous point by applying a known maneuver and propagating for a known time of flight
+    let tof = xi_orig.period() / 2.0 + 10.minutes();
+    let mut xi_mnvr = spacecraft;
+    xi_mnvr.orbit.vx_km_s += 0.05;
+    xi_mnvr.orbit.vz_km_s -= 0.02;
+    let rdv = setup.with(xi_mnvr).for_duration(tof).unwrap();
+
+    // Target that position at the desired time of flight, but start with an achievement epoch guess at half a period
+    let objectives = [
+        Objective::within_tolerance(StateParameter::X, rdv.orbit.x_km, 1e-1),
+        Objective::within_tolerance(StateParameter::Y, rdv.orbit.y_km, 1e-1),
+        Objective::within_tolerance(StateParameter::Z, rdv.orbit.z_km, 1e-1),
+        Objective::time_of_flight(tof, 1.seconds()),
+    ];
+
+    let variables = [
+        Vary::VelocityX.into(),
+        Vary::VelocityY.into(),
+        Vary::VelocityZ.into(),
+        Vary::AchievementEpoch.into(),
+    ];
+
+    let tgt = Optimizer::new(&setup, variables, objectives);
+
+    println!("{}", tgt);
+
+    let solution_fd = tgt
+        .try_achieve_from(spacecraft, orig_dt, orig_dt + xi_orig.period() / 2.0)
+        .unwrap();
+
+    println!("Finite differencing solution: {}", solution_fd);
+
+    // The arrival epoch must have been corrected
+    assert!((solution_fd.achieved_state.epoch() - (orig_dt + tof)).abs() < 1.seconds());
+    assert!((solution_fd.achieved_state.orbit.radius() - rdv.orbit.radius()).norm() < 1e-1);
+
+    tgt.apply(&solution_fd).unwrap();
+}
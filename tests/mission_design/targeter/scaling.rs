@@ -0,0 +1,91 @@
+extern crate nyx_space as nyx;
+
+use nyx::md::opti::solution::TargeterSolution;
+use nyx::md::optimizer::*;
+use nyx::md::prelude::*;
+
+#[test]
+fn tgt_scaling_mixed_units() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let orig_dt = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+    let xi_orig = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 90.0, orig_dt, eme2k);
+    let spacecraft = Spacecraft::from_srp_defaults(xi_orig, 100.0, 0.0);
+    let achievement_epoch = orig_dt + xi_orig.period() / 4.0;
+
+    let setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+
+    // Move both the position (km) and the velocity (km/s) to achieve the SMA (km) and the inclination (deg)
+    let variables = [
+        Variable::from(Vary::PositionX).with_max(50.0),
+        Variable::from(Vary::PositionY).with_max(50.0),
+        Variable::from(Vary::PositionZ).with_max(50.0),
+        Variable::from(Vary::VelocityX),
+        Variable::from(Vary::VelocityY),
+        Variable::from(Vary::VelocityZ),
+    ];
+    let objectives = [
+        Objective::within_tolerance(StateParameter::SMA, 8_050.0, 1e-3),
+        Objective::within_tolerance(StateParameter::Inclination, 30.5, 1e-4),
+    ];
+
+    let units = CanonicalUnits::from_orbit(&xi_orig);
+    println!("{units}");
+    let canonical_norm = |sol: &TargeterSolution<6, 2>| {
+        let pos = sol.correction.fixed_rows::<3>(0).norm() / units.length_km;
+        let vel = sol.correction.fixed_rows::<3>(3).norm() / units.velocity_km_s();
+        (pos, vel, (pos.powi(2) + vel.powi(2)).sqrt())
+    };
+
+    let tgt = Optimizer::new(&setup, variables, objectives);
+    assert_eq!(tgt.scaling, Scaling::Auto);
+    let unscaled = tgt.clone().with_scaling(Scaling::Disabled);
+
+    let mut canonical_norms = Vec::new();
+    for (name, sol) in [
+        (
+            "scaled FD",
+            tgt.try_achieve_fd(spacecraft, orig_dt, achievement_epoch),
+        ),
+        (
+            "scaled dual",
+            tgt.try_achieve_dual(spacecraft, orig_dt, achievement_epoch),
+        ),
+        (
+            "unscaled FD",
+            unscaled.try_achieve_fd(spacecraft, orig_dt, achievement_epoch),
+        ),
+    ] {
+        let sol = sol.unwrap();
+        let (pos, vel, norm) = canonical_norm(&sol);
+        println!(
+            "{name}: {} iterations\tΔr = {:.3} km\tΔv = {:.3} m/s\tcanonical: {pos:.3e} + {vel:.3e} = {norm:.3e}",
+            sol.iterations,
+            sol.correction.fixed_rows::<3>(0).norm(),
+            sol.correction.fixed_rows::<3>(3).norm() * 1e3
+        );
+        tgt.apply(&sol).unwrap();
+        canonical_norms.push((pos, norm));
+    }
+
+    // Without scaling, the minimum norm correction is almost only in velocity because a kilometer per second is worth far
+    // more than a kilometer, whereas in canonical units both contribute and the correction is smaller
+    assert!(canonical_norms[0].0 > 1e-4);
+    assert!((canonical_norms[0].1 - canonical_norms[1].1).abs() < 1e-6);
+    assert!(canonical_norms[2].0 < 1e-6);
+    assert!(canonical_norms[0].1 < canonical_norms[2].1);
+
+    // The scale of a variable overrides the canonical units, e.g. to favor velocity corrections
+    let mut favor_velocity = variables;
+    for var in favor_velocity.iter_mut().take(3) {
+        *var = var.with_scale(1e-3);
+    }
+    let sol = Optimizer::new(&setup, favor_velocity, objectives)
+        .with_scaling(Scaling::Units(units))
+        .try_achieve_fd(spacecraft, orig_dt, achievement_epoch)
+        .unwrap();
+    assert!(sol.correction.fixed_rows::<3>(0).norm() < 1e-3);
+}
@@ -2,6 +2,7 @@ extern crate nyx_space as nyx;
 
 use nyx::md::optimizer::*;
 use nyx::md::prelude::*;
+use nyx::md::ConditionExpr;
 
 // Semi major axis
 
@@ -566,3 +567,42 @@ fn tgt_aop_from_peri() {
         "Finite differencing result different from GMAT (greater than 6 m/s)."
     );
 }
+
+#[test]
+fn tgt_rmag_at_apo_event() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let orig_dt = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    let xi_orig = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 0.0, orig_dt, eme2k);
+
+    let spacecraft = Spacecraft::from_srp_defaults(xi_orig, 100.0, 0.0);
+
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+    let setup = Propagator::default(dynamics);
+
+    // Raise the apoapsis radius, which also moves the epoch of the apoapsis
+    let objectives = [Objective::new(StateParameter::Rmag, 9_800.0)];
+
+    let tgt = Optimizer::delta_v(&setup, objectives);
+
+    let apo = ConditionExpr::event(Event::apoapsis());
+    let solution = tgt
+        .try_achieve_at_event(spacecraft, orig_dt, xi_orig.period(), &apo)
+        .unwrap();
+
+    println!("{}", solution);
+
+    // The objective is achieved at the apoapsis of the corrected trajectory
+    let apo_state = setup
+        .with(solution.corrected_state)
+        .until_event_online(xi_orig.period(), &apo)
+        .unwrap();
+    println!("{apo_state:x}");
+    assert!((apo_state.epoch() - solution.achieved_state.epoch()).abs() < 1 * Unit::Second);
+    assert!((apo_state.orbit.rmag_km() - 9_800.0).abs() < 1e-1);
+    assert!((apo_state.orbit.ta_deg() - 180.0).abs() < 1e-2);
+}
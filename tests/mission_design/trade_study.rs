@@ -0,0 +1,217 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Bodies, Cosm, LightTimeCalc};
+use nyx::io::ExportCfg;
+use nyx::md::trade_study::*;
+use nyx::time::{Epoch, TimeUnits, Unit};
+use nyx::tools::lambert::{standard, TransferKind};
+use nyx::NyxError;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs::File;
+use std::path::PathBuf;
+
+#[test]
+fn earth_mars_trade_study() {
+    let cosm = Cosm::de438();
+    let sun = cosm.frame("Sun J2000");
+    let eme2k = cosm.frame("EME2000");
+
+    // Departure from a 300 km parking orbit
+    let r_park_km = eme2k.equatorial_radius() + 300.0;
+    let v_park_km_s = (eme2k.gm() / r_park_km).sqrt();
+    let wet_mass_kg = 2000.0;
+    let exhaust_vel_km_s = 320.0 * 9.80665e-3;
+    // Mass of the engine per Newton of thrust
+    let engine_kg_per_n = 0.5;
+
+    let study = TradeStudy::new(
+        vec![
+            DesignVariable::epochs(
+                "Launch epoch",
+                Epoch::from_gregorian_utc_at_midnight(2020, 7, 1),
+                Epoch::from_gregorian_utc_at_midnight(2020, 8, 30),
+                5.days(),
+            ),
+            DesignVariable::durations("TOF", 150.days(), 300.days(), 15.days()),
+            DesignVariable::from_values("Thrust (N)", vec![50.0, 100.0, 200.0, 400.0]),
+        ],
+        vec![
+            TradeObjective::minimize("Delta-V (km/s)"),
+            TradeObjective::minimize("Time of flight (days)"),
+            TradeObjective::maximize("Delivered mass (kg)"),
+            TradeObjective::minimize("Burn duration (min)"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(study.num_points(), 13 * 11 * 4);
+
+    let results = study.run(|point| {
+        let launch = point.epoch("Launch epoch")?;
+        let tof = point.duration("TOF")?;
+        let thrust_n = point.value("Thrust (N)")?;
+
+        let earth =
+            cosm.celestial_state(Bodies::Earth.ephem_path(), launch, sun, LightTimeCalc::None);
+        let mars = cosm.celestial_state(
+            Bodies::MarsBarycenter.ephem_path(),
+            launch + tof,
+            sun,
+            LightTimeCalc::None,
+        );
+        let transfer = standard(
+            earth.radius(),
+            mars.radius(),
+            tof.to_seconds(),
+            sun.gm(),
+            TransferKind::ShortWay,
+        )?;
+
+        // Injection from the parking orbit onto the departure hyperbola
+        let v_inf_km_s = (transfer.v_init - earth.velocity()).norm();
+        let dv_km_s = (v_inf_km_s.powi(2) + 2.0 * eme2k.gm() / r_park_km).sqrt() - v_park_km_s;
+
+        let prop_mass_kg = wet_mass_kg * (1.0 - (-dv_km_s / exhaust_vel_km_s).exp());
+        let delivered_kg = wet_mass_kg - prop_mass_kg - engine_kg_per_n * thrust_n;
+        let burn_duration = (prop_mass_kg * exhaust_vel_km_s * 1e3 / thrust_n) * Unit::Second;
+
+        Ok(vec![
+            dv_km_s,
+            tof.to_unit(Unit::Day),
+            delivered_kg,
+            burn_duration.to_unit(Unit::Minute),
+        ])
+    });
+
+    println!("{results}");
+    assert_eq!(results.points.len(), study.num_points());
+    assert_eq!(results.num_feasible(), study.num_points());
+
+    // The front is made of the non-dominated points only
+    let front = results.pareto_front();
+    assert!(!front.is_empty());
+    assert!(front.len() < results.points.len());
+
+    let signs = [1.0, 1.0, -1.0, 1.0];
+    let dominates = |a: &[f64], b: &[f64]| {
+        a.iter().zip(b).zip(signs).all(|((a, b), s)| s * a <= s * b)
+            && a.iter().zip(b).zip(signs).any(|((a, b), s)| s * a < s * b)
+    };
+    for point in &results.points {
+        let dominated = results
+            .points
+            .iter()
+            .any(|other| dominates(&other.objectives, &point.objectives));
+        assert_eq!(point.pareto_optimal, !dominated);
+        if dominated {
+            // Dominance is transitive, so a dominated point is dominated by the front
+            assert!(front
+                .iter()
+                .any(|opt| dominates(&opt.objectives, &point.objectives)));
+        }
+    }
+
+    // The cheapest transfer of the 2020 opportunity is on the front
+    let cheapest = results.best("Delta-V (km/s)").unwrap();
+    println!("cheapest: {cheapest:?}");
+    assert!(cheapest.pareto_optimal);
+    assert!(cheapest.objectives[0] > 3.3 && cheapest.objectives[0] < 4.5);
+    // And it uses the lightest engine, which is then the heaviest delivered mass
+    let heaviest = results.best("Delivered mass (kg)").unwrap();
+    assert_eq!(heaviest.values[..2], cheapest.values[..2]);
+    assert_eq!(heaviest.values[2], 50.0);
+
+    // Exported for plotting
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "earth_mars_trade_study.parquet",
+    ]
+    .iter()
+    .collect();
+    let path = results.to_parquet(path, ExportCfg::default()).unwrap();
+    let columns = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+        .unwrap()
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect::<Vec<String>>();
+    assert_eq!(
+        columns,
+        vec![
+            "Launch epoch (UTC)",
+            "TOF (days)",
+            "Thrust (N)",
+            "Delta-V (km/s)",
+            "Time of flight (days)",
+            "Delivered mass (kg)",
+            "Burn duration (min)",
+            "Pareto optimal",
+            "Error"
+        ]
+    );
+}
+
+#[test]
+fn trade_study_infeasible_points() {
+    let study = TradeStudy::new(
+        vec![
+            DesignVariable::linspace("x", -1.0, 1.0, 21),
+            DesignVariable::linspace("y", 0.0, 1.0, 11),
+        ],
+        vec![
+            TradeObjective::minimize("f1"),
+            TradeObjective::minimize("f2"),
+        ],
+    )
+    .unwrap();
+
+    // Convex front f2 = (1 - sqrt(f1))^2 for y = 0, and the negative values of x cannot be evaluated
+    let results = study.run(|point| {
+        let x = point.value("x")?;
+        let y = point.value("y")?;
+        if x < 0.0 {
+            return Err(NyxError::CustomError("x must be positive".to_string()));
+        } else if x > 0.95 {
+            return Ok(vec![f64::NAN, 0.0]);
+        }
+        Ok(vec![x * x + y, (1.0 - x).powi(2) + y])
+    });
+
+    assert_eq!(results.num_feasible(), 10 * 11);
+    let front = results.pareto_front();
+    assert_eq!(front.len(), 10);
+    for point in front {
+        assert!(point.is_feasible());
+        assert_eq!(point.values[1], 0.0);
+    }
+    for point in results.points.iter().filter(|point| !point.is_feasible()) {
+        assert!(!point.pareto_optimal);
+        assert!(point.objectives.is_empty());
+        assert!(point.error.is_some());
+    }
+
+    // Unknown design variables are errors of the evaluation
+    let results = study.run(|point| Ok(vec![point.value("z")?, 0.0]));
+    assert_eq!(results.num_feasible(), 0);
+    assert!(results.pareto_front().is_empty());
+
+    // As is the wrong number of objectives
+    let results = study.run(|_| Ok(vec![0.0]));
+    assert_eq!(results.num_feasible(), 0);
+
+    // Variables must be uniquely named and have values
+    assert!(TradeStudy::new(
+        vec![
+            DesignVariable::linspace("x", 0.0, 1.0, 2),
+            DesignVariable::linspace("x", 0.0, 1.0, 2),
+        ],
+        vec![TradeObjective::minimize("f")],
+    )
+    .is_err());
+    assert!(TradeStudy::new(
+        vec![DesignVariable::from_values("x", Vec::new())],
+        vec![TradeObjective::minimize("f")],
+    )
+    .is_err());
+}
@@ -0,0 +1,196 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Frame, Orbit, Spacecraft};
+use nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
+use nyx::io::ExportCfg;
+use nyx::linalg::Vector3;
+use nyx::mc::*;
+use nyx::propagators::{PropOpts, Propagator, RK4Fixed};
+use nyx::time::{Epoch, TimeUnits};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs::File;
+use std::path::PathBuf;
+
+#[test]
+fn cubesat_deployment_dispersions() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let epoch = Epoch::from_gregorian_utc_at_noon(2023, 3, 1);
+    let orbit = Orbit::keplerian(6878.0, 1e-4, 97.4, 30.0, 0.0, 0.0, epoch, eme2k);
+    let carrier = Spacecraft::new(orbit, 1000.0, 0.0, 5.0, 5.0, 1.5, 2.2);
+    let cubesat = Spacecraft::new(orbit, 4.0, 0.0, 0.03, 0.03, 1.5, 2.2);
+
+    let deployment = DeploymentDispersion::new(
+        vec![
+            // Four cubesats ejected backward every thirty seconds
+            Deployer::new("P-POD A", cubesat, 4, -Vector3::x(), Frame::VNC, 1.0)
+                .with_dispersions(0.05, 2.0)
+                .with_sequence(1.minutes(), 30.seconds()),
+            // And three toward the zenith ten minutes later
+            Deployer::new("P-POD B", cubesat, 3, Vector3::x(), Frame::RIC, 1.5)
+                .with_dispersions(0.1, 5.0)
+                .with_sequence(10.minutes(), 1.minutes()),
+        ],
+        0,
+    )
+    .unwrap();
+    assert_eq!(deployment.num_satellites(), 7);
+
+    let setup = Propagator::new::<RK4Fixed>(
+        SpacecraftDynamics::new(OrbitalDynamics::two_body()),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+
+    let num_trials = 20;
+    let results = deployment
+        .run(&setup, carrier, 1.days(), 1.minutes(), num_trials)
+        .unwrap();
+    println!("{results}");
+    assert_eq!(results.trials.len(), num_trials);
+    assert_eq!(results.epochs.first(), Some(&(epoch + 2.minutes())));
+    assert_eq!(results.epochs.last(), Some(&(epoch + 1.days())));
+
+    // The separations are dispersed about their nominal
+    let (_, carrier_traj) = setup
+        .with(carrier)
+        .until_epoch_with_traj(epoch + 1.days())
+        .unwrap();
+    for trial in &results.trials {
+        assert_eq!(trial.index, results.trials[trial.index].index);
+        assert_eq!(trial.satellites.len(), 7);
+        assert_eq!(trial.satellites[0].name, "P-POD A-1");
+        assert_eq!(trial.satellites[6].name, "P-POD B-3");
+        assert_eq!(trial.satellites[3].epoch, epoch + 2.5.minutes());
+        assert_eq!(trial.satellites[4].epoch, epoch + 10.minutes());
+        // Every pair of objects, including the carrier, has a closest approach
+        assert_eq!(trial.approaches.len(), 8 * 7 / 2);
+        assert_eq!(trial.spread.len(), results.epochs.len());
+
+        for satellite in &trial.satellites {
+            let carrier_orbit = carrier_traj.at(satellite.epoch).unwrap().orbit;
+            let (nominal, dv_m_s, dv_sigma, pointing_sigma) = if satellite.deployer == "P-POD A" {
+                (-carrier_orbit.velocity().normalize(), 1.0, 0.05, 2.0_f64)
+            } else {
+                (carrier_orbit.radius().normalize(), 1.5, 0.1, 5.0_f64)
+            };
+            let dv_m_s_actual = satellite.dv_km_s.norm() * 1e3;
+            assert!((dv_m_s_actual - dv_m_s).abs() < 5.0 * dv_sigma);
+            let angle_deg = satellite
+                .dv_km_s
+                .normalize()
+                .dot(&nominal)
+                .acos()
+                .to_degrees();
+            assert!(angle_deg < 5.0 * pointing_sigma);
+            assert_eq!(satellite.state.dry_mass_kg, 4.0);
+            assert_eq!(satellite.state.orbit.radius(), carrier_orbit.radius());
+        }
+    }
+
+    // The trials are reproducible from the seed
+    let (_, carrier_traj) = setup
+        .with(carrier)
+        .until_epoch_with_traj(epoch + 1.hours())
+        .unwrap();
+    assert_eq!(
+        deployment.generate(&carrier_traj, 3).unwrap(),
+        results.trials[3].satellites
+    );
+    assert_ne!(
+        deployment.generate(&carrier_traj, 4).unwrap(),
+        results.trials[3].satellites
+    );
+
+    // The backward ejections lower the orbits, so the cluster drifts apart in-track
+    let last = results.trials[0].spread.last().unwrap();
+    println!("{last:?}");
+    assert_eq!(last.num_deployed, 7);
+    assert!(last.rms_ric_km[1] > last.rms_ric_km[0]);
+    assert!(last.rms_ric_km[1] > last.rms_ric_km[2]);
+    let median_range = results.range_percentile(50.0);
+    let upper_range = results.range_percentile(95.0);
+    assert_eq!(median_range.len(), results.epochs.len());
+    assert!(median_range.last().unwrap() > &(100.0 * median_range[0]));
+    for (median, upper) in median_range.iter().zip(&upper_range) {
+        assert!(median <= upper);
+    }
+
+    // The collision risk increases with the keep-out distance
+    let closest = results.closest_approach().unwrap();
+    println!("{closest:?}");
+    assert!(closest.distance_km > 0.0);
+    assert_eq!(results.collision_probability(closest.distance_km), 0.0);
+    assert!(results.collision_probability(1.0) <= results.collision_probability(10.0));
+    assert_eq!(results.collision_probability(1e4), 1.0);
+    assert!(results.separation_percentile(5.0)[0] < 0.5);
+    assert_eq!(
+        results.pair_collision_probability(CARRIER_NAME, "P-POD A-1", 1e4),
+        1.0
+    );
+    assert_eq!(
+        results.pair_collision_probability("P-POD A-1", "Unknown", 1e4),
+        0.0
+    );
+
+    // Exported for plotting
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "cubesat_deployment.parquet",
+    ]
+    .iter()
+    .collect();
+    let path = results.to_parquet(path, ExportCfg::default()).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap()).unwrap();
+    assert_eq!(
+        reader.metadata().file_metadata().num_rows() as usize,
+        num_trials * results.epochs.len()
+    );
+    assert_eq!(reader.schema().fields().len(), 9);
+}
+
+#[test]
+fn deployment_invalid_configurations() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let epoch = Epoch::from_gregorian_utc_at_noon(2023, 3, 1);
+    let orbit = Orbit::keplerian(6878.0, 1e-4, 97.4, 30.0, 0.0, 0.0, epoch, eme2k);
+    let cubesat = Spacecraft::new(orbit, 4.0, 0.0, 0.03, 0.03, 1.5, 2.2);
+    let deployer = Deployer::new("P-POD", cubesat, 2, Vector3::x(), Frame::VNC, 1.0);
+
+    assert!(DeploymentDispersion::new(Vec::new(), 0).is_err());
+    assert!(DeploymentDispersion::new(vec![deployer.clone(), deployer.clone()], 0).is_err());
+    let mut empty = deployer.clone();
+    empty.num_satellites = 0;
+    assert!(DeploymentDispersion::new(vec![empty], 0).is_err());
+    let mut no_direction = deployer.clone();
+    no_direction.direction = Vector3::zeros();
+    assert!(DeploymentDispersion::new(vec![no_direction], 0).is_err());
+    assert!(
+        DeploymentDispersion::new(vec![deployer.clone().with_dispersions(-1.0, 0.0)], 0).is_err()
+    );
+
+    // The satellites must be ejected before the end of the analysis, sampled at a positive step
+    let setup = Propagator::new::<RK4Fixed>(
+        SpacecraftDynamics::new(OrbitalDynamics::two_body()),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+    let late =
+        DeploymentDispersion::new(vec![deployer.with_sequence(50.minutes(), 15.minutes())], 0)
+            .unwrap();
+    assert!(late
+        .run(&setup, cubesat, 1.hours(), 1.minutes(), 1)
+        .is_err());
+    assert!(late
+        .run(&setup, cubesat, 2.hours(), 0.minutes(), 1)
+        .is_err());
+    assert_eq!(
+        late.run(&setup, cubesat, 2.hours(), 1.minutes(), 2)
+            .unwrap()
+            .trials
+            .len(),
+        2
+    );
+}
@@ -71,3 +71,218 @@ fn test_monte_carlo_epoch() {
     println!("Average final SMA = {} km", average_final_sma);
     println!("Average SMA = {} km", average_sma);
 }
+
+#[test]
+fn test_monte_carlo_envelopes() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_utc_at_midnight(2021, 1, 31);
+    let state = Orbit::keplerian(7_000.0, 0.01, 51.6, 306.614, 314.19, 99.887_7, dt, eme2k);
+
+    let generator = GaussianGenerator::from_std_dev_prcts(
+        state,
+        &[
+            (StateParameter::SMA, 0.01),
+            (StateParameter::Eccentricity, 0.1),
+        ],
+    )
+    .unwrap();
+
+    let prop = Propagator::default(OrbitalDynamics::two_body());
+
+    let my_mc = MonteCarlo {
+        generator,
+        seed: 0,
+        scenario: "test_monte_carlo_envelopes".to_string(),
+    };
+
+    let num_runs = 50;
+    let rslts = my_mc.run_until_epoch(prop, dt + 2 * Unit::Hour, num_runs);
+
+    let envelopes = rslts
+        .percentile_envelopes(
+            &[
+                StateParameter::SMA,
+                StateParameter::Rmag,
+                StateParameter::BdotR,
+            ],
+            &[5.0, 50.0, 95.0],
+            5.minutes(),
+        )
+        .unwrap();
+    println!("{envelopes}");
+
+    assert_eq!(envelopes.epochs.len(), 25);
+    assert_eq!(envelopes.epochs[0], dt);
+    assert_eq!(*envelopes.epochs.last().unwrap(), dt + 2 * Unit::Hour);
+
+    // The SMA is constant in two body dynamics, and the median matches the median of the initial values
+    let mut initial_smas = rslts.first_values_of(StateParameter::SMA, None);
+    initial_smas.sort_by(|a, b| a.total_cmp(b));
+    let median_sma = 0.5 * (initial_smas[24] + initial_smas[25]);
+    let sma_p5 = envelopes.of(StateParameter::SMA, 5.0).unwrap();
+    let sma_p50 = envelopes.of(StateParameter::SMA, 50.0).unwrap();
+    let sma_p95 = envelopes.of(StateParameter::SMA, 95.0).unwrap();
+    for e in 0..envelopes.epochs.len() {
+        assert!(sma_p5[e] < sma_p50[e] && sma_p50[e] < sma_p95[e]);
+        // Within the interpolation error of the trajectories
+        assert!((sma_p50[e] - median_sma).abs() < 1e-4);
+        assert_eq!(envelopes.num_runs[0][e], num_runs);
+    }
+    // About 3.3 sigmas between the 5th and 95th percentiles of 70 km
+    let spread = sma_p95[0] - sma_p5[0];
+    println!("SMA 5-95% spread: {spread:.3} km");
+    assert!(spread > 150.0 && spread < 300.0);
+
+    let rmag_p5 = envelopes.of(StateParameter::Rmag, 5.0).unwrap();
+    let rmag_p95 = envelopes.of(StateParameter::Rmag, 95.0).unwrap();
+    assert!(rmag_p5.iter().zip(rmag_p95).all(|(p5, p95)| p5 < p95));
+
+    // The B-plane is unavailable on elliptical orbits
+    assert!(envelopes.of(StateParameter::BdotR, 50.0).unwrap()[0].is_nan());
+    assert_eq!(envelopes.num_runs[2][0], 0);
+    assert!(envelopes.of(StateParameter::BdotT, 50.0).is_err());
+    assert!(envelopes.of(StateParameter::SMA, 99.0).is_err());
+
+    let path: std::path::PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "mc_envelopes.parquet",
+    ]
+    .iter()
+    .collect();
+    envelopes.to_parquet(path, ExportCfg::default()).unwrap();
+}
+
+#[test]
+fn test_monte_carlo_stochastic() {
+    use nyx::dynamics::{RandomWalk, StochasticAccel, StochasticDynamics};
+    use nyx::od::noise::GaussMarkov;
+
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_utc_at_midnight(2021, 1, 31);
+    let state = Orbit::keplerian(7_000.0, 0.01, 51.6, 306.614, 314.19, 99.887_7, dt, eme2k);
+    let end_dt = dt + 6 * Unit::Hour;
+
+    // Unmodeled accelerations of about one micrometer per second squared, correlated over ten minutes, in the RIC frame
+    let fogm = Arc::new(GaussMarkov::new(10 * Unit::Minute, 1e-9, 1e-9).unwrap());
+    let accel = StochasticAccel::isotropic(fogm, Frame::RIC, 1 * Unit::Minute, 0).unwrap();
+    println!("{accel}");
+
+    // The realization is piecewise constant and reproducible
+    let a0 = accel.acceleration(dt);
+    assert_eq!(accel.acceleration(dt + 59 * Unit::Second), a0);
+    let a1 = accel.acceleration(dt + 1 * Unit::Minute);
+    assert_ne!(a1, a0);
+    assert_eq!(accel.clone().acceleration(dt), a0);
+    // The realization starts at the first evaluation
+    let replay = accel.with_seed(0);
+    assert_eq!(replay.acceleration(dt), a0);
+    assert_eq!(replay.acceleration(dt + 1 * Unit::Minute), a1);
+    assert_ne!(accel.with_seed(1).acceleration(dt), a0);
+    // Before the start, the intervals differ from their mirror after the start, regardless of the evaluation order
+    let before = accel.acceleration(dt - 1 * Unit::Second);
+    assert_eq!(accel.acceleration(dt - 1 * Unit::Minute), before);
+    assert_ne!(before, a0);
+    assert_ne!(accel.acceleration(dt - 61 * Unit::Second), a1);
+    let reversed = accel.with_seed(0);
+    assert_eq!(reversed.acceleration(dt), a0);
+    let earlier = reversed.acceleration(dt - 2 * Unit::Minute);
+    assert_eq!(reversed.acceleration(dt - 1 * Unit::Second), before);
+    assert_eq!(accel.acceleration(dt - 2 * Unit::Minute), earlier);
+    assert!(StochasticAccel::new(
+        [
+            Arc::new(RandomWalk::new(1e-9)),
+            Arc::new(RandomWalk::new(1e-9)),
+            Arc::new(RandomWalk::new(1e-9)),
+        ],
+        Frame::Inertial,
+        Unit::Minute * 0,
+        0
+    )
+    .is_err());
+
+    // Without any noise, the stochastic dynamics match the deterministic ones
+    let opts = PropOpts::with_fixed_step(30 * Unit::Second);
+    let (_, nominal) = Propagator::rk89(OrbitalDynamics::two_body(), opts)
+        .with(state)
+        .until_epoch_with_traj(end_dt)
+        .unwrap();
+    let zero =
+        StochasticAccel::isotropic(Arc::new(GaussMarkov::ZERO), Frame::RIC, 1 * Unit::Minute, 0)
+            .unwrap();
+    let quiet = Propagator::rk89(
+        StochasticDynamics::new(OrbitalDynamics::two_body(), zero),
+        opts,
+    )
+    .with(state)
+    .until_epoch(end_dt)
+    .unwrap();
+    assert_eq!(quiet, *nominal.last());
+
+    // Disperse the initial state by a few decimeters, and let the stochastic accelerations grow the uncertainty
+    let generator = || GaussianGenerator::from_std_dev(state, StateParameter::SMA, 1e-4).unwrap();
+    let my_mc = MonteCarlo {
+        generator: generator(),
+        seed: 0,
+        scenario: "test_monte_carlo_stochastic".to_string(),
+    };
+
+    let prop = Propagator::rk89(
+        StochasticDynamics::new(OrbitalDynamics::two_body(), accel),
+        opts,
+    );
+    let num_runs = 20;
+    let rslts = my_mc.run_stochastic_until_epoch(prop, end_dt, num_runs);
+
+    // Same dispersed states without the stochastic accelerations
+    let deterministic = MonteCarlo {
+        generator: generator(),
+        seed: 0,
+        scenario: "test_monte_carlo_deterministic".to_string(),
+    }
+    .run_until_epoch(
+        Propagator::rk89(OrbitalDynamics::two_body(), opts),
+        end_dt,
+        num_runs,
+    );
+
+    let stochastic_trajs = rslts
+        .runs
+        .iter()
+        .map(|run| &run.result.as_ref().unwrap().traj)
+        .collect::<Vec<&Traj<Orbit>>>();
+    let deterministic_trajs = deterministic
+        .runs
+        .iter()
+        .map(|run| &run.result.as_ref().unwrap().traj)
+        .collect::<Vec<&Traj<Orbit>>>();
+
+    let rss_to_nominal = |trajs: &[&Traj<Orbit>], epoch: Epoch| {
+        let nominal = nominal.at(epoch).unwrap();
+        trajs
+            .iter()
+            .map(|traj| traj.at(epoch).unwrap().rss(&nominal).0)
+            .collect::<Vec<f64>>()
+    };
+
+    let early = rss_to_nominal(&stochastic_trajs, dt + 10 * Unit::Minute);
+    let late = rss_to_nominal(&stochastic_trajs, end_dt);
+    let late_deterministic = rss_to_nominal(&deterministic_trajs, end_dt);
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    println!(
+        "Mean position difference to the nominal: {:.3} m after 10 min, {:.3} m after 6 h ({:.3} m without stochastic accelerations)",
+        mean(&early) * 1e3,
+        mean(&late) * 1e3,
+        mean(&late_deterministic) * 1e3
+    );
+    assert!(mean(&late) > 10.0 * mean(&early));
+    assert!(mean(&late) > 2.0 * mean(&late_deterministic));
+    // Each run has its own realization
+    assert!(late.windows(2).all(|pair| pair[0] != pair[1]));
+}
@@ -1,2 +1,5 @@
+mod deployment;
+#[cfg(feature = "parallel")]
 mod framework;
 mod manual_montecarlo;
+mod reentry;
@@ -0,0 +1,185 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit, Spacecraft};
+use nyx::dynamics::{ForceModel, OrbitalDynamics, SpacecraftDynamics};
+use nyx::linalg::{Matrix3, Vector3};
+use nyx::mc::*;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, TimeUnits};
+use nyx::NyxError;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Exponential atmosphere from sea level co-rotating with the Earth, with a single scale height, which is valid down to
+/// the ground unlike the density models for orbit lifetime analyses
+struct EntryAtmosphere;
+
+impl fmt::Display for EntryAtmosphere {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "entry atmosphere")
+    }
+}
+
+impl ForceModel for EntryAtmosphere {
+    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, NyxError> {
+        let altitude_km = ctx.orbit.rmag_km() - ctx.orbit.frame.equatorial_radius();
+        let rho = 1.225 * (-altitude_km / 7.2).exp();
+        let wind = Vector3::new(0.0, 0.0, 7.292_115e-5).cross(&ctx.orbit.radius());
+        let v_rel = ctx.orbit.velocity() - wind;
+        // Velocities in km/s, hence the factor to get a force in kg km/s^2
+        Ok(-0.5 * rho * ctx.drag.cd * ctx.drag.area_m2 * v_rel.norm() * v_rel * 1e3)
+    }
+
+    fn dual_eom(&self, _osc_ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), NyxError> {
+        Err(NyxError::PartialsUndefined)
+    }
+}
+
+#[test]
+fn reentry_footprint() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let prop = Propagator::default(SpacecraftDynamics::from_model(
+        OrbitalDynamics::two_body(),
+        Arc::new(EntryAtmosphere),
+    ));
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 6, 1);
+    let orbit = Orbit::keplerian(6378.0 + 130.0, 1e-3, 51.6, 20.0, 0.0, 0.0, epoch, eme2k);
+    let state = Spacecraft::new(orbit, 500.0, 0.0, 4.0, 4.0, 1.5, 2.2);
+
+    let fragments = vec![
+        Fragment::new("Tank", 50.0, 1.0, 1.0),
+        Fragment::new("Reaction wheel", 10.0, 0.1, 1.0),
+        Fragment::new("Panel", 5.0, 2.0, 2.0),
+    ];
+    let estimator = EntryFootprint::new(fragments.clone(), iau_earth, cosm.clone())
+        .unwrap()
+        .with_max_duration(2.days())
+        .with_seed(42);
+
+    let num_runs = 30;
+    let results = estimator.run(&prop, state, num_runs);
+    println!("{results}");
+    assert_eq!(results.runs.len(), num_runs);
+    assert_eq!(results.num_complete(), num_runs);
+
+    for run in &results.runs {
+        assert!(run.drag_scale > 0.0);
+        let breakup = cosm.frame_chg(&run.breakup.unwrap(), iau_earth);
+        assert!((breakup.geodetic_height_km() - run.breakup_altitude_km).abs() < 1e-2);
+        assert_eq!(run.impacts.len(), 3);
+        for (impact, fragment) in run.impacts.iter().zip(&fragments) {
+            assert_eq!(impact.fragment, fragment.name);
+            assert!(impact.epoch > breakup.epoch);
+            // The fragments reached their terminal velocity
+            assert!(impact.speed_km_s < 0.5);
+            assert!(impact.longitude_deg.abs() <= 180.0);
+        }
+    }
+
+    // The impact ellipses contain the impacts with roughly their probability
+    for fragment in &fragments {
+        let ellipse = results.ellipse(&fragment.name, 0.99).unwrap();
+        let median = results.ellipse(&fragment.name, 0.5).unwrap();
+        println!("{ellipse}");
+        assert_eq!(ellipse.num_impacts, num_runs);
+        assert!(ellipse.semi_major_km > median.semi_major_km);
+        assert!(ellipse.semi_major_km >= ellipse.semi_minor_km);
+        assert!((0.0..180.0).contains(&ellipse.azimuth_deg));
+        let impacts = results.impacts_of(&fragment.name);
+        let num_inside = impacts
+            .iter()
+            .filter(|impact| ellipse.contains(impact.latitude_deg, impact.longitude_deg))
+            .count();
+        assert!(num_inside >= num_runs - 2);
+        assert!(ellipse.contains(ellipse.center_latitude_deg, ellipse.center_longitude_deg));
+        assert!(!ellipse.contains(
+            -ellipse.center_latitude_deg,
+            ellipse.center_longitude_deg + 180.0
+        ));
+
+        // The boundary is a closed ring around the center
+        let boundary = ellipse.boundary(36);
+        assert_eq!(boundary.len(), 37);
+        assert_eq!(boundary.first(), boundary.last());
+    }
+
+    // The dispersions of the drag spread the footprint along the ground track
+    let footprint = results.footprint(0.99).unwrap();
+    println!("{footprint}");
+    assert_eq!(footprint.num_impacts, 3 * num_runs);
+    assert!(footprint.semi_major_km > 3.0 * footprint.semi_minor_km);
+
+    // Casualty area of the surviving fragments
+    let expected_area_m2 = (0.6_f64 + 1.0).powi(2)
+        + (0.6_f64 + 0.1_f64.sqrt()).powi(2)
+        + (0.6_f64 + 2.0_f64.sqrt()).powi(2);
+    assert!((results.casualty_area_m2() - expected_area_m2).abs() < 1e-12);
+    assert!((results.expected_casualties(100.0) - expected_area_m2 * 1e-4).abs() < 1e-12);
+
+    // The runs are reproducible from the seed
+    let again = estimator.run(&prop, state, 2);
+    assert_eq!(again.runs[..], results.runs[..2]);
+    let other = estimator.clone().with_seed(7).run(&prop, state, 2);
+    assert_ne!(
+        other.runs[0].breakup_altitude_km,
+        results.runs[0].breakup_altitude_km
+    );
+
+    // Exported for plotting
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "reentry_footprint.geojson",
+    ]
+    .iter()
+    .collect();
+    let path = results.to_geojson(path, 0.99).unwrap();
+    let geojson = std::fs::read_to_string(path).unwrap();
+    assert!(geojson.starts_with("{\"type\":\"FeatureCollection\""));
+    assert_eq!(geojson.matches("\"Polygon\"").count(), 4);
+    assert_eq!(geojson.matches("\"Point\"").count(), 3 * num_runs);
+}
+
+#[test]
+fn reentry_invalid_configurations() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let tank = Fragment::new("Tank", 50.0, 1.0, 1.0);
+
+    assert!(EntryFootprint::new(Vec::new(), iau_earth, cosm.clone()).is_err());
+    assert!(EntryFootprint::new(vec![tank.clone()], eme2k, cosm.clone()).is_err());
+    assert!(
+        EntryFootprint::new(vec![tank.clone(), tank.clone()], iau_earth, cosm.clone()).is_err()
+    );
+    assert!(EntryFootprint::new(
+        vec![Fragment::new("Massless", 0.0, 1.0, 1.0)],
+        iau_earth,
+        cosm.clone()
+    )
+    .is_err());
+
+    // Without drag, the object never decays and the runs fail without stopping the estimation
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 6, 1);
+    let orbit = Orbit::keplerian(7000.0, 1e-3, 51.6, 20.0, 0.0, 0.0, epoch, eme2k);
+    let state = Spacecraft::new(orbit, 500.0, 0.0, 4.0, 4.0, 1.5, 2.2);
+    let results = EntryFootprint::new(vec![tank], iau_earth, cosm)
+        .unwrap()
+        .with_max_duration(3.hours())
+        .run(
+            &Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body())),
+            state,
+            3,
+        );
+    assert_eq!(results.num_complete(), 0);
+    assert!(results
+        .runs
+        .iter()
+        .all(|run| run.error.is_some() && run.breakup.is_none()));
+    assert!(results.footprint(0.99).is_err());
+}
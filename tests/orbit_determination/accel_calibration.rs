@@ -0,0 +1,92 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Frame, Orbit, Spacecraft};
+use nyx::dynamics::{
+    Accelerometer, ForceProfile, OrbitalDynamics, ProfileKind, SpacecraftDynamics,
+};
+use nyx::linalg::Vector3;
+use nyx::od::AccelCalibrationEstimator;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, Unit};
+
+#[test]
+fn accelerometer_calibration() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian_altitude(400.0, 1e-3, 87.0, 30.0, 0.0, 0.0, epoch, eme2k);
+    let sc = Spacecraft::new(orbit, 500.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+
+    // True non-gravitational accelerations in the VNC frame, e.g. drag and a slowly varying thrust, in km/s^2
+    let duration = 6 * Unit::Hour;
+    let period_s = orbit.period().to_seconds();
+    let mut truth_samples = Vec::new();
+    let mut t = epoch;
+    while t <= epoch + duration {
+        let phase = std::f64::consts::TAU * (t - epoch).to_seconds() / period_s;
+        truth_samples.push((
+            t,
+            Vector3::new(
+                -2e-9 * (1.0 + 0.5 * phase.cos()),
+                4e-9 * phase.sin(),
+                1e-9 * (0.3 + phase.cos()),
+            ),
+        ));
+        t += 30 * Unit::Second;
+    }
+
+    // What the accelerometer measures: true = scale ⊙ measured + bias
+    let true_bias = Vector3::new(3e-10, -2e-10, 1e-10);
+    let true_scale = Vector3::new(1.02, 0.97, 1.05);
+    let measured = truth_samples
+        .iter()
+        .map(|(t, a)| (*t, (a - true_bias).component_div(&true_scale)))
+        .collect();
+
+    // Reference trajectory and position fixes every five minutes
+    let truth_profile =
+        ForceProfile::from_samples(truth_samples, ProfileKind::Acceleration, Frame::VNC).unwrap();
+    let truth_dyn = SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), truth_profile);
+    let (_, traj) = Propagator::default(truth_dyn)
+        .with(sc)
+        .for_duration_with_traj(duration)
+        .unwrap();
+    let fixes: Vec<Orbit> = traj
+        .every(5 * Unit::Minute)
+        .skip(1)
+        .map(|state| state.orbit)
+        .collect();
+
+    // Start from an uncalibrated accelerometer and an initial state off by a few hundred meters
+    let accel = Accelerometer::from_samples(measured, Frame::VNC).unwrap();
+    let mut guess = sc;
+    guess.orbit.x_km += 0.3;
+    guess.orbit.vy_km_s -= 2e-4;
+
+    let estimator = AccelCalibrationEstimator::new(
+        SpacecraftDynamics::new(OrbitalDynamics::two_body()),
+        accel,
+        1e-3,
+    );
+    let calib = estimator.estimate(guess, &fixes).unwrap();
+    println!("{calib}");
+
+    assert!(calib.iterations < estimator.max_iterations);
+    assert!(calib.rms_residual_km < 1e-5);
+    assert!((calib.initial_state.orbit.radius() - sc.orbit.radius()).norm() < 1e-4);
+    for i in 0..3 {
+        assert!(
+            (calib.accelerometer.bias[i] - true_bias[i]).abs() < 5e-12,
+            "bias axis {i}"
+        );
+        assert!(
+            (calib.accelerometer.scale[i] - true_scale[i]).abs() < 1e-3,
+            "scale axis {i}"
+        );
+    }
+    // The formal uncertainties are dominated by the a priori position sigma
+    assert!(calib.bias_sigma().iter().all(|s| *s > 0.0));
+}
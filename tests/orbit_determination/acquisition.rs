@@ -0,0 +1,148 @@
+use pretty_env_logger::try_init;
+
+use nyx_space::cosmic::{Cosm, Frame, Orbit};
+use nyx_space::dynamics::orbital::OrbitalDynamics;
+use nyx_space::linalg::{Matrix6, Vector3};
+use nyx_space::od::noise::GaussMarkov;
+use nyx_space::od::prelude::*;
+use nyx_space::od::{acquisition_aid, InjectionAxes, InjectionCovariance};
+use nyx_space::propagators::Propagator;
+use nyx_space::time::{Epoch, TimeUnits};
+use std::env;
+
+#[test]
+fn acquisition_aid_cones() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_utc_hms(2023, 11, 16, 14, 32, 0);
+    let injection = Orbit::keplerian(7000.0, 0.001, 51.6, 40.0, 0.0, 30.0, epoch, eme2k);
+    let dispersions = InjectionCovariance::from_std_devs(
+        InjectionAxes::Orbital(Frame::RIC),
+        Vector3::new(1.0, 5.0, 0.5),
+        Vector3::new(1e-3, 2e-3, 5e-4),
+    )
+    .unwrap();
+
+    let stations = vec![
+        GroundStation::dss65_madrid(5.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+        GroundStation::dss34_canberra(5.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+        GroundStation::dss13_goldstone(5.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+    ];
+
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+    let k_sigma = 3.0;
+    let aid = acquisition_aid(
+        setup.with(injection.with_stm()),
+        &dispersions,
+        &stations,
+        6.hours(),
+        1.minutes(),
+        k_sigma,
+        &cosm,
+    )
+    .unwrap();
+
+    assert_eq!(aid.len(), stations.len());
+    for (acquisition, station) in aid.iter().zip(&stations) {
+        println!("{acquisition}");
+        assert_eq!(acquisition.station, station.name);
+        assert_eq!(acquisition.samples.len(), 361);
+        assert_eq!(acquisition.samples[0].epoch, epoch);
+        assert_eq!(acquisition.samples[360].epoch, epoch + 6.hours());
+
+        for sample in &acquisition.samples {
+            // The pointing center is the nominal trajectory as seen from the station
+            assert_eq!(sample.visible, sample.elevation_deg >= 5.0);
+            assert!(sample.range_sigma_km > 0.0 && sample.cone_half_angle_deg > 0.0);
+            // The uncertainty of the angles along any direction fits in the cone
+            let cone_sigma_deg = (sample.cone_half_angle_deg.to_radians().tan() / k_sigma)
+                .to_degrees()
+                * (1.0 + 1e-3);
+            assert!(sample.elevation_sigma_deg <= cone_sigma_deg);
+            if sample.elevation_deg < 80.0 {
+                assert!(
+                    sample.azimuth_sigma_deg * sample.elevation_deg.to_radians().cos()
+                        <= cone_sigma_deg,
+                    "{sample:?}"
+                );
+            }
+        }
+    }
+    let first = aid
+        .iter()
+        .filter_map(|acquisition| acquisition.first_acquisition())
+        .min_by_key(|sample| sample.epoch)
+        .expect("no station acquires the spacecraft");
+    println!("first acquisition: {first:?}");
+    assert!(first.epoch > epoch);
+
+    // The position uncertainty grows along the trajectory
+    let samples = &aid[0].samples;
+    let cross_track_km = |sample: &nyx_space::od::PointingSample| {
+        (sample.cone_half_angle_deg.to_radians()).tan() * sample.range_km / k_sigma
+    };
+    assert!(
+        cross_track_km(&samples[360]).hypot(samples[360].range_sigma_km)
+            > cross_track_km(&samples[0]).hypot(samples[0].range_sigma_km)
+    );
+
+    // Without any dispersion, the cones collapse onto the pointing centers
+    let perfect = InjectionCovariance::new(InjectionAxes::Inertial, Matrix6::zeros()).unwrap();
+    let aid_perfect = acquisition_aid(
+        setup.with(injection.with_stm()),
+        &perfect,
+        &stations[..1],
+        1.hours(),
+        10.minutes(),
+        k_sigma,
+        &cosm,
+    )
+    .unwrap();
+    assert_eq!(aid_perfect[0].samples.len(), 7);
+    for (sample, dispersed) in aid_perfect[0]
+        .samples
+        .iter()
+        .zip(aid[0].samples.iter().step_by(10))
+    {
+        assert_eq!(sample.epoch, dispersed.epoch);
+        assert_eq!(sample.cone_half_angle_deg, 0.0);
+        assert_eq!(sample.range_sigma_km, 0.0);
+        assert!((sample.azimuth_deg - dispersed.azimuth_deg).abs() < 1e-6);
+        assert!((sample.elevation_deg - dispersed.elevation_deg).abs() < 1e-6);
+    }
+
+    // Antenna pointing predicts
+    let path = env::temp_dir().join("acquisition_aid_madrid.csv");
+    aid[0].to_csv(&path, true).unwrap();
+    let mut reader = csv::Reader::from_path(&path).unwrap();
+    let headers = reader.headers().unwrap().clone();
+    assert_eq!(&headers[0], "epoch_utc");
+    assert_eq!(&headers[7], "cone_half_angle_3sigma_deg");
+    assert_eq!(reader.records().count(), aid[0].visible().count());
+
+    // Invalid requests
+    assert!(acquisition_aid(
+        setup.with(injection.with_stm()),
+        &dispersions,
+        &stations,
+        6.hours(),
+        0.minutes(),
+        k_sigma,
+        &cosm,
+    )
+    .is_err());
+    assert!(acquisition_aid(
+        setup.with(injection.with_stm()),
+        &dispersions,
+        &stations,
+        6.hours(),
+        1.minutes(),
+        0.0,
+        &cosm,
+    )
+    .is_err());
+}
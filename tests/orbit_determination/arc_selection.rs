@@ -0,0 +1,177 @@
+use pretty_env_logger::try_init;
+
+use nyx_space::cosmic::{Bodies, Cosm, LightTimeCalc, Orbit};
+use nyx_space::dynamics::orbital::OrbitalDynamics;
+use nyx_space::io::ConfigRepr;
+use nyx_space::od::noise::GaussMarkov;
+use nyx_space::od::prelude::*;
+use nyx_space::od::scenario::{simulate_od_scenario, ODScenario};
+use nyx_space::propagators::Propagator;
+use nyx_space::time::{Epoch, TimeUnits};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[test]
+fn od_media_arc_selection() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2023, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(initial_state)
+        .for_duration_with_traj(1.days())
+        .unwrap();
+
+    // Track down to the horizon
+    let stations = vec![
+        GroundStation::dss65_madrid(
+            0.0,
+            GaussMarkov::high_precision_range_km(),
+            GaussMarkov::high_precision_doppler_km_s(),
+            iau_earth,
+        ),
+        GroundStation::dss34_canberra(
+            0.0,
+            GaussMarkov::high_precision_range_km(),
+            GaussMarkov::high_precision_doppler_km_s(),
+            iau_earth,
+        ),
+    ];
+
+    let mut configs = HashMap::new();
+    for station in &stations {
+        configs.insert(
+            station.name.clone(),
+            TrkConfig {
+                sampling: 10.minutes(),
+                ..Default::default()
+            },
+        );
+    }
+
+    let mut arc_sim =
+        TrackingArcSim::with_seed(stations.clone(), traj.clone(), configs, 0).unwrap();
+    let arc: TrackingArc<RangeDoppler> = arc_sim.generate_measurements(cosm.clone()).unwrap();
+
+    let mut devices = HashMap::new();
+    for station in stations {
+        devices.insert(station.name.clone(), station);
+    }
+
+    // Nothing is edited with permissive thresholds
+    let permissive = MediaEditConfig {
+        min_elevation_deg: -90.0,
+        min_sep_deg: 0.0,
+    };
+    assert!(permissive
+        .analyze(&arc, &devices, &traj, &cosm)
+        .unwrap()
+        .is_empty());
+
+    // Low elevation edits
+    let config = MediaEditConfig {
+        min_elevation_deg: 15.0,
+        min_sep_deg: 0.0,
+    };
+    let edits = config.analyze(&arc, &devices, &traj, &cosm).unwrap();
+    println!("{edits}");
+    assert!(!edits.is_empty());
+    assert!(edits.len() < arc.measurements.len());
+
+    let edited_arc = edits.apply(&arc);
+    assert_eq!(
+        edited_arc.measurements.len() + edits.len(),
+        arc.measurements.len()
+    );
+    for edit in &edits.edits {
+        match edit.reason {
+            EditReason::LowElevation(el) => assert!(el < 15.0),
+            _ => panic!("unexpected edit {edit}"),
+        }
+    }
+    for (name, msr) in &edited_arc.measurements {
+        let rx = traj.at(msr.epoch()).unwrap();
+        let (_, el, _, _) = devices[name].azimuth_elevation_of(rx, &cosm);
+        assert!(el >= 15.0);
+    }
+
+    // Solar conjunction edits: the SEP angle is computed from the station
+    let config = MediaEditConfig {
+        min_elevation_deg: 0.0,
+        min_sep_deg: 90.0,
+    };
+    let edits = config.analyze(&arc, &devices, &traj, &cosm).unwrap();
+    assert!(!edits.is_empty());
+    for edit in &edits.edits {
+        let sep = match edit.reason {
+            EditReason::SolarConjunction(sep) => sep,
+            _ => panic!("unexpected edit {edit}"),
+        };
+        assert!(sep < 90.0);
+        let rx = traj.at(edit.epoch).unwrap();
+        let (_, _, rx, tx) = devices[&edit.device].azimuth_elevation_of(rx, &cosm);
+        let sun = cosm.celestial_state(
+            Bodies::Sun.ephem_path(),
+            edit.epoch,
+            eme2k,
+            LightTimeCalc::None,
+        );
+        let expected = (sun.radius() - tx.radius())
+            .angle(&(rx.radius() - tx.radius()))
+            .to_degrees();
+        assert!((sep - expected).abs() < 1e-9);
+    }
+
+    // Measurements from unknown devices are not analyzed
+    assert!(config
+        .analyze(&arc, &HashMap::new(), &traj, &cosm)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn od_scenario_media_edit() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "data",
+        "tests",
+        "config",
+        "od_scenario.yaml",
+    ]
+    .iter()
+    .collect();
+
+    let mut scenario = ODScenario::load(path).unwrap();
+    scenario.filter.media_edit = Some(MediaEditConfig {
+        min_elevation_deg: 20.0,
+        min_sep_deg: 10.0,
+    });
+
+    let results = simulate_od_scenario(scenario, cosm).unwrap();
+    println!("{}", results.edits);
+    assert!(!results.edits.is_empty());
+    // Only the measurements which were not edited are processed
+    let num_msr_updates = results
+        .residuals
+        .iter()
+        .filter(|resid| resid.is_some())
+        .count();
+    assert_eq!(
+        num_msr_updates,
+        results.arc.measurements.len() - results.edits.len()
+    );
+
+    let (pos_err_km, vel_err_km_s) = results.final_errors().unwrap();
+    println!("final errors: {pos_err_km:.3e} km\t{vel_err_km_s:.3e} km/s");
+    assert!(pos_err_km < 0.1);
+    assert!(vel_err_km_s < 1e-4);
+}
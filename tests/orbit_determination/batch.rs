@@ -0,0 +1,464 @@
+use pretty_env_logger::try_init;
+
+use nyx_space::cosmic::{Cosm, Frame, Orbit, Spacecraft};
+use nyx_space::dynamics::guidance::Thruster;
+use nyx_space::dynamics::orbital::OrbitalDynamics;
+use nyx_space::dynamics::{BurnDirection, FiniteBurn, SolarPressure, SpacecraftDynamics};
+use nyx_space::linalg::{Matrix2, Matrix6, Vector2, Vector3, Vector6};
+use nyx_space::od::noise::GaussMarkov;
+use nyx_space::od::prelude::*;
+use nyx_space::propagators::{PropOpts, Propagator, RK4Fixed};
+use nyx_space::time::{Epoch, TimeUnits, Unit};
+use std::collections::HashMap;
+
+#[test]
+fn od_batch_least_squares() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2023, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(initial_state)
+        .for_duration_with_traj(12.hours())
+        .unwrap();
+
+    let stations = vec![
+        GroundStation::dss65_madrid(
+            0.0,
+            GaussMarkov::high_precision_range_km(),
+            GaussMarkov::high_precision_doppler_km_s(),
+            iau_earth,
+        ),
+        GroundStation::dss34_canberra(
+            0.0,
+            GaussMarkov::high_precision_range_km(),
+            GaussMarkov::high_precision_doppler_km_s(),
+            iau_earth,
+        ),
+    ];
+
+    let mut configs = HashMap::new();
+    for station in &stations {
+        configs.insert(
+            station.name.clone(),
+            TrkConfig {
+                sampling: 5.minutes(),
+                ..Default::default()
+            },
+        );
+    }
+
+    let mut arc_sim = TrackingArcSim::with_seed(stations.clone(), traj, configs, 0).unwrap();
+    let arc: TrackingArc<RangeDoppler> = arc_sim.generate_measurements(cosm.clone()).unwrap();
+    assert!(arc.measurements.len() > 50);
+
+    let mut devices = HashMap::new();
+    for station in stations {
+        devices.insert(station.name.clone(), station);
+    }
+
+    // Initial guess off by a few kilometers and meters per second
+    let mut guess = initial_state.with_stm();
+    guess.x_km += 2.0;
+    guess.y_km -= 1.5;
+    guess.vz_km_s += 1e-3;
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+
+    let noise = Matrix2::from_diagonal(&Vector2::new(5e-3_f64.powi(2), 50e-6_f64.powi(2)));
+
+    let batch =
+        BatchLeastSquares::<_, _, RangeDoppler, Orbit>::new(&setup, guess, noise, cosm.clone());
+    let solution = batch.estimate(&arc.measurements, &mut devices).unwrap();
+    for iteration in &solution.iterations {
+        println!("{iteration}");
+    }
+    println!("{}", solution.state);
+
+    assert!(solution.converged);
+    assert!(solution.iterations.len() >= 2);
+    assert!(solution.iterations[0].weighted_rms > 10.0 * solution.weighted_rms());
+    assert!(solution.weighted_rms() < 3.0);
+    assert_eq!(solution.residuals.len(), arc.measurements.len());
+    assert!(solution.residuals.iter().all(|resid| !resid.rejected));
+
+    let pos_err_km = (solution.state.radius() - initial_state.radius()).norm();
+    let vel_err_km_s = (solution.state.velocity() - initial_state.velocity()).norm();
+    println!("errors: {pos_err_km:.3e} km\t{vel_err_km_s:.3e} km/s");
+    assert!(pos_err_km < 0.05);
+    assert!(vel_err_km_s < 1e-5);
+    // The formal uncertainty is consistent with the error
+    let sigmas = solution.sigmas();
+    assert!(sigmas.iter().all(|sigma| *sigma > 0.0));
+    assert!(pos_err_km < 10.0 * sigmas.fixed_rows::<3>(0).norm());
+
+    // A few outliers are rejected by the measurement editing
+    let mut measurements = arc.measurements.clone();
+    for idx in [5, 20, 35] {
+        measurements[idx].1.obs[0] += 1.0;
+    }
+
+    let polluted = batch.estimate(&measurements, &mut devices).unwrap();
+    let polluted_err_km = (polluted.state.radius() - initial_state.radius()).norm();
+
+    let edited =
+        BatchLeastSquares::<_, _, RangeDoppler, Orbit>::new(&setup, guess, noise, cosm.clone())
+            .with_rejection(3.0)
+            .estimate(&measurements, &mut devices)
+            .unwrap();
+    let edited_err_km = (edited.state.radius() - initial_state.radius()).norm();
+    println!("errors with outliers: {polluted_err_km:.3e} km without editing, {edited_err_km:.3e} km with editing");
+    let rejected = edited
+        .residuals
+        .iter()
+        .enumerate()
+        .filter(|(_, resid)| resid.rejected)
+        .map(|(idx, _)| idx)
+        .collect::<Vec<usize>>();
+    assert_eq!(rejected, vec![5, 20, 35]);
+    assert_eq!(edited.iterations.last().unwrap().num_rejected, 3);
+    assert!(edited_err_km < 0.05);
+    assert!(edited_err_km < polluted_err_km);
+
+    // A tight a priori keeps the solution close to the initial guess
+    let a_priori =
+        BatchLeastSquares::<_, _, RangeDoppler, Orbit>::new(&setup, guess, noise, cosm.clone())
+            .with_a_priori(Matrix6::from_diagonal(&Vector6::new(
+                1e-10, 1e-10, 1e-10, 1e-16, 1e-16, 1e-16,
+            )))
+            .estimate(&arc.measurements, &mut devices)
+            .unwrap();
+    let a_priori_shift_km = (a_priori.state.radius() - guess.radius()).norm();
+    assert!(a_priori_shift_km < 0.1 * (solution.state.radius() - guess.radius()).norm());
+
+    // Invalid measurements
+    assert!(batch.estimate(&[], &mut devices).is_err());
+    let mut reversed = arc.measurements.clone();
+    reversed.reverse();
+    assert!(batch.estimate(&reversed, &mut devices).is_err());
+    let mut unknown = arc.measurements.clone();
+    unknown[0].0 = "Unknown".to_string();
+    assert!(batch.estimate(&unknown, &mut devices).is_err());
+}
+
+#[test]
+fn od_batch_time_tag_bias() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2023, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(initial_state)
+        .for_duration_with_traj(1.days())
+        .unwrap();
+
+    // Precise ranging with white noise only
+    let stations = vec![
+        GroundStation::dss65_madrid(
+            0.0,
+            GaussMarkov::white_noise(1e-4),
+            GaussMarkov::white_noise(1e-6),
+            iau_earth,
+        ),
+        GroundStation::dss34_canberra(
+            0.0,
+            GaussMarkov::white_noise(1e-4),
+            GaussMarkov::white_noise(1e-6),
+            iau_earth,
+        ),
+    ];
+
+    let mut configs = HashMap::new();
+    for station in &stations {
+        configs.insert(
+            station.name.clone(),
+            TrkConfig {
+                sampling: 5.minutes(),
+                ..Default::default()
+            },
+        );
+    }
+
+    let mut arc_sim = TrackingArcSim::with_seed(stations.clone(), traj, configs, 0).unwrap();
+    arc_sim.disallow_overlap();
+    let arc: TrackingArc<RangeDoppler> = arc_sim
+        .generate_measurements(cosm.clone())
+        .unwrap()
+        .filter_by_offset(1.minutes()..);
+
+    // The ground systems tag the measurements with a timing offset: the true epoch is the time tag plus the bias
+    let true_biases = [
+        ("Madrid", 20.milliseconds()),
+        ("Canberra", -5.milliseconds()),
+    ];
+    let mut biased = arc.clone();
+    for (name, msr) in biased.measurements.iter_mut() {
+        let (_, bias) = true_biases
+            .iter()
+            .find(|(station, _)| station == name)
+            .unwrap();
+        msr.epoch -= *bias;
+    }
+
+    let mut devices = HashMap::new();
+    for station in stations {
+        devices.insert(station.name.clone(), station);
+    }
+
+    // Timing calibration against a well known orbit: the a priori is ten meters and one centimeter per second off
+    let mut guess = initial_state.with_stm();
+    guess.x_km += 1e-2;
+    guess.vz_km_s += 1e-5;
+    let a_priori = Matrix6::from_diagonal(&Vector6::new(1e-4, 1e-4, 1e-4, 1e-10, 1e-10, 1e-10));
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+    let noise = Matrix2::from_diagonal(&Vector2::new(1e-4_f64.powi(2), 1e-6_f64.powi(2)));
+
+    // Ignoring the time-tag biases leaves large residuals
+    let ignored =
+        BatchLeastSquares::<_, _, RangeDoppler, Orbit>::new(&setup, guess, noise, cosm.clone())
+            .with_a_priori(a_priori)
+            .estimate(&biased.measurements, &mut devices)
+            .unwrap();
+    assert!(ignored.time_biases.is_empty());
+
+    for config in [
+        TimeBiasConfig::per_device(),
+        TimeBiasConfig::per_pass(1.hours()).with_a_priori(1.seconds()),
+    ] {
+        let solution =
+            BatchLeastSquares::<_, _, RangeDoppler, Orbit>::new(&setup, guess, noise, cosm.clone())
+                .with_a_priori(a_priori)
+                .with_time_bias(config)
+                .estimate(&biased.measurements, &mut devices)
+                .unwrap();
+        for bias in &solution.time_biases {
+            println!("{bias}");
+        }
+        let err_km = (solution.state.radius() - initial_state.radius()).norm();
+        println!(
+            "weighted RMS: {:.3} with time biases, {:.3} without\terror: {err_km:.3e} km",
+            solution.weighted_rms(),
+            ignored.weighted_rms()
+        );
+        assert!(solution.converged);
+        assert!(solution.weighted_rms() < 3.0);
+        assert!(ignored.weighted_rms() > 3.0 * solution.weighted_rms());
+        assert!(err_km < 0.02);
+
+        if config.pass_gap.is_none() {
+            assert_eq!(solution.time_biases.len(), 2);
+        } else {
+            assert!(solution.time_biases.len() > 2);
+        }
+        // The common part of the biases is limited by the knowledge of the orbit along track, but not their difference
+        let mut madrid_minus_canberra = (0.0, 0.0);
+        for bias in &solution.time_biases {
+            let (_, expected) = true_biases
+                .iter()
+                .find(|(station, _)| *station == bias.device)
+                .unwrap();
+            assert!(bias.sigma < 5.milliseconds());
+            assert!((bias.bias - *expected).abs() < 3 * bias.sigma);
+            let ms = bias.bias.to_unit(Unit::Millisecond);
+            if bias.device == "Madrid" {
+                madrid_minus_canberra.0 += ms;
+            } else {
+                madrid_minus_canberra.1 += ms;
+            }
+        }
+        let num_madrid = solution
+            .time_biases
+            .iter()
+            .filter(|bias| bias.device == "Madrid")
+            .count() as f64;
+        let num_canberra = solution.time_biases.len() as f64 - num_madrid;
+        let diff_ms = madrid_minus_canberra.0 / num_madrid - madrid_minus_canberra.1 / num_canberra;
+        assert!(
+            (diff_ms - 25.0).abs() < 0.1,
+            "differential bias of {diff_ms} ms"
+        );
+
+        // Correcting the time tags restores the original epochs
+        let corrected = biased.correct_time_tags(&solution.time_biases);
+        assert_eq!(corrected.measurements.len(), arc.measurements.len());
+        for ((_, fixed), (_, orig)) in corrected.measurements.iter().zip(&arc.measurements) {
+            assert!((fixed.epoch - orig.epoch).abs() < 10.milliseconds());
+        }
+    }
+}
+
+#[test]
+fn od_batch_dynamic_parameters() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2023, 1, 1);
+    let orbit = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+    // Large area to mass ratio for the SRP to be observable over a day
+    let initial_state = Spacecraft::new(orbit, 100.0, 50.0, 10.0, 0.0, 1.5, 2.2);
+
+    // One Newton prograde burn for twenty minutes, whose thruster underperforms by three percent
+    let burn = FiniteBurn::from_thruster(
+        epoch + 6.hours(),
+        20.minutes(),
+        Thruster {
+            thrust_N: 1.0,
+            isp_s: 300.0,
+        },
+        BurnDirection::Fixed {
+            vector: Vector3::x(),
+            frame: Frame::VNC,
+        },
+    )
+    .unwrap();
+    let dynamics = |thrust_scale: f64| {
+        let mut burn = burn.clone();
+        burn.thrust_scale = thrust_scale;
+        SpacecraftDynamics::from_model(
+            OrbitalDynamics::two_body(),
+            SolarPressure::default(eme2k, cosm.clone()),
+        )
+        .with_burns(vec![burn])
+    };
+
+    let truth_setup =
+        Propagator::new::<RK4Fixed>(dynamics(0.97), PropOpts::with_fixed_step(10.seconds()));
+    let (_, traj) = truth_setup
+        .with(initial_state)
+        .for_duration_with_traj(1.days())
+        .unwrap();
+
+    let stations = vec![
+        GroundStation::dss65_madrid(
+            0.0,
+            GaussMarkov::white_noise(1e-3),
+            GaussMarkov::white_noise(1e-6),
+            iau_earth,
+        ),
+        GroundStation::dss34_canberra(
+            0.0,
+            GaussMarkov::white_noise(1e-3),
+            GaussMarkov::white_noise(1e-6),
+            iau_earth,
+        ),
+    ];
+
+    let mut configs = HashMap::new();
+    for station in &stations {
+        configs.insert(
+            station.name.clone(),
+            TrkConfig::from_sample_rate(2.minutes()),
+        );
+    }
+
+    let mut arc_sim = TrackingArcSim::with_seed(stations.clone(), traj, configs, 0).unwrap();
+    arc_sim.disallow_overlap();
+    let arc: TrackingArc<RangeDoppler> = arc_sim.generate_measurements(cosm.clone()).unwrap();
+
+    let mut devices = HashMap::new();
+    for station in stations {
+        devices.insert(station.name.clone(), station);
+    }
+
+    // The nominal dynamics assume the full thrust and the initial guess assumes the wrong Cr
+    let setup = Propagator::new::<RK4Fixed>(dynamics(1.0), PropOpts::with_fixed_step(10.seconds()));
+    let mut guess = initial_state.with_stm();
+    guess.orbit.x_km += 1.0;
+    guess.orbit.vy_km_s -= 1e-4;
+    guess.srp.cr = 1.2;
+
+    let noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-12));
+
+    // Without estimating the dynamic parameters, the mismodeling remains in the residuals
+    let unmodeled =
+        BatchLeastSquares::<_, _, RangeDoppler, Orbit>::new(&setup, guess, noise, cosm.clone())
+            .estimate(&arc.measurements, &mut devices)
+            .unwrap();
+    assert!(unmodeled.parameters.is_empty());
+    assert!(unmodeled.consider_covar.is_none());
+
+    let batch =
+        BatchLeastSquares::<_, _, RangeDoppler, Orbit>::new(&setup, guess, noise, cosm.clone())
+            .with_parameter(DynamicParameter::srp_coefficient(1.2, 0.5))
+            .with_parameter(DynamicParameter::thrust_scale(0, 0.1))
+            .with_parameter(DynamicParameter::pointing_bias(0, 2, 1e-3).considered());
+    let solution = batch.estimate(&arc.measurements, &mut devices).unwrap();
+    for iteration in &solution.iterations {
+        println!("{iteration}");
+    }
+    println!(
+        "weighted RMS: {:.3} without the parameters, {:.3} with them",
+        unmodeled.weighted_rms(),
+        solution.weighted_rms()
+    );
+    assert!(solution.converged);
+    assert!(solution.weighted_rms() < 3.0);
+    assert!(unmodeled.weighted_rms() > 3.0 * solution.weighted_rms());
+
+    // The parameters are reported in order, estimated ones first
+    let names = solution
+        .parameters
+        .iter()
+        .map(|param| param.name.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(
+        names,
+        vec!["Cr", "Thrust scale #0", "Pointing bias #0 axis 2 (rad)"]
+    );
+    let cr = &solution.parameters[0];
+    let scale = &solution.parameters[1];
+    let bias = &solution.parameters[2];
+    println!("{cr}\n{scale}\n{bias}");
+    assert_eq!(cr.mode, ParameterMode::Estimate);
+    assert!((cr.value - 1.5).abs() < 3.0 * cr.sigma);
+    assert!((cr.value - 1.5).abs() < 0.05);
+    assert!(cr.sigma < 0.5);
+    assert!((scale.value - 0.97).abs() < 3.0 * scale.sigma);
+    assert!((scale.value - 0.97).abs() < 5e-3);
+    // Considered parameters keep their a priori
+    assert_eq!(bias.mode, ParameterMode::Consider);
+    assert_eq!(bias.value, 0.0);
+    assert_eq!(bias.sigma, 1e-3);
+
+    let pos_err_km = (solution.state.orbit.radius() - orbit.radius()).norm();
+    println!("position error: {pos_err_km:.3e} km");
+    assert!(pos_err_km < 0.05);
+
+    // The uncertainty of the considered parameters inflates the covariance
+    let consider_covar = solution.consider_covar.unwrap();
+    for i in 0..6 {
+        assert!(consider_covar[(i, i)] >= solution.covar[(i, i)]);
+    }
+    assert!(consider_covar.trace() > solution.covar.trace());
+
+    // Parameters must apply to the dynamics
+    assert!(BatchLeastSquares::<_, _, RangeDoppler, Orbit>::new(
+        &setup,
+        guess,
+        noise,
+        cosm.clone()
+    )
+    .with_parameter(DynamicParameter::thrust_scale(1, 0.1))
+    .estimate(&arc.measurements, &mut devices)
+    .is_err());
+}
@@ -0,0 +1,209 @@
+use nyx_space::io::{ConfigRepr, ExportCfg};
+use nyx_space::md::prelude::*;
+use nyx_space::od::prelude::*;
+use nyx_space::od::simulator::{TrackingArcSim, TrkConfig};
+use nyx_space::time::TimeSeries;
+use rstest::*;
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[fixture]
+fn traj() -> Traj<Orbit> {
+    let cosm = Cosm::de438();
+
+    let orbit = Orbit::keplerian_altitude(
+        500.0,
+        1e-3,
+        30.0,
+        45.0,
+        75.0,
+        23.4,
+        Epoch::from_str("2023-02-22T19:18:17.16 UTC").unwrap(),
+        cosm.frame("EME2000"),
+    );
+
+    let (_, trajectory) = Propagator::default(OrbitalDynamics::two_body())
+        .with(orbit)
+        .for_duration_with_traj(1.5.days())
+        .unwrap();
+
+    trajectory
+}
+
+#[fixture]
+fn devices() -> Vec<GroundStation> {
+    let ground_station_file: PathBuf = [
+        &env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "data",
+        "tests",
+        "config",
+        "many_ground_stations.yaml",
+    ]
+    .iter()
+    .collect();
+
+    let mut devices = GroundStation::load_many(ground_station_file).unwrap();
+    // Canberra tracks two-way, and the demo station only receives the one-way downlink
+    for device in devices.iter_mut() {
+        if device.name == "Canberra" {
+            device.integration_time = Some(60.seconds());
+        }
+    }
+    devices
+}
+
+#[test]
+fn clock_propagation() {
+    let epoch = Epoch::from_str("2023-02-22T19:18:17.16 UTC").unwrap();
+    let clock = SpacecraftClock::new(epoch, 1e-3, 1e-8, 1e-14)
+        .with_sigmas(1e-6, 1e-11, 1e-16)
+        .with_noise(ClockNoise {
+            white_frequency: 1e-22,
+            random_walk_frequency: 1e-30,
+            random_run_frequency: 0.0,
+        });
+
+    // The propagation is consistent with the offset model, forward and backward
+    let later = epoch + 1.days();
+    let propagated = clock.propagate(later);
+    assert_eq!(propagated.bias_s, clock.offset_s(later));
+    assert!((propagated.offset_s(epoch) - clock.bias_s).abs() < 1e-15);
+    assert!((propagated.propagate(epoch).drift_s_s - clock.drift_s_s).abs() < 1e-20);
+
+    // The uncertainty grows with the drift, the aging, and the noise
+    assert!(propagated.bias_sigma_s() > clock.bias_sigma_s());
+    let no_noise = clock.with_noise(ClockNoise::default()).propagate(later);
+    assert!(propagated.bias_sigma_s() > no_noise.bias_sigma_s());
+    let expected =
+        (1e-12_f64 + (1e-11 * 86_400.0_f64).powi(2) + (0.5e-16 * 86_400.0_f64.powi(2)).powi(2))
+            .sqrt();
+    assert!((no_noise.bias_sigma_s() - expected).abs() < 1e-9 * expected);
+}
+
+#[rstest]
+fn clock_along_traj(traj: Traj<Orbit>) {
+    let clock = SpacecraftClock::new(traj.first().epoch, 1e-3, 1e-8, 1e-14);
+    let clocks = clock.along(&traj);
+    assert_eq!(clocks.len(), traj.states.len());
+    for (state, clk) in traj.states.iter().zip(clocks.iter()) {
+        assert_eq!(clk.epoch, state.epoch);
+        assert!((clk.bias_s - clock.offset_s(state.epoch)).abs() < 1e-12);
+    }
+}
+
+#[rstest]
+fn clock_time_correlation(traj: Traj<Orbit>) {
+    let _ = pretty_env_logger::try_init();
+
+    let start = traj.first().epoch;
+    let end = traj.last().epoch;
+    let clock = SpacecraftClock::new(start, 1e-3, 1e-8, 1e-14).with_sigmas(1e-6, 1e-11, 1e-16);
+
+    let correlation = clock.time_correlation(start, end, 6.hours()).unwrap();
+    println!("{correlation}");
+    assert_eq!(correlation.records.len(), 7);
+    assert!(clock.time_correlation(end, start, 6.hours()).is_err());
+
+    // Both conversions are consistent with the clock model, to within the precision of the clock reading
+    for epoch in TimeSeries::inclusive(start, end, 47.minutes()) {
+        let sclk_s = correlation.sclk_s(epoch).unwrap();
+        assert!((sclk_s - clock.sclk_s(epoch)).abs() < 1e-5, "{epoch}");
+        let tdb = correlation.tdb(sclk_s).unwrap();
+        assert!((tdb - epoch).abs() < 1.microseconds(), "{epoch}");
+    }
+    assert!(correlation.tdb(0.0).is_err());
+    assert!(correlation.sclk_s(start - 1.seconds()).is_err());
+
+    // The uncertainty of the records grows
+    assert!(correlation
+        .records
+        .windows(2)
+        .all(|pair| pair[1].offset_sigma_s > pair[0].offset_sigma_s));
+
+    let path: PathBuf = [
+        &env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "output_data",
+        "clock_time_correlation.tsc",
+    ]
+    .iter()
+    .collect();
+
+    let output_fn = correlation
+        .to_sclk_kernel(path, -1001, ExportCfg::default())
+        .unwrap();
+    let kernel = std::fs::read_to_string(output_fn).unwrap();
+    assert!(kernel.starts_with("KPL/SCLK"));
+    assert!(kernel.contains("SCLK01_COEFFICIENTS_1001 = ("));
+    assert!(kernel.contains("SCLK01_TIME_SYSTEM_1001 = ( 1 )"));
+}
+
+#[rstest]
+fn clock_estimation(traj: Traj<Orbit>, devices: Vec<GroundStation>) {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+
+    let trkconfg_yaml: PathBuf = [
+        &env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "data",
+        "tests",
+        "config",
+        "tracking_cfg.yaml",
+    ]
+    .iter()
+    .collect();
+
+    let configs: HashMap<String, TrkConfig> = TrkConfig::load_named(trkconfg_yaml).unwrap();
+
+    let mut trk = TrackingArcSim::<Orbit, RangeDoppler, _>::with_seed(
+        devices.clone(),
+        traj.clone(),
+        configs,
+        12345,
+    )
+    .unwrap();
+
+    let arc = trk.generate_measurements(cosm.clone()).unwrap();
+
+    // The one-way measurements are timed by the spacecraft clock
+    let truth = SpacecraftClock::new(traj.first().epoch, 1e-3, 1e-8, 1e-14);
+    let arc = truth.apply_to_one_way(&arc, &devices);
+
+    let a_priori =
+        SpacecraftClock::new(traj.first().epoch, 0.0, 0.0, 0.0).with_sigmas(1.0, 1e-6, 1e-12);
+
+    // The trajectory would be the result of an orbit determination on the two-way measurements
+    let estimator = ClockEstimator::new(devices, 5e-3, 5e-5, cosm);
+    let solution = estimator.estimate(&a_priori, &traj, &arc).unwrap();
+    println!("{solution}");
+
+    assert!(solution.num_one_way > 0);
+    assert!(solution.num_two_way > 0);
+    // The two-way measurements do not depend on the clock, so the trajectory fits them to the noise level
+    assert!(solution.two_way_range_rms_km.unwrap() < 1e-2);
+    assert!(solution.one_way_range_rms_km < 1e-2);
+    assert!(solution.one_way_doppler_rms_km_s < 1e-4);
+
+    let clock = solution.clock;
+    assert!((clock.bias_s - truth.bias_s).abs() < 1e-7, "{clock}");
+    assert!((clock.drift_s_s - truth.drift_s_s).abs() < 1e-10, "{clock}");
+    assert!(
+        (clock.aging_s_s2 - truth.aging_s_s2).abs() < 0.1 * truth.aging_s_s2,
+        "{clock}"
+    );
+    assert!(clock.bias_sigma_s() < a_priori.bias_sigma_s());
+
+    // Without one-way measurements, the clock cannot be estimated
+    let two_way_only = TrackingArc {
+        device_cfg: arc.device_cfg.clone(),
+        measurements: arc
+            .measurements
+            .iter()
+            .filter(|(name, _)| name == "Canberra")
+            .cloned()
+            .collect(),
+    };
+    assert!(estimator.estimate(&a_priori, &traj, &two_way_only).is_err());
+}
@@ -0,0 +1,162 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{BPlane, Cosm, Frame, Orbit};
+use nyx::linalg::{Matrix3, Matrix6, Vector2, Vector3, Vector6};
+use nyx::od::estimate::{CovarEllipse, CovarEllipsoid};
+use nyx::time::Epoch;
+use rand::SeedableRng;
+use rand_distr::{Distribution, StandardNormal};
+use rand_pcg::Pcg64Mcg;
+use std::path::PathBuf;
+
+#[test]
+fn covar_ellipsoid_geometry() {
+    // Rotate a diagonal covariance by 30 degrees about Z
+    let (sin, cos) = 30.0_f64.to_radians().sin_cos();
+    let rot = Matrix3::new(cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0);
+    let covar = rot * Matrix3::from_diagonal(&Vector3::new(4.0, 1.0, 9.0)) * rot.transpose();
+    let center = Vector3::new(1.0, 2.0, 3.0);
+
+    let ellipsoid = CovarEllipsoid::new(center, covar).unwrap();
+    println!("{ellipsoid}");
+    assert!((ellipsoid.sigmas - Vector3::new(3.0, 2.0, 1.0)).norm() < 1e-12);
+    // The semi-major axis is along Z, the second one is the rotated X axis
+    assert!((ellipsoid.axes.column(0).dot(&Vector3::z()).abs() - 1.0).abs() < 1e-12);
+    assert!((ellipsoid.axes.column(1).dot(&rot.column(0)).abs() - 1.0).abs() < 1e-12);
+
+    // Points on the principal axes lie on the corresponding k-sigma ellipsoid
+    let semi_axes = ellipsoid.semi_axes(2.0);
+    for i in 0..3 {
+        let point = center + semi_axes.column(i);
+        assert!((ellipsoid.mahalanobis(&point) - 2.0).abs() < 1e-12);
+        assert!(ellipsoid.contains(&point, 2.01));
+        assert!(!ellipsoid.contains(&point, 1.99));
+    }
+    for point in ellipsoid.surface(3.0, 10, 12) {
+        assert!((ellipsoid.mahalanobis(&point) - 3.0).abs() < 1e-10);
+    }
+
+    // Chi-squared containment probabilities
+    let probs3 = [0.198_748, 0.738_536, 0.970_709];
+    let probs2 = [0.393_469, 0.864_665, 0.988_891];
+    for k in 1..=3 {
+        let p3 = CovarEllipsoid::containment_probability(k as f64);
+        let p2 = CovarEllipse::containment_probability(k as f64);
+        assert!((p3 - probs3[k - 1]).abs() < 1e-6);
+        assert!((p2 - probs2[k - 1]).abs() < 1e-6);
+        assert!((CovarEllipsoid::sigma_for_probability(p3).unwrap() - k as f64).abs() < 1e-9);
+        assert!((CovarEllipse::sigma_for_probability(p2).unwrap() - k as f64).abs() < 1e-9);
+    }
+    assert!(CovarEllipsoid::sigma_for_probability(1.0).is_err());
+
+    // Monte Carlo matches the analytical containment
+    let mc = ellipsoid.probability(|point| ellipsoid.contains(point, 2.0), 50_000, 0);
+    println!("2-sigma containment: {mc:.4} (expected {:.4})", probs3[1]);
+    assert!((mc - probs3[1]).abs() < 0.01);
+
+    // The projection onto the XY plane is the marginal distribution
+    let ellipse = ellipsoid.project(&Vector3::x(), &Vector3::y()).unwrap();
+    println!("{ellipse}");
+    assert!((ellipse.center - Vector2::new(1.0, 2.0)).norm() < 1e-12);
+    assert!((ellipse.sigmas - Vector2::new(2.0, 1.0)).norm() < 1e-12);
+    assert!((ellipse.angle_deg - 30.0).abs() < 1e-9);
+    for point in ellipse.boundary(3.0, 36) {
+        assert!((ellipse.mahalanobis(&point) - 3.0).abs() < 1e-10);
+    }
+    let mc = ellipse.probability(|point| ellipse.contains(point, 1.0), 50_000, 0);
+    assert!((mc - probs2[0]).abs() < 0.01);
+    assert!(ellipsoid
+        .project(&Vector3::x(), &Vector3::new(1.0, 1.0, 0.0))
+        .is_err());
+
+    // Not positive semi definite
+    assert!(CovarEllipsoid::new(
+        center,
+        Matrix3::from_diagonal(&Vector3::new(1.0, -1.0, 1.0))
+    )
+    .is_err());
+
+    // Export for plotting
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "covar_ellipse.csv",
+    ]
+    .iter()
+    .collect();
+    ellipse.to_csv(path, &[1.0, 2.0, 3.0], 72).unwrap();
+}
+
+#[test]
+fn covar_ellipsoid_ric_bplane() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 1, 1);
+
+    // Position covariance of 10 m radial, 100 m in-track, 30 m cross-track
+    let orbit = Orbit::keplerian(7000.0, 0.01, 45.0, 20.0, 30.0, 40.0, epoch, eme2k);
+    let dcm = orbit.dcm_from_traj_frame(Frame::RIC).unwrap();
+    let ric_covar = Matrix3::from_diagonal(&Vector3::new(1e-4, 1e-2, 9e-4));
+    let mut covar = Matrix6::from_diagonal(&Vector6::new(0.0, 0.0, 0.0, 1e-8, 1e-8, 1e-8));
+    covar
+        .fixed_view_mut::<3, 3>(0, 0)
+        .copy_from(&(dcm * ric_covar * dcm.transpose()));
+
+    let ric = CovarEllipsoid::position_ric(&orbit, &covar).unwrap();
+    println!("RIC {ric}");
+    assert!((ric.sigmas - Vector3::new(0.1, 0.03, 0.01)).norm() < 1e-12);
+    assert!((ric.axes.column(0).dot(&Vector3::y()).abs() - 1.0).abs() < 1e-9);
+
+    let inertial = CovarEllipsoid::position(&orbit, &covar).unwrap();
+    assert_eq!(inertial.center, orbit.radius());
+    assert!((inertial.sigmas - ric.sigmas).norm() < 1e-12);
+    let vel = CovarEllipsoid::velocity(&orbit, &covar).unwrap();
+    assert!((vel.sigmas - Vector3::new(1e-4, 1e-4, 1e-4)).norm() < 1e-12);
+
+    // Linearized B-plane dispersion of a hyperbolic arrival, compared to a Monte Carlo
+    let hyperbola = Orbit::cartesian(
+        546_507.344_255_845,
+        -527_978.380_486_028,
+        531_109.066_836_708,
+        -4.9193,
+        5.0496,
+        -5.0151,
+        epoch,
+        eme2k,
+    );
+    let covar = Matrix6::from_diagonal(&Vector6::new(1.0, 1.0, 1.0, 1e-8, 1e-8, 1e-8));
+    let ellipse = CovarEllipse::bplane(&hyperbola, &covar).unwrap();
+    println!("B-plane {ellipse}");
+    let nominal = BPlane::new(hyperbola).unwrap();
+    assert!((ellipse.center[0] - nominal.b_dot_t()).abs() < 1e-9);
+    assert!((ellipse.center[1] - nominal.b_dot_r()).abs() < 1e-9);
+
+    let mut rng = Pcg64Mcg::seed_from_u64(0);
+    let sqrt_covar = covar.map(f64::sqrt);
+    let num = 2_000;
+    let samples: Vec<Vector2<f64>> = (0..num)
+        .map(|_| {
+            let z = Vector6::from_fn(|_, _| StandardNormal.sample(&mut rng));
+            let delta = sqrt_covar * z;
+            let dispersed = Orbit::cartesian(
+                hyperbola.x_km + delta[0],
+                hyperbola.y_km + delta[1],
+                hyperbola.z_km + delta[2],
+                hyperbola.vx_km_s + delta[3],
+                hyperbola.vy_km_s + delta[4],
+                hyperbola.vz_km_s + delta[5],
+                epoch,
+                eme2k,
+            );
+            let bplane = BPlane::new(dispersed).unwrap();
+            Vector2::new(bplane.b_dot_t(), bplane.b_dot_r())
+        })
+        .collect();
+    let within = samples
+        .iter()
+        .filter(|point| ellipse.contains(point, 2.0))
+        .count() as f64
+        / num as f64;
+    println!("Monte Carlo 2-sigma B-plane containment: {within:.4}");
+    assert!((within - CovarEllipse::containment_probability(2.0)).abs() < 0.03);
+}
@@ -0,0 +1,102 @@
+use pretty_env_logger::try_init;
+
+use nyx_space::cosmic::{Cosm, Orbit};
+use nyx_space::dynamics::orbital::OrbitalDynamics;
+use nyx_space::linalg::{Matrix6, Vector6};
+use nyx_space::od::covariance_map;
+use nyx_space::od::prelude::*;
+use nyx_space::propagators::{PropOpts, Propagator, RK4Fixed};
+use nyx_space::time::{Epoch, TimeUnits};
+use nyx_space::State;
+
+#[test]
+fn covar_map_two_body() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2023, 1, 1);
+    let initial_state =
+        Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k).with_stm();
+
+    let initial_covar = Matrix6::from_diagonal(&Vector6::new(1.0, 1.0, 1.0, 1e-6, 1e-6, 1e-6));
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+
+    let epochs = vec![epoch, epoch + 17.minutes(), epoch + 2.hours()];
+
+    // Without process noise, the mapped covariance is the initial covariance mapped by the STM from the start
+    let mapped = covariance_map(
+        setup.with(initial_state),
+        initial_covar,
+        &epochs,
+        vec![],
+        10.minutes(),
+    )
+    .unwrap();
+    assert_eq!(mapped.len(), epochs.len());
+    assert_eq!(mapped[0].covar, initial_covar);
+
+    for (estimate, epoch) in mapped.iter().zip(&epochs).skip(1) {
+        assert_eq!(estimate.epoch(), *epoch);
+        let nominal = setup.with(initial_state).until_epoch(*epoch).unwrap();
+        assert!((nominal.radius() - estimate.nominal_state.radius()).norm() < 1e-6);
+        let stm = nominal.stm().unwrap();
+        let expected = stm * initial_covar * stm.transpose();
+        assert!(
+            (expected - estimate.covar).norm() <= 1e-6 * expected.norm(),
+            "{}",
+            expected - estimate.covar
+        );
+    }
+    // The position uncertainty grows along the orbit
+    assert!(mapped[2].covar.trace() > mapped[0].covar.trace());
+
+    // With process noise, the covariance grows further
+    let sigma_q = 1e-8_f64.powi(2);
+    let snc = SNC3::from_diagonal(20.minutes(), &[sigma_q, sigma_q, sigma_q]);
+    let mapped_snc = covariance_map(
+        setup.with(initial_state),
+        initial_covar,
+        &epochs,
+        vec![snc],
+        10.minutes(),
+    )
+    .unwrap();
+    for (with_snc, without) in mapped_snc.iter().zip(&mapped).skip(1) {
+        assert!(with_snc.covar.trace() > without.covar.trace());
+        for i in 3..6 {
+            assert!(with_snc.covar[(i, i)] > without.covar[(i, i)]);
+        }
+    }
+
+    // Invalid requests
+    assert!(covariance_map(
+        setup.with(initial_state),
+        initial_covar,
+        &[epoch - 1.seconds()],
+        vec![],
+        10.minutes(),
+    )
+    .is_err());
+    assert!(covariance_map(
+        setup.with(initial_state),
+        initial_covar,
+        &[epoch + 2.hours(), epoch + 1.hours()],
+        vec![],
+        10.minutes(),
+    )
+    .is_err());
+    assert!(covariance_map(
+        setup.with(initial_state),
+        initial_covar,
+        &epochs,
+        vec![],
+        0.seconds(),
+    )
+    .is_err());
+}
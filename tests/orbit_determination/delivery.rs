@@ -0,0 +1,112 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Bodies, Cosm, Orbit, Spacecraft};
+use nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
+use nyx::linalg::{Matrix2, Matrix6, Vector2, Vector6};
+use nyx::od::delivery::DeliveryAnalysis;
+use nyx::od::estimate::CovarEllipse;
+use nyx::od::noise::GaussMarkov;
+use nyx::od::prelude::*;
+use nyx::propagators::Propagator;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[test]
+fn delivery_vs_data_cutoff() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let iau_earth = cosm.frame("IAU Earth");
+    let mars2k = cosm.frame("Mars Barycenter J2000");
+
+    let stations = vec![
+        GroundStation::dss65_madrid(0.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+        GroundStation::dss34_canberra(0.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+        GroundStation::dss13_goldstone(0.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+    ];
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::point_masses(
+        &[Bodies::Sun, Bodies::Earth],
+        cosm.clone(),
+    ));
+
+    // Mars approach with a v-infinity of 3 km/s and a periapsis at 500 km of altitude, started three days before periapsis
+    let periapsis_epoch = Epoch::from_gregorian_utc_at_midnight(2024, 2, 1);
+    let sma_km = -mars2k.gm() / 9.0;
+    let ecc = 1.0 - (3_396.19 + 500.0) / sma_km;
+    let periapsis = Orbit::keplerian(sma_km, ecc, 30.0, 45.0, 60.0, 0.0, periapsis_epoch, mars2k);
+    let setup = Propagator::default(dynamics.clone());
+    let start = setup
+        .with(Spacecraft::new(periapsis, 1000.0, 0.0, 1.0, 10.0, 1.8, 2.2))
+        .for_duration(-3.days())
+        .unwrap();
+
+    // Tracking starts six hours after the initial epoch
+    let mut tracking = HashMap::new();
+    for station in &stations {
+        tracking.insert(
+            station.name.clone(),
+            TrkConfig {
+                start: Availability::Epoch(start.epoch() + 6.hours()),
+                ..TrkConfig::from_sample_rate(10.minutes())
+            },
+        );
+    }
+
+    let covar = Matrix6::from_diagonal(&Vector6::new(1e4, 1e4, 1e4, 1e-6, 1e-6, 1e-6));
+    let encounter = periapsis_epoch - 2.hours();
+    let cutoffs = vec![
+        start.epoch(),
+        start.epoch() + 1.days(),
+        start.epoch() + 2.days(),
+        encounter - 6.hours(),
+    ];
+
+    let analysis = DeliveryAnalysis {
+        name: "Mars approach".to_string(),
+        spacecraft: start,
+        covar,
+        dynamics,
+        stations,
+        tracking,
+        // 10 m in range and 0.1 mm/s in Doppler, as for X-band tracking. The plane of sky information only comes from the
+        // diurnal Doppler signature of the stations, so with 1 mm/s in Doppler the last cutoff only shrinks the semi-major
+        // axis from 285 km to 48 km, i.e. 17% instead of the 10% checked below (it met it while the stations moved at twice
+        // the rotation velocity of the Earth).
+        measurement_noise: Matrix2::from_diagonal(&Vector2::new(1e-4, 1e-14)),
+        sncs: vec![SNC3::from_diagonal(2.hours(), &[1e-24; 3])],
+        encounter,
+        target_frame: mars2k,
+        cutoffs,
+    };
+
+    let curve = analysis.run(cosm).unwrap();
+    println!("{curve}");
+
+    assert_eq!(curve.points.len(), 4);
+    // Without any data, the delivery is the initial covariance mapped to the encounter
+    let first = curve.points[0];
+    assert_eq!(first.num_msrs, 0);
+    let at_encounter = setup.with(start.with_stm()).until_epoch(encounter).unwrap();
+    let stm = at_encounter.orbit.stm().unwrap();
+    let expected =
+        CovarEllipse::bplane(&at_encounter.orbit, &(stm * covar * stm.transpose())).unwrap();
+    assert!((first.ellipse.sigmas - expected.sigmas).norm() < 1e-6 * expected.sigmas.norm());
+
+    // The tracking data shrinks the delivery ellipse
+    for pair in curve.points.windows(2) {
+        assert!(pair[1].num_msrs > pair[0].num_msrs);
+        assert!(pair[1].ellipse.sigmas[0] < pair[0].ellipse.sigmas[0]);
+    }
+    let last = curve.points.last().unwrap();
+    assert!(last.ellipse.sigmas[0] < 0.1 * first.ellipse.sigmas[0]);
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "delivery_vs_cutoff.parquet",
+    ]
+    .iter()
+    .collect();
+    let written = curve.to_parquet(path, Default::default()).unwrap();
+    assert!(written.exists());
+}
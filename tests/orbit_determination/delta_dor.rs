@@ -0,0 +1,229 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::io::ConfigRepr;
+use nyx::linalg::{Matrix1, Matrix6, Vector3, Vector6};
+use nyx::od::noise::GaussMarkov;
+use nyx::od::prelude::*;
+use nyx::od::EstimateFrom;
+use nyx::propagators::Propagator;
+use rand_pcg::Pcg64Mcg;
+use std::collections::HashMap;
+
+/// Returns the right ascension and declination of the provided direction, in degrees
+fn ra_dec_deg(direction: &Vector3<f64>) -> (f64, f64) {
+    (
+        direction[1].atan2(direction[0]).to_degrees(),
+        (direction[2] / direction.norm()).asin().to_degrees(),
+    )
+}
+
+#[test]
+fn delta_dor_model() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let range_noise = GaussMarkov::default_range_km();
+    let doppler_noise = GaussMarkov::default_doppler_km_s();
+    let goldstone = GroundStation::dss13_goldstone(10.0, range_noise, doppler_noise, iau_earth);
+    let madrid = GroundStation::dss65_madrid(10.0, range_noise, doppler_noise, iau_earth);
+
+    // Spacecraft in cruise, about four times further than the Moon
+    let cruise = Orbit::cartesian(1.2e6, 5e5, 3e5, 0.9, 0.35, 0.2, epoch, eme2k);
+    let (ra_deg, dec_deg) = ra_dec_deg(&cruise.radius());
+    let quasars = vec![
+        Quasar::new("Near".to_string(), ra_deg + 3.0, dec_deg - 2.0),
+        Quasar::new("Far".to_string(), ra_deg + 40.0, dec_deg),
+    ];
+
+    let mut baseline = DeltaDorBaseline::new(
+        "Goldstone-Madrid".to_string(),
+        goldstone,
+        madrid,
+        quasars.clone(),
+    );
+    println!("{baseline}");
+
+    // Find an epoch when both stations see the spacecraft
+    let rx = (0..288)
+        .map(|i| {
+            let mut rx = cruise;
+            rx.epoch = epoch + i * 5 * Unit::Minute;
+            rx
+        })
+        .find(|rx| baseline.common_elevation_deg(*rx, &cosm) > 15.0)
+        .expect("no common visibility");
+    assert_eq!(
+        baseline.reference_quasar(rx, &cosm).unwrap().name,
+        "Near".to_string()
+    );
+
+    let msr: DeltaDor = baseline
+        .measure_instantaneous(rx, None, cosm.clone())
+        .unwrap()
+        .unwrap();
+
+    // The quasar referenced differential range, from the geometry of the baseline
+    let tx_first = cosm.frame_chg(&baseline.first_station.to_orbit(rx.epoch), eme2k);
+    let tx_second = cosm.frame_chg(&baseline.second_station.to_orbit(rx.epoch), eme2k);
+    let baseline_km = baseline.baseline_km(rx.epoch, eme2k, &cosm);
+    assert!((baseline_km - (tx_second.radius() - tx_first.radius())).norm() < 1e-9);
+    println!("baseline of {:.3} km", baseline_km.norm());
+    let spacecraft_dor_km =
+        (rx.radius() - tx_first.radius()).norm() - (rx.radius() - tx_second.radius()).norm();
+    let quasar_dor_km = quasars[0].direction().dot(&baseline_km);
+    println!(
+        "differential range {spacecraft_dor_km:.6} km, quasar {quasar_dor_km:.6} km, Delta-DOR {:.6} km",
+        msr.delta_dor_km()
+    );
+    assert!((msr.delta_dor_km() - (spacecraft_dor_km - quasar_dor_km)).abs() < 1e-9);
+    // Close to the quasar, the Delta-DOR is a fraction of the baseline
+    assert!(msr.delta_dor_km().abs() < 0.1 * baseline_km.norm());
+
+    // The partials match finite differences of the measurement
+    let loc = TrackingDeviceSim::<Orbit, DeltaDor>::location(&baseline, rx.epoch, eme2k, &cosm);
+    let h_tilde = <Orbit as EstimateFrom<Orbit, DeltaDor>>::sensitivity(&msr, rx, loc);
+    for i in 0..3 {
+        let mut pert = rx;
+        match i {
+            0 => pert.x_km += 1.0,
+            1 => pert.y_km += 1.0,
+            _ => pert.z_km += 1.0,
+        }
+        let pert_msr: DeltaDor = baseline
+            .measure_instantaneous(pert, None, cosm.clone())
+            .unwrap()
+            .unwrap();
+        let numerical = pert_msr.delta_dor_km() - msr.delta_dor_km();
+        assert!(
+            (h_tilde[(0, i)] - numerical).abs() < 1e-8,
+            "{i}: {} != {numerical}",
+            h_tilde[(0, i)]
+        );
+        assert_eq!(h_tilde[(0, i + 3)], 0.0);
+    }
+
+    // A measurement loaded from its observation gets its partials from the computed measurement
+    let loaded = DeltaDor::from_observation(msr.epoch, msr.observation());
+    assert_ne!(loaded, msr);
+    assert_eq!(loaded.with_geometry_of(&msr), msr);
+
+    // No measurement without any quasar close enough to the spacecraft
+    baseline.max_separation_deg = 1.0;
+    assert!(baseline.reference_quasar(rx, &cosm).is_none());
+    let msr: Option<DeltaDor> = baseline
+        .measure_instantaneous(rx, None, cosm.clone())
+        .unwrap();
+    assert!(msr.is_none());
+    baseline.max_separation_deg = 10.0;
+
+    // The noise must be configured to simulate noisy measurements
+    let mut rng = Pcg64Mcg::new(0);
+    let noisy: Result<Option<DeltaDor>, _> =
+        baseline.measure_instantaneous(rx, Some(&mut rng), cosm.clone());
+    assert!(noisy.is_err());
+
+    // The quasar catalog and the baseline are configurable
+    let catalog = Quasar::loads_many(&serde_yaml::to_string(&quasars).unwrap()).unwrap();
+    assert_eq!(catalog, quasars);
+    let yaml = serde_yaml::to_string(&baseline).unwrap();
+    let rebuilt: DeltaDorBaseline = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(rebuilt, baseline);
+}
+
+#[test]
+fn od_delta_dor_cruise() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let initial_state = Orbit::cartesian(1.2e6, 5e5, 3e5, 0.9, 0.35, 0.2, epoch, eme2k);
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+    let (_, traj) = setup
+        .with(initial_state)
+        .for_duration_with_traj(3 * Unit::Day)
+        .unwrap();
+
+    let (ra_deg, dec_deg) = ra_dec_deg(&traj.last().radius());
+    let quasars = vec![
+        Quasar::new("Q1".to_string(), ra_deg - 2.0, dec_deg + 1.0),
+        Quasar::new("Q2".to_string(), ra_deg + 3.0, dec_deg - 2.0),
+    ];
+
+    let range_noise = GaussMarkov::default_range_km();
+    let doppler_noise = GaussMarkov::default_doppler_km_s();
+    let goldstone = GroundStation::dss13_goldstone(10.0, range_noise, doppler_noise, iau_earth);
+    let madrid = GroundStation::dss65_madrid(10.0, range_noise, doppler_noise, iau_earth);
+    let canberra = GroundStation::dss34_canberra(10.0, range_noise, doppler_noise, iau_earth);
+
+    let ddor_noise = GaussMarkov::white_noise(1e-4);
+    let mut goldstone_madrid = DeltaDorBaseline::new(
+        "Goldstone-Madrid".to_string(),
+        goldstone.clone(),
+        madrid,
+        quasars.clone(),
+    );
+    goldstone_madrid.noise_km = Some(ddor_noise);
+    let mut goldstone_canberra = DeltaDorBaseline::new(
+        "Goldstone-Canberra".to_string(),
+        goldstone,
+        canberra,
+        quasars,
+    );
+    goldstone_canberra.noise_km = Some(ddor_noise);
+
+    let mut configs = HashMap::new();
+    for name in [&goldstone_madrid.name, &goldstone_canberra.name] {
+        configs.insert(name.clone(), TrkConfig::from_sample_rate(10 * Unit::Minute));
+    }
+    let mut arc_sim = TrackingArcSim::<Orbit, DeltaDor, _>::with_seed(
+        vec![goldstone_madrid, goldstone_canberra],
+        traj.clone(),
+        configs,
+        0,
+    )
+    .unwrap();
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+    println!("{arc}");
+    assert!(!arc.measurements.is_empty());
+
+    let dispersed = initial_state + Vector6::new(50.0, -30.0, 40.0, 1e-3, -1e-3, 5e-4);
+    let initial_covar = Matrix6::from_diagonal(&Vector6::new(1e4, 1e4, 1e4, 1e-5, 1e-5, 1e-5));
+    let initial_estimate = KfEstimate::from_covar(dispersed, initial_covar);
+    let kf = KF::no_snc(initial_estimate, Matrix1::new(1e-8));
+    let mut odp = ODProcess::ekf(
+        setup.with(dispersed.with_stm()),
+        kf,
+        EkfTrigger::new(20, 1 * Unit::Hour),
+        None,
+        cosm,
+    );
+    odp.process_arc::<DeltaDorBaseline>(&arc).unwrap();
+
+    let est = odp.estimates.last().unwrap();
+    println!("Final estimate:\n{est}");
+    let truth = traj.at(est.epoch()).unwrap();
+    let err = est.state().to_cartesian_vec() - truth.to_cartesian_vec();
+    println!("position error: {:.3} km", err.fixed_rows::<3>(0).norm());
+    for i in 0..6 {
+        assert!(err[i].abs() < 3.0 * est.covar[(i, i)].sqrt());
+    }
+
+    // The Delta-DOR observes the plane of sky, where the uncertainty drops well below its initial value
+    let line_of_sight = truth.radius().normalize();
+    let across = line_of_sight.cross(&Vector3::z()).normalize();
+    let along = line_of_sight.cross(&across);
+    for direction in [across, along] {
+        let position_covar = est.covar.fixed_view::<3, 3>(0, 0);
+        let sigma_km = (direction.transpose() * position_covar * direction)[(0, 0)].sqrt();
+        println!("plane of sky uncertainty: {sigma_km:.3} km");
+        assert!(sigma_km < 10.0);
+        assert!(err.fixed_rows::<3>(0).dot(&direction).abs() < 3.0 * sigma_km);
+    }
+}
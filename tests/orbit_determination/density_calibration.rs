@@ -0,0 +1,136 @@
+use pretty_env_logger::try_init;
+
+use nyx_space::cosmic::{Cosm, Orbit, Spacecraft};
+use nyx_space::dynamics::ForceModel;
+use nyx_space::dynamics::{
+    CorrectedDrag, DensityCorrection, Drag, OrbitalDynamics, SpacecraftDynamics,
+};
+use nyx_space::linalg::{Const, OMatrix, OVector};
+use nyx_space::od::prelude::*;
+use nyx_space::propagators::Propagator;
+use nyx_space::time::{Epoch, TimeUnits, Unit};
+use std::sync::Arc;
+
+#[test]
+fn density_calibration_storm() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let storm_start = Epoch::from_gregorian_utc_at_midnight(2023, 3, 23);
+
+    // The density is 2.5 times the model at the start of the storm and recovers within a few days
+    let truth = DensityCorrection {
+        ref_epoch: storm_start,
+        scale: 2.5,
+        rate_per_day: 0.0,
+        time_constant: 1.5 * Unit::Day,
+    };
+    println!("{truth}");
+    assert_eq!(truth.scale_at(storm_start), 2.5);
+    assert!(
+        (truth.scale_at(storm_start + 1.5 * Unit::Day) - (1.0 + 1.5 / 1.0_f64.exp())).abs() < 1e-12
+    );
+
+    // Daily orbit determinations since the start of the storm
+    let history = (0..4)
+        .map(|day| {
+            let epoch = storm_start + day * Unit::Day;
+            DragScaleSample {
+                epoch,
+                scale: truth.scale_at(epoch),
+                sigma: 0.05,
+            }
+        })
+        .rev()
+        .collect::<Vec<DragScaleSample>>();
+
+    let calibration = DensityCalibration::new(1.days(), 1.days());
+    let correction = calibration.fit(&history).unwrap();
+    println!("{correction}");
+    let last_epoch = storm_start + 3.days();
+    assert_eq!(correction.ref_epoch, last_epoch);
+    assert!((correction.scale - truth.scale_at(last_epoch)).abs() < 1e-9);
+    assert!(((correction.time_constant - truth.time_constant).to_seconds()).abs() < 1e-3);
+    for hours in [0, 6, 12, 24, 48] {
+        let epoch = last_epoch + hours * Unit::Hour;
+        assert!((correction.scale_at(epoch) - truth.scale_at(epoch)).abs() < 1e-6);
+    }
+    // Before the reference epoch, the fitted trend is followed
+    assert!(correction.rate_per_day < 0.0);
+    assert!(correction.scale_at(last_epoch - 1.days()) > correction.scale);
+
+    // Without decaying phase in the history, the default time constant is used
+    let rising = vec![
+        DragScaleSample {
+            epoch: storm_start,
+            scale: 1.2,
+            sigma: 0.1,
+        },
+        DragScaleSample {
+            epoch: storm_start + 1.days(),
+            scale: 1.8,
+            sigma: 0.1,
+        },
+    ];
+    let rising_correction = DensityCalibration::new(3.days(), 2.days())
+        .fit(&rising)
+        .unwrap();
+    assert_eq!(rising_correction.time_constant, 2.days());
+    assert!((rising_correction.rate_per_day - 0.6).abs() < 1e-9);
+    assert!((rising_correction.scale - 1.8).abs() < 1e-9);
+
+    assert!(calibration.fit(&[]).is_err());
+    let mut bad = history.clone();
+    bad[0].sigma = 0.0;
+    assert!(calibration.fit(&bad).is_err());
+
+    // The history can be built from the estimates of the orbit determinations which solved for the coefficient of drag
+    let nominal_cd = 2.2;
+    let orbit = Orbit::keplerian_altitude(350.0, 1e-3, 51.6, 30.0, 0.0, 0.0, last_epoch, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 10.0).with_drag(10.0, nominal_cd);
+    let estimates = history
+        .iter()
+        .map(|sample| {
+            let mut state = sc;
+            state.orbit.epoch = sample.epoch;
+            state.drag.cd = nominal_cd * sample.scale;
+            let mut diag = OVector::<f64, Const<9>>::from_element(1e-6);
+            diag[7] = (nominal_cd * sample.sigma).powi(2);
+            KfEstimate::from_covar(
+                state,
+                OMatrix::<f64, Const<9>, Const<9>>::from_diagonal(&diag),
+            )
+        })
+        .collect::<Vec<KfEstimate<Spacecraft>>>();
+    let sample = DragScaleSample::from_estimate(&estimates[0], nominal_cd);
+    assert!((sample.scale - history[0].scale).abs() < 1e-12);
+    assert!((sample.sigma - 0.05).abs() < 1e-12);
+    assert_eq!(
+        calibration.fit_estimates(&estimates, nominal_cd).unwrap(),
+        correction
+    );
+
+    // The calibrated prediction is closer to the truth than the prediction with the nominal density
+    let drag = Drag::earth_exp(cosm);
+    let prediction = |correction: Option<DensityCorrection>| {
+        let drag_model: Arc<dyn ForceModel> = match correction {
+            Some(correction) => CorrectedDrag::new(drag.clone(), correction),
+            None => drag.clone(),
+        };
+        let dynamics = SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), drag_model);
+        Propagator::default(dynamics)
+            .with(sc)
+            .for_duration(1.days())
+            .unwrap()
+            .orbit
+    };
+
+    let truth_orbit = prediction(Some(truth));
+    let nominal_err = (prediction(None).radius() - truth_orbit.radius()).norm();
+    let calibrated_err = (prediction(Some(correction)).radius() - truth_orbit.radius()).norm();
+    println!("nominal error: {nominal_err:.3} km\tcalibrated error: {calibrated_err:.3e} km");
+    assert!(nominal_err > 0.01);
+    assert!(calibrated_err < 1e-2 * nominal_err);
+}
@@ -0,0 +1,164 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::{
+    AccelModel, GravityCorrection, GravityParameter, Harmonics, OrbitalDynamics, SpacecraftDynamics,
+};
+use nyx::io::gravity::HarmonicsMem;
+use nyx::md::prelude::*;
+use nyx::od::noise::GaussMarkov;
+use nyx::od::prelude::*;
+use nyx::od::GravityFieldEstimator;
+use std::sync::Arc;
+
+#[test]
+fn gravity_field_estimation() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    let c20 = GravityParameter::C {
+        degree: 2,
+        order: 0,
+    };
+    let c22 = GravityParameter::C {
+        degree: 2,
+        order: 2,
+    };
+    let s22 = GravityParameter::S {
+        degree: 2,
+        order: 2,
+    };
+
+    // The acceleration is linear in the corrections
+    let orbit = Orbit::keplerian(7_000.0, 0.01, 51.6, 20.0, 30.0, 0.0, epoch, eme2k);
+    let deltas = vec![
+        (GravityParameter::GM, 5.0),
+        (c20, 2e-7),
+        (c22, 5e-8),
+        (s22, -3e-8),
+    ];
+    let truth = GravityCorrection::new(iau_earth, deltas.clone(), cosm.clone()).unwrap();
+    println!("{truth}");
+    let partials = truth
+        .accel_partials(&orbit, &[GravityParameter::GM, c20, c22, s22])
+        .unwrap();
+    let expected_gm = -orbit.radius() / orbit.rmag_km().powi(3);
+    assert!((partials[0] - expected_gm).norm() < 1e-12 * expected_gm.norm());
+    let sum = deltas
+        .iter()
+        .zip(&partials)
+        .map(|((_, delta), partial)| *delta * partial)
+        .sum::<nyx::linalg::Vector3<f64>>();
+    let accel = truth.eom(&orbit).unwrap();
+    assert!((sum - accel).norm() < 1e-12 * accel.norm());
+    assert!(GravityCorrection::new(
+        iau_earth,
+        vec![(
+            GravityParameter::S {
+                degree: 2,
+                order: 0
+            },
+            1e-8
+        )],
+        cosm.clone()
+    )
+    .is_err());
+    assert!(GravityCorrection::new(
+        iau_earth,
+        vec![(
+            GravityParameter::C {
+                degree: 1,
+                order: 1
+            },
+            1e-8
+        )],
+        cosm.clone()
+    )
+    .is_err());
+
+    // Truth dynamics, with the corrections on top of the nominal J2 field
+    let nominal = SpacecraftDynamics::new(OrbitalDynamics::from_model(Harmonics::from_stor(
+        iau_earth,
+        HarmonicsMem::j2_jgm3(),
+        cosm.clone(),
+    )));
+    let mut truth_dynamics = nominal.clone();
+    truth_dynamics.orbital_dyn.add_model(Arc::new(truth));
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 0.0);
+
+    // Noiseless range and Doppler from the DSN, on propagated states rather than interpolated ones
+    let mut stations = vec![
+        GroundStation::dss65_madrid(10.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+        GroundStation::dss34_canberra(10.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+        GroundStation::dss13_goldstone(10.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+    ];
+    let prop = Propagator::default(truth_dynamics);
+    let mut truth_prop = prop.with(sc);
+    let mut measurements = Vec::new();
+    for _ in 0..=720 {
+        for station in stations.iter_mut() {
+            if let Some(msr) = station
+                .measure_instantaneous(truth_prop.state, None, cosm.clone())
+                .unwrap()
+            {
+                measurements.push((station.name.clone(), msr));
+            }
+        }
+        truth_prop.for_duration(1.minutes()).unwrap();
+    }
+    println!("{} measurements", measurements.len());
+
+    let mut initial_guess = sc;
+    initial_guess.orbit.x_km += 0.5;
+    initial_guess.orbit.vy_km_s -= 5e-4;
+
+    let estimator = GravityFieldEstimator::new(
+        nominal.clone(),
+        iau_earth,
+        stations.clone(),
+        1e-3,
+        1e-6,
+        cosm.clone(),
+    )
+    .with_parameter(GravityParameter::GM, None)
+    .with_parameter(c20, None)
+    .with_parameter(c22, None)
+    .with_parameter(s22, None);
+    let solution = estimator.estimate(initial_guess, &measurements).unwrap();
+    println!("{solution}");
+
+    for (param, delta) in &deltas {
+        let estimated = solution.delta(*param).unwrap();
+        assert!(
+            (estimated - delta).abs() < 1e-2 * delta.abs(),
+            "{param}: {estimated:e} != {delta:e}"
+        );
+    }
+    assert!((solution.initial_state.orbit.radius() - orbit.radius()).norm() < 1e-3);
+    assert!(solution.rms_range_km < 1e-4);
+
+    // A tight a priori constraint keeps the correction of its parameter at its nominal value
+    let constrained =
+        GravityFieldEstimator::new(nominal, iau_earth, stations, 1e-3, 1e-6, cosm.clone())
+            .with_parameter(GravityParameter::GM, None)
+            .with_parameter(c20, None)
+            .with_parameter(c22, None)
+            .with_parameter(s22, Some(1e-12))
+            .estimate(initial_guess, &measurements)
+            .unwrap();
+    println!("{constrained}");
+    assert!(constrained.delta(s22).unwrap().abs() < 1e-11);
+    assert!(constrained.sigma(s22).unwrap() <= 1e-12);
+    // Forcing the wrong S22 degrades the fit
+    assert!(constrained.rms_range_km > solution.rms_range_km);
+    assert!(solution
+        .delta(GravityParameter::C {
+            degree: 3,
+            order: 0
+        })
+        .is_none());
+}
@@ -0,0 +1,157 @@
+use pretty_env_logger::try_init;
+
+use nyx_space::cosmic::{Cosm, Frame, Orbit};
+use nyx_space::dynamics::orbital::OrbitalDynamics;
+use nyx_space::linalg::{Matrix6, Vector3, Vector6};
+use nyx_space::od::covariance_map;
+use nyx_space::od::prelude::*;
+use nyx_space::propagators::{PropOpts, Propagator, RK4Fixed};
+use nyx_space::time::{Epoch, TimeUnits};
+
+#[test]
+fn injection_covariance_a_priori() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_utc_hms(2023, 11, 16, 14, 32, 0);
+    let injection = Orbit::keplerian(7000.0, 0.001, 51.6, 40.0, 0.0, 30.0, epoch, eme2k);
+
+    // Dispersions in the local orbital frame of the injection state
+    let ric = InjectionCovariance::from_std_devs(
+        InjectionAxes::Orbital(Frame::RIC),
+        Vector3::new(1.0, 5.0, 0.5),
+        Vector3::new(1e-3, 2e-3, 5e-4),
+    )
+    .unwrap();
+    println!("{ric}");
+    let covar = ric.covar_in_frame_of(&injection, &cosm).unwrap();
+    let r_hat = injection.radius() / injection.rmag_km();
+    let h_hat = injection.hvec() / injection.hmag_km2_s();
+    let pos = covar.fixed_view::<3, 3>(0, 0);
+    assert!(((r_hat.transpose() * pos * r_hat)[0] - 1.0).abs() < 1e-9);
+    assert!(((h_hat.transpose() * pos * h_hat)[0] - 0.25).abs() < 1e-9);
+    assert!((covar.trace() - ric.covar.trace()).abs() < 1e-9);
+
+    // Correlated dispersions are preserved by the rotation
+    let mut correlations = Matrix6::identity();
+    correlations[(0, 4)] = -0.9;
+    correlations[(4, 0)] = -0.9;
+    let correlated = InjectionCovariance::from_correlations(
+        InjectionAxes::Orbital(Frame::RIC),
+        Vector6::new(1.0, 5.0, 0.5, 1e-3, 2e-3, 5e-4),
+        correlations,
+    )
+    .unwrap();
+    assert!((correlated.covar[(0, 4)] + 0.9 * 2e-3).abs() < 1e-15);
+    let dcm = correlated.dcm_to_frame_of(&injection, &cosm).unwrap();
+    let back = dcm.transpose() * correlated.covar_in_frame_of(&injection, &cosm).unwrap() * dcm;
+    assert!((back - correlated.covar).norm() < 1e-12);
+
+    // Dispersions in the topocentric frame of the launch pad
+    let pad = InjectionAxes::LaunchPad {
+        latitude_deg: 5.236,
+        longitude_deg: -52.769,
+        height_km: 0.0,
+        body_fixed: iau_earth,
+    };
+    let zenith_only =
+        InjectionCovariance::from_std_devs(pad, Vector3::new(0.0, 0.0, 2.0), Vector3::zeros())
+            .unwrap();
+    println!("{zenith_only}");
+    let covar = zenith_only.covar_in_frame_of(&injection, &cosm).unwrap();
+    let zenith_fixed = Orbit::from_geodesic(5.236, -52.769, 0.0, epoch, iau_earth)
+        .dcm_from_traj_frame(Frame::SEZ)
+        .unwrap()
+        * Vector3::z();
+    let zenith = cosm
+        .try_position_dcm_from_to(&iau_earth, &eme2k, epoch)
+        .unwrap()
+        * zenith_fixed;
+    let pos = covar.fixed_view::<3, 3>(0, 0);
+    assert!(((zenith.transpose() * pos * zenith)[0] - 4.0).abs() < 1e-9);
+    assert!((pos.trace() - 4.0).abs() < 1e-9);
+    // The rotation of the Earth transports a position dispersion into an inertial velocity dispersion of about ω σ
+    let vel_sigma_km_s = covar.fixed_view::<3, 3>(3, 3).trace().sqrt();
+    println!(
+        "transported velocity dispersion: {:.3} m/s",
+        vel_sigma_km_s * 1e3
+    );
+    assert!(vel_sigma_km_s > 0.5 * 7.29e-5 * 2.0 && vel_sigma_km_s < 1.5 * 7.29e-5 * 2.0);
+
+    let velocity_only =
+        InjectionCovariance::from_std_devs(pad, Vector3::zeros(), Vector3::new(3e-3, 4e-3, 1e-3))
+            .unwrap();
+    let covar = velocity_only.covar_in_frame_of(&injection, &cosm).unwrap();
+    assert!(covar.fixed_view::<3, 3>(0, 0).norm() < 1e-15);
+    assert!((covar.fixed_view::<3, 3>(3, 3).trace() - 26e-6).abs() < 1e-12);
+
+    // A priori estimate for the early acquisition, mapped to the first pass
+    let estimate = ric.estimate(injection.with_stm(), &cosm).unwrap();
+    assert_eq!(estimate.nominal_state.epoch, epoch);
+    assert_eq!(
+        estimate.covar,
+        ric.covar_in_frame_of(&injection, &cosm).unwrap()
+    );
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+    let acquisition = epoch + 25.minutes();
+    let mapped = covariance_map(
+        setup.with(estimate.nominal_state),
+        estimate.covar,
+        &[epoch, acquisition],
+        vec![],
+        5.minutes(),
+    )
+    .unwrap();
+    let sigma_pos_km = |covar: &Matrix6<f64>| covar.fixed_view::<3, 3>(0, 0).trace().sqrt();
+    println!(
+        "position uncertainty: {:.3} km at injection, {:.3} km at acquisition",
+        sigma_pos_km(&mapped[0].covar),
+        sigma_pos_km(&mapped[1].covar)
+    );
+    assert!(sigma_pos_km(&mapped[1].covar) > sigma_pos_km(&mapped[0].covar));
+
+    // Invalid injection covariances
+    assert!(InjectionCovariance::from_std_devs(
+        InjectionAxes::Orbital(eme2k),
+        Vector3::new(1.0, 1.0, 1.0),
+        Vector3::zeros()
+    )
+    .is_err());
+    assert!(InjectionCovariance::from_std_devs(
+        InjectionAxes::LaunchPad {
+            latitude_deg: 5.236,
+            longitude_deg: -52.769,
+            height_km: 0.0,
+            body_fixed: eme2k,
+        },
+        Vector3::new(1.0, 1.0, 1.0),
+        Vector3::zeros()
+    )
+    .is_err());
+    assert!(InjectionCovariance::from_std_devs(
+        InjectionAxes::Inertial,
+        Vector3::new(-1.0, 1.0, 1.0),
+        Vector3::zeros()
+    )
+    .is_err());
+    let mut asymmetric = Matrix6::identity();
+    asymmetric[(0, 1)] = 0.5;
+    assert!(InjectionCovariance::new(InjectionAxes::Inertial, asymmetric).is_err());
+    let mut indefinite = Matrix6::identity();
+    indefinite[(0, 1)] = 2.0;
+    indefinite[(1, 0)] = 2.0;
+    assert!(InjectionCovariance::new(InjectionAxes::Inertial, indefinite).is_err());
+    correlations[(0, 4)] = -1.1;
+    assert!(InjectionCovariance::from_correlations(
+        InjectionAxes::Inertial,
+        Vector6::repeat(1.0),
+        correlations
+    )
+    .is_err());
+}
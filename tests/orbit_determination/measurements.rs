@@ -32,6 +32,8 @@ fn nil_measurement() {
         doppler_noise_km_s: Some(GaussMarkov::ZERO),
         integration_time: None,
         light_time_correction: false,
+        aberration_correction: false,
+        media: None,
     };
 
     let at_station = Orbit::from_geodesic(lat, long, height, epoch, eme2k);
@@ -177,6 +179,7 @@ fn val_measurements_topo() {
             (obs[0] - truth.range).abs(),
             (obs[1] - truth.range_rate).abs()
         );
+        assert!((obs[1] - truth.range_rate).abs() < 1e-3);
     }
 
     // Second cislunar test
@@ -244,5 +247,99 @@ fn val_measurements_topo() {
             (obs[0] - truth.range).abs(),
             (obs[1] - truth.range_rate).abs()
         );
+        assert!((obs[1] - truth.range_rate).abs() < 1e-3);
     }
 }
+
+#[test]
+fn light_time_measurements() {
+    use nyx::cosmic::SPEED_OF_LIGHT_KMS;
+    use nyx::md::prelude::*;
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 11, 16);
+
+    // A spacecraft near lunar distance, so that the light time is over a second.
+    let orbit = Orbit::keplerian(400_000.0, 0.1, 30.0, 10.0, 20.0, 40.0, epoch, eme2k);
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(orbit)
+        .for_duration_with_traj(2.hours())
+        .unwrap();
+
+    let mut station =
+        GroundStation::dss65_madrid(-90.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth);
+    let rx_epoch = epoch + 1.hours();
+
+    // One-way: the leg length is the distance traveled by light, and the spacecraft moved during that light time.
+    let legs = station
+        .light_time_legs(rx_epoch, &traj, false, &cosm)
+        .unwrap();
+    assert_eq!(legs.len(), 1);
+    let (bounce, station_rx) = legs[0];
+    assert_eq!(station_rx.epoch, rx_epoch);
+    let light_time_s = (rx_epoch - bounce.epoch).to_seconds();
+    let leg_km = (station_rx.radius() - bounce.radius()).norm();
+    assert!(light_time_s > 1.0);
+    assert!((leg_km - light_time_s * SPEED_OF_LIGHT_KMS).abs() < 1e-3);
+
+    let geometric = station
+        .measure(rx_epoch, &traj, None, cosm.clone())
+        .unwrap()
+        .unwrap();
+    station.light_time_correction = true;
+    let one_way = station
+        .measure(rx_epoch, &traj, None, cosm.clone())
+        .unwrap()
+        .unwrap();
+    // To first order, the range differs by the range rate over the light time.
+    let expected_delta_km = -geometric.obs[1] * light_time_s;
+    println!(
+        "geometric {geometric:?}\nlight time {one_way:?}\nexpected range change {expected_delta_km:.3} km"
+    );
+    assert!((one_way.obs[0] - leg_km).abs() < 1e-9);
+    assert!(((one_way.obs[0] - geometric.obs[0]) - expected_delta_km).abs() < 0.1);
+    assert!((one_way.obs[1] - geometric.obs[1]).abs() < 1e-3);
+
+    // Two-way: the uplink is received by the spacecraft when it transmits the downlink.
+    let legs = station
+        .light_time_legs(rx_epoch, &traj, true, &cosm)
+        .unwrap();
+    assert_eq!(legs.len(), 2);
+    let (station_tx, uplink_rx) = legs[0];
+    let (downlink_tx, _) = legs[1];
+    assert_eq!(uplink_rx.epoch, downlink_tx.epoch);
+    assert!(
+        ((uplink_rx.epoch - station_tx.epoch).to_seconds() * SPEED_OF_LIGHT_KMS
+            - (uplink_rx.radius() - station_tx.radius()).norm())
+        .abs()
+            < 1e-3
+    );
+
+    // The integrated Doppler matches the geometric range rate.
+    station.integration_time = Some(60.seconds());
+    let two_way = station
+        .measure(rx_epoch, &traj, None, cosm.clone())
+        .unwrap()
+        .unwrap();
+    println!("two way {two_way:?}");
+    assert_eq!(two_way.epoch, rx_epoch);
+    assert!((two_way.obs[1] - geometric.obs[1]).abs() < 1e-3);
+
+    // The stellar aberration only shifts the apparent direction, by about v/c.
+    station.aberration_correction = false;
+    let (az, el, _, _) = station.azimuth_elevation_of(*traj.at(rx_epoch).unwrap().orbit(), &cosm);
+    station.aberration_correction = true;
+    let (az_app, el_app, rx, _) =
+        station.azimuth_elevation_of(*traj.at(rx_epoch).unwrap().orbit(), &cosm);
+    let shift_deg = ((az_app - az).powi(2) + (el_app - el).powi(2)).sqrt();
+    println!("aberration shift: {:.3} arcsec", shift_deg * 3600.0);
+    assert!(shift_deg > 0.0 && shift_deg < 1e-3);
+    assert_eq!(rx, *traj.at(rx_epoch).unwrap().orbit());
+    let with_aberration = station
+        .measure(rx_epoch, &traj, None, cosm)
+        .unwrap()
+        .unwrap();
+    assert_eq!(with_aberration, two_way);
+}
@@ -0,0 +1,141 @@
+use nyx_space::cosmic::{Cosm, Orbit};
+use nyx_space::dynamics::OrbitalDynamics;
+use nyx_space::od::noise::GaussMarkov;
+use nyx_space::od::prelude::*;
+use nyx_space::od::{niell_mapping, Ionosphere, MediaCorrections, Meteo, Troposphere};
+use nyx_space::propagators::Propagator;
+use nyx_space::time::{Epoch, TimeUnits};
+
+#[test]
+fn media_delay_models() {
+    let epoch = Epoch::from_gregorian_utc_hms(2023, 7, 1, 2, 0, 0);
+
+    // Niell mapping functions are unity at the zenith and close to the cosecant at moderate elevations
+    let (m_hydro, m_wet) = niell_mapping(45.0, 0.0, 90.0, epoch);
+    assert!((m_hydro - 1.0).abs() < 1e-12 && (m_wet - 1.0).abs() < 1e-12);
+    let (m_hydro, m_wet) = niell_mapping(45.0, 0.0, 30.0, epoch);
+    assert!((m_hydro - 2.0).abs() < 0.01 && (m_wet - 2.0).abs() < 0.01);
+    let (m_hydro, m_wet) = niell_mapping(45.0, 0.0, 5.0, epoch);
+    println!("Niell at 5 deg: hydrostatic {m_hydro:.4}, wet {m_wet:.4}");
+    assert!(m_hydro > 9.5 && m_hydro < 10.5 && m_wet > 10.0 && m_wet < 11.5);
+
+    // Saastamoinen zenith delays in the standard atmosphere at sea level
+    let standard = Troposphere::StandardAtmosphere;
+    let (hydro_km, wet_km) = standard.zenith_delays_km(45.0, 0.0);
+    println!(
+        "zenith delays: {:.4} m + {:.4} m",
+        hydro_km * 1e3,
+        wet_km * 1e3
+    );
+    assert!((hydro_km * 1e3 - 2.3).abs() < 0.01);
+    assert!(wet_km * 1e3 > 0.05 && wet_km * 1e3 < 0.3);
+    let low_el_km = standard.delay_km(45.0, 0.0, 10.0, epoch);
+    assert!(low_el_km * 1e3 > 12.0 && low_el_km * 1e3 < 16.0);
+    // Drier and higher stations have smaller delays
+    let dry = Troposphere::Measured(Meteo {
+        relative_humidity: 0.0,
+        ..Meteo::standard(0.0)
+    });
+    assert_eq!(dry.zenith_delays_km(45.0, 0.0).1, 0.0);
+    assert!(standard.delay_km(45.0, 1.5, 10.0, epoch) < low_el_km);
+
+    // Thin shell ionosphere, scaled by the inverse square of the frequency
+    let shell = Ionosphere::SingleLayer {
+        vtec_tecu: 10.0,
+        shell_height_km: 350.0,
+    };
+    let s_band_km = shell.delay_km(45.0, 0.0, 0.0, 90.0, epoch, 2.2e9);
+    assert!((s_band_km * 1e3 - 40.3 * 1e17 / 2.2e9_f64.powi(2)).abs() < 1e-9);
+    let x_band_km = shell.delay_km(45.0, 0.0, 0.0, 90.0, epoch, 8.4e9);
+    assert!((x_band_km / s_band_km - (2.2_f64 / 8.4).powi(2)).abs() < 1e-12);
+    let slant_km = shell.delay_km(45.0, 0.0, 0.0, 10.0, epoch, 2.2e9);
+    assert!(slant_km / s_band_km > 2.5 && slant_km / s_band_km < 3.5);
+
+    // Klobuchar at L1: the night time delay is the constant 5 ns, and the day time delay is larger
+    let klobuchar = Ionosphere::Klobuchar {
+        alpha: [1.1176e-8, 7.4506e-9, -5.9605e-8, -5.9605e-8],
+        beta: [90112.0, 0.0, -196608.0, -65536.0],
+    };
+    let night_km = klobuchar.delay_km(0.0, 0.0, 0.0, 90.0, epoch, 1_575.42e6);
+    assert!((night_km * 1e3 - 5e-9 * 299_792_458.0).abs() < 0.01);
+    let day_km = klobuchar.delay_km(0.0, 0.0, 0.0, 90.0, epoch + 12.hours(), 1_575.42e6);
+    println!(
+        "Klobuchar zenith: night {:.3} m, day {:.3} m",
+        night_km * 1e3,
+        day_km * 1e3
+    );
+    assert!(day_km > 2.0 * night_km && day_km * 1e3 < 30.0);
+    assert!(klobuchar.delay_km(0.0, 0.0, 0.0, 10.0, epoch, 1_575.42e6) > 2.0 * night_km);
+}
+
+#[test]
+fn media_delay_measurements() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2023, 11, 16);
+
+    let orbit = Orbit::keplerian(26_000.0, 0.1, 30.0, 10.0, 20.0, 40.0, epoch, eme2k);
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(orbit)
+        .for_duration_with_traj(1.days())
+        .unwrap();
+
+    let mut station =
+        GroundStation::dss65_madrid(10.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth);
+    let visible = station
+        .visibility_intervals(&traj, 5.minutes(), &cosm)
+        .unwrap();
+    let pass = visible.intervals.first().expect("no pass over Madrid");
+    let msr_epoch = pass.start + 10.minutes();
+
+    let geometric = station
+        .measure(msr_epoch, &traj, None, cosm.clone())
+        .unwrap()
+        .unwrap();
+    station.media = Some(MediaCorrections {
+        frequency_hz: 8.4e9,
+        troposphere: Some(Troposphere::StandardAtmosphere),
+        ionosphere: Some(Ionosphere::SingleLayer {
+            vtec_tecu: 20.0,
+            shell_height_km: 350.0,
+        }),
+    });
+    let with_media = station
+        .measure(msr_epoch, &traj, None, cosm.clone())
+        .unwrap()
+        .unwrap();
+    let (delay_km, delay_rate_km_s) = station.media_delay(traj.at(msr_epoch).unwrap(), &cosm);
+    println!(
+        "{geometric:?}\n{with_media:?}\ndelay {delay_km:.6} km, rate {delay_rate_km_s:.6e} km/s"
+    );
+    assert!(delay_km > 2e-3 && delay_km < 30e-3);
+    assert!((with_media.obs[0] - geometric.obs[0] - delay_km).abs() < 1e-12);
+    assert!((with_media.obs[1] - geometric.obs[1] - delay_rate_km_s).abs() < 1e-12);
+
+    // The delay rate is consistent with the change of the delay between successive measurements
+    let later = station
+        .measure(msr_epoch + 10.seconds(), &traj, None, cosm.clone())
+        .unwrap()
+        .unwrap();
+    let (later_delay_km, _) =
+        station.media_delay(traj.at(msr_epoch + 10.seconds()).unwrap(), &cosm);
+    let finite_rate_km_s = (later_delay_km - delay_km) / 10.0;
+    println!("delay rate over 10 s: {finite_rate_km_s:.6e} km/s");
+    assert!((finite_rate_km_s - delay_rate_km_s).abs() < 0.05 * delay_rate_km_s.abs());
+    assert!(later.obs[0] > 0.0);
+
+    // The computed measurement of the filter uses the same model, so the delays cancel out in the residuals, and removing
+    // the delays from the measurements recovers the geometric ones.
+    let computed = station
+        .measure(msr_epoch, &traj, None, cosm.clone())
+        .unwrap()
+        .unwrap();
+    assert_eq!(computed, with_media);
+    assert!((with_media.obs[0] - delay_km - geometric.obs[0]).abs() < 1e-12);
+
+    // The media corrections are part of the station configuration
+    let yaml = serde_yaml::to_string(&station).unwrap();
+    let loaded: GroundStation = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(loaded.media, station.media);
+}
@@ -5,12 +5,36 @@ use self::nyx::cosmic::Orbit;
 use self::nyx::od::prelude::{Estimate, Filter, KfEstimate, NyxError, KF};
 use self::nyx::State;
 
+mod accel_calibration;
+mod acquisition;
+mod arc_selection;
+mod batch;
+mod clock;
+mod covar_ellipsoid;
+mod covar_map;
+mod delivery;
+mod delta_dor;
+mod density_calibration;
+mod gravity_field;
+mod injection;
 mod measurements;
+mod media;
 mod multi_body;
+mod opnav;
+mod position_fixes;
+mod replay;
 mod resid_reject;
 mod robust;
+mod scenario;
 mod simulator;
+mod smoother;
+mod snapshot;
 mod spacecraft;
+mod spacecraft_tracker;
+mod srif;
+mod station_survey;
+mod surface_asset;
+mod tdm;
 mod trackingarc;
 mod two_body;
 mod xhat_dev;
@@ -0,0 +1,216 @@
+extern crate nalgebra as na;
+extern crate nyx_space as nyx;
+
+use na::UnitQuaternion;
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::linalg::{Matrix3, Matrix6, Vector2, Vector3, Vector6};
+use nyx::od::prelude::*;
+use nyx::od::EstimateFrom;
+use nyx::propagators::{PropOpts, Propagator, RK4Fixed};
+use std::collections::HashMap;
+
+#[test]
+fn opnav_camera_model() {
+    let cosm = Cosm::de438();
+    let luna = cosm.frame("Luna");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let camera = PinholeCamera::from_fov(10.0, 1024.0, 1024.0);
+    assert!((camera.ifov_deg() - 10.0 / 1024.0).abs() < 1e-4);
+    let direction = Vector3::new(0.02, -0.03, 1.0).normalize();
+    let pixel_line = camera.project(&direction).unwrap();
+    assert!((camera.direction(&pixel_line) - direction).norm() < 1e-12);
+    assert!(camera.project(&-direction).is_none());
+    assert!(camera.project(&Vector3::new(1.0, 0.0, 1.0)).is_none());
+
+    let orbiter = Orbit::keplerian(30_000.0, 0.2, 40.0, 20.0, 10.0, 45.0, epoch, luna);
+    let mut opnav = OpNavCamera::new("NavCam".to_string(), luna, camera, 0.5, 1.0);
+    println!("{opnav}");
+
+    // Pointed at the target, the centroid is at the center of the image and observes the line of sight
+    let (center, radius_px, _) = opnav.image(&orbiter, &cosm).unwrap().unwrap();
+    assert!((center - Vector2::new(512.0, 512.0)).norm() < 1e-9);
+    let rho_km = orbiter.rmag_km();
+    let expected_radius_px =
+        camera.focal_length_px * (luna.equatorial_radius() / rho_km).asin().tan();
+    assert!((radius_px - expected_radius_px).abs() < 1e-9);
+
+    let centroid: OpNavCentroid = opnav
+        .measure_instantaneous(orbiter, None, cosm.clone())
+        .unwrap()
+        .unwrap();
+    let truth = OpNavCentroid::new(epoch, &-orbiter.radius());
+    assert!((centroid.obs - truth.obs).norm() < 1e-10);
+    let limb: OpNavLimb = opnav
+        .measure_instantaneous(orbiter, None, cosm.clone())
+        .unwrap()
+        .unwrap();
+    let truth = OpNavLimb::new(epoch, &-orbiter.radius(), luna.equatorial_radius());
+    assert!((limb.obs - truth.obs).norm() < 1e-10);
+
+    // A fixed attitude pointing away from the target does not image it
+    let mut away = opnav.clone().with_attitude(CameraAttitude::inertial(
+        UnitQuaternion::rotation_between(&Vector3::z(), &orbiter.radius()).unwrap(),
+    ));
+    let msr: Option<OpNavCentroid> = away
+        .measure_instantaneous(orbiter, None, cosm.clone())
+        .unwrap();
+    assert!(msr.is_none());
+
+    // Slightly off-pointed, the target is off center but the star-relative centroid is unchanged
+    let toward = UnitQuaternion::rotation_between(&Vector3::z(), &-orbiter.radius()).unwrap();
+    let tilt = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 2.0_f64.to_radians());
+    let mut history = opnav.clone().with_attitude(CameraAttitude::History(vec![
+        CameraAttitudeSample {
+            epoch: epoch - 1 * Unit::Minute,
+            quaternion: [toward.w, toward.i, toward.j, toward.k],
+        },
+        CameraAttitudeSample {
+            epoch: epoch + 1 * Unit::Minute,
+            quaternion: {
+                let q = toward * tilt * tilt;
+                [q.w, q.i, q.j, q.k]
+            },
+        },
+    ]));
+    let (center, _, _) = history.image(&orbiter, &cosm).unwrap().unwrap();
+    let off_center_px = (center - Vector2::new(512.0, 512.0)).norm();
+    assert!((off_center_px / camera.focal_length_px - 2.0_f64.to_radians().tan()).abs() < 1e-9);
+    let tilted: OpNavCentroid = history
+        .measure_instantaneous(orbiter, None, cosm.clone())
+        .unwrap()
+        .unwrap();
+    assert!((tilted.obs - centroid.obs).norm() < 1e-10);
+    // Outside of the attitude history
+    let later = Orbit::keplerian(
+        30_000.0,
+        0.2,
+        40.0,
+        20.0,
+        10.0,
+        45.0,
+        epoch + 1 * Unit::Hour,
+        luna,
+    );
+    assert!(history.image(&later, &cosm).is_err());
+
+    // The partials match the finite differences of the observations
+    let target = opnav.target_state(epoch, luna, &cosm);
+    let h_tilde = <Orbit as EstimateFrom<Orbit, OpNavLimb>>::sensitivity(&limb, orbiter, target);
+    for i in 0..3 {
+        let mut delta = Vector3::zeros();
+        delta[i] = 1e-3;
+        let obs_at = |sign: f64| {
+            OpNavLimb::new(
+                epoch,
+                &(target.radius() - orbiter.radius() - delta * sign),
+                luna.equatorial_radius(),
+            )
+            .obs
+        };
+        let fd = (obs_at(1.0) - obs_at(-1.0)) / 2e-3;
+        for row in 0..3 {
+            assert!(
+                (h_tilde[(row, i)] - fd[row]).abs() < 1e-9,
+                "row {row} col {i}: {} != {}",
+                h_tilde[(row, i)],
+                fd[row]
+            );
+        }
+        // No partials on the velocity
+        assert_eq!(h_tilde[(0, i + 3)], 0.0);
+    }
+
+    // The limb is not fit when the target is too small in the image
+    opnav.min_limb_radius_px = 2.0 * radius_px;
+    let msr: Option<OpNavLimb> = opnav
+        .measure_instantaneous(orbiter, None, cosm.clone())
+        .unwrap();
+    assert!(msr.is_none());
+
+    // The right ascension is unwrapped about the computed one to form the residuals
+    let across = OpNavCentroid::from_observation(epoch, Vector2::new(179.9, 10.0));
+    let about = across.observation_about(&Vector2::new(-179.9, 10.0));
+    assert!((about[0] + 180.1).abs() < 1e-9);
+    assert_eq!(about[1], 10.0);
+
+    // The camera is serialized with its attitude
+    let yaml = serde_yaml::to_string(&history).unwrap();
+    let rebuilt: OpNavCamera = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(rebuilt, history);
+}
+
+#[test]
+fn od_opnav_lunar_approach() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let luna = cosm.frame("Luna");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(30 * Unit::Second),
+    );
+    let initial_state = Orbit::keplerian(10_000.0, 0.2, 40.0, 20.0, 10.0, 45.0, epoch, luna);
+    let (_, traj) = setup
+        .with(initial_state)
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    let opnav = OpNavCamera::new(
+        "NavCam".to_string(),
+        luna,
+        PinholeCamera::from_fov(30.0, 1024.0, 1024.0),
+        0.5,
+        1.0,
+    );
+    let mut configs = HashMap::new();
+    configs.insert(
+        opnav.name.clone(),
+        TrkConfig::from_sample_rate(5 * Unit::Minute),
+    );
+    let mut arc_sim = TrackingArcSim::<Orbit, OpNavLimb, _>::with_seed(
+        vec![opnav.clone()],
+        traj.clone(),
+        configs,
+        0,
+    )
+    .unwrap();
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+    println!("{arc}");
+    assert_eq!(arc.measurements.len(), 289);
+
+    let dispersed = initial_state + Vector6::new(10.0, -5.0, 5.0, 1e-3, 0.0, -1e-3);
+    let initial_estimate = KfEstimate::from_covar(
+        dispersed,
+        Matrix6::from_diagonal(&Vector6::new(100.0, 100.0, 100.0, 1e-5, 1e-5, 1e-5)),
+    );
+    // The right ascension noise is larger than the angular noise away from the equator
+    let measurement_noise = Matrix3::from_diagonal(&Vector3::new(
+        (2.0 * opnav.centroid_sigma_deg()).powi(2),
+        opnav.centroid_sigma_deg().powi(2),
+        opnav.limb_sigma_deg().powi(2),
+    ));
+    let kf = KF::no_snc(initial_estimate, measurement_noise);
+    let mut odp = ODProcess::ekf(
+        setup.with(dispersed.with_stm()),
+        kf,
+        EkfTrigger::new(20, 1 * Unit::Hour),
+        None,
+        cosm,
+    );
+    odp.process_arc::<OpNavCamera>(&arc).unwrap();
+
+    let est = odp.estimates.last().unwrap();
+    println!("Final estimate:\n{est}");
+    let truth = traj.at(est.epoch()).unwrap();
+    let err = est.state().to_cartesian_vec() - truth.to_cartesian_vec();
+    let err_km = err.fixed_rows::<3>(0).norm();
+    println!("position error: {err_km:.3} km");
+    for i in 0..6 {
+        assert!(err[i].abs() < 3.0 * est.covar[(i, i)].sqrt());
+    }
+    assert!(err_km < 1.0);
+}
@@ -0,0 +1,102 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::linalg::{Matrix3, Matrix6, Vector6};
+use nyx::od::prelude::*;
+use nyx::propagators::{PropOpts, Propagator, RK4Fixed};
+use std::collections::HashMap;
+
+#[test]
+fn od_position_fixes_fusion() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let step_size = 10.0 * Unit::Second;
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(step_size),
+    );
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k);
+    let (_, traj) = setup
+        .with(initial_state)
+        .for_duration_with_traj(12 * Unit::Hour)
+        .unwrap();
+
+    // An ephemeris delivered by another provider in a different frame is converted into the navigation frame
+    let precise = PositionFixSource::from_sigma("precise".to_string(), 0.01);
+    let provider_ephem = traj.to_frame(iau_earth, cosm.clone()).unwrap();
+    let ephem_arc = precise
+        .pseudo_measurements(&provider_ephem, Some(1 * Unit::Hour), eme2k, cosm.clone())
+        .unwrap();
+    println!("{ephem_arc}");
+    assert_eq!(ephem_arc.measurements.len(), 13);
+    for (name, msr) in &ephem_arc.measurements {
+        assert_eq!(name, "precise");
+        let truth = traj.at(msr.epoch()).unwrap();
+        assert!((msr.observation() - truth.radius()).norm() < 1e-6);
+    }
+    let devices = ephem_arc
+        .rebuild_devices::<Orbit, PositionFixSource>(cosm.clone())
+        .unwrap();
+    assert_eq!(devices["precise"], precise);
+
+    // Two noisy sources of different quality, time triggered at different rates
+    let coarse = PositionFixSource::new(
+        "coarse".to_string(),
+        Matrix3::from_diagonal(&[1.0, 0.5, 2.0].into()),
+    );
+    println!("{precise}\n{coarse}");
+    let mut configs = HashMap::new();
+    configs.insert(
+        precise.name.clone(),
+        TrkConfig::from_sample_rate(10 * Unit::Minute),
+    );
+    configs.insert(
+        coarse.name.clone(),
+        TrkConfig::from_sample_rate(2 * Unit::Minute),
+    );
+    let mut arc_sim =
+        TrackingArcSim::with_seed(vec![precise.clone(), coarse], traj.clone(), configs, 0).unwrap();
+    arc_sim.allow_overlap();
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+    println!("{arc}");
+    assert_eq!(arc.device_names().len(), 2);
+
+    // Start the filter with a dispersed state
+    let dispersed = initial_state + Vector6::new(0.5, -0.3, 0.2, 0.0, 0.0, 0.0);
+    let initial_estimate = KfEstimate::from_covar(
+        dispersed,
+        Matrix6::from_diagonal(&Vector6::new(1.0, 1.0, 1.0, 1e-6, 1e-6, 1e-6)),
+    );
+    // The covariance of each source replaces this (poor) measurement noise of the filter
+    let filter_noise = Matrix3::identity() * 1e4;
+    let kf = KF::no_snc(initial_estimate, filter_noise);
+    let mut odp = ODProcess::ekf(
+        setup.with(dispersed.with_stm()),
+        kf,
+        EkfTrigger::new(20, 1 * Unit::Hour),
+        None,
+        cosm,
+    );
+    odp.process_arc::<PositionFixSource>(&arc).unwrap();
+    assert_eq!(odp.kf.measurement_noise, filter_noise);
+
+    let est = odp.estimates.last().unwrap();
+    println!("Final estimate:\n{est}");
+    let truth = traj.at(est.epoch()).unwrap();
+    let err = est.state().to_cartesian_vec() - truth.to_cartesian_vec();
+    let err_km = err.fixed_rows::<3>(0).norm();
+    println!("position error: {err_km:.3e} km");
+    for i in 0..6 {
+        assert!(err[i].abs() < 3.0 * est.covar[(i, i)].sqrt());
+    }
+    // The precise source drives the estimate
+    assert!(err_km < 0.01);
+    assert!(est.covar[(0, 0)].sqrt() < 0.01);
+}
@@ -0,0 +1,174 @@
+use pretty_env_logger::try_init;
+
+use nyx_space::cosmic::{Cosm, Orbit};
+use nyx_space::dynamics::orbital::OrbitalDynamics;
+use nyx_space::linalg::{DMatrix, Matrix2, Vector2};
+use nyx_space::md::prelude::*;
+use nyx_space::md::StateParameter;
+use nyx_space::od::noise::GaussMarkov;
+use nyx_space::od::prelude::*;
+use nyx_space::propagators::{PropOpts, Propagator, RK4Fixed};
+use nyx_space::time::{Epoch, TimeUnits};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[test]
+fn od_replay_step_through() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2023, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(initial_state)
+        .for_duration_with_traj(2.hours())
+        .unwrap();
+
+    let dss65_madrid = GroundStation::dss65_madrid(
+        0.0,
+        GaussMarkov::high_precision_range_km(),
+        GaussMarkov::high_precision_doppler_km_s(),
+        iau_earth,
+    );
+
+    let mut configs = HashMap::new();
+    configs.insert(
+        dss65_madrid.name.clone(),
+        TrkConfig {
+            sampling: 60.seconds(),
+            ..Default::default()
+        },
+    );
+
+    let mut arc_sim =
+        TrackingArcSim::with_seed(vec![dss65_madrid.clone()], traj.clone(), configs, 0).unwrap();
+    let arc: TrackingArc<RangeDoppler> = arc_sim.generate_measurements(cosm.clone()).unwrap();
+
+    let initial_estimate = KfEstimate::disperse_from_diag(
+        initial_state,
+        &[
+            (StateParameter::SMA, 0.5),
+            (StateParameter::Inclination, 0.01),
+        ],
+        Some(0),
+    );
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+    let prop_est = setup.with(initial_estimate.nominal_state.with_stm());
+
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-3));
+    let kf = KF::no_snc(initial_estimate, measurement_noise);
+
+    let mut odp = ODProcess::ckf(prop_est, kf, Some(FltResid::default()), cosm).with_replay();
+
+    let mut devices = HashMap::new();
+    devices.insert(dss65_madrid.name.clone(), dss65_madrid);
+
+    odp.process(
+        &arc.measurements,
+        &mut devices,
+        arc.min_duration_sep().unwrap(),
+    )
+    .unwrap();
+    odp.predict_for(1.minutes(), false, 10.minutes()).unwrap();
+
+    let replay = odp.replay.clone().unwrap();
+    assert_eq!(replay.len(), odp.estimates.len());
+
+    let num_msr = odp.residuals.iter().flatten().count();
+    assert!(num_msr > 10);
+    assert_eq!(
+        replay
+            .steps
+            .iter()
+            .filter(|step| step.is_measurement_update())
+            .count(),
+        num_msr
+    );
+
+    for (step, (estimate, residual)) in replay
+        .steps
+        .iter()
+        .zip(odp.estimates.iter().zip(odp.residuals.iter()))
+    {
+        assert_eq!(step.epoch, estimate.epoch());
+        assert_eq!(
+            step.state_deviation.as_slice(),
+            estimate.state_deviation.as_slice()
+        );
+        assert_eq!(step.covar.as_slice(), estimate.covar.as_slice());
+        assert_eq!(residual.is_some(), step.is_measurement_update());
+
+        if let (Some(msr), Some(residual)) = (&step.measurement, residual) {
+            assert_eq!(msr.device, "Madrid");
+            assert_eq!(msr.prefit.as_slice(), residual.prefit.as_slice());
+            assert_eq!(msr.real_obs.clone() - msr.computed_obs.clone(), msr.prefit);
+            if msr.rejected {
+                assert!(estimate.predicted);
+                continue;
+            }
+            // The update of the state deviation is the gain times the postfit residual of the prior deviation
+            let update = &msr.gain * (&msr.prefit - &msr.h_tilde * &step.prior_deviation);
+            assert!(
+                (update - step.update()).norm() <= 1e-9 * step.update().norm(),
+                "{step}"
+            );
+            // And the covariance follows the Joseph update with that gain
+            let i_kh = DMatrix::identity(6, 6) - &msr.gain * &msr.h_tilde;
+            let covar = &i_kh * &step.covar_bar * i_kh.transpose()
+                + &msr.gain * &msr.noise * msr.gain.transpose();
+            assert!(
+                (covar - &step.covar).norm() <= 1e-9 * step.covar.norm(),
+                "{step}"
+            );
+            assert!(step.innovation_covar().is_some());
+        } else {
+            assert_eq!(step.update().norm(), 0.0);
+            assert!(step.innovation_covar().is_none());
+        }
+    }
+
+    // Step through the replay
+    let mut cursor = replay.cursor();
+    assert_eq!(cursor.index(), 0);
+    assert!(cursor.step_back().is_none());
+    let first_msr = cursor.next_measurement().unwrap();
+    assert!(first_msr.is_measurement_update());
+    assert!(cursor.step_back().unwrap().epoch < first_msr.epoch);
+    assert_eq!(cursor.step_forward().unwrap(), first_msr);
+    let second_msr = cursor.next_measurement().unwrap();
+    assert!(second_msr.epoch > first_msr.epoch);
+    println!("{second_msr}");
+
+    let mid = arc.measurements[arc.measurements.len() / 2].1.epoch();
+    assert_eq!(cursor.seek(mid).unwrap().epoch, mid);
+    assert!(cursor.seek(epoch - 1.seconds()).is_none());
+    assert_eq!(cursor.current().unwrap().epoch, mid);
+
+    // The prediction at the end only has time updates
+    cursor.seek(replay.steps.last().unwrap().epoch);
+    assert!(cursor.next_measurement().is_none());
+    assert_eq!(cursor.index(), replay.len() - 1);
+    assert!(cursor.step_forward().is_none());
+
+    // Stored and loaded back exactly
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "od_replay.parquet",
+    ]
+    .iter()
+    .collect();
+    replay.to_parquet(&path).unwrap();
+    let loaded = FilterReplay::from_parquet(&path).unwrap();
+    assert_eq!(loaded, replay);
+
+    assert!(FilterReplay::default().to_parquet(&path).is_err());
+}
@@ -0,0 +1,46 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::Cosm;
+use nyx::io::ConfigRepr;
+use nyx::od::scenario::{simulate_od_scenario, ODScenario};
+use std::path::PathBuf;
+
+#[test]
+fn od_scenario_from_yaml() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "data",
+        "tests",
+        "config",
+        "od_scenario.yaml",
+    ]
+    .iter()
+    .collect();
+
+    let mut scenario = ODScenario::load(path).unwrap();
+    scenario.output_dir = Some([env!("CARGO_MANIFEST_DIR"), "output_data"].iter().collect());
+
+    let results = simulate_od_scenario(scenario, cosm).unwrap();
+
+    println!("{}", results.arc);
+    assert!(results.arc.measurements.len() > 50);
+    assert_eq!(results.estimates.len(), results.residuals.len());
+    assert_eq!(
+        results.smoothed.as_ref().unwrap().len(),
+        results.estimates.len()
+    );
+    assert_eq!(results.exported.len(), 4);
+    for path in &results.exported {
+        assert!(path.exists(), "{} not exported", path.display());
+    }
+
+    let (pos_err_km, vel_err_km_s) = results.final_errors().unwrap();
+    println!("final errors: {pos_err_km:.3e} km\t{vel_err_km_s:.3e} km/s");
+    // The initial estimate is 500 m off in semi-major axis
+    assert!(pos_err_km < 0.1, "final position error too large");
+    assert!(vel_err_km_s < 1e-4, "final velocity error too large");
+}
@@ -123,3 +123,98 @@ fn continuous_tracking() {
     // Check that we've copied over the device configurations as well
     assert_eq!(arc_concrete.device_cfg, arc.device_cfg);
 }
+
+#[test]
+fn visibility_intervals() {
+    // Test that the visibility intervals are consistent with the elevation and shared with the measurement generation
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+
+    let orbit = Orbit::keplerian_altitude(
+        500.0,
+        1e-3,
+        30.0,
+        45.0,
+        75.0,
+        23.4,
+        Epoch::from_str("2023-02-22T19:18:17.16 UTC").unwrap(),
+        cosm.frame("EME2000"),
+    );
+
+    let (_, trajectory) = Propagator::default(OrbitalDynamics::two_body())
+        .with(orbit)
+        .for_duration_with_traj(1.5.days())
+        .unwrap();
+
+    let ground_station_file: PathBuf = [
+        &env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "data",
+        "tests",
+        "config",
+        "many_ground_stations.yaml",
+    ]
+    .iter()
+    .collect();
+
+    let devices = GroundStation::load_many(ground_station_file).unwrap();
+
+    // Check the intervals computed directly from a ground station against its elevation
+    let station = &devices[0];
+    let visibility = station
+        .visibility_intervals(&trajectory, 1.minutes(), &cosm)
+        .unwrap();
+    println!("{visibility}");
+
+    assert!(!visibility.is_empty());
+    for pass in &visibility.intervals {
+        assert!(pass.start < pass.max_elevation_epoch && pass.max_elevation_epoch < pass.end);
+        assert!(pass.max_elevation_deg >= station.elevation_mask_deg);
+        // The bounds are refined to the non-visible side of the crossing, within one millisecond
+        for (epoch, inside) in [
+            (pass.start, pass.start + 1.milliseconds()),
+            (pass.end, pass.end - 1.milliseconds()),
+        ] {
+            if epoch == trajectory.first().epoch() || epoch == trajectory.last().epoch() {
+                continue;
+            }
+            let (_, el, _, _) = station.azimuth_elevation_of(trajectory.at(epoch).unwrap(), &cosm);
+            assert!(el < station.elevation_mask_deg, "{el} deg at {epoch}");
+            let (_, el, _, _) = station.azimuth_elevation_of(trajectory.at(inside).unwrap(), &cosm);
+            assert!(el >= station.elevation_mask_deg, "{el} deg at {inside}");
+        }
+    }
+    assert!(!visibility.contains(trajectory.first().epoch() - 1.minutes()));
+
+    let trkconfg_yaml: PathBuf = [
+        &env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "data",
+        "tests",
+        "config",
+        "tracking_cfg.yaml",
+    ]
+    .iter()
+    .collect();
+
+    let configs: HashMap<String, TrkConfig> = TrkConfig::load_named(trkconfg_yaml).unwrap();
+
+    let mut trk =
+        TrackingArcSim::<Orbit, RangeDoppler, _>::with_seed(devices, trajectory, configs, 12345)
+            .unwrap();
+
+    let visibility = trk.visibility(cosm.clone()).unwrap().clone();
+    assert_eq!(visibility.len(), trk.devices.len());
+
+    let arc = trk.generate_measurements(cosm).unwrap();
+    // Same number of measurements as when evaluating the elevation at every epoch
+    assert_eq!(arc.measurements.len(), 146);
+
+    // Every measurement is within a visibility interval of its device
+    for (name, msr) in &arc.measurements {
+        assert!(
+            visibility[name].contains(msr.epoch()),
+            "{name} measurement at {} outside of visibility",
+            msr.epoch()
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use pretty_env_logger::try_init;
+
+use nyx_space::cosmic::{Cosm, Orbit};
+use nyx_space::dynamics::orbital::OrbitalDynamics;
+use nyx_space::io::ExportCfg;
+use nyx_space::linalg::{Matrix2, Matrix6, Vector2, Vector6};
+use nyx_space::md::trajectory::Traj;
+use nyx_space::od::noise::GaussMarkov;
+use nyx_space::od::prelude::*;
+use nyx_space::propagators::{PropOpts, Propagator, RK4Fixed};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::PathBuf;
+
+/// Simulates a tracking arc of a two-body orbit from two stations with white noise only
+fn tracking_arc(cosm: std::sync::Arc<Cosm>) -> (Traj<Orbit>, TrackingArc<RangeDoppler>) {
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+    let (_, traj) = setup
+        .with(initial_state)
+        .for_duration_with_traj(12.hours())
+        .unwrap();
+
+    let stations = vec![
+        GroundStation::dss65_madrid(
+            0.0,
+            GaussMarkov::white_noise(1e-3),
+            GaussMarkov::white_noise(1e-6),
+            iau_earth,
+        ),
+        GroundStation::dss34_canberra(
+            0.0,
+            GaussMarkov::white_noise(1e-3),
+            GaussMarkov::white_noise(1e-6),
+            iau_earth,
+        ),
+    ];
+
+    let mut configs = HashMap::new();
+    for station in &stations {
+        configs.insert(
+            station.name.clone(),
+            TrkConfig::from_sample_rate(1.minutes()),
+        );
+    }
+
+    let mut arc_sim = TrackingArcSim::with_seed(stations, traj.clone(), configs, 0).unwrap();
+    arc_sim.disallow_overlap();
+    let arc = arc_sim.generate_measurements(cosm).unwrap();
+
+    (traj, arc)
+}
+
+#[test]
+fn od_rts_smoother() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let (traj, arc) = tracking_arc(cosm.clone());
+    let initial_state = *traj.first();
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+
+    // Start half a kilometer off
+    let mut nominal = initial_state.with_stm();
+    nominal.x_km += 0.5;
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(1.0, 1.0, 1.0, 1e-6, 1e-6, 1e-6));
+    let initial_estimate = KfEstimate::from_covar(nominal, init_covar);
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-12));
+    let sncs = vec![SNC3::from_diagonal(2.minutes(), &[1e-16; 3])];
+
+    let mut odp = ODProcess::ckf(
+        setup.with(nominal),
+        KF::with_sncs(initial_estimate, sncs, measurement_noise),
+        None,
+        cosm.clone(),
+    );
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+
+    let smoothed = odp.smooth_rts().unwrap();
+    let num = odp.estimates.len();
+    assert_eq!(smoothed.estimates.len(), num);
+    assert_eq!(smoothed.consistency.len(), num);
+    assert_eq!(smoothed.cross_covariances.len(), num - 1);
+
+    // The last estimate is not changed
+    assert_eq!(smoothed.estimates[num - 1], odp.estimates[num - 1]);
+
+    // The smoothed initial estimate uses all of the measurements
+    let truth = traj.at(smoothed.estimates[0].epoch()).unwrap();
+    let flt_err_km = (odp.estimates[0].state().radius() - truth.radius()).norm();
+    let sm_err_km = (smoothed.estimates[0].state().radius() - truth.radius()).norm();
+    println!("initial position error: filter {flt_err_km:.3e} km\tsmoother {sm_err_km:.3e} km");
+    assert!(sm_err_km < 0.1 * flt_err_km);
+    let sm_sigma_km = smoothed.estimates[0]
+        .covar
+        .diagonal()
+        .fixed_rows::<3>(0)
+        .sum()
+        .sqrt();
+    println!("smoothed position sigma: {sm_sigma_km:.3e} km");
+    assert!(sm_err_km < 3.0 * sm_sigma_km);
+
+    // The smoothed errors are within the smoothed covariance, and the covariance never grows with smoothing
+    let mut num_outside = 0;
+    for (flt_est, sm_est) in odp.estimates.iter().zip(&smoothed.estimates) {
+        let truth = traj.at(sm_est.epoch()).unwrap();
+        let err = truth.to_cartesian_vec() - sm_est.state().to_cartesian_vec();
+        for i in 0..6 {
+            assert!(sm_est.covar[(i, i)] > 0.0);
+            assert!(sm_est.covar[(i, i)] <= flt_est.covar[(i, i)] * (1.0 + 1e-9));
+            if err[i].abs() > 3.0 * sm_est.covar[(i, i)].sqrt() {
+                num_outside += 1;
+            }
+        }
+    }
+    println!("{num_outside} smoothed components outside of 3 sigma");
+    assert!(num_outside * 100 < 6 * num);
+
+    // The filter is consistent with the smoother
+    let num_inconsistent = smoothed.num_inconsistent(3.0);
+    println!(
+        "{num_inconsistent} / {num} inconsistent estimates, max consistency of {:.3}",
+        smoothed.max_consistency()
+    );
+    assert!(num_inconsistent * 100 < num);
+
+    // The cross-covariance is bounded by the smoothed covariances, up to the numerical precision since consecutive
+    // estimates are almost fully correlated
+    for (k, cross) in smoothed.cross_covariances.iter().enumerate() {
+        for i in 0..6 {
+            let bound = (smoothed.estimates[k].covar[(i, i)]
+                * smoothed.estimates[k + 1].covar[(i, i)])
+                .sqrt();
+            assert!(cross[(i, i)].abs() <= bound * (1.0 + 1e-4));
+        }
+    }
+
+    // Exported with the same layout as the filter results and the consistency statistics
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "od_smoother.parquet",
+    ]
+    .iter()
+    .collect();
+    let flt_path = odp
+        .to_parquet(
+            path.with_file_name("od_smoother_filter.parquet"),
+            ExportCfg::default(),
+        )
+        .unwrap();
+    let sm_path = odp
+        .smoother_to_parquet(&smoothed, &path, ExportCfg::default())
+        .unwrap();
+
+    let columns_of = |path: &PathBuf| {
+        ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+            .unwrap()
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<String>>()
+    };
+    let flt_columns = columns_of(&flt_path);
+    let sm_columns = columns_of(&sm_path);
+    assert_eq!(sm_columns.len(), flt_columns.len() + 6);
+    assert_eq!(sm_columns[..flt_columns.len()], flt_columns[..]);
+    assert_eq!(sm_columns.last().unwrap(), "Consistency Vz");
+}
+
+#[test]
+fn od_rts_smoother_ekf() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let (traj, arc) = tracking_arc(cosm.clone());
+    let initial_state = *traj.first();
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+
+    let mut nominal = initial_state.with_stm();
+    nominal.x_km += 0.5;
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(1.0, 1.0, 1.0, 1e-6, 1e-6, 1e-6));
+    let initial_estimate = KfEstimate::from_covar(nominal, init_covar);
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-12));
+
+    // The reference trajectory is updated with each estimate once the EKF is enabled
+    let mut odp = ODProcess::ekf(
+        setup.with(nominal),
+        KF::no_snc(initial_estimate, measurement_noise),
+        EkfTrigger::new(30, 1.hours()),
+        None,
+        cosm.clone(),
+    );
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+
+    let smoothed = odp.smooth_rts().unwrap();
+
+    let truth = traj.at(smoothed.estimates[0].epoch()).unwrap();
+    let flt_err_km = (odp.estimates[0].state().radius() - truth.radius()).norm();
+    let sm_err_km = (smoothed.estimates[0].state().radius() - truth.radius()).norm();
+    println!("initial position error: filter {flt_err_km:.3e} km\tsmoother {sm_err_km:.3e} km");
+    assert!(sm_err_km < 0.1 * flt_err_km);
+
+    // Without process noise, every smoothed estimate is the same trajectory
+    for sm_est in &smoothed.estimates {
+        let truth = traj.at(sm_est.epoch()).unwrap();
+        assert!((sm_est.state().radius() - truth.radius()).norm() < 1e-2);
+    }
+
+    // Smoothing requires the estimates of the process
+    odp.estimates.pop();
+    assert!(odp.smooth_rts().is_err());
+}
@@ -0,0 +1,93 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit, Spacecraft};
+use nyx::linalg::{Matrix6, OMatrix, U9};
+use nyx::md::StateParameter;
+use nyx::od::prelude::*;
+use nyx::time::{Epoch, TimeUnits};
+use std::env;
+use std::path::PathBuf;
+
+#[test]
+fn nav_snapshot_roundtrip() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let epoch = Epoch::from_gregorian_tai_at_noon(2023, 3, 14);
+    let orbit = Orbit::keplerian_altitude(500.0, 1e-3, 30.0, 45.0, 75.0, 23.4, epoch, eme2k);
+    let sc = Spacecraft::new(orbit, 100.0, 25.0, 2.0, 1.5, 1.6, 2.1);
+
+    // Build a symmetric covariance with correlations between all of the parameters
+    let mut covar = OMatrix::<f64, U9, U9>::zeros();
+    for i in 0..9 {
+        for j in 0..9 {
+            covar[(i, j)] = if i == j {
+                (i + 1) as f64 * 1e-2
+            } else {
+                1e-5 * (i + j) as f64
+            };
+        }
+    }
+
+    let sc_snapshot = NavSnapshot::from_spacecraft_estimate(&KfEstimate::from_covar(sc, covar));
+    println!("{sc_snapshot}");
+
+    let orbit_snapshot =
+        NavSnapshot::from_orbit_estimate(&KfEstimate::from_covar(orbit, Matrix6::identity()));
+
+    let path: PathBuf = [
+        &env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "output_data",
+        "nav_snapshot.parquet",
+    ]
+    .iter()
+    .collect();
+
+    // Several snapshots in a single file
+    let later = NavSnapshot {
+        state: sc.with_orbit(orbit.at_epoch(epoch + 1.hours()).unwrap()),
+        ..sc_snapshot.clone()
+    };
+    NavSnapshot::many_to_parquet(&[sc_snapshot.clone(), later.clone()], &path).unwrap();
+
+    let loaded = NavSnapshot::many_from_parquet(&path, &cosm).unwrap();
+    assert_eq!(loaded.len(), 2);
+
+    for (expected, snapshot) in [&sc_snapshot, &later].iter().zip(&loaded) {
+        assert_eq!(snapshot.epoch(), expected.epoch());
+        assert_eq!(snapshot.params, expected.params);
+        assert_eq!(snapshot.covar, expected.covar);
+        assert_eq!(snapshot.state.orbit, expected.state.orbit);
+        assert_eq!(snapshot.state.dry_mass_kg, expected.state.dry_mass_kg);
+        assert_eq!(snapshot.state.fuel_mass_kg, expected.state.fuel_mass_kg);
+        assert_eq!(snapshot.state.srp, expected.state.srp);
+        assert_eq!(snapshot.state.drag, expected.state.drag);
+    }
+
+    // Converting back to an estimate preserves the covariance
+    let estimate = loaded[0].to_spacecraft_estimate();
+    assert_eq!(estimate.covar(), covar);
+    assert_eq!(
+        loaded[0].sigma(StateParameter::Cr).unwrap(),
+        covar[(6, 6)].sqrt()
+    );
+
+    // An orbit snapshot only estimates the Cartesian state
+    orbit_snapshot.to_parquet(&path).unwrap();
+    let loaded = NavSnapshot::from_parquet(&path, &cosm).unwrap();
+    assert_eq!(loaded.params.len(), 6);
+    assert_eq!(loaded.orbit_covar().unwrap(), Matrix6::identity());
+    assert!(loaded.sigma(StateParameter::Cr).is_err());
+
+    let estimate = loaded.to_spacecraft_estimate();
+    assert_eq!(estimate.covar()[(6, 6)], 0.0);
+
+    // Snapshots in different frames cannot be mixed in a single file
+    let other = NavSnapshot {
+        state: sc.with_orbit(cosm.frame_chg(&orbit, cosm.frame("Moon J2000"))),
+        ..sc_snapshot.clone()
+    };
+    assert!(NavSnapshot::many_to_parquet(&[sc_snapshot, other], &path).is_err());
+}
@@ -0,0 +1,151 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::eclipse::{line_of_sight, EclipseState};
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::linalg::{Matrix2, Matrix6, Vector2, Vector6};
+use nyx::od::noise::GaussMarkov;
+use nyx::od::prelude::*;
+use nyx::propagators::{PropOpts, Propagator, RK4Fixed};
+use std::collections::HashMap;
+
+#[test]
+fn od_spacecraft_tracker_crosslink() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let earth = cosm.frame("IAU Earth");
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10 * Unit::Second),
+    );
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let relay = Orbit::keplerian(42_164.0, 0.0, 0.1, 0.0, 0.0, 0.0, dt, eme2k);
+    let user = Orbit::keplerian(7_000.0, 0.001, 51.6, 30.0, 0.0, 90.0, dt, eme2k);
+
+    let (_, relay_traj) = setup
+        .with(relay)
+        .for_duration_with_traj(7 * Unit::Hour)
+        .unwrap();
+    let (_, traj) = setup
+        .with(user)
+        .for_duration_with_traj(6 * Unit::Hour)
+        .unwrap();
+
+    let mut tracker = SpacecraftTracker::with_occultation(
+        "GEO relay".to_string(),
+        relay_traj.clone(),
+        vec![earth],
+        GaussMarkov::white_noise(1e-3),
+        GaussMarkov::white_noise(1e-6),
+    );
+    println!("{tracker}");
+
+    // The link is occulted exactly when the Earth blocks the line of sight
+    let mut occulted = 0;
+    let mut epoch = dt;
+    while epoch < dt + 6 * Unit::Hour {
+        let rx = traj.at(epoch).unwrap();
+        let tx = relay_traj.at(epoch).unwrap();
+        let msr = tracker.measure(epoch, &traj, None, cosm.clone()).unwrap();
+        match line_of_sight(&tx, &rx, earth, &cosm) {
+            EclipseState::Visibilis => {
+                let msr = msr.expect("link should be available");
+                let range_km = (tx.radius() - rx.radius()).norm();
+                assert!((msr.obs[0] - range_km).abs() < 1e-9);
+                let fd_doppler_km_s = ((relay_traj.at(epoch + Unit::Second).unwrap().radius()
+                    - traj.at(epoch + Unit::Second).unwrap().radius())
+                .norm()
+                    - (relay_traj.at(epoch - Unit::Second).unwrap().radius()
+                        - traj.at(epoch - Unit::Second).unwrap().radius())
+                    .norm())
+                    / 2.0;
+                assert!((msr.obs[1] - fd_doppler_km_s).abs() < 1e-5);
+            }
+            _ => {
+                assert!(msr.is_none(), "{epoch} should be occulted");
+                occulted += 1;
+            }
+        }
+        epoch += 5 * Unit::Minute;
+    }
+    println!("{occulted} occulted samples");
+    assert!(occulted > 0);
+
+    // The visibility intervals match the line of sight
+    let visibility = tracker
+        .visibility_intervals(&traj, 1 * Unit::Minute, &cosm)
+        .unwrap();
+    println!("{visibility:?}");
+    assert!(visibility.intervals.len() > 1);
+    for interval in &visibility.intervals {
+        let mid = interval.start + interval.duration() * 0.5;
+        assert!(tracker
+            .measure(mid, &traj, None, cosm.clone())
+            .unwrap()
+            .is_some());
+    }
+
+    // A higher grazing altitude shortens the passes
+    let mut grazing = tracker.clone();
+    grazing.grazing_altitude_km = 100.0;
+    let grazing_visibility = grazing
+        .visibility_intervals(&traj, 1 * Unit::Minute, &cosm)
+        .unwrap();
+    let total = |vis: &Visibility| {
+        vis.intervals
+            .iter()
+            .map(|interval| interval.duration().to_seconds())
+            .sum::<f64>()
+    };
+    assert!(total(&grazing_visibility) < total(&visibility));
+
+    // The tracker is serialized with its ephemeris
+    let tracking_arc_cfg = serde_yaml::to_string(&tracker).unwrap();
+    let rebuilt: SpacecraftTracker = serde_yaml::from_str(&tracking_arc_cfg).unwrap();
+    assert_eq!(rebuilt.ephemeris.states.len(), relay_traj.states.len());
+    assert_eq!(rebuilt.occulting_bodies, vec![earth]);
+
+    // Navigate the user from the crosslink only
+    let mut configs = HashMap::new();
+    configs.insert(
+        tracker.name.clone(),
+        TrkConfig::from_sample_rate(1 * Unit::Minute),
+    );
+    let mut arc_sim = TrackingArcSim::with_seed(vec![tracker], traj.clone(), configs, 0).unwrap();
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+    println!("{arc}");
+    assert!(!arc.measurements.is_empty());
+
+    let dispersed = user + Vector6::new(0.5, -0.3, 0.2, 1e-4, 0.0, -1e-4);
+    let initial_estimate = KfEstimate::from_covar(
+        dispersed,
+        Matrix6::from_diagonal(&Vector6::new(1.0, 1.0, 1.0, 1e-6, 1e-6, 1e-6)),
+    );
+    let kf = KF::no_snc(
+        initial_estimate,
+        Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-12)),
+    );
+    let mut odp = ODProcess::ekf(
+        setup.with(dispersed.with_stm()),
+        kf,
+        EkfTrigger::new(20, 1 * Unit::Hour),
+        None,
+        cosm,
+    );
+    odp.process_arc::<SpacecraftTracker>(&arc).unwrap();
+
+    let est = odp.estimates.last().unwrap();
+    println!("Final estimate:\n{est}");
+    let truth = traj.at(est.epoch()).unwrap();
+    let err = est.state().to_cartesian_vec() - truth.to_cartesian_vec();
+    let err_km = err.fixed_rows::<3>(0).norm();
+    println!("position error: {err_km:.3e} km");
+    for i in 0..6 {
+        assert!(err[i].abs() < 3.0 * est.covar[(i, i)].sqrt());
+    }
+    assert!(err_km < 0.05);
+}
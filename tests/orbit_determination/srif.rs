@@ -0,0 +1,233 @@
+use pretty_env_logger::try_init;
+
+use nyx_space::cosmic::{Cosm, Orbit};
+use nyx_space::dynamics::orbital::OrbitalDynamics;
+use nyx_space::linalg::{Matrix2, Matrix6, Vector2, Vector6};
+use nyx_space::md::trajectory::Traj;
+use nyx_space::od::noise::GaussMarkov;
+use nyx_space::od::prelude::*;
+use nyx_space::propagators::{PropOpts, Propagator, RK4Fixed};
+use std::collections::HashMap;
+
+/// Simulates a tracking arc of a two-body orbit from two stations with white noise only
+fn tracking_arc(
+    cosm: std::sync::Arc<Cosm>,
+    duration: Duration,
+    range_sigma_km: f64,
+    doppler_sigma_km_s: f64,
+) -> (Traj<Orbit>, TrackingArc<RangeDoppler>) {
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+    let (_, traj) = setup
+        .with(initial_state)
+        .for_duration_with_traj(duration)
+        .unwrap();
+
+    let stations = vec![
+        GroundStation::dss65_madrid(
+            0.0,
+            GaussMarkov::white_noise(range_sigma_km),
+            GaussMarkov::white_noise(doppler_sigma_km_s),
+            iau_earth,
+        ),
+        GroundStation::dss34_canberra(
+            0.0,
+            GaussMarkov::white_noise(range_sigma_km),
+            GaussMarkov::white_noise(doppler_sigma_km_s),
+            iau_earth,
+        ),
+    ];
+
+    let mut configs = HashMap::new();
+    for station in &stations {
+        configs.insert(
+            station.name.clone(),
+            TrkConfig::from_sample_rate(1.minutes()),
+        );
+    }
+
+    let mut arc_sim = TrackingArcSim::with_seed(stations, traj.clone(), configs, 0).unwrap();
+    arc_sim.disallow_overlap();
+    let arc = arc_sim.generate_measurements(cosm).unwrap();
+
+    (traj, arc)
+}
+
+#[test]
+fn od_srif_matches_kf() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    let (traj, arc) = tracking_arc(cosm.clone(), 12.hours(), 1e-3, 1e-6);
+    let initial_state = *traj.first();
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+
+    // Start half a kilometer off
+    let mut nominal = initial_state.with_stm();
+    nominal.x_km += 0.5;
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(1.0, 1.0, 1.0, 1e-6, 1e-6, 1e-6));
+    let initial_estimate = KfEstimate::from_covar(nominal, init_covar);
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-12));
+    let sncs = vec![SNC3::from_diagonal(2.minutes(), &[1e-18; 3])];
+
+    let mut odp_kf = ODProcess::ckf(
+        setup.with(nominal),
+        KF::with_sncs(initial_estimate, sncs.clone(), measurement_noise),
+        None,
+        cosm.clone(),
+    );
+    odp_kf.process_arc::<GroundStation>(&arc).unwrap();
+
+    let mut odp_srif = ODProcess::ckf(
+        setup.with(nominal),
+        SRIF::with_sncs(initial_estimate, sncs, measurement_noise),
+        None,
+        cosm.clone(),
+    );
+    odp_srif.process_arc::<GroundStation>(&arc).unwrap();
+
+    assert_eq!(odp_kf.estimates.len(), odp_srif.estimates.len());
+
+    let kf_est = odp_kf.estimates.last().unwrap();
+    let srif_est = odp_srif.estimates.last().unwrap();
+    println!("KF:\n{kf_est}\nSRIF:\n{srif_est}");
+
+    let truth = traj.at(srif_est.epoch()).unwrap();
+    let kf_err_km = (kf_est.state().radius() - truth.radius()).norm();
+    let srif_err_km = (srif_est.state().radius() - truth.radius()).norm();
+    println!("final error: KF {kf_err_km:.3e} km\tSRIF {srif_err_km:.3e} km");
+    assert!(srif_err_km < 0.01);
+
+    // Both filters agree on the estimate and its covariance
+    let state_diff_km =
+        (kf_est.state().to_cartesian_vec() - srif_est.state().to_cartesian_vec()).norm();
+    assert!(
+        state_diff_km < 1e-5,
+        "estimates differ by {state_diff_km} km"
+    );
+    for i in 0..6 {
+        let (kf_var, srif_var) = (kf_est.covar[(i, i)], srif_est.covar[(i, i)]);
+        assert!(
+            (kf_var - srif_var).abs() < 1e-3 * kf_var,
+            "variance {i} differs: {kf_var:e} vs {srif_var:e}"
+        );
+    }
+
+    // The residuals agree as well
+    for (kf_resid, srif_resid) in odp_kf.residuals.iter().zip(&odp_srif.residuals) {
+        match (kf_resid, srif_resid) {
+            (Some(kf_resid), Some(srif_resid)) => {
+                assert!((kf_resid.prefit - srif_resid.prefit).norm() < 1e-5);
+            }
+            (None, None) => {}
+            _ => panic!("time and measurement updates differ"),
+        }
+    }
+
+    // And the smoother applies to the SRIF estimates
+    let kf_smoothed = odp_kf.smooth(SmoothingArc::All).unwrap();
+    let srif_smoothed = odp_srif.smooth(SmoothingArc::All).unwrap();
+    assert_eq!(srif_smoothed.len(), odp_srif.estimates.len());
+    let first_diff_km = (kf_smoothed[0].state().to_cartesian_vec()
+        - srif_smoothed[0].state().to_cartesian_vec())
+    .norm();
+    println!("smoothed initial estimates differ by {first_diff_km:.3e} km");
+    assert!(first_diff_km < 1e-5);
+    assert!(srif_smoothed[0]
+        .covar
+        .diagonal()
+        .iter()
+        .all(|var| *var > 0.0));
+}
+
+#[test]
+fn od_srif_ill_conditioned() {
+    let _ = try_init().is_err();
+
+    let cosm = Cosm::de438();
+    // Centimeter ranging and micrometer per second Doppler
+    let (traj, arc) = tracking_arc(cosm.clone(), 1.days(), 1e-5, 1e-9);
+    let initial_state = *traj.first();
+
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+
+    // A priori uncertainty of a thousand kilometers and one kilometer per second
+    let mut nominal = initial_state.with_stm();
+    nominal.y_km -= 0.1;
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(1e6, 1e6, 1e6, 1.0, 1.0, 1.0));
+    let initial_estimate = KfEstimate::from_covar(nominal, init_covar);
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-10, 1e-18));
+
+    let mut odp_kf = ODProcess::ckf(
+        setup.with(nominal),
+        KF::no_snc(initial_estimate, measurement_noise),
+        None,
+        cosm.clone(),
+    );
+    odp_kf.process_arc::<GroundStation>(&arc).unwrap();
+
+    let mut odp = ODProcess::ckf(
+        setup.with(nominal),
+        SRIF::no_snc(initial_estimate, measurement_noise),
+        None,
+        cosm.clone(),
+    );
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+
+    let num_not_pd = |estimates: &[KfEstimate<Orbit>]| {
+        estimates
+            .iter()
+            .filter(|est| est.covar.cholesky().is_none())
+            .count()
+    };
+    println!(
+        "covariances not positive definite: KF {} / {}\tSRIF {} / {}",
+        num_not_pd(&odp_kf.estimates),
+        odp_kf.estimates.len(),
+        num_not_pd(&odp.estimates),
+        odp.estimates.len()
+    );
+
+    // Only the first few covariances, before the orbit is fully observed, are at the limit of the double precision
+    assert!(num_not_pd(&odp.estimates) * 10 < num_not_pd(&odp_kf.estimates));
+
+    // Every covariance remains symmetric with a positive diagonal
+    for est in &odp.estimates {
+        let covar = est.covar;
+        assert!(
+            (covar - covar.transpose()).norm() < 1e-9 * covar.norm(),
+            "covariance not symmetric @ {}",
+            est.epoch()
+        );
+        assert!(
+            covar.diagonal().iter().all(|var| *var > 0.0),
+            "negative variance @ {}",
+            est.epoch()
+        );
+    }
+
+    let est = odp.estimates.last().unwrap();
+    let sigmas = est.covar.diagonal().map(|var| var.sqrt());
+    println!("final sigmas: {sigmas:.3e}");
+    assert!(sigmas.fixed_rows::<3>(0).norm() < 1e-3);
+    assert!(est.covar.cholesky().is_some());
+
+    let err_km = (est.state().radius() - traj.at(est.epoch()).unwrap().radius()).norm();
+    println!("final error: {err_km:.3e} km");
+    assert!(err_km < 1e-3);
+}
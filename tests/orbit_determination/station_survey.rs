@@ -0,0 +1,133 @@
+extern crate nyx_space as nyx;
+
+use hifitime::J2000_OFFSET;
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::OrbitalDynamics;
+use nyx::od::noise::GaussMarkov;
+use nyx::od::prelude::*;
+use nyx::propagators::Propagator;
+use nyx::time::{Epoch, Unit};
+
+#[test]
+fn station_geodetic_partials() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_mjd_tai(J2000_OFFSET);
+    let station = GroundStation::dss65_madrid(0.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth);
+    // A spacecraft above Madrid
+    let above = cosm.frame_chg(
+        &Orbit::from_geodesic(45.0, 10.0, 20_000.0, epoch, iau_earth),
+        eme2k,
+    );
+    let rx = Orbit::cartesian(
+        above.x_km, above.y_km, above.z_km, 1.2, -2.5, 0.7, epoch, eme2k,
+    );
+
+    let (computed, partials) = station.geodetic_partials(rx, &cosm).unwrap();
+    println!("{computed}{partials}");
+
+    // Same values as the measurement models
+    let (_, elevation_deg, rx_inertial, tx_inertial) = station.azimuth_elevation_of(rx, &cosm);
+    let msr = RangeDoppler::one_way(tx_inertial, rx_inertial, 0.0, 0.0, 0.0);
+    assert!((computed[0] - msr.obs[0]).abs() < 1e-9);
+    assert!((computed[1] - msr.obs[1]).abs() < 1e-12);
+    assert!((computed[2] - elevation_deg).abs() < 1e-9);
+    assert!(elevation_deg > 10.0);
+
+    // Same partials as central finite differences
+    for (j, step) in [1e-6, 1e-6, 1e-4].iter().enumerate() {
+        let mut plus = station.clone();
+        let mut minus = station.clone();
+        match j {
+            0 => {
+                plus.latitude_deg += step;
+                minus.latitude_deg -= step;
+            }
+            1 => {
+                plus.longitude_deg += step;
+                minus.longitude_deg -= step;
+            }
+            _ => {
+                plus.height_km += step;
+                minus.height_km -= step;
+            }
+        }
+        let (f_plus, _) = plus.geodetic_partials(rx, &cosm).unwrap();
+        let (f_minus, _) = minus.geodetic_partials(rx, &cosm).unwrap();
+        let finite_diff = (f_plus - f_minus) / (2.0 * step);
+        for i in 0..3 {
+            let err = (finite_diff[i] - partials[(i, j)]).abs();
+            assert!(
+                err < 1e-5 * partials[(i, j)].abs().max(1e-3),
+                "partial ({i}, {j}): {} != {}",
+                partials[(i, j)],
+                finite_diff[i]
+            );
+        }
+    }
+}
+
+#[test]
+fn station_survey() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let epoch = Epoch::from_mjd_tai(J2000_OFFSET);
+    let mut truth =
+        GroundStation::dss65_madrid(10.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth);
+
+    // A MEO spacecraft whose trajectory is known
+    let orbit = Orbit::keplerian(26_000.0, 0.05, 55.0, 30.0, 20.0, 0.0, epoch, eme2k);
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(orbit)
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    let msrs: Vec<RangeDoppler> = traj
+        .every(5 * Unit::Minute)
+        .filter_map(|state| {
+            TrackingDeviceSim::<Orbit, RangeDoppler>::measure_instantaneous(
+                &mut truth,
+                state,
+                None,
+                cosm.clone(),
+            )
+            .unwrap()
+        })
+        .collect();
+    println!("{} measurements", msrs.len());
+    assert!(msrs.len() > 20);
+
+    // Start from a location off by about a kilometer
+    let mut guess = truth.clone();
+    guess.latitude_deg += 0.01;
+    guess.longitude_deg -= 0.01;
+    guess.height_km += 0.5;
+
+    let (surveyed, covar) = guess.survey(&msrs, &traj, 1e-3, 1e-6, &cosm).unwrap();
+    println!("{surveyed}\n{covar:.3e}");
+
+    let lat_err_m = (surveyed.latitude_deg - truth.latitude_deg).to_radians() * 6.378e6;
+    let long_err_m = (surveyed.longitude_deg - truth.longitude_deg).to_radians() * 6.378e6;
+    let height_err_m = (surveyed.height_km - truth.height_km) * 1e3;
+    println!("errors: {lat_err_m:.3e} m\t{long_err_m:.3e} m\t{height_err_m:.3e} m");
+    assert!(lat_err_m.abs() < 1e-3);
+    assert!(long_err_m.abs() < 1e-3);
+    assert!(height_err_m.abs() < 1e-3);
+
+    // Meter level ranging and mm/s Doppler determine the site to better than a few meters
+    for i in 0..3 {
+        assert!(covar[(i, i)] > 0.0);
+    }
+    let height_sigma_m = covar[(2, 2)].sqrt() * 1e3;
+    println!("height sigma: {height_sigma_m:.3} m");
+    assert!(height_sigma_m < 5.0);
+
+    // A single measurement cannot survey a station
+    assert!(guess.survey(&msrs[..1], &traj, 1e-3, 1e-6, &cosm).is_err());
+}
@@ -0,0 +1,162 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Bodies, Cosm, LightTimeCalc};
+use nyx::od::noise::GaussMarkov;
+use nyx::od::prelude::*;
+use nyx::time::{Epoch, Unit};
+
+#[test]
+fn surface_asset_rotation() {
+    let cosm = Cosm::de438();
+    let iau_mars = cosm.frame("IAU Mars");
+    let mars_j2k = cosm.frame_from_ephem_path(&iau_mars.ephem_path());
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    // A lander at rest on Mars moves with the rotation of the body
+    let lander = SurfaceAsset::fixed("InSight".to_string(), 4.5024, 135.6234, -2.6, iau_mars);
+    println!("{lander}");
+    let fixed = lander.to_orbit(epoch);
+    assert_eq!(fixed.vmag_km_s(), 0.0);
+    let inertial = lander.state(epoch, mars_j2k, &cosm).unwrap();
+    let expected_speed_km_s = iau_mars.angular_velocity() * fixed.x_km.hypot(fixed.y_km);
+    println!(
+        "{:.6} km/s vs {expected_speed_km_s:.6} km/s",
+        inertial.vmag_km_s()
+    );
+    assert!((inertial.vmag_km_s() - expected_speed_km_s).abs() < 1e-4);
+    assert!(expected_speed_km_s > 0.2);
+    // Same velocity as the finite differences of the position
+    let plus = lander.state(epoch + Unit::Second, mars_j2k, &cosm).unwrap();
+    let minus = lander.state(epoch - Unit::Second, mars_j2k, &cosm).unwrap();
+    let fd_velocity = (plus.radius() - minus.radius()) / 2.0;
+    assert!((fd_velocity - inertial.velocity()).norm() < 1e-6);
+
+    // A rover driving north by about a kilometer in an hour
+    let traverse = vec![
+        Waypoint {
+            epoch,
+            latitude_deg: 4.5,
+            longitude_deg: 137.4,
+            height_km: -4.5,
+        },
+        Waypoint {
+            epoch: epoch + Unit::Hour,
+            latitude_deg: 4.517,
+            longitude_deg: 137.4,
+            height_km: -4.5,
+        },
+    ];
+    assert!(
+        SurfaceAsset::moving("Curiosity".to_string(), traverse[..1].to_vec(), iau_mars).is_err()
+    );
+    let rover = SurfaceAsset::moving("Curiosity".to_string(), traverse, iau_mars).unwrap();
+    println!("{rover}");
+    let mid = epoch + 30 * Unit::Minute;
+    let (location, rates) = rover.geodetic_at(mid);
+    assert!((location[0] - 4.5085).abs() < 1e-9);
+    assert!((rates[0] - 0.017 / 3600.0).abs() < 1e-15);
+    let driving = rover.to_orbit(mid);
+    println!("driving at {:.3} m/s", driving.vmag_km_s() * 1e3);
+    // About the arc length along the meridian, which is shorter than along a sphere due to the flattening of Mars
+    let spherical_km_s = 0.017_f64.to_radians() * driving.rmag_km() / 3600.0;
+    assert!((driving.vmag_km_s() / spherical_km_s - 1.0).abs() < 0.02);
+    // The rover is at rest after the traverse
+    assert_eq!(rover.to_orbit(epoch + 2 * Unit::Hour).vmag_km_s(), 0.0);
+    let plus = rover.state(mid + Unit::Second, mars_j2k, &cosm).unwrap();
+    let minus = rover.state(mid - Unit::Second, mars_j2k, &cosm).unwrap();
+    let fd_velocity = (plus.radius() - minus.radius()) / 2.0;
+    assert!((fd_velocity - rover.state(mid, mars_j2k, &cosm).unwrap().velocity()).norm() < 1e-6);
+
+    // The trajectory of the asset interpolates its states
+    let traj = rover
+        .trajectory(
+            epoch,
+            epoch + 2 * Unit::Hour,
+            5 * Unit::Minute,
+            mars_j2k,
+            &cosm,
+        )
+        .unwrap();
+    let interp = traj.at(mid + 150 * Unit::Second).unwrap();
+    let truth = rover
+        .state(mid + 150 * Unit::Second, mars_j2k, &cosm)
+        .unwrap();
+    assert!((interp.radius() - truth.radius()).norm() < 1e-5);
+    assert!(rover.survey(&[], 1e-3, 1e-6, &cosm).is_err());
+}
+
+#[test]
+fn surface_asset_doppler_survey() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+    let iau_mars = cosm.frame("IAU Mars");
+    let start = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    let lander = SurfaceAsset::fixed("InSight".to_string(), 4.5024, 135.6234, -2.6, iau_mars)
+        .with_elevation_mask(10.0);
+    let mut stations = [
+        GroundStation::dss65_madrid(10.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+        GroundStation::dss34_canberra(10.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+        GroundStation::dss13_goldstone(10.0, GaussMarkov::ZERO, GaussMarkov::ZERO, iau_earth),
+    ];
+
+    let mut tracking = Vec::new();
+    let mut min_rotation_km_s = f64::INFINITY;
+    let mut max_rotation_km_s = -f64::INFINITY;
+    let mut epoch = start;
+    while epoch < start + 3 * Unit::Day {
+        for station in stations.iter_mut() {
+            if let Some(msr) = lander.measure(station, epoch, None, cosm.clone()).unwrap() {
+                // The Doppler is the rate of change of the range, up to the consistency of the velocities of the ephemeris
+                let range_at = |epoch| {
+                    let rx = lander.state(epoch, eme2k, &cosm).unwrap();
+                    let tx = cosm.frame_chg(&station.to_orbit(epoch), eme2k);
+                    (tx.radius() - rx.radius()).norm()
+                };
+                let fd_doppler =
+                    (range_at(epoch + Unit::Second) - range_at(epoch - Unit::Second)) / 2.0;
+                assert!(
+                    (msr.obs[1] - fd_doppler).abs() < 1e-3,
+                    "{epoch}: {} != {fd_doppler}",
+                    msr.obs[1]
+                );
+                // The Doppler of the lander differs from that of the center of Mars by its rotation
+                let center = cosm.celestial_state(
+                    Bodies::MarsBarycenter.ephem_path(),
+                    epoch,
+                    eme2k,
+                    LightTimeCalc::None,
+                );
+                let tx = cosm.frame_chg(&station.to_orbit(epoch), eme2k);
+                let center_msr = RangeDoppler::one_way(tx, center, 0.0, 0.0, 0.0);
+                let rotation_km_s = msr.obs[1] - center_msr.obs[1];
+                min_rotation_km_s = min_rotation_km_s.min(rotation_km_s);
+                max_rotation_km_s = max_rotation_km_s.max(rotation_km_s);
+
+                tracking.push((station.clone(), msr));
+            }
+        }
+        epoch += 10 * Unit::Minute;
+    }
+    println!(
+        "{} measurements, rotation signature from {min_rotation_km_s:.4} to {max_rotation_km_s:.4} km/s",
+        tracking.len()
+    );
+    assert!(tracking.len() > 100);
+    assert!(max_rotation_km_s - min_rotation_km_s > 0.2);
+
+    // Survey the lander from a wrong initial location
+    let mut guess = lander.clone();
+    guess.latitude_deg += 0.05;
+    guess.longitude_deg -= 0.05;
+    guess.height_km += 2.0;
+    let (surveyed, covar) = guess.survey(&tracking, 1e-3, 1e-7, &cosm).unwrap();
+    println!("{surveyed}\n{covar:.3e}");
+    assert!((surveyed.latitude_deg - lander.latitude_deg).abs() < 1e-6);
+    assert!((surveyed.longitude_deg - lander.longitude_deg).abs() < 1e-6);
+    assert!((surveyed.height_km - lander.height_km).abs() < 1e-3);
+    assert!(covar.diagonal().iter().all(|var| *var > 0.0));
+}
@@ -0,0 +1,215 @@
+use nyx_space::io::tdm::{Tdm, TdmKeyword};
+use nyx_space::io::{ConfigRepr, ExportCfg};
+use nyx_space::md::prelude::*;
+use nyx_space::od::msr::{OpNavCentroid, RangeDoppler, RangeMsr};
+use nyx_space::od::prelude::*;
+use nyx_space::od::simulator::{TrackingArcSim, TrkConfig};
+use nyx_space::time::TimeScale;
+use rstest::*;
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+fn tdm_path(name: &str) -> PathBuf {
+    [
+        &env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "data",
+        "tests",
+        "ccsds",
+        "tdm",
+        name,
+    ]
+    .iter()
+    .collect()
+}
+
+#[test]
+fn tdm_read_xml() {
+    let _ = pretty_env_logger::try_init();
+
+    let tdm = Tdm::from_file(tdm_path("orekit_TDMExample6.xml")).unwrap();
+
+    assert_eq!(tdm.segments.len(), 1);
+    let segment = &tdm.segments[0];
+    assert_eq!(segment.tracking_device().unwrap(), "NORTH");
+    assert_eq!(segment.time_system, TimeScale::UTC);
+    assert!(segment.range_in_km());
+    assert_eq!(segment.angle_type.as_deref(), Some("AZEL"));
+
+    // Only the range observations can be converted
+    let arc = tdm.to_tracking_arc::<RangeMsr>().unwrap();
+    println!("{arc}");
+
+    assert_eq!(arc.measurements.len(), 4);
+    assert_eq!(arc.device_names().len(), 1);
+    let (device, first) = &arc.measurements[0];
+    assert_eq!(device, "NORTH");
+    assert_eq!(
+        first.epoch(),
+        Epoch::from_str("1998-06-10T00:57:37 UTC").unwrap()
+    );
+    assert_eq!(first.observation()[0], 80452.7542);
+
+    // The angles of this TDM are azimuth and elevation, which cannot be converted to a right ascension and declination
+    assert!(tdm.to_tracking_arc::<OpNavCentroid>().is_err());
+}
+
+#[test]
+fn tdm_read_errors() {
+    let _ = pretty_env_logger::try_init();
+
+    // The range is in range units, which requires the transmit frequencies to convert to kilometers
+    let tdm = Tdm::from_file(tdm_path("orekit_TDMExample4.xml")).unwrap();
+    assert!(!tdm.segments[0].range_in_km());
+    assert!(tdm.to_tracking_arc::<RangeMsr>().is_err());
+
+    for name in [
+        "orekit_TDM-metadata-timesystem-not-implemented.xml",
+        "orekit_TDM-inconsistent-time-systems.xml",
+        "orekit_TDM-missing-timesystem.xml",
+    ] {
+        assert!(
+            Tdm::from_file(tdm_path(name)).is_err(),
+            "{name} should not load"
+        );
+    }
+
+    assert!(Tdm::from_file(tdm_path("does-not-exist.tdm")).is_err());
+
+    // Observations are only parsed if they can be used, so write a KVN TDM with an invalid range
+    let path: PathBuf = [
+        &env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "output_data",
+        "tdm_number_format_error.tdm",
+    ]
+    .iter()
+    .collect();
+
+    std::fs::write(
+        &path,
+        "CCSDS_TDM_VERS = 2.0
+CREATION_DATE = 2023-02-22T19:18:17
+ORIGINATOR = NYX
+META_START
+TIME_SYSTEM = UTC
+PARTICIPANT_1 = DSS-65
+PARTICIPANT_2 = SPACECRAFT
+RANGE_UNITS = km
+META_STOP
+DATA_START
+RANGE = 2023-02-22T19:18:17.000 1.5E4
+RANGE = 2023-053T19:19:17 this-is-not-a-number
+DATA_STOP
+",
+    )
+    .unwrap();
+
+    assert!(Tdm::from_file(&path).is_err());
+}
+
+#[fixture]
+fn traj() -> Traj<Orbit> {
+    let cosm = Cosm::de438();
+
+    let orbit = Orbit::keplerian_altitude(
+        500.0,
+        1e-3,
+        30.0,
+        45.0,
+        75.0,
+        23.4,
+        Epoch::from_str("2023-02-22T19:18:17.16 UTC").unwrap(),
+        cosm.frame("EME2000"),
+    );
+
+    let (_, trajectory) = Propagator::default(OrbitalDynamics::two_body())
+        .with(orbit)
+        .for_duration_with_traj(1.5.days())
+        .unwrap();
+
+    trajectory
+}
+
+#[fixture]
+fn devices() -> Vec<GroundStation> {
+    let ground_station_file: PathBuf = [
+        &env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "data",
+        "tests",
+        "config",
+        "many_ground_stations.yaml",
+    ]
+    .iter()
+    .collect();
+
+    GroundStation::load_many(ground_station_file).unwrap()
+}
+
+#[rstest]
+fn tdm_round_trip(traj: Traj<Orbit>, devices: Vec<GroundStation>) {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+
+    let trkconfg_yaml: PathBuf = [
+        &env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "data",
+        "tests",
+        "config",
+        "tracking_cfg.yaml",
+    ]
+    .iter()
+    .collect();
+
+    let configs: HashMap<String, TrkConfig> = TrkConfig::load_named(trkconfg_yaml).unwrap();
+
+    let mut trk =
+        TrackingArcSim::<Orbit, RangeDoppler, _>::with_seed(devices, traj, configs, 12345).unwrap();
+
+    let arc = trk.generate_measurements(cosm).unwrap();
+
+    let path: PathBuf = [
+        &env::var("CARGO_MANIFEST_DIR").unwrap(),
+        "output_data",
+        "tdm_round_trip.tdm",
+    ]
+    .iter()
+    .collect();
+
+    let output_fn = arc.to_tdm_file(path, ExportCfg::default()).unwrap();
+    println!("[{}] {arc}", output_fn.to_string_lossy());
+
+    let tdm = Tdm::from_file(output_fn).unwrap();
+    assert_eq!(tdm.segments.len(), arc.device_names().len());
+    for segment in &tdm.segments {
+        assert_eq!(segment.participants[1], "SPACECRAFT");
+        assert!(segment.observations.iter().all(|obs| [
+            TdmKeyword::Range,
+            TdmKeyword::DopplerInstantaneous
+        ]
+        .contains(&obs.keyword)));
+    }
+
+    let arc_tdm = tdm.to_tracking_arc::<RangeDoppler>().unwrap();
+
+    assert_eq!(arc_tdm.measurements.len(), arc.measurements.len());
+    assert_eq!(arc_tdm.device_names(), arc.device_names());
+
+    // The measurements of the original arc may not be sorted by device at the same epoch, so compare them by device and epoch
+    let mut expected = arc.measurements.clone();
+    expected.sort_by(|(a_dev, a), (b_dev, b)| (a.epoch(), a_dev).cmp(&(b.epoch(), b_dev)));
+    let mut loaded = arc_tdm.measurements.clone();
+    loaded.sort_by(|(a_dev, a), (b_dev, b)| (a.epoch(), a_dev).cmp(&(b.epoch(), b_dev)));
+
+    for ((exp_dev, exp), (dev, msr)) in expected.iter().zip(loaded.iter()) {
+        assert_eq!(exp_dev, dev);
+        assert!(
+            (exp.epoch() - msr.epoch()).abs() < 1.microseconds(),
+            "epoch mismatch: {} vs {}",
+            exp.epoch(),
+            msr.epoch()
+        );
+        assert!((exp.observation() - msr.observation()).norm() < 1e-9);
+    }
+}
@@ -5,6 +5,7 @@ use nyx_space::od::msr::RangeDoppler;
 use nyx_space::od::prelude::*;
 use nyx_space::od::simulator::TrackingArcSim;
 use nyx_space::od::simulator::{Availability, EpochRanges, Schedule, TrkConfig};
+use nyx_space::propagators::RK4Fixed;
 use rstest::*;
 use std::collections::HashMap;
 use std::env;
@@ -359,3 +360,81 @@ fn trkconfig_cadence(traj: Traj<Orbit>, devices: Vec<GroundStation>) {
     // Regression
     assert_eq!(arc.measurements.len(), 90);
 }
+
+/// Tests that a fixed schedule generates the measurements on its own epochs, regardless of the sampling of the trajectory
+#[rstest]
+fn trkconfig_fixed_schedule(traj: Traj<Orbit>, devices: Vec<GroundStation>) {
+    let cosm = Cosm::de438();
+
+    // The trajectory starts at 19:18:17.16 UTC, but the measurements are on whole minutes
+    let reference = Epoch::from_str("2023-02-22T00:00:00 UTC").unwrap();
+    let doppler_cfg = TrkConfig::from_fixed_schedule(1.minutes(), reference);
+    let range_cfg = TrkConfig::from_fixed_schedule(10.minutes(), reference);
+
+    let serialized = serde_yaml::to_string(&range_cfg).unwrap();
+    println!("{serialized}");
+    let deserd: TrkConfig = serde_yaml::from_str(&serialized).unwrap();
+    assert_eq!(deserd, range_cfg);
+
+    let mut configs = HashMap::new();
+    configs.insert(devices[0].name.clone(), doppler_cfg);
+    configs.insert(devices[1].name.clone(), range_cfg);
+
+    let simulate = |traj: Traj<Orbit>| {
+        let mut trk = TrackingArcSim::<Orbit, RangeDoppler, _>::with_seed(
+            devices[..2].to_vec(),
+            traj,
+            configs.clone(),
+            0,
+        )
+        .unwrap();
+        trk.allow_overlap();
+        println!("{trk}");
+        trk.generate_measurements(cosm.clone()).unwrap()
+    };
+
+    let arc = simulate(traj.clone());
+    println!("{arc}");
+
+    for (name, sampling) in [
+        (&devices[0].name, 1.minutes()),
+        (&devices[1].name, 10.minutes()),
+    ] {
+        let epochs: Vec<Epoch> = arc
+            .measurements
+            .iter()
+            .filter(|(device, _)| device == name)
+            .map(|(_, msr)| msr.epoch())
+            .collect();
+        assert!(!epochs.is_empty(), "no measurement from {name}");
+        for epoch in &epochs {
+            let offset = *epoch - reference;
+            assert_eq!(
+                offset.total_nanoseconds() % sampling.total_nanoseconds(),
+                0,
+                "{name} measurement @ {epoch} not on its schedule"
+            );
+        }
+        for pair in epochs.windows(2) {
+            assert!(pair[1] - pair[0] >= sampling);
+        }
+    }
+
+    // The same orbit integrated with a fixed step yields the same schedule
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(7.seconds()),
+    );
+    let (_, fixed_step_traj) = setup
+        .with(*traj.first())
+        .until_epoch_with_traj(traj.last().epoch())
+        .unwrap();
+    let fixed_step_arc = simulate(fixed_step_traj);
+    assert_eq!(fixed_step_arc.measurements.len(), arc.measurements.len());
+    for ((name, msr), (fixed_step_name, fixed_step_msr)) in
+        arc.measurements.iter().zip(&fixed_step_arc.measurements)
+    {
+        assert_eq!(name, fixed_step_name);
+        assert_eq!(msr.epoch(), fixed_step_msr.epoch());
+    }
+}
@@ -192,10 +192,14 @@ fn xhat_dev_test_ekf_two_body() {
         sm_err_p * 1e3
     );
 
+    // The initial velocity error is zero and the smoother nearly recovers it (0.24 m/s), whereas the final velocity error
+    // (0.48 m/s) is limited by the short arc, so bound the final velocity error instead. The former check against the
+    // smoothed initial velocity error only held while the stations moved at twice the rotation velocity of the Earth, which
+    // left a smoothed initial velocity error of 1.08 m/s.
     let vmag_err = (final_truth_state - est.state()).vmag_km_s();
     assert!(
-        vmag_err < sm_err_v,
-        "final velocity error ({:.3} m) should be better than initial state error ({:.3} m)",
+        vmag_err < 1e-3,
+        "final velocity error ({:.3} m/s) should be below a meter per second (smoothed initial error: {:.3} m/s)",
         vmag_err * 1e3,
         sm_err_v * 1e3
     );
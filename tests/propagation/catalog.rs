@@ -0,0 +1,76 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::OrbitalDynamics;
+use nyx::io::catalog::{CatalogEntry, StateCatalog};
+use nyx::io::ExportCfg;
+use nyx::propagators::*;
+use nyx::time::{Epoch, TimeUnits};
+use std::path::PathBuf;
+
+#[test]
+fn catalog_propagate_roundtrip() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let epoch = Epoch::from_gregorian_utc_at_midnight(2020, 1, 1);
+
+    // Build a small constellation with objects at different epochs
+    let mut catalog = StateCatalog::default();
+    for i in 0..24 {
+        catalog.entries.push(CatalogEntry {
+            name: format!("SAT-{i:02}"),
+            state: Orbit::keplerian(
+                7000.0 + 10.0 * i as f64,
+                0.01,
+                53.0,
+                15.0 * i as f64,
+                0.0,
+                30.0 * i as f64,
+                epoch + (i as i64).hours(),
+                eme2k,
+            ),
+        });
+    }
+
+    let prop = Propagator::default(OrbitalDynamics::two_body());
+    let common_epoch = epoch + 1.days();
+
+    let propagated = catalog.propagate_to(&prop, common_epoch).unwrap();
+    assert_eq!(propagated.len(), catalog.len());
+
+    for (orig, prop_entry) in catalog.entries.iter().zip(propagated.entries.iter()) {
+        assert_eq!(orig.name, prop_entry.name);
+        assert_eq!(prop_entry.state.epoch, common_epoch);
+        // Two body dynamics: the SMA is conserved
+        assert!((orig.state.sma_km() - prop_entry.state.sma_km()).abs() < 1e-6);
+    }
+
+    // Round trip through CSV
+    let csv_path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "output_data", "catalog.csv"]
+        .iter()
+        .collect();
+    propagated.to_csv(&csv_path).unwrap();
+    let from_csv = StateCatalog::from_csv(&csv_path, cosm.clone()).unwrap();
+
+    // And through parquet
+    let pq_path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "output_data", "catalog.parquet"]
+        .iter()
+        .collect();
+    let pq_path = propagated
+        .to_parquet(pq_path, ExportCfg::default())
+        .unwrap();
+    let from_pq = StateCatalog::from_parquet(pq_path, cosm).unwrap();
+
+    for reloaded in [from_csv, from_pq] {
+        assert_eq!(reloaded.len(), propagated.len());
+        for (orig, entry) in propagated.entries.iter().zip(reloaded.entries.iter()) {
+            assert_eq!(orig.name, entry.name);
+            assert!((orig.state.epoch - entry.state.epoch).abs() < 1.microseconds());
+            assert!((orig.state.radius() - entry.state.radius()).norm() < 1e-9);
+            assert!((orig.state.velocity() - entry.state.velocity()).norm() < 1e-12);
+        }
+    }
+}
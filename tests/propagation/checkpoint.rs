@@ -0,0 +1,81 @@
+extern crate nyx_space as nyx;
+use hifitime::J2000_OFFSET;
+use nyx::cosmic::{Bodies, Cosm, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::mc::Pcg64Mcg;
+use nyx::propagators::*;
+use nyx::time::{Epoch, Unit};
+use nyx::State;
+use rand::Rng;
+use std::path::PathBuf;
+
+#[test]
+fn checkpoint_resume() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(8000.0, 0.2, 30.0, 60.0, 90.0, 0.0, dt, eme2k);
+    let dynamics = OrbitalDynamics::point_masses(&[Bodies::Luna, Bodies::Sun], cosm);
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "checkpoint_resume.yaml",
+    ]
+    .iter()
+    .collect();
+
+    // Adaptive Runge Kutta with the STM, and a multi-step integrator whose history of derivatives must be restored
+    let rk89 = Propagator::default(dynamics.clone()).with_stm();
+    let abm = Propagator::new::<AdamsBashforthMoulton<8>>(
+        dynamics,
+        PropOpts::with_fixed_step(30 * Unit::Second),
+    );
+
+    for (name, setup) in [("RK89", &rk89), ("ABM8", &abm)] {
+        let mut uninterrupted = setup.with(init);
+        uninterrupted.for_duration(6 * Unit::Hour).unwrap();
+        let expected = uninterrupted.for_duration(6 * Unit::Hour).unwrap();
+
+        let mut rng = Pcg64Mcg::new(42);
+        let _: f64 = rng.gen();
+
+        let mut first_leg = setup.with(init);
+        first_leg.for_duration(6 * Unit::Hour).unwrap();
+        first_leg
+            .checkpoint()
+            .unwrap()
+            .with_rng(rng.clone())
+            .save(&path)
+            .unwrap();
+
+        // A new process loads the checkpoint
+        let checkpoint = PropCheckpoint::<Orbit>::load(&path).unwrap();
+        let mut restored_rng = checkpoint.rng.clone().unwrap();
+        assert_eq!(restored_rng.gen::<f64>(), rng.gen::<f64>());
+
+        let mut resumed = setup.resume(checkpoint).unwrap();
+        assert_eq!(resumed.state.epoch(), dt + 6 * Unit::Hour);
+        let state = resumed.for_duration(6 * Unit::Hour).unwrap();
+        println!("{name}: {state}");
+
+        // Bit for bit the same propagation, including the STM
+        assert_eq!(state.epoch, expected.epoch);
+        assert_eq!(state.as_vector().unwrap(), expected.as_vector().unwrap());
+        assert_eq!(state.stm.is_some(), name == "RK89");
+        assert_eq!(resumed.details.step, uninterrupted.details.step);
+    }
+
+    // A checkpoint may only be resumed by the same integrator
+    let checkpoint = PropCheckpoint::<Orbit>::load(&path).unwrap();
+    assert!(rk89.resume(checkpoint).is_err());
+
+    // Including the same Runge Kutta without the Sundman regularization, despite the same order and stages
+    let sundman = Propagator::default(rk89.dynamics.clone()).with_sundman(1.0);
+    let mut instance = sundman.with(init);
+    instance.for_duration(1 * Unit::Hour).unwrap();
+    let checkpoint = instance.checkpoint().unwrap();
+    assert!(rk89.resume(checkpoint.clone()).is_err());
+    assert!(sundman.resume(checkpoint).is_ok());
+}
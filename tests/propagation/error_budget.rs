@@ -0,0 +1,108 @@
+extern crate nyx_space as nyx;
+
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::propagators::error_ctrl::RSSCartesianStep;
+use nyx::propagators::*;
+use nyx::time::{Epoch, Unit};
+use std::path::PathBuf;
+
+#[test]
+fn error_budget_leo_day() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let init = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, dt, eme2k,
+    );
+
+    let methods = [
+        IntegratorMethod::RK89,
+        IntegratorMethod::Dormand78,
+        IntegratorMethod::Fehlberg45,
+        IntegratorMethod::RK4Fixed,
+    ];
+    let tolerances = [1e-6, 1e-9, 1e-12];
+    let max_steps = [60 * Unit::Second, 10 * Unit::Minute];
+
+    let budget = ErrorBudget::from_matrix(
+        &methods,
+        &tolerances,
+        0.1 * Unit::Second,
+        &max_steps,
+        RSSCartesianStep {},
+    );
+    // The fixed step method is not combined with the tolerances
+    assert_eq!(budget.settings.len(), 3 * 3 * 2 + 2);
+
+    let report = budget
+        .run(OrbitalDynamics::two_body(), init, 1 * Unit::Day)
+        .unwrap();
+    println!("{report}");
+    assert_eq!(report.entries.len(), budget.settings.len());
+
+    for entry in &report.entries {
+        assert!(entry.num_steps > 0);
+        if entry.fixed_step {
+            assert_eq!(
+                entry.num_steps as f64,
+                (86_400.0 / entry.max_step.to_seconds()).ceil()
+            );
+        }
+    }
+
+    // Tightening the tolerance improves the accuracy of the adaptive methods, unless the maximum step is the limiting factor
+    for method in &methods[..3] {
+        for max_step in &max_steps {
+            let err_at = |tol: f64| {
+                report
+                    .entries
+                    .iter()
+                    .find(|e| e.method == *method && e.max_step == *max_step && e.tolerance == tol)
+                    .unwrap()
+                    .pos_err_km
+            };
+            assert!(
+                err_at(1e-12) <= err_at(1e-6),
+                "{method} with max step of {max_step}"
+            );
+            if *max_step == 10 * Unit::Minute {
+                assert!(err_at(1e-12) < 1e-3 * err_at(1e-6));
+            }
+        }
+    }
+    // A large fixed step is much less accurate than the reference
+    let rk4_coarse = report
+        .entries
+        .iter()
+        .find(|e| e.fixed_step && e.max_step == 10 * Unit::Minute)
+        .unwrap();
+    assert!(rk4_coarse.pos_err_km > 1e-3);
+
+    // The trade curve is sorted by runtime with a decreasing error
+    let front = report.pareto_front();
+    assert!(!front.is_empty());
+    for pair in front.windows(2) {
+        assert!(pair[0].runtime <= pair[1].runtime);
+        assert!(pair[0].pos_err_km > pair[1].pos_err_km);
+    }
+
+    // A meter level accuracy is achievable, and the fastest settings to achieve it are on the trade curve
+    let pick = report.fastest_within(1e-3).unwrap();
+    println!("Fastest settings within 1 m: {pick}");
+    assert!(pick.pos_err_km <= 1e-3);
+    assert!(front.contains(&pick));
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "error_budget_leo.parquet",
+    ]
+    .iter()
+    .collect();
+    let written = report.to_parquet(path, Default::default()).unwrap();
+    assert!(written.exists());
+}
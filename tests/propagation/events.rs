@@ -123,3 +123,75 @@ fn event_tracker_true_anomaly() {
         });
     println!("[eclipses] {} =>\n{}", penumbra_event_loc, pretty);
 }
+
+#[test]
+fn event_search_window_arcs() {
+    use nyx::md::prelude::*;
+    use nyx::md::EventEvaluator;
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_tai_at_noon(2020, 1, 1);
+    let state = Orbit::keplerian(7000.0, 1e-3, 45.0, 10.0, 20.0, 30.0, dt, eme2k);
+    let period = state.period();
+
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+    let (_, traj) = setup
+        .with(state)
+        .for_duration_with_traj(5 * period)
+        .unwrap();
+
+    // Northern hemisphere: the spacecraft starts above the equator
+    let north = Event::new(StateParameter::Z, 0.0);
+    assert!(north.eval(traj.first()) > 0.0);
+
+    let all = traj.find_all(&north).unwrap();
+    assert_eq!(all.len(), 10);
+
+    // Search window within the trajectory, and the same window searched backward
+    let start = dt + period;
+    let end = dt + 4 * period;
+    let forward = traj.find_between(start, end, &north).unwrap();
+    assert_eq!(forward.len(), 6);
+    for (state, expected) in forward.iter().zip(&all[2..8]) {
+        assert!((state.epoch() - expected.epoch()).abs() < 1 * Unit::Second);
+    }
+    let backward = traj.find_between(end, start, &north).unwrap();
+    assert_eq!(backward.len(), 6);
+    for (state, expected) in backward.iter().zip(forward.iter().rev()) {
+        assert_eq!(state.epoch(), expected.epoch());
+    }
+
+    // The window must be within the trajectory
+    assert!(traj
+        .find_between(dt - 1 * Unit::Minute, end, &north)
+        .is_err());
+
+    // Arcs above the equator: the first one is in progress at the start of the trajectory
+    let arcs = traj
+        .find_arcs(traj.first().epoch(), traj.last().epoch(), &north)
+        .unwrap();
+    for arc in &arcs {
+        println!("{arc}");
+    }
+    assert_eq!(arcs.len(), 6);
+    assert_eq!(arcs[0].rise.epoch(), dt);
+    assert_eq!(arcs[0].fall.epoch(), all[0].epoch());
+    assert_eq!(arcs[5].fall.epoch(), traj.last().epoch());
+    for arc in &arcs[1..5] {
+        assert!((arc.duration() - 0.5 * period).abs() < 1 * Unit::Minute);
+        let mid = traj.at(arc.rise.epoch() + arc.duration() * 0.5).unwrap();
+        assert!(north.eval(&mid) > 0.0);
+        assert!(arc.contains(mid.epoch()));
+    }
+
+    // Searching backward returns the same arcs, latest first
+    let arcs_backward = traj
+        .find_arcs(traj.last().epoch(), traj.first().epoch(), &north)
+        .unwrap();
+    assert_eq!(arcs_backward.len(), arcs.len());
+    for (arc, expected) in arcs_backward.iter().zip(arcs.iter().rev()) {
+        assert_eq!(arc, expected);
+    }
+}
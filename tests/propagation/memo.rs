@@ -0,0 +1,91 @@
+extern crate nyx_space as nyx;
+use hifitime::J2000_OFFSET;
+use nyx::cosmic::{Bodies, Cosm, Orbit, Spacecraft};
+use nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
+use nyx::propagators::*;
+use nyx::time::{Epoch, Unit};
+use nyx::State;
+use std::path::PathBuf;
+use std::time::Instant;
+
+#[test]
+fn memoized_propagation() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(8000.0, 0.2, 30.0, 60.0, 90.0, 0.0, dt, eme2k);
+    let end = dt + 2 * Unit::Day;
+    let dynamics = OrbitalDynamics::point_masses(&[Bodies::Luna, Bodies::Sun], cosm.clone());
+
+    let dir: PathBuf = [env!("CARGO_MANIFEST_DIR"), "output_data", "memo"]
+        .iter()
+        .collect();
+    let memo = PropMemo::new(&dir).unwrap();
+    memo.clear().unwrap();
+    let cached = || std::fs::read_dir(&dir).unwrap().count();
+
+    let setup = Propagator::default(dynamics.clone()).with_stm();
+    let start = Instant::now();
+    let (state, traj) = memo.until_epoch_with_traj(&setup, init, end).unwrap();
+    let computed = start.elapsed();
+    assert_eq!(cached(), 1);
+
+    let start = Instant::now();
+    let (memo_state, memo_traj) = memo.until_epoch_with_traj(&setup, init, end).unwrap();
+    println!("computed in {computed:?}, loaded in {:?}", start.elapsed());
+    assert_eq!(cached(), 1);
+
+    // Bit for bit the same results, including the STM
+    assert_eq!(memo_state.epoch, state.epoch);
+    assert_eq!(memo_state.as_vector().unwrap(), state.as_vector().unwrap());
+    assert_eq!(memo_state.stm().unwrap(), state.stm().unwrap());
+    assert_eq!(memo_traj.states.len(), traj.states.len());
+    let mid = dt + 1 * Unit::Day;
+    assert_eq!(
+        memo_traj.at(mid).unwrap().to_cartesian_vec(),
+        traj.at(mid).unwrap().to_cartesian_vec()
+    );
+
+    // Any change of the scenario propagates again
+    let tighter = Propagator::new::<RK89>(dynamics, PropOpts::with_tolerance(1e-14));
+    memo.until_epoch_with_traj(&tighter, init, end).unwrap();
+    assert_eq!(cached(), 2);
+
+    let with_sun = Propagator::default(OrbitalDynamics::point_masses(&[Bodies::Sun], cosm));
+    memo.until_epoch_with_traj(&with_sun, init, end).unwrap();
+    assert_eq!(cached(), 3);
+
+    let mut moved = init;
+    moved.x_km += 1e-9;
+    memo.until_epoch_with_traj(&with_sun, moved, end).unwrap();
+    assert_eq!(cached(), 4);
+
+    // Spacecraft states are cached too
+    let sc = Spacecraft::from_srp_defaults(init, 100.0, 1.0);
+    let sc_setup = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()));
+    let (sc_state, _) = memo
+        .for_duration_with_traj(&sc_setup, sc, 6 * Unit::Hour)
+        .unwrap();
+    let (memo_sc_state, _) = memo
+        .for_duration_with_traj(&sc_setup, sc, 6 * Unit::Hour)
+        .unwrap();
+    assert_eq!(cached(), 5);
+    assert_eq!(
+        memo_sc_state.as_vector().unwrap(),
+        sc_state.as_vector().unwrap()
+    );
+    assert_eq!(memo_sc_state.srp.area_m2, sc_state.srp.area_m2);
+
+    // A corrupted cache is ignored and overwritten
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        std::fs::write(entry.unwrap().path(), "not a propagation").unwrap();
+    }
+    let (state, _) = memo.until_epoch_with_traj(&setup, init, end).unwrap();
+    assert_eq!(state.as_vector().unwrap(), memo_state.as_vector().unwrap());
+
+    assert_eq!(memo.clear().unwrap(), 5);
+    assert_eq!(cached(), 0);
+}
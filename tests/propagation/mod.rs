@@ -1,5 +1,13 @@
+mod catalog;
+mod checkpoint;
+mod error_budget;
 mod events;
+mod memo;
+mod named_params;
 mod propagators;
+mod regularization;
+mod soi;
+mod step_hooks;
 mod stm;
 mod stopcond;
 mod trajectory;
@@ -0,0 +1,250 @@
+extern crate nyx_space as nyx;
+extern crate pretty_env_logger;
+
+use nyx::cosmic::{Cosm, Frame, Orbit};
+use nyx::dynamics::OrbitalDynamics;
+use nyx::linalg::{Const, OVector};
+use nyx::md::prelude::{ExportCfg, Interpolatable, Traj};
+use nyx::md::{NamedEvent, NamedParameter, StateParameter};
+use nyx::propagators::*;
+use nyx::time::{Epoch, TimeUnits};
+use nyx::{NyxError, State};
+use std::fmt;
+use std::path::PathBuf;
+
+/// An orbit along with the charge of the battery of the spacecraft, as custom dynamics would propagate it
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+struct PoweredOrbit {
+    orbit: Orbit,
+    charge_wh: f64,
+}
+
+const CAPACITY_WH: f64 = 120.0;
+
+impl fmt::Display for PoweredOrbit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} with {} Wh", self.orbit, self.charge_wh)
+    }
+}
+
+impl fmt::LowerExp for PoweredOrbit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:e} with {:e} Wh", self.orbit, self.charge_wh)
+    }
+}
+
+impl State for PoweredOrbit {
+    type Size = Const<7>;
+    type VecLength = Const<7>;
+
+    fn as_vector(&self) -> Result<OVector<f64, Const<7>>, NyxError> {
+        let mut vector = OVector::<f64, Const<7>>::zeros();
+        for (i, val) in self.orbit.to_cartesian_vec().iter().take(6).enumerate() {
+            vector[i] = *val;
+        }
+        vector[6] = self.charge_wh;
+        Ok(vector)
+    }
+
+    fn unset_stm(&mut self) {}
+
+    fn set(&mut self, epoch: Epoch, vector: &OVector<f64, Const<7>>) -> Result<(), NyxError> {
+        self.orbit.epoch = epoch;
+        self.orbit.x_km = vector[0];
+        self.orbit.y_km = vector[1];
+        self.orbit.z_km = vector[2];
+        self.orbit.vx_km_s = vector[3];
+        self.orbit.vy_km_s = vector[4];
+        self.orbit.vz_km_s = vector[5];
+        self.charge_wh = vector[6];
+        Ok(())
+    }
+
+    fn epoch(&self) -> Epoch {
+        self.orbit.epoch
+    }
+
+    fn set_epoch(&mut self, epoch: Epoch) {
+        self.orbit.epoch = epoch;
+    }
+
+    fn value(&self, param: StateParameter) -> Result<f64, NyxError> {
+        self.orbit.value(param)
+    }
+
+    fn named_parameters() -> Vec<(&'static str, &'static str)> {
+        vec![("charge", "Wh"), ("depth of discharge", "")]
+    }
+
+    fn named_value_at(&self, index: usize) -> Result<f64, NyxError> {
+        match index {
+            0 => Ok(self.charge_wh),
+            1 => Ok(1.0 - self.charge_wh / CAPACITY_WH),
+            _ => Err(NyxError::NamedParameterUnavailable(
+                NamedParameter::Index(index),
+                "no such parameter".to_string(),
+            )),
+        }
+    }
+}
+
+impl Interpolatable for PoweredOrbit {
+    fn interpolate(self, epoch: Epoch, states: &[Self]) -> Result<Self, NyxError> {
+        let orbits = states
+            .iter()
+            .map(|state| state.orbit)
+            .collect::<Vec<Orbit>>();
+        let orbit = self.orbit.interpolate(epoch, &orbits)?;
+        // The charge varies linearly between the states which bracket this epoch
+        let next = states
+            .iter()
+            .position(|state| state.epoch() >= epoch)
+            .unwrap_or(states.len() - 1)
+            .max(1);
+        let (prev, next) = (states[next - 1], states[next]);
+        let frac = (epoch - prev.epoch()).to_seconds() / (next.epoch() - prev.epoch()).to_seconds();
+        Ok(Self {
+            orbit,
+            charge_wh: prev.charge_wh + frac * (next.charge_wh - prev.charge_wh),
+        })
+    }
+
+    fn frame(&self) -> Frame {
+        self.orbit.frame
+    }
+
+    fn set_frame(&mut self, frame: Frame) {
+        self.orbit.frame = frame;
+    }
+
+    fn export_params() -> Vec<StateParameter> {
+        vec![StateParameter::SMA, StateParameter::Eccentricity]
+    }
+
+    fn orbit(&self) -> &Orbit {
+        &self.orbit
+    }
+
+    fn set_orbit(&mut self, orbit: Orbit) {
+        self.orbit = orbit;
+    }
+}
+
+#[test]
+fn named_params_events_and_export() {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+
+    let _ = pretty_env_logger::try_init();
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let orbit = Orbit::keplerian_altitude(500.0, 1e-3, 51.6, 30.0, 0.0, 0.0, start, eme2k);
+
+    let (_, orbit_traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(orbit)
+        .for_duration_with_traj(2.hours())
+        .unwrap();
+
+    // The battery discharges by 10 Wh per hour
+    let charge_at = |epoch: Epoch| 100.0 - 10.0 * (epoch - start).to_unit(nyx::time::Unit::Hour);
+    let mut traj = Traj::new();
+    for orbit in &orbit_traj.states {
+        traj.states.push(PoweredOrbit {
+            orbit: *orbit,
+            charge_wh: charge_at(orbit.epoch),
+        });
+    }
+    traj.finalize();
+
+    let state = traj.at(start + 45.minutes()).unwrap();
+    let charge = NamedParameter::from("charge");
+    assert!((state.named_value(&charge).unwrap() - 92.5).abs() < 1e-9);
+    assert!(
+        (state.named_value(&NamedParameter::Index(1)).unwrap() - (1.0 - 92.5 / CAPACITY_WH)).abs()
+            < 1e-9
+    );
+    assert_eq!(
+        state.named_value(&NamedParameter::from("temperature")),
+        Err(NyxError::NamedParameterUnavailable(
+            NamedParameter::from("temperature"),
+            "no such parameter in this state".to_string()
+        ))
+    );
+    assert!(state.named_value(&NamedParameter::Index(2)).is_err());
+    // Orbits have no named parameters
+    assert!(orbit.named_value(&charge).is_err());
+
+    // Events on the named parameters, by name or by index
+    let event = NamedEvent::new("charge", 95.0);
+    println!("{event}");
+    let found = traj.find_all(&event).unwrap();
+    assert_eq!(found.len(), 1);
+    assert!((found[0].epoch() - (start + 30.minutes())).abs() < 1.milliseconds());
+
+    let event = NamedEvent::new(1, 1.0 - 90.0 / CAPACITY_WH);
+    let found = traj.find_all(&event).unwrap();
+    assert_eq!(found.len(), 1);
+    assert!((found[0].epoch() - (start + 1.hours())).abs() < 1.milliseconds());
+
+    // The named parameters are exported with the trajectory
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "named_params.parquet",
+    ]
+    .iter()
+    .collect();
+    let cfg = ExportCfg::builder().step(10.minutes()).build();
+    let exported_path = traj.to_parquet(&path, None, cfg).unwrap();
+
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(File::open(&exported_path).unwrap()).unwrap();
+    let columns = builder
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect::<Vec<String>>();
+    assert_eq!(
+        columns,
+        vec![
+            "Epoch:Gregorian UTC",
+            "Epoch:Gregorian TAI",
+            "Epoch:TAI (s)",
+            "sma (km)",
+            "ecc",
+            "charge (Wh)",
+            "depth of discharge"
+        ]
+    );
+
+    let batch = builder.build().unwrap().next().unwrap().unwrap();
+    let charge_wh = batch
+        .column_by_name("charge (Wh)")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<arrow::array::Float64Array>()
+        .unwrap();
+    assert_eq!(charge_wh.len(), 13);
+    for ii in 0..charge_wh.len() {
+        assert!((charge_wh.value(ii) - (100.0 - ii as f64 * 10.0 / 6.0)).abs() < 1e-9);
+    }
+
+    // Only export the requested named parameters
+    let cfg = ExportCfg::builder()
+        .fields(vec![StateParameter::SMA])
+        .named_fields(vec![NamedParameter::from("depth of discharge")])
+        .build();
+    let exported_path = traj.to_parquet(&path, None, cfg).unwrap();
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(File::open(exported_path).unwrap()).unwrap();
+    assert_eq!(builder.schema().fields().len(), 5);
+    assert_eq!(builder.schema().field(4).name(), "depth of discharge");
+
+    let cfg = ExportCfg::builder()
+        .named_fields(vec![NamedParameter::from("temperature")])
+        .build();
+    assert!(traj.to_parquet(&path, None, cfg).is_err());
+}
@@ -2,6 +2,7 @@ extern crate nyx_space as nyx;
 use hifitime::J2000_OFFSET;
 use nyx::cosmic::{assert_orbit_eq_or_abs, assert_orbit_eq_or_rel, Cosm, Orbit};
 use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::dynamics::Relativity;
 use nyx::propagators::error_ctrl::RSSCartesianState;
 use nyx::propagators::*;
 use nyx::time::{Epoch, Unit};
@@ -465,3 +466,606 @@ fn gmat_val_leo_day_fixed() {
         println!();
     }
 }
+
+#[test]
+fn adams_bashforth_moulton_leo() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let prop_time = 1 * Unit::Day;
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, dt, eme2k,
+    );
+
+    let dynamics = OrbitalDynamics::two_body();
+
+    // Reference with a tight tolerance RK89
+    let rk89 = Propagator::new::<RK89>(
+        dynamics.clone(),
+        PropOpts::with_adaptive_step(
+            0.1 * Unit::Second,
+            30.0 * Unit::Second,
+            1e-14,
+            RSSCartesianState {},
+        ),
+    );
+    let truth = rk89.with(init).for_duration(prop_time).unwrap();
+
+    // Fixed step, the error decreases with the order
+    let setup = Propagator::new::<AdamsBashforthMoulton<4>>(
+        dynamics.clone(),
+        PropOpts::with_fixed_step(10.0 * Unit::Second),
+    );
+    let abm4 = setup.with(init).for_duration(prop_time).unwrap();
+    let (err4_km, _) = rss_orbit_errors(&abm4, &truth);
+
+    let setup = Propagator::new::<AdamsBashforthMoulton<8>>(
+        dynamics.clone(),
+        PropOpts::with_fixed_step(10.0 * Unit::Second),
+    );
+    let abm8 = setup.with(init).for_duration(prop_time).unwrap();
+    let (err8_km, _) = rss_orbit_errors(&abm8, &truth);
+
+    let setup = Propagator::new::<AdamsBashforthMoulton<12>>(
+        dynamics.clone(),
+        PropOpts::with_fixed_step(10.0 * Unit::Second),
+    );
+    let abm12 = setup.with(init).for_duration(prop_time).unwrap();
+    let (err12_km, _) = rss_orbit_errors(&abm12, &truth);
+
+    println!(
+        "ABM fixed step errors: 4: {err4_km:.3e} km\t8: {err8_km:.3e} km\t12: {err12_km:.3e} km"
+    );
+    assert!(err4_km < 1e-1);
+    assert!(err8_km < err4_km);
+    assert!(err8_km < 1e-5);
+    assert!(err12_km < 1e-5);
+
+    // Adaptive step, compared to an RK89 with the same options.
+    // High order Adams methods have small stability regions, so the maximum step is kept to a minute.
+    let opts = PropOpts::with_adaptive_step(
+        0.1 * Unit::Second,
+        60.0 * Unit::Second,
+        1e-12,
+        RSSCartesianState {},
+    );
+    let rk89 = Propagator::new::<RK89>(dynamics.clone(), opts);
+    let (err_rk89_km, _) =
+        rss_orbit_errors(&rk89.with(init).for_duration(prop_time).unwrap(), &truth);
+
+    let setup = Propagator::new::<AdamsBashforthMoulton<10>>(dynamics.clone(), opts);
+    let mut prop = setup.with(init);
+    let abm10 = prop.for_duration(prop_time).unwrap();
+    let (err10_km, _) = rss_orbit_errors(&abm10, &truth);
+    println!(
+        "ABM10 adaptive error: {err10_km:.3e} km (RK89: {err_rk89_km:.3e} km)\t{}",
+        prop.latest_details()
+    );
+    assert_eq!(abm10.epoch, truth.epoch);
+    assert!(err10_km < 1e-5);
+
+    // And back to the initial state
+    let back = prop.for_duration(-prop_time).unwrap();
+    let (err_back_km, _) = rss_orbit_errors(&back, &init);
+    println!("ABM10 back propagation error: {err_back_km:.3e} km");
+    assert!(err_back_km < 1e-5);
+}
+
+#[test]
+fn propagation_tracing_spans() {
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Records the name and fields of each span
+    #[derive(Default)]
+    struct SpanRecorder {
+        spans: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut String);
+
+    impl<'a> tracing::field::Visit for FieldVisitor<'a> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for SpanRecorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut repr = span.metadata().name().to_string();
+            span.record(&mut FieldVisitor(&mut repr));
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(repr);
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, dt, eme2k,
+    );
+
+    let recorder = SpanRecorder::default();
+    let spans = recorder.spans.clone();
+
+    tracing::subscriber::with_default(recorder, || {
+        Propagator::default(OrbitalDynamics::two_body())
+            .with(init)
+            .for_duration(1 * Unit::Hour)
+            .unwrap();
+    });
+
+    let spans = spans.lock().unwrap();
+    assert_eq!(spans.len(), 1, "expected one propagation span: {spans:?}");
+    assert_eq!(
+        spans[0],
+        format!("propagate start={dt} stop={}", dt + 1 * Unit::Hour)
+    );
+}
+
+#[test]
+fn benchmark_workloads() {
+    use nyx::tools::benchmark::{BenchmarkSuite, Workload};
+
+    let mut suite = BenchmarkSuite::new(Cosm::de438());
+    // Shorten the workloads to keep the test fast
+    suite.leo_duration = 10 * Unit::Minute;
+    suite.interplanetary_duration = 1 * Unit::Day;
+    suite.od_arc_duration = 1 * Unit::Hour;
+    suite.kf_updates = 1_000;
+
+    let results = suite.run(&Workload::all(), 2).unwrap();
+    assert_eq!(results.len(), 4);
+    for (result, workload) in results.iter().zip(Workload::all()) {
+        println!("{result}");
+        assert_eq!(result.workload, workload);
+        assert_eq!(result.iterations, 2);
+        assert!(result.min <= result.mean && result.mean <= result.max);
+    }
+
+    assert!(suite.run(&[Workload::LeoHarmonics], 0).is_err());
+}
+
+#[test]
+fn symplectic_long_term_energy() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(8000.0, 0.2, 30.0, 60.0, 90.0, 0.0, dt, eme2k);
+    let energy = init.energy_km2_s2();
+    let dynamics = OrbitalDynamics::two_body();
+
+    // Maximum relative energy error over a propagation
+    let energy_err = |states: &mut dyn Iterator<Item = Orbit>| {
+        states
+            .map(|state| ((state.energy_km2_s2() - energy) / energy).abs())
+            .fold(0.0, f64::max)
+    };
+
+    // About 400 revolutions with a large fixed step: the RK4 energy error grows secularly
+    let step = 60.0 * Unit::Second;
+    let first = 2 * Unit::Day;
+    let prop_time = 80 * Unit::Day;
+
+    let rk4 = Propagator::new::<RK4Fixed>(dynamics.clone(), PropOpts::with_fixed_step(step));
+    let mut prop = rk4.with(init);
+    let (_, traj) = prop.for_duration_with_traj(first).unwrap();
+    let rk4_early = energy_err(&mut traj.states.into_iter());
+    let (_, traj) = prop.for_duration_with_traj(prop_time - first).unwrap();
+    let rk4_late = energy_err(&mut traj.states.into_iter());
+
+    // The Störmer-Verlet energy error remains bounded
+    let verlet = Propagator::symplectic::<StormerVerlet>(dynamics.clone(), step).unwrap();
+    let mut prop = verlet.with(init);
+    let (_, traj) = prop.for_duration_with_traj(first).unwrap();
+    let verlet_early = energy_err(&mut traj.states.into_iter());
+    let (_, traj) = prop.for_duration_with_traj(prop_time - first).unwrap();
+    let verlet_late = energy_err(&mut traj.states.into_iter());
+
+    let yoshida = Propagator::symplectic::<Yoshida6>(dynamics.clone(), step).unwrap();
+    let (_, traj) = yoshida
+        .with(init)
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+    let yoshida6 = energy_err(&mut traj.states.into_iter());
+
+    println!("RK4: {rk4_early:.3e} -> {rk4_late:.3e}\tVerlet: {verlet_early:.3e} -> {verlet_late:.3e}\tYoshida6: {yoshida6:.3e}");
+    assert!(rk4_late > 10.0 * rk4_early);
+    assert!(verlet_late < 1.5 * verlet_early);
+    assert!(yoshida6 < verlet_late);
+    assert!(yoshida6 < 1e-8);
+
+    // Accuracy over a day compared to a tight tolerance RK89
+    let rk89 = Propagator::new::<RK89>(
+        dynamics.clone(),
+        PropOpts::with_adaptive_step(
+            0.1 * Unit::Second,
+            30.0 * Unit::Second,
+            1e-14,
+            RSSCartesianState {},
+        ),
+    );
+    let truth = rk89.with(init).for_duration(1 * Unit::Day).unwrap();
+    let setup = Propagator::symplectic::<Yoshida4>(dynamics.clone(), 10.0 * Unit::Second).unwrap();
+    let (err4_km, _) = rss_orbit_errors(
+        &setup.with(init).for_duration(1 * Unit::Day).unwrap(),
+        &truth,
+    );
+    let setup = Propagator::symplectic::<Yoshida6>(dynamics, 10.0 * Unit::Second).unwrap();
+    let (err6_km, _) = rss_orbit_errors(
+        &setup.with(init).for_duration(1 * Unit::Day).unwrap(),
+        &truth,
+    );
+    println!("Yoshida errors after a day: 4: {err4_km:.3e} km\t6: {err6_km:.3e} km");
+    assert!(err6_km < err4_km);
+    assert!(err6_km < 1e-5);
+
+    // The STM cannot be propagated
+    let setup = Propagator::symplectic::<Yoshida4>(OrbitalDynamics::two_body(), step).unwrap();
+    assert!(setup
+        .with(init.with_stm())
+        .for_duration(1 * Unit::Hour)
+        .is_err());
+
+    // Nor can velocity dependent accelerations, rejected when building the propagator
+    let relativistic = OrbitalDynamics::two_body().with_relativity(Relativity::earth(cosm));
+    assert!(Propagator::symplectic::<Yoshida4>(relativistic, step).is_err());
+}
+
+#[test]
+fn rkn_two_body() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(8000.0, 0.2, 30.0, 60.0, 90.0, 0.0, dt, eme2k);
+    let prop_time = 1 * Unit::Day;
+
+    let truth_setup = Propagator::new::<RK89>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_adaptive_step(
+            0.1 * Unit::Second,
+            30.0 * Unit::Second,
+            1e-14,
+            RSSCartesianState {},
+        ),
+    );
+    let truth = truth_setup.with(init).for_duration(prop_time).unwrap();
+
+    // Sixth order convergence with a fixed step
+    let err_with_step = |step| {
+        let setup =
+            Propagator::rkn::<RKN64>(OrbitalDynamics::two_body(), PropOpts::with_fixed_step(step))
+                .unwrap();
+        rss_orbit_errors(&setup.with(init).for_duration(prop_time).unwrap(), &truth).0
+    };
+    let err_coarse = err_with_step(2 * Unit::Minute);
+    let err_fine = err_with_step(1 * Unit::Minute);
+    println!("RKN64 fixed step errors: {err_coarse:.3e} km -> {err_fine:.3e} km");
+
+    // At the same step, it is at least as accurate as the sixth order Verner, with six instead of eight evaluations per step
+    let verner = Propagator::new::<Verner56>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(1 * Unit::Minute),
+    );
+    let (verner_err, _) =
+        rss_orbit_errors(&verner.with(init).for_duration(prop_time).unwrap(), &truth);
+    println!("Verner56 fixed step error: {verner_err:.3e} km");
+    assert!(err_coarse > 50.0 * err_fine);
+    assert!(err_fine < verner_err);
+
+    // Adaptive step, forward and backward
+    let rkn = Propagator::rkn::<RKN64>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_adaptive_step(
+            0.1 * Unit::Second,
+            30.0 * Unit::Minute,
+            1e-12,
+            RSSCartesianState {},
+        ),
+    )
+    .unwrap();
+    let rkn_state = rkn.with(init).for_duration(prop_time).unwrap();
+    let (rkn_err, _) = rss_orbit_errors(&rkn_state, &truth);
+    let back = rkn.with(rkn_state).for_duration(-prop_time).unwrap();
+    let (back_err, _) = rss_orbit_errors(&back, &init);
+    println!("RKN64 adaptive error: {rkn_err:.3e} km\tback to initial state: {back_err:.3e} km");
+    assert_eq!(back.epoch, init.epoch);
+    assert!(rkn_err < 1e-5);
+    assert!(back_err < 1e-5);
+
+    // The STM cannot be propagated
+    assert!(rkn
+        .with(init.with_stm())
+        .for_duration(1 * Unit::Hour)
+        .is_err());
+
+    // Nor can velocity dependent accelerations, rejected when building the propagator
+    let relativistic = OrbitalDynamics::two_body().with_relativity(Relativity::earth(cosm));
+    assert!(
+        Propagator::rkn::<RKN64>(relativistic, PropOpts::with_fixed_step(1 * Unit::Minute))
+            .is_err()
+    );
+}
+
+#[test]
+fn rkn1210_two_body() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(8000.0, 0.2, 30.0, 60.0, 90.0, 0.0, dt, eme2k);
+    let prop_time = 1 * Unit::Day;
+
+    let truth_setup = Propagator::new::<RK89>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_adaptive_step(
+            0.1 * Unit::Second,
+            30.0 * Unit::Second,
+            1e-14,
+            RSSCartesianState {},
+        ),
+    );
+    let truth = truth_setup.with(init).for_duration(prop_time).unwrap();
+
+    // Twelfth order convergence with a fixed step: halving the step divides the error by 2^12 = 4096, with some margin since
+    // the errors of smaller steps reach that of the reference (about 1e-8 km)
+    let err_with_step = |step| {
+        let setup = Propagator::rkn::<RKN1210>(
+            OrbitalDynamics::two_body(),
+            PropOpts::with_fixed_step(step),
+        )
+        .unwrap();
+        rss_orbit_errors(&setup.with(init).for_duration(prop_time).unwrap(), &truth).0
+    };
+    let err_coarse = err_with_step(8 * Unit::Minute);
+    let err_fine = err_with_step(4 * Unit::Minute);
+    println!("RKN1210 fixed step errors: {err_coarse:.3e} km -> {err_fine:.3e} km");
+    assert!(err_coarse > 2_000.0 * err_fine);
+    assert!(err_fine < 1e-5);
+
+    // At the same step, it is far more accurate than the RKN6(4) pair
+    let rkn64 = Propagator::rkn::<RKN64>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(8 * Unit::Minute),
+    )
+    .unwrap();
+    let (rkn64_err, _) =
+        rss_orbit_errors(&rkn64.with(init).for_duration(prop_time).unwrap(), &truth);
+    println!("RKN64 fixed step error: {rkn64_err:.3e} km");
+    assert!(err_fine < 1e-3 * rkn64_err);
+
+    // Adaptive step, forward and backward
+    let rkn = Propagator::rkn::<RKN1210>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_adaptive_step(
+            0.1 * Unit::Second,
+            1.0 * Unit::Hour,
+            1e-12,
+            RSSCartesianState {},
+        ),
+    )
+    .unwrap();
+    let rkn_state = rkn.with(init).for_duration(prop_time).unwrap();
+    let (rkn_err, _) = rss_orbit_errors(&rkn_state, &truth);
+    let back = rkn.with(rkn_state).for_duration(-prop_time).unwrap();
+    let (back_err, _) = rss_orbit_errors(&back, &init);
+    println!("RKN1210 adaptive error: {rkn_err:.3e} km\tback to initial state: {back_err:.3e} km");
+    assert!(rkn_err < 1e-5);
+    assert!(back_err < 1e-5);
+}
+
+#[test]
+fn bulirsch_stoer() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let prop_time = 1 * Unit::Day;
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(8000.0, 0.2, 30.0, 60.0, 90.0, 0.0, dt, eme2k);
+
+    let dynamics = OrbitalDynamics::two_body();
+
+    // Reference with a tight tolerance RK89
+    let rk89 = Propagator::new::<RK89>(
+        dynamics.clone(),
+        PropOpts::with_adaptive_step(
+            0.1 * Unit::Second,
+            30.0 * Unit::Second,
+            1e-14,
+            RSSCartesianState {},
+        ),
+    );
+    let truth = rk89.with(init).for_duration(prop_time).unwrap();
+
+    // Same options for both integrators
+    let opts = PropOpts::with_adaptive_step(
+        0.1 * Unit::Second,
+        1 * Unit::Hour,
+        1e-12,
+        RSSCartesianState {},
+    );
+    let rk89 = Propagator::new::<RK89>(dynamics.clone(), opts);
+    let (rk89_state, rk89_traj) = rk89.with(init).for_duration_with_traj(prop_time).unwrap();
+    let (err_rk89_km, _) = rss_orbit_errors(&rk89_state, &truth);
+
+    let setup = Propagator::new::<BulirschStoer>(dynamics.clone(), opts);
+    let mut prop = setup.with(init);
+    let (bs_state, bs_traj) = prop.for_duration_with_traj(prop_time).unwrap();
+    let (err_bs_km, _) = rss_orbit_errors(&bs_state, &truth);
+    println!(
+        "Bulirsch-Stoer: {err_bs_km:.3e} km in {} steps\tRK89: {err_rk89_km:.3e} km in {} steps",
+        bs_traj.states.len(),
+        rk89_traj.states.len()
+    );
+    assert_eq!(bs_state.epoch, truth.epoch);
+    assert!(err_bs_km < 1e-5);
+    assert!(bs_traj.states.len() < rk89_traj.states.len());
+
+    // And back to the initial state
+    let back = prop.for_duration(-prop_time).unwrap();
+    let (err_back_km, _) = rss_orbit_errors(&back, &init);
+    println!("Bulirsch-Stoer back propagation error: {err_back_km:.3e} km");
+    assert!(err_back_km < 1e-5);
+
+    // Fixed step, with all of the extrapolation rows
+    let setup =
+        Propagator::new::<BulirschStoer>(dynamics, PropOpts::with_fixed_step(5 * Unit::Minute));
+    let (err_fixed_km, _) =
+        rss_orbit_errors(&setup.with(init).for_duration(prop_time).unwrap(), &truth);
+    println!("Bulirsch-Stoer fixed step error: {err_fixed_km:.3e} km");
+    assert!(err_fixed_km < 1e-5);
+}
+
+#[allow(clippy::identity_op)]
+#[test]
+fn auto_initial_step() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(7000.0, 0.01, 30.0, 60.0, 90.0, 0.0, dt, eme2k);
+
+    let dynamics = OrbitalDynamics::two_body();
+
+    // The adaptive step options start with the maximum step, which is far too large for this LEO
+    let opts = PropOpts::with_adaptive_step(
+        1 * Unit::Millisecond,
+        1 * Unit::Day,
+        1e-12,
+        RSSCartesianState {},
+    );
+    let manual = Propagator::new::<RK89>(dynamics.clone(), opts);
+    let auto = Propagator::new::<RK89>(dynamics, opts.with_auto_init_step());
+
+    let init_step = auto.initial_step(&init).unwrap();
+    println!("automatic initial step: {init_step}");
+    assert!(init_step > opts.min_step && init_step < 10 * Unit::Minute);
+
+    let mut manual_prop = manual.with(init);
+    manual_prop.single_step().unwrap();
+    let mut auto_prop = auto.with(init);
+    auto_prop.single_step().unwrap();
+    println!(
+        "first step: manual {} in {} attempts\tauto {} in {} attempts",
+        manual_prop.details.step,
+        manual_prop.details.attempts,
+        auto_prop.details.step,
+        auto_prop.details.attempts
+    );
+    assert!(auto_prop.details.attempts < manual_prop.details.attempts);
+    assert!(auto_prop.details.attempts <= 2);
+
+    // And both agree at the end
+    let (manual_state, manual_traj) = manual
+        .with(init)
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+    let (auto_state, auto_traj) = auto
+        .with(init)
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+    let (err_km, _) = rss_orbit_errors(&manual_state, &auto_state);
+    println!(
+        "difference after a day: {err_km:.3e} km\tsteps: manual {}\tauto {}",
+        manual_traj.states.len(),
+        auto_traj.states.len()
+    );
+    assert!(err_km < 1e-4);
+    // The step quickly grows from the conservative estimate
+    assert!(auto_traj.states.len() < manual_traj.states.len() + 10);
+
+    // A fixed step ignores the option
+    let fixed = Propagator::new::<RK89>(
+        OrbitalDynamics::two_body(),
+        PropOpts {
+            auto_init_step: true,
+            ..PropOpts::with_fixed_step(10 * Unit::Second)
+        },
+    );
+    assert_eq!(fixed.with(init).details.step, 10 * Unit::Second);
+}
+
+#[test]
+fn fixed_output_epochs() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(8000.0, 0.2, 30.0, 60.0, 90.0, 0.0, dt, eme2k);
+
+    // Measurement times which are not on any step boundary, given out of order
+    let epochs = vec![
+        dt + 3 * Unit::Hour + 17.25 * Unit::Second,
+        dt + 1.234567 * Unit::Second,
+        dt + 45 * Unit::Minute + 0.5 * Unit::Millisecond,
+        dt + 11 * Unit::Hour + 59 * Unit::Minute,
+    ];
+
+    let free = Propagator::default(OrbitalDynamics::two_body());
+    let constrained =
+        Propagator::default(OrbitalDynamics::two_body()).with_fixed_output_epochs(epochs.clone());
+    assert_eq!(constrained.output_epochs().len(), 4);
+
+    let (free_state, free_traj) = free
+        .with(init)
+        .for_duration_with_traj(12 * Unit::Hour)
+        .unwrap();
+    let (state, traj) = constrained
+        .with(init)
+        .for_duration_with_traj(12 * Unit::Hour)
+        .unwrap();
+
+    for epoch in &epochs {
+        // The propagator landed exactly on each epoch
+        let at_epoch = traj
+            .states
+            .iter()
+            .find(|state| state.epoch == *epoch)
+            .unwrap();
+        // Which matches stopping the propagation at that epoch and the interpolation of the unconstrained propagation
+        let stopped = free.with(init).until_epoch(*epoch).unwrap();
+        let (err_km, err_km_s) = rss_orbit_errors(at_epoch, &stopped);
+        println!("{epoch}: {err_km:.3e} km\t{err_km_s:.3e} km/s");
+        assert!(err_km < 1e-6);
+        assert!(err_km_s < 1e-9);
+        let (err_km, _) = rss_orbit_errors(at_epoch, &free_traj.at(*epoch).unwrap());
+        assert!(err_km < 1e-4);
+    }
+
+    // The step size resumes after each output epoch
+    println!(
+        "steps: free {}\tconstrained {}",
+        free_traj.states.len(),
+        traj.states.len()
+    );
+    assert!(traj.states.len() <= free_traj.states.len() + 2 * epochs.len());
+    assert_eq!(state.epoch, free_state.epoch);
+    let (err_km, _) = rss_orbit_errors(&state, &free_state);
+    assert!(err_km < 1e-6);
+
+    // The output epochs are also landed on when propagating backward
+    let (_, back_traj) = constrained
+        .with(state)
+        .for_duration_with_traj(-12 * Unit::Hour)
+        .unwrap();
+    for epoch in &epochs {
+        assert!(back_traj.states.iter().any(|state| state.epoch == *epoch));
+    }
+}
@@ -0,0 +1,66 @@
+extern crate nyx_space as nyx;
+use hifitime::J2000_OFFSET;
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::propagators::error_ctrl::RSSCartesianStep;
+use nyx::propagators::*;
+use nyx::time::{Epoch, Unit};
+
+#[test]
+fn sundman_heo() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    // Highly eccentric orbit with a periapsis radius of 6000 km, starting at apoapsis
+    let init = Orbit::keplerian(200_000.0, 0.97, 63.4, 45.0, 270.0, 180.0, dt, eme2k);
+    // After whole revolutions, the two body orbit returns to its initial position
+    let prop_time = 3 * init.period();
+    let end = dt + prop_time;
+
+    let opts = PropOpts::with_adaptive_step(
+        0.001 * Unit::Second,
+        1 * Unit::Day,
+        1e-12,
+        RSSCartesianStep {},
+    );
+
+    let mut errs_km = Vec::new();
+    let mut steps = Vec::new();
+    for sundman in [None, Some(1.0), Some(1.5)] {
+        let mut setup = Propagator::rk89(OrbitalDynamics::two_body(), opts);
+        if let Some(exponent) = sundman {
+            setup = setup.with_sundman(exponent);
+        }
+        let (final_state, traj) = setup.with(init).for_duration_with_traj(prop_time).unwrap();
+        // The final state is exactly at the requested epoch
+        assert_eq!(final_state.epoch, end);
+        let err_km = (final_state.radius() - init.radius()).norm();
+        println!(
+            "Sundman {sundman:?}: {} steps, error of {err_km:.3e} km",
+            traj.states.len()
+        );
+        errs_km.push(err_km);
+        steps.push(traj.states.len());
+
+        if sundman.is_none() {
+            continue;
+        }
+        // Propagating backward returns to the initial state
+        let back = setup.with(final_state).until_epoch(dt).unwrap();
+        assert_eq!(back.epoch, dt);
+        let back_err_km = (back.radius() - init.radius()).norm();
+        println!("Sundman {sundman:?}: back propagation error of {back_err_km:.3e} km");
+        assert!(back_err_km < 1e-4);
+    }
+
+    // Regularized steps are fewer for the same accuracy
+    for (err_km, num_steps) in errs_km.iter().zip(&steps).skip(1) {
+        assert!(*num_steps < steps[0]);
+        assert!(*err_km < 1e-4);
+    }
+    // Steps in the eccentric anomaly reduce the accumulated error
+    assert!(errs_km[1] < errs_km[0]);
+}
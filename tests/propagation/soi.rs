@@ -0,0 +1,77 @@
+extern crate nyx_space as nyx;
+use hifitime::J2000_OFFSET;
+use nyx::cosmic::{Bodies, Cosm, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::dynamics::SoiSwitching;
+use nyx::propagators::error_ctrl::RSSCartesianState;
+use nyx::propagators::*;
+use nyx::time::{Epoch, Unit};
+use nyx::State;
+
+#[test]
+fn soi_switching_lunar_escape() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let luna = cosm.frame("Luna");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    // Radial departure from the Moon, well above the local escape velocity
+    let init = Orbit::cartesian(60_000.0, 0.0, 0.0, 1.0, 0.0, 0.0, dt, luna);
+    let prop_time = 1 * Unit::Day;
+
+    let opts = PropOpts::with_adaptive_step(
+        0.1 * Unit::Second,
+        30.0 * Unit::Minute,
+        1e-12,
+        RSSCartesianState {},
+    );
+
+    let soi = SoiSwitching::new(eme2k, cosm.clone()).with_laplace_body(luna, eme2k, dt);
+    let moon_soi_km = soi.bodies[0].1;
+    println!("{soi}");
+    assert!((moon_soi_km - 66_000.0).abs() < 2_000.0);
+    assert_eq!(soi.central_frame(&init), luna);
+
+    // The Earth is a third body while in the SOI of the Moon, and conversely
+    let dynamics =
+        OrbitalDynamics::point_masses(&[Bodies::Earth, Bodies::Luna, Bodies::Sun], cosm.clone())
+            .with_soi_switching(soi);
+
+    let (final_state, traj) = Propagator::new::<RK89>(dynamics, opts)
+        .with(init)
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    // The spacecraft left the SOI of the Moon and is now integrated about the Earth
+    assert_eq!(final_state.frame, eme2k);
+    assert_eq!(traj.first().frame, luna);
+    assert_eq!(traj.last().frame, eme2k);
+    assert!(cosm.frame_chg(&final_state, luna).rmag_km() > moon_soi_km);
+
+    // Same propagation about the Earth only
+    let reference = Propagator::new::<RK89>(
+        OrbitalDynamics::point_masses(&[Bodies::Luna, Bodies::Sun], cosm.clone()),
+        opts,
+    )
+    .with(cosm.frame_chg(&init, eme2k))
+    .for_duration(prop_time)
+    .unwrap();
+
+    let err_km = (final_state.radius() - reference.radius()).norm();
+    let err_km_s = (final_state.velocity() - reference.velocity()).norm();
+    println!("SOI switching vs. Earth centered: {err_km:.3e} km\t{err_km_s:.3e} km/s");
+    assert!(err_km < 1e-3);
+    assert!(err_km_s < 1e-8);
+
+    // Seamless trajectory in a single frame
+    let eme2k_traj = traj.to_frame(eme2k, cosm.clone()).unwrap();
+    assert!(eme2k_traj.states.iter().all(|state| state.frame == eme2k));
+    for pair in eme2k_traj.states.windows(2) {
+        let step_s = (pair[1].epoch() - pair[0].epoch()).to_seconds();
+        // No jump at the switch: the displacement is bounded by the velocity over the step
+        let disp_km = (pair[1].radius() - pair[0].radius()).norm();
+        assert!(disp_km <= 1.1 * pair[0].vmag_km_s().max(pair[1].vmag_km_s()) * step_s);
+    }
+    let mid = eme2k_traj.at(dt + 0.5 * prop_time).unwrap();
+    assert_eq!(mid.frame, eme2k);
+}
@@ -0,0 +1,144 @@
+extern crate nyx_space as nyx;
+use hifitime::J2000_OFFSET;
+use nyx::cosmic::{Bodies, Cosm, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::md::maneuver_design::combined_plane_change;
+use nyx::md::{ConservationDrift, ConservedQuantity};
+use nyx::propagators::*;
+use nyx::time::{Epoch, Unit};
+
+#[test]
+fn step_hook_impulsive_mnvrs() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(7000.0, 0.0, 28.5, 45.0, 0.0, 60.0, dt, eme2k);
+    // Transfer to GEO starting at the next node, which is not on a step boundary
+    let plan = combined_plane_change(&init, 42_164.0, -28.5).unwrap();
+    println!("{plan}");
+    let end = plan.mnvrs[1].epoch + 6 * Unit::Hour;
+
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+    let (final_state, traj) = setup
+        .with(init)
+        .with_hook(ImpulsiveMnvrHook::new(plan.mnvrs.clone()))
+        .until_epoch_with_traj(end)
+        .unwrap();
+    println!("{final_state:x}");
+
+    // The propagation landed on each maneuver epoch, where the maneuver was applied
+    for mnvr in &plan.mnvrs {
+        assert!(traj.states.iter().any(|state| state.epoch == mnvr.epoch));
+    }
+    let at_second = traj.at(plan.mnvrs[1].epoch).unwrap();
+    let err_km = (at_second.radius() - plan.final_orbit.radius()).norm();
+    let err_km_s = (at_second.velocity() - plan.final_orbit.velocity()).norm();
+    println!("vs. plan: {err_km:.3e} km\t{err_km_s:.3e} km/s");
+    assert!(err_km < 1e-3);
+    assert!(err_km_s < 1e-6);
+    assert!((final_state.sma_km() - 42_164.0).abs() < 1e-3);
+    assert!(final_state.ecc() < 1e-7);
+    assert!(final_state.inc_deg() < 1e-6);
+
+    // Same as stopping the propagation at each maneuver and restarting it
+    let mut state = init;
+    for mnvr in &plan.mnvrs {
+        state = setup.with(state).until_epoch(mnvr.epoch).unwrap();
+        state.apply_dv(mnvr.dv_km_s);
+    }
+    state = setup.with(state).until_epoch(end).unwrap();
+    let err_km = (state.radius() - final_state.radius()).norm();
+    println!("vs. manual restarts: {err_km:.3e} km");
+    assert!(err_km < 1e-3);
+
+    // Propagating backward through the maneuvers removes them
+    let back = setup
+        .with(final_state)
+        .with_hook(ImpulsiveMnvrHook::new(plan.mnvrs))
+        .until_epoch(dt)
+        .unwrap();
+    let err_km = (back.radius() - init.radius()).norm();
+    println!("backward: {err_km:.3e} km");
+    assert_eq!(back.epoch, dt);
+    assert!(err_km < 1e-3);
+}
+
+#[test]
+fn step_hook_conservation_monitor() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(15_000.0, 0.6, 28.5, 45.0, 0.0, 180.0, dt, eme2k);
+
+    // The adaptive two body propagation conserves both quantities
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+    for quantity in [
+        ConservedQuantity::Energy,
+        ConservedQuantity::AngularMomentum,
+    ] {
+        let drift = ConservationDrift::new(quantity, &init, 1e-10).unwrap();
+        let mut prop = setup
+            .with(init)
+            .with_hook(ConservationMonitor::new(drift.clone()).failing());
+        let final_state = prop.for_duration(5 * init.period()).unwrap();
+        let final_drift = drift.drift(&final_state).unwrap();
+        println!("{}: {final_drift:e}", drift.quantity);
+        assert!(final_drift < 1e-10);
+    }
+
+    // A fixed step of ten minutes is too coarse near periapsis
+    let coarse = Propagator::rk89(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10 * Unit::Minute),
+    );
+    let drift = ConservationDrift::new(ConservedQuantity::Energy, &init, 1e-6).unwrap();
+    let err = coarse
+        .with(init)
+        .with_hook(ConservationMonitor::new(drift.clone()).failing())
+        .for_duration(5 * init.period());
+    assert!(err.is_err());
+    println!("{}", err.unwrap_err());
+
+    // The same drift as an event
+    let (found, _) = coarse
+        .with(init)
+        .until_event(5 * init.period(), &drift)
+        .unwrap();
+    println!("{drift} found at {}", found.epoch);
+    assert!((drift.drift(&found).unwrap() - 1e-6).abs() < 1e-8);
+
+    // The Jacobi constant of the Earth Moon system is nearly conserved by their point masses, unlike the energy about the
+    // Earth: it only drifts because the orbit of the Moon is not circular
+    let luna = cosm.frame("Luna");
+    let halo = Orbit::cartesian(
+        333_321.004_516,
+        -76_134.198_887,
+        -20_873.831_939,
+        0.257_153_712,
+        0.930_284_066,
+        0.346_177,
+        Epoch::from_gregorian_tai_at_midnight(2020, 1, 1),
+        eme2k,
+    );
+    let jacobi = ConservationDrift::new(
+        ConservedQuantity::jacobi(eme2k, luna, cosm.clone()),
+        &halo,
+        1e-2,
+    )
+    .unwrap();
+    let energy = ConservationDrift::new(ConservedQuantity::Energy, &halo, 1e-3).unwrap();
+    let final_state = Propagator::default(OrbitalDynamics::point_masses(&[Bodies::Luna], cosm))
+        .with(halo)
+        .for_duration(3 * Unit::Day)
+        .unwrap();
+    let jacobi_drift = jacobi.drift(&final_state).unwrap();
+    let energy_drift = energy.drift(&final_state).unwrap();
+    println!("{jacobi}: {jacobi_drift:e}\n{energy}: {energy_drift:e}");
+    assert!(jacobi_drift < 1e-2);
+    assert!(energy_drift > 10.0 * jacobi_drift);
+}
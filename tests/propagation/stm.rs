@@ -207,6 +207,61 @@ fn stm_between_steps() {
     }
 }
 
+#[test]
+fn stm_propagator_mode() {
+    // The STM mode of the propagator matches the propagation of a state whose STM was enabled manually
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let init = Orbit::keplerian(8000.0, 0.2, 10.0, 5.0, 25.0, 0.0, epoch, eme2k);
+
+    let prop = Propagator::default(OrbitalDynamics::two_body());
+    let mut plain = prop.with(init);
+    plain.for_duration(1 * Unit::Hour).unwrap();
+    assert!(
+        plain.stm().is_err(),
+        "STM should not be integrated by default"
+    );
+
+    let stm_prop = prop.clone().with_stm();
+    let mut instance = stm_prop.with(init);
+    // The STM starts at identity
+    assert_eq!(instance.stm().unwrap(), Matrix6::identity());
+
+    // The STM is available at any epoch of the instance
+    instance.for_duration(30 * Unit::Minute).unwrap();
+    let phi_t1_t0 = instance.stm().unwrap();
+    instance.for_duration(30 * Unit::Minute).unwrap();
+    let phi_t2_t0 = instance.stm().unwrap();
+
+    let manual = prop
+        .with(init.with_stm())
+        .for_duration(1 * Unit::Hour)
+        .unwrap();
+    // The instance took an extra step at 30 minutes, so both only match to the integration tolerance
+    assert!((instance.state.radius() - manual.radius()).norm() < 1e-8);
+    assert!((phi_t2_t0 - manual.stm().unwrap()).norm() < 1e-10 * phi_t2_t0.norm());
+
+    // Two body dynamics are Hamiltonian, so the STM is symplectic and its determinant is one
+    assert!((phi_t1_t0.determinant() - 1.0).abs() < 1e-8);
+    assert!((phi_t2_t0.determinant() - 1.0).abs() < 1e-8);
+
+    // Resetting the STM of the state maps from the reset epoch onward
+    instance.state.reset_stm();
+    instance.for_duration(1 * Unit::Hour).unwrap();
+    let phi_t3_t2 = instance.stm().unwrap();
+    let phi_t3_t0 = stm_prop
+        .with(init)
+        .for_duration(2 * Unit::Hour)
+        .unwrap()
+        .stm()
+        .unwrap();
+    let delta = phi_t3_t2 * phi_t2_t0 - phi_t3_t0;
+    println!("{delta:.3e}");
+    assert!(delta.fixed_columns::<3>(0).norm() < 1e-3 * phi_t3_t0.norm());
+}
+
 #[test]
 fn stm_hifi_variable_step() {
     // Using higher fidelity dynamics for STM testing
@@ -393,3 +448,62 @@ fn sc_and_orbit_stm_chk() {
         "Identical dynamics for Spacecraft and Orbit lead to different STM"
     );
 }
+
+#[test]
+fn sc_stm_cr_partials() {
+    use nyx::dynamics::SolarPressure;
+    use nyx::md::sensitivity::{ModelParameter, ParameterSensitivity};
+
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let init_orbit = Orbit::keplerian(42164.0, 1e-3, 1.0, 5.0, 25.0, 0.0, epoch, eme2k);
+    let init_sc = Spacecraft::from_srp_defaults(init_orbit, 500.0, 10.0);
+
+    let dynamics = SpacecraftDynamics::from_model(
+        OrbitalDynamics::two_body(),
+        SolarPressure::default(eme2k, cosm),
+    );
+
+    let prop = Propagator::new::<RK4Fixed>(dynamics, PropOpts::with_fixed_step(30 * Unit::Second));
+
+    let final_sc = prop
+        .with(init_sc.with_stm())
+        .for_duration(12 * Unit::Hour)
+        .unwrap();
+
+    let stm_cr_col = final_sc
+        .stm()
+        .unwrap()
+        .fixed_view::<6, 1>(0, 6)
+        .into_owned();
+
+    let sensitivity = ParameterSensitivity::compute(
+        &prop,
+        init_sc,
+        12 * Unit::Hour,
+        &[ModelParameter::Cr, ModelParameter::CentralBodyGM],
+    )
+    .unwrap();
+
+    // The sensitivity is computed by central differences of the final state, so it validates the Cr column of the STM
+    let fd_cr_col = sensitivity.partials(ModelParameter::Cr).unwrap();
+    println!("STM Cr column = {:?}", stm_cr_col.as_slice());
+    println!("FD Cr column = {:?}", fd_cr_col.as_slice());
+    let rel_err = (stm_cr_col - fd_cr_col).norm() / fd_cr_col.norm();
+    assert!(
+        rel_err < 1e-2,
+        "STM and finite difference Cr partials differ by {rel_err:e}"
+    );
+
+    // Per unit of each parameter, the position is more sensitive to the GM than to Cr
+    let gm_col = sensitivity.partials(ModelParameter::CentralBodyGM).unwrap();
+    let gm_pos_sensitivity = gm_col.fixed_rows::<3>(0).norm();
+    let cr_pos_sensitivity = fd_cr_col.fixed_rows::<3>(0).norm();
+    println!(
+        "|∂r/∂GM| = {gm_pos_sensitivity:e} km/(km^3/s^2)\t|∂r/∂Cr| = {cr_pos_sensitivity:e} km"
+    );
+    assert!(gm_pos_sensitivity > cr_pos_sensitivity);
+    assert!(sensitivity.partials(ModelParameter::Thrust).is_none());
+}
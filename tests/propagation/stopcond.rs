@@ -8,7 +8,7 @@ use nyx::cosmic::{Bodies, Cosm, Orbit};
 use nyx::dynamics::guidance::{FiniteBurns, Mnvr, Thruster};
 use nyx::dynamics::orbital::OrbitalDynamics;
 use nyx::dynamics::SpacecraftDynamics;
-use nyx::md::{Event, EventEvaluator, StateParameter};
+use nyx::md::{ConditionExpr, Event, EventEvaluator, StateParameter};
 use nyx::propagators::error_ctrl::RSSCartesianStep;
 use nyx::propagators::{PropOpts, Propagator};
 use nyx::time::{Epoch, TimeUnits, Unit};
@@ -381,3 +381,123 @@ fn event_and_combination() {
         }
     }
 }
+
+#[test]
+fn stop_cond_online() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let state = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.01, start_dt, eme2k,
+    );
+    let period = state.period();
+    let apo_event = Event::apoapsis();
+
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+
+    // Same result as the search on the trajectory
+    let (third_apo, _) = setup
+        .with(state)
+        .until_nth_event(5 * period, &apo_event, 2)
+        .unwrap();
+
+    let mut prop = setup.with(state);
+    let third_apo_online = prop
+        .until_nth_event_online(5 * period, &apo_event, 2)
+        .unwrap();
+    println!("{third_apo:x}\n{third_apo_online:x}");
+    assert!((third_apo_online.epoch - third_apo.epoch).abs() < 10.milliseconds());
+    assert!(apo_event.eval(&third_apo_online).abs() < apo_event.value_precision);
+    // The propagation stopped at the event
+    assert_eq!(prop.state, third_apo_online);
+
+    // And can continue from there to the next periapsis
+    let peri = prop
+        .until_event_online(period, &Event::periapsis())
+        .unwrap();
+    let half_period = peri.epoch - third_apo_online.epoch - 0.5 * period;
+    assert!(half_period.abs() < 10.milliseconds());
+
+    // Backward propagation from that periapsis finds the same apoapsis
+    let prev_apo = prop.until_event_online(-period, &apo_event).unwrap();
+    assert!((prev_apo.epoch - third_apo_online.epoch).abs() < 10.milliseconds());
+
+    // Not enough triggers within the maximum duration
+    let mut prop = setup.with(state);
+    assert!(prop
+        .until_nth_event_online(1.5 * period, &apo_event, 2)
+        .is_err());
+    assert_eq!(prop.state.epoch, start_dt + 1.5 * period);
+}
+
+#[test]
+fn stop_cond_expr() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    // Apoapsis at an argument of latitude of 240 degrees, i.e. in the southern hemisphere
+    let state = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 0.0, start_dt, eme2k);
+    let period = state.period();
+
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+
+    let apo = ConditionExpr::event(Event::apoapsis());
+    let north = ConditionExpr::condition(Event::new(StateParameter::Declination, 0.0));
+
+    // Apoapsis AND in the southern hemisphere is simply the apoapsis
+    let south_apo = apo.clone().and(!north.clone());
+    println!("{south_apo}");
+    let apo_state = setup
+        .with(state)
+        .until_event_online(2 * period, &Event::apoapsis())
+        .unwrap();
+    let south_apo_state = setup
+        .with(state)
+        .until_event_online(2 * period, &south_apo)
+        .unwrap();
+    assert!((south_apo_state.epoch - apo_state.epoch).abs() < 10.milliseconds());
+    assert!(south_apo_state.declination_deg() < 0.0);
+
+    // Apoapsis AND in the northern hemisphere never occurs, even when crossing the equator after the apoapsis
+    let north_apo = apo.clone().and(north.clone());
+    assert!(setup
+        .with(state)
+        .until_event_online(3 * period, &north_apo)
+        .is_err());
+    assert!(!north_apo.occurs(&state, &apo_state));
+    assert!(!north_apo.holds(&apo_state));
+
+    // Apoapsis OR a radius above 9000 km stops on the radius, which comes first
+    let high = ConditionExpr::condition(Event::new(StateParameter::Rmag, 9_000.0));
+    let apo_or_high = apo.clone().or(high);
+    let high_state = setup
+        .with(state)
+        .until_event_online(2 * period, &apo_or_high)
+        .unwrap();
+    println!("{apo_or_high} -> {high_state:x}");
+    assert!((high_state.rmag_km() - 9_000.0).abs() < 1e-3);
+    assert!(high_state.epoch < apo_state.epoch);
+
+    // But if the radius condition cannot be met, it stops at the apoapsis
+    let too_high = ConditionExpr::condition(Event::new(StateParameter::Rmag, 12_000.0));
+    let apo_state_or = setup
+        .with(state)
+        .until_event_online(2 * period, &apo.or(too_high))
+        .unwrap();
+    assert!((apo_state_or.epoch - apo_state.epoch).abs() < 10.milliseconds());
+
+    // The search on the trajectory finds the same states
+    let (south_apo_traj, traj) = setup
+        .with(state)
+        .until_event(2 * period, &south_apo)
+        .unwrap();
+    assert!((south_apo_traj.epoch - south_apo_state.epoch).abs() < 10.milliseconds());
+    let (high_traj, _) = setup
+        .with(state)
+        .until_event(2 * period, &apo_or_high)
+        .unwrap();
+    assert!((high_traj.epoch - high_state.epoch).abs() < 10.milliseconds());
+    assert!(traj.find_all(&north_apo).is_err());
+}
@@ -7,8 +7,8 @@ use nyx::cosmic::{Cosm, GuidanceMode, Orbit, Spacecraft};
 use nyx::dynamics::guidance::{GuidanceLaw, Ruggiero, Thruster};
 use nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
 use nyx::io::trajectory_data::TrajectoryLoader;
-use nyx::md::prelude::{ExportCfg, Interpolatable, Objective};
-use nyx::md::StateParameter;
+use nyx::md::prelude::{ExportCfg, Extrapolation, Interpolatable, Objective};
+use nyx::md::{Event, StateParameter};
 use nyx::propagators::*;
 use nyx::time::{Epoch, TimeSeries, Unit};
 use nyx::State;
@@ -573,3 +573,687 @@ fn traj_ephem_backward() {
         "Maximum state in interpolation is too high!"
     );
 }
+
+#[test]
+fn traj_sample_at() {
+    let _ = pretty_env_logger::try_init();
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let start_state = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, start_dt, eme2k,
+    );
+
+    let (_, ephem) = Propagator::default(OrbitalDynamics::two_body())
+        .with(start_state)
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    // Sample more epochs than a single chunk, including the first and last states.
+    let epochs = TimeSeries::inclusive(start_dt, ephem.last().epoch(), 37 * Unit::Second)
+        .collect::<Vec<Epoch>>();
+    let samples = ephem.sample_at(&epochs).unwrap();
+    assert_eq!(samples.len(), epochs.len());
+    assert_eq!(samples.epochs(), epochs);
+
+    for (epoch, sample) in epochs.iter().zip(samples.states.iter()) {
+        assert_eq!(
+            &ephem.at(*epoch).unwrap(),
+            sample,
+            "wrong sample at {epoch}"
+        );
+    }
+
+    // Unsorted epochs are still supported.
+    let mut unsorted = epochs.clone();
+    unsorted.reverse();
+    let samples_rev = ephem.sample_at(&unsorted).unwrap();
+    for (epoch, sample) in unsorted.iter().zip(samples_rev.states.iter()) {
+        assert_eq!(
+            &ephem.at(*epoch).unwrap(),
+            sample,
+            "wrong sample at {epoch}"
+        );
+    }
+
+    let sma = samples.column(StateParameter::SMA).unwrap();
+    assert_eq!(sma.len(), epochs.len());
+    assert!((sma[0] - start_state.sma_km()).abs() < 1e-9);
+
+    // Any epoch outside of the trajectory is an error.
+    assert!(ephem
+        .sample_at(&[start_dt, ephem.last().epoch() + 1 * Unit::Second])
+        .is_err());
+}
+
+#[test]
+fn traj_export_options() {
+    use nyx::cosmic::AngleUnit;
+    use nyx::io::LengthUnit;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use parquet::file::reader::FileReader;
+    use parquet::file::serialized_reader::SerializedFileReader;
+    use std::fs::File;
+
+    let _ = pretty_env_logger::try_init();
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let start_state = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, start_dt, eme2k,
+    );
+
+    let (_, ephem) = Propagator::default(OrbitalDynamics::two_body())
+        .with(start_state)
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    let mut cfg = ExportCfg::cartesian();
+    cfg.append_field(StateParameter::Inclination);
+    cfg.step = Some(1 * Unit::Minute);
+    cfg.length_unit = Some(LengthUnit::Meters);
+    cfg.angle_unit = Some(AngleUnit::Radians);
+    cfg.significant_digits = Some(6);
+    cfg.row_group_size = Some(100);
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "ephem_export_options.parquet",
+    ]
+    .iter()
+    .collect();
+
+    let exported_path = ephem.to_parquet_with_cfg(path, cfg).unwrap();
+
+    // 1441 rows split in groups of 100 rows
+    let reader = SerializedFileReader::new(File::open(&exported_path).unwrap()).unwrap();
+    assert_eq!(reader.metadata().file_metadata().num_rows(), 1441);
+    assert_eq!(reader.metadata().num_row_groups(), 15);
+
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&exported_path).unwrap())
+        .unwrap()
+        .with_batch_size(2000);
+    let columns = builder
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect::<Vec<String>>();
+    assert_eq!(
+        columns,
+        vec![
+            "Epoch:Gregorian UTC",
+            "Epoch:Gregorian TAI",
+            "Epoch:TAI (s)",
+            "x (m)",
+            "y (m)",
+            "z (m)",
+            "vx (m/s)",
+            "vy (m/s)",
+            "vz (m/s)",
+            "inc (rad)"
+        ]
+    );
+
+    let batch = builder.build().unwrap().next().unwrap().unwrap();
+    let x_m = batch
+        .column_by_name("x (m)")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<arrow::array::Float64Array>()
+        .unwrap();
+    let inc_rad = batch
+        .column_by_name("inc (rad)")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<arrow::array::Float64Array>()
+        .unwrap();
+
+    for (ii, epoch) in
+        TimeSeries::inclusive(start_dt, ephem.last().epoch(), 1 * Unit::Minute).enumerate()
+    {
+        let state = ephem.at(epoch).unwrap();
+        let x_rel_err = (x_m.value(ii) - state.x_km * 1e3).abs() / (state.x_km * 1e3).abs();
+        assert!(x_rel_err < 1e-6, "x error too large at {epoch}");
+        assert!((inc_rad.value(ii) - state.inc_deg().to_radians()).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn traj_stm_export() {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+
+    let _ = pretty_env_logger::try_init();
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let start_state = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, start_dt, eme2k,
+    );
+
+    let (_, ephem) = Propagator::default(OrbitalDynamics::two_body())
+        .with(start_state.with_stm())
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "ephem_stm.parquet",
+    ]
+    .iter()
+    .collect();
+
+    // Only export the sensitivities to the initial X and VX, at most every hour
+    let cfg = ExportCfg::builder().step(1 * Unit::Hour).build();
+    let exported_path = ephem
+        .stm_to_parquet(path.clone(), Some(vec![0, 3]), cfg)
+        .unwrap();
+
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(File::open(&exported_path).unwrap()).unwrap();
+    let columns = builder
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect::<Vec<String>>();
+    assert_eq!(columns.len(), 3 + 12);
+    assert_eq!(columns[3], "STM[0,0]");
+    assert_eq!(columns[14], "STM[5,3]");
+
+    let batch = builder.build().unwrap().next().unwrap().unwrap();
+    let tai_s = batch
+        .column_by_name("Epoch:TAI (s)")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<arrow::array::Float64Array>()
+        .unwrap();
+    let dy_dvx = batch
+        .column_by_name("STM[1,3]")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<arrow::array::Float64Array>()
+        .unwrap();
+    println!(
+        "{} of {} states exported",
+        batch.num_rows(),
+        ephem.states.len()
+    );
+    assert!(batch.num_rows() > 20 && batch.num_rows() < ephem.states.len());
+
+    // Each row is the STM of a stored state, at least an hour after the previous one
+    let mut prev_epoch: Option<Epoch> = None;
+    for ii in 0..batch.num_rows() {
+        let epoch = Epoch::from_tai_seconds(tai_s.value(ii));
+        let state = ephem
+            .states
+            .iter()
+            .find(|state| (state.epoch - epoch).abs() < 1 * Unit::Microsecond)
+            .unwrap();
+        assert_eq!(dy_dvx.value(ii), state.stm().unwrap()[(1, 3)]);
+        if let Some(prev_epoch) = prev_epoch {
+            assert!(epoch - prev_epoch >= 1 * Unit::Hour);
+        }
+        prev_epoch = Some(epoch);
+    }
+    assert_eq!(Epoch::from_tai_seconds(tai_s.value(0)), ephem.first().epoch);
+
+    // Invalid sensitivities and trajectories without STM cannot be exported
+    assert!(ephem
+        .stm_to_parquet(path.clone(), Some(vec![6]), ExportCfg::default())
+        .is_err());
+    let (_, no_stm) = Propagator::default(OrbitalDynamics::two_body())
+        .with(start_state)
+        .for_duration_with_traj(1 * Unit::Hour)
+        .unwrap();
+    assert!(no_stm
+        .stm_to_parquet(path, None, ExportCfg::default())
+        .is_err());
+}
+
+#[test]
+fn traj_accel_breakdown() {
+    use nyx::dynamics::{Dynamics, Harmonics, PointMasses, SolarPressure};
+    use nyx::io::gravity::HarmonicsMem;
+    use nyx::linalg::Vector3;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+
+    let _ = pretty_env_logger::try_init();
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let orbit = Orbit::keplerian(7_200.0, 1e-3, 51.6, 20.0, 178.0, 0.0, start_dt, eme2k);
+
+    let objectives = &[Objective::within_tolerance(
+        StateParameter::AoP,
+        183.0,
+        5e-3,
+    )];
+    let ruggiero_ctrl = Ruggiero::new(objectives, orbit).unwrap();
+    let lowt = Thruster {
+        thrust_N: 89e-3,
+        isp_s: 1650.0,
+    };
+    let start_state = Spacecraft::from_thruster(orbit, 300.0, 67.0, lowt, GuidanceMode::Thrust);
+
+    let orbital_dyn = OrbitalDynamics::new(vec![
+        PointMasses::new(&[nyx::cosmic::Bodies::Luna], cosm.clone()),
+        Harmonics::from_stor(iau_earth, HarmonicsMem::j2_jgm3(), cosm.clone()),
+    ]);
+    let dynamics =
+        SpacecraftDynamics::from_model(orbital_dyn, SolarPressure::default(eme2k, cosm.clone()))
+            .with_guidance_law(ruggiero_ctrl);
+
+    let (_, traj) = Propagator::default(dynamics.clone())
+        .with(start_state)
+        .for_duration_with_traj(1 * Unit::Hour)
+        .unwrap();
+
+    // The contributions sum up to the acceleration of the dynamics
+    let state = traj.at(start_dt + 17 * Unit::Minute).unwrap();
+    let breakdown = dynamics.accel_breakdown(&state).unwrap();
+    for (name, accel) in &breakdown {
+        println!("{name}: {:.6e} km/s^2", accel.norm());
+    }
+    let names = breakdown
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<String>>();
+    assert_eq!(names.len(), 5);
+    assert!(names[0].starts_with("Two body"));
+    assert_eq!(names[4], "Thrust");
+    let total = breakdown
+        .iter()
+        .map(|(_, accel)| accel)
+        .sum::<Vector3<f64>>();
+    let d_x = dynamics
+        .eom(0.0, &state.as_vector().unwrap(), &state)
+        .unwrap();
+    let expected = Vector3::new(d_x[3], d_x[4], d_x[5]);
+    assert!((total - expected).norm() < 1e-15 * expected.norm());
+    // The full thrust of 89 mN
+    assert!((breakdown[4].1.norm() - 89e-6 / state.mass_kg()).abs() < 1e-15);
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "accel_breakdown.parquet",
+    ]
+    .iter()
+    .collect();
+    let cfg = ExportCfg::builder().step(1 * Unit::Minute).build();
+    let exported_path = traj
+        .accel_breakdown_to_parquet(&dynamics, path, cfg)
+        .unwrap();
+
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(File::open(&exported_path).unwrap()).unwrap();
+    let columns = builder
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect::<Vec<String>>();
+    assert_eq!(columns.len(), 3 + 4 * 5);
+    assert_eq!(columns[22], "Thrust: |a| (km/s^2)");
+    let batch = builder.build().unwrap().next().unwrap().unwrap();
+    assert_eq!(batch.num_rows(), 61);
+    let two_body = batch
+        .column_by_name(&format!("{}: |a| (km/s^2)", names[0]))
+        .unwrap()
+        .as_any()
+        .downcast_ref::<arrow::array::Float64Array>()
+        .unwrap();
+    let state = traj.at(start_dt + 1 * Unit::Minute).unwrap();
+    assert!((two_body.value(1) - eme2k.gm() / state.orbit.rmag_km().powi(2)).abs() < 1e-15);
+}
+
+#[test]
+fn traj_export_anonymous_watermark() {
+    use nyx::io::watermark::Watermark;
+    use parquet::file::reader::FileReader;
+    use parquet::file::serialized_reader::SerializedFileReader;
+    use std::fs::File;
+
+    let _ = pretty_env_logger::try_init();
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let start_state = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, start_dt, eme2k,
+    );
+
+    let (_, ephem) = Propagator::default(OrbitalDynamics::two_body())
+        .with(start_state)
+        .for_duration_with_traj(1 * Unit::Hour)
+        .unwrap();
+
+    let mut watermark = Watermark::for_organization("ACME Space".to_string());
+    watermark.metadata.insert(
+        "Distribution".to_string(),
+        "Approved for public release".to_string(),
+    );
+    watermark.apply();
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "ephem_anonymous.parquet",
+    ]
+    .iter()
+    .collect();
+
+    let exported_path = ephem.to_parquet_with_cfg(path, ExportCfg::cartesian());
+    Watermark::reset();
+
+    let reader = SerializedFileReader::new(File::open(exported_path.unwrap()).unwrap()).unwrap();
+    let metadata = reader
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .unwrap()
+        .iter()
+        .map(|kv| (kv.key.clone(), kv.value.clone().unwrap_or_default()))
+        .collect::<std::collections::HashMap<String, String>>();
+
+    assert_eq!(metadata["Created by"], "ACME Space");
+    assert_eq!(metadata["Distribution"], "Approved for public release");
+    assert_eq!(metadata["Purpose"], "Trajectory data");
+}
+
+#[test]
+fn traj_extrapolation() {
+    let _ = pretty_env_logger::try_init();
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let start_state = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, start_dt, eme2k,
+    );
+
+    let (_, ephem) = Propagator::default(OrbitalDynamics::two_body())
+        .with(start_state)
+        .for_duration_with_traj(1 * Unit::Hour)
+        .unwrap();
+
+    // Two body truth, which includes the few minutes after the end of the ephemeris
+    let (_, truth) = Propagator::default(OrbitalDynamics::two_body())
+        .with(start_state)
+        .for_duration_with_traj(70 * Unit::Minute)
+        .unwrap();
+
+    let extrapolation = Extrapolation::new(10 * Unit::Minute);
+
+    // Within the ephemeris, this is the same as the interpolation
+    let epoch = start_dt + 30 * Unit::Minute;
+    assert_eq!(
+        ephem.at_or_extrapolate(epoch, extrapolation).unwrap(),
+        ephem.at(epoch).unwrap()
+    );
+
+    // Slightly beyond either end of the ephemeris
+    let epoch = ephem.last().epoch() + 5 * Unit::Minute;
+    assert!(ephem.at(epoch).is_err());
+    let state = ephem.at_or_extrapolate(epoch, extrapolation).unwrap();
+    assert_eq!(state.epoch(), epoch);
+    let (rss_pos_km, rss_vel_km_s) = state.rss(&truth.at(epoch).unwrap());
+    assert!(rss_pos_km < 1e-3, "{rss_pos_km} km");
+    assert!(rss_vel_km_s < 1e-6, "{rss_vel_km_s} km/s");
+
+    let state = ephem
+        .at_or_extrapolate(start_dt - 5 * Unit::Minute, extrapolation)
+        .unwrap();
+    assert_eq!(state.epoch(), start_dt - 5 * Unit::Minute);
+
+    // But not beyond the maximum duration
+    assert!(ephem
+        .at_or_extrapolate(ephem.last().epoch() + 11 * Unit::Minute, extrapolation)
+        .is_err());
+
+    // Remove the states in the middle of the ephemeris to create a data gap
+    let mut gapped = ephem.clone();
+    let gap_start = start_dt + 20 * Unit::Minute;
+    let gap_end = start_dt + 40 * Unit::Minute;
+    gapped
+        .states
+        .retain(|state| state.epoch() <= gap_start || state.epoch() >= gap_end);
+
+    let with_gaps = extrapolation.with_min_gap(5 * Unit::Minute);
+
+    let epoch = gap_start + 2 * Unit::Minute;
+    let state = gapped.at_or_extrapolate(epoch, with_gaps).unwrap();
+    let (rss_pos_km, _) = state.rss(&truth.at(epoch).unwrap());
+    assert!(rss_pos_km < 1e-3, "{rss_pos_km} km");
+
+    // The middle of the gap is too far from either side
+    assert!(gapped
+        .at_or_extrapolate(start_dt + 30 * Unit::Minute, with_gaps)
+        .is_err());
+}
+
+#[allow(clippy::identity_op)]
+#[test]
+fn traj_splice() {
+    let _ = pretty_env_logger::try_init();
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let start_state = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, start_dt, eme2k,
+    );
+    let end_dt = start_dt + 6 * Unit::Hour;
+
+    let setup = Propagator::rk89(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(1 * Unit::Minute),
+    );
+    let (_, nominal) = setup
+        .with(start_state)
+        .until_epoch_with_traj(end_dt)
+        .unwrap();
+
+    // Tune a maneuver executed at the third hour without propagating the first three hours again
+    let mnvr_dt = start_dt + 3 * Unit::Hour;
+    let delta_v = |mut state: Orbit| {
+        state.vx_km_s += 0.01;
+        state
+    };
+    let (final_state, spliced) = setup
+        .repropagate_from(&nominal, mnvr_dt, end_dt, delta_v)
+        .unwrap();
+    println!("{spliced}");
+
+    // The earlier states are unchanged and the later ones are those of the full propagation with the maneuver
+    assert_eq!(spliced.states.len(), nominal.states.len());
+    assert_eq!(spliced.first(), nominal.first());
+    assert_eq!(
+        spliced.at(mnvr_dt - 1 * Unit::Minute).unwrap(),
+        nominal.at(mnvr_dt - 1 * Unit::Minute).unwrap()
+    );
+    let (_, before) = setup
+        .with(start_state)
+        .until_epoch_with_traj(mnvr_dt)
+        .unwrap();
+    let (full_final, _) = setup
+        .with(delta_v(*before.last()))
+        .until_epoch_with_traj(end_dt)
+        .unwrap();
+    assert_eq!(final_state, full_final);
+    assert_eq!(*spliced.last(), full_final);
+    assert_eq!(
+        spliced.at(mnvr_dt).unwrap(),
+        delta_v(nominal.at(mnvr_dt).unwrap())
+    );
+    let (rss_pos_km, _) = spliced.last().rss(nominal.last());
+    assert!(rss_pos_km > 10.0, "{rss_pos_km} km");
+
+    // Without any change, the spliced trajectory is the nominal one
+    let (_, same) = setup
+        .repropagate_from(&nominal, mnvr_dt, end_dt, |state| state)
+        .unwrap();
+    assert_eq!(same.last(), nominal.last());
+
+    // Splicing a shorter segment truncates the trajectory, and a segment in another frame is rejected
+    let (_, short) = setup
+        .with(nominal.at(mnvr_dt).unwrap())
+        .for_duration_with_traj(1 * Unit::Hour)
+        .unwrap();
+    let truncated = nominal.splice(&short).unwrap();
+    assert_eq!(truncated.last().epoch(), mnvr_dt + 1 * Unit::Hour);
+    let other_frame = short.to_frame(cosm.frame("Luna"), cosm.clone()).unwrap();
+    assert!(nominal.splice(&other_frame).is_err());
+    // The segment cannot start before the trajectory
+    assert!(short.splice(&nominal).is_err());
+}
+
+#[allow(clippy::identity_op)]
+#[test]
+fn traj_backward_events() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let start_state = Orbit::keplerian(8_000.0, 0.2, 30.0, 60.0, 60.0, 30.0, start_dt, eme2k);
+    let period = start_state.period();
+
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+    let (fwd_end, fwd_traj) = setup
+        .with(start_state)
+        .for_duration_with_traj(2 * period)
+        .unwrap();
+    assert!(!fwd_traj.backward);
+
+    // Propagate back to the start from the end of the forward trajectory
+    let (bwd_end, bwd_traj) = setup.with(fwd_end).until_epoch_with_traj(start_dt).unwrap();
+    println!("{fwd_traj}\n{bwd_traj}");
+    assert!(bwd_traj.backward);
+    assert_eq!(bwd_traj.start(), &fwd_end);
+    assert_eq!(bwd_traj.end(), &bwd_end);
+    assert_eq!(bwd_traj.first(), &bwd_end);
+    assert_eq!(bwd_traj.last(), &fwd_end);
+    assert!(bwd_traj.states.windows(2).all(|w| w[0].epoch < w[1].epoch));
+
+    // Both trajectories are queryable by epoch and match
+    for epoch in TimeSeries::inclusive(start_dt, fwd_end.epoch, 5 * Unit::Minute) {
+        let err_km =
+            (fwd_traj.at(epoch).unwrap().radius() - bwd_traj.at(epoch).unwrap().radius()).norm();
+        assert!(err_km < 1e-6, "{err_km:e} km error at {epoch}");
+    }
+
+    // Events are found in the order of propagation
+    let apo = Event::apoapsis();
+    let fwd_apos = fwd_traj.find_all(&apo).unwrap();
+    let bwd_apos = bwd_traj.find_all(&apo).unwrap();
+    assert_eq!(fwd_apos.len(), 2);
+    assert_eq!(bwd_apos.len(), 2);
+    assert!(bwd_apos[0].epoch > bwd_apos[1].epoch);
+    assert!((bwd_apos[0].epoch - fwd_apos[1].epoch).abs() < 10.milliseconds());
+
+    // So the first event of a backward propagation is the closest one to its start
+    let (first_bwd_apo, _) = setup.with(fwd_end).until_event(-2 * period, &apo).unwrap();
+    let first_bwd_apo_online = setup
+        .with(fwd_end)
+        .until_event_online(-2 * period, &apo)
+        .unwrap();
+    assert!((first_bwd_apo.epoch - first_bwd_apo_online.epoch).abs() < 10.milliseconds());
+    assert!((first_bwd_apo.epoch - fwd_apos[1].epoch).abs() < 10.milliseconds());
+
+    // Reversing the backward trajectory converts it into a forward trajectory
+    let reversed = bwd_traj.reverse();
+    assert!(!reversed.backward);
+    assert_eq!(reversed.start(), &bwd_end);
+    let rev_apos = reversed.find_all(&apo).unwrap();
+    assert!(rev_apos[0].epoch < rev_apos[1].epoch);
+    assert!(reversed.reverse().backward);
+}
+
+#[test]
+fn traj_verify() {
+    let _ = pretty_env_logger::try_init();
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_dt = Epoch::from_gregorian_utc_at_noon(2021, 1, 1);
+    let start_state = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, start_dt, eme2k,
+    );
+    let end_dt = start_dt + 6 * Unit::Hour;
+
+    let setup = Propagator::rk89(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(1 * Unit::Minute),
+    );
+    let (_, traj) = setup
+        .with(start_state)
+        .until_epoch_with_traj(end_dt)
+        .unwrap();
+
+    // Segments span consecutive states, and those at the end are interpolated with a slightly smaller window
+    let segments = traj.segments();
+    assert_eq!(segments.len(), traj.states.len() - 1);
+    assert_eq!(segments[0].start, start_dt);
+    assert_eq!(segments.last().unwrap().end, end_dt);
+    for (segment, next) in segments.iter().zip(segments.iter().skip(1)) {
+        assert_eq!(segment.end, next.start);
+        assert_eq!(segment.duration(), 1 * Unit::Minute);
+        assert!(segment.samples >= 12);
+    }
+    assert_eq!(segments[0].samples, 13);
+    assert_eq!(segments.last().unwrap().samples, 12);
+    println!("{}", segments[0]);
+
+    let mid_dt = start_dt + 90 * Unit::Second;
+    let segment = traj.segment_at(mid_dt).unwrap();
+    assert!(segment.contains(mid_dt));
+    assert_eq!(segment, segments[1]);
+    assert_eq!(traj.segment_at(end_dt).unwrap(), *segments.last().unwrap());
+    assert!(traj.segment_at(end_dt + 1 * Unit::Second).is_err());
+
+    // A short trajectory is interpolated with all of its states
+    let (_, short) = setup
+        .with(start_state)
+        .until_epoch_with_traj(start_dt + 4 * Unit::Minute)
+        .unwrap();
+    assert!(short.segments().iter().all(|segment| segment.samples == 5));
+
+    // The interpolation of the propagated trajectory matches the re-propagation
+    let check = traj.interpolation_error(&setup, 100).unwrap();
+    println!("{check}");
+    assert_eq!(check.epoch, segments[100].midpoint());
+    assert!(check.position_error_km < 1e-3, "{check}");
+    assert!(traj.interpolation_error(&setup, segments.len()).is_err());
+
+    let verification = traj.verify(&setup, 1e-3).unwrap();
+    println!("{verification}");
+    assert!(verification.passed());
+    assert_eq!(verification.checks.len(), 10);
+    assert_eq!(verification.checks[0].segment, segments[0]);
+    assert_eq!(verification.checks[9].segment, *segments.last().unwrap());
+
+    // Resampling the trajectory too coarsely is caught by the spot checks
+    let coarse = traj.resample(30 * Unit::Minute).unwrap();
+    let verification = coarse.verify(&setup, 1e-3).unwrap();
+    println!("{verification}");
+    assert!(!verification.passed());
+    assert!(verification.worst().unwrap().position_error_km > 1e-3);
+    assert_eq!(
+        verification.failures().len(),
+        verification
+            .checks
+            .iter()
+            .filter(|check| check.position_error_km > 1e-3)
+            .count()
+    );
+}
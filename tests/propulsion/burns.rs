@@ -0,0 +1,200 @@
+extern crate nyx_space as nyx;
+
+use self::nyx::cosmic::{Cosm, Frame, GuidanceMode, Orbit, Spacecraft, STD_GRAVITY};
+use self::nyx::dynamics::guidance::{FiniteBurns, Mnvr, Thruster};
+use self::nyx::dynamics::{
+    BurnDirection, BurnProfile, FiniteBurn, OrbitalDynamics, SpacecraftDynamics,
+};
+use self::nyx::linalg::Vector3;
+use self::nyx::propagators::{PropOpts, Propagator};
+use self::nyx::time::{Epoch, Unit};
+use self::nyx::State;
+
+#[test]
+fn finite_burn_vs_guidance() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2002, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.01, 28.5, 45.0, 0.0, 60.0, start_time, eme2k);
+    let monoprop = Thruster {
+        thrust_N: 10.0,
+        isp_s: 300.0,
+    };
+    let burn_start = start_time + 10 * Unit::Minute;
+    let burn_end = burn_start + 30 * Unit::Minute;
+    let end_time = burn_end + 20 * Unit::Minute;
+
+    // The same maneuver with the finite burns guidance law
+    let mnvr = Mnvr::from_time_invariant(
+        burn_start,
+        burn_end,
+        1.0,
+        Vector3::new(1.0, 0.0, 0.0),
+        Frame::VNC,
+    );
+    let guided = SpacecraftDynamics::from_guidance_law(
+        OrbitalDynamics::two_body(),
+        FiniteBurns::from_mnvrs(vec![mnvr]),
+    );
+    let sc = Spacecraft::from_thruster(orbit, 1e3, 756.0, monoprop, GuidanceMode::Coast);
+    // NOTE: the guidance law switches its mode after each step, so we use a small fixed step
+    let setup = Propagator::rk89(guided, PropOpts::with_fixed_step(1 * Unit::Second));
+    let mut prop = setup.with(sc);
+    prop.until_epoch(burn_start).unwrap();
+    prop.state.mut_mode(GuidanceMode::Thrust);
+    let guided_state = prop.until_epoch(burn_end).unwrap();
+
+    // The finite burn does not need any thruster on the spacecraft
+    let burn = FiniteBurn::from_thruster(
+        burn_start,
+        burn_end - burn_start,
+        monoprop,
+        BurnDirection::Fixed {
+            vector: Vector3::new(1.0, 0.0, 0.0),
+            frame: Frame::VNC,
+        },
+    )
+    .unwrap();
+    println!("{burn}");
+    let dynamics =
+        SpacecraftDynamics::new(OrbitalDynamics::two_body()).with_burns(vec![burn.clone()]);
+    println!("{dynamics}");
+    let sc = Spacecraft::new(orbit, 1e3, 756.0, 0.0, 0.0, 0.0, 0.0);
+    let setup = Propagator::rk89(dynamics, PropOpts::with_fixed_step(1 * Unit::Second));
+
+    // The events land on the start and the end of the burn
+    let (at_start, _) = setup
+        .with(sc)
+        .until_event(end_time - start_time, &burn.start_event())
+        .unwrap();
+    assert!((at_start.epoch() - burn_start).abs() < 1 * Unit::Millisecond);
+
+    let mut prop = setup.with(sc);
+    prop.until_epoch(burn_start).unwrap();
+    let at_end = prop.until_epoch(burn_end).unwrap();
+    let final_state = prop.until_epoch(end_time).unwrap();
+
+    let err_km = (at_end.orbit.radius() - guided_state.orbit.radius()).norm();
+    let err_km_s = (at_end.orbit.velocity() - guided_state.orbit.velocity()).norm();
+    println!("{at_end:x}\nvs. guidance: {err_km:.3e} km\t{err_km_s:.3e} km/s");
+    assert!(err_km < 2e-3);
+    assert!(err_km_s < 1e-5);
+    assert!((at_end.fuel_mass_kg - guided_state.fuel_mass_kg).abs() < 1e-3);
+
+    let fuel_used_kg = 756.0 - final_state.fuel_mass_kg;
+    let expected_kg = 10.0 * (burn_end - burn_start).to_seconds() / (300.0 * STD_GRAVITY);
+    assert!((fuel_used_kg - expected_kg).abs() < 1e-9);
+}
+
+#[test]
+fn finite_burn_thrust_curve() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2002, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.01, 28.5, 45.0, 0.0, 60.0, start_time, eme2k);
+    let sc = Spacecraft::new(orbit, 1e3, 756.0, 0.0, 0.0, 0.0, 0.0);
+
+    // Deorbit burn whose thrust ramps up over ten minutes, and whose Isp decreases linearly
+    let burn = FiniteBurn::new(
+        start_time + 5 * Unit::Minute,
+        start_time + 35 * Unit::Minute,
+        BurnProfile::Table(vec![(0 * Unit::Second, 0.0), (10 * Unit::Minute, 20.0)]),
+        BurnProfile::Polynomial(nyx::polyfit::CommonPolynomial::Linear(-0.01, 300.0)),
+        BurnDirection::AntiVelocity,
+    )
+    .unwrap();
+    let end_time = start_time + 1 * Unit::Hour;
+
+    let dynamics = SpacecraftDynamics::new(OrbitalDynamics::two_body()).with_burns(vec![burn]);
+    let setup = Propagator::default(dynamics.clone());
+    let mut prop = setup.with(sc);
+    // Land on the start of the burn, the end of the thrust ramp and the end of the burn
+    for minutes in [5, 15, 35] {
+        prop.until_epoch(start_time + minutes * Unit::Minute)
+            .unwrap();
+    }
+    let final_state = prop.until_epoch(end_time).unwrap();
+    println!("{final_state:x}");
+
+    // Fuel used by the ramp then by the constant thrust, integrated by the trapezoidal and Simpson rules respectively
+    let mdot = |t_s: f64| {
+        let thrust_n = if t_s < 600.0 { t_s / 30.0 } else { 20.0 };
+        thrust_n / ((300.0 - 0.01 * t_s) * STD_GRAVITY)
+    };
+    let n = 6000;
+    let h = 1800.0 / n as f64;
+    let expected_kg = (0..n)
+        .map(|i| {
+            let t = i as f64 * h;
+            h / 6.0 * (mdot(t) + 4.0 * mdot(t + h / 2.0) + mdot(t + h))
+        })
+        .sum::<f64>();
+    // NOTE: the error control of the adaptive step only applies to the orbit, not to the fuel mass
+    let fuel_used_kg = 756.0 - final_state.fuel_mass_kg;
+    println!("fuel used: {fuel_used_kg} kg (expected {expected_kg} kg)");
+    assert!((fuel_used_kg - expected_kg).abs() < 1e-4);
+
+    // The anti-velocity burn lowered the orbit
+    assert!(final_state.orbit.sma_km() < orbit.sma_km() - 10.0);
+    let breakdown = dynamics.accel_breakdown(&final_state).unwrap();
+    assert_eq!(breakdown.len(), 2);
+    assert_eq!(breakdown[1].1, Vector3::zeros());
+
+    // Without mass depletion
+    let mut no_decr = dynamics;
+    no_decr.decrement_mass = false;
+    let final_state = Propagator::default(no_decr)
+        .with(sc)
+        .until_epoch(end_time)
+        .unwrap();
+    assert_eq!(final_state.fuel_mass_kg, 756.0);
+
+    // Invalid burns
+    assert!(FiniteBurn::new(
+        start_time,
+        start_time,
+        BurnProfile::Constant(1.0),
+        BurnProfile::Constant(300.0),
+        BurnDirection::AntiVelocity,
+    )
+    .is_err());
+    assert!(FiniteBurn::new(
+        start_time,
+        end_time,
+        BurnProfile::Table(vec![(1 * Unit::Minute, 1.0), (0 * Unit::Minute, 2.0)]),
+        BurnProfile::Constant(300.0),
+        BurnDirection::AntiVelocity,
+    )
+    .is_err());
+}
+
+#[test]
+fn massless_stm_without_burns() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2002, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.01, 28.5, 45.0, 0.0, 60.0, start_time, eme2k);
+    // Without any burn, the dynamics of a massless spacecraft are those of its orbit
+    let sc = Spacecraft::from_srp_defaults(orbit, 0.0, 0.0).with_stm();
+
+    let final_orbit = Propagator::default(OrbitalDynamics::two_body())
+        .with(orbit.with_stm())
+        .for_duration(1 * Unit::Hour)
+        .unwrap();
+    let final_sc = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(sc)
+        .for_duration(1 * Unit::Hour)
+        .unwrap();
+
+    let sc_stm = final_sc.stm().unwrap();
+    assert!(sc_stm.iter().all(|val| val.is_finite()));
+    assert_eq!(final_sc.orbit, final_orbit);
+    assert_eq!(sc_stm.fixed_view::<6, 6>(0, 0), final_orbit.stm().unwrap());
+}
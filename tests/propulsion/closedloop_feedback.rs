@@ -0,0 +1,106 @@
+extern crate nalgebra as na;
+extern crate nyx_space as nyx;
+
+use self::nyx::cosmic::{Cosm, GuidanceMode, Orbit, Spacecraft};
+use self::nyx::dynamics::guidance::{Lyapunov, Objective, QLaw, StateParameter, Thruster};
+use self::nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
+use self::nyx::propagators::{PropOpts, Propagator, RK4Fixed};
+use self::nyx::time::{Epoch, Unit};
+
+#[test]
+fn qlaw_sma_ecc() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let orbit = Orbit::keplerian(24396.0, 0.1, 10.0, 0.0, 0.0, 0.0, start_time, eme2k);
+
+    let prop_time = 60 * Unit::Day;
+
+    // Define the thruster
+    let lowt = Thruster {
+        thrust_N: 89e-3,
+        isp_s: 1650.0,
+    };
+
+    // Define the objectives
+    let objectives = &[
+        Objective::within_tolerance(StateParameter::SMA, 42_164.0, 20.0),
+        Objective::within_tolerance(StateParameter::Eccentricity, 0.01, 5e-3),
+    ];
+
+    let guid_law = QLaw::new(objectives).unwrap();
+
+    let fuel_mass = 67.0;
+    let dry_mass = 300.0;
+    let sc_state =
+        Spacecraft::from_thruster(orbit, dry_mass, fuel_mass, lowt, GuidanceMode::Thrust);
+
+    let sc = SpacecraftDynamics::from_guidance_law(OrbitalDynamics::two_body(), guid_law);
+    println!("[qlaw_sma_ecc] {:x}", orbit);
+
+    let final_state =
+        Propagator::new::<RK4Fixed>(sc.clone(), PropOpts::with_fixed_step(10.0 * Unit::Second))
+            .with(sc_state)
+            .for_duration(prop_time)
+            .unwrap();
+
+    let fuel_usage = fuel_mass - final_state.fuel_mass_kg;
+    println!("[qlaw_sma_ecc] {:x}", final_state.orbit);
+    println!("[qlaw_sma_ecc] fuel usage: {:.3} kg", fuel_usage);
+
+    assert!(
+        sc.guidance_achieved(&final_state).unwrap(),
+        "objective not achieved"
+    );
+}
+
+#[test]
+fn lyapunov_sma_ecc() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let orbit = Orbit::keplerian(24396.0, 0.1, 10.0, 0.0, 0.0, 0.0, start_time, eme2k);
+
+    let prop_time = 60 * Unit::Day;
+
+    // Define the thruster
+    let lowt = Thruster {
+        thrust_N: 89e-3,
+        isp_s: 1650.0,
+    };
+
+    // Define the objectives
+    let objectives = &[
+        Objective::within_tolerance(StateParameter::SMA, 42_164.0, 20.0),
+        Objective::within_tolerance(StateParameter::Eccentricity, 0.01, 5e-3),
+    ];
+
+    let guid_law = Lyapunov::new(objectives).unwrap();
+
+    let fuel_mass = 67.0;
+    let dry_mass = 300.0;
+    let sc_state =
+        Spacecraft::from_thruster(orbit, dry_mass, fuel_mass, lowt, GuidanceMode::Thrust);
+
+    let sc = SpacecraftDynamics::from_guidance_law(OrbitalDynamics::two_body(), guid_law);
+    println!("[lyapunov_sma_ecc] {:x}", orbit);
+
+    let final_state =
+        Propagator::new::<RK4Fixed>(sc.clone(), PropOpts::with_fixed_step(10.0 * Unit::Second))
+            .with(sc_state)
+            .for_duration(prop_time)
+            .unwrap();
+
+    let fuel_usage = fuel_mass - final_state.fuel_mass_kg;
+    println!("[lyapunov_sma_ecc] {:x}", final_state.orbit);
+    println!("[lyapunov_sma_ecc] fuel usage: {:.3} kg", fuel_usage);
+
+    assert!(
+        sc.guidance_achieved(&final_state).unwrap(),
+        "objective not achieved"
+    );
+}
@@ -0,0 +1,110 @@
+extern crate nyx_space as nyx;
+
+use self::nyx::cosmic::eclipse::EclipseLocator;
+use self::nyx::cosmic::{Cosm, GuidanceMode, Orbit, Spacecraft};
+use self::nyx::dynamics::guidance::{Objective, PowerModel, Ruggiero, StateParameter, Thruster};
+use self::nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
+use self::nyx::md::ep_campaign::{CampaignConstraints, CampaignPlanner};
+use self::nyx::od::GroundStation;
+use self::nyx::time::{Epoch, Unit};
+
+#[test]
+fn ep_orbit_raising_campaign() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let orbit = Orbit::keplerian(7000.0, 0.0, 51.6, 0.0, 0.0, 0.0, start_time, eme2k);
+
+    let lowt = Thruster {
+        thrust_N: 89e-3,
+        isp_s: 1650.0,
+    };
+
+    let objectives = &[Objective::within_tolerance(
+        StateParameter::SMA,
+        7_100.0,
+        1.0,
+    )];
+
+    let sc_state = Spacecraft::from_thruster(orbit, 300.0, 67.0, lowt, GuidanceMode::Thrust);
+
+    let dynamics = SpacecraftDynamics::from_guidance_law(
+        OrbitalDynamics::two_body(),
+        Ruggiero::new(objectives, orbit).unwrap(),
+    );
+
+    // Without any operational constraint
+    let unconstrained = CampaignPlanner::new(
+        dynamics.clone(),
+        CampaignConstraints::duty_cycle(1.0, 1 * Unit::Day),
+        cosm.clone(),
+    )
+    .unwrap()
+    .with_report_period(1 * Unit::Day)
+    .plan(sc_state, 30 * Unit::Day)
+    .unwrap();
+
+    println!("{unconstrained}");
+    assert!(unconstrained.achieved);
+
+    // Thrust at most half of the time, only within a few hours of a contact with Madrid, and quickly through the belts
+    let madrid = GroundStation::from_point(
+        "Madrid".to_string(),
+        40.427_222,
+        4.250_556,
+        0.834_939,
+        iau_earth,
+    );
+
+    let constraints = CampaignConstraints::duty_cycle(0.5, 1 * Unit::Day)
+        .with_ground_contacts(vec![madrid], 6 * Unit::Hour)
+        .with_radiation_belts(680.0, 2000.0);
+
+    let power = PowerModel::new(2000.0, 300.0, 1500.0, 0.2).unwrap();
+
+    let plan = CampaignPlanner::new(dynamics, constraints.clone(), cosm.clone())
+        .unwrap()
+        .with_power(power, EclipseLocator::cislunar(cosm))
+        .with_report_period(1 * Unit::Day)
+        .plan(sc_state, 30 * Unit::Day)
+        .unwrap();
+
+    println!("{plan}");
+    assert!(plan.achieved);
+    assert!(plan.periods.len() > 1);
+    assert!(plan.duration() > unconstrained.duration());
+    assert!(plan.total_thrust_time() < plan.duration());
+    assert!(plan.total_time_in_belts() > 0 * Unit::Second);
+    assert!(plan.periods.iter().map(|p| p.contacts).sum::<usize>() > 0);
+    // The arrays produce more than needed in full sunlight, and the throttle is reduced in penumbra so the margin is never negative
+    let min_margin_w = plan.min_power_margin_w().unwrap();
+    assert!(min_margin_w >= 0.0);
+    assert!(plan
+        .periods
+        .iter()
+        .all(|p| p.min_power_margin_w.unwrap() < 400.0));
+    assert!(unconstrained.min_power_margin_w().is_none());
+
+    // The duty cycle is only exceeded in the radiation belts
+    for period in &plan.periods {
+        if period.time_in_belts == 0 * Unit::Second {
+            assert!(period.duty_cycle() <= 0.5 + 1e-9, "{period}");
+        }
+    }
+
+    // Both campaigns need about the same delta-V for the same SMA raise
+    let dv_m_s = plan.total_dv_km_s() * 1e3;
+    let unconstrained_dv_m_s = unconstrained.total_dv_km_s() * 1e3;
+    assert!(dv_m_s > 0.0);
+    assert!((dv_m_s - unconstrained_dv_m_s).abs() / unconstrained_dv_m_s < 0.1);
+
+    // Consistency of the delta-V with the rocket equation
+    let final_mass = plan.final_state.mass_kg();
+    let expected_dv_m_s = lowt.exhaust_velocity_m_s() * (sc_state.mass_kg() / final_mass).ln();
+    assert!((dv_m_s - expected_dv_m_s).abs() < 1e-4);
+}
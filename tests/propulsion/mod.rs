@@ -1,3 +1,7 @@
+mod burns;
+mod closedloop_feedback;
 mod closedloop_multi_oe_ruggiero;
 mod closedloop_single_oe_ruggiero;
+mod ep_campaign;
+mod power_limited;
 mod schedule;
@@ -0,0 +1,174 @@
+extern crate nyx_space as nyx;
+
+use self::nyx::cosmic::eclipse::{EclipseLocator, EclipseState};
+use self::nyx::cosmic::{Cosm, GuidanceMode, Orbit, Spacecraft};
+use self::nyx::dynamics::guidance::{
+    GuidanceLaw, Objective, PowerLimited, PowerModel, Ruggiero, StateParameter, Thruster,
+};
+use self::nyx::dynamics::{OrbitalDynamics, SpacecraftDynamics};
+use self::nyx::propagators::{PropOpts, Propagator, RK4Fixed};
+use self::nyx::time::{Epoch, Unit};
+
+#[test]
+fn power_model() {
+    let power = PowerModel::new(1500.0, 300.0, 1500.0, 0.2).unwrap();
+    // Full sunlight at 1 AU leaves 1200 W for the thruster
+    assert!((power.available_thruster_power_w(1.0, 1.0) - 1200.0).abs() < f64::EPSILON);
+    assert!((power.max_throttle(1.0, 1.0) - 0.8).abs() < 1e-12);
+    // Plenty of power closer to the Sun, but the throttle is capped
+    assert!((power.max_throttle(1.0, 0.5) - 1.0).abs() < f64::EPSILON);
+    // No power in umbra, and the thruster cannot operate below its minimum throttle
+    assert_eq!(power.max_throttle(0.0, 1.0), 0.0);
+    assert_eq!(power.max_throttle(0.3, 1.0), 0.0);
+    assert!((power.max_throttle(0.5, 1.0) - 0.3).abs() < 1e-12);
+    // Invalid models
+    assert!(PowerModel::new(0.0, 300.0, 1500.0, 0.2).is_err());
+    assert!(PowerModel::new(1500.0, 300.0, 1500.0, 1.2).is_err());
+}
+
+#[test]
+fn rugg_sma_power_limited() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    // Equatorial LEO, which is in the shadow of the Earth for about a third of every orbit
+    let orbit = Orbit::keplerian(7000.0, 0.0, 0.0, 0.0, 0.0, 0.0, start_time, eme2k);
+
+    let prop_time = 1 * Unit::Day;
+
+    let lowt = Thruster {
+        thrust_N: 89e-3,
+        isp_s: 1650.0,
+    };
+
+    let objectives = &[Objective::within_tolerance(
+        StateParameter::SMA,
+        8_000.0,
+        1.0,
+    )];
+
+    let fuel_mass = 67.0;
+    let dry_mass = 300.0;
+    let sc_state =
+        Spacecraft::from_thruster(orbit, dry_mass, fuel_mass, lowt, GuidanceMode::Thrust);
+
+    // Unlimited power first
+    let guid_law = Ruggiero::new(objectives, orbit).unwrap();
+    let sc = SpacecraftDynamics::from_guidance_law(OrbitalDynamics::two_body(), guid_law.clone());
+    let unlimited_state =
+        Propagator::new::<RK4Fixed>(sc, PropOpts::with_fixed_step(10.0 * Unit::Second))
+            .with(sc_state)
+            .for_duration(prop_time)
+            .unwrap();
+
+    // And now with the power limited by the eclipses and the arrays
+    let power = PowerModel::new(1500.0, 300.0, 1500.0, 0.2).unwrap();
+    let e_loc = EclipseLocator::cislunar(cosm.clone());
+    let limited_law = PowerLimited::new(guid_law, power, e_loc.clone());
+    println!("[rugg_sma_power_limited] {limited_law}");
+
+    let sc =
+        SpacecraftDynamics::from_guidance_law(OrbitalDynamics::two_body(), limited_law.clone());
+    let (limited_state, traj) =
+        Propagator::new::<RK4Fixed>(sc, PropOpts::with_fixed_step(10.0 * Unit::Second))
+            .with(sc_state)
+            .for_duration_with_traj(prop_time)
+            .unwrap();
+
+    let unlimited_fuel = fuel_mass - unlimited_state.fuel_mass_kg;
+    let limited_fuel = fuel_mass - limited_state.fuel_mass_kg;
+    let unlimited_raise = unlimited_state.orbit.sma_km() - orbit.sma_km();
+    let limited_raise = limited_state.orbit.sma_km() - orbit.sma_km();
+    println!(
+        "[rugg_sma_power_limited] unlimited: {unlimited_raise:.3} km for {unlimited_fuel:.3} kg"
+    );
+    println!("[rugg_sma_power_limited] limited: {limited_raise:.3} km for {limited_fuel:.3} kg");
+
+    // The thruster runs at about 80% in sunlight and not at all in eclipse
+    assert!(limited_fuel < 0.8 * unlimited_fuel);
+    assert!(limited_raise > 0.0);
+    assert!(limited_raise < 0.8 * unlimited_raise);
+
+    let mut umbra_count = 0;
+    for state in traj.every(1 * Unit::Minute) {
+        let throttle = limited_law.throttle(&state);
+        match e_loc.compute(&state.orbit) {
+            EclipseState::Umbra => {
+                umbra_count += 1;
+                assert_eq!(throttle, 0.0, "thrusting in umbra");
+            }
+            // The Earth is near perihelion in January, so the arrays produce a bit more than at 1 AU
+            EclipseState::Visibilis => assert!(throttle > 0.8 && throttle < 0.85),
+            EclipseState::Penumbra(_) => assert!(throttle < 0.85),
+        }
+    }
+    assert!(umbra_count > 0, "no eclipse found");
+}
+
+#[test]
+fn step_controllers_shadow_crossings() {
+    use self::nyx::propagators::{RSSCartesianStep, StepController, RK89};
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.0, 0.0, 0.0, 0.0, 0.0, start_time, eme2k);
+    let prop_time = 1 * Unit::Day;
+
+    let lowt = Thruster {
+        thrust_N: 89e-3,
+        isp_s: 1650.0,
+    };
+    let objectives = &[Objective::within_tolerance(
+        StateParameter::SMA,
+        8_000.0,
+        1.0,
+    )];
+    let sc_state = Spacecraft::from_thruster(orbit, 300.0, 67.0, lowt, GuidanceMode::Thrust);
+
+    // The throttle changes quickly at each shadow crossing
+    let guid_law = Ruggiero::new(objectives, orbit).unwrap();
+    let power = PowerModel::new(1500.0, 300.0, 1500.0, 0.2).unwrap();
+    let limited_law = PowerLimited::new(guid_law, power, EclipseLocator::cislunar(cosm));
+    let sc = SpacecraftDynamics::from_guidance_law(OrbitalDynamics::two_body(), limited_law);
+
+    let reference =
+        Propagator::new::<RK4Fixed>(sc.clone(), PropOpts::with_fixed_step(1.0 * Unit::Second))
+            .with(sc_state)
+            .for_duration(prop_time)
+            .unwrap();
+
+    let mut rejections_of = Vec::new();
+    for step_ctrl in [
+        StepController::Integral,
+        StepController::gustafsson(),
+        StepController::h312_pid(),
+    ] {
+        let opts = PropOpts::with_adaptive_step(
+            0.1 * Unit::Second,
+            10.0 * Unit::Minute,
+            1e-10,
+            RSSCartesianStep {},
+        )
+        .with_step_controller(step_ctrl);
+        let setup = Propagator::new::<RK89>(sc.clone(), opts);
+        let mut prop = setup.with(sc_state);
+        let (mut steps, mut rejections) = (0, 0);
+        while prop.state.orbit.epoch < start_time + prop_time {
+            prop.single_step().unwrap();
+            steps += 1;
+            rejections += usize::from(prop.details.attempts) - 1;
+        }
+        let state = setup.with(sc_state).for_duration(prop_time).unwrap();
+        let err_km = (state.orbit.radius() - reference.orbit.radius()).norm();
+        println!("{step_ctrl}: {steps} steps, {rejections} rejections, error {err_km:.3e} km");
+        assert!(err_km < 2e-2);
+        rejections_of.push(rejections);
+    }
+    // The PI and PID controllers reject about half as many steps as the integral controller
+    assert!(rejections_of[1] * 10 < rejections_of[0] * 6);
+    assert!(rejections_of[2] * 10 < rejections_of[0] * 6);
+}